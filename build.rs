@@ -1,4 +1,8 @@
+use std::collections::BTreeMap;
 use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 fn main() {
     // esp-hal 1.0 已修复 App Descriptor 和链接脚本问题
@@ -13,4 +17,296 @@ fn main() {
     // 添加 ld 目录到链接路径（如果有自定义链接脚本）
     let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
     println!("cargo:rustc-link-search={}/ld", manifest_dir);
+
+    // C 语言接口: 从 src/ffi 生成 include/rustrtos.h
+    #[cfg(feature = "ffi")]
+    generate_header(&manifest_dir);
+
+    // 内存布局报告与预算 (仅目标板链接)
+    if env::var("TARGET").is_ok_and(|t| t.starts_with("xtensa")) {
+        memory_layout();
+    }
+}
+
+#[cfg(feature = "ffi")]
+fn generate_header(manifest_dir: &str) {
+    println!("cargo:rerun-if-changed=src/ffi");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", manifest_dir))
+        .expect("cbindgen.toml 无效");
+    // 只解析 src/ffi，避免把 crate 内其他 pub const 导出到头文件
+    match cbindgen::Builder::new()
+        .with_src(format!("{}/src/ffi/mod.rs", manifest_dir))
+        .with_config(config)
+        .generate()
+    {
+        Ok(bindings) => {
+            bindings.write_to_file(format!("{}/include/rustrtos.h", manifest_dir));
+        }
+        // 源码暂时无法解析时保留已提交的头文件
+        Err(e) => println!("cargo:warning=cbindgen: {}", e),
+    }
+}
+
+// ===== 内存布局报告与预算 =====
+//
+// 链接时输出 map 文件 (`target/<triple>/<profile>/rustrtos.map`)，构建脚本解析
+// 最近一次链接的 map，按区域、输出段与 crate 统计占用，写出
+// `memory-report.txt` / `memory-report.json`。构建脚本在链接之前运行，报告反映的
+// 是上一次链接结果 (源码变化后重新生成)。
+//
+// 预算通过环境变量配置 (可写入 .cargo/config.toml 的 [env])，取值如 `60K`、`2M`、
+// `61440`。预算同时生成链接脚本断言，超出时当次链接失败，不依赖报告的时效。
+
+/// 预算环境变量与断言覆盖的输出段
+const BUDGETS: [(Region, &str, &[&str]); 3] = [
+    (Region::Iram, "RUSTRTOS_IRAM_BUDGET", &[".rwtext"]),
+    (Region::Dram, "RUSTRTOS_DRAM_BUDGET", &[".data", ".bss"]),
+    (Region::Flash, "RUSTRTOS_FLASH_BUDGET", &[".text", ".rodata"]),
+];
+
+/// 接近预算时提示的占用比例 (%)
+const BUDGET_WARN_PERCENT: u64 = 90;
+
+/// 内存区域 (按 ESP32-S3 地址映射划分)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Region {
+    Iram,
+    Dram,
+    Flash,
+    Rtc,
+}
+
+impl Region {
+    fn of(addr: u64) -> Option<Self> {
+        match addr {
+            0x4037_0000..=0x403D_FFFF => Some(Region::Iram),
+            0x3FC8_8000..=0x3FCF_FFFF => Some(Region::Dram),
+            0x3C00_0000..=0x3DFF_FFFF | 0x4200_0000..=0x43FF_FFFF => Some(Region::Flash),
+            0x5000_0000..=0x5000_1FFF | 0x600F_E000..=0x600F_FFFF => Some(Region::Rtc),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Region::Iram => "IRAM",
+            Region::Dram => "DRAM",
+            Region::Flash => "FLASH",
+            Region::Rtc => "RTC",
+        }
+    }
+}
+
+/// map 文件统计结果 (字节)
+#[derive(Default)]
+struct Usage {
+    regions: BTreeMap<Region, u64>,
+    sections: BTreeMap<(Region, String), u64>,
+    crates: BTreeMap<(Region, String), u64>,
+}
+
+fn memory_layout() {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    // OUT_DIR = target/<triple>/<profile>/build/<pkg>-<hash>/out
+    let profile_dir = out_dir.ancestors().nth(3).unwrap_or(&out_dir).to_path_buf();
+    let map_path = profile_dir.join("rustrtos.map");
+    println!("cargo:rustc-link-arg=-Wl,-Map={}", map_path.display());
+    println!("cargo:rerun-if-changed=src");
+
+    let mut budgets = BTreeMap::new();
+    for (region, var, _) in BUDGETS {
+        println!("cargo:rerun-if-env-changed={}", var);
+        if let Ok(value) = env::var(var) {
+            match parse_size(&value) {
+                Some(bytes) => {
+                    budgets.insert(region, bytes);
+                }
+                None => panic!("{} 无效: {:?} (示例: 60K、2M、61440)", var, value),
+            }
+        }
+    }
+
+    write_budget_script(&out_dir, &budgets);
+
+    // 首次构建尚无 map
+    let Ok(map) = fs::read_to_string(&map_path) else {
+        return;
+    };
+    let usage = parse_map(&map);
+    let _ = fs::write(profile_dir.join("memory-report.txt"), text_report(&usage, &budgets));
+    let _ = fs::write(profile_dir.join("memory-report.json"), json_report(&usage, &budgets));
+
+    for (region, &budget) in &budgets {
+        let used = usage.regions.get(region).copied().unwrap_or(0);
+        if used > budget || used * 100 >= budget * BUDGET_WARN_PERCENT {
+            println!(
+                "cargo:warning={} {} / {} bytes ({}%) in last link, see {}",
+                region.name(),
+                used,
+                budget,
+                used * 100 / budget.max(1),
+                profile_dir.join("memory-report.txt").display()
+            );
+        }
+    }
+}
+
+/// 解析 `60K` / `60KB` / `2M` / `61440`
+fn parse_size(value: &str) -> Option<u64> {
+    let value = value.trim().to_ascii_uppercase();
+    let value = value.trim_end_matches('B').trim_end_matches('I');
+    let (digits, scale) = match value.strip_suffix('K') {
+        Some(digits) => (digits, 1024),
+        None => match value.strip_suffix('M') {
+            Some(digits) => (digits, 1024 * 1024),
+            None => (value, 1),
+        },
+    };
+    digits.trim().parse::<u64>().ok().map(|n| n * scale)
+}
+
+/// 生成预算断言链接脚本 (未配置预算时为空文件)
+fn write_budget_script(out_dir: &Path, budgets: &BTreeMap<Region, u64>) {
+    let mut script = String::from("/* 由 build.rs 生成: 内存预算断言 */\n");
+    for (region, var, sections) in BUDGETS {
+        let Some(budget) = budgets.get(&region) else {
+            continue;
+        };
+        let sum: Vec<String> = sections.iter().map(|s| format!("SIZEOF({})", s)).collect();
+        let _ = writeln!(
+            script,
+            "ASSERT({} <= {}, \"{} budget exceeded ({} = {} bytes), see memory-report.txt\");",
+            sum.join(" + "),
+            budget,
+            region.name(),
+            var,
+            budget
+        );
+    }
+    fs::write(out_dir.join("memory_budget.x"), script).expect("无法写入 memory_budget.x");
+    println!("cargo:rustc-link-search={}", out_dir.display());
+    println!("cargo:rustc-link-arg=-Tmemory_budget.x");
+}
+
+fn parse_hex(field: &str) -> Option<u64> {
+    u64::from_str_radix(field.strip_prefix("0x")?, 16).ok()
+}
+
+/// 输入文件 → crate 名
+///
+/// `.../libfoo-0123456789abcdef.rlib(foo-....rcgu.o)` → `foo`，
+/// `.../libbtdm_app.a(x.o)` → `btdm_app`，`.../deps/rustrtos-<hash>.rustrtos.<cgu>.rcgu.o` → `rustrtos`
+fn crate_of(file: &str) -> String {
+    let archive = file.split('(').next().unwrap_or(file);
+    let base = Path::new(archive).file_name().and_then(|n| n.to_str()).unwrap_or(archive);
+    let base = match base.strip_prefix("lib") {
+        Some(rest) if archive.ends_with(".rlib") || archive.ends_with(".a") => rest,
+        _ => base,
+    };
+    let stem = base.split('.').next().unwrap_or(base);
+    match stem.rsplit_once('-') {
+        Some((name, hash)) if hash.len() == 16 && hash.bytes().all(|b| b.is_ascii_hexdigit()) => name.to_string(),
+        _ => stem.to_string(),
+    }
+}
+
+/// 解析 GNU ld map 文件的 "Linker script and memory map" 部分
+///
+/// 输出段行顶格 (`.rwtext 0x40378000 0x1234`)，输入段行缩进一格并带来源文件；
+/// 名称过长时地址与大小换到下一行。
+fn parse_map(map: &str) -> Usage {
+    let mut usage = Usage::default();
+    let body = map.split_once("Linker script and memory map").map_or(map, |(_, body)| body);
+    let mut current: Option<(Region, String)> = None;
+    let mut wrapped: Option<(bool, String)> = None;
+
+    for line in body.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let Some(&first) = fields.first() else {
+            continue;
+        };
+        let output = !line.starts_with(' ');
+        let (output, name, rest) = match wrapped.take() {
+            Some((output, name)) if parse_hex(first).is_some() => (output, name, &fields[..]),
+            _ if fields.len() == 1 && (first.starts_with('.') || first == "COMMON") => {
+                wrapped = Some((output, first.to_string()));
+                continue;
+            }
+            _ => (output, first.to_string(), &fields[1..]),
+        };
+        let (Some(addr), Some(size)) = (rest.first().and_then(|f| parse_hex(f)), rest.get(1).and_then(|f| parse_hex(f)))
+        else {
+            continue;
+        };
+
+        if output {
+            current = Region::of(addr).map(|region| (region, name.clone()));
+            if let Some(region) = Region::of(addr) {
+                if size > 0 {
+                    *usage.regions.entry(region).or_default() += size;
+                    *usage.sections.entry((region, name)).or_default() += size;
+                }
+            }
+        } else if let Some((region, _)) = &current {
+            if size == 0 {
+                continue;
+            }
+            let owner = match rest.get(2..) {
+                _ if name == "*fill*" => "(padding)".to_string(),
+                Some(file) if !file.is_empty() => crate_of(&file.join(" ")),
+                _ => "(linker)".to_string(),
+            };
+            *usage.crates.entry((*region, owner)).or_default() += size;
+        }
+    }
+    usage
+}
+
+fn text_report(usage: &Usage, budgets: &BTreeMap<Region, u64>) -> String {
+    let mut out = String::from("# RustRTOS memory layout (last link)\n\n## Regions\n");
+    for (region, used) in &usage.regions {
+        let _ = match budgets.get(region) {
+            Some(budget) => writeln!(
+                out,
+                "{:<6} {:>9} bytes / budget {:>9} ({}%){}",
+                region.name(),
+                used,
+                budget,
+                used * 100 / (*budget).max(1),
+                if used > budget { "  OVER BUDGET" } else { "" }
+            ),
+            None => writeln!(out, "{:<6} {:>9} bytes", region.name(), used),
+        };
+    }
+    for (title, table) in [("Sections", &usage.sections), ("Crates", &usage.crates)] {
+        let _ = writeln!(out, "\n## {}", title);
+        let mut rows: Vec<_> = table.iter().collect();
+        // 区域内按占用从大到小
+        rows.sort_by(|a, b| a.0 .0.cmp(&b.0 .0).then(b.1.cmp(a.1)));
+        for ((region, name), size) in rows {
+            let _ = writeln!(out, "{:<6} {:>9}  {}", region.name(), size, name);
+        }
+    }
+    out
+}
+
+fn json_report(usage: &Usage, budgets: &BTreeMap<Region, u64>) -> String {
+    let mut out = String::from("{\"regions\":{");
+    for (i, (region, used)) in usage.regions.iter().enumerate() {
+        let budget = budgets.get(region).map_or("null".to_string(), |b| b.to_string());
+        let _ = write!(out, "{}\"{}\":{{\"used\":{},\"budget\":{}}}", if i > 0 { "," } else { "" }, region.name(), used, budget);
+    }
+    out.push('}');
+    for (key, table) in [("sections", &usage.sections), ("crates", &usage.crates)] {
+        let _ = write!(out, ",\"{}\":[", key);
+        for (i, ((region, name), size)) in table.iter().enumerate() {
+            let name = name.replace('\\', "\\\\").replace('"', "\\\"");
+            let _ = write!(out, "{}{{\"region\":\"{}\",\"name\":\"{}\",\"size\":{}}}", if i > 0 { "," } else { "" }, region.name(), name, size);
+        }
+        out.push(']');
+    }
+    out.push_str("}\n");
+    out
 }
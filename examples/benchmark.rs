@@ -4,6 +4,7 @@
 //! - 任务切换延迟
 //! - 信号传递延迟
 //! - 中断响应时间
+//! - 硬件定时器 (GPTimer) 报警抖动
 //!
 //! # 运行
 //! ```bash
@@ -17,8 +18,10 @@ esp_bootloader_esp_idf::esp_app_desc!();
 
 use embassy_executor::Spawner;
 use embassy_time::{Duration, Instant, Timer};
-use esp_hal::timer::timg::TimerGroup;
+use esp_hal::ram;
+use esp_hal::timer::timg::{Timer as TimgTimer, TimerGroup};
 use portable_atomic::{AtomicU32, Ordering};
+use rustrtos::drivers::gptimer::{AlarmEvent, GpTimer};
 
 // ===== 条件编译日志 =====
 #[cfg(feature = "dev")]
@@ -90,6 +93,33 @@ async fn measure_timer_precision() {
     println!("Timer precision benchmark complete");
 }
 
+/// GPTimer 报警回调 (仅计数)
+#[ram]
+fn on_alarm(_event: &AlarmEvent) {
+    ALARMS.fetch_add(1, Ordering::Relaxed);
+}
+
+static ALARMS: AtomicU32 = AtomicU32::new(0);
+
+/// 测量硬件定时器报警抖动
+#[embassy_executor::task]
+async fn measure_gptimer_jitter(timer: TimgTimer<'static>) {
+    println!("Starting GPTimer jitter benchmark...");
+
+    let Ok(mut gptimer) = GpTimer::new(timer, on_alarm) else {
+        println!("  GPTimer unavailable");
+        return;
+    };
+    for period_us in [10u32, 50, 100, 1000] {
+        match gptimer.characterize(period_us, 1000).await {
+            Ok(report) => println!("  Period {} us: {}", period_us, report),
+            Err(_e) => println!("  Period {} us: {}", period_us, _e),
+        }
+    }
+
+    println!("GPTimer jitter benchmark complete");
+}
+
 /// 报告任务
 #[embassy_executor::task]
 async fn reporter_task() {
@@ -98,6 +128,7 @@ async fn reporter_task() {
     
     println!("=== Benchmark Summary ===");
     println!("Total task switches: {}", TASK_SWITCHES.load(Ordering::Relaxed));
+    println!("Total GPTimer alarms: {}", ALARMS.load(Ordering::Relaxed));
     println!("========================");
 }
 
@@ -110,9 +141,11 @@ async fn main(spawner: Spawner) {
     
     let timg0 = TimerGroup::new(peripherals.TIMG0);
     esp_rtos::start(timg0.timer0);
+    let timg1 = TimerGroup::new(peripherals.TIMG1);
     
     spawner.spawn(measure_task_switch()).ok();
     spawner.spawn(measure_timer_precision()).ok();
+    spawner.spawn(measure_gptimer_jitter(timg1.timer0)).ok();
     spawner.spawn(reporter_task()).ok();
     
     loop {
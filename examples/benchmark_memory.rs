@@ -3,6 +3,7 @@
 //! 测试各种内存分配策略的性能:
 //! - DRAM 分配
 //! - 内存池分配
+//! - DRAM / PSRAM 访问带宽与延迟 (顺序/随机、多种步长)
 //! - 对比分析
 //!
 //! # 运行
@@ -19,6 +20,7 @@ use embassy_executor::Spawner;
 use embassy_time::{Duration, Instant, Timer};
 use esp_hal::timer::timg::TimerGroup;
 use rustrtos::mem::pool::{MemoryPool, Backend};
+use rustrtos::mem::{bench, psram};
 use portable_atomic::{AtomicU32, Ordering};
 
 // ===== 条件编译日志 =====
@@ -140,6 +142,33 @@ async fn pool_benchmark_task() {
     println!("\nMemory benchmark complete!");
 }
 
+// DRAM 测试缓冲区 (16KB)
+static mut DRAM_BUF: [u32; 4096] = [0; 4096];
+
+/// DRAM / PSRAM 访问基准测试
+#[embassy_executor::task]
+async fn access_benchmark_task() {
+    // 等待内存池测试结束，避免互相干扰
+    Timer::after(Duration::from_secs(2)).await;
+
+    println!("\n=== DRAM vs PSRAM Access Benchmark ===");
+
+    // PSRAM 缓冲区 (256KB，远大于数据 cache)
+    let mut psram_buf = match psram::alloc_array::<u32, 65536>() {
+        Ok(buf) => buf,
+        Err(e) => {
+            println!("PSRAM allocation failed: {:?}", e);
+            return;
+        }
+    };
+
+    #[allow(static_mut_refs)]
+    let dram = unsafe { &mut DRAM_BUF };
+    for result in bench::run(dram, &mut psram_buf[..]) {
+        println!("{}", result);
+    }
+}
+
 #[esp_rtos::main]
 async fn main(spawner: Spawner) {
     let peripherals = esp_hal::init(esp_hal::Config::default());
@@ -150,7 +179,12 @@ async fn main(spawner: Spawner) {
     let timg0 = TimerGroup::new(peripherals.TIMG0);
     esp_rtos::start(timg0.timer0);
     
+    if let Err(e) = psram::init() {
+        println!("PSRAM init failed: {:?}", e);
+    }
+    
     spawner.spawn(pool_benchmark_task()).ok();
+    spawner.spawn(access_benchmark_task()).ok();
     
     loop {
         Timer::after(Duration::from_secs(60)).await;
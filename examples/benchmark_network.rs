@@ -42,15 +42,11 @@ use core::net::SocketAddrV4;
 use embassy_executor::Spawner;
 use embassy_time::{Duration, Instant, Timer};
 use esp_hal::timer::timg::TimerGroup;
-use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
-use embassy_sync::channel::Channel;
-use embassy_sync::signal::Signal;
+use esp_radio::wifi::{ClientConfig, ModeConfig, WifiController};
 use static_cell::StaticCell;
 use portable_atomic::{AtomicU32, AtomicU64, Ordering};
 
-use rustrtos::net::wifi::{WifiController, WifiEvent, WifiMode};
-use rustrtos::net::tcp::{TcpClient, NetworkStack, StackConfig, Ipv4Address};
-use rustrtos::net::config::WIFI_EVENT_QUEUE_SIZE;
+use rustrtos::net::tcp::{TcpClient, NetworkStack, NetResources, StackConfig, Ipv4Address, WifiRunner};
 
 // ===== 配置 =====
 const WIFI_SSID: &str = "SSID";
@@ -86,8 +82,9 @@ fn panic(_info: &core::panic::PanicInfo) -> ! {
 }
 
 // ===== 静态分配 =====
-static WIFI_EVENT_CHANNEL: StaticCell<Channel<CriticalSectionRawMutex, WifiEvent, WIFI_EVENT_QUEUE_SIZE>> = StaticCell::new();
-static WIFI_CONNECTED_SIGNAL: StaticCell<Signal<CriticalSectionRawMutex, bool>> = StaticCell::new();
+static RADIO_CONTROLLER: StaticCell<esp_radio::Controller<'static>> = StaticCell::new();
+static NET_RESOURCES: StaticCell<NetResources> = StaticCell::new();
+static STACK: StaticCell<NetworkStack<'static>> = StaticCell::new();
 
 // 统计数据
 static TX_BYTES: AtomicU64 = AtomicU64::new(0);
@@ -136,17 +133,18 @@ impl BenchmarkResult {
 /// WiFi 连接时间测试
 async fn benchmark_wifi_connect(
     wifi_ctrl: &mut WifiController<'_>,
+    stack: &NetworkStack<'_>,
 ) -> BenchmarkResult {
     println!("\n[Benchmark] WiFi Connection Time");
     println!("Connecting to '{}'...", WIFI_SSID);
     
     // 确保断开
-    let _ = wifi_ctrl.disconnect().await;
+    let _ = wifi_ctrl.disconnect_async().await;
     Timer::after(Duration::from_millis(500)).await;
     
     let start = Instant::now();
     
-    let connect_result = wifi_ctrl.connect(WIFI_SSID, WIFI_PASSWORD).await;
+    let connect_result = wifi_ctrl.connect_async().await;
     
     let connect_time = start.elapsed();
     
@@ -159,14 +157,15 @@ async fn benchmark_wifi_connect(
         };
     }
     
-    // 等待 IP
+    // 等待 DHCP 获取 IP
     let ip_start = Instant::now();
-    let ip_result = wifi_ctrl.wait_for_ip().await;
+    let ip_result = stack.wait_ready(Duration::from_secs(30)).await;
     let ip_time = ip_start.elapsed();
     
     let total_time = start.elapsed();
     
-    if let Ok(ip) = ip_result {
+    if let (Ok(()), Some(ip)) = (ip_result, stack.local_ip()) {
+        let ip = ip.octets();
         println!("Connected! IP: {}.{}.{}.{}", ip[0], ip[1], ip[2], ip[3]);
     }
     
@@ -182,7 +181,7 @@ async fn benchmark_wifi_connect(
 
 /// TCP 吞吐量测试 (发送)
 async fn benchmark_tcp_throughput_tx(
-    _stack: &NetworkStack<'_>,
+    stack: &NetworkStack<'_>,
 ) -> BenchmarkResult {
     println!("\n[Benchmark] TCP TX Throughput");
     println!("Connecting to {}:{}...", 
@@ -196,7 +195,9 @@ async fn benchmark_tcp_throughput_tx(
     );
     let server_addr = SocketAddrV4::new(server_ip.to_std(), IPERF_SERVER_PORT);
     
-    let mut tcp_client = TcpClient::new();
+    let mut socket_rx = [0u8; 4096];
+    let mut socket_tx = [0u8; 4096];
+    let mut tcp_client = TcpClient::new(stack, &mut socket_rx, &mut socket_tx);
     
     if tcp_client.connect(server_addr).await.is_err() {
         println!("TCP connect failed!");
@@ -254,7 +255,7 @@ async fn benchmark_tcp_throughput_tx(
 
 /// TCP 吞吐量测试 (接收)
 async fn benchmark_tcp_throughput_rx(
-    _stack: &NetworkStack<'_>,
+    stack: &NetworkStack<'_>,
 ) -> BenchmarkResult {
     println!("\n[Benchmark] TCP RX Throughput");
     println!("Note: Requires iperf client sending data to this device");
@@ -268,7 +269,9 @@ async fn benchmark_tcp_throughput_rx(
     );
     let server_addr = SocketAddrV4::new(server_ip.to_std(), IPERF_SERVER_PORT);
     
-    let mut tcp_client = TcpClient::new();
+    let mut socket_rx = [0u8; 4096];
+    let mut socket_tx = [0u8; 4096];
+    let mut tcp_client = TcpClient::new(stack, &mut socket_rx, &mut socket_tx);
     
     if tcp_client.connect(server_addr).await.is_err() {
         println!("TCP connect failed!");
@@ -290,14 +293,11 @@ async fn benchmark_tcp_throughput_rx(
     
     while start.elapsed() < deadline {
         match tcp_client.read(&mut rx_buffer).await {
-            Ok(received) if received > 0 => {
+            Ok(0) | Err(_) => break,
+            Ok(received) => {
                 RX_BYTES.fetch_add(received as u64, Ordering::Relaxed);
                 RX_PACKETS.fetch_add(1, Ordering::Relaxed);
             }
-            _ => {
-                // 短暂等待后重试
-                Timer::after(Duration::from_millis(10)).await;
-            }
         }
     }
     
@@ -327,7 +327,7 @@ async fn benchmark_tcp_throughput_rx(
 
 /// TCP 延迟测试 (Ping-Pong)
 async fn benchmark_tcp_latency(
-    _stack: &NetworkStack<'_>,
+    stack: &NetworkStack<'_>,
 ) -> BenchmarkResult {
     println!("\n[Benchmark] TCP Latency (Echo)");
     
@@ -337,7 +337,9 @@ async fn benchmark_tcp_latency(
     );
     let server_addr = SocketAddrV4::new(server_ip.to_std(), IPERF_SERVER_PORT);
     
-    let mut tcp_client = TcpClient::new();
+    let mut socket_rx = [0u8; 4096];
+    let mut socket_tx = [0u8; 4096];
+    let mut tcp_client = TcpClient::new(stack, &mut socket_rx, &mut socket_tx);
     
     if tcp_client.connect(server_addr).await.is_err() {
         println!("TCP connect failed!");
@@ -364,13 +366,20 @@ async fn benchmark_tcp_latency(
         let start = Instant::now();
         
         // 发送
-        if tcp_client.write(&ping_data).await.is_err() {
-            continue;
+        if tcp_client.write_all(&ping_data).await.is_err() {
+            break;
         }
         
-        // 接收
-        if tcp_client.read(&mut pong_data).await.is_err() {
-            continue;
+        // 接收 (回显可能分段到达)
+        let mut received = 0;
+        while received < PING_SIZE {
+            match tcp_client.read(&mut pong_data[received..]).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => received += n,
+            }
+        }
+        if received < PING_SIZE {
+            break;
         }
         
         let latency_us = start.elapsed().as_micros() as u32;
@@ -405,11 +414,17 @@ async fn benchmark_tcp_latency(
     }
 }
 
+/// 协议栈驱动任务 (必须持续运行)
+#[embassy_executor::task]
+async fn net_task(mut runner: WifiRunner<'static>) {
+    runner.run().await
+}
+
 /// 网络基准测试主任务
 #[embassy_executor::task]
 async fn benchmark_task(
-    event_channel: &'static Channel<CriticalSectionRawMutex, WifiEvent, WIFI_EVENT_QUEUE_SIZE>,
-    connected_signal: &'static Signal<CriticalSectionRawMutex, bool>,
+    mut wifi_ctrl: WifiController<'static>,
+    stack: &'static mut NetworkStack<'static>,
 ) {
     println!("\n");
     println!("╔══════════════════════════════════════════╗");
@@ -423,27 +438,22 @@ async fn benchmark_task(
     // =========================================
     // 初始化
     // =========================================
-    let mut wifi_ctrl = WifiController::new(event_channel, connected_signal);
-    
-    println!("\n[Init] Initializing WiFi controller...");
-    if let Err(e) = wifi_ctrl.init().await {
-        println!("WiFi init failed: {:?}", e);
+    println!("\n[Init] Starting WiFi...");
+    let config = ModeConfig::Client(
+        ClientConfig::default()
+            .with_ssid(WIFI_SSID.try_into().unwrap())
+            .with_password(WIFI_PASSWORD.try_into().unwrap()),
+    );
+    if let Err(e) = wifi_ctrl.set_config(&config) {
+        println!("WiFi set config failed: {:?}", e);
         return;
     }
-    
-    if let Err(e) = wifi_ctrl.set_mode(WifiMode::Sta).await {
-        println!("Set mode failed: {:?}", e);
+    if let Err(e) = wifi_ctrl.start_async().await {
+        println!("WiFi start failed: {:?}", e);
         return;
     }
     println!("[Init] WiFi ready");
     
-    // 初始化网络栈
-    let mut stack = NetworkStack::new(StackConfig::default());
-    if let Err(e) = stack.init().await {
-        println!("Stack init failed: {:?}", e);
-        return;
-    }
-    
     // =========================================
     // 运行基准测试
     // =========================================
@@ -451,19 +461,19 @@ async fn benchmark_task(
     // 1. WiFi 连接时间
     println!("\n==================================================");
     println!("Running benchmark 1/4: WiFi Connection Time");
-    let result = benchmark_wifi_connect(&mut wifi_ctrl).await;
+    let result = benchmark_wifi_connect(&mut wifi_ctrl, stack).await;
     let _ = results.push(result);
     
     // 确保已连接并有 IP
-    if !wifi_ctrl.is_connected() {
-        if let Err(e) = wifi_ctrl.connect(WIFI_SSID, WIFI_PASSWORD).await {
+    if !matches!(wifi_ctrl.is_connected(), Ok(true)) {
+        if let Err(e) = wifi_ctrl.connect_async().await {
             println!("Connect failed: {:?}", e);
             return;
         }
     }
     
-    if let Err(e) = wifi_ctrl.wait_for_ip().await {
-        println!("Get IP failed: {:?}", e);
+    if let Err(e) = stack.init().await {
+        println!("Link not up: {:?}", e);
         return;
     }
     
@@ -475,7 +485,7 @@ async fn benchmark_task(
     // 2. TCP 发送吞吐量
     println!("\n==================================================");
     println!("Running benchmark 2/4: TCP TX Throughput");
-    let result = benchmark_tcp_throughput_tx(&stack).await;
+    let result = benchmark_tcp_throughput_tx(stack).await;
     let _ = results.push(result);
     
     Timer::after(Duration::from_secs(2)).await;
@@ -483,7 +493,7 @@ async fn benchmark_task(
     // 3. TCP 接收吞吐量
     println!("\n==================================================");
     println!("Running benchmark 3/4: TCP RX Throughput");
-    let result = benchmark_tcp_throughput_rx(&stack).await;
+    let result = benchmark_tcp_throughput_rx(stack).await;
     let _ = results.push(result);
     
     Timer::after(Duration::from_secs(2)).await;
@@ -491,7 +501,7 @@ async fn benchmark_task(
     // 4. TCP 延迟
     println!("\n==================================================");
     println!("Running benchmark 4/4: TCP Latency");
-    let result = benchmark_tcp_latency(&stack).await;
+    let result = benchmark_tcp_latency(stack).await;
     let _ = results.push(result);
     
     // =========================================
@@ -533,23 +543,46 @@ async fn main(spawner: Spawner) {
     esp_rtos::start(timg0.timer0);
     
     // 初始化 esp-radio (WiFi/BLE 驱动)
-    match esp_radio::init() {
-        Ok(_controller) => println!("esp-radio initialized successfully"),
+    let radio_controller = match esp_radio::init() {
+        Ok(ctrl) => {
+            println!("esp-radio initialized successfully");
+            ctrl
+        }
         Err(e) => {
             println!("esp-radio init failed: {:?}", e);
             loop { core::hint::spin_loop(); }
         }
-    }
-    
-    // 初始化静态通道
-    let event_channel = WIFI_EVENT_CHANNEL.init(Channel::new());
-    let connected_signal = WIFI_CONNECTED_SIGNAL.init(Signal::new());
-    
+    };
+    let radio_ref = RADIO_CONTROLLER.init(radio_controller);
+
+    let (wifi_ctrl, interfaces) = match esp_radio::wifi::new(radio_ref, peripherals.WIFI, Default::default()) {
+        Ok(wifi) => wifi,
+        Err(e) => {
+            println!("WiFi init failed: {:?}", e);
+            loop { core::hint::spin_loop(); }
+        }
+    };
+
+    // 在 STA 接口上创建协议栈
+    let mut rng = esp_hal::rng::Rng::new();
+    let seed = ((rng.random() as u64) << 32) | rng.random() as u64;
+    let (stack, runner) = match NetworkStack::new(
+        interfaces.sta,
+        StackConfig::default(),
+        NET_RESOURCES.init(NetResources::new()),
+        seed,
+    ) {
+        Ok(stack) => stack,
+        Err(e) => {
+            println!("Network stack init failed: {:?}", e);
+            loop { core::hint::spin_loop(); }
+        }
+    };
+
+    spawner.spawn(net_task(runner)).ok();
+
     // 启动基准测试任务
-    spawner.spawn(benchmark_task(
-        event_channel,
-        connected_signal,
-    )).ok();
+    spawner.spawn(benchmark_task(wifi_ctrl, STACK.init(stack))).ok();
     
     // 主循环
     loop {
@@ -1,6 +1,7 @@
 //! TCP 客户端示例
 //!
-//! 演示如何使用 TCP 客户端连接到服务器并发送 HTTP 请求。
+//! 演示如何使用 TCP 客户端连接到服务器，以及用 `HttpClient` 发送 HTTP 请求
+//! (请求构造、响应头解析、分块/定长响应体与重定向由客户端处理)。
 //!
 //! # 配置
 //! 修改 WIFI_SSID, WIFI_PASSWORD 和目标服务器地址。
@@ -37,18 +38,14 @@ fn init_heap() {
 }
 
 use core::net::SocketAddrV4;
-use core::str::FromStr;
 use embassy_executor::Spawner;
 use embassy_time::{Duration, Timer};
 use esp_hal::timer::timg::TimerGroup;
-use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
-use embassy_sync::channel::Channel;
-use embassy_sync::signal::Signal;
+use esp_radio::wifi::{ClientConfig, ModeConfig, WifiController};
 use static_cell::StaticCell;
 
-use rustrtos::net::wifi::{WifiController, WifiEvent, WifiMode};
-use rustrtos::net::tcp::{TcpClient, NetworkStack, StackConfig, Ipv4Address};
-use rustrtos::net::config::WIFI_EVENT_QUEUE_SIZE;
+use rustrtos::net::http::{self, HttpClient, TcpTransport};
+use rustrtos::net::tcp::{Ipv4Address, NetResources, NetworkStack, StackConfig, TcpClient, WifiRunner};
 
 // ===== 配置 =====
 const WIFI_SSID: &str = "YourSSID";
@@ -58,6 +55,9 @@ const WIFI_PASSWORD: &str = "YourPassword";
 const SERVER_IP: [u8; 4] = [93, 184, 216, 34]; // example.com
 const SERVER_PORT: u16 = 80;
 
+// HTTP 请求目标 (主机名经 DNS 解析)
+const HTTP_URL: &str = "http://example.com/";
+
 // ===== 条件编译日志 =====
 #[cfg(feature = "dev")]
 use esp_println::println;
@@ -78,84 +78,87 @@ fn panic(_info: &core::panic::PanicInfo) -> ! {
 }
 
 // ===== 静态分配 =====
-static WIFI_EVENT_CHANNEL: StaticCell<Channel<CriticalSectionRawMutex, WifiEvent, WIFI_EVENT_QUEUE_SIZE>> = StaticCell::new();
-static WIFI_CONNECTED_SIGNAL: StaticCell<Signal<CriticalSectionRawMutex, bool>> = StaticCell::new();
+static RADIO_CONTROLLER: StaticCell<esp_radio::Controller<'static>> = StaticCell::new();
+static NET_RESOURCES: StaticCell<NetResources> = StaticCell::new();
+static STACK: StaticCell<NetworkStack<'static>> = StaticCell::new();
 
-/// HTTP GET 请求
-const HTTP_REQUEST: &[u8] = b"GET / HTTP/1.1\r\nHost: example.com\r\nConnection: close\r\n\r\n";
+/// 协议栈驱动任务 (必须持续运行)
+#[embassy_executor::task]
+async fn net_task(mut runner: WifiRunner<'static>) {
+    runner.run().await
+}
 
-/// TCP 客户端任务
+/// WiFi 连接任务
 #[embassy_executor::task]
-async fn tcp_client_task(
-    event_channel: &'static Channel<CriticalSectionRawMutex, WifiEvent, WIFI_EVENT_QUEUE_SIZE>,
-    connected_signal: &'static Signal<CriticalSectionRawMutex, bool>,
-) {
-    println!("TCP client task started");
-    
-    // =========================================
-    // 1. WiFi 连接
-    // =========================================
-    let mut wifi_ctrl = WifiController::new(event_channel, connected_signal);
-    
-    println!("Initializing WiFi controller...");
-    if let Err(e) = wifi_ctrl.init().await {
-        println!("WiFi init failed: {:?}", e);
+async fn wifi_task(mut wifi_ctrl: WifiController<'static>) {
+    let config = ModeConfig::Client(
+        ClientConfig::default()
+            .with_ssid(WIFI_SSID.try_into().unwrap())
+            .with_password(WIFI_PASSWORD.try_into().unwrap()),
+    );
+    if let Err(e) = wifi_ctrl.set_config(&config) {
+        println!("WiFi set config failed: {:?}", e);
         return;
     }
-    
-    if let Err(e) = wifi_ctrl.set_mode(WifiMode::Sta).await {
-        println!("Set mode failed: {:?}", e);
+    if let Err(e) = wifi_ctrl.start_async().await {
+        println!("WiFi start failed: {:?}", e);
         return;
     }
-    
+
     println!("Connecting to WiFi '{}'...", WIFI_SSID);
-    if let Err(e) = wifi_ctrl.connect(WIFI_SSID, WIFI_PASSWORD).await {
-        println!("WiFi connect failed: {:?}", e);
-        return;
-    }
-    
-    println!("Waiting for IP...");
-    let local_ip = match wifi_ctrl.wait_for_ip().await {
-        Ok(ip) => {
-            println!("Got IP: {}.{}.{}.{}", ip[0], ip[1], ip[2], ip[3]);
-            ip
-        }
-        Err(e) => {
-            println!("Failed to get IP: {:?}", e);
-            return;
+    loop {
+        match wifi_ctrl.connect_async().await {
+            Ok(()) => {
+                println!("WiFi connected");
+                wifi_ctrl.wait_for_event(esp_radio::wifi::WifiEvent::StaDisconnected).await;
+                println!("WiFi disconnected, reconnecting...");
+            }
+            Err(e) => println!("WiFi connect failed: {:?}", e),
         }
-    };
-    
+        Timer::after(Duration::from_secs(5)).await;
+    }
+}
+
+/// TCP 客户端任务
+#[embassy_executor::task]
+async fn tcp_client_task(stack: &'static mut NetworkStack<'static>) {
+    println!("TCP client task started");
+
     // =========================================
-    // 2. 初始化网络栈
+    // 1. 等待链路与 DHCP
     // =========================================
-    println!("\nInitializing network stack...");
-    let mut stack = NetworkStack::new(StackConfig::default());
-    
     if let Err(e) = stack.init().await {
-        println!("Stack init failed: {:?}", e);
+        println!("Link not up: {:?}", e);
         return;
     }
-    
+
+    println!("Waiting for IP...");
     if let Err(e) = stack.start_dhcp().await {
         println!("DHCP failed: {:?}", e);
         return;
     }
-    
+    if let Some(ip) = stack.local_ip() {
+        let ip = ip.octets();
+        println!("Got IP: {}.{}.{}.{}", ip[0], ip[1], ip[2], ip[3]);
+    }
+
     println!("Network stack ready");
-    
+
     // =========================================
-    // 3. TCP 连接
+    // 2. TCP 连接
     // =========================================
     let server_ip = Ipv4Address::new(SERVER_IP[0], SERVER_IP[1], SERVER_IP[2], SERVER_IP[3]);
     let server_addr = SocketAddrV4::new(server_ip.to_std(), SERVER_PORT);
-    
+
     println!("\n=========================================");
     println!("Connecting to {}.{}.{}.{}:{}...",
         SERVER_IP[0], SERVER_IP[1], SERVER_IP[2], SERVER_IP[3], SERVER_PORT);
-    
-    let mut tcp_client = TcpClient::new();
-    
+
+    let mut socket_rx = [0u8; 2048];
+    let mut socket_tx = [0u8; 1024];
+    let mut tcp_client = TcpClient::new(stack, &mut socket_rx, &mut socket_tx);
+    tcp_client.set_timeout(Some(Duration::from_secs(10)));
+
     match tcp_client.connect(server_addr).await {
         Ok(_) => {
             println!("TCP connected!");
@@ -166,79 +169,48 @@ async fn tcp_client_task(
             return;
         }
     }
-    
-    // =========================================
-    // 4. 发送 HTTP 请求
-    // =========================================
-    println!("\nSending HTTP request...");
-    println!("---");
-    // 打印请求 (安全地处理非 UTF8)
-    if let Ok(req_str) = core::str::from_utf8(HTTP_REQUEST) {
-        for line in req_str.lines() {
-            println!("> {}", line);
-        }
-    }
-    println!("---");
-    
-    match tcp_client.write(HTTP_REQUEST).await {
-        Ok(sent) => println!("Sent {} bytes", sent),
-        Err(e) => {
-            println!("Send failed: {:?}", e);
-            return;
-        }
+
+    println!("Closing connection...");
+    if let Err(e) = tcp_client.close().await {
+        println!("Close error: {:?}", e);
     }
-    
+    drop(tcp_client);
+
     // =========================================
-    // 5. 接收响应
+    // 3. HTTP GET (HttpClient)
     // =========================================
-    println!("\nWaiting for response...");
-    
-    let mut rx_buf = [0u8; 1024];
-    let mut total_received = 0usize;
-    
-    // 简单的接收循环 (实际实现需要更复杂的逻辑)
-    for _ in 0..10 {
-        Timer::after(Duration::from_millis(500)).await;
-        
-        match tcp_client.read(&mut rx_buf).await {
-            Ok(len) if len > 0 => {
-                total_received += len;
-                
-                // 打印接收到的数据 (作为字符串)
-                if let Ok(response) = core::str::from_utf8(&rx_buf[..len]) {
-                    for line in response.lines().take(10) {
-                        println!("< {}", line);
-                    }
-                    if response.lines().count() > 10 {
-                        println!("< ... (truncated)");
-                    }
-                }
-            }
-            Ok(_) => {
-                // 没有更多数据
-                break;
+    println!("\nGET {}", HTTP_URL);
+
+    let mut http_rx = [0u8; 2048];
+    let mut http_tx = [0u8; 1024];
+    let mut client = HttpClient::new(
+        http::ClientConfig::new().with_timeout(Duration::from_secs(10)),
+        [TcpTransport::new(stack.stack(), &mut http_rx, &mut http_tx)],
+    );
+
+    let mut body = [0u8; 2048];
+    match client.get_url(HTTP_URL, &mut body).await {
+        Ok(resp) => {
+            println!("< HTTP {}", resp.status);
+            for (name, value) in resp.headers.iter() {
+                println!("< {}: {}", name, value);
             }
-            Err(e) => {
-                println!("Read error: {:?}", e);
-                break;
+            if let Some(text) = resp.text() {
+                for line in text.lines().take(10) {
+                    println!("< {}", line);
+                }
             }
+            println!("\nBody: {} bytes", resp.body.len());
         }
+        Err(e) => println!("HTTP request failed: {}", e),
     }
-    
-    println!("\nTotal received: {} bytes", total_received);
-    
-    // =========================================
-    // 6. 关闭连接
-    // =========================================
-    println!("Closing connection...");
-    if let Err(e) = tcp_client.close().await {
-        println!("Close error: {:?}", e);
-    }
-    
+    println!("{}", client.stats());
+    client.close_all();
+
     println!("\n=========================================");
     println!("   TCP Client Demo Complete!");
     println!("=========================================");
-    
+
     // 保持任务运行
     loop {
         Timer::after(Duration::from_secs(60)).await;
@@ -262,24 +234,46 @@ async fn main(spawner: Spawner) {
     esp_rtos::start(timg0.timer0);
     
     // 初始化 esp-radio (WiFi/BLE 驱动)
-    match esp_radio::init() {
-        Ok(_controller) => println!("esp-radio initialized successfully"),
+    let radio_controller = match esp_radio::init() {
+        Ok(ctrl) => {
+            println!("esp-radio initialized successfully");
+            ctrl
+        }
         Err(e) => {
             println!("esp-radio init failed: {:?}", e);
             loop { core::hint::spin_loop(); }
         }
-    }
-    
-    // 初始化静态通道
-    let event_channel = WIFI_EVENT_CHANNEL.init(Channel::new());
-    let connected_signal = WIFI_CONNECTED_SIGNAL.init(Signal::new());
-    
-    // 启动 TCP 客户端任务
-    spawner.spawn(tcp_client_task(
-        event_channel,
-        connected_signal,
-    )).ok();
-    
+    };
+    let radio_ref = RADIO_CONTROLLER.init(radio_controller);
+
+    let (wifi_ctrl, interfaces) = match esp_radio::wifi::new(radio_ref, peripherals.WIFI, Default::default()) {
+        Ok(wifi) => wifi,
+        Err(e) => {
+            println!("WiFi init failed: {:?}", e);
+            loop { core::hint::spin_loop(); }
+        }
+    };
+
+    // 在 STA 接口上创建协议栈
+    let mut rng = esp_hal::rng::Rng::new();
+    let seed = ((rng.random() as u64) << 32) | rng.random() as u64;
+    let (stack, runner) = match NetworkStack::new(
+        interfaces.sta,
+        StackConfig::default(),
+        NET_RESOURCES.init(NetResources::new()),
+        seed,
+    ) {
+        Ok(stack) => stack,
+        Err(e) => {
+            println!("Network stack init failed: {:?}", e);
+            loop { core::hint::spin_loop(); }
+        }
+    };
+
+    spawner.spawn(net_task(runner)).ok();
+    spawner.spawn(wifi_task(wifi_ctrl)).ok();
+    spawner.spawn(tcp_client_task(STACK.init(stack))).ok();
+
     // 主循环
     loop {
         Timer::after(Duration::from_secs(60)).await;
@@ -0,0 +1,198 @@
+/* RustRTOS C 接口 - 由 cbindgen 根据 src/ffi 生成，请勿手动修改 */
+
+#ifndef RUSTRTOS_H
+#define RUSTRTOS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+// 成功
+#define RRTOS_OK 0
+
+// 参数无效 (NULL 指针、非 UTF-8、键名非法)
+#define RRTOS_ERR_INVALID_ARG -1
+
+// 服务未就绪 (未注册文件系统或未挂载)
+#define RRTOS_ERR_NOT_READY -2
+
+// 不存在
+#define RRTOS_ERR_NOT_FOUND -3
+
+// 已存在
+#define RRTOS_ERR_EXISTS -4
+
+// 存储空间不足
+#define RRTOS_ERR_NO_SPACE -5
+
+// 句柄无效
+#define RRTOS_ERR_BAD_HANDLE -6
+
+// 句柄正被另一任务使用
+#define RRTOS_ERR_BUSY -7
+
+// 打开的句柄过多
+#define RRTOS_ERR_TOO_MANY -8
+
+// 缓冲区不足或值过长
+#define RRTOS_ERR_TOO_LARGE -9
+
+// 权限或状态不允许 (只读句柄写入、文件已固定)
+#define RRTOS_ERR_DENIED -10
+
+// I/O 错误或数据损坏
+#define RRTOS_ERR_IO -11
+
+// 同时打开的最大文件数
+#define RRTOS_MAX_FILES 8
+
+// 打开标志: 读
+#define RRTOS_O_READ (1 << 0)
+
+// 打开标志: 写
+#define RRTOS_O_WRITE (1 << 1)
+
+// 打开标志: 不存在时创建
+#define RRTOS_O_CREATE (1 << 2)
+
+// 打开标志: 截断
+#define RRTOS_O_TRUNC (1 << 3)
+
+// 打开标志: 追加
+#define RRTOS_O_APPEND (1 << 4)
+
+// 打开标志: 必须新建 (已存在时失败)
+#define RRTOS_O_EXCL (1 << 5)
+
+// 定位: 从文件开头
+#define RRTOS_SEEK_SET 0
+
+// 定位: 从当前位置
+#define RRTOS_SEEK_CUR 1
+
+// 定位: 从文件末尾
+#define RRTOS_SEEK_END 2
+
+// 日志级别: 错误
+#define RRTOS_LOG_ERROR 0
+
+// 日志级别: 警告
+#define RRTOS_LOG_WARN 1
+
+// 日志级别: 信息
+#define RRTOS_LOG_INFO 2
+
+// 日志级别: 调试
+#define RRTOS_LOG_DEBUG 3
+
+// 日志级别: 跟踪
+#define RRTOS_LOG_TRACE 4
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// 状态码说明 (静态字符串，无需释放)
+const char *rrtos_strerror(int32_t code);
+
+// 打开文件
+//
+// # 返回
+// 非负句柄，或负的错误码
+//
+// # Safety
+// `path` 为 NUL 结尾字符串
+int32_t rrtos_fs_open(const char *path, uint32_t flags);
+
+// 关闭文件 (写入的数据先同步到 Flash)
+int32_t rrtos_fs_close(int32_t fd);
+
+// 读取，返回读到的字节数 (0 表示文件末尾)
+//
+// # Safety
+// `buf` 指向至少 `len` 字节的可写内存
+int32_t rrtos_fs_read(int32_t fd, uint8_t *buf, size_t len);
+
+// 写入，返回写入的字节数
+//
+// # Safety
+// `data` 指向至少 `len` 字节的可读内存
+int32_t rrtos_fs_write(int32_t fd, const uint8_t *data, size_t len);
+
+// 定位，返回新位置
+int32_t rrtos_fs_seek(int32_t fd, int64_t offset, int32_t whence);
+
+// 把缓冲的写入同步到 Flash
+int32_t rrtos_fs_sync(int32_t fd);
+
+// 文件大小
+//
+// # Safety
+// `path` 为 NUL 结尾字符串
+int32_t rrtos_fs_size(const char *path);
+
+// 删除文件或空目录
+//
+// # Safety
+// `path` 为 NUL 结尾字符串
+int32_t rrtos_fs_remove(const char *path);
+
+// 重命名
+//
+// # Safety
+// `from` 与 `to` 为 NUL 结尾字符串
+int32_t rrtos_fs_rename(const char *from, const char *to);
+
+// 创建目录 (包括父目录)
+//
+// # Safety
+// `path` 为 NUL 结尾字符串
+int32_t rrtos_fs_mkdir(const char *path);
+
+// 写入值
+//
+// # Safety
+// `ns`、`key` 为 NUL 结尾字符串，`data` 指向至少 `len` 字节
+int32_t rrtos_nvs_set(const char *ns, const char *key, const uint8_t *data, size_t len);
+
+// 读取值，返回长度
+//
+// `buf` 为 NULL 时只返回值长度；缓冲区不足返回 `RRTOS_ERR_TOO_LARGE`。
+//
+// # Safety
+// `ns`、`key` 为 NUL 结尾字符串，`buf` 为 NULL 或指向至少 `len` 字节的可写内存
+int32_t rrtos_nvs_get(const char *ns, const char *key, uint8_t *buf, size_t len);
+
+// 写入 32 位整数 (小端)
+//
+// # Safety
+// `ns`、`key` 为 NUL 结尾字符串
+int32_t rrtos_nvs_set_u32(const char *ns, const char *key, uint32_t value);
+
+// 读取 32 位整数 (值长度不是 4 字节时返回 `RRTOS_ERR_INVALID_ARG`)
+//
+// # Safety
+// `ns`、`key` 为 NUL 结尾字符串，`out` 指向可写的 `uint32_t`
+int32_t rrtos_nvs_get_u32(const char *ns, const char *key, uint32_t *out);
+
+// 删除键
+//
+// # Safety
+// `ns`、`key` 为 NUL 结尾字符串
+int32_t rrtos_nvs_erase(const char *ns, const char *key);
+
+// 输出一条日志
+//
+// # Safety
+// `tag` 为 NULL 或 NUL 结尾字符串，`msg` 为 NUL 结尾字符串
+void rrtos_log(int32_t level, const char *tag, const char *msg);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#define RRTOS_LOGE(tag, msg) rrtos_log(RRTOS_LOG_ERROR, (tag), (msg))
+#define RRTOS_LOGW(tag, msg) rrtos_log(RRTOS_LOG_WARN, (tag), (msg))
+#define RRTOS_LOGI(tag, msg) rrtos_log(RRTOS_LOG_INFO, (tag), (msg))
+#define RRTOS_LOGD(tag, msg) rrtos_log(RRTOS_LOG_DEBUG, (tag), (msg))
+
+#endif  /* RUSTRTOS_H */
@@ -0,0 +1,164 @@
+//! IMA ADPCM 编解码 (4:1)
+//!
+//! 每个 16 位样本编码为 4 位。块头携带预测值和步长索引，
+//! 单个数据包丢失不会影响后续包的解码。
+
+/// 块头长度 (预测值 i16 + 步长索引 u8 + 保留)
+pub const BLOCK_HEADER_LEN: usize = 4;
+
+const INDEX_TABLE: [i8; 16] = [-1, -1, -1, -1, 2, 4, 6, 8, -1, -1, -1, -1, 2, 4, 6, 8];
+
+const STEP_TABLE: [i16; 89] = [
+    7, 8, 9, 10, 11, 12, 13, 14, 16, 17, 19, 21, 23, 25, 28, 31, 34, 37, 41, 45, 50, 55, 60, 66,
+    73, 80, 88, 97, 107, 118, 130, 143, 157, 173, 190, 209, 230, 253, 279, 307, 337, 371, 408,
+    449, 494, 544, 598, 658, 724, 796, 876, 963, 1060, 1166, 1282, 1411, 1552, 1707, 1878, 2066,
+    2272, 2499, 2749, 3024, 3327, 3660, 4026, 4428, 4871, 5358, 5894, 6484, 7132, 7845, 8630,
+    9493, 10442, 11487, 12635, 13899, 15289, 16818, 18500, 20350, 22385, 24623, 27086, 29794,
+    32767,
+];
+
+/// 编解码器状态
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AdpcmState {
+    predictor: i16,
+    index: u8,
+}
+
+impl AdpcmState {
+    /// 初始状态
+    pub const fn new() -> Self {
+        Self { predictor: 0, index: 0 }
+    }
+
+    /// 编码一个样本
+    #[inline]
+    pub fn encode_sample(&mut self, sample: i16) -> u8 {
+        let step = STEP_TABLE[self.index as usize] as i32;
+        let mut diff = sample as i32 - self.predictor as i32;
+        let mut code = 0u8;
+        if diff < 0 {
+            code = 8;
+            diff = -diff;
+        }
+
+        let mut delta = step >> 3;
+        if diff >= step {
+            code |= 4;
+            diff -= step;
+            delta += step;
+        }
+        if diff >= step >> 1 {
+            code |= 2;
+            diff -= step >> 1;
+            delta += step >> 1;
+        }
+        if diff >= step >> 2 {
+            code |= 1;
+            delta += step >> 2;
+        }
+
+        self.apply(code, delta);
+        code
+    }
+
+    /// 解码一个样本
+    #[inline]
+    pub fn decode_sample(&mut self, code: u8) -> i16 {
+        let step = STEP_TABLE[self.index as usize] as i32;
+        let mut delta = step >> 3;
+        if code & 4 != 0 {
+            delta += step;
+        }
+        if code & 2 != 0 {
+            delta += step >> 1;
+        }
+        if code & 1 != 0 {
+            delta += step >> 2;
+        }
+        self.apply(code, delta);
+        self.predictor
+    }
+
+    fn apply(&mut self, code: u8, delta: i32) {
+        let predicted = if code & 8 != 0 {
+            self.predictor as i32 - delta
+        } else {
+            self.predictor as i32 + delta
+        };
+        self.predictor = predicted.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+        self.index = (self.index as i32 + INDEX_TABLE[code as usize] as i32).clamp(0, 88) as u8;
+    }
+}
+
+/// 编码后长度
+pub const fn encoded_len(samples: usize) -> usize {
+    BLOCK_HEADER_LEN + samples.div_ceil(2)
+}
+
+/// 编码一块样本 (带块头)
+///
+/// # 返回
+/// 写入 `out` 的字节数；`out` 不足时返回 None
+pub fn encode_block(state: &mut AdpcmState, pcm: &[i16], out: &mut [u8]) -> Option<usize> {
+    let len = encoded_len(pcm.len());
+    if out.len() < len {
+        return None;
+    }
+    out[0..2].copy_from_slice(&state.predictor.to_le_bytes());
+    out[2] = state.index;
+    out[3] = 0;
+
+    for (i, pair) in pcm.chunks(2).enumerate() {
+        let lo = state.encode_sample(pair[0]);
+        let hi = if pair.len() > 1 { state.encode_sample(pair[1]) } else { 0 };
+        out[BLOCK_HEADER_LEN + i] = lo | (hi << 4);
+    }
+    Some(len)
+}
+
+/// 解码一块数据 (带块头)
+///
+/// # 返回
+/// 解码的样本数
+pub fn decode_block(data: &[u8], pcm: &mut [i16]) -> Option<usize> {
+    if data.len() < BLOCK_HEADER_LEN {
+        return None;
+    }
+    let mut state = AdpcmState {
+        predictor: i16::from_le_bytes([data[0], data[1]]),
+        index: data[2].min(88),
+    };
+    let samples = ((data.len() - BLOCK_HEADER_LEN) * 2).min(pcm.len());
+    for i in 0..samples {
+        let byte = data[BLOCK_HEADER_LEN + i / 2];
+        let code = if i % 2 == 0 { byte & 0x0F } else { byte >> 4 };
+        pcm[i] = state.decode_sample(code);
+    }
+    Some(samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adpcm_roundtrip_tracks_signal() {
+        let mut pcm = [0i16; 64];
+        for (i, s) in pcm.iter_mut().enumerate() {
+            // 三角波
+            let phase = (i % 32) as i32;
+            *s = ((if phase < 16 { phase } else { 32 - phase }) * 1000 - 8000) as i16;
+        }
+
+        let mut state = AdpcmState::new();
+        let mut encoded = [0u8; encoded_len(64)];
+        assert_eq!(encode_block(&mut state, &pcm, &mut encoded), Some(36));
+
+        let mut decoded = [0i16; 64];
+        assert_eq!(decode_block(&encoded, &mut decoded), Some(64));
+        // 收敛后误差应较小
+        for i in 32..64 {
+            assert!((decoded[i] as i32 - pcm[i] as i32).abs() < 1500);
+        }
+    }
+}
@@ -0,0 +1,234 @@
+//! 接收端抖动缓冲
+//!
+//! 按序号重排数据包，预填充到目标深度后开始播放；
+//! 统计丢包、迟到、重复、乱序和到达间隔抖动 (RFC 3550)。
+
+/// 抖动缓冲统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JitterStats {
+    /// 接收的数据包
+    pub received: u32,
+    /// 播放时缺失的数据包
+    pub lost: u32,
+    /// 到达时已错过播放的数据包
+    pub late: u32,
+    /// 重复数据包
+    pub duplicate: u32,
+    /// 乱序到达的数据包
+    pub reordered: u32,
+    /// 缓冲为空导致的播放欠载
+    pub underruns: u32,
+    /// 到达间隔抖动估计 (μs)
+    pub jitter_us: u32,
+    /// 当前缓冲深度 (帧)
+    pub depth: u32,
+}
+
+#[derive(Clone, Copy)]
+struct Slot<const FRAME: usize> {
+    seq: Option<u16>,
+    len: u16,
+    data: [u8; FRAME],
+}
+
+/// 抖动缓冲
+///
+/// `SLOTS` 个槽位，每帧最大 `FRAME` 字节
+pub struct JitterBuffer<const SLOTS: usize, const FRAME: usize> {
+    slots: [Slot<FRAME>; SLOTS],
+    /// 下一个播放序号
+    next_seq: Option<u16>,
+    /// 已接收的最大序号
+    highest: Option<u16>,
+    /// 开始播放所需的预填充帧数
+    prefill: usize,
+    playing: bool,
+    /// 上一个包的 (媒体时间, 到达时间)
+    last_transit: Option<i64>,
+    /// 抖动估计 (μs × 16)
+    jitter_x16: u32,
+    stats: JitterStats,
+}
+
+/// 序号 a 是否在 b 之前 (考虑回绕)
+#[inline]
+fn seq_before(a: u16, b: u16) -> bool {
+    (a.wrapping_sub(b) as i16) < 0
+}
+
+impl<const SLOTS: usize, const FRAME: usize> JitterBuffer<SLOTS, FRAME> {
+    /// 创建抖动缓冲 (`prefill` 为开始播放前的缓冲帧数)
+    pub const fn new(prefill: usize) -> Self {
+        Self {
+            slots: [Slot { seq: None, len: 0, data: [0; FRAME] }; SLOTS],
+            next_seq: None,
+            highest: None,
+            prefill,
+            playing: false,
+            last_transit: None,
+            jitter_x16: 0,
+            stats: JitterStats {
+                received: 0,
+                lost: 0,
+                late: 0,
+                duplicate: 0,
+                reordered: 0,
+                underruns: 0,
+                jitter_us: 0,
+                depth: 0,
+            },
+        }
+    }
+
+    /// 放入数据包
+    ///
+    /// `media_us` 为发送端媒体时间，`arrival_us` 为本地到达时间
+    pub fn push(&mut self, seq: u16, media_us: u32, arrival_us: u32, payload: &[u8]) {
+        self.stats.received += 1;
+        self.update_jitter(media_us, arrival_us);
+
+        let next = *self.next_seq.get_or_insert(seq);
+        if seq_before(seq, next) {
+            self.stats.late += 1;
+            return;
+        }
+        // 超出窗口: 跳过过旧的帧
+        if seq.wrapping_sub(next) as usize >= SLOTS {
+            let new_next = seq.wrapping_sub(SLOTS as u16 - 1);
+            self.stats.lost += new_next.wrapping_sub(next) as u32;
+            self.drop_before(new_next);
+            self.next_seq = Some(new_next);
+        }
+
+        match self.highest {
+            Some(h) if seq_before(seq, h) => self.stats.reordered += 1,
+            Some(h) if h == seq => {}
+            _ => self.highest = Some(seq),
+        }
+
+        let slot = &mut self.slots[seq as usize % SLOTS];
+        if slot.seq == Some(seq) {
+            self.stats.duplicate += 1;
+            return;
+        }
+        let len = payload.len().min(FRAME);
+        slot.seq = Some(seq);
+        slot.len = len as u16;
+        slot.data[..len].copy_from_slice(&payload[..len]);
+        self.stats.depth = self.depth() as u32;
+    }
+
+    /// 取出下一帧播放
+    ///
+    /// # 返回
+    /// - `Some(Some(frame))`: 正常帧
+    /// - `Some(None)`: 该帧丢失 (调用者应做丢包隐藏)
+    /// - `None`: 尚在预填充或缓冲为空
+    pub fn pop(&mut self) -> Option<Option<&[u8]>> {
+        let next = self.next_seq?;
+        let depth = self.depth();
+        if !self.playing {
+            if depth < self.prefill.max(1) {
+                return None;
+            }
+            self.playing = true;
+        }
+        if depth == 0 {
+            // 欠载: 重新预填充
+            self.stats.underruns += 1;
+            self.playing = false;
+            return None;
+        }
+
+        self.next_seq = Some(next.wrapping_add(1));
+        self.stats.depth = depth as u32 - 1;
+        let index = next as usize % SLOTS;
+        if self.slots[index].seq == Some(next) {
+            self.slots[index].seq = None;
+            let slot = &self.slots[index];
+            Some(Some(&slot.data[..slot.len as usize]))
+        } else {
+            self.stats.lost += 1;
+            self.stats.depth = depth as u32;
+            Some(None)
+        }
+    }
+
+    /// 当前缓冲深度 (下一播放帧到最大已接收帧之间的已到达帧数)
+    pub fn depth(&self) -> usize {
+        let Some(next) = self.next_seq else { return 0 };
+        self.slots
+            .iter()
+            .filter(|s| s.seq.is_some_and(|seq| !seq_before(seq, next)))
+            .count()
+    }
+
+    /// 统计
+    pub fn stats(&self) -> JitterStats {
+        self.stats
+    }
+
+    /// 重置
+    pub fn reset(&mut self) {
+        *self = Self::new(self.prefill);
+    }
+
+    fn drop_before(&mut self, seq: u16) {
+        for slot in self.slots.iter_mut() {
+            if slot.seq.is_some_and(|s| seq_before(s, seq)) {
+                slot.seq = None;
+            }
+        }
+    }
+
+    fn update_jitter(&mut self, media_us: u32, arrival_us: u32) {
+        let transit = arrival_us as i64 - media_us as i64;
+        if let Some(last) = self.last_transit {
+            let d = (transit - last).unsigned_abs().min(u32::MAX as u64) as u32;
+            // J += (|D| - J) / 16
+            self.jitter_x16 = self.jitter_x16 + d - (self.jitter_x16 >> 4);
+            self.stats.jitter_us = self.jitter_x16 >> 4;
+        }
+        self.last_transit = Some(transit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reorder_and_loss() {
+        let mut jb: JitterBuffer<8, 4> = JitterBuffer::new(2);
+        jb.push(10, 0, 0, &[10]);
+        assert_eq!(jb.pop(), None); // 预填充
+        jb.push(12, 0, 0, &[12]);
+        jb.push(11, 0, 0, &[11]);
+        assert_eq!(jb.stats().reordered, 1);
+
+        assert_eq!(jb.pop(), Some(Some(&[10u8][..])));
+        assert_eq!(jb.pop(), Some(Some(&[11u8][..])));
+        jb.push(14, 0, 0, &[14]);
+        assert_eq!(jb.pop(), Some(Some(&[12u8][..])));
+        assert_eq!(jb.pop(), Some(None)); // 13 丢失
+        assert_eq!(jb.pop(), Some(Some(&[14u8][..])));
+        assert_eq!(jb.stats().lost, 1);
+
+        jb.push(11, 0, 0, &[11]);
+        assert_eq!(jb.stats().late, 1);
+    }
+
+    #[test]
+    fn test_jitter_estimate() {
+        let mut jb: JitterBuffer<4, 4> = JitterBuffer::new(1);
+        // 20ms 帧，到达间隔交替 10ms / 30ms
+        let mut arrival = 0;
+        for i in 0..64u32 {
+            arrival += if i % 2 == 0 { 10_000 } else { 30_000 };
+            jb.push(i as u16, i * 20_000, arrival, &[0]);
+            jb.pop();
+        }
+        let j = jb.stats().jitter_us;
+        assert!((9_000..=11_000).contains(&j), "jitter {}", j);
+    }
+}
@@ -0,0 +1,467 @@
+//! 音频管线: 麦克风 → 双缓冲 → 编码 → 网络
+//!
+//! 端到端演示各模块的零拷贝衔接:
+//! - I2S DMA 采集写入 `DmaDoubleBuffer`
+//! - 编码任务 (建议运行在 Core1) 取走已完成的一半，按帧编码 (PCM / IMA ADPCM)
+//! - 编码后的数据包经 `PacketSink` 发送 (UDP，或自定义 WebSocket 等传输)
+//! - 接收端使用 `JitterBuffer` 重排并统计抖动
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::audio::{self, AudioConfig, Codec, AudioPipeline};
+//!
+//! static PIPELINE: AudioPipeline<640, 4> = AudioPipeline::new(AudioConfig::new(16_000, 320, Codec::ImaAdpcm));
+//!
+//! // Core0: 采集
+//! #[embassy_executor::task]
+//! async fn capture_task(mut rx: I2sReadDmaTransferAsync<'static, &'static mut [u8]>) {
+//!     PIPELINE.capture(&mut rx).await;
+//! }
+//!
+//! // Core1: 编码
+//! #[embassy_executor::task]
+//! async fn encode_task() {
+//!     PIPELINE.encode().await;
+//! }
+//!
+//! // 网络发送
+//! #[embassy_executor::task]
+//! async fn stream_task(mut sink: UdpSink<'static>) {
+//!     PIPELINE.stream(&mut sink).await;
+//! }
+//! ```
+
+pub mod adpcm;
+pub mod jitter;
+
+use core::fmt;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use crate::mem::dma::{DmaDoubleBuffer, DmaStrategy};
+use crate::sync::primitives::{CriticalChannel, CriticalMutex, CriticalSignal};
+use crate::util::log::*;
+
+pub use adpcm::AdpcmState;
+pub use jitter::{JitterBuffer, JitterStats};
+
+/// 数据包头长度
+pub const PACKET_HEADER_LEN: usize = 8;
+
+/// 数据包魔数
+pub const PACKET_MAGIC: u8 = 0xA5;
+
+/// 单个数据包最大长度 (适合单个以太网/WiFi 帧)
+pub const MAX_PACKET_LEN: usize = 1024;
+
+// ===== 错误类型 =====
+
+/// 音频错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioError {
+    /// 采集失败
+    Capture,
+    /// 编码输出空间不足
+    BufferTooSmall,
+    /// 数据包格式无效
+    InvalidPacket,
+    /// 发送失败
+    Send,
+}
+
+impl fmt::Display for AudioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Capture => write!(f, "Audio capture failed"),
+            Self::BufferTooSmall => write!(f, "Buffer too small"),
+            Self::InvalidPacket => write!(f, "Invalid audio packet"),
+            Self::Send => write!(f, "Audio send failed"),
+        }
+    }
+}
+
+// ===== 配置 =====
+
+/// 编码格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    /// 16 位小端 PCM
+    Pcm16,
+    /// IMA ADPCM (4:1)
+    ImaAdpcm,
+}
+
+impl Codec {
+    /// 线上编码
+    pub const fn as_u8(self) -> u8 {
+        match self {
+            Codec::Pcm16 => 0,
+            Codec::ImaAdpcm => 1,
+        }
+    }
+
+    /// 从线上编码解析
+    pub const fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Codec::Pcm16),
+            1 => Some(Codec::ImaAdpcm),
+            _ => None,
+        }
+    }
+
+    /// 编码 `samples` 个样本后的负载长度
+    pub const fn payload_len(self, samples: usize) -> usize {
+        match self {
+            Codec::Pcm16 => samples * 2,
+            Codec::ImaAdpcm => adpcm::encoded_len(samples),
+        }
+    }
+}
+
+/// 音频配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioConfig {
+    /// 采样率 (Hz)
+    pub sample_rate: u32,
+    /// 每帧样本数 (单声道)
+    pub frame_samples: usize,
+    /// 编码格式
+    pub codec: Codec,
+}
+
+impl AudioConfig {
+    /// 创建配置
+    pub const fn new(sample_rate: u32, frame_samples: usize, codec: Codec) -> Self {
+        Self { sample_rate, frame_samples, codec }
+    }
+
+    /// 帧时长 (μs)
+    pub const fn frame_us(&self) -> u32 {
+        (self.frame_samples as u64 * 1_000_000 / self.sample_rate as u64) as u32
+    }
+
+    /// 设置编码格式
+    pub const fn with_codec(mut self, codec: Codec) -> Self {
+        self.codec = codec;
+        self
+    }
+}
+
+// ===== 数据包 =====
+
+/// 数据包头
+///
+/// | 偏移 | 长度 | 字段 |
+/// |------|------|------|
+/// | 0    | 1    | 魔数 0xA5 |
+/// | 1    | 1    | 编码格式 |
+/// | 2    | 2    | 序号 (小端) |
+/// | 4    | 4    | 首样本索引 (小端) |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PacketHeader {
+    /// 编码格式
+    pub codec: Codec,
+    /// 序号
+    pub seq: u16,
+    /// 首样本索引
+    pub timestamp: u32,
+}
+
+impl PacketHeader {
+    /// 编码
+    pub fn encode(&self) -> [u8; PACKET_HEADER_LEN] {
+        let mut out = [0u8; PACKET_HEADER_LEN];
+        out[0] = PACKET_MAGIC;
+        out[1] = self.codec.as_u8();
+        out[2..4].copy_from_slice(&self.seq.to_le_bytes());
+        out[4..8].copy_from_slice(&self.timestamp.to_le_bytes());
+        out
+    }
+
+    /// 解析，返回包头和负载
+    pub fn decode(packet: &[u8]) -> Result<(Self, &[u8]), AudioError> {
+        if packet.len() < PACKET_HEADER_LEN || packet[0] != PACKET_MAGIC {
+            return Err(AudioError::InvalidPacket);
+        }
+        let codec = Codec::from_u8(packet[1]).ok_or(AudioError::InvalidPacket)?;
+        let header = Self {
+            codec,
+            seq: u16::from_le_bytes([packet[2], packet[3]]),
+            timestamp: u32::from_le_bytes([packet[4], packet[5], packet[6], packet[7]]),
+        };
+        Ok((header, &packet[PACKET_HEADER_LEN..]))
+    }
+}
+
+/// 编码后的数据包
+#[derive(Clone, Copy)]
+pub struct AudioPacket {
+    len: u16,
+    data: [u8; MAX_PACKET_LEN],
+}
+
+impl AudioPacket {
+    /// 数据包字节
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data[..self.len as usize]
+    }
+}
+
+/// 将一帧 PCM (小端字节) 编码为数据包
+pub fn encode_frame(
+    header: PacketHeader,
+    pcm_le: &[u8],
+    adpcm: &mut AdpcmState,
+    out: &mut [u8],
+) -> Result<usize, AudioError> {
+    let samples = pcm_le.len() / 2;
+    let total = PACKET_HEADER_LEN + header.codec.payload_len(samples);
+    if out.len() < total {
+        return Err(AudioError::BufferTooSmall);
+    }
+    out[..PACKET_HEADER_LEN].copy_from_slice(&header.encode());
+    let payload = &mut out[PACKET_HEADER_LEN..total];
+
+    match header.codec {
+        Codec::Pcm16 => payload.copy_from_slice(&pcm_le[..samples * 2]),
+        Codec::ImaAdpcm => {
+            // 分段转换，避免在栈上放整帧 i16；状态跨段延续，各段数据可直接拼接
+            let mut pcm = [0i16; 64];
+            let mut block = [0u8; adpcm::BLOCK_HEADER_LEN + 32];
+            let mut written = 0;
+            for chunk in pcm_le.chunks(128) {
+                let n = chunk.len() / 2;
+                for (i, s) in pcm[..n].iter_mut().enumerate() {
+                    *s = i16::from_le_bytes([chunk[2 * i], chunk[2 * i + 1]]);
+                }
+                let len = adpcm::encode_block(adpcm, &pcm[..n], &mut block)
+                    .ok_or(AudioError::BufferTooSmall)?;
+                // 仅保留首段块头
+                let start = if written == 0 { 0 } else { adpcm::BLOCK_HEADER_LEN };
+                let body = &block[start..len];
+                payload[written..written + body.len()].copy_from_slice(body);
+                written += body.len();
+            }
+        }
+    }
+    Ok(total)
+}
+
+// ===== 采集源与发送端 =====
+
+/// PCM 采集源
+#[allow(async_fn_in_trait)]
+pub trait PcmSource {
+    /// 读取 PCM 数据填满 `buf`
+    async fn read(&mut self, buf: &mut [u8]) -> Result<(), AudioError>;
+}
+
+impl<'d, BUFFER> PcmSource for esp_hal::i2s::master::asynch::I2sReadDmaTransferAsync<'d, BUFFER>
+where
+    BUFFER: esp_hal::dma::WriteBuffer,
+{
+    async fn read(&mut self, buf: &mut [u8]) -> Result<(), AudioError> {
+        let mut filled = 0;
+        while filled < buf.len() {
+            filled += self.pop(&mut buf[filled..]).await.map_err(|_| AudioError::Capture)?;
+        }
+        Ok(())
+    }
+}
+
+/// 数据包发送端 (UDP、WebSocket 等)
+#[allow(async_fn_in_trait)]
+pub trait PacketSink {
+    /// 发送一个数据包
+    async fn send(&mut self, packet: &[u8]) -> Result<(), AudioError>;
+}
+
+/// UDP 发送端
+#[cfg(feature = "network")]
+pub struct UdpSink<'a> {
+    socket: embassy_net::udp::UdpSocket<'a>,
+    target: embassy_net::IpEndpoint,
+}
+
+#[cfg(feature = "network")]
+impl<'a> UdpSink<'a> {
+    /// 创建 (socket 须已绑定)
+    pub fn new(socket: embassy_net::udp::UdpSocket<'a>, target: embassy_net::IpEndpoint) -> Self {
+        Self { socket, target }
+    }
+}
+
+#[cfg(feature = "network")]
+impl<'a> PacketSink for UdpSink<'a> {
+    async fn send(&mut self, packet: &[u8]) -> Result<(), AudioError> {
+        self.socket.send_to(packet, self.target).await.map_err(|_| AudioError::Send)
+    }
+}
+
+// ===== 管线 =====
+
+/// 管线统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AudioStats {
+    /// 已采集帧
+    pub captured: u32,
+    /// 采集溢出 (编码来不及)
+    pub overruns: u32,
+    /// 已编码帧
+    pub encoded: u32,
+    /// 发送队列满丢弃的数据包
+    pub dropped: u32,
+    /// 已发送数据包
+    pub sent: u32,
+    /// 发送失败
+    pub send_errors: u32,
+}
+
+/// 音频管线
+///
+/// `FRAME_BYTES` 为每帧 PCM 字节数 (= frame_samples × 2)，`QUEUE` 为发送队列深度
+pub struct AudioPipeline<const FRAME_BYTES: usize, const QUEUE: usize> {
+    config: AudioConfig,
+    buffer: CriticalMutex<DmaDoubleBuffer<FRAME_BYTES>>,
+    frame_ready: CriticalSignal<()>,
+    packets: CriticalChannel<AudioPacket, QUEUE>,
+    captured: AtomicU32,
+    overruns: AtomicU32,
+    encoded: AtomicU32,
+    dropped: AtomicU32,
+    sent: AtomicU32,
+    send_errors: AtomicU32,
+}
+
+impl<const FRAME_BYTES: usize, const QUEUE: usize> AudioPipeline<FRAME_BYTES, QUEUE> {
+    /// 创建管线
+    pub const fn new(config: AudioConfig) -> Self {
+        assert!(config.frame_samples * 2 == FRAME_BYTES);
+        Self {
+            config,
+            buffer: CriticalMutex::new(DmaDoubleBuffer::new(DmaStrategy::ForceDram)),
+            frame_ready: CriticalSignal::new(),
+            packets: CriticalChannel::new(),
+            captured: AtomicU32::new(0),
+            overruns: AtomicU32::new(0),
+            encoded: AtomicU32::new(0),
+            dropped: AtomicU32::new(0),
+            sent: AtomicU32::new(0),
+            send_errors: AtomicU32::new(0),
+        }
+    }
+
+    /// 配置
+    pub fn config(&self) -> &AudioConfig {
+        &self.config
+    }
+
+    /// 采集循环: 将 PCM 写入双缓冲并通知编码任务
+    ///
+    /// I2S 循环 DMA 自带环形缓冲，先读入本地帧再短暂加锁拷贝，
+    /// 避免在等待采集期间阻塞编码任务
+    pub async fn capture<S: PcmSource>(&self, source: &mut S) -> ! {
+        let mut frame = [0u8; FRAME_BYTES];
+        loop {
+            if source.read(&mut frame).await.is_err() {
+                log_warn!("Audio capture error");
+                continue;
+            }
+            let committed = {
+                let mut buffer = self.buffer.lock().await;
+                // 采集半区只由本任务写入，不会处于 DMA 中
+                let _ = buffer.write_half().try_copy_from_slice(&frame);
+                buffer.commit()
+            };
+            if !committed {
+                self.overruns.fetch_add(1, Ordering::Relaxed);
+            }
+            self.captured.fetch_add(1, Ordering::Relaxed);
+            self.frame_ready.signal(());
+        }
+    }
+
+    /// 编码循环: 取走完成的帧，编码后放入发送队列
+    pub async fn encode(&self) -> ! {
+        let mut seq: u16 = 0;
+        let mut timestamp: u32 = 0;
+        let mut adpcm = AdpcmState::new();
+        let mut packet = AudioPacket { len: 0, data: [0; MAX_PACKET_LEN] };
+
+        loop {
+            self.frame_ready.wait().await;
+
+            let header = PacketHeader { codec: self.config.codec, seq, timestamp };
+            let result = {
+                let mut buffer = self.buffer.lock().await;
+                match buffer.take_ready() {
+                    Some(pcm) => Some(encode_frame(header, pcm, &mut adpcm, &mut packet.data)),
+                    None => None,
+                }
+            };
+
+            match result {
+                Some(Ok(len)) => {
+                    packet.len = len as u16;
+                    seq = seq.wrapping_add(1);
+                    timestamp = timestamp.wrapping_add(self.config.frame_samples as u32);
+                    self.encoded.fetch_add(1, Ordering::Relaxed);
+                    if self.packets.try_send(packet).is_err() {
+                        self.dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+                Some(Err(_)) => log_warn!("Audio frame too large for packet"),
+                None => {}
+            }
+        }
+    }
+
+    /// 发送循环
+    pub async fn stream<K: PacketSink>(&self, sink: &mut K) -> ! {
+        loop {
+            let packet = self.packets.receive().await;
+            match sink.send(packet.as_bytes()).await {
+                Ok(()) => self.sent.fetch_add(1, Ordering::Relaxed),
+                Err(_) => self.send_errors.fetch_add(1, Ordering::Relaxed),
+            };
+        }
+    }
+
+    /// 统计快照
+    pub fn stats(&self) -> AudioStats {
+        AudioStats {
+            captured: self.captured.load(Ordering::Relaxed),
+            overruns: self.overruns.load(Ordering::Relaxed),
+            encoded: self.encoded.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            sent: self.sent.load(Ordering::Relaxed),
+            send_errors: self.send_errors.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packet_header_roundtrip() {
+        let header = PacketHeader { codec: Codec::ImaAdpcm, seq: 0xBEEF, timestamp: 48_000 };
+        let mut packet = [0u8; PACKET_HEADER_LEN + 2];
+        packet[..PACKET_HEADER_LEN].copy_from_slice(&header.encode());
+        let (decoded, payload) = PacketHeader::decode(&packet).unwrap();
+        assert_eq!(decoded, header);
+        assert_eq!(payload.len(), 2);
+        assert_eq!(PacketHeader::decode(&[0u8; 4]).unwrap_err(), AudioError::InvalidPacket);
+    }
+
+    #[test]
+    fn test_encode_frame_adpcm_len() {
+        let pcm = [0u8; 640];
+        let mut out = [0u8; MAX_PACKET_LEN];
+        let mut state = AdpcmState::new();
+        let header = PacketHeader { codec: Codec::ImaAdpcm, seq: 0, timestamp: 0 };
+        let len = encode_frame(header, &pcm, &mut state, &mut out).unwrap();
+        assert_eq!(len, PACKET_HEADER_LEN + adpcm::encoded_len(320));
+    }
+}
@@ -0,0 +1,469 @@
+//! 受信任密钥与信任环
+//!
+//! 管理厂商公钥集合，支持多密钥并存、降级与撤销。
+//!
+//! 轮换加入的密钥与降级/撤销状态可持久化到键值存储 (`KeyRing::save` / `KeyRing::load`)，
+//! 重启后不会重新信任已撤销的密钥。
+
+use heapless::Vec;
+
+use crate::fs::kv::{KvError, KvStore};
+use crate::fs::littlefs::FsError;
+
+use super::{constant_time_eq, sha256, verify_signature, CryptoError};
+
+/// 公钥最大长度 (SEC1 非压缩 P-256)
+pub const MAX_PUBLIC_KEY_LEN: usize = 65;
+
+/// 签名长度 (Ed25519 / P-256 r||s)
+pub const SIGNATURE_LEN: usize = 64;
+
+/// 信任环状态的键值存储命名空间
+pub const KEYRING_NAMESPACE: &str = "crypto";
+
+/// 信任环状态的键名
+pub const KEYRING_KEY: &str = "keyring";
+
+/// 信任环状态魔数
+pub const KEYRING_STATE_MAGIC: [u8; 4] = *b"RTKS";
+
+/// 可持久化的最大密钥数量
+pub const MAX_PERSISTED_KEYS: usize = 16;
+
+/// 信任环状态最大长度 (头部 + 密钥记录 + SHA-256)
+pub const KEYRING_STATE_MAX_LEN: usize = 5 + MAX_PERSISTED_KEYS * (4 + MAX_PUBLIC_KEY_LEN) + 32;
+
+/// 签名算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum SignatureAlgorithm {
+    /// Ed25519 (推荐)
+    Ed25519 = 0x01,
+    /// ECDSA over NIST P-256 + SHA-256
+    EcdsaP256 = 0x02,
+}
+
+impl SignatureAlgorithm {
+    /// 从线上编码解析
+    pub fn from_u8(value: u8) -> Result<Self, CryptoError> {
+        match value {
+            0x01 => Ok(Self::Ed25519),
+            0x02 => Ok(Self::EcdsaP256),
+            _ => Err(CryptoError::UnsupportedAlgorithm),
+        }
+    }
+
+    /// 转换为线上编码
+    pub const fn as_u8(&self) -> u8 {
+        *self as u8
+    }
+}
+
+/// 密钥状态 (按 `Active` < `Deprecated` < `Revoked` 递进，只能收紧)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum KeyState {
+    /// 活跃: 可验证负载，可签发轮换记录
+    Active,
+    /// 已降级: 仍可验证负载，不可签发轮换记录
+    Deprecated,
+    /// 已撤销: 拒绝所有验证
+    Revoked,
+}
+
+impl KeyState {
+    const fn as_u8(self) -> u8 {
+        match self {
+            KeyState::Active => 0,
+            KeyState::Deprecated => 1,
+            KeyState::Revoked => 2,
+        }
+    }
+
+    const fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            0 => Some(KeyState::Active),
+            1 => Some(KeyState::Deprecated),
+            2 => Some(KeyState::Revoked),
+            _ => None,
+        }
+    }
+}
+
+/// 受信任公钥
+#[derive(Debug, Clone)]
+pub struct TrustedKey {
+    /// 密钥 ID (清单中引用)
+    pub id: u8,
+    /// 签名算法
+    pub algorithm: SignatureAlgorithm,
+    /// 公钥原始字节
+    pub public_key: Vec<u8, MAX_PUBLIC_KEY_LEN>,
+    /// 当前状态
+    pub state: KeyState,
+}
+
+impl TrustedKey {
+    /// 创建受信任公钥
+    pub fn new(id: u8, algorithm: SignatureAlgorithm, public_key: &[u8]) -> Result<Self, CryptoError> {
+        let expected_ok = match algorithm {
+            SignatureAlgorithm::Ed25519 => public_key.len() == 32,
+            SignatureAlgorithm::EcdsaP256 => public_key.len() == 33 || public_key.len() == 65,
+        };
+        if !expected_ok {
+            return Err(CryptoError::InvalidKey);
+        }
+
+        let mut key = Vec::new();
+        key.extend_from_slice(public_key).map_err(|_| CryptoError::InvalidKey)?;
+
+        Ok(Self {
+            id,
+            algorithm,
+            public_key: key,
+            state: KeyState::Active,
+        })
+    }
+
+    /// 创建 Ed25519 公钥
+    pub fn ed25519(id: u8, public_key: &[u8; 32]) -> Result<Self, CryptoError> {
+        Self::new(id, SignatureAlgorithm::Ed25519, public_key)
+    }
+
+    /// 创建 P-256 公钥 (SEC1 编码)
+    pub fn p256(id: u8, sec1_public_key: &[u8]) -> Result<Self, CryptoError> {
+        Self::new(id, SignatureAlgorithm::EcdsaP256, sec1_public_key)
+    }
+
+    /// 使用此密钥验证签名
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), CryptoError> {
+        if self.state == KeyState::Revoked {
+            return Err(CryptoError::KeyRevoked);
+        }
+        verify_signature(self.algorithm, &self.public_key, message, signature)
+    }
+}
+
+/// 信任环
+///
+/// 固定容量的受信任公钥集合。
+///
+/// # 类型参数
+/// - `N`: 最大密钥数量
+pub struct KeyRing<const N: usize> {
+    keys: Vec<TrustedKey, N>,
+}
+
+impl<const N: usize> KeyRing<N> {
+    /// 创建空信任环
+    pub const fn new() -> Self {
+        Self { keys: Vec::new() }
+    }
+
+    /// 添加密钥
+    pub fn add(&mut self, key: TrustedKey) -> Result<(), CryptoError> {
+        if self.keys.iter().any(|k| k.id == key.id) {
+            return Err(CryptoError::DuplicateKey);
+        }
+        self.keys.push(key).map_err(|_| CryptoError::KeyRingFull)
+    }
+
+    /// 按 ID 查找密钥
+    pub fn get(&self, id: u8) -> Option<&TrustedKey> {
+        self.keys.iter().find(|k| k.id == id)
+    }
+
+    /// 撤销密钥
+    ///
+    /// 撤销后该密钥签发的所有负载都将被拒绝；需调用 `save` 才能在重启后保持
+    pub fn revoke(&mut self, id: u8) -> Result<(), CryptoError> {
+        let key = self.keys.iter_mut().find(|k| k.id == id).ok_or(CryptoError::UnknownKey)?;
+        key.state = KeyState::Revoked;
+        Ok(())
+    }
+
+    /// 使用指定 ID 的密钥验证签名
+    pub fn verify(&self, key_id: u8, message: &[u8], signature: &[u8]) -> Result<(), CryptoError> {
+        let key = self.get(key_id).ok_or(CryptoError::UnknownKey)?;
+        key.verify(message, signature)
+    }
+
+    /// 应用密钥轮换记录
+    ///
+    /// 记录必须由当前 `Active` 密钥签发。成功后新密钥以 `Active` 加入，
+    /// 签发者降级为 `Deprecated`；需调用 `save` 才能在重启后保持。
+    pub fn rotate(&mut self, rotation: &KeyRotation<'_>) -> Result<(), CryptoError> {
+        let signer = self.get(rotation.signer_id).ok_or(CryptoError::UnknownKey)?;
+        match signer.state {
+            KeyState::Active => {}
+            KeyState::Revoked => return Err(CryptoError::KeyRevoked),
+            KeyState::Deprecated => return Err(CryptoError::VerificationFailed),
+        }
+        signer.verify(rotation.signed_bytes, rotation.signature)?;
+
+        let new_key = TrustedKey::new(rotation.new_id, rotation.algorithm, rotation.public_key)?;
+        self.add(new_key)?;
+
+        if let Some(signer) = self.keys.iter_mut().find(|k| k.id == rotation.signer_id) {
+            signer.state = KeyState::Deprecated;
+        }
+        Ok(())
+    }
+
+    /// 所有密钥
+    pub fn keys(&self) -> &[TrustedKey] {
+        &self.keys
+    }
+
+    /// 活跃密钥数量
+    pub fn active_count(&self) -> usize {
+        self.keys.iter().filter(|k| k.state == KeyState::Active).count()
+    }
+
+    /// 序列化全部密钥及其状态，返回长度
+    ///
+    /// 格式: 魔数 `RTKS`、密钥数，每个密钥 `ID、算法、状态、公钥长度 L、公钥`，
+    /// 末尾为前述全部字节的 SHA-256 (只检测损坏，不防篡改)。
+    pub fn encode_state(&self, out: &mut [u8]) -> Result<usize, CryptoError> {
+        if self.keys.len() > MAX_PERSISTED_KEYS {
+            return Err(CryptoError::KeyRingFull);
+        }
+        let mut len = 0;
+        let mut push = |data: &[u8]| {
+            let dst = out.get_mut(len..len + data.len()).ok_or(CryptoError::Malformed)?;
+            dst.copy_from_slice(data);
+            len += data.len();
+            Ok::<(), CryptoError>(())
+        };
+        push(&KEYRING_STATE_MAGIC)?;
+        push(&[self.keys.len() as u8])?;
+        for key in &self.keys {
+            push(&[key.id, key.algorithm.as_u8(), key.state.as_u8(), key.public_key.len() as u8])?;
+            push(&key.public_key)?;
+        }
+        let digest = sha256(&out[..len]);
+        let dst = out.get_mut(len..len + digest.len()).ok_or(CryptoError::Malformed)?;
+        dst.copy_from_slice(&digest);
+        Ok(len + digest.len())
+    }
+
+    /// 合并 `encode_state` 保存的状态
+    ///
+    /// 已有密钥 (如固件内置的出厂密钥) 的状态取两者中更严格者，撤销不会被恢复；
+    /// 仅存在于状态中的密钥 (轮换加入) 按保存的状态加入。同 ID 公钥不一致、
+    /// 摘要或格式错误时不修改信任环。
+    pub fn restore_state(&mut self, data: &[u8]) -> Result<(), CryptoError> {
+        let body_len = data.len().checked_sub(32).ok_or(CryptoError::Malformed)?;
+        let (body, digest) = data.split_at(body_len);
+        if !constant_time_eq(&sha256(body), digest) {
+            return Err(CryptoError::DigestMismatch);
+        }
+        if body.len() < 5 || body[0..4] != KEYRING_STATE_MAGIC {
+            return Err(CryptoError::Malformed);
+        }
+
+        // 先完整解析并检查冲突，再一次性应用
+        let mut stored: Vec<TrustedKey, MAX_PERSISTED_KEYS> = Vec::new();
+        let mut rest = &body[5..];
+        for _ in 0..body[4] {
+            let [id, algorithm, state, key_len, ..] = *rest else {
+                return Err(CryptoError::Malformed);
+            };
+            let public_key = rest.get(4..4 + key_len as usize).ok_or(CryptoError::Malformed)?;
+            let mut key = TrustedKey::new(id, SignatureAlgorithm::from_u8(algorithm)?, public_key)?;
+            key.state = KeyState::from_u8(state).ok_or(CryptoError::Malformed)?;
+            if let Some(existing) = self.get(id) {
+                if existing.algorithm != key.algorithm || existing.public_key != key.public_key {
+                    return Err(CryptoError::InvalidKey);
+                }
+            }
+            stored.push(key).map_err(|_| CryptoError::KeyRingFull)?;
+            rest = &rest[4 + key_len as usize..];
+        }
+        if !rest.is_empty() {
+            return Err(CryptoError::Malformed);
+        }
+        let added = stored.iter().filter(|k| self.get(k.id).is_none()).count();
+        if self.keys.len() + added > N {
+            return Err(CryptoError::KeyRingFull);
+        }
+
+        for key in stored {
+            match self.keys.iter_mut().find(|k| k.id == key.id) {
+                Some(existing) => existing.state = existing.state.max(key.state),
+                None => self.keys.push(key).map_err(|_| CryptoError::KeyRingFull)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// 保存状态到键值存储 (`rotate` / `revoke` 后调用)
+    pub fn save(&self, kv: &KvStore<'_>) -> Result<(), CryptoError> {
+        let mut buf = [0u8; KEYRING_STATE_MAX_LEN];
+        let len = self.encode_state(&mut buf)?;
+        kv.set(KEYRING_NAMESPACE, KEYRING_KEY, &buf[..len])?;
+        Ok(())
+    }
+
+    /// 从键值存储合并状态 (启动时在任何清单或配置验证之前调用)
+    ///
+    /// 尚未保存过时返回 `Ok(false)`；数据损坏返回错误，调用方应拒绝继续验证。
+    pub fn load(&mut self, kv: &KvStore<'_>) -> Result<bool, CryptoError> {
+        let mut buf = [0u8; KEYRING_STATE_MAX_LEN];
+        let len = match kv.get(KEYRING_NAMESPACE, KEYRING_KEY, &mut buf) {
+            Ok(len) => len,
+            Err(KvError::Fs(FsError::NotFound)) => return Ok(false),
+            Err(KvError::BufferTooSmall(_)) => return Err(CryptoError::Malformed),
+            Err(e) => return Err(e.into()),
+        };
+        self.restore_state(&buf[..len])?;
+        Ok(true)
+    }
+}
+
+impl<const N: usize> Default for KeyRing<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 密钥轮换记录魔数
+pub const KEY_ROTATION_MAGIC: [u8; 4] = *b"RTKR";
+
+/// 密钥轮换记录
+///
+/// 线上格式 (小端):
+///
+/// | 偏移 | 长度 | 字段 |
+/// |------|------|------|
+/// | 0    | 4    | 魔数 `RTKR` |
+/// | 4    | 1    | 签发者密钥 ID |
+/// | 5    | 1    | 新密钥 ID |
+/// | 6    | 1    | 新密钥算法 |
+/// | 7    | 1    | 新公钥长度 L |
+/// | 8    | L    | 新公钥 |
+/// | 8+L  | 64   | 签名 (覆盖前 8+L 字节) |
+#[derive(Debug, Clone, Copy)]
+pub struct KeyRotation<'a> {
+    /// 签发者密钥 ID
+    pub signer_id: u8,
+    /// 新密钥 ID
+    pub new_id: u8,
+    /// 新密钥算法
+    pub algorithm: SignatureAlgorithm,
+    /// 新公钥
+    pub public_key: &'a [u8],
+    /// 被签名的字节
+    signed_bytes: &'a [u8],
+    /// 签名
+    signature: &'a [u8],
+}
+
+impl<'a> KeyRotation<'a> {
+    /// 解析轮换记录
+    pub fn parse(data: &'a [u8]) -> Result<Self, CryptoError> {
+        if data.len() < 8 || data[0..4] != KEY_ROTATION_MAGIC {
+            return Err(CryptoError::Malformed);
+        }
+        let key_len = data[7] as usize;
+        if key_len > MAX_PUBLIC_KEY_LEN || data.len() != 8 + key_len + SIGNATURE_LEN {
+            return Err(CryptoError::Malformed);
+        }
+
+        Ok(Self {
+            signer_id: data[4],
+            new_id: data[5],
+            algorithm: SignatureAlgorithm::from_u8(data[6])?,
+            public_key: &data[8..8 + key_len],
+            signed_bytes: &data[..8 + key_len],
+            signature: &data[8 + key_len..],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyring_add_and_revoke() {
+        let mut ring: KeyRing<2> = KeyRing::new();
+        ring.add(TrustedKey::ed25519(1, &[0u8; 32]).unwrap()).unwrap();
+        assert_eq!(ring.add(TrustedKey::ed25519(1, &[1u8; 32]).unwrap()), Err(CryptoError::DuplicateKey));
+        ring.add(TrustedKey::ed25519(2, &[2u8; 32]).unwrap()).unwrap();
+        assert_eq!(ring.add(TrustedKey::ed25519(3, &[3u8; 32]).unwrap()), Err(CryptoError::KeyRingFull));
+
+        ring.revoke(1).unwrap();
+        assert_eq!(ring.active_count(), 1);
+        assert_eq!(ring.verify(1, b"", &[0u8; 64]), Err(CryptoError::KeyRevoked));
+        assert_eq!(ring.verify(9, b"", &[0u8; 64]), Err(CryptoError::UnknownKey));
+    }
+
+    #[test]
+    fn test_rotation_parse() {
+        let mut record = [0u8; 8 + 32 + SIGNATURE_LEN];
+        record[0..4].copy_from_slice(&KEY_ROTATION_MAGIC);
+        record[4] = 1;
+        record[5] = 2;
+        record[6] = SignatureAlgorithm::Ed25519.as_u8();
+        record[7] = 32;
+
+        let rotation = KeyRotation::parse(&record).unwrap();
+        assert_eq!(rotation.signer_id, 1);
+        assert_eq!(rotation.new_id, 2);
+        assert_eq!(rotation.public_key.len(), 32);
+
+        assert_eq!(KeyRotation::parse(&record[..40]).unwrap_err(), CryptoError::Malformed);
+    }
+
+    #[test]
+    fn test_state_survives_reload() {
+        use p256::ecdsa::{signature::Signer, Signature, SigningKey};
+
+        let factory_signer = SigningKey::from_slice(&[0x21; 32]).unwrap();
+        let factory_public = factory_signer.verifying_key().to_encoded_point(false);
+        let factory = || TrustedKey::p256(1, factory_public.as_bytes()).unwrap();
+
+        let mut ring: KeyRing<4> = KeyRing::new();
+        ring.add(factory()).unwrap();
+
+        // 出厂密钥签发轮换记录加入密钥 2，随后撤销出厂密钥
+        let new_key = [0x42u8; 32];
+        let mut record = [0u8; 8 + 32 + SIGNATURE_LEN];
+        record[0..4].copy_from_slice(&KEY_ROTATION_MAGIC);
+        record[4..8].copy_from_slice(&[1, 2, SignatureAlgorithm::Ed25519.as_u8(), 32]);
+        record[8..40].copy_from_slice(&new_key);
+        let signature: Signature = factory_signer.sign(&record[..40]);
+        record[40..].copy_from_slice(&signature.to_bytes());
+        ring.rotate(&KeyRotation::parse(&record).unwrap()).unwrap();
+        ring.revoke(1).unwrap();
+
+        let mut state = [0u8; KEYRING_STATE_MAX_LEN];
+        let len = ring.encode_state(&mut state).unwrap();
+
+        // 重启: 只有出厂密钥的新信任环合并保存的状态
+        let mut reloaded: KeyRing<4> = KeyRing::new();
+        reloaded.add(factory()).unwrap();
+        reloaded.restore_state(&state[..len]).unwrap();
+        assert_eq!(reloaded.get(1).map(|k| k.state), Some(KeyState::Revoked));
+        assert_eq!(reloaded.get(2).map(|k| k.state), Some(KeyState::Active));
+        let signature: Signature = factory_signer.sign(b"manifest");
+        assert_eq!(reloaded.verify(1, b"manifest", &signature.to_bytes()), Err(CryptoError::KeyRevoked));
+
+        // 保存的状态不能恢复已撤销的密钥
+        let mut stale = [0u8; KEYRING_STATE_MAX_LEN];
+        let stale_len = {
+            let mut old: KeyRing<4> = KeyRing::new();
+            old.add(factory()).unwrap();
+            old.encode_state(&mut stale).unwrap()
+        };
+        reloaded.restore_state(&stale[..stale_len]).unwrap();
+        assert_eq!(reloaded.get(1).map(|k| k.state), Some(KeyState::Revoked));
+
+        // 损坏的状态被拒绝且不修改信任环
+        state[6] ^= 1;
+        let mut fresh: KeyRing<4> = KeyRing::new();
+        fresh.add(factory()).unwrap();
+        assert_eq!(fresh.restore_state(&state[..len]), Err(CryptoError::DigestMismatch));
+        assert_eq!(fresh.keys().len(), 1);
+    }
+}
@@ -0,0 +1,241 @@
+//! 签名固件清单与签名数据块
+//!
+//! - `FirmwareManifest`: 固件镜像的签名描述 (版本、大小、SHA-256)，供 OTA 更新使用
+//! - `SignedBlob`: 通用签名负载 (配置文件等)，供配置管理使用
+
+use super::keys::{KeyRing, SignatureAlgorithm, SIGNATURE_LEN};
+use super::{constant_time_eq, sha256, CryptoError};
+
+/// SHA-256 摘要
+pub type Sha256Digest = [u8; 32];
+
+/// 固件清单魔数
+pub const MANIFEST_MAGIC: [u8; 4] = *b"RTMF";
+
+/// 清单格式版本
+pub const MANIFEST_FORMAT_VERSION: u16 = 1;
+
+/// 清单被签名部分的长度
+pub const MANIFEST_SIGNED_LEN: usize = 64;
+
+/// 清单总长度 (签名部分 + 签名)
+pub const MANIFEST_LEN: usize = MANIFEST_SIGNED_LEN + SIGNATURE_LEN;
+
+/// 签名数据块魔数
+pub const BLOB_MAGIC: [u8; 4] = *b"RTCF";
+
+/// 签名数据块头部长度
+pub const BLOB_HEADER_LEN: usize = 12;
+
+/// 固件清单
+///
+/// 线上格式 (小端，共 128 字节):
+///
+/// | 偏移 | 长度 | 字段 |
+/// |------|------|------|
+/// | 0    | 4    | 魔数 `RTMF` |
+/// | 4    | 2    | 格式版本 |
+/// | 6    | 1    | 签名密钥 ID |
+/// | 7    | 1    | 签名算法 |
+/// | 8    | 4    | 固件版本号 |
+/// | 12   | 4    | 镜像大小 (字节) |
+/// | 16   | 32   | 镜像 SHA-256 |
+/// | 48   | 4    | 最低可接受版本 (防回滚) |
+/// | 52   | 12   | 保留 (0) |
+/// | 64   | 64   | 签名 (覆盖前 64 字节) |
+#[derive(Debug, Clone)]
+pub struct FirmwareManifest {
+    /// 签名密钥 ID
+    pub key_id: u8,
+    /// 签名算法
+    pub algorithm: SignatureAlgorithm,
+    /// 固件版本号
+    pub version: u32,
+    /// 镜像大小
+    pub image_size: u32,
+    /// 镜像摘要
+    pub image_digest: Sha256Digest,
+    /// 最低可接受版本
+    pub min_version: u32,
+    /// 被签名的原始字节
+    signed: [u8; MANIFEST_SIGNED_LEN],
+    /// 签名
+    signature: [u8; SIGNATURE_LEN],
+}
+
+impl FirmwareManifest {
+    /// 解析清单
+    pub fn parse(data: &[u8]) -> Result<Self, CryptoError> {
+        if data.len() < MANIFEST_LEN || data[0..4] != MANIFEST_MAGIC {
+            return Err(CryptoError::Malformed);
+        }
+        let format = u16::from_le_bytes([data[4], data[5]]);
+        if format != MANIFEST_FORMAT_VERSION {
+            return Err(CryptoError::Malformed);
+        }
+
+        let mut signed = [0u8; MANIFEST_SIGNED_LEN];
+        signed.copy_from_slice(&data[..MANIFEST_SIGNED_LEN]);
+        let mut signature = [0u8; SIGNATURE_LEN];
+        signature.copy_from_slice(&data[MANIFEST_SIGNED_LEN..MANIFEST_LEN]);
+        let mut image_digest = [0u8; 32];
+        image_digest.copy_from_slice(&data[16..48]);
+
+        Ok(Self {
+            key_id: data[6],
+            algorithm: SignatureAlgorithm::from_u8(data[7])?,
+            version: u32::from_le_bytes([data[8], data[9], data[10], data[11]]),
+            image_size: u32::from_le_bytes([data[12], data[13], data[14], data[15]]),
+            image_digest,
+            min_version: u32::from_le_bytes([data[48], data[49], data[50], data[51]]),
+            signed,
+            signature,
+        })
+    }
+
+    /// 验证清单签名
+    ///
+    /// 签名密钥必须在信任环中且算法一致
+    pub fn verify<const N: usize>(&self, ring: &KeyRing<N>) -> Result<(), CryptoError> {
+        let key = ring.get(self.key_id).ok_or(CryptoError::UnknownKey)?;
+        if key.algorithm != self.algorithm {
+            return Err(CryptoError::UnsupportedAlgorithm);
+        }
+        key.verify(&self.signed, &self.signature)
+    }
+
+    /// 验证镜像摘要
+    pub fn verify_digest(&self, digest: &Sha256Digest) -> Result<(), CryptoError> {
+        if constant_time_eq(digest, &self.image_digest) {
+            Ok(())
+        } else {
+            Err(CryptoError::DigestMismatch)
+        }
+    }
+
+    /// 验证完整镜像 (适用于小镜像或已映射到内存的镜像)
+    pub fn verify_image(&self, image: &[u8]) -> Result<(), CryptoError> {
+        if image.len() != self.image_size as usize {
+            return Err(CryptoError::DigestMismatch);
+        }
+        self.verify_digest(&sha256(image))
+    }
+
+    /// 检查版本是否允许安装 (防回滚)
+    pub fn allows_upgrade_from(&self, running_version: u32) -> bool {
+        running_version >= self.min_version && self.version > running_version
+    }
+}
+
+/// 签名数据块
+///
+/// 线上格式 (小端):
+///
+/// | 偏移 | 长度 | 字段 |
+/// |------|------|------|
+/// | 0    | 4    | 魔数 `RTCF` |
+/// | 4    | 1    | 签名密钥 ID |
+/// | 5    | 1    | 签名算法 |
+/// | 6    | 2    | 保留 (0) |
+/// | 8    | 4    | 负载长度 L |
+/// | 12   | L    | 负载 |
+/// | 12+L | 64   | 签名 (覆盖前 12+L 字节) |
+#[derive(Debug, Clone, Copy)]
+pub struct SignedBlob<'a> {
+    /// 签名密钥 ID
+    pub key_id: u8,
+    /// 签名算法
+    pub algorithm: SignatureAlgorithm,
+    /// 负载 (未验证前不可信)
+    payload: &'a [u8],
+    /// 被签名的字节
+    signed: &'a [u8],
+    /// 签名
+    signature: &'a [u8],
+}
+
+impl<'a> SignedBlob<'a> {
+    /// 解析签名数据块
+    pub fn parse(data: &'a [u8]) -> Result<Self, CryptoError> {
+        if data.len() < BLOB_HEADER_LEN + SIGNATURE_LEN || data[0..4] != BLOB_MAGIC {
+            return Err(CryptoError::Malformed);
+        }
+        let len = u32::from_le_bytes([data[8], data[9], data[10], data[11]]) as usize;
+        let signed_len = BLOB_HEADER_LEN.checked_add(len).ok_or(CryptoError::Malformed)?;
+        if signed_len.checked_add(SIGNATURE_LEN) != Some(data.len()) {
+            return Err(CryptoError::Malformed);
+        }
+
+        Ok(Self {
+            key_id: data[4],
+            algorithm: SignatureAlgorithm::from_u8(data[5])?,
+            payload: &data[BLOB_HEADER_LEN..signed_len],
+            signed: &data[..signed_len],
+            signature: &data[signed_len..],
+        })
+    }
+
+    /// 验证签名并返回负载
+    pub fn verify<const N: usize>(&self, ring: &KeyRing<N>) -> Result<&'a [u8], CryptoError> {
+        let key = ring.get(self.key_id).ok_or(CryptoError::UnknownKey)?;
+        if key.algorithm != self.algorithm {
+            return Err(CryptoError::UnsupportedAlgorithm);
+        }
+        key.verify(self.signed, self.signature)?;
+        Ok(self.payload)
+    }
+
+    /// 写入签名数据块头部 (供主机端工具或测试构造负载)
+    pub fn encode_header(key_id: u8, algorithm: SignatureAlgorithm, payload_len: u32) -> [u8; BLOB_HEADER_LEN] {
+        let mut header = [0u8; BLOB_HEADER_LEN];
+        header[0..4].copy_from_slice(&BLOB_MAGIC);
+        header[4] = key_id;
+        header[5] = algorithm.as_u8();
+        header[8..12].copy_from_slice(&payload_len.to_le_bytes());
+        header
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_manifest() -> [u8; MANIFEST_LEN] {
+        let mut data = [0u8; MANIFEST_LEN];
+        data[0..4].copy_from_slice(&MANIFEST_MAGIC);
+        data[4..6].copy_from_slice(&MANIFEST_FORMAT_VERSION.to_le_bytes());
+        data[6] = 7;
+        data[7] = SignatureAlgorithm::Ed25519.as_u8();
+        data[8..12].copy_from_slice(&5u32.to_le_bytes());
+        data[12..16].copy_from_slice(&3u32.to_le_bytes());
+        data[16..48].copy_from_slice(&sha256(b"abc"));
+        data[48..52].copy_from_slice(&2u32.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_manifest_parse() {
+        let manifest = FirmwareManifest::parse(&sample_manifest()).unwrap();
+        assert_eq!(manifest.key_id, 7);
+        assert_eq!(manifest.version, 5);
+        assert!(manifest.verify_image(b"abc").is_ok());
+        assert_eq!(manifest.verify_image(b"abd"), Err(CryptoError::DigestMismatch));
+        assert!(manifest.allows_upgrade_from(3));
+        assert!(!manifest.allows_upgrade_from(1));
+        assert!(!manifest.allows_upgrade_from(5));
+    }
+
+    #[test]
+    fn test_blob_parse_length_check() {
+        let mut data = [0u8; BLOB_HEADER_LEN + 4 + SIGNATURE_LEN];
+        data[..BLOB_HEADER_LEN].copy_from_slice(&SignedBlob::encode_header(1, SignatureAlgorithm::Ed25519, 4));
+        let blob = SignedBlob::parse(&data).unwrap();
+        assert_eq!(blob.key_id, 1);
+
+        assert_eq!(SignedBlob::parse(&data[..data.len() - 1]).unwrap_err(), CryptoError::Malformed);
+
+        // 长度字段接近 u32::MAX 时不得溢出
+        data[..BLOB_HEADER_LEN].copy_from_slice(&SignedBlob::encode_header(1, SignatureAlgorithm::Ed25519, u32::MAX));
+        assert_eq!(SignedBlob::parse(&data).unwrap_err(), CryptoError::Malformed);
+    }
+}
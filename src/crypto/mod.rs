@@ -0,0 +1,204 @@
+//! 密码学模块
+//!
+//! 提供固件清单与配置负载的签名验证，确保只接受厂商密钥签名的数据:
+//! - Ed25519 签名验证 (软件实现，`ed25519-compact`)
+//! - ECDSA P-256 签名验证 (软件实现，`p256`)
+//! - SHA-256 摘要
+//! - 多密钥信任环与密钥轮换
+//! - 随机数源抽象 (`Entropy`)
+//!
+//! # 密钥轮换
+//!
+//! 设备出厂时内置一个或多个受信任公钥 (`KeyRing`)。轮换新密钥时，
+//! 厂商使用当前 `Active` 密钥对新公钥签发一条 `KeyRotation` 记录:
+//!
+//! 1. 设备验证记录签名 (必须由 `Active` 密钥签发)
+//! 2. 新密钥以 `Active` 状态加入信任环
+//! 3. 签发者降级为 `Deprecated` (仍可验证旧负载，便于平滑过渡)
+//! 4. 过渡期结束后厂商可通过 `KeyRing::revoke` 撤销旧密钥
+//!
+//! 信任环只在内存中修改，轮换或撤销后调用 `KeyRing::save` 写入键值存储；
+//! 启动时先加入出厂密钥，再用 `KeyRing::load` 合并保存的状态，之后才验证清单或配置。
+//! 合并只会收紧出厂密钥的状态，已撤销的密钥重启后仍被拒绝。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::crypto::{KeyRing, TrustedKey, SignatureAlgorithm, FirmwareManifest};
+//! use rustrtos::fs::kv::KvStore;
+//!
+//! let mut ring: KeyRing<4> = KeyRing::new();
+//! ring.add(TrustedKey::ed25519(1, &VENDOR_PUBKEY))?;
+//! ring.load(&KvStore::new(&fs))?;
+//!
+//! let manifest = FirmwareManifest::parse(&header_bytes)?;
+//! manifest.verify(&ring)?;
+//! ```
+
+pub mod keys;
+pub mod manifest;
+
+use core::fmt;
+
+use crate::fs::kv::KvError;
+
+pub use keys::{KeyRing, KeyRotation, KeyState, SignatureAlgorithm, TrustedKey};
+pub use manifest::{FirmwareManifest, SignedBlob, Sha256Digest};
+
+/// 密码学操作错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CryptoError {
+    /// 公钥格式无效
+    InvalidKey,
+    /// 签名格式无效
+    InvalidSignature,
+    /// 签名验证失败
+    VerificationFailed,
+    /// 未找到对应密钥 ID
+    UnknownKey,
+    /// 密钥已被撤销
+    KeyRevoked,
+    /// 信任环已满
+    KeyRingFull,
+    /// 密钥 ID 重复
+    DuplicateKey,
+    /// 数据格式错误
+    Malformed,
+    /// 摘要不匹配
+    DigestMismatch,
+    /// 不支持的算法
+    UnsupportedAlgorithm,
+    /// 信任环状态读写失败
+    Storage(KvError),
+}
+
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidKey => write!(f, "Invalid public key"),
+            Self::InvalidSignature => write!(f, "Invalid signature encoding"),
+            Self::VerificationFailed => write!(f, "Signature verification failed"),
+            Self::UnknownKey => write!(f, "Unknown key id"),
+            Self::KeyRevoked => write!(f, "Key revoked"),
+            Self::KeyRingFull => write!(f, "Key ring full"),
+            Self::DuplicateKey => write!(f, "Duplicate key id"),
+            Self::Malformed => write!(f, "Malformed data"),
+            Self::DigestMismatch => write!(f, "Digest mismatch"),
+            Self::UnsupportedAlgorithm => write!(f, "Unsupported algorithm"),
+            Self::Storage(e) => write!(f, "Key ring storage error: {}", e),
+        }
+    }
+}
+
+impl From<KvError> for CryptoError {
+    fn from(e: KvError) -> Self {
+        Self::Storage(e)
+    }
+}
+
+/// 计算 SHA-256 摘要
+#[inline]
+pub fn sha256(data: &[u8]) -> Sha256Digest {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+/// 常量时间比较 (避免时序侧信道)
+#[inline]
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut diff = 0u8;
+    for (x, y) in a.iter().zip(b.iter()) {
+        diff |= x ^ y;
+    }
+    diff == 0
+}
+
+/// 随机数源
+///
+/// 生成密钥、握手随机数等需要密码学强度的随机数。ESP32-S3 的硬件 RNG
+/// 在 WiFi/BLE 射频开启时才具备真随机熵，否则需先启用 ADC 熵源。
+pub trait Entropy {
+    /// 以随机字节填满缓冲区
+    fn fill(&mut self, buf: &mut [u8]);
+}
+
+impl Entropy for esp_hal::rng::Rng {
+    fn fill(&mut self, buf: &mut [u8]) {
+        self.read(buf);
+    }
+}
+
+/// 验证签名
+///
+/// # 参数
+/// - `algorithm`: 签名算法
+/// - `public_key`: 公钥 (Ed25519 为 32 字节，P-256 为 SEC1 编码 33/65 字节)
+/// - `message`: 被签名的消息
+/// - `signature`: 签名 (64 字节，P-256 为 r||s)
+pub fn verify_signature(
+    algorithm: SignatureAlgorithm,
+    public_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+) -> Result<(), CryptoError> {
+    match algorithm {
+        SignatureAlgorithm::Ed25519 => {
+            let pk = ed25519_compact::PublicKey::from_slice(public_key)
+                .map_err(|_| CryptoError::InvalidKey)?;
+            let sig = ed25519_compact::Signature::from_slice(signature)
+                .map_err(|_| CryptoError::InvalidSignature)?;
+            pk.verify(message, &sig)
+                .map_err(|_| CryptoError::VerificationFailed)
+        }
+        SignatureAlgorithm::EcdsaP256 => {
+            use p256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+            let vk = VerifyingKey::from_sec1_bytes(public_key)
+                .map_err(|_| CryptoError::InvalidKey)?;
+            let sig = Signature::from_slice(signature)
+                .map_err(|_| CryptoError::InvalidSignature)?;
+            vk.verify(message, &sig)
+                .map_err(|_| CryptoError::VerificationFailed)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 8032 Test 1 (空消息)
+    const RFC8032_PK: [u8; 32] = [
+        0xd7, 0x5a, 0x98, 0x01, 0x82, 0xb1, 0x0a, 0xb7, 0xd5, 0x4b, 0xfe, 0xd3, 0xc9, 0x64, 0x07, 0x3a,
+        0x0e, 0xe1, 0x72, 0xf3, 0xda, 0xa6, 0x23, 0x25, 0xaf, 0x02, 0x1a, 0x68, 0xf7, 0x07, 0x51, 0x1a,
+    ];
+    const RFC8032_SIG: [u8; 64] = [
+        0xe5, 0x56, 0x43, 0x00, 0xc3, 0x60, 0xac, 0x72, 0x90, 0x86, 0xe2, 0xcc, 0x80, 0x6e, 0x82, 0x8a,
+        0x84, 0x87, 0x7f, 0x1e, 0xb8, 0xe5, 0xd9, 0x74, 0xd8, 0x73, 0xe0, 0x65, 0x22, 0x49, 0x01, 0x55,
+        0x5f, 0xb8, 0x82, 0x15, 0x90, 0xa3, 0x3b, 0xac, 0xc6, 0x1e, 0x39, 0x70, 0x1c, 0xf9, 0xb4, 0x6b,
+        0xd2, 0x5b, 0xf5, 0xf0, 0x59, 0x5b, 0xbe, 0x24, 0x65, 0x51, 0x41, 0x43, 0x8e, 0x7a, 0x10, 0x0b,
+    ];
+
+    #[test]
+    fn test_ed25519_rfc8032_vector() {
+        assert!(verify_signature(SignatureAlgorithm::Ed25519, &RFC8032_PK, b"", &RFC8032_SIG).is_ok());
+
+        let mut bad = RFC8032_SIG;
+        bad[0] ^= 0x01;
+        assert_eq!(
+            verify_signature(SignatureAlgorithm::Ed25519, &RFC8032_PK, b"", &bad),
+            Err(CryptoError::VerificationFailed)
+        );
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"ab"));
+    }
+}
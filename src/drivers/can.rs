@@ -0,0 +1,699 @@
+//! CAN (TWAI) 总线驱动
+//!
+//! ESP32-S3 的 TWAI 控制器兼容 CAN 2.0A/B (最高 1Mbps)。
+//!
+//! # 特性
+//! - 标准波特率或按采样点自动计算的位时序
+//! - 验收滤波 (标准帧/扩展帧 ID + 掩码)
+//! - 异步发送，可配置重发策略
+//! - 接收帧通过 `PooledChannel` 分发，消息体不拷贝
+//! - Bus-Off 检测与自动恢复
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::drivers::can::{CanBus, CanConfig, CanFrame, CanBitrate, AcceptanceFilter};
+//! use rustrtos::sync::PooledChannel;
+//!
+//! static CAN_RX: PooledChannel<CanFrame, 16> = PooledChannel::new();
+//! static CAN_TX: CriticalChannel<CanFrame, 8> = CriticalChannel::new();
+//!
+//! #[embassy_executor::task]
+//! async fn can_task(mut bus: CanBus<'static>) {
+//!     bus.run(&CAN_RX, &CAN_TX).await;
+//! }
+//!
+//! let config = CanConfig::new(CanBitrate::K500)
+//!     .with_filter(AcceptanceFilter::standard(0x100, 0x700));
+//! let bus = CanBus::new(peripherals.TWAI0, peripherals.GPIO4, peripherals.GPIO5, config)?;
+//! spawner.spawn(can_task(bus)).ok();
+//!
+//! CAN_TX.send(CanFrame::new_standard(0x123, &[1, 2, 3]).unwrap()).await;
+//! let frame = CAN_RX.receive().await;
+//! ```
+
+use core::fmt;
+
+use embassy_futures::select::{select, Either};
+use embassy_time::{Duration, Timer};
+use embedded_can::{ExtendedId, Frame, Id, StandardId};
+use esp_hal::gpio::interconnect::{PeripheralInput, PeripheralOutput};
+use esp_hal::twai::{
+    filter, BaudRate, EspTwaiError, EspTwaiFrame, TimingConfig, Twai, TwaiConfiguration, TwaiMode,
+};
+use esp_hal::Async;
+use portable_atomic::{AtomicU32, Ordering};
+
+use crate::power::lifecycle::{DriverLifecycle, LifecycleError, SleepKind};
+use crate::power::peripherals::{self, KeepAlive, Peripheral};
+use crate::sync::pooled::PooledChannel;
+use crate::sync::primitives::CriticalChannel;
+use crate::util::log::*;
+
+/// TWAI 模块时钟 (APB)
+pub const TWAI_CLOCK_HZ: u32 = 80_000_000;
+
+// ===== 错误类型 =====
+
+/// CAN 错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CanError {
+    /// 帧 ID 或数据长度无效
+    InvalidFrame,
+    /// 无法满足的位时序
+    InvalidTiming,
+    /// 控制器处于 Bus-Off
+    BusOff,
+    /// 重发次数耗尽
+    TransmitFailed,
+    /// 总线错误 (位错误、填充错误、CRC 错误等)
+    Bus,
+}
+
+impl fmt::Display for CanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CanError::InvalidFrame => write!(f, "Invalid CAN frame"),
+            CanError::InvalidTiming => write!(f, "Bit timing not achievable"),
+            CanError::BusOff => write!(f, "Controller is bus-off"),
+            CanError::TransmitFailed => write!(f, "Transmit retries exhausted"),
+            CanError::Bus => write!(f, "Bus error"),
+        }
+    }
+}
+
+// ===== 帧 =====
+
+/// CAN 帧
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CanFrame {
+    /// 帧 ID (标准帧 11 位，扩展帧 29 位)
+    pub id: u32,
+    /// 是否为扩展帧
+    pub extended: bool,
+    /// 是否为远程帧
+    pub remote: bool,
+    /// 数据长度
+    pub dlc: u8,
+    /// 数据
+    pub data: [u8; 8],
+}
+
+impl CanFrame {
+    /// 创建标准数据帧
+    pub fn new_standard(id: u16, data: &[u8]) -> Option<Self> {
+        if id > 0x7FF {
+            return None;
+        }
+        Self::with_data(id as u32, false, data)
+    }
+
+    /// 创建扩展数据帧
+    pub fn new_extended(id: u32, data: &[u8]) -> Option<Self> {
+        if id > 0x1FFF_FFFF {
+            return None;
+        }
+        Self::with_data(id, true, data)
+    }
+
+    /// 创建远程帧
+    pub fn new_remote(id: u32, extended: bool, dlc: u8) -> Option<Self> {
+        let max_id = if extended { 0x1FFF_FFFF } else { 0x7FF };
+        if id > max_id || dlc > 8 {
+            return None;
+        }
+        Some(Self { id, extended, remote: true, dlc, data: [0; 8] })
+    }
+
+    fn with_data(id: u32, extended: bool, data: &[u8]) -> Option<Self> {
+        if data.len() > 8 {
+            return None;
+        }
+        let mut frame = Self { id, extended, remote: false, dlc: data.len() as u8, data: [0; 8] };
+        frame.data[..data.len()].copy_from_slice(data);
+        Some(frame)
+    }
+
+    /// 有效数据
+    pub fn payload(&self) -> &[u8] {
+        &self.data[..self.dlc as usize]
+    }
+
+    fn to_hal(&self) -> Result<EspTwaiFrame, CanError> {
+        let id: Id = if self.extended {
+            ExtendedId::new(self.id).ok_or(CanError::InvalidFrame)?.into()
+        } else {
+            StandardId::new(self.id as u16).ok_or(CanError::InvalidFrame)?.into()
+        };
+        let frame = if self.remote {
+            EspTwaiFrame::new_remote(id, self.dlc as usize)
+        } else {
+            EspTwaiFrame::new(id, self.payload())
+        };
+        frame.ok_or(CanError::InvalidFrame)
+    }
+
+    fn from_hal(frame: &EspTwaiFrame) -> Self {
+        let (id, extended) = match frame.id() {
+            Id::Standard(id) => (id.as_raw() as u32, false),
+            Id::Extended(id) => (id.as_raw(), true),
+        };
+        let mut out = Self {
+            id,
+            extended,
+            remote: frame.is_remote_frame(),
+            dlc: frame.dlc() as u8,
+            data: [0; 8],
+        };
+        let data = frame.data();
+        out.data[..data.len()].copy_from_slice(data);
+        out
+    }
+}
+
+// ===== 配置 =====
+
+/// 位时序参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitTiming {
+    /// 波特率预分频 (偶数，2 ~ 16384)
+    pub prescaler: u16,
+    /// 相位段 1 (含传播段，1 ~ 16 tq)
+    pub tseg1: u8,
+    /// 相位段 2 (1 ~ 8 tq)
+    pub tseg2: u8,
+    /// 同步跳转宽度 (1 ~ 4 tq)
+    pub sjw: u8,
+    /// 三次采样 (低速总线抗干扰)
+    pub triple_sample: bool,
+}
+
+impl BitTiming {
+    /// 按目标波特率和采样点计算位时序
+    ///
+    /// # 参数
+    /// - `bitrate`: 目标波特率 (bps)
+    /// - `sample_point_permille`: 采样点 (千分比，CANopen 推荐 875)
+    ///
+    /// # 返回
+    /// 无法精确整除时钟时返回 `None`
+    pub fn calculate(bitrate: u32, sample_point_permille: u16) -> Option<Self> {
+        if bitrate == 0 {
+            return None;
+        }
+        let mut best: Option<(u32, Self)> = None;
+
+        // 每位 tq 数越多，采样点越精确
+        for tq in (8..=25u32).rev() {
+            let Some(divisor) = bitrate.checked_mul(tq) else {
+                continue;
+            };
+            if TWAI_CLOCK_HZ % divisor != 0 {
+                continue;
+            }
+            let prescaler = TWAI_CLOCK_HZ / divisor;
+            if prescaler < 2 || prescaler > 16384 || prescaler % 2 != 0 {
+                continue;
+            }
+
+            // 采样点 = (1 + tseg1) / tq
+            let sample_tq = (tq * sample_point_permille as u32 + 500) / 1000;
+            let tseg1 = sample_tq.saturating_sub(1).clamp(1, 16);
+            // 采样点过晚时 tseg1 可能占满整个位，没有 tseg2 的余地
+            let Some(tseg2) = tq.checked_sub(1 + tseg1) else {
+                continue;
+            };
+            if !(1..=8).contains(&tseg2) {
+                continue;
+            }
+
+            let actual = (1 + tseg1) * 1000 / tq;
+            let error = actual.abs_diff(sample_point_permille as u32);
+            let timing = Self {
+                prescaler: prescaler as u16,
+                tseg1: tseg1 as u8,
+                tseg2: tseg2 as u8,
+                sjw: tseg2.min(4) as u8,
+                triple_sample: false,
+            };
+            if best.map_or(true, |(e, _)| error < e) {
+                best = Some((error, timing));
+            }
+        }
+        best.map(|(_, t)| t)
+    }
+
+    /// 实际波特率
+    pub fn bitrate(&self) -> u32 {
+        TWAI_CLOCK_HZ / (self.prescaler as u32 * (1 + self.tseg1 as u32 + self.tseg2 as u32))
+    }
+}
+
+/// 波特率
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CanBitrate {
+    /// 125 kbps
+    K125,
+    /// 250 kbps
+    K250,
+    /// 500 kbps
+    K500,
+    /// 1 Mbps
+    K1000,
+    /// 自定义位时序
+    Custom(BitTiming),
+}
+
+impl CanBitrate {
+    fn to_hal(self) -> BaudRate {
+        match self {
+            CanBitrate::K125 => BaudRate::B125K,
+            CanBitrate::K250 => BaudRate::B250K,
+            CanBitrate::K500 => BaudRate::B500K,
+            CanBitrate::K1000 => BaudRate::B1000K,
+            CanBitrate::Custom(t) => BaudRate::Custom(TimingConfig {
+                baud_rate_prescaler: t.prescaler,
+                sync_jump_width: t.sjw,
+                tseg_1: t.tseg1,
+                tseg_2: t.tseg2,
+                triple_sample: t.triple_sample,
+            }),
+        }
+    }
+}
+
+/// 验收滤波器
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AcceptanceFilter {
+    /// 接收全部帧
+    AcceptAll,
+    /// 标准帧: (帧 ID & mask) == (id & mask) 时接收
+    Standard { id: u16, mask: u16 },
+    /// 扩展帧: (帧 ID & mask) == (id & mask) 时接收
+    Extended { id: u32, mask: u32 },
+}
+
+impl AcceptanceFilter {
+    /// 标准帧滤波器
+    pub const fn standard(id: u16, mask: u16) -> Self {
+        Self::Standard { id, mask }
+    }
+
+    /// 扩展帧滤波器
+    pub const fn extended(id: u32, mask: u32) -> Self {
+        Self::Extended { id, mask }
+    }
+
+    /// 软件判断帧是否通过 (用于测试和 AcceptAll 以外的二次过滤)
+    pub fn matches(&self, frame: &CanFrame) -> bool {
+        match *self {
+            AcceptanceFilter::AcceptAll => true,
+            AcceptanceFilter::Standard { id, mask } => {
+                !frame.extended && (frame.id as u16 & mask) == (id & mask)
+            }
+            AcceptanceFilter::Extended { id, mask } => {
+                frame.extended && (frame.id & mask) == (id & mask)
+            }
+        }
+    }
+}
+
+/// 把 ID + 掩码转换为 esp-hal 的位模式 ('0' / '1' / 'x')，高位在前
+fn bit_pattern<const BITS: usize>(id: u32, mask: u32) -> [u8; BITS] {
+    let mut out = [b'x'; BITS];
+    for (i, slot) in out.iter_mut().enumerate() {
+        let bit = BITS - 1 - i;
+        if mask & (1 << bit) != 0 {
+            *slot = if id & (1 << bit) != 0 { b'1' } else { b'0' };
+        }
+    }
+    out
+}
+
+/// 重发策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetransmitPolicy {
+    /// 不重发 (单次发送)
+    Never,
+    /// 最多重发 N 次
+    Limited(u8),
+    /// 一直重发直到成功或 Bus-Off
+    Forever,
+}
+
+/// 控制器模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CanMode {
+    /// 正常模式
+    Normal,
+    /// 只听模式 (不发送 ACK)
+    ListenOnly,
+    /// 自测模式 (无需 ACK，用于回环测试)
+    SelfTest,
+}
+
+/// CAN 配置
+#[derive(Debug, Clone, Copy)]
+pub struct CanConfig {
+    /// 波特率
+    pub bitrate: CanBitrate,
+    /// 控制器模式
+    pub mode: CanMode,
+    /// 验收滤波器
+    pub filter: AcceptanceFilter,
+    /// 重发策略
+    pub retransmit: RetransmitPolicy,
+    /// Bus-Off 后是否自动恢复
+    pub auto_recover: bool,
+    /// 恢复前等待时间
+    pub recovery_delay: Duration,
+}
+
+impl CanConfig {
+    /// 创建默认配置 (正常模式、接收全部、重发 3 次、自动恢复)
+    pub const fn new(bitrate: CanBitrate) -> Self {
+        Self {
+            bitrate,
+            mode: CanMode::Normal,
+            filter: AcceptanceFilter::AcceptAll,
+            retransmit: RetransmitPolicy::Limited(3),
+            auto_recover: true,
+            recovery_delay: Duration::from_millis(100),
+        }
+    }
+
+    /// 设置模式
+    pub fn with_mode(mut self, mode: CanMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// 设置验收滤波器
+    pub fn with_filter(mut self, filter: AcceptanceFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// 设置重发策略
+    pub fn with_retransmit(mut self, policy: RetransmitPolicy) -> Self {
+        self.retransmit = policy;
+        self
+    }
+
+    /// 设置 Bus-Off 恢复策略
+    pub fn with_recovery(mut self, auto_recover: bool, delay: Duration) -> Self {
+        self.auto_recover = auto_recover;
+        self.recovery_delay = delay;
+        self
+    }
+}
+
+// ===== 统计 =====
+
+/// CAN 统计 (原子计数，可跨任务读取)
+pub struct CanStats {
+    /// 已发送帧
+    pub tx_frames: AtomicU32,
+    /// 已接收帧
+    pub rx_frames: AtomicU32,
+    /// 接收池满丢弃的帧
+    pub rx_dropped: AtomicU32,
+    /// 重发次数
+    pub retransmits: AtomicU32,
+    /// 发送失败
+    pub tx_failed: AtomicU32,
+    /// Bus-Off 次数
+    pub bus_off: AtomicU32,
+}
+
+impl CanStats {
+    const fn new() -> Self {
+        Self {
+            tx_frames: AtomicU32::new(0),
+            rx_frames: AtomicU32::new(0),
+            rx_dropped: AtomicU32::new(0),
+            retransmits: AtomicU32::new(0),
+            tx_failed: AtomicU32::new(0),
+            bus_off: AtomicU32::new(0),
+        }
+    }
+}
+
+// ===== 驱动 =====
+
+/// CAN 总线驱动
+pub struct CanBus<'d> {
+    /// 运行中的控制器 (Bus-Off 后为 None)
+    twai: Option<Twai<'d, Async>>,
+    /// Bus-Off 后停止的控制器，等待恢复
+    stopped: Option<TwaiConfiguration<'d, Async>>,
+    config: CanConfig,
+    stats: CanStats,
+    /// 挂起时控制器在运行 (恢复时重新启动)
+    suspended: bool,
+    _power: KeepAlive,
+}
+
+impl<'d> CanBus<'d> {
+    /// 初始化 TWAI 控制器并启动
+    pub fn new(
+        peripheral: esp_hal::peripherals::TWAI0<'d>,
+        rx_pin: impl PeripheralInput<'d>,
+        tx_pin: impl PeripheralOutput<'d>,
+        config: CanConfig,
+    ) -> Result<Self, CanError> {
+        if let CanBitrate::Custom(t) = config.bitrate {
+            if t.prescaler < 2 || t.tseg1 == 0 || t.tseg1 > 16 || t.tseg2 == 0 || t.tseg2 > 8 {
+                return Err(CanError::InvalidTiming);
+            }
+        }
+
+        let power = peripherals::keep_alive(Peripheral::Twai);
+        let mode = match config.mode {
+            CanMode::Normal => TwaiMode::Normal,
+            CanMode::ListenOnly => TwaiMode::ListenOnly,
+            CanMode::SelfTest => TwaiMode::SelfTest,
+        };
+
+        let mut twai_config =
+            TwaiConfiguration::new(peripheral, rx_pin, tx_pin, config.bitrate.to_hal(), mode).into_async();
+
+        match config.filter {
+            AcceptanceFilter::AcceptAll => {}
+            AcceptanceFilter::Standard { id, mask } => {
+                let pattern = bit_pattern::<11>(id as u32, mask as u32);
+                twai_config.set_filter(filter::SingleStandardFilter::new(
+                    &pattern,
+                    b"x",
+                    [b"xxxxxxxx", b"xxxxxxxx"],
+                ));
+            }
+            AcceptanceFilter::Extended { id, mask } => {
+                let pattern = bit_pattern::<29>(id, mask);
+                twai_config.set_filter(filter::SingleExtendedFilter::new(&pattern, b"x"));
+            }
+        }
+
+        log_info!("CAN bus started");
+        Ok(Self {
+            twai: Some(twai_config.start()),
+            stopped: None,
+            config,
+            stats: CanStats::new(),
+            suspended: false,
+            _power: power,
+        })
+    }
+
+    /// 统计信息
+    pub fn stats(&self) -> &CanStats {
+        &self.stats
+    }
+
+    /// 当前配置
+    pub fn config(&self) -> &CanConfig {
+        &self.config
+    }
+
+    /// 是否处于 Bus-Off
+    pub fn is_bus_off(&self) -> bool {
+        self.twai.as_ref().map_or(true, |t| t.is_bus_off())
+    }
+
+    /// 发送错误计数 (TEC)
+    pub fn transmit_error_count(&self) -> u8 {
+        self.twai.as_ref().map_or(0, |t| t.transmit_error_count())
+    }
+
+    /// 接收错误计数 (REC)
+    pub fn receive_error_count(&self) -> u8 {
+        self.twai.as_ref().map_or(0, |t| t.receive_error_count())
+    }
+
+    /// 异步发送 (按配置的重发策略)
+    pub async fn transmit(&mut self, frame: &CanFrame) -> Result<(), CanError> {
+        let hal_frame = frame.to_hal()?;
+        let mut attempts: u16 = 0;
+
+        loop {
+            let twai = self.twai.as_mut().ok_or(CanError::BusOff)?;
+            match twai.transmit_async(&hal_frame).await {
+                Ok(()) => {
+                    self.stats.tx_frames.fetch_add(1, Ordering::Relaxed);
+                    return Ok(());
+                }
+                Err(EspTwaiError::BusOff) => {
+                    self.stats.tx_failed.fetch_add(1, Ordering::Relaxed);
+                    self.handle_bus_off().await;
+                    return Err(CanError::BusOff);
+                }
+                Err(_) => {
+                    let retry = match self.config.retransmit {
+                        RetransmitPolicy::Never => false,
+                        RetransmitPolicy::Limited(n) => attempts < n as u16,
+                        RetransmitPolicy::Forever => true,
+                    };
+                    if !retry {
+                        self.stats.tx_failed.fetch_add(1, Ordering::Relaxed);
+                        return Err(CanError::TransmitFailed);
+                    }
+                    attempts = attempts.saturating_add(1);
+                    self.stats.retransmits.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+
+    /// 异步接收一帧
+    pub async fn receive(&mut self) -> Result<CanFrame, CanError> {
+        let twai = self.twai.as_mut().ok_or(CanError::BusOff)?;
+        match twai.receive_async().await {
+            Ok(frame) => {
+                self.stats.rx_frames.fetch_add(1, Ordering::Relaxed);
+                Ok(CanFrame::from_hal(&frame))
+            }
+            Err(EspTwaiError::BusOff) => {
+                self.handle_bus_off().await;
+                Err(CanError::BusOff)
+            }
+            Err(_) => Err(CanError::Bus),
+        }
+    }
+
+    /// 驱动主循环 (永不返回)
+    ///
+    /// 接收帧投递到 `rx` (池满时丢弃并计数)，同时从 `tx` 取帧发送。
+    pub async fn run<const RX: usize, const TX: usize>(
+        &mut self,
+        rx: &'static PooledChannel<CanFrame, RX>,
+        tx: &CriticalChannel<CanFrame, TX>,
+    ) -> ! {
+        loop {
+            let Some(twai) = self.twai.as_mut() else {
+                // 等待手动恢复
+                Timer::after(self.config.recovery_delay).await;
+                continue;
+            };
+
+            match select(twai.receive_async(), tx.receive()).await {
+                Either::First(Ok(frame)) => {
+                    self.stats.rx_frames.fetch_add(1, Ordering::Relaxed);
+                    if rx.try_send(CanFrame::from_hal(&frame)).is_err() {
+                        self.stats.rx_dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+                Either::First(Err(EspTwaiError::BusOff)) => self.handle_bus_off().await,
+                Either::First(Err(_)) => {}
+                Either::Second(frame) => {
+                    // 发送错误已计入统计
+                    let _ = self.transmit(&frame).await;
+                }
+            }
+        }
+    }
+
+    /// 手动从 Bus-Off 恢复 (配置 `auto_recover = false` 时使用)
+    ///
+    /// # 返回
+    /// 控制器已恢复运行时返回 `true`
+    pub fn recover(&mut self) -> bool {
+        if let Some(stopped) = self.stopped.take() {
+            self.twai = Some(stopped.start());
+            log_info!("CAN bus recovered");
+        }
+        self.twai.is_some()
+    }
+
+    /// Bus-Off 处理: 停止控制器，按配置等待后重新启动
+    async fn handle_bus_off(&mut self) {
+        let Some(twai) = self.twai.take() else {
+            return;
+        };
+
+        self.stats.bus_off.fetch_add(1, Ordering::Relaxed);
+        log_warn!("CAN bus-off (TEC={})", twai.transmit_error_count());
+        self.stopped = Some(twai.stop());
+
+        if self.config.auto_recover {
+            Timer::after(self.config.recovery_delay).await;
+            self.recover();
+        }
+    }
+}
+
+impl DriverLifecycle for CanBus<'_> {
+    fn name(&self) -> &'static str {
+        "can"
+    }
+
+    /// 停止控制器离开总线 (不计入 Bus-Off)
+    async fn suspend(&mut self, _kind: SleepKind) -> Result<(), LifecycleError> {
+        if let Some(twai) = self.twai.take() {
+            self.stopped = Some(twai.stop());
+            self.suspended = true;
+        }
+        Ok(())
+    }
+
+    /// 挂起前在运行时重新启动；挂起前已 Bus-Off 的保持停止
+    async fn resume(&mut self) -> Result<(), LifecycleError> {
+        if core::mem::take(&mut self.suspended) && !self.recover() {
+            return Err(LifecycleError::InvalidState);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bit_timing_500k() {
+        let t = BitTiming::calculate(500_000, 875).unwrap();
+        assert_eq!(t.bitrate(), 500_000);
+        let tq = 1 + t.tseg1 as u32 + t.tseg2 as u32;
+        assert_eq!((1 + t.tseg1 as u32) * 1000 / tq, 875);
+
+        // 极端参数不得溢出
+        assert!(BitTiming::calculate(1_000_000, 1000).is_some_and(|t| t.tseg2 >= 1));
+        assert!(BitTiming::calculate(1_000_000, u16::MAX).is_some_and(|t| t.tseg2 >= 1));
+        assert_eq!(BitTiming::calculate(u32::MAX, 875), None);
+    }
+
+    #[test]
+    fn test_frame_validation() {
+        assert!(CanFrame::new_standard(0x800, &[]).is_none());
+        assert!(CanFrame::new_standard(0x7FF, &[0; 9]).is_none());
+        let frame = CanFrame::new_extended(0x1234_5678, &[1, 2]).unwrap();
+        assert_eq!(frame.payload(), &[1, 2]);
+    }
+
+    #[test]
+    fn test_filter_and_pattern() {
+        let filter = AcceptanceFilter::standard(0x120, 0x7F0);
+        assert!(filter.matches(&CanFrame::new_standard(0x12A, &[]).unwrap()));
+        assert!(!filter.matches(&CanFrame::new_standard(0x130, &[]).unwrap()));
+        assert_eq!(&bit_pattern::<4>(0b1010, 0b1100), b"10xx");
+    }
+}
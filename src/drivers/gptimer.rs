@@ -0,0 +1,515 @@
+//! 通用硬件定时器 (GPTimer) 微秒级回调
+//!
+//! embassy 定时器以 1ms 级精度为主，且经过执行器调度；部分控制环需要
+//! 10μs 以内的触发精度。本模块直接使用 TIMG 硬件定时器的报警中断:
+//! - `GpTimer`: 周期 / 单次报警，回调在 Priority3 中断中执行，中断入口与
+//!   分发代码位于 IRAM (`#[ram]`)，回调本身也应标注 `#[ram]`
+//! - `Handoff`: 回调结果经池化通道 (`PooledChannel`) 交给普通任务，
+//!   不拷贝消息体、不阻塞；池满时丢弃并计数
+//! - 抖动统计: 每次触发以 CPU 周期计数器 (CCOUNT) 测量相对上次触发的间隔，
+//!   与期望周期比较，`characterize` 在基准套件中给出均值 / p99 / 最大抖动
+//!
+//! TIMG0 的 timer0 已被 esp-rtos 用作时间驱动，可用的是 TIMG0 timer1 与
+//! TIMG1 的两个定时器，最多同时使用 `GPTIMER_SLOTS` 个。回调运行在中断上下文，
+//! 只能做有界的短操作 (读 ADC 寄存器、翻转 GPIO、`Handoff::post`)。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::drivers::gptimer::{AlarmEvent, GpTimer, Handoff};
+//!
+//! static SAMPLES: Handoff<Sample, 32> = Handoff::new();
+//!
+//! #[ram]
+//! fn on_alarm(event: &AlarmEvent) {
+//!     SAMPLES.post(Sample { seq: event.count, raw: read_adc_reg() });
+//! }
+//!
+//! let timg1 = TimerGroup::new(peripherals.TIMG1);
+//! let mut timer = GpTimer::new(timg1.timer0, on_alarm)?;
+//! timer.start_periodic(50)?; // 20 kHz
+//!
+//! loop {
+//!     let sample = SAMPLES.receive().await;
+//!     control_step(&sample);
+//! }
+//! ```
+
+use core::cell::RefCell;
+use core::fmt;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_time::{Duration, Timer as EmbassyTimer};
+use esp_hal::interrupt::Priority;
+use esp_hal::timer::{PeriodicTimer, Timer};
+use esp_hal::{handler, ram, Blocking};
+use portable_atomic::{AtomicU32, AtomicU8, Ordering};
+
+use crate::config::CPU_FREQ_HZ;
+use crate::power::lifecycle::{DriverLifecycle, LifecycleError, SleepKind};
+use crate::sync::pooled::{Pooled, PooledChannel};
+use crate::util::dsp::bench::cycle_count;
+
+/// 可同时使用的硬件定时器数 (TIMG0 timer1、TIMG1 timer0/timer1)
+pub const GPTIMER_SLOTS: usize = 3;
+
+/// 最短报警周期 (μs)，更短时中断开销占满 CPU
+pub const MIN_PERIOD_US: u32 = 5;
+
+/// 抖动直方图桶上界 (ns)，最后一个桶收集更大的值
+pub const JITTER_BOUNDS_NS: [u32; 8] = [100, 250, 500, 1_000, 2_000, 5_000, 10_000, 20_000];
+
+// ===== 错误类型 =====
+
+/// GPTimer 错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpTimerError {
+    /// 所有槽位已被占用
+    Busy,
+    /// 周期短于 `MIN_PERIOD_US`
+    InvalidPeriod,
+    /// 定时器配置失败
+    Config,
+}
+
+impl fmt::Display for GpTimerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GpTimerError::Busy => write!(f, "All hardware timer slots in use"),
+            GpTimerError::InvalidPeriod => write!(f, "Alarm period too short"),
+            GpTimerError::Config => write!(f, "Hardware timer configuration failed"),
+        }
+    }
+}
+
+// ===== 抖动统计 =====
+
+/// μs → CPU 周期
+const fn us_to_cycles(us: u32) -> u32 {
+    let cycles = us as u64 * (CPU_FREQ_HZ / 1_000_000) as u64;
+    if cycles > u32::MAX as u64 { u32::MAX } else { cycles as u32 }
+}
+
+/// CPU 周期 → ns
+const fn cycles_to_ns(cycles: u32) -> u32 {
+    (cycles as u64 * 1_000 / (CPU_FREQ_HZ / 1_000_000) as u64) as u32
+}
+
+/// 触发间隔抖动统计 (CPU 周期)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JitterStats {
+    buckets: [u32; JITTER_BOUNDS_NS.len() + 1],
+    samples: u32,
+    sum_cycles: u64,
+    max_cycles: u32,
+}
+
+impl JitterStats {
+    /// 创建
+    pub const fn new() -> Self {
+        Self { buckets: [0; JITTER_BOUNDS_NS.len() + 1], samples: 0, sum_cycles: 0, max_cycles: 0 }
+    }
+
+    /// 记录一次触发间隔
+    ///
+    /// # 返回
+    /// 本次抖动 (CPU 周期，提前与滞后取绝对值)
+    pub fn record(&mut self, expected_cycles: u32, actual_cycles: u32) -> u32 {
+        let jitter = expected_cycles.abs_diff(actual_cycles);
+        let ns = cycles_to_ns(jitter);
+        let bucket = JITTER_BOUNDS_NS.iter().position(|&bound| ns <= bound).unwrap_or(JITTER_BOUNDS_NS.len());
+        self.buckets[bucket] += 1;
+        self.samples += 1;
+        self.sum_cycles += jitter as u64;
+        self.max_cycles = self.max_cycles.max(jitter);
+        jitter
+    }
+
+    /// 汇总
+    pub fn report(&self) -> JitterReport {
+        if self.samples == 0 {
+            return JitterReport::default();
+        }
+        let max_ns = cycles_to_ns(self.max_cycles);
+        let rank = (self.samples as u64 * 99).div_ceil(100);
+        let mut seen = 0u64;
+        let mut p99_ns = max_ns;
+        for (i, &count) in self.buckets.iter().enumerate() {
+            seen += count as u64;
+            if seen >= rank {
+                p99_ns = JITTER_BOUNDS_NS.get(i).copied().unwrap_or(max_ns).min(max_ns);
+                break;
+            }
+        }
+        JitterReport {
+            samples: self.samples,
+            mean_ns: (self.sum_cycles * 1_000 / (CPU_FREQ_HZ / 1_000_000) as u64 / self.samples as u64) as u32,
+            p99_ns,
+            max_ns,
+        }
+    }
+}
+
+/// 抖动汇总
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JitterReport {
+    /// 采样次数 (触发间隔数)
+    pub samples: u32,
+    /// 平均抖动 (ns)
+    pub mean_ns: u32,
+    /// 99 分位 (ns，桶上界)
+    pub p99_ns: u32,
+    /// 最大抖动 (ns)
+    pub max_ns: u32,
+}
+
+impl fmt::Display for JitterReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} samples, mean {} ns, p99 <= {} ns, max {} ns",
+            self.samples, self.mean_ns, self.p99_ns, self.max_ns
+        )
+    }
+}
+
+// ===== 结果交接 =====
+
+/// 中断回调 → 任务的结果交接 (池化通道)
+///
+/// `post` 只做一次池分配与入队，可在中断中调用；池满时丢弃并计数。
+pub struct Handoff<T: 'static, const N: usize> {
+    channel: PooledChannel<T, N>,
+    dropped: AtomicU32,
+}
+
+impl<T: Send + 'static, const N: usize> Handoff<T, N> {
+    /// 创建
+    pub const fn new() -> Self {
+        Self { channel: PooledChannel::new(), dropped: AtomicU32::new(0) }
+    }
+
+    /// 投递结果 (非阻塞)
+    ///
+    /// # 返回
+    /// 池已满、结果被丢弃时返回 `false`
+    #[inline]
+    pub fn post(&'static self, value: T) -> bool {
+        if self.channel.try_send(value).is_ok() {
+            return true;
+        }
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+        false
+    }
+
+    /// 接收结果
+    pub async fn receive(&self) -> Pooled<T, N> {
+        self.channel.receive().await
+    }
+
+    /// 尝试接收 (非阻塞)
+    pub fn try_receive(&self) -> Option<Pooled<T, N>> {
+        self.channel.try_receive()
+    }
+
+    /// 因池满被丢弃的结果数
+    pub fn dropped(&self) -> u32 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl<T: Send + 'static, const N: usize> Default for Handoff<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ===== 中断与槽位 =====
+
+/// 报警事件 (传给回调)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlarmEvent {
+    /// 槽位编号
+    pub slot: u8,
+    /// 自启动以来的触发序号 (从 1 开始)
+    pub count: u32,
+    /// 本次触发相对期望时刻的抖动 (ns，首次触发为 0)
+    pub jitter_ns: u32,
+}
+
+/// 报警回调 (中断上下文，应标注 `#[ram]`)
+pub type AlarmCallback = fn(&AlarmEvent);
+
+/// 槽位运行状态
+#[derive(Clone, Copy)]
+struct SlotState {
+    callback: Option<AlarmCallback>,
+    /// 单次报警: 首次触发后停止
+    one_shot: bool,
+    /// 期望触发间隔 (CPU 周期)
+    expected_cycles: u32,
+    /// 上次触发时的 CCOUNT
+    last_cycles: Option<u32>,
+    count: u32,
+    jitter: JitterStats,
+}
+
+impl SlotState {
+    const fn new() -> Self {
+        Self { callback: None, one_shot: false, expected_cycles: 0, last_cycles: None, count: 0, jitter: JitterStats::new() }
+    }
+
+    /// 记录一次触发，返回要传给回调的事件
+    fn on_fire(&mut self, slot: u8, now: u32) -> AlarmEvent {
+        let jitter = match self.last_cycles {
+            Some(last) if !self.one_shot => self.jitter.record(self.expected_cycles, now.wrapping_sub(last)),
+            _ => 0,
+        };
+        self.last_cycles = Some(now);
+        self.count = self.count.wrapping_add(1);
+        AlarmEvent { slot, count: self.count, jitter_ns: cycles_to_ns(jitter) }
+    }
+}
+
+struct Slot {
+    timer: BlockingMutex<CriticalSectionRawMutex, RefCell<Option<PeriodicTimer<'static, Blocking>>>>,
+    state: BlockingMutex<CriticalSectionRawMutex, RefCell<SlotState>>,
+}
+
+impl Slot {
+    const fn new() -> Self {
+        Self { timer: BlockingMutex::new(RefCell::new(None)), state: BlockingMutex::new(RefCell::new(SlotState::new())) }
+    }
+}
+
+static SLOTS: [Slot; GPTIMER_SLOTS] = [Slot::new(), Slot::new(), Slot::new()];
+
+/// 已占用槽位位图
+static CLAIMED: AtomicU8 = AtomicU8::new(0);
+
+/// 报警中断分发 (IRAM)
+#[ram]
+fn dispatch(index: usize) {
+    let now = cycle_count();
+    let slot = &SLOTS[index];
+    let one_shot = slot.state.lock(|state| state.borrow().one_shot);
+    slot.timer.lock(|timer| {
+        if let Some(timer) = timer.borrow_mut().as_mut() {
+            timer.clear_interrupt();
+            if one_shot {
+                let _ = timer.cancel();
+            }
+        }
+    });
+
+    let (callback, event) = slot.state.lock(|state| {
+        let mut state = state.borrow_mut();
+        let event = state.on_fire(index as u8, now);
+        (state.callback, event)
+    });
+    // 回调在锁外执行，可以继续使用临界区 (`Handoff::post`)
+    if let Some(callback) = callback {
+        callback(&event);
+    }
+}
+
+#[handler(priority = Priority::Priority3)]
+#[ram]
+fn gptimer_slot0() {
+    dispatch(0);
+}
+
+#[handler(priority = Priority::Priority3)]
+#[ram]
+fn gptimer_slot1() {
+    dispatch(1);
+}
+
+#[handler(priority = Priority::Priority3)]
+#[ram]
+fn gptimer_slot2() {
+    dispatch(2);
+}
+
+// ===== 驱动 =====
+
+/// 硬件定时器报警
+pub struct GpTimer {
+    slot: usize,
+    /// 周期模式的周期 (挂起后恢复用)
+    period_us: Option<u32>,
+}
+
+impl GpTimer {
+    /// 占用一个槽位并绑定报警中断 (尚未启动)
+    pub fn new(timer: impl Timer + 'static, callback: AlarmCallback) -> Result<Self, GpTimerError> {
+        let slot = (0..GPTIMER_SLOTS)
+            .find(|&i| CLAIMED.fetch_or(1 << i, Ordering::AcqRel) & (1 << i) == 0)
+            .ok_or(GpTimerError::Busy)?;
+
+        let mut periodic = PeriodicTimer::new(timer);
+        periodic.set_interrupt_handler(match slot {
+            0 => gptimer_slot0,
+            1 => gptimer_slot1,
+            _ => gptimer_slot2,
+        });
+        periodic.listen();
+
+        SLOTS[slot].state.lock(|state| {
+            *state.borrow_mut() = SlotState { callback: Some(callback), ..SlotState::new() };
+        });
+        SLOTS[slot].timer.lock(|t| *t.borrow_mut() = Some(periodic));
+        Ok(Self { slot, period_us: None })
+    }
+
+    /// 槽位编号 (与 `AlarmEvent::slot` 对应)
+    pub fn slot(&self) -> u8 {
+        self.slot as u8
+    }
+
+    /// 以 `period_us` 为周期重复触发
+    pub fn start_periodic(&mut self, period_us: u32) -> Result<(), GpTimerError> {
+        self.start(period_us, false)
+    }
+
+    /// `delay_us` 后触发一次
+    pub fn start_once(&mut self, delay_us: u32) -> Result<(), GpTimerError> {
+        self.start(delay_us, true)
+    }
+
+    fn start(&mut self, us: u32, one_shot: bool) -> Result<(), GpTimerError> {
+        if us < MIN_PERIOD_US {
+            return Err(GpTimerError::InvalidPeriod);
+        }
+        let slot = &SLOTS[self.slot];
+        slot.state.lock(|state| {
+            let mut state = state.borrow_mut();
+            state.one_shot = one_shot;
+            state.expected_cycles = us_to_cycles(us);
+            state.last_cycles = None;
+            state.count = 0;
+        });
+        slot.timer.lock(|timer| match timer.borrow_mut().as_mut() {
+            Some(timer) => timer.start(esp_hal::time::Duration::from_micros(us as u64)).map_err(|_| GpTimerError::Config),
+            None => Err(GpTimerError::Config),
+        })?;
+        self.period_us = if one_shot { None } else { Some(us) };
+        Ok(())
+    }
+
+    /// 停止报警
+    pub fn stop(&mut self) {
+        self.period_us = None;
+        self.cancel();
+    }
+
+    fn cancel(&self) {
+        SLOTS[self.slot].timer.lock(|timer| {
+            if let Some(timer) = timer.borrow_mut().as_mut() {
+                let _ = timer.cancel();
+            }
+        });
+    }
+
+    /// 自上次启动以来的触发次数
+    pub fn fire_count(&self) -> u32 {
+        SLOTS[self.slot].state.lock(|state| state.borrow().count)
+    }
+
+    /// 抖动统计 (跨多次启动累计，`reset_jitter` 清零)
+    pub fn jitter(&self) -> JitterReport {
+        SLOTS[self.slot].state.lock(|state| state.borrow().jitter.report())
+    }
+
+    /// 清零抖动统计
+    pub fn reset_jitter(&self) {
+        SLOTS[self.slot].state.lock(|state| state.borrow_mut().jitter = JitterStats::new());
+    }
+
+    /// 抖动基准: 以 `period_us` 周期运行 `samples` 次后返回统计
+    ///
+    /// 回调照常执行，测得的抖动包含回调在前一次触发中的耗时影响。
+    pub async fn characterize(&mut self, period_us: u32, samples: u32) -> Result<JitterReport, GpTimerError> {
+        self.reset_jitter();
+        self.start_periodic(period_us)?;
+        // 触发间隔数 = 触发次数 - 1
+        while self.fire_count() <= samples {
+            EmbassyTimer::after(Duration::from_millis(1)).await;
+        }
+        self.stop();
+        Ok(self.jitter())
+    }
+}
+
+impl DriverLifecycle for GpTimer {
+    fn name(&self) -> &'static str {
+        "gptimer"
+    }
+
+    /// 取消报警；周期模式保留周期，单次报警直接丢弃
+    async fn suspend(&mut self, _kind: SleepKind) -> Result<(), LifecycleError> {
+        self.cancel();
+        Ok(())
+    }
+
+    /// 按原周期重新启动 (相位与抖动基准从头开始)
+    async fn resume(&mut self) -> Result<(), LifecycleError> {
+        match self.period_us {
+            Some(us) => self.start_periodic(us).map_err(|_| LifecycleError::Hardware),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for GpTimer {
+    fn drop(&mut self) {
+        let slot = &SLOTS[self.slot];
+        slot.timer.lock(|timer| {
+            if let Some(mut timer) = timer.borrow_mut().take() {
+                let _ = timer.cancel();
+                timer.unlisten();
+            }
+        });
+        slot.state.lock(|state| *state.borrow_mut() = SlotState::new());
+        CLAIMED.fetch_and(!(1u8 << self.slot), Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jitter_stats() {
+        let mut stats = JitterStats::new();
+        let period = us_to_cycles(50);
+        assert_eq!(period, 12_000);
+
+        // 98 次准时，1 次滞后 240 周期 (1μs)，1 次提前 24 周期 (100ns)
+        for _ in 0..98 {
+            stats.record(period, period);
+        }
+        assert_eq!(stats.record(period, period + 240), 240);
+        assert_eq!(stats.record(period, period - 24), 24);
+
+        let report = stats.report();
+        assert_eq!(report, JitterReport { samples: 100, mean_ns: 11, p99_ns: 100, max_ns: 1_000 });
+        assert_eq!(JitterStats::new().report(), JitterReport::default());
+    }
+
+    #[test]
+    fn test_slot_fire_events() {
+        let mut state = SlotState { expected_cycles: 2_400, ..SlotState::new() };
+        assert_eq!(state.on_fire(1, u32::MAX - 100), AlarmEvent { slot: 1, count: 1, jitter_ns: 0 });
+        // CCOUNT 回绕
+        let event = state.on_fire(1, (u32::MAX - 100).wrapping_add(2_400 + 48));
+        assert_eq!(event, AlarmEvent { slot: 1, count: 2, jitter_ns: 200 });
+        assert_eq!(state.jitter.report().samples, 1);
+
+        // 单次报警不计入抖动
+        let mut once = SlotState { one_shot: true, expected_cycles: 2_400, ..SlotState::new() };
+        assert_eq!(once.on_fire(0, 5).jitter_ns, 0);
+        assert_eq!(once.jitter.report().samples, 0);
+    }
+}
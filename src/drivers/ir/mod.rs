@@ -0,0 +1,293 @@
+//! 红外遥控收发 (RMT)
+//!
+//! # 特性
+//! - 接收: RMT 捕获脉冲宽度，解码为 NEC / RC5 事件，无法识别时保留原始序列
+//! - 发送: 可配置载波频率与占空比，支持 NEC / RC5 / 原始脉冲
+//! - 长序列 (空调遥控等数百个脉冲) 使用 `PulseSequence`，编码缓冲区
+//!   放在 DMA 可访问的内部 SRAM 中，RMT 中断续填时不会访问 PSRAM
+//!
+//! RMT 时钟分频为 1μs/tick，单个脉冲最长 32767μs。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::drivers::ir::{IrReceiver, IrTransmitter, IrEvent, CarrierConfig};
+//!
+//! let rmt = Rmt::new(peripherals.RMT, Rate::from_mhz(80)).unwrap().into_async();
+//! let mut rx = IrReceiver::new(rmt.channel4, peripherals.GPIO6)?;
+//! let mut tx = IrTransmitter::new(rmt.channel0, peripherals.GPIO7, CarrierConfig::KHZ_38)?;
+//!
+//! match rx.receive().await? {
+//!     IrEvent::Nec { address, command } => log_info!("NEC {} {}", address, command),
+//!     _ => {}
+//! }
+//! tx.send_nec(0x04, 0x08).await?;
+//! ```
+
+pub mod protocol;
+
+use core::fmt;
+
+use esp_hal::gpio::interconnect::{PeripheralInput, PeripheralOutput};
+use esp_hal::gpio::Level;
+use esp_hal::rmt::{
+    Channel, PulseCode, RxChannelAsync, RxChannelConfig, RxChannelCreator, TxChannelAsync, TxChannelConfig,
+    TxChannelCreator,
+};
+use esp_hal::Async;
+use heapless::Vec;
+
+use crate::mem::dma::is_dma_safe;
+use crate::power::lifecycle::DriverLifecycle;
+use crate::power::peripherals::{self, KeepAlive, Peripheral};
+
+pub use protocol::{decode, encode_nec, encode_rc5, IrEvent, Pulse};
+
+/// RMT 源时钟分频 (80MHz / 80 = 1μs)
+const CLK_DIVIDER: u8 = 80;
+
+/// 接收缓冲区 (PulseCode 数量，每个包含两个脉冲)
+pub const RX_CODES: usize = 64;
+
+/// 帧结束判定的空闲时间 (μs)
+const IDLE_THRESHOLD_US: u16 = 12_000;
+
+/// 毛刺滤波 (μs 以下的脉冲丢弃)
+const FILTER_THRESHOLD: u8 = 100;
+
+// ===== 错误类型 =====
+
+/// 红外驱动错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IrError {
+    /// RMT 通道配置失败
+    Config,
+    /// 收发失败
+    Transfer,
+    /// 序列超出缓冲区
+    SequenceTooLong,
+    /// 缓冲区不在 DMA 可访问内存中
+    BufferNotInternal,
+}
+
+impl fmt::Display for IrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IrError::Config => write!(f, "RMT channel configuration failed"),
+            IrError::Transfer => write!(f, "RMT transfer failed"),
+            IrError::SequenceTooLong => write!(f, "Pulse sequence too long"),
+            IrError::BufferNotInternal => write!(f, "Pulse buffer must be in internal SRAM"),
+        }
+    }
+}
+
+// ===== 载波 =====
+
+/// 载波配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CarrierConfig {
+    /// 载波频率 (Hz)
+    pub frequency_hz: u32,
+    /// 占空比 (%)
+    pub duty_percent: u8,
+}
+
+impl CarrierConfig {
+    /// 38kHz / 33% (NEC 及大多数遥控器)
+    pub const KHZ_38: Self = Self { frequency_hz: 38_000, duty_percent: 33 };
+    /// 36kHz / 33% (RC5)
+    pub const KHZ_36: Self = Self { frequency_hz: 36_000, duty_percent: 33 };
+
+    /// 计算载波高/低电平的 RMT 源时钟周期数 (80MHz)
+    fn ticks(&self) -> (u16, u16) {
+        let period = 80_000_000 / self.frequency_hz.max(1);
+        let high = period * self.duty_percent.min(100) as u32 / 100;
+        (high as u16, (period - high) as u16)
+    }
+}
+
+// ===== 长序列 =====
+
+/// 预编码的 RMT 脉冲序列
+///
+/// 内部按 32 字节对齐，声明为 `static` 时位于内部 SRAM
+#[repr(C, align(32))]
+pub struct PulseSequence<const N: usize> {
+    codes: [PulseCode; N],
+    len: usize,
+}
+
+impl<const N: usize> PulseSequence<N> {
+    /// 创建空序列
+    pub const fn new() -> Self {
+        Self { codes: [PulseCode::end_marker(); N], len: 0 }
+    }
+
+    /// 从脉冲列表编码 (两个脉冲打包为一个 PulseCode，末尾追加结束标记)
+    pub fn encode(&mut self, pulses: &[Pulse]) -> Result<(), IrError> {
+        let needed = pulses.len().div_ceil(2) + 1;
+        if needed > N {
+            return Err(IrError::SequenceTooLong);
+        }
+        self.len = 0;
+        for pair in pulses.chunks(2) {
+            let first = pair[0];
+            let second = pair.get(1).copied().unwrap_or(Pulse::space(0));
+            self.codes[self.len] = to_code(first, second);
+            self.len += 1;
+        }
+        self.codes[self.len] = PulseCode::end_marker();
+        self.len += 1;
+        Ok(())
+    }
+
+    /// 编码后的 PulseCode
+    pub fn codes(&self) -> &[PulseCode] {
+        &self.codes[..self.len]
+    }
+
+    /// 检查缓冲区位置是否适合中断续填 (内部 SRAM)
+    pub fn is_internal(&self) -> bool {
+        is_dma_safe(self.codes.as_ptr(), N * core::mem::size_of::<PulseCode>())
+    }
+}
+
+impl<const N: usize> Default for PulseSequence<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[inline]
+fn level(mark: bool) -> Level {
+    if mark { Level::High } else { Level::Low }
+}
+
+#[inline]
+fn to_code(a: Pulse, b: Pulse) -> PulseCode {
+    PulseCode::new(level(a.mark), a.duration_us.min(0x7FFF), level(b.mark), b.duration_us.min(0x7FFF))
+}
+
+// ===== 接收 =====
+
+/// 红外接收器
+///
+/// 一体化接收头 (TSOP38238 等) 输出低电平有效，默认按反相处理
+pub struct IrReceiver<'d> {
+    channel: Channel<'d, Async, esp_hal::rmt::Rx>,
+    active_low: bool,
+    codes: [PulseCode; RX_CODES],
+    _power: KeepAlive,
+}
+
+impl<'d> IrReceiver<'d> {
+    /// 创建接收器
+    pub fn new<C: RxChannelCreator<'d, Async>>(creator: C, pin: impl PeripheralInput<'d>) -> Result<Self, IrError> {
+        let power = peripherals::keep_alive(Peripheral::Rmt);
+        let config = RxChannelConfig::default()
+            .with_clk_divider(CLK_DIVIDER)
+            .with_idle_threshold(IDLE_THRESHOLD_US)
+            .with_filter_threshold(FILTER_THRESHOLD);
+        let channel = creator.configure_rx(pin, config).map_err(|_| IrError::Config)?;
+        Ok(Self { channel, active_low: true, codes: [PulseCode::end_marker(); RX_CODES], _power: power })
+    }
+
+    /// 接收头为高电平有效时调用
+    pub fn with_active_high(mut self) -> Self {
+        self.active_low = false;
+        self
+    }
+
+    /// 接收一帧原始脉冲
+    pub async fn receive_raw(&mut self) -> Result<Vec<Pulse, { RX_CODES * 2 }>, IrError> {
+        self.codes.fill(PulseCode::end_marker());
+        self.channel.receive(&mut self.codes).await.map_err(|_| IrError::Transfer)?;
+
+        let mut pulses = Vec::new();
+        'outer: for code in self.codes.iter() {
+            for (lvl, len) in [(code.level1(), code.length1()), (code.level2(), code.length2())] {
+                if len == 0 {
+                    break 'outer;
+                }
+                let mark = (lvl == Level::Low) == self.active_low;
+                if pulses.push(Pulse { mark, duration_us: len }).is_err() {
+                    break 'outer;
+                }
+            }
+        }
+        Ok(pulses)
+    }
+
+    /// 接收并解码一帧
+    pub async fn receive(&mut self) -> Result<IrEvent, IrError> {
+        let pulses = self.receive_raw().await?;
+        Ok(decode(&pulses))
+    }
+}
+
+impl DriverLifecycle for IrReceiver<'_> {
+    fn name(&self) -> &'static str {
+        "ir-rx"
+    }
+}
+
+// ===== 发送 =====
+
+/// 红外发射器
+pub struct IrTransmitter<'d> {
+    channel: Channel<'d, Async, esp_hal::rmt::Tx>,
+    _power: KeepAlive,
+}
+
+impl<'d> IrTransmitter<'d> {
+    /// 创建发射器
+    pub fn new<C: TxChannelCreator<'d, Async>>(
+        creator: C,
+        pin: impl PeripheralOutput<'d>,
+        carrier: CarrierConfig,
+    ) -> Result<Self, IrError> {
+        let power = peripherals::keep_alive(Peripheral::Rmt);
+        let (high, low) = carrier.ticks();
+        let config = TxChannelConfig::default()
+            .with_clk_divider(CLK_DIVIDER)
+            .with_idle_output(true)
+            .with_idle_output_level(Level::Low)
+            .with_carrier_modulation(true)
+            .with_carrier_high(high)
+            .with_carrier_low(low)
+            .with_carrier_level(Level::High);
+        let channel = creator.configure_tx(pin, config).map_err(|_| IrError::Config)?;
+        Ok(Self { channel, _power: power })
+    }
+
+    /// 发送 NEC
+    pub async fn send_nec(&mut self, address: u16, command: u8) -> Result<(), IrError> {
+        self.send_pulses::<36>(&encode_nec(address, command)).await
+    }
+
+    /// 发送 RC5
+    pub async fn send_rc5(&mut self, address: u8, command: u8, toggle: bool) -> Result<(), IrError> {
+        self.send_pulses::<16>(&encode_rc5(address, command, toggle)).await
+    }
+
+    /// 发送原始脉冲 (N 为 PulseCode 容量，至少为 脉冲数 / 2 + 1)
+    pub async fn send_pulses<const N: usize>(&mut self, pulses: &[Pulse]) -> Result<(), IrError> {
+        let mut seq = PulseSequence::<N>::new();
+        seq.encode(pulses)?;
+        self.send_sequence(&seq).await
+    }
+
+    /// 发送预编码的长序列
+    pub async fn send_sequence<const N: usize>(&mut self, seq: &PulseSequence<N>) -> Result<(), IrError> {
+        if !seq.is_internal() {
+            return Err(IrError::BufferNotInternal);
+        }
+        self.channel.transmit(seq.codes()).await.map_err(|_| IrError::Transfer)
+    }
+}
+
+impl DriverLifecycle for IrTransmitter<'_> {
+    fn name(&self) -> &'static str {
+        "ir-tx"
+    }
+}
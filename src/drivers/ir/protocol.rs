@@ -0,0 +1,234 @@
+//! 红外协议编解码
+//!
+//! 与硬件无关: 输入/输出均为 `Pulse` 序列 (载波有/无 + 持续时间 μs)。
+
+use heapless::Vec;
+
+/// 单个脉冲
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pulse {
+    /// true = 载波 (mark)，false = 空闲 (space)
+    pub mark: bool,
+    /// 持续时间 (μs)
+    pub duration_us: u16,
+}
+
+impl Pulse {
+    /// 载波脉冲
+    pub const fn mark(duration_us: u16) -> Self {
+        Self { mark: true, duration_us }
+    }
+
+    /// 空闲脉冲
+    pub const fn space(duration_us: u16) -> Self {
+        Self { mark: false, duration_us }
+    }
+}
+
+/// 解码结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IrEvent {
+    /// NEC 按键 (扩展 NEC 时 address 为 16 位)
+    Nec { address: u16, command: u8 },
+    /// NEC 重复码 (按键保持)
+    NecRepeat,
+    /// RC5 按键
+    Rc5 { address: u8, command: u8, toggle: bool },
+    /// 无法识别的脉冲序列 (长度)
+    Unknown { pulses: u16 },
+}
+
+/// 判断时长是否在容差 (±25%) 内
+#[inline]
+fn near(actual: u16, expected: u16) -> bool {
+    let tolerance = expected / 4;
+    actual.abs_diff(expected) <= tolerance
+}
+
+// ===== NEC =====
+
+const NEC_LEADER_MARK: u16 = 9000;
+const NEC_LEADER_SPACE: u16 = 4500;
+const NEC_REPEAT_SPACE: u16 = 2250;
+const NEC_BIT_MARK: u16 = 562;
+const NEC_ZERO_SPACE: u16 = 562;
+const NEC_ONE_SPACE: u16 = 1687;
+
+/// 解码 NEC
+pub fn decode_nec(pulses: &[Pulse]) -> Option<IrEvent> {
+    let (leader, rest) = pulses.split_first_chunk::<2>()?;
+    if !leader[0].mark || !near(leader[0].duration_us, NEC_LEADER_MARK) {
+        return None;
+    }
+    if near(leader[1].duration_us, NEC_REPEAT_SPACE) {
+        return Some(IrEvent::NecRepeat);
+    }
+    if !near(leader[1].duration_us, NEC_LEADER_SPACE) || rest.len() < 64 {
+        return None;
+    }
+
+    let mut raw = 0u32;
+    for (i, bit) in rest.chunks_exact(2).take(32).enumerate() {
+        if !bit[0].mark || !near(bit[0].duration_us, NEC_BIT_MARK) {
+            return None;
+        }
+        if near(bit[1].duration_us, NEC_ONE_SPACE) {
+            raw |= 1 << i;
+        } else if !near(bit[1].duration_us, NEC_ZERO_SPACE) {
+            return None;
+        }
+    }
+
+    let [addr, addr_inv, cmd, cmd_inv] = raw.to_le_bytes();
+    if cmd != !cmd_inv {
+        return None;
+    }
+    let address = if addr == !addr_inv {
+        addr as u16
+    } else {
+        // 扩展 NEC: 16 位地址
+        u16::from_le_bytes([addr, addr_inv])
+    };
+    Some(IrEvent::Nec { address, command: cmd })
+}
+
+/// 编码 NEC (address > 0xFF 时使用扩展 NEC)
+pub fn encode_nec(address: u16, command: u8) -> Vec<Pulse, 68> {
+    let mut out = Vec::new();
+    let addr_bytes = if address > 0xFF {
+        address.to_le_bytes()
+    } else {
+        [address as u8, !(address as u8)]
+    };
+    let raw = u32::from_le_bytes([addr_bytes[0], addr_bytes[1], command, !command]);
+
+    let _ = out.push(Pulse::mark(NEC_LEADER_MARK));
+    let _ = out.push(Pulse::space(NEC_LEADER_SPACE));
+    for i in 0..32 {
+        let _ = out.push(Pulse::mark(NEC_BIT_MARK));
+        let space = if raw & (1 << i) != 0 { NEC_ONE_SPACE } else { NEC_ZERO_SPACE };
+        let _ = out.push(Pulse::space(space));
+    }
+    let _ = out.push(Pulse::mark(NEC_BIT_MARK));
+    out
+}
+
+// ===== RC5 =====
+
+const RC5_HALF_BIT: u16 = 889;
+
+/// 解码 RC5 (曼彻斯特编码，14 位)
+pub fn decode_rc5(pulses: &[Pulse]) -> Option<IrEvent> {
+    // 展开为半位电平序列 (RC5 中 mark 的后半位代表 1)
+    let mut halves: Vec<bool, 28> = Vec::new();
+    // 第一个起始位的前半位是 space (接收端看不到)
+    halves.push(false).ok()?;
+    for p in pulses {
+        let count = if near(p.duration_us, RC5_HALF_BIT) {
+            1
+        } else if near(p.duration_us, RC5_HALF_BIT * 2) {
+            2
+        } else {
+            return None;
+        };
+        for _ in 0..count {
+            if halves.push(p.mark).is_err() {
+                break;
+            }
+        }
+    }
+    // 末尾的 space 不可见，补齐
+    while halves.len() < 28 {
+        halves.push(false).ok()?;
+    }
+
+    let mut raw = 0u16;
+    for pair in halves.chunks_exact(2) {
+        let bit = match (pair[0], pair[1]) {
+            (false, true) => 1,
+            (true, false) => 0,
+            _ => return None,
+        };
+        raw = (raw << 1) | bit;
+    }
+
+    // 起始位 S1 必须为 1
+    if raw & 0x2000 == 0 {
+        return None;
+    }
+    // S2 为 0 时表示扩展命令位 (RC5X)
+    let ext = if raw & 0x1000 == 0 { 0x40 } else { 0 };
+    Some(IrEvent::Rc5 {
+        toggle: raw & 0x0800 != 0,
+        address: ((raw >> 6) & 0x1F) as u8,
+        command: (raw & 0x3F) as u8 | ext,
+    })
+}
+
+/// 编码 RC5
+pub fn encode_rc5(address: u8, command: u8, toggle: bool) -> Vec<Pulse, 28> {
+    let s2 = if command & 0x40 != 0 { 0 } else { 1 };
+    let raw: u16 = (1 << 13)
+        | (s2 << 12)
+        | ((toggle as u16) << 11)
+        | (((address & 0x1F) as u16) << 6)
+        | (command & 0x3F) as u16;
+
+    let mut out: Vec<Pulse, 28> = Vec::new();
+    let mut push_half = |mark: bool| {
+        match out.last_mut() {
+            Some(last) if last.mark == mark => last.duration_us += RC5_HALF_BIT,
+            _ => {
+                let _ = out.push(Pulse { mark, duration_us: RC5_HALF_BIT });
+            }
+        }
+    };
+    for i in (0..14).rev() {
+        let bit = raw & (1 << i) != 0;
+        // 1 = space → mark，0 = mark → space
+        push_half(!bit);
+        push_half(bit);
+    }
+    // 去掉开头不可见的 space
+    if out.first().is_some_and(|p| !p.mark) {
+        out.remove(0);
+    }
+    // 去掉结尾 space
+    if out.last().is_some_and(|p| !p.mark) {
+        out.pop();
+    }
+    out
+}
+
+/// 依次尝试所有协议解码
+pub fn decode(pulses: &[Pulse]) -> IrEvent {
+    decode_nec(pulses)
+        .or_else(|| decode_rc5(pulses))
+        .unwrap_or(IrEvent::Unknown { pulses: pulses.len() as u16 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nec_roundtrip() {
+        let pulses = encode_nec(0x04, 0x08);
+        assert_eq!(decode(&pulses), IrEvent::Nec { address: 0x04, command: 0x08 });
+
+        let extended = encode_nec(0x1234, 0x55);
+        assert_eq!(decode(&extended), IrEvent::Nec { address: 0x1234, command: 0x55 });
+    }
+
+    #[test]
+    fn test_nec_repeat() {
+        let pulses = [Pulse::mark(9000), Pulse::space(2250), Pulse::mark(562)];
+        assert_eq!(decode_nec(&pulses), Some(IrEvent::NecRepeat));
+    }
+
+    #[test]
+    fn test_rc5_roundtrip() {
+        let pulses = encode_rc5(5, 35, true);
+        assert_eq!(decode(&pulses), IrEvent::Rc5 { address: 5, command: 35, toggle: true });
+    }
+}
@@ -0,0 +1,21 @@
+//! 外设驱动模块
+//!
+//! 对 esp-hal 外设的高层封装，提供异步接口和统一的错误处理:
+//! - `can`: TWAI (CAN 2.0) 总线驱动
+//! - `spi_slave`: SPI 从机 (协处理器模式)
+//! - `ir`: 红外遥控收发 (RMT，NEC/RC5)
+//! - `onewire`: 1-Wire 总线 (DS18B20) 与 DHT22 温湿度传感器
+//! - `sensor`: 统一传感器接口与注册表
+//! - `pcnt_capture`: 脉冲频率 / 占空比测量 (PCNT 计数 + MCPWM 捕获)
+//! - `ulp`: ULP RISC-V 协处理器 (程序加载、启停、RTC 内存共享邮箱)
+//! - `gptimer`: 硬件定时器微秒级报警回调 (IRAM 中断、池化通道交接结果、抖动统计)
+//!
+//! 各驱动实现 `power::lifecycle::DriverLifecycle`，睡眠与重启前由持有驱动的任务挂起或关闭。
+pub mod can;
+pub mod spi_slave;
+pub mod ir;
+pub mod onewire;
+pub mod sensor;
+pub mod pcnt_capture;
+pub mod ulp;
+pub mod gptimer;
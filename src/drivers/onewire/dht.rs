@@ -0,0 +1,145 @@
+//! DHT22 (AM2302) 温湿度传感器
+//!
+//! 单总线协议: 主机拉低 ≥1ms 启动，传感器回应 80μs 低 + 80μs 高，
+//! 随后 40 位数据，每位 50μs 低电平后接 26–28μs (0) 或 70μs (1) 高电平。
+//!
+//! 数据接收约 5ms，期间处于临界区；两次读取间隔不得小于 2 秒。
+
+use embassy_time::{Duration, Instant, Timer};
+use esp_hal::gpio::{DriveMode, Flex, OutputConfig, Pull};
+
+use super::{measure_level_us, OneWireError};
+use crate::power::lifecycle::DriverLifecycle;
+
+/// 两次读取的最小间隔
+pub const MIN_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 高电平宽度超过此值判为 1 (μs)
+const BIT_THRESHOLD_US: u32 = 48;
+
+/// DHT22 读数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DhtReading {
+    /// 相对湿度 (0.1 %RH)
+    pub humidity_permille: u16,
+    /// 温度 (0.1 °C)
+    pub temperature_decicelsius: i16,
+}
+
+impl DhtReading {
+    /// 从 5 字节原始数据解析 (校验和在最后一字节)
+    pub fn from_bytes(data: &[u8; 5]) -> Result<Self, OneWireError> {
+        let sum = data[..4].iter().fold(0u8, |acc, &b| acc.wrapping_add(b));
+        if sum != data[4] {
+            return Err(OneWireError::CrcMismatch);
+        }
+        let humidity = u16::from_be_bytes([data[0], data[1]]);
+        let raw_temp = u16::from_be_bytes([data[2] & 0x7F, data[3]]) as i16;
+        let temperature = if data[2] & 0x80 != 0 { -raw_temp } else { raw_temp };
+        Ok(Self { humidity_permille: humidity, temperature_decicelsius: temperature })
+    }
+
+    /// 摄氏度
+    pub fn celsius(&self) -> f32 {
+        self.temperature_decicelsius as f32 / 10.0
+    }
+
+    /// 相对湿度 (%)
+    pub fn humidity(&self) -> f32 {
+        self.humidity_permille as f32 / 10.0
+    }
+}
+
+/// DHT22 传感器
+pub struct Dht22<'d> {
+    pin: Flex<'d>,
+    last_read: Option<Instant>,
+    last_value: Option<DhtReading>,
+}
+
+impl<'d> Dht22<'d> {
+    /// 创建传感器 (开漏 + 上拉)
+    pub fn new(mut pin: Flex<'d>) -> Self {
+        pin.apply_output_config(
+            &OutputConfig::default()
+                .with_drive_mode(DriveMode::OpenDrain)
+                .with_pull(Pull::Up),
+        );
+        pin.set_input_enable(true);
+        pin.set_high();
+        pin.set_output_enable(true);
+        Self { pin, last_read: None, last_value: None }
+    }
+
+    /// 读取温湿度
+    ///
+    /// 距上次读取不足 `MIN_INTERVAL` 时返回缓存值
+    pub async fn read(&mut self) -> Result<DhtReading, OneWireError> {
+        if let (Some(at), Some(value)) = (self.last_read, self.last_value) {
+            if at.elapsed() < MIN_INTERVAL {
+                return Ok(value);
+            }
+        }
+
+        // 启动信号
+        self.pin.set_low();
+        Timer::after(Duration::from_micros(1_100)).await;
+
+        let data = critical_section::with(|_| self.receive())?;
+        self.last_read = Some(Instant::now());
+        let reading = DhtReading::from_bytes(&data)?;
+        self.last_value = Some(reading);
+        Ok(reading)
+    }
+
+    /// 接收响应与 40 位数据 (需在临界区内调用)
+    fn receive(&mut self) -> Result<[u8; 5], OneWireError> {
+        self.pin.set_high();
+
+        // 等待传感器拉低，然后是 80μs 低 + 80μs 高的响应
+        measure_level_us(&self.pin, true, 100).ok_or(OneWireError::NoPresence)?;
+        measure_level_us(&self.pin, false, 100).ok_or(OneWireError::Timeout)?;
+        measure_level_us(&self.pin, true, 100).ok_or(OneWireError::Timeout)?;
+
+        let mut data = [0u8; 5];
+        for i in 0..40 {
+            measure_level_us(&self.pin, false, 80).ok_or(OneWireError::Timeout)?;
+            let high = measure_level_us(&self.pin, true, 100).ok_or(OneWireError::Timeout)?;
+            if high > BIT_THRESHOLD_US {
+                data[i / 8] |= 0x80 >> (i % 8);
+            }
+        }
+        Ok(data)
+    }
+
+    /// 释放引脚
+    pub fn release(self) -> Flex<'d> {
+        self.pin
+    }
+}
+
+impl DriverLifecycle for Dht22<'_> {
+    fn name(&self) -> &'static str {
+        "dht22"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dht_decode() {
+        // 65.2 %RH, 35.1 °C
+        let data = [0x02, 0x8C, 0x01, 0x5F, 0xEE];
+        let r = DhtReading::from_bytes(&data).unwrap();
+        assert_eq!(r.humidity_permille, 652);
+        assert_eq!(r.temperature_decicelsius, 351);
+
+        // 负温度 -10.1 °C
+        let data = [0x02, 0x8C, 0x80, 0x65, 0x73];
+        assert_eq!(DhtReading::from_bytes(&data).unwrap().temperature_decicelsius, -101);
+
+        assert_eq!(DhtReading::from_bytes(&[1, 2, 3, 4, 0]), Err(OneWireError::CrcMismatch));
+    }
+}
@@ -0,0 +1,184 @@
+//! DS18B20 数字温度传感器
+//!
+//! 温度转换期间使用异步定时器等待 (12 位分辨率最长 750ms)，不占用 CPU。
+
+use embassy_time::{Duration, Timer};
+
+use super::{crc8, OneWireBus, OneWireError, Rom};
+
+/// DS18B20 家族码
+pub const FAMILY_CODE: u8 = 0x28;
+
+const CMD_CONVERT_T: u8 = 0x44;
+const CMD_READ_SCRATCHPAD: u8 = 0xBE;
+const CMD_WRITE_SCRATCHPAD: u8 = 0x4E;
+const CMD_COPY_SCRATCHPAD: u8 = 0x48;
+
+/// 转换分辨率
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// 9 位 (0.5°C, 93.75ms)
+    Bits9,
+    /// 10 位 (0.25°C, 187.5ms)
+    Bits10,
+    /// 11 位 (0.125°C, 375ms)
+    Bits11,
+    /// 12 位 (0.0625°C, 750ms)
+    Bits12,
+}
+
+impl Resolution {
+    /// 配置寄存器值
+    const fn config_byte(self) -> u8 {
+        match self {
+            Resolution::Bits9 => 0x1F,
+            Resolution::Bits10 => 0x3F,
+            Resolution::Bits11 => 0x5F,
+            Resolution::Bits12 => 0x7F,
+        }
+    }
+
+    /// 最大转换时间
+    pub const fn conversion_time(self) -> Duration {
+        match self {
+            Resolution::Bits9 => Duration::from_micros(93_750),
+            Resolution::Bits10 => Duration::from_micros(187_500),
+            Resolution::Bits11 => Duration::from_millis(375),
+            Resolution::Bits12 => Duration::from_millis(750),
+        }
+    }
+}
+
+/// 温度读数 (1/16 °C)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Temperature(pub i16);
+
+impl Temperature {
+    /// 毫摄氏度
+    pub fn millicelsius(&self) -> i32 {
+        self.0 as i32 * 625 / 10
+    }
+
+    /// 摄氏度
+    pub fn celsius(&self) -> f32 {
+        self.0 as f32 / 16.0
+    }
+}
+
+/// DS18B20 设备
+pub struct Ds18b20 {
+    rom: Option<Rom>,
+    resolution: Resolution,
+}
+
+impl Ds18b20 {
+    /// 按 ROM 码创建 (校验家族码)
+    pub fn new(rom: Rom) -> Result<Self, OneWireError> {
+        if rom.family() != FAMILY_CODE {
+            return Err(OneWireError::WrongFamily);
+        }
+        Ok(Self { rom: Some(rom), resolution: Resolution::Bits12 })
+    }
+
+    /// 总线上唯一设备 (使用 SKIP ROM 寻址)
+    pub const fn single() -> Self {
+        Self { rom: None, resolution: Resolution::Bits12 }
+    }
+
+    /// ROM 码
+    pub fn rom(&self) -> Option<Rom> {
+        self.rom
+    }
+
+    /// 设置分辨率 (写入暂存器，`persist` 为 true 时同时写入 EEPROM)
+    pub fn set_resolution<B: OneWireBus>(
+        &mut self,
+        bus: &mut B,
+        resolution: Resolution,
+        persist: bool,
+    ) -> Result<(), OneWireError> {
+        bus.select(self.rom.as_ref())?;
+        bus.write_byte(CMD_WRITE_SCRATCHPAD);
+        bus.write_byte(0x7F); // TH
+        bus.write_byte(0x80); // TL
+        bus.write_byte(resolution.config_byte());
+        if persist {
+            bus.select(self.rom.as_ref())?;
+            bus.write_byte(CMD_COPY_SCRATCHPAD);
+        }
+        self.resolution = resolution;
+        Ok(())
+    }
+
+    /// 启动温度转换
+    pub fn start_conversion<B: OneWireBus>(&self, bus: &mut B) -> Result<(), OneWireError> {
+        bus.select(self.rom.as_ref())?;
+        bus.write_byte(CMD_CONVERT_T);
+        Ok(())
+    }
+
+    /// 读取暂存器中的上次转换结果
+    pub fn read_result<B: OneWireBus>(&self, bus: &mut B) -> Result<Temperature, OneWireError> {
+        bus.select(self.rom.as_ref())?;
+        bus.write_byte(CMD_READ_SCRATCHPAD);
+        let mut scratchpad = [0u8; 9];
+        for b in scratchpad.iter_mut() {
+            *b = bus.read_byte();
+        }
+        parse_scratchpad(&scratchpad)
+    }
+
+    /// 转换并读取温度
+    pub async fn read_temperature<B: OneWireBus>(&self, bus: &mut B) -> Result<Temperature, OneWireError> {
+        self.start_conversion(bus)?;
+        Timer::after(self.resolution.conversion_time()).await;
+        self.read_result(bus)
+    }
+}
+
+/// 广播启动总线上所有 DS18B20 的转换
+pub fn start_conversion_all<B: OneWireBus>(bus: &mut B) -> Result<(), OneWireError> {
+    bus.select(None)?;
+    bus.write_byte(CMD_CONVERT_T);
+    Ok(())
+}
+
+/// 解析 9 字节暂存器
+pub fn parse_scratchpad(scratchpad: &[u8; 9]) -> Result<Temperature, OneWireError> {
+    // 全 0xFF 表示设备未响应 (总线被上拉)
+    if scratchpad.iter().all(|&b| b == 0xFF) {
+        return Err(OneWireError::NoPresence);
+    }
+    if crc8(&scratchpad[..8]) != scratchpad[8] {
+        return Err(OneWireError::CrcMismatch);
+    }
+    Ok(Temperature(i16::from_le_bytes([scratchpad[0], scratchpad[1]])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratchpad(raw: i16) -> [u8; 9] {
+        let [lo, hi] = raw.to_le_bytes();
+        let mut sp = [lo, hi, 0x4B, 0x46, 0x7F, 0xFF, 0x01, 0x10, 0];
+        sp[8] = crc8(&sp[..8]);
+        sp
+    }
+
+    #[test]
+    fn test_temperature_conversion() {
+        assert_eq!(Temperature(0x0191).millicelsius(), 25_062);
+        assert_eq!(Temperature(-0x0191).millicelsius(), -25_062);
+        assert_eq!(Temperature(0x07D0).celsius(), 125.0);
+    }
+
+    #[test]
+    fn test_parse_scratchpad() {
+        assert_eq!(parse_scratchpad(&scratchpad(0x0550)), Ok(Temperature(0x0550)));
+        let mut bad = scratchpad(0x0550);
+        bad[0] ^= 1;
+        assert_eq!(parse_scratchpad(&bad), Err(OneWireError::CrcMismatch));
+        assert_eq!(parse_scratchpad(&[0xFF; 9]), Err(OneWireError::NoPresence));
+    }
+}
@@ -0,0 +1,446 @@
+//! 1-Wire 总线驱动
+//!
+//! 软件位操作实现，时隙使用 IRAM 中的 CCOUNT 忙等待，每个时隙在临界区内
+//! 完成 (最长约 70μs)，复位脉冲 (480μs) 不屏蔽中断。
+//!
+//! # 特性
+//! - 复位/存在检测、位/字节读写
+//! - ROM 搜索 (Maxim AN187 算法) 与 CRC8 校验
+//! - DS18B20 温度传感器 (`ds18b20`)
+//! - DHT22 温湿度传感器 (`dht`，单总线但非 1-Wire 协议)
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::drivers::onewire::{OneWire, ds18b20::Ds18b20};
+//!
+//! let mut bus = OneWire::new(Flex::new(peripherals.GPIO8));
+//! let mut search = bus.search();
+//! while let Some(rom) = search.next(&mut bus)? {
+//!     let sensor = Ds18b20::new(rom)?;
+//!     let t = sensor.read_temperature(&mut bus).await?;
+//!     log_info!("{}: {} m°C", rom.serial(), t.millicelsius());
+//! }
+//! ```
+
+pub mod ds18b20;
+pub mod dht;
+
+use core::fmt;
+
+use esp_hal::gpio::{DriveMode, Flex, OutputConfig, Pull};
+use esp_hal::ram;
+
+use crate::config::CPU_FREQ_HZ;
+use crate::power::lifecycle::DriverLifecycle;
+
+// ===== 错误类型 =====
+
+/// 1-Wire 错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OneWireError {
+    /// 复位后无设备应答
+    NoPresence,
+    /// CRC 校验失败
+    CrcMismatch,
+    /// 总线被拉低 (短路或上拉缺失)
+    BusStuckLow,
+    /// 设备家族码不匹配
+    WrongFamily,
+    /// 等待设备响应超时
+    Timeout,
+}
+
+impl fmt::Display for OneWireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OneWireError::NoPresence => write!(f, "No device presence pulse"),
+            OneWireError::CrcMismatch => write!(f, "CRC mismatch"),
+            OneWireError::BusStuckLow => write!(f, "Bus stuck low"),
+            OneWireError::WrongFamily => write!(f, "Unexpected device family"),
+            OneWireError::Timeout => write!(f, "Device response timeout"),
+        }
+    }
+}
+
+// ===== ROM 码 =====
+
+/// 64 位 ROM 码 (家族码 + 48 位序列号 + CRC)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rom(pub [u8; 8]);
+
+impl Rom {
+    /// 家族码
+    pub fn family(&self) -> u8 {
+        self.0[0]
+    }
+
+    /// 48 位序列号
+    pub fn serial(&self) -> u64 {
+        let mut bytes = [0u8; 8];
+        bytes[..6].copy_from_slice(&self.0[1..7]);
+        u64::from_le_bytes(bytes)
+    }
+
+    /// CRC 是否正确
+    pub fn is_valid(&self) -> bool {
+        crc8(&self.0[..7]) == self.0[7]
+    }
+}
+
+/// Dallas/Maxim CRC8 (多项式 x^8 + x^5 + x^4 + 1)
+pub fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for &byte in data {
+        let mut b = byte;
+        for _ in 0..8 {
+            let mix = (crc ^ b) & 1;
+            crc >>= 1;
+            if mix != 0 {
+                crc ^= 0x8C;
+            }
+            b >>= 1;
+        }
+    }
+    crc
+}
+
+// ===== 命令 =====
+
+/// 搜索 ROM
+pub const CMD_SEARCH_ROM: u8 = 0xF0;
+/// 读 ROM (总线上只有一个设备时)
+pub const CMD_READ_ROM: u8 = 0x33;
+/// 匹配 ROM
+pub const CMD_MATCH_ROM: u8 = 0x55;
+/// 跳过 ROM (广播)
+pub const CMD_SKIP_ROM: u8 = 0xCC;
+
+// ===== 总线抽象 =====
+
+/// 1-Wire 位操作
+///
+/// ROM 搜索和设备驱动基于此 trait 实现，便于替换为硬件 (RMT) 实现或测试桩
+pub trait OneWireBus {
+    /// 复位并检测存在脉冲
+    fn reset(&mut self) -> Result<(), OneWireError>;
+
+    /// 写一位
+    fn write_bit(&mut self, bit: bool);
+
+    /// 读一位
+    fn read_bit(&mut self) -> bool;
+
+    /// 写一个字节 (LSB 在前)
+    fn write_byte(&mut self, byte: u8) {
+        for i in 0..8 {
+            self.write_bit(byte & (1 << i) != 0);
+        }
+    }
+
+    /// 读一个字节 (LSB 在前)
+    fn read_byte(&mut self) -> u8 {
+        let mut byte = 0;
+        for i in 0..8 {
+            if self.read_bit() {
+                byte |= 1 << i;
+            }
+        }
+        byte
+    }
+
+    /// 选中设备 (None 表示 SKIP ROM)
+    fn select(&mut self, rom: Option<&Rom>) -> Result<(), OneWireError> {
+        self.reset()?;
+        match rom {
+            Some(rom) => {
+                self.write_byte(CMD_MATCH_ROM);
+                for &b in rom.0.iter() {
+                    self.write_byte(b);
+                }
+            }
+            None => self.write_byte(CMD_SKIP_ROM),
+        }
+        Ok(())
+    }
+}
+
+// ===== 精确延时 =====
+
+/// 读取 CPU 周期计数器
+#[inline(always)]
+fn ccount() -> u32 {
+    let count: u32;
+    unsafe {
+        core::arch::asm!("rsr.ccount {0}", out(reg) count, options(nostack, preserves_flags));
+    }
+    count
+}
+
+/// 忙等待 (μs)，放在 IRAM 中避免 Flash cache miss 引入抖动
+#[ram]
+#[inline(never)]
+pub(crate) fn busy_wait_us(us: u32) {
+    let cycles = us * (CPU_FREQ_HZ / 1_000_000);
+    let start = ccount();
+    while ccount().wrapping_sub(start) < cycles {}
+}
+
+/// 计时: 等待引脚离开 `level`，返回经过的 μs (超时返回 None)
+#[ram]
+#[inline(never)]
+pub(crate) fn measure_level_us(pin: &Flex<'_>, level: bool, timeout_us: u32) -> Option<u32> {
+    let per_us = CPU_FREQ_HZ / 1_000_000;
+    let limit = timeout_us * per_us;
+    let start = ccount();
+    while pin.is_high() == level {
+        if ccount().wrapping_sub(start) > limit {
+            return None;
+        }
+    }
+    Some(ccount().wrapping_sub(start) / per_us)
+}
+
+// ===== GPIO 实现 =====
+
+/// 基于 GPIO 开漏输出的 1-Wire 总线
+///
+/// 需要 4.7kΩ 外部上拉 (内部上拉仅适合短线)
+pub struct OneWire<'d> {
+    pin: Flex<'d>,
+}
+
+impl<'d> OneWire<'d> {
+    /// 创建总线
+    pub fn new(mut pin: Flex<'d>) -> Self {
+        pin.apply_output_config(
+            &OutputConfig::default()
+                .with_drive_mode(DriveMode::OpenDrain)
+                .with_pull(Pull::Up),
+        );
+        pin.set_input_enable(true);
+        pin.set_high();
+        pin.set_output_enable(true);
+        Self { pin }
+    }
+
+    /// 开始 ROM 搜索
+    pub fn search(&self) -> RomSearch {
+        RomSearch::new(CMD_SEARCH_ROM)
+    }
+
+    /// 释放引脚
+    pub fn release(self) -> Flex<'d> {
+        self.pin
+    }
+}
+
+impl DriverLifecycle for OneWire<'_> {
+    fn name(&self) -> &'static str {
+        "onewire"
+    }
+}
+
+impl<'d> OneWireBus for OneWire<'d> {
+    fn reset(&mut self) -> Result<(), OneWireError> {
+        if !self.pin.is_high() {
+            return Err(OneWireError::BusStuckLow);
+        }
+        self.pin.set_low();
+        busy_wait_us(480);
+        let present = critical_section::with(|_| {
+            self.pin.set_high();
+            busy_wait_us(70);
+            !self.pin.is_high()
+        });
+        busy_wait_us(410);
+        if present { Ok(()) } else { Err(OneWireError::NoPresence) }
+    }
+
+    fn write_bit(&mut self, bit: bool) {
+        critical_section::with(|_| {
+            self.pin.set_low();
+            if bit {
+                busy_wait_us(6);
+                self.pin.set_high();
+                busy_wait_us(64);
+            } else {
+                busy_wait_us(60);
+                self.pin.set_high();
+                busy_wait_us(10);
+            }
+        });
+    }
+
+    fn read_bit(&mut self) -> bool {
+        critical_section::with(|_| {
+            self.pin.set_low();
+            busy_wait_us(6);
+            self.pin.set_high();
+            busy_wait_us(9);
+            let bit = self.pin.is_high();
+            busy_wait_us(55);
+            bit
+        })
+    }
+}
+
+// ===== ROM 搜索 =====
+
+/// ROM 搜索状态 (逐个返回总线上的设备)
+pub struct RomSearch {
+    command: u8,
+    rom: [u8; 8],
+    last_discrepancy: u8,
+    done: bool,
+}
+
+impl RomSearch {
+    /// 创建搜索 (`command` 为 0xF0 普通搜索或 0xEC 报警搜索)
+    pub const fn new(command: u8) -> Self {
+        Self { command, rom: [0; 8], last_discrepancy: 0, done: false }
+    }
+
+    /// 查找下一个设备
+    ///
+    /// # 返回
+    /// 搜索结束时返回 `Ok(None)`
+    pub fn next<B: OneWireBus>(&mut self, bus: &mut B) -> Result<Option<Rom>, OneWireError> {
+        if self.done {
+            return Ok(None);
+        }
+        match bus.reset() {
+            Ok(()) => {}
+            Err(OneWireError::NoPresence) => {
+                self.done = true;
+                return Ok(None);
+            }
+            Err(e) => return Err(e),
+        }
+        bus.write_byte(self.command);
+
+        let mut last_zero = 0u8;
+        for bit_index in 1..=64u8 {
+            let byte = ((bit_index - 1) / 8) as usize;
+            let mask = 1u8 << ((bit_index - 1) % 8);
+
+            let id_bit = bus.read_bit();
+            let cmp_bit = bus.read_bit();
+            let direction = match (id_bit, cmp_bit) {
+                // 没有设备响应
+                (true, true) => {
+                    self.done = true;
+                    return Ok(None);
+                }
+                // 所有设备该位一致
+                (a, b) if a != b => a,
+                // 冲突: 按上次分支决定
+                _ => {
+                    let dir = if bit_index < self.last_discrepancy {
+                        self.rom[byte] & mask != 0
+                    } else {
+                        bit_index == self.last_discrepancy
+                    };
+                    if !dir {
+                        last_zero = bit_index;
+                    }
+                    dir
+                }
+            };
+
+            if direction {
+                self.rom[byte] |= mask;
+            } else {
+                self.rom[byte] &= !mask;
+            }
+            bus.write_bit(direction);
+        }
+
+        self.last_discrepancy = last_zero;
+        if last_zero == 0 {
+            self.done = true;
+        }
+
+        let rom = Rom(self.rom);
+        if !rom.is_valid() {
+            return Err(OneWireError::CrcMismatch);
+        }
+        Ok(Some(rom))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 模拟总线: 多个设备的线与行为
+    struct MockBus {
+        devices: [[u8; 8]; 2],
+        active: [bool; 2],
+        bit: usize,
+        phase: u8,
+    }
+
+    impl MockBus {
+        fn bit_of(rom: &[u8; 8], i: usize) -> bool {
+            rom[i / 8] & (1 << (i % 8)) != 0
+        }
+    }
+
+    impl OneWireBus for MockBus {
+        fn reset(&mut self) -> Result<(), OneWireError> {
+            self.active = [true; 2];
+            self.bit = 0;
+            self.phase = 0;
+            Ok(())
+        }
+
+        fn write_byte(&mut self, _byte: u8) {}
+
+        fn write_bit(&mut self, bit: bool) {
+            for (i, dev) in self.devices.iter().enumerate() {
+                if Self::bit_of(dev, self.bit) != bit {
+                    self.active[i] = false;
+                }
+            }
+            self.bit += 1;
+            self.phase = 0;
+        }
+
+        fn read_bit(&mut self) -> bool {
+            let complement = self.phase == 1;
+            self.phase += 1;
+            // 线与: 任一设备拉低即为 0
+            self.devices
+                .iter()
+                .zip(self.active.iter())
+                .filter(|(_, a)| **a)
+                .all(|(dev, _)| Self::bit_of(dev, self.bit) != complement)
+        }
+    }
+
+    fn rom_with_crc(mut rom: [u8; 8]) -> [u8; 8] {
+        rom[7] = crc8(&rom[..7]);
+        rom
+    }
+
+    #[test]
+    fn test_crc8() {
+        // Maxim AN27 样例 ROM: 02 1C B8 01 00 00 00 A2
+        assert_eq!(crc8(&[0x02, 0x1C, 0xB8, 0x01, 0x00, 0x00, 0x00]), 0xA2);
+    }
+
+    #[test]
+    fn test_search_finds_all_devices() {
+        let a = rom_with_crc([0x28, 0x01, 0, 0, 0, 0, 0, 0]);
+        let b = rom_with_crc([0x28, 0x02, 0, 0, 0, 0, 0, 0]);
+        let mut bus = MockBus { devices: [a, b], active: [true; 2], bit: 0, phase: 0 };
+        let mut search = RomSearch::new(CMD_SEARCH_ROM);
+
+        let first = search.next(&mut bus).unwrap().unwrap();
+        let second = search.next(&mut bus).unwrap().unwrap();
+        assert_eq!(search.next(&mut bus).unwrap(), None);
+        assert!(first.0 == a || first.0 == b);
+        assert!(second.0 != first.0);
+    }
+}
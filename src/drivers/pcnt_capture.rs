@@ -0,0 +1,552 @@
+//! 脉冲频率 / 占空比测量 (PCNT + MCPWM 捕获)
+//!
+//! 测量外部信号 (风扇转速、流量计、PWM 反馈):
+//! - `PulseCounter`: PCNT 计数单元统计边沿数，按采样窗口得到频率；同时维护
+//!   64 位累计计数 (流量计总量)。16 位硬件计数器的溢出在软件中处理:
+//!   采样期间按 `max_frequency_hz` 推算的间隔读取计数器，保证两次读取之间最多回绕一次
+//! - `PwmCapture`: MCPWM0 捕获通道在每个边沿记录 80MHz 捕获定时器的值，
+//!   由中断计算周期与高电平时间，得到 12.5ns 分辨率的频率与占空比
+//!
+//! esp-hal 未封装 MCPWM 捕获，捕获部分直接访问 MCPWM0 寄存器；
+//! 使用前须先用 `McPwm::new` 打开 MCPWM0 外设时钟。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::drivers::pcnt_capture::{CaptureChannel, CounterConfig, PulseCounter, PwmCapture};
+//!
+//! // 风扇测速: 每转 2 个脉冲
+//! let pcnt = Pcnt::new(peripherals.PCNT);
+//! let tach = Input::new(peripherals.GPIO10, InputConfig::default().with_pull(Pull::Up));
+//! let mut fan = PulseCounter::new(pcnt.unit0, tach.peripheral_input(), &CounterConfig::new(5_000))?;
+//! let reading = fan.sample(Duration::from_secs(1)).await;
+//! log_info!("fan {} rpm", reading.rpm(2));
+//!
+//! // PWM 占空比
+//! let mcpwm = McPwm::new(peripherals.MCPWM0, PeripheralClockConfig::with_frequency(Rate::from_mhz(40))?);
+//! let mut cap = PwmCapture::new(&mcpwm, CaptureChannel::Cap0, peripherals.GPIO11)?;
+//! let pwm = cap.measure(Duration::from_millis(100)).await?;
+//! log_info!("{} Hz, duty {}", pwm.frequency_hz(), pwm.duty());
+//! ```
+
+use core::cell::Cell;
+use core::fmt;
+use core::marker::PhantomData;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_time::{with_timeout, Duration, Instant, Timer};
+use esp_hal::gpio::interconnect::PeripheralInput;
+use esp_hal::gpio::InputSignal;
+use esp_hal::handler;
+use esp_hal::interrupt::{self, Priority};
+use esp_hal::mcpwm::{McPwm, PwmPeripheral};
+use esp_hal::pcnt::channel::{CtrlMode, EdgeMode};
+use esp_hal::pcnt::unit::Unit;
+use esp_hal::peripherals::{Interrupt, MCPWM0};
+use portable_atomic::{AtomicU8, Ordering};
+
+use crate::power::lifecycle::{DriverLifecycle, LifecycleError, SleepKind};
+use crate::power::peripherals::{self, KeepAlive, Peripheral};
+use crate::sync::primitives::CriticalSignal;
+
+/// PCNT 计数上限 (达到后硬件清零)
+pub const PCNT_LIMIT: i16 = 30_000;
+
+/// 捕获定时器时钟 (APB)
+pub const CAPTURE_CLOCK_HZ: u32 = 80_000_000;
+
+/// APB 时钟 (PCNT 毛刺滤波以 APB 周期计)
+const APB_CLOCK_HZ: u32 = 80_000_000;
+
+/// PCNT 滤波阈值上限 (APB 周期)
+const MAX_FILTER_CYCLES: u32 = 1023;
+
+/// MCPWM 捕获通道数
+pub const CAPTURE_CHANNELS: usize = 3;
+
+// ===== 错误类型 =====
+
+/// 捕获错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureError {
+    /// 外设配置失败
+    Config,
+    /// 捕获通道已被占用
+    Busy,
+    /// 超时内没有检测到完整周期 (信号恒定或频率过低)
+    NoSignal,
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaptureError::Config => write!(f, "Capture peripheral configuration failed"),
+            CaptureError::Busy => write!(f, "Capture channel already in use"),
+            CaptureError::NoSignal => write!(f, "No complete signal period detected"),
+        }
+    }
+}
+
+// ===== PCNT 脉冲计数 =====
+
+/// 计数边沿
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CountEdge {
+    /// 上升沿
+    #[default]
+    Rising,
+    /// 下降沿
+    Falling,
+    /// 双边沿 (计数为脉冲数的 2 倍)
+    Both,
+}
+
+/// 计数器配置
+#[derive(Debug, Clone, Copy)]
+pub struct CounterConfig {
+    /// 计数边沿
+    pub edge: CountEdge,
+    /// 预期最高信号频率 (Hz)，决定溢出处理的读取间隔
+    pub max_frequency_hz: u32,
+    /// 毛刺滤波: 短于该宽度的脉冲被忽略 (ns，最长约 12.8μs，0 表示关闭)
+    pub glitch_filter_ns: u32,
+}
+
+impl CounterConfig {
+    /// 创建配置 (上升沿计数，1μs 毛刺滤波)
+    pub const fn new(max_frequency_hz: u32) -> Self {
+        Self { edge: CountEdge::Rising, max_frequency_hz, glitch_filter_ns: 1_000 }
+    }
+
+    /// 设置计数边沿
+    pub const fn with_edge(mut self, edge: CountEdge) -> Self {
+        self.edge = edge;
+        self
+    }
+
+    /// 设置毛刺滤波宽度
+    pub const fn with_glitch_filter_ns(mut self, ns: u32) -> Self {
+        self.glitch_filter_ns = ns;
+        self
+    }
+
+    /// 滤波阈值 (APB 周期)
+    fn filter_cycles(&self) -> Option<u16> {
+        if self.glitch_filter_ns == 0 {
+            return None;
+        }
+        let cycles = (self.glitch_filter_ns as u64 * APB_CLOCK_HZ as u64 / 1_000_000_000) as u32;
+        Some(cycles.clamp(1, MAX_FILTER_CYCLES) as u16)
+    }
+
+    /// 溢出处理的读取间隔: 最高频率下计满半个量程的时间 (1ms ~ 1s)
+    fn poll_interval(&self) -> Duration {
+        let edges_per_sec = self.max_frequency_hz.max(1) as u64 * if self.edge == CountEdge::Both { 2 } else { 1 };
+        let us = PCNT_LIMIT as u64 / 2 * 1_000_000 / edges_per_sec;
+        Duration::from_micros(us.clamp(1_000, 1_000_000))
+    }
+}
+
+/// 计数器读数增量 (计数器在 `limit` 处清零)
+fn counter_delta(prev: i16, now: i16, limit: i16) -> u32 {
+    if now >= prev {
+        (now - prev) as u32
+    } else {
+        (limit - prev) as u32 + now as u32
+    }
+}
+
+/// 频率读数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FrequencyReading {
+    /// 窗口内的计数
+    pub count: u32,
+    /// 实际窗口长度 (μs)
+    pub window_us: u32,
+    /// 每个脉冲对应的计数 (双边沿为 2)
+    pub counts_per_pulse: u8,
+}
+
+impl FrequencyReading {
+    /// 脉冲频率 (Hz)
+    pub fn frequency_hz(&self) -> f32 {
+        if self.window_us == 0 {
+            return 0.0;
+        }
+        self.count as f32 * 1_000_000.0 / self.window_us as f32 / self.counts_per_pulse.max(1) as f32
+    }
+
+    /// 转速 (每转 `pulses_per_rev` 个脉冲)
+    pub fn rpm(&self, pulses_per_rev: u8) -> f32 {
+        self.frequency_hz() * 60.0 / pulses_per_rev.max(1) as f32
+    }
+}
+
+/// PCNT 脉冲计数器
+pub struct PulseCounter<'d, const NUM: usize> {
+    unit: Unit<'d, NUM>,
+    counts_per_pulse: u8,
+    poll: Duration,
+    last: i16,
+    total: u64,
+    _power: KeepAlive,
+}
+
+impl<'d, const NUM: usize> PulseCounter<'d, NUM> {
+    /// 在计数单元的通道 0 上对 `input` 计数
+    ///
+    /// 开漏输出的测速信号 (风扇) 需要在 `input` 上启用上拉。
+    pub fn new(unit: Unit<'d, NUM>, input: impl PeripheralInput<'d>, config: &CounterConfig) -> Result<Self, CaptureError> {
+        let power = peripherals::keep_alive(Peripheral::Pcnt);
+        unit.set_high_limit(Some(PCNT_LIMIT)).map_err(|_| CaptureError::Config)?;
+        unit.set_low_limit(None).map_err(|_| CaptureError::Config)?;
+        unit.set_filter(config.filter_cycles()).map_err(|_| CaptureError::Config)?;
+
+        let (negative, positive) = match config.edge {
+            CountEdge::Rising => (EdgeMode::Hold, EdgeMode::Increment),
+            CountEdge::Falling => (EdgeMode::Increment, EdgeMode::Hold),
+            CountEdge::Both => (EdgeMode::Increment, EdgeMode::Increment),
+        };
+        let channel = &unit.channel0;
+        channel.set_edge_signal(input);
+        channel.set_ctrl_mode(CtrlMode::Keep, CtrlMode::Keep);
+        channel.set_input_mode(negative, positive);
+
+        unit.clear();
+        unit.resume();
+        Ok(Self {
+            unit,
+            counts_per_pulse: if config.edge == CountEdge::Both { 2 } else { 1 },
+            poll: config.poll_interval(),
+            last: 0,
+            total: 0,
+            _power: power,
+        })
+    }
+
+    /// 读取计数器并更新累计值
+    ///
+    /// 两次调用间隔须小于计数器计满一个量程的时间，`sample` 会自动满足这一点。
+    pub fn update(&mut self) -> u64 {
+        let now = self.unit.value();
+        self.total += counter_delta(self.last, now, PCNT_LIMIT) as u64;
+        self.last = now;
+        self.total
+    }
+
+    /// 累计脉冲数 (最近一次 `update` 时)
+    pub fn total_pulses(&self) -> u64 {
+        self.total / self.counts_per_pulse as u64
+    }
+
+    /// 清零累计值
+    pub fn reset(&mut self) {
+        self.unit.clear();
+        self.last = 0;
+        self.total = 0;
+    }
+
+    /// 在 `window` 时长内计数，返回频率读数
+    pub async fn sample(&mut self, window: Duration) -> FrequencyReading {
+        let start_total = self.update();
+        let start = Instant::now();
+        let end = start + window;
+        loop {
+            let now = Instant::now();
+            if now >= end {
+                break;
+            }
+            Timer::at((now + self.poll).min(end)).await;
+            self.update();
+        }
+        FrequencyReading {
+            count: (self.total - start_total).min(u32::MAX as u64) as u32,
+            window_us: start.elapsed().as_micros() as u32,
+            counts_per_pulse: self.counts_per_pulse,
+        }
+    }
+}
+
+impl<const NUM: usize> DriverLifecycle for PulseCounter<'_, NUM> {
+    fn name(&self) -> &'static str {
+        "pcnt"
+    }
+
+    /// 结算累计值后暂停计数 (睡眠期间的脉冲不计入)
+    async fn suspend(&mut self, _kind: SleepKind) -> Result<(), LifecycleError> {
+        self.update();
+        self.unit.pause();
+        Ok(())
+    }
+
+    async fn resume(&mut self) -> Result<(), LifecycleError> {
+        self.unit.resume();
+        Ok(())
+    }
+}
+
+// ===== MCPWM 捕获 =====
+
+/// MCPWM0 捕获通道
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureChannel {
+    Cap0,
+    Cap1,
+    Cap2,
+}
+
+impl CaptureChannel {
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    fn signal(self) -> InputSignal {
+        match self {
+            CaptureChannel::Cap0 => InputSignal::PWM0_CAP0,
+            CaptureChannel::Cap1 => InputSignal::PWM0_CAP1,
+            CaptureChannel::Cap2 => InputSignal::PWM0_CAP2,
+        }
+    }
+}
+
+/// PWM 读数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PwmReading {
+    /// 周期 (捕获时钟周期)
+    pub period_ticks: u32,
+    /// 高电平时间 (捕获时钟周期)
+    pub high_ticks: u32,
+}
+
+impl PwmReading {
+    /// 频率 (Hz)
+    pub fn frequency_hz(&self) -> f32 {
+        if self.period_ticks == 0 {
+            return 0.0;
+        }
+        CAPTURE_CLOCK_HZ as f32 / self.period_ticks as f32
+    }
+
+    /// 占空比 (0.0 ~ 1.0)
+    pub fn duty(&self) -> f32 {
+        if self.period_ticks == 0 {
+            return 0.0;
+        }
+        self.high_ticks as f32 / self.period_ticks as f32
+    }
+
+    /// 周期 (ns)
+    pub fn period_ns(&self) -> u64 {
+        self.period_ticks as u64 * 1_000_000_000 / CAPTURE_CLOCK_HZ as u64
+    }
+
+    /// 高电平时间 (ns)
+    pub fn high_ns(&self) -> u64 {
+        self.high_ticks as u64 * 1_000_000_000 / CAPTURE_CLOCK_HZ as u64
+    }
+}
+
+/// 边沿时间戳 → 周期 / 高电平时间
+///
+/// 每个上升沿结束一个周期; 32 位捕获值按回绕差计算 (80MHz 下约 53 秒回绕一次)。
+#[derive(Debug, Clone, Copy, Default)]
+struct EdgeTracker {
+    last_rise: Option<u32>,
+    high: Option<u32>,
+    latest: Option<PwmReading>,
+}
+
+impl EdgeTracker {
+    const fn new() -> Self {
+        Self { last_rise: None, high: None, latest: None }
+    }
+
+    fn on_edge(&mut self, rising: bool, ticks: u32) -> Option<PwmReading> {
+        if rising {
+            let reading = match (self.last_rise, self.high) {
+                (Some(rise), Some(high)) => Some(PwmReading { period_ticks: ticks.wrapping_sub(rise), high_ticks: high }),
+                _ => None,
+            };
+            self.last_rise = Some(ticks);
+            self.high = None;
+            if reading.is_some() {
+                self.latest = reading;
+            }
+            reading
+        } else {
+            self.high = self.last_rise.map(|rise| ticks.wrapping_sub(rise));
+            None
+        }
+    }
+}
+
+/// 单个捕获通道的中断共享状态
+struct CaptureSlot {
+    tracker: BlockingMutex<CriticalSectionRawMutex, Cell<EdgeTracker>>,
+    ready: CriticalSignal<PwmReading>,
+}
+
+impl CaptureSlot {
+    const fn new() -> Self {
+        Self { tracker: BlockingMutex::new(Cell::new(EdgeTracker::new())), ready: CriticalSignal::new() }
+    }
+
+    fn reset(&self) {
+        self.tracker.lock(|t| t.set(EdgeTracker::new()));
+        self.ready.reset();
+    }
+}
+
+static SLOTS: [CaptureSlot; CAPTURE_CHANNELS] = [CaptureSlot::new(), CaptureSlot::new(), CaptureSlot::new()];
+
+/// 已占用通道位图
+static CLAIMED: AtomicU8 = AtomicU8::new(0);
+
+#[handler(priority = Priority::Priority2)]
+fn mcpwm0_capture() {
+    let regs = MCPWM0::regs();
+    let status = regs.int_st().read();
+    for n in 0..CAPTURE_CHANNELS as u8 {
+        if !status.cap(n).bit_is_set() {
+            continue;
+        }
+        let ticks = regs.cap_ch(n as usize).read().value().bits();
+        // CAPn_EDGE: 1 表示下降沿
+        let rising = !regs.cap_status().read().cap_edge(n).bit_is_set();
+        regs.int_clr().write(|w| w.cap(n).clear_bit_by_one());
+
+        let slot = &SLOTS[n as usize];
+        let reading = slot.tracker.lock(|cell| {
+            let mut tracker = cell.get();
+            let reading = tracker.on_edge(rising, ticks);
+            cell.set(tracker);
+            reading
+        });
+        if let Some(reading) = reading {
+            slot.ready.signal(reading);
+        }
+    }
+}
+
+/// MCPWM0 捕获通道
+pub struct PwmCapture<'d> {
+    channel: CaptureChannel,
+    _pin: PhantomData<&'d ()>,
+    _power: KeepAlive,
+}
+
+impl<'d> PwmCapture<'d> {
+    /// 在指定捕获通道上测量 `input`
+    ///
+    /// `mcpwm` 仅用于保证 MCPWM0 外设时钟已打开。
+    pub fn new<PWM: PwmPeripheral + 'd>(
+        _mcpwm: &McPwm<'d, PWM>,
+        channel: CaptureChannel,
+        input: impl PeripheralInput<'d>,
+    ) -> Result<Self, CaptureError> {
+        let bit = 1u8 << channel.index();
+        if CLAIMED.fetch_or(bit, Ordering::AcqRel) & bit != 0 {
+            return Err(CaptureError::Busy);
+        }
+
+        let power = peripherals::keep_alive(Peripheral::Mcpwm0);
+        SLOTS[channel.index()].reset();
+        channel.signal().connect_to(&input);
+
+        let regs = MCPWM0::regs();
+        let n = channel.index() as u8;
+        regs.cap_timer_cfg().modify(|_, w| w.cap_timer_en().set_bit());
+        // 双边沿捕获，不分频
+        regs.cap_ch_cfg(channel.index()).write(|w| unsafe { w.en().set_bit().mode().bits(0b11).prescale().bits(0) });
+        regs.int_clr().write(|w| w.cap(n).clear_bit_by_one());
+        regs.int_ena().modify(|_, w| w.cap(n).set_bit());
+
+        unsafe { interrupt::bind_interrupt(Interrupt::MCPWM0, mcpwm0_capture.handler()) };
+        interrupt::enable(Interrupt::MCPWM0, mcpwm0_capture.priority()).map_err(|_| CaptureError::Config)?;
+
+        Ok(Self { channel, _pin: PhantomData, _power: power })
+    }
+
+    /// 最近一次完整周期 (不等待)
+    pub fn latest(&self) -> Option<PwmReading> {
+        SLOTS[self.channel.index()].tracker.lock(|t| t.get().latest)
+    }
+
+    /// 等待下一个完整周期
+    ///
+    /// 超时 (信号恒为高 / 低或频率低于 1/timeout) 返回 `NoSignal`。
+    pub async fn measure(&mut self, timeout: Duration) -> Result<PwmReading, CaptureError> {
+        let slot = &SLOTS[self.channel.index()];
+        slot.ready.reset();
+        with_timeout(timeout, slot.ready.wait()).await.map_err(|_| CaptureError::NoSignal)
+    }
+}
+
+impl DriverLifecycle for PwmCapture<'_> {
+    fn name(&self) -> &'static str {
+        "pwm-capture"
+    }
+
+    /// 关闭捕获中断
+    async fn suspend(&mut self, _kind: SleepKind) -> Result<(), LifecycleError> {
+        let n = self.channel.index() as u8;
+        MCPWM0::regs().int_ena().modify(|_, w| w.cap(n).clear_bit());
+        Ok(())
+    }
+
+    /// 丢弃睡眠前的半个周期后重新打开捕获中断
+    async fn resume(&mut self) -> Result<(), LifecycleError> {
+        let regs = MCPWM0::regs();
+        let n = self.channel.index() as u8;
+        SLOTS[self.channel.index()].reset();
+        regs.int_clr().write(|w| w.cap(n).clear_bit_by_one());
+        regs.int_ena().modify(|_, w| w.cap(n).set_bit());
+        Ok(())
+    }
+}
+
+impl Drop for PwmCapture<'_> {
+    fn drop(&mut self) {
+        let regs = MCPWM0::regs();
+        let n = self.channel.index() as u8;
+        regs.int_ena().modify(|_, w| w.cap(n).clear_bit());
+        regs.cap_ch_cfg(self.channel.index()).modify(|_, w| w.en().clear_bit());
+        CLAIMED.fetch_and(!(1u8 << n), Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counter_wrap() {
+        assert_eq!(counter_delta(100, 250, PCNT_LIMIT), 150);
+        assert_eq!(counter_delta(29_900, 50, PCNT_LIMIT), 150);
+        assert_eq!(counter_delta(7, 7, PCNT_LIMIT), 0);
+
+        let config = CounterConfig::new(20_000).with_edge(CountEdge::Both);
+        // 40k 边沿/秒，半量程 15000 → 375ms
+        assert_eq!(config.poll_interval(), Duration::from_millis(375));
+        assert_eq!(CounterConfig::new(1).with_glitch_filter_ns(100_000).filter_cycles(), Some(1023));
+    }
+
+    #[test]
+    fn test_edge_tracker() {
+        let mut t = EdgeTracker::new();
+        // 首个下降沿之前没有上升沿参考
+        assert_eq!(t.on_edge(false, 10), None);
+        assert_eq!(t.on_edge(true, 100), None);
+        assert_eq!(t.on_edge(false, 125), None);
+        assert_eq!(t.on_edge(true, 200), Some(PwmReading { period_ticks: 100, high_ticks: 25 }));
+        // 捕获值回绕
+        let start = u32::MAX - 30;
+        t.on_edge(true, start);
+        t.on_edge(false, start.wrapping_add(40));
+        let reading = t.on_edge(true, start.wrapping_add(80_000)).unwrap();
+        assert_eq!(reading, PwmReading { period_ticks: 80_000, high_ticks: 40 });
+        assert_eq!(reading.frequency_hz(), 1_000.0);
+        assert_eq!(reading.period_ns(), 1_000_000);
+    }
+}
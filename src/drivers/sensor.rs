@@ -0,0 +1,449 @@
+//! 统一传感器接口与注册表
+//!
+//! 驱动实现 `Sensor` trait 后，由通用的 `sample` 任务周期读取并写入注册表；
+//! 日志记录、遥测等消费者只需遍历注册表，无需为每种传感器编写胶水代码。
+//!
+//! # 特性
+//! - `Sensor` trait: 名称、单位、异步读取
+//! - `Measurement`: 带单位的定点读数 (千分之一单位)
+//! - `SensorRegistry`: 固定容量，保存最新读数、时间戳与错误计数
+//! - 内置适配: 片上温度传感器、DS18B20
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::drivers::sensor::{self, SENSORS, InternalTemperature};
+//!
+//! #[embassy_executor::task]
+//! async fn chip_temp_task(mut s: InternalTemperature<'static>) {
+//!     let id = SENSORS.register(&s).unwrap();
+//!     sensor::sample(&mut s, &SENSORS, id, Duration::from_secs(5)).await;
+//! }
+//!
+//! // 数据记录任务
+//! SENSORS.for_each(|info, reading| {
+//!     if let Some(r) = reading.latest {
+//!         log_info!("{} = {} m{}", info.name, r.milli(), info.unit.symbol());
+//!     }
+//! });
+//! ```
+
+use core::cell::RefCell;
+use core::fmt;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_time::{Duration, Instant, Ticker};
+use heapless::Vec;
+
+use super::onewire::ds18b20::Ds18b20;
+use super::onewire::{OneWire, OneWireError};
+
+/// 全局注册表容量
+pub const MAX_SENSORS: usize = 16;
+
+/// 全局传感器注册表
+pub static SENSORS: SensorRegistry<MAX_SENSORS> = SensorRegistry::new();
+
+// ===== 类型定义 =====
+
+/// 物理单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    /// 摄氏度
+    Celsius,
+    /// 相对湿度 (%RH)
+    RelativeHumidity,
+    /// 伏特
+    Volt,
+    /// 安培
+    Ampere,
+    /// 帕斯卡
+    Pascal,
+    /// 勒克斯
+    Lux,
+    /// 百万分比浓度
+    Ppm,
+    /// 无量纲计数
+    Count,
+}
+
+impl Unit {
+    /// 单位符号
+    pub const fn symbol(&self) -> &'static str {
+        match self {
+            Unit::Celsius => "°C",
+            Unit::RelativeHumidity => "%RH",
+            Unit::Volt => "V",
+            Unit::Ampere => "A",
+            Unit::Pascal => "Pa",
+            Unit::Lux => "lx",
+            Unit::Ppm => "ppm",
+            Unit::Count => "",
+        }
+    }
+}
+
+/// 测量值 (千分之一单位，如 m°C、mV)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Measurement {
+    milli: i32,
+    unit: Unit,
+}
+
+impl Measurement {
+    /// 由千分之一单位创建
+    pub const fn from_milli(milli: i32, unit: Unit) -> Self {
+        Self { milli, unit }
+    }
+
+    /// 由浮点值创建
+    pub fn from_f32(value: f32, unit: Unit) -> Self {
+        let scaled = value * 1000.0;
+        let milli = if scaled >= 0.0 { scaled + 0.5 } else { scaled - 0.5 } as i32;
+        Self { milli, unit }
+    }
+
+    /// 千分之一单位值
+    pub const fn milli(&self) -> i32 {
+        self.milli
+    }
+
+    /// 浮点值
+    pub fn as_f32(&self) -> f32 {
+        self.milli as f32 / 1000.0
+    }
+
+    /// 单位
+    pub const fn unit(&self) -> Unit {
+        self.unit
+    }
+}
+
+/// 传感器错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SensorError {
+    /// 设备未响应
+    NotResponding,
+    /// 数据校验失败
+    Checksum,
+    /// 读数超出量程
+    OutOfRange,
+    /// 总线错误
+    Bus,
+}
+
+impl fmt::Display for SensorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SensorError::NotResponding => write!(f, "Sensor not responding"),
+            SensorError::Checksum => write!(f, "Sensor checksum error"),
+            SensorError::OutOfRange => write!(f, "Reading out of range"),
+            SensorError::Bus => write!(f, "Sensor bus error"),
+        }
+    }
+}
+
+impl From<OneWireError> for SensorError {
+    fn from(e: OneWireError) -> Self {
+        match e {
+            OneWireError::NoPresence | OneWireError::Timeout => SensorError::NotResponding,
+            OneWireError::CrcMismatch => SensorError::Checksum,
+            OneWireError::BusStuckLow | OneWireError::WrongFamily => SensorError::Bus,
+        }
+    }
+}
+
+/// 传感器描述
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SensorInfo {
+    /// 名称 (注册表内唯一)
+    pub name: &'static str,
+    /// 单位
+    pub unit: Unit,
+}
+
+// ===== Sensor trait =====
+
+/// 传感器
+#[allow(async_fn_in_trait)]
+pub trait Sensor {
+    /// 名称
+    fn name(&self) -> &'static str;
+
+    /// 单位
+    fn unit(&self) -> Unit;
+
+    /// 读取一次
+    async fn read(&mut self) -> Result<Measurement, SensorError>;
+
+    /// 描述
+    fn info(&self) -> SensorInfo {
+        SensorInfo { name: self.name(), unit: self.unit() }
+    }
+}
+
+// ===== 注册表 =====
+
+/// 注册表中的传感器句柄
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SensorId(u8);
+
+impl SensorId {
+    /// 索引
+    pub const fn index(&self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// 注册表错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegistryError {
+    /// 注册表已满
+    Full,
+    /// 名称重复
+    Duplicate,
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::Full => write!(f, "Sensor registry full"),
+            RegistryError::Duplicate => write!(f, "Sensor name already registered"),
+        }
+    }
+}
+
+/// 传感器最新状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SensorReading {
+    /// 最新成功读数
+    pub latest: Option<Measurement>,
+    /// 最新读数时间
+    pub timestamp: Option<Instant>,
+    /// 最近一次错误
+    pub last_error: Option<SensorError>,
+    /// 成功次数
+    pub samples: u32,
+    /// 失败次数
+    pub errors: u32,
+}
+
+/// 传感器注册表
+pub struct SensorRegistry<const N: usize> {
+    entries: BlockingMutex<CriticalSectionRawMutex, RefCell<Vec<(SensorInfo, SensorReading), N>>>,
+}
+
+impl<const N: usize> SensorRegistry<N> {
+    /// 创建空注册表
+    pub const fn new() -> Self {
+        Self { entries: BlockingMutex::new(RefCell::new(Vec::new())) }
+    }
+
+    /// 注册传感器
+    pub fn register<S: Sensor>(&self, sensor: &S) -> Result<SensorId, RegistryError> {
+        self.register_info(sensor.info())
+    }
+
+    /// 按描述注册 (适用于外部推送读数的数据源)
+    pub fn register_info(&self, info: SensorInfo) -> Result<SensorId, RegistryError> {
+        self.entries.lock(|entries| {
+            let mut entries = entries.borrow_mut();
+            if entries.iter().any(|(i, _)| i.name == info.name) {
+                return Err(RegistryError::Duplicate);
+            }
+            let id = SensorId(entries.len() as u8);
+            entries
+                .push((info, SensorReading::default()))
+                .map_err(|_| RegistryError::Full)?;
+            Ok(id)
+        })
+    }
+
+    /// 按名称查找
+    pub fn find(&self, name: &str) -> Option<SensorId> {
+        self.entries.lock(|entries| {
+            entries
+                .borrow()
+                .iter()
+                .position(|(i, _)| i.name == name)
+                .map(|idx| SensorId(idx as u8))
+        })
+    }
+
+    /// 写入读取结果
+    pub fn update(&self, id: SensorId, result: Result<Measurement, SensorError>) {
+        self.entries.lock(|entries| {
+            if let Some((_, reading)) = entries.borrow_mut().get_mut(id.index()) {
+                match result {
+                    Ok(m) => {
+                        reading.latest = Some(m);
+                        reading.timestamp = Some(Instant::now());
+                        reading.samples = reading.samples.wrapping_add(1);
+                    }
+                    Err(e) => {
+                        reading.last_error = Some(e);
+                        reading.errors = reading.errors.wrapping_add(1);
+                    }
+                }
+            }
+        });
+    }
+
+    /// 读取单个传感器状态
+    pub fn get(&self, id: SensorId) -> Option<(SensorInfo, SensorReading)> {
+        self.entries.lock(|entries| entries.borrow().get(id.index()).copied())
+    }
+
+    /// 遍历所有传感器 (回调在临界区内执行，应保持简短)
+    pub fn for_each(&self, mut f: impl FnMut(&SensorInfo, &SensorReading)) {
+        self.entries.lock(|entries| {
+            for (info, reading) in entries.borrow().iter() {
+                f(info, reading);
+            }
+        });
+    }
+
+    /// 复制全部状态 (在临界区外处理)
+    pub fn snapshot<const M: usize>(&self) -> Vec<(SensorInfo, SensorReading), M> {
+        let mut out = Vec::new();
+        self.for_each(|info, reading| {
+            let _ = out.push((*info, *reading));
+        });
+        out
+    }
+
+    /// 已注册数量
+    pub fn len(&self) -> usize {
+        self.entries.lock(|entries| entries.borrow().len())
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<const N: usize> Default for SensorRegistry<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 周期采样: 读取传感器并写入注册表 (不返回)
+pub async fn sample<S: Sensor, const N: usize>(
+    sensor: &mut S,
+    registry: &SensorRegistry<N>,
+    id: SensorId,
+    period: Duration,
+) -> ! {
+    let mut ticker = Ticker::every(period);
+    loop {
+        let result = sensor.read().await;
+        registry.update(id, result);
+        ticker.next().await;
+    }
+}
+
+// ===== 内置适配 =====
+
+/// 片上温度传感器
+pub struct InternalTemperature<'d> {
+    tsens: esp_hal::tsens::TemperatureSensor<'d>,
+}
+
+impl<'d> InternalTemperature<'d> {
+    /// 创建
+    pub fn new(tsens: esp_hal::tsens::TemperatureSensor<'d>) -> Self {
+        Self { tsens }
+    }
+}
+
+impl<'d> Sensor for InternalTemperature<'d> {
+    fn name(&self) -> &'static str {
+        "chip_temp"
+    }
+
+    fn unit(&self) -> Unit {
+        Unit::Celsius
+    }
+
+    async fn read(&mut self) -> Result<Measurement, SensorError> {
+        let celsius = self.tsens.get_temperature().to_celsius();
+        Ok(Measurement::from_f32(celsius, Unit::Celsius))
+    }
+}
+
+/// DS18B20 温度传感器适配
+pub struct Ds18b20Sensor<'d> {
+    name: &'static str,
+    bus: OneWire<'d>,
+    device: Ds18b20,
+}
+
+impl<'d> Ds18b20Sensor<'d> {
+    /// 创建 (独占总线)
+    pub fn new(name: &'static str, bus: OneWire<'d>, device: Ds18b20) -> Self {
+        Self { name, bus, device }
+    }
+}
+
+impl<'d> Sensor for Ds18b20Sensor<'d> {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn unit(&self) -> Unit {
+        Unit::Celsius
+    }
+
+    async fn read(&mut self) -> Result<Measurement, SensorError> {
+        let t = self.device.read_temperature(&mut self.bus).await?;
+        Ok(Measurement::from_milli(t.millicelsius(), Unit::Celsius))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(i32);
+
+    impl Sensor for Fixed {
+        fn name(&self) -> &'static str {
+            "fixed"
+        }
+
+        fn unit(&self) -> Unit {
+            Unit::Volt
+        }
+
+        async fn read(&mut self) -> Result<Measurement, SensorError> {
+            Ok(Measurement::from_milli(self.0, Unit::Volt))
+        }
+    }
+
+    #[test]
+    fn test_measurement_rounding() {
+        assert_eq!(Measurement::from_f32(1.2345, Unit::Volt).milli(), 1235);
+        assert_eq!(Measurement::from_f32(-0.0015, Unit::Celsius).milli(), -2);
+    }
+
+    #[test]
+    fn test_registry_update() {
+        let registry: SensorRegistry<2> = SensorRegistry::new();
+        let mut sensor = Fixed(3300);
+        let id = registry.register(&sensor).unwrap();
+        assert_eq!(registry.register(&sensor), Err(RegistryError::Duplicate));
+        assert_eq!(registry.find("fixed"), Some(id));
+
+        let result = embassy_futures::block_on(sensor.read());
+        registry.update(id, result);
+        registry.update(id, Err(SensorError::Checksum));
+
+        let (info, reading) = registry.get(id).unwrap();
+        assert_eq!(info.unit, Unit::Volt);
+        assert_eq!(reading.latest.map(|m| m.milli()), Some(3300));
+        assert_eq!((reading.samples, reading.errors), (1, 1));
+    }
+}
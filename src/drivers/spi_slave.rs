@@ -0,0 +1,473 @@
+//! SPI 从机驱动 (协处理器模式)
+//!
+//! ESP32-S3 作为主 SoC 的外设: 主机发起每一次传输，从机在传输开始前
+//! 预装好待发送数据。消息使用 `LengthCrc` 帧编码，帧内格式:
+//!
+//! | 偏移 | 长度 | 字段 |
+//! |------|------|------|
+//! | 0    | 1    | 消息类型 |
+//! | 1    | 1    | 序号 (响应沿用请求序号) |
+//! | 2    | n    | 负载 |
+//!
+//! # 特性
+//! - DMA 接收环: 多个接收槽轮转，处理上一帧时下一次传输已可进行
+//! - 按消息类型注册同步处理函数，响应在下一次传输中送出
+//! - 未注册的类型交给应用任务，通过 `next_request` / `respond` 异步处理
+//! - 可选握手 GPIO: 有待发送数据时拉高，通知主机发起传输
+//!
+//! `EspSpiSlave` 使用 SPI2，并接管 SPI2 中断: 传输完成 (主机释放 CS) 时由中断唤醒任务，
+//! 同时读取硬件记录的本次传输位数作为接收长度。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::drivers::spi_slave::{SpiSlaveLink, EspSpiSlave};
+//!
+//! fn handle_version(_req: &[u8], resp: &mut [u8]) -> Result<usize, u8> {
+//!     resp[..3].copy_from_slice(&[1, 2, 0]);
+//!     Ok(3)
+//! }
+//!
+//! static LINK: SpiSlaveLink<4, 8> = SpiSlaveLink::new();
+//!
+//! #[embassy_executor::task]
+//! async fn spi_task(transport: EspSpiSlave<'static>) {
+//!     LINK.register(0x01, handle_version).unwrap();
+//!     LINK.run(transport).await;
+//! }
+//!
+//! // 应用任务处理其余消息
+//! let req = LINK.next_request().await;
+//! LINK.respond(req.msg_type, req.seq, b"ok").await;
+//! ```
+
+use core::fmt;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use esp_hal::handler;
+use esp_hal::interrupt::{self, Priority};
+use esp_hal::peripherals::{Interrupt, SPI2};
+use heapless::Vec;
+use portable_atomic::{AtomicU32, Ordering};
+
+use crate::power::lifecycle::{DriverLifecycle, LifecycleError, SleepKind};
+use crate::protocols::framing::{FramingError, LengthCrc};
+use crate::sync::primitives::{CriticalChannel, CriticalSignal};
+use crate::util::log::*;
+
+/// 单次传输的最大长度 (字节)
+pub const SPI_FRAME_SIZE: usize = 256;
+
+/// 消息负载最大长度
+pub const MAX_MESSAGE_PAYLOAD: usize = SPI_FRAME_SIZE - LengthCrc::OVERHEAD - 2;
+
+/// 空闲消息类型 (无数据时发送)
+pub const MSG_IDLE: u8 = 0x00;
+
+/// 错误响应类型 (负载为错误码)
+pub const MSG_ERROR: u8 = 0xFF;
+
+// ===== 错误类型 =====
+
+/// SPI 从机错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpiSlaveError {
+    /// 底层传输失败
+    Transfer,
+    /// 帧编解码失败
+    Framing(FramingError),
+    /// 处理函数表已满
+    HandlerTableFull,
+    /// 负载过长
+    PayloadTooLarge,
+    /// 中断配置失败
+    Interrupt,
+}
+
+impl fmt::Display for SpiSlaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpiSlaveError::Transfer => write!(f, "SPI transfer failed"),
+            SpiSlaveError::Framing(e) => write!(f, "Framing error: {}", e),
+            SpiSlaveError::HandlerTableFull => write!(f, "Handler table full"),
+            SpiSlaveError::PayloadTooLarge => write!(f, "Payload too large"),
+            SpiSlaveError::Interrupt => write!(f, "SPI interrupt setup failed"),
+        }
+    }
+}
+
+impl From<FramingError> for SpiSlaveError {
+    fn from(e: FramingError) -> Self {
+        SpiSlaveError::Framing(e)
+    }
+}
+
+// ===== 传输层 =====
+
+/// 从机传输层
+#[allow(async_fn_in_trait)]
+pub trait SlaveTransport {
+    /// 预装 `tx` 并等待主机完成一次传输
+    ///
+    /// # 返回
+    /// 写入 `rx` 的字节数
+    async fn transfer(&mut self, rx: &mut [u8], tx: &[u8]) -> Result<usize, SpiSlaveError>;
+
+    /// 设置握手信号 (有待发送数据时为 true)
+    fn set_ready(&mut self, _ready: bool) {}
+}
+
+/// 传输完成信号 (附本次传输的位数)
+static TRANS_DONE: CriticalSignal<u32> = CriticalSignal::new();
+
+#[handler(priority = Priority::Priority2)]
+fn spi2_trans_done() {
+    let regs = SPI2::regs();
+    if regs.dma_int_st().read().trans_done().bit_is_set() {
+        regs.dma_int_clr().write(|w| w.trans_done().clear_bit_by_one());
+        TRANS_DONE.signal(regs.slave1().read().data_bitlen().bits());
+    }
+}
+
+/// 基于 esp-hal SPI 从机 DMA 的传输层 (SPI2)
+pub struct EspSpiSlave<'d> {
+    spi: esp_hal::spi::slave::dma::SpiDma<'d, esp_hal::Blocking>,
+    handshake: Option<esp_hal::gpio::Output<'d>>,
+}
+
+impl<'d> EspSpiSlave<'d> {
+    /// 包装已配置 DMA 的 SPI2 从机，并启用传输完成中断
+    pub fn new(spi: esp_hal::spi::slave::dma::SpiDma<'d, esp_hal::Blocking>) -> Result<Self, SpiSlaveError> {
+        let regs = SPI2::regs();
+        regs.dma_int_clr().write(|w| w.trans_done().clear_bit_by_one());
+        regs.dma_int_ena().modify(|_, w| w.trans_done().set_bit());
+        unsafe { interrupt::bind_interrupt(Interrupt::SPI2, spi2_trans_done.handler()) };
+        interrupt::enable(Interrupt::SPI2, spi2_trans_done.priority()).map_err(|_| SpiSlaveError::Interrupt)?;
+        Ok(Self { spi, handshake: None })
+    }
+
+    /// 设置握手 GPIO
+    pub fn with_handshake(mut self, pin: esp_hal::gpio::Output<'d>) -> Self {
+        self.handshake = Some(pin);
+        self
+    }
+}
+
+impl<'d> SlaveTransport for EspSpiSlave<'d> {
+    async fn transfer(&mut self, rx: &mut [u8], tx: &[u8]) -> Result<usize, SpiSlaveError> {
+        let capacity = rx.len();
+        TRANS_DONE.reset();
+        let transfer = self.spi.transfer(rx, tx).map_err(|_| SpiSlaveError::Transfer)?;
+        // 传输由主机时钟驱动，主机释放 CS 后由传输完成中断唤醒
+        let bits = TRANS_DONE.wait().await;
+        transfer.wait().map_err(|_| SpiSlaveError::Transfer)?;
+        Ok((bits as usize / 8).min(capacity))
+    }
+
+    fn set_ready(&mut self, ready: bool) {
+        if let Some(pin) = self.handshake.as_mut() {
+            if ready {
+                pin.set_high();
+            } else {
+                pin.set_low();
+            }
+        }
+    }
+}
+
+impl Drop for EspSpiSlave<'_> {
+    fn drop(&mut self) {
+        SPI2::regs().dma_int_ena().modify(|_, w| w.trans_done().clear_bit());
+    }
+}
+
+impl DriverLifecycle for EspSpiSlave<'_> {
+    fn name(&self) -> &'static str {
+        "spi-slave"
+    }
+
+    /// 拉低握手信号，主机在恢复前不再发起传输
+    async fn suspend(&mut self, _kind: SleepKind) -> Result<(), LifecycleError> {
+        self.set_ready(false);
+        Ok(())
+    }
+}
+
+// ===== 接收环 =====
+
+/// 接收槽
+struct RxSlot {
+    data: [u8; SPI_FRAME_SIZE],
+    len: usize,
+}
+
+/// DMA 接收环
+///
+/// 传输写入 `head` 槽，处理完成后前进；满时覆盖最旧的未处理槽并计数
+pub struct RxRing<const SLOTS: usize> {
+    slots: [RxSlot; SLOTS],
+    head: usize,
+    tail: usize,
+    count: usize,
+    overruns: u32,
+}
+
+impl<const SLOTS: usize> RxRing<SLOTS> {
+    /// 创建接收环
+    pub const fn new() -> Self {
+        const EMPTY: RxSlot = RxSlot { data: [0; SPI_FRAME_SIZE], len: 0 };
+        Self { slots: [EMPTY; SLOTS], head: 0, tail: 0, count: 0, overruns: 0 }
+    }
+
+    /// 下一个可写入的槽
+    fn write_slot(&mut self) -> &mut [u8; SPI_FRAME_SIZE] {
+        &mut self.slots[self.head].data
+    }
+
+    /// 提交已写入的槽
+    fn commit(&mut self, len: usize) {
+        self.slots[self.head].len = len;
+        self.head = (self.head + 1) % SLOTS;
+        if self.count == SLOTS {
+            self.tail = (self.tail + 1) % SLOTS;
+            self.overruns += 1;
+        } else {
+            self.count += 1;
+        }
+    }
+
+    /// 取出最旧的槽
+    fn pop(&mut self) -> Option<&[u8]> {
+        if self.count == 0 {
+            return None;
+        }
+        let slot = &self.slots[self.tail];
+        self.tail = (self.tail + 1) % SLOTS;
+        self.count -= 1;
+        Some(&slot.data[..slot.len])
+    }
+
+    /// 覆盖次数
+    pub fn overruns(&self) -> u32 {
+        self.overruns
+    }
+}
+
+// ===== 消息与路由 =====
+
+/// 同步消息处理函数
+///
+/// 将响应负载写入 `resp`，返回长度；返回 `Err(code)` 时发送错误响应
+pub type MessageHandler = fn(req: &[u8], resp: &mut [u8]) -> Result<usize, u8>;
+
+/// 交给应用任务的请求
+#[derive(Debug, Clone)]
+pub struct SlaveRequest {
+    /// 消息类型
+    pub msg_type: u8,
+    /// 序号
+    pub seq: u8,
+    /// 负载
+    pub payload: Vec<u8, MAX_MESSAGE_PAYLOAD>,
+}
+
+/// 待发送的响应
+#[derive(Debug, Clone)]
+struct SlaveResponse {
+    msg_type: u8,
+    seq: u8,
+    payload: Vec<u8, MAX_MESSAGE_PAYLOAD>,
+}
+
+/// 链路统计
+pub struct LinkStats {
+    /// 收到的有效消息
+    pub rx_messages: AtomicU32,
+    /// 发送的响应
+    pub tx_messages: AtomicU32,
+    /// 帧错误
+    pub frame_errors: AtomicU32,
+    /// 请求队列满丢弃
+    pub dropped: AtomicU32,
+}
+
+/// SPI 从机链路
+///
+/// # Type Parameters
+/// * `H` - 最多注册的处理函数数量
+/// * `Q` - 请求/响应队列深度
+pub struct SpiSlaveLink<const H: usize, const Q: usize> {
+    handlers: BlockingMutex<CriticalSectionRawMutex, core::cell::RefCell<Vec<(u8, MessageHandler), H>>>,
+    requests: CriticalChannel<SlaveRequest, Q>,
+    responses: CriticalChannel<SlaveResponse, Q>,
+    stats: LinkStats,
+}
+
+impl<const H: usize, const Q: usize> SpiSlaveLink<H, Q> {
+    /// 创建链路
+    pub const fn new() -> Self {
+        Self {
+            handlers: BlockingMutex::new(core::cell::RefCell::new(Vec::new())),
+            requests: CriticalChannel::new(),
+            responses: CriticalChannel::new(),
+            stats: LinkStats {
+                rx_messages: AtomicU32::new(0),
+                tx_messages: AtomicU32::new(0),
+                frame_errors: AtomicU32::new(0),
+                dropped: AtomicU32::new(0),
+            },
+        }
+    }
+
+    /// 注册消息类型的处理函数 (重复注册时替换)
+    pub fn register(&self, msg_type: u8, handler: MessageHandler) -> Result<(), SpiSlaveError> {
+        self.handlers.lock(|cell| {
+            let mut table = cell.borrow_mut();
+            if let Some(entry) = table.iter_mut().find(|(t, _)| *t == msg_type) {
+                entry.1 = handler;
+                return Ok(());
+            }
+            table.push((msg_type, handler)).map_err(|_| SpiSlaveError::HandlerTableFull)
+        })
+    }
+
+    /// 等待一个未注册处理函数的请求
+    pub async fn next_request(&self) -> SlaveRequest {
+        self.requests.receive().await
+    }
+
+    /// 发送响应 (在主机的下一次传输中送出)
+    pub async fn respond(&self, msg_type: u8, seq: u8, payload: &[u8]) -> Result<(), SpiSlaveError> {
+        let payload = Vec::from_slice(payload).map_err(|_| SpiSlaveError::PayloadTooLarge)?;
+        self.responses.send(SlaveResponse { msg_type, seq, payload }).await;
+        Ok(())
+    }
+
+    /// 统计信息
+    pub fn stats(&self) -> &LinkStats {
+        &self.stats
+    }
+
+    /// 链路主循环 (永不返回)
+    pub async fn run<T: SlaveTransport>(&self, mut transport: T) -> ! {
+        let mut ring = RxRing::<4>::new();
+        let mut tx = [0u8; SPI_FRAME_SIZE];
+
+        log_info!("SPI slave link started");
+
+        loop {
+            // 预装下一帧: 待发送响应或空闲帧
+            let response = self.responses.try_receive().ok();
+            let (msg_type, seq, payload) = match &response {
+                Some(r) => (r.msg_type, r.seq, r.payload.as_slice()),
+                None => (MSG_IDLE, 0, &[][..]),
+            };
+            encode_message(msg_type, seq, payload, &mut tx);
+            transport.set_ready(response.is_some());
+
+            let result = transport.transfer(ring.write_slot(), &tx).await;
+            match result {
+                Ok(len) => ring.commit(len),
+                Err(_) => {
+                    self.stats.frame_errors.fetch_add(1, Ordering::Relaxed);
+                    continue;
+                }
+            }
+            if response.is_some() {
+                self.stats.tx_messages.fetch_add(1, Ordering::Relaxed);
+            }
+
+            while let Some(frame) = ring.pop() {
+                self.dispatch(frame);
+            }
+        }
+    }
+
+    /// 解码并分发一帧
+    fn dispatch(&self, frame: &[u8]) {
+        let Ok(message) = LengthCrc::decode(frame) else {
+            self.stats.frame_errors.fetch_add(1, Ordering::Relaxed);
+            return;
+        };
+        if message.len() < 2 || message[0] == MSG_IDLE {
+            return;
+        }
+        self.stats.rx_messages.fetch_add(1, Ordering::Relaxed);
+
+        let (msg_type, seq, payload) = (message[0], message[1], &message[2..]);
+        let handler = self.handlers.lock(|cell| {
+            cell.borrow().iter().find(|(t, _)| *t == msg_type).map(|(_, h)| *h)
+        });
+
+        match handler {
+            Some(handler) => {
+                let mut resp = [0u8; MAX_MESSAGE_PAYLOAD];
+                let response = match handler(payload, &mut resp) {
+                    Ok(n) => SlaveResponse {
+                        msg_type,
+                        seq,
+                        payload: Vec::from_slice(&resp[..n.min(MAX_MESSAGE_PAYLOAD)]).unwrap_or_default(),
+                    },
+                    Err(code) => SlaveResponse {
+                        msg_type: MSG_ERROR,
+                        seq,
+                        payload: Vec::from_slice(&[msg_type, code]).unwrap_or_default(),
+                    },
+                };
+                if self.responses.try_send(response).is_err() {
+                    self.stats.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            None => {
+                let request = SlaveRequest {
+                    msg_type,
+                    seq,
+                    payload: Vec::from_slice(payload).unwrap_or_default(),
+                };
+                if self.requests.try_send(request).is_err() {
+                    self.stats.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+}
+
+/// 编码消息到定长发送缓冲区 (不足部分补 0)
+fn encode_message(msg_type: u8, seq: u8, payload: &[u8], out: &mut [u8; SPI_FRAME_SIZE]) {
+    let mut message = [0u8; MAX_MESSAGE_PAYLOAD + 2];
+    let len = payload.len().min(MAX_MESSAGE_PAYLOAD);
+    message[0] = msg_type;
+    message[1] = seq;
+    message[2..2 + len].copy_from_slice(&payload[..len]);
+
+    out.fill(0);
+    // 长度已限制在 MAX_MESSAGE_PAYLOAD 内，编码不会失败
+    let _ = LengthCrc::encode(&message[..2 + len], out);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_roundtrip() {
+        let mut out = [0u8; SPI_FRAME_SIZE];
+        encode_message(0x10, 7, b"ping", &mut out);
+        let message = LengthCrc::decode(&out).unwrap();
+        assert_eq!(message, &[0x10, 7, b'p', b'i', b'n', b'g']);
+    }
+
+    #[test]
+    fn test_rx_ring_overrun() {
+        let mut ring = RxRing::<2>::new();
+        for i in 0..3u8 {
+            ring.write_slot()[0] = i;
+            ring.commit(1);
+        }
+        assert_eq!(ring.overruns(), 1);
+        assert_eq!(ring.pop(), Some(&[1u8][..]));
+        assert_eq!(ring.pop(), Some(&[2u8][..]));
+        assert_eq!(ring.pop(), None);
+    }
+}
@@ -0,0 +1,378 @@
+//! ULP RISC-V 协处理器 (程序加载与共享邮箱)
+//!
+//! ESP32-S3 的 ULP RISC-V 核在主核深度睡眠时仍可运行，用于低功耗采样 GPIO/ADC:
+//! - 程序加载: ULP 从 RTC 慢速内存起始地址执行，`load` / `load_file` 把原始二进制
+//!   (`objcopy -O binary`) 写入保留区，来源可以是 `include_bytes!` 内嵌资源或文件系统
+//! - 启停: 基于 esp-hal `UlpCore`，加载前自动停止正在运行的程序
+//! - 邮箱: 保留区末尾的固定布局结构，深度睡眠期间保持；双方按序号交换数据
+//!
+//! # 邮箱协议
+//!
+//! 布局 (32 位字): `magic, ulp_seq, host_seq, host_ack, to_host[8], to_ulp[8]`
+//! - ULP 写 `to_host` 时按顺序锁规则: 先把 `ulp_seq` 加 1 (奇数表示写入中)，
+//!   写完数据再加 1；主核读到奇数或前后序号不一致时重读
+//! - 主核写 `to_ulp` 后递增 `host_seq`，ULP 比较序号得知新参数
+//! - 主核消费数据后把 `ulp_seq` 写入 `host_ack`，唤醒后据此判断数据是否变化
+//!
+//! 保留区 (`ULP_RESERVE`) 与 esp-hal 的 `rtc_slow` 段重叠，使用本模块时应用不应再
+//! 把变量放入 RTC 慢速内存 (RTC 快速内存不受影响)。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::drivers::ulp::{self, Ulp};
+//!
+//! static ULP_IMAGE: &[u8] = include_bytes!("../ulp/adc_sampler.bin");
+//!
+//! let mut ulp = Ulp::new(peripherals.ULP_RISCV_CORE);
+//! if !ulp.is_loaded(ULP_IMAGE) {
+//!     ulp.load(ULP_IMAGE)?;
+//!     ulp::write_mailbox(&[THRESHOLD_MV]);
+//!     ulp.start();
+//! }
+//!
+//! // 唤醒后检查 ULP 是否更新了数据
+//! if let Some(snapshot) = ulp::notify_changes() {
+//!     log_info!("ulp seq={} adc={}", snapshot.seq, snapshot.data[0]);
+//! }
+//! ```
+
+use core::fmt;
+
+use embassy_time::{Duration, Timer};
+use esp_hal::peripherals::ULP_RISCV_CORE;
+use esp_hal::rtc_cntl::{wakeup_cause, SleepSource};
+use esp_hal::ulp_core::{UlpCore, UlpCoreWakeupSource};
+
+use crate::fs::littlefs::{FileSystem, FsError};
+use crate::fs::OpenOptions;
+use crate::power::lifecycle::{DriverLifecycle, LifecycleError};
+use crate::sync::bus::{self, SystemEvent};
+use crate::sync::primitives::CriticalSignal;
+use crate::util::log::*;
+
+/// RTC 慢速内存起始地址 (ULP 地址 0)
+pub const RTC_SLOW_MEM: usize = 0x5000_0000;
+
+/// ULP 保留区大小 (字节，含邮箱)
+pub const ULP_RESERVE: usize = 4096;
+
+/// 邮箱每个方向的数据字数
+pub const MAILBOX_WORDS: usize = 8;
+
+/// 邮箱魔数 ("ULPM")
+pub const MAILBOX_MAGIC: u32 = 0x4D50_4C55;
+
+/// 邮箱大小 (字节)
+pub const MAILBOX_SIZE: usize = (4 + 2 * MAILBOX_WORDS) * 4;
+
+/// 邮箱在保留区中的偏移 (程序最大长度)
+pub const MAILBOX_OFFSET: usize = ULP_RESERVE - MAILBOX_SIZE;
+
+// 邮箱字索引 (相对邮箱起始)
+const MAGIC: usize = 0;
+const ULP_SEQ: usize = 1;
+const HOST_SEQ: usize = 2;
+const HOST_ACK: usize = 3;
+const TO_HOST: usize = 4;
+const TO_ULP: usize = TO_HOST + MAILBOX_WORDS;
+
+/// 顺序锁读取的最大重试次数
+const READ_RETRIES: usize = 8;
+
+/// 文件加载的分块大小
+const CHUNK: usize = 256;
+
+// ===== 错误类型 =====
+
+/// ULP 错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UlpError {
+    /// 程序为空
+    Empty,
+    /// 程序超出保留区 (邮箱之前的空间)
+    TooLarge,
+    /// 读取程序文件失败
+    Fs(FsError),
+}
+
+impl fmt::Display for UlpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UlpError::Empty => write!(f, "ULP image is empty"),
+            UlpError::TooLarge => write!(f, "ULP image exceeds reserved memory"),
+            UlpError::Fs(e) => write!(f, "ULP image read failed: {:?}", e),
+        }
+    }
+}
+
+impl From<FsError> for UlpError {
+    fn from(e: FsError) -> Self {
+        UlpError::Fs(e)
+    }
+}
+
+// ===== 共享内存 =====
+
+/// 邮箱快照 (ULP → 主核)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Snapshot {
+    /// ULP 侧更新序号
+    pub seq: u32,
+    /// 数据
+    pub data: [u32; MAILBOX_WORDS],
+}
+
+/// ULP 保留区视图 (按字 volatile 访问)
+struct Region {
+    base: *mut u32,
+}
+
+impl Region {
+    /// RTC 慢速内存中的保留区
+    const fn rtc() -> Self {
+        Self { base: RTC_SLOW_MEM as *mut u32 }
+    }
+
+    fn word(&self, index: usize) -> u32 {
+        debug_assert!(index < ULP_RESERVE / 4);
+        // Safety: 索引在保留区内，保留区为本模块独占
+        unsafe { self.base.add(index).read_volatile() }
+    }
+
+    fn set_word(&self, index: usize, value: u32) {
+        debug_assert!(index < ULP_RESERVE / 4);
+        // Safety: 同上
+        unsafe { self.base.add(index).write_volatile(value) }
+    }
+
+    fn mailbox(&self, index: usize) -> u32 {
+        self.word(MAILBOX_OFFSET / 4 + index)
+    }
+
+    fn set_mailbox(&self, index: usize, value: u32) {
+        self.set_word(MAILBOX_OFFSET / 4 + index, value)
+    }
+
+    /// 在字节偏移处写入 (非对齐部分读改写)
+    fn write_bytes(&self, offset: usize, data: &[u8]) {
+        for (i, &byte) in data.iter().enumerate() {
+            let at = offset + i;
+            let shift = (at % 4) * 8;
+            let word = (self.word(at / 4) & !(0xFF << shift)) | ((byte as u32) << shift);
+            self.set_word(at / 4, word);
+        }
+    }
+
+    /// 程序字节是否与保留区内容一致
+    fn matches(&self, image: &[u8]) -> bool {
+        self.mailbox(MAGIC) == MAILBOX_MAGIC
+            && image.len() <= MAILBOX_OFFSET
+            && image.iter().enumerate().all(|(i, &b)| (self.word(i / 4) >> ((i % 4) * 8)) as u8 == b)
+    }
+
+    /// 清零程序区并初始化邮箱
+    fn clear(&self) {
+        for i in 0..ULP_RESERVE / 4 {
+            self.set_word(i, 0);
+        }
+        self.set_mailbox(MAGIC, MAILBOX_MAGIC);
+    }
+
+    /// 顺序锁读取 ULP 数据 (邮箱未初始化或 ULP 持续写入时返回 `None`)
+    fn read(&self) -> Option<Snapshot> {
+        if self.mailbox(MAGIC) != MAILBOX_MAGIC {
+            return None;
+        }
+        for _ in 0..READ_RETRIES {
+            let seq = self.mailbox(ULP_SEQ);
+            if seq & 1 != 0 {
+                continue;
+            }
+            let mut data = [0u32; MAILBOX_WORDS];
+            for (i, word) in data.iter_mut().enumerate() {
+                *word = self.mailbox(TO_HOST + i);
+            }
+            if self.mailbox(ULP_SEQ) == seq {
+                return Some(Snapshot { seq, data });
+            }
+        }
+        None
+    }
+
+    fn write(&self, data: &[u32]) {
+        for (i, &word) in data.iter().take(MAILBOX_WORDS).enumerate() {
+            self.set_mailbox(TO_ULP + i, word);
+        }
+        self.set_mailbox(HOST_SEQ, self.mailbox(HOST_SEQ).wrapping_add(1));
+    }
+
+    /// 取出未确认的更新并确认
+    fn take_change(&self) -> Option<Snapshot> {
+        let snapshot = self.read()?;
+        if snapshot.seq == self.mailbox(HOST_ACK) {
+            return None;
+        }
+        self.set_mailbox(HOST_ACK, snapshot.seq);
+        Some(snapshot)
+    }
+}
+
+// ===== 协处理器 =====
+
+/// ULP RISC-V 协处理器
+pub struct Ulp<'d> {
+    core: UlpCore<'d>,
+    running: bool,
+}
+
+impl<'d> Ulp<'d> {
+    /// 创建 (不影响深度睡眠前已在运行的程序)
+    pub fn new(core: ULP_RISCV_CORE<'d>) -> Self {
+        Self { core: UlpCore::new(core), running: false }
+    }
+
+    /// 保留区中是否已是该程序 (深度睡眠唤醒后无需重新加载)
+    pub fn is_loaded(&self, image: &[u8]) -> bool {
+        Region::rtc().matches(image)
+    }
+
+    /// 加载内嵌程序 (停止当前程序，清空邮箱)
+    pub fn load(&mut self, image: &[u8]) -> Result<(), UlpError> {
+        check_size(image.len())?;
+        self.stop();
+        let region = Region::rtc();
+        region.clear();
+        region.write_bytes(0, image);
+        log_info!("ULP image loaded ({} bytes)", image.len());
+        Ok(())
+    }
+
+    /// 从文件系统加载程序
+    pub fn load_file(&mut self, fs: &FileSystem, path: &str) -> Result<(), UlpError> {
+        let mut file = fs.open(path, OpenOptions::read_only())?;
+        check_size(file.size() as usize)?;
+        self.stop();
+        let region = Region::rtc();
+        region.clear();
+
+        let mut chunk = [0u8; CHUNK];
+        let mut offset = 0;
+        loop {
+            let n = file.read(&mut chunk)?;
+            if n == 0 {
+                break;
+            }
+            check_size(offset + n)?;
+            region.write_bytes(offset, &chunk[..n]);
+            offset += n;
+        }
+        log_info!("ULP image loaded from {} ({} bytes)", path, offset);
+        Ok(())
+    }
+
+    /// 启动程序 (由主核触发运行)
+    pub fn start(&mut self) {
+        self.core.run(UlpCoreWakeupSource::HpCpu);
+        self.running = true;
+    }
+
+    /// 停止程序
+    pub fn stop(&mut self) {
+        self.core.stop();
+        self.running = false;
+    }
+
+    /// 本次启动后是否由本驱动启动
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+}
+
+impl DriverLifecycle for Ulp<'_> {
+    fn name(&self) -> &'static str {
+        "ulp"
+    }
+
+    /// 睡眠期间程序继续运行 (默认挂起不做任何事)，重启前停止
+    async fn shutdown(&mut self) -> Result<(), LifecycleError> {
+        self.stop();
+        Ok(())
+    }
+}
+
+fn check_size(len: usize) -> Result<(), UlpError> {
+    match len {
+        0 => Err(UlpError::Empty),
+        n if n > MAILBOX_OFFSET => Err(UlpError::TooLarge),
+        _ => Ok(()),
+    }
+}
+
+// ===== 邮箱 =====
+
+/// 邮箱变化通知
+pub static CHANGED: CriticalSignal<Snapshot> = CriticalSignal::new();
+
+/// 读取 ULP 数据
+pub fn read_mailbox() -> Option<Snapshot> {
+    Region::rtc().read()
+}
+
+/// 向 ULP 写入参数 (超出 `MAILBOX_WORDS` 的部分忽略)
+pub fn write_mailbox(data: &[u32]) {
+    Region::rtc().write(data)
+}
+
+/// 是否由 ULP 唤醒
+pub fn woke_by_ulp() -> bool {
+    matches!(wakeup_cause(), SleepSource::Ulp)
+}
+
+/// 检查未确认的更新: 有则确认、发送 `CHANGED` 并发布 `SystemEvent::UlpMailbox`
+///
+/// 启动 (深度睡眠唤醒) 时调用一次，之后可由 `watch` 周期调用。
+pub fn notify_changes() -> Option<Snapshot> {
+    let snapshot = Region::rtc().take_change()?;
+    CHANGED.signal(snapshot);
+    bus::publish(SystemEvent::UlpMailbox { seq: snapshot.seq, wake: woke_by_ulp() });
+    Some(snapshot)
+}
+
+/// 主核运行期间周期检查邮箱 (不返回)
+pub async fn watch(interval: Duration) -> ! {
+    loop {
+        notify_changes();
+        Timer::after(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mailbox_protocol() {
+        let mut memory = [0u32; ULP_RESERVE / 4];
+        let region = Region { base: memory.as_mut_ptr() };
+        region.clear();
+        region.write_bytes(0, &[0x13, 0x05, 0x00, 0x00, 0x6F]);
+        assert!(region.matches(&[0x13, 0x05, 0x00, 0x00, 0x6F]));
+        assert!(!region.matches(&[0x13, 0x06]));
+        assert_eq!(region.take_change(), None);
+
+        // ULP 写入中 (奇数序号) 时读不到数据
+        region.set_mailbox(ULP_SEQ, 1);
+        assert_eq!(region.read(), None);
+        region.set_mailbox(TO_HOST, 1234);
+        region.set_mailbox(ULP_SEQ, 2);
+
+        let snapshot = region.take_change().unwrap();
+        assert_eq!((snapshot.seq, snapshot.data[0]), (2, 1234));
+        assert_eq!(region.take_change(), None);
+
+        region.write(&[7, 8]);
+        assert_eq!((region.mailbox(TO_ULP), region.mailbox(TO_ULP + 1), region.mailbox(HOST_SEQ)), (7, 8, 1));
+        assert_eq!(check_size(MAILBOX_OFFSET + 1), Err(UlpError::TooLarge));
+    }
+}
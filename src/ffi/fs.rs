@@ -0,0 +1,238 @@
+//! 文件系统 C 接口
+//!
+//! 句柄为固定大小表的下标 (0..`RRTOS_MAX_FILES`)。操作期间句柄被临时取出，
+//! 其他任务同时使用同一句柄时返回 `RRTOS_ERR_BUSY`，不会在临界区内访问 Flash。
+
+use core::cell::RefCell;
+use core::ffi::c_char;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+
+use super::{c_str, filesystem, fs_error_code, status, RRTOS_ERR_BAD_HANDLE, RRTOS_ERR_BUSY};
+use super::{RRTOS_ERR_INVALID_ARG, RRTOS_ERR_TOO_MANY, RRTOS_OK};
+use crate::fs::littlefs::{File, OpenOptions, SeekFrom};
+
+/// 同时打开的最大文件数
+pub const RRTOS_MAX_FILES: usize = 8;
+
+/// 打开标志: 读
+pub const RRTOS_O_READ: u32 = 1 << 0;
+/// 打开标志: 写
+pub const RRTOS_O_WRITE: u32 = 1 << 1;
+/// 打开标志: 不存在时创建
+pub const RRTOS_O_CREATE: u32 = 1 << 2;
+/// 打开标志: 截断
+pub const RRTOS_O_TRUNC: u32 = 1 << 3;
+/// 打开标志: 追加
+pub const RRTOS_O_APPEND: u32 = 1 << 4;
+/// 打开标志: 必须新建 (已存在时失败)
+pub const RRTOS_O_EXCL: u32 = 1 << 5;
+
+/// 定位: 从文件开头
+pub const RRTOS_SEEK_SET: i32 = 0;
+/// 定位: 从当前位置
+pub const RRTOS_SEEK_CUR: i32 = 1;
+/// 定位: 从文件末尾
+pub const RRTOS_SEEK_END: i32 = 2;
+
+enum Slot {
+    Free,
+    Open(File<'static>),
+    /// 正在被某个调用使用
+    InUse,
+}
+
+const FREE: Slot = Slot::Free;
+
+static FILES: BlockingMutex<CriticalSectionRawMutex, RefCell<[Slot; RRTOS_MAX_FILES]>> =
+    BlockingMutex::new(RefCell::new([FREE; RRTOS_MAX_FILES]));
+
+/// 打开标志 → 打开选项
+pub fn open_options(flags: u32) -> OpenOptions {
+    OpenOptions::new()
+        .read(flags & RRTOS_O_READ != 0)
+        .write(flags & (RRTOS_O_WRITE | RRTOS_O_APPEND) != 0)
+        .create(flags & RRTOS_O_CREATE != 0)
+        .truncate(flags & RRTOS_O_TRUNC != 0)
+        .append(flags & RRTOS_O_APPEND != 0)
+        .create_new(flags & RRTOS_O_EXCL != 0)
+}
+
+/// 取出句柄对应的文件，槽位标记为使用中
+fn take(fd: i32) -> Result<(usize, File<'static>), i32> {
+    let index = usize::try_from(fd).ok().filter(|&i| i < RRTOS_MAX_FILES).ok_or(RRTOS_ERR_BAD_HANDLE)?;
+    FILES.lock(|files| {
+        let mut files = files.borrow_mut();
+        match core::mem::replace(&mut files[index], Slot::InUse) {
+            Slot::Open(file) => Ok((index, file)),
+            Slot::InUse => Err(RRTOS_ERR_BUSY),
+            Slot::Free => {
+                files[index] = Slot::Free;
+                Err(RRTOS_ERR_BAD_HANDLE)
+            }
+        }
+    })
+}
+
+fn put(index: usize, slot: Slot) {
+    FILES.lock(|files| files.borrow_mut()[index] = slot);
+}
+
+/// 取出句柄对应的文件执行 `f`，结束后放回
+fn with_file(fd: i32, f: impl FnOnce(&mut File<'static>) -> Result<i32, i32>) -> i32 {
+    status(take(fd).and_then(|(index, mut file)| {
+        let result = f(&mut file);
+        put(index, Slot::Open(file));
+        result
+    }))
+}
+
+/// 打开文件
+///
+/// # 返回
+/// 非负句柄，或负的错误码
+///
+/// # Safety
+/// `path` 为 NUL 结尾字符串
+#[no_mangle]
+pub unsafe extern "C" fn rrtos_fs_open(path: *const c_char, flags: u32) -> i32 {
+    status((|| {
+        let path = c_str(path)?;
+        let fs = filesystem()?;
+        let index = FILES.lock(|files| {
+            let mut files = files.borrow_mut();
+            let index = files.iter().position(|s| matches!(s, Slot::Free))?;
+            files[index] = Slot::InUse;
+            Some(index)
+        });
+        let index = index.ok_or(RRTOS_ERR_TOO_MANY)?;
+        match fs.open(path, open_options(flags)) {
+            Ok(file) => {
+                put(index, Slot::Open(file));
+                Ok(index as i32)
+            }
+            Err(e) => {
+                put(index, Slot::Free);
+                Err(fs_error_code(e))
+            }
+        }
+    })())
+}
+
+/// 关闭文件 (写入的数据先同步到 Flash)
+#[no_mangle]
+pub extern "C" fn rrtos_fs_close(fd: i32) -> i32 {
+    status(take(fd).and_then(|(index, mut file)| {
+        let result = file.sync();
+        put(index, Slot::Free);
+        result.map(|_| RRTOS_OK).map_err(fs_error_code)
+    }))
+}
+
+/// 读取，返回读到的字节数 (0 表示文件末尾)
+///
+/// # Safety
+/// `buf` 指向至少 `len` 字节的可写内存
+#[no_mangle]
+pub unsafe extern "C" fn rrtos_fs_read(fd: i32, buf: *mut u8, len: usize) -> i32 {
+    if buf.is_null() && len > 0 {
+        return RRTOS_ERR_INVALID_ARG;
+    }
+    let len = len.min(i32::MAX as usize);
+    with_file(fd, |file| {
+        if len == 0 {
+            return Ok(0);
+        }
+        let buf = core::slice::from_raw_parts_mut(buf, len);
+        file.read(buf).map(|n| n as i32).map_err(fs_error_code)
+    })
+}
+
+/// 写入，返回写入的字节数
+///
+/// # Safety
+/// `data` 指向至少 `len` 字节的可读内存
+#[no_mangle]
+pub unsafe extern "C" fn rrtos_fs_write(fd: i32, data: *const u8, len: usize) -> i32 {
+    if data.is_null() && len > 0 {
+        return RRTOS_ERR_INVALID_ARG;
+    }
+    let len = len.min(i32::MAX as usize);
+    with_file(fd, |file| {
+        if len == 0 {
+            return Ok(0);
+        }
+        let data = core::slice::from_raw_parts(data, len);
+        file.write(data).map(|n| n as i32).map_err(fs_error_code)
+    })
+}
+
+/// 定位，返回新位置
+#[no_mangle]
+pub extern "C" fn rrtos_fs_seek(fd: i32, offset: i64, whence: i32) -> i32 {
+    let pos = match whence {
+        RRTOS_SEEK_SET => match u32::try_from(offset) {
+            Ok(offset) => SeekFrom::Start(offset),
+            Err(_) => return RRTOS_ERR_INVALID_ARG,
+        },
+        RRTOS_SEEK_CUR => SeekFrom::Current(offset),
+        RRTOS_SEEK_END => SeekFrom::End(offset),
+        _ => return RRTOS_ERR_INVALID_ARG,
+    };
+    with_file(fd, |file| file.seek(pos).map(|p| p.min(i32::MAX as u32) as i32).map_err(fs_error_code))
+}
+
+/// 把缓冲的写入同步到 Flash
+#[no_mangle]
+pub extern "C" fn rrtos_fs_sync(fd: i32) -> i32 {
+    with_file(fd, |file| file.sync().map(|_| RRTOS_OK).map_err(fs_error_code))
+}
+
+/// 文件大小
+///
+/// # Safety
+/// `path` 为 NUL 结尾字符串
+#[no_mangle]
+pub unsafe extern "C" fn rrtos_fs_size(path: *const c_char) -> i32 {
+    status((|| {
+        let meta = filesystem()?.metadata(c_str(path)?).map_err(fs_error_code)?;
+        Ok(meta.size.min(i32::MAX as u32) as i32)
+    })())
+}
+
+/// 删除文件或空目录
+///
+/// # Safety
+/// `path` 为 NUL 结尾字符串
+#[no_mangle]
+pub unsafe extern "C" fn rrtos_fs_remove(path: *const c_char) -> i32 {
+    status((|| {
+        filesystem()?.remove(c_str(path)?).map_err(fs_error_code)?;
+        Ok(RRTOS_OK)
+    })())
+}
+
+/// 重命名
+///
+/// # Safety
+/// `from` 与 `to` 为 NUL 结尾字符串
+#[no_mangle]
+pub unsafe extern "C" fn rrtos_fs_rename(from: *const c_char, to: *const c_char) -> i32 {
+    status((|| {
+        filesystem()?.rename(c_str(from)?, c_str(to)?).map_err(fs_error_code)?;
+        Ok(RRTOS_OK)
+    })())
+}
+
+/// 创建目录 (包括父目录)
+///
+/// # Safety
+/// `path` 为 NUL 结尾字符串
+#[no_mangle]
+pub unsafe extern "C" fn rrtos_fs_mkdir(path: *const c_char) -> i32 {
+    status((|| {
+        filesystem()?.create_dir_all(c_str(path)?).map_err(fs_error_code)?;
+        Ok(RRTOS_OK)
+    })())
+}
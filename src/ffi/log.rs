@@ -0,0 +1,49 @@
+//! 日志 C 接口
+//!
+//! C 组件的日志走与 Rust 相同的后端 (`util::log`)，输出格式为 `[tag] message`。
+//! 未启用日志 feature 时调用为空操作。格式化在 C 侧完成 (如 `snprintf`)。
+
+use core::ffi::c_char;
+
+use super::c_str;
+use crate::util::log::*;
+
+/// 日志级别: 错误
+pub const RRTOS_LOG_ERROR: i32 = 0;
+/// 日志级别: 警告
+pub const RRTOS_LOG_WARN: i32 = 1;
+/// 日志级别: 信息
+pub const RRTOS_LOG_INFO: i32 = 2;
+/// 日志级别: 调试
+pub const RRTOS_LOG_DEBUG: i32 = 3;
+/// 日志级别: 跟踪
+pub const RRTOS_LOG_TRACE: i32 = 4;
+
+/// 输出一条日志
+///
+/// # Safety
+/// `tag` 为 NULL 或 NUL 结尾字符串，`msg` 为 NUL 结尾字符串
+#[no_mangle]
+pub unsafe extern "C" fn rrtos_log(level: i32, tag: *const c_char, msg: *const c_char) {
+    let tag = c_str(tag).unwrap_or("c");
+    let Ok(msg) = c_str(msg) else {
+        return;
+    };
+    match level {
+        RRTOS_LOG_ERROR => {
+            log_error!("[{}] {}", tag, msg);
+        }
+        RRTOS_LOG_WARN => {
+            log_warn!("[{}] {}", tag, msg);
+        }
+        RRTOS_LOG_INFO => {
+            log_info!("[{}] {}", tag, msg);
+        }
+        RRTOS_LOG_DEBUG => {
+            log_debug!("[{}] {}", tag, msg);
+        }
+        _ => {
+            log_trace!("[{}] {}", tag, msg);
+        }
+    }
+}
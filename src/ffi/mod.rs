@@ -0,0 +1,175 @@
+//! C 语言接口
+//!
+//! 以稳定的 `extern "C"` 接口导出关键子系统，供已有的 C 组件 (厂商 SDK 片段等)
+//! 直接链接本 crate 的服务，而不是并行维护第二套存储栈:
+//! - `fs`: 文件读写、删除、重命名、目录 (`rrtos_fs_*`)
+//! - `nvs`: 命名空间键值存储 (`rrtos_nvs_*`，基于 `fs::kv`)
+//! - `log`: 日志输出到本 crate 的日志后端 (`rrtos_log`)
+//!
+//! 头文件 `include/rustrtos.h` 由 cbindgen 生成 (启用 `ffi` feature 构建时更新)。
+//!
+//! # 约定
+//! - 所有函数返回 `int32_t`: 非负为结果 (句柄、字节数)，负数为 `RRTOS_ERR_*`
+//! - 字符串参数为 NUL 结尾的 UTF-8，传入 NULL 返回 `RRTOS_ERR_INVALID_ARG`
+//! - 函数可在任意任务中调用，但会阻塞当前任务直到 Flash 操作完成
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! static FS: StaticCell<FileSystem> = StaticCell::new();
+//! let fs = FS.init(FileSystem::new(storage));
+//! fs.mount()?;
+//! rustrtos::ffi::register_fs(fs);
+//! ```
+//!
+//! ```c
+//! #include "rustrtos.h"
+//!
+//! int32_t fd = rrtos_fs_open("/cal/phy.bin", RRTOS_O_READ);
+//! if (fd < 0) {
+//!     RRTOS_LOGW("phy", rrtos_strerror(fd));
+//! }
+//! ```
+
+pub mod fs;
+pub mod nvs;
+pub mod log;
+
+use core::cell::Cell;
+use core::ffi::{c_char, CStr};
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+
+use crate::fs::kv::KvError;
+use crate::fs::littlefs::{FileSystem, FsError};
+
+// ===== 状态码 =====
+
+/// 成功
+pub const RRTOS_OK: i32 = 0;
+/// 参数无效 (NULL 指针、非 UTF-8、键名非法)
+pub const RRTOS_ERR_INVALID_ARG: i32 = -1;
+/// 服务未就绪 (未注册文件系统或未挂载)
+pub const RRTOS_ERR_NOT_READY: i32 = -2;
+/// 不存在
+pub const RRTOS_ERR_NOT_FOUND: i32 = -3;
+/// 已存在
+pub const RRTOS_ERR_EXISTS: i32 = -4;
+/// 存储空间不足
+pub const RRTOS_ERR_NO_SPACE: i32 = -5;
+/// 句柄无效
+pub const RRTOS_ERR_BAD_HANDLE: i32 = -6;
+/// 句柄正被另一任务使用
+pub const RRTOS_ERR_BUSY: i32 = -7;
+/// 打开的句柄过多
+pub const RRTOS_ERR_TOO_MANY: i32 = -8;
+/// 缓冲区不足或值过长
+pub const RRTOS_ERR_TOO_LARGE: i32 = -9;
+/// 权限或状态不允许 (只读句柄写入、文件已固定)
+pub const RRTOS_ERR_DENIED: i32 = -10;
+/// I/O 错误或数据损坏
+pub const RRTOS_ERR_IO: i32 = -11;
+
+/// 文件系统错误 → 状态码
+pub fn fs_error_code(e: FsError) -> i32 {
+    match e {
+        FsError::NotFound => RRTOS_ERR_NOT_FOUND,
+        FsError::AlreadyExists => RRTOS_ERR_EXISTS,
+        FsError::NoSpace | FsError::Full => RRTOS_ERR_NO_SPACE,
+        FsError::NotMounted => RRTOS_ERR_NOT_READY,
+        FsError::TooManyOpenFiles => RRTOS_ERR_TOO_MANY,
+        FsError::InvalidHandle => RRTOS_ERR_BAD_HANDLE,
+        FsError::Pinned => RRTOS_ERR_DENIED,
+        FsError::InvalidParam
+        | FsError::PathTooLong
+        | FsError::NameTooLong
+        | FsError::NotADirectory
+        | FsError::NotAFile
+        | FsError::DirectoryNotEmpty
+        | FsError::NotContiguous => RRTOS_ERR_INVALID_ARG,
+        FsError::Storage(_)
+        | FsError::Corrupt
+        | FsError::MountFailed
+        | FsError::FormatFailed
+        | FsError::IoError => RRTOS_ERR_IO,
+    }
+}
+
+/// 键值存储错误 → 状态码
+pub fn kv_error_code(e: KvError) -> i32 {
+    match e {
+        KvError::InvalidKey => RRTOS_ERR_INVALID_ARG,
+        KvError::TooLarge | KvError::BufferTooSmall(_) => RRTOS_ERR_TOO_LARGE,
+        KvError::Fs(e) => fs_error_code(e),
+    }
+}
+
+/// 状态码说明 (静态字符串，无需释放)
+#[no_mangle]
+pub extern "C" fn rrtos_strerror(code: i32) -> *const c_char {
+    let text: &CStr = match code {
+        c if c >= RRTOS_OK => c"ok",
+        RRTOS_ERR_INVALID_ARG => c"invalid argument",
+        RRTOS_ERR_NOT_READY => c"service not ready",
+        RRTOS_ERR_NOT_FOUND => c"not found",
+        RRTOS_ERR_EXISTS => c"already exists",
+        RRTOS_ERR_NO_SPACE => c"no space",
+        RRTOS_ERR_BAD_HANDLE => c"bad handle",
+        RRTOS_ERR_BUSY => c"handle busy",
+        RRTOS_ERR_TOO_MANY => c"too many open handles",
+        RRTOS_ERR_TOO_LARGE => c"too large",
+        RRTOS_ERR_DENIED => c"operation not permitted",
+        RRTOS_ERR_IO => c"io error",
+        _ => c"unknown error",
+    };
+    text.as_ptr()
+}
+
+// ===== 服务注册 =====
+
+static FILESYSTEM: BlockingMutex<CriticalSectionRawMutex, Cell<Option<&'static FileSystem>>> =
+    BlockingMutex::new(Cell::new(None));
+
+/// 注册供 C 接口使用的文件系统 (需已挂载)
+pub fn register_fs(fs: &'static FileSystem) {
+    FILESYSTEM.lock(|f| f.set(Some(fs)));
+}
+
+/// 已注册的文件系统
+pub(crate) fn filesystem() -> Result<&'static FileSystem, i32> {
+    FILESYSTEM.lock(|f| f.get()).ok_or(RRTOS_ERR_NOT_READY)
+}
+
+/// 读取 C 字符串参数
+///
+/// # Safety
+/// `ptr` 为 NULL 或指向 NUL 结尾、在返回值使用期间有效的字符串
+pub(crate) unsafe fn c_str<'a>(ptr: *const c_char) -> Result<&'a str, i32> {
+    if ptr.is_null() {
+        return Err(RRTOS_ERR_INVALID_ARG);
+    }
+    CStr::from_ptr(ptr).to_str().map_err(|_| RRTOS_ERR_INVALID_ARG)
+}
+
+/// 把结果折叠为状态码
+pub(crate) fn status(result: Result<i32, i32>) -> i32 {
+    result.unwrap_or_else(|code| code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes() {
+        assert_eq!(fs_error_code(FsError::NotFound), RRTOS_ERR_NOT_FOUND);
+        assert_eq!(kv_error_code(KvError::BufferTooSmall(8)), RRTOS_ERR_TOO_LARGE);
+        assert_eq!(kv_error_code(KvError::Fs(FsError::Full)), RRTOS_ERR_NO_SPACE);
+
+        let text = unsafe { CStr::from_ptr(rrtos_strerror(RRTOS_ERR_BUSY)) };
+        assert_eq!(text.to_str(), Ok("handle busy"));
+        assert_eq!(unsafe { c_str(c"/cal/phy.bin".as_ptr()) }, Ok("/cal/phy.bin"));
+        assert_eq!(unsafe { c_str(core::ptr::null()) }, Err(RRTOS_ERR_INVALID_ARG));
+    }
+}
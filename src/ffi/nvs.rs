@@ -0,0 +1,91 @@
+//! 键值存储 C 接口
+//!
+//! 与 ESP-IDF `nvs_set_blob` / `nvs_get_blob` 用法一致，数据保存在 `fs::kv` 中，
+//! Rust 侧用 `KvStore` 可读写同一命名空间。
+
+use core::ffi::c_char;
+
+use super::{c_str, filesystem, kv_error_code, status, RRTOS_ERR_INVALID_ARG, RRTOS_OK};
+use crate::fs::kv::KvStore;
+
+fn store() -> Result<KvStore<'static>, i32> {
+    filesystem().map(KvStore::new)
+}
+
+/// 写入值
+///
+/// # Safety
+/// `ns`、`key` 为 NUL 结尾字符串，`data` 指向至少 `len` 字节
+#[no_mangle]
+pub unsafe extern "C" fn rrtos_nvs_set(ns: *const c_char, key: *const c_char, data: *const u8, len: usize) -> i32 {
+    status((|| {
+        let value = match len {
+            0 => &[][..],
+            _ if data.is_null() => return Err(RRTOS_ERR_INVALID_ARG),
+            _ => core::slice::from_raw_parts(data, len),
+        };
+        store()?.set(c_str(ns)?, c_str(key)?, value).map_err(kv_error_code)?;
+        Ok(RRTOS_OK)
+    })())
+}
+
+/// 读取值，返回长度
+///
+/// `buf` 为 NULL 时只返回值长度；缓冲区不足返回 `RRTOS_ERR_TOO_LARGE`。
+///
+/// # Safety
+/// `ns`、`key` 为 NUL 结尾字符串，`buf` 为 NULL 或指向至少 `len` 字节的可写内存
+#[no_mangle]
+pub unsafe extern "C" fn rrtos_nvs_get(ns: *const c_char, key: *const c_char, buf: *mut u8, len: usize) -> i32 {
+    status((|| {
+        let (ns, key, kv) = (c_str(ns)?, c_str(key)?, store()?);
+        let n = if buf.is_null() {
+            kv.len(ns, key)
+        } else {
+            kv.get(ns, key, core::slice::from_raw_parts_mut(buf, len))
+        };
+        n.map(|n| n as i32).map_err(kv_error_code)
+    })())
+}
+
+/// 写入 32 位整数 (小端)
+///
+/// # Safety
+/// `ns`、`key` 为 NUL 结尾字符串
+#[no_mangle]
+pub unsafe extern "C" fn rrtos_nvs_set_u32(ns: *const c_char, key: *const c_char, value: u32) -> i32 {
+    let bytes = value.to_le_bytes();
+    rrtos_nvs_set(ns, key, bytes.as_ptr(), bytes.len())
+}
+
+/// 读取 32 位整数 (值长度不是 4 字节时返回 `RRTOS_ERR_INVALID_ARG`)
+///
+/// # Safety
+/// `ns`、`key` 为 NUL 结尾字符串，`out` 指向可写的 `uint32_t`
+#[no_mangle]
+pub unsafe extern "C" fn rrtos_nvs_get_u32(ns: *const c_char, key: *const c_char, out: *mut u32) -> i32 {
+    status((|| {
+        if out.is_null() {
+            return Err(RRTOS_ERR_INVALID_ARG);
+        }
+        let mut bytes = [0u8; 4];
+        let n = store()?.get(c_str(ns)?, c_str(key)?, &mut bytes).map_err(kv_error_code)?;
+        if n != bytes.len() {
+            return Err(RRTOS_ERR_INVALID_ARG);
+        }
+        out.write(u32::from_le_bytes(bytes));
+        Ok(RRTOS_OK)
+    })())
+}
+
+/// 删除键
+///
+/// # Safety
+/// `ns`、`key` 为 NUL 结尾字符串
+#[no_mangle]
+pub unsafe extern "C" fn rrtos_nvs_erase(ns: *const c_char, key: *const c_char) -> i32 {
+    status((|| {
+        store()?.remove(c_str(ns)?, c_str(key)?).map_err(kv_error_code)?;
+        Ok(RRTOS_OK)
+    })())
+}
@@ -0,0 +1,471 @@
+//! A/B 双区配置
+//!
+//! 仿照 OTA 的 A/B 槽位管理配置: 保留两代配置，新配置写入非活动区后切换，
+//! 在限定时间内通过自检才确认，否则自动回滚到上一代，避免一次错误的远程配置推送
+//! 让整批设备失联:
+//! - 每个区保存在键值存储中: 数据 `<区>` (头部 + 配置内容，CRC 校验) 与状态 `<区>.meta`
+//! - 活动区 = 状态为试用或已确认、代号最大的区；数据写完后才写状态，掉电时保持旧配置
+//! - 试用期内重启超过 `max_trial_boots` 次仍未确认 (例如新配置导致崩溃) 时启动检查自动回滚
+//! - 活动区数据损坏时标记为坏区并回退到另一区
+//! - 回滚发布 `SystemEvent::ConfigRollback`
+//! - 远程推送的配置应经 `stage_signed` 写入: 先按信任环验证 `SignedBlob` 签名，只暂存验证通过的负载
+//!
+//! 配置内容的格式由应用决定 (JSON、postcard 等)，本模块只按字节保存。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::fs::abconfig::ConfigBanks;
+//! use rustrtos::test::selftest;
+//!
+//! let banks = ConfigBanks::new(KvStore::new(&fs));
+//!
+//! // 启动时: 上次试用未确认且重启次数超限则回滚
+//! banks.check_boot()?;
+//! let mut buf = [0u8; 1024];
+//! let (info, len) = banks.load(&mut buf)?;
+//! apply(&buf[..len]);
+//!
+//! // 收到远程推送: 验证签名后切换到新配置，并在 60 秒内完成自检
+//! let (_, config) = banks.stage_signed(&pushed, &ring)?;
+//! apply(config);
+//! banks.trial(Duration::from_secs(60), async { selftest::run(&mut checks).await.passed() }).await?;
+//! ```
+
+use core::fmt;
+use core::future::Future;
+
+use embassy_time::{with_timeout, Duration};
+
+use super::idf_nvs::crc32_le;
+use super::kv::{KvError, KvStore, MAX_VALUE_LEN};
+use crate::crypto::{CryptoError, KeyRing, SignedBlob};
+use crate::sync::bus::{self, SystemEvent};
+use crate::util::log::*;
+
+/// 默认命名空间
+pub const DEFAULT_NAMESPACE: &str = "abconfig";
+
+/// 数据头部长度
+pub const HEADER_LEN: usize = 16;
+
+/// 单区配置内容最大长度
+pub const MAX_CONFIG_LEN: usize = MAX_VALUE_LEN - HEADER_LEN;
+
+/// 状态记录长度
+const META_LEN: usize = 6;
+
+/// 数据头部魔数
+const MAGIC: [u8; 4] = *b"ABCF";
+
+// ===== 错误类型 =====
+
+/// 双区配置错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigBankError {
+    /// 没有可用的配置
+    Empty,
+    /// 配置内容过长
+    TooLarge,
+    /// 缓冲区不足 (附带所需长度，含头部)
+    BufferTooSmall(usize),
+    /// 两个区的数据都已损坏
+    Corrupt,
+    /// 活动区不在试用期
+    NotInTrial,
+    /// 试用期自检失败或超时，已回滚
+    TrialFailed,
+    /// 签名验证失败
+    Signature(CryptoError),
+    /// 键值存储错误
+    Kv(KvError),
+}
+
+impl fmt::Display for ConfigBankError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "No configuration stored"),
+            Self::TooLarge => write!(f, "Configuration too large"),
+            Self::BufferTooSmall(n) => write!(f, "Buffer too small ({} bytes needed)", n),
+            Self::Corrupt => write!(f, "Configuration corrupt"),
+            Self::NotInTrial => write!(f, "Active configuration is not on trial"),
+            Self::TrialFailed => write!(f, "Configuration trial failed, rolled back"),
+            Self::Signature(e) => write!(f, "Configuration signature invalid: {}", e),
+            Self::Kv(e) => write!(f, "KV error: {}", e),
+        }
+    }
+}
+
+impl From<KvError> for ConfigBankError {
+    fn from(e: KvError) -> Self {
+        Self::Kv(e)
+    }
+}
+
+impl From<CryptoError> for ConfigBankError {
+    fn from(e: CryptoError) -> Self {
+        Self::Signature(e)
+    }
+}
+
+// ===== 区与状态 =====
+
+/// 配置区
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bank {
+    A,
+    B,
+}
+
+impl Bank {
+    /// 另一个区
+    pub const fn other(self) -> Self {
+        match self {
+            Self::A => Self::B,
+            Self::B => Self::A,
+        }
+    }
+
+    const fn data_key(self) -> &'static str {
+        match self {
+            Self::A => "a",
+            Self::B => "b",
+        }
+    }
+
+    const fn meta_key(self) -> &'static str {
+        match self {
+            Self::A => "a.meta",
+            Self::B => "b.meta",
+        }
+    }
+}
+
+/// 区状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BankStatus {
+    /// 已切换，等待自检确认
+    Trial,
+    /// 已确认
+    Confirmed,
+    /// 自检失败、试用期重启超限或数据损坏
+    Bad,
+}
+
+impl BankStatus {
+    /// 状态名称
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Trial => "trial",
+            Self::Confirmed => "confirmed",
+            Self::Bad => "bad",
+        }
+    }
+
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(Self::Trial),
+            2 => Some(Self::Confirmed),
+            3 => Some(Self::Bad),
+            _ => None,
+        }
+    }
+
+    const fn to_u8(self) -> u8 {
+        match self {
+            Self::Trial => 1,
+            Self::Confirmed => 2,
+            Self::Bad => 3,
+        }
+    }
+}
+
+/// 区状态记录
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BankMeta {
+    /// 配置代号 (每次切换递增)
+    pub generation: u32,
+    /// 状态
+    pub status: BankStatus,
+    /// 试用期内的启动次数
+    pub trial_boots: u8,
+}
+
+impl BankMeta {
+    /// 编码
+    pub fn encode(&self) -> [u8; META_LEN] {
+        let g = self.generation.to_le_bytes();
+        [self.status.to_u8(), self.trial_boots, g[0], g[1], g[2], g[3]]
+    }
+
+    /// 解码 (长度或状态无效时返回 `None`)
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        if data.len() != META_LEN {
+            return None;
+        }
+        Some(Self {
+            status: BankStatus::from_u8(data[0])?,
+            trial_boots: data[1],
+            generation: u32::from_le_bytes([data[2], data[3], data[4], data[5]]),
+        })
+    }
+
+    /// 是否可作为活动区
+    pub fn is_usable(&self) -> bool {
+        self.status != BankStatus::Bad
+    }
+}
+
+/// 选出活动区: 可用且代号最大者
+pub fn select_active(a: Option<BankMeta>, b: Option<BankMeta>) -> Option<(Bank, BankMeta)> {
+    let a = a.filter(BankMeta::is_usable).map(|m| (Bank::A, m));
+    let b = b.filter(BankMeta::is_usable).map(|m| (Bank::B, m));
+    match (a, b) {
+        (Some(a), Some(b)) => Some(if b.1.generation > a.1.generation { b } else { a }),
+        (a, b) => a.or(b),
+    }
+}
+
+// ===== 数据封装 =====
+
+/// 写出头部: 魔数、代号、内容长度、CRC (覆盖代号、长度与内容)
+fn seal(generation: u32, payload: &[u8]) -> [u8; HEADER_LEN] {
+    let mut header = [0u8; HEADER_LEN];
+    header[0..4].copy_from_slice(&MAGIC);
+    header[4..8].copy_from_slice(&generation.to_le_bytes());
+    header[8..12].copy_from_slice(&(payload.len() as u32).to_le_bytes());
+    let crc = crc32_le(crc32_le(0xFFFF_FFFF, &header[4..12]), payload);
+    header[12..16].copy_from_slice(&crc.to_le_bytes());
+    header
+}
+
+/// 校验数据，返回 (代号, 内容长度)
+pub fn unseal(data: &[u8]) -> Option<(u32, usize)> {
+    let header = data.get(..HEADER_LEN)?;
+    if header[0..4] != MAGIC {
+        return None;
+    }
+    let field = |at: usize| u32::from_le_bytes([header[at], header[at + 1], header[at + 2], header[at + 3]]);
+    let len = field(8) as usize;
+    let payload = data.get(HEADER_LEN..HEADER_LEN + len)?;
+    (crc32_le(crc32_le(0xFFFF_FFFF, &header[4..12]), payload) == field(12)).then_some((field(4), len))
+}
+
+// ===== 双区配置 =====
+
+/// 启动检查结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootCheck {
+    /// 尚无配置
+    Empty,
+    /// 活动配置已确认
+    Confirmed,
+    /// 活动配置仍在试用期 (本次为第几次启动)
+    Trial(u8),
+    /// 试用期重启超限，已回滚到上一代 (若有)
+    RolledBack,
+}
+
+/// 双区配置
+#[derive(Clone, Copy)]
+pub struct ConfigBanks<'a> {
+    kv: KvStore<'a>,
+    namespace: &'static str,
+    max_trial_boots: u8,
+}
+
+impl<'a> ConfigBanks<'a> {
+    /// 创建 (默认命名空间，试用期允许重启 1 次)
+    pub const fn new(kv: KvStore<'a>) -> Self {
+        Self { kv, namespace: DEFAULT_NAMESPACE, max_trial_boots: 1 }
+    }
+
+    /// 设置命名空间
+    pub const fn with_namespace(mut self, namespace: &'static str) -> Self {
+        self.namespace = namespace;
+        self
+    }
+
+    /// 设置试用期允许的启动次数 (新配置需要重启才能生效时至少为 1)
+    pub const fn with_max_trial_boots(mut self, boots: u8) -> Self {
+        self.max_trial_boots = boots;
+        self
+    }
+
+    /// 读取区状态
+    pub fn meta(&self, bank: Bank) -> Option<BankMeta> {
+        let mut buf = [0u8; META_LEN];
+        match self.kv.get(self.namespace, bank.meta_key(), &mut buf) {
+            Ok(len) => BankMeta::decode(&buf[..len]),
+            Err(_) => None,
+        }
+    }
+
+    fn set_meta(&self, bank: Bank, meta: BankMeta) -> Result<(), ConfigBankError> {
+        self.kv.set(self.namespace, bank.meta_key(), &meta.encode())?;
+        Ok(())
+    }
+
+    /// 当前活动区
+    pub fn active(&self) -> Option<(Bank, BankMeta)> {
+        select_active(self.meta(Bank::A), self.meta(Bank::B))
+    }
+
+    /// 读取活动配置到 `buf[..len]`，返回 (活动区状态, 长度)
+    ///
+    /// `buf` 须额外留出 `HEADER_LEN` 字节。活动区数据损坏时标记为坏区并回退到另一区。
+    pub fn load(&self, buf: &mut [u8]) -> Result<(BankMeta, usize), ConfigBankError> {
+        for _ in 0..2 {
+            let (bank, meta) = self.active().ok_or(ConfigBankError::Empty)?;
+            let read = match self.kv.get(self.namespace, bank.data_key(), buf) {
+                Err(KvError::BufferTooSmall(n)) => return Err(ConfigBankError::BufferTooSmall(n)),
+                other => other,
+            };
+            match read.ok().and_then(|n| unseal(&buf[..n])) {
+                Some((generation, len)) if generation == meta.generation => {
+                    buf.copy_within(HEADER_LEN..HEADER_LEN + len, 0);
+                    return Ok((meta, len));
+                }
+                _ => {
+                    log_error!("Config bank {:?} (gen {}) corrupt", bank, meta.generation);
+                    self.set_meta(bank, BankMeta { status: BankStatus::Bad, ..meta })?;
+                }
+            }
+        }
+        Err(ConfigBankError::Corrupt)
+    }
+
+    /// 写入新配置并切换为活动区 (进入试用期)
+    ///
+    /// 新配置写入非活动区，代号为当前代号加一；写入完成前掉电则保持当前配置。
+    pub fn stage(&self, config: &[u8]) -> Result<BankMeta, ConfigBankError> {
+        if config.len() > MAX_CONFIG_LEN {
+            return Err(ConfigBankError::TooLarge);
+        }
+        let (target, generation) = match self.active() {
+            Some((bank, meta)) => (bank.other(), meta.generation.wrapping_add(1)),
+            None => (Bank::A, 1),
+        };
+
+        // 先使目标区失效，数据完整写入后再写状态
+        if self.kv.contains(self.namespace, target.meta_key()) {
+            self.kv.remove(self.namespace, target.meta_key())?;
+        }
+        self.kv.set_parts(self.namespace, target.data_key(), &[&seal(generation, config), config])?;
+
+        let meta = BankMeta { generation, status: BankStatus::Trial, trial_boots: 0 };
+        self.set_meta(target, meta)?;
+        log_info!("Config gen {} staged in bank {:?} (trial)", generation, target);
+        Ok(meta)
+    }
+
+    /// 验证签名数据块并暂存其负载，返回 (新区状态, 已验证的配置内容)
+    ///
+    /// 签名无效或签发密钥不在 `ring` 中时不改动任何一区。
+    pub fn stage_signed<'b, const N: usize>(
+        &self,
+        blob: &'b [u8],
+        ring: &KeyRing<N>,
+    ) -> Result<(BankMeta, &'b [u8]), ConfigBankError> {
+        let config = SignedBlob::parse(blob)?.verify(ring)?;
+        Ok((self.stage(config)?, config))
+    }
+
+    /// 确认试用中的活动配置
+    pub fn confirm(&self) -> Result<(), ConfigBankError> {
+        let (bank, meta) = self.active().ok_or(ConfigBankError::Empty)?;
+        if meta.status != BankStatus::Trial {
+            return Err(ConfigBankError::NotInTrial);
+        }
+        self.set_meta(bank, BankMeta { status: BankStatus::Confirmed, ..meta })?;
+        log_info!("Config gen {} confirmed", meta.generation);
+        Ok(())
+    }
+
+    /// 放弃试用中的活动配置，回到上一代 (返回新的活动区状态)
+    pub fn rollback(&self) -> Result<Option<BankMeta>, ConfigBankError> {
+        let (bank, meta) = self.active().ok_or(ConfigBankError::Empty)?;
+        if meta.status != BankStatus::Trial {
+            return Err(ConfigBankError::NotInTrial);
+        }
+        self.set_meta(bank, BankMeta { status: BankStatus::Bad, ..meta })?;
+        let restored = self.active().map(|(_, m)| m);
+        let to = restored.map_or(0, |m| m.generation);
+        log_warn!("Config gen {} rolled back to gen {}", meta.generation, to);
+        bus::publish(SystemEvent::ConfigRollback { from: meta.generation, to });
+        Ok(restored)
+    }
+
+    /// 启动检查 (在读取配置前调用)
+    ///
+    /// 活动配置在试用期时计一次启动；超过 `max_trial_boots` 视为新配置导致无法正常运行并回滚。
+    pub fn check_boot(&self) -> Result<BootCheck, ConfigBankError> {
+        let Some((bank, meta)) = self.active() else {
+            return Ok(BootCheck::Empty);
+        };
+        match meta.status {
+            BankStatus::Trial if meta.trial_boots >= self.max_trial_boots => {
+                self.rollback()?;
+                Ok(BootCheck::RolledBack)
+            }
+            BankStatus::Trial => {
+                let boots = meta.trial_boots + 1;
+                self.set_meta(bank, BankMeta { trial_boots: boots, ..meta })?;
+                Ok(BootCheck::Trial(boots))
+            }
+            _ => Ok(BootCheck::Confirmed),
+        }
+    }
+
+    /// 在 `timeout` 内运行自检: 通过则确认，失败或超时则回滚并返回 `TrialFailed`
+    pub async fn trial<F: Future<Output = bool>>(&self, timeout: Duration, check: F) -> Result<(), ConfigBankError> {
+        match self.active() {
+            Some((_, meta)) if meta.status == BankStatus::Trial => {}
+            Some(_) => return Err(ConfigBankError::NotInTrial),
+            None => return Err(ConfigBankError::Empty),
+        }
+        match with_timeout(timeout, check).await {
+            Ok(true) => self.confirm(),
+            _result => {
+                log_error!("Config trial {}", if _result.is_err() { "timed out" } else { "failed" });
+                self.rollback()?;
+                Err(ConfigBankError::TrialFailed)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(generation: u32, status: BankStatus) -> Option<BankMeta> {
+        Some(BankMeta { generation, status, trial_boots: 0 })
+    }
+
+    #[test]
+    fn test_select_active() {
+        assert_eq!(select_active(None, None), None);
+        assert_eq!(select_active(meta(1, BankStatus::Confirmed), None).map(|a| a.0), Some(Bank::A));
+        assert_eq!(select_active(meta(1, BankStatus::Confirmed), meta(2, BankStatus::Trial)).map(|a| a.0), Some(Bank::B));
+        // 试用失败的新一代被跳过
+        assert_eq!(select_active(meta(3, BankStatus::Confirmed), meta(4, BankStatus::Bad)).map(|a| a.1.generation), Some(3));
+
+        let m = BankMeta { generation: 0x0102_0304, status: BankStatus::Trial, trial_boots: 2 };
+        assert_eq!(BankMeta::decode(&m.encode()), Some(m));
+        assert_eq!(BankMeta::decode(&[9, 0, 0, 0, 0, 0]), None);
+    }
+
+    #[test]
+    fn test_seal_and_unseal() {
+        let config = br#"{"interval":5}"#;
+        let mut data = [0u8; HEADER_LEN + 14];
+        data[..HEADER_LEN].copy_from_slice(&seal(7, config));
+        data[HEADER_LEN..].copy_from_slice(config);
+        assert_eq!(unseal(&data), Some((7, config.len())));
+
+        data[HEADER_LEN + 3] ^= 1;
+        assert_eq!(unseal(&data), None);
+        assert_eq!(unseal(&data[..HEADER_LEN - 1]), None);
+    }
+}
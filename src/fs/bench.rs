@@ -0,0 +1,372 @@
+//! Flash 存储基准测试
+//!
+//! 测量读/编程/擦除吞吐量，以及 Flash 操作 (Cache 被禁用) 对高优先级执行器
+//! 造成的延迟尖峰，用于验证 `WriteScheduler` 的时间片策略是否有效。
+//!
+//! # 测量方法
+//!
+//! `LatencyProbe` 应运行在高优先级执行器 (中断执行器) 上，按固定周期唤醒并记录
+//! 实际唤醒相对预期时刻的延迟。基准依次执行三个阶段，每阶段开始前清零探针:
+//! - 空闲: 无 Flash 操作，得到基线抖动
+//! - 直接写: 在当前任务中直接擦除/编程 (不经调度器)
+//! - 调度写: 同样的擦除/编程经 `WriteScheduler` 执行
+//!
+//! 测试会擦除并改写指定块，请使用专用于测试的区域。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::fs::bench::{self, FlashBenchConfig, LatencyProbe};
+//!
+//! static PROBE: LatencyProbe = LatencyProbe::new();
+//!
+//! // 高优先级执行器
+//! #[embassy_executor::task]
+//! async fn probe_task() {
+//!     PROBE.run(Duration::from_micros(500)).await
+//! }
+//!
+//! let config = FlashBenchConfig::new(2000, 8);
+//! let report = bench::run(&mut storage, &FLASH_WRITES, &PROBE, &config).await?;
+//! println!("{}", report);
+//! ```
+
+use core::fmt;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use embassy_time::{Duration, Instant, Timer};
+
+use super::scheduler::{WriteScheduler, PAGE_SIZE};
+use super::storage::{FlashStorage, StorageError};
+
+// ===== 延迟探针 =====
+
+/// 延迟统计快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LatencySnapshot {
+    /// 采样次数
+    pub samples: u32,
+    /// 平均延迟 (μs)
+    pub avg_us: u32,
+    /// 最大延迟 (μs)
+    pub max_us: u32,
+    /// 超过阈值的次数
+    pub over_threshold: u32,
+}
+
+impl fmt::Display for LatencySnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} samples, avg {} us, max {} us, {} over threshold",
+            self.samples, self.avg_us, self.max_us, self.over_threshold
+        )
+    }
+}
+
+/// 执行器唤醒延迟探针
+pub struct LatencyProbe {
+    samples: AtomicU32,
+    total_us: AtomicU32,
+    max_us: AtomicU32,
+    over_threshold: AtomicU32,
+    threshold_us: AtomicU32,
+}
+
+impl LatencyProbe {
+    /// 创建探针 (默认阈值 1ms)
+    pub const fn new() -> Self {
+        Self {
+            samples: AtomicU32::new(0),
+            total_us: AtomicU32::new(0),
+            max_us: AtomicU32::new(0),
+            over_threshold: AtomicU32::new(0),
+            threshold_us: AtomicU32::new(1000),
+        }
+    }
+
+    /// 设置延迟阈值 (通常为实时任务的截止时间余量)
+    pub fn set_threshold(&self, threshold: Duration) {
+        self.threshold_us.store(threshold.as_micros() as u32, Ordering::Relaxed);
+    }
+
+    /// 清零统计
+    pub fn reset(&self) {
+        self.samples.store(0, Ordering::Relaxed);
+        self.total_us.store(0, Ordering::Relaxed);
+        self.max_us.store(0, Ordering::Relaxed);
+        self.over_threshold.store(0, Ordering::Relaxed);
+    }
+
+    /// 记录一次延迟
+    pub fn record(&self, late_us: u32) {
+        self.samples.fetch_add(1, Ordering::Relaxed);
+        self.total_us.fetch_add(late_us, Ordering::Relaxed);
+        self.max_us.fetch_max(late_us, Ordering::Relaxed);
+        if late_us > self.threshold_us.load(Ordering::Relaxed) {
+            self.over_threshold.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// 统计快照
+    pub fn snapshot(&self) -> LatencySnapshot {
+        let samples = self.samples.load(Ordering::Relaxed);
+        LatencySnapshot {
+            samples,
+            avg_us: self.total_us.load(Ordering::Relaxed).checked_div(samples).unwrap_or(0),
+            max_us: self.max_us.load(Ordering::Relaxed),
+            over_threshold: self.over_threshold.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 周期唤醒并记录延迟 (不返回，在高优先级执行器上运行)
+    pub async fn run(&self, period: Duration) -> ! {
+        let mut expected = Instant::now() + period;
+        loop {
+            Timer::at(expected).await;
+            let late = Instant::now().saturating_duration_since(expected);
+            self.record(late.as_micros() as u32);
+            expected += period;
+            // 长时间停顿后不补发积压的周期
+            let now = Instant::now();
+            if expected < now {
+                expected = now + period;
+            }
+        }
+    }
+}
+
+impl Default for LatencyProbe {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ===== 配置与结果 =====
+
+/// 基准配置
+#[derive(Debug, Clone, Copy)]
+pub struct FlashBenchConfig {
+    /// 起始块 (分区内块号)
+    pub first_block: u32,
+    /// 测试块数
+    pub blocks: u32,
+    /// 空闲基线采样时长
+    pub idle_window: Duration,
+}
+
+impl FlashBenchConfig {
+    /// 创建配置
+    pub fn new(first_block: u32, blocks: u32) -> Self {
+        Self { first_block, blocks: blocks.max(1), idle_window: Duration::from_millis(500) }
+    }
+
+    /// 设置空闲基线采样时长
+    pub fn with_idle_window(mut self, window: Duration) -> Self {
+        self.idle_window = window;
+        self
+    }
+}
+
+/// 吞吐量结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Throughput {
+    /// 读取 (KiB/s)
+    pub read_kib_s: u32,
+    /// 编程 (KiB/s)
+    pub program_kib_s: u32,
+    /// 擦除 (KiB/s)
+    pub erase_kib_s: u32,
+    /// 单块擦除最短耗时 (μs)
+    pub erase_min_us: u32,
+    /// 单块擦除平均耗时 (μs)
+    pub erase_avg_us: u32,
+    /// 单块擦除最长耗时 (μs)
+    pub erase_max_us: u32,
+    /// 单页编程最长耗时 (μs)
+    pub program_max_us: u32,
+}
+
+/// 基准报告
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FlashBenchReport {
+    /// 直接操作吞吐量
+    pub throughput: Throughput,
+    /// 空闲基线
+    pub idle: LatencySnapshot,
+    /// 直接写期间的执行器延迟
+    pub direct: LatencySnapshot,
+    /// 调度写期间的执行器延迟
+    pub scheduled: LatencySnapshot,
+    /// 调度写总耗时 (μs)
+    pub scheduled_total_us: u32,
+}
+
+impl FlashBenchReport {
+    /// 调度器是否把最大延迟压到直接写以下
+    pub fn scheduler_effective(&self) -> bool {
+        self.scheduled.max_us < self.direct.max_us
+    }
+}
+
+impl fmt::Display for FlashBenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let t = &self.throughput;
+        writeln!(f, "read:    {} KiB/s", t.read_kib_s)?;
+        writeln!(f, "program: {} KiB/s (max page {} us)", t.program_kib_s, t.program_max_us)?;
+        writeln!(
+            f,
+            "erase:   {} KiB/s (block min {} / avg {} / max {} us)",
+            t.erase_kib_s, t.erase_min_us, t.erase_avg_us, t.erase_max_us
+        )?;
+        writeln!(f, "executor latency idle:      {}", self.idle)?;
+        writeln!(f, "executor latency direct:    {}", self.direct)?;
+        writeln!(f, "executor latency scheduled: {} ({} us total)", self.scheduled, self.scheduled_total_us)
+    }
+}
+
+fn kib_per_s(bytes: u64, us: u64) -> u32 {
+    if us == 0 {
+        return 0;
+    }
+    (bytes * 1_000_000 / 1024 / us) as u32
+}
+
+// ===== 运行 =====
+
+/// 测试数据页 (每块按块号变化，便于发现错位)
+fn pattern(block: u32, page: usize) -> [u8; PAGE_SIZE] {
+    let mut data = [0u8; PAGE_SIZE];
+    for (i, b) in data.iter_mut().enumerate() {
+        *b = (i as u32 ^ block.wrapping_mul(31) ^ (page as u32) << 3) as u8;
+    }
+    data
+}
+
+/// 最大支持的块大小 (读取缓冲区)
+const MAX_BLOCK: usize = 4096;
+
+/// 读取整块并校验测试数据，返回读取耗时 (μs)
+fn read_verify(storage: &FlashStorage, block: u32) -> Result<u64, StorageError> {
+    let block_size = storage.block_size() as usize;
+    if block_size > MAX_BLOCK {
+        return Err(StorageError::OutOfBounds);
+    }
+    let mut buf = [0u8; MAX_BLOCK];
+    let start = Instant::now();
+    storage.read_block(block, &mut buf[..block_size])?;
+    let us = start.elapsed().as_micros();
+
+    for (page, chunk) in buf[..block_size].chunks_exact(PAGE_SIZE).enumerate() {
+        if chunk != pattern(block, page) {
+            return Err(StorageError::VerifyError);
+        }
+    }
+    Ok(us)
+}
+
+/// 直接操作 Flash，测量吞吐量 (写入后读回校验)
+pub async fn measure_direct(storage: &mut FlashStorage, config: &FlashBenchConfig) -> Result<Throughput, StorageError> {
+    let block_size = storage.block_size() as usize;
+    let pages = block_size / PAGE_SIZE;
+    let mut t = Throughput { erase_min_us: u32::MAX, ..Default::default() };
+    let (mut erase_us, mut program_us, mut read_us) = (0u64, 0u64, 0u64);
+
+    for block in config.first_block..config.first_block + config.blocks {
+        let start = Instant::now();
+        storage.erase_block(block)?;
+        let us = start.elapsed().as_micros();
+        erase_us += us;
+        t.erase_min_us = t.erase_min_us.min(us as u32);
+        t.erase_max_us = t.erase_max_us.max(us as u32);
+
+        for page in 0..pages {
+            let data = pattern(block, page);
+            let start = Instant::now();
+            storage.program(block, (page * PAGE_SIZE) as u32, &data)?;
+            let us = start.elapsed().as_micros();
+            program_us += us;
+            t.program_max_us = t.program_max_us.max(us as u32);
+        }
+
+        read_us += read_verify(storage, block)?;
+
+        // 块之间让出，模拟普通任务中的连续写入
+        embassy_futures::yield_now().await;
+    }
+
+    let bytes = config.blocks as u64 * block_size as u64;
+    t.read_kib_s = kib_per_s(bytes, read_us);
+    t.program_kib_s = kib_per_s(bytes, program_us);
+    t.erase_kib_s = kib_per_s(bytes, erase_us);
+    t.erase_avg_us = (erase_us / config.blocks as u64) as u32;
+    Ok(t)
+}
+
+/// 经调度器执行同样的擦除/编程，返回总耗时 (μs)
+///
+/// 调度器的 `run` 需运行在其他任务中 (通常 Core1 或低优先级执行器)。
+pub async fn measure_scheduled<const Q: usize>(
+    scheduler: &WriteScheduler<Q>,
+    block_size: u32,
+    config: &FlashBenchConfig,
+) -> Result<u32, StorageError> {
+    let pages = block_size as usize / PAGE_SIZE;
+    let start = Instant::now();
+    for block in config.first_block..config.first_block + config.blocks {
+        scheduler.erase(block).await;
+        for page in 0..pages {
+            scheduler.program(block, (page * PAGE_SIZE) as u32, &pattern(block, page)).await;
+        }
+    }
+    scheduler.flush().await?;
+    Ok(start.elapsed().as_micros() as u32)
+}
+
+/// 运行完整基准 (空闲基线、直接写、调度写)
+///
+/// `storage` 仅用于直接写阶段与结果校验；调度写阶段由调度器自己的存储实例执行，
+/// 两者须指向同一分区。
+pub async fn run<const Q: usize>(
+    storage: &mut FlashStorage,
+    scheduler: &WriteScheduler<Q>,
+    probe: &LatencyProbe,
+    config: &FlashBenchConfig,
+) -> Result<FlashBenchReport, StorageError> {
+    let mut report = FlashBenchReport::default();
+
+    probe.reset();
+    Timer::after(config.idle_window).await;
+    report.idle = probe.snapshot();
+
+    probe.reset();
+    report.throughput = measure_direct(storage, config).await?;
+    report.direct = probe.snapshot();
+
+    probe.reset();
+    report.scheduled_total_us = measure_scheduled(scheduler, storage.block_size(), config).await?;
+    report.scheduled = probe.snapshot();
+
+    for block in config.first_block..config.first_block + config.blocks {
+        read_verify(storage, block)?;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_snapshot() {
+        let probe = LatencyProbe::new();
+        probe.set_threshold(Duration::from_micros(100));
+        for late in [10, 20, 300] {
+            probe.record(late);
+        }
+        let snap = probe.snapshot();
+        assert_eq!(snap, LatencySnapshot { samples: 3, avg_us: 110, max_us: 300, over_threshold: 1 });
+        probe.reset();
+        assert_eq!(probe.snapshot(), LatencySnapshot::default());
+    }
+}
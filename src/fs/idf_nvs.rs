@@ -0,0 +1,866 @@
+//! ESP-IDF NVS 分区读取
+//!
+//! 只读解析 ESP-IDF 格式的 NVS 分区 (页/条目结构)，用于把已出货设备上的用户数据
+//! 迁移到 `KvStore`，升级固件无需擦除分区:
+//! - 按页序号 (seq) 遍历 ACTIVE/FULL/FREEING 页，同名键以最新写入为准
+//! - 支持全部整数类型、字符串、v1 blob 与 v2 分块 blob (BLOB_IDX + BLOB_DATA)
+//! - 校验页头、条目与变长数据的 CRC32，损坏条目跳过并计数
+//! - 加密 NVS (`nvs-encryption` feature): 从 `nvs_keys` 分区读取 XTS-AES-256 密钥，
+//!   条目区按 32 字节数据单元解密 (页头与状态位图不加密)
+//!
+//! 迁移时数值按对应宽度小端存储 (与 `ffi::nvs` 的 `rrtos_nvs_get_u32` 等一致)，
+//! 字符串去掉结尾 NUL，blob 原样保存。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::fs::idf_nvs::{IdfNvs, NvsKeys};
+//! use rustrtos::fs::{FlashStorage, KvStore};
+//!
+//! let mut nvs_flash = FlashStorage::from_partition(table.find_nvs().unwrap(), FLASH_SIZE);
+//! nvs_flash.init()?;
+//!
+//! // 加密分区: 先读取 nvs_keys
+//! let mut keys_flash = FlashStorage::from_partition(table.find_by_label("nvs_keys").unwrap(), FLASH_SIZE);
+//! keys_flash.init()?;
+//! let mut nvs = IdfNvs::open_encrypted(&nvs_flash, NvsKeys::from_partition(&keys_flash)?)?;
+//!
+//! let mut ssid = [0u8; 32];
+//! let (_, len) = nvs.get("wifi", "ssid", &mut ssid)?;
+//!
+//! if !kv.contains("sys", "nvs_migrated") {
+//!     let mut scratch = [0u8; 4000];
+//!     let report = nvs.migrate(&kv, &mut scratch)?;
+//!     kv.set("sys", "nvs_migrated", &[1])?;
+//! }
+//! ```
+
+use core::fmt;
+
+use heapless::{String, Vec};
+
+use super::kv::{KvError, KvStore, MAX_KEY_LEN};
+use super::storage::{FlashStorage, StorageError};
+
+/// 页大小
+pub const PAGE_SIZE: usize = 4096;
+
+/// 条目大小 (也是加密数据单元大小)
+pub const ENTRY_SIZE: usize = 32;
+
+/// 每页条目数
+pub const ENTRY_COUNT: usize = 126;
+
+/// 支持的最大页数 (256KB 分区)
+pub const MAX_PAGES: usize = 64;
+
+/// 最多命名空间数量
+pub const MAX_NAMESPACES: usize = 32;
+
+/// 单次迁移最多 v2 blob 数量
+pub const MAX_BLOBS: usize = 32;
+
+/// 状态位图偏移
+const BITMAP_OFFSET: usize = 32;
+
+/// 条目区偏移
+const ENTRY_OFFSET: usize = 64;
+
+/// 命名空间定义所在的命名空间索引
+const NS_INDEX: u8 = 0;
+
+/// 页格式版本 (v2 支持分块 blob)
+const VERSION_2: u8 = 0xFE;
+
+// ===== 错误类型 =====
+
+/// NVS 读取错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdfNvsError {
+    /// Flash 读取失败
+    Storage(StorageError),
+    /// 分区页数超过 `MAX_PAGES`
+    TooManyPages,
+    /// 有已写入条目但全部校验失败 (分区已加密但未提供密钥，或密钥错误)
+    Encrypted,
+    /// 密钥分区未初始化或 CRC 错误
+    InvalidKeys,
+    /// 命名空间或键不存在
+    NotFound,
+    /// 数据不完整 (blob 分块缺失或长度不符)
+    Corrupt,
+    /// 缓冲区不足 (附带所需长度)
+    BufferTooSmall(usize),
+    /// 写入键值存储失败
+    Kv(KvError),
+}
+
+impl fmt::Display for IdfNvsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Storage(e) => write!(f, "Storage error: {}", e),
+            Self::TooManyPages => write!(f, "Too many NVS pages"),
+            Self::Encrypted => write!(f, "NVS unreadable (encrypted or wrong keys)"),
+            Self::InvalidKeys => write!(f, "Invalid NVS keys partition"),
+            Self::NotFound => write!(f, "Not found"),
+            Self::Corrupt => write!(f, "NVS data corrupt"),
+            Self::BufferTooSmall(n) => write!(f, "Buffer too small ({} bytes needed)", n),
+            Self::Kv(e) => write!(f, "KV error: {}", e),
+        }
+    }
+}
+
+impl From<StorageError> for IdfNvsError {
+    fn from(e: StorageError) -> Self {
+        Self::Storage(e)
+    }
+}
+
+impl From<KvError> for IdfNvsError {
+    fn from(e: KvError) -> Self {
+        Self::Kv(e)
+    }
+}
+
+// ===== CRC32 =====
+
+/// ESP-IDF `esp_rom_crc32_le` 语义的 CRC32 (反射多项式 0xEDB88320)
+///
+/// NVS 以 `crc32_le(0xFFFFFFFF, data)` 计算校验值；把上一次结果作为 `crc`
+/// 传入即可分段计算。
+pub fn crc32_le(crc: u32, data: &[u8]) -> u32 {
+    let mut crc = !crc;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]])
+}
+
+// ===== 页 =====
+
+/// 页状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageState {
+    /// 未使用 (已擦除)
+    Uninitialized,
+    /// 正在写入
+    Active,
+    /// 已写满
+    Full,
+    /// 正在回收 (数据仍有效)
+    Freeing,
+    /// 已损坏或未知状态
+    Corrupt,
+}
+
+impl PageState {
+    fn from_u32(value: u32) -> Self {
+        match value {
+            0xFFFF_FFFF => Self::Uninitialized,
+            0xFFFF_FFFE => Self::Active,
+            0xFFFF_FFFC => Self::Full,
+            0xFFFF_FFF8 => Self::Freeing,
+            _ => Self::Corrupt,
+        }
+    }
+
+    /// 是否包含有效数据
+    pub fn is_readable(&self) -> bool {
+        matches!(self, Self::Active | Self::Full | Self::Freeing)
+    }
+}
+
+/// 页头 (32 字节: 状态、序号、版本、保留、CRC32)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageHeader {
+    /// 状态
+    pub state: PageState,
+    /// 序号 (越大越新)
+    pub seq: u32,
+    /// 格式版本 (0xFE = v2，0xFF = v1)
+    pub version: u8,
+}
+
+impl PageHeader {
+    /// 解析页头
+    ///
+    /// 有效数据页的 CRC 不匹配时状态视为 `Corrupt`
+    pub fn parse(page: &[u8]) -> Self {
+        let mut state = PageState::from_u32(read_u32(page, 0));
+        if state.is_readable() && crc32_le(0xFFFF_FFFF, &page[4..28]) != read_u32(page, 28) {
+            state = PageState::Corrupt;
+        }
+        Self { state, seq: read_u32(page, 4), version: page[8] }
+    }
+
+    /// 是否为 v2 格式 (blob 以 BLOB_IDX + BLOB_DATA 分块存储)
+    pub fn is_v2(&self) -> bool {
+        self.version == VERSION_2
+    }
+}
+
+/// 条目状态 (位图中每条目 2 bit)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryState {
+    /// 空闲
+    Empty,
+    /// 已写入
+    Written,
+    /// 已删除
+    Erased,
+}
+
+/// 读取条目状态
+pub fn entry_state(page: &[u8], index: usize) -> EntryState {
+    let bits = (page[BITMAP_OFFSET + index / 4] >> ((index % 4) * 2)) & 0b11;
+    match bits {
+        0b11 => EntryState::Empty,
+        0b10 => EntryState::Written,
+        _ => EntryState::Erased,
+    }
+}
+
+// ===== 条目 =====
+
+/// 条目数据类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemType {
+    /// u8
+    U8,
+    /// i8
+    I8,
+    /// u16
+    U16,
+    /// i16
+    I16,
+    /// u32
+    U32,
+    /// i32
+    I32,
+    /// u64
+    U64,
+    /// i64
+    I64,
+    /// 字符串 (含结尾 NUL)
+    Str,
+    /// blob (v1 单页 blob，或 `IdfNvs::get` 返回的完整 v2 blob)
+    Blob,
+    /// v2 blob 分块
+    BlobData,
+    /// v2 blob 索引
+    BlobIndex,
+}
+
+impl ItemType {
+    fn from_u8(value: u8) -> Option<Self> {
+        Some(match value {
+            0x01 => Self::U8,
+            0x11 => Self::I8,
+            0x02 => Self::U16,
+            0x12 => Self::I16,
+            0x04 => Self::U32,
+            0x14 => Self::I32,
+            0x08 => Self::U64,
+            0x18 => Self::I64,
+            0x21 => Self::Str,
+            0x41 => Self::Blob,
+            0x42 => Self::BlobData,
+            0x48 => Self::BlobIndex,
+            _ => return None,
+        })
+    }
+
+    /// 整数类型的字节宽度
+    pub fn scalar_size(&self) -> Option<usize> {
+        match self {
+            Self::U8 | Self::I8 => Some(1),
+            Self::U16 | Self::I16 => Some(2),
+            Self::U32 | Self::I32 => Some(4),
+            Self::U64 | Self::I64 => Some(8),
+            _ => None,
+        }
+    }
+
+    /// 是否为变长类型 (数据位于后续条目)
+    fn is_variable(&self) -> bool {
+        matches!(self, Self::Str | Self::Blob | Self::BlobData)
+    }
+}
+
+/// 页内的一个有效条目
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Item<'p> {
+    /// 命名空间索引 (0 为命名空间定义)
+    pub namespace: u8,
+    /// 数据类型
+    pub item_type: ItemType,
+    /// 分块序号 (仅 `BlobData`/`BlobIndex` 有意义)
+    pub chunk_index: u8,
+    /// 键名
+    pub key: &'p str,
+    /// 值: 整数为对应宽度的小端字节，变长类型为已校验的数据，`BlobIndex` 为原始 8 字节
+    pub value: &'p [u8],
+}
+
+impl Item<'_> {
+    /// 解析 blob 索引 (总长度、分块数、起始分块序号)
+    pub fn blob_index(&self) -> Option<(u32, u8, u8)> {
+        (self.item_type == ItemType::BlobIndex).then(|| (read_u32(self.value, 0), self.value[4], self.value[5]))
+    }
+
+    /// 去掉字符串结尾 NUL 后的值
+    pub fn data(&self) -> &[u8] {
+        match (self.item_type, self.value.split_last()) {
+            (ItemType::Str, Some((0, rest))) => rest,
+            _ => self.value,
+        }
+    }
+}
+
+/// 页内有效条目迭代器 (见 `items`)
+pub struct Items<'p> {
+    page: &'p [u8],
+    index: usize,
+    /// 已跳过的损坏条目数
+    pub corrupt: u32,
+}
+
+/// 遍历页内已写入且校验通过的条目 (页须已解密)
+pub fn items(page: &[u8]) -> Items<'_> {
+    Items { page, index: 0, corrupt: 0 }
+}
+
+impl<'p> Items<'p> {
+    fn parse(&self, index: usize) -> Option<(Item<'p>, usize)> {
+        let page = self.page;
+        let entry = &page[ENTRY_OFFSET + index * ENTRY_SIZE..][..ENTRY_SIZE];
+        let crc = crc32_le(crc32_le(0xFFFF_FFFF, &entry[0..4]), &entry[8..32]);
+        if crc != read_u32(entry, 4) {
+            return None;
+        }
+        let item_type = ItemType::from_u8(entry[1])?;
+        let span = entry[2] as usize;
+        if span == 0 || index + span > ENTRY_COUNT {
+            return None;
+        }
+        let key_bytes = &entry[8..24];
+        let key_len = key_bytes.iter().position(|&b| b == 0).unwrap_or(key_bytes.len());
+        let key = core::str::from_utf8(&key_bytes[..key_len]).ok()?;
+        let data = &entry[24..32];
+
+        let value = if let Some(size) = item_type.scalar_size() {
+            &data[..size]
+        } else if item_type.is_variable() {
+            let size = u16::from_le_bytes([data[0], data[1]]) as usize;
+            if size > (span - 1) * ENTRY_SIZE {
+                return None;
+            }
+            let value = &page[ENTRY_OFFSET + (index + 1) * ENTRY_SIZE..][..size];
+            if crc32_le(0xFFFF_FFFF, value) != read_u32(data, 4) {
+                return None;
+            }
+            value
+        } else {
+            data
+        };
+        let item = Item { namespace: entry[0], item_type, chunk_index: entry[3], key, value };
+        Some((item, span))
+    }
+}
+
+impl<'p> Iterator for Items<'p> {
+    type Item = Item<'p>;
+
+    fn next(&mut self) -> Option<Item<'p>> {
+        while self.index < ENTRY_COUNT {
+            let index = self.index;
+            if entry_state(self.page, index) != EntryState::Written {
+                self.index += 1;
+                continue;
+            }
+            match self.parse(index) {
+                Some((item, span)) => {
+                    self.index += span;
+                    return Some(item);
+                }
+                None => {
+                    self.corrupt += 1;
+                    self.index += 1;
+                }
+            }
+        }
+        None
+    }
+}
+
+// ===== 加密 =====
+
+/// NVS 加密密钥 (`nvs_keys` 分区: 数据密钥 32 字节 + 调整密钥 32 字节 + CRC32)
+#[cfg(feature = "nvs-encryption")]
+#[derive(Clone)]
+pub struct NvsKeys {
+    data: aes::Aes256,
+    tweak: aes::Aes256,
+}
+
+#[cfg(feature = "nvs-encryption")]
+impl NvsKeys {
+    /// 密钥分区内容长度
+    pub const SIZE: usize = 68;
+
+    /// 解析密钥分区内容
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, IdfNvsError> {
+        use aes::cipher::KeyInit;
+
+        if bytes.len() < Self::SIZE || bytes[..64].iter().all(|&b| b == 0xFF) {
+            return Err(IdfNvsError::InvalidKeys);
+        }
+        let (eky, tky) = (&bytes[..32], &bytes[32..64]);
+        if crc32_le(crc32_le(0xFFFF_FFFF, eky), tky) != read_u32(bytes, 64) {
+            return Err(IdfNvsError::InvalidKeys);
+        }
+        let data = aes::Aes256::new_from_slice(eky).map_err(|_| IdfNvsError::InvalidKeys)?;
+        let tweak = aes::Aes256::new_from_slice(tky).map_err(|_| IdfNvsError::InvalidKeys)?;
+        Ok(Self { data, tweak })
+    }
+
+    /// 从 `nvs_keys` 分区读取
+    pub fn from_partition(storage: &FlashStorage) -> Result<Self, IdfNvsError> {
+        let mut bytes = [0u8; Self::SIZE];
+        storage.read_block(0, &mut bytes)?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// XTS-AES-256 解密一个 32 字节数据单元
+    ///
+    /// `address` 为数据单元相对分区起始的偏移 (小端写入 128 位调整值)
+    pub fn decrypt(&self, address: u32, unit: &mut [u8]) {
+        use aes::cipher::{BlockDecrypt, BlockEncrypt};
+        use aes::Block;
+
+        let mut tweak = Block::default();
+        tweak[..4].copy_from_slice(&address.to_le_bytes());
+        self.tweak.encrypt_block(&mut tweak);
+
+        for chunk in unit.chunks_exact_mut(16) {
+            let block = Block::from_mut_slice(chunk);
+            block.iter_mut().zip(tweak.iter()).for_each(|(b, t)| *b ^= t);
+            self.data.decrypt_block(block);
+            block.iter_mut().zip(tweak.iter()).for_each(|(b, t)| *b ^= t);
+
+            // 调整值乘以 GF(2^128) 的本原元 α
+            let mut carry = 0;
+            for byte in tweak.iter_mut() {
+                let next = *byte >> 7;
+                *byte = (*byte << 1) | carry;
+                carry = next;
+            }
+            if carry != 0 {
+                tweak[0] ^= 0x87;
+            }
+        }
+    }
+
+    /// 解密整页的已使用条目
+    pub fn decrypt_page(&self, page_index: u32, page: &mut [u8]) {
+        let base = page_index * PAGE_SIZE as u32;
+        for index in 0..ENTRY_COUNT {
+            if entry_state(page, index) == EntryState::Empty {
+                continue;
+            }
+            let offset = ENTRY_OFFSET + index * ENTRY_SIZE;
+            self.decrypt(base + offset as u32, &mut page[offset..offset + ENTRY_SIZE]);
+        }
+    }
+}
+
+// ===== 迁移报告 =====
+
+/// 迁移结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// 已写入 `KvStore` 的键数
+    pub migrated: u32,
+    /// 因键名不合法、值过长或 blob 数量超限跳过的键数
+    pub skipped: u32,
+    /// 分区中校验失败的条目数
+    pub corrupt: u32,
+}
+
+impl fmt::Display for MigrationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} migrated, {} skipped, {} corrupt", self.migrated, self.skipped, self.corrupt)
+    }
+}
+
+/// 待组装的 v2 blob
+struct PendingBlob {
+    namespace: u8,
+    key: String<MAX_KEY_LEN>,
+    size: u32,
+    chunks: u8,
+    start: u8,
+}
+
+// ===== 分区读取器 =====
+
+/// ESP-IDF NVS 分区读取器
+///
+/// 内含一页缓冲区 (约 4.6KB)，建议迁移完成后即释放。
+pub struct IdfNvs<'a> {
+    storage: &'a FlashStorage,
+    #[cfg(feature = "nvs-encryption")]
+    keys: Option<NvsKeys>,
+    /// (序号, 块号)，按序号升序
+    pages: Vec<(u32, u16), MAX_PAGES>,
+    namespaces: Vec<(u8, String<MAX_KEY_LEN>), MAX_NAMESPACES>,
+    corrupt: u32,
+    buf: [u8; PAGE_SIZE],
+}
+
+impl<'a> IdfNvs<'a> {
+    /// 打开未加密分区
+    pub fn open(storage: &'a FlashStorage) -> Result<Self, IdfNvsError> {
+        let mut nvs = Self {
+            storage,
+            #[cfg(feature = "nvs-encryption")]
+            keys: None,
+            pages: Vec::new(),
+            namespaces: Vec::new(),
+            corrupt: 0,
+            buf: [0; PAGE_SIZE],
+        };
+        nvs.scan()?;
+        Ok(nvs)
+    }
+
+    /// 打开加密分区
+    #[cfg(feature = "nvs-encryption")]
+    pub fn open_encrypted(storage: &'a FlashStorage, keys: NvsKeys) -> Result<Self, IdfNvsError> {
+        let mut nvs = Self {
+            storage,
+            keys: Some(keys),
+            pages: Vec::new(),
+            namespaces: Vec::new(),
+            corrupt: 0,
+            buf: [0; PAGE_SIZE],
+        };
+        nvs.scan()?;
+        Ok(nvs)
+    }
+
+    /// 扫描页头并建立命名空间表
+    fn scan(&mut self) -> Result<(), IdfNvsError> {
+        let mut header = [0u8; 32];
+        for block in 0..self.storage.block_count() {
+            self.storage.read_block(block, &mut header)?;
+            let parsed = PageHeader::parse(&header);
+            if parsed.state.is_readable() {
+                self.pages.push((parsed.seq, block as u16)).map_err(|_| IdfNvsError::TooManyPages)?;
+            }
+        }
+        self.pages.sort_unstable_by_key(|&(seq, _)| seq);
+
+        let mut valid = 0u32;
+        for i in 0..self.pages.len() {
+            self.read_page(self.pages[i].1)?;
+            let mut iter = items(&self.buf);
+            for item in iter.by_ref() {
+                valid += 1;
+                if item.namespace != NS_INDEX || item.item_type != ItemType::U8 {
+                    continue;
+                }
+                let mut name = String::new();
+                if name.push_str(item.key).is_err() {
+                    continue;
+                }
+                let index = item.value[0];
+                self.namespaces.retain(|(i, _)| *i != index);
+                let _ = self.namespaces.push((index, name));
+            }
+            self.corrupt += iter.corrupt;
+        }
+        if valid == 0 && self.corrupt > 0 {
+            return Err(IdfNvsError::Encrypted);
+        }
+        Ok(())
+    }
+
+    /// 读取一页到缓冲区 (加密分区同时解密)
+    fn read_page(&mut self, block: u16) -> Result<(), IdfNvsError> {
+        self.storage.read_block(block as u32, &mut self.buf)?;
+        #[cfg(feature = "nvs-encryption")]
+        if let Some(keys) = &self.keys {
+            keys.decrypt_page(block as u32, &mut self.buf);
+        }
+        Ok(())
+    }
+
+    /// 有效页数
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// 分区是否为空 (全新或已擦除)
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+
+    /// 校验失败的条目数
+    pub fn corrupt_entries(&self) -> u32 {
+        self.corrupt
+    }
+
+    /// 命名空间列表 (索引, 名称)
+    pub fn namespaces(&self) -> impl Iterator<Item = (u8, &str)> {
+        self.namespaces.iter().map(|(index, name)| (*index, name.as_str()))
+    }
+
+    fn namespace_index(&self, name: &str) -> Option<u8> {
+        self.namespaces.iter().find(|(_, n)| n == name).map(|(index, _)| *index)
+    }
+
+    /// 遍历全部键 (按写入顺序，不含命名空间定义与 blob 分块)
+    ///
+    /// 回调参数为命名空间名称与条目；同名键可能出现多次，以最后一次为准。
+    pub fn for_each(&mut self, mut f: impl FnMut(&str, &Item<'_>)) -> Result<(), IdfNvsError> {
+        for i in 0..self.pages.len() {
+            self.read_page(self.pages[i].1)?;
+            for item in items(&self.buf) {
+                if item.namespace == NS_INDEX || item.item_type == ItemType::BlobData {
+                    continue;
+                }
+                if let Some((_, name)) = self.namespaces.iter().find(|(index, _)| *index == item.namespace) {
+                    f(name, &item);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 读取一个键，返回类型与长度
+    ///
+    /// 整数写入对应宽度的小端字节，字符串不含结尾 NUL，v2 blob 组装为完整数据
+    /// (类型报告为 `Blob`)。
+    pub fn get(&mut self, namespace: &str, key: &str, buf: &mut [u8]) -> Result<(ItemType, usize), IdfNvsError> {
+        let ns = self.namespace_index(namespace).ok_or(IdfNvsError::NotFound)?;
+        let mut found = None;
+        let mut blob = None;
+        for i in 0..self.pages.len() {
+            self.read_page(self.pages[i].1)?;
+            for item in items(&self.buf) {
+                if item.namespace != ns || item.key != key || item.item_type == ItemType::BlobData {
+                    continue;
+                }
+                blob = item.blob_index();
+                if blob.is_some() {
+                    found = Some((ItemType::Blob, 0));
+                    continue;
+                }
+                let data = item.data();
+                buf.get_mut(..data.len()).ok_or(IdfNvsError::BufferTooSmall(data.len()))?.copy_from_slice(data);
+                found = Some((item.item_type, data.len()));
+            }
+        }
+        let (item_type, len) = found.ok_or(IdfNvsError::NotFound)?;
+        match blob {
+            Some((size, chunks, start)) => {
+                if size as usize > buf.len() {
+                    return Err(IdfNvsError::BufferTooSmall(size as usize));
+                }
+                let len = self.read_blob(ns, key, chunks, start, buf)?;
+                if len != size as usize {
+                    return Err(IdfNvsError::Corrupt);
+                }
+                Ok((item_type, len))
+            }
+            None => Ok((item_type, len)),
+        }
+    }
+
+    /// 按分块序号依次组装 v2 blob
+    fn read_blob(&mut self, ns: u8, key: &str, chunks: u8, start: u8, buf: &mut [u8]) -> Result<usize, IdfNvsError> {
+        let mut filled = 0;
+        for chunk in 0..chunks {
+            let index = start.wrapping_add(chunk);
+            let mut len = None;
+            for i in 0..self.pages.len() {
+                self.read_page(self.pages[i].1)?;
+                for item in items(&self.buf) {
+                    if item.namespace == ns && item.key == key && item.item_type == ItemType::BlobData && item.chunk_index == index {
+                        let dst = buf.get_mut(filled..filled + item.value.len()).ok_or(IdfNvsError::Corrupt)?;
+                        dst.copy_from_slice(item.value);
+                        len = Some(item.value.len());
+                    }
+                }
+            }
+            filled += len.ok_or(IdfNvsError::Corrupt)?;
+        }
+        Ok(filled)
+    }
+
+    /// 把全部键迁移到 `KvStore` (覆盖同名键)
+    ///
+    /// `scratch` 用于组装 v2 blob，长度不足的 blob 计入跳过。分区只读，迁移不修改原数据，
+    /// 调用方可写入迁移标记避免重复迁移。
+    pub fn migrate(&mut self, kv: &KvStore<'_>, scratch: &mut [u8]) -> Result<MigrationReport, IdfNvsError> {
+        let mut report = MigrationReport { corrupt: self.corrupt, ..Default::default() };
+        let mut pending: Vec<PendingBlob, MAX_BLOBS> = Vec::new();
+
+        for i in 0..self.pages.len() {
+            self.read_page(self.pages[i].1)?;
+            for item in items(&self.buf) {
+                if item.namespace == NS_INDEX || item.item_type == ItemType::BlobData {
+                    continue;
+                }
+                let Some((_, ns_name)) = self.namespaces.iter().find(|(index, _)| *index == item.namespace) else {
+                    continue;
+                };
+                pending.retain(|b| !(b.namespace == item.namespace && b.key == item.key));
+
+                if let Some((size, chunks, start)) = item.blob_index() {
+                    let mut key = String::new();
+                    let queued = key.push_str(item.key).is_ok()
+                        && pending.push(PendingBlob { namespace: item.namespace, key, size, chunks, start }).is_ok();
+                    if !queued {
+                        report.skipped += 1;
+                    }
+                    continue;
+                }
+                match kv.set(ns_name, item.key, item.data()) {
+                    Ok(()) => report.migrated += 1,
+                    Err(KvError::InvalidKey | KvError::TooLarge) => report.skipped += 1,
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        }
+
+        for blob in &pending {
+            let size = blob.size as usize;
+            let Some(buf) = scratch.get_mut(..size) else {
+                report.skipped += 1;
+                continue;
+            };
+            let len = match self.read_blob(blob.namespace, &blob.key, blob.chunks, blob.start, buf) {
+                Ok(len) if len == size => len,
+                _ => {
+                    report.skipped += 1;
+                    continue;
+                }
+            };
+            let Some((_, ns_name)) = self.namespaces.iter().find(|(index, _)| *index == blob.namespace) else {
+                continue;
+            };
+            match kv.set(ns_name, &blob.key, &buf[..len]) {
+                Ok(()) => report.migrated += 1,
+                Err(KvError::InvalidKey | KvError::TooLarge) => report.skipped += 1,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 构造条目 (计算条目 CRC)
+    fn entry(ns: u8, item_type: u8, span: u8, chunk: u8, key: &str, data: [u8; 8]) -> [u8; ENTRY_SIZE] {
+        let mut e = [0u8; ENTRY_SIZE];
+        e[..4].copy_from_slice(&[ns, item_type, span, chunk]);
+        e[8..8 + key.len()].copy_from_slice(key.as_bytes());
+        e[24..].copy_from_slice(&data);
+        let crc = crc32_le(crc32_le(0xFFFF_FFFF, &e[0..4]), &e[8..32]);
+        e[4..8].copy_from_slice(&crc.to_le_bytes());
+        e
+    }
+
+    /// 构造空白的 ACTIVE 页
+    fn page(seq: u32) -> [u8; PAGE_SIZE] {
+        let mut p = [0xFFu8; PAGE_SIZE];
+        p[0..4].copy_from_slice(&0xFFFF_FFFEu32.to_le_bytes());
+        p[4..8].copy_from_slice(&seq.to_le_bytes());
+        p[8] = VERSION_2;
+        let crc = crc32_le(0xFFFF_FFFF, &p[4..28]);
+        p[28..32].copy_from_slice(&crc.to_le_bytes());
+        p
+    }
+
+    fn put(p: &mut [u8], index: usize, bytes: &[u8]) {
+        for (i, chunk) in bytes.chunks(ENTRY_SIZE).enumerate() {
+            let offset = ENTRY_OFFSET + (index + i) * ENTRY_SIZE;
+            p[offset..offset + chunk.len()].copy_from_slice(chunk);
+            let bit = index + i;
+            p[BITMAP_OFFSET + bit / 4] &= !(0b01 << ((bit % 4) * 2));
+        }
+    }
+
+    #[test]
+    fn test_parse_page() {
+        assert_eq!(crc32_le(0xFFFF_FFFF, b"123456789"), 0xD202_D277);
+
+        let mut p = page(3);
+        put(&mut p, 0, &entry(0, 0x01, 1, 0xFF, "wifi", [1, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]));
+        put(&mut p, 1, &entry(1, 0x14, 1, 0xFF, "retries", [0xFD, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]));
+
+        let ssid = b"office\0";
+        let mut data = [0xFFu8; 8];
+        data[..4].copy_from_slice(&[ssid.len() as u8, 0, 0xFF, 0xFF]);
+        data[4..].copy_from_slice(&crc32_le(0xFFFF_FFFF, ssid).to_le_bytes());
+        put(&mut p, 2, &entry(1, 0x21, 2, 0xFF, "ssid", data));
+        put(&mut p, 3, ssid);
+
+        // 损坏条目被跳过
+        let mut bad = entry(1, 0x04, 1, 0xFF, "bad", [0; 8]);
+        bad[30] ^= 1;
+        put(&mut p, 4, &bad);
+
+        let header = PageHeader::parse(&p);
+        assert_eq!(header.state, PageState::Active);
+        assert_eq!(header.seq, 3);
+        assert!(header.is_v2());
+
+        let mut iter = items(&p);
+        let ns = iter.next().unwrap();
+        assert_eq!((ns.namespace, ns.key, ns.value), (0, "wifi", &[1u8][..]));
+        let retries = iter.next().unwrap();
+        assert_eq!(retries.item_type, ItemType::I32);
+        assert_eq!(i32::from_le_bytes(retries.value.try_into().unwrap()), -3);
+        let s = iter.next().unwrap();
+        assert_eq!((s.item_type, s.data()), (ItemType::Str, &b"office"[..]));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.corrupt, 1);
+    }
+
+    #[cfg(feature = "nvs-encryption")]
+    #[test]
+    fn test_decrypt_entry() {
+        let mut keys = [0u8; NvsKeys::SIZE];
+        keys[..32].fill(0x11);
+        keys[32..64].fill(0x22);
+        keys[64..].copy_from_slice(&0x3CCF_EF2Cu32.to_le_bytes());
+        let keys = NvsKeys::from_bytes(&keys).unwrap();
+        assert!(NvsKeys::from_bytes(&[0xFF; NvsKeys::SIZE]).is_err());
+
+        // 页 1 条目 0 (分区偏移 0x1040) 的 XTS-AES-256 密文
+        let cipher: [u8; 32] = [
+            0xae, 0x8c, 0xe6, 0x67, 0xb5, 0x61, 0x1e, 0x4d, 0x39, 0xfe, 0xb8, 0xad, 0x8d, 0x28, 0x11, 0xf1,
+            0xfa, 0x42, 0x2f, 0x93, 0x9a, 0x94, 0xe8, 0x07, 0x02, 0xc8, 0x9b, 0xcf, 0x66, 0xde, 0x35, 0x27,
+        ];
+        let mut p = page(1);
+        put(&mut p, 0, &cipher);
+        keys.decrypt_page(1, &mut p);
+
+        let item = items(&p).next().unwrap();
+        assert_eq!((item.namespace, item.key, item.item_type), (1, "boot", ItemType::U32));
+        assert_eq!(item.value, &0x1234_5678u32.to_le_bytes());
+    }
+}
@@ -0,0 +1,194 @@
+//! 键值存储
+//!
+//! 基于文件系统的命名空间键值存储，语义与 ESP-IDF NVS 的 blob 接口一致，
+//! Rust 服务与 C 组件 (经 `ffi::nvs`) 共用同一份数据，无需第二套存储栈:
+//! - 每个值保存为 `<根目录>/<命名空间>/<键>` 文件
+//! - 写入先写临时文件再重命名，掉电安全
+//! - 命名空间与键名限制沿用 NVS (最长 15 字节)
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::fs::kv::KvStore;
+//!
+//! let kv = KvStore::new(&fs);
+//! kv.set("wifi", "ssid", b"office")?;
+//!
+//! let mut buf = [0u8; 32];
+//! let len = kv.get("wifi", "ssid", &mut buf)?;
+//! ```
+
+use core::fmt;
+
+use heapless::String;
+
+use super::littlefs::{FileSystem, FsError, OpenOptions};
+
+/// 默认根目录
+pub const KV_ROOT: &str = "/nvs";
+
+/// 命名空间 / 键名最大长度
+pub const MAX_KEY_LEN: usize = 15;
+
+/// 值最大长度
+pub const MAX_VALUE_LEN: usize = 4000;
+
+/// 完整路径缓冲区
+type KvPath = String<64>;
+
+/// 键值存储错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KvError {
+    /// 命名空间或键名无效
+    InvalidKey,
+    /// 值过长
+    TooLarge,
+    /// 缓冲区不足 (附带所需长度)
+    BufferTooSmall(usize),
+    /// 文件系统错误
+    Fs(FsError),
+}
+
+impl fmt::Display for KvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidKey => write!(f, "Invalid namespace or key"),
+            Self::TooLarge => write!(f, "Value too large"),
+            Self::BufferTooSmall(n) => write!(f, "Buffer too small ({} bytes needed)", n),
+            Self::Fs(e) => write!(f, "Filesystem error: {}", e),
+        }
+    }
+}
+
+impl From<FsError> for KvError {
+    fn from(e: FsError) -> Self {
+        Self::Fs(e)
+    }
+}
+
+/// 检查命名空间或键名 (1-15 字节，字母、数字、`_`、`-`、`.`，不以 `.` 开头)
+pub fn validate_key(key: &str) -> Result<(), KvError> {
+    let valid = !key.is_empty()
+        && key.len() <= MAX_KEY_LEN
+        && !key.starts_with('.')
+        && key.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-' | b'.'));
+    if valid { Ok(()) } else { Err(KvError::InvalidKey) }
+}
+
+/// 键值存储
+#[derive(Clone, Copy)]
+pub struct KvStore<'a> {
+    fs: &'a FileSystem,
+    root: &'static str,
+}
+
+impl<'a> KvStore<'a> {
+    /// 使用默认根目录创建
+    pub const fn new(fs: &'a FileSystem) -> Self {
+        Self { fs, root: KV_ROOT }
+    }
+
+    /// 设置根目录
+    pub const fn with_root(mut self, root: &'static str) -> Self {
+        self.root = root;
+        self
+    }
+
+    fn path(&self, namespace: &str, key: Option<&str>) -> Result<KvPath, KvError> {
+        validate_key(namespace)?;
+        let mut path = KvPath::new();
+        let joined = path
+            .push_str(self.root)
+            .and_then(|_| path.push('/'))
+            .and_then(|_| path.push_str(namespace));
+        let joined = match key {
+            Some(key) => {
+                validate_key(key)?;
+                joined.and_then(|_| path.push('/')).and_then(|_| path.push_str(key))
+            }
+            None => joined,
+        };
+        joined.map_err(|_| KvError::Fs(FsError::PathTooLong))?;
+        Ok(path)
+    }
+
+    /// 读取值，返回长度
+    pub fn get(&self, namespace: &str, key: &str, buf: &mut [u8]) -> Result<usize, KvError> {
+        let path = self.path(namespace, Some(key))?;
+        let mut file = self.fs.open(&path, OpenOptions::read_only())?;
+        let size = file.size() as usize;
+        if size > buf.len() {
+            return Err(KvError::BufferTooSmall(size));
+        }
+        let mut filled = 0;
+        while filled < size {
+            match file.read(&mut buf[filled..size])? {
+                0 => break,
+                n => filled += n,
+            }
+        }
+        Ok(filled)
+    }
+
+    /// 值长度
+    pub fn len(&self, namespace: &str, key: &str) -> Result<usize, KvError> {
+        let path = self.path(namespace, Some(key))?;
+        Ok(self.fs.metadata(&path)?.size as usize)
+    }
+
+    /// 是否存在
+    pub fn contains(&self, namespace: &str, key: &str) -> bool {
+        self.path(namespace, Some(key)).is_ok_and(|path| self.fs.exists(&path).unwrap_or(false))
+    }
+
+    /// 写入值 (覆盖)
+    pub fn set(&self, namespace: &str, key: &str, value: &[u8]) -> Result<(), KvError> {
+        self.set_parts(namespace, key, &[value])
+    }
+
+    /// 将多段数据拼接后作为一个值写入 (免去调用方拼接缓冲区)
+    pub fn set_parts(&self, namespace: &str, key: &str, parts: &[&[u8]]) -> Result<(), KvError> {
+        if parts.iter().map(|p| p.len()).sum::<usize>() > MAX_VALUE_LEN {
+            return Err(KvError::TooLarge);
+        }
+        let path = self.path(namespace, Some(key))?;
+        self.fs.create_dir_all(&self.path(namespace, None)?)?;
+
+        let mut tmp = path.clone();
+        tmp.push_str(".tmp").map_err(|_| FsError::PathTooLong)?;
+        {
+            let mut file = self.fs.open(&tmp, OpenOptions::write_only())?;
+            for part in parts {
+                file.write_all(part)?;
+            }
+            file.sync()?;
+        }
+        if self.fs.exists(&path)? {
+            self.fs.remove(&path)?;
+        }
+        self.fs.rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    /// 删除键 (不存在时返回 `Fs(NotFound)`)
+    pub fn remove(&self, namespace: &str, key: &str) -> Result<(), KvError> {
+        let path = self.path(namespace, Some(key))?;
+        self.fs.remove(&path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_key() {
+        assert!(validate_key("wifi").is_ok());
+        assert!(validate_key("phy_cal.v2").is_ok());
+        assert_eq!(validate_key(""), Err(KvError::InvalidKey));
+        assert_eq!(validate_key(".."), Err(KvError::InvalidKey));
+        assert_eq!(validate_key("a/b"), Err(KvError::InvalidKey));
+        assert_eq!(validate_key("sixteen-chars-xx"), Err(KvError::InvalidKey));
+    }
+}
@@ -1,10 +1,54 @@
 //! LittleFS 文件系统封装
 //!
 //! 提供基于 littlefs2 的文件系统操作 API
+//!
+//! LittleFS 在 `mount` 时挂载一次，挂载状态与打开的 littlefs2 文件保存在 `FileSystem` 内部：
+//! 文件在首次读写时打开并按句柄槽位缓存，写入在 `sync`、`close` 或 drop 时提交。
+//! `FileSystem` 在挂载后被移动 (例如放入 `StaticCell`) 时，下一次操作重新挂载一次。
+//! 同一时刻只允许一个操作，重入 (例如在另一个操作进行中访问) 返回 `Storage(Busy)`。
+//!
+//! 类型参数 `P` 指定挂载参数 (块数、缓存、lookahead、块周期)，默认 `DefaultProfile`；
+//! 参数不同的多个分区可同时挂载，并注册到 `fs::vfs` 挂载表。
+//!
+//! 连续存储文件的数据位于分区末尾的连续存储区域，LittleFS 中只保留一个空的
+//! 占位文件 (目录遍历、删除和重命名照常工作)；区段表保存在 `EXTENT_TABLE_PATH`，
+//! 每次变化后写回，挂载时加载。
 
+use core::cell::{RefCell, UnsafeCell};
 use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use generic_array::typenum::Unsigned;
+use littlefs2::fs::{Allocation, File as LfsFile, FileAllocation, Filesystem, OpenOptions as LfsOpenOptions};
+use littlefs2::io::{Error as LfsError, Read, Result as LfsResult, Seek, SeekFrom as LfsSeekFrom, Write};
+use littlefs2::path::{Path, PathBuf};
+
+use super::storage::littlefs_adapter::{DefaultProfile, LfsProfile, LfsStorageAdapter, LFS_BLOCK_SIZE};
 use super::storage::{FlashStorage, StorageError};
 
+/// 连续区段表容量
+pub const MAX_EXTENTS: usize = 8;
+
+/// 区段表文件
+pub const EXTENT_TABLE_PATH: &str = "/.extents";
+
+/// 区段表文件魔数
+const EXTENT_TABLE_MAGIC: [u8; 4] = *b"EXT1";
+
+/// 区段表记录长度: 路径长度 (1) + 路径 (64) + 起始块、数据长度、容量 (各 4) + 固定标志 (1)
+const EXTENT_RECORD_LEN: usize = 1 + 64 + 4 + 4 + 4 + 1;
+
+/// 区段表文件最大长度: 魔数 + 记录数 + 记录
+const EXTENT_TABLE_LEN: usize = 4 + 1 + MAX_EXTENTS * EXTENT_RECORD_LEN;
+
+/// 同时打开的最大文件数 (每个槽位占用一个 littlefs2 文件缓存)
+pub const MAX_OPEN_FILES: usize = 8;
+
+/// LittleFS 路径长度上限
+pub const MAX_PATH_LEN: usize = 255;
+
 /// 文件系统错误
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FsError {
@@ -44,6 +88,10 @@ pub enum FsError {
     FormatFailed,
     /// IO 错误
     IoError,
+    /// 文件未连续存储，无法映射
+    NotContiguous,
+    /// 文件已固定 (映射期间禁止修改)
+    Pinned,
 }
 
 impl From<StorageError> for FsError {
@@ -52,6 +100,24 @@ impl From<StorageError> for FsError {
     }
 }
 
+impl From<LfsError> for FsError {
+    fn from(e: LfsError) -> Self {
+        match e {
+            LfsError::NoSuchEntry => Self::NotFound,
+            LfsError::EntryAlreadyExisted => Self::AlreadyExists,
+            LfsError::PathNotDir => Self::NotADirectory,
+            LfsError::PathIsDir => Self::NotAFile,
+            LfsError::DirNotEmpty => Self::DirectoryNotEmpty,
+            LfsError::BadFileDescriptor => Self::InvalidHandle,
+            LfsError::FileTooBig | LfsError::NoSpace => Self::NoSpace,
+            LfsError::Invalid => Self::InvalidParam,
+            LfsError::FilenameTooLong => Self::NameTooLong,
+            LfsError::Corruption => Self::Corrupt,
+            _ => Self::IoError,
+        }
+    }
+}
+
 impl fmt::Display for FsError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -73,6 +139,8 @@ impl fmt::Display for FsError {
             Self::MountFailed => write!(f, "Mount failed"),
             Self::FormatFailed => write!(f, "Format failed"),
             Self::IoError => write!(f, "IO error"),
+            Self::NotContiguous => write!(f, "File not stored contiguously"),
+            Self::Pinned => write!(f, "File is pinned"),
         }
     }
 }
@@ -196,10 +264,122 @@ impl OpenOptions {
     }
 }
 
+/// 连续存储区段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Extent {
+    /// 起始块号
+    pub first_block: u32,
+    /// 区段长度 (字节)
+    pub len: u32,
+}
+
+impl Extent {
+    /// 占用的块数
+    pub fn block_count(&self, block_size: u32) -> u32 {
+        self.len.div_ceil(block_size)
+    }
+}
+
+/// 区段表项
+#[derive(Debug, Clone)]
+struct ExtentEntry {
+    path: heapless::String<64>,
+    extent: Extent,
+    /// 已分配块数 (预分配时可大于数据长度)
+    capacity: u32,
+    pinned: bool,
+}
+
+/// 打开文件的句柄状态
+#[derive(Debug, Clone)]
+struct OpenHandle {
+    /// 文件路径
+    path: heapless::String<64>,
+    /// 打开选项
+    options: OpenOptions,
+    /// 上次同步后是否有写入
+    dirty: bool,
+    /// 打开时文件数据所在的连续区段 (为 `None` 时数据在 LittleFS 中)
+    extent: Option<Extent>,
+}
+
+/// 文件句柄表
+///
+/// 句柄 ID 低 8 位为槽位下标，高位为槽位的分配代数，
+/// 槽位被重新分配后旧 ID 失效 (返回 `InvalidHandle`)，不会与新文件混淆
+struct HandleTable {
+    slots: [Option<OpenHandle>; MAX_OPEN_FILES],
+    generations: [u32; MAX_OPEN_FILES],
+}
+
+impl HandleTable {
+    const fn new() -> Self {
+        Self {
+            slots: [const { None }; MAX_OPEN_FILES],
+            generations: [0; MAX_OPEN_FILES],
+        }
+    }
+
+    /// 分配句柄
+    fn open(&mut self, path: &str, options: OpenOptions, extent: Option<Extent>) -> Result<u32, FsError> {
+        let index = self
+            .slots
+            .iter()
+            .position(Option::is_none)
+            .ok_or(FsError::TooManyOpenFiles)?;
+        let mut name = heapless::String::new();
+        name.push_str(path).map_err(|_| FsError::PathTooLong)?;
+
+        // 代数从 1 开始，ID 永不为 0
+        let generation = match self.generations[index].wrapping_add(1) & 0x00FF_FFFF {
+            0 => 1,
+            g => g,
+        };
+        self.generations[index] = generation;
+        self.slots[index] = Some(OpenHandle { path: name, options, dirty: false, extent });
+        Ok(generation << 8 | index as u32)
+    }
+
+    fn index(&self, id: u32) -> Result<usize, FsError> {
+        let index = (id & 0xFF) as usize;
+        match self.slots.get(index) {
+            Some(Some(_)) if self.generations[index] == id >> 8 => Ok(index),
+            _ => Err(FsError::InvalidHandle),
+        }
+    }
+
+    /// 查询句柄状态
+    fn get_mut(&mut self, id: u32) -> Result<&mut OpenHandle, FsError> {
+        let index = self.index(id)?;
+        self.slots[index].as_mut().ok_or(FsError::InvalidHandle)
+    }
+
+    /// 释放句柄
+    fn close(&mut self, id: u32) -> Result<OpenHandle, FsError> {
+        let index = self.index(id)?;
+        self.slots[index].take().ok_or(FsError::InvalidHandle)
+    }
+
+    /// 是否有句柄打开了 `path` (`write` 为 true 时只统计可写句柄)
+    fn is_open(&self, path: &str, write: bool) -> bool {
+        self.slots
+            .iter()
+            .flatten()
+            .any(|h| h.path == path && (!write || h.options.write))
+    }
+
+    /// 已打开的句柄数
+    fn len(&self) -> usize {
+        self.slots.iter().filter(|s| s.is_some()).count()
+    }
+}
+
 /// 文件句柄
-pub struct File<'a> {
+///
+/// 占用文件系统句柄表中的一个槽位，drop 时提交写入 (关闭 littlefs2 文件) 并释放
+pub struct File<'a, P: LfsProfile = DefaultProfile> {
     /// 文件系统引用
-    fs: &'a FileSystem,
+    fs: &'a FileSystem<P>,
     /// 内部文件 ID
     id: u32,
     /// 打开选项
@@ -208,9 +388,13 @@ pub struct File<'a> {
     position: u32,
     /// 文件大小 (缓存)
     size: u32,
+    /// 连续存储区段 (若有)
+    extent: Option<Extent>,
+    /// 是否已固定
+    pinned: bool,
 }
 
-impl<'a> File<'a> {
+impl<'a, P: LfsProfile> File<'a, P> {
     /// 读取数据
     pub fn read(&mut self, buffer: &mut [u8]) -> Result<usize, FsError> {
         if !self.options.read {
@@ -237,6 +421,9 @@ impl<'a> File<'a> {
         if !self.options.write {
             return Err(FsError::InvalidParam);
         }
+        if self.pinned {
+            return Err(FsError::Pinned);
+        }
 
         // 调用底层写入
         let written = self.fs.write_file_internal(self.id, self.position, data)?;
@@ -299,6 +486,9 @@ impl<'a> File<'a> {
         if !self.options.write {
             return Err(FsError::InvalidParam);
         }
+        if self.pinned {
+            return Err(FsError::Pinned);
+        }
 
         self.fs.truncate_file_internal(self.id, size)?;
         self.size = size;
@@ -309,6 +499,47 @@ impl<'a> File<'a> {
 
         Ok(())
     }
+
+    /// 获取文件内容的内存映射视图 (零拷贝)
+    ///
+    /// 仅当文件以连续区段存储 (预分配或碎片整理后) 且已固定时可用，
+    /// 否则返回 `FsError::NotContiguous`。适用于网页资源、模型等只读大文件。
+    ///
+    /// # 示例
+    ///
+    /// ```rust,ignore
+    /// fs.pin("/www/app.js")?;
+    /// let file = fs.open("/www/app.js", OpenOptions::read_only())?;
+    /// let body: &[u8] = file.map()?;
+    /// ```
+    pub fn map(&self) -> Result<&'a [u8], FsError> {
+        if !self.options.read {
+            return Err(FsError::InvalidParam);
+        }
+        match self.extent {
+            Some(extent) if self.pinned && extent.len >= self.size => {
+                self.fs.with_storage(|storage| Ok(storage.inner().map(extent.first_block, self.size)?))
+            }
+            _ => Err(FsError::NotContiguous),
+        }
+    }
+
+    /// 是否可以映射
+    pub fn is_mappable(&self) -> bool {
+        self.pinned && self.extent.is_some_and(|e| e.len >= self.size)
+    }
+
+    /// 关闭文件，返回提交结果 (drop 时会忽略提交错误)
+    pub fn close(self) -> Result<(), FsError> {
+        // 句柄已释放，随后的 drop 不再重复关闭
+        self.fs.close_file_internal(self.id)
+    }
+}
+
+impl<P: LfsProfile> Drop for File<'_, P> {
+    fn drop(&mut self) {
+        let _ = self.fs.close_file_internal(self.id);
+    }
 }
 
 /// 文件指针位置
@@ -323,19 +554,21 @@ pub enum SeekFrom {
 }
 
 /// 目录迭代器
-pub struct Dir<'a> {
+///
+/// 不包含 `.` 与 `..`；遍历期间修改目录可能导致条目被跳过或重复
+pub struct Dir<'a, P: LfsProfile = DefaultProfile> {
     /// 文件系统引用
-    fs: &'a FileSystem,
-    /// 内部目录 ID
-    id: u32,
+    fs: &'a FileSystem<P>,
+    /// 目录路径
+    path: PathBuf,
     /// 迭代索引
     index: u32,
 }
 
-impl<'a> Dir<'a> {
+impl<'a, P: LfsProfile> Dir<'a, P> {
     /// 读取下一个目录项
     pub fn next(&mut self) -> Result<Option<Metadata>, FsError> {
-        let result = self.fs.read_dir_internal(self.id, self.index)?;
+        let result = self.fs.read_dir_internal(&self.path, self.index)?;
         if result.is_some() {
             self.index += 1;
         }
@@ -349,6 +582,9 @@ impl<'a> Dir<'a> {
 }
 
 /// 文件系统配置
+///
+/// LittleFS 实际使用的几何参数来自挂载参数配置 (`LfsProfile`) 的编译期常量，
+/// 挂载时校验块大小与块数；缓存、lookahead 与块周期在创建时按配置填入，其余字段仅作记录
 #[derive(Debug, Clone, Copy)]
 pub struct FsConfig {
     /// 块大小
@@ -365,6 +601,8 @@ pub struct FsConfig {
     pub lookahead_size: u32,
     /// 块周期 (磨损均衡)
     pub block_cycles: i32,
+    /// 分区末尾保留给连续存储文件的块数 (LittleFS 不使用这些块)
+    pub extent_blocks: u32,
 }
 
 impl Default for FsConfig {
@@ -377,143 +615,265 @@ impl Default for FsConfig {
             cache_size: 512,
             lookahead_size: 16,
             block_cycles: 500,
+            extent_blocks: 0,
         }
     }
 }
 
-/// LittleFS 文件系统
-pub struct FileSystem {
+impl FsConfig {
+    /// 将 read/prog 大小向上取整到存储的最小读写单位
+    ///
+    /// 加密分区要求 32 字节对齐；缓存大小随之调整为二者的公倍数
+    pub fn aligned_to(mut self, unit: u32) -> Self {
+        let unit = unit.max(1);
+        self.read_size = self.read_size.max(1).next_multiple_of(unit);
+        self.prog_size = self.prog_size.max(1).next_multiple_of(unit);
+        let (mut a, mut b) = (self.read_size, self.prog_size);
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        self.cache_size = self.cache_size.next_multiple_of(self.read_size / a * self.prog_size);
+        self
+    }
+}
+
+/// 挂载后的 littlefs2 文件系统 (借用同一 `Lfs` 中的 `alloc` 与 `storage`)
+type Mounted<P> = Filesystem<'static, LfsStorageAdapter<P>>;
+
+/// 打开的 littlefs2 文件 (借用同一 `Lfs` 中的挂载状态与文件分配)
+type OpenLfsFile<P> = LfsFile<'static, 'static, LfsStorageAdapter<P>>;
+
+/// LittleFS 运行状态
+///
+/// 挂载状态与打开的文件借用本结构中的字段，只在结构不移动时有效：
+/// 每次访问前比较当前地址与挂载时的地址，不一致时丢弃 (不访问) 旧状态并重新挂载
+struct Lfs<P: LfsProfile> {
+    /// littlefs2 状态与缓存
+    alloc: Allocation<LfsStorageAdapter<P>>,
     /// 存储适配器
-    storage: super::storage::littlefs_adapter::LfsStorageAdapter,
+    storage: LfsStorageAdapter<P>,
+    /// 挂载状态
+    mounted: Option<Mounted<P>>,
+    /// 挂载时本结构的地址
+    mounted_at: *const Self,
+    /// 各句柄槽位打开的文件 (句柄 ID, 文件)
+    files: [Option<(u32, OpenLfsFile<P>)>; MAX_OPEN_FILES],
+    /// 各句柄槽位的文件缓存
+    file_allocs: [FileAllocation<LfsStorageAdapter<P>>; MAX_OPEN_FILES],
+}
+
+impl<P: LfsProfile> Lfs<P> {
+    fn new(storage: LfsStorageAdapter<P>) -> Self {
+        Self {
+            alloc: Filesystem::allocate(),
+            storage,
+            mounted: None,
+            mounted_at: core::ptr::null(),
+            files: [const { None }; MAX_OPEN_FILES],
+            file_allocs: core::array::from_fn(|_| OpenLfsFile::<P>::allocate()),
+        }
+    }
+
+    /// 结构已被移动时丢弃挂载状态与打开的文件
+    ///
+    /// 旧状态中的指针指向移动前的地址，不能再关闭 (littlefs2 的文件与挂载状态没有 drop 逻辑，直接丢弃)；
+    /// 打开的文件借用 `FileSystem`，移动时不会有文件在使用，之后按需重新打开
+    fn forget_if_moved(&mut self) {
+        if core::ptr::eq(self.mounted_at, self) {
+            return;
+        }
+        self.files = [const { None }; MAX_OPEN_FILES];
+        self.mounted = None;
+    }
+
+    /// 挂载状态 (尚未挂载时挂载)
+    fn filesystem(&mut self) -> LfsResult<&Mounted<P>> {
+        self.forget_if_moved();
+        if self.mounted.is_none() {
+            // Safety: 挂载状态只借用本结构的字段，结构移动后在使用前被丢弃 (见 `forget_if_moved`)；
+            // 直接访问 `storage` 的操作与 littlefs2 调用由 `FileSystem::busy` 串行化，不会交叠
+            let (alloc, storage) = unsafe {
+                (&mut *core::ptr::addr_of_mut!(self.alloc), &mut *core::ptr::addr_of_mut!(self.storage))
+            };
+            self.mounted = Some(Filesystem::mount(alloc, storage)?);
+            self.mounted_at = self as *const Self;
+        }
+        self.mounted.as_ref().ok_or(LfsError::Invalid)
+    }
+
+    /// 句柄 `id` 的 littlefs2 文件 (首次访问时打开并缓存在句柄槽位中)
+    fn file(&mut self, id: u32, path: &Path, write: bool) -> LfsResult<&OpenLfsFile<P>> {
+        let fs: *const Mounted<P> = self.filesystem()?;
+        let index = (id & 0xFF) as usize;
+        // 槽位中是已释放句柄未能关闭的文件
+        if self.files[index].as_ref().is_some_and(|(cached, _)| *cached != id) {
+            self.close_slot(index)?;
+        }
+        if self.files[index].is_none() {
+            let alloc = core::ptr::addr_of_mut!(self.file_allocs[index]);
+            // Safety: 文件借用的挂载状态与文件缓存都在本结构中，文件在挂载状态丢弃前关闭或遗忘
+            let file = unsafe { LfsOpenOptions::new().read(true).write(write).open(&*fs, &mut *alloc, path)? };
+            self.files[index] = Some((id, file));
+        }
+        self.files[index].as_ref().map(|(_, file)| file).ok_or(LfsError::Invalid)
+    }
+
+    /// 关闭 (提交) 句柄 `id` 打开的文件
+    fn close(&mut self, id: u32) -> LfsResult<()> {
+        self.forget_if_moved();
+        let index = (id & 0xFF) as usize;
+        match &self.files[index] {
+            Some((cached, _)) if *cached == id => self.close_slot(index),
+            _ => Ok(()),
+        }
+    }
+
+    fn close_slot(&mut self, index: usize) -> LfsResult<()> {
+        match self.files[index].take() {
+            // Safety: 挂载状态与文件缓存仍有效
+            Some((_, file)) => unsafe { file.close() },
+            None => Ok(()),
+        }
+    }
+
+    /// 关闭所有文件并丢弃挂载状态，返回第一个关闭错误
+    fn unmount(&mut self) -> LfsResult<()> {
+        self.forget_if_moved();
+        let mut result = Ok(());
+        for index in 0..MAX_OPEN_FILES {
+            let closed = self.close_slot(index);
+            result = result.and(closed);
+        }
+        self.mounted = None;
+        result
+    }
+}
+
+/// LittleFS 文件系统
+pub struct FileSystem<P: LfsProfile = DefaultProfile> {
+    /// LittleFS 状态 (由 `busy` 串行化访问)
+    lfs: UnsafeCell<Lfs<P>>,
+    /// 是否有操作正在访问 `lfs`
+    busy: AtomicBool,
     /// 文件系统配置
     config: FsConfig,
     /// 是否已挂载
     mounted: bool,
-    /// 下一个文件 ID
-    next_file_id: u32,
-    /// 下一个目录 ID
-    next_dir_id: u32,
+    /// 打开文件句柄表
+    handles: BlockingMutex<CriticalSectionRawMutex, RefCell<HandleTable>>,
+    /// 连续存储文件的区段表
+    extents: BlockingMutex<CriticalSectionRawMutex, RefCell<heapless::Vec<ExtentEntry, MAX_EXTENTS>>>,
 }
 
+// Safety: 对 `lfs` 的共享访问由 `busy` 标志串行化 (见 `with_state`)；
+// littlefs2 状态中的指针只指向 `lfs` 自身，结构移动后在下一次访问前重新挂载
+unsafe impl<P: LfsProfile> Send for FileSystem<P> {}
+unsafe impl<P: LfsProfile> Sync for FileSystem<P> {}
+
 impl FileSystem {
-    /// 创建文件系统实例
+    /// 创建文件系统实例 (默认挂载参数)
     pub fn new(storage: FlashStorage) -> Self {
-        let adapter = super::storage::littlefs_adapter::LfsStorageAdapter::new(storage);
-        let block_count = adapter.block_count();
+        Self::with_profile(storage, FsConfig::default())
+    }
 
-        Self {
-            storage: adapter,
-            config: FsConfig {
-                block_count,
-                ..Default::default()
-            },
-            mounted: false,
-            next_file_id: 1,
-            next_dir_id: 1,
-        }
+    /// 使用自定义配置创建 (默认挂载参数)
+    pub fn with_config(storage: FlashStorage, config: FsConfig) -> Self {
+        Self::with_profile(storage, config)
     }
+}
 
-    /// 使用自定义配置创建
-    pub fn with_config(storage: FlashStorage, mut config: FsConfig) -> Self {
-        let adapter = super::storage::littlefs_adapter::LfsStorageAdapter::new(storage);
+impl<P: LfsProfile> FileSystem<P> {
+    /// 使用挂载参数配置 `P` 创建
+    ///
+    /// `config.block_count` 为 0 时取自存储；缓存、lookahead 与块周期由 `P` 决定
+    pub fn with_profile(storage: FlashStorage, mut config: FsConfig) -> Self {
+        let adapter = LfsStorageAdapter::new(storage);
         
         if config.block_count == 0 {
             config.block_count = adapter.block_count();
         }
+        let config = config.aligned_to(adapter.io_unit());
 
+        Self::from_parts(adapter, config)
+    }
+
+    fn from_parts(storage: LfsStorageAdapter<P>, mut config: FsConfig) -> Self {
+        config.cache_size = P::CacheSize::U32;
+        config.lookahead_size = P::LookaheadSize::U32 * 8;
+        config.block_cycles = P::BLOCK_CYCLES as i32;
         Self {
-            storage: adapter,
+            lfs: UnsafeCell::new(Lfs::new(storage)),
+            busy: AtomicBool::new(false),
             config,
             mounted: false,
-            next_file_id: 1,
-            next_dir_id: 1,
+            handles: BlockingMutex::new(RefCell::new(HandleTable::new())),
+            extents: BlockingMutex::new(RefCell::new(heapless::Vec::new())),
         }
     }
 
     /// 挂载文件系统
     ///
-    /// # 实现说明
-    /// 当前使用简化的魔数检查。完整实现应使用 littlefs2 crate:
-    /// ```ignore
-    /// use littlefs2::fs::Filesystem;
-    /// let mut alloc = Filesystem::allocate();
-    /// Filesystem::mount(&mut alloc, storage)?;
-    /// ```
+    /// 分区中没有有效的 LittleFS 时返回 `Corrupt`，需先 `format`
     pub fn mount(&mut self) -> Result<(), FsError> {
         if self.mounted {
             return Ok(());
         }
 
-        // 初始化存储
-        self.storage.inner_mut().init()?;
-
-        // 简化实现: 读取超级块验证魔数
-        // 完整实现应使用 littlefs2::fs::Filesystem::mount()
-        let mut buffer = [0u8; 4096];
-        self.storage.read(0, 0, &mut buffer)?;
-        
-        // 检查 littlefs 魔数 "littlefs"
-        if &buffer[8..16] != b"littlefs" {
-            return Err(FsError::Corrupt);
+        self.check_layout()?;
+        let lfs = self.lfs.get_mut();
+        lfs.storage.inner_mut().init()?;
+
+        // 挂载失败时不保留挂载状态
+        match lfs.filesystem().map(drop) {
+            Ok(()) => {
+                self.mounted = true;
+                let loaded = self.load_extents();
+                if loaded.is_err() {
+                    self.mounted = false;
+                    let _ = self.lfs.get_mut().unmount();
+                }
+                loaded
+            }
+            Err(LfsError::Corruption) => Err(FsError::Corrupt),
+            Err(e) => Err(lfs_error(&mut lfs.storage, e, FsError::MountFailed)),
         }
-
-        self.mounted = true;
-        Ok(())
     }
 
     /// 卸载文件系统
     ///
-    /// # 实现说明
-    /// 完整实现应使用 littlefs2 crate 的 unmount 方法。
+    /// 提交并关闭仍缓存的 littlefs2 文件，同步存储
     pub fn unmount(&mut self) -> Result<(), FsError> {
         if !self.mounted {
             return Ok(());
         }
 
-        // 同步所有数据
-        self.storage.sync()?;
-
-        // 简化实现: 仅更新状态
-        // 完整实现应调用 littlefs2::fs::Filesystem::unmount()
-
         self.mounted = false;
-        Ok(())
+        let lfs = self.lfs.get_mut();
+        lfs.unmount().map_err(|e| lfs_error(&mut lfs.storage, e, FsError::IoError))?;
+        Ok(lfs.storage.sync()?)
     }
 
     /// 格式化文件系统
     ///
-    /// # 实现说明
-    /// 当前使用简化实现，只写入基本的魔数。
-    /// 完整实现应使用 littlefs2 crate:
-    /// ```ignore
-    /// use littlefs2::fs::Filesystem;
-    /// Filesystem::format(storage)?;
-    /// ```
+    /// 清除分区前 `P::BLOCK_COUNT` 个块中的所有文件 (包括区段表)，
+    /// 连续存储区域的数据不再被引用
     pub fn format(&mut self) -> Result<(), FsError> {
         // 如果已挂载，先卸载
         if self.mounted {
             self.unmount()?;
         }
 
-        // 初始化存储
-        self.storage.inner_mut().init()?;
+        self.check_layout()?;
+        let lfs = self.lfs.get_mut();
+        lfs.storage.inner_mut().init()?;
 
-        // 简化实现: 擦除前几个块并写入超级块
-        // 完整实现应使用 littlefs2::fs::Filesystem::format()
-        for block in 0..core::cmp::min(4, self.config.block_count) {
-            self.storage.erase(block)?;
+        match Filesystem::format(&mut lfs.storage) {
+            Ok(()) => {
+                self.extents.lock(|table| table.borrow_mut().clear());
+                Ok(lfs.storage.sync()?)
+            }
+            Err(e) => Err(lfs_error(&mut lfs.storage, e, FsError::FormatFailed)),
         }
-
-        // 写入简化的超级块 (包含 littlefs 魔数)
-        let mut superblock = [0xFFu8; 4096];
-        superblock[8..16].copy_from_slice(b"littlefs");
-        superblock[0..4].copy_from_slice(&0x00000002u32.to_le_bytes()); // version
-        superblock[4..8].copy_from_slice(&self.config.block_size.to_le_bytes());
-        
-        self.storage.prog(0, 0, &superblock)?;
-        self.storage.sync()?;
-
-        Ok(())
     }
 
     /// 检查是否已挂载
@@ -527,23 +887,16 @@ impl FileSystem {
     }
 
     /// 获取已用空间 (块数)
-    ///
-    /// # 实现说明
-    /// 当前返回 0，完整实现应使用 littlefs2 的 fs_size() 方法。
     pub fn used_blocks(&self) -> Result<u32, FsError> {
-        if !self.mounted {
-            return Err(FsError::NotMounted);
-        }
-
-        // 占位实现 - 完整实现应使用 littlefs2::fs::Filesystem::size()
-        
-        Ok(0) // 占位
+        self.with_lfs(|fs| Ok((fs.total_blocks() - fs.available_blocks()?) as u32))
     }
 
     /// 获取可用空间 (块数)
     pub fn free_blocks(&self) -> Result<u32, FsError> {
         let used = self.used_blocks()?;
-        Ok(self.config.block_count.saturating_sub(used))
+        Ok(self.config.block_count
+            .saturating_sub(self.config.extent_blocks)
+            .saturating_sub(used))
     }
 
     /// 获取总空间 (字节)
@@ -555,77 +908,146 @@ impl FileSystem {
 
     /// 打开文件
     ///
-    /// # 实现说明
-    /// 当前为占位实现，返回模拟的 File 结构。
-    /// 完整实现应使用 littlefs2 crate 的 file_open 方法。
-    pub fn open(&self, path: &str, options: OpenOptions) -> Result<File<'_>, FsError> {
-        if !self.mounted {
-            return Err(FsError::NotMounted);
-        }
-
-        // 占位实现 - 完整实现应使用 littlefs2::fs::Filesystem::open()
-        let id = self.allocate_file_id();
-        let size = if options.truncate { 0 } else { self.get_file_size(path)? };
-
+    /// 同时打开的文件数受 `MAX_OPEN_FILES` 限制，超出时返回 `TooManyOpenFiles`。
+    /// `create`/`create_new`/`truncate` 需要同时设置 `write` 或 `append`
+    pub fn open(&self, path: &str, options: OpenOptions) -> Result<File<'_, P>, FsError> {
+        let (id, size, extent, pinned) = self.open_handle(path, options)?;
         Ok(File {
             fs: self,
             id,
             options,
             position: if options.append { size } else { 0 },
             size,
+            extent,
+            pinned,
+        })
+    }
+
+    /// 分配句柄，返回 (ID, 大小, 区段, 是否固定)
+    fn open_handle(&self, path: &str, options: OpenOptions) -> Result<(u32, u32, Option<Extent>, bool), FsError> {
+        if !self.mounted {
+            return Err(FsError::NotMounted);
+        }
+
+        let writable = options.write || options.append;
+        if (options.create || options.create_new || options.truncate) && !writable {
+            return Err(FsError::InvalidParam);
+        }
+        let (extent, pinned) = self.extent_entry(path).unzip();
+        let pinned = pinned.unwrap_or(false);
+        if pinned && (options.truncate || options.append) {
+            return Err(FsError::Pinned);
+        }
+
+        let (size, extent) = match extent {
+            // 连续存储文件的数据位于区段中
+            Some(e) if !options.truncate => (e.len, Some(e)),
+            // 截断后数据改存 LittleFS: 先删除区段记录并写回区段表，再截断占位文件
+            Some(_) => {
+                self.forget_extent(path)?;
+                (self.prepare_file(path, options)?, None)
+            }
+            None => (self.prepare_file(path, options)?, None),
+        };
+        let id = self.handles.lock(|table| table.borrow_mut().open(path, options, extent))?;
+        Ok((id, size, extent, pinned))
+    }
+
+    /// 按打开选项创建或截断 LittleFS 文件，返回文件大小 (选项已由调用者校验)
+    fn prepare_file(&self, path: &str, options: OpenOptions) -> Result<u32, FsError> {
+        let path = lfs_path(path)?;
+        self.with_lfs(|fs| {
+            let exists = match fs.metadata(&path) {
+                Ok(meta) if meta.is_dir() => return Err(LfsError::PathIsDir),
+                Ok(_) => true,
+                Err(LfsError::NoSuchEntry) => false,
+                Err(e) => return Err(e),
+            };
+            match (exists, options) {
+                (true, OpenOptions { create_new: true, .. }) => Err(LfsError::EntryAlreadyExisted),
+                (false, OpenOptions { create: false, create_new: false, .. }) => Err(LfsError::NoSuchEntry),
+                (false, _) | (true, OpenOptions { truncate: true, .. }) => {
+                    fs.create_file_and_then(&path, |_| Ok(0))
+                }
+                (true, _) => Ok(fs.metadata(&path)?.len() as u32),
+            }
         })
     }
 
+    /// 当前打开的文件数
+    pub fn open_files(&self) -> usize {
+        self.handles.lock(|table| table.borrow().len())
+    }
+
+    /// 文件是否被打开
+    pub fn is_open(&self, path: &str) -> bool {
+        self.handles.lock(|table| table.borrow().is_open(path, false))
+    }
+
     /// 创建文件
-    pub fn create(&self, path: &str) -> Result<File<'_>, FsError> {
+    pub fn create(&self, path: &str) -> Result<File<'_, P>, FsError> {
         self.open(path, OpenOptions::write_only())
     }
 
-    /// 删除文件
-    ///
-    /// # 实现说明
-    /// 当前为占位实现。完整实现应使用 littlefs2 的 remove 方法。
+    /// 删除文件或空目录
     pub fn remove(&self, path: &str) -> Result<(), FsError> {
         if !self.mounted {
             return Err(FsError::NotMounted);
         }
 
-        // 占位实现 - 完整实现应使用 littlefs2::fs::Filesystem::remove()
-        let _ = path;
-        Ok(())
+        // 先删除区段记录: 中途掉电最多留下一个空的占位文件
+        self.forget_extent(path)?;
+
+        let lfs_path = lfs_path(path)?;
+        self.with_lfs(|fs| fs.remove(&lfs_path))
     }
 
     /// 重命名文件/目录
-    ///
-    /// # 实现说明
-    /// 当前为占位实现。完整实现应使用 littlefs2 的 rename 方法。
     pub fn rename(&self, old_path: &str, new_path: &str) -> Result<(), FsError> {
         if !self.mounted {
             return Err(FsError::NotMounted);
         }
 
-        // 占位实现 - 完整实现应使用 littlefs2::fs::Filesystem::rename()
-        let _ = (old_path, new_path);
-        Ok(())
+        let mut new_name = heapless::String::<64>::new();
+        new_name.push_str(new_path).map_err(|_| FsError::PathTooLong)?;
+        let (source, target) = (self.extent_entry(old_path), self.extent_entry(new_path));
+        if matches!(source, Some((_, true))) || matches!(target, Some((_, true))) {
+            return Err(FsError::Pinned);
+        }
+
+        // 占位文件随 LittleFS 重命名，成功后再更新区段表
+        let (from, to) = (lfs_path(old_path)?, lfs_path(new_path)?);
+        self.with_lfs(|fs| fs.rename(&from, &to))?;
+        if source.is_none() && target.is_none() {
+            return Ok(());
+        }
+        self.extents.lock(|table| {
+            let mut table = table.borrow_mut();
+            // 被覆盖文件的区段一并释放
+            table.retain(|e| e.path != new_name || e.path == old_path);
+            if let Some(entry) = table.iter_mut().find(|e| e.path == old_path) {
+                entry.path = new_name;
+            }
+        });
+        self.save_extents()
     }
 
     /// 获取文件元数据
     ///
-    /// # 实现说明
-    /// 当前返回默认值。完整实现应使用 littlefs2 的 stat 方法。
+    /// 连续存储文件的大小取区段中的数据长度
     pub fn metadata(&self, path: &str) -> Result<Metadata, FsError> {
-        if !self.mounted {
-            return Err(FsError::NotMounted);
-        }
+        let extent = self.extent(path);
+        let lfs_path = lfs_path(path)?;
+        let (file_type, size) = match self.with_lfs(|fs| fs.metadata(&lfs_path)) {
+            Ok(meta) if meta.is_dir() => (FileType::Directory, 0),
+            Ok(meta) => (FileType::File, extent.map_or(meta.len() as u32, |e| e.len)),
+            Err(e) => return Err(e),
+        };
 
-        // 占位实现 - 完整实现应使用 littlefs2::fs::Filesystem::metadata()
-        let _ = path;
-        
-        Ok(Metadata {
-            file_type: FileType::File,
-            size: 0,
-            name: heapless::String::new(),
-        })
+        let mut name = heapless::String::new();
+        let file_name = path.trim_end_matches('/').rsplit('/').next().unwrap_or("");
+        name.push_str(file_name).map_err(|_| FsError::NameTooLong)?;
+        Ok(Metadata { file_type, size, name })
     }
 
     /// 检查文件是否存在
@@ -637,22 +1059,254 @@ impl FileSystem {
         }
     }
 
-    // ==================== 目录操作 ====================
+    // ==================== 连续存储 ====================
 
-    /// 创建目录
+    /// 查询文件的连续存储区段
+    pub fn extent(&self, path: &str) -> Option<Extent> {
+        self.extent_entry(path).map(|(e, _)| e)
+    }
+
+    /// 固定文件: 禁止修改和重定位，之后可通过 `File::map` 映射
     ///
-    /// # 实现说明
-    /// 当前为占位实现。完整实现应使用 littlefs2 的 mkdir 方法。
-    pub fn create_dir(&self, path: &str) -> Result<(), FsError> {
+    /// 文件必须已连续存储，否则返回 `FsError::NotContiguous`；
+    /// 仍有可写句柄打开该文件时返回 `FsError::InvalidParam`
+    pub fn pin(&self, path: &str) -> Result<(), FsError> {
+        if self.handles.lock(|table| table.borrow().is_open(path, true)) {
+            return Err(FsError::InvalidParam);
+        }
+        self.set_pinned(path, true)
+    }
+
+    /// 取消固定 (调用者需保证不再持有映射视图)
+    pub fn unpin(&self, path: &str) -> Result<(), FsError> {
+        self.set_pinned(path, false)
+    }
+
+    /// 记录文件的连续存储区段并写回区段表 (由预分配/碎片整理调用)
+    pub(crate) fn record_extent(&self, path: &str, extent: Extent, capacity: u32) -> Result<(), FsError> {
+        let mut name = heapless::String::new();
+        name.push_str(path).map_err(|_| FsError::PathTooLong)?;
+        self.extents.lock(|table| {
+            let mut table = table.borrow_mut();
+            if let Some(entry) = table.iter_mut().find(|e| e.path == name) {
+                if entry.pinned {
+                    return Err(FsError::Pinned);
+                }
+                entry.extent = extent;
+                entry.capacity = capacity;
+                return Ok(());
+            }
+            table
+                .push(ExtentEntry { path: name, extent, capacity, pinned: false })
+                .map_err(|_| FsError::Full)
+        })?;
+        self.save_extents()
+    }
+
+    /// 连续存储区域 (分区末尾 `extent_blocks` 个块)
+    pub fn extent_region(&self) -> core::ops::Range<u32> {
+        let end = self.config.block_count;
+        end.saturating_sub(self.config.extent_blocks)..end
+    }
+
+    /// 预分配连续存储空间
+    ///
+    /// 在连续存储区域中分配 `size` 字节的连续块并预先擦除。之后可通过
+    /// `extent_writer` 顺序写入 (无需边写边擦除，适合摄像头/音频录制)。
+    /// 文件已有区段且容量足够时直接返回。
+    pub fn preallocate(&mut self, path: &str, size: u32) -> Result<Extent, FsError> {
         if !self.mounted {
             return Err(FsError::NotMounted);
         }
+        let block_size = self.config.block_size;
+        let need = size.div_ceil(block_size).max(1);
+
+        let existing = self.extents.lock(|table| {
+            table.borrow().iter().find(|e| e.path == path).map(|e| (e.extent, e.capacity, e.pinned))
+        });
+        match existing {
+            Some((_, _, true)) => return Err(FsError::Pinned),
+            Some((extent, capacity, false)) if capacity >= need => return Ok(extent),
+            Some(_) => self.forget_extent(path)?,
+            None => {}
+        }
 
-        // 占位实现 - 完整实现应使用 littlefs2::fs::Filesystem::create_dir()
-        let _ = path;
+        let first_block = self.find_free_run(need).ok_or(FsError::NoSpace)?;
+        // LittleFS 中的占位文件 (已存在时保留原内容，碎片整理复制完成后再截断)
+        self.prepare_file(path, OpenOptions::new().write(true).create(true))?;
+        let storage = &mut self.lfs.get_mut().storage;
+        for block in first_block..first_block + need {
+            storage.erase(block)?;
+        }
+
+        let extent = Extent { first_block, len: 0 };
+        self.record_extent(path, extent, need)?;
+        Ok(extent)
+    }
+
+    /// 打开预分配文件的顺序写入器
+    pub fn extent_writer(&mut self, path: &str) -> Result<ExtentWriter<'_, P>, FsError> {
+        let (extent, capacity, pinned) = self
+            .extents
+            .lock(|table| {
+                table.borrow().iter().find(|e| e.path == path).map(|e| (e.extent, e.capacity, e.pinned))
+            })
+            .ok_or(FsError::NotContiguous)?;
+        if pinned {
+            return Err(FsError::Pinned);
+        }
+        let mut name = heapless::String::new();
+        name.push_str(path).map_err(|_| FsError::PathTooLong)?;
+        Ok(ExtentWriter {
+            limit: capacity * self.config.block_size,
+            fs: self,
+            path: name,
+            first_block: extent.first_block,
+            capacity,
+            written: extent.len,
+        })
+    }
+
+    /// 碎片整理: 将文件重写到连续块中
+    ///
+    /// 文件内容被复制到连续存储区域，区段表写回后原 LittleFS 数据才被截断释放。
+    /// 成功后文件可通过 `pin` + `File::map` 零拷贝读取。
+    pub fn defragment(&mut self, path: &str) -> Result<Extent, FsError> {
+        if let Some(extent) = self.extent(path) {
+            return Ok(extent);
+        }
+
+        // 在登记区段前打开，句柄指向 LittleFS 中的原始数据
+        let (file_id, size, _, _) = self.open_handle(path, OpenOptions::read_write())?;
+
+        let result = self
+            .preallocate(path, size)
+            .and_then(|_| self.copy_into_extent(path, file_id, size));
+        if result.is_err() {
+            let _ = self.forget_extent(path);
+        }
+        self.close_file_internal(file_id)?;
+        result
+    }
+
+    fn copy_into_extent(&mut self, path: &str, file_id: u32, size: u32) -> Result<Extent, FsError> {
+        let mut buffer = [0u8; 256];
+        let mut writer = self.extent_writer(path)?;
+        let mut offset = 0u32;
+        while offset < size {
+            let chunk = core::cmp::min(buffer.len() as u32, size - offset) as usize;
+            let read = writer.fs.read_file_internal(file_id, offset, &mut buffer[..chunk])?;
+            if read == 0 {
+                return Err(FsError::IoError);
+            }
+            writer.write(&buffer[..read])?;
+            offset += read as u32;
+        }
+        // `finish` 写回区段表，之后截断不会丢失数据
+        let extent = writer.finish()?;
+
+        // 释放 LittleFS 中的原始数据
+        self.truncate_file_internal(file_id, 0)?;
+        Ok(extent)
+    }
+
+    /// 在连续存储区域中查找 `need` 个空闲连续块 (首次适配)
+    fn find_free_run(&self, need: u32) -> Option<u32> {
+        let mut used: heapless::Vec<(u32, u32), MAX_EXTENTS> = self.extents.lock(|table| {
+            table.borrow().iter().map(|e| (e.extent.first_block, e.capacity)).collect()
+        });
+        used.sort_unstable();
+        find_free_run(self.extent_region(), &used, need)
+    }
+
+    /// 删除区段记录并写回区段表 (文件被删除或改为非连续存储)
+    pub(crate) fn forget_extent(&self, path: &str) -> Result<(), FsError> {
+        let removed = self.extents.lock(|table| {
+            let mut table = table.borrow_mut();
+            match table.iter().position(|e| e.path == path) {
+                Some(i) if table[i].pinned => Err(FsError::Pinned),
+                Some(i) => {
+                    table.swap_remove(i);
+                    Ok(true)
+                }
+                None => Ok(false),
+            }
+        })?;
+        if removed {
+            self.save_extents()?;
+        }
         Ok(())
     }
 
+    /// 写回区段表 (LittleFS 文件关闭即提交，掉电时保留旧表或新表之一)
+    fn save_extents(&self) -> Result<(), FsError> {
+        let mut buffer = [0u8; EXTENT_TABLE_LEN];
+        let len = self.extents.lock(|table| encode_extents(&table.borrow(), &mut buffer));
+        let path = lfs_path(EXTENT_TABLE_PATH)?;
+        self.with_lfs(|fs| fs.create_file_and_then(&path, |file| file.write_all(&buffer[..len])))
+    }
+
+    /// 从区段表文件加载区段表 (文件不存在时为空表，内容无效时返回 `Corrupt`)
+    fn load_extents(&self) -> Result<(), FsError> {
+        let mut buffer = [0u8; EXTENT_TABLE_LEN];
+        let path = lfs_path(EXTENT_TABLE_PATH)?;
+        let read = self.with_lfs(|fs| {
+            fs.open_file_and_then(&path, |file| {
+                let mut done = 0;
+                while done < buffer.len() {
+                    match file.read(&mut buffer[done..])? {
+                        0 => break,
+                        n => done += n,
+                    }
+                }
+                Ok(done)
+            })
+        });
+        let table = match read {
+            Ok(len) => decode_extents(&buffer[..len]).ok_or(FsError::Corrupt)?,
+            Err(FsError::NotFound) => heapless::Vec::new(),
+            Err(e) => return Err(e),
+        };
+        self.extents.lock(|extents| *extents.borrow_mut() = table);
+        Ok(())
+    }
+
+    fn extent_entry(&self, path: &str) -> Option<(Extent, bool)> {
+        self.extents.lock(|table| {
+            table
+                .borrow()
+                .iter()
+                .find(|e| e.path == path)
+                .map(|e| (e.extent, e.pinned))
+        })
+    }
+
+    fn set_pinned(&self, path: &str, pinned: bool) -> Result<(), FsError> {
+        if !self.mounted {
+            return Err(FsError::NotMounted);
+        }
+        self.extents.lock(|table| {
+            let mut table = table.borrow_mut();
+            let entry = table
+                .iter_mut()
+                .find(|e| e.path == path)
+                .ok_or(FsError::NotContiguous)?;
+            entry.pinned = pinned;
+            Ok::<_, FsError>(())
+        })?;
+        self.save_extents()
+    }
+
+    // ==================== 目录操作 ====================
+
+    /// 创建目录
+    ///
+    /// 父目录必须已存在，目录已存在时返回 `AlreadyExists`
+    pub fn create_dir(&self, path: &str) -> Result<(), FsError> {
+        let path = lfs_path(path)?;
+        self.with_lfs(|fs| fs.create_dir(&path))
+    }
+
     /// 创建目录 (包括父目录)
     pub fn create_dir_all(&self, path: &str) -> Result<(), FsError> {
         if !self.mounted {
@@ -682,71 +1336,326 @@ impl FileSystem {
     }
 
     /// 打开目录进行遍历
-    ///
-    /// # 实现说明
-    /// 当前为占位实现。完整实现应使用 littlefs2 的 dir_open 方法。
-    pub fn read_dir(&self, path: &str) -> Result<Dir<'_>, FsError> {
-        if !self.mounted {
-            return Err(FsError::NotMounted);
+    pub fn read_dir(&self, path: &str) -> Result<Dir<'_, P>, FsError> {
+        let path = lfs_path(path)?;
+        if !self.with_lfs(|fs| fs.metadata(&path))?.is_dir() {
+            return Err(FsError::NotADirectory);
         }
 
-        // 占位实现 - 完整实现应使用 littlefs2::fs::Filesystem::read_dir()
-        let _ = path;
-        let id = self.allocate_dir_id();
-
         Ok(Dir {
             fs: self,
-            id,
+            path,
             index: 0,
         })
     }
 
     // ==================== 内部方法 ====================
 
-    fn allocate_file_id(&self) -> u32 {
-        // 简化实现，实际需要原子操作
-        // self.next_file_id.fetch_add(1, Ordering::Relaxed)
-        1
+    /// LittleFS 区域须与 littlefs2 的编译期几何参数一致，且不与连续存储区域重叠
+    fn check_layout(&self) -> Result<(), FsError> {
+        let lfs_blocks = self.config.block_count.saturating_sub(self.config.extent_blocks);
+        if self.config.block_size as usize != LFS_BLOCK_SIZE || (lfs_blocks as usize) < P::BLOCK_COUNT {
+            return Err(FsError::InvalidParam);
+        }
+        Ok(())
     }
 
-    fn allocate_dir_id(&self) -> u32 {
-        // 简化实现
-        1
+    /// 独占访问存储适配器
+    ///
+    /// 已有操作进行中 (重入) 时返回 `Storage(Busy)`
+    fn with_storage<R>(&self, f: impl FnOnce(&mut LfsStorageAdapter<P>) -> Result<R, FsError>) -> Result<R, FsError> {
+        self.with_state(|lfs| f(&mut lfs.storage))
     }
 
-    fn get_file_size(&self, _path: &str) -> Result<u32, FsError> {
-        // 占位实现 - 完整实现应使用 littlefs2::fs::Filesystem::metadata()
-        Ok(0)
+    fn with_state<R>(&self, f: impl FnOnce(&mut Lfs<P>) -> Result<R, FsError>) -> Result<R, FsError> {
+        if self.busy.swap(true, Ordering::Acquire) {
+            return Err(FsError::Storage(StorageError::Busy));
+        }
+        // Safety: `busy` 保证同一时刻只有一个可变引用
+        let result = f(unsafe { &mut *self.lfs.get() });
+        self.busy.store(false, Ordering::Release);
+        result
     }
 
-    fn read_file_internal(&self, _id: u32, _offset: u32, buffer: &mut [u8]) -> Result<usize, FsError> {
-        // 占位实现 - 完整实现应使用 littlefs2 文件读取 API
-        Ok(buffer.len())
+    /// 在已挂载的 LittleFS 上执行操作
+    fn with_lfs<R>(&self, f: impl FnOnce(&Mounted<P>) -> LfsResult<R>) -> Result<R, FsError> {
+        if !self.mounted {
+            return Err(FsError::NotMounted);
+        }
+        self.with_state(|lfs| {
+            let result = match lfs.filesystem() {
+                Ok(fs) => f(fs),
+                Err(e) => Err(e),
+            };
+            result.map_err(|e| lfs_error(&mut lfs.storage, e, FsError::IoError))
+        })
     }
 
-    fn write_file_internal(&self, _id: u32, _offset: u32, data: &[u8]) -> Result<usize, FsError> {
-        // 占位实现 - 完整实现应使用 littlefs2 文件写入 API
-        Ok(data.len())
+    /// 访问句柄缓存的 littlefs2 文件 (首次访问时打开)
+    fn with_file<R>(&self, id: u32, f: impl FnOnce(&OpenLfsFile<P>) -> LfsResult<R>) -> Result<R, FsError> {
+        if !self.mounted {
+            return Err(FsError::NotMounted);
+        }
+        let (path, options) = self.with_handle(id, |handle| (handle.path.clone(), handle.options))?;
+        let path = lfs_path(&path)?;
+        self.with_state(|lfs| {
+            let result = match lfs.file(id, &path, options.write || options.append) {
+                Ok(file) => f(file),
+                Err(e) => Err(e),
+            };
+            result.map_err(|e| lfs_error(&mut lfs.storage, e, FsError::IoError))
+        })
     }
 
-    fn sync_file_internal(&self, _id: u32) -> Result<(), FsError> {
-        // 占位实现 - 完整实现应使用 littlefs2 文件同步 API
-        self.storage.inner().config(); // 保持对 storage 的引用
-        Ok(())
+    /// 访问句柄状态
+    fn with_handle<R>(&self, id: u32, f: impl FnOnce(&mut OpenHandle) -> R) -> Result<R, FsError> {
+        self.handles.lock(|table| table.borrow_mut().get_mut(id).map(f))
     }
 
-    fn truncate_file_internal(&self, _id: u32, _size: u32) -> Result<(), FsError> {
-        // 占位实现 - 完整实现应使用 littlefs2 文件截断 API
+    /// 句柄对应的区段
+    fn handle_extent(&self, id: u32) -> Result<Option<Extent>, FsError> {
+        self.with_handle(id, |handle| handle.extent)
+    }
+
+    fn read_file_internal(&self, id: u32, offset: u32, buffer: &mut [u8]) -> Result<usize, FsError> {
+        if let Some(extent) = self.handle_extent(id)? {
+            return self.read_extent(extent, offset, buffer);
+        }
+
+        self.with_file(id, |file| {
+            file.seek(LfsSeekFrom::Start(offset))?;
+            let mut done = 0;
+            while done < buffer.len() {
+                match file.read(&mut buffer[done..])? {
+                    0 => break,
+                    n => done += n,
+                }
+            }
+            Ok(done)
+        })
+    }
+
+    /// 从连续存储区段读取
+    fn read_extent(&self, extent: Extent, offset: u32, buffer: &mut [u8]) -> Result<usize, FsError> {
+        let block_size = self.config.block_size;
+        let len = (extent.len.saturating_sub(offset) as usize).min(buffer.len());
+        self.with_storage(|storage| {
+            let mut done = 0;
+            while done < len {
+                let position = offset + done as u32;
+                let within = position % block_size;
+                let n = ((block_size - within) as usize).min(len - done);
+                storage.read(extent.first_block + position / block_size, within, &mut buffer[done..done + n])?;
+                done += n;
+            }
+            Ok(len)
+        })
+    }
+
+    fn write_file_internal(&self, id: u32, offset: u32, data: &[u8]) -> Result<usize, FsError> {
+        // 连续存储文件通过 `extent_writer` 写入
+        if self.handle_extent(id)?.is_some() {
+            return Err(FsError::NotContiguous);
+        }
+
+        let written = self.with_file(id, |file| {
+            file.seek(LfsSeekFrom::Start(offset))?;
+            file.write(data)
+        })?;
+        self.with_handle(id, |handle| handle.dirty = true)?;
+        Ok(written)
+    }
+
+    fn sync_file_internal(&self, id: u32) -> Result<(), FsError> {
+        if self.with_handle(id, |handle| handle.dirty)? {
+            self.with_file(id, |file| file.sync())?;
+            self.with_handle(id, |handle| handle.dirty = false)?;
+        }
+        self.with_storage(|storage| Ok(storage.sync()?))
+    }
+
+    fn truncate_file_internal(&self, id: u32, size: u32) -> Result<(), FsError> {
+        if self.handle_extent(id)?.is_some() {
+            return Err(FsError::NotContiguous);
+        }
+
+        self.with_file(id, |file| file.set_len(size as usize))?;
+        self.with_handle(id, |handle| handle.dirty = true)
+    }
+
+    /// 提交并关闭句柄的 littlefs2 文件，释放句柄
+    ///
+    /// 文件未能关闭 (例如重入返回 `Busy`) 时句柄仍被释放，文件在槽位复用或卸载时关闭
+    fn close_file_internal(&self, id: u32) -> Result<(), FsError> {
+        self.handles.lock(|table| table.borrow().index(id))?;
+        let closed = if self.mounted {
+            self.with_state(|lfs| lfs.close(id).map_err(|e| lfs_error(&mut lfs.storage, e, FsError::IoError)))
+        } else {
+            Ok(())
+        };
+        self.handles.lock(|table| table.borrow_mut().close(id))?;
+        closed?;
+        self.with_storage(|storage| Ok(storage.sync()?))
+    }
+
+    fn read_dir_internal(&self, path: &Path, index: u32) -> Result<Option<Metadata>, FsError> {
+        let parent: &str = path.as_ref();
+        self.with_lfs(|fs| {
+            fs.read_dir_and_then(path, |dir| {
+                let mut position = 0;
+                for entry in dir {
+                    let entry = entry?;
+                    let file_name: &str = entry.file_name().as_ref();
+                    // 根目录下的区段表文件不列出
+                    if file_name == "." || file_name == ".." || (parent == "/" && file_name == &EXTENT_TABLE_PATH[1..]) {
+                        continue;
+                    }
+                    if position < index {
+                        position += 1;
+                        continue;
+                    }
+
+                    let meta = entry.metadata();
+                    let mut name = heapless::String::new();
+                    name.push_str(file_name).map_err(|_| LfsError::FilenameTooLong)?;
+                    return Ok(Some(Metadata {
+                        file_type: if meta.is_dir() { FileType::Directory } else { FileType::File },
+                        size: meta.len() as u32,
+                        name,
+                    }));
+                }
+                Ok(None)
+            })
+        })
+    }
+}
+
+/// 转换为 littlefs2 路径
+fn lfs_path(path: &str) -> Result<PathBuf, FsError> {
+    if path.len() > MAX_PATH_LEN {
+        return Err(FsError::PathTooLong);
+    }
+    if path.is_empty() || path.contains('\0') {
+        return Err(FsError::InvalidParam);
+    }
+    Ok(PathBuf::from(path))
+}
+
+/// 转换 littlefs2 错误，`Io` 优先使用适配器记录的存储错误
+fn lfs_error<P: LfsProfile>(storage: &mut LfsStorageAdapter<P>, e: LfsError, io: FsError) -> FsError {
+    match (e, storage.take_error()) {
+        (LfsError::Io, Some(storage_error)) => FsError::Storage(storage_error),
+        (LfsError::Io, None) => io,
+        (e, _) => FsError::from(e),
+    }
+}
+
+/// 预分配区段的顺序写入器
+///
+/// 按页编程预先擦除的块，`finish` 时登记实际数据长度
+pub struct ExtentWriter<'a, P: LfsProfile = DefaultProfile> {
+    fs: &'a mut FileSystem<P>,
+    path: heapless::String<64>,
+    first_block: u32,
+    capacity: u32,
+    written: u32,
+    limit: u32,
+}
+
+impl<'a, P: LfsProfile> ExtentWriter<'a, P> {
+    /// 追加数据
+    pub fn write(&mut self, data: &[u8]) -> Result<(), FsError> {
+        if self.written as usize + data.len() > self.limit as usize {
+            return Err(FsError::NoSpace);
+        }
+        let block_size = self.fs.config.block_size;
+        let mut consumed = 0;
+        while consumed < data.len() {
+            let block = self.first_block + self.written / block_size;
+            let offset = self.written % block_size;
+            let chunk = core::cmp::min((block_size - offset) as usize, data.len() - consumed);
+            self.fs.lfs.get_mut().storage.prog(block, offset, &data[consumed..consumed + chunk])?;
+            consumed += chunk;
+            self.written += chunk as u32;
+        }
         Ok(())
     }
 
-    fn read_dir_internal(&self, _id: u32, _index: u32) -> Result<Option<Metadata>, FsError> {
-        // 占位实现 - 完整实现应使用 littlefs2 目录读取 API
-        Ok(None)
+    /// 已写入字节数
+    pub fn written(&self) -> u32 {
+        self.written
+    }
+
+    /// 剩余容量
+    pub fn remaining(&self) -> u32 {
+        self.limit - self.written
     }
+
+    /// 完成写入并登记数据长度
+    pub fn finish(self) -> Result<Extent, FsError> {
+        self.fs.lfs.get_mut().storage.sync()?;
+        let extent = Extent { first_block: self.first_block, len: self.written };
+        self.fs.record_extent(&self.path, extent, self.capacity)?;
+        Ok(extent)
+    }
+}
+
+/// 编码区段表，返回长度
+fn encode_extents(table: &[ExtentEntry], out: &mut [u8; EXTENT_TABLE_LEN]) -> usize {
+    out[..4].copy_from_slice(&EXTENT_TABLE_MAGIC);
+    out[4] = table.len() as u8;
+    for (entry, record) in table.iter().zip(out[5..].chunks_exact_mut(EXTENT_RECORD_LEN)) {
+        record.fill(0);
+        record[0] = entry.path.len() as u8;
+        record[1..1 + entry.path.len()].copy_from_slice(entry.path.as_bytes());
+        record[65..69].copy_from_slice(&entry.extent.first_block.to_le_bytes());
+        record[69..73].copy_from_slice(&entry.extent.len.to_le_bytes());
+        record[73..77].copy_from_slice(&entry.capacity.to_le_bytes());
+        record[77] = entry.pinned as u8;
+    }
+    5 + table.len() * EXTENT_RECORD_LEN
+}
+
+/// 解码区段表，格式无效时返回 `None`
+fn decode_extents(data: &[u8]) -> Option<heapless::Vec<ExtentEntry, MAX_EXTENTS>> {
+    if data.len() < 5 || data[..4] != EXTENT_TABLE_MAGIC {
+        return None;
+    }
+    let records = &data[5..];
+    if records.len() != data[4] as usize * EXTENT_RECORD_LEN {
+        return None;
+    }
+
+    let mut table = heapless::Vec::new();
+    for record in records.chunks_exact(EXTENT_RECORD_LEN) {
+        let path = record.get(1..1 + record[0] as usize).filter(|p| p.len() <= 64)?;
+        let word = |at: usize| u32::from_le_bytes([record[at], record[at + 1], record[at + 2], record[at + 3]]);
+        let entry = ExtentEntry {
+            path: heapless::String::try_from(core::str::from_utf8(path).ok()?).ok()?,
+            extent: Extent { first_block: word(65), len: word(69) },
+            capacity: word(73),
+            pinned: record[77] != 0,
+        };
+        table.push(entry).ok()?;
+    }
+    Some(table)
+}
+
+/// 在 `region` 中查找 `need` 个空闲连续块
+///
+/// `used` 为按起始块排序的 (起始块, 块数) 列表
+fn find_free_run(region: core::ops::Range<u32>, used: &[(u32, u32)], need: u32) -> Option<u32> {
+    let mut cursor = region.start;
+    for &(start, blocks) in used {
+        if start >= cursor + need {
+            break;
+        }
+        cursor = cursor.max(start + blocks);
+    }
+    (cursor + need <= region.end).then_some(cursor)
 }
 
-impl Drop for FileSystem {
+impl<P: LfsProfile> Drop for FileSystem<P> {
     fn drop(&mut self) {
         if self.mounted {
             let _ = self.unmount();
@@ -792,6 +1701,171 @@ mod tests {
         assert!(!opts.truncate);
     }
 
+    #[test]
+    fn test_config_aligned_to() {
+        let config = FsConfig { read_size: 16, prog_size: 48, cache_size: 64, ..Default::default() }.aligned_to(32);
+        assert_eq!((config.read_size, config.prog_size, config.cache_size), (32, 64, 64));
+        assert_eq!(FsConfig::default().aligned_to(1).prog_size, 256);
+    }
+
+    #[test]
+    fn test_extent_block_count() {
+        let extent = Extent { first_block: 10, len: 4097 };
+        assert_eq!(extent.block_count(4096), 2);
+        assert_eq!(Extent { first_block: 0, len: 4096 }.block_count(4096), 1);
+    }
+
+    #[test]
+    fn test_find_free_run() {
+        let region = 100..120;
+        assert_eq!(find_free_run(region.clone(), &[], 5), Some(100));
+        assert_eq!(find_free_run(region.clone(), &[(100, 4), (106, 2)], 2), Some(104));
+        assert_eq!(find_free_run(region.clone(), &[(100, 4), (106, 2)], 3), Some(108));
+        assert_eq!(find_free_run(region, &[(100, 18)], 3), None);
+    }
+
+    #[test]
+    fn test_extent_table_encoding() {
+        let entry = |path: &str, first_block, len, pinned| ExtentEntry {
+            path: heapless::String::try_from(path).unwrap(),
+            extent: Extent { first_block, len },
+            capacity: len.div_ceil(4096),
+            pinned,
+        };
+        let table = [entry("/cam/0001.raw", 300, 10_000, false), entry("/ui.bin", 303, 4096, true)];
+
+        let mut buffer = [0u8; EXTENT_TABLE_LEN];
+        let len = encode_extents(&table, &mut buffer);
+        let decoded = decode_extents(&buffer[..len]).unwrap();
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0].path.as_str(), "/cam/0001.raw");
+        assert_eq!((decoded[0].extent, decoded[0].capacity), (Extent { first_block: 300, len: 10_000 }, 3));
+        assert!(decoded[1].pinned);
+        let mut empty = [0u8; EXTENT_TABLE_LEN];
+        let empty_len = encode_extents(&[], &mut empty);
+        assert!(decode_extents(&empty[..empty_len]).unwrap().is_empty());
+
+        // 截断、魔数错误、路径长度越界
+        assert!(decode_extents(&buffer[..len - 1]).is_none());
+        buffer[0] = b'X';
+        assert!(decode_extents(&buffer[..len]).is_none());
+        buffer[0] = EXTENT_TABLE_MAGIC[0];
+        buffer[5] = 65;
+        assert!(decode_extents(&buffer[..len]).is_none());
+    }
+
+    crate::lfs_profile!(TestProfile, blocks = 16, cache = U256, lookahead = U1, cycles = -1);
+
+    #[test]
+    fn test_extents_survive_remount() {
+        use crate::fs::storage::{ram_flash, FlashConfig};
+
+        let _flash = ram_flash::install();
+        // 16 个 LittleFS 块 + 4 个连续存储块，位于测试 Flash 的第 2 个块之后
+        let mount = || {
+            let config = FlashConfig {
+                partition_offset: ram_flash::BASE + 2 * 4096,
+                partition_size: 20 * 4096,
+                ..FlashConfig::default()
+            };
+            FileSystem::<TestProfile>::with_profile(
+                FlashStorage::new(config),
+                FsConfig { extent_blocks: 4, ..FsConfig::default() },
+            )
+        };
+
+        {
+            let mut fs = mount();
+            fs.format().unwrap();
+            fs.mount().unwrap();
+
+            let mut log = fs.create("/log.txt").unwrap();
+            log.write_all(b"boot ok").unwrap();
+            log.close().unwrap();
+            assert_eq!(fs.defragment("/log.txt").unwrap().len, 7);
+
+            fs.preallocate("/cam.raw", 5000).unwrap();
+            let mut writer = fs.extent_writer("/cam.raw").unwrap();
+            writer.write(b"frame").unwrap();
+            writer.finish().unwrap();
+            fs.pin("/cam.raw").unwrap();
+            fs.rename("/log.txt", "/boot.txt").unwrap();
+        }
+
+        let mut fs = mount();
+        fs.mount().unwrap();
+        assert_eq!(fs.extent("/boot.txt").map(|e| e.len), Some(7));
+        assert_eq!(fs.extent("/log.txt"), None);
+        assert_eq!(fs.metadata("/cam.raw").unwrap().size, 5);
+        assert_eq!(fs.pin("/cam.raw"), Ok(()));
+        assert_eq!(fs.remove("/cam.raw"), Err(FsError::Pinned));
+
+        let mut buffer = [0u8; 16];
+        let n = fs.open("/boot.txt", OpenOptions::read_only()).unwrap().read(&mut buffer).unwrap();
+        assert_eq!(&buffer[..n], b"boot ok");
+
+        // 删除后区段释放，区段表文件不出现在目录中
+        fs.remove("/boot.txt").unwrap();
+        assert!(!fs.exists("/boot.txt").unwrap());
+        let mut dir = fs.read_dir("/").unwrap();
+        assert_eq!(dir.next().unwrap().unwrap().name.as_str(), "cam.raw");
+        assert!(dir.next().unwrap().is_none());
+
+        // 截断打开预分配文件: 区段记录删除，新内容写入 LittleFS
+        fs.preallocate("/fw.bin", 4096).unwrap();
+        let mut writer = fs.extent_writer("/fw.bin").unwrap();
+        writer.write(b"old image").unwrap();
+        writer.finish().unwrap();
+        let mut file = fs.create("/fw.bin").unwrap();
+        file.write_all(b"new").unwrap();
+        file.close().unwrap();
+        drop(fs);
+
+        // 挂载后移动的文件系统在下一次操作时重新挂载
+        let fs = {
+            let mut fs = mount();
+            fs.mount().unwrap();
+            fs
+        };
+        assert_eq!(fs.extent("/fw.bin"), None);
+        assert_eq!(fs.metadata("/fw.bin").unwrap().size, 3);
+        let n = fs.open("/fw.bin", OpenOptions::read_only()).unwrap().read(&mut buffer).unwrap();
+        assert_eq!(&buffer[..n], b"new");
+    }
+
+    #[test]
+    fn test_handle_table() {
+        let mut table = HandleTable::new();
+        let ids: heapless::Vec<u32, MAX_OPEN_FILES> = (0..MAX_OPEN_FILES)
+            .map(|i| table.open(if i == 0 { "/a" } else { "/b" }, OpenOptions::read_only(), None).unwrap())
+            .collect();
+        assert_eq!(table.len(), MAX_OPEN_FILES);
+        assert_ne!(ids[0], ids[1]);
+        assert_eq!(table.open("/c", OpenOptions::read_only(), None), Err(FsError::TooManyOpenFiles));
+
+        // 关闭后槽位复用，旧 ID 失效
+        assert_eq!(table.close(ids[0]).unwrap().path.as_str(), "/a");
+        let reused = table.open("/c", OpenOptions::read_only(), None).unwrap();
+        assert_eq!(reused & 0xFF, ids[0] & 0xFF);
+        assert_ne!(reused, ids[0]);
+        assert!(table.get_mut(ids[0]).is_err());
+        assert_eq!(table.close(ids[0]).unwrap_err(), FsError::InvalidHandle);
+        assert_eq!(table.get_mut(reused).unwrap().path.as_str(), "/c");
+        assert!(table.is_open("/b", false));
+        assert!(!table.is_open("/b", true));
+    }
+
+    #[test]
+    fn test_lfs_errors() {
+        assert_eq!(FsError::from(LfsError::NoSuchEntry), FsError::NotFound);
+        assert_eq!(FsError::from(LfsError::DirNotEmpty), FsError::DirectoryNotEmpty);
+        assert_eq!(lfs_path("").err(), Some(FsError::InvalidParam));
+        assert_eq!(lfs_path("/a\0b").err(), Some(FsError::InvalidParam));
+        let long = [b'x'; MAX_PATH_LEN + 1];
+        assert_eq!(lfs_path(core::str::from_utf8(&long).unwrap()).err(), Some(FsError::PathTooLong));
+        assert!(lfs_path("/log/boot.txt").is_ok());
+    }
+
     #[test]
     fn test_seek_from() {
         // 测试 SeekFrom 枚举
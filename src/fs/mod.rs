@@ -2,14 +2,36 @@
 //!
 //! 基于 littlefs2 实现的嵌入式文件系统支持，特性：
 //! - 掉电安全的日志结构文件系统
-//! - 支持 ESP32 分区表
+//! - 支持 ESP32 分区表 (MD5 校验、只读/加密标志)
 //! - 可配置的文件系统大小和块大小
 //! - 目录和文件操作 API
+//! - 连续存储文件的零拷贝内存映射读取、预分配与碎片整理
+//! - Flash 写入调度 (时间片 + 喂狗，避免阻塞实时任务)
+//! - Flash 基准测试 (吞吐量、擦除延迟、对高优先级执行器的影响)
+//! - 命名空间键值存储 (NVS 风格 blob 接口)
+//! - ESP-IDF NVS 分区只读解析 (含加密 NVS)，迁移到键值存储无需擦除
+//! - 跨任务共享访问 (`SharedFileSystem`，高优先级请求优先)
+//! - A/B 双区配置 (试用期自检确认，失败或重启超限自动回滚)
+//! - 多分区同时挂载 (各自的缓存/lookahead/块周期参数，经挂载表按路径分派)
 
 pub mod littlefs;
 pub mod partition;
 pub mod storage;
+pub mod scheduler;
+pub mod bench;
+pub mod kv;
+pub mod idf_nvs;
+pub mod shared;
+pub mod abconfig;
+pub mod vfs;
 
-pub use littlefs::{FileSystem, File, Dir, OpenOptions, FileType, Metadata};
-pub use partition::{PartitionTable, Partition, PartitionType, DataSubType, AppSubType};
-pub use storage::{FlashStorage, StorageError};
+pub use littlefs::{FileSystem, File, Dir, OpenOptions, FileType, Metadata, Extent, ExtentWriter, FsConfig};
+pub use partition::{PartitionTable, Partition, PartitionType, DataSubType, AppSubType, ChecksumStatus};
+pub use storage::{FlashDriver, FlashStorage, StorageError, ReadMode};
+pub use scheduler::{WriteScheduler, SlicePolicy, WriteStats};
+pub use kv::{KvStore, KvError};
+pub use idf_nvs::{IdfNvs, IdfNvsError, MigrationReport};
+pub use shared::{SharedFileSystem, FsHandle, FsGuard, FsPriority};
+pub use abconfig::{ConfigBanks, ConfigBankError};
+pub use vfs::{Vfs, MountedFs, MountPoint};
+pub use storage::littlefs_adapter::{LfsProfile, DefaultProfile};
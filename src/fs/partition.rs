@@ -1,9 +1,16 @@
 //! ESP32 分区表支持
 //!
 //! 解析和管理 ESP32 分区表，支持定位文件系统分区
+//!
+//! ESP-IDF 生成的分区表在最后一个条目之后附带 MD5 校验条目，解析时会校验
+//! 前面所有条目的 MD5，结果通过 `PartitionTable::checksum()` 查询。
 
 use core::fmt;
 
+use md5::{Digest, Md5};
+
+use crate::util::fmt::Bytes;
+
 /// 分区表魔数 (ESP-IDF 格式)
 const PARTITION_TABLE_MAGIC: u16 = 0xAA50;
 
@@ -13,9 +20,18 @@ const MAX_PARTITION_ENTRIES: usize = 95;
 /// 分区表在 Flash 中的偏移量 (默认 0x8000)
 pub const PARTITION_TABLE_OFFSET: u32 = 0x8000;
 
+/// 默认分区表 (`presets::simple_16mb`) 中 LittleFS 存储分区的偏移
+pub const STORAGE_PARTITION_OFFSET: u32 = 0x410000;
+
+/// 默认分区表中 LittleFS 存储分区的大小 (~12MB)
+pub const STORAGE_PARTITION_SIZE: u32 = 0xBF0000;
+
 /// 单个分区条目大小
 const PARTITION_ENTRY_SIZE: usize = 32;
 
+/// MD5 校验条目魔数
+const MD5_ENTRY_MAGIC: u16 = 0xEBEB;
+
 /// 分区类型
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
@@ -141,7 +157,7 @@ impl AppSubType {
 }
 
 /// 分区标志
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct PartitionFlags {
     /// 分区已加密
     pub encrypted: bool,
@@ -149,15 +165,53 @@ pub struct PartitionFlags {
     pub readonly: bool,
 }
 
+impl PartitionFlags {
+    /// 加密标志位
+    pub const ENCRYPTED: u32 = 0x01;
+    /// 只读标志位
+    pub const READONLY: u32 = 0x02;
+
+    /// 从原始值解析，含未定义的标志位时返回 None
+    pub fn from_bits(value: u32) -> Option<Self> {
+        if value & !(Self::ENCRYPTED | Self::READONLY) != 0 {
+            return None;
+        }
+        Some(Self::from(value))
+    }
+
+    /// 转换为原始值
+    pub fn bits(&self) -> u32 {
+        let mut bits = 0;
+        if self.encrypted {
+            bits |= Self::ENCRYPTED;
+        }
+        if self.readonly {
+            bits |= Self::READONLY;
+        }
+        bits
+    }
+}
+
 impl From<u32> for PartitionFlags {
     fn from(value: u32) -> Self {
         Self {
-            encrypted: (value & 0x01) != 0,
-            readonly: (value & 0x02) != 0,
+            encrypted: (value & Self::ENCRYPTED) != 0,
+            readonly: (value & Self::READONLY) != 0,
         }
     }
 }
 
+/// 分区表校验状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumStatus {
+    /// 没有 MD5 校验条目 (旧格式或手动创建的分区表)
+    Missing,
+    /// MD5 校验通过
+    Valid,
+    /// MD5 不匹配 (分区表已损坏或被篡改)
+    Mismatch,
+}
+
 /// 单个分区描述
 #[derive(Clone)]
 pub struct Partition {
@@ -196,7 +250,8 @@ impl Partition {
         let mut label = heapless::String::new();
         label.push_str(label_str).ok()?;
 
-        let flags = PartitionFlags::from(u32::from_le_bytes([data[28], data[29], data[30], data[31]]));
+        // 未定义的标志位视为损坏条目
+        let flags = PartitionFlags::from_bits(u32::from_le_bytes([data[28], data[29], data[30], data[31]]))?;
 
         Some(Self {
             label,
@@ -251,6 +306,16 @@ impl Partition {
         self.is_data() && self.subtype == DataSubType::Nvs.as_u8()
     }
 
+    /// 检查分区是否只读
+    pub fn is_readonly(&self) -> bool {
+        self.flags.readonly
+    }
+
+    /// 检查分区是否加密
+    pub fn is_encrypted(&self) -> bool {
+        self.flags.encrypted
+    }
+
     /// 获取分区结束地址
     pub fn end_offset(&self) -> u32 {
         self.offset + self.size
@@ -269,7 +334,7 @@ impl fmt::Debug for Partition {
             .field("type", &self.partition_type)
             .field("subtype", &self.subtype)
             .field("offset", &format_args!("0x{:08X}", self.offset))
-            .field("size", &format_args!("0x{:08X} ({}KB)", self.size, self.size / 1024))
+            .field("size", &format_args!("0x{:08X} ({})", self.size, Bytes(self.size as u64)))
             .field("flags", &self.flags)
             .finish()
     }
@@ -279,6 +344,8 @@ impl fmt::Debug for Partition {
 pub struct PartitionTable {
     /// 分区列表
     partitions: heapless::Vec<Partition, MAX_PARTITION_ENTRIES>,
+    /// MD5 校验状态
+    checksum: ChecksumStatus,
 }
 
 impl PartitionTable {
@@ -286,6 +353,7 @@ impl PartitionTable {
     pub const fn new() -> Self {
         Self {
             partitions: heapless::Vec::new(),
+            checksum: ChecksumStatus::Missing,
         }
     }
 
@@ -305,7 +373,7 @@ impl PartitionTable {
         }
 
         // 解析每个分区条目
-        for chunk in data.chunks_exact(PARTITION_ENTRY_SIZE) {
+        for (index, chunk) in data.chunks_exact(PARTITION_ENTRY_SIZE).enumerate() {
             let entry_data: &[u8; PARTITION_ENTRY_SIZE] = chunk.try_into().ok()?;
 
             // 检查是否为结束标记 (全 0xFF 或魔数不匹配)
@@ -313,6 +381,17 @@ impl PartitionTable {
                 break;
             }
 
+            // MD5 校验条目: 覆盖之前所有条目
+            if u16::from_le_bytes([entry_data[0], entry_data[1]]) == MD5_ENTRY_MAGIC {
+                let digest = Md5::digest(&data[..index * PARTITION_ENTRY_SIZE]);
+                table.checksum = if digest[..] == entry_data[16..] {
+                    ChecksumStatus::Valid
+                } else {
+                    ChecksumStatus::Mismatch
+                };
+                break;
+            }
+
             if let Some(partition) = Partition::from_bytes(entry_data) {
                 table.partitions.push(partition).ok()?;
             } else {
@@ -328,6 +407,16 @@ impl PartitionTable {
         }
     }
 
+    /// MD5 校验状态
+    pub fn checksum(&self) -> ChecksumStatus {
+        self.checksum
+    }
+
+    /// MD5 校验是否通过
+    pub fn is_verified(&self) -> bool {
+        self.checksum == ChecksumStatus::Valid
+    }
+
     /// 手动创建分区 (用于已知分区布局)
     ///
     /// # 参数
@@ -415,6 +504,7 @@ impl fmt::Debug for PartitionTable {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("PartitionTable")
             .field("count", &self.partitions.len())
+            .field("checksum", &self.checksum)
             .field("partitions", &self.partitions.as_slice())
             .finish()
     }
@@ -515,7 +605,7 @@ pub mod presets {
         
         // LittleFS 存储分区 (剩余约 12MB)
         table.add_partition("storage", PartitionType::Data, DataSubType::LittleFs.as_u8(),
-            0x410000, 0xBF0000).ok();
+            STORAGE_PARTITION_OFFSET, STORAGE_PARTITION_SIZE).ok();
         
         table
     }
@@ -561,4 +651,48 @@ mod tests {
         assert!(table.find_by_label("storage").is_some());
         assert!(table.find_littlefs().is_some());
     }
+
+    fn entry(label: &str, subtype: u8, offset: u32, size: u32, flags: u32) -> [u8; 32] {
+        let mut data = [0u8; 32];
+        data[..2].copy_from_slice(&PARTITION_TABLE_MAGIC.to_le_bytes());
+        data[2] = 0x01;
+        data[3] = subtype;
+        data[4..8].copy_from_slice(&offset.to_le_bytes());
+        data[8..12].copy_from_slice(&size.to_le_bytes());
+        data[12..12 + label.len()].copy_from_slice(label.as_bytes());
+        data[28..].copy_from_slice(&flags.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_md5_and_flags() {
+        let mut data = [0xFFu8; 32 * 4];
+        data[..32].copy_from_slice(&entry("nvs", 0x02, 0x9000, 0x6000, 0));
+        data[32..64].copy_from_slice(&entry("storage", 0x83, 0x410000, 0xBF0000, PartitionFlags::READONLY));
+        data[64..66].copy_from_slice(&MD5_ENTRY_MAGIC.to_le_bytes());
+        let digest = Md5::digest(&data[..64]);
+        data[80..96].copy_from_slice(&digest);
+
+        let table = PartitionTable::from_flash_data(&data).unwrap();
+        assert_eq!(table.len(), 2);
+        assert!(table.is_verified());
+        let storage = table.find_littlefs().unwrap();
+        assert!(storage.is_readonly());
+        assert!(!storage.is_encrypted());
+
+        // 篡改条目后校验失败
+        data[40] ^= 0x01;
+        let table = PartitionTable::from_flash_data(&data).unwrap();
+        assert_eq!(table.checksum(), ChecksumStatus::Mismatch);
+
+        // 没有校验条目
+        data[64..96].fill(0xFF);
+        let table = PartitionTable::from_flash_data(&data).unwrap();
+        assert_eq!(table.checksum(), ChecksumStatus::Missing);
+
+        // 未定义的标志位
+        assert_eq!(PartitionFlags::from_bits(0x04), None);
+        assert_eq!(PartitionFlags::from_bits(0x03).map(|f| f.bits()), Some(0x03));
+        assert!(Partition::from_bytes(&entry("bad", 0x83, 0, 0x1000, 0x80)).is_none());
+    }
 }
@@ -0,0 +1,413 @@
+//! Flash 写入调度器
+//!
+//! Flash 编程/擦除期间 Cache 被禁用，正在执行的代码若不在 IRAM 中会被挂起，
+//! 高优先级执行器可能因此错过截止时间。调度器将写操作排队，按受限的时间片
+//! 执行 (建议运行在 Core1 的执行器上，或仅在实时任务让出的窗口内执行)，
+//! 每个时间片后喂狗，并统计执行和排队延迟。
+//!
+//! # 特性
+//! - 页对齐拆分 (每个编程操作不跨页，单次停顿 < 1ms)
+//! - 擦除单独占用一个时间片
+//! - 周期模式或实时窗口模式 (`SlicePolicy`)
+//! - 喂狗 (`WatchdogFeed`)
+//! - 延迟统计 (单操作耗时、排队延迟)
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::fs::scheduler::{WriteScheduler, SlicePolicy};
+//!
+//! static FLASH_WRITES: WriteScheduler<32> = WriteScheduler::new();
+//!
+//! // Core1 执行器
+//! #[embassy_executor::task]
+//! async fn flash_task(mut storage: FlashStorage, mut wdt: Wdt<TIMG1<'static>>) {
+//!     FLASH_WRITES.run(&mut storage, &mut wdt, SlicePolicy::default()).await;
+//! }
+//!
+//! FLASH_WRITES.erase(12).await;
+//! FLASH_WRITES.program(12, 0, &record).await;
+//! FLASH_WRITES.flush().await;
+//! log_info!("max op: {} us", FLASH_WRITES.stats().max_op_us);
+//! ```
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use embassy_time::{Duration, Instant, Timer};
+
+use super::storage::{FlashStorage, StorageError};
+use crate::sync::primitives::{CriticalChannel, CriticalSignal};
+
+/// 编程操作的最大长度 (Flash 页大小)
+pub const PAGE_SIZE: usize = 256;
+
+// ===== 写操作 =====
+
+/// 写操作
+#[derive(Clone, Copy)]
+pub enum WriteOp {
+    /// 擦除块
+    Erase {
+        /// 块号
+        block: u32,
+    },
+    /// 编程 (不跨页)
+    Program {
+        /// 块号
+        block: u32,
+        /// 块内偏移
+        offset: u32,
+        /// 有效长度
+        len: u16,
+        /// 数据
+        data: [u8; PAGE_SIZE],
+    },
+}
+
+impl WriteOp {
+    /// 是否为擦除操作
+    pub fn is_erase(&self) -> bool {
+        matches!(self, WriteOp::Erase { .. })
+    }
+}
+
+/// 排队中的操作
+#[derive(Clone, Copy)]
+struct Queued {
+    op: WriteOp,
+    enqueued: Instant,
+}
+
+/// 按页边界拆分写入区间，返回 (偏移, 长度) 序列
+pub fn page_chunks(offset: u32, len: usize) -> impl Iterator<Item = (u32, usize)> {
+    let end = offset as usize + len;
+    let mut pos = offset as usize;
+    core::iter::from_fn(move || {
+        if pos >= end {
+            return None;
+        }
+        let page_end = (pos / PAGE_SIZE + 1) * PAGE_SIZE;
+        let chunk = page_end.min(end) - pos;
+        let item = (pos as u32, chunk);
+        pos += chunk;
+        Some(item)
+    })
+}
+
+// ===== 时间片策略 =====
+
+/// 时间片策略
+#[derive(Clone, Copy)]
+pub enum SlicePolicy {
+    /// 周期执行: 每个时间片最长 `budget`，之后让出 `gap`
+    Periodic {
+        /// 单个时间片预算
+        budget: Duration,
+        /// 时间片间隔
+        gap: Duration,
+    },
+    /// 实时窗口: 等待实时任务发出信号 (截止时间已满足) 后执行一个时间片
+    Window {
+        /// 单个时间片预算
+        budget: Duration,
+        /// 窗口信号
+        window: &'static CriticalSignal<()>,
+    },
+}
+
+impl SlicePolicy {
+    fn budget(&self) -> Duration {
+        match self {
+            SlicePolicy::Periodic { budget, .. } | SlicePolicy::Window { budget, .. } => *budget,
+        }
+    }
+}
+
+impl Default for SlicePolicy {
+    fn default() -> Self {
+        SlicePolicy::Periodic {
+            budget: Duration::from_millis(2),
+            gap: Duration::from_millis(5),
+        }
+    }
+}
+
+// ===== 看门狗 =====
+
+/// 看门狗喂狗接口
+pub trait WatchdogFeed {
+    /// 喂狗
+    fn feed(&mut self);
+}
+
+impl WatchdogFeed for () {
+    fn feed(&mut self) {}
+}
+
+impl<TG: esp_hal::timer::timg::TimerGroupInstance> WatchdogFeed for esp_hal::timer::timg::Wdt<TG> {
+    fn feed(&mut self) {
+        esp_hal::timer::timg::Wdt::feed(self);
+    }
+}
+
+// ===== 统计 =====
+
+/// 统计快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WriteStats {
+    /// 已完成编程操作
+    pub programs: u32,
+    /// 已完成擦除操作
+    pub erases: u32,
+    /// 失败操作
+    pub errors: u32,
+    /// 已执行时间片
+    pub slices: u32,
+    /// 喂狗次数
+    pub watchdog_feeds: u32,
+    /// 单操作最大耗时 (μs)
+    pub max_op_us: u32,
+    /// 单操作平均耗时 (μs)
+    pub avg_op_us: u32,
+    /// 最大排队延迟 (μs)
+    pub max_queue_us: u32,
+    /// 当前待处理操作
+    pub pending: u32,
+}
+
+struct Counters {
+    programs: AtomicU32,
+    erases: AtomicU32,
+    errors: AtomicU32,
+    slices: AtomicU32,
+    watchdog_feeds: AtomicU32,
+    max_op_us: AtomicU32,
+    total_op_us: AtomicU32,
+    max_queue_us: AtomicU32,
+}
+
+impl Counters {
+    const fn new() -> Self {
+        Self {
+            programs: AtomicU32::new(0),
+            erases: AtomicU32::new(0),
+            errors: AtomicU32::new(0),
+            slices: AtomicU32::new(0),
+            watchdog_feeds: AtomicU32::new(0),
+            max_op_us: AtomicU32::new(0),
+            total_op_us: AtomicU32::new(0),
+            max_queue_us: AtomicU32::new(0),
+        }
+    }
+}
+
+// ===== 调度器 =====
+
+/// Flash 写入调度器
+pub struct WriteScheduler<const Q: usize> {
+    queue: CriticalChannel<Queued, Q>,
+    pending: AtomicU32,
+    idle: CriticalSignal<()>,
+    last_error: CriticalSignal<StorageError>,
+    counters: Counters,
+}
+
+impl<const Q: usize> WriteScheduler<Q> {
+    /// 创建调度器
+    pub const fn new() -> Self {
+        Self {
+            queue: CriticalChannel::new(),
+            pending: AtomicU32::new(0),
+            idle: CriticalSignal::new(),
+            last_error: CriticalSignal::new(),
+            counters: Counters::new(),
+        }
+    }
+
+    /// 提交操作 (队列满时等待)
+    pub async fn submit(&self, op: WriteOp) {
+        self.pending.fetch_add(1, Ordering::AcqRel);
+        self.queue.send(Queued { op, enqueued: Instant::now() }).await;
+    }
+
+    /// 尝试提交操作 (队列满时返回 Err)
+    pub fn try_submit(&self, op: WriteOp) -> Result<(), WriteOp> {
+        self.pending.fetch_add(1, Ordering::AcqRel);
+        self.queue
+            .try_send(Queued { op, enqueued: Instant::now() })
+            .map_err(|e| {
+                self.pending.fetch_sub(1, Ordering::AcqRel);
+                match e {
+                    embassy_sync::channel::TrySendError::Full(q) => q.op,
+                }
+            })
+    }
+
+    /// 排队擦除块
+    pub async fn erase(&self, block: u32) {
+        self.submit(WriteOp::Erase { block }).await;
+    }
+
+    /// 排队编程 (自动按页拆分)
+    pub async fn program(&self, block: u32, offset: u32, data: &[u8]) {
+        let mut consumed = 0;
+        for (chunk_offset, len) in page_chunks(offset, data.len()) {
+            let mut page = [0xFFu8; PAGE_SIZE];
+            page[..len].copy_from_slice(&data[consumed..consumed + len]);
+            consumed += len;
+            self.submit(WriteOp::Program { block, offset: chunk_offset, len: len as u16, data: page })
+                .await;
+        }
+    }
+
+    /// 等待队列中所有操作完成
+    ///
+    /// # 返回
+    /// 期间发生的最后一个错误
+    pub async fn flush(&self) -> Result<(), StorageError> {
+        while self.pending.load(Ordering::Acquire) != 0 {
+            self.idle.wait().await;
+        }
+        match self.last_error.try_take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// 待处理操作数
+    pub fn pending(&self) -> u32 {
+        self.pending.load(Ordering::Acquire)
+    }
+
+    /// 统计快照
+    pub fn stats(&self) -> WriteStats {
+        let c = &self.counters;
+        let programs = c.programs.load(Ordering::Relaxed);
+        let erases = c.erases.load(Ordering::Relaxed);
+        let errors = c.errors.load(Ordering::Relaxed);
+        let ops = programs + erases + errors;
+        WriteStats {
+            programs,
+            erases,
+            errors,
+            slices: c.slices.load(Ordering::Relaxed),
+            watchdog_feeds: c.watchdog_feeds.load(Ordering::Relaxed),
+            max_op_us: c.max_op_us.load(Ordering::Relaxed),
+            avg_op_us: if ops == 0 { 0 } else { c.total_op_us.load(Ordering::Relaxed) / ops },
+            max_queue_us: c.max_queue_us.load(Ordering::Relaxed),
+            pending: self.pending(),
+        }
+    }
+
+    /// 运行调度循环 (不返回)
+    pub async fn run<W: WatchdogFeed>(
+        &self,
+        storage: &mut FlashStorage,
+        watchdog: &mut W,
+        policy: SlicePolicy,
+    ) -> ! {
+        loop {
+            // 阻塞等待第一个操作
+            let first = self.queue.receive().await;
+
+            if let SlicePolicy::Window { window, .. } = policy {
+                window.wait().await;
+            }
+
+            self.run_slice(storage, first, policy.budget());
+            watchdog.feed();
+            self.counters.watchdog_feeds.fetch_add(1, Ordering::Relaxed);
+            self.counters.slices.fetch_add(1, Ordering::Relaxed);
+
+            if self.pending.load(Ordering::Acquire) == 0 {
+                self.idle.signal(());
+            }
+
+            if let SlicePolicy::Periodic { gap, .. } = policy {
+                Timer::after(gap).await;
+            }
+        }
+    }
+
+    /// 执行一个时间片
+    fn run_slice(&self, storage: &mut FlashStorage, first: Queued, budget: Duration) {
+        let start = Instant::now();
+        let mut next = Some(first);
+
+        while let Some(item) = next.take() {
+            self.execute(storage, item);
+
+            // 擦除耗时长，独占时间片
+            if item.op.is_erase() || start.elapsed() >= budget {
+                break;
+            }
+            next = match self.queue.try_peek() {
+                Ok(peek) if peek.op.is_erase() => None,
+                Ok(_) => self.queue.try_receive().ok(),
+                Err(_) => None,
+            };
+        }
+    }
+
+    /// 执行单个操作并记录统计
+    fn execute(&self, storage: &mut FlashStorage, item: Queued) {
+        let c = &self.counters;
+        let started = Instant::now();
+        let queue_us = (started - item.enqueued).as_micros() as u32;
+        c.max_queue_us.fetch_max(queue_us, Ordering::Relaxed);
+
+        let result = match item.op {
+            WriteOp::Erase { block } => storage.erase_block(block),
+            WriteOp::Program { block, offset, len, data } => {
+                storage.program(block, offset, &data[..len as usize])
+            }
+        };
+
+        let op_us = started.elapsed().as_micros() as u32;
+        c.max_op_us.fetch_max(op_us, Ordering::Relaxed);
+        c.total_op_us.fetch_add(op_us, Ordering::Relaxed);
+
+        match (result, item.op.is_erase()) {
+            (Ok(()), true) => {
+                c.erases.fetch_add(1, Ordering::Relaxed);
+            }
+            (Ok(()), false) => {
+                c.programs.fetch_add(1, Ordering::Relaxed);
+            }
+            (Err(e), _) => {
+                c.errors.fetch_add(1, Ordering::Relaxed);
+                self.last_error.signal(e);
+            }
+        }
+        self.pending.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl<const Q: usize> Default for WriteScheduler<Q> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_chunks() {
+        let mut it = page_chunks(200, 400);
+        assert_eq!(it.next(), Some((200, 56)));
+        assert_eq!(it.next(), Some((256, 256)));
+        assert_eq!(it.next(), Some((512, 88)));
+        assert_eq!(it.next(), None);
+        assert_eq!(page_chunks(0, 0).count(), 0);
+    }
+
+    #[test]
+    fn test_try_submit_full() {
+        let sched: WriteScheduler<1> = WriteScheduler::new();
+        assert!(sched.try_submit(WriteOp::Erase { block: 1 }).is_ok());
+        assert!(sched.try_submit(WriteOp::Erase { block: 2 }).is_err());
+        assert_eq!(sched.pending(), 1);
+    }
+}
@@ -0,0 +1,282 @@
+//! 跨任务共享的文件系统
+//!
+//! `FileSystem` 本身不保证并发访问安全 (LittleFS 的操作不可重入)。
+//! `SharedFileSystem` 用一把异步锁串行化所有访问，日志任务、HTTP 服务、
+//! 数据记录器各持有一个可复制的 `FsHandle`。
+//!
+//! # 阻塞行为
+//! - `lock` 异步等待，不占用 CPU；`try_lock` 不等待；`lock_timeout` 超时返回 `Timeout`
+//! - 持有锁期间的 Flash 操作是同步的，会阻塞当前执行器直到完成，
+//!   持锁时不要 `await` 其他长时间操作，否则其他任务会一直等待
+//! - 锁不可重入，同一任务重复加锁会死锁
+//!
+//! # 优先级
+//! `High` 优先级的请求排队时，新的 `Normal` 请求不会抢先获得锁，
+//! 避免高优先级任务 (例如保存故障记录) 被频繁的日志写入饿死。
+//! 同级请求之间不保证顺序。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::fs::shared::{SharedFileSystem, FsPriority};
+//!
+//! static FS: StaticCell<SharedFileSystem> = StaticCell::new();
+//! let fs = FS.init(SharedFileSystem::new(FileSystem::new(storage)));
+//! let handle = fs.handle();
+//!
+//! // 日志任务
+//! let mut guard = handle.lock().await;
+//! guard.open("/log/boot.txt", OpenOptions::append_mode())?.write_all(line)?;
+//! drop(guard);
+//!
+//! // 故障记录 (高优先级)
+//! handle.with_priority(FsPriority::High).with(|fs| fs.remove("/log/old.txt")).await?;
+//! ```
+
+use core::cell::{RefCell, UnsafeCell};
+use core::fmt;
+use core::future::poll_fn;
+use core::ops::{Deref, DerefMut};
+use core::task::Poll;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_sync::waitqueue::MultiWakerRegistration;
+use embassy_time::{with_timeout, Duration};
+
+use super::littlefs::FileSystem;
+
+/// 同时等待锁的最大任务数 (超出时等待者会被多唤醒一次，不影响正确性)
+pub const MAX_WAITERS: usize = 8;
+
+/// 访问优先级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FsPriority {
+    /// 普通 (日志、网页资源)
+    #[default]
+    Normal,
+    /// 高 (故障记录、配置保存)，排队时优先于普通请求
+    High,
+}
+
+/// 加锁超时
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeout;
+
+impl fmt::Display for Timeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Filesystem lock timeout")
+    }
+}
+
+/// 锁状态
+struct LockState {
+    locked: bool,
+    /// 正在等待的高优先级请求数
+    high_waiting: usize,
+    wakers: MultiWakerRegistration<MAX_WAITERS>,
+}
+
+impl LockState {
+    /// 当前是否允许该优先级获得锁
+    fn can_acquire(&self, priority: FsPriority) -> bool {
+        !self.locked && (priority == FsPriority::High || self.high_waiting == 0)
+    }
+}
+
+/// 跨任务共享的文件系统
+pub struct SharedFileSystem {
+    fs: UnsafeCell<FileSystem>,
+    state: BlockingMutex<CriticalSectionRawMutex, RefCell<LockState>>,
+}
+
+// Safety: 对 `fs` 的访问由 `state.locked` 串行化
+unsafe impl Sync for SharedFileSystem {}
+
+impl SharedFileSystem {
+    /// 包装文件系统
+    pub const fn new(fs: FileSystem) -> Self {
+        Self {
+            fs: UnsafeCell::new(fs),
+            state: BlockingMutex::new(RefCell::new(LockState {
+                locked: false,
+                high_waiting: 0,
+                wakers: MultiWakerRegistration::new(),
+            })),
+        }
+    }
+
+    /// 获取可复制的访问句柄 (普通优先级)
+    pub fn handle(&'static self) -> FsHandle {
+        FsHandle { shared: self, priority: FsPriority::Normal }
+    }
+
+    /// 异步加锁
+    pub async fn lock(&self, priority: FsPriority) -> FsGuard<'_> {
+        let mut waiting = Waiting { shared: self, high: false };
+        poll_fn(|cx| {
+            self.state.lock(|state| {
+                let mut state = state.borrow_mut();
+                if state.can_acquire(priority) {
+                    state.locked = true;
+                    if waiting.high {
+                        state.high_waiting -= 1;
+                        waiting.high = false;
+                    }
+                    return Poll::Ready(());
+                }
+                if priority == FsPriority::High && !waiting.high {
+                    state.high_waiting += 1;
+                    waiting.high = true;
+                }
+                state.wakers.register(cx.waker());
+                Poll::Pending
+            })
+        })
+        .await;
+        FsGuard { shared: self }
+    }
+
+    /// 尝试加锁 (不等待)
+    ///
+    /// 有高优先级请求在等待时，普通优先级返回 None
+    pub fn try_lock(&self, priority: FsPriority) -> Option<FsGuard<'_>> {
+        self.state.lock(|state| {
+            let mut state = state.borrow_mut();
+            if !state.can_acquire(priority) {
+                return None;
+            }
+            state.locked = true;
+            Some(FsGuard { shared: self })
+        })
+    }
+
+    /// 带超时加锁
+    pub async fn lock_timeout(&self, priority: FsPriority, timeout: Duration) -> Result<FsGuard<'_>, Timeout> {
+        with_timeout(timeout, self.lock(priority)).await.map_err(|_| Timeout)
+    }
+
+    /// 当前是否被占用
+    pub fn is_locked(&self) -> bool {
+        self.state.lock(|state| state.borrow().locked)
+    }
+
+    /// 取回文件系统 (需独占所有权)
+    pub fn into_inner(self) -> FileSystem {
+        self.fs.into_inner()
+    }
+
+    fn unlock(&self) {
+        self.state.lock(|state| {
+            let mut state = state.borrow_mut();
+            state.locked = false;
+            state.wakers.wake();
+        });
+    }
+}
+
+/// 等待中的加锁请求 (future 被取消时撤销高优先级计数)
+struct Waiting<'a> {
+    shared: &'a SharedFileSystem,
+    high: bool,
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        if self.high {
+            self.shared.state.lock(|state| {
+                let mut state = state.borrow_mut();
+                state.high_waiting -= 1;
+                // 普通请求可能一直在等这个高优先级请求
+                state.wakers.wake();
+            });
+        }
+    }
+}
+
+/// 文件系统锁守卫，drop 时释放
+pub struct FsGuard<'a> {
+    shared: &'a SharedFileSystem,
+}
+
+impl Deref for FsGuard<'_> {
+    type Target = FileSystem;
+
+    fn deref(&self) -> &FileSystem {
+        // Safety: 持有守卫即持有锁
+        unsafe { &*self.shared.fs.get() }
+    }
+}
+
+impl DerefMut for FsGuard<'_> {
+    fn deref_mut(&mut self) -> &mut FileSystem {
+        // Safety: 持有守卫即持有锁
+        unsafe { &mut *self.shared.fs.get() }
+    }
+}
+
+impl Drop for FsGuard<'_> {
+    fn drop(&mut self) {
+        self.shared.unlock();
+    }
+}
+
+/// 可复制的文件系统访问句柄
+#[derive(Clone, Copy)]
+pub struct FsHandle {
+    shared: &'static SharedFileSystem,
+    priority: FsPriority,
+}
+
+impl FsHandle {
+    /// 使用指定优先级的句柄
+    pub const fn with_priority(mut self, priority: FsPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// 句柄优先级
+    pub fn priority(&self) -> FsPriority {
+        self.priority
+    }
+
+    /// 异步加锁
+    pub async fn lock(&self) -> FsGuard<'static> {
+        self.shared.lock(self.priority).await
+    }
+
+    /// 尝试加锁 (不等待)
+    pub fn try_lock(&self) -> Option<FsGuard<'static>> {
+        self.shared.try_lock(self.priority)
+    }
+
+    /// 带超时加锁
+    pub async fn lock_timeout(&self, timeout: Duration) -> Result<FsGuard<'static>, Timeout> {
+        self.shared.lock_timeout(self.priority, timeout).await
+    }
+
+    /// 加锁后执行 `f`，结束立即释放
+    pub async fn with<R>(&self, f: impl FnOnce(&mut FileSystem) -> R) -> R {
+        let mut guard = self.lock().await;
+        f(&mut guard)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority_gate() {
+        let mut state = LockState { locked: false, high_waiting: 0, wakers: MultiWakerRegistration::new() };
+        assert!(state.can_acquire(FsPriority::Normal));
+
+        // 高优先级请求排队时普通请求让路
+        state.high_waiting = 1;
+        assert!(!state.can_acquire(FsPriority::Normal));
+        assert!(state.can_acquire(FsPriority::High));
+
+        state.locked = true;
+        assert!(!state.can_acquire(FsPriority::High));
+    }
+}
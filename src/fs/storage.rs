@@ -1,9 +1,41 @@
 //! Flash 存储抽象层
 //!
 //! 提供对 ESP32 SPI Flash 的读写抽象，支持 littlefs2 所需的块设备接口
+//!
+//! 读取默认通过 ROM 的 SPI 读命令完成，不依赖 MMU 映射；内存映射读取只在
+//! 查询 MMU 表确认目标区域已映射到数据总线时使用。`ReadMode::Verify`
+//! 同时执行两种读取并比较，用于排查映射配置问题。
+//!
+//! 启用 Flash 加密后，标记为加密的分区改走加密路径: 写入使用 ROM 加密写
+//! (地址与长度按 `ENCRYPTED_BLOCK` 对齐)，读取经 Cache 解密 (区域未映射时
+//! 借用一个空闲 MMU 条目临时映射)，SPI 直读只能得到密文。
+//!
+//! 明文写入与擦除经启动时安装的 `FlashDriver` 完成 (通常是 `esp_storage::FlashStorage`)，
+//! 未安装时返回 `NoDriver`，不会静默丢弃数据。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::fs::storage::{self, FlashStorage};
+//! use static_cell::StaticCell;
+//!
+//! static FLASH: StaticCell<esp_storage::FlashStorage<'static>> = StaticCell::new();
+//! storage::install_driver(FLASH.init(esp_storage::FlashStorage::new(peripherals.FLASH)));
+//!
+//! let mut flash = FlashStorage::with_defaults();
+//! flash.init()?;
+//! flash.erase_block(0)?;
+//! flash.program(0, 0, b"hello")?;
+//! ```
 
+use core::cell::RefCell;
 use core::fmt;
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex;
+use embedded_storage::nor_flash::{NorFlash, ReadNorFlash};
 use esp_hal::spi::master::SpiDmaBus;
+
+use super::partition::{STORAGE_PARTITION_OFFSET, STORAGE_PARTITION_SIZE};
 // DMA 通道通过 peripherals.DMA_CHx 获取
 
 /// 存储操作错误
@@ -29,6 +61,10 @@ pub enum StorageError {
     VerifyError,
     /// DMA 错误
     DmaError,
+    /// 区域未映射到数据总线
+    NotMapped,
+    /// 未安装 Flash 驱动 (见 `install_driver`)
+    NoDriver,
 }
 
 impl fmt::Display for StorageError {
@@ -44,10 +80,281 @@ impl fmt::Display for StorageError {
             Self::NotInitialized => write!(f, "Not initialized"),
             Self::VerifyError => write!(f, "Verify error"),
             Self::DmaError => write!(f, "DMA transfer error"),
+            Self::NotMapped => write!(f, "Region not mapped"),
+            Self::NoDriver => write!(f, "Flash driver not installed"),
+        }
+    }
+}
+
+/// 读取方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReadMode {
+    /// ROM SPI 读命令 (不依赖 MMU 映射)
+    #[default]
+    Spi,
+    /// 查询 MMU 映射后从数据总线读取，区域未映射时回退到 SPI 读
+    Mapped,
+    /// 同时执行映射读与 SPI 读并比较，不一致返回 `VerifyError`
+    Verify,
+}
+
+/// ESP32-S3 MMU (数据总线) 映射查询
+mod mmu {
+    /// MMU 表基地址
+    const TABLE: *const u32 = 0x600C_5000 as *const u32;
+    /// MMU 表条目数
+    pub const ENTRIES: usize = 512;
+    /// 映射页大小 (64KB)
+    pub const PAGE_SIZE: u32 = 0x10000;
+    /// 条目无效
+    pub const INVALID: u32 = 1 << 14;
+    /// 条目指向 PSRAM
+    pub const ACCESS_SPIRAM: u32 = 1 << 15;
+    /// 物理页号掩码
+    pub const PAGE_MASK: u32 = 0x3FFF;
+    /// 数据总线虚拟地址基址
+    pub const DBUS_BASE: u32 = 0x3C00_0000;
+
+    /// 读取 MMU 表条目 (非 Xtensa 目标没有 MMU 表，全部视为无效)
+    pub fn entry(index: usize) -> u32 {
+        #[cfg(target_arch = "xtensa")]
+        {
+            // Safety: index < ENTRIES，MMU 表为只读访问的寄存器区域
+            unsafe { TABLE.add(index).read_volatile() }
+        }
+        #[cfg(not(target_arch = "xtensa"))]
+        {
+            let _ = (TABLE, index);
+            INVALID
+        }
+    }
+
+    /// 查找 Flash 物理区域 `[address, address + len)` 的数据总线地址
+    ///
+    /// 区域跨越多个页时要求这些页在虚拟地址上连续映射
+    pub fn find(entry: impl Fn(usize) -> u32, address: u32, len: u32) -> Option<u32> {
+        let first = address / PAGE_SIZE;
+        let last = (address + len.max(1) - 1) / PAGE_SIZE;
+        let pages = (last - first + 1) as usize;
+        let maps = |index: usize, page: u32| {
+            let e = entry(index);
+            e & (INVALID | ACCESS_SPIRAM) == 0 && e & PAGE_MASK == page
+        };
+
+        (0..=ENTRIES.checked_sub(pages)?)
+            .find(|&index| (0..pages).all(|k| maps(index + k, first + k as u32)))
+            .map(|index| DBUS_BASE + index as u32 * PAGE_SIZE + address % PAGE_SIZE)
+    }
+
+    /// 使用当前 MMU 表查找
+    pub fn lookup(address: u32, len: u32) -> Option<u32> {
+        find(entry, address, len)
+    }
+
+    /// 临时映射使用的 MMU 条目 (表末尾，启动代码不会占用)
+    const WINDOW: usize = ENTRIES - 1;
+
+    /// 通过临时映射窗口从数据总线读取 `[address, address + buffer.len())`
+    ///
+    /// 区域不得跨越 64KB 页；窗口条目已被占用时返回 `None`
+    pub fn read_window(address: u32, buffer: &mut [u8]) -> Option<()> {
+        let page = address / PAGE_SIZE;
+        if (address + buffer.len().max(1) as u32 - 1) / PAGE_SIZE != page {
+            return None;
+        }
+
+        critical_section::with(|_| unsafe {
+            let slot = (TABLE as *mut u32).add(WINDOW);
+            if slot.read_volatile() & INVALID == 0 {
+                return None;
+            }
+            let vaddr = DBUS_BASE + WINDOW as u32 * PAGE_SIZE;
+            slot.write_volatile(page);
+            Cache_Invalidate_Addr(vaddr, PAGE_SIZE);
+            core::ptr::copy_nonoverlapping(
+                (vaddr + address % PAGE_SIZE) as *const u8,
+                buffer.as_mut_ptr(),
+                buffer.len(),
+            );
+            slot.write_volatile(INVALID);
+            Cache_Invalidate_Addr(vaddr, PAGE_SIZE);
+            Some(())
+        })
+    }
+
+    /// 丢弃区域在 Cache 中的旧内容 (加密写入后映射视图需重新解密)
+    pub fn invalidate(address: u32, len: u32) {
+        if let Some(vaddr) = lookup(address, len) {
+            unsafe { Cache_Invalidate_Addr(vaddr, len) };
+        }
+    }
+
+    extern "C" {
+        /// ROM 函数: 使指定虚拟地址范围的 Cache 失效
+        fn Cache_Invalidate_Addr(addr: u32, size: u32) -> i32;
+    }
+
+    /// 反向查找: 数据总线地址对应的 Flash 物理地址
+    pub fn physical(entry: impl Fn(usize) -> u32, vaddr: u32) -> Option<u32> {
+        let index = (vaddr.checked_sub(DBUS_BASE)? / PAGE_SIZE) as usize;
+        if index >= ENTRIES {
+            return None;
+        }
+        let e = entry(index);
+        (e & (INVALID | ACCESS_SPIRAM) == 0).then(|| (e & PAGE_MASK) * PAGE_SIZE + vaddr % PAGE_SIZE)
+    }
+}
+
+/// 常量数据 (位于 Flash 映射区) 的 Flash 物理地址
+///
+/// 指针不在数据总线映射区或指向 PSRAM 时返回 `None`
+pub fn flash_address_of<T>(data: &'static T) -> Option<u32> {
+    mmu::physical(mmu::entry, data as *const T as u32)
+}
+
+extern "C" {
+    /// ROM 函数: 通过 SPI1 读取 Flash (地址与长度需 4 字节对齐)
+    fn esp_rom_spiflash_read(src_addr: u32, dest: *mut u32, len: u32) -> i32;
+    /// ROM 函数: 允许 SPI1 写入经过 Flash 加密模块
+    fn esp_rom_spiflash_write_encrypted_enable();
+    /// ROM 函数: 加密写入 (地址与长度需 16 字节对齐)
+    fn esp_rom_spiflash_write_encrypted(flash_addr: u32, data: *mut u32, len: u32) -> i32;
+    /// ROM 函数: 恢复明文写入
+    fn esp_rom_spiflash_write_encrypted_disable();
+}
+
+// ===== Flash 驱动 =====
+
+/// Flash 明文读写驱动 (地址为 Flash 物理地址)
+///
+/// 实现了 `embedded_storage::nor_flash::NorFlash` 的类型 (如 `esp_storage::FlashStorage`)
+/// 自动实现本 trait，地址与长度不满足驱动对齐要求时按块补齐 (写入补 0xFF，不改变已有内容)
+pub trait FlashDriver {
+    /// 读取 `[address, address + buffer.len())`
+    fn read(&mut self, address: u32, buffer: &mut [u8]) -> Result<(), StorageError>;
+    /// 编程 `[address, address + data.len())` (目标区域须已擦除)
+    fn write(&mut self, address: u32, data: &[u8]) -> Result<(), StorageError>;
+    /// 擦除 `[from, to)` (按扇区对齐)
+    fn erase(&mut self, from: u32, to: u32) -> Result<(), StorageError>;
+}
+
+impl<T: NorFlash> FlashDriver for T {
+    fn read(&mut self, address: u32, buffer: &mut [u8]) -> Result<(), StorageError> {
+        let mut chunk = [0u8; DRIVER_CHUNK];
+        let mut done = 0;
+        while done < buffer.len() {
+            let (aligned, skip, n, len) = driver_span(address + done as u32, buffer.len() - done, T::READ_SIZE);
+            ReadNorFlash::read(self, aligned, &mut chunk[..len]).map_err(|_| StorageError::ReadError)?;
+            buffer[done..done + n].copy_from_slice(&chunk[skip..skip + n]);
+            done += n;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, address: u32, data: &[u8]) -> Result<(), StorageError> {
+        let mut chunk = [0u8; DRIVER_CHUNK];
+        let mut done = 0;
+        while done < data.len() {
+            let (aligned, skip, n, len) = driver_span(address + done as u32, data.len() - done, T::WRITE_SIZE);
+            chunk[..len].fill(0xFF);
+            chunk[skip..skip + n].copy_from_slice(&data[done..done + n]);
+            NorFlash::write(self, aligned, &chunk[..len]).map_err(|_| StorageError::WriteError)?;
+            done += n;
+        }
+        Ok(())
+    }
+
+    fn erase(&mut self, from: u32, to: u32) -> Result<(), StorageError> {
+        let unit = T::ERASE_SIZE as u32;
+        if !from.is_multiple_of(unit) || !to.is_multiple_of(unit) {
+            return Err(StorageError::AlignmentError);
         }
+        NorFlash::erase(self, from, to).map_err(|_| StorageError::EraseError)
     }
 }
 
+/// 驱动读写的分块大小 (驱动的读写单位须整除它)
+const DRIVER_CHUNK: usize = 256;
+
+/// 计算从 `address` 开始、最多 `remaining` 字节的一次驱动访问
+///
+/// 返回 (对齐后的地址, 数据在块内的偏移, 本次数据长度, 对齐后的访问长度)
+fn driver_span(address: u32, remaining: usize, unit: usize) -> (u32, usize, usize, usize) {
+    let unit = unit.max(1);
+    let skip = address as usize % unit;
+    let n = (DRIVER_CHUNK - skip).min(remaining);
+    (address - skip as u32, skip, n, (skip + n).next_multiple_of(unit))
+}
+
+/// Flash 驱动槽位
+enum DriverSlot {
+    /// 未安装
+    Empty,
+    /// 已安装且空闲
+    Idle(&'static mut (dyn FlashDriver + Send)),
+    /// 已被某个操作取出
+    InUse,
+}
+
+/// 已安装的 Flash 驱动 (Flash 芯片为 OTA、NVS 与文件系统共用的单例)
+///
+/// 临界区只用于取出和放回驱动，擦除与编程 (几十到几百毫秒) 在临界区外执行
+static DRIVER: Mutex<CriticalSectionRawMutex, RefCell<DriverSlot>> = Mutex::new(RefCell::new(DriverSlot::Empty));
+
+/// 安装 Flash 驱动 (启动时调用一次，替换已安装的驱动)
+///
+/// 未安装驱动时写入与擦除返回 `NoDriver`，读取使用 ROM SPI 读命令
+pub fn install_driver(driver: &'static mut (dyn FlashDriver + Send)) {
+    DRIVER.lock(|slot| *slot.borrow_mut() = DriverSlot::Idle(driver));
+}
+
+/// 是否已安装 Flash 驱动
+pub fn has_driver() -> bool {
+    DRIVER.lock(|slot| !matches!(*slot.borrow(), DriverSlot::Empty))
+}
+
+/// 使用已安装的驱动执行操作
+///
+/// 操作期间驱动从槽位中取出，其他上下文 (另一个任务、中断或核心) 同时访问时返回 `Busy`，
+/// 不会在临界区内等待 Flash 完成
+fn with_driver<R>(f: impl FnOnce(&mut dyn FlashDriver) -> Result<R, StorageError>) -> Result<R, StorageError> {
+    let driver = DRIVER.lock(|slot| {
+        let mut slot = slot.borrow_mut();
+        match core::mem::replace(&mut *slot, DriverSlot::InUse) {
+            DriverSlot::Idle(driver) => Ok(driver),
+            DriverSlot::Empty => {
+                *slot = DriverSlot::Empty;
+                Err(StorageError::NoDriver)
+            }
+            DriverSlot::InUse => Err(StorageError::Busy),
+        }
+    })?;
+
+    let result = f(&mut *driver);
+
+    // 操作期间重新安装了驱动时丢弃旧驱动
+    DRIVER.lock(|slot| {
+        let mut slot = slot.borrow_mut();
+        if matches!(*slot, DriverSlot::InUse) {
+            *slot = DriverSlot::Idle(driver);
+        }
+    });
+    result
+}
+
+/// 加密分区的最小读写单位
+///
+/// XTS-AES 以 16 字节为单位加密，ESP32 系列的加密块为 32 字节；
+/// 按 32 字节对齐同时满足两者
+pub const ENCRYPTED_BLOCK: u32 = 32;
+
+/// SPI 读取分块大小
+const SPI_READ_CHUNK: usize = 256;
+
+/// 4 字节对齐的 SPI 读缓冲区
+#[repr(C, align(4))]
+struct SpiChunk([u8; SPI_READ_CHUNK]);
+
 /// Flash 存储配置
 #[derive(Debug, Clone, Copy)]
 pub struct FlashConfig {
@@ -63,6 +370,10 @@ pub struct FlashConfig {
     pub partition_offset: u32,
     /// 分区大小
     pub partition_size: u32,
+    /// 写保护 (分区表标记为只读的分区)，写入和擦除返回 `WriteProtected`
+    pub write_protected: bool,
+    /// 分区表标记为加密的分区 (仅在芯片启用 Flash 加密时生效)
+    pub encrypted: bool,
 }
 
 impl Default for FlashConfig {
@@ -72,8 +383,10 @@ impl Default for FlashConfig {
             sector_size: 4096,              // 4KB
             block_size: 4096,               // 4KB
             page_size: 256,                 // 256B
-            partition_offset: 0x410000,     // 默认存储分区偏移
-            partition_size: 0xBF0000,       // ~12MB
+            partition_offset: STORAGE_PARTITION_OFFSET,
+            partition_size: STORAGE_PARTITION_SIZE,
+            write_protected: false,
+            encrypted: false,
         }
     }
 }
@@ -86,6 +399,10 @@ pub struct FlashStorage {
     config: FlashConfig,
     /// 是否已初始化
     initialized: bool,
+    /// 读取方式
+    read_mode: ReadMode,
+    /// 是否走加密读写路径
+    encryption: bool,
 }
 
 impl FlashStorage {
@@ -94,9 +411,17 @@ impl FlashStorage {
         Self {
             config,
             initialized: false,
+            read_mode: ReadMode::Spi,
+            encryption: false,
         }
     }
 
+    /// 设置读取方式
+    pub const fn with_read_mode(mut self, mode: ReadMode) -> Self {
+        self.read_mode = mode;
+        self
+    }
+
     /// 使用默认配置创建
     pub const fn with_defaults() -> Self {
         Self::new(FlashConfig {
@@ -104,12 +429,16 @@ impl FlashStorage {
             sector_size: 4096,
             block_size: 4096,
             page_size: 256,
-            partition_offset: 0x410000,
-            partition_size: 0xBF0000,
+            partition_offset: STORAGE_PARTITION_OFFSET,
+            partition_size: STORAGE_PARTITION_SIZE,
+            write_protected: false,
+            encrypted: false,
         })
     }
 
     /// 从分区信息创建
+    ///
+    /// 分区表标记为只读的分区会启用写保护，加密标记记录在 `FlashConfig::encrypted`
     pub fn from_partition(partition: &super::partition::Partition, total_flash_size: u32) -> Self {
         Self::new(FlashConfig {
             total_size: total_flash_size,
@@ -118,6 +447,8 @@ impl FlashStorage {
             page_size: 256,
             partition_offset: partition.offset,
             partition_size: partition.size,
+            write_protected: partition.is_readonly(),
+            encrypted: partition.is_encrypted(),
         })
     }
 
@@ -146,6 +477,44 @@ impl FlashStorage {
         &self.config
     }
 
+    /// 读取方式
+    pub fn read_mode(&self) -> ReadMode {
+        self.read_mode
+    }
+
+    /// 切换读取方式
+    pub fn set_read_mode(&mut self, mode: ReadMode) {
+        self.read_mode = mode;
+    }
+
+    /// 是否写保护
+    pub fn is_write_protected(&self) -> bool {
+        self.config.write_protected
+    }
+
+    /// 是否走加密读写路径
+    pub fn is_encrypted(&self) -> bool {
+        self.encryption
+    }
+
+    /// 切换加密读写路径
+    ///
+    /// 调用者负责确认芯片已启用 Flash 加密，见 `LfsStorageAdapter::new`
+    pub fn set_encrypted(&mut self, encrypted: bool) {
+        self.encryption = encrypted;
+    }
+
+    /// 检查是否允许修改 Flash
+    fn check_writable(&self) -> Result<(), StorageError> {
+        if !self.initialized {
+            return Err(StorageError::NotInitialized);
+        }
+        if self.config.write_protected {
+            return Err(StorageError::WriteProtected);
+        }
+        Ok(())
+    }
+
     /// 获取分区中的块数
     pub fn block_count(&self) -> u32 {
         self.config.partition_size / self.config.block_size
@@ -165,10 +534,9 @@ impl FlashStorage {
         Ok(self.config.partition_offset + offset)
     }
 
-    /// 读取块数据 (内部 Flash 使用内存映射)
+    /// 读取块数据
     ///
-    /// # 安全性
-    /// ESP32 内部 Flash 映射到地址空间 0x3C000000+，可直接读取
+    /// 按 `ReadMode` 选择 SPI 读或映射读
     pub fn read_block(&self, block: u32, buffer: &mut [u8]) -> Result<(), StorageError> {
         if !self.initialized {
             return Err(StorageError::NotInitialized);
@@ -179,11 +547,7 @@ impl FlashStorage {
         }
 
         let address = self.block_to_address(block)?;
-        
-        // ESP32-S3 Flash 映射地址
-        // 内部 Flash 映射到 0x3C000000 (数据) 或通过 SPI 访问
-        // 这里使用 ROM 函数或 SPI 读取
-        
+
         unsafe {
             self.read_flash_internal(address, buffer)?;
         }
@@ -191,14 +555,32 @@ impl FlashStorage {
         Ok(())
     }
 
+    /// 获取从指定块开始的连续区域的内存映射视图
+    ///
+    /// 通过 MMU 表查找区域的数据总线地址，未映射 (或映射不连续) 时返回
+    /// `NotMapped`。视图在对应块被擦除或重写前有效，调用者需保证期间不修改这些块。
+    pub fn map(&self, block: u32, len: u32) -> Result<&'static [u8], StorageError> {
+        if !self.initialized {
+            return Err(StorageError::NotInitialized);
+        }
+
+        let offset = block
+            .checked_mul(self.config.block_size)
+            .ok_or(StorageError::OutOfBounds)?;
+        if offset as u64 + len as u64 > self.config.partition_size as u64 {
+            return Err(StorageError::OutOfBounds);
+        }
+
+        let address = mmu::lookup(self.config.partition_offset + offset, len).ok_or(StorageError::NotMapped)?;
+        Ok(unsafe { core::slice::from_raw_parts(address as *const u8, len as usize) })
+    }
+
     /// 写入块数据
     ///
     /// # 注意
     /// Flash 写入前需要先擦除对应扇区
     pub fn write_block(&mut self, block: u32, data: &[u8]) -> Result<(), StorageError> {
-        if !self.initialized {
-            return Err(StorageError::NotInitialized);
-        }
+        self.check_writable()?;
 
         if data.len() > self.config.block_size as usize {
             return Err(StorageError::OutOfBounds);
@@ -213,13 +595,30 @@ impl FlashStorage {
         Ok(())
     }
 
+    /// 在块内指定偏移处编程
+    ///
+    /// 目标区域必须已擦除
+    pub fn program(&mut self, block: u32, offset: u32, data: &[u8]) -> Result<(), StorageError> {
+        self.check_writable()?;
+
+        if offset + data.len() as u32 > self.config.block_size {
+            return Err(StorageError::OutOfBounds);
+        }
+
+        let address = self.block_to_address(block)? + offset;
+
+        unsafe {
+            self.write_flash_internal(address, data)?;
+        }
+
+        Ok(())
+    }
+
     /// 擦除块
     ///
     /// 将整个块设置为 0xFF
     pub fn erase_block(&mut self, block: u32) -> Result<(), StorageError> {
-        if !self.initialized {
-            return Err(StorageError::NotInitialized);
-        }
+        self.check_writable()?;
 
         let address = self.block_to_address(block)?;
 
@@ -252,112 +651,143 @@ impl FlashStorage {
 
     /// 内部 Flash 读取实现
     ///
-    /// 使用 ESP32 ROM 函数或 SPI 读取
+    /// `address` 为 Flash 物理地址，调用者已检查在分区范围内
     unsafe fn read_flash_internal(&self, address: u32, buffer: &mut [u8]) -> Result<(), StorageError> {
-        // ESP32-S3 内部 Flash 可通过缓存映射直接读取
-        // 数据地址映射: 0x3C000000 + offset
-        
-        // 方法1: 通过内存映射读取 (需要确保地址在映射范围内)
-        // 方法2: 使用 SPI 读取命令
-        
-        // 简化实现: 假设 Flash 已映射到内存
-        // 实际实现需要根据 esp-hal 的 Flash 驱动
-        
-        let flash_data_base: u32 = 0x3C000000;
-        let mapped_addr = flash_data_base + address;
-        
-        let src = mapped_addr as *const u8;
-        core::ptr::copy_nonoverlapping(src, buffer.as_mut_ptr(), buffer.len());
-        
+        if address as u64 + buffer.len() as u64 > self.config.total_size as u64 {
+            return Err(StorageError::OutOfBounds);
+        }
+
+        // SPI 直读得到的是密文，加密分区只能经 Cache 解密读取
+        if self.encryption {
+            return Self::read_decrypted(address, buffer);
+        }
+
+        match self.read_mode {
+            ReadMode::Spi => Self::read_spi(address, buffer),
+            ReadMode::Mapped => match mmu::lookup(address, buffer.len() as u32) {
+                Some(mapped) => {
+                    core::ptr::copy_nonoverlapping(mapped as *const u8, buffer.as_mut_ptr(), buffer.len());
+                    Ok(())
+                }
+                None => Self::read_spi(address, buffer),
+            },
+            ReadMode::Verify => {
+                Self::read_spi(address, buffer)?;
+                if let Some(mapped) = mmu::lookup(address, buffer.len() as u32) {
+                    let view = core::slice::from_raw_parts(mapped as *const u8, buffer.len());
+                    if view != buffer {
+                        return Err(StorageError::VerifyError);
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// 通过 SPI 读取 (处理非对齐的地址与长度)
+    ///
+    /// 已安装 `FlashDriver` 时经驱动读取，与写入/擦除串行；否则使用 ROM SPI 读命令
+    fn read_spi(address: u32, buffer: &mut [u8]) -> Result<(), StorageError> {
+        match with_driver(|driver| driver.read(address, buffer)) {
+            Err(StorageError::NoDriver) => {}
+            result => return result,
+        }
+
+        let mut chunk = SpiChunk([0; SPI_READ_CHUNK]);
+        let mut done = 0;
+
+        while done < buffer.len() {
+            let current = address + done as u32;
+            let aligned = current & !3;
+            let skip = (current - aligned) as usize;
+            let n = (SPI_READ_CHUNK - skip).min(buffer.len() - done);
+            let len = (skip + n + 3) & !3;
+
+            // SPI1 与 Cache (SPI0) 共用 Flash，读取期间禁止被打断
+            let rc = critical_section::with(|_| unsafe {
+                esp_rom_spiflash_read(aligned, chunk.0.as_mut_ptr() as *mut u32, len as u32)
+            });
+            if rc != 0 {
+                return Err(StorageError::ReadError);
+            }
+
+            buffer[done..done + n].copy_from_slice(&chunk.0[skip..skip + n]);
+            done += n;
+        }
+
+        Ok(())
+    }
+
+    /// 经 Cache 解密读取 (区域未映射时按页借用临时映射窗口)
+    fn read_decrypted(address: u32, buffer: &mut [u8]) -> Result<(), StorageError> {
+        if let Some(mapped) = mmu::lookup(address, buffer.len() as u32) {
+            unsafe { core::ptr::copy_nonoverlapping(mapped as *const u8, buffer.as_mut_ptr(), buffer.len()) };
+            return Ok(());
+        }
+
+        let mut done = 0;
+        while done < buffer.len() {
+            let current = address + done as u32;
+            let n = ((mmu::PAGE_SIZE - current % mmu::PAGE_SIZE) as usize).min(buffer.len() - done);
+            mmu::read_window(current, &mut buffer[done..done + n]).ok_or(StorageError::NotMapped)?;
+            done += n;
+        }
         Ok(())
     }
 
     /// 内部 Flash 写入实现
     ///
-    /// 使用 ESP32 ROM 函数进行编程
+    /// 明文分区经已安装的 `FlashDriver` 写入，加密分区使用 ROM 加密写
     unsafe fn write_flash_internal(&mut self, address: u32, data: &[u8]) -> Result<(), StorageError> {
-        // ESP32 Flash 写入需要:
-        // 1. 禁用中断和缓存
-        // 2. 使用 ROM 函数或 SPI 命令
-        // 3. 等待写入完成
-        // 4. 恢复缓存和中断
-        
-        // 按页面大小分块写入
-        let page_size = self.config.page_size as usize;
-        let mut offset = 0;
-        
-        while offset < data.len() {
-            let current_addr = address + offset as u32;
-            let page_offset = (current_addr % self.config.page_size) as usize;
-            let write_size = core::cmp::min(
-                page_size - page_offset,
-                data.len() - offset
-            );
-            
-            // 调用 ROM 函数写入
-            // esp_rom_spiflash_write(current_addr, data[offset..].as_ptr(), write_size)
-            
-            // 占位实现 - 实际需要调用 esp-hal 的 Flash 写入 API
-            self.write_page_internal(current_addr, &data[offset..offset + write_size])?;
-            
-            offset += write_size;
+        if address as u64 + data.len() as u64 > self.config.total_size as u64 {
+            return Err(StorageError::OutOfBounds);
         }
-        
+        if self.encryption {
+            return self.write_encrypted_internal(address, data);
+        }
+
+        with_driver(|driver| driver.write(address, data))?;
+        mmu::invalidate(address, data.len() as u32);
         Ok(())
     }
 
-    /// 写入单个页面
+    /// 加密写入
     ///
-    /// # Safety
-    /// 调用者必须确保地址有效且在分区范围内。
-    ///
-    /// # 实现说明
-    /// ESP32-S3 内部 Flash 写入需要使用 ROM 函数。
-    /// 直接内存映射只能读取，不能写入。
-    ///
-    /// 当前为占位实现，返回 Ok 但不执行实际写入。
-    /// 实际应用中应使用 esp-storage crate 或 esp-hal 的 flash API。
-    unsafe fn write_page_internal(&mut self, _address: u32, _data: &[u8]) -> Result<(), StorageError> {
-        // 实现步骤:
-        // 1. 禁用中断和 Cache
-        // 2. 发送 Write Enable 命令 (0x06)
-        // 3. 发送 Page Program 命令 (0x02) + 地址 + 数据
-        // 4. 轮询 Status Register 等待 WIP 位清零
-        // 5. 恢复 Cache 和中断
-        //
-        // 可选方案:
-        // - esp-storage crate: https://github.com/esp-rs/esp-storage
-        // - esp_rom_spiflash_write() ROM 函数
-        //
-        // 占位实现 - 返回 Ok 但不执行实际写入
-        // 这允许编译和基本测试，但不会持久化数据
+    /// 数据经 Flash 加密模块写入，地址与长度必须按 `ENCRYPTED_BLOCK` 对齐
+    unsafe fn write_encrypted_internal(&mut self, address: u32, data: &[u8]) -> Result<(), StorageError> {
+        if !address.is_multiple_of(ENCRYPTED_BLOCK) || !(data.len() as u32).is_multiple_of(ENCRYPTED_BLOCK) {
+            return Err(StorageError::AlignmentError);
+        }
+
+        // ROM 函数要求 4 字节对齐的源缓冲区
+        let mut chunk = SpiChunk([0; SPI_READ_CHUNK]);
+        for (i, part) in data.chunks(SPI_READ_CHUNK).enumerate() {
+            let current = address + (i * SPI_READ_CHUNK) as u32;
+            chunk.0[..part.len()].copy_from_slice(part);
+
+            let rc = critical_section::with(|_| {
+                esp_rom_spiflash_write_encrypted_enable();
+                let rc = esp_rom_spiflash_write_encrypted(current, chunk.0.as_mut_ptr() as *mut u32, part.len() as u32);
+                esp_rom_spiflash_write_encrypted_disable();
+                rc
+            });
+            if rc != 0 {
+                return Err(StorageError::WriteError);
+            }
+        }
+
+        mmu::invalidate(address, data.len() as u32);
         Ok(())
     }
 
     /// 擦除单个扇区
     ///
     /// # Safety
-    /// 调用者必须确保地址有效且在分区范围内。
-    ///
-    /// # 实现说明
-    /// 扇区擦除通常需要几十到几百毫秒。
-    ///
-    /// 当前为占位实现，返回 Ok 但不执行实际擦除。
-    /// 实际应用中应使用 esp-storage crate 或 esp-hal 的 flash API。
-    unsafe fn erase_sector_internal(&mut self, _address: u32) -> Result<(), StorageError> {
-        // 实现步骤:
-        // 1. 禁用中断和 Cache
-        // 2. 发送 Write Enable 命令 (0x06)
-        // 3. 发送 Sector Erase 命令 (0x20) + 地址
-        // 4. 轮询 Status Register 等待擦除完成 (通常 50-200ms)
-        // 5. 恢复 Cache 和中断
-        //
-        // 可选方案:
-        // - esp-storage crate: https://github.com/esp-rs/esp-storage
-        // - esp_rom_spiflash_erase_sector() ROM 函数
-        //
-        // 占位实现 - 返回 Ok 但不执行实际擦除
-        // 这允许编译和基本测试，但不会修改 Flash 内容
+    /// 调用者必须确保地址有效且在分区范围内。扇区擦除通常需要几十到几百毫秒
+    unsafe fn erase_sector_internal(&mut self, address: u32) -> Result<(), StorageError> {
+        let end = address + self.config.sector_size;
+        with_driver(|driver| driver.erase(address, end))?;
+        mmu::invalidate(address, self.config.sector_size);
         Ok(())
     }
 }
@@ -419,18 +849,106 @@ impl<'d> ExternalFlash<'d> {
 /// 这个模块提供 FlashStorage 到 littlefs2 Storage trait 的适配
 pub mod littlefs_adapter {
     use super::*;
-    
+    use core::marker::PhantomData;
+    use generic_array::ArrayLength;
+    use littlefs2::driver::Storage;
+    use littlefs2::io;
+
+    /// LittleFS 块大小
+    pub const LFS_BLOCK_SIZE: usize = 4096;
+
+    /// LittleFS 使用的块数 (分区开头的 `LFS_BLOCK_COUNT` 个块)
+    ///
+    /// littlefs2 0.4 的几何参数是编译期常量，这里取默认分区表中存储分区
+    /// (`STORAGE_PARTITION_SIZE`) 的块数；分区去掉连续存储区域后不足此值时挂载返回 `InvalidParam`
+    pub const LFS_BLOCK_COUNT: usize = STORAGE_PARTITION_SIZE as usize / LFS_BLOCK_SIZE;
+
+    /// LittleFS 读/编程单位 (同时满足加密分区的 `ENCRYPTED_BLOCK` 对齐)
+    pub const LFS_IO_SIZE: usize = 256;
+
+    /// 缓存与 lookahead 大小使用的类型级常量 (`U512` 等)
+    pub use littlefs2::consts;
+
+    /// 挂载参数配置
+    ///
+    /// littlefs2 0.4 的块数、缓存、lookahead 与块周期都是 `Storage` 的编译期参数，
+    /// 不同分区使用不同参数时各自实现一个配置类型 (见 `lfs_profile!`)
+    pub trait LfsProfile: 'static {
+        /// LittleFS 使用的块数 (分区开头)
+        const BLOCK_COUNT: usize;
+        /// 块周期 (磨损均衡，-1 关闭)
+        const BLOCK_CYCLES: isize;
+        /// 块缓存大小 (字节，须为 `LFS_IO_SIZE` 的倍数)
+        type CacheSize: ArrayLength<u8>;
+        /// lookahead 缓冲区大小 (以 8 字节为单位)
+        type LookaheadSize: ArrayLength<u64>;
+    }
+
+    /// 默认配置: 主存储分区
+    pub struct DefaultProfile;
+
+    impl LfsProfile for DefaultProfile {
+        const BLOCK_COUNT: usize = LFS_BLOCK_COUNT;
+        const BLOCK_CYCLES: isize = 500;
+        type CacheSize = consts::U512;
+        type LookaheadSize = consts::U2;
+    }
+
+    /// 定义挂载参数配置
+    ///
+    /// ```rust,ignore
+    /// // 只读为主的资源分区: 1MB，大缓存，关闭磨损均衡搬移
+    /// lfs_profile!(pub AssetsProfile, blocks = 256, cache = U1024, lookahead = U4, cycles = -1);
+    /// ```
+    #[macro_export]
+    macro_rules! lfs_profile {
+        ($vis:vis $name:ident, blocks = $blocks:expr, cache = $cache:ident, lookahead = $lookahead:ident, cycles = $cycles:expr) => {
+            $vis struct $name;
+
+            impl $crate::fs::storage::littlefs_adapter::LfsProfile for $name {
+                const BLOCK_COUNT: usize = $blocks;
+                const BLOCK_CYCLES: isize = $cycles;
+                type CacheSize = $crate::fs::storage::littlefs_adapter::consts::$cache;
+                type LookaheadSize = $crate::fs::storage::littlefs_adapter::consts::$lookahead;
+            }
+        };
+    }
+
     /// LittleFS 存储适配器
     /// 
-    /// 包装 FlashStorage 实现 littlefs2 所需的接口
-    pub struct LfsStorageAdapter {
+    /// 包装 FlashStorage 实现 littlefs2 所需的接口。加密分区的编程请求
+    /// 必须按 `io_unit()` 对齐，文件系统据此调整 read/prog 大小
+    pub struct LfsStorageAdapter<P: LfsProfile = DefaultProfile> {
         storage: FlashStorage,
+        /// 最近一次失败的存储错误 (littlefs2 只能返回 `Io`)
+        error: Option<StorageError>,
+        profile: PhantomData<P>,
     }
 
-    impl LfsStorageAdapter {
+    impl<P: LfsProfile> LfsStorageAdapter<P> {
         /// 创建适配器
+        ///
+        /// 分区标记为加密且芯片已启用 Flash 加密 (eFuse) 时使用加密读写路径
         pub fn new(storage: FlashStorage) -> Self {
-            Self { storage }
+            let encrypted = storage.config().encrypted
+                && crate::sys::security::SecurityStatus::read().flash_encryption;
+            Self::with_encryption(storage, encrypted)
+        }
+
+        /// 使用指定的加密状态创建适配器 (不读取 eFuse)
+        pub fn with_encryption(mut storage: FlashStorage, encrypted: bool) -> Self {
+            storage.set_encrypted(encrypted);
+            Self { storage, error: None, profile: PhantomData }
+        }
+
+        /// 是否走加密读写路径
+        pub fn is_encrypted(&self) -> bool {
+            self.storage.is_encrypted()
+        }
+
+        /// 最小读写单位 (read_size / prog_size 必须是它的倍数)
+        pub fn io_unit(&self) -> u32 {
+            if self.storage.is_encrypted() { ENCRYPTED_BLOCK } else { 1 }
         }
 
         /// 获取内部存储引用
@@ -451,39 +969,24 @@ pub mod littlefs_adapter {
             if offset + buffer.len() as u32 > block_size {
                 return Err(StorageError::OutOfBounds);
             }
-
-            // 读取整个块到临时缓冲区，然后复制所需部分
-            // 优化: 如果读取整个块，直接使用目标缓冲区
-            if offset == 0 && buffer.len() == block_size as usize {
-                return self.storage.read_block(block, buffer);
+            if !self.storage.initialized {
+                return Err(StorageError::NotInitialized);
             }
 
-            // 部分读取 - 需要临时缓冲区
-            // 注意: 在 no_std 环境中，可能需要使用固定大小的栈缓冲区
-            let mut temp = [0u8; 4096]; // 假设最大块大小为 4KB
-            self.storage.read_block(block, &mut temp[..block_size as usize])?;
-            buffer.copy_from_slice(&temp[offset as usize..offset as usize + buffer.len()]);
-
-            Ok(())
+            // 直接读取块内区域，不经过整块缓冲
+            let address = self.storage.block_to_address(block)? + offset;
+            unsafe { self.storage.read_flash_internal(address, buffer) }
         }
 
         /// 写入操作 (编程)
+        ///
+        /// 加密分区要求偏移与长度按 `io_unit()` 对齐
         pub fn prog(&mut self, block: u32, offset: u32, data: &[u8]) -> Result<(), StorageError> {
-            let block_size = self.storage.config.block_size;
-            
-            if offset + data.len() as u32 > block_size {
-                return Err(StorageError::OutOfBounds);
+            let unit = self.io_unit();
+            if !offset.is_multiple_of(unit) || !(data.len() as u32).is_multiple_of(unit) {
+                return Err(StorageError::AlignmentError);
             }
-
-            // 计算实际 Flash 地址
-            let base_addr = self.storage.block_to_address(block)?;
-            let write_addr = base_addr + offset;
-
-            unsafe {
-                self.storage.write_flash_internal(write_addr, data)?;
-            }
-
-            Ok(())
+            self.storage.program(block, offset, data)
         }
 
         /// 擦除操作
@@ -505,6 +1008,141 @@ pub mod littlefs_adapter {
         pub fn block_size(&self) -> u32 {
             self.storage.block_size()
         }
+
+        /// 取出最近一次失败的存储错误
+        pub fn take_error(&mut self) -> Option<StorageError> {
+            self.error.take()
+        }
+
+        /// 记录存储错误并转换为 littlefs2 错误
+        fn fail(&mut self, error: StorageError) -> io::Error {
+            self.error = Some(error);
+            io::Error::Io
+        }
+    }
+
+    impl<P: LfsProfile> Storage for LfsStorageAdapter<P> {
+        const READ_SIZE: usize = LFS_IO_SIZE;
+        const WRITE_SIZE: usize = LFS_IO_SIZE;
+        const BLOCK_SIZE: usize = LFS_BLOCK_SIZE;
+        const BLOCK_COUNT: usize = P::BLOCK_COUNT;
+        const BLOCK_CYCLES: isize = P::BLOCK_CYCLES;
+        type CACHE_SIZE = P::CacheSize;
+        /// 以 8 字节为单位
+        type LOOKAHEAD_SIZE = P::LookaheadSize;
+
+        fn read(&mut self, off: usize, buf: &mut [u8]) -> io::Result<usize> {
+            let (block, offset) = ((off / LFS_BLOCK_SIZE) as u32, (off % LFS_BLOCK_SIZE) as u32);
+            LfsStorageAdapter::read(self, block, offset, buf).map_err(|e| self.fail(e))?;
+            Ok(buf.len())
+        }
+
+        fn write(&mut self, off: usize, data: &[u8]) -> io::Result<usize> {
+            let (block, offset) = ((off / LFS_BLOCK_SIZE) as u32, (off % LFS_BLOCK_SIZE) as u32);
+            self.prog(block, offset, data).map_err(|e| self.fail(e))?;
+            Ok(data.len())
+        }
+
+        fn erase(&mut self, off: usize, len: usize) -> io::Result<usize> {
+            let first = off / LFS_BLOCK_SIZE;
+            for block in first..first + len.div_ceil(LFS_BLOCK_SIZE) {
+                LfsStorageAdapter::erase(self, block as u32).map_err(|e| self.fail(e))?;
+            }
+            Ok(len)
+        }
+    }
+}
+
+/// 测试用的内存 NOR Flash 驱动
+///
+/// 驱动是全局的，各模块的测试共用同一块内存并各自使用不同的地址范围:
+/// `fs::storage` 使用前 2 个块，`fs::littlefs` 使用其后的块。
+/// 驱动在操作期间被独占，测试须持有 `install` 返回的守卫以串行执行
+#[cfg(test)]
+pub(crate) mod ram_flash {
+    extern crate std;
+
+    use super::{DriverSlot, FlashDriver, DRIVER};
+    use embedded_storage::nor_flash::{ErrorType, NorFlash, NorFlashErrorKind, ReadNorFlash};
+    use std::sync::{Mutex, MutexGuard};
+
+    /// 起始 Flash 地址
+    pub const BASE: u32 = 0x100000;
+    /// 容量
+    pub const SIZE: usize = 24 * 4096;
+
+    /// 4 字节读写单位，编程只能把 1 变为 0
+    struct RamFlash {
+        data: [u8; SIZE],
+    }
+
+    static mut FLASH: RamFlash = RamFlash { data: [0; SIZE] };
+
+    /// 串行化使用测试 Flash 的测试
+    static SERIAL: Mutex<()> = Mutex::new(());
+
+    /// 安装驱动 (已安装时保持不变)，返回的守卫释放前其他测试不会访问驱动
+    pub fn install() -> MutexGuard<'static, ()> {
+        let guard = SERIAL.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        DRIVER.lock(|slot| {
+            let mut slot = slot.borrow_mut();
+            if matches!(*slot, DriverSlot::Empty) {
+                // Safety: 只在首次安装时创建唯一的可变引用
+                let flash: &'static mut (dyn FlashDriver + Send) = unsafe { &mut *core::ptr::addr_of_mut!(FLASH) };
+                *slot = DriverSlot::Idle(flash);
+            }
+        });
+        guard
+    }
+
+    impl RamFlash {
+        fn index(&self, address: u32, len: usize) -> Result<usize, NorFlashErrorKind> {
+            if !address.is_multiple_of(4) || !len.is_multiple_of(4) {
+                return Err(NorFlashErrorKind::NotAligned);
+            }
+            let start = address.checked_sub(BASE).ok_or(NorFlashErrorKind::OutOfBounds)? as usize;
+            if start + len > self.data.len() {
+                return Err(NorFlashErrorKind::OutOfBounds);
+            }
+            Ok(start)
+        }
+    }
+
+    impl ErrorType for RamFlash {
+        type Error = NorFlashErrorKind;
+    }
+
+    impl ReadNorFlash for RamFlash {
+        const READ_SIZE: usize = 4;
+
+        fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+            let start = self.index(offset, bytes.len())?;
+            bytes.copy_from_slice(&self.data[start..start + bytes.len()]);
+            Ok(())
+        }
+
+        fn capacity(&self) -> usize {
+            self.data.len()
+        }
+    }
+
+    impl NorFlash for RamFlash {
+        const WRITE_SIZE: usize = 4;
+        const ERASE_SIZE: usize = 4096;
+
+        fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+            let start = self.index(from, (to - from) as usize)?;
+            self.data[start..start + (to - from) as usize].fill(0xFF);
+            Ok(())
+        }
+
+        fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+            let start = self.index(offset, bytes.len())?;
+            for (cell, byte) in self.data[start..].iter_mut().zip(bytes) {
+                *cell &= byte;
+            }
+            Ok(())
+        }
     }
 }
 
@@ -528,6 +1166,8 @@ mod tests {
             page_size: 256,
             partition_offset: 0x100000,
             partition_size: 0x200000,
+            write_protected: false,
+            encrypted: false,
         });
 
         // 块 0 -> 分区起始
@@ -535,4 +1175,101 @@ mod tests {
         // 块 1 -> 分区起始 + 块大小
         assert_eq!(storage.block_to_address(1).unwrap(), 0x101000);
     }
+
+    #[test]
+    fn test_readonly_partition() {
+        use crate::fs::partition::{Partition, PartitionFlags, PartitionType};
+
+        let partition = Partition {
+            label: heapless::String::try_from("nvs").unwrap(),
+            partition_type: PartitionType::Data,
+            subtype: 0x02,
+            offset: 0x9000,
+            size: 0x6000,
+            flags: PartitionFlags::from(PartitionFlags::READONLY),
+        };
+
+        let mut storage = FlashStorage::from_partition(&partition, 16 * 1024 * 1024);
+        storage.init().unwrap();
+        assert!(storage.is_write_protected());
+        assert_eq!(storage.erase_block(0), Err(StorageError::WriteProtected));
+        assert_eq!(storage.program(0, 0, &[0]), Err(StorageError::WriteProtected));
+    }
+
+    #[test]
+    fn test_encrypted_adapter() {
+        use littlefs_adapter::LfsStorageAdapter;
+
+        let config = FlashConfig { encrypted: true, ..FlashConfig::default() };
+        let mut storage = FlashStorage::new(config);
+        storage.init().unwrap();
+
+        let plain: LfsStorageAdapter = LfsStorageAdapter::with_encryption(FlashStorage::new(config), false);
+        assert_eq!(plain.io_unit(), 1);
+
+        let mut adapter: LfsStorageAdapter = LfsStorageAdapter::with_encryption(storage, true);
+        assert!(adapter.is_encrypted());
+        assert_eq!(adapter.io_unit(), ENCRYPTED_BLOCK);
+        assert_eq!(adapter.prog(0, 16, &[0; 32]), Err(StorageError::AlignmentError));
+        assert_eq!(adapter.prog(0, 0, &[0; 20]), Err(StorageError::AlignmentError));
+    }
+
+    #[test]
+    fn test_write_remount_read() {
+        use littlefs_adapter::LfsStorageAdapter;
+
+        let _flash = ram_flash::install();
+        assert!(has_driver());
+        // 驱动在操作期间被取出，嵌套访问返回 Busy 而不是在临界区内等待
+        assert_eq!(with_driver(|_| with_driver(|_| Ok(()))), Err(StorageError::Busy));
+
+        let config = FlashConfig { partition_offset: ram_flash::BASE, partition_size: 0x2000, ..FlashConfig::default() };
+        let mount = || {
+            let mut storage = FlashStorage::new(config);
+            storage.init().unwrap();
+            LfsStorageAdapter::<littlefs_adapter::DefaultProfile>::with_encryption(storage, false)
+        };
+
+        {
+            let mut adapter = mount();
+            adapter.erase(1).unwrap();
+            // 非对齐的偏移与长度由驱动层补齐
+            adapter.prog(1, 3, b"persist").unwrap();
+            adapter.prog(1, 10, b"!").unwrap();
+            assert_eq!(adapter.prog(2, 0, b"x"), Err(StorageError::OutOfBounds));
+        }
+
+        // 重新挂载后读回
+        let mut adapter = mount();
+        let mut buffer = [0u8; 12];
+        adapter.read(1, 0, &mut buffer).unwrap();
+        assert_eq!(&buffer, b"\xFF\xFF\xFFpersist!\xFF");
+
+        adapter.erase(1).unwrap();
+        adapter.read(1, 0, &mut buffer).unwrap();
+        assert_eq!(buffer, [0xFF; 12]);
+    }
+
+    #[test]
+    fn test_mmu_lookup() {
+        // 虚拟页 2、3 映射 Flash 物理页 0x41、0x42，虚拟页 5 映射 PSRAM 页 0x43
+        let entry = |index: usize| match index {
+            2 => 0x41,
+            3 => 0x42,
+            5 => 0x43 | mmu::ACCESS_SPIRAM,
+            _ => mmu::INVALID,
+        };
+
+        assert_eq!(mmu::find(entry, 0x41_1234, 16), Some(mmu::DBUS_BASE + 0x2_1234));
+        // 跨页且连续
+        assert_eq!(mmu::find(entry, 0x41_FFF0, 0x20), Some(mmu::DBUS_BASE + 0x2_FFF0));
+        // 跨到未映射页
+        assert_eq!(mmu::find(entry, 0x42_FFF0, 0x20), None);
+        // PSRAM 条目不算
+        assert_eq!(mmu::find(entry, 0x43_0000, 4), None);
+
+        assert_eq!(mmu::physical(entry, mmu::DBUS_BASE + 0x3_0010), Some(0x42_0010));
+        assert_eq!(mmu::physical(entry, mmu::DBUS_BASE + 0x5_0000), None);
+        assert_eq!(mmu::physical(entry, 0x4000_0000), None);
+    }
 }
@@ -0,0 +1,411 @@
+//! 虚拟文件系统 (多分区挂载表)
+//!
+//! 多个 LittleFS 分区同时挂载到不同挂载点，例如只读为主的资源分区挂在 `/assets`
+//! (大缓存、关闭磨损均衡搬移)，频繁写入的日志分区挂在 `/log` (小缓存、较短块周期)。
+//! 路径按最长前缀匹配分派给对应的文件系统，传入前去掉挂载点前缀。
+//!
+//! 各分区的 `FileSystem<P>` 挂载参数类型不同，挂载表经对象安全的 `MountedFs`
+//! 特征只提供按路径的操作；需要 `File` 句柄或连续存储等功能时，直接使用具体的文件系统。
+//! 只读挂载点上的写操作返回 `Storage(WriteProtected)`，跨挂载点重命名返回 `InvalidParam`。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::fs::{FileSystem, FlashStorage, FsConfig, Vfs};
+//! use rustrtos::lfs_profile;
+//!
+//! lfs_profile!(AssetsProfile, blocks = 256, cache = U1024, lookahead = U4, cycles = -1);
+//! lfs_profile!(LogProfile, blocks = 512, cache = U256, lookahead = U8, cycles = 100);
+//!
+//! static ASSETS: StaticCell<FileSystem<AssetsProfile>> = StaticCell::new();
+//! static LOGS: StaticCell<FileSystem<LogProfile>> = StaticCell::new();
+//!
+//! let assets = FlashStorage::from_partition(&assets_part, flash_size);
+//! let assets = ASSETS.init(FileSystem::with_profile(assets, FsConfig::default()));
+//! let logs = FlashStorage::from_partition(&log_part, flash_size);
+//! let logs = LOGS.init(FileSystem::with_profile(logs, FsConfig::default()));
+//! assets.mount()?;
+//! logs.mount()?;
+//!
+//! let mut vfs: Vfs<'_> = Vfs::new();
+//! vfs.register_read_only("/assets", assets)?;
+//! vfs.register("/log", logs)?;
+//!
+//! vfs.write("/log/boot.txt", b"booted\n", true)?;
+//! let n = vfs.read("/assets/index.html", 0, &mut buf)?;
+//! ```
+
+use super::littlefs::{FileSystem, FsConfig, FsError, Metadata, OpenOptions, SeekFrom};
+use super::storage::littlefs_adapter::LfsProfile;
+use super::storage::StorageError;
+
+/// 挂载点数量上限
+pub const MAX_MOUNTS: usize = 4;
+
+/// 挂载点前缀长度上限
+pub const MAX_PREFIX_LEN: usize = 16;
+
+// ===== 挂载的文件系统 =====
+
+/// 可注册到挂载表的文件系统 (路径均相对于挂载点)
+pub trait MountedFs {
+    /// 是否已挂载
+    fn is_mounted(&self) -> bool;
+
+    /// 文件系统配置
+    fn config(&self) -> &FsConfig;
+
+    /// 可用块数
+    fn free_blocks(&self) -> Result<u32, FsError>;
+
+    /// 从 `offset` 处读取文件
+    fn read_at(&self, path: &str, offset: u32, buffer: &mut [u8]) -> Result<usize, FsError>;
+
+    /// 写入文件 (`append` 为 false 时覆盖原内容)
+    fn write_file(&self, path: &str, data: &[u8], append: bool) -> Result<(), FsError>;
+
+    /// 获取元数据
+    fn metadata(&self, path: &str) -> Result<Metadata, FsError>;
+
+    /// 删除文件或空目录
+    fn remove(&self, path: &str) -> Result<(), FsError>;
+
+    /// 重命名
+    fn rename(&self, old_path: &str, new_path: &str) -> Result<(), FsError>;
+
+    /// 递归创建目录
+    fn create_dir_all(&self, path: &str) -> Result<(), FsError>;
+
+    /// 目录中第 `index` 个条目
+    fn dir_entry(&self, path: &str, index: u32) -> Result<Option<Metadata>, FsError>;
+}
+
+impl<P: LfsProfile> MountedFs for FileSystem<P> {
+    fn is_mounted(&self) -> bool {
+        FileSystem::is_mounted(self)
+    }
+
+    fn config(&self) -> &FsConfig {
+        FileSystem::config(self)
+    }
+
+    fn free_blocks(&self) -> Result<u32, FsError> {
+        FileSystem::free_blocks(self)
+    }
+
+    fn read_at(&self, path: &str, offset: u32, buffer: &mut [u8]) -> Result<usize, FsError> {
+        let mut file = self.open(path, OpenOptions::read_only())?;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut done = 0;
+        while done < buffer.len() {
+            match file.read(&mut buffer[done..])? {
+                0 => break,
+                n => done += n,
+            }
+        }
+        Ok(done)
+    }
+
+    fn write_file(&self, path: &str, data: &[u8], append: bool) -> Result<(), FsError> {
+        let options = if append { OpenOptions::append_mode() } else { OpenOptions::write_only() };
+        let mut file = self.open(path, options)?;
+        file.write_all(data)?;
+        file.close()
+    }
+
+    fn metadata(&self, path: &str) -> Result<Metadata, FsError> {
+        FileSystem::metadata(self, path)
+    }
+
+    fn remove(&self, path: &str) -> Result<(), FsError> {
+        FileSystem::remove(self, path)
+    }
+
+    fn rename(&self, old_path: &str, new_path: &str) -> Result<(), FsError> {
+        FileSystem::rename(self, old_path, new_path)
+    }
+
+    fn create_dir_all(&self, path: &str) -> Result<(), FsError> {
+        FileSystem::create_dir_all(self, path)
+    }
+
+    fn dir_entry(&self, path: &str, index: u32) -> Result<Option<Metadata>, FsError> {
+        let mut dir = self.read_dir(path)?;
+        for _ in 0..index {
+            if dir.next()?.is_none() {
+                return Ok(None);
+            }
+        }
+        dir.next()
+    }
+}
+
+// ===== 挂载表 =====
+
+/// 挂载点
+pub struct MountPoint<'a> {
+    prefix: heapless::String<MAX_PREFIX_LEN>,
+    fs: &'a dyn MountedFs,
+    read_only: bool,
+}
+
+impl<'a> MountPoint<'a> {
+    /// 挂载点前缀
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// 文件系统
+    pub fn fs(&self) -> &'a dyn MountedFs {
+        self.fs
+    }
+
+    /// 是否只读
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+}
+
+/// 挂载表
+pub struct Vfs<'a, const N: usize = MAX_MOUNTS> {
+    mounts: heapless::Vec<MountPoint<'a>, N>,
+}
+
+impl<'a, const N: usize> Vfs<'a, N> {
+    /// 创建空挂载表
+    pub const fn new() -> Self {
+        Self { mounts: heapless::Vec::new() }
+    }
+
+    /// 注册可读写挂载点
+    ///
+    /// 前缀须以 `/` 开头且不以 `/` 结尾 (根目录 `/` 除外)；文件系统须已挂载
+    pub fn register(&mut self, prefix: &str, fs: &'a dyn MountedFs) -> Result<(), FsError> {
+        self.insert(prefix, fs, false)
+    }
+
+    /// 注册只读挂载点
+    pub fn register_read_only(&mut self, prefix: &str, fs: &'a dyn MountedFs) -> Result<(), FsError> {
+        self.insert(prefix, fs, true)
+    }
+
+    fn insert(&mut self, prefix: &str, fs: &'a dyn MountedFs, read_only: bool) -> Result<(), FsError> {
+        if !valid_prefix(prefix) {
+            return Err(FsError::InvalidParam);
+        }
+        if !fs.is_mounted() {
+            return Err(FsError::NotMounted);
+        }
+        if self.mounts.iter().any(|m| m.prefix == prefix) {
+            return Err(FsError::AlreadyExists);
+        }
+        let prefix = heapless::String::try_from(prefix).map_err(|_| FsError::PathTooLong)?;
+        self.mounts
+            .push(MountPoint { prefix, fs, read_only })
+            .map_err(|_| FsError::Full)
+    }
+
+    /// 注销挂载点
+    pub fn unregister(&mut self, prefix: &str) -> Result<(), FsError> {
+        let index = self.mounts.iter().position(|m| m.prefix == prefix).ok_or(FsError::NotFound)?;
+        self.mounts.swap_remove(index);
+        Ok(())
+    }
+
+    /// 已注册的挂载点
+    pub fn mounts(&self) -> impl Iterator<Item = &MountPoint<'a>> {
+        self.mounts.iter()
+    }
+
+    /// 解析路径: 返回挂载点与相对路径
+    pub fn resolve<'p>(&self, path: &'p str) -> Result<(&MountPoint<'a>, &'p str), FsError> {
+        self.mounts
+            .iter()
+            .filter_map(|m| strip_mount(&m.prefix, path).map(|rest| (m, rest)))
+            .max_by_key(|(m, _)| m.prefix.len())
+            .ok_or(FsError::NotFound)
+    }
+
+    fn resolve_writable<'p>(&self, path: &'p str) -> Result<(&MountPoint<'a>, &'p str), FsError> {
+        let (mount, rest) = self.resolve(path)?;
+        if mount.read_only {
+            return Err(FsError::Storage(StorageError::WriteProtected));
+        }
+        Ok((mount, rest))
+    }
+
+    /// 读取文件
+    pub fn read(&self, path: &str, offset: u32, buffer: &mut [u8]) -> Result<usize, FsError> {
+        let (mount, rest) = self.resolve(path)?;
+        mount.fs.read_at(rest, offset, buffer)
+    }
+
+    /// 写入文件 (`append` 为 false 时覆盖)
+    pub fn write(&self, path: &str, data: &[u8], append: bool) -> Result<(), FsError> {
+        let (mount, rest) = self.resolve_writable(path)?;
+        mount.fs.write_file(rest, data, append)
+    }
+
+    /// 获取元数据
+    pub fn metadata(&self, path: &str) -> Result<Metadata, FsError> {
+        let (mount, rest) = self.resolve(path)?;
+        mount.fs.metadata(rest)
+    }
+
+    /// 检查文件是否存在
+    pub fn exists(&self, path: &str) -> Result<bool, FsError> {
+        match self.metadata(path) {
+            Ok(_) => Ok(true),
+            Err(FsError::NotFound) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 删除文件或空目录
+    pub fn remove(&self, path: &str) -> Result<(), FsError> {
+        let (mount, rest) = self.resolve_writable(path)?;
+        mount.fs.remove(rest)
+    }
+
+    /// 重命名 (只能在同一挂载点内)
+    pub fn rename(&self, old_path: &str, new_path: &str) -> Result<(), FsError> {
+        let (from_mount, from) = self.resolve_writable(old_path)?;
+        let (to_mount, to) = self.resolve_writable(new_path)?;
+        if from_mount.prefix != to_mount.prefix {
+            return Err(FsError::InvalidParam);
+        }
+        from_mount.fs.rename(from, to)
+    }
+
+    /// 递归创建目录
+    pub fn create_dir_all(&self, path: &str) -> Result<(), FsError> {
+        let (mount, rest) = self.resolve_writable(path)?;
+        mount.fs.create_dir_all(rest)
+    }
+
+    /// 目录中第 `index` 个条目
+    pub fn dir_entry(&self, path: &str, index: u32) -> Result<Option<Metadata>, FsError> {
+        let (mount, rest) = self.resolve(path)?;
+        mount.fs.dir_entry(rest, index)
+    }
+}
+
+impl<const N: usize> Default for Vfs<'_, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn valid_prefix(prefix: &str) -> bool {
+    prefix.starts_with('/')
+        && (prefix == "/" || !prefix.ends_with('/'))
+        && !prefix.contains("//")
+        && prefix.len() <= MAX_PREFIX_LEN
+}
+
+/// 路径属于挂载点时返回相对路径 (挂载点自身对应 `/`)
+fn strip_mount<'p>(prefix: &str, path: &'p str) -> Option<&'p str> {
+    if prefix == "/" {
+        return path.starts_with('/').then_some(path);
+    }
+    match path.strip_prefix(prefix)? {
+        "" => Some("/"),
+        rest if rest.starts_with('/') => Some(rest),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::storage::{FlashConfig, FlashStorage};
+
+    crate::lfs_profile!(SmallProfile, blocks = 64, cache = U256, lookahead = U1, cycles = 100);
+
+    #[test]
+    fn test_strip_mount() {
+        assert_eq!(strip_mount("/assets", "/assets/index.html"), Some("/index.html"));
+        assert_eq!(strip_mount("/assets", "/assets"), Some("/"));
+        assert_eq!(strip_mount("/assets", "/assetsx/a"), None);
+        assert_eq!(strip_mount("/", "/log/a.txt"), Some("/log/a.txt"));
+        assert!(valid_prefix("/log") && valid_prefix("/"));
+        assert!(!valid_prefix("log") && !valid_prefix("/log/") && !valid_prefix("/a//b"));
+    }
+
+    static CONFIG: FsConfig = FsConfig {
+        block_size: 4096,
+        block_count: 16,
+        read_size: 256,
+        prog_size: 256,
+        cache_size: 512,
+        lookahead_size: 16,
+        block_cycles: 500,
+        extent_blocks: 0,
+    };
+
+    /// 只记录路径的假文件系统
+    struct Echo;
+
+    impl MountedFs for Echo {
+        fn is_mounted(&self) -> bool {
+            true
+        }
+        fn config(&self) -> &FsConfig {
+            &CONFIG
+        }
+        fn free_blocks(&self) -> Result<u32, FsError> {
+            Ok(0)
+        }
+        fn read_at(&self, _: &str, _: u32, _: &mut [u8]) -> Result<usize, FsError> {
+            Ok(0)
+        }
+        fn write_file(&self, _: &str, _: &[u8], _: bool) -> Result<(), FsError> {
+            Ok(())
+        }
+        fn metadata(&self, path: &str) -> Result<Metadata, FsError> {
+            let name = heapless::String::try_from(path).map_err(|_| FsError::NameTooLong)?;
+            Ok(Metadata { file_type: crate::fs::FileType::File, size: 0, name })
+        }
+        fn remove(&self, _: &str) -> Result<(), FsError> {
+            Ok(())
+        }
+        fn rename(&self, _: &str, _: &str) -> Result<(), FsError> {
+            Ok(())
+        }
+        fn create_dir_all(&self, _: &str) -> Result<(), FsError> {
+            Ok(())
+        }
+        fn dir_entry(&self, _: &str, _: u32) -> Result<Option<Metadata>, FsError> {
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn test_register_and_resolve() {
+        let config = FlashConfig { partition_size: 64 * 4096, ..FlashConfig::default() };
+        let small: FileSystem<SmallProfile> = FileSystem::with_profile(FlashStorage::new(config), FsConfig::default());
+        assert_eq!((small.config().cache_size, small.config().lookahead_size), (256, 8));
+        assert_eq!(small.config().block_cycles, 100);
+
+        let (root, assets, log) = (Echo, Echo, Echo);
+        let mut vfs: Vfs<'_, 3> = Vfs::new();
+        // 未挂载的文件系统不能注册
+        assert_eq!(vfs.register("/log", &small).unwrap_err(), FsError::NotMounted);
+        vfs.register("/", &root).unwrap();
+        vfs.register_read_only("/assets", &assets).unwrap();
+        vfs.register("/log", &log).unwrap();
+        assert_eq!(vfs.register("/log", &log).unwrap_err(), FsError::AlreadyExists);
+        assert_eq!(vfs.register("/tmp", &log).unwrap_err(), FsError::Full);
+
+        // 最长前缀匹配，前缀去掉后交给文件系统
+        assert_eq!(vfs.metadata("/assets/app.js").unwrap().name.as_str(), "/app.js");
+        assert_eq!(vfs.resolve("/log").unwrap().0.prefix(), "/log");
+        assert_eq!(vfs.metadata("/logs/a").unwrap().name.as_str(), "/logs/a");
+
+        assert_eq!(vfs.write("/assets/x", b"1", false), Err(FsError::Storage(StorageError::WriteProtected)));
+        assert_eq!(vfs.rename("/log/a", "/b"), Err(FsError::InvalidParam));
+        assert!(vfs.rename("/log/a", "/log/b").is_ok());
+        vfs.unregister("/").unwrap();
+        assert_eq!(vfs.resolve("/data").err(), Some(FsError::NotFound));
+    }
+}
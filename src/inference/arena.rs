@@ -0,0 +1,110 @@
+//! 张量内存区
+//!
+//! 推理运行时的全部中间张量从一块对齐的 PSRAM 区域中按 bump 方式分配，
+//! `reset` 后整体复用。记录高水位，便于按实际需求缩小区域。
+
+use core::mem::MaybeUninit;
+
+use super::InferenceError;
+use crate::mem::psram::{PsramBox, PsramConfig};
+
+/// 默认张量对齐 (TFLM 要求 16 字节，这里取 cache line)
+pub const DEFAULT_TENSOR_ALIGN: usize = 32;
+
+/// 张量内存区
+pub struct TensorArena {
+    base: *mut u8,
+    size: usize,
+    offset: usize,
+    high_water: usize,
+}
+
+// 内存区独占所有权，可跨核转移
+unsafe impl Send for TensorArena {}
+
+impl TensorArena {
+    /// 在 PSRAM 中分配 `SIZE` 字节、按 `align` 对齐的内存区
+    pub fn in_psram<const SIZE: usize>(align: usize) -> Result<Self, InferenceError> {
+        if !align.is_power_of_two() {
+            return Err(InferenceError::Alignment);
+        }
+        let config = PsramConfig::default().with_alignment(align);
+        let mut boxed = PsramBox::<[MaybeUninit<u8>; SIZE]>::new_uninit_with_config(config)
+            .map_err(|_| InferenceError::ArenaTooSmall)?;
+        // PsramBox 由 bump 分配器分配，永不释放
+        let base = boxed.as_mut_ptr() as *mut u8;
+        Ok(Self { base, size: SIZE, offset: 0, high_water: 0 })
+    }
+
+    /// 使用外部提供的静态缓冲区 (例如 DRAM 中的小模型)
+    pub fn from_static(buffer: &'static mut [u8]) -> Self {
+        Self { base: buffer.as_mut_ptr(), size: buffer.len(), offset: 0, high_water: 0 }
+    }
+
+    /// 分配张量内存
+    pub fn alloc(&mut self, size: usize, align: usize) -> Result<&mut [u8], InferenceError> {
+        let (start, end) = bump(self.base as usize, self.offset, size, align)?;
+        if end > self.size {
+            return Err(InferenceError::ArenaTooSmall);
+        }
+        self.offset = end;
+        self.high_water = self.high_water.max(end);
+        Ok(unsafe { core::slice::from_raw_parts_mut(self.base.add(start), size) })
+    }
+
+    /// 将剩余空间整体交给后端 (如 TFLM 解释器自行管理)
+    pub fn remaining(&mut self) -> &mut [u8] {
+        let rest = self.size - self.offset;
+        unsafe { core::slice::from_raw_parts_mut(self.base.add(self.offset), rest) }
+    }
+
+    /// 记录后端实际使用量 (用于高水位统计)
+    pub fn record_external_use(&mut self, used: usize) {
+        self.high_water = self.high_water.max(self.offset + used);
+    }
+
+    /// 释放所有分配
+    pub fn reset(&mut self) {
+        self.offset = 0;
+    }
+
+    /// 总大小
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// 当前已用
+    pub fn used(&self) -> usize {
+        self.offset
+    }
+
+    /// 历史最大用量
+    pub fn high_water(&self) -> usize {
+        self.high_water
+    }
+}
+
+/// 计算对齐后的分配区间 (相对基址的偏移)
+fn bump(base: usize, offset: usize, size: usize, align: usize) -> Result<(usize, usize), InferenceError> {
+    if !align.is_power_of_two() {
+        return Err(InferenceError::Alignment);
+    }
+    let addr = base + offset;
+    let aligned = (addr + align - 1) & !(align - 1);
+    let start = aligned - base;
+    let end = start.checked_add(size).ok_or(InferenceError::ArenaTooSmall)?;
+    Ok((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bump_alignment() {
+        assert_eq!(bump(0x1000, 0, 10, 16), Ok((0, 10)));
+        assert_eq!(bump(0x1000, 10, 4, 16), Ok((16, 20)));
+        assert_eq!(bump(0x1004, 0, 4, 16), Ok((12, 16)));
+        assert_eq!(bump(0x1000, 0, 4, 3), Err(InferenceError::Alignment));
+    }
+}
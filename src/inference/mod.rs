@@ -0,0 +1,268 @@
+//! 推理运行时集成
+//!
+//! 为 TFLite-Micro 风格的推理引擎提供内存与调度管线，引擎本身通过
+//! `InferenceBackend` trait 接入 (例如 tflite-micro 的 C 绑定)。
+//!
+//! # 特性
+//! - 模型存储: 映射文件 / PSRAM 加载 / 分页缓存 (`model`)
+//! - 张量内存区: PSRAM 对齐分配、高水位统计 (`arena`)
+//! - 在 Core1 工作队列上执行推理，调用方异步等待
+//! - 推理耗时统计
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::inference::{InferenceJob, Model, TensorArena};
+//! use rustrtos::tasks::workqueue::WorkQueue;
+//!
+//! static CORE1_WQ: WorkQueue<4> = WorkQueue::new();
+//! static KWS: StaticCell<InferenceJob<TflmBackend>> = StaticCell::new();
+//!
+//! let model = Model::load::<{ 512 * 1024 }>(&mut fs.open("/models/kws.tflite", OpenOptions::read_only())?)?;
+//! let arena = TensorArena::in_psram::<{ 128 * 1024 }>(16)?;
+//! let job = KWS.init(InferenceJob::new(Runtime::new(TflmBackend::new(), model, arena)?));
+//!
+//! job.with_runtime(|rt| rt.backend_mut().input(0).map(|t| t.copy_from_slice(&features))).await?;
+//! let timing = job.run_on(&CORE1_WQ).await?;
+//! log_info!("inference {} us", timing.invoke_us);
+//! ```
+
+pub mod arena;
+pub mod model;
+
+use core::fmt;
+
+use embassy_time::Instant;
+
+use crate::sync::primitives::{CriticalMutex, CriticalSignal};
+use crate::tasks::workqueue::{WorkItem, WorkQueue};
+
+pub use arena::TensorArena;
+pub use model::{Model, ModelFormat, PagedModel};
+
+// ===== 错误类型 =====
+
+/// 推理错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InferenceError {
+    /// 模型格式无效
+    InvalidModel,
+    /// 模型无法读取或映射
+    ModelUnavailable,
+    /// 模型超出预留空间
+    ModelTooLarge,
+    /// 张量内存区不足
+    ArenaTooSmall,
+    /// 对齐参数无效
+    Alignment,
+    /// 后端准备失败
+    Prepare,
+    /// 后端执行失败
+    Invoke,
+    /// 张量索引越界
+    TensorIndex,
+    /// 运行时正被其他上下文访问
+    Busy,
+}
+
+impl fmt::Display for InferenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidModel => write!(f, "Invalid model"),
+            Self::ModelUnavailable => write!(f, "Model unavailable"),
+            Self::ModelTooLarge => write!(f, "Model too large"),
+            Self::ArenaTooSmall => write!(f, "Tensor arena too small"),
+            Self::Alignment => write!(f, "Invalid alignment"),
+            Self::Prepare => write!(f, "Backend prepare failed"),
+            Self::Invoke => write!(f, "Backend invoke failed"),
+            Self::TensorIndex => write!(f, "Tensor index out of range"),
+            Self::Busy => write!(f, "Runtime busy"),
+        }
+    }
+}
+
+// ===== 后端 =====
+
+/// 推理引擎后端
+pub trait InferenceBackend: Send {
+    /// 加载模型并在内存区中分配张量
+    fn prepare(&mut self, model: &Model, arena: &mut TensorArena) -> Result<(), InferenceError>;
+
+    /// 输入张量
+    fn input(&mut self, index: usize) -> Result<&mut [u8], InferenceError>;
+
+    /// 输出张量
+    fn output(&self, index: usize) -> Result<&[u8], InferenceError>;
+
+    /// 执行一次推理
+    fn invoke(&mut self) -> Result<(), InferenceError>;
+}
+
+// ===== 统计 =====
+
+/// 单次推理计时
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InferenceTiming {
+    /// 推理执行时间 (μs)
+    pub invoke_us: u32,
+    /// 从提交到完成的总时间 (μs，含排队)
+    pub total_us: u32,
+}
+
+/// 推理统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InferenceStats {
+    /// 推理次数
+    pub runs: u32,
+    /// 失败次数
+    pub failures: u32,
+    /// 最近一次耗时 (μs)
+    pub last_us: u32,
+    /// 最大耗时 (μs)
+    pub max_us: u32,
+    /// 最小耗时 (μs)
+    pub min_us: u32,
+    /// 累计耗时 (μs)
+    pub total_us: u64,
+    /// 张量内存区高水位 (字节)
+    pub arena_high_water: usize,
+}
+
+impl InferenceStats {
+    /// 平均耗时 (μs)
+    pub fn avg_us(&self) -> u32 {
+        if self.runs == 0 { 0 } else { (self.total_us / self.runs as u64) as u32 }
+    }
+
+    fn record(&mut self, us: u32) {
+        self.min_us = if self.runs == 0 { us } else { self.min_us.min(us) };
+        self.runs += 1;
+        self.last_us = us;
+        self.max_us = self.max_us.max(us);
+        self.total_us += us as u64;
+    }
+}
+
+// ===== 运行时 =====
+
+/// 推理运行时 (后端 + 模型 + 张量内存区)
+pub struct Runtime<B: InferenceBackend> {
+    backend: B,
+    model: Model,
+    arena: TensorArena,
+    stats: InferenceStats,
+}
+
+impl<B: InferenceBackend> Runtime<B> {
+    /// 创建并准备运行时
+    pub fn new(mut backend: B, model: Model, mut arena: TensorArena) -> Result<Self, InferenceError> {
+        backend.prepare(&model, &mut arena)?;
+        let stats = InferenceStats { arena_high_water: arena.high_water(), ..Default::default() };
+        Ok(Self { backend, model, arena, stats })
+    }
+
+    /// 执行一次推理并计时
+    pub fn invoke(&mut self) -> Result<u32, InferenceError> {
+        let start = Instant::now();
+        let result = self.backend.invoke();
+        let us = start.elapsed().as_micros() as u32;
+        match result {
+            Ok(()) => {
+                self.stats.record(us);
+                self.stats.arena_high_water = self.arena.high_water();
+                Ok(us)
+            }
+            Err(e) => {
+                self.stats.failures += 1;
+                Err(e)
+            }
+        }
+    }
+
+    /// 后端
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// 后端 (可变，用于写入输入张量)
+    pub fn backend_mut(&mut self) -> &mut B {
+        &mut self.backend
+    }
+
+    /// 模型
+    pub fn model(&self) -> &Model {
+        &self.model
+    }
+
+    /// 统计
+    pub fn stats(&self) -> InferenceStats {
+        self.stats
+    }
+}
+
+// ===== 工作队列任务 =====
+
+/// 在工作队列上执行的推理任务
+///
+/// 输入输出在提交前后通过 `with_runtime` 访问；同一时刻只允许一个推理请求。
+pub struct InferenceJob<B: InferenceBackend> {
+    runtime: CriticalMutex<Runtime<B>>,
+    done: CriticalSignal<Result<u32, InferenceError>>,
+}
+
+impl<B: InferenceBackend> InferenceJob<B> {
+    /// 创建任务
+    pub fn new(runtime: Runtime<B>) -> Self {
+        Self {
+            runtime: CriticalMutex::new(runtime),
+            done: CriticalSignal::new(),
+        }
+    }
+
+    /// 访问运行时 (写输入、读输出、读统计)
+    ///
+    /// 推理进行中时等待其完成
+    pub async fn with_runtime<R>(&self, f: impl FnOnce(&mut Runtime<B>) -> R) -> R {
+        f(&mut *self.runtime.lock().await)
+    }
+
+    /// 提交到工作队列并等待完成
+    pub async fn run_on<const N: usize>(&'static self, queue: &WorkQueue<N>) -> Result<InferenceTiming, InferenceError> {
+        let start = Instant::now();
+        self.done.reset();
+        queue.submit(self).await;
+        let invoke_us = self.done.wait().await?;
+        Ok(InferenceTiming { invoke_us, total_us: start.elapsed().as_micros() as u32 })
+    }
+
+    /// 统计
+    pub async fn stats(&self) -> InferenceStats {
+        self.with_runtime(|rt| rt.stats()).await
+    }
+}
+
+impl<B: InferenceBackend> WorkItem for InferenceJob<B> {
+    fn execute(&self) {
+        // 提交方不应在推理期间持有运行时；若被占用则报告 Busy 而不是阻塞工作队列
+        let result = match self.runtime.try_lock() {
+            Ok(mut rt) => rt.invoke(),
+            Err(_) => Err(InferenceError::Busy),
+        };
+        self.done.signal(result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_record() {
+        let mut stats = InferenceStats::default();
+        stats.record(300);
+        stats.record(100);
+        stats.record(200);
+        assert_eq!((stats.min_us, stats.max_us, stats.last_us), (100, 300, 200));
+        assert_eq!(stats.avg_us(), 200);
+    }
+}
@@ -0,0 +1,177 @@
+//! 模型存储
+//!
+//! 模型按以下优先级获取，尽量避免复制:
+//! 1. 连续存储并固定的文件: `File::map` 零拷贝映射
+//! 2. 普通文件: 一次性读入 PSRAM
+//! 3. 分页访问: 通过 `FlashCache` 按需换入 (适合支持流式读取权重的自定义算子)
+
+use super::InferenceError;
+use crate::fs::File;
+use crate::mem::flashcache::{FlashCache, FlashCacheError, PageSource};
+use crate::mem::psram::{PsramBox, PsramConfig};
+
+/// TFLite FlatBuffer 文件标识
+pub const TFLITE_IDENTIFIER: [u8; 4] = *b"TFL3";
+
+/// 模型格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelFormat {
+    /// TFLite FlatBuffer
+    TfLite,
+    /// 未识别格式 (由后端自行解析)
+    Raw,
+}
+
+/// 模型来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelOrigin {
+    /// 内存映射 Flash
+    Mapped,
+    /// 已复制到 PSRAM
+    Psram,
+    /// 静态数据 (编译进固件)
+    Static,
+}
+
+/// 连续存储的模型
+#[derive(Debug, Clone, Copy)]
+pub struct Model {
+    data: &'static [u8],
+    format: ModelFormat,
+    origin: ModelOrigin,
+}
+
+impl Model {
+    /// 使用编译进固件的模型
+    pub fn from_static(data: &'static [u8]) -> Result<Self, InferenceError> {
+        Self::with_origin(data, ModelOrigin::Static)
+    }
+
+    /// 从文件映射 (文件须已连续存储并固定)
+    pub fn from_mapped(file: &File<'static>) -> Result<Self, InferenceError> {
+        let data = file.map().map_err(|_| InferenceError::ModelUnavailable)?;
+        Self::with_origin(data, ModelOrigin::Mapped)
+    }
+
+    /// 从文件加载: 优先映射，否则读入 PSRAM (`MAX` 为最大模型大小)
+    pub fn load<const MAX: usize>(file: &mut File<'static>) -> Result<Self, InferenceError> {
+        if file.is_mappable() {
+            return Self::from_mapped(file);
+        }
+
+        let size = file.size() as usize;
+        if size > MAX {
+            return Err(InferenceError::ModelTooLarge);
+        }
+        let mut boxed = PsramBox::<[u8; MAX]>::new_uninit_with_config(PsramConfig::bulk_transfer())
+            .map_err(|_| InferenceError::ModelTooLarge)?;
+        // PsramBox 永不释放，可视为 'static
+        let buffer = unsafe { core::slice::from_raw_parts_mut(boxed.as_mut_ptr() as *mut u8, size) };
+
+        let mut read = 0;
+        while read < size {
+            let n = file.read(&mut buffer[read..]).map_err(|_| InferenceError::ModelUnavailable)?;
+            if n == 0 {
+                return Err(InferenceError::ModelUnavailable);
+            }
+            read += n;
+        }
+        Self::with_origin(buffer, ModelOrigin::Psram)
+    }
+
+    fn with_origin(data: &'static [u8], origin: ModelOrigin) -> Result<Self, InferenceError> {
+        Ok(Self { data, format: detect_format(data)?, origin })
+    }
+
+    /// 模型数据
+    pub fn data(&self) -> &'static [u8] {
+        self.data
+    }
+
+    /// 格式
+    pub fn format(&self) -> ModelFormat {
+        self.format
+    }
+
+    /// 来源
+    pub fn origin(&self) -> ModelOrigin {
+        self.origin
+    }
+
+    /// 大小 (字节)
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+/// 识别模型格式
+pub fn detect_format(data: &[u8]) -> Result<ModelFormat, InferenceError> {
+    if data.len() < 8 {
+        return Err(InferenceError::InvalidModel);
+    }
+    if data[4..8] == TFLITE_IDENTIFIER {
+        Ok(ModelFormat::TfLite)
+    } else {
+        Ok(ModelFormat::Raw)
+    }
+}
+
+/// 分页访问的模型 (权重按需从 Flash 换入 PSRAM 缓存)
+pub struct PagedModel<'c, 'f, S: PageSource, const PAGES: usize, const PAGE: usize> {
+    cache: &'c mut FlashCache<'f, PAGES, PAGE>,
+    source: S,
+}
+
+impl<'c, 'f, S: PageSource, const PAGES: usize, const PAGE: usize> PagedModel<'c, 'f, S, PAGES, PAGE> {
+    /// 创建并固定模型头页 (元数据访问频繁)
+    pub fn new(cache: &'c mut FlashCache<'f, PAGES, PAGE>, mut source: S) -> Result<Self, InferenceError> {
+        cache.clear();
+        cache.pin(&mut source, 0).map_err(map_cache_error)?;
+        Ok(Self { cache, source })
+    }
+
+    /// 读取模型区间
+    pub fn read(&mut self, offset: u32, buf: &mut [u8]) -> Result<(), InferenceError> {
+        self.cache.read(&mut self.source, offset, buf).map_err(map_cache_error)
+    }
+
+    /// 模型大小
+    pub fn len(&self) -> u32 {
+        self.source.len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.source.is_empty()
+    }
+
+    /// 缓存
+    pub fn cache(&self) -> &FlashCache<'f, PAGES, PAGE> {
+        self.cache
+    }
+}
+
+fn map_cache_error(e: FlashCacheError) -> InferenceError {
+    match e {
+        FlashCacheError::OutOfBounds => InferenceError::InvalidModel,
+        _ => InferenceError::ModelUnavailable,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_format() {
+        let tflite = [0x18, 0, 0, 0, b'T', b'F', b'L', b'3', 0, 0];
+        assert_eq!(detect_format(&tflite), Ok(ModelFormat::TfLite));
+        assert_eq!(detect_format(&[0u8; 16]), Ok(ModelFormat::Raw));
+        assert_eq!(detect_format(&[0u8; 4]), Err(InferenceError::InvalidModel));
+    }
+}
@@ -10,9 +10,19 @@
 //! - 零拷贝同步原语
 //! - 高性能环形缓冲区
 //! - 条件编译日志系统
+//! - 签名验证 (固件清单、配置负载)
+//! - 系统服务 (调试器检测、生产锁定)
+//! - 通信协议 (Modbus RTU/TCP、帧编解码)
+//! - 推理运行时集成 (模型存储、PSRAM 张量内存区、Core1 工作队列)
+//! - 外设驱动 (CAN/TWAI、SPI 从机、红外遥控、1-Wire)
+//! - 电源管理 (外设时钟/电源门控、保活句柄、跨深度睡眠的周期调度)
+//! - 音频管线 (I2S 采集、ADPCM 编码、网络串流、抖动缓冲)
+//! - 媒体处理 (摄像头帧 JPEG 编码、UI 帧缓冲合成)
+//! - 设备端浸泡测试 (长时间负载、内存水位泄漏检测)
 //! - WiFi 网络连接 (可选, 需启用 `wifi` feature)
 //! - BLE 低功耗蓝牙 (可选, 需启用 `ble` feature)
 //! - TCP/IP 网络栈 (可选, 需启用 `network` feature)
+//! - C 语言接口 (可选, 需启用 `ffi` feature)
 
 #![no_std]
 #![feature(asm_experimental_arch)]
@@ -22,11 +32,24 @@ pub mod sync;
 pub mod util;
 pub mod mem;
 pub mod fs;
+pub mod crypto;
+pub mod sys;
+pub mod protocols;
+pub mod drivers;
+pub mod power;
+pub mod inference;
+pub mod audio;
+pub mod media;
+pub mod test;
 
 // ===== 网络模块 (条件编译) =====
 #[cfg(any(feature = "wifi", feature = "ble", feature = "ble-esp"))]
 pub mod net;
 
+// ===== C 语言接口 (条件编译) =====
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
 // ===== 重导出常用类型 =====
 pub use sync::primitives::{
     CriticalMutex,
@@ -55,6 +78,9 @@ pub use fs::{
     FlashStorage, StorageError,
 };
 
+// 密码学重导出
+pub use crypto::{CryptoError, KeyRing, TrustedKey, SignatureAlgorithm, FirmwareManifest, SignedBlob};
+
 // ===== 网络模块重导出 (条件编译) =====
 #[cfg(feature = "wifi")]
 pub use net::wifi::{WifiController, WifiMode, WifiEvent, WifiError, WifiState, ScanResult};
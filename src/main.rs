@@ -28,7 +28,7 @@ use esp_rtos::embassy::InterruptExecutor;
 use static_cell::StaticCell;
 
 // ===== ESP App Descriptor =====
-esp_bootloader_esp_idf::esp_app_desc!();
+rustrtos::app_desc!();
 
 // ===== 条件编译日志 =====
 #[allow(unused_imports)]
@@ -153,7 +153,9 @@ async fn main(low_prio_spawner: Spawner) {
     // 8. 低优先级任务 (主执行器)
     // ========================================
     low_prio_spawner.must_spawn(tasks::normal::led_blink_task(led));
-    low_prio_spawner.must_spawn(tasks::normal::background_task());
+    if let Err(_e) = tasks::spawn::spawn(low_prio_spawner, &tasks::normal::BACKGROUND, tasks::normal::background_task) {
+        log_warn!("Background task not started: {}", _e);
+    }
     
     log_info!("All tasks spawned, entering main loop");
     
@@ -169,6 +171,8 @@ async fn main(low_prio_spawner: Spawner) {
         unsafe {
             SYSTEM_STATE.flags = tick_count as u32;
         }
+        // 空闲堆广播给 UI 观察者 (变化不足 1 KB 时不唤醒)
+        rustrtos::sys::status::STATUS.sample_heap();
         
         // 每 10 秒输出系统状态
         if tick_count % 10 == 0 {
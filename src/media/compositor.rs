@@ -0,0 +1,717 @@
+//! 帧缓冲合成器 (PSRAM 图层 + 局部刷新)
+//!
+//! 面向 SPI/并口 LCD 的简单 UI 合成:
+//! - 多个全屏 RGB565 图层 (背景、控件、叠加层) 放在 PSRAM，按添加顺序自下而上合成
+//! - 图层属性: 可见性、整体不透明度 (0-255)、透明色键
+//! - 脏区跟踪: 绘制时登记矩形，相交或相邻的矩形自动合并，超出容量时并入增长最小的一个
+//! - 刷新: 只合成脏区，逐条带写入 DRAM 反弹缓冲 (外设 DMA 不能直接访问 PSRAM)，
+//!   两个条带交替使用，DMA 发送一个条带的同时合成下一个
+//! - 帧统计: 合成耗时、整帧耗时 (平均/最大)、超出帧预算的次数，用于调到稳定帧率
+//!
+//! 显示驱动实现 `DisplaySink` (设置窗口 + 写像素)，`SpiPanel` 适配 MIPI-DCS 面板
+//! (ST7789、ILI9341 等)。像素以大端 RGB565 发送。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::media::compositor::{Compositor, LayerConfig, Rect, SpiPanel};
+//!
+//! static UI: StaticCell<Compositor<{ 320 * 240 }, 3, 4096>> = StaticCell::new();
+//! let ui = UI.init(Compositor::new(320, 240)?.with_target_fps(30));
+//! let background = ui.add_layer(LayerConfig::default())?;
+//! let widgets = ui.add_layer(LayerConfig::default().with_color_key(0x0000))?;
+//! let overlay = ui.add_layer(LayerConfig::default().with_opacity(160))?;
+//!
+//! ui.fill(background, ui.bounds(), 0x001F);
+//! let mut panel = SpiPanel::new(spi_device, dc_pin);
+//! let mut ticker = Ticker::every(Duration::from_hz(30));
+//! loop {
+//!     ui.fill(widgets, Rect::new(10, 10, 100, 20), progress_color);
+//!     ui.flush(&mut panel).await?;
+//!     ticker.next().await;
+//!     if ui.stats().frames % 300 == 0 {
+//!         log_info!("ui: {}", ui.stats());
+//!     }
+//! }
+//! ```
+
+use core::fmt;
+
+use embassy_futures::join::join;
+use embassy_time::Instant;
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::spi::SpiDevice;
+use heapless::Vec;
+
+use super::MediaError;
+use crate::mem::psram::{self, PsramBox, PsramConfig};
+
+/// 脏区矩形容量 (超出时合并)
+pub const MAX_DIRTY: usize = 8;
+
+/// 图层 PSRAM 分配标签
+pub const PSRAM_TAG: &str = "compositor";
+
+// ===== 矩形 =====
+
+/// 屏幕矩形 (右/下边界不含)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rect {
+    /// 左上角 X
+    pub x: u16,
+    /// 左上角 Y
+    pub y: u16,
+    /// 宽度
+    pub w: u16,
+    /// 高度
+    pub h: u16,
+}
+
+impl Rect {
+    /// 创建矩形
+    pub const fn new(x: u16, y: u16, w: u16, h: u16) -> Self {
+        Self { x, y, w, h }
+    }
+
+    /// 右边界 (不含)
+    pub const fn right(&self) -> u16 {
+        self.x.saturating_add(self.w)
+    }
+
+    /// 下边界 (不含)
+    pub const fn bottom(&self) -> u16 {
+        self.y.saturating_add(self.h)
+    }
+
+    /// 像素数
+    pub const fn area(&self) -> u32 {
+        self.w as u32 * self.h as u32
+    }
+
+    /// 是否为空
+    pub const fn is_empty(&self) -> bool {
+        self.w == 0 || self.h == 0
+    }
+
+    /// 交集 (不相交时为空矩形)
+    pub fn intersect(&self, other: &Rect) -> Rect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        if right <= x || bottom <= y {
+            return Rect::default();
+        }
+        Rect::new(x, y, right - x, bottom - y)
+    }
+
+    /// 包围两者的最小矩形
+    pub fn union(&self, other: &Rect) -> Rect {
+        if self.is_empty() {
+            return *other;
+        }
+        if other.is_empty() {
+            return *self;
+        }
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Rect::new(x, y, self.right().max(other.right()) - x, self.bottom().max(other.bottom()) - y)
+    }
+
+    /// 相交或共享一条边 (合并后不会引入额外面积之外的大块空白)
+    pub fn touches(&self, other: &Rect) -> bool {
+        let h_overlap = self.x < other.right() && other.x < self.right();
+        let v_overlap = self.y < other.bottom() && other.y < self.bottom();
+        let h_touch = self.x <= other.right() && other.x <= self.right();
+        let v_touch = self.y <= other.bottom() && other.y <= self.bottom();
+        (h_overlap && v_touch) || (v_overlap && h_touch)
+    }
+}
+
+// ===== 脏区 =====
+
+/// 脏区集合
+#[derive(Debug, Clone, Default)]
+pub struct DirtyRegions<const N: usize> {
+    rects: Vec<Rect, N>,
+}
+
+impl<const N: usize> DirtyRegions<N> {
+    /// 创建空集合
+    pub const fn new() -> Self {
+        Self { rects: Vec::new() }
+    }
+
+    /// 登记矩形: 吸收相交/相邻的矩形，已满时并入面积增长最小的一个
+    pub fn add(&mut self, rect: Rect) {
+        if rect.is_empty() {
+            return;
+        }
+        let mut rect = rect;
+        let mut i = 0;
+        while i < self.rects.len() {
+            if self.rects[i].touches(&rect) {
+                rect = rect.union(&self.rects.swap_remove(i));
+                i = 0;
+            } else {
+                i += 1;
+            }
+        }
+        if let Err(rect) = self.rects.push(rect) {
+            let best = self
+                .rects
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, r)| r.union(&rect).area() - r.area())
+                .map(|(i, _)| i);
+            if let Some(best) = best {
+                let merged = self.rects.swap_remove(best).union(&rect);
+                self.add(merged);
+            }
+        }
+    }
+
+    /// 取出全部脏区
+    pub fn take(&mut self) -> Vec<Rect, N> {
+        core::mem::take(&mut self.rects)
+    }
+
+    /// 当前脏区
+    pub fn rects(&self) -> &[Rect] {
+        &self.rects
+    }
+
+    /// 是否没有脏区
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    /// 脏区像素总数
+    pub fn area(&self) -> u32 {
+        self.rects.iter().map(Rect::area).sum()
+    }
+}
+
+// ===== 混合 =====
+
+/// RGB565 alpha 混合 (alpha 255 = 完全前景)
+#[inline]
+pub fn blend(fg: u16, bg: u16, alpha: u8) -> u16 {
+    // 展开为 0b00000GGGGGG00000RRRRR000000BBBBB，三个通道一次乘完
+    const MASK: u32 = 0x07E0_F81F;
+    let a = (alpha as u32 + 4) >> 3;
+    let fg = (fg as u32 | (fg as u32) << 16) & MASK;
+    let bg = (bg as u32 | (bg as u32) << 16) & MASK;
+    let mixed = ((fg.wrapping_sub(bg).wrapping_mul(a) >> 5).wrapping_add(bg)) & MASK;
+    (mixed | mixed >> 16) as u16
+}
+
+/// 图层样式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayerConfig {
+    /// 是否参与合成
+    pub visible: bool,
+    /// 整体不透明度 (255 = 不透明)
+    pub opacity: u8,
+    /// 透明色键 (等于该颜色的像素不绘制)
+    pub color_key: Option<u16>,
+}
+
+impl LayerConfig {
+    /// 默认样式: 可见、不透明、无色键
+    pub const fn new() -> Self {
+        Self { visible: true, opacity: 255, color_key: None }
+    }
+
+    /// 设置不透明度
+    pub const fn with_opacity(mut self, opacity: u8) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    /// 设置透明色键
+    pub const fn with_color_key(mut self, key: u16) -> Self {
+        self.color_key = Some(key);
+        self
+    }
+
+    /// 设置初始可见性
+    pub const fn with_visible(mut self, visible: bool) -> Self {
+        self.visible = visible;
+        self
+    }
+
+    /// 把该图层的像素叠到 `below` 上
+    #[inline]
+    pub fn apply(&self, pixel: u16, below: u16) -> u16 {
+        if !self.visible || self.opacity == 0 || self.color_key == Some(pixel) {
+            below
+        } else if self.opacity == 255 {
+            pixel
+        } else {
+            blend(pixel, below, self.opacity)
+        }
+    }
+}
+
+impl Default for LayerConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ===== 图层 =====
+
+/// 图层句柄
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayerId(u8);
+
+struct Layer<const N: usize> {
+    pixels: PsramBox<[u16; N]>,
+    config: LayerConfig,
+}
+
+/// 图层中一块区域的可写视图 (创建时已登记为脏区)
+pub struct Canvas<'a> {
+    pixels: &'a mut [u16],
+    stride: usize,
+    rect: Rect,
+}
+
+impl Canvas<'_> {
+    /// 区域
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// 写像素 (区域内坐标，越界忽略)
+    pub fn set(&mut self, x: u16, y: u16, color: u16) {
+        if x < self.rect.w && y < self.rect.h {
+            self.pixels[self.index(x, y)] = color;
+        }
+    }
+
+    /// 一行像素
+    pub fn row_mut(&mut self, y: u16) -> &mut [u16] {
+        let start = self.index(0, y);
+        &mut self.pixels[start..start + self.rect.w as usize]
+    }
+
+    /// 填充整个区域
+    pub fn fill(&mut self, color: u16) {
+        for y in 0..self.rect.h {
+            self.row_mut(y).fill(color);
+        }
+    }
+
+    fn index(&self, x: u16, y: u16) -> usize {
+        (self.rect.y + y) as usize * self.stride + (self.rect.x + x) as usize
+    }
+}
+
+// ===== 显示输出 =====
+
+/// 显示驱动接口
+#[allow(async_fn_in_trait)]
+pub trait DisplaySink {
+    /// 驱动错误
+    type Error;
+
+    /// 设置后续像素写入的窗口
+    async fn set_window(&mut self, area: Rect) -> Result<(), Self::Error>;
+
+    /// 写入窗口像素 (大端 RGB565，按行连续)
+    async fn write_pixels(&mut self, pixels: &[u8]) -> Result<(), Self::Error>;
+}
+
+/// SPI 面板错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanelError<S, P> {
+    /// SPI 传输失败
+    Spi(S),
+    /// D/C 引脚操作失败
+    Pin(P),
+}
+
+/// MIPI-DCS SPI 面板 (CASET / RASET / RAMWR)
+pub struct SpiPanel<SPI, DC> {
+    spi: SPI,
+    dc: DC,
+    offset: (u16, u16),
+}
+
+impl<SPI: SpiDevice, DC: OutputPin> SpiPanel<SPI, DC> {
+    const CASET: u8 = 0x2A;
+    const RASET: u8 = 0x2B;
+    const RAMWR: u8 = 0x2C;
+
+    /// 创建面板 (初始化序列由调用者发送)
+    pub fn new(spi: SPI, dc: DC) -> Self {
+        Self { spi, dc, offset: (0, 0) }
+    }
+
+    /// 设置显存偏移 (如 240×240 ST7789 的行偏移 80)
+    pub fn with_offset(mut self, x: u16, y: u16) -> Self {
+        self.offset = (x, y);
+        self
+    }
+
+    /// 发送命令及参数
+    pub async fn command(&mut self, cmd: u8, params: &[u8]) -> Result<(), PanelError<SPI::Error, DC::Error>> {
+        self.dc.set_low().map_err(PanelError::Pin)?;
+        self.spi.write(&[cmd]).await.map_err(PanelError::Spi)?;
+        if !params.is_empty() {
+            self.dc.set_high().map_err(PanelError::Pin)?;
+            self.spi.write(params).await.map_err(PanelError::Spi)?;
+        }
+        Ok(())
+    }
+
+    /// 释放 SPI 设备与引脚
+    pub fn release(self) -> (SPI, DC) {
+        (self.spi, self.dc)
+    }
+}
+
+impl<SPI: SpiDevice, DC: OutputPin> DisplaySink for SpiPanel<SPI, DC> {
+    type Error = PanelError<SPI::Error, DC::Error>;
+
+    async fn set_window(&mut self, area: Rect) -> Result<(), Self::Error> {
+        let x0 = area.x + self.offset.0;
+        let y0 = area.y + self.offset.1;
+        let x1 = x0 + area.w - 1;
+        let y1 = y0 + area.h - 1;
+        let [a, b] = x0.to_be_bytes();
+        let [c, d] = x1.to_be_bytes();
+        self.command(Self::CASET, &[a, b, c, d]).await?;
+        let [a, b] = y0.to_be_bytes();
+        let [c, d] = y1.to_be_bytes();
+        self.command(Self::RASET, &[a, b, c, d]).await?;
+        self.command(Self::RAMWR, &[]).await?;
+        self.dc.set_high().map_err(PanelError::Pin)
+    }
+
+    async fn write_pixels(&mut self, pixels: &[u8]) -> Result<(), Self::Error> {
+        self.spi.write(pixels).await.map_err(PanelError::Spi)
+    }
+}
+
+// ===== 帧统计 =====
+
+/// 单帧耗时
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FrameTiming {
+    /// 刷新的脏区数
+    pub regions: u8,
+    /// 刷新的像素数
+    pub pixels: u32,
+    /// 合成耗时 (微秒，与 DMA 发送重叠的部分也计入)
+    pub compose_us: u32,
+    /// 整帧耗时 (微秒)
+    pub total_us: u32,
+}
+
+/// 帧统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FrameStats {
+    /// 已刷新帧数
+    pub frames: u32,
+    /// 没有脏区而跳过的刷新
+    pub idle: u32,
+    /// 超出帧预算的帧数
+    pub late: u32,
+    /// 帧预算 (微秒，0 = 不检查)
+    pub budget_us: u32,
+    /// 最近一帧
+    pub last: FrameTiming,
+    /// 平均整帧耗时 (微秒，指数平滑 1/8)
+    pub avg_us: u32,
+    /// 最大整帧耗时 (微秒)
+    pub max_us: u32,
+}
+
+impl FrameStats {
+    /// 记录一帧
+    pub fn record(&mut self, timing: FrameTiming) {
+        self.avg_us = if self.frames == 0 {
+            timing.total_us
+        } else {
+            (self.avg_us as i64 + (timing.total_us as i64 - self.avg_us as i64) / 8) as u32
+        };
+        self.frames += 1;
+        self.max_us = self.max_us.max(timing.total_us);
+        if self.budget_us != 0 && timing.total_us > self.budget_us {
+            self.late += 1;
+        }
+        self.last = timing;
+    }
+
+    /// 按平均耗时可达到的帧率
+    pub fn max_fps(&self) -> u32 {
+        1_000_000u32.checked_div(self.avg_us).unwrap_or(0)
+    }
+}
+
+impl fmt::Display for FrameStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "frames={} idle={} late={} avg={}us max={}us last={}px/{}us (compose {}us)",
+            self.frames,
+            self.idle,
+            self.late,
+            self.avg_us,
+            self.max_us,
+            self.last.pixels,
+            self.last.total_us,
+            self.last.compose_us
+        )
+    }
+}
+
+// ===== 合成器 =====
+
+/// DRAM 反弹缓冲条带 (cache line 对齐)
+#[repr(C, align(32))]
+struct Band<const B: usize>([u16; B]);
+
+/// 帧缓冲合成器
+///
+/// - `N`: 每个图层的像素数 (≥ 宽 × 高)
+/// - `L`: 最大图层数
+/// - `B`: 每个反弹条带的像素数 (≥ 屏幕宽度，越大 DMA 次数越少)
+pub struct Compositor<const N: usize, const L: usize, const B: usize> {
+    width: u16,
+    height: u16,
+    layers: Vec<Layer<N>, L>,
+    dirty: DirtyRegions<MAX_DIRTY>,
+    bands: [Band<B>; 2],
+    stats: FrameStats,
+}
+
+impl<const N: usize, const L: usize, const B: usize> Compositor<N, L, B> {
+    /// 创建合成器 (图层由 `add_layer` 分配)
+    pub fn new(width: u16, height: u16) -> Result<Self, MediaError> {
+        if width == 0 || height == 0 || width as usize * height as usize > N || (width as usize) > B {
+            return Err(MediaError::InvalidFrame);
+        }
+        Ok(Self {
+            width,
+            height,
+            layers: Vec::new(),
+            dirty: DirtyRegions::new(),
+            bands: [Band([0; B]), Band([0; B])],
+            stats: FrameStats::default(),
+        })
+    }
+
+    /// 设置目标帧率 (超出 1/fps 的帧计入 `late`)
+    pub fn with_target_fps(mut self, fps: u32) -> Self {
+        self.stats.budget_us = 1_000_000u32.checked_div(fps).unwrap_or(0);
+        self
+    }
+
+    /// 整个屏幕
+    pub fn bounds(&self) -> Rect {
+        Rect::new(0, 0, self.width, self.height)
+    }
+
+    /// 在 PSRAM 中分配一个新图层 (位于现有图层之上，初始为 0)
+    pub fn add_layer(&mut self, config: LayerConfig) -> Result<LayerId, MediaError> {
+        if self.layers.is_full() {
+            return Err(MediaError::TooManyLayers);
+        }
+        let pixels = psram::alloc_array_with_config::<u16, N>(PsramConfig::bulk_transfer().with_tag(PSRAM_TAG))
+            .map_err(MediaError::Alloc)?;
+        let id = LayerId(self.layers.len() as u8);
+        let _ = self.layers.push(Layer { pixels, config });
+        self.dirty.add(self.bounds());
+        Ok(id)
+    }
+
+    /// 图层样式
+    pub fn layer_config(&self, id: LayerId) -> LayerConfig {
+        self.layers[id.0 as usize].config
+    }
+
+    /// 修改图层样式 (整屏重绘)
+    pub fn set_layer_config(&mut self, id: LayerId, config: LayerConfig) {
+        let layer = &mut self.layers[id.0 as usize];
+        if layer.config != config {
+            layer.config = config;
+            self.dirty.add(self.bounds());
+        }
+    }
+
+    /// 显示/隐藏图层
+    pub fn set_visible(&mut self, id: LayerId, visible: bool) {
+        let config = self.layer_config(id).with_visible(visible);
+        self.set_layer_config(id, config);
+    }
+
+    /// 设置图层不透明度
+    pub fn set_opacity(&mut self, id: LayerId, opacity: u8) {
+        let config = self.layer_config(id).with_opacity(opacity);
+        self.set_layer_config(id, config);
+    }
+
+    /// 获取图层区域的可写视图，并把该区域登记为脏区
+    pub fn canvas(&mut self, id: LayerId, rect: Rect) -> Canvas<'_> {
+        let rect = rect.intersect(&self.bounds());
+        self.dirty.add(rect);
+        let stride = self.width as usize;
+        Canvas { pixels: &mut self.layers[id.0 as usize].pixels[..], stride, rect }
+    }
+
+    /// 填充矩形
+    pub fn fill(&mut self, id: LayerId, rect: Rect, color: u16) {
+        self.canvas(id, rect).fill(color);
+    }
+
+    /// 复制像素到矩形 (`pixels` 按行连续，宽度为 `rect.w`)
+    pub fn blit(&mut self, id: LayerId, rect: Rect, pixels: &[u16]) {
+        let width = rect.w as usize;
+        let mut canvas = self.canvas(id, rect);
+        let visible = canvas.rect();
+        if visible.is_empty() {
+            return;
+        }
+        let skip_x = (visible.x - rect.x) as usize;
+        let skip_y = (visible.y - rect.y) as usize;
+        for (y, src) in pixels.chunks(width).skip(skip_y).take(visible.h as usize).enumerate() {
+            let row = canvas.row_mut(y as u16);
+            let src = src.get(skip_x..).unwrap_or(&[]);
+            let n = row.len().min(src.len());
+            row[..n].copy_from_slice(&src[..n]);
+        }
+    }
+
+    /// 强制重绘区域
+    pub fn invalidate(&mut self, rect: Rect) {
+        self.dirty.add(rect.intersect(&self.bounds()));
+    }
+
+    /// 当前脏区
+    pub fn dirty(&self) -> &[Rect] {
+        self.dirty.rects()
+    }
+
+    /// 帧统计
+    pub fn stats(&self) -> &FrameStats {
+        &self.stats
+    }
+
+    /// 合成脏区并写入显示器
+    ///
+    /// 没有脏区时直接返回 `None`。条带在 DRAM 中交替使用: DMA 发送当前条带时合成下一条带。
+    pub async fn flush<S: DisplaySink>(&mut self, sink: &mut S) -> Result<Option<FrameTiming>, S::Error> {
+        if self.dirty.is_empty() {
+            self.stats.idle += 1;
+            return Ok(None);
+        }
+        let start = Instant::now();
+        let regions = self.dirty.take();
+        let mut timing = FrameTiming { regions: regions.len() as u8, ..FrameTiming::default() };
+
+        for rect in regions {
+            sink.set_window(rect).await?;
+            let rows = (B / rect.w as usize) as u16;
+            let [a, b] = &mut self.bands;
+            let mut front = &mut a.0;
+            let mut back = &mut b.0;
+
+            let mut y = rect.y;
+            let mut count = compose(&self.layers, self.width, rect, y, rows, front, &mut timing);
+            while count > 0 {
+                let len = count as usize * rect.w as usize;
+                y += count;
+                let (sent, next) = join(sink.write_pixels(as_wire(&front[..len])), async {
+                    compose(&self.layers, self.width, rect, y, rows, back, &mut timing)
+                })
+                .await;
+                sent?;
+                timing.pixels += len as u32;
+                count = next;
+                core::mem::swap(&mut front, &mut back);
+            }
+        }
+
+        timing.total_us = start.elapsed().as_micros() as u32;
+        self.stats.record(timing);
+        Ok(Some(timing))
+    }
+}
+
+/// 合成 `rect` 中从 `y` 开始最多 `rows` 行到 `out` (大端 RGB565)，返回合成的行数
+fn compose<const N: usize>(
+    layers: &[Layer<N>],
+    stride: u16,
+    rect: Rect,
+    y: u16,
+    rows: u16,
+    out: &mut [u16],
+    timing: &mut FrameTiming,
+) -> u16 {
+    let count = rows.min(rect.bottom().saturating_sub(y));
+    if count == 0 {
+        return 0;
+    }
+    let start = Instant::now();
+    let width = rect.w as usize;
+    for row in 0..count {
+        let base = (y + row) as usize * stride as usize + rect.x as usize;
+        let dst = &mut out[row as usize * width..(row as usize + 1) * width];
+        for (x, pixel) in dst.iter_mut().enumerate() {
+            let color = layers
+                .iter()
+                .fold(0, |below, layer| layer.config.apply(layer.pixels[base + x], below));
+            *pixel = color.to_be();
+        }
+    }
+    timing.compose_us += start.elapsed().as_micros() as u32;
+    count
+}
+
+/// 条带按字节发送
+fn as_wire(pixels: &[u16]) -> &[u8] {
+    // Safety: u16 没有填充字节，长度按字节数换算；像素已转换为大端
+    unsafe { core::slice::from_raw_parts(pixels.as_ptr() as *const u8, pixels.len() * 2) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dirty_merging() {
+        let mut dirty = DirtyRegions::<2>::new();
+        dirty.add(Rect::new(0, 0, 10, 10));
+        dirty.add(Rect::new(10, 0, 10, 10));
+        assert_eq!(dirty.rects(), &[Rect::new(0, 0, 20, 10)]);
+
+        dirty.add(Rect::new(100, 100, 5, 5));
+        dirty.add(Rect::new(30, 0, 5, 5));
+        assert_eq!(dirty.rects().len(), 2);
+        assert!(dirty.rects().contains(&Rect::new(0, 0, 35, 10)));
+        assert_eq!(dirty.area(), 35 * 10 + 25);
+
+        assert!(!Rect::new(0, 0, 10, 10).touches(&Rect::new(10, 10, 5, 5)));
+        assert_eq!(Rect::new(0, 0, 10, 10).intersect(&Rect::new(20, 0, 5, 5)), Rect::default());
+        assert_eq!(dirty.take().len(), 2);
+        assert!(dirty.is_empty());
+    }
+
+    #[test]
+    fn test_layer_blending() {
+        assert_eq!(blend(0xFFFF, 0x0000, 255), 0xFFFF);
+        assert_eq!(blend(0xFFFF, 0x1234, 0), 0x1234);
+        // 50% 白叠黑: 各通道约为一半
+        let half = blend(0xFFFF, 0x0000, 128);
+        assert_eq!((half >> 11, (half >> 5) & 0x3F, half & 0x1F), (15, 31, 15));
+
+        let keyed = LayerConfig::new().with_color_key(0xF81F);
+        assert_eq!(keyed.apply(0xF81F, 0x07E0), 0x07E0);
+        assert_eq!(keyed.apply(0x001F, 0x07E0), 0x001F);
+        assert_eq!(LayerConfig::new().with_visible(false).apply(0x001F, 0x07E0), 0x07E0);
+    }
+}
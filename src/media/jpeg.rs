@@ -0,0 +1,705 @@
+//! 软件基线 JPEG 编码器
+//!
+//! 按 MCU 行 (条带) 处理帧缓冲区：每次只把一条 8/16 行高的 YCbCr 条带
+//! 转换进暂存区 (通常放在 PSRAM)，DRAM 中仅保留单个 8×8 块的工作数据，
+//! 因此内存占用与图像高度无关。
+//!
+//! # 特性
+//! - 基线顺序 DCT (SOF0)，标准 Huffman 表 (ITU T.81 附录 K)
+//! - 灰度、YUV 4:4:4、YUV 4:2:0 采样
+//! - IJG 质量系数 (1-100)
+//! - 输出到任意 `JpegSink` (内存缓冲区、文件、网络连接)
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! let scratch = JpegScratch::in_psram::<{ JpegScratch::required(800, Sampling::Yuv420) }>()?;
+//! let mut encoder = JpegEncoder::new(JpegConfig::default(), scratch);
+//!
+//! let mut out = SliceSink::new(&mut jpeg_buf);
+//! let len = encoder.encode(&frame, &mut out)?;
+//! ```
+
+use core::mem::MaybeUninit;
+
+use super::{Frame, MediaError, PixelFormat};
+use crate::mem::psram::{PsramBox, PsramConfig};
+
+// ===== 标准表 =====
+
+/// Z 字形扫描顺序 (zigzag 序号 → 自然序号)
+const ZIGZAG: [usize; 64] = [
+    0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20,
+    13, 6, 7, 14, 21, 28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59,
+    52, 45, 38, 31, 39, 46, 53, 60, 61, 54, 47, 55, 62, 63,
+];
+
+/// 亮度量化表 (自然顺序)
+const LUMA_QUANT: [u8; 64] = [
+    16, 11, 10, 16, 24, 40, 51, 61, 12, 12, 14, 19, 26, 58, 60, 55, 14, 13, 16, 24, 40, 57, 69, 56,
+    14, 17, 22, 29, 51, 87, 80, 62, 18, 22, 37, 56, 68, 109, 103, 77, 24, 35, 55, 64, 81, 104, 113,
+    92, 49, 64, 78, 87, 103, 121, 120, 101, 72, 92, 95, 98, 112, 100, 103, 99,
+];
+
+/// 色度量化表 (自然顺序)
+const CHROMA_QUANT: [u8; 64] = [
+    17, 18, 24, 47, 99, 99, 99, 99, 18, 21, 26, 66, 99, 99, 99, 99, 24, 26, 56, 99, 99, 99, 99, 99,
+    47, 66, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99,
+    99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99,
+];
+
+/// AAN 浮点 DCT 比例因子
+const AAN_SCALE: [f32; 8] = [
+    1.0, 1.387_039_8, 1.306_563, 1.175_875_6, 1.0, 0.785_694_96, 0.541_196_1, 0.275_899_38,
+];
+
+const DC_LUMA_BITS: [u8; 16] = [0, 1, 5, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0];
+const DC_CHROMA_BITS: [u8; 16] = [0, 3, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0];
+const DC_VALUES: [u8; 12] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+
+const AC_LUMA_BITS: [u8; 16] = [0, 2, 1, 3, 3, 2, 4, 3, 5, 5, 4, 4, 0, 0, 1, 0x7D];
+const AC_LUMA_VALUES: [u8; 162] = [
+    0x01, 0x02, 0x03, 0x00, 0x04, 0x11, 0x05, 0x12, 0x21, 0x31, 0x41, 0x06, 0x13, 0x51, 0x61, 0x07,
+    0x22, 0x71, 0x14, 0x32, 0x81, 0x91, 0xA1, 0x08, 0x23, 0x42, 0xB1, 0xC1, 0x15, 0x52, 0xD1, 0xF0,
+    0x24, 0x33, 0x62, 0x72, 0x82, 0x09, 0x0A, 0x16, 0x17, 0x18, 0x19, 0x1A, 0x25, 0x26, 0x27, 0x28,
+    0x29, 0x2A, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3A, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49,
+    0x4A, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5A, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69,
+    0x6A, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7A, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89,
+    0x8A, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9A, 0xA2, 0xA3, 0xA4, 0xA5, 0xA6, 0xA7,
+    0xA8, 0xA9, 0xAA, 0xB2, 0xB3, 0xB4, 0xB5, 0xB6, 0xB7, 0xB8, 0xB9, 0xBA, 0xC2, 0xC3, 0xC4, 0xC5,
+    0xC6, 0xC7, 0xC8, 0xC9, 0xCA, 0xD2, 0xD3, 0xD4, 0xD5, 0xD6, 0xD7, 0xD8, 0xD9, 0xDA, 0xE1, 0xE2,
+    0xE3, 0xE4, 0xE5, 0xE6, 0xE7, 0xE8, 0xE9, 0xEA, 0xF1, 0xF2, 0xF3, 0xF4, 0xF5, 0xF6, 0xF7, 0xF8,
+    0xF9, 0xFA,
+];
+
+const AC_CHROMA_BITS: [u8; 16] = [0, 2, 1, 2, 4, 4, 3, 4, 7, 5, 4, 4, 0, 1, 2, 0x77];
+const AC_CHROMA_VALUES: [u8; 162] = [
+    0x00, 0x01, 0x02, 0x03, 0x11, 0x04, 0x05, 0x21, 0x31, 0x06, 0x12, 0x41, 0x51, 0x07, 0x61, 0x71,
+    0x13, 0x22, 0x32, 0x81, 0x08, 0x14, 0x42, 0x91, 0xA1, 0xB1, 0xC1, 0x09, 0x23, 0x33, 0x52, 0xF0,
+    0x15, 0x62, 0x72, 0xD1, 0x0A, 0x16, 0x24, 0x34, 0xE1, 0x25, 0xF1, 0x17, 0x18, 0x19, 0x1A, 0x26,
+    0x27, 0x28, 0x29, 0x2A, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3A, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48,
+    0x49, 0x4A, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5A, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68,
+    0x69, 0x6A, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7A, 0x82, 0x83, 0x84, 0x85, 0x86, 0x87,
+    0x88, 0x89, 0x8A, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9A, 0xA2, 0xA3, 0xA4, 0xA5,
+    0xA6, 0xA7, 0xA8, 0xA9, 0xAA, 0xB2, 0xB3, 0xB4, 0xB5, 0xB6, 0xB7, 0xB8, 0xB9, 0xBA, 0xC2, 0xC3,
+    0xC4, 0xC5, 0xC6, 0xC7, 0xC8, 0xC9, 0xCA, 0xD2, 0xD3, 0xD4, 0xD5, 0xD6, 0xD7, 0xD8, 0xD9, 0xDA,
+    0xE2, 0xE3, 0xE4, 0xE5, 0xE6, 0xE7, 0xE8, 0xE9, 0xEA, 0xF2, 0xF3, 0xF4, 0xF5, 0xF6, 0xF7, 0xF8,
+    0xF9, 0xFA,
+];
+
+/// Huffman 编码表 (符号 → 码字)
+struct HuffTable {
+    code: [u16; 256],
+    size: [u8; 256],
+}
+
+impl HuffTable {
+    /// 由 BITS/HUFFVAL 生成码字 (T.81 附录 C)
+    const fn build(bits: &[u8; 16], values: &[u8]) -> Self {
+        let mut table = Self { code: [0; 256], size: [0; 256] };
+        let mut code: u16 = 0;
+        let mut k = 0;
+        let mut len = 0;
+        while len < 16 {
+            let mut i = 0;
+            while i < bits[len] {
+                let symbol = values[k] as usize;
+                table.code[symbol] = code;
+                table.size[symbol] = (len + 1) as u8;
+                code += 1;
+                k += 1;
+                i += 1;
+            }
+            code <<= 1;
+            len += 1;
+        }
+        table
+    }
+}
+
+static DC_LUMA: HuffTable = HuffTable::build(&DC_LUMA_BITS, &DC_VALUES);
+static DC_CHROMA: HuffTable = HuffTable::build(&DC_CHROMA_BITS, &DC_VALUES);
+static AC_LUMA: HuffTable = HuffTable::build(&AC_LUMA_BITS, &AC_LUMA_VALUES);
+static AC_CHROMA: HuffTable = HuffTable::build(&AC_CHROMA_BITS, &AC_CHROMA_VALUES);
+
+// ===== 配置 =====
+
+/// 色度采样方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sampling {
+    /// 仅亮度 (灰度 JPEG)
+    Gray,
+    /// 不降采样
+    Yuv444,
+    /// 色度水平、垂直各 2:1 降采样
+    Yuv420,
+}
+
+impl Sampling {
+    /// 分量数
+    pub const fn components(self) -> usize {
+        match self {
+            Sampling::Gray => 1,
+            _ => 3,
+        }
+    }
+
+    /// MCU 边长 (像素)
+    pub const fn mcu_size(self) -> usize {
+        match self {
+            Sampling::Yuv420 => 16,
+            _ => 8,
+        }
+    }
+}
+
+/// 编码配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JpegConfig {
+    /// 质量 (1-100)
+    pub quality: u8,
+    /// 采样方式 (灰度帧自动使用 `Gray`)
+    pub sampling: Sampling,
+}
+
+impl Default for JpegConfig {
+    fn default() -> Self {
+        Self { quality: 80, sampling: Sampling::Yuv420 }
+    }
+}
+
+impl JpegConfig {
+    /// 设置质量
+    pub fn with_quality(mut self, quality: u8) -> Self {
+        self.quality = quality.clamp(1, 100);
+        self
+    }
+
+    /// 设置采样方式
+    pub fn with_sampling(mut self, sampling: Sampling) -> Self {
+        self.sampling = sampling;
+        self
+    }
+}
+
+/// 按 IJG 质量系数缩放量化表 (结果为自然顺序)
+pub fn scale_quant_table(base: &[u8; 64], quality: u8) -> [u8; 64] {
+    let quality = quality.clamp(1, 100) as u32;
+    let scale = if quality < 50 { 5000 / quality } else { 200 - quality * 2 };
+    let mut table = [0u8; 64];
+    for (out, &q) in table.iter_mut().zip(base.iter()) {
+        *out = ((q as u32 * scale + 50) / 100).clamp(1, 255) as u8;
+    }
+    table
+}
+
+// ===== 输出 =====
+
+/// JPEG 输出目标
+pub trait JpegSink {
+    /// 写入全部数据
+    fn write_all(&mut self, data: &[u8]) -> Result<(), MediaError>;
+}
+
+/// 写入内存缓冲区
+pub struct SliceSink<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> SliceSink<'a> {
+    /// 创建
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+
+    /// 已写入长度
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 已写入数据
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl JpegSink for SliceSink<'_> {
+    fn write_all(&mut self, data: &[u8]) -> Result<(), MediaError> {
+        let end = self.len + data.len();
+        if end > self.buf.len() {
+            return Err(MediaError::BufferFull);
+        }
+        self.buf[self.len..end].copy_from_slice(data);
+        self.len = end;
+        Ok(())
+    }
+}
+
+impl JpegSink for crate::fs::File<'_> {
+    fn write_all(&mut self, data: &[u8]) -> Result<(), MediaError> {
+        crate::fs::File::write_all(self, data).map_err(|_| MediaError::Write)
+    }
+}
+
+/// 位写入器 (带 0xFF 填充与小块输出缓冲)
+struct BitWriter<'s, S: JpegSink> {
+    sink: &'s mut S,
+    buf: [u8; 256],
+    pos: usize,
+    acc: u32,
+    bits: u32,
+    written: usize,
+}
+
+impl<'s, S: JpegSink> BitWriter<'s, S> {
+    fn new(sink: &'s mut S) -> Self {
+        Self { sink, buf: [0; 256], pos: 0, acc: 0, bits: 0, written: 0 }
+    }
+
+    #[inline]
+    fn byte(&mut self, b: u8) -> Result<(), MediaError> {
+        if self.pos == self.buf.len() {
+            self.flush_buf()?;
+        }
+        self.buf[self.pos] = b;
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn bytes(&mut self, data: &[u8]) -> Result<(), MediaError> {
+        for &b in data {
+            self.byte(b)?;
+        }
+        Ok(())
+    }
+
+    fn u16(&mut self, v: u16) -> Result<(), MediaError> {
+        self.bytes(&v.to_be_bytes())
+    }
+
+    /// 写入 `len` 位 (len <= 16)
+    #[inline]
+    fn put(&mut self, value: u32, len: u32) -> Result<(), MediaError> {
+        self.acc = (self.acc << len) | (value & ((1 << len) - 1));
+        self.bits += len;
+        while self.bits >= 8 {
+            let b = (self.acc >> (self.bits - 8)) as u8;
+            self.byte(b)?;
+            if b == 0xFF {
+                self.byte(0x00)?;
+            }
+            self.bits -= 8;
+        }
+        self.acc &= (1 << self.bits) - 1;
+        Ok(())
+    }
+
+    /// 以 1 填充剩余位
+    fn align(&mut self) -> Result<(), MediaError> {
+        if self.bits > 0 {
+            self.put(0x7F, 8 - self.bits)?;
+        }
+        Ok(())
+    }
+
+    fn flush_buf(&mut self) -> Result<(), MediaError> {
+        self.sink.write_all(&self.buf[..self.pos])?;
+        self.written += self.pos;
+        self.pos = 0;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<usize, MediaError> {
+        self.flush_buf()?;
+        Ok(self.written)
+    }
+}
+
+// ===== 暂存区 =====
+
+/// 条带暂存区 (一条 MCU 行的 Y/Cb/Cr 平面)
+pub struct JpegScratch<'a> {
+    buf: &'a mut [u8],
+}
+
+impl JpegScratch<'static> {
+    /// 在 PSRAM 中分配 `SIZE` 字节暂存区
+    pub fn in_psram<const SIZE: usize>() -> Result<Self, MediaError> {
+        let mut boxed = PsramBox::<[MaybeUninit<u8>; SIZE]>::new_uninit_with_config(PsramConfig::default())
+            .map_err(MediaError::Alloc)?;
+        // PsramBox 由 bump 分配器分配，永不释放
+        let buf = unsafe { core::slice::from_raw_parts_mut(boxed.as_mut_ptr() as *mut u8, SIZE) };
+        Ok(Self { buf })
+    }
+}
+
+impl<'a> JpegScratch<'a> {
+    /// 使用外部缓冲区
+    pub fn from_slice(buf: &'a mut [u8]) -> Self {
+        Self { buf }
+    }
+
+    /// 编码宽度为 `width` 的图像所需字节数
+    pub const fn required(width: usize, sampling: Sampling) -> usize {
+        let mcu = sampling.mcu_size();
+        let padded = width.div_ceil(mcu) * mcu;
+        padded * mcu * sampling.components()
+    }
+
+    /// 容量
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+}
+
+// ===== 编码器 =====
+
+/// 基线 JPEG 编码器
+pub struct JpegEncoder<'a> {
+    config: JpegConfig,
+    scratch: JpegScratch<'a>,
+    luma_quant: [u8; 64],
+    chroma_quant: [u8; 64],
+    luma_div: [f32; 64],
+    chroma_div: [f32; 64],
+}
+
+impl<'a> JpegEncoder<'a> {
+    /// 创建编码器
+    pub fn new(config: JpegConfig, scratch: JpegScratch<'a>) -> Self {
+        let luma_quant = scale_quant_table(&LUMA_QUANT, config.quality);
+        let chroma_quant = scale_quant_table(&CHROMA_QUANT, config.quality);
+        Self {
+            config,
+            scratch,
+            luma_div: divisors(&luma_quant),
+            chroma_div: divisors(&chroma_quant),
+            luma_quant,
+            chroma_quant,
+        }
+    }
+
+    /// 配置
+    pub fn config(&self) -> &JpegConfig {
+        &self.config
+    }
+
+    /// 编码一帧
+    ///
+    /// # 返回
+    /// 输出的字节数
+    pub fn encode<S: JpegSink>(&mut self, frame: &Frame<'_>, sink: &mut S) -> Result<usize, MediaError> {
+        let sampling = if frame.format() == PixelFormat::Gray8 { Sampling::Gray } else { self.config.sampling };
+        let width = frame.width() as usize;
+        let height = frame.height() as usize;
+        let mcu = sampling.mcu_size();
+        let padded = width.div_ceil(mcu) * mcu;
+        let plane = padded * mcu;
+        if self.scratch.capacity() < plane * sampling.components() {
+            return Err(MediaError::ScratchTooSmall);
+        }
+
+        let mut out = BitWriter::new(sink);
+        self.write_headers(&mut out, frame, sampling)?;
+
+        let mut pred = [0i32; 3];
+        for strip in 0..height.div_ceil(mcu) {
+            self.load_strip(frame, sampling, strip * mcu, padded);
+            let planes = &self.scratch.buf[..plane * sampling.components()];
+
+            for mx in 0..padded / mcu {
+                let x0 = mx * mcu;
+                // 亮度块
+                for by in 0..mcu / 8 {
+                    for bx in 0..mcu / 8 {
+                        let block = extract_block(&planes[..plane], padded, x0 + bx * 8, by * 8, false);
+                        encode_block(&mut out, &block, &self.luma_div, &DC_LUMA, &AC_LUMA, &mut pred[0])?;
+                    }
+                }
+                // 色度块
+                if sampling != Sampling::Gray {
+                    let subsample = sampling == Sampling::Yuv420;
+                    for c in 1..3 {
+                        let block = extract_block(&planes[c * plane..(c + 1) * plane], padded, x0, 0, subsample);
+                        encode_block(&mut out, &block, &self.chroma_div, &DC_CHROMA, &AC_CHROMA, &mut pred[c])?;
+                    }
+                }
+            }
+        }
+
+        out.align()?;
+        out.bytes(&[0xFF, 0xD9])?;
+        out.finish()
+    }
+
+    /// 将一条 MCU 行转换为 YCbCr 平面 (边缘像素复制填充)
+    fn load_strip(&mut self, frame: &Frame<'_>, sampling: Sampling, y0: usize, padded: usize) {
+        let mcu = sampling.mcu_size();
+        let plane = padded * mcu;
+        let width = frame.width() as usize;
+        let height = frame.height() as usize;
+        let color = sampling != Sampling::Gray;
+
+        for row in 0..mcu {
+            let sy = (y0 + row).min(height - 1);
+            for col in 0..padded {
+                let (y, cb, cr) = frame.ycbcr(col.min(width - 1), sy);
+                let i = row * padded + col;
+                self.scratch.buf[i] = y;
+                if color {
+                    self.scratch.buf[plane + i] = cb;
+                    self.scratch.buf[2 * plane + i] = cr;
+                }
+            }
+        }
+    }
+
+    fn write_headers<S: JpegSink>(
+        &self,
+        out: &mut BitWriter<'_, S>,
+        frame: &Frame<'_>,
+        sampling: Sampling,
+    ) -> Result<(), MediaError> {
+        let components = sampling.components();
+
+        // SOI + APP0 (JFIF 1.01，无缩略图)
+        out.bytes(&[0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10, b'J', b'F', b'I', b'F', 0, 1, 1, 0, 0, 1, 0, 1, 0, 0])?;
+
+        // DQT
+        let tables = if components == 1 { 1 } else { 2 };
+        out.bytes(&[0xFF, 0xDB])?;
+        out.u16(2 + 65 * tables as u16)?;
+        for (id, quant) in [&self.luma_quant, &self.chroma_quant].iter().take(tables).enumerate() {
+            out.byte(id as u8)?;
+            for &n in ZIGZAG.iter() {
+                out.byte(quant[n])?;
+            }
+        }
+
+        // SOF0
+        let luma_factor = if sampling == Sampling::Yuv420 { 0x22 } else { 0x11 };
+        out.bytes(&[0xFF, 0xC0])?;
+        out.u16(8 + 3 * components as u16)?;
+        out.byte(8)?;
+        out.u16(frame.height())?;
+        out.u16(frame.width())?;
+        out.byte(components as u8)?;
+        out.bytes(&[1, luma_factor, 0])?;
+        if components == 3 {
+            out.bytes(&[2, 0x11, 1, 3, 0x11, 1])?;
+        }
+
+        // DHT
+        write_dht(out, 0x00, &DC_LUMA_BITS, &DC_VALUES)?;
+        write_dht(out, 0x10, &AC_LUMA_BITS, &AC_LUMA_VALUES)?;
+        if components == 3 {
+            write_dht(out, 0x01, &DC_CHROMA_BITS, &DC_VALUES)?;
+            write_dht(out, 0x11, &AC_CHROMA_BITS, &AC_CHROMA_VALUES)?;
+        }
+
+        // SOS
+        out.bytes(&[0xFF, 0xDA])?;
+        out.u16(6 + 2 * components as u16)?;
+        out.byte(components as u8)?;
+        out.bytes(&[1, 0x00])?;
+        if components == 3 {
+            out.bytes(&[2, 0x11, 3, 0x11])?;
+        }
+        out.bytes(&[0, 63, 0])
+    }
+}
+
+fn write_dht<S: JpegSink>(out: &mut BitWriter<'_, S>, class_id: u8, bits: &[u8; 16], values: &[u8]) -> Result<(), MediaError> {
+    out.bytes(&[0xFF, 0xC4])?;
+    out.u16(2 + 1 + 16 + values.len() as u16)?;
+    out.byte(class_id)?;
+    out.bytes(bits)?;
+    out.bytes(values)
+}
+
+/// 量化除数倒数 (含 AAN 比例因子，自然顺序)
+fn divisors(quant: &[u8; 64]) -> [f32; 64] {
+    let mut div = [0f32; 64];
+    for (i, d) in div.iter_mut().enumerate() {
+        *d = 1.0 / (quant[i] as f32 * AAN_SCALE[i / 8] * AAN_SCALE[i % 8] * 8.0);
+    }
+    div
+}
+
+/// 取出一个 8×8 块并做电平偏移；`subsample` 时对 16×16 区域做 2×2 平均
+#[inline]
+fn extract_block(plane: &[u8], stride: usize, x0: usize, y0: usize, subsample: bool) -> [f32; 64] {
+    let mut block = [0f32; 64];
+    for row in 0..8 {
+        for col in 0..8 {
+            let v = if subsample {
+                let i = (y0 + row * 2) * stride + x0 + col * 2;
+                let sum = plane[i] as u32 + plane[i + 1] as u32 + plane[i + stride] as u32 + plane[i + stride + 1] as u32;
+                ((sum + 2) / 4) as f32
+            } else {
+                plane[(y0 + row) * stride + x0 + col] as f32
+            };
+            block[row * 8 + col] = v - 128.0;
+        }
+    }
+    block
+}
+
+/// AAN 浮点正向 DCT (原地，输出带比例因子)
+#[inline]
+fn fdct(data: &mut [f32; 64]) {
+    fn pass(d: &mut [f32; 64], base: usize, step: usize) {
+        let at = |i: usize| base + i * step;
+        let tmp0 = d[at(0)] + d[at(7)];
+        let tmp7 = d[at(0)] - d[at(7)];
+        let tmp1 = d[at(1)] + d[at(6)];
+        let tmp6 = d[at(1)] - d[at(6)];
+        let tmp2 = d[at(2)] + d[at(5)];
+        let tmp5 = d[at(2)] - d[at(5)];
+        let tmp3 = d[at(3)] + d[at(4)];
+        let tmp4 = d[at(3)] - d[at(4)];
+
+        // 偶数部分
+        let tmp10 = tmp0 + tmp3;
+        let tmp13 = tmp0 - tmp3;
+        let tmp11 = tmp1 + tmp2;
+        let tmp12 = tmp1 - tmp2;
+        d[at(0)] = tmp10 + tmp11;
+        d[at(4)] = tmp10 - tmp11;
+        let z1 = (tmp12 + tmp13) * 0.707_106_77;
+        d[at(2)] = tmp13 + z1;
+        d[at(6)] = tmp13 - z1;
+
+        // 奇数部分
+        let tmp10 = tmp4 + tmp5;
+        let tmp11 = tmp5 + tmp6;
+        let tmp12 = tmp6 + tmp7;
+        let z5 = (tmp10 - tmp12) * 0.382_683_43;
+        let z2 = 0.541_196_1 * tmp10 + z5;
+        let z4 = 1.306_563 * tmp12 + z5;
+        let z3 = tmp11 * 0.707_106_77;
+        let z11 = tmp7 + z3;
+        let z13 = tmp7 - z3;
+        d[at(5)] = z13 + z2;
+        d[at(3)] = z13 - z2;
+        d[at(1)] = z11 + z4;
+        d[at(7)] = z11 - z4;
+    }
+
+    for row in 0..8 {
+        pass(data, row * 8, 1);
+    }
+    for col in 0..8 {
+        pass(data, col, 8);
+    }
+}
+
+/// 位数类别 (SSSS)
+#[inline]
+fn category(v: i32) -> u32 {
+    32 - v.unsigned_abs().leading_zeros()
+}
+
+/// 幅值附加位 (负数取反码)
+#[inline]
+fn magnitude_bits(v: i32, cat: u32) -> u32 {
+    let bits = if v < 0 { v - 1 } else { v } as u32;
+    bits & ((1 << cat) - 1)
+}
+
+/// 量化并输出一个块
+fn encode_block<S: JpegSink>(
+    out: &mut BitWriter<'_, S>,
+    block: &[f32; 64],
+    div: &[f32; 64],
+    dc: &HuffTable,
+    ac: &HuffTable,
+    pred: &mut i32,
+) -> Result<(), MediaError> {
+    let mut data = *block;
+    fdct(&mut data);
+
+    let mut q = [0i32; 64];
+    for (k, &n) in ZIGZAG.iter().enumerate() {
+        let v = data[n] * div[n];
+        q[k] = if v >= 0.0 { (v + 0.5) as i32 } else { (v - 0.5) as i32 };
+    }
+
+    // DC 差分
+    let diff = q[0] - *pred;
+    *pred = q[0];
+    let cat = category(diff);
+    out.put(dc.code[cat as usize] as u32, dc.size[cat as usize] as u32)?;
+    if cat > 0 {
+        out.put(magnitude_bits(diff, cat), cat)?;
+    }
+
+    // AC 游程
+    let mut run = 0u32;
+    for &v in &q[1..] {
+        if v == 0 {
+            run += 1;
+            continue;
+        }
+        while run > 15 {
+            out.put(ac.code[0xF0] as u32, ac.size[0xF0] as u32)?;
+            run -= 16;
+        }
+        let cat = category(v);
+        let symbol = ((run << 4) | cat) as usize;
+        out.put(ac.code[symbol] as u32, ac.size[symbol] as u32)?;
+        out.put(magnitude_bits(v, cat), cat)?;
+        run = 0;
+    }
+    if run > 0 {
+        out.put(ac.code[0x00] as u32, ac.size[0x00] as u32)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_huffman_table_codes() {
+        // T.81 表 K.3: 类别 0 为 "00"，类别 11 为 "111111110"
+        assert_eq!((DC_LUMA.code[0], DC_LUMA.size[0]), (0b00, 2));
+        assert_eq!((DC_LUMA.code[11], DC_LUMA.size[11]), (0b1_1111_1110, 9));
+        // 表 K.5: EOB 为 "1010"，ZRL 为 "11111111001"
+        assert_eq!((AC_LUMA.code[0x00], AC_LUMA.size[0x00]), (0b1010, 4));
+        assert_eq!((AC_LUMA.code[0xF0], AC_LUMA.size[0xF0]), (0b111_1111_1001, 11));
+    }
+
+    #[test]
+    fn test_flat_block_dc_only() {
+        let mut block = [40f32; 64];
+        fdct(&mut block);
+        let div = divisors(&[1; 64]);
+        assert!((block[0] * div[0] - 320.0).abs() < 0.01);
+        for i in 1..64 {
+            assert!((block[i] * div[i]).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn test_encode_gray_markers() {
+        let pixels = [0x80u8; 16 * 16];
+        let frame = Frame::new(&pixels, 16, 16, PixelFormat::Gray8).unwrap();
+        let mut scratch = [0u8; 16 * 8];
+        let mut encoder = JpegEncoder::new(JpegConfig::default(), JpegScratch::from_slice(&mut scratch));
+        let mut buf = [0u8; 1024];
+        let mut sink = SliceSink::new(&mut buf);
+        let len = encoder.encode(&frame, &mut sink).unwrap();
+        let out = sink.as_slice();
+        assert_eq!(len, out.len());
+        assert_eq!(&out[..2], &[0xFF, 0xD8]);
+        assert_eq!(&out[len - 2..], &[0xFF, 0xD9]);
+    }
+}
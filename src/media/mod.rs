@@ -0,0 +1,157 @@
+//! 媒体处理模块
+//!
+//! 面向摄像头帧缓冲区的图像处理，特性：
+//! - 常见摄像头像素格式 (灰度、RGB565、RGB888、YUYV)
+//! - 软件基线 JPEG 编码 (条带处理，PSRAM 暂存，DRAM 占用有界)
+//! - UI 帧缓冲合成 (PSRAM 多图层、脏区局部刷新、DMA 条带发送、帧耗时统计)
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::media::{Frame, PixelFormat};
+//! use rustrtos::media::jpeg::{JpegConfig, JpegEncoder, JpegScratch};
+//!
+//! let frame = Frame::new(&fb, 640, 480, PixelFormat::Rgb565)?;
+//! let scratch = JpegScratch::in_psram::<{ 640 * 16 * 3 }>()?;
+//! let mut encoder = JpegEncoder::new(JpegConfig::default().with_quality(75), scratch);
+//!
+//! let mut file = fs.create("/snap.jpg")?;
+//! let size = encoder.encode(&frame, &mut file)?;
+//! ```
+
+pub mod jpeg;
+pub mod compositor;
+
+use core::fmt;
+
+use crate::mem::psram::PsramError;
+
+/// 媒体错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaError {
+    /// 帧尺寸或缓冲区长度无效
+    InvalidFrame,
+    /// 暂存区不足
+    ScratchTooSmall,
+    /// 输出缓冲区已满
+    BufferFull,
+    /// 输出写入失败
+    Write,
+    /// 图层数已达上限
+    TooManyLayers,
+    /// PSRAM 分配失败
+    Alloc(PsramError),
+}
+
+impl fmt::Display for MediaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidFrame => write!(f, "Invalid frame"),
+            Self::ScratchTooSmall => write!(f, "Scratch buffer too small"),
+            Self::BufferFull => write!(f, "Output buffer full"),
+            Self::Write => write!(f, "Output write failed"),
+            Self::TooManyLayers => write!(f, "Too many layers"),
+            Self::Alloc(e) => write!(f, "PSRAM allocation failed: {:?}", e),
+        }
+    }
+}
+
+/// 像素格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// 8 位灰度
+    Gray8,
+    /// RGB565 (小端)
+    Rgb565,
+    /// RGB888 (R, G, B 顺序)
+    Rgb888,
+    /// YUV422 (Y0 U Y1 V 顺序)
+    Yuyv,
+}
+
+impl PixelFormat {
+    /// 每像素字节数
+    pub const fn bytes_per_pixel(self) -> usize {
+        match self {
+            PixelFormat::Gray8 => 1,
+            PixelFormat::Rgb565 | PixelFormat::Yuyv => 2,
+            PixelFormat::Rgb888 => 3,
+        }
+    }
+}
+
+/// 帧缓冲区视图
+#[derive(Debug, Clone, Copy)]
+pub struct Frame<'a> {
+    data: &'a [u8],
+    width: u16,
+    height: u16,
+    format: PixelFormat,
+}
+
+impl<'a> Frame<'a> {
+    /// 创建帧视图，校验缓冲区长度
+    pub fn new(data: &'a [u8], width: u16, height: u16, format: PixelFormat) -> Result<Self, MediaError> {
+        let needed = width as usize * height as usize * format.bytes_per_pixel();
+        if width == 0 || height == 0 || data.len() < needed {
+            return Err(MediaError::InvalidFrame);
+        }
+        if format == PixelFormat::Yuyv && width & 1 != 0 {
+            return Err(MediaError::InvalidFrame);
+        }
+        Ok(Self { data, width, height, format })
+    }
+
+    /// 宽度
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    /// 高度
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// 像素格式
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    /// 原始数据
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// 读取像素并转换为 YCbCr (JFIF)
+    #[inline]
+    pub fn ycbcr(&self, x: usize, y: usize) -> (u8, u8, u8) {
+        let index = y * self.width as usize + x;
+        match self.format {
+            PixelFormat::Gray8 => (self.data[index], 128, 128),
+            PixelFormat::Rgb565 => {
+                let v = u16::from_le_bytes([self.data[index * 2], self.data[index * 2 + 1]]);
+                let r = ((v >> 11) & 0x1F) as i32;
+                let g = ((v >> 5) & 0x3F) as i32;
+                let b = (v & 0x1F) as i32;
+                rgb_to_ycbcr((r << 3) | (r >> 2), (g << 2) | (g >> 4), (b << 3) | (b >> 2))
+            }
+            PixelFormat::Rgb888 => {
+                let p = &self.data[index * 3..index * 3 + 3];
+                rgb_to_ycbcr(p[0] as i32, p[1] as i32, p[2] as i32)
+            }
+            PixelFormat::Yuyv => {
+                let base = (index & !1) * 2;
+                (self.data[index * 2], self.data[base + 1], self.data[base + 3])
+            }
+        }
+    }
+}
+
+/// RGB → YCbCr (JFIF，16 位定点)
+#[inline]
+pub fn rgb_to_ycbcr(r: i32, g: i32, b: i32) -> (u8, u8, u8) {
+    let y = (19595 * r + 38470 * g + 7471 * b + 32768) >> 16;
+    let cb = ((-11059 * r - 21709 * g + 32768 * b + 32768) >> 16) + 128;
+    let cr = ((32768 * r - 27439 * g - 5329 * b + 32768) >> 16) + 128;
+    (y.clamp(0, 255) as u8, cb.clamp(0, 255) as u8, cr.clamp(0, 255) as u8)
+}
@@ -0,0 +1,310 @@
+//! 内存访问基准测试
+//!
+//! 对比 DRAM、PSRAM (缓存命中路径) 与 PSRAM (强制未命中) 的访问代价:
+//! - 顺序读/写，多种步长 (4B 连续、32B 每行一次、128B 跨行)
+//! - 随机读 (按 cache line 指针追逐，测真实延迟)
+//! - 随机写 (伪随机 cache line)
+//!
+//! ESP32-S3 的 PSRAM 只能经 MMU + 数据 cache 访问，没有真正的非缓存窗口。
+//! `Region::PsramUncached` 在每次访问前对目标行执行 `dhwbi` (写回并失效)，
+//! 使每次访问都落到 PSRAM 上，结果包含该指令本身的几个周期。
+//!
+//! 使用 CPU 周期计数器测量，结果包含中断带来的噪声，建议在空闲的普通优先级任务中运行。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::mem::{bench, psram};
+//!
+//! static mut DRAM_BUF: [u32; 4096] = [0; 4096];
+//! let mut psram_buf = psram::alloc_array::<u32, 65536>()?;
+//!
+//! for result in bench::run(unsafe { &mut DRAM_BUF }, &mut psram_buf[..]) {
+//!     println!("{}", result);
+//! }
+//! ```
+
+use core::fmt;
+use core::ptr::{read_volatile, write_volatile};
+
+use heapless::Vec;
+
+use super::psram::cache;
+use crate::util::dsp::bench::measure;
+
+/// CPU 主频 (MHz)，与 `config::CPU_FREQ_HZ` 一致
+const CPU_MHZ: u64 = 240;
+
+/// cache line 大小 (字节)
+const LINE: usize = 32;
+
+/// 每项测试最多访问次数
+pub const MAX_ACCESSES: usize = 16384;
+
+/// 顺序访问步长 (字节)
+pub const STRIDES: [usize; 3] = [4, 32, 128];
+
+/// 全部结果数量
+pub const RESULTS: usize = 3 * (STRIDES.len() * 2 + 2);
+
+/// 内存区域
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    /// 内部 DRAM
+    Dram,
+    /// PSRAM，正常经 cache 访问
+    PsramCached,
+    /// PSRAM，每次访问前失效对应 cache line
+    PsramUncached,
+}
+
+impl Region {
+    /// 名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Dram => "dram",
+            Self::PsramCached => "psram-cached",
+            Self::PsramUncached => "psram-uncached",
+        }
+    }
+}
+
+/// 访问模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pattern {
+    /// 顺序读
+    SeqRead,
+    /// 顺序写
+    SeqWrite,
+    /// 随机读 (指针追逐)
+    RandRead,
+    /// 随机写
+    RandWrite,
+}
+
+impl Pattern {
+    /// 名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::SeqRead => "seq-read",
+            Self::SeqWrite => "seq-write",
+            Self::RandRead => "rand-read",
+            Self::RandWrite => "rand-write",
+        }
+    }
+}
+
+/// 单项测试结果
+#[derive(Debug, Clone, Copy)]
+pub struct MemBench {
+    /// 内存区域
+    pub region: Region,
+    /// 访问模式
+    pub pattern: Pattern,
+    /// 步长 (字节，随机模式为 cache line 大小)
+    pub stride: usize,
+    /// 访问次数 (每次 4 字节)
+    pub accesses: u32,
+    /// 总周期数
+    pub cycles: u32,
+}
+
+impl MemBench {
+    /// 平均每次访问耗时 (x10 ns，避免浮点格式化)
+    pub fn ns_per_access_x10(&self) -> u32 {
+        if self.accesses == 0 {
+            return 0;
+        }
+        (self.cycles as u64 * 10_000 / (CPU_MHZ * self.accesses as u64)) as u32
+    }
+
+    /// 有效带宽 (KiB/s，按实际读写的字节计)
+    pub fn bandwidth_kib_s(&self) -> u32 {
+        if self.cycles == 0 {
+            return 0;
+        }
+        let bytes = self.accesses as u64 * 4;
+        (bytes * CPU_MHZ * 1_000_000 / self.cycles as u64 / 1024) as u32
+    }
+}
+
+impl fmt::Display for MemBench {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ns = self.ns_per_access_x10();
+        write!(
+            f,
+            "{:<15} {:<10} stride {:>4}B  {:>5}.{} ns/access  {:>7} KiB/s",
+            self.region.name(),
+            self.pattern.name(),
+            self.stride,
+            ns / 10,
+            ns % 10,
+            self.bandwidth_kib_s()
+        )
+    }
+}
+
+// ===== 访问内核 =====
+
+#[inline(always)]
+fn evict(region: Region, ptr: *const u32) {
+    if region == Region::PsramUncached {
+        unsafe { cache::flush(ptr as *const u8, 4) };
+    }
+}
+
+fn seq_read(region: Region, buf: &[u32], step: usize) -> u32 {
+    let mut sum = 0u32;
+    for word in buf.iter().step_by(step).take(MAX_ACCESSES) {
+        evict(region, word);
+        sum = sum.wrapping_add(unsafe { read_volatile(word) });
+    }
+    sum
+}
+
+fn seq_write(region: Region, buf: &mut [u32], step: usize) {
+    for (i, word) in buf.iter_mut().step_by(step).take(MAX_ACCESSES).enumerate() {
+        evict(region, word);
+        unsafe { write_volatile(word, i as u32) };
+    }
+}
+
+fn rand_read(region: Region, buf: &[u32], accesses: usize) -> u32 {
+    let words = LINE / 4;
+    let mut slot = 0usize;
+    for _ in 0..accesses {
+        let word = &buf[slot * words];
+        evict(region, word);
+        slot = unsafe { read_volatile(word) } as usize;
+    }
+    slot as u32
+}
+
+fn rand_write(region: Region, buf: &mut [u32], accesses: usize) {
+    let words = LINE / 4;
+    let slots = buf.len() / words;
+    let mut rng = 0x2545_F491u32;
+    for i in 0..accesses {
+        rng = xorshift(rng);
+        let word = &mut buf[(rng as usize % slots) * words];
+        evict(region, word);
+        unsafe { write_volatile(word, i as u32) };
+    }
+}
+
+fn xorshift(mut x: u32) -> u32 {
+    x ^= x << 13;
+    x ^= x >> 17;
+    x ^= x << 5;
+    x
+}
+
+/// 构建指针追逐链: 每个 cache line 首字存放下一行序号，形成覆盖全部行的单一随机环
+/// (Sattolo 洗牌)，返回行数
+fn build_chain(buf: &mut [u32]) -> usize {
+    let words = LINE / 4;
+    let slots = buf.len() / words;
+    for i in 0..slots {
+        buf[i * words] = i as u32;
+    }
+    let mut rng = 0x9E37_79B9u32;
+    for i in (1..slots).rev() {
+        rng = xorshift(rng);
+        let j = rng as usize % i;
+        buf.swap(i * words, j * words);
+    }
+    slots
+}
+
+// ===== 运行 =====
+
+/// 对一个区域运行全部模式
+///
+/// `buf` 至少 2 个 cache line；为反映 PSRAM 真实代价，PSRAM 缓冲区应远大于
+/// 数据 cache (建议 ≥ 256KB)。
+pub fn run_region<const M: usize>(region: Region, buf: &mut [u32], results: &mut Vec<MemBench, M>) {
+    if buf.len() < 2 * LINE / 4 {
+        return;
+    }
+
+    for stride in STRIDES {
+        let step = stride / 4;
+        let accesses = buf.len().div_ceil(step).min(MAX_ACCESSES) as u32;
+
+        // 预热一遍，使缓存路径处于稳态
+        seq_read(region, buf, step);
+        let (_, cycles) = measure(|| seq_read(region, buf, step));
+        let _ = results.push(MemBench { region, pattern: Pattern::SeqRead, stride, accesses, cycles });
+
+        let (_, cycles) = measure(|| seq_write(region, buf, step));
+        let _ = results.push(MemBench { region, pattern: Pattern::SeqWrite, stride, accesses, cycles });
+    }
+
+    let slots = build_chain(buf);
+    let accesses = slots.min(MAX_ACCESSES);
+    if region == Region::PsramCached {
+        // 链已在 cache 中 (刚写入)，先整体写回失效以免测到命中
+        unsafe { cache::flush(buf.as_ptr() as *const u8, buf.len() * 4) };
+    }
+    let (_, cycles) = measure(|| rand_read(region, buf, accesses));
+    let _ = results.push(MemBench {
+        region,
+        pattern: Pattern::RandRead,
+        stride: LINE,
+        accesses: accesses as u32,
+        cycles,
+    });
+
+    let (_, cycles) = measure(|| rand_write(region, buf, accesses));
+    let _ = results.push(MemBench {
+        region,
+        pattern: Pattern::RandWrite,
+        stride: LINE,
+        accesses: accesses as u32,
+        cycles,
+    });
+}
+
+/// 运行全部区域 (DRAM、PSRAM 缓存、PSRAM 未命中)
+pub fn run(dram: &mut [u32], psram: &mut [u32]) -> Vec<MemBench, RESULTS> {
+    let mut results = Vec::new();
+    run_region(Region::Dram, dram, &mut results);
+    run_region(Region::PsramCached, psram, &mut results);
+    run_region(Region::PsramUncached, psram, &mut results);
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_is_single_cycle() {
+        let mut buf = [0u32; 64 * 8];
+        let slots = build_chain(&mut buf);
+        assert_eq!(slots, 64);
+
+        let mut seen = [false; 64];
+        let mut slot = 0usize;
+        for _ in 0..slots {
+            assert!(!seen[slot]);
+            seen[slot] = true;
+            slot = buf[slot * 8] as usize;
+        }
+        assert_eq!(slot, 0);
+    }
+
+    #[test]
+    fn test_report_units() {
+        let result = MemBench {
+            region: Region::Dram,
+            pattern: Pattern::SeqRead,
+            stride: 4,
+            accesses: 1000,
+            cycles: 2400,
+        };
+        // 2.4 周期/次 @ 240MHz = 10ns
+        assert_eq!(result.ns_per_access_x10(), 100);
+        assert_eq!(result.bandwidth_kib_s(), 4_000_000 / 10 * 1000 / 1024);
+    }
+}
@@ -7,7 +7,9 @@
 //! - 32 字节对齐 (DMA 和 cache line 要求)
 //! - 自动策略选择: 小缓冲区用 DRAM，大缓冲区可用 PSRAM + bounce buffer
 //! - Cache 一致性操作封装
+//! - `try_*` 访问接口: DMA 进行中返回 `DmaBusy` 而不是 panic
 //! - 与 esp-hal DMA traits 集成
+//! - GDMA 通道运行时分配 (`ChannelAllocator`，按优先级排队，租约 drop 时归还)
 //!
 //! # DMA 限制
 //!
@@ -31,14 +33,30 @@
 //! buf.prepare_for_dma_write();
 //! // ... DMA 写入 ...
 //! buf.complete_dma_write();
+//!
+//! // 通道分配: 启动时登记全部 GDMA 通道，各驱动按需租用
+//! static DMA: GdmaAllocator = ChannelAllocator::new();
+//! DMA.add(peripherals.DMA_CH0.degrade()).ok();
+//! DMA.add(peripherals.DMA_CH1.degrade()).ok();
+//!
+//! let mut lease = DMA.acquire(DmaUser::Spi, DmaPriority::High).await;
+//! let spi = Spi::new(peripherals.SPI2, config)?.with_dma(lease.reborrow());
+//! // lease drop 时通道归还，等待中的最高优先级请求被唤醒
 //! ```
 
-use core::cell::UnsafeCell;
+use core::cell::{RefCell, UnsafeCell};
+use core::fmt;
+use core::future::poll_fn;
 use core::marker::PhantomData;
-use core::mem::MaybeUninit;
+use core::mem::{ManuallyDrop, MaybeUninit};
 use core::ops::{Deref, DerefMut};
 use core::ptr::NonNull;
 use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::Poll;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_sync::waitqueue::MultiWakerRegistration;
 
 use crate::mem::psram;
 
@@ -72,6 +90,16 @@ pub enum DmaState {
     DmaWriting,
 }
 
+/// DMA 正在进行，缓冲区暂不可被 CPU 访问
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DmaBusy;
+
+impl fmt::Display for DmaBusy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DMA transfer in progress")
+    }
+}
+
 /// DMA 缓冲区对齐要求
 pub const DMA_ALIGNMENT: usize = 32;
 
@@ -133,36 +161,64 @@ impl<const SIZE: usize> DmaBuffer<SIZE> {
         self.state.load(Ordering::Acquire)
     }
     
-    /// 获取数据指针 (只在 DMA 非活跃时安全)
+    /// 获取数据指针
     ///
-    /// # Panics
-    ///
-    /// 如果 DMA 正在进行会 panic
-    pub fn as_ptr(&self) -> *const u8 {
-        assert!(!self.is_dma_active(), "Cannot access buffer during DMA");
-        self.data.get() as *const u8
+    /// # 错误
+    /// DMA 正在进行时返回 `DmaBusy`
+    pub fn try_as_ptr(&self) -> Result<*const u8, DmaBusy> {
+        self.ensure_idle()?;
+        Ok(self.data.get() as *const u8)
     }
     
-    /// 获取可变数据指针 (只在 DMA 非活跃时安全)
-    ///
-    /// # Panics
+    /// 获取可变数据指针
     ///
-    /// 如果 DMA 正在进行会 panic
-    pub fn as_mut_ptr(&mut self) -> *mut u8 {
-        assert!(!self.is_dma_active(), "Cannot access buffer during DMA");
-        self.data.get() as *mut u8
+    /// # 错误
+    /// DMA 正在进行时返回 `DmaBusy`
+    pub fn try_as_mut_ptr(&mut self) -> Result<*mut u8, DmaBusy> {
+        self.ensure_idle()?;
+        Ok(self.data.get() as *mut u8)
     }
     
     /// 获取数据切片
-    pub fn as_slice(&self) -> &[u8] {
-        assert!(!self.is_dma_active(), "Cannot access buffer during DMA");
-        unsafe { &*self.data.get() }
+    ///
+    /// # 错误
+    /// DMA 正在进行时返回 `DmaBusy`
+    pub fn try_as_slice(&self) -> Result<&[u8], DmaBusy> {
+        self.ensure_idle()?;
+        Ok(unsafe { &*self.data.get() })
     }
     
     /// 获取可变数据切片
-    pub fn as_mut_slice(&mut self) -> &mut [u8] {
-        assert!(!self.is_dma_active(), "Cannot access buffer during DMA");
-        unsafe { &mut *self.data.get() }
+    ///
+    /// # 错误
+    /// DMA 正在进行时返回 `DmaBusy`
+    pub fn try_as_mut_slice(&mut self) -> Result<&mut [u8], DmaBusy> {
+        self.ensure_idle()?;
+        Ok(unsafe { &mut *self.data.get() })
+    }
+    
+    /// 填充缓冲区
+    pub fn try_fill(&mut self, value: u8) -> Result<(), DmaBusy> {
+        self.try_as_mut_slice()?.fill(value);
+        Ok(())
+    }
+    
+    /// 从切片复制数据，返回复制的字节数
+    pub fn try_copy_from_slice(&mut self, src: &[u8]) -> Result<usize, DmaBusy> {
+        let len = src.len().min(SIZE);
+        self.try_as_mut_slice()?[..len].copy_from_slice(&src[..len]);
+        Ok(len)
+    }
+    
+    /// 复制数据到切片，返回复制的字节数
+    pub fn try_copy_to_slice(&self, dst: &mut [u8]) -> Result<usize, DmaBusy> {
+        let len = dst.len().min(SIZE);
+        dst[..len].copy_from_slice(&self.try_as_slice()?[..len]);
+        Ok(len)
+    }
+    
+    fn ensure_idle(&self) -> Result<(), DmaBusy> {
+        if self.is_dma_active() { Err(DmaBusy) } else { Ok(()) }
     }
     
     /// 准备 DMA 读取 (外设将读取此缓冲区)
@@ -211,28 +267,61 @@ impl<const SIZE: usize> DmaBuffer<SIZE> {
         // 标记 DMA 完成
         self.state.store(false, Ordering::Release);
     }
+}
+
+/// 直接访问接口: DMA 进行中访问会 panic (`panic-free` feature 下不提供)
+#[cfg(not(feature = "panic-free"))]
+impl<const SIZE: usize> DmaBuffer<SIZE> {
+    /// 获取数据指针 (只在 DMA 非活跃时安全)
+    ///
+    /// # Panics
+    ///
+    /// 如果 DMA 正在进行会 panic，不可 panic 的代码使用 `try_as_ptr`
+    pub fn as_ptr(&self) -> *const u8 {
+        busy_panic(self.try_as_ptr())
+    }
+    
+    /// 获取可变数据指针 (只在 DMA 非活跃时安全)
+    ///
+    /// # Panics
+    ///
+    /// 如果 DMA 正在进行会 panic，不可 panic 的代码使用 `try_as_mut_ptr`
+    pub fn as_mut_ptr(&mut self) -> *mut u8 {
+        busy_panic(self.try_as_mut_ptr())
+    }
+    
+    /// 获取数据切片
+    pub fn as_slice(&self) -> &[u8] {
+        busy_panic(self.try_as_slice())
+    }
+    
+    /// 获取可变数据切片
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        busy_panic(self.try_as_mut_slice())
+    }
     
     /// 填充缓冲区
     pub fn fill(&mut self, value: u8) {
-        assert!(!self.is_dma_active(), "Cannot access buffer during DMA");
-        let slice = unsafe { &mut *self.data.get() };
-        slice.fill(value);
+        busy_panic(self.try_fill(value))
     }
     
     /// 从切片复制数据
     pub fn copy_from_slice(&mut self, src: &[u8]) {
-        assert!(!self.is_dma_active(), "Cannot access buffer during DMA");
-        let len = src.len().min(SIZE);
-        let slice = unsafe { &mut *self.data.get() };
-        slice[..len].copy_from_slice(&src[..len]);
+        busy_panic(self.try_copy_from_slice(src));
     }
     
     /// 复制数据到切片
     pub fn copy_to_slice(&self, dst: &mut [u8]) {
-        assert!(!self.is_dma_active(), "Cannot access buffer during DMA");
-        let len = dst.len().min(SIZE);
-        let slice = unsafe { &*self.data.get() };
-        dst[..len].copy_from_slice(&slice[..len]);
+        busy_panic(self.try_copy_to_slice(dst));
+    }
+}
+
+#[cfg(not(feature = "panic-free"))]
+#[track_caller]
+fn busy_panic<T>(result: Result<T, DmaBusy>) -> T {
+    match result {
+        Ok(value) => value,
+        Err(_) => panic!("Cannot access buffer during DMA"),
     }
 }
 
@@ -326,12 +415,380 @@ impl<const SIZE: usize> DmaBufferBuilder<SIZE> {
     pub fn build(self) -> DmaBuffer<SIZE> {
         let mut buf = DmaBuffer::new(self.strategy);
         if let Some(value) = self.prefill {
-            buf.fill(value);
+            // 新建缓冲区没有进行中的 DMA
+            let _ = buf.try_fill(value);
         }
         buf
     }
 }
 
+/// DMA 双缓冲 (乒乓缓冲)
+///
+/// 外设写入一半的同时，消费者处理另一半已完成的数据。
+/// 消费者未及时取走时，新提交的数据覆盖旧数据并计入溢出次数。
+///
+/// # 示例
+///
+/// ```rust,ignore
+/// let mut pingpong = DmaDoubleBuffer::<1024>::new(DmaStrategy::ForceDram);
+///
+/// // 生产者 (DMA 完成中断或捕获任务)
+/// rx.pop(pingpong.write_half().as_mut_slice()).await?;
+/// pingpong.commit();
+///
+/// // 消费者
+/// if let Some(frame) = pingpong.take_ready() {
+///     encode(frame);
+/// }
+/// ```
+pub struct DmaDoubleBuffer<const SIZE: usize> {
+    halves: [DmaBuffer<SIZE>; 2],
+    /// 当前写入的一半
+    write: usize,
+    /// 待消费的一半
+    ready: Option<usize>,
+    /// 溢出次数
+    overruns: u32,
+    /// 已提交次数
+    commits: u32,
+}
+
+impl<const SIZE: usize> DmaDoubleBuffer<SIZE> {
+    /// 创建双缓冲
+    pub const fn new(strategy: DmaStrategy) -> Self {
+        Self {
+            halves: [DmaBuffer::new(strategy), DmaBuffer::new(strategy)],
+            write: 0,
+            ready: None,
+            overruns: 0,
+            commits: 0,
+        }
+    }
+
+    /// 当前写入目标
+    pub fn write_half(&mut self) -> &mut DmaBuffer<SIZE> {
+        &mut self.halves[self.write]
+    }
+
+    /// 提交写入的一半并切换
+    ///
+    /// # 返回
+    /// 上一帧未被消费 (发生溢出) 时返回 `false`
+    pub fn commit(&mut self) -> bool {
+        let overrun = self.ready.is_some();
+        if overrun {
+            self.overruns = self.overruns.wrapping_add(1);
+        }
+        self.ready = Some(self.write);
+        self.write ^= 1;
+        self.commits = self.commits.wrapping_add(1);
+        !overrun
+    }
+
+    /// 取走已完成的一半 (该半区仍在 DMA 中时保留待消费状态并返回 `None`)
+    pub fn take_ready(&mut self) -> Option<&[u8]> {
+        let index = self.ready?;
+        let data = self.halves[index].try_as_slice().ok()?;
+        self.ready = None;
+        Some(data)
+    }
+
+    /// 是否有待消费数据
+    pub fn has_ready(&self) -> bool {
+        self.ready.is_some()
+    }
+
+    /// 溢出次数
+    pub fn overruns(&self) -> u32 {
+        self.overruns
+    }
+
+    /// 已提交次数
+    pub fn commits(&self) -> u32 {
+        self.commits
+    }
+}
+
+// ===== GDMA 通道分配 =====
+
+/// ESP32-S3 GDMA 通道数
+pub const GDMA_CHANNELS: usize = 5;
+
+/// 同时等待通道的最大任务数 (超出时等待者会被多唤醒一次，不影响正确性)
+pub const MAX_DMA_WAITERS: usize = 8;
+
+/// 通道使用者
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DmaUser {
+    /// SPI 主/从机
+    Spi,
+    /// I2S 音频
+    I2s,
+    /// 摄像头 (LCD_CAM 输入)
+    Camera,
+    /// LCD (LCD_CAM 输出)
+    Lcd,
+    /// UART (UHCI)
+    Uart,
+    /// 内存到内存拷贝
+    MemCopy,
+    /// 其他
+    Other,
+}
+
+/// 通道请求优先级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum DmaPriority {
+    /// 低 (后台拷贝)
+    Low,
+    /// 普通
+    #[default]
+    Normal,
+    /// 高 (音频、摄像头等实时流)
+    High,
+}
+
+impl DmaPriority {
+    const COUNT: usize = 3;
+}
+
+/// 通道分配统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DmaChannelStats {
+    /// 成功分配次数
+    pub grants: u32,
+    /// 需要排队等待的分配次数
+    pub contended: u32,
+    /// 同时占用的通道数峰值
+    pub peak_in_use: u8,
+}
+
+/// 分配器状态
+struct AllocState<C, const N: usize> {
+    /// 空闲通道 (`None` 表示未登记或已借出)
+    slots: [Option<C>; N],
+    /// 已借出通道的使用者
+    owners: [Option<DmaUser>; N],
+    /// 已登记通道数
+    registered: usize,
+    /// 各优先级正在等待的请求数
+    waiting: [usize; DmaPriority::COUNT],
+    wakers: MultiWakerRegistration<MAX_DMA_WAITERS>,
+    stats: DmaChannelStats,
+}
+
+impl<C, const N: usize> AllocState<C, N> {
+    /// 该优先级可以取得的空闲通道 (有更高优先级请求在等待时让路)
+    fn free_slot(&self, priority: DmaPriority) -> Option<usize> {
+        if self.waiting[priority as usize + 1..].iter().any(|&n| n > 0) {
+            return None;
+        }
+        self.slots.iter().position(Option::is_some)
+    }
+
+    fn grant(&mut self, index: usize, user: DmaUser) -> Option<C> {
+        let channel = self.slots[index].take()?;
+        self.owners[index] = Some(user);
+        self.stats.grants = self.stats.grants.saturating_add(1);
+        let in_use = self.owners.iter().filter(|o| o.is_some()).count() as u8;
+        self.stats.peak_in_use = self.stats.peak_in_use.max(in_use);
+        Some(channel)
+    }
+}
+
+/// GDMA 通道分配器
+///
+/// 启动时用 `add` 登记通道，之后 SPI、I2S、摄像头、内存拷贝等使用者通过
+/// `acquire` 租用；没有空闲通道时按优先级排队 (同级不保证顺序)，
+/// 有更高优先级请求等待时低优先级请求不会抢先取得归还的通道。
+pub struct ChannelAllocator<C, const N: usize = GDMA_CHANNELS> {
+    state: BlockingMutex<CriticalSectionRawMutex, RefCell<AllocState<C, N>>>,
+}
+
+/// esp-hal GDMA 通道分配器
+pub type GdmaAllocator = ChannelAllocator<esp_hal::dma::AnyGdmaChannel<'static>, GDMA_CHANNELS>;
+
+impl<C, const N: usize> ChannelAllocator<C, N> {
+    /// 创建空分配器
+    pub const fn new() -> Self {
+        Self {
+            state: BlockingMutex::new(RefCell::new(AllocState {
+                slots: [const { None }; N],
+                owners: [None; N],
+                registered: 0,
+                waiting: [0; DmaPriority::COUNT],
+                wakers: MultiWakerRegistration::new(),
+                stats: DmaChannelStats { grants: 0, contended: 0, peak_in_use: 0 },
+            })),
+        }
+    }
+
+    /// 登记通道，返回通道编号 (已满时退回通道)
+    pub fn add(&self, channel: C) -> Result<usize, C> {
+        self.state.lock(|state| {
+            let mut state = state.borrow_mut();
+            let index = state.registered;
+            if index >= N {
+                return Err(channel);
+            }
+            state.slots[index] = Some(channel);
+            state.registered += 1;
+            state.wakers.wake();
+            Ok(index)
+        })
+    }
+
+    /// 异步租用通道
+    ///
+    /// 没有登记任何通道时会一直等待
+    pub async fn acquire(&self, user: DmaUser, priority: DmaPriority) -> DmaLease<'_, C, N> {
+        let mut waiting = Waiting { allocator: self, priority, queued: false };
+        let (index, channel) = poll_fn(|cx| {
+            self.state.lock(|state| {
+                let mut state = state.borrow_mut();
+                if let Some(index) = state.free_slot(priority) {
+                    if waiting.queued {
+                        state.waiting[priority as usize] -= 1;
+                        state.stats.contended = state.stats.contended.saturating_add(1);
+                        waiting.queued = false;
+                    }
+                    if let Some(channel) = state.grant(index, user) {
+                        return Poll::Ready((index, channel));
+                    }
+                }
+                if !waiting.queued {
+                    state.waiting[priority as usize] += 1;
+                    waiting.queued = true;
+                }
+                state.wakers.register(cx.waker());
+                Poll::Pending
+            })
+        })
+        .await;
+        DmaLease { allocator: self, index, user, priority, channel: ManuallyDrop::new(channel) }
+    }
+
+    /// 尝试租用通道 (不等待)
+    ///
+    /// 没有空闲通道或有更高优先级请求在等待时返回 `None`
+    pub fn try_acquire(&self, user: DmaUser, priority: DmaPriority) -> Option<DmaLease<'_, C, N>> {
+        self.state.lock(|state| {
+            let mut state = state.borrow_mut();
+            let index = state.free_slot(priority)?;
+            let channel = state.grant(index, user)?;
+            Some(DmaLease { allocator: self, index, user, priority, channel: ManuallyDrop::new(channel) })
+        })
+    }
+
+    /// 已登记通道数
+    pub fn capacity(&self) -> usize {
+        self.state.lock(|state| state.borrow().registered)
+    }
+
+    /// 空闲通道数
+    pub fn available(&self) -> usize {
+        self.state.lock(|state| state.borrow().slots.iter().filter(|s| s.is_some()).count())
+    }
+
+    /// 通道当前的使用者
+    pub fn owner(&self, index: usize) -> Option<DmaUser> {
+        self.state.lock(|state| state.borrow().owners.get(index).copied().flatten())
+    }
+
+    /// 正在等待的请求数
+    pub fn waiting(&self) -> usize {
+        self.state.lock(|state| state.borrow().waiting.iter().sum())
+    }
+
+    /// 分配统计
+    pub fn stats(&self) -> DmaChannelStats {
+        self.state.lock(|state| state.borrow().stats)
+    }
+
+    fn release(&self, index: usize, channel: C) {
+        self.state.lock(|state| {
+            let mut state = state.borrow_mut();
+            state.slots[index] = Some(channel);
+            state.owners[index] = None;
+            state.wakers.wake();
+        });
+    }
+}
+
+impl<C, const N: usize> Default for ChannelAllocator<C, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 等待中的租用请求 (future 被取消时撤销排队计数)
+struct Waiting<'a, C, const N: usize> {
+    allocator: &'a ChannelAllocator<C, N>,
+    priority: DmaPriority,
+    queued: bool,
+}
+
+impl<C, const N: usize> Drop for Waiting<'_, C, N> {
+    fn drop(&mut self) {
+        if self.queued {
+            self.allocator.state.lock(|state| {
+                let mut state = state.borrow_mut();
+                state.waiting[self.priority as usize] -= 1;
+                // 低优先级请求可能一直在等这个请求
+                state.wakers.wake();
+            });
+        }
+    }
+}
+
+/// 通道租约，drop 时归还通道
+pub struct DmaLease<'a, C, const N: usize = GDMA_CHANNELS> {
+    allocator: &'a ChannelAllocator<C, N>,
+    index: usize,
+    user: DmaUser,
+    priority: DmaPriority,
+    channel: ManuallyDrop<C>,
+}
+
+impl<C, const N: usize> DmaLease<'_, C, N> {
+    /// 通道编号
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// 使用者
+    pub fn user(&self) -> DmaUser {
+        self.user
+    }
+
+    /// 请求优先级
+    pub fn priority(&self) -> DmaPriority {
+        self.priority
+    }
+}
+
+impl<C, const N: usize> Deref for DmaLease<'_, C, N> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        &self.channel
+    }
+}
+
+impl<C, const N: usize> DerefMut for DmaLease<'_, C, N> {
+    fn deref_mut(&mut self) -> &mut C {
+        &mut self.channel
+    }
+}
+
+impl<C, const N: usize> Drop for DmaLease<'_, C, N> {
+    fn drop(&mut self) {
+        // Safety: drop 之后不再访问 `channel`
+        let channel = unsafe { ManuallyDrop::take(&mut self.channel) };
+        self.allocator.release(self.index, channel);
+    }
+}
+
 /// 计算对齐后的大小
 pub const fn aligned_size(size: usize, alignment: usize) -> usize {
     (size + alignment - 1) & !(alignment - 1)
@@ -386,10 +843,60 @@ mod tests {
         assert_eq!(aligned_size(1, 32), 32);
     }
     
+    #[test]
+    fn test_double_buffer_overrun() {
+        let mut db = DmaDoubleBuffer::<4>::new(DmaStrategy::ForceDram);
+        db.write_half().try_copy_from_slice(&[1, 1, 1, 1]).unwrap();
+        assert!(db.commit());
+        db.write_half().try_copy_from_slice(&[2, 2, 2, 2]).unwrap();
+        assert!(!db.commit());
+        assert_eq!(db.take_ready(), Some(&[2u8, 2, 2, 2][..]));
+        assert_eq!(db.take_ready(), None);
+        assert_eq!(db.overruns(), 1);
+    }
+
+    #[test]
+    fn test_busy_access() {
+        let mut buf = DmaBuffer::<8>::new(DmaStrategy::ForceDram);
+        assert_eq!(buf.try_copy_from_slice(&[7; 16]), Ok(8));
+        buf.prepare_for_dma_read();
+        assert_eq!(buf.try_as_slice(), Err(DmaBusy));
+        assert_eq!(buf.try_fill(0), Err(DmaBusy));
+        buf.complete_dma_read();
+        assert_eq!(buf.try_as_slice(), Ok(&[7u8; 8][..]));
+    }
+
     #[test]
     fn test_dma_buffer_size() {
         let buf = DmaBuffer::<1024>::new_auto();
         assert_eq!(buf.size(), 1024);
         assert_eq!(buf.alignment(), 32);
     }
+
+    #[test]
+    fn test_channel_priority_gate() {
+        let dma: ChannelAllocator<u8, 2> = ChannelAllocator::new();
+        assert_eq!(dma.add(10), Ok(0));
+        assert_eq!(dma.add(11), Ok(1));
+        assert_eq!(dma.add(12), Err(12));
+
+        let spi = dma.try_acquire(DmaUser::Spi, DmaPriority::Normal).unwrap();
+        assert_eq!((*spi, spi.index()), (10, 0));
+        assert_eq!(dma.owner(0), Some(DmaUser::Spi));
+
+        // 高优先级请求排队时普通请求让路
+        dma.state.lock(|s| s.borrow_mut().waiting[DmaPriority::High as usize] = 1);
+        assert!(dma.try_acquire(DmaUser::MemCopy, DmaPriority::Normal).is_none());
+        let i2s = dma.try_acquire(DmaUser::I2s, DmaPriority::High).unwrap();
+        assert_eq!(*i2s, 11);
+        dma.state.lock(|s| s.borrow_mut().waiting[DmaPriority::High as usize] = 0);
+
+        assert!(dma.try_acquire(DmaUser::MemCopy, DmaPriority::High).is_none());
+        drop(spi);
+        assert_eq!((dma.available(), dma.owner(0)), (1, None));
+        assert_eq!(*dma.try_acquire(DmaUser::MemCopy, DmaPriority::Low).unwrap(), 10);
+        assert_eq!(dma.available(), 1);
+        assert_eq!(dma.stats().grants, 3);
+        assert_eq!(dma.stats().peak_in_use, 2);
+    }
 }
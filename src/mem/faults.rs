@@ -0,0 +1,330 @@
+//! 分配失败注入 (`alloc-faults` feature)
+//!
+//! 让内存池、PSRAM 与堆分配器按计划返回失败，使 `PoolFull` / `OutOfMemory`
+//! 等错误路径在硬件 CI 中也被执行，而不是只跑正常路径:
+//! - `Nth`: 布防后第 N 次分配失败 (只失败一次)
+//! - `EveryNth`: 每第 N 次分配失败
+//! - `Random`: 按种子伪随机，平均每 `one_in` 次失败一次 (同一种子可复现)
+//! - `Sites` 选择受影响的分配器，计数只统计被选中的分配器
+//!
+//! 内存池与 PSRAM 的注入点已内置；堆分配需把 esp-alloc 包装为 `FaultyHeap`
+//! 作为全局分配器 (并关闭 esp-alloc 的 `global-allocator` feature)。
+//! 状态全部为原子量，可在中断和分配器内部调用。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::mem::faults::{FaultPlan, FaultyHeap, Sites, FAULTS};
+//!
+//! #[global_allocator]
+//! static ALLOCATOR: FaultyHeap<esp_alloc::EspHeap> = FaultyHeap::new(&esp_alloc::HEAP);
+//!
+//! // 第 3 次池分配失败，验证网络缓冲池耗尽时的降级
+//! FAULTS.arm(FaultPlan::nth(3).with_sites(Sites::POOL));
+//! run_scenario().await;
+//! assert_eq!(FAULTS.stats().injected, 1);
+//!
+//! // 可复现的随机失败 (约 5%)
+//! FAULTS.arm(FaultPlan::random(0xC0FFEE, 20));
+//! ```
+
+#[cfg(feature = "production")]
+compile_error!("feature `alloc-faults` is for test builds and must not be combined with `production`");
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::ops::BitOr;
+use core::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+
+// ===== 计划 =====
+
+/// 分配器集合
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Sites(pub u8);
+
+impl Sites {
+    /// 空集合
+    pub const NONE: Sites = Sites(0);
+    /// `MemoryPool`
+    pub const POOL: Sites = Sites(1 << 0);
+    /// PSRAM 分配
+    pub const PSRAM: Sites = Sites(1 << 1);
+    /// 全局堆 (`FaultyHeap`)
+    pub const HEAP: Sites = Sites(1 << 2);
+    /// 全部分配器
+    pub const ALL: Sites = Sites(0x07);
+
+    /// 是否包含全部指定分配器
+    pub const fn contains(self, other: Sites) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for Sites {
+    type Output = Sites;
+
+    fn bitor(self, rhs: Sites) -> Sites {
+        Sites(self.0 | rhs.0)
+    }
+}
+
+/// 失败模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultMode {
+    /// 不注入
+    Off,
+    /// 第 N 次分配失败 (从 1 开始，只失败一次)
+    Nth(u32),
+    /// 每第 N 次分配失败
+    EveryNth(u32),
+    /// 伪随机，平均每 `one_in` 次失败一次
+    Random {
+        /// 种子 (0 按 1 处理)
+        seed: u32,
+        /// 失败概率的倒数
+        one_in: u32,
+    },
+}
+
+/// 注入计划
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FaultPlan {
+    /// 失败模式
+    pub mode: FaultMode,
+    /// 受影响的分配器
+    pub sites: Sites,
+}
+
+impl FaultPlan {
+    /// 第 `n` 次分配失败 (全部分配器)
+    pub const fn nth(n: u32) -> Self {
+        Self { mode: FaultMode::Nth(n), sites: Sites::ALL }
+    }
+
+    /// 每第 `n` 次分配失败 (全部分配器)
+    pub const fn every(n: u32) -> Self {
+        Self { mode: FaultMode::EveryNth(n), sites: Sites::ALL }
+    }
+
+    /// 按种子伪随机失败 (全部分配器)
+    pub const fn random(seed: u32, one_in: u32) -> Self {
+        Self { mode: FaultMode::Random { seed, one_in }, sites: Sites::ALL }
+    }
+
+    /// 设置受影响的分配器
+    pub const fn with_sites(mut self, sites: Sites) -> Self {
+        self.sites = sites;
+        self
+    }
+}
+
+/// 注入统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FaultStats {
+    /// 布防后被选中分配器的分配次数
+    pub checked: u32,
+    /// 注入的失败次数
+    pub injected: u32,
+}
+
+// ===== 注入器 =====
+
+const MODE_OFF: u8 = 0;
+const MODE_NTH: u8 = 1;
+const MODE_EVERY: u8 = 2;
+const MODE_RANDOM: u8 = 3;
+
+/// 失败注入器
+pub struct FaultInjector {
+    mode: AtomicU8,
+    sites: AtomicU8,
+    param: AtomicU32,
+    rng: AtomicU32,
+    checked: AtomicU32,
+    injected: AtomicU32,
+}
+
+/// 全局注入器 (内置注入点使用)
+pub static FAULTS: FaultInjector = FaultInjector::new();
+
+impl FaultInjector {
+    /// 创建 (未布防)
+    pub const fn new() -> Self {
+        Self {
+            mode: AtomicU8::new(MODE_OFF),
+            sites: AtomicU8::new(0),
+            param: AtomicU32::new(0),
+            rng: AtomicU32::new(1),
+            checked: AtomicU32::new(0),
+            injected: AtomicU32::new(0),
+        }
+    }
+
+    /// 布防 (清零计数)
+    ///
+    /// 应在待测场景开始前调用；布防过程中并发的分配可能按旧计划判定
+    pub fn arm(&self, plan: FaultPlan) {
+        self.mode.store(MODE_OFF, Ordering::Release);
+        let (mode, param) = match plan.mode {
+            FaultMode::Off => (MODE_OFF, 0),
+            FaultMode::Nth(n) => (MODE_NTH, n),
+            FaultMode::EveryNth(n) => (MODE_EVERY, n.max(1)),
+            FaultMode::Random { seed, one_in } => {
+                self.rng.store(seed.max(1), Ordering::Relaxed);
+                (MODE_RANDOM, one_in.max(1))
+            }
+        };
+        self.param.store(param, Ordering::Relaxed);
+        self.sites.store(plan.sites.0, Ordering::Relaxed);
+        self.checked.store(0, Ordering::Relaxed);
+        self.injected.store(0, Ordering::Relaxed);
+        self.mode.store(mode, Ordering::Release);
+    }
+
+    /// 撤防 (保留统计)
+    pub fn disarm(&self) {
+        self.mode.store(MODE_OFF, Ordering::Release);
+    }
+
+    /// 是否已布防
+    pub fn is_armed(&self) -> bool {
+        self.mode.load(Ordering::Acquire) != MODE_OFF
+    }
+
+    /// 统计
+    pub fn stats(&self) -> FaultStats {
+        FaultStats {
+            checked: self.checked.load(Ordering::Relaxed),
+            injected: self.injected.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 分配器在分配前调用，返回 `true` 时应按分配失败处理
+    pub fn should_fail(&self, site: Sites) -> bool {
+        let mode = self.mode.load(Ordering::Acquire);
+        if mode == MODE_OFF || !Sites(self.sites.load(Ordering::Relaxed)).contains(site) {
+            return false;
+        }
+        let count = self.checked.fetch_add(1, Ordering::Relaxed).wrapping_add(1);
+        let param = self.param.load(Ordering::Relaxed);
+        let fail = match mode {
+            MODE_NTH => count == param,
+            MODE_EVERY => count.is_multiple_of(param),
+            MODE_RANDOM => self.next_random().is_multiple_of(param),
+            _ => false,
+        };
+        if fail {
+            self.injected.fetch_add(1, Ordering::Relaxed);
+        }
+        fail
+    }
+
+    /// xorshift32
+    fn next_random(&self) -> u32 {
+        let step = |mut x: u32| {
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            x
+        };
+        let previous = self.rng.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |x| Some(step(x)));
+        step(previous.unwrap_or(1))
+    }
+}
+
+impl Default for FaultInjector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 内置注入点使用的全局判定
+#[inline]
+pub fn inject(site: Sites) -> bool {
+    FAULTS.should_fail(site)
+}
+
+// ===== 堆包装 =====
+
+/// 带失败注入的全局分配器包装 (`alloc`/`alloc_zeroed`/`realloc` 注入，`dealloc` 原样转发)
+pub struct FaultyHeap<A: GlobalAlloc + 'static> {
+    inner: &'static A,
+}
+
+impl<A: GlobalAlloc + 'static> FaultyHeap<A> {
+    /// 包装底层分配器
+    pub const fn new(inner: &'static A) -> Self {
+        Self { inner }
+    }
+}
+
+unsafe impl<A: GlobalAlloc + Sync + 'static> GlobalAlloc for FaultyHeap<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if inject(Sites::HEAP) {
+            return core::ptr::null_mut();
+        }
+        unsafe { self.inner.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        if inject(Sites::HEAP) {
+            return core::ptr::null_mut();
+        }
+        unsafe { self.inner.alloc_zeroed(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { self.inner.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if inject(Sites::HEAP) {
+            return core::ptr::null_mut();
+        }
+        unsafe { self.inner.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failures(injector: &FaultInjector, site: Sites, n: usize) -> [bool; 8] {
+        let mut out = [false; 8];
+        for slot in out.iter_mut().take(n) {
+            *slot = injector.should_fail(site);
+        }
+        out
+    }
+
+    #[test]
+    fn test_nth_and_every() {
+        let injector = FaultInjector::new();
+        assert!(!injector.should_fail(Sites::POOL));
+
+        injector.arm(FaultPlan::nth(3).with_sites(Sites::POOL));
+        // 未选中的分配器不计数
+        assert!(!injector.should_fail(Sites::PSRAM));
+        assert_eq!(failures(&injector, Sites::POOL, 8), [false, false, true, false, false, false, false, false]);
+        assert_eq!(injector.stats(), FaultStats { checked: 8, injected: 1 });
+
+        injector.arm(FaultPlan::every(3));
+        assert_eq!(failures(&injector, Sites::HEAP, 7)[..7], [false, false, true, false, false, true, false]);
+
+        injector.disarm();
+        assert!(!injector.should_fail(Sites::HEAP));
+        assert_eq!(injector.stats().injected, 2);
+    }
+
+    #[test]
+    fn test_random_is_reproducible() {
+        let injector = FaultInjector::new();
+        let mut runs = [[false; 8]; 2];
+        for run in runs.iter_mut() {
+            injector.arm(FaultPlan::random(42, 2));
+            *run = failures(&injector, Sites::PSRAM, 8);
+        }
+        assert_eq!(runs[0], runs[1]);
+        assert!(runs[0].iter().any(|&f| f));
+        assert!(runs[0].iter().any(|&f| !f));
+    }
+}
@@ -0,0 +1,357 @@
+//! Flash 资源分页缓存
+//!
+//! 将只读 Flash 区域 (模型、字库) 按页调入 PSRAM 中的 LRU 缓存，
+//! 介于直接 XIP 读取 (慢且与代码争用 Cache) 和整体复制到 RAM (占用大) 之间。
+//!
+//! # 特性
+//! - 固定页大小、固定帧数，无动态分配
+//! - LRU 淘汰，支持固定页 (常驻，不被淘汰)
+//! - 跨页读取
+//! - 命中/未命中/淘汰统计
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::mem::flashcache::{FlashCache, MappedFlash};
+//!
+//! // 64 帧 × 4KB = 256KB PSRAM
+//! let mut cache = FlashCache::<64, 4096>::in_psram()?;
+//! let mut model = MappedFlash::new(0x0050_0000, 900 * 1024);
+//!
+//! cache.pin(&mut model, 0)?;               // 模型头常驻
+//! cache.read(&mut model, 8192, &mut weights)?;
+//! ```
+
+use core::fmt;
+
+use super::psram::{PsramBox, PsramConfig, PsramError};
+
+/// Flash 数据总线映射基地址
+const FLASH_DATA_BASE: usize = 0x3C00_0000;
+
+/// 缓存错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlashCacheError {
+    /// 读取超出源区域
+    OutOfBounds,
+    /// 所有帧均被固定，无法换入新页
+    AllPinned,
+    /// 页源读取失败
+    Source,
+    /// PSRAM 分配失败
+    Alloc(PsramError),
+}
+
+impl fmt::Display for FlashCacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfBounds => write!(f, "Read out of bounds"),
+            Self::AllPinned => write!(f, "All cache frames pinned"),
+            Self::Source => write!(f, "Page source read failed"),
+            Self::Alloc(_) => write!(f, "PSRAM allocation failed"),
+        }
+    }
+}
+
+// ===== 页源 =====
+
+/// 可按偏移读取的只读数据源
+pub trait PageSource {
+    /// 源区域长度 (字节)
+    fn len(&self) -> u32;
+
+    /// 从 `offset` 读取填满 `buf`
+    fn read_at(&mut self, offset: u32, buf: &mut [u8]) -> Result<(), FlashCacheError>;
+
+    /// 是否为空
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// 通过数据总线映射读取的内部 Flash 区域
+pub struct MappedFlash {
+    /// Flash 物理偏移
+    offset: u32,
+    /// 区域长度
+    len: u32,
+}
+
+impl MappedFlash {
+    /// 创建 (要求区域已由 MMU 映射到数据总线)
+    pub const fn new(offset: u32, len: u32) -> Self {
+        Self { offset, len }
+    }
+}
+
+impl PageSource for MappedFlash {
+    fn len(&self) -> u32 {
+        self.len
+    }
+
+    fn read_at(&mut self, offset: u32, buf: &mut [u8]) -> Result<(), FlashCacheError> {
+        if offset as u64 + buf.len() as u64 > self.len as u64 {
+            return Err(FlashCacheError::OutOfBounds);
+        }
+        let src = (FLASH_DATA_BASE + (self.offset + offset) as usize) as *const u8;
+        unsafe {
+            core::ptr::copy_nonoverlapping(src, buf.as_mut_ptr(), buf.len());
+        }
+        Ok(())
+    }
+}
+
+impl PageSource for &[u8] {
+    fn len(&self) -> u32 {
+        <[u8]>::len(self) as u32
+    }
+
+    fn read_at(&mut self, offset: u32, buf: &mut [u8]) -> Result<(), FlashCacheError> {
+        let start = offset as usize;
+        let src = self
+            .get(start..start + buf.len())
+            .ok_or(FlashCacheError::OutOfBounds)?;
+        buf.copy_from_slice(src);
+        Ok(())
+    }
+}
+
+// ===== 缓存 =====
+
+/// 缓存统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FlashCacheStats {
+    /// 命中次数
+    pub hits: u32,
+    /// 未命中 (换入) 次数
+    pub misses: u32,
+    /// 淘汰次数
+    pub evictions: u32,
+    /// 当前固定帧数
+    pub pinned: u32,
+}
+
+impl FlashCacheStats {
+    /// 命中率 (千分比)
+    pub fn hit_rate_permille(&self) -> u32 {
+        let total = self.hits + self.misses;
+        if total == 0 { 0 } else { (self.hits as u64 * 1000 / total as u64) as u32 }
+    }
+}
+
+/// 帧元数据
+#[derive(Debug, Clone, Copy, Default)]
+struct FrameMeta {
+    /// 缓存的页号
+    page: Option<u32>,
+    /// 最近访问时刻 (逻辑时钟)
+    last_used: u32,
+    /// 固定计数
+    pins: u16,
+    /// 有效字节数 (源末尾的页可能不满)
+    valid: u16,
+}
+
+/// Flash 分页缓存
+///
+/// `PAGES` 帧，每帧 `PAGE` 字节。一个缓存实例服务单个页源，
+/// 切换页源前应调用 `clear`。
+pub struct FlashCache<'a, const PAGES: usize, const PAGE: usize> {
+    frames: &'a mut [[u8; PAGE]; PAGES],
+    meta: [FrameMeta; PAGES],
+    clock: u32,
+    stats: FlashCacheStats,
+}
+
+impl<const PAGES: usize, const PAGE: usize> FlashCache<'static, PAGES, PAGE> {
+    /// 在 PSRAM 中分配帧缓冲区
+    pub fn in_psram() -> Result<Self, FlashCacheError> {
+        let mut frames = PsramBox::<[[u8; PAGE]; PAGES]>::new_uninit_with_config(PsramConfig::default())
+            .map_err(FlashCacheError::Alloc)?;
+        // PsramBox 不会释放 (bump 分配器)，可视为 'static
+        let frames = unsafe {
+            let ptr = frames.as_mut_ptr() as *mut [[u8; PAGE]; PAGES];
+            ptr.write_bytes(0, 1);
+            &mut *ptr
+        };
+        Ok(Self::new(frames))
+    }
+}
+
+impl<'a, const PAGES: usize, const PAGE: usize> FlashCache<'a, PAGES, PAGE> {
+    /// 使用给定帧缓冲区创建
+    pub fn new(frames: &'a mut [[u8; PAGE]; PAGES]) -> Self {
+        const { assert!(PAGE <= u16::MAX as usize, "page must fit in u16") };
+        Self {
+            frames,
+            meta: [FrameMeta::default(); PAGES],
+            clock: 0,
+            stats: FlashCacheStats::default(),
+        }
+    }
+
+    /// 获取页内容 (必要时换入)
+    pub fn page<S: PageSource>(&mut self, source: &mut S, page: u32) -> Result<&[u8], FlashCacheError> {
+        let frame = self.lookup_or_load(source, page)?;
+        let valid = self.meta[frame].valid as usize;
+        Ok(&self.frames[frame][..valid])
+    }
+
+    /// 读取任意区间 (可跨页)
+    pub fn read<S: PageSource>(&mut self, source: &mut S, offset: u32, buf: &mut [u8]) -> Result<(), FlashCacheError> {
+        if offset as u64 + buf.len() as u64 > source.len() as u64 {
+            return Err(FlashCacheError::OutOfBounds);
+        }
+        let mut pos = offset as usize;
+        let mut done = 0;
+        while done < buf.len() {
+            let page = (pos / PAGE) as u32;
+            let in_page = pos % PAGE;
+            let chunk = core::cmp::min(PAGE - in_page, buf.len() - done);
+            let frame = self.lookup_or_load(source, page)?;
+            buf[done..done + chunk].copy_from_slice(&self.frames[frame][in_page..in_page + chunk]);
+            done += chunk;
+            pos += chunk;
+        }
+        Ok(())
+    }
+
+    /// 固定页 (换入并常驻，可嵌套)
+    pub fn pin<S: PageSource>(&mut self, source: &mut S, page: u32) -> Result<(), FlashCacheError> {
+        let frame = self.lookup_or_load(source, page)?;
+        if self.meta[frame].pins == 0 {
+            self.stats.pinned += 1;
+        }
+        self.meta[frame].pins += 1;
+        Ok(())
+    }
+
+    /// 取消固定
+    pub fn unpin(&mut self, page: u32) {
+        if let Some(frame) = self.find(page) {
+            let meta = &mut self.meta[frame];
+            if meta.pins > 0 {
+                meta.pins -= 1;
+                if meta.pins == 0 {
+                    self.stats.pinned -= 1;
+                }
+            }
+        }
+    }
+
+    /// 页是否已缓存
+    pub fn contains(&self, page: u32) -> bool {
+        self.find(page).is_some()
+    }
+
+    /// 清空缓存 (包括固定页)
+    pub fn clear(&mut self) {
+        self.meta = [FrameMeta::default(); PAGES];
+        self.stats.pinned = 0;
+    }
+
+    /// 统计
+    pub fn stats(&self) -> FlashCacheStats {
+        self.stats
+    }
+
+    fn find(&self, page: u32) -> Option<usize> {
+        self.meta.iter().position(|m| m.page == Some(page))
+    }
+
+    fn lookup_or_load<S: PageSource>(&mut self, source: &mut S, page: u32) -> Result<usize, FlashCacheError> {
+        self.clock = self.clock.wrapping_add(1);
+
+        if let Some(frame) = self.find(page) {
+            self.stats.hits += 1;
+            self.meta[frame].last_used = self.clock;
+            return Ok(frame);
+        }
+
+        let start = page as u64 * PAGE as u64;
+        if start >= source.len() as u64 {
+            return Err(FlashCacheError::OutOfBounds);
+        }
+        let valid = core::cmp::min(PAGE as u64, source.len() as u64 - start) as usize;
+
+        let frame = self.victim().ok_or(FlashCacheError::AllPinned)?;
+        if self.meta[frame].page.is_some() {
+            self.stats.evictions += 1;
+        }
+        // 读取失败时帧保持无效
+        self.meta[frame].page = None;
+        source.read_at(start as u32, &mut self.frames[frame][..valid])?;
+
+        self.stats.misses += 1;
+        self.meta[frame] = FrameMeta {
+            page: Some(page),
+            last_used: self.clock,
+            pins: 0,
+            valid: valid as u16,
+        };
+        Ok(frame)
+    }
+
+    /// 选择换出帧: 优先空帧，其次最久未使用的非固定帧
+    fn victim(&self) -> Option<usize> {
+        if let Some(free) = self.meta.iter().position(|m| m.page.is_none()) {
+            return Some(free);
+        }
+        self.meta
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.pins == 0)
+            .max_by_key(|(_, m)| self.clock.wrapping_sub(m.last_used))
+            .map(|(i, _)| i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATA: [u8; 40] = {
+        let mut d = [0u8; 40];
+        let mut i = 0;
+        while i < 40 {
+            d[i] = i as u8;
+            i += 1;
+        }
+        d
+    };
+
+    #[test]
+    fn test_cross_page_read_and_lru() {
+        let mut frames = [[0u8; 8]; 2];
+        let mut cache = FlashCache::new(&mut frames);
+        let mut src: &[u8] = &DATA;
+
+        let mut buf = [0u8; 6];
+        cache.read(&mut src, 5, &mut buf).unwrap();
+        assert_eq!(buf, [5, 6, 7, 8, 9, 10]);
+        assert_eq!(cache.stats().misses, 2);
+
+        // 访问页 0 使页 1 成为最久未用，换入页 2 时淘汰页 1
+        cache.page(&mut src, 0).unwrap();
+        cache.page(&mut src, 2).unwrap();
+        assert!(cache.contains(0) && !cache.contains(1));
+        assert_eq!(cache.stats().evictions, 1);
+
+        assert_eq!(cache.read(&mut src, 38, &mut buf), Err(FlashCacheError::OutOfBounds));
+    }
+
+    #[test]
+    fn test_pinned_pages_not_evicted() {
+        let mut frames = [[0u8; 16]; 2];
+        let mut cache = FlashCache::new(&mut frames);
+        let mut src: &[u8] = &DATA;
+
+        cache.pin(&mut src, 0).unwrap();
+        cache.pin(&mut src, 1).unwrap();
+        assert_eq!(cache.page(&mut src, 2).unwrap_err(), FlashCacheError::AllPinned);
+
+        cache.unpin(1);
+        assert_eq!(cache.page(&mut src, 2).unwrap(), &DATA[32..40]);
+        assert!(cache.contains(0));
+    }
+}
@@ -4,6 +4,10 @@
 //! - PSRAM 初始化与分配 (自动缓存策略)
 //! - 内存池分配器 (零拷贝、无锁)
 //! - DMA 缓冲区管理 (对齐、cache 一致性)
+//! - GDMA 通道运行时分配 (优先级排队，租约 drop 时归还)
+//! - Flash 资源分页缓存 (PSRAM LRU)
+//! - 内存访问基准测试 (DRAM / PSRAM 带宽与延迟)
+//! - 分配失败注入 (`alloc-faults` feature，覆盖 PoolFull / OutOfMemory 错误路径)
 //!
 //! # 内存区域
 //!
@@ -29,15 +33,26 @@
 //! ```
 
 #![allow(dead_code)]
+#![cfg_attr(
+    all(feature = "panic-free", not(test)),
+    deny(clippy::unwrap_used, clippy::expect_used, clippy::panic, clippy::unreachable, clippy::todo, clippy::unimplemented)
+)]
 
 pub mod psram;
 pub mod pool;
 pub mod dma;
+pub mod flashcache;
+pub mod bench;
+#[cfg(feature = "alloc-faults")]
+pub mod faults;
 
 // 重导出常用类型
 pub use psram::{CacheMode, PsramConfig, PsramBox};
 pub use pool::{MemoryPool, PoolBox, Backend};
-pub use dma::{DmaBuffer, DmaStrategy};
+pub use dma::{DmaBuffer, DmaBusy, DmaDoubleBuffer, DmaStrategy, ChannelAllocator, DmaLease, DmaPriority, DmaUser, GdmaAllocator};
+pub use flashcache::{FlashCache, PageSource, MappedFlash};
+#[cfg(feature = "alloc-faults")]
+pub use faults::{FaultPlan, FaultyHeap, Sites, FAULTS};
 
 /// 内存区域标记宏
 /// 
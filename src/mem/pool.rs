@@ -248,7 +248,7 @@ pub struct MemoryPool<T, const N: usize, const BACKEND: u8> {
 impl<T, const N: usize, const BACKEND: u8> MemoryPool<T, N, BACKEND> {
     /// 创建新的内存池
     pub const fn new() -> Self {
-        assert!(N <= 256, "Pool size must be <= 256");
+        const { assert!(N <= 256, "Pool size must be <= 256") };
         
         Self {
             slots: UnsafeCell::new(unsafe { MaybeUninit::uninit().assume_init() }),
@@ -259,6 +259,11 @@ impl<T, const N: usize, const BACKEND: u8> MemoryPool<T, N, BACKEND> {
     
     /// 分配一个槽位
     pub fn alloc(&self) -> Result<PoolBox<'_, T, N, BACKEND>, PoolError> {
+        #[cfg(feature = "alloc-faults")]
+        if super::faults::inject(super::faults::Sites::POOL) {
+            return Err(PoolError::PoolFull);
+        }
+
         let index = self.bitmap.alloc().ok_or(PoolError::PoolFull)?;
         
         if index >= N {
@@ -14,14 +14,34 @@
 //! - PSRAM 延迟约 100ns，DRAM 约 10ns
 //! - 缓存模式下需要注意 DMA 的 cache 一致性
 //! - 非实时任务的大型缓冲区推荐使用 PSRAM
+//!
+//! # 按标签统计
+//!
+//! 每次分配按 `PsramConfig::tag` 记账 (当前/峰值用量、分配与失败次数)，用于在相机帧、
+//! 网络缓冲池、ML 张量区之间分配 8MB 预算。`top` 按用量排序，`set_threshold_watch`
+//! 在总用量越过百分比阈值 (上升或回落) 时回调。从其他分配器 (如 esp-alloc 的 PSRAM 区域)
+//! 取得的内存可用 `charge` / `release` 记入同一张表。
+//!
+//! ```rust,ignore
+//! let frame = psram::alloc_array_with_config::<u8, 153_600>(PsramConfig::default().with_tag("camera"))?;
+//! psram::set_threshold_watch(&[75, 90], |e| log_warn!("psram {}% ({} bytes)", e.percent, e.used));
+//! for usage in psram::top::<4>() {
+//!     log_info!("{}", usage);
+//! }
+//! ```
 
-use core::cell::UnsafeCell;
+use core::cell::{Cell, RefCell, UnsafeCell};
+use core::fmt;
 use core::marker::PhantomData;
 use core::mem::MaybeUninit;
 use core::ops::{Deref, DerefMut};
 use core::ptr::NonNull;
 use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use heapless::Vec;
+
 /// PSRAM 缓存模式
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CacheMode {
@@ -49,6 +69,8 @@ pub struct PsramConfig {
     pub realtime: bool,
     /// 对齐要求 (字节)
     pub alignment: usize,
+    /// 归属标签 (按标签统计用量)
+    pub tag: &'static str,
 }
 
 impl Default for PsramConfig {
@@ -57,6 +79,7 @@ impl Default for PsramConfig {
             cache_mode: CacheMode::Auto,
             realtime: false,
             alignment: 32, // 缓存行对齐
+            tag: DEFAULT_TAG,
         }
     }
 }
@@ -68,6 +91,7 @@ impl PsramConfig {
             cache_mode: CacheMode::Cached,
             realtime: true,
             alignment: 32,
+            tag: DEFAULT_TAG,
         }
     }
     
@@ -77,6 +101,7 @@ impl PsramConfig {
             cache_mode: CacheMode::Direct,
             realtime: false,
             alignment: 32,
+            tag: DEFAULT_TAG,
         }
     }
     
@@ -91,6 +116,12 @@ impl PsramConfig {
         self.alignment = align;
         self
     }
+    
+    /// 设置归属标签
+    pub fn with_tag(mut self, tag: &'static str) -> Self {
+        self.tag = tag;
+        self
+    }
 }
 
 /// PSRAM 全局状态
@@ -160,11 +191,28 @@ pub enum PsramError {
 ///
 /// - `size`: 分配大小
 /// - `align`: 对齐要求
+/// - `tag`: 归属标签 (按对齐后的实际占用记账，失败也计数)
 ///
 /// # 返回
 ///
 /// 分配的内存指针，如果失败返回 None
-fn psram_alloc_raw(size: usize, align: usize) -> Result<*mut u8, PsramError> {
+fn psram_alloc_raw(size: usize, align: usize, tag: &'static str) -> Result<*mut u8, PsramError> {
+    #[cfg(feature = "alloc-faults")]
+    if super::faults::inject(super::faults::Sites::PSRAM) {
+        record_failure(tag);
+        return Err(PsramError::OutOfMemory);
+    }
+
+    let result = psram_bump(size, align);
+    match result {
+        Ok((_, charged)) => charge(tag, charged),
+        Err(_) => record_failure(tag),
+    }
+    result.map(|(ptr, _)| ptr)
+}
+
+/// bump 分配，返回指针与含对齐填充的占用字节数
+fn psram_bump(size: usize, align: usize) -> Result<(*mut u8, usize), PsramError> {
     if size == 0 {
         return Err(PsramError::ZeroSize);
     }
@@ -190,7 +238,7 @@ fn psram_alloc_raw(size: usize, align: usize) -> Result<*mut u8, PsramError> {
             .compare_exchange(current_offset, new_offset, Ordering::AcqRel, Ordering::Relaxed)
             .is_ok()
         {
-            return Ok((base + aligned_offset) as *mut u8);
+            return Ok(((base + aligned_offset) as *mut u8, new_offset - current_offset));
         }
         // 如果 CAS 失败，重试
     }
@@ -217,7 +265,7 @@ impl<T> PsramBox<T> {
         let size = core::mem::size_of::<T>();
         let align = config.alignment.max(core::mem::align_of::<T>());
         
-        let ptr = psram_alloc_raw(size, align)?;
+        let ptr = psram_alloc_raw(size, align, config.tag)?;
         let typed_ptr = ptr as *mut T;
         
         // 写入初始值
@@ -242,7 +290,7 @@ impl<T> PsramBox<T> {
         let size = core::mem::size_of::<T>();
         let align = config.alignment.max(core::mem::align_of::<T>());
         
-        let ptr = psram_alloc_raw(size, align)?;
+        let ptr = psram_alloc_raw(size, align, config.tag)?;
         let typed_ptr = ptr as *mut MaybeUninit<T>;
         
         Ok(PsramBox {
@@ -327,7 +375,7 @@ pub fn alloc_array_with_config<T: Default + Clone, const N: usize>(
     let size = core::mem::size_of::<[T; N]>();
     let align = config.alignment.max(core::mem::align_of::<T>());
     
-    let ptr = psram_alloc_raw(size, align)?;
+    let ptr = psram_alloc_raw(size, align, config.tag)?;
     let typed_ptr = ptr as *mut [T; N];
     
     // 初始化数组
@@ -367,6 +415,185 @@ pub struct PsramStats {
     pub free: usize,
 }
 
+// ===== 按标签统计 =====
+
+/// 未设置标签的分配
+pub const DEFAULT_TAG: &str = "untagged";
+
+/// 标签表满后新标签归入此项
+pub const OVERFLOW_TAG: &str = "other";
+
+/// 最多单独统计的标签数 (含 `OVERFLOW_TAG`)
+pub const MAX_TAGS: usize = 16;
+
+/// 单个标签的用量
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TagUsage {
+    /// 标签
+    pub tag: &'static str,
+    /// 当前占用 (字节)
+    pub current: usize,
+    /// 峰值占用 (字节)
+    pub peak: usize,
+    /// 成功分配次数
+    pub allocations: u32,
+    /// 分配失败次数
+    pub failures: u32,
+}
+
+impl TagUsage {
+    const fn new(tag: &'static str) -> Self {
+        Self { tag, current: 0, peak: 0, allocations: 0, failures: 0 }
+    }
+}
+
+impl fmt::Display for TagUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<12} {:>8} B (peak {} B, {} allocs, {} failed)",
+            self.tag, self.current, self.peak, self.allocations, self.failures
+        )
+    }
+}
+
+/// 总用量越过阈值的事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThresholdEvent {
+    /// 越过的阈值 (百分比)
+    pub percent: u8,
+    /// true 为上升越过，false 为回落到阈值以下
+    pub rising: bool,
+    /// 当前总占用 (字节)
+    pub used: usize,
+    /// 总容量 (字节)
+    pub total: usize,
+}
+
+/// 阈值监视: 升序百分比阈值、回调、当前所处级别
+#[derive(Clone, Copy)]
+struct Watch {
+    levels: &'static [u8],
+    callback: fn(ThresholdEvent),
+    level: usize,
+}
+
+static TAGS: BlockingMutex<CriticalSectionRawMutex, RefCell<Vec<TagUsage, MAX_TAGS>>> =
+    BlockingMutex::new(RefCell::new(Vec::new()));
+
+static WATCH: BlockingMutex<CriticalSectionRawMutex, Cell<Option<Watch>>> = BlockingMutex::new(Cell::new(None));
+
+/// 在标签表中找到或创建条目并更新 (表满时归入 `OVERFLOW_TAG`)
+fn update_tag(tag: &'static str, f: impl FnOnce(&mut TagUsage)) {
+    TAGS.lock(|tags| {
+        let mut tags = tags.borrow_mut();
+        let index = match tags.iter().position(|u| u.tag == tag) {
+            Some(index) => Some(index),
+            // 预留最后一项给溢出标签
+            None if tags.len() < MAX_TAGS - 1 || (tag == OVERFLOW_TAG && tags.len() < MAX_TAGS) => {
+                tags.push(TagUsage::new(tag)).ok().map(|_| tags.len() - 1)
+            }
+            None => match tags.iter().position(|u| u.tag == OVERFLOW_TAG) {
+                Some(index) => Some(index),
+                None => tags.push(TagUsage::new(OVERFLOW_TAG)).ok().map(|_| tags.len() - 1),
+            },
+        };
+        if let Some(usage) = index.and_then(|i| tags.get_mut(i)) {
+            f(usage);
+        }
+    });
+}
+
+/// 记入一次分配 (供其他 PSRAM 分配器使用)
+pub fn charge(tag: &'static str, bytes: usize) {
+    update_tag(tag, |u| {
+        u.current = u.current.saturating_add(bytes);
+        u.peak = u.peak.max(u.current);
+        u.allocations = u.allocations.wrapping_add(1);
+    });
+    check_thresholds();
+}
+
+/// 记入一次释放 (供支持释放的分配器使用)
+pub fn release(tag: &'static str, bytes: usize) {
+    update_tag(tag, |u| u.current = u.current.saturating_sub(bytes));
+    check_thresholds();
+}
+
+fn record_failure(tag: &'static str) {
+    update_tag(tag, |u| u.failures = u.failures.wrapping_add(1));
+}
+
+/// 某标签的用量
+pub fn tag_usage(tag: &str) -> Option<TagUsage> {
+    TAGS.lock(|tags| tags.borrow().iter().find(|u| u.tag == tag).copied())
+}
+
+/// 按当前占用降序的前 `N` 个标签
+pub fn top<const N: usize>() -> Vec<TagUsage, N> {
+    let mut all = TAGS.lock(|tags| tags.borrow().clone());
+    all.sort_unstable_by(|a, b| b.current.cmp(&a.current).then(b.peak.cmp(&a.peak)));
+    all.into_iter().take(N).collect()
+}
+
+/// 各标签占用之和
+fn tagged_total() -> usize {
+    TAGS.lock(|tags| tags.borrow().iter().map(|u| u.current).sum())
+}
+
+/// 设置总用量阈值监视 (`levels` 为升序百分比，如 `&[75, 90]`)
+///
+/// 回调在分配或释放的调用者上下文中执行，应尽快返回。
+pub fn set_threshold_watch(levels: &'static [u8], callback: fn(ThresholdEvent)) {
+    let level = level_for(levels, usage_percent(tagged_total(), PSRAM_SIZE.load(Ordering::Relaxed)));
+    WATCH.lock(|watch| watch.set(Some(Watch { levels, callback, level })));
+}
+
+/// 取消阈值监视
+pub fn clear_threshold_watch() {
+    WATCH.lock(|watch| watch.set(None));
+}
+
+fn usage_percent(used: usize, total: usize) -> u8 {
+    if total == 0 {
+        return 0;
+    }
+    (used as u64 * 100 / total as u64).min(100) as u8
+}
+
+/// 已越过的阈值个数
+fn level_for(levels: &[u8], percent: u8) -> usize {
+    levels.iter().take_while(|&&level| percent >= level).count()
+}
+
+/// 级别变化时生成事件 (跨越多级时报告最近越过的阈值)
+fn crossing(watch: &mut Watch, used: usize, total: usize) -> Option<ThresholdEvent> {
+    let level = level_for(watch.levels, usage_percent(used, total));
+    if level == watch.level {
+        return None;
+    }
+    let rising = level > watch.level;
+    let index = if rising { level - 1 } else { level };
+    watch.level = level;
+    let percent = watch.levels.get(index).copied()?;
+    Some(ThresholdEvent { percent, rising, used, total })
+}
+
+fn check_thresholds() {
+    let total = PSRAM_SIZE.load(Ordering::Relaxed);
+    let used = tagged_total();
+    let fired = WATCH.lock(|cell| {
+        let mut watch = cell.get()?;
+        let event = crossing(&mut watch, used, total);
+        cell.set(Some(watch));
+        event.map(|e| (watch.callback, e))
+    });
+    // 回调在锁外执行，可再次分配
+    if let Some((callback, event)) = fired {
+        callback(event);
+    }
+}
+
 /// Cache 操作 (用于 DMA 一致性)
 pub mod cache {
     use core::arch::asm;
@@ -454,5 +681,24 @@ mod tests {
         assert_eq!(config.cache_mode, CacheMode::Auto);
         assert!(!config.realtime);
         assert_eq!(config.alignment, 32);
+        assert_eq!(config.tag, DEFAULT_TAG);
+    }
+    
+    #[test]
+    fn test_tag_accounting_and_thresholds() {
+        charge("camera", 300);
+        charge("net", 100);
+        charge("camera", 200);
+        release("net", 60);
+        let top = top::<2>();
+        assert_eq!((top[0].tag, top[0].current, top[0].allocations), ("camera", 500, 2));
+        assert_eq!((top[1].tag, top[1].current, top[1].peak), ("net", 40, 100));
+        
+        let mut watch = Watch { levels: &[50, 75, 90], callback: |_| {}, level: 0 };
+        assert_eq!(crossing(&mut watch, 40, 100), None);
+        let up = crossing(&mut watch, 92, 100).unwrap();
+        assert_eq!((up.percent, up.rising, watch.level), (90, true, 3));
+        let down = crossing(&mut watch, 60, 100).unwrap();
+        assert_eq!((down.percent, down.rising, watch.level), (75, false, 1));
     }
 }
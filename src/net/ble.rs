@@ -8,7 +8,9 @@
 //! - BLE 广播 (Advertising)
 //! - GATT Server (外设角色)
 //! - GATT Client (中心角色)
-//! - 连接管理
+//! - 连接管理 (多连接外设: 已连接时可继续广播)
+//! - 每连接独立的 GATT 上下文 (订阅状态)
+//! - L2CAP 面向连接通道 (PSM 监听、通道开闭事件；数据流见 `net::l2cap`)
 //! - 安全配对 (可选)
 //!
 //! # 示例
@@ -34,6 +36,10 @@ use embassy_time::{Duration, Timer};
 use heapless::{String, Vec};
 
 use super::config::*;
+use super::tuning::{self, Knob};
+use crate::power::lifecycle::{DriverLifecycle, LifecycleError, SleepKind};
+use crate::power::peripherals::{self, KeepAlive, Peripheral};
+use crate::sync::bus::Envelope;
 
 // ===== 错误类型 =====
 
@@ -146,8 +152,25 @@ pub enum BleEvent {
         /// 是否绑定
         bonded: bool,
     },
+    /// L2CAP 通道已建立
+    ChannelOpened {
+        /// 连接句柄
+        conn_handle: u16,
+        /// 协议/服务复用号
+        psm: u16,
+    },
+    /// L2CAP 通道已关闭
+    ChannelClosed {
+        /// 连接句柄
+        conn_handle: u16,
+        /// 协议/服务复用号
+        psm: u16,
+    },
 }
 
+/// BLE 事件通道 (事件附带序号、时间戳与来源)
+pub type BleEventChannel = Channel<CriticalSectionRawMutex, Envelope<BleEvent>, BLE_EVENT_QUEUE_SIZE>;
+
 /// BLE 断开原因
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum DisconnectReason {
@@ -166,6 +189,15 @@ pub enum DisconnectReason {
     UnacceptableConnectionParameters,
 }
 
+/// 每个连接最多记录的订阅数 (CCCD)
+pub const MAX_SUBSCRIPTIONS: usize = 8;
+
+/// 同时监听的 L2CAP PSM 数
+pub const MAX_L2CAP_PSMS: usize = 4;
+
+/// 每个连接最多同时打开的 L2CAP 通道数
+pub const MAX_L2CAP_CHANNELS: usize = 2;
+
 // ===== BLE 状态 =====
 
 /// BLE 状态
@@ -184,12 +216,21 @@ pub enum BleState {
     Scanning,
 }
 
+/// 广播模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdvertisingMode {
+    /// 可连接广播
+    Connectable,
+    /// 不可连接广播 (仅供发现，连接数已满或配置为不可连接)
+    NonConnectable,
+}
+
 // ===== 广播配置 =====
 
 /// 广播配置
 #[derive(Debug, Clone)]
 pub struct AdvertiseConfig {
-    /// 设备名称
+    /// 设备名称 (默认取自 `sys::identity`)
     pub name: String<32>,
     /// 广播间隔 (毫秒)
     pub interval_ms: u32,
@@ -203,18 +244,21 @@ pub struct AdvertiseConfig {
     pub scan_rsp_data: Vec<u8, 31>,
     /// 广播超时 (0 = 无限)
     pub timeout_secs: u16,
+    /// 已有连接时是否继续广播 (多连接外设)
+    pub while_connected: bool,
 }
 
 impl Default for AdvertiseConfig {
     fn default() -> Self {
         Self {
-            name: String::try_from("RustRTOS").unwrap_or_default(),
+            name: String::try_from(crate::sys::identity::IDENTITY.ble_name().as_str()).unwrap_or_default(),
             interval_ms: BLE_ADV_INTERVAL_FAST_MS,
             connectable: true,
             scannable: true,
             adv_data: Vec::new(),
             scan_rsp_data: Vec::new(),
             timeout_secs: 0,
+            while_connected: false,
         }
     }
 }
@@ -251,6 +295,27 @@ impl AdvertiseConfig {
         let _ = self.adv_data.extend_from_slice(data);
         self
     }
+
+    /// 设置已有连接时是否继续广播
+    ///
+    /// 启用后，连接数未满时继续可连接广播以接受下一个中心设备；
+    /// 达到 `BLE_MAX_CONNECTIONS` 后降为不可连接广播。
+    pub fn with_while_connected(mut self, enabled: bool) -> Self {
+        self.while_connected = enabled;
+        self
+    }
+
+    /// 给定当前连接数时应使用的广播模式 (`None` 表示暂停广播)
+    pub fn mode_for(&self, connections: usize) -> Option<AdvertisingMode> {
+        if connections > 0 && !self.while_connected {
+            return None;
+        }
+        if self.connectable && connections < BLE_MAX_CONNECTIONS {
+            Some(AdvertisingMode::Connectable)
+        } else {
+            Some(AdvertisingMode::NonConnectable)
+        }
+    }
 }
 
 // ===== 连接信息 =====
@@ -274,6 +339,81 @@ pub struct ConnectionInfo {
     pub bonded: bool,
 }
 
+impl ConnectionInfo {
+    /// 单个通知/写入可携带的最大数据长度 (MTU - 3 字节 ATT 头)
+    pub fn max_payload(&self) -> usize {
+        self.mtu.saturating_sub(3) as usize
+    }
+}
+
+// ===== GATT 上下文 =====
+
+/// 特征订阅 (对端写入的 CCCD)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Subscription {
+    /// 特征值句柄
+    pub attr_handle: u16,
+    /// 已订阅通知
+    pub notify: bool,
+    /// 已订阅指示
+    pub indicate: bool,
+}
+
+/// 每连接的 GATT 上下文
+///
+/// 各中心设备的订阅互相独立，断开时随连接一起释放。
+#[derive(Debug, Clone, Default)]
+pub struct GattContext {
+    subscriptions: Vec<Subscription, MAX_SUBSCRIPTIONS>,
+}
+
+impl GattContext {
+    /// 创建空上下文
+    pub const fn new() -> Self {
+        Self { subscriptions: Vec::new() }
+    }
+
+    /// 记录 CCCD 写入 (两者皆为 `false` 时取消订阅)
+    pub fn subscribe(&mut self, attr_handle: u16, notify: bool, indicate: bool) -> Result<(), BleError> {
+        let existing = self.subscriptions.iter().position(|s| s.attr_handle == attr_handle);
+        match (existing, notify || indicate) {
+            (Some(pos), true) => self.subscriptions[pos] = Subscription { attr_handle, notify, indicate },
+            (Some(pos), false) => {
+                self.subscriptions.swap_remove(pos);
+            }
+            (None, true) => self
+                .subscriptions
+                .push(Subscription { attr_handle, notify, indicate })
+                .map_err(|_| BleError::OutOfMemory)?,
+            (None, false) => {}
+        }
+        Ok(())
+    }
+
+    /// 是否订阅了指定特征 (通知或指示)
+    pub fn is_subscribed(&self, attr_handle: u16) -> bool {
+        self.subscriptions.iter().any(|s| s.attr_handle == attr_handle)
+    }
+
+    /// 当前订阅列表
+    pub fn subscriptions(&self) -> &[Subscription] {
+        &self.subscriptions
+    }
+
+    /// 清除全部订阅 (未绑定的对端重连后需要重新订阅)
+    pub fn clear(&mut self) {
+        self.subscriptions.clear();
+    }
+}
+
+/// 活动连接 (连接参数 + GATT 上下文 + 已打开的 L2CAP 通道)
+#[derive(Debug, Clone)]
+pub(crate) struct Connection {
+    info: ConnectionInfo,
+    gatt: GattContext,
+    channels: Vec<u16, MAX_L2CAP_CHANNELS>,
+}
+
 // ===== GATT 服务定义 =====
 
 /// GATT 服务 UUID
@@ -383,21 +523,29 @@ pub struct BleController<'a> {
     /// 当前状态
     state: BleState,
     /// 事件通道
-    event_channel: &'a Channel<CriticalSectionRawMutex, BleEvent, BLE_EVENT_QUEUE_SIZE>,
+    event_channel: &'a BleEventChannel,
     /// 连接信号
     connected_signal: &'a Signal<CriticalSectionRawMutex, bool>,
     /// 活动连接
-    connections: Vec<ConnectionInfo, BLE_MAX_CONNECTIONS>,
+    connections: Vec<Connection, BLE_MAX_CONNECTIONS>,
     /// 本地地址
     local_addr: [u8; 6],
     /// 广播配置
     adv_config: Option<AdvertiseConfig>,
+    /// 应用是否请求广播
+    adv_enabled: bool,
+    /// 当前实际广播模式
+    advertising: Option<AdvertisingMode>,
+    /// 射频保活句柄 (`init` 后持有)
+    power: Option<KeepAlive>,
+    /// 接受对端连接请求的 L2CAP PSM
+    l2cap_psms: Vec<u16, MAX_L2CAP_PSMS>,
 }
 
 impl<'a> BleController<'a> {
     /// 创建新的 BLE 控制器
     pub fn new(
-        event_channel: &'a Channel<CriticalSectionRawMutex, BleEvent, BLE_EVENT_QUEUE_SIZE>,
+        event_channel: &'a BleEventChannel,
         connected_signal: &'a Signal<CriticalSectionRawMutex, bool>,
     ) -> Self {
         Self {
@@ -407,6 +555,10 @@ impl<'a> BleController<'a> {
             connections: Vec::new(),
             local_addr: [0; 6],
             adv_config: None,
+            adv_enabled: false,
+            advertising: None,
+            power: None,
+            l2cap_psms: Vec::new(),
         }
     }
 
@@ -421,6 +573,7 @@ impl<'a> BleController<'a> {
     pub async fn init(&mut self) -> Result<(), BleError> {
         // esp-radio 的初始化在更高层完成
         // 这里只是设置本地状态
+        self.power.get_or_insert_with(|| peripherals::keep_alive(Peripheral::Bt));
         self.state = BleState::Idle;
         
         // 生成随机本地地址 (实际应从芯片获取)
@@ -439,8 +592,22 @@ impl<'a> BleController<'a> {
         self.local_addr
     }
 
+    /// 是否正在广播
+    pub fn is_advertising(&self) -> bool {
+        self.advertising.is_some()
+    }
+
+    /// 当前广播模式
+    pub fn advertising_mode(&self) -> Option<AdvertisingMode> {
+        self.advertising
+    }
+
     /// 开始广播
     ///
+    /// 已有连接时，仅在 `config.while_connected` 启用后继续广播，否则在连接
+    /// 全部断开后开始。广播模式随连接数变化时先发出 `AdvertisingStopped`
+    /// 再发出 `AdvertisingStarted`，应用据 `advertising_mode()` 以新参数重新广播。
+    ///
     /// **注意**: 此函数仅管理状态。实际广播应通过 trouble-host 的
     /// `Peripheral::advertise()` 完成。参见 `examples/ble_advertise.rs`。
     pub async fn start_advertising(&mut self, config: AdvertiseConfig) -> Result<(), BleError> {
@@ -448,15 +615,15 @@ impl<'a> BleController<'a> {
             return Err(BleError::NotInitialized);
         }
 
-        if self.state == BleState::Advertising {
+        if self.adv_enabled {
             return Err(BleError::AlreadyAdvertising);
         }
 
         self.adv_config = Some(config);
-        self.state = BleState::Advertising;
+        self.adv_enabled = true;
 
         // 状态管理层 - 实际广播通过 trouble_host::Peripheral 完成
-        let _ = self.event_channel.try_send(BleEvent::AdvertisingStarted);
+        self.update_advertising();
 
         Ok(())
     }
@@ -466,13 +633,9 @@ impl<'a> BleController<'a> {
     /// **注意**: 此函数仅管理状态。实际停止应通过取消 trouble-host 的
     /// advertise future 完成。
     pub async fn stop_advertising(&mut self) -> Result<(), BleError> {
-        if self.state != BleState::Advertising {
-            return Ok(());
-        }
-
         // 状态管理层 - 停止广播通过取消 future 完成
-        self.state = BleState::Idle;
-        let _ = self.event_channel.try_send(BleEvent::AdvertisingStopped);
+        self.adv_enabled = false;
+        self.update_advertising();
 
         Ok(())
     }
@@ -480,17 +643,17 @@ impl<'a> BleController<'a> {
     /// 断开指定连接
     pub async fn disconnect(&mut self, conn_handle: u16) -> Result<(), BleError> {
         // 查找并移除连接
-        if let Some(pos) = self.connections.iter().position(|c| c.handle == conn_handle) {
+        if let Some(pos) = self.connections.iter().position(|c| c.info.handle == conn_handle) {
             let conn = self.connections.remove(pos);
-            
-            let _ = self.event_channel.try_send(BleEvent::Disconnected {
+            for &psm in &conn.channels {
+                self.emit(BleEvent::ChannelClosed { conn_handle, psm });
+            }
+
+            self.emit(BleEvent::Disconnected {
                 conn_handle,
                 reason: DisconnectReason::LocalHostTerminated,
             });
-
-            if self.connections.is_empty() {
-                self.state = BleState::Idle;
-            }
+            self.update_advertising();
         }
 
         Ok(())
@@ -499,12 +662,15 @@ impl<'a> BleController<'a> {
     /// 断开所有连接
     pub async fn disconnect_all(&mut self) -> Result<(), BleError> {
         while let Some(conn) = self.connections.pop() {
-            let _ = self.event_channel.try_send(BleEvent::Disconnected {
-                conn_handle: conn.handle,
+            for &psm in &conn.channels {
+                self.emit(BleEvent::ChannelClosed { conn_handle: conn.info.handle, psm });
+            }
+            self.emit(BleEvent::Disconnected {
+                conn_handle: conn.info.handle,
                 reason: DisconnectReason::LocalHostTerminated,
             });
         }
-        self.state = BleState::Idle;
+        self.update_advertising();
         Ok(())
     }
 
@@ -515,7 +681,118 @@ impl<'a> BleController<'a> {
 
     /// 获取连接信息
     pub fn connection_info(&self, handle: u16) -> Option<&ConnectionInfo> {
-        self.connections.iter().find(|c| c.handle == handle)
+        self.connection(handle).map(|c| &c.info)
+    }
+
+    /// 所有活动连接
+    pub fn connections(&self) -> impl Iterator<Item = &ConnectionInfo> {
+        self.connections.iter().map(|c| &c.info)
+    }
+
+    /// 获取连接的 GATT 上下文
+    pub fn gatt_context(&self, handle: u16) -> Option<&GattContext> {
+        self.connection(handle).map(|c| &c.gatt)
+    }
+
+    /// 获取连接的 GATT 上下文 (可修改，如记录 CCCD 写入)
+    pub fn gatt_context_mut(&mut self, handle: u16) -> Option<&mut GattContext> {
+        self.connection_mut(handle).map(|c| &mut c.gatt)
+    }
+
+    /// 已订阅指定特征的连接句柄
+    pub fn subscribers(&self, attr_handle: u16) -> impl Iterator<Item = u16> + '_ {
+        self.connections
+            .iter()
+            .filter(move |c| c.gatt.is_subscribed(attr_handle))
+            .map(|c| c.info.handle)
+    }
+
+    /// 监听 L2CAP PSM (接受对端在该 PSM 上的 CoC 连接请求)
+    pub fn listen_l2cap(&mut self, psm: u16) -> Result<(), BleError> {
+        if psm == 0 || psm > 0x00FF {
+            return Err(BleError::InvalidParameter);
+        }
+        if self.l2cap_psms.contains(&psm) {
+            return Ok(());
+        }
+        self.l2cap_psms.push(psm).map_err(|_| BleError::OutOfMemory)
+    }
+
+    /// 停止监听 L2CAP PSM (已打开的通道不受影响)
+    pub fn unlisten_l2cap(&mut self, psm: u16) {
+        self.l2cap_psms.retain(|&p| p != psm);
+    }
+
+    /// 是否接受该 PSM 上的连接请求 (协议栈收到请求时查询，否则回复 PSM 不支持)
+    pub fn is_listening_l2cap(&self, psm: u16) -> bool {
+        self.l2cap_psms.contains(&psm)
+    }
+
+    /// 登记已建立的 L2CAP 通道 (被动接受或主动发起)
+    pub fn l2cap_opened(&mut self, conn_handle: u16, psm: u16) -> Result<(), BleError> {
+        let conn = self.connection_mut(conn_handle).ok_or(BleError::Disconnected)?;
+        if !conn.channels.contains(&psm) {
+            conn.channels.push(psm).map_err(|_| BleError::OutOfMemory)?;
+        }
+        self.emit(BleEvent::ChannelOpened { conn_handle, psm });
+        Ok(())
+    }
+
+    /// 登记已关闭的 L2CAP 通道
+    pub fn l2cap_closed(&mut self, conn_handle: u16, psm: u16) {
+        let removed = self.connection_mut(conn_handle).is_some_and(|conn| {
+            let before = conn.channels.len();
+            conn.channels.retain(|&p| p != psm);
+            conn.channels.len() != before
+        });
+        if removed {
+            self.emit(BleEvent::ChannelClosed { conn_handle, psm });
+        }
+    }
+
+    /// 连接上已打开的 L2CAP 通道 (PSM)
+    pub fn l2cap_channels(&self, conn_handle: u16) -> &[u16] {
+        self.connection(conn_handle).map(|c| c.channels.as_slice()).unwrap_or(&[])
+    }
+
+    fn connection(&self, handle: u16) -> Option<&Connection> {
+        self.connections.iter().find(|c| c.info.handle == handle)
+    }
+
+    fn connection_mut(&mut self, handle: u16) -> Option<&mut Connection> {
+        self.connections.iter_mut().find(|c| c.info.handle == handle)
+    }
+
+    /// 按请求与连接数重新计算广播模式，变化时投递事件
+    fn update_advertising(&mut self) {
+        let next = match &self.adv_config {
+            Some(config) if self.adv_enabled => config.mode_for(self.connections.len()),
+            _ => None,
+        };
+        if next != self.advertising {
+            if self.advertising.is_some() {
+                self.emit(BleEvent::AdvertisingStopped);
+            }
+            self.advertising = next;
+            if next.is_some() {
+                self.emit(BleEvent::AdvertisingStarted);
+            }
+        }
+        self.refresh_state();
+    }
+
+    /// 由连接与广播状态推导总体状态 (连接优先)
+    fn refresh_state(&mut self) {
+        if self.state == BleState::Uninitialized {
+            return;
+        }
+        self.state = if !self.connections.is_empty() {
+            BleState::Connected
+        } else if self.advertising.is_some() {
+            BleState::Advertising
+        } else {
+            BleState::Idle
+        };
     }
 
     /// 发送通知
@@ -528,29 +805,104 @@ impl<'a> BleController<'a> {
         attr_handle: u16,
         data: &[u8],
     ) -> Result<(), BleError> {
-        if !self.connections.iter().any(|c| c.handle == conn_handle) {
+        if self.connection(conn_handle).is_none() {
             return Err(BleError::Disconnected);
         }
 
         // 状态管理层 - 实际通知通过 trouble_host GATT API 完成
         let _ = attr_handle; // 暂用于类型检查
         let _ = data;
-        let _ = self.event_channel.try_send(BleEvent::NotificationSent { conn_handle });
+        self.emit(BleEvent::NotificationSent { conn_handle });
 
         Ok(())
     }
 
+    /// 向所有订阅了指定特征的连接发送通知
+    ///
+    /// # 返回
+    /// 发送的连接数
+    pub async fn notify_subscribers(&self, attr_handle: u16, data: &[u8]) -> usize {
+        let mut sent = 0;
+        for handle in self.subscribers(attr_handle) {
+            if self.notify(handle, attr_handle, data).await.is_ok() {
+                sent += 1;
+            }
+        }
+        sent
+    }
+
     /// 接收 BLE 事件
     pub async fn recv_event(&self) -> BleEvent {
-        self.event_channel.receive().await
+        self.event_channel.receive().await.event
     }
 
     /// 尝试接收 BLE 事件 (非阻塞)
     pub fn try_recv_event(&self) -> Option<BleEvent> {
-        self.event_channel.try_receive().ok()
+        self.event_channel.try_receive().ok().map(|envelope| envelope.event)
+    }
+
+    /// 接收带序号与时间戳的事件
+    pub async fn recv_envelope(&self) -> Envelope<BleEvent> {
+        self.event_channel.receive().await
+    }
+
+    /// 投递事件 (通道满时丢弃)
+    fn emit(&self, event: BleEvent) {
+        let _ = self.event_channel.try_send(Envelope::new("ble", event));
+        tuning::record(Knob::BleEventQueue, self.event_channel.len());
+    }
+
+    /// 注入事件 (仿真/回放模式)
+    ///
+    /// 与协议栈上报事件的路径一致地投递到事件通道 (连接由 `wait_for_connection`
+    /// 登记)，并处理对端发起的断开、MTU 与配对状态。断开后按需恢复可连接广播。
+    /// 参见 `net::replay`。
+    pub async fn inject_event(&mut self, event: BleEvent) {
+        match &event {
+            BleEvent::AdvertisingStarted => {
+                self.adv_enabled = true;
+                self.advertising.get_or_insert(AdvertisingMode::Connectable);
+            }
+            BleEvent::AdvertisingStopped => {
+                self.adv_enabled = false;
+                self.advertising = None;
+            }
+            BleEvent::Disconnected { conn_handle, .. } => {
+                self.connections.retain(|c| c.info.handle != *conn_handle);
+            }
+            BleEvent::MtuUpdated { conn_handle, mtu } => {
+                if let Some(conn) = self.connection_mut(*conn_handle) {
+                    conn.info.mtu = *mtu;
+                }
+            }
+            BleEvent::PairingComplete { conn_handle, bonded } => {
+                if let Some(conn) = self.connection_mut(*conn_handle) {
+                    conn.info.bonded = *bonded;
+                }
+            }
+            BleEvent::ChannelOpened { conn_handle, psm } => {
+                if let Some(conn) = self.connection_mut(*conn_handle) {
+                    if !conn.channels.contains(psm) {
+                        let _ = conn.channels.push(*psm);
+                    }
+                }
+            }
+            BleEvent::ChannelClosed { conn_handle, psm } => {
+                if let Some(conn) = self.connection_mut(*conn_handle) {
+                    conn.channels.retain(|p| p != psm);
+                }
+            }
+            _ => {}
+        }
+        self.event_channel.send(Envelope::new("ble", event)).await;
+        tuning::record(Knob::BleEventQueue, self.event_channel.len());
+        self.update_advertising();
     }
 
     /// 等待连接
+    ///
+    /// 每个连接获得独立的 GATT 上下文；超过 `BLE_MAX_CONNECTIONS` 时返回
+    /// `MaxConnectionsReached`，连接数满后广播降为不可连接。
     pub async fn wait_for_connection(&mut self) -> Result<ConnectionInfo, BleError> {
         loop {
             match self.recv_event().await {
@@ -565,11 +917,16 @@ impl<'a> BleController<'a> {
                         bonded: false,
                     };
                     
-                    if self.connections.push(conn.clone()).is_err() {
+                    let accepted = self
+                        .connections
+                        .push(Connection { info: conn.clone(), gatt: GattContext::new(), channels: Vec::new() })
+                        .is_ok();
+                    tuning::record(Knob::BleConnections, self.connections.len());
+                    if !accepted {
                         return Err(BleError::MaxConnectionsReached);
                     }
-                    
-                    self.state = BleState::Connected;
+
+                    self.update_advertising();
                     return Ok(conn);
                 }
                 _ => continue,
@@ -578,6 +935,21 @@ impl<'a> BleController<'a> {
     }
 }
 
+impl DriverLifecycle for BleController<'_> {
+    fn name(&self) -> &'static str {
+        "ble"
+    }
+
+    /// 深度睡眠与关闭前停止广播并断开全部连接，对端据此立即得知离线
+    async fn suspend(&mut self, kind: SleepKind) -> Result<(), LifecycleError> {
+        if kind == SleepKind::Deep && self.state != BleState::Uninitialized {
+            self.stop_advertising().await.map_err(|_| LifecycleError::InvalidState)?;
+            self.disconnect_all().await.map_err(|_| LifecycleError::InvalidState)?;
+        }
+        Ok(())
+    }
+}
+
 // ===== GATT Server =====
 
 /// GATT Server 构建器
@@ -641,6 +1013,28 @@ impl GattServer {
     }
 }
 
+// ===== 设备信息服务 =====
+
+/// 设备信息服务 (标准 DIS 0x180A，附加能力特征)
+///
+/// `capabilities` 为 `sys::caps::Capabilities::to_bytes()` (编译位 u32 + 就绪位 u32，小端)，
+/// 主机工具连接后读取即可适配设备功能。需加入应用的 `#[gatt_server]`，并在启动完成后写入:
+///
+/// ```rust,ignore
+/// server.set(&server.device_info.firmware_revision, &String::try_from(rustrtos::VERSION).unwrap())?;
+/// server.set(&server.device_info.capabilities, &rustrtos::sys::capabilities().to_bytes())?;
+/// ```
+#[cfg(feature = "ble")]
+#[trouble_host::prelude::gatt_service(uuid = trouble_host::prelude::service::DEVICE_INFORMATION)]
+pub struct DeviceInfoService {
+    /// 固件版本
+    #[characteristic(uuid = trouble_host::prelude::characteristic::FIRMWARE_REVISION_STRING, read)]
+    pub firmware_revision: String<16>,
+    /// 设备能力
+    #[characteristic(uuid = "72727473-6361-7073-0000-000000000001", read)]
+    pub capabilities: [u8; 8],
+}
+
 // ===== BLE 统计信息 =====
 
 /// BLE 统计信息
@@ -661,3 +1055,37 @@ pub struct BleStats {
     /// 接收错误
     pub rx_errors: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advertising_mode_while_connected() {
+        let config = AdvertiseConfig::default();
+        assert_eq!(config.mode_for(0), Some(AdvertisingMode::Connectable));
+        assert_eq!(config.mode_for(1), None);
+
+        let config = config.with_while_connected(true);
+        assert_eq!(config.mode_for(BLE_MAX_CONNECTIONS - 1), Some(AdvertisingMode::Connectable));
+        assert_eq!(config.mode_for(BLE_MAX_CONNECTIONS), Some(AdvertisingMode::NonConnectable));
+
+        let beacon = AdvertiseConfig::default().with_connectable(false);
+        assert_eq!(beacon.mode_for(0), Some(AdvertisingMode::NonConnectable));
+    }
+
+    #[test]
+    fn test_gatt_context_subscriptions() {
+        let mut gatt = GattContext::new();
+        gatt.subscribe(0x2a, true, false).unwrap();
+        gatt.subscribe(0x2a, false, true).unwrap();
+        assert_eq!(gatt.subscriptions(), &[Subscription { attr_handle: 0x2a, notify: false, indicate: true }]);
+        gatt.subscribe(0x2a, false, false).unwrap();
+        assert!(!gatt.is_subscribed(0x2a));
+
+        for handle in 0..MAX_SUBSCRIPTIONS as u16 {
+            gatt.subscribe(handle, true, false).unwrap();
+        }
+        assert_eq!(gatt.subscribe(0x100, true, false), Err(BleError::OutOfMemory));
+    }
+}
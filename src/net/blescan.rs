@@ -0,0 +1,376 @@
+//! BLE 扫描结果流 (中心角色)
+//!
+//! 网关类应用要同时跟踪数百个广播者，若每个广播报告都复制并唤醒应用任务，
+//! 大部分开销花在无关设备上。本模块在协议栈的扫描回调中就地求值过滤表达式，
+//! 只有匹配的报告才复制进队列并唤醒消费者:
+//! - `Filter`: 声明式过滤表达式 (名称前缀、服务 UUID、厂商 ID、最小 RSSI，
+//!   可用 `All` / `Any` / `Not` 组合，均可在 `const` 中构造)
+//! - 广播数据按 AD 结构原地解析，不分配、不复制
+//! - 去重窗口: 同一地址在窗口内只投递一次 (直接映射表，冲突时宁可多投递)
+//! - 消费者通过 `next` 或 `util::stream::Stream` (`stream`) 异步接收
+//!
+//! 与具体协议栈无关: 在 trouble-host 的 `on_adv_reports` 等回调中调用 `ingest`。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::blescan::{Filter, ScanStream};
+//!
+//! // 厂商 0x004C 或广播温湿度服务 (0x181A)，且 RSSI 不低于 -85 dBm
+//! static MATCH: Filter = Filter::All(&[
+//!     Filter::Any(&[Filter::ManufacturerId(0x004C), Filter::ServiceUuid16(0x181A)]),
+//!     Filter::MinRssi(-85),
+//! ]);
+//! static SCAN: ScanStream<32> = ScanStream::new(&MATCH).with_dedup(Duration::from_secs(5));
+//!
+//! // 协议栈扫描回调 (不阻塞)
+//! for report in reports {
+//!     SCAN.ingest(report.addr.raw(), report.rssi, report.data);
+//! }
+//!
+//! // 网关任务
+//! loop {
+//!     let report = SCAN.next().await;
+//!     log_info!("{} {} dBm {:?}", MacAddr(report.addr), report.rssi, report.name());
+//! }
+//! ```
+
+use core::cell::{Cell, RefCell};
+use core::fmt;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_sync::channel::Channel;
+use embassy_time::{Duration, Instant};
+use heapless::Vec;
+
+use crate::util::stream::{self, ChannelStream};
+
+/// 传统广播数据最大长度
+pub const MAX_ADV_LEN: usize = 31;
+
+/// 去重表槽位数
+pub const DEDUP_SLOTS: usize = 64;
+
+// AD 类型
+const AD_UUID16_INCOMPLETE: u8 = 0x02;
+const AD_UUID16_COMPLETE: u8 = 0x03;
+const AD_UUID128_INCOMPLETE: u8 = 0x06;
+const AD_UUID128_COMPLETE: u8 = 0x07;
+const AD_NAME_SHORT: u8 = 0x08;
+const AD_NAME_COMPLETE: u8 = 0x09;
+const AD_SERVICE_DATA16: u8 = 0x16;
+const AD_MANUFACTURER: u8 = 0xFF;
+
+// ===== 广播数据解析 =====
+
+/// AD 结构迭代器: 产出 `(类型, 数据)`，遇到格式错误时结束
+#[derive(Clone)]
+pub struct AdStructures<'a> {
+    data: &'a [u8],
+}
+
+/// 解析广播数据
+pub fn ad_structures(data: &[u8]) -> AdStructures<'_> {
+    AdStructures { data }
+}
+
+impl<'a> Iterator for AdStructures<'a> {
+    type Item = (u8, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (&len, rest) = self.data.split_first()?;
+        let len = len as usize;
+        if len == 0 || len > rest.len() {
+            self.data = &[];
+            return None;
+        }
+        let (field, rest) = rest.split_at(len);
+        self.data = rest;
+        let (&kind, value) = field.split_first()?;
+        Some((kind, value))
+    }
+}
+
+/// 广播的设备名 (完整或缩写)
+fn name_of(data: &[u8]) -> Option<&[u8]> {
+    ad_structures(data).find(|(kind, _)| matches!(*kind, AD_NAME_COMPLETE | AD_NAME_SHORT)).map(|(_, v)| v)
+}
+
+/// 厂商 ID 与厂商数据
+fn manufacturer_of(data: &[u8]) -> Option<(u16, &[u8])> {
+    let (_, value) = ad_structures(data).find(|(kind, value)| *kind == AD_MANUFACTURER && value.len() >= 2)?;
+    Some((u16::from_le_bytes([value[0], value[1]]), &value[2..]))
+}
+
+/// 是否广播了 16 位服务 UUID (服务列表或服务数据)
+fn has_uuid16(data: &[u8], uuid: u16) -> bool {
+    let target = uuid.to_le_bytes();
+    ad_structures(data).any(|(kind, value)| match kind {
+        AD_UUID16_INCOMPLETE | AD_UUID16_COMPLETE => value.chunks_exact(2).any(|c| c == target),
+        AD_SERVICE_DATA16 => value.get(..2) == Some(&target[..]),
+        _ => false,
+    })
+}
+
+/// 是否广播了 128 位服务 UUID (小端字节序，与空口一致)
+fn has_uuid128(data: &[u8], uuid: &[u8; 16]) -> bool {
+    ad_structures(data).any(|(kind, value)| {
+        matches!(kind, AD_UUID128_INCOMPLETE | AD_UUID128_COMPLETE) && value.chunks_exact(16).any(|c| c == uuid)
+    })
+}
+
+// ===== 过滤表达式 =====
+
+/// 过滤表达式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+    /// 设备名以此前缀开头
+    NamePrefix(&'static str),
+    /// 广播了该 16 位服务 UUID
+    ServiceUuid16(u16),
+    /// 广播了该 128 位服务 UUID (小端)
+    ServiceUuid128([u8; 16]),
+    /// 厂商数据的厂商 ID
+    ManufacturerId(u16),
+    /// RSSI 不低于 (dBm)
+    MinRssi(i8),
+    /// 全部满足
+    All(&'static [Filter]),
+    /// 任一满足
+    Any(&'static [Filter]),
+    /// 取反
+    Not(&'static Filter),
+}
+
+impl Filter {
+    /// 对一条广播报告求值
+    pub fn matches(&self, rssi: i8, data: &[u8]) -> bool {
+        match self {
+            Self::NamePrefix(prefix) => name_of(data).is_some_and(|name| name.starts_with(prefix.as_bytes())),
+            Self::ServiceUuid16(uuid) => has_uuid16(data, *uuid),
+            Self::ServiceUuid128(uuid) => has_uuid128(data, uuid),
+            Self::ManufacturerId(id) => manufacturer_of(data).is_some_and(|(company, _)| company == *id),
+            Self::MinRssi(min) => rssi >= *min,
+            Self::All(filters) => filters.iter().all(|f| f.matches(rssi, data)),
+            Self::Any(filters) => filters.iter().any(|f| f.matches(rssi, data)),
+            Self::Not(filter) => !filter.matches(rssi, data),
+        }
+    }
+}
+
+// ===== 扫描报告 =====
+
+/// 匹配的扫描报告
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanReport {
+    /// 广播者地址
+    pub addr: [u8; 6],
+    /// 信号强度 (dBm)
+    pub rssi: i8,
+    /// 广播数据 (超出 `MAX_ADV_LEN` 的部分截断)
+    pub data: Vec<u8, MAX_ADV_LEN>,
+    /// 接收时刻
+    pub timestamp: Instant,
+}
+
+impl ScanReport {
+    /// 设备名 (非 UTF-8 时为 `None`)
+    pub fn name(&self) -> Option<&str> {
+        name_of(&self.data).and_then(|name| core::str::from_utf8(name).ok())
+    }
+
+    /// 厂商 ID 与厂商数据
+    pub fn manufacturer(&self) -> Option<(u16, &[u8])> {
+        manufacturer_of(&self.data)
+    }
+
+    /// AD 结构
+    pub fn ad_structures(&self) -> AdStructures<'_> {
+        ad_structures(&self.data)
+    }
+}
+
+/// 扫描统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanStats {
+    /// 收到的广播报告
+    pub received: u32,
+    /// 通过过滤
+    pub matched: u32,
+    /// 被去重窗口抑制
+    pub deduplicated: u32,
+    /// 队列满丢弃
+    pub dropped: u32,
+}
+
+impl fmt::Display for ScanStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "received={} matched={} dedup={} dropped={}",
+            self.received, self.matched, self.deduplicated, self.dropped
+        )
+    }
+}
+
+// ===== 扫描流 =====
+
+#[derive(Clone, Copy)]
+struct Slot {
+    addr: [u8; 6],
+    at: Instant,
+}
+
+/// 扫描结果流 (队列深度 `N`)
+pub struct ScanStream<const N: usize> {
+    filter: BlockingMutex<CriticalSectionRawMutex, Cell<&'static Filter>>,
+    dedup: Option<Duration>,
+    seen: BlockingMutex<CriticalSectionRawMutex, RefCell<[Option<Slot>; DEDUP_SLOTS]>>,
+    stats: BlockingMutex<CriticalSectionRawMutex, Cell<ScanStats>>,
+    channel: Channel<CriticalSectionRawMutex, ScanReport, N>,
+}
+
+impl<const N: usize> ScanStream<N> {
+    /// 创建 (不去重)
+    pub const fn new(filter: &'static Filter) -> Self {
+        Self {
+            filter: BlockingMutex::new(Cell::new(filter)),
+            dedup: None,
+            seen: BlockingMutex::new(RefCell::new([None; DEDUP_SLOTS])),
+            stats: BlockingMutex::new(Cell::new(ScanStats { received: 0, matched: 0, deduplicated: 0, dropped: 0 })),
+            channel: Channel::new(),
+        }
+    }
+
+    /// 设置去重窗口
+    pub const fn with_dedup(mut self, window: Duration) -> Self {
+        self.dedup = Some(window);
+        self
+    }
+
+    /// 替换过滤表达式 (清空去重表)
+    pub fn set_filter(&self, filter: &'static Filter) {
+        self.filter.lock(|f| f.set(filter));
+        self.seen.lock(|seen| *seen.borrow_mut() = [None; DEDUP_SLOTS]);
+    }
+
+    fn update_stats(&self, f: impl FnOnce(&mut ScanStats)) {
+        self.stats.lock(|cell| {
+            let mut stats = cell.get();
+            f(&mut stats);
+            cell.set(stats);
+        });
+    }
+
+    /// 去重: 窗口内已投递过则返回 false
+    fn admit(&self, addr: &[u8; 6], now: Instant) -> bool {
+        let Some(window) = self.dedup else {
+            return true;
+        };
+        let index = addr.iter().fold(0usize, |h, &b| h.wrapping_mul(31).wrapping_add(b as usize)) % DEDUP_SLOTS;
+        self.seen.lock(|seen| {
+            let mut seen = seen.borrow_mut();
+            let slot = &mut seen[index];
+            if slot.is_some_and(|s| s.addr == *addr && now < s.at + window) {
+                return false;
+            }
+            *slot = Some(Slot { addr: *addr, at: now });
+            true
+        })
+    }
+
+    /// 投入一条广播报告 (协议栈回调中调用，不阻塞)
+    ///
+    /// 返回是否进入队列。
+    pub fn ingest(&self, addr: [u8; 6], rssi: i8, data: &[u8]) -> bool {
+        self.ingest_at(addr, rssi, data, Instant::now())
+    }
+
+    /// 指定接收时刻投入广播报告
+    pub fn ingest_at(&self, addr: [u8; 6], rssi: i8, data: &[u8], now: Instant) -> bool {
+        let filter = self.filter.lock(|f| f.get());
+        let matched = filter.matches(rssi, data);
+        self.update_stats(|s| {
+            s.received = s.received.wrapping_add(1);
+            s.matched = s.matched.wrapping_add(matched as u32);
+        });
+        if !matched {
+            return false;
+        }
+        if !self.admit(&addr, now) {
+            self.update_stats(|s| s.deduplicated = s.deduplicated.wrapping_add(1));
+            return false;
+        }
+
+        let len = data.len().min(MAX_ADV_LEN);
+        let report = ScanReport { addr, rssi, data: Vec::from_slice(&data[..len]).unwrap_or_default(), timestamp: now };
+        if self.channel.try_send(report).is_err() {
+            self.update_stats(|s| s.dropped = s.dropped.wrapping_add(1));
+            return false;
+        }
+        true
+    }
+
+    /// 接收下一条匹配的报告
+    pub async fn next(&self) -> ScanReport {
+        self.channel.receive().await
+    }
+
+    /// 非阻塞接收
+    pub fn try_next(&self) -> Option<ScanReport> {
+        self.channel.try_receive().ok()
+    }
+
+    /// 作为 `util::stream::Stream` (可接 `map`、`chunked` 等适配器)
+    pub fn stream(&self) -> ChannelStream<'_, ScanReport, N> {
+        stream::from_channel(&self.channel)
+    }
+
+    /// 统计
+    pub fn stats(&self) -> ScanStats {
+        self.stats.lock(|cell| cell.get())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADV: &[u8] = &[
+        0x02, 0x01, 0x06, // flags
+        0x03, 0x03, 0x1A, 0x18, // 16 位 UUID 0x181A
+        0x06, 0x09, b'T', b'H', b'-', b'0', b'1', // 完整名称 "TH-01"
+        0x05, 0xFF, 0x4C, 0x00, 0x02, 0x15, // 厂商 0x004C
+    ];
+
+    #[test]
+    fn test_filter_expressions() {
+        static SENSOR: Filter = Filter::All(&[Filter::NamePrefix("TH-"), Filter::ServiceUuid16(0x181A), Filter::MinRssi(-80)]);
+        static NOT_APPLE: Filter = Filter::Not(&Filter::ManufacturerId(0x004C));
+
+        assert!(SENSOR.matches(-70, ADV));
+        assert!(!SENSOR.matches(-90, ADV));
+        assert!(!NOT_APPLE.matches(-70, ADV));
+        assert!(Filter::Any(&[Filter::ServiceUuid16(0xFEAA), Filter::ManufacturerId(0x004C)]).matches(0, ADV));
+        // 截断的 AD 结构不越界
+        assert!(!Filter::NamePrefix("TH").matches(0, &[0x06, 0x09, b'T']));
+    }
+
+    #[test]
+    fn test_stream_dedup_and_stats() {
+        static FILTER: Filter = Filter::ServiceUuid16(0x181A);
+        let scan: ScanStream<2> = ScanStream::new(&FILTER).with_dedup(Duration::from_secs(5));
+        let t0 = Instant::from_secs(100);
+
+        assert!(scan.ingest_at([1; 6], -60, ADV, t0));
+        assert!(!scan.ingest_at([1; 6], -60, ADV, t0 + Duration::from_secs(1)));
+        assert!(!scan.ingest_at([2; 6], -60, &ADV[..3], t0));
+        assert!(scan.ingest_at([1; 6], -58, ADV, t0 + Duration::from_secs(6)));
+        assert!(!scan.ingest_at([3; 6], -60, ADV, t0));
+
+        let report = scan.try_next().unwrap();
+        assert_eq!((report.addr, report.name(), report.manufacturer().map(|m| m.0)), ([1; 6], Some("TH-01"), Some(0x004C)));
+        assert_eq!(scan.stats(), ScanStats { received: 5, matched: 4, deduplicated: 1, dropped: 1 });
+    }
+}
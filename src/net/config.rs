@@ -1,9 +1,12 @@
 //! 网络配置常量
 //!
 //! 定义 WiFi、BLE 和 TCP/IP 网络的默认配置参数。
+//! 队列深度与缓冲区大小取自 `tuning::TUNING`，在那里统一调整。
 
 use heapless::String;
 
+use super::tuning::TUNING;
+
 /// 网络配置结构
 #[derive(Clone)]
 pub struct NetworkConfig {
@@ -11,7 +14,7 @@ pub struct NetworkConfig {
     pub wifi_ssid: String<32>,
     /// WiFi 密码 (最大 64 字节)
     pub wifi_password: String<64>,
-    /// BLE 设备名称 (最大 32 字节)
+    /// BLE 设备名称 (最大 32 字节，默认取自 `sys::identity`)
     pub ble_device_name: String<32>,
     /// DHCP 启用
     pub dhcp_enabled: bool,
@@ -28,7 +31,7 @@ impl Default for NetworkConfig {
         Self {
             wifi_ssid: String::new(),
             wifi_password: String::new(),
-            ble_device_name: String::try_from("RustRTOS").unwrap_or_default(),
+            ble_device_name: String::try_from(crate::sys::identity::IDENTITY.ble_name().as_str()).unwrap_or_default(),
             dhcp_enabled: true,
             static_ip: None,
             gateway: None,
@@ -92,10 +95,13 @@ pub const WIFI_RECONNECT_INTERVAL_MS: u32 = 5_000;
 pub const WIFI_MAX_RECONNECT_ATTEMPTS: u32 = 10;
 
 /// WiFi 事件队列大小
-pub const WIFI_EVENT_QUEUE_SIZE: usize = 8;
+pub const WIFI_EVENT_QUEUE_SIZE: usize = TUNING.wifi_event_queue;
 
 /// WiFi 扫描结果最大数量
-pub const WIFI_MAX_SCAN_RESULTS: usize = 16;
+pub const WIFI_MAX_SCAN_RESULTS: usize = TUNING.wifi_scan_results;
+
+/// SoftAP 最大客户端数量 (esp-radio 上限)
+pub const WIFI_AP_MAX_CLIENTS: usize = 10;
 
 // ===== BLE 配置常量 =====
 
@@ -121,43 +127,90 @@ pub const BLE_SUPERVISION_TIMEOUT: u16 = 400; // 4 秒
 pub const BLE_MTU_SIZE: u16 = 247;
 
 /// BLE 最大连接数
-pub const BLE_MAX_CONNECTIONS: usize = 3;
+pub const BLE_MAX_CONNECTIONS: usize = TUNING.ble_connections;
 
 /// BLE 事件队列大小
-pub const BLE_EVENT_QUEUE_SIZE: usize = 8;
+pub const BLE_EVENT_QUEUE_SIZE: usize = TUNING.ble_event_queue;
 
 // ===== TCP/IP 配置常量 =====
 
 /// TCP 接收缓冲区大小
-pub const TCP_RX_BUFFER_SIZE: usize = 4096;
+pub const TCP_RX_BUFFER_SIZE: usize = TUNING.tcp_rx_buffer;
 
 /// TCP 发送缓冲区大小
-pub const TCP_TX_BUFFER_SIZE: usize = 4096;
+pub const TCP_TX_BUFFER_SIZE: usize = TUNING.tcp_tx_buffer;
 
 /// UDP 接收缓冲区大小
-pub const UDP_RX_BUFFER_SIZE: usize = 2048;
+pub const UDP_RX_BUFFER_SIZE: usize = TUNING.udp_rx_buffer;
 
 /// UDP 发送缓冲区大小
-pub const UDP_TX_BUFFER_SIZE: usize = 2048;
+pub const UDP_TX_BUFFER_SIZE: usize = TUNING.udp_tx_buffer;
 
 /// 最大 TCP Socket 数量
-pub const MAX_TCP_SOCKETS: usize = 4;
+pub const MAX_TCP_SOCKETS: usize = TUNING.tcp_sockets;
 
 /// 最大 UDP Socket 数量
-pub const MAX_UDP_SOCKETS: usize = 4;
+pub const MAX_UDP_SOCKETS: usize = TUNING.udp_sockets;
 
 /// DNS 缓存大小
 pub const DNS_CACHE_SIZE: usize = 4;
 
+/// DNS 缓存默认 TTL (秒，解析器未提供 TTL 时使用)
+pub const DNS_DEFAULT_TTL_SECS: u32 = 300;
+
+/// DNS 缓存最大 TTL (秒)
+pub const DNS_MAX_TTL_SECS: u32 = 3600;
+
+/// DNS 解析失败的负缓存时间 (秒)
+pub const DNS_NEGATIVE_TTL_SECS: u32 = 30;
+
+/// DNS 单次查询超时 (毫秒，每个服务器每次尝试)
+pub const DNS_QUERY_TIMEOUT_MS: u32 = 2_000;
+
+/// DNS 查询尝试轮数 (每轮依次询问全部服务器)
+pub const DNS_QUERY_ATTEMPTS: u8 = 2;
+
+/// 可配置的 DNS 服务器数量上限
+pub const DNS_MAX_SERVERS: usize = 3;
+
 /// DHCP 超时时间 (秒)
 pub const DHCP_TIMEOUT_SECS: u32 = 30;
 
+/// 使用静态回退配置期间重试 DHCP 的间隔 (秒)
+pub const DHCP_RETRY_INTERVAL_SECS: u32 = 300;
+
+/// SoftAP DHCP 服务器地址池大小
+pub const DHCP_SERVER_POOL_SIZE: usize = 8;
+
+/// SoftAP DHCP 服务器租期 (秒)
+pub const DHCP_SERVER_LEASE_SECS: u32 = 7200;
+
 /// TCP 连接超时 (秒)
 pub const TCP_CONNECT_TIMEOUT_SECS: u32 = 10;
 
 /// TCP Keep-Alive 间隔 (秒)
 pub const TCP_KEEPALIVE_INTERVAL_SECS: u32 = 60;
 
+// ===== MQTT 配置常量 =====
+
+/// MQTT 默认保活间隔 (秒)
+pub const MQTT_KEEP_ALIVE_SECS: u16 = 60;
+
+/// 等待 CONNACK/PUBACK/SUBACK 的超时 (秒)
+pub const MQTT_ACK_TIMEOUT_SECS: u32 = 10;
+
+/// MQTT 报文最大长度 (收发缓冲区各一份)
+pub const MQTT_MAX_PACKET_LEN: usize = 1024;
+
+/// 收到的主题最大长度
+pub const MQTT_MAX_TOPIC_LEN: usize = 128;
+
+/// 收到的负载最大长度
+pub const MQTT_MAX_PAYLOAD_LEN: usize = 512;
+
+/// 收件通道深度
+pub const MQTT_INBOX_DEPTH: usize = 4;
+
 // ===== 网络缓冲区配置 =====
 
 /// 以太网帧最大大小
@@ -167,7 +220,7 @@ pub const ETHERNET_MTU: usize = 1514;
 pub const IP_MTU: usize = 1500;
 
 /// 网络缓冲区池大小
-pub const NET_BUFFER_POOL_SIZE: usize = 16;
+pub const NET_BUFFER_POOL_SIZE: usize = TUNING.net_buffer_pool;
 
 /// 单个网络缓冲区大小
-pub const NET_BUFFER_SIZE: usize = 1536;
+pub const NET_BUFFER_SIZE: usize = TUNING.net_buffer_size;
@@ -0,0 +1,369 @@
+//! TCP 控制台
+//!
+//! 将 `sys::shell` 暴露在 TCP 上，供无串口的台架设备调试:
+//! - Telnet (剥离 IAC 协商、服务端回显) 或原始 TCP 两种模式
+//! - 同一时刻仅允许一个会话
+//! - 经 `sys::auth::Authenticator` 认证 (口令来自凭据保险库)，未配置口令时拒绝登录
+//! - 策略中有多个用户时先询问用户名，否则只询问口令
+//! - 登录失败延迟 + 单连接次数上限；按客户端地址累计失败并锁定，审计事件发往系统总线
+//! - 会话空闲超时断开
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::console::{self, ConsoleConfig};
+//! use rustrtos::sys::{shell::Shell, vault::VAULT};
+//!
+//! static SHELL: Shell = Shell::new(&[]);
+//!
+//! VAULT.set_password("shell", b"bench-pass", &salt)?;
+//!
+//! #[embassy_executor::task]
+//! async fn console_task(stack: embassy_net::Stack<'static>) {
+//!     console::serve(stack, &SHELL, &ConsoleConfig::default()).await
+//! }
+//! ```
+
+use core::fmt;
+
+use embassy_net::tcp::TcpSocket;
+use embassy_net::{IpAddress, IpEndpoint, Stack};
+use embassy_time::{Duration, Timer};
+use embedded_io_async::{ErrorType, Read, Write};
+use heapless::String;
+
+use crate::sys::auth::{AuthError, AuthPolicy, Authenticator, Credential, UNKNOWN_SOURCE};
+use crate::sys::shell::{self, LineEditor, SessionConfig, Shell, ShellError, MAX_LINE};
+use crate::util::log::*;
+
+/// 默认控制台认证器: 用户 "shell"
+pub static CONSOLE_AUTH: Authenticator = Authenticator::new("console", AuthPolicy::new().with_users(&["shell"]));
+
+/// 控制台模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleMode {
+    /// Telnet 协议 (标准 telnet 客户端)
+    Telnet,
+    /// 原始 TCP (nc / socat)
+    Raw,
+}
+
+/// 控制台配置
+#[derive(Debug, Clone, Copy)]
+pub struct ConsoleConfig {
+    /// 监听端口
+    pub port: u16,
+    /// 协议模式
+    pub mode: ConsoleMode,
+    /// 会话空闲超时
+    pub idle_timeout: Duration,
+    /// 认证器 (策略中的用户名即保险库中的口令名称)
+    pub auth: &'static Authenticator,
+    /// 单次连接最多尝试次数
+    pub max_attempts: u8,
+    /// 登录失败后的延迟
+    pub failure_delay: Duration,
+}
+
+impl Default for ConsoleConfig {
+    fn default() -> Self {
+        Self {
+            port: 23,
+            mode: ConsoleMode::Telnet,
+            idle_timeout: Duration::from_secs(300),
+            auth: &CONSOLE_AUTH,
+            max_attempts: 3,
+            failure_delay: Duration::from_secs(2),
+        }
+    }
+}
+
+impl ConsoleConfig {
+    /// 设置端口
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// 设置模式
+    pub fn with_mode(mut self, mode: ConsoleMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// 设置空闲超时
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    /// 设置认证器
+    pub fn with_auth(mut self, auth: &'static Authenticator) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// 设置最多尝试次数
+    pub fn with_max_attempts(mut self, attempts: u8) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+}
+
+// ===== Telnet =====
+
+const IAC: u8 = 0xFF;
+const SE: u8 = 0xF0;
+const SB: u8 = 0xFA;
+const WILL: u8 = 0xFB;
+const DONT: u8 = 0xFE;
+const OPT_ECHO: u8 = 0x01;
+const OPT_SGA: u8 = 0x03;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TelnetState {
+    Data,
+    Iac,
+    Option,
+    Sub,
+    SubIac,
+}
+
+/// Telnet 传输适配器 (读时剥离命令序列，写时转义 0xFF)
+pub struct TelnetIo<'a, T> {
+    inner: &'a mut T,
+    state: TelnetState,
+}
+
+impl<'a, T: Read + Write> TelnetIo<'a, T> {
+    /// 包装连接
+    pub fn new(inner: &'a mut T) -> Self {
+        Self { inner, state: TelnetState::Data }
+    }
+
+    /// 协商服务端回显与抑制 GA (字符模式)
+    pub async fn negotiate(&mut self) -> Result<(), T::Error> {
+        self.inner.write_all(&[IAC, WILL, OPT_ECHO, IAC, WILL, OPT_SGA]).await?;
+        self.inner.flush().await
+    }
+
+    /// 过滤一个字节，返回是否为数据
+    fn filter(&mut self, byte: u8) -> bool {
+        self.state = match (self.state, byte) {
+            (TelnetState::Data, IAC) => TelnetState::Iac,
+            (TelnetState::Data, _) => return true,
+            // IAC IAC 表示数据 0xFF
+            (TelnetState::Iac, IAC) => {
+                self.state = TelnetState::Data;
+                return true;
+            }
+            (TelnetState::Iac, SB) => TelnetState::Sub,
+            (TelnetState::Iac, WILL..=DONT) => TelnetState::Option,
+            (TelnetState::Iac, _) | (TelnetState::Option, _) => TelnetState::Data,
+            (TelnetState::Sub, IAC) => TelnetState::SubIac,
+            (TelnetState::Sub, _) => TelnetState::Sub,
+            (TelnetState::SubIac, SE) => TelnetState::Data,
+            (TelnetState::SubIac, _) => TelnetState::Sub,
+        };
+        false
+    }
+}
+
+impl<T: ErrorType> ErrorType for TelnetIo<'_, T> {
+    type Error = T::Error;
+}
+
+impl<T: Read + Write> Read for TelnetIo<'_, T> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        loop {
+            let n = self.inner.read(buf).await?;
+            if n == 0 {
+                return Ok(0);
+            }
+            let mut kept = 0;
+            for i in 0..n {
+                let byte = buf[i];
+                if self.filter(byte) {
+                    buf[kept] = byte;
+                    kept += 1;
+                }
+            }
+            if kept > 0 {
+                return Ok(kept);
+            }
+        }
+    }
+}
+
+impl<T: Read + Write> Write for TelnetIo<'_, T> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        match buf.iter().position(|&b| b == IAC) {
+            Some(0) => {
+                self.inner.write_all(&[IAC, IAC]).await?;
+                Ok(1)
+            }
+            Some(pos) => self.inner.write(&buf[..pos]).await,
+            None => self.inner.write(buf).await,
+        }
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush().await
+    }
+}
+
+// ===== 会话 =====
+
+/// 登录失败原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LoginError {
+    NotProvisioned,
+    Rejected,
+    LockedOut(u32),
+    Shell(ShellError),
+}
+
+impl fmt::Display for LoginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotProvisioned => write!(f, "No console credential provisioned"),
+            Self::Rejected => write!(f, "Too many failed logins"),
+            Self::LockedOut(secs) => write!(f, "Client locked out for {} s", secs),
+            Self::Shell(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// 提示并读取一行
+async fn prompt<'e, T: Read + Write>(
+    io: &mut T,
+    editor: &'e mut LineEditor,
+    text: &str,
+    echo: bool,
+    timeout: Duration,
+) -> Result<&'e str, LoginError> {
+    shell::write_text(io, text).await.map_err(LoginError::Shell)?;
+    io.flush().await.map_err(|_| LoginError::Shell(ShellError::Io))?;
+    shell::read_line(io, editor, echo, timeout).await.map_err(LoginError::Shell)
+}
+
+async fn login<T: Read + Write>(io: &mut T, config: &ConsoleConfig, source: u32) -> Result<&'static str, LoginError> {
+    let auth = config.auth;
+    if !auth.provisioned() {
+        let _ = shell::write_text(io, "console disabled: no credential provisioned\n").await;
+        return Err(LoginError::NotProvisioned);
+    }
+
+    let mut editor = LineEditor::new();
+    for _ in 0..config.max_attempts {
+        if let Some(remaining) = auth.locked_out(source) {
+            let _ = shell::write_text(io, "too many failed logins, try again later\n").await;
+            return Err(LoginError::LockedOut(remaining.as_secs() as u32));
+        }
+
+        let mut user: String<MAX_LINE> = String::new();
+        match auth.policy().users {
+            [only] => {
+                let _ = user.push_str(only);
+            }
+            _ => {
+                let line = prompt(io, &mut editor, "Login: ", true, config.idle_timeout).await?;
+                let _ = user.push_str(line);
+            }
+        }
+        let password = prompt(io, &mut editor, "Password: ", false, config.idle_timeout).await?;
+        let result = auth.authenticate(source, Credential::Password { user: &user, password: password.as_bytes() });
+        editor.clear();
+        match result {
+            Ok(principal) => return Ok(principal),
+            Err(AuthError::LockedOut { retry_after_secs }) => return Err(LoginError::LockedOut(retry_after_secs)),
+            Err(_) => {}
+        }
+        Timer::after(config.failure_delay).await;
+        shell::write_text(io, "Login incorrect\n").await.map_err(LoginError::Shell)?;
+    }
+    Err(LoginError::Rejected)
+}
+
+async fn session<T: Read + Write>(
+    io: &mut T,
+    shell: &Shell,
+    config: &ConsoleConfig,
+    source: u32,
+) -> Result<(), LoginError> {
+    let principal = login(io, config, source).await?;
+    log_info!("Console session opened for {}", principal);
+    let session = SessionConfig::default().with_idle_timeout(config.idle_timeout);
+    shell::run_session(io, shell, &session).await.map_err(LoginError::Shell)
+}
+
+/// 客户端 IPv4 地址作为锁定键
+fn source_key(endpoint: Option<IpEndpoint>) -> u32 {
+    match endpoint.map(|e| e.addr) {
+        #[allow(unreachable_patterns)]
+        Some(IpAddress::Ipv4(addr)) => u32::from(addr),
+        _ => UNKNOWN_SOURCE,
+    }
+}
+
+/// 运行控制台服务 (单会话，连接结束后继续监听)
+pub async fn serve(stack: Stack<'_>, shell: &Shell, config: &ConsoleConfig) -> ! {
+    let mut rx = [0u8; 512];
+    let mut tx = [0u8; 1024];
+    log_info!("Console listening on port {} ({:?})", config.port, config.mode);
+    loop {
+        let mut socket = TcpSocket::new(stack, &mut rx, &mut tx);
+        socket.set_timeout(Some(config.idle_timeout));
+        if socket.accept(config.port).await.is_err() {
+            continue;
+        }
+        log_info!("Console connection from {:?}", socket.remote_endpoint());
+        let source = source_key(socket.remote_endpoint());
+
+        let result = match config.mode {
+            ConsoleMode::Telnet => {
+                let mut telnet = TelnetIo::new(&mut socket);
+                match telnet.negotiate().await {
+                    Ok(()) => session(&mut telnet, shell, config, source).await,
+                    Err(_) => Err(LoginError::Shell(ShellError::Io)),
+                }
+            }
+            ConsoleMode::Raw => session(&mut socket, shell, config, source).await,
+        };
+        match result {
+            Ok(()) => log_info!("Console session closed"),
+            Err(e) => log_warn!("Console session ended: {}", e),
+        }
+        socket.close();
+        let _ = socket.flush().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_telnet_filter() {
+        struct Dummy;
+        impl ErrorType for Dummy {
+            type Error = core::convert::Infallible;
+        }
+        impl Read for Dummy {
+            async fn read(&mut self, _: &mut [u8]) -> Result<usize, Self::Error> {
+                Ok(0)
+            }
+        }
+        impl Write for Dummy {
+            async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+                Ok(buf.len())
+            }
+        }
+
+        let mut dummy = Dummy;
+        let mut telnet = TelnetIo::new(&mut dummy);
+        let input = [b'a', IAC, 0xFD, OPT_ECHO, IAC, SB, 0x18, 0x00, IAC, SE, IAC, IAC, b'b'];
+        let data: heapless::Vec<u8, 16> = input.iter().copied().filter(|&b| telnet.filter(b)).collect();
+        assert_eq!(data.as_slice(), &[b'a', IAC, b'b']);
+    }
+}
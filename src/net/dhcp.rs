@@ -0,0 +1,630 @@
+//! DHCP 客户端
+//!
+//! DHCP 报文交换 (DISCOVER/OFFER/REQUEST/ACK) 与 T1/T2 续租、重绑由 embassy-net 内置的
+//! smoltcp DHCPv4 Socket 完成 (接口取得地址前普通 UDP Socket 无法以 0.0.0.0 发送广播)。
+//! 本模块补齐它没有暴露的部分:
+//! - 租约信息: `DhcpSnoop` 驱动包装在接收路径解析发给本机的 DHCP ACK/NAK，记录地址、掩码、
+//!   网关、DNS (选项 6)、服务器标识、租期与 T1/T2。`NetworkStack::new` 自动包装设备
+//! - 租约监管任务 `supervise`: 获取超时后切换到静态回退配置并按间隔重试 DHCP；
+//!   租约到期仍未续租成功时重新开始 DHCP；链路断开后重新获取
+//! - 统计: ACK/NAK/续租/回退/重启次数
+//!
+//! 租约状态是全局的 (设备只有一个 STA 协议栈)。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::dhcp::{self, DhcpConfig};
+//!
+//! #[embassy_executor::task]
+//! async fn dhcp_task(stack: &'static NetworkStack<'static>) {
+//!     let fallback = StackConfig::with_static(
+//!         Ipv4Address::new(192, 168, 4, 50),
+//!         Ipv4Address::new(255, 255, 255, 0),
+//!         Ipv4Address::new(192, 168, 4, 1),
+//!     );
+//!     dhcp::supervise(stack, &DhcpConfig::new().with_fallback(fallback)).await
+//! }
+//!
+//! if let Some(lease) = stack.lease() {
+//!     log_info!("lease from {} for {}s", lease.server.to_std(), lease.lease_secs);
+//! }
+//! ```
+
+use core::cell::RefCell;
+use core::fmt;
+use core::task::Context;
+
+use embassy_futures::select::{select, Either};
+use embassy_net_driver::{Capabilities, Driver, HardwareAddress, LinkState, RxToken};
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_time::{with_timeout, Duration, Instant, Timer};
+
+use super::config::{DHCP_RETRY_INTERVAL_SECS, DHCP_TIMEOUT_SECS};
+use super::tcp::{Ipv4Address, NetworkStack, StackConfig};
+use crate::sync::primitives::CriticalSignal;
+use crate::util::log::*;
+
+/// 租约中最多保留的 DNS 服务器数
+pub const MAX_DNS_SERVERS: usize = 3;
+
+/// 无限租期 (选项 51 取 0xFFFFFFFF)
+pub const INFINITE_LEASE: u32 = u32::MAX;
+
+/// 租约到期后等待续租 ACK 的宽限时间
+const EXPIRY_GRACE: Duration = Duration::from_secs(5);
+
+pub(super) const SERVER_PORT: u16 = 67;
+pub(super) const CLIENT_PORT: u16 = 68;
+pub(super) const BOOTREPLY: u8 = 2;
+pub(super) const MAGIC_COOKIE: [u8; 4] = [99, 130, 83, 99];
+/// 固定 BOOTP 头长度 (含魔数)
+pub(super) const OPTIONS_OFFSET: usize = 240;
+
+pub(super) const OPT_PAD: u8 = 0;
+pub(super) const OPT_SUBNET_MASK: u8 = 1;
+pub(super) const OPT_ROUTER: u8 = 3;
+pub(super) const OPT_DNS: u8 = 6;
+pub(super) const OPT_LEASE_TIME: u8 = 51;
+pub(super) const OPT_MESSAGE_TYPE: u8 = 53;
+pub(super) const OPT_SERVER_ID: u8 = 54;
+pub(super) const OPT_RENEWAL_TIME: u8 = 58;
+pub(super) const OPT_REBINDING_TIME: u8 = 59;
+pub(super) const OPT_END: u8 = 255;
+
+pub(super) const DHCPACK: u8 = 5;
+pub(super) const DHCPNAK: u8 = 6;
+
+// ===== 租约 =====
+
+/// DHCP 租约
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lease {
+    /// 分配的地址
+    pub address: Ipv4Address,
+    /// 子网掩码 (选项 1)
+    pub netmask: Option<Ipv4Address>,
+    /// 默认网关 (选项 3 的第一个)
+    pub router: Option<Ipv4Address>,
+    dns: [Ipv4Address; MAX_DNS_SERVERS],
+    dns_count: u8,
+    /// DHCP 服务器标识 (选项 54)
+    pub server: Ipv4Address,
+    /// 租期 (秒，`INFINITE_LEASE` 表示不过期)
+    pub lease_secs: u32,
+    /// 续租时间 T1 (秒，未提供时为租期的 1/2)
+    pub renew_secs: u32,
+    /// 重绑时间 T2 (秒，未提供时为租期的 7/8)
+    pub rebind_secs: u32,
+    /// 收到 ACK 的时刻
+    pub obtained_at: Instant,
+}
+
+impl Lease {
+    /// DNS 服务器 (选项 6)
+    pub fn dns_servers(&self) -> &[Ipv4Address] {
+        &self.dns[..self.dns_count as usize]
+    }
+
+    /// 是否为无限租期
+    pub fn is_infinite(&self) -> bool {
+        self.lease_secs == INFINITE_LEASE
+    }
+
+    /// 到期时刻 (无限租期返回 `None`)
+    pub fn expires_at(&self) -> Option<Instant> {
+        (!self.is_infinite()).then(|| self.obtained_at + Duration::from_secs(self.lease_secs as u64))
+    }
+
+    /// 续租时刻 (T1)
+    pub fn renew_at(&self) -> Option<Instant> {
+        (!self.is_infinite()).then(|| self.obtained_at + Duration::from_secs(self.renew_secs as u64))
+    }
+
+    /// 重绑时刻 (T2)
+    pub fn rebind_at(&self) -> Option<Instant> {
+        (!self.is_infinite()).then(|| self.obtained_at + Duration::from_secs(self.rebind_secs as u64))
+    }
+
+    /// 剩余租期 (无限租期返回 `None`)
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.expires_at().map(|at| at.saturating_duration_since(now))
+    }
+}
+
+/// 解析出的 DHCP 应答
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DhcpReply {
+    /// 确认 (获得或续租)
+    Ack(Lease),
+    /// 拒绝 (附服务器标识)
+    Nak(Option<Ipv4Address>),
+}
+
+/// 解析以太网帧中发给 `mac` 的 DHCP ACK/NAK (其他帧返回 `None`)
+pub fn parse_frame(frame: &[u8], mac: &[u8; 6], now: Instant) -> Option<DhcpReply> {
+    // 以太网 + IPv4 + UDP
+    if frame.len() < 14 + 20 + 8 || frame[12..14] != [0x08, 0x00] {
+        return None;
+    }
+    let ip = &frame[14..];
+    let ihl = ((ip[0] & 0x0F) as usize) * 4;
+    if ip[0] >> 4 != 4 || ihl < 20 || ip[9] != 17 || ip.len() < ihl + 8 {
+        return None;
+    }
+    let udp = &ip[ihl..];
+    if be16(udp, 0) != SERVER_PORT || be16(udp, 2) != CLIENT_PORT {
+        return None;
+    }
+    parse_message(&udp[8..], mac, now)
+}
+
+/// 解析 DHCP 报文 (BOOTP 负载)
+pub fn parse_message(msg: &[u8], mac: &[u8; 6], now: Instant) -> Option<DhcpReply> {
+    if msg.len() < OPTIONS_OFFSET
+        || msg[0] != BOOTREPLY
+        || msg[28..34] != mac[..]
+        || msg[236..240] != MAGIC_COOKIE
+    {
+        return None;
+    }
+
+    let mut message_type = None;
+    let mut netmask = None;
+    let mut router = None;
+    let mut dns = [Ipv4Address::UNSPECIFIED; MAX_DNS_SERVERS];
+    let mut dns_count = 0u8;
+    let mut server = None;
+    let mut lease_secs = None;
+    let mut renew_secs = None;
+    let mut rebind_secs = None;
+
+    let mut pos = OPTIONS_OFFSET;
+    while pos < msg.len() {
+        let code = msg[pos];
+        if code == OPT_PAD {
+            pos += 1;
+            continue;
+        }
+        if code == OPT_END {
+            break;
+        }
+        let len = *msg.get(pos + 1)? as usize;
+        let data = msg.get(pos + 2..pos + 2 + len)?;
+        pos += 2 + len;
+        match (code, data.len()) {
+            (OPT_MESSAGE_TYPE, 1) => message_type = Some(data[0]),
+            (OPT_SUBNET_MASK, 4) => netmask = Some(addr(data)),
+            (OPT_ROUTER, n) if n >= 4 => router = Some(addr(data)),
+            (OPT_DNS, n) if n >= 4 => {
+                // 选项 6 可能重复出现，超出容量的服务器丢弃
+                for chunk in data.chunks_exact(4) {
+                    let Some(slot) = dns.get_mut(dns_count as usize) else { break };
+                    *slot = addr(chunk);
+                    dns_count += 1;
+                }
+            }
+            (OPT_SERVER_ID, 4) => server = Some(addr(data)),
+            (OPT_LEASE_TIME, 4) => lease_secs = Some(be32(data)),
+            (OPT_RENEWAL_TIME, 4) => renew_secs = Some(be32(data)),
+            (OPT_REBINDING_TIME, 4) => rebind_secs = Some(be32(data)),
+            _ => {}
+        }
+    }
+
+    match message_type? {
+        DHCPACK => {
+            let lease_secs = lease_secs.unwrap_or(INFINITE_LEASE);
+            Some(DhcpReply::Ack(Lease {
+                address: addr(&msg[16..20]),
+                netmask,
+                router,
+                dns,
+                dns_count,
+                server: server.unwrap_or(addr(&msg[20..24])),
+                lease_secs,
+                renew_secs: renew_secs.unwrap_or(lease_secs / 2),
+                rebind_secs: rebind_secs.unwrap_or((lease_secs as u64 * 7 / 8) as u32),
+                obtained_at: now,
+            }))
+        }
+        DHCPNAK => Some(DhcpReply::Nak(server)),
+        _ => None,
+    }
+}
+
+fn be16(buf: &[u8], pos: usize) -> u16 {
+    u16::from_be_bytes([buf[pos], buf[pos + 1]])
+}
+
+pub(super) fn be32(data: &[u8]) -> u32 {
+    u32::from_be_bytes([data[0], data[1], data[2], data[3]])
+}
+
+pub(super) fn addr(data: &[u8]) -> Ipv4Address {
+    Ipv4Address::new(data[0], data[1], data[2], data[3])
+}
+
+// ===== 全局状态 =====
+
+/// DHCP 统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DhcpStats {
+    /// 收到的 ACK (含续租)
+    pub acks: u32,
+    /// 收到的 NAK
+    pub naks: u32,
+    /// 续租成功次数 (ACK 地址不变)
+    pub renewals: u32,
+    /// 切换到静态回退配置的次数
+    pub fallbacks: u32,
+    /// 租约过期后重新开始 DHCP 的次数
+    pub restarts: u32,
+}
+
+impl fmt::Display for DhcpStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "acks={} naks={} renewals={} fallbacks={} restarts={}",
+            self.acks, self.naks, self.renewals, self.fallbacks, self.restarts
+        )
+    }
+}
+
+/// 租约事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DhcpEvent {
+    /// 获得新地址
+    Acquired(Lease),
+    /// 续租成功
+    Renewed(Lease),
+    /// 租约丢失 (NAK 或过期)
+    Lost,
+    /// 已切换到静态回退配置
+    Fallback,
+}
+
+struct State {
+    lease: Option<Lease>,
+    stats: DhcpStats,
+}
+
+static STATE: BlockingMutex<CriticalSectionRawMutex, RefCell<State>> = BlockingMutex::new(RefCell::new(State {
+    lease: None,
+    stats: DhcpStats { acks: 0, naks: 0, renewals: 0, fallbacks: 0, restarts: 0 },
+}));
+
+/// 最近一次租约事件
+pub static EVENTS: CriticalSignal<DhcpEvent> = CriticalSignal::new();
+
+fn update<R>(f: impl FnOnce(&mut State) -> R) -> R {
+    STATE.lock(|state| f(&mut state.borrow_mut()))
+}
+
+/// 当前租约
+pub fn lease() -> Option<Lease> {
+    update(|s| s.lease)
+}
+
+/// DHCP 统计
+pub fn stats() -> DhcpStats {
+    update(|s| s.stats)
+}
+
+/// 记录解析出的应答 (由 `DhcpSnoop` 调用)
+pub fn record(reply: DhcpReply) {
+    let event = update(|s| match reply {
+        DhcpReply::Ack(lease) => {
+            s.stats.acks = s.stats.acks.saturating_add(1);
+            let renewed = s.lease.is_some_and(|old| old.address == lease.address);
+            s.lease = Some(lease);
+            if renewed {
+                s.stats.renewals = s.stats.renewals.saturating_add(1);
+                DhcpEvent::Renewed(lease)
+            } else {
+                DhcpEvent::Acquired(lease)
+            }
+        }
+        DhcpReply::Nak(_) => {
+            s.stats.naks = s.stats.naks.saturating_add(1);
+            s.lease = None;
+            DhcpEvent::Lost
+        }
+    });
+    EVENTS.signal(event);
+}
+
+fn clear_lease() {
+    update(|s| s.lease = None);
+}
+
+// ===== 驱动包装 =====
+
+/// 在接收路径解析 DHCP 应答的驱动包装 (帧原样交给协议栈)
+pub struct DhcpSnoop<D> {
+    inner: D,
+}
+
+impl<D: Driver> DhcpSnoop<D> {
+    /// 包装驱动
+    pub fn new(inner: D) -> Self {
+        Self { inner }
+    }
+
+    /// 取回原驱动
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+}
+
+/// 接收令牌包装
+pub struct SnoopRx<T> {
+    inner: T,
+    mac: Option<[u8; 6]>,
+}
+
+impl<T: RxToken> RxToken for SnoopRx<T> {
+    fn consume<R, F>(self, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        let mac = self.mac;
+        self.inner.consume(|frame| {
+            if let Some(reply) = mac.and_then(|mac| parse_frame(frame, &mac, Instant::now())) {
+                record(reply);
+            }
+            f(frame)
+        })
+    }
+}
+
+impl<D: Driver> Driver for DhcpSnoop<D> {
+    type RxToken<'a>
+        = SnoopRx<D::RxToken<'a>>
+    where
+        Self: 'a;
+    type TxToken<'a>
+        = D::TxToken<'a>
+    where
+        Self: 'a;
+
+    fn receive(&mut self, cx: &mut Context) -> Option<(Self::RxToken<'_>, Self::TxToken<'_>)> {
+        let mac = match self.inner.hardware_address() {
+            HardwareAddress::Ethernet(mac) => Some(mac),
+            _ => None,
+        };
+        self.inner.receive(cx).map(|(rx, tx)| (SnoopRx { inner: rx, mac }, tx))
+    }
+
+    fn transmit(&mut self, cx: &mut Context) -> Option<Self::TxToken<'_>> {
+        self.inner.transmit(cx)
+    }
+
+    fn link_state(&mut self, cx: &mut Context) -> LinkState {
+        self.inner.link_state(cx)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    fn hardware_address(&self) -> HardwareAddress {
+        self.inner.hardware_address()
+    }
+}
+
+// ===== 租约监管 =====
+
+/// 监管配置
+#[derive(Debug, Clone)]
+pub struct DhcpConfig {
+    /// 获取地址超时
+    pub acquire_timeout: Duration,
+    /// 获取超时后使用的静态配置 (`None` 时持续等待 DHCP)
+    pub fallback: Option<StackConfig>,
+    /// 使用静态回退配置期间重试 DHCP 的间隔
+    pub retry_interval: Duration,
+}
+
+impl DhcpConfig {
+    /// 默认配置 (无回退)
+    pub const fn new() -> Self {
+        Self {
+            acquire_timeout: Duration::from_secs(DHCP_TIMEOUT_SECS as u64),
+            fallback: None,
+            retry_interval: Duration::from_secs(DHCP_RETRY_INTERVAL_SECS as u64),
+        }
+    }
+
+    /// 设置获取超时
+    pub const fn with_acquire_timeout(mut self, timeout: Duration) -> Self {
+        self.acquire_timeout = timeout;
+        self
+    }
+
+    /// 设置静态回退配置
+    pub fn with_fallback(mut self, fallback: StackConfig) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
+
+    /// 设置回退期间的重试间隔
+    pub const fn with_retry_interval(mut self, interval: Duration) -> Self {
+        self.retry_interval = interval;
+        self
+    }
+}
+
+impl Default for DhcpConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 租约监管任务 (不返回)
+///
+/// 每轮: 等待链路 → 启动 DHCP → 获取成功则持有租约直到丢失，超时则切换到静态回退配置，
+/// `retry_interval` 后再试 (重试期间静态地址暂时失效)。
+pub async fn supervise(stack: &NetworkStack<'_>, config: &DhcpConfig) -> ! {
+    let net = stack.stack();
+    loop {
+        net.wait_link_up().await;
+        clear_lease();
+        net.set_config_v4(embassy_net::ConfigV4::Dhcp(Default::default()));
+
+        if with_timeout(config.acquire_timeout, net.wait_config_up()).await.is_err() {
+            let Some(fallback) = &config.fallback else {
+                // 无回退: smoltcp 继续 DISCOVER，直到获得地址
+                net.wait_config_up().await;
+                hold_lease(stack).await;
+                continue;
+            };
+            if let Some(v4) = fallback.to_embassy().ok().and_then(|c| match c.ipv4 {
+                embassy_net::ConfigV4::Static(v4) => Some(v4),
+                _ => None,
+            }) {
+                log_warn!("DHCP: no lease after {}s, using static fallback", config.acquire_timeout.as_secs());
+                net.set_config_v4(embassy_net::ConfigV4::Static(v4));
+                update(|s| s.stats.fallbacks = s.stats.fallbacks.saturating_add(1));
+                EVENTS.signal(DhcpEvent::Fallback);
+            }
+            let _ = select(Timer::after(config.retry_interval), net.wait_link_down()).await;
+            continue;
+        }
+        hold_lease(stack).await;
+    }
+}
+
+/// 持有租约，直到配置失效、链路断开或租约过期未续
+async fn hold_lease(stack: &NetworkStack<'_>) {
+    let net = stack.stack();
+    if let Some(_lease) = lease() {
+        log_info!(
+            "DHCP: {} from {} lease {}s",
+            _lease.address.to_std(),
+            _lease.server.to_std(),
+            _lease.lease_secs
+        );
+    }
+    loop {
+        let lost = select(net.wait_config_down(), net.wait_link_down());
+        let Some(expires) = lease().and_then(|l| l.expires_at()) else {
+            lost.await;
+            return;
+        };
+        match select(lost, Timer::at(expires + EXPIRY_GRACE)).await {
+            Either::First(_) => {
+                clear_lease();
+                EVENTS.signal(DhcpEvent::Lost);
+                return;
+            }
+            Either::Second(()) => {
+                // 期间收到续租 ACK 时到期时间已更新
+                if lease().and_then(|l| l.expires_at()) == Some(expires) {
+                    log_warn!("DHCP: lease expired without renewal, restarting");
+                    update(|s| s.stats.restarts = s.stats.restarts.saturating_add(1));
+                    clear_lease();
+                    EVENTS.signal(DhcpEvent::Lost);
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAC: [u8; 6] = [0x24, 0x0a, 0xc4, 0x12, 0x34, 0x56];
+
+    fn ack_frame(options: &[u8]) -> heapless::Vec<u8, 400> {
+        let mut frame: heapless::Vec<u8, 400> = heapless::Vec::new();
+        // 以太网头
+        frame.extend_from_slice(&MAC).unwrap();
+        frame.extend_from_slice(&[0x02, 0, 0, 0, 0, 1, 0x08, 0x00]).unwrap();
+        // IPv4 头 (20 字节，UDP)
+        frame.extend_from_slice(&[0x45, 0, 0, 0, 0, 0, 0, 0, 64, 17, 0, 0, 192, 168, 1, 1, 255, 255, 255, 255]).unwrap();
+        // UDP 头
+        frame.extend_from_slice(&[0, 67, 0, 68, 0, 0, 0, 0]).unwrap();
+        // BOOTP
+        let mut bootp = [0u8; OPTIONS_OFFSET];
+        bootp[0] = BOOTREPLY;
+        bootp[16..20].copy_from_slice(&[192, 168, 1, 57]);
+        bootp[28..34].copy_from_slice(&MAC);
+        bootp[236..240].copy_from_slice(&MAGIC_COOKIE);
+        frame.extend_from_slice(&bootp).unwrap();
+        frame.extend_from_slice(options).unwrap();
+        frame.push(OPT_END).unwrap();
+        frame
+    }
+
+    #[test]
+    fn test_parse_ack() {
+        let frame = ack_frame(&[
+            53, 1, DHCPACK, 0, 54, 4, 192, 168, 1, 1, 51, 4, 0, 0, 0x0E, 0x10, 1, 4, 255, 255, 255, 0, 3, 4, 192,
+            168, 1, 1, 6, 16, 1, 1, 1, 1, 8, 8, 8, 8, 9, 9, 9, 9, 4, 4, 4, 4,
+        ]);
+        let now = Instant::from_secs(100);
+        let Some(DhcpReply::Ack(lease)) = parse_frame(&frame, &MAC, now) else {
+            panic!("expected ack");
+        };
+        assert_eq!(lease.address, Ipv4Address::new(192, 168, 1, 57));
+        assert_eq!(lease.server, Ipv4Address::new(192, 168, 1, 1));
+        assert_eq!(lease.netmask, Some(Ipv4Address::new(255, 255, 255, 0)));
+        assert_eq!(lease.router, Some(Ipv4Address::new(192, 168, 1, 1)));
+        assert_eq!(
+            lease.dns_servers(),
+            &[Ipv4Address::new(1, 1, 1, 1), Ipv4Address::new(8, 8, 8, 8), Ipv4Address::new(9, 9, 9, 9)]
+        );
+        assert_eq!((lease.lease_secs, lease.renew_secs, lease.rebind_secs), (3600, 1800, 3150));
+        assert_eq!(lease.expires_at(), Some(Instant::from_secs(3700)));
+        assert_eq!(lease.remaining(Instant::from_secs(3000)), Some(Duration::from_secs(700)));
+
+        // 发给其他客户端、非 ACK
+        assert_eq!(parse_frame(&frame, &[0; 6], now), None);
+        let offer = ack_frame(&[53, 1, 2]);
+        assert_eq!(parse_frame(&offer, &MAC, now), None);
+        let nak = ack_frame(&[53, 1, DHCPNAK, 54, 4, 10, 0, 0, 1]);
+        assert_eq!(parse_frame(&nak, &MAC, now), Some(DhcpReply::Nak(Some(Ipv4Address::new(10, 0, 0, 1)))));
+    }
+
+    #[test]
+    fn test_repeated_dns_option() {
+        let frame = ack_frame(&[
+            53, 1, DHCPACK, 6, 8, 1, 1, 1, 1, 8, 8, 8, 8, 6, 8, 9, 9, 9, 9, 4, 4, 4, 4, 6, 4, 5, 5, 5, 5,
+        ]);
+        let Some(DhcpReply::Ack(lease)) = parse_frame(&frame, &MAC, Instant::from_secs(1)) else {
+            panic!("expected ack");
+        };
+        assert_eq!(
+            lease.dns_servers(),
+            &[Ipv4Address::new(1, 1, 1, 1), Ipv4Address::new(8, 8, 8, 8), Ipv4Address::new(9, 9, 9, 9)]
+        );
+    }
+
+    #[test]
+    fn test_lease_tracking() {
+        let frame = ack_frame(&[53, 1, DHCPACK, 51, 4, 0xFF, 0xFF, 0xFF, 0xFF]);
+        let Some(DhcpReply::Ack(lease)) = parse_frame(&frame, &MAC, Instant::from_secs(1)) else {
+            panic!("expected ack");
+        };
+        assert!(lease.is_infinite());
+        assert_eq!(lease.expires_at(), None);
+        // 无选项 54 时取 siaddr
+        assert_eq!(lease.server, Ipv4Address::UNSPECIFIED);
+
+        let before = stats();
+        record(DhcpReply::Ack(lease));
+        record(DhcpReply::Ack(lease));
+        assert_eq!(self::lease(), Some(lease));
+        record(DhcpReply::Nak(None));
+        assert_eq!(self::lease(), None);
+        let after = stats();
+        assert_eq!(after.acks - before.acks, 2);
+        assert_eq!(after.renewals - before.renewals, 1);
+        assert_eq!(after.naks - before.naks, 1);
+    }
+}
@@ -0,0 +1,562 @@
+//! SoftAP DHCP 服务器
+//!
+//! 设备以 SoftAP 方式提供配网页面时没有外部路由器，接入的手机/电脑需要由设备分配地址。
+//! 本模块是一个最小的 DHCPv4 服务器:
+//! - 地址池为服务器所在子网中连续的 `pool_size` 个地址，按 MAC 分配，
+//!   同一客户端重新接入时优先拿回原地址
+//! - 处理 DISCOVER/REQUEST/RELEASE/DECLINE，回复 OFFER/ACK/NAK
+//! - 下发子网掩码、路由器与 DNS (默认均为服务器自身，便于配网门户劫持 DNS)
+//! - 未确认的 OFFER 保留 `OFFER_HOLD`，DECLINE 的地址在一个租期内不再分配
+//!
+//! 应答一律广播到 68 端口 (客户端取得地址前无法接收单播)。协议逻辑在 `DhcpServer::handle`，
+//! 与 Socket 无关；`run` 在 AP 接口的协议栈上绑定 67 端口循环处理。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::dhcp_server::{self, DhcpServer, DhcpServerBuffers, DhcpServerConfig, DEFAULT_AP_ADDRESS};
+//!
+//! // AP 接口使用静态地址
+//! let ap_stack = NetworkStack::from_stack(stack, StackConfig::with_static(
+//!     DEFAULT_AP_ADDRESS,
+//!     Ipv4Address::new(255, 255, 255, 0),
+//!     DEFAULT_AP_ADDRESS,
+//! ));
+//!
+//! #[embassy_executor::task]
+//! async fn dhcp_server_task(stack: &'static NetworkStack<'static>) {
+//!     static mut BUFFERS: DhcpServerBuffers = DhcpServerBuffers::new();
+//!     let mut server: DhcpServer = DhcpServer::new(DhcpServerConfig::new(DEFAULT_AP_ADDRESS));
+//!     let buffers = unsafe { &mut *core::ptr::addr_of_mut!(BUFFERS) };
+//!     if let Err(_e) = dhcp_server::run(stack, buffers, &mut server).await {
+//!         log_error!("DHCP server stopped: {}", _e);
+//!     }
+//! }
+//! ```
+
+use core::net::{Ipv4Addr, SocketAddrV4};
+
+use embassy_time::{Duration, Instant};
+
+use super::config::{DHCP_SERVER_LEASE_SECS, DHCP_SERVER_POOL_SIZE};
+use super::dhcp::{
+    addr, BOOTREPLY, CLIENT_PORT, DHCPACK, DHCPNAK, MAGIC_COOKIE, OPTIONS_OFFSET, OPT_DNS, OPT_END,
+    OPT_LEASE_TIME, OPT_MESSAGE_TYPE, OPT_PAD, OPT_ROUTER, OPT_SERVER_ID, OPT_SUBNET_MASK, SERVER_PORT,
+};
+use super::tcp::{Ipv4Address, NetworkError, NetworkStack, UdpBuffers, UdpSocket};
+use crate::util::log::*;
+
+/// SoftAP 默认地址 (与 ESP-IDF 一致)
+pub const DEFAULT_AP_ADDRESS: Ipv4Address = Ipv4Address::new(192, 168, 4, 1);
+
+/// 未确认的 OFFER 保留时间
+pub const OFFER_HOLD: Duration = Duration::from_secs(30);
+
+/// DHCP 报文最大长度
+pub const MAX_MESSAGE_LEN: usize = 576;
+
+/// BOOTP 报文最小长度 (不足时以 PAD 补齐)
+const MIN_MESSAGE_LEN: usize = 300;
+
+const BOOTREQUEST: u8 = 1;
+const OPT_REQUESTED_IP: u8 = 50;
+
+const DHCPDISCOVER: u8 = 1;
+const DHCPOFFER: u8 = 2;
+const DHCPREQUEST: u8 = 3;
+const DHCPDECLINE: u8 = 4;
+const DHCPRELEASE: u8 = 7;
+
+/// DHCP 服务器 Socket 缓冲区
+pub type DhcpServerBuffers = UdpBuffers<4, { MAX_MESSAGE_LEN * 2 }, { MAX_MESSAGE_LEN * 2 }>;
+
+// ===== 配置 =====
+
+/// DHCP 服务器配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DhcpServerConfig {
+    /// 服务器地址 (AP 接口地址)
+    pub server: Ipv4Address,
+    /// 子网掩码
+    pub netmask: Ipv4Address,
+    /// 地址池起始地址
+    pub pool_start: Ipv4Address,
+    /// 地址池大小 (不超过服务器的槽位数)
+    pub pool_size: u8,
+    /// 租期 (秒)
+    pub lease_secs: u32,
+    /// 下发的路由器 (`None` 时不下发，客户端不会把默认路由指向设备)
+    pub router: Option<Ipv4Address>,
+    /// 下发的 DNS 服务器
+    pub dns: Option<Ipv4Address>,
+}
+
+impl DhcpServerConfig {
+    /// 以 `server` 为网关与 DNS，/24 子网，地址池从 `server` 的下一个地址开始
+    pub const fn new(server: Ipv4Address) -> Self {
+        let [a, b, c, d] = server.0;
+        Self {
+            server,
+            netmask: Ipv4Address::new(255, 255, 255, 0),
+            pool_start: Ipv4Address::new(a, b, c, d.wrapping_add(1)),
+            pool_size: DHCP_SERVER_POOL_SIZE as u8,
+            lease_secs: DHCP_SERVER_LEASE_SECS,
+            router: Some(server),
+            dns: Some(server),
+        }
+    }
+
+    /// 设置地址池
+    pub const fn with_pool(mut self, start: Ipv4Address, size: u8) -> Self {
+        self.pool_start = start;
+        self.pool_size = size;
+        self
+    }
+
+    /// 设置租期
+    pub const fn with_lease_secs(mut self, secs: u32) -> Self {
+        self.lease_secs = secs;
+        self
+    }
+
+    /// 设置下发的路由器
+    pub const fn with_router(mut self, router: Option<Ipv4Address>) -> Self {
+        self.router = router;
+        self
+    }
+
+    /// 设置下发的 DNS 服务器
+    pub const fn with_dns(mut self, dns: Option<Ipv4Address>) -> Self {
+        self.dns = dns;
+        self
+    }
+
+    /// 地址池中第 `index` 个地址
+    fn pool_addr(&self, index: usize) -> Ipv4Address {
+        let base = u32::from_be_bytes(self.pool_start.0);
+        Ipv4Address(base.wrapping_add(index as u32).to_be_bytes())
+    }
+
+    /// 地址在池中的序号
+    fn pool_index(&self, address: Ipv4Address) -> Option<usize> {
+        let offset = u32::from_be_bytes(address.0).wrapping_sub(u32::from_be_bytes(self.pool_start.0));
+        (offset < self.pool_size as u32).then_some(offset as usize)
+    }
+}
+
+impl Default for DhcpServerConfig {
+    fn default() -> Self {
+        Self::new(DEFAULT_AP_ADDRESS)
+    }
+}
+
+// ===== 地址绑定 =====
+
+/// 绑定状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindingState {
+    /// 已发出 OFFER，等待 REQUEST
+    Offered,
+    /// 已确认 (ACK)
+    Bound,
+    /// 客户端检测到地址冲突，暂不分配
+    Declined,
+}
+
+/// 地址绑定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binding {
+    /// 客户端 MAC
+    pub mac: [u8; 6],
+    /// 分配的地址
+    pub address: Ipv4Address,
+    /// 状态
+    pub state: BindingState,
+    /// 过期时刻
+    pub expires_at: Instant,
+}
+
+/// DHCP 服务器统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DhcpServerStats {
+    /// 收到的 DISCOVER
+    pub discovers: u32,
+    /// 发出的 ACK
+    pub acks: u32,
+    /// 发出的 NAK
+    pub naks: u32,
+    /// 收到的 RELEASE
+    pub releases: u32,
+    /// 收到的 DECLINE
+    pub declines: u32,
+    /// 地址池耗尽次数
+    pub exhausted: u32,
+}
+
+/// 解析出的客户端请求
+struct Request<'a> {
+    message_type: u8,
+    mac: [u8; 6],
+    ciaddr: Ipv4Address,
+    requested: Option<Ipv4Address>,
+    server_id: Option<Ipv4Address>,
+    /// 原始报文 (应答时复制 xid、flags、giaddr、chaddr)
+    raw: &'a [u8],
+}
+
+impl<'a> Request<'a> {
+    fn parse(msg: &'a [u8]) -> Option<Self> {
+        if msg.len() < OPTIONS_OFFSET
+            || msg[0] != BOOTREQUEST
+            || msg[1] != 1
+            || msg[2] != 6
+            || msg[236..240] != MAGIC_COOKIE
+        {
+            return None;
+        }
+
+        let mut message_type = None;
+        let mut requested = None;
+        let mut server_id = None;
+        let mut pos = OPTIONS_OFFSET;
+        while pos < msg.len() {
+            let code = msg[pos];
+            if code == OPT_PAD {
+                pos += 1;
+                continue;
+            }
+            if code == OPT_END {
+                break;
+            }
+            let len = *msg.get(pos + 1)? as usize;
+            let data = msg.get(pos + 2..pos + 2 + len)?;
+            pos += 2 + len;
+            match (code, data.len()) {
+                (OPT_MESSAGE_TYPE, 1) => message_type = Some(data[0]),
+                (OPT_REQUESTED_IP, 4) => requested = Some(addr(data)),
+                (OPT_SERVER_ID, 4) => server_id = Some(addr(data)),
+                _ => {}
+            }
+        }
+
+        let mut mac = [0u8; 6];
+        mac.copy_from_slice(&msg[28..34]);
+        Some(Self {
+            message_type: message_type?,
+            mac,
+            ciaddr: addr(&msg[12..16]),
+            requested,
+            server_id,
+            raw: msg,
+        })
+    }
+}
+
+// ===== 服务器 =====
+
+/// DHCP 服务器状态 (地址池与绑定表)
+///
+/// 槽位 `i` 对应地址池中的第 `i` 个地址；`N` 为槽位数，配置的地址池大小不超过它。
+pub struct DhcpServer<const N: usize = DHCP_SERVER_POOL_SIZE> {
+    config: DhcpServerConfig,
+    slots: [Option<Binding>; N],
+    stats: DhcpServerStats,
+}
+
+impl<const N: usize> DhcpServer<N> {
+    /// 创建服务器
+    pub fn new(mut config: DhcpServerConfig) -> Self {
+        config.pool_size = config.pool_size.min(N.min(u8::MAX as usize) as u8);
+        Self { config, slots: [None; N], stats: DhcpServerStats::default() }
+    }
+
+    /// 配置
+    pub fn config(&self) -> &DhcpServerConfig {
+        &self.config
+    }
+
+    /// 统计
+    pub fn stats(&self) -> DhcpServerStats {
+        self.stats
+    }
+
+    /// 已确认的绑定 (含已过期但尚未被回收的)
+    pub fn bindings(&self) -> impl Iterator<Item = &Binding> {
+        self.slots.iter().flatten().filter(|b| b.state == BindingState::Bound)
+    }
+
+    /// 释放客户端的地址 (如 AP 踢出客户端后)
+    pub fn release(&mut self, mac: &[u8; 6]) -> bool {
+        match self.find(mac) {
+            Some(index) => {
+                self.slots[index] = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 处理一个请求报文，需要应答时把应答写入 `out` 并返回长度
+    ///
+    /// `out` 至少 `MAX_MESSAGE_LEN` 字节。
+    pub fn handle(&mut self, msg: &[u8], now: Instant, out: &mut [u8]) -> Option<usize> {
+        let request = Request::parse(msg)?;
+        self.expire(now);
+        let ours = request.server_id.is_none_or(|id| id == self.config.server);
+
+        match request.message_type {
+            DHCPDISCOVER => {
+                self.stats.discovers = self.stats.discovers.wrapping_add(1);
+                let Some(index) = self.allocate(&request.mac, request.requested) else {
+                    self.stats.exhausted = self.stats.exhausted.wrapping_add(1);
+                    log_warn!("DHCP server: pool exhausted");
+                    return None;
+                };
+                let address = self.config.pool_addr(index);
+                if self.slots[index].is_none_or(|b| b.state != BindingState::Bound) {
+                    self.slots[index] = Some(Binding {
+                        mac: request.mac,
+                        address,
+                        state: BindingState::Offered,
+                        expires_at: now + OFFER_HOLD,
+                    });
+                }
+                self.reply(&request, DHCPOFFER, address, out)
+            }
+            DHCPREQUEST => {
+                if !ours {
+                    // 客户端选择了其他服务器
+                    if let Some(index) = self.find(&request.mac) {
+                        if self.slots[index].is_some_and(|b| b.state == BindingState::Offered) {
+                            self.slots[index] = None;
+                        }
+                    }
+                    return None;
+                }
+                let address = request.requested.unwrap_or(request.ciaddr);
+                let index = self.config.pool_index(address).filter(|&i| {
+                    self.slots[i].is_none_or(|b| b.mac == request.mac && b.state != BindingState::Declined)
+                });
+                match index {
+                    Some(index) => {
+                        if let Some(previous) = self.find(&request.mac).filter(|&i| i != index) {
+                            self.slots[previous] = None;
+                        }
+                        self.slots[index] = Some(Binding {
+                            mac: request.mac,
+                            address,
+                            state: BindingState::Bound,
+                            expires_at: now + Duration::from_secs(self.config.lease_secs as u64),
+                        });
+                        self.stats.acks = self.stats.acks.wrapping_add(1);
+                        self.reply(&request, DHCPACK, address, out)
+                    }
+                    None => {
+                        self.stats.naks = self.stats.naks.wrapping_add(1);
+                        self.reply(&request, DHCPNAK, Ipv4Address::UNSPECIFIED, out)
+                    }
+                }
+            }
+            DHCPDECLINE if ours => {
+                self.stats.declines = self.stats.declines.wrapping_add(1);
+                if let Some(index) = request.requested.and_then(|a| self.config.pool_index(a)) {
+                    self.slots[index] = Some(Binding {
+                        mac: request.mac,
+                        address: self.config.pool_addr(index),
+                        state: BindingState::Declined,
+                        expires_at: now + Duration::from_secs(self.config.lease_secs as u64),
+                    });
+                }
+                None
+            }
+            DHCPRELEASE if ours => {
+                self.stats.releases = self.stats.releases.wrapping_add(1);
+                if let Some(index) = self.find(&request.mac) {
+                    if self.slots[index].is_some_and(|b| b.address == request.ciaddr) {
+                        self.slots[index] = None;
+                    }
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// 回收过期的绑定
+    fn expire(&mut self, now: Instant) {
+        for slot in self.slots.iter_mut() {
+            if slot.is_some_and(|b| b.expires_at <= now) {
+                *slot = None;
+            }
+        }
+    }
+
+    /// 客户端占用的槽位 (不含 DECLINE)
+    fn find(&self, mac: &[u8; 6]) -> Option<usize> {
+        self.slots
+            .iter()
+            .position(|s| s.is_some_and(|b| b.mac == *mac && b.state != BindingState::Declined))
+    }
+
+    /// 为客户端选择槽位: 已有绑定 > 请求的空闲地址 > 第一个空闲地址
+    fn allocate(&self, mac: &[u8; 6], requested: Option<Ipv4Address>) -> Option<usize> {
+        let pool = self.config.pool_size as usize;
+        self.find(mac)
+            .or_else(|| {
+                requested
+                    .and_then(|a| self.config.pool_index(a))
+                    .filter(|&i| self.slots[i].is_none())
+            })
+            .or_else(|| (0..pool).find(|&i| self.slots[i].is_none()))
+    }
+
+    /// 构造应答
+    fn reply(&self, request: &Request<'_>, message_type: u8, yiaddr: Ipv4Address, out: &mut [u8]) -> Option<usize> {
+        let out = out.get_mut(..MAX_MESSAGE_LEN)?;
+        out.fill(0);
+        out[0] = BOOTREPLY;
+        out[1] = 1;
+        out[2] = 6;
+        out[4..8].copy_from_slice(&request.raw[4..8]); // xid
+        out[10..12].copy_from_slice(&request.raw[10..12]); // flags
+        out[16..20].copy_from_slice(&yiaddr.0);
+        out[20..24].copy_from_slice(&self.config.server.0);
+        out[24..44].copy_from_slice(&request.raw[24..44]); // giaddr + chaddr
+        out[236..240].copy_from_slice(&MAGIC_COOKIE);
+
+        let mut pos = OPTIONS_OFFSET;
+        let mut option = |code: u8, data: &[u8]| {
+            out[pos] = code;
+            out[pos + 1] = data.len() as u8;
+            out[pos + 2..pos + 2 + data.len()].copy_from_slice(data);
+            pos += 2 + data.len();
+        };
+        option(OPT_MESSAGE_TYPE, &[message_type]);
+        option(OPT_SERVER_ID, &self.config.server.0);
+        if message_type != DHCPNAK {
+            option(OPT_LEASE_TIME, &self.config.lease_secs.to_be_bytes());
+            option(OPT_SUBNET_MASK, &self.config.netmask.0);
+            if let Some(router) = self.config.router {
+                option(OPT_ROUTER, &router.0);
+            }
+            if let Some(dns) = self.config.dns {
+                option(OPT_DNS, &dns.0);
+            }
+        }
+        out[pos] = OPT_END;
+        Some((pos + 1).max(MIN_MESSAGE_LEN))
+    }
+}
+
+// ===== 服务任务 =====
+
+/// 在 `stack` 上绑定 67 端口并处理请求 (只在 Socket 出错时返回)
+pub async fn run<const N: usize>(
+    stack: &NetworkStack<'_>,
+    buffers: &mut DhcpServerBuffers,
+    server: &mut DhcpServer<N>,
+) -> Result<(), NetworkError> {
+    let mut socket = UdpSocket::new(stack, buffers);
+    socket.bind(SERVER_PORT).await?;
+    log_info!("DHCP server on {} ({} addresses)", server.config.server.to_std(), server.config.pool_size);
+
+    let broadcast = SocketAddrV4::new(Ipv4Addr::BROADCAST, CLIENT_PORT);
+    let mut rx = [0u8; MAX_MESSAGE_LEN];
+    let mut tx = [0u8; MAX_MESSAGE_LEN];
+    loop {
+        let n = match socket.recv_from(&mut rx).await {
+            Ok((n, _)) => n,
+            Err(NetworkError::BufferFull) => continue,
+            Err(e) => return Err(e),
+        };
+        if let Some(len) = server.handle(&rx[..n], Instant::now(), &mut tx) {
+            socket.send_to(&tx[..len], broadcast).await?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIENT: [u8; 6] = [0x02, 0, 0, 0, 0, 0x11];
+
+    fn request(message_type: u8, mac: [u8; 6], requested: Option<Ipv4Address>, server: Option<Ipv4Address>) -> [u8; 300] {
+        let mut msg = [0u8; 300];
+        msg[0] = BOOTREQUEST;
+        msg[1] = 1;
+        msg[2] = 6;
+        msg[4..8].copy_from_slice(&[1, 2, 3, 4]);
+        msg[28..34].copy_from_slice(&mac);
+        msg[236..240].copy_from_slice(&MAGIC_COOKIE);
+        let mut pos = OPTIONS_OFFSET;
+        msg[pos..pos + 3].copy_from_slice(&[OPT_MESSAGE_TYPE, 1, message_type]);
+        pos += 3;
+        for (code, address) in [(OPT_REQUESTED_IP, requested), (OPT_SERVER_ID, server)] {
+            if let Some(address) = address {
+                msg[pos..pos + 2].copy_from_slice(&[code, 4]);
+                msg[pos + 2..pos + 6].copy_from_slice(&address.0);
+                pos += 6;
+            }
+        }
+        msg[pos] = OPT_END;
+        msg
+    }
+
+    /// 应答的 (消息类型, yiaddr)
+    fn answer(out: &[u8]) -> (u8, Ipv4Address) {
+        assert_eq!((out[0], &out[4..8], &out[28..34]), (BOOTREPLY, &[1, 2, 3, 4][..], &CLIENT[..]));
+        assert_eq!(&out[OPTIONS_OFFSET..OPTIONS_OFFSET + 2], &[OPT_MESSAGE_TYPE, 1]);
+        (out[OPTIONS_OFFSET + 2], addr(&out[16..20]))
+    }
+
+    #[test]
+    fn test_discover_request_ack() {
+        let server_ip = DEFAULT_AP_ADDRESS;
+        let mut server: DhcpServer<4> = DhcpServer::new(DhcpServerConfig::new(server_ip));
+        let mut out = [0u8; MAX_MESSAGE_LEN];
+        let now = Instant::from_secs(100);
+
+        let len = server.handle(&request(DHCPDISCOVER, CLIENT, None, None), now, &mut out).unwrap();
+        assert!(len >= MIN_MESSAGE_LEN);
+        let (kind, offered) = answer(&out);
+        assert_eq!((kind, offered), (DHCPOFFER, Ipv4Address::new(192, 168, 4, 2)));
+        assert_eq!(server.bindings().count(), 0);
+
+        server.handle(&request(DHCPREQUEST, CLIENT, Some(offered), Some(server_ip)), now, &mut out).unwrap();
+        assert_eq!(answer(&out), (DHCPACK, offered));
+        let binding = server.bindings().next().unwrap();
+        assert_eq!((binding.mac, binding.address), (CLIENT, offered));
+
+        // 重新接入拿回原地址；请求池外地址被拒绝
+        server.handle(&request(DHCPDISCOVER, CLIENT, None, None), now, &mut out).unwrap();
+        assert_eq!(answer(&out), (DHCPOFFER, offered));
+        server.handle(&request(DHCPREQUEST, CLIENT, Some(Ipv4Address::new(10, 0, 0, 9)), None), now, &mut out).unwrap();
+        assert_eq!(answer(&out).0, DHCPNAK);
+
+        // 选择其他服务器时不应答
+        let other = Some(Ipv4Address::new(192, 168, 4, 254));
+        assert!(server.handle(&request(DHCPREQUEST, CLIENT, Some(offered), other), now, &mut out).is_none());
+    }
+
+    #[test]
+    fn test_pool_exhaustion_and_expiry() {
+        let config = DhcpServerConfig::new(DEFAULT_AP_ADDRESS).with_pool(Ipv4Address::new(192, 168, 4, 10), 2);
+        let mut server: DhcpServer<4> = DhcpServer::new(config);
+        let mut out = [0u8; MAX_MESSAGE_LEN];
+        let now = Instant::from_secs(0);
+
+        for last in [1, 2] {
+            assert!(server.handle(&request(DHCPDISCOVER, [2, 0, 0, 0, 0, last], None, None), now, &mut out).is_some());
+        }
+        assert!(server.handle(&request(DHCPDISCOVER, [2, 0, 0, 0, 0, 3], None, None), now, &mut out).is_none());
+        assert_eq!(server.stats().exhausted, 1);
+
+        // 未确认的 OFFER 过期后地址回收
+        let later = now + OFFER_HOLD;
+        server.handle(&request(DHCPDISCOVER, CLIENT, None, None), later, &mut out).unwrap();
+        assert_eq!(answer(&out).1, Ipv4Address::new(192, 168, 4, 10));
+        assert!(server.release(&CLIENT));
+        assert!(!server.release(&CLIENT));
+    }
+}
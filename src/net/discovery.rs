@@ -0,0 +1,288 @@
+//! 设备发现
+//!
+//! 不依赖 mDNS 的极简 UDP 发现协议，主机工具广播一个魔术包即可找到网段内的全部设备:
+//! - 请求: `RRTOS-DISCOVER`，可追加空格 + 过滤词 (设备名或能力名)
+//! - 响应: 单个 JSON 对象，包含名称、固件版本、IP、MAC、能力列表与运行时间
+//! - 响应直接单播回请求方；令牌桶限速，防止被用作反射放大
+//!
+//! 主机侧可直接用 `socat` 验证:
+//! `echo -n RRTOS-DISCOVER | socat - UDP-DATAGRAM:255.255.255.255:7364,broadcast`
+//!
+//! 响应示例:
+//! `{"proto":1,"name":"pump-3","version":"0.1.0","ip":"192.168.1.42","mac":"7c:df:a1:00:12:34","caps":["http","console"],"uptime":3600}`
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::discovery::{DeviceIdentity, Discovery, DISCOVERY_PORT};
+//!
+//! static DISCOVERY: Discovery = Discovery::new(
+//!     DeviceIdentity::new().with_name("pump-3").with_capabilities(&["http", "console"]),
+//! );
+//!
+//! DISCOVERY.run(stack, DISCOVERY_PORT, mac).await;
+//! ```
+
+use core::fmt::Write as _;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use embassy_time::{Duration, Instant};
+use heapless::String;
+
+use crate::sys::identity::{DeviceName, IDENTITY};
+use crate::util::fmt::{Ipv4, MacAddr};
+use crate::util::json::{ArrayWriter, JsonError, ObjectWriter};
+use crate::util::log::*;
+use crate::util::ratelimit::{RateLimit, TokenBucket};
+
+/// 默认发现端口
+pub const DISCOVERY_PORT: u16 = 7364;
+
+/// 请求魔术字
+pub const DISCOVERY_MAGIC: &[u8] = b"RRTOS-DISCOVER";
+
+/// 协议版本 (响应中的 `proto` 字段)
+pub const PROTOCOL_VERSION: u8 = 1;
+
+/// 响应最大长度
+pub const MAX_RESPONSE_LEN: usize = 384;
+
+/// 最大请求长度 (魔术字 + 过滤词)
+pub const MAX_REQUEST_LEN: usize = 64;
+
+// ===== 设备标识 =====
+
+/// 设备标识
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceIdentity {
+    /// 设备名称 (`None` 时取自 `sys::identity`)
+    pub name: Option<&'static str>,
+    /// 固件版本
+    pub version: &'static str,
+    /// 对外提供的能力 (如 "http"、"console"、"modbus")
+    pub capabilities: &'static [&'static str],
+}
+
+impl DeviceIdentity {
+    /// 默认标识: 名称取自 `sys::identity`，版本取自 crate 元数据，无能力
+    pub const fn new() -> Self {
+        Self { name: None, version: crate::VERSION, capabilities: &[] }
+    }
+
+    /// 设置设备名称
+    pub const fn with_name(mut self, name: &'static str) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// 设置能力列表
+    pub const fn with_capabilities(mut self, capabilities: &'static [&'static str]) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// 设备名称
+    pub fn name(&self) -> DeviceName {
+        match self.name {
+            Some(name) => DeviceName::try_from(name).unwrap_or_default(),
+            None => IDENTITY.name(),
+        }
+    }
+
+    /// 是否匹配过滤词 (设备名或任一能力)
+    pub fn matches(&self, filter: &str) -> bool {
+        filter == self.name().as_str() || self.capabilities.contains(&filter)
+    }
+}
+
+impl Default for DeviceIdentity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ===== 协议 =====
+
+/// 解析发现请求
+///
+/// # 返回
+/// - `None`: 不是发现请求
+/// - `Some(None)`: 无过滤词
+/// - `Some(Some(filter))`: 带过滤词
+pub fn parse_request(packet: &[u8]) -> Option<Option<&str>> {
+    let rest = packet.strip_prefix(DISCOVERY_MAGIC)?;
+    let rest = core::str::from_utf8(rest).ok()?;
+    if rest.is_empty() {
+        return Some(None);
+    }
+    let filter = rest.strip_prefix(' ')?.trim();
+    Some((!filter.is_empty()).then_some(filter))
+}
+
+/// 写出响应 JSON
+pub fn write_response<const N: usize>(
+    identity: &DeviceIdentity,
+    ip: Option<[u8; 4]>,
+    mac: [u8; 6],
+    uptime_secs: u64,
+    out: &mut String<N>,
+) -> Result<(), JsonError> {
+    let mut obj = ObjectWriter::new(out)?;
+    obj.field("proto", &PROTOCOL_VERSION)?
+        .field("name", &identity.name())?
+        .field("version", identity.version)?;
+    match ip {
+        Some(ip) => obj.field_with("ip", |w| write!(w, "\"{}\"", Ipv4(ip)))?,
+        None => obj.field("ip", &None::<u8>)?,
+    };
+    obj.field_with("mac", |w| write!(w, "\"{}\"", MacAddr(mac)))?
+    .field_with("caps", |w| {
+        let mut caps = ArrayWriter::new(w)?;
+        for cap in identity.capabilities {
+            caps.item(*cap)?;
+        }
+        caps.finish()
+    })?
+    .field("uptime", &uptime_secs)?;
+    obj.finish()?;
+    Ok(())
+}
+
+// ===== 响应器 =====
+
+/// 发现统计快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiscoveryStats {
+    /// 收到的发现请求
+    pub requests: u32,
+    /// 已发送的响应
+    pub replies: u32,
+    /// 因过滤不匹配而忽略的请求
+    pub filtered: u32,
+    /// 因限速丢弃的请求
+    pub throttled: u32,
+}
+
+/// 发现响应器
+pub struct Discovery {
+    identity: DeviceIdentity,
+    /// 响应限速: 突发 8 个，之后每秒 4 个
+    limiter: TokenBucket,
+    requests: AtomicU32,
+    replies: AtomicU32,
+    filtered: AtomicU32,
+    throttled: AtomicU32,
+}
+
+impl Discovery {
+    /// 创建
+    pub const fn new(identity: DeviceIdentity) -> Self {
+        Self {
+            identity,
+            limiter: TokenBucket::new(8, Duration::from_millis(250)),
+            requests: AtomicU32::new(0),
+            replies: AtomicU32::new(0),
+            filtered: AtomicU32::new(0),
+            throttled: AtomicU32::new(0),
+        }
+    }
+
+    /// 设备标识
+    pub fn identity(&self) -> &DeviceIdentity {
+        &self.identity
+    }
+
+    /// 统计快照
+    pub fn stats(&self) -> DiscoveryStats {
+        DiscoveryStats {
+            requests: self.requests.load(Ordering::Relaxed),
+            replies: self.replies.load(Ordering::Relaxed),
+            filtered: self.filtered.load(Ordering::Relaxed),
+            throttled: self.throttled.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 处理一个数据报，需要回复时把响应写入 `out`
+    ///
+    /// 非发现请求、过滤不匹配或被限速时返回 `false`。
+    pub fn handle<const N: usize>(
+        &self,
+        packet: &[u8],
+        ip: Option<[u8; 4]>,
+        mac: [u8; 6],
+        out: &mut String<N>,
+    ) -> bool {
+        let Some(filter) = parse_request(packet) else {
+            return false;
+        };
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        if filter.is_some_and(|f| !self.identity.matches(f)) {
+            self.filtered.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+        if self.limiter.try_acquire(1).is_err() {
+            self.throttled.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+        out.clear();
+        write_response(&self.identity, ip, mac, Instant::now().as_secs(), out).is_ok()
+    }
+
+    /// 在指定端口运行发现响应器 (不返回)
+    ///
+    /// `mac` 为 STA 接口 MAC 地址；IP 在每次响应时从协议栈读取。
+    pub async fn run(&self, stack: embassy_net::Stack<'_>, port: u16, mac: [u8; 6]) -> ! {
+        use embassy_net::udp::{PacketMetadata, UdpSocket};
+
+        let mut rx_meta = [PacketMetadata::EMPTY; 4];
+        let mut tx_meta = [PacketMetadata::EMPTY; 4];
+        let mut rx = [0u8; MAX_REQUEST_LEN * 4];
+        let mut tx = [0u8; MAX_RESPONSE_LEN * 2];
+        let mut buf = [0u8; MAX_REQUEST_LEN];
+        let mut out: String<MAX_RESPONSE_LEN> = String::new();
+        let mut socket = UdpSocket::new(stack, &mut rx_meta, &mut rx, &mut tx_meta, &mut tx);
+        if socket.bind(port).is_err() {
+            log_warn!("Discovery: port {} unavailable", port);
+            core::future::pending::<()>().await;
+        }
+
+        log_info!("Discovery responder on UDP port {} as {}", port, self.identity.name());
+        loop {
+            let Ok((n, meta)) = socket.recv_from(&mut buf).await else {
+                continue;
+            };
+            let ip = stack.config_v4().map(|c| c.address.address().octets());
+            if !self.handle(&buf[..n], ip, mac, &mut out) {
+                continue;
+            }
+            if socket.send_to(out.as_bytes(), meta).await.is_ok() {
+                self.replies.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_and_response() {
+        assert_eq!(parse_request(b"RRTOS-DISCOVER"), Some(None));
+        assert_eq!(parse_request(b"RRTOS-DISCOVER  http "), Some(Some("http")));
+        assert_eq!(parse_request(b"RRTOS-DISCOVERY"), None);
+        assert_eq!(parse_request(b"hello"), None);
+
+        let identity = DeviceIdentity::new().with_name("pump-3").with_capabilities(&["http", "console"]);
+        assert!(identity.matches("console") && identity.matches("pump-3") && !identity.matches("modbus"));
+
+        let mut out: String<MAX_RESPONSE_LEN> = String::new();
+        write_response(&identity, Some([192, 168, 1, 42]), [0x7c, 0xdf, 0xa1, 0, 0x12, 0x34], 3600, &mut out).unwrap();
+        let value = crate::util::json::parse(&out).unwrap();
+        assert_eq!(value.get("name").unwrap().as_raw_str().unwrap(), "pump-3");
+        assert_eq!(value.get("ip").unwrap().as_raw_str().unwrap(), "192.168.1.42");
+        assert_eq!(value.get("mac").unwrap().as_raw_str().unwrap(), "7c:df:a1:00:12:34");
+        assert_eq!(value.get("caps").unwrap().items().unwrap().count(), 2);
+        assert_eq!(value.get("uptime").unwrap().as_i64().unwrap(), 3600);
+    }
+}
@@ -0,0 +1,418 @@
+//! DNS 解析缓存
+//!
+//! MQTT/HTTP 客户端每次重连都要解析主机名，断线频繁时 DNS 查询会拖慢恢复并
+//! 增加上游负担。本模块在解析器前加一层小缓存:
+//! - 成功结果按 TTL 缓存 (解析器未提供 TTL 时用默认值，且不超过上限)
+//! - 解析失败负缓存一小段时间，避免对不存在的主机反复查询
+//! - 容量满时先淘汰已过期条目，否则淘汰最早过期的条目
+//! - 支持手动清空 (整体或单个主机)，并统计命中率
+//!
+//! `NetworkStack::dns_resolve` 与 `TcpClient::connect_host` 内部同样经由
+//! 本模块的 `resolve` (未命中时交给 `net::resolver` 查询)，与直接使用
+//! embassy-net Socket 的调用方共用全局缓存。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::dns;
+//!
+//! let mut socket = TcpSocket::new(stack, &mut rx, &mut tx);
+//! dns::connect_host(stack, &mut socket, "broker.example.com", 1883).await?;
+//!
+//! let stats = dns::stats();
+//! log_info!("DNS hit rate {}%", stats.hit_rate_percent());
+//! ```
+
+use core::cell::RefCell;
+use core::fmt;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_time::{Duration, Instant};
+use heapless::{String, Vec};
+
+use super::config::{DNS_CACHE_SIZE, DNS_DEFAULT_TTL_SECS, DNS_MAX_TTL_SECS, DNS_NEGATIVE_TTL_SECS};
+use super::tcp::{Ipv4Address, NetworkError};
+use crate::util::log::*;
+
+/// 可缓存的最大主机名长度
+pub const MAX_HOST_LEN: usize = 64;
+
+// ===== 配置 =====
+
+/// 缓存配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DnsCacheConfig {
+    /// 解析器未提供 TTL 时的缓存时间
+    pub default_ttl: Duration,
+    /// TTL 上限
+    pub max_ttl: Duration,
+    /// 解析失败的负缓存时间 (0 = 不缓存失败)
+    pub negative_ttl: Duration,
+}
+
+impl DnsCacheConfig {
+    /// 默认配置 (见 `net::config` 中的 `DNS_*` 常量)
+    pub const fn new() -> Self {
+        Self {
+            default_ttl: Duration::from_secs(DNS_DEFAULT_TTL_SECS as u64),
+            max_ttl: Duration::from_secs(DNS_MAX_TTL_SECS as u64),
+            negative_ttl: Duration::from_secs(DNS_NEGATIVE_TTL_SECS as u64),
+        }
+    }
+
+    /// 设置默认 TTL
+    pub const fn with_default_ttl(mut self, ttl: Duration) -> Self {
+        self.default_ttl = ttl;
+        self
+    }
+
+    /// 设置 TTL 上限
+    pub const fn with_max_ttl(mut self, ttl: Duration) -> Self {
+        self.max_ttl = ttl;
+        self
+    }
+
+    /// 设置负缓存时间
+    pub const fn with_negative_ttl(mut self, ttl: Duration) -> Self {
+        self.negative_ttl = ttl;
+        self
+    }
+}
+
+impl Default for DnsCacheConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ===== 缓存 =====
+
+/// 缓存查询结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lookup<A = Ipv4Address> {
+    /// 命中
+    Hit(A),
+    /// 命中负缓存 (近期解析失败)
+    Negative,
+    /// 未命中或已过期
+    Miss,
+}
+
+/// 缓存统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DnsStats {
+    /// 命中次数
+    pub hits: u32,
+    /// 负缓存命中次数
+    pub negative_hits: u32,
+    /// 未命中次数
+    pub misses: u32,
+    /// 容量不足淘汰次数
+    pub evictions: u32,
+}
+
+impl DnsStats {
+    /// 查询总次数
+    pub fn lookups(&self) -> u32 {
+        self.hits.saturating_add(self.negative_hits).saturating_add(self.misses)
+    }
+
+    /// 命中率 (百分比，负缓存命中也算命中)
+    pub fn hit_rate_percent(&self) -> u32 {
+        let lookups = self.lookups() as u64;
+        if lookups == 0 {
+            return 0;
+        }
+        ((self.hits as u64 + self.negative_hits as u64) * 100 / lookups) as u32
+    }
+}
+
+impl fmt::Display for DnsStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "hits={} negative={} misses={} evictions={} hit_rate={}%",
+            self.hits,
+            self.negative_hits,
+            self.misses,
+            self.evictions,
+            self.hit_rate_percent()
+        )
+    }
+}
+
+struct Entry<A> {
+    host: String<MAX_HOST_LEN>,
+    /// `None` 表示负缓存
+    addr: Option<A>,
+    expires: Instant,
+}
+
+/// DNS 缓存 (最多 `N` 个主机，地址类型默认为 IPv4)
+pub struct DnsCache<const N: usize, A = Ipv4Address> {
+    entries: Vec<Entry<A>, N>,
+    config: DnsCacheConfig,
+    stats: DnsStats,
+}
+
+impl<const N: usize, A: Copy> DnsCache<N, A> {
+    /// 创建空缓存
+    pub const fn new(config: DnsCacheConfig) -> Self {
+        Self { entries: Vec::new(), config, stats: DnsStats { hits: 0, negative_hits: 0, misses: 0, evictions: 0 } }
+    }
+
+    /// 查询 (过期条目在此移除)
+    pub fn lookup(&mut self, host: &str, now: Instant) -> Lookup<A> {
+        let result = match self.position(host) {
+            Some(index) if self.entries[index].expires > now => match self.entries[index].addr {
+                Some(addr) => Lookup::Hit(addr),
+                None => Lookup::Negative,
+            },
+            Some(index) => {
+                self.entries.swap_remove(index);
+                Lookup::Miss
+            }
+            None => Lookup::Miss,
+        };
+        match result {
+            Lookup::Hit(_) => self.stats.hits = self.stats.hits.saturating_add(1),
+            Lookup::Negative => self.stats.negative_hits = self.stats.negative_hits.saturating_add(1),
+            Lookup::Miss => self.stats.misses = self.stats.misses.saturating_add(1),
+        }
+        result
+    }
+
+    /// 缓存解析结果 (`ttl` 为 `None` 时使用默认 TTL)
+    pub fn insert(&mut self, host: &str, addr: A, ttl: Option<Duration>, now: Instant) {
+        let ttl = ttl.unwrap_or(self.config.default_ttl).min(self.config.max_ttl);
+        self.store(host, Some(addr), ttl, now);
+    }
+
+    /// 缓存解析失败
+    pub fn insert_negative(&mut self, host: &str, now: Instant) {
+        self.store(host, None, self.config.negative_ttl, now);
+    }
+
+    /// 移除单个主机，返回是否存在
+    pub fn flush_host(&mut self, host: &str) -> bool {
+        match self.position(host) {
+            Some(index) => {
+                self.entries.swap_remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 清空缓存 (统计保留)
+    pub fn flush(&mut self) {
+        self.entries.clear();
+    }
+
+    /// 缓存条目数 (含未清理的过期条目)
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 统计
+    pub fn stats(&self) -> DnsStats {
+        self.stats
+    }
+
+    /// 清零统计
+    pub fn reset_stats(&mut self) {
+        self.stats = DnsStats::default();
+    }
+
+    fn position(&self, host: &str) -> Option<usize> {
+        self.entries.iter().position(|e| e.host.eq_ignore_ascii_case(host))
+    }
+
+    fn store(&mut self, host: &str, addr: Option<A>, ttl: Duration, now: Instant) {
+        if ttl.as_ticks() == 0 {
+            self.flush_host(host);
+            return;
+        }
+        let Ok(name) = String::try_from(host) else {
+            // 超长主机名不缓存
+            return;
+        };
+        let entry = Entry { host: name, addr, expires: now + ttl };
+
+        if let Some(index) = self.position(host) {
+            self.entries[index] = entry;
+            return;
+        }
+        if self.entries.is_full() {
+            self.evict(now);
+        }
+        let _ = self.entries.push(entry);
+    }
+
+    /// 腾出一个位置: 优先移除已过期条目，否则移除最早过期的
+    fn evict(&mut self, now: Instant) {
+        let expired = self.entries.iter().position(|e| e.expires <= now);
+        let victim = expired.or_else(|| {
+            self.entries.iter().enumerate().min_by_key(|(_, e)| e.expires).map(|(index, _)| index)
+        });
+        if let Some(index) = victim {
+            if expired.is_none() {
+                self.stats.evictions = self.stats.evictions.saturating_add(1);
+            }
+            self.entries.swap_remove(index);
+        }
+    }
+}
+
+impl<const N: usize, A: Copy> Default for DnsCache<N, A> {
+    fn default() -> Self {
+        Self::new(DnsCacheConfig::new())
+    }
+}
+
+// ===== 全局缓存 =====
+
+static CACHE: BlockingMutex<CriticalSectionRawMutex, RefCell<DnsCache<DNS_CACHE_SIZE>>> =
+    BlockingMutex::new(RefCell::new(DnsCache::new(DnsCacheConfig::new())));
+
+fn with_cache<R>(f: impl FnOnce(&mut DnsCache<DNS_CACHE_SIZE>) -> R) -> R {
+    CACHE.lock(|cache| f(&mut cache.borrow_mut()))
+}
+
+/// 查询全局缓存
+pub fn lookup(host: &str) -> Lookup {
+    with_cache(|cache| cache.lookup(host, Instant::now()))
+}
+
+/// 写入解析结果 (`Err` 写入负缓存)
+pub fn record(host: &str, result: Result<Ipv4Address, NetworkError>, ttl: Option<Duration>) {
+    let now = Instant::now();
+    with_cache(|cache| match result {
+        Ok(addr) => cache.insert(host, addr, ttl, now),
+        Err(_) => cache.insert_negative(host, now),
+    });
+}
+
+/// 移除单个主机 (如连接失败，怀疑地址已变更)
+pub fn flush_host(host: &str) -> bool {
+    with_cache(|cache| cache.flush_host(host))
+}
+
+/// 清空全局缓存 (如切换网络后)
+pub fn flush() {
+    with_cache(|cache| cache.flush());
+    log_debug!("DNS cache flushed");
+}
+
+/// 全局缓存统计
+pub fn stats() -> DnsStats {
+    with_cache(|cache| cache.stats())
+}
+
+/// 配置全局缓存 (已缓存条目保留原过期时间)
+pub fn configure(config: DnsCacheConfig) {
+    with_cache(|cache| cache.config = config);
+}
+
+/// 解析 IP 字面量 (如 "192.168.1.10")，无需查询
+pub fn parse_literal(host: &str) -> Option<Ipv4Address> {
+    host.parse::<core::net::Ipv4Addr>().ok().map(Ipv4Address::from)
+}
+
+// ===== embassy-net 集成 =====
+
+/// 经缓存解析主机名 (A 记录)
+///
+/// 未命中时使用临时 `Resolver` 向协议栈下发的 DNS 服务器查询，成功结果按记录 TTL 缓存。
+pub async fn resolve(stack: embassy_net::Stack<'_>, host: &str) -> Result<Ipv4Address, NetworkError> {
+    if let Some(addr) = parse_literal(host) {
+        return Ok(addr);
+    }
+    match lookup(host) {
+        Lookup::Hit(addr) => return Ok(addr),
+        Lookup::Negative => return Err(NetworkError::DnsResolutionFailed),
+        Lookup::Miss => {}
+    }
+    super::resolver::fetch_with_stack(stack, host).await
+}
+
+/// 解析主机名并连接 TCP Socket
+///
+/// 连接失败时移除该主机的缓存，下次重连重新解析。
+pub async fn connect_host(
+    stack: embassy_net::Stack<'_>,
+    socket: &mut embassy_net::tcp::TcpSocket<'_>,
+    host: &str,
+    port: u16,
+) -> Result<Ipv4Address, NetworkError> {
+    let addr = resolve(stack, host).await?;
+    match socket.connect((addr.to_std(), port)).await {
+        Ok(()) => Ok(addr),
+        Err(e) => {
+            flush_host(host);
+            Err(match e {
+                embassy_net::tcp::ConnectError::InvalidState => NetworkError::InternalError,
+                embassy_net::tcp::ConnectError::ConnectionReset => NetworkError::ConnectionRefused,
+                embassy_net::tcp::ConnectError::TimedOut => NetworkError::Timeout,
+                embassy_net::tcp::ConnectError::NoRoute => NetworkError::HostUnreachable,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: DnsCacheConfig = DnsCacheConfig::new()
+        .with_default_ttl(Duration::from_secs(60))
+        .with_max_ttl(Duration::from_secs(120))
+        .with_negative_ttl(Duration::from_secs(10));
+
+    #[test]
+    fn test_ttl_and_negative_caching() {
+        let mut cache: DnsCache<4> = DnsCache::new(CONFIG);
+        let t0 = Instant::from_secs(100);
+        let addr = Ipv4Address::new(10, 0, 0, 7);
+
+        assert_eq!(cache.lookup("broker.local", t0), Lookup::Miss);
+        cache.insert("broker.local", addr, Some(Duration::from_secs(600)), t0);
+        cache.insert_negative("missing.local", t0);
+
+        assert_eq!(cache.lookup("BROKER.local", t0 + Duration::from_secs(119)), Lookup::Hit(addr));
+        assert_eq!(cache.lookup("broker.local", t0 + Duration::from_secs(120)), Lookup::Miss);
+        assert_eq!(cache.lookup("missing.local", t0 + Duration::from_secs(5)), Lookup::Negative);
+        assert_eq!(cache.lookup("missing.local", t0 + Duration::from_secs(10)), Lookup::Miss);
+        assert!(cache.is_empty());
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.negative_hits, stats.misses), (1, 1, 3));
+        assert_eq!(stats.hit_rate_percent(), 40);
+    }
+
+    #[test]
+    fn test_eviction_and_flush() {
+        let mut cache: DnsCache<2> = DnsCache::new(CONFIG);
+        let t0 = Instant::from_secs(0);
+        cache.insert("a", Ipv4Address::new(1, 1, 1, 1), Some(Duration::from_secs(30)), t0);
+        cache.insert("b", Ipv4Address::new(2, 2, 2, 2), None, t0);
+        cache.insert("c", Ipv4Address::new(3, 3, 3, 3), None, t0);
+
+        assert_eq!(cache.lookup("a", t0), Lookup::Miss);
+        assert_eq!(cache.lookup("b", t0), Lookup::Hit(Ipv4Address::new(2, 2, 2, 2)));
+        assert_eq!(cache.stats().evictions, 1);
+
+        assert!(cache.flush_host("c"));
+        assert!(!cache.flush_host("c"));
+        cache.flush();
+        assert_eq!(cache.len(), 0);
+        assert_eq!(parse_literal("192.168.4.1"), Some(Ipv4Address::new(192, 168, 4, 1)));
+        assert_eq!(parse_literal("broker.local"), None);
+    }
+}
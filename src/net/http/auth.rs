@@ -0,0 +1,203 @@
+//! HTTP 认证
+//!
+//! 将 `sys::auth::Authenticator` 接入 HTTP 处理器:
+//! - 解析 `Authorization: Bearer <token>` 与 `Authorization: Basic <base64>`
+//! - `require`: 在单个路由中认证，失败时直接回复 401 (带 `WWW-Authenticate`)，
+//!   被锁定时回复 429 (带 `Retry-After`)
+//! - `Protected`: 包装处理器，对某个路径前缀下的全部请求统一认证
+//!
+//! HTTP 处理器拿不到对端地址，失败计数按凭据主体区分: Basic 认证按用户名，令牌共用一个计数。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::http::auth::Protected;
+//! use rustrtos::sys::auth::{AuthPolicy, Authenticator};
+//!
+//! static ADMIN: Authenticator = Authenticator::new("http", AuthPolicy::new().with_users(&["admin"]));
+//!
+//! let app = Protected::new(&ADMIN, Api).with_prefix("/api");
+//! http::listen::<2, _>(stack, &HttpConfig::default(), &app).await;
+//! ```
+
+use core::fmt::Write as _;
+
+use embedded_io_async::{Read, Write};
+use heapless::String;
+
+use super::{Exchange, Handler, HttpError, Request, Status};
+use crate::sys::auth::{AuthError, Authenticator, Credential, MAX_TRACKED};
+
+/// Basic 凭据解码后的最大长度 (`user:password`)
+pub const MAX_BASIC_LEN: usize = 96;
+
+/// 默认认证域
+pub const DEFAULT_REALM: &str = "rustrtos";
+
+/// 从 `Authorization` 头解析凭据
+///
+/// Basic 凭据解码到 `buf`，格式错误或超长时返回 `None`。
+pub fn credential<'a>(req: &Request<'a>, buf: &'a mut [u8]) -> Option<Credential<'a>> {
+    let value = req.header("Authorization")?.trim();
+    let (scheme, rest) = value.split_once(' ')?;
+    let rest = rest.trim();
+    if scheme.eq_ignore_ascii_case("Bearer") {
+        return (!rest.is_empty()).then_some(Credential::Token(rest.as_bytes()));
+    }
+    if !scheme.eq_ignore_ascii_case("Basic") {
+        return None;
+    }
+    let len = decode_base64(rest, buf)?;
+    let decoded = &buf[..len];
+    let colon = decoded.iter().position(|&b| b == b':')?;
+    let (user, password) = decoded.split_at(colon);
+    let user = core::str::from_utf8(user).ok()?;
+    Some(Credential::Password { user, password: &password[1..] })
+}
+
+/// 认证请求
+///
+/// 成功返回匹配的令牌名 / 用户名；失败时已写出错误响应，返回 `Ok(None)`。
+/// 未携带凭据的请求不计入失败次数 (浏览器首次访问总是先不带凭据)。
+pub async fn require<C: Read + Write, const N: usize>(
+    auth: &Authenticator<N>,
+    realm: &str,
+    req: &Request<'_>,
+    ex: &mut Exchange<'_, C>,
+) -> Result<Option<&'static str>, HttpError> {
+    let mut buf = [0u8; MAX_BASIC_LEN];
+    let result = match credential(req, &mut buf) {
+        Some(credential) => auth.authenticate(credential.subject_key(), credential),
+        None => Err(AuthError::Missing),
+    };
+    buf.fill(0);
+    match result {
+        Ok(principal) => Ok(Some(principal)),
+        Err(e) => {
+            reject(ex, realm, e).await?;
+            Ok(None)
+        }
+    }
+}
+
+/// 按认证错误回复
+async fn reject<C: Read + Write>(ex: &mut Exchange<'_, C>, realm: &str, error: AuthError) -> Result<(), HttpError> {
+    let mut value: String<80> = String::new();
+    let (status, header) = match error {
+        AuthError::LockedOut { retry_after_secs } => {
+            let _ = write!(value, "{}", retry_after_secs);
+            (Status::TooManyRequests, "Retry-After")
+        }
+        AuthError::NotProvisioned => {
+            let _ = value.push_str("no-store");
+            (Status::ServiceUnavailable, "Cache-Control")
+        }
+        AuthError::Missing | AuthError::Invalid => {
+            let _ = write!(value, "Basic realm=\"{}\", charset=\"UTF-8\"", realm);
+            (Status::Unauthorized, "WWW-Authenticate")
+        }
+    };
+    let body = status.reason().as_bytes();
+    ex.start(status, &[("Content-Type", "text/plain"), (header, &value)], body.len()).await?;
+    ex.write(body).await
+}
+
+/// 需要认证的处理器包装
+pub struct Protected<'a, H, const N: usize = MAX_TRACKED> {
+    auth: &'a Authenticator<N>,
+    realm: &'static str,
+    prefix: &'static str,
+    inner: H,
+}
+
+impl<'a, H: Handler, const N: usize> Protected<'a, H, N> {
+    /// 包装处理器 (默认保护全部路径)
+    pub fn new(auth: &'a Authenticator<N>, inner: H) -> Self {
+        Self { auth, realm: DEFAULT_REALM, prefix: "/", inner }
+    }
+
+    /// 设置认证域
+    pub fn with_realm(mut self, realm: &'static str) -> Self {
+        self.realm = realm;
+        self
+    }
+
+    /// 只保护该前缀下的路径 (按路径段匹配)
+    pub fn with_prefix(mut self, prefix: &'static str) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// 路径是否受保护
+    fn covers(&self, path: &str) -> bool {
+        match path.strip_prefix(self.prefix) {
+            Some(rest) => self.prefix.ends_with('/') || rest.is_empty() || rest.starts_with('/'),
+            None => false,
+        }
+    }
+}
+
+impl<H: Handler, const N: usize> Handler for Protected<'_, H, N> {
+    async fn handle<C: Read + Write>(&self, req: &Request<'_>, ex: &mut Exchange<'_, C>) -> Result<(), HttpError> {
+        if self.covers(req.path()) && require(self.auth, self.realm, req, ex).await?.is_none() {
+            return Ok(());
+        }
+        self.inner.handle(req, ex).await
+    }
+}
+
+/// 解码标准 Base64 (允许省略末尾填充)
+fn decode_base64(input: &str, out: &mut [u8]) -> Option<usize> {
+    fn value(c: u8) -> Option<u32> {
+        Some(match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        } as u32)
+    }
+
+    let input = input.trim_end_matches('=').as_bytes();
+    if input.len() % 4 == 1 {
+        return None;
+    }
+    let mut len = 0;
+    for chunk in input.chunks(4) {
+        let mut acc = 0u32;
+        for &c in chunk {
+            acc = (acc << 6) | value(c)?;
+        }
+        acc <<= 6 * (4 - chunk.len()) as u32;
+        let bytes = acc.to_be_bytes();
+        let n = chunk.len() - 1;
+        out.get_mut(len..len + n)?.copy_from_slice(&bytes[1..1 + n]);
+        len += n;
+    }
+    Some(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_authorization() {
+        let mut buf = [0u8; MAX_BASIC_LEN];
+        let head = b"GET / HTTP/1.1\r\nAuthorization: Basic YWRtaW46cDpzcw==\r\n\r\n";
+        let req = Request::parse(head).unwrap();
+        assert_eq!(
+            credential(&req, &mut buf),
+            Some(Credential::Password { user: "admin", password: b"p:ss" })
+        );
+
+        let head = b"GET / HTTP/1.1\r\nauthorization: bearer abc123\r\n\r\n";
+        let req = Request::parse(head).unwrap();
+        assert_eq!(credential(&req, &mut buf), Some(Credential::Token(b"abc123")));
+
+        let head = b"GET / HTTP/1.1\r\nAuthorization: Basic !!!\r\n\r\n";
+        let req = Request::parse(head).unwrap();
+        assert_eq!(credential(&req, &mut buf), None);
+    }
+}
@@ -0,0 +1,1275 @@
+//! HTTP/1.1 客户端 (连接复用与管线化)
+//!
+//! 设备每隔几秒轮询同一 API 时，每次重新建立 TCP (以及 TLS) 连接的开销远大于请求本身。
+//! 客户端维护一个按 `host:port` 索引的小型连接缓存：
+//! - Keep-Alive 复用: 响应完整且服务器未要求关闭时，连接留在缓存中供下次请求使用
+//! - 空闲超时淘汰 (取配置值与服务器 `Keep-Alive: timeout=` 中较小者)，缓存满时淘汰最久未用的连接
+//! - 复用的连接已被服务器关闭 (未收到任何响应字节) 时透明重连并重试一次
+//! - 管线化: `pipeline` 在同一连接上连续发出多个 GET，再按序读取响应
+//! - 重定向: 301/302/303/307/308 按 `Location` 跟随 (绝对、协议相对与相对路径)，次数受 `max_redirects` 限制
+//! - 响应头复制到 `ResponseHeaders` (heapless 缓冲区)，按名字不区分大小写查询
+//! - 统计: 请求数、新建连接、复用次数、重定向、淘汰与失败
+//!
+//! 传输层通过 `Transport` 抽象；`TcpTransport` 基于 embassy-net，主机名经 `net::dns` 缓存解析。
+//! 响应体须放得下调用方提供的缓冲区，支持 Content-Length、分块编码与以关闭连接结束的响应体。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::http::{ClientConfig, HttpClient, TcpTransport};
+//!
+//! let mut bufs = [[0u8; 1024]; 4];
+//! let [rx0, tx0, rx1, tx1] = &mut bufs;
+//! let mut client = HttpClient::new(
+//!     ClientConfig::new().with_idle_timeout(Duration::from_secs(20)),
+//!     [TcpTransport::new(stack, rx0, tx0), TcpTransport::new(stack, rx1, tx1)],
+//! );
+//!
+//! let mut body = [0u8; 512];
+//! loop {
+//!     let resp = client.get("api.example.com", 80, "/v1/state", &mut body).await?;
+//!     handle(resp.status, resp.headers.content_type(), resp.body);
+//!     Timer::after_secs(5).await;
+//! }
+//!
+//! // 上报 JSON
+//! let resp = client.post("api.example.com", 80, "/v1/readings", "application/json", payload, &mut body).await?;
+//! // 按 URL 请求 (跟随重定向)
+//! let resp = client.get_url("http://example.com/", &mut body).await?;
+//! // 连接复用情况
+//! log_info!("{}", client.stats());
+//! ```
+
+use core::fmt::{self, Write as _};
+
+use embassy_time::{with_timeout, Duration, Instant};
+use embedded_io_async::{ErrorType, Read, Write};
+use heapless::{String, Vec};
+
+use super::{find_head_end, HttpError, Method, HTTPS_PORT, HTTP_PORT, MAX_HEADERS, MAX_HEAD_LEN};
+use crate::net::dns::{self, MAX_HOST_LEN};
+use crate::net::tcp::NetworkError;
+use crate::util::log::*;
+
+/// 单次管线化的最大请求数 (避免双方缓冲区同时写满而互相等待)
+pub const MAX_PIPELINE: usize = 4;
+
+/// 保存响应头名与值的缓冲区大小
+pub const RESPONSE_HEADERS_LEN: usize = 512;
+
+/// 重定向目标路径的最大长度
+pub const MAX_LOCATION_LEN: usize = 256;
+
+// ===== 配置 =====
+
+/// 客户端配置
+#[derive(Debug, Clone, Copy)]
+pub struct ClientConfig {
+    /// 连接 / 读写超时
+    pub timeout: Duration,
+    /// 空闲连接保留时间
+    pub idle_timeout: Duration,
+    /// 单个连接最多承载的请求数 (之后主动关闭)
+    pub max_requests: u32,
+    /// User-Agent
+    pub user_agent: &'static str,
+    /// 最多跟随的重定向次数 (0 表示不跟随)
+    pub max_redirects: u8,
+}
+
+impl ClientConfig {
+    /// 默认配置
+    pub const fn new() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+            idle_timeout: Duration::from_secs(30),
+            max_requests: 100,
+            user_agent: "rustrtos",
+            max_redirects: 3,
+        }
+    }
+
+    /// 设置连接 / 读写超时
+    pub const fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 设置空闲连接保留时间
+    pub const fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// 设置单个连接最多承载的请求数
+    pub const fn with_max_requests(mut self, max_requests: u32) -> Self {
+        self.max_requests = max_requests;
+        self
+    }
+
+    /// 设置 User-Agent
+    pub const fn with_user_agent(mut self, user_agent: &'static str) -> Self {
+        self.user_agent = user_agent;
+        self
+    }
+
+    /// 设置最多跟随的重定向次数
+    pub const fn with_max_redirects(mut self, max_redirects: u8) -> Self {
+        self.max_redirects = max_redirects;
+        self
+    }
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ===== 传输层 =====
+
+/// 可重复打开的连接 (连接缓存的一个槽位)
+#[allow(async_fn_in_trait)]
+pub trait Transport: Read + Write {
+    /// 连接到 `host:port`
+    async fn open(&mut self, host: &str, port: u16) -> Result<(), HttpError>;
+
+    /// 关闭连接 (不等待对端确认)
+    fn close(&mut self);
+}
+
+/// 基于 embassy-net TCP Socket 的传输层
+pub struct TcpTransport<'a> {
+    stack: embassy_net::Stack<'a>,
+    socket: embassy_net::tcp::TcpSocket<'a>,
+}
+
+impl<'a> TcpTransport<'a> {
+    /// 创建 (缓冲区在整个生命周期内由该槽位独占)
+    pub fn new(stack: embassy_net::Stack<'a>, rx: &'a mut [u8], tx: &'a mut [u8]) -> Self {
+        Self { stack, socket: embassy_net::tcp::TcpSocket::new(stack, rx, tx) }
+    }
+}
+
+impl ErrorType for TcpTransport<'_> {
+    type Error = embassy_net::tcp::Error;
+}
+
+impl Read for TcpTransport<'_> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        self.socket.read(buf).await
+    }
+}
+
+impl Write for TcpTransport<'_> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.socket.write(buf).await
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.socket.flush().await
+    }
+}
+
+impl Transport for TcpTransport<'_> {
+    async fn open(&mut self, host: &str, port: u16) -> Result<(), HttpError> {
+        dns::connect_host(self.stack, &mut self.socket, host, port).await.map(|_| ()).map_err(HttpError::Connect)
+    }
+
+    fn close(&mut self) {
+        // abort 立即回到 Closed 状态，槽位可直接重新连接
+        self.socket.abort();
+    }
+}
+
+// ===== 请求与响应 =====
+
+/// 客户端请求
+#[derive(Debug, Clone, Copy)]
+pub struct ClientRequest<'r> {
+    method: Method,
+    path: &'r str,
+    headers: &'r [(&'r str, &'r str)],
+    body: &'r [u8],
+}
+
+impl<'r> ClientRequest<'r> {
+    /// 创建
+    pub const fn new(method: Method, path: &'r str) -> Self {
+        Self { method, path, headers: &[], body: &[] }
+    }
+
+    /// GET 请求
+    pub const fn get(path: &'r str) -> Self {
+        Self::new(Method::Get, path)
+    }
+
+    /// POST 请求
+    pub const fn post(path: &'r str, body: &'r [u8]) -> Self {
+        Self::new(Method::Post, path).with_body(body)
+    }
+
+    /// 附加头部 (Host、User-Agent、Content-Length 由客户端生成)
+    pub const fn with_headers(mut self, headers: &'r [(&'r str, &'r str)]) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// 设置请求体
+    pub const fn with_body(mut self, body: &'r [u8]) -> Self {
+        self.body = body;
+        self
+    }
+}
+
+/// 响应头 (名与值复制到内部缓冲区，放不下的头部被丢弃并计数)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResponseHeaders {
+    text: String<RESPONSE_HEADERS_LEN>,
+    /// (名起点, 值起点, 值终点)
+    spans: Vec<(u16, u16, u16), MAX_HEADERS>,
+    dropped: u8,
+}
+
+impl ResponseHeaders {
+    /// 创建空集合
+    pub const fn new() -> Self {
+        Self { text: String::new(), spans: Vec::new(), dropped: 0 }
+    }
+
+    fn push(&mut self, name: &str, value: &str) {
+        let start = self.text.len();
+        let fits = self.spans.len() < MAX_HEADERS && start + name.len() + value.len() <= RESPONSE_HEADERS_LEN;
+        if !fits {
+            self.dropped = self.dropped.saturating_add(1);
+            return;
+        }
+        let _ = self.text.push_str(name);
+        let _ = self.text.push_str(value);
+        let _ = self.spans.push((start as u16, (start + name.len()) as u16, self.text.len() as u16));
+    }
+
+    /// 按名字查询 (不区分大小写，重复时返回第一个)
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v)
+    }
+
+    /// 遍历 (名, 值)
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> + '_ {
+        self.spans.iter().map(|&(n, v, e)| (&self.text[n as usize..v as usize], &self.text[v as usize..e as usize]))
+    }
+
+    /// 保存的头部数量
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// 因缓冲区不足被丢弃的头部数量
+    pub fn dropped(&self) -> u8 {
+        self.dropped
+    }
+
+    /// Content-Type
+    pub fn content_type(&self) -> Option<&str> {
+        self.get("content-type")
+    }
+}
+
+/// 服务器响应
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response<'b> {
+    /// 状态码
+    pub status: u16,
+    /// 响应头
+    pub headers: ResponseHeaders,
+    /// 响应体
+    pub body: &'b [u8],
+}
+
+impl Response<'_> {
+    /// 是否为 2xx
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// 是否为需要跟随的重定向 (301/302/303/307/308)
+    pub fn is_redirect(&self) -> bool {
+        is_redirect(self.status)
+    }
+
+    /// 响应体按 UTF-8 解释
+    pub fn text(&self) -> Option<&str> {
+        core::str::from_utf8(self.body).ok()
+    }
+}
+
+fn is_redirect(status: u16) -> bool {
+    matches!(status, 301 | 302 | 303 | 307 | 308)
+}
+
+// ===== URL 与重定向 =====
+
+/// 解析后的 `http://` / `https://` URL (引用原字符串)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Url<'a> {
+    /// 是否为 https
+    pub https: bool,
+    /// 主机名
+    pub host: &'a str,
+    /// 端口 (未写明时取协议默认端口)
+    pub port: u16,
+    /// 路径与查询 (以 `/` 开头，不含片段)
+    pub path: &'a str,
+}
+
+impl<'a> Url<'a> {
+    /// 解析绝对 URL
+    pub fn parse(url: &'a str) -> Result<Self, HttpError> {
+        let (scheme, rest) = url.split_once("://").ok_or(HttpError::BadRequest)?;
+        let https = if scheme.eq_ignore_ascii_case("https") {
+            true
+        } else if scheme.eq_ignore_ascii_case("http") {
+            false
+        } else {
+            return Err(HttpError::BadRequest);
+        };
+        Self::parse_authority(rest, https)
+    }
+
+    /// 解析 `host[:port][/path]` 部分
+    fn parse_authority(rest: &'a str, https: bool) -> Result<Self, HttpError> {
+        let rest = rest.split('#').next().unwrap_or("");
+        let split = rest.find(['/', '?']).unwrap_or(rest.len());
+        let (authority, path) = rest.split_at(split);
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().map_err(|_| HttpError::BadRequest)?),
+            None => (authority, if https { HTTPS_PORT } else { HTTP_PORT }),
+        };
+        // 路径为空时补 `/`；只有查询串的 URL (`host?q`) 无法不复制地补齐，不支持
+        if host.is_empty() || host.len() > MAX_HOST_LEN || path.starts_with('?') {
+            return Err(HttpError::BadRequest);
+        }
+        let path = if path.is_empty() { "/" } else { path };
+        Ok(Self { https, host, port, path })
+    }
+}
+
+/// 重定向后的请求目标
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Target {
+    host: String<MAX_HOST_LEN>,
+    port: u16,
+    path: String<MAX_LOCATION_LEN>,
+    method: Method,
+    /// 是否沿用原请求体
+    keep_body: bool,
+    /// 是否沿用附加头部 (跨源时丢弃，避免把认证头发给其他主机)
+    keep_headers: bool,
+}
+
+impl Target {
+    /// 按 `Location` 计算下一跳
+    ///
+    /// 303 以及 301/302 之后的 POST 改为不带请求体的 GET (与浏览器一致)，307/308 保持方法与请求体；
+    /// 跳到其他主机或端口时不再附加调用方的头部
+    fn resolve(host: &str, port: u16, path: &str, method: Method, status: u16, location: &str) -> Result<Self, HttpError> {
+        let location = location.trim();
+        let url = if location.contains("://") {
+            Some(Url::parse(location).map_err(|_| HttpError::BadResponse)?)
+        } else if let Some(rest) = location.strip_prefix("//") {
+            Some(Url::parse_authority(rest, port == HTTPS_PORT).map_err(|_| HttpError::BadResponse)?)
+        } else {
+            None
+        };
+
+        let mut target = Self {
+            host: String::new(),
+            port: url.map_or(port, |u| u.port),
+            path: String::new(),
+            method,
+            keep_body: true,
+            keep_headers: true,
+        };
+        target.host.push_str(url.map_or(host, |u| u.host)).map_err(|_| HttpError::BadResponse)?;
+        let pushed = match url {
+            Some(url) => target.path.push_str(url.path),
+            None if location.starts_with('/') => target.path.push_str(location),
+            None => {
+                // 相对路径: 以当前路径所在目录为基准
+                let base = path.split('?').next().unwrap_or("/");
+                let dir = base.rfind('/').map_or("/", |i| &base[..=i]);
+                target.path.push_str(dir).and_then(|_| target.path.push_str(location))
+            }
+        };
+        pushed.map_err(|_| HttpError::HeaderTooLarge)?;
+        target.keep_headers = target.port == port && target.host.eq_ignore_ascii_case(host);
+
+        let rewrite = status == 303 || (matches!(status, 301 | 302) && method == Method::Post);
+        if rewrite && method != Method::Head {
+            target.method = Method::Get;
+            target.keep_body = false;
+        }
+        Ok(target)
+    }
+}
+
+// ===== 统计 =====
+
+/// 客户端统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClientStats {
+    /// 完成的请求
+    pub requests: u32,
+    /// 新建连接
+    pub connects: u32,
+    /// 复用已有连接
+    pub reused: u32,
+    /// 复用的连接已失效后重连重试
+    pub stale_retries: u32,
+    /// 跟随的重定向
+    pub redirects: u32,
+    /// 空闲超时淘汰
+    pub idle_evictions: u32,
+    /// 缓存满时淘汰
+    pub lru_evictions: u32,
+    /// 失败的请求
+    pub failures: u32,
+}
+
+impl ClientStats {
+    /// 复用率 (百分比)
+    pub fn reuse_percent(&self) -> u32 {
+        (self.reused * 100).checked_div(self.connects + self.reused).unwrap_or(0)
+    }
+}
+
+impl fmt::Display for ClientStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "requests={} connects={} reused={} ({}%) stale={} redirects={} evicted={}/{} failures={}",
+            self.requests,
+            self.connects,
+            self.reused,
+            self.reuse_percent(),
+            self.stale_retries,
+            self.redirects,
+            self.idle_evictions,
+            self.lru_evictions,
+            self.failures
+        )
+    }
+}
+
+// ===== 响应读取 =====
+
+/// 已解析的响应头
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Head {
+    status: u16,
+    body: BodyKind,
+    keep_alive: bool,
+    idle: Option<Duration>,
+    headers: ResponseHeaders,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BodyKind {
+    Length(usize),
+    Chunked,
+    UntilClose,
+}
+
+impl Head {
+    fn parse(head: &[u8], method: Method) -> Result<Self, HttpError> {
+        let text = core::str::from_utf8(head).map_err(|_| HttpError::BadResponse)?;
+        let mut lines = text.split("\r\n");
+        let mut parts = lines.next().ok_or(HttpError::BadResponse)?.split(' ');
+        let version = parts.next().ok_or(HttpError::BadResponse)?;
+        let status: u16 = parts.next().and_then(|s| s.parse().ok()).ok_or(HttpError::BadResponse)?;
+        if !version.starts_with("HTTP/1.") {
+            return Err(HttpError::BadResponse);
+        }
+
+        let mut parsed = Self {
+            status,
+            body: BodyKind::UntilClose,
+            keep_alive: version == "HTTP/1.1",
+            idle: None,
+            headers: ResponseHeaders::new(),
+        };
+        for line in lines.take_while(|l| !l.is_empty()) {
+            let (name, value) = line.split_once(':').ok_or(HttpError::BadResponse)?;
+            let value = value.trim();
+            parsed.headers.push(name, value);
+            if name.eq_ignore_ascii_case("content-length") {
+                if parsed.body != BodyKind::Chunked {
+                    parsed.body = BodyKind::Length(value.parse().map_err(|_| HttpError::BadResponse)?);
+                }
+            } else if name.eq_ignore_ascii_case("transfer-encoding") {
+                if value.eq_ignore_ascii_case("chunked") {
+                    parsed.body = BodyKind::Chunked;
+                }
+            } else if name.eq_ignore_ascii_case("connection") {
+                parsed.keep_alive = !value.eq_ignore_ascii_case("close");
+            } else if name.eq_ignore_ascii_case("keep-alive") {
+                parsed.idle = value
+                    .split(',')
+                    .filter_map(|p| p.trim().strip_prefix("timeout="))
+                    .find_map(|s| s.parse().ok())
+                    .map(Duration::from_secs);
+            }
+        }
+        // 无响应体的响应
+        if method == Method::Head || status == 204 || status == 304 || (100..200).contains(&status) {
+            parsed.body = BodyKind::Length(0);
+        }
+        if parsed.body == BodyKind::UntilClose {
+            parsed.keep_alive = false;
+        }
+        Ok(parsed)
+    }
+}
+
+/// 带缓冲的响应读取器
+///
+/// 头部与分块长度行经内部缓冲区读取；定长数据直接读入输出缓冲区，
+/// 从不越过当前响应读取，管线化时后续响应保留在缓冲区或连接中。
+struct Reader<'c, C> {
+    io: &'c mut C,
+    buf: [u8; MAX_HEAD_LEN],
+    start: usize,
+    end: usize,
+    received: usize,
+    timeout: Duration,
+}
+
+impl<'c, C: Read> Reader<'c, C> {
+    fn new(io: &'c mut C, timeout: Duration) -> Self {
+        Self { io, buf: [0; MAX_HEAD_LEN], start: 0, end: 0, received: 0, timeout }
+    }
+
+    fn buffered(&self) -> &[u8] {
+        &self.buf[self.start..self.end]
+    }
+
+    async fn raw_read(&mut self, out: &mut [u8]) -> Result<usize, HttpError> {
+        let n = read_some(self.io, out, self.timeout).await?;
+        self.received += n;
+        Ok(n)
+    }
+
+    /// 读取更多数据到缓冲区，对端关闭时返回 0
+    async fn fill(&mut self) -> Result<usize, HttpError> {
+        if self.start > 0 {
+            self.buf.copy_within(self.start..self.end, 0);
+            self.end -= self.start;
+            self.start = 0;
+        }
+        if self.end == self.buf.len() {
+            return Err(HttpError::HeaderTooLarge);
+        }
+        let n = read_some(self.io, &mut self.buf[self.end..], self.timeout).await?;
+        self.received += n;
+        self.end += n;
+        Ok(n)
+    }
+
+    async fn head(&mut self, method: Method) -> Result<Head, HttpError> {
+        loop {
+            if let Some(len) = find_head_end(self.buffered()) {
+                let head = Head::parse(&self.buf[self.start..self.start + len], method)?;
+                self.start += len;
+                return Ok(head);
+            }
+            if self.fill().await? == 0 {
+                return Err(HttpError::Io);
+            }
+        }
+    }
+
+    /// 读取一行，返回行长度 (不含 CRLF)，内容位于 `buffered()` 开头
+    async fn line(&mut self) -> Result<usize, HttpError> {
+        loop {
+            if let Some(len) = self.buffered().windows(2).position(|w| w == b"\r\n") {
+                return Ok(len);
+            }
+            if self.fill().await? == 0 {
+                return Err(HttpError::Io);
+            }
+        }
+    }
+
+    async fn read_exact(&mut self, out: &mut [u8]) -> Result<(), HttpError> {
+        let n = out.len().min(self.end - self.start);
+        out[..n].copy_from_slice(&self.buf[self.start..self.start + n]);
+        self.start += n;
+        let mut filled = n;
+        while filled < out.len() {
+            let n = self.raw_read(&mut out[filled..]).await?;
+            if n == 0 {
+                return Err(HttpError::Io);
+            }
+            filled += n;
+        }
+        Ok(())
+    }
+
+    /// 读取响应体到 `out`，返回长度
+    async fn body(&mut self, kind: BodyKind, out: &mut [u8]) -> Result<usize, HttpError> {
+        match kind {
+            BodyKind::Length(len) => {
+                let dest = out.get_mut(..len).ok_or(HttpError::PayloadTooLarge)?;
+                self.read_exact(dest).await?;
+                Ok(len)
+            }
+            BodyKind::Chunked => {
+                let mut len = 0;
+                loop {
+                    let line = self.line().await?;
+                    let text = core::str::from_utf8(&self.buffered()[..line]).map_err(|_| HttpError::BadResponse)?;
+                    let size = text.split(';').next().unwrap_or("").trim();
+                    let size = usize::from_str_radix(size, 16).map_err(|_| HttpError::BadResponse)?;
+                    self.start += line + 2;
+                    if size == 0 {
+                        // 跳过 trailer 直到空行
+                        loop {
+                            let line = self.line().await?;
+                            self.start += line + 2;
+                            if line == 0 {
+                                return Ok(len);
+                            }
+                        }
+                    }
+                    let dest = len
+                        .checked_add(size)
+                        .and_then(|end| out.get_mut(len..end))
+                        .ok_or(HttpError::PayloadTooLarge)?;
+                    self.read_exact(dest).await?;
+                    if self.line().await? != 0 {
+                        return Err(HttpError::BadResponse);
+                    }
+                    self.start += 2;
+                    len += size;
+                }
+            }
+            BodyKind::UntilClose => {
+                let mut len = self.end - self.start;
+                let dest = out.get_mut(..len).ok_or(HttpError::PayloadTooLarge)?;
+                dest.copy_from_slice(self.buffered());
+                self.start = self.end;
+                while len < out.len() {
+                    let n = self.raw_read(&mut out[len..]).await?;
+                    if n == 0 {
+                        return Ok(len);
+                    }
+                    len += n;
+                }
+                // 缓冲区恰好填满: 对端随即关闭才算完整
+                match self.raw_read(&mut [0u8; 1]).await? {
+                    0 => Ok(len),
+                    _ => Err(HttpError::PayloadTooLarge),
+                }
+            }
+        }
+    }
+}
+
+async fn read_some<C: Read>(io: &mut C, out: &mut [u8], timeout: Duration) -> Result<usize, HttpError> {
+    with_timeout(timeout, io.read(out)).await.map_err(|_| HttpError::Timeout)?.map_err(|_| HttpError::Io)
+}
+
+// ===== 请求发送 =====
+
+async fn write_all<C: Write>(io: &mut C, data: &[u8], timeout: Duration) -> Result<(), HttpError> {
+    with_timeout(timeout, io.write_all(data)).await.map_err(|_| HttpError::Timeout)?.map_err(|_| HttpError::Io)
+}
+
+async fn send<C: Write>(
+    io: &mut C,
+    host: &str,
+    port: u16,
+    req: &ClientRequest<'_>,
+    config: &ClientConfig,
+) -> Result<(), HttpError> {
+    let mut line: String<{ MAX_HEAD_LEN / 4 }> = String::new();
+    write!(line, "{} {} HTTP/1.1\r\n", req.method.as_str(), req.path).map_err(|_| HttpError::HeaderTooLarge)?;
+    write_all(io, line.as_bytes(), config.timeout).await?;
+
+    line.clear();
+    let _ = write!(line, "Host: {}", host);
+    if port != HTTP_PORT && port != HTTPS_PORT {
+        let _ = write!(line, ":{}", port);
+    }
+    let _ = write!(line, "\r\nUser-Agent: {}\r\n", config.user_agent);
+    if !req.body.is_empty() || matches!(req.method, Method::Post | Method::Put | Method::Patch) {
+        let _ = write!(line, "Content-Length: {}\r\n", req.body.len());
+    }
+    write_all(io, line.as_bytes(), config.timeout).await?;
+
+    for (name, value) in req.headers {
+        write_all(io, name.as_bytes(), config.timeout).await?;
+        write_all(io, b": ", config.timeout).await?;
+        write_all(io, value.as_bytes(), config.timeout).await?;
+        write_all(io, b"\r\n", config.timeout).await?;
+    }
+    write_all(io, b"\r\n", config.timeout).await?;
+    if !req.body.is_empty() {
+        write_all(io, req.body, config.timeout).await?;
+    }
+    Ok(())
+}
+
+async fn flush<C: Write>(io: &mut C, timeout: Duration) -> Result<(), HttpError> {
+    with_timeout(timeout, io.flush()).await.map_err(|_| HttpError::Timeout)?.map_err(|_| HttpError::Io)
+}
+
+/// 一次交换的结果
+struct Outcome {
+    status: u16,
+    headers: ResponseHeaders,
+    len: usize,
+    keep_alive: bool,
+    idle: Option<Duration>,
+}
+
+/// 一次交换的失败 (`stale` 表示未收到任何响应字节)
+struct Failure {
+    error: HttpError,
+    stale: bool,
+}
+
+// ===== 连接缓存 =====
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Origin {
+    host: String<MAX_HOST_LEN>,
+    port: u16,
+}
+
+impl Origin {
+    fn matches(&self, host: &str, port: u16) -> bool {
+        self.port == port && self.host.eq_ignore_ascii_case(host)
+    }
+}
+
+struct Slot<T> {
+    conn: T,
+    origin: Option<Origin>,
+    last_used: Instant,
+    idle: Duration,
+    uses: u32,
+}
+
+/// HTTP 客户端 (最多缓存 `N` 个连接)
+pub struct HttpClient<T, const N: usize> {
+    slots: [Slot<T>; N],
+    config: ClientConfig,
+    stats: ClientStats,
+}
+
+impl<T: Transport, const N: usize> HttpClient<T, N> {
+    /// 创建 (每个传输层对象是一个连接槽位)
+    pub fn new(config: ClientConfig, transports: [T; N]) -> Self {
+        let slots = transports.map(|conn| Slot {
+            conn,
+            origin: None,
+            last_used: Instant::from_ticks(0),
+            idle: config.idle_timeout,
+            uses: 0,
+        });
+        Self { slots, config, stats: ClientStats::default() }
+    }
+
+    /// 发送请求，响应体写入 `body`
+    ///
+    /// 跟随最多 `max_redirects` 次重定向；超过次数或缺少 `Location` 时返回该 3xx 响应
+    pub async fn request<'b>(
+        &mut self,
+        host: &str,
+        port: u16,
+        req: &ClientRequest<'_>,
+        body: &'b mut [u8],
+    ) -> Result<Response<'b>, HttpError> {
+        let mut target: Option<Target> = None;
+        let mut hops = 0;
+        let (status, headers, len) = loop {
+            let (host, port, req) = match &target {
+                Some(t) => (
+                    t.host.as_str(),
+                    t.port,
+                    ClientRequest {
+                        method: t.method,
+                        path: t.path.as_str(),
+                        headers: if t.keep_headers { req.headers } else { &[] },
+                        body: if t.keep_body { req.body } else { &[] },
+                    },
+                ),
+                None => (host, port, *req),
+            };
+            let (status, headers, len) = self.exchange_once(host, port, &req, body).await?;
+            if !is_redirect(status) || hops >= self.config.max_redirects {
+                break (status, headers, len);
+            }
+            let Some(location) = headers.get("location") else {
+                break (status, headers, len);
+            };
+            let mut next = Target::resolve(host, port, req.path, req.method, status, location)?;
+            // 已丢弃的请求体与头部不会在后续跳转中恢复
+            if let Some(t) = &target {
+                next.keep_body &= t.keep_body;
+                next.keep_headers &= t.keep_headers;
+            }
+            log_debug!("HTTP client: {} redirect to {}:{}{}", status, next.host.as_str(), next.port, next.path.as_str());
+            self.stats.redirects += 1;
+            hops += 1;
+            target = Some(next);
+        };
+        Ok(Response { status, headers, body: &body[..len] })
+    }
+
+    /// GET 请求
+    pub async fn get<'b>(&mut self, host: &str, port: u16, path: &str, body: &'b mut [u8]) -> Result<Response<'b>, HttpError> {
+        self.request(host, port, &ClientRequest::get(path), body).await
+    }
+
+    /// 按 URL 发送 GET 请求 (https 需传输层自行完成 TLS)
+    pub async fn get_url<'b>(&mut self, url: &str, body: &'b mut [u8]) -> Result<Response<'b>, HttpError> {
+        let url = Url::parse(url)?;
+        self.get(url.host, url.port, url.path, body).await
+    }
+
+    /// POST 请求
+    pub async fn post<'b>(
+        &mut self,
+        host: &str,
+        port: u16,
+        path: &str,
+        content_type: &str,
+        payload: &[u8],
+        body: &'b mut [u8],
+    ) -> Result<Response<'b>, HttpError> {
+        let headers = [("Content-Type", content_type)];
+        self.request(host, port, &ClientRequest::post(path, payload).with_headers(&headers), body).await
+    }
+
+    /// 发送一次请求 (不跟随重定向)，复用的连接失效时重连重试一次
+    async fn exchange_once(
+        &mut self,
+        host: &str,
+        port: u16,
+        req: &ClientRequest<'_>,
+        body: &mut [u8],
+    ) -> Result<(u16, ResponseHeaders, usize), HttpError> {
+        let mut retried = false;
+        loop {
+            let (index, reused) = self.checkout(host, port).await?;
+            let slot = &mut self.slots[index];
+            let result = match send(&mut slot.conn, host, port, req, &self.config).await {
+                Ok(()) => exchange(&mut slot.conn, req.method, body, &self.config).await,
+                Err(error) => Err(Failure { error, stale: true }),
+            };
+            match result {
+                Ok(outcome) => {
+                    self.stats.requests += 1;
+                    self.checkin(index, outcome.keep_alive, outcome.idle);
+                    return Ok((outcome.status, outcome.headers, outcome.len));
+                }
+                Err(failure) => {
+                    self.release(index);
+                    if reused && failure.stale && !retried {
+                        // 服务器已关闭空闲连接，重连重试
+                        retried = true;
+                        self.stats.stale_retries += 1;
+                        continue;
+                    }
+                    self.stats.failures += 1;
+                    return Err(failure.error);
+                }
+            }
+        }
+    }
+
+    /// 管线化 GET: 先发出全部请求，再按序读取响应并交给 `on_response`
+    ///
+    /// 不跟随重定向。每个响应体依次复用 `body`。服务器中途要求关闭连接时停止，返回已处理的响应数，
+    /// 调用方可对剩余路径重新发起。
+    pub async fn pipeline(
+        &mut self,
+        host: &str,
+        port: u16,
+        paths: &[&str],
+        body: &mut [u8],
+        mut on_response: impl FnMut(usize, Response<'_>),
+    ) -> Result<usize, HttpError> {
+        if paths.is_empty() {
+            return Ok(0);
+        }
+        if paths.len() > MAX_PIPELINE {
+            return Err(HttpError::PayloadTooLarge);
+        }
+        let mut retried = false;
+        loop {
+            let (index, reused) = self.checkout(host, port).await?;
+            let config = self.config;
+            let slot = &mut self.slots[index];
+
+            let mut sent = Ok(());
+            for path in paths {
+                sent = send(&mut slot.conn, host, port, &ClientRequest::get(path), &config).await;
+                if sent.is_err() {
+                    break;
+                }
+            }
+            let sent = match sent {
+                Ok(()) => flush(&mut slot.conn, config.timeout).await,
+                Err(e) => Err(e),
+            };
+            if let Err(error) = sent {
+                self.release(index);
+                if reused && !retried {
+                    retried = true;
+                    self.stats.stale_retries += 1;
+                    continue;
+                }
+                self.stats.failures += 1;
+                return Err(error);
+            }
+
+            let mut reader = Reader::new(&mut slot.conn, config.timeout);
+            let mut done = 0;
+            let mut keep = (true, None);
+            let mut error = None;
+            for i in 0..paths.len() {
+                let result = async {
+                    let head = reader.head(Method::Get).await?;
+                    let len = reader.body(head.body, body).await?;
+                    Ok::<_, HttpError>((head, len))
+                }
+                .await;
+                match result {
+                    Ok((head, len)) => {
+                        keep = (head.keep_alive, head.idle);
+                        on_response(i, Response { status: head.status, headers: head.headers, body: &body[..len] });
+                        done += 1;
+                        if !keep.0 {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        error = Some((e, reader.received == 0));
+                        break;
+                    }
+                }
+            }
+            self.stats.requests += done as u32;
+
+            match error {
+                Some((_, true)) if reused && !retried => {
+                    self.release(index);
+                    retried = true;
+                    self.stats.stale_retries += 1;
+                }
+                Some((e, _)) => {
+                    self.release(index);
+                    self.stats.failures += 1;
+                    return if done > 0 { Ok(done) } else { Err(e) };
+                }
+                None => {
+                    self.checkin(index, keep.0, keep.1);
+                    return Ok(done);
+                }
+            }
+        }
+    }
+
+    /// 取得到 `host:port` 的连接，返回槽位与是否复用
+    async fn checkout(&mut self, host: &str, port: u16) -> Result<(usize, bool), HttpError> {
+        let now = Instant::now();
+        self.evict_idle_at(now);
+
+        if let Some(index) = self.slots.iter().position(|s| s.origin.as_ref().is_some_and(|o| o.matches(host, port))) {
+            self.stats.reused += 1;
+            return Ok((index, true));
+        }
+
+        let index = match self.slots.iter().position(|s| s.origin.is_none()) {
+            Some(index) => index,
+            None => {
+                let (index, _) = self.slots.iter().enumerate().min_by_key(|(_, s)| s.last_used).ok_or(HttpError::Io)?;
+                self.stats.lru_evictions += 1;
+                self.release(index);
+                index
+            }
+        };
+
+        let mut origin_host = String::new();
+        origin_host.push_str(host).map_err(|_| HttpError::Connect(NetworkError::InvalidAddress))?;
+        let slot = &mut self.slots[index];
+        match with_timeout(self.config.timeout, slot.conn.open(host, port)).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => {
+                slot.conn.close();
+                self.stats.failures += 1;
+                return Err(e);
+            }
+            Err(_) => {
+                slot.conn.close();
+                self.stats.failures += 1;
+                return Err(HttpError::Timeout);
+            }
+        }
+        log_debug!("HTTP client: connected to {}:{} (slot {})", host, port, index);
+        slot.origin = Some(Origin { host: origin_host, port });
+        slot.uses = 0;
+        slot.idle = self.config.idle_timeout;
+        slot.last_used = now;
+        self.stats.connects += 1;
+        Ok((index, false))
+    }
+
+    /// 请求成功后归还连接
+    fn checkin(&mut self, index: usize, keep_alive: bool, idle: Option<Duration>) {
+        let slot = &mut self.slots[index];
+        slot.uses += 1;
+        slot.last_used = Instant::now();
+        if let Some(idle) = idle {
+            // 提前一秒，避免与服务器关闭连接竞争
+            slot.idle = self.config.idle_timeout.min(idle.checked_sub(Duration::from_secs(1)).unwrap_or_default());
+        }
+        if !keep_alive || slot.uses >= self.config.max_requests {
+            self.release(index);
+        }
+    }
+
+    /// 关闭槽位上的连接
+    fn release(&mut self, index: usize) {
+        let slot = &mut self.slots[index];
+        if slot.origin.take().is_some() {
+            slot.conn.close();
+        }
+    }
+
+    /// 按当前时间淘汰空闲连接
+    pub fn evict_idle(&mut self) -> usize {
+        self.evict_idle_at(Instant::now())
+    }
+
+    /// 淘汰在 `now` 时已空闲超时的连接，返回淘汰数
+    pub fn evict_idle_at(&mut self, now: Instant) -> usize {
+        let mut evicted = 0;
+        for index in 0..N {
+            let slot = &self.slots[index];
+            if slot.origin.is_some() && now >= slot.last_used + slot.idle {
+                self.release(index);
+                evicted += 1;
+            }
+        }
+        self.stats.idle_evictions += evicted as u32;
+        evicted
+    }
+
+    /// 关闭全部缓存的连接
+    pub fn close_all(&mut self) {
+        for index in 0..N {
+            self.release(index);
+        }
+    }
+
+    /// 当前缓存的连接数
+    pub fn open_connections(&self) -> usize {
+        self.slots.iter().filter(|s| s.origin.is_some()).count()
+    }
+
+    /// 统计
+    pub fn stats(&self) -> ClientStats {
+        self.stats
+    }
+
+    /// 清零统计
+    pub fn reset_stats(&mut self) {
+        self.stats = ClientStats::default();
+    }
+}
+
+/// 发出请求后读取一个响应
+async fn exchange<C: Read + Write>(
+    io: &mut C,
+    method: Method,
+    body: &mut [u8],
+    config: &ClientConfig,
+) -> Result<Outcome, Failure> {
+    flush(io, config.timeout).await.map_err(|error| Failure { error, stale: true })?;
+    let mut reader = Reader::new(io, config.timeout);
+    let result = async {
+        let head = reader.head(method).await?;
+        let len = reader.body(head.body, body).await?;
+        Ok(Outcome { status: head.status, headers: head.headers, len, keep_alive: head.keep_alive, idle: head.idle })
+    }
+    .await;
+    result.map_err(|error| Failure { error, stale: reader.received == 0 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embassy_futures::block_on;
+    use heapless::Vec;
+
+    /// 脚本化的服务器: 每收到一个请求 (flush 时) 投放下一条脚本响应，空响应表示对端已关闭
+    struct Scripted {
+        script: &'static [&'static [u8]],
+        next: usize,
+        pending: usize,
+        inbox: Vec<u8, 512>,
+        opens: u32,
+    }
+
+    impl Scripted {
+        fn new(script: &'static [&'static [u8]]) -> Self {
+            Self { script, next: 0, pending: 0, inbox: Vec::new(), opens: 0 }
+        }
+    }
+
+    impl ErrorType for Scripted {
+        type Error = core::convert::Infallible;
+    }
+
+    impl Read for Scripted {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            let n = buf.len().min(self.inbox.len()).min(7);
+            buf[..n].copy_from_slice(&self.inbox[..n]);
+            self.inbox = self.inbox[n..].iter().copied().collect();
+            Ok(n)
+        }
+    }
+
+    impl Write for Scripted {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            if buf.ends_with(b" HTTP/1.1\r\n") {
+                self.pending += 1;
+            }
+            Ok(buf.len())
+        }
+
+        async fn flush(&mut self) -> Result<(), Self::Error> {
+            for _ in 0..core::mem::take(&mut self.pending) {
+                let _ = self.inbox.extend_from_slice(self.script[self.next]);
+                self.next += 1;
+            }
+            Ok(())
+        }
+    }
+
+    impl Transport for Scripted {
+        async fn open(&mut self, _host: &str, _port: u16) -> Result<(), HttpError> {
+            self.opens += 1;
+            Ok(())
+        }
+
+        fn close(&mut self) {
+            self.inbox.clear();
+        }
+    }
+
+    #[test]
+    fn test_reuse_chunked_and_pipeline() {
+        static SCRIPT: &[&[u8]] = &[
+            b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok",
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n6;x=1\r\n world\r\n0\r\n\r\n",
+            b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nContent-Length: 3\r\n\r\nabc",
+        ];
+        let mut client = HttpClient::new(ClientConfig::new(), [Scripted::new(SCRIPT)]);
+        let mut body = [0u8; 64];
+
+        let resp = block_on(client.get("api.local", 8080, "/a", &mut body)).unwrap();
+        assert_eq!((resp.status, resp.body), (200, &b"ok"[..]));
+        let resp = block_on(client.get("API.local", 8080, "/b", &mut body)).unwrap();
+        assert_eq!(resp.body, b"hello world");
+
+        let mut seen = [0u16; 2];
+        let done = block_on(client.pipeline("api.local", 8080, &["/c", "/d"], &mut body, |i, r| seen[i] = r.status));
+        assert_eq!((done, seen), (Ok(2), [404, 200]));
+
+        let stats = client.stats();
+        assert_eq!((stats.requests, stats.connects, stats.reused), (4, 1, 2));
+        assert_eq!(client.slots[0].conn.opens, 1);
+    }
+
+    #[test]
+    fn test_stale_retry_close_and_idle_eviction() {
+        static SCRIPT: &[&[u8]] = &[
+            b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n",
+            b"",
+            b"HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Length: 1\r\n\r\nx",
+            b"HTTP/1.1 200 OK\r\nKeep-Alive: timeout=5, max=10\r\nContent-Length: 0\r\n\r\n",
+        ];
+        let mut client = HttpClient::new(ClientConfig::new(), [Scripted::new(SCRIPT)]);
+        let mut body = [0u8; 16];
+
+        block_on(client.get("api.local", 80, "/1", &mut body)).unwrap();
+        assert_eq!(block_on(client.get("api.local", 80, "/2", &mut body)).unwrap().body, b"x");
+        assert_eq!(client.open_connections(), 0);
+        block_on(client.get("api.local", 80, "/3", &mut body)).unwrap();
+
+        let stats = client.stats();
+        assert_eq!((stats.connects, stats.reused, stats.stale_retries, stats.failures), (3, 1, 1, 0));
+        assert_eq!(client.evict_idle_at(Instant::now() + Duration::from_secs(3)), 0);
+        assert_eq!(client.evict_idle_at(Instant::now() + Duration::from_secs(5)), 1);
+        assert_eq!(client.stats().idle_evictions, 1);
+    }
+
+    #[test]
+    fn test_body_limits() {
+        static EXACT: &[&[u8]] = &[b"HTTP/1.1 200 OK\r\nConnection: close\r\n\r\nhello"];
+        static LONGER: &[&[u8]] = &[b"HTTP/1.1 200 OK\r\nConnection: close\r\n\r\nhello!"];
+        // 第二个块的长度为 usize::MAX (64 位)，累加长度溢出
+        static HUGE_CHUNK: &[&[u8]] =
+            &[b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n1\r\na\r\nffffffffffffffff\r\n"];
+        let mut body = [0u8; 5];
+
+        let mut client = HttpClient::new(ClientConfig::new(), [Scripted::new(EXACT)]);
+        assert_eq!(block_on(client.get("api.local", 80, "/", &mut body)).unwrap().body, b"hello");
+        let mut client = HttpClient::new(ClientConfig::new(), [Scripted::new(LONGER)]);
+        assert_eq!(block_on(client.get("api.local", 80, "/", &mut body)).err(), Some(HttpError::PayloadTooLarge));
+        let mut client = HttpClient::new(ClientConfig::new(), [Scripted::new(HUGE_CHUNK)]);
+        assert_eq!(block_on(client.get("api.local", 80, "/", &mut body)).err(), Some(HttpError::PayloadTooLarge));
+    }
+
+    #[test]
+    fn test_headers_and_redirects() {
+        static SCRIPT: &[&[u8]] = &[
+            b"HTTP/1.1 302 Found\r\nLocation: /v2/state?x=1\r\nContent-Length: 0\r\n\r\n",
+            b"HTTP/1.1 301 Moved\r\nLocation: http://mirror.local:8081/state\r\nContent-Length: 0\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nX-Request-Id: 7\r\nContent-Length: 2\r\n\r\nok",
+        ];
+        let mut client = HttpClient::new(ClientConfig::new(), [Scripted::new(SCRIPT)]);
+        let mut body = [0u8; 16];
+
+        let resp = block_on(client.get_url("http://api.local/v1/state", &mut body)).unwrap();
+        assert_eq!((resp.status, resp.text()), (200, Some("ok")));
+        assert_eq!(resp.headers.content_type(), Some("text/plain"));
+        assert_eq!(resp.headers.get("x-request-id"), Some("7"));
+        assert_eq!(resp.headers.len(), 3);
+        let stats = client.stats();
+        assert_eq!((stats.requests, stats.redirects, stats.connects), (3, 2, 2));
+
+        let url = Url::parse("https://example.com:8443/a?q#frag").unwrap();
+        assert_eq!((url.https, url.host, url.port, url.path), (true, "example.com", 8443, "/a?q"));
+        assert_eq!(Url::parse("HTTP://example.com").map(|u| (u.port, u.path)), Ok((80, "/")));
+        assert_eq!(Url::parse("ftp://example.com"), Err(HttpError::BadRequest));
+
+        // POST 经 303 改为 GET，跨源时丢弃头部
+        let next = Target::resolve("api.local", 80, "/v1/items?a=1", Method::Post, 303, "done").unwrap();
+        assert_eq!((next.path.as_str(), next.method, next.keep_body, next.keep_headers), ("/v1/done", Method::Get, false, true));
+        let next = Target::resolve("api.local", 443, "/", Method::Put, 307, "//cdn.local/blob").unwrap();
+        assert_eq!((next.host.as_str(), next.port, next.method, next.keep_body), ("cdn.local", 443, Method::Put, true));
+        assert!(!next.keep_headers);
+    }
+}
@@ -0,0 +1,809 @@
+//! 嵌入式 HTTP/1.1 服务器
+//!
+//! 面向设备管理面的小型 HTTP 服务器，特性：
+//! - 零分配请求解析 (请求行与头部直接引用接收缓冲区)
+//! - 请求体流式读取，内存占用与上传大小无关
+//! - Keep-Alive、空闲超时与请求体大小限制
+//! - multipart/form-data 文件上传直接写入 `fs::File`
+//! - 分块传输编码与 Server-Sent Events 实时推送
+//! - JSON REST 辅助与标准管理接口 (系统状态、配置读写、文件列表)
+//! - 令牌 / Basic 认证 (`auth`，失败锁定与审计事件)
+//! - 请求限速 (`RateLimited`，基于 `util::ratelimit`)
+//! - HTTPS: 基于 `net::tls` 的 TLS 1.3 监听 (feature `tls`)
+//! - HTTP 客户端 (`client`，按 `host:port` 缓存 Keep-Alive 连接、管线化 GET、跟随重定向)
+//!
+//! 会话处理基于 `embedded-io-async`，可直接用于 `embassy_net::tcp::TcpSocket`。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::http::{self, Exchange, Handler, HttpConfig, HttpError, Method, Request, Status};
+//!
+//! struct Api;
+//!
+//! impl Handler for Api {
+//!     async fn handle<C: Read + Write>(&self, req: &Request<'_>, ex: &mut Exchange<'_, C>) -> Result<(), HttpError> {
+//!         match (req.method(), req.path()) {
+//!             (Method::Get, "/") => ex.respond(Status::Ok, "text/html", INDEX_HTML).await,
+//!             _ => ex.respond(Status::NotFound, "text/plain", b"not found").await,
+//!         }
+//!     }
+//! }
+//!
+//! http::listen::<2, _>(stack, &HttpConfig::default(), &Api).await;
+//! ```
+
+pub mod auth;
+pub mod client;
+pub mod multipart;
+pub mod rest;
+pub mod sse;
+
+use core::fmt::{self, Write as _};
+
+use embassy_time::{with_timeout, Duration};
+use embedded_io_async::{Read, Write};
+use heapless::{String, Vec};
+
+use crate::fs::littlefs::FsError;
+use crate::net::tcp::NetworkError;
+use crate::util::ratelimit::RateLimit;
+
+pub use auth::Protected;
+pub use client::{ClientConfig, ClientRequest, ClientStats, HttpClient, Response, ResponseHeaders, TcpTransport, Transport, Url};
+pub use multipart::{Multipart, Part, UploadLimits, UploadProgress};
+pub use rest::{match_route, ConfigError, ConfigStore, ManagementApi, NoConfig, PathParams};
+pub use sse::{SseHub, SseMessage, SseStats};
+
+/// 请求头缓冲区大小 (请求行 + 全部头部)
+pub const MAX_HEAD_LEN: usize = 1024;
+
+/// 最多解析的头部数量
+pub const MAX_HEADERS: usize = 16;
+
+/// HTTP 默认端口
+pub const HTTP_PORT: u16 = 80;
+
+/// HTTPS 默认端口
+pub const HTTPS_PORT: u16 = 443;
+
+// ===== 错误类型 =====
+
+/// HTTP 错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpError {
+    /// 请求格式错误
+    BadRequest,
+    /// 不支持的方法
+    UnsupportedMethod,
+    /// 请求头过大
+    HeaderTooLarge,
+    /// 请求体超出限制
+    PayloadTooLarge,
+    /// 读写超时
+    Timeout,
+    /// 连接 I/O 错误
+    Io,
+    /// 文件系统错误
+    Fs(FsError),
+    /// 客户端连接失败
+    Connect(NetworkError),
+    /// 服务器响应格式错误
+    BadResponse,
+}
+
+impl HttpError {
+    /// 对应的响应状态码
+    pub fn status(&self) -> Status {
+        match self {
+            Self::BadRequest => Status::BadRequest,
+            Self::UnsupportedMethod => Status::MethodNotAllowed,
+            Self::HeaderTooLarge => Status::HeaderFieldsTooLarge,
+            Self::PayloadTooLarge => Status::PayloadTooLarge,
+            Self::Timeout => Status::RequestTimeout,
+            Self::Io | Self::Fs(_) => Status::InternalServerError,
+            Self::Connect(_) | Self::BadResponse => Status::ServiceUnavailable,
+        }
+    }
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadRequest => write!(f, "Bad request"),
+            Self::UnsupportedMethod => write!(f, "Unsupported method"),
+            Self::HeaderTooLarge => write!(f, "Request header too large"),
+            Self::PayloadTooLarge => write!(f, "Payload too large"),
+            Self::Timeout => write!(f, "Timeout"),
+            Self::Io => write!(f, "Connection I/O error"),
+            Self::Fs(e) => write!(f, "Filesystem error: {:?}", e),
+            Self::Connect(e) => write!(f, "Connect failed: {}", e),
+            Self::BadResponse => write!(f, "Malformed response"),
+        }
+    }
+}
+
+impl From<FsError> for HttpError {
+    fn from(e: FsError) -> Self {
+        HttpError::Fs(e)
+    }
+}
+
+// ===== 方法与状态码 =====
+
+/// 请求方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Head,
+    Post,
+    Put,
+    Patch,
+    Delete,
+    Options,
+}
+
+impl Method {
+    /// 解析方法名
+    pub fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "GET" => Method::Get,
+            "HEAD" => Method::Head,
+            "POST" => Method::Post,
+            "PUT" => Method::Put,
+            "PATCH" => Method::Patch,
+            "DELETE" => Method::Delete,
+            "OPTIONS" => Method::Options,
+            _ => return None,
+        })
+    }
+
+    /// 方法名
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Method::Get => "GET",
+            Method::Head => "HEAD",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Patch => "PATCH",
+            Method::Delete => "DELETE",
+            Method::Options => "OPTIONS",
+        }
+    }
+}
+
+/// 响应状态码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    Created,
+    NoContent,
+    Found,
+    BadRequest,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    MethodNotAllowed,
+    RequestTimeout,
+    PayloadTooLarge,
+    TooManyRequests,
+    HeaderFieldsTooLarge,
+    InternalServerError,
+    ServiceUnavailable,
+}
+
+impl Status {
+    /// 状态码
+    pub const fn code(self) -> u16 {
+        match self {
+            Status::Ok => 200,
+            Status::Created => 201,
+            Status::NoContent => 204,
+            Status::Found => 302,
+            Status::BadRequest => 400,
+            Status::Unauthorized => 401,
+            Status::Forbidden => 403,
+            Status::NotFound => 404,
+            Status::MethodNotAllowed => 405,
+            Status::RequestTimeout => 408,
+            Status::PayloadTooLarge => 413,
+            Status::TooManyRequests => 429,
+            Status::HeaderFieldsTooLarge => 431,
+            Status::InternalServerError => 500,
+            Status::ServiceUnavailable => 503,
+        }
+    }
+
+    /// 原因短语
+    pub const fn reason(self) -> &'static str {
+        match self {
+            Status::Ok => "OK",
+            Status::Created => "Created",
+            Status::NoContent => "No Content",
+            Status::Found => "Found",
+            Status::BadRequest => "Bad Request",
+            Status::Unauthorized => "Unauthorized",
+            Status::Forbidden => "Forbidden",
+            Status::NotFound => "Not Found",
+            Status::MethodNotAllowed => "Method Not Allowed",
+            Status::RequestTimeout => "Request Timeout",
+            Status::PayloadTooLarge => "Payload Too Large",
+            Status::TooManyRequests => "Too Many Requests",
+            Status::HeaderFieldsTooLarge => "Request Header Fields Too Large",
+            Status::InternalServerError => "Internal Server Error",
+            Status::ServiceUnavailable => "Service Unavailable",
+        }
+    }
+}
+
+// ===== 请求 =====
+
+/// 查找头部结束位置 (返回 `\r\n\r\n` 之后的偏移)
+pub fn find_head_end(buf: &[u8]) -> Option<usize> {
+    buf.windows(4).position(|w| w == b"\r\n\r\n").map(|p| p + 4)
+}
+
+/// 已解析的请求 (引用接收缓冲区)
+#[derive(Debug)]
+pub struct Request<'b> {
+    method: Method,
+    path: &'b str,
+    query: Option<&'b str>,
+    headers: Vec<(&'b str, &'b str), MAX_HEADERS>,
+    content_length: usize,
+    keep_alive: bool,
+}
+
+impl<'b> Request<'b> {
+    /// 解析请求行与头部 (`head` 须包含结尾的空行)
+    pub fn parse(head: &'b [u8]) -> Result<Self, HttpError> {
+        let text = core::str::from_utf8(head).map_err(|_| HttpError::BadRequest)?;
+        let mut lines = text.split("\r\n");
+
+        let mut parts = lines.next().ok_or(HttpError::BadRequest)?.split(' ');
+        let method = parts.next().ok_or(HttpError::BadRequest)?;
+        let target = parts.next().ok_or(HttpError::BadRequest)?;
+        let version = parts.next().ok_or(HttpError::BadRequest)?;
+        let method = Method::parse(method).ok_or(HttpError::UnsupportedMethod)?;
+        if !version.starts_with("HTTP/1.") || !target.starts_with('/') {
+            return Err(HttpError::BadRequest);
+        }
+        let (path, query) = match target.split_once('?') {
+            Some((p, q)) => (p, Some(q)),
+            None => (target, None),
+        };
+
+        let mut request = Self {
+            method,
+            path,
+            query,
+            headers: Vec::new(),
+            content_length: 0,
+            keep_alive: version == "HTTP/1.1",
+        };
+
+        for line in lines.take_while(|l| !l.is_empty()) {
+            let (name, value) = line.split_once(':').ok_or(HttpError::BadRequest)?;
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                request.content_length = value.parse().map_err(|_| HttpError::BadRequest)?;
+            } else if name.eq_ignore_ascii_case("connection") {
+                request.keep_alive = !value.eq_ignore_ascii_case("close");
+            } else if name.eq_ignore_ascii_case("transfer-encoding") {
+                // 不支持分块请求体
+                return Err(HttpError::BadRequest);
+            }
+            request.headers.push((name, value)).map_err(|_| HttpError::HeaderTooLarge)?;
+        }
+        Ok(request)
+    }
+
+    /// 请求方法
+    pub fn method(&self) -> Method {
+        self.method
+    }
+
+    /// 路径 (不含查询串)
+    pub fn path(&self) -> &'b str {
+        self.path
+    }
+
+    /// 原始查询串
+    pub fn query(&self) -> Option<&'b str> {
+        self.query
+    }
+
+    /// 查询参数 (不做百分号解码)
+    pub fn query_param(&self, name: &str) -> Option<&'b str> {
+        self.query?
+            .split('&')
+            .filter_map(|kv| kv.split_once('=').or(Some((kv, ""))))
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| v)
+    }
+
+    /// 头部值 (名称不区分大小写)
+    pub fn header(&self, name: &str) -> Option<&'b str> {
+        self.headers.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| *v)
+    }
+
+    /// Content-Type
+    pub fn content_type(&self) -> Option<&'b str> {
+        self.header("content-type")
+    }
+
+    /// 请求体长度
+    pub fn content_length(&self) -> usize {
+        self.content_length
+    }
+
+    /// 是否保持连接
+    pub fn keep_alive(&self) -> bool {
+        self.keep_alive
+    }
+}
+
+// ===== 请求/响应交换 =====
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResponseState {
+    Idle,
+    Body { remaining: usize },
+    Chunked,
+    Done,
+}
+
+/// 一次请求/响应交换
+///
+/// 请求体按需从连接读取；响应头写出后再写入正文。
+pub struct Exchange<'a, C> {
+    io: &'a mut C,
+    pending: &'a [u8],
+    body_len: usize,
+    body_read: usize,
+    timeout: Duration,
+    response: ResponseState,
+}
+
+impl<'a, C: Read + Write> Exchange<'a, C> {
+    /// 创建 (`pending` 为头部之后已收到的数据)
+    pub fn new(io: &'a mut C, pending: &'a [u8], body_len: usize, timeout: Duration) -> Self {
+        let pending = &pending[..pending.len().min(body_len)];
+        Self { io, pending, body_len, body_read: 0, timeout, response: ResponseState::Idle }
+    }
+
+    // ----- 请求体 -----
+
+    /// 请求体总长度
+    pub fn body_len(&self) -> usize {
+        self.body_len
+    }
+
+    /// 已读取的请求体字节数
+    pub fn body_read(&self) -> usize {
+        self.body_read
+    }
+
+    /// 读取请求体，结束时返回 0
+    pub async fn read_body(&mut self, buf: &mut [u8]) -> Result<usize, HttpError> {
+        let want = buf.len().min(self.body_len - self.body_read);
+        if want == 0 {
+            return Ok(0);
+        }
+        let n = if !self.pending.is_empty() {
+            let n = want.min(self.pending.len());
+            buf[..n].copy_from_slice(&self.pending[..n]);
+            self.pending = &self.pending[n..];
+            n
+        } else {
+            let n = with_timeout(self.timeout, self.io.read(&mut buf[..want]))
+                .await
+                .map_err(|_| HttpError::Timeout)?
+                .map_err(|_| HttpError::Io)?;
+            if n == 0 {
+                return Err(HttpError::Io);
+            }
+            n
+        };
+        self.body_read += n;
+        Ok(n)
+    }
+
+    /// 读取完整请求体到 `buf`
+    pub async fn read_body_to<'b>(&mut self, buf: &'b mut [u8]) -> Result<&'b [u8], HttpError> {
+        if self.body_len - self.body_read > buf.len() {
+            return Err(HttpError::PayloadTooLarge);
+        }
+        let mut len = 0;
+        loop {
+            let n = self.read_body(&mut buf[len..]).await?;
+            if n == 0 {
+                return Ok(&buf[..len]);
+            }
+            len += n;
+        }
+    }
+
+    /// 丢弃剩余请求体
+    pub async fn discard_body(&mut self) -> Result<(), HttpError> {
+        let mut scratch = [0u8; 128];
+        while self.read_body(&mut scratch).await? > 0 {}
+        Ok(())
+    }
+
+    // ----- 响应 -----
+
+    /// 写出响应头 (含 Content-Length)
+    pub async fn start(
+        &mut self,
+        status: Status,
+        headers: &[(&str, &str)],
+        content_length: usize,
+    ) -> Result<(), HttpError> {
+        if self.response != ResponseState::Idle {
+            return Err(HttpError::Io);
+        }
+        let mut line: String<64> = String::new();
+        let _ = write!(line, "Content-Length: {}\r\n", content_length);
+        self.write_head(status, headers, &line).await?;
+        self.response = if content_length == 0 {
+            ResponseState::Done
+        } else {
+            ResponseState::Body { remaining: content_length }
+        };
+        Ok(())
+    }
+
+    /// 写出分块编码的响应头，正文长度不定 (以 `finish` 结束)
+    pub async fn start_chunked(&mut self, status: Status, headers: &[(&str, &str)]) -> Result<(), HttpError> {
+        if self.response != ResponseState::Idle {
+            return Err(HttpError::Io);
+        }
+        self.write_head(status, headers, "Transfer-Encoding: chunked\r\n").await?;
+        self.response = ResponseState::Chunked;
+        Ok(())
+    }
+
+    /// 写出响应正文
+    ///
+    /// 定长响应的总长度不得超过 `start` 声明的长度；分块响应每次调用输出一个分块。
+    pub async fn write(&mut self, data: &[u8]) -> Result<(), HttpError> {
+        let remaining = match self.response {
+            ResponseState::Body { remaining } => remaining,
+            ResponseState::Chunked => {
+                if data.is_empty() {
+                    // 空分块表示结束，只能由 finish 写出
+                    return Ok(());
+                }
+                let mut size: String<12> = String::new();
+                let _ = write!(size, "{:X}\r\n", data.len());
+                self.write_raw(size.as_bytes()).await?;
+                self.write_raw(data).await?;
+                return self.write_raw(b"\r\n").await;
+            }
+            _ => return Err(HttpError::Io),
+        };
+        if data.len() > remaining {
+            return Err(HttpError::Io);
+        }
+        self.write_raw(data).await?;
+        let remaining = remaining - data.len();
+        self.response = if remaining == 0 { ResponseState::Done } else { ResponseState::Body { remaining } };
+        Ok(())
+    }
+
+    /// 发送完整响应
+    pub async fn respond(&mut self, status: Status, content_type: &str, body: &[u8]) -> Result<(), HttpError> {
+        self.start(status, &[("Content-Type", content_type)], body.len()).await?;
+        if !body.is_empty() {
+            self.write(body).await?;
+        }
+        Ok(())
+    }
+
+    /// 结束分块响应
+    pub async fn finish(&mut self) -> Result<(), HttpError> {
+        if self.response == ResponseState::Chunked {
+            self.write_raw(b"0\r\n\r\n").await?;
+            self.response = ResponseState::Done;
+        }
+        self.flush().await
+    }
+
+    /// 是否已开始响应
+    pub fn response_started(&self) -> bool {
+        self.response != ResponseState::Idle
+    }
+
+    /// 响应是否完整
+    pub fn response_complete(&self) -> bool {
+        self.response == ResponseState::Done
+    }
+
+    async fn write_head(&mut self, status: Status, headers: &[(&str, &str)], extra: &str) -> Result<(), HttpError> {
+        let mut line: String<64> = String::new();
+        let _ = write!(line, "HTTP/1.1 {} {}\r\n", status.code(), status.reason());
+        self.write_raw(line.as_bytes()).await?;
+        for (name, value) in headers {
+            self.write_raw(name.as_bytes()).await?;
+            self.write_raw(b": ").await?;
+            self.write_raw(value.as_bytes()).await?;
+            self.write_raw(b"\r\n").await?;
+        }
+        self.write_raw(extra.as_bytes()).await?;
+        self.write_raw(b"\r\n").await
+    }
+
+    async fn write_raw(&mut self, data: &[u8]) -> Result<(), HttpError> {
+        with_timeout(self.timeout, self.io.write_all(data))
+            .await
+            .map_err(|_| HttpError::Timeout)?
+            .map_err(|_| HttpError::Io)
+    }
+
+    /// 立即发送已缓冲的响应数据 (流式响应在每个事件后调用)
+    pub async fn flush(&mut self) -> Result<(), HttpError> {
+        with_timeout(self.timeout, self.io.flush())
+            .await
+            .map_err(|_| HttpError::Timeout)?
+            .map_err(|_| HttpError::Io)
+    }
+}
+
+// ===== 处理器与服务器 =====
+
+/// 请求处理器
+#[allow(async_fn_in_trait)]
+pub trait Handler {
+    /// 处理一个请求
+    ///
+    /// 返回错误且尚未写出响应时，服务器以错误对应的状态码回复。
+    async fn handle<C: Read + Write>(&self, req: &Request<'_>, ex: &mut Exchange<'_, C>) -> Result<(), HttpError>;
+}
+
+/// 限速处理器包装
+///
+/// 每个请求消耗一个许可，超限时回复 429 (带 `Retry-After`) 而不调用内部处理器。
+pub struct RateLimited<'a, L, H> {
+    limiter: &'a L,
+    inner: H,
+}
+
+impl<'a, L: RateLimit, H: Handler> RateLimited<'a, L, H> {
+    /// 包装处理器
+    pub fn new(limiter: &'a L, inner: H) -> Self {
+        Self { limiter, inner }
+    }
+}
+
+impl<L: RateLimit, H: Handler> Handler for RateLimited<'_, L, H> {
+    async fn handle<C: Read + Write>(&self, req: &Request<'_>, ex: &mut Exchange<'_, C>) -> Result<(), HttpError> {
+        let Err(wait) = self.limiter.try_acquire(1) else {
+            return self.inner.handle(req, ex).await;
+        };
+        let mut retry: String<12> = String::new();
+        let _ = write!(retry, "{}", wait.as_millis().div_ceil(1000).max(1));
+        let body = Status::TooManyRequests.reason().as_bytes();
+        ex.start(Status::TooManyRequests, &[("Content-Type", "text/plain"), ("Retry-After", &retry)], body.len()).await?;
+        ex.write(body).await
+    }
+}
+
+/// 服务器配置
+#[derive(Debug, Clone, Copy)]
+pub struct HttpConfig {
+    /// 监听端口
+    pub port: u16,
+    /// 空闲 / 读写超时
+    pub timeout: Duration,
+    /// 请求体上限
+    pub max_body: usize,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self { port: HTTP_PORT, timeout: Duration::from_secs(10), max_body: 64 * 1024 }
+    }
+}
+
+impl HttpConfig {
+    /// 设置端口
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// 设置超时
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 设置请求体上限
+    pub fn with_max_body(mut self, max_body: usize) -> Self {
+        self.max_body = max_body;
+        self
+    }
+}
+
+/// 处理一个连接上的全部请求
+///
+/// 对端关闭或请求 `Connection: close` 时返回 `Ok(())`。
+/// 不支持管线化: 请求体之后多余的数据会被丢弃。
+pub async fn serve_connection<C, H>(conn: &mut C, handler: &H, config: &HttpConfig) -> Result<(), HttpError>
+where
+    C: Read + Write,
+    H: Handler,
+{
+    let mut buf = [0u8; MAX_HEAD_LEN];
+
+    loop {
+        let mut filled = 0;
+        let head_end = loop {
+            if let Some(end) = find_head_end(&buf[..filled]) {
+                break end;
+            }
+            if filled == buf.len() {
+                let mut ex = Exchange::new(conn, &[], 0, config.timeout);
+                return reject(&mut ex, HttpError::HeaderTooLarge).await;
+            }
+            let n = match with_timeout(config.timeout, conn.read(&mut buf[filled..])).await {
+                Ok(Ok(n)) => n,
+                // 空闲连接超时视为正常关闭
+                Err(_) if filled == 0 => return Ok(()),
+                Err(_) => return Err(HttpError::Timeout),
+                Ok(Err(_)) => return Err(HttpError::Io),
+            };
+            if n == 0 {
+                return if filled == 0 { Ok(()) } else { Err(HttpError::Io) };
+            }
+            filled += n;
+        };
+
+        let (head, rest) = buf[..filled].split_at(head_end);
+        let request = match Request::parse(head) {
+            Ok(r) => r,
+            Err(e) => {
+                let mut ex = Exchange::new(conn, &[], 0, config.timeout);
+                return reject(&mut ex, e).await;
+            }
+        };
+        let mut ex = Exchange::new(conn, rest, request.content_length(), config.timeout);
+        if request.content_length() > config.max_body {
+            return reject(&mut ex, HttpError::PayloadTooLarge).await;
+        }
+
+        if let Err(e) = handler.handle(&request, &mut ex).await {
+            if ex.response_started() {
+                return Err(e);
+            }
+            return reject(&mut ex, e).await;
+        }
+        if !ex.response_complete() {
+            return Err(HttpError::Io);
+        }
+        ex.discard_body().await?;
+        ex.flush().await?;
+
+        if !request.keep_alive() {
+            return Ok(());
+        }
+    }
+}
+
+/// 以错误状态码回复并结束连接
+async fn reject<C: Read + Write>(ex: &mut Exchange<'_, C>, error: HttpError) -> Result<(), HttpError> {
+    let status = error.status();
+    ex.start(status, &[("Content-Type", "text/plain"), ("Connection", "close")], status.reason().len())
+        .await?;
+    ex.write(status.reason().as_bytes()).await?;
+    ex.flush().await?;
+    Err(error)
+}
+
+/// 多连接 HTTP 服务器 (永不返回)
+///
+/// 并发运行 `N` 个 accept 循环，每个循环独占一个 socket，最多同时服务 `N` 个客户端。
+pub async fn listen<const N: usize, H: Handler>(stack: embassy_net::Stack<'_>, config: &HttpConfig, handler: &H) -> ! {
+    use crate::util::log::*;
+
+    async fn accept_loop<H: Handler>(stack: embassy_net::Stack<'_>, config: &HttpConfig, handler: &H) -> ! {
+        let mut rx = [0u8; 1024];
+        let mut tx = [0u8; 1024];
+        loop {
+            let mut socket = embassy_net::tcp::TcpSocket::new(stack, &mut rx, &mut tx);
+            socket.set_timeout(Some(config.timeout));
+            if socket.accept(config.port).await.is_err() {
+                continue;
+            }
+            if let Err(e) = serve_connection(&mut socket, handler, config).await {
+                log_debug!("HTTP connection closed: {:?}", e);
+            }
+            socket.close();
+            let _ = socket.flush().await;
+        }
+    }
+
+    log_info!("HTTP server listening on port {} ({} connections)", config.port, N);
+    let loops: [_; N] = core::array::from_fn(|_| accept_loop(stack, config, handler));
+    embassy_futures::join::join_array(loops).await;
+    unreachable!()
+}
+
+/// 多连接 HTTPS 服务器 (永不返回)
+///
+/// 与 `listen` 相同，但每个连接先完成 TLS 握手 (超时取 `config.timeout`)。
+/// 每个 accept 循环额外占用约 `tls::RX_BUFFER_LEN + tls::TX_BUFFER_LEN` 字节的记录缓冲区。
+#[cfg(feature = "tls")]
+pub async fn listen_tls<const N: usize, H: Handler>(
+    stack: embassy_net::Stack<'_>,
+    config: &HttpConfig,
+    identity: &crate::net::tls::TlsIdentity<'_>,
+    handler: &H,
+) -> ! {
+    use crate::net::tls;
+    use crate::util::log::*;
+
+    async fn accept_loop<H: Handler>(
+        stack: embassy_net::Stack<'_>,
+        config: &HttpConfig,
+        identity: &tls::TlsIdentity<'_>,
+        handler: &H,
+    ) -> ! {
+        let mut rx = [0u8; 1024];
+        let mut tx = [0u8; 1024];
+        let mut record_rx = [0u8; tls::RX_BUFFER_LEN];
+        let mut record_tx = [0u8; tls::TX_BUFFER_LEN];
+        let mut rng = esp_hal::rng::Rng::new();
+        loop {
+            let mut socket = embassy_net::tcp::TcpSocket::new(stack, &mut rx, &mut tx);
+            socket.set_timeout(Some(config.timeout));
+            if socket.accept(config.port).await.is_err() {
+                continue;
+            }
+            let handshake = tls::accept(&mut socket, identity, &mut rng, &mut record_rx, &mut record_tx);
+            match with_timeout(config.timeout, handshake).await {
+                Ok(Ok(mut stream)) => {
+                    if let Err(e) = serve_connection(&mut stream, handler, config).await {
+                        log_debug!("HTTPS connection closed: {:?}", e);
+                    }
+                    let _ = stream.close().await;
+                }
+                Ok(Err(e)) => log_debug!("TLS handshake failed: {:?}", e),
+                Err(_) => log_debug!("TLS handshake timed out"),
+            }
+            socket.close();
+            let _ = socket.flush().await;
+        }
+    }
+
+    log_info!("HTTPS server listening on port {} ({} connections)", config.port, N);
+    let loops: [_; N] = core::array::from_fn(|_| accept_loop(stack, config, identity, handler));
+    embassy_futures::join::join_array(loops).await;
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request() {
+        let head = b"POST /upload?dir=/www&x HTTP/1.1\r\nHost: dev\r\nContent-Length: 42\r\nConnection: close\r\n\r\n";
+        assert_eq!(find_head_end(head), Some(head.len()));
+        let req = Request::parse(head).unwrap();
+        assert_eq!(req.method(), Method::Post);
+        assert_eq!(req.path(), "/upload");
+        assert_eq!(req.query_param("dir"), Some("/www"));
+        assert_eq!(req.query_param("x"), Some(""));
+        assert_eq!(req.header("HOST"), Some("dev"));
+        assert_eq!(req.content_length(), 42);
+        assert!(!req.keep_alive());
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_request_line() {
+        assert_eq!(Request::parse(b"FOO / HTTP/1.1\r\n\r\n").unwrap_err(), HttpError::UnsupportedMethod);
+        assert_eq!(Request::parse(b"GET nope HTTP/1.1\r\n\r\n").unwrap_err(), HttpError::BadRequest);
+    }
+}
@@ -0,0 +1,382 @@
+//! multipart/form-data 流式解析
+//!
+//! 在固定大小的窗口内搜索分隔符，逐块把各部分内容交给调用方，
+//! 因此上传文件的大小不受 RAM 限制。文件部分可直接写入 `fs::File`，
+//! 并按块上报进度。
+
+use embedded_io_async::{Read, Write};
+use heapless::{String, Vec};
+
+use super::{Exchange, HttpError, Request};
+use crate::fs::littlefs::{File, FileSystem, FsError};
+
+/// 解析窗口大小 (须能容纳一个部分的全部头部)
+const WINDOW_LEN: usize = 512;
+
+/// 边界最大长度 (RFC 2046)
+const MAX_BOUNDARY_LEN: usize = 70;
+
+/// 上传文件路径最大长度
+const MAX_UPLOAD_PATH: usize = 128;
+
+/// 从 Content-Type 中提取边界
+pub fn boundary(content_type: &str) -> Option<&str> {
+    let mut params = content_type.split(';');
+    if !params.next()?.trim().eq_ignore_ascii_case("multipart/form-data") {
+        return None;
+    }
+    params
+        .filter_map(|p| p.trim().split_once('='))
+        .find(|(k, _)| k.eq_ignore_ascii_case("boundary"))
+        .map(|(_, v)| v.trim_matches('"'))
+        .filter(|b| !b.is_empty() && b.len() <= MAX_BOUNDARY_LEN)
+}
+
+/// 表单部分
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Part {
+    /// 字段名
+    pub name: String<32>,
+    /// 文件名 (文件字段)
+    pub filename: Option<String<64>>,
+    /// 内容类型
+    pub content_type: Option<String<64>>,
+}
+
+impl Part {
+    /// 解析部分头部
+    fn parse(headers: &str) -> Result<Self, HttpError> {
+        let mut part = Part::default();
+        for line in headers.split("\r\n").filter(|l| !l.is_empty()) {
+            let (name, value) = line.split_once(':').ok_or(HttpError::BadRequest)?;
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-disposition") {
+                for param in value.split(';').skip(1) {
+                    let Some((k, v)) = param.trim().split_once('=') else { continue };
+                    let v = v.trim_matches('"');
+                    if k.eq_ignore_ascii_case("name") {
+                        part.name = String::try_from(v).map_err(|_| HttpError::BadRequest)?;
+                    } else if k.eq_ignore_ascii_case("filename") {
+                        part.filename = Some(String::try_from(v).map_err(|_| HttpError::BadRequest)?);
+                    }
+                }
+            } else if name.eq_ignore_ascii_case("content-type") {
+                part.content_type = String::try_from(value).ok();
+            }
+        }
+        Ok(part)
+    }
+
+    /// 是否为文件字段
+    pub fn is_file(&self) -> bool {
+        self.filename.as_ref().is_some_and(|f| !f.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// 当前部分内容 (首个分隔符之前的前导数据也按此处理)
+    Data,
+    /// 刚越过分隔符
+    Delimiter,
+    /// 已遇到结束分隔符
+    Done,
+}
+
+/// 流式 multipart 读取器
+pub struct Multipart<'x, 'a, C> {
+    ex: &'x mut Exchange<'a, C>,
+    /// `\r\n--` + 边界
+    delimiter: Vec<u8, { MAX_BOUNDARY_LEN + 4 }>,
+    window: [u8; WINDOW_LEN],
+    start: usize,
+    end: usize,
+    eof: bool,
+    state: State,
+}
+
+impl<'x, 'a, C: Read + Write> Multipart<'x, 'a, C> {
+    /// 根据请求的 Content-Type 创建读取器
+    pub fn new(req: &Request<'_>, ex: &'x mut Exchange<'a, C>) -> Result<Self, HttpError> {
+        let boundary = req.content_type().and_then(boundary).ok_or(HttpError::BadRequest)?;
+        let mut delimiter = Vec::new();
+        let _ = delimiter.extend_from_slice(b"\r\n--");
+        let _ = delimiter.extend_from_slice(boundary.as_bytes());
+
+        // 首个分隔符前没有 CRLF，预置一个使所有分隔符形式一致
+        let mut window = [0u8; WINDOW_LEN];
+        window[..2].copy_from_slice(b"\r\n");
+        Ok(Self { ex, delimiter, window, start: 0, end: 2, eof: false, state: State::Data })
+    }
+
+    /// 已读取的请求体字节数
+    pub fn body_read(&self) -> usize {
+        self.ex.body_read()
+    }
+
+    /// 请求体总长度
+    pub fn body_len(&self) -> usize {
+        self.ex.body_len()
+    }
+
+    /// 前进到下一部分，没有更多部分时返回 `None`
+    pub async fn next_part(&mut self) -> Result<Option<Part>, HttpError> {
+        // 跳过当前部分 (或前导数据) 的剩余内容
+        let mut scratch = [0u8; 64];
+        while self.state == State::Data {
+            self.read(&mut scratch).await?;
+        }
+        if self.state == State::Done {
+            return Ok(None);
+        }
+
+        // 分隔符之后: "--" 表示结束，"\r\n" 后为部分头部
+        self.fill_to(2).await?;
+        match &self.window[self.start..self.start + 2] {
+            b"--" => {
+                self.state = State::Done;
+                return Ok(None);
+            }
+            b"\r\n" => self.start += 2,
+            _ => return Err(HttpError::BadRequest),
+        }
+
+        let header_len = loop {
+            if let Some(pos) = self.window[self.start..self.end].windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+            if self.end - self.start == WINDOW_LEN {
+                return Err(HttpError::HeaderTooLarge);
+            }
+            if self.fill().await? == 0 {
+                return Err(HttpError::BadRequest);
+            }
+        };
+        let headers = core::str::from_utf8(&self.window[self.start..self.start + header_len])
+            .map_err(|_| HttpError::BadRequest)?;
+        let part = Part::parse(headers)?;
+        self.start += header_len;
+        self.state = State::Data;
+        Ok(Some(part))
+    }
+
+    /// 读取当前部分内容，部分结束时返回 0
+    pub async fn read(&mut self, buf: &mut [u8]) -> Result<usize, HttpError> {
+        if self.state != State::Data || buf.is_empty() {
+            return Ok(0);
+        }
+        let delim_len = self.delimiter.len();
+        loop {
+            let available = &self.window[self.start..self.end];
+            if let Some(pos) = available.windows(delim_len).position(|w| w == self.delimiter.as_slice()) {
+                if pos == 0 {
+                    self.start += delim_len;
+                    self.state = State::Delimiter;
+                    return Ok(0);
+                }
+                return Ok(self.take(pos.min(buf.len()), buf));
+            }
+            // 末尾可能是分隔符的前缀，保留 delim_len - 1 字节
+            let safe = available.len().saturating_sub(delim_len - 1);
+            if safe > 0 {
+                return Ok(self.take(safe.min(buf.len()), buf));
+            }
+            if self.fill().await? == 0 {
+                // 请求体结束但未遇到分隔符
+                return Err(HttpError::BadRequest);
+            }
+        }
+    }
+
+    /// 将当前部分写入文件
+    ///
+    /// # 返回
+    /// 写入的字节数；超过 `limit` 时返回 `PayloadTooLarge`
+    pub async fn save_to(
+        &mut self,
+        file: &mut File<'_>,
+        limit: usize,
+        progress: &mut impl FnMut(&UploadProgress),
+    ) -> Result<usize, HttpError> {
+        let mut chunk = [0u8; 256];
+        let mut written = 0;
+        loop {
+            let n = self.read(&mut chunk).await?;
+            if n == 0 {
+                return Ok(written);
+            }
+            written += n;
+            if written > limit {
+                return Err(HttpError::PayloadTooLarge);
+            }
+            file.write_all(&chunk[..n])?;
+            progress(&UploadProgress {
+                file_bytes: written,
+                body_read: self.body_read(),
+                body_len: self.body_len(),
+            });
+        }
+    }
+
+    fn take(&mut self, n: usize, buf: &mut [u8]) -> usize {
+        buf[..n].copy_from_slice(&self.window[self.start..self.start + n]);
+        self.start += n;
+        n
+    }
+
+    /// 压缩窗口并从请求体读取更多数据
+    async fn fill(&mut self) -> Result<usize, HttpError> {
+        if self.eof {
+            return Ok(0);
+        }
+        if self.start > 0 {
+            self.window.copy_within(self.start..self.end, 0);
+            self.end -= self.start;
+            self.start = 0;
+        }
+        let n = self.ex.read_body(&mut self.window[self.end..]).await?;
+        self.end += n;
+        self.eof = n == 0;
+        Ok(n)
+    }
+
+    async fn fill_to(&mut self, len: usize) -> Result<(), HttpError> {
+        while self.end - self.start < len {
+            if self.fill().await? == 0 {
+                return Err(HttpError::BadRequest);
+            }
+        }
+        Ok(())
+    }
+}
+
+// ===== 文件上传 =====
+
+/// 上传进度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UploadProgress {
+    /// 当前文件已写入字节数
+    pub file_bytes: usize,
+    /// 请求体已读取字节数
+    pub body_read: usize,
+    /// 请求体总长度
+    pub body_len: usize,
+}
+
+impl UploadProgress {
+    /// 整体进度 (百分比)
+    pub fn percent(&self) -> u8 {
+        if self.body_len == 0 {
+            return 100;
+        }
+        (self.body_read as u64 * 100 / self.body_len as u64) as u8
+    }
+}
+
+/// 上传限制
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UploadLimits {
+    /// 单个文件上限
+    pub max_file_size: usize,
+    /// 单次请求最多文件数
+    pub max_files: usize,
+}
+
+impl Default for UploadLimits {
+    fn default() -> Self {
+        Self { max_file_size: 256 * 1024, max_files: 4 }
+    }
+}
+
+impl UploadLimits {
+    /// 设置单个文件上限
+    pub fn with_max_file_size(mut self, size: usize) -> Self {
+        self.max_file_size = size;
+        self
+    }
+
+    /// 设置文件数上限
+    pub fn with_max_files(mut self, count: usize) -> Self {
+        self.max_files = count;
+        self
+    }
+}
+
+/// 校验上传文件名 (只取最后一段，拒绝空名与隐藏文件)
+pub fn sanitize_filename(name: &str) -> Option<&str> {
+    let name = name.rsplit(['/', '\\']).next()?;
+    if name.is_empty() || name.starts_with('.') || name.chars().any(|c| c.is_control()) {
+        return None;
+    }
+    Some(name)
+}
+
+/// 接收表单中的全部文件，保存到 `dir` 目录
+///
+/// 非文件字段被忽略。写入失败或超限时删除不完整的文件。
+///
+/// # 返回
+/// 保存的文件数
+pub async fn receive_files<C: Read + Write>(
+    req: &Request<'_>,
+    ex: &mut Exchange<'_, C>,
+    fs: &FileSystem,
+    dir: &str,
+    limits: UploadLimits,
+    mut progress: impl FnMut(&UploadProgress),
+) -> Result<usize, HttpError> {
+    let mut form = Multipart::new(req, ex)?;
+    let mut saved = 0;
+
+    while let Some(part) = form.next_part().await? {
+        let Some(name) = part.filename.as_deref().and_then(sanitize_filename) else {
+            continue;
+        };
+        if saved == limits.max_files {
+            return Err(HttpError::PayloadTooLarge);
+        }
+
+        let mut path: String<MAX_UPLOAD_PATH> = String::new();
+        path.push_str(dir.trim_end_matches('/'))
+            .and_then(|_| path.push('/'))
+            .and_then(|_| path.push_str(name))
+            .map_err(|_| HttpError::Fs(FsError::PathTooLong))?;
+
+        let result = {
+            let mut file = fs.create(&path)?;
+            let result = form.save_to(&mut file, limits.max_file_size, &mut progress).await;
+            result.and_then(|_| file.sync().map_err(HttpError::from))
+        };
+        if let Err(e) = result {
+            let _ = fs.remove(&path);
+            return Err(e);
+        }
+        saved += 1;
+    }
+    Ok(saved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boundary_and_part_headers() {
+        let ct = "multipart/form-data; boundary=\"----WebKitFormBoundaryX\"";
+        assert_eq!(boundary(ct), Some("----WebKitFormBoundaryX"));
+        assert_eq!(boundary("application/json"), None);
+
+        let part = Part::parse("Content-Disposition: form-data; name=\"fw\"; filename=\"app.bin\"\r\nContent-Type: application/octet-stream\r\n\r\n").unwrap();
+        assert_eq!(part.name.as_str(), "fw");
+        assert_eq!(part.filename.as_deref(), Some("app.bin"));
+        assert!(part.is_file());
+    }
+
+    #[test]
+    fn test_sanitize_filename() {
+        assert_eq!(sanitize_filename("C:\\Users\\me\\logo.png"), Some("logo.png"));
+        assert_eq!(sanitize_filename("../../etc/passwd"), Some("passwd"));
+        assert_eq!(sanitize_filename(".hidden"), None);
+        assert_eq!(sanitize_filename("dir/"), None);
+    }
+}
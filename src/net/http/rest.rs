@@ -0,0 +1,371 @@
+//! JSON REST 接口辅助
+//!
+//! - 路由匹配: `/api/config/:key`、`/static/*` 形式的模式
+//! - `Exchange` 的 JSON 请求/响应扩展
+//! - 标准管理面 `ManagementApi`: 系统状态、配置读写、文件列表
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! static CONFIG: CriticalMutex<MyConfig> = CriticalMutex::new(MyConfig::new());
+//!
+//! impl Handler for App {
+//!     async fn handle<C: Read + Write>(&self, req: &Request<'_>, ex: &mut Exchange<'_, C>) -> Result<(), HttpError> {
+//!         if self.api.handle(req, ex).await? {
+//!             return Ok(());
+//!         }
+//!         if let Some(params) = match_route("/led/:state", req.path()) {
+//!             let on = params.get("state") == Some("on");
+//!             return ex.respond_json(Status::Ok, &on).await;
+//!         }
+//!         ex.respond(Status::NotFound, "text/plain", b"not found").await
+//!     }
+//! }
+//! ```
+
+use core::fmt::{self, Write as _};
+
+use embedded_io_async::{Read, Write};
+use heapless::{String, Vec};
+
+use super::{Exchange, HttpError, Method, Request, Status};
+use crate::fs::littlefs::{FileSystem, FsError};
+use crate::sync::primitives::CriticalMutex;
+use crate::util::json::{self, JsonValue, ObjectWriter, ToJson};
+
+/// JSON 响应/请求体缓冲区大小
+pub const JSON_BODY_LEN: usize = 1024;
+
+/// 路由最多捕获的参数数量
+pub const MAX_PARAMS: usize = 4;
+
+// ===== 路由匹配 =====
+
+/// 路径参数
+#[derive(Debug, Default)]
+pub struct PathParams<'p> {
+    params: Vec<(&'static str, &'p str), MAX_PARAMS>,
+}
+
+impl<'p> PathParams<'p> {
+    /// 按名称取参数 (`*` 为通配部分)
+    pub fn get(&self, name: &str) -> Option<&'p str> {
+        self.params.iter().find(|(n, _)| *n == name).map(|(_, v)| *v)
+    }
+}
+
+/// 按模式匹配路径
+///
+/// 模式段 `:name` 匹配任意单段，末尾 `*` 匹配剩余路径 (可为空)。
+pub fn match_route<'p>(pattern: &'static str, path: &'p str) -> Option<PathParams<'p>> {
+    let mut params = PathParams::default();
+    let mut rest = path.trim_start_matches('/');
+    for seg in pattern.trim_start_matches('/').split('/') {
+        if seg == "*" {
+            params.params.push(("*", rest)).ok()?;
+            return Some(params);
+        }
+        let (head, tail) = rest.split_once('/').unwrap_or((rest, ""));
+        if let Some(name) = seg.strip_prefix(':') {
+            if head.is_empty() {
+                return None;
+            }
+            params.params.push((name, head)).ok()?;
+        } else if seg != head {
+            return None;
+        }
+        rest = tail;
+    }
+    rest.is_empty().then_some(params)
+}
+
+// ===== JSON 扩展 =====
+
+impl<C: Read + Write> Exchange<'_, C> {
+    /// 以 JSON 回复
+    pub async fn respond_json<T: ToJson + ?Sized>(&mut self, status: Status, value: &T) -> Result<(), HttpError> {
+        let body: String<JSON_BODY_LEN> = json::to_string(value).map_err(|_| HttpError::PayloadTooLarge)?;
+        self.respond(status, "application/json", body.as_bytes()).await
+    }
+
+    /// 以 `{"error": "..."}` 回复
+    pub async fn respond_error(&mut self, status: Status, message: &str) -> Result<(), HttpError> {
+        let mut body: String<128> = String::new();
+        let mut obj = ObjectWriter::new(&mut body).map_err(|_| HttpError::PayloadTooLarge)?;
+        obj.field("error", message).map_err(|_| HttpError::PayloadTooLarge)?;
+        obj.finish().map_err(|_| HttpError::PayloadTooLarge)?;
+        self.respond(status, "application/json", body.as_bytes()).await
+    }
+
+    /// 读取并解析 JSON 请求体
+    pub async fn read_json<'b>(&mut self, buf: &'b mut [u8]) -> Result<JsonValue<'b>, HttpError> {
+        let body = self.read_body_to(buf).await?;
+        let text = core::str::from_utf8(body).map_err(|_| HttpError::BadRequest)?;
+        json::parse(text).map_err(|_| HttpError::BadRequest)
+    }
+}
+
+// ===== 配置存储 =====
+
+/// 配置访问错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigError {
+    /// 键不存在
+    UnknownKey,
+    /// 值类型或范围无效
+    InvalidValue,
+    /// 只读键
+    ReadOnly,
+    /// 持久化失败
+    Storage,
+}
+
+impl ConfigError {
+    fn status(self) -> Status {
+        match self {
+            ConfigError::UnknownKey => Status::NotFound,
+            ConfigError::InvalidValue => Status::BadRequest,
+            ConfigError::ReadOnly => Status::Forbidden,
+            ConfigError::Storage => Status::InternalServerError,
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownKey => write!(f, "Unknown config key"),
+            Self::InvalidValue => write!(f, "Invalid config value"),
+            Self::ReadOnly => write!(f, "Config key is read-only"),
+            Self::Storage => write!(f, "Config storage error"),
+        }
+    }
+}
+
+/// 可通过 REST 读写的配置
+pub trait ConfigStore {
+    /// 全部键
+    fn keys(&self) -> &'static [&'static str];
+
+    /// 以 JSON 写出键值
+    fn write_value(&self, key: &str, out: &mut dyn fmt::Write) -> Result<(), ConfigError>;
+
+    /// 设置键值
+    fn set_value(&mut self, key: &str, value: JsonValue<'_>) -> Result<(), ConfigError>;
+}
+
+/// 无配置接口 (仅启用系统状态与文件列表时使用)
+pub struct NoConfig;
+
+impl ConfigStore for NoConfig {
+    fn keys(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn write_value(&self, _key: &str, _out: &mut dyn fmt::Write) -> Result<(), ConfigError> {
+        Err(ConfigError::UnknownKey)
+    }
+
+    fn set_value(&mut self, _key: &str, _value: JsonValue<'_>) -> Result<(), ConfigError> {
+        Err(ConfigError::UnknownKey)
+    }
+}
+
+// ===== 管理接口 =====
+
+/// 标准管理面
+///
+/// | 方法 | 路径 | 说明 |
+/// |------|------|------|
+/// | GET | `/api/system` | 设备名与 ID、运行时间、PSRAM 使用、版本、能力 |
+/// | GET | `/api/config` | 全部配置 |
+/// | GET | `/api/config/:key` | 单个配置 |
+/// | PUT | `/api/config/:key` | 设置配置，请求体 `{"value": ...}` |
+/// | GET | `/api/files?path=/dir` | 目录列表 |
+pub struct ManagementApi<'a, S: ConfigStore> {
+    config: Option<&'a CriticalMutex<S>>,
+    fs: Option<&'a FileSystem>,
+}
+
+impl<'a, S: ConfigStore> ManagementApi<'a, S> {
+    /// 创建 (仅系统状态)
+    pub const fn new() -> Self {
+        Self { config: None, fs: None }
+    }
+
+    /// 启用配置接口
+    pub const fn with_config(mut self, config: &'a CriticalMutex<S>) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// 启用文件列表接口
+    pub const fn with_fs(mut self, fs: &'a FileSystem) -> Self {
+        self.fs = Some(fs);
+        self
+    }
+
+    /// 处理请求
+    ///
+    /// # 返回
+    /// 路径不属于管理面时返回 `false`，由调用方继续路由
+    pub async fn handle<C: Read + Write>(&self, req: &Request<'_>, ex: &mut Exchange<'_, C>) -> Result<bool, HttpError> {
+        let path = req.path();
+        if !path.starts_with("/api/") {
+            return Ok(false);
+        }
+
+        match req.method() {
+            Method::Get if path == "/api/system" => self.system(ex).await?,
+            Method::Get if path == "/api/config" => self.config_all(ex).await?,
+            Method::Get | Method::Put if path.starts_with("/api/config/") => {
+                let key = &path["/api/config/".len()..];
+                if req.method() == Method::Get {
+                    self.config_get(ex, key).await?
+                } else {
+                    self.config_set(ex, key).await?
+                }
+            }
+            Method::Get if path == "/api/files" => {
+                self.files(ex, req.query_param("path").unwrap_or("/")).await?
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    async fn system<C: Read + Write>(&self, ex: &mut Exchange<'_, C>) -> Result<(), HttpError> {
+        let psram = crate::mem::psram::stats();
+        let identity = &crate::sys::identity::IDENTITY;
+        let caps = crate::sys::caps::capabilities();
+        let mut body: String<512> = String::new();
+        let written = (|| -> fmt::Result {
+            let mut obj = ObjectWriter::new(&mut body)?;
+            obj.field("name", &identity.name())?
+                .field_with("device_id", |w| write!(w, "\"{}\"", identity.id()))?
+                .field("version", crate::VERSION)?
+                .field("uptime_ms", &embassy_time::Instant::now().as_millis())?
+                .field("cpu_mhz", &(crate::config::CPU_FREQ_HZ / 1_000_000))?
+                .field_with("psram", |w| {
+                    let mut p = ObjectWriter::new(w)?;
+                    p.field("total", &psram.total)?.field("used", &psram.used)?.field("free", &psram.free)?;
+                    p.finish()
+                })?
+                .field("capabilities", &caps)?;
+            obj.finish()
+        })();
+        written.map_err(|_| HttpError::PayloadTooLarge)?;
+        ex.respond(Status::Ok, "application/json", body.as_bytes()).await
+    }
+
+    async fn config_all<C: Read + Write>(&self, ex: &mut Exchange<'_, C>) -> Result<(), HttpError> {
+        let Some(config) = self.config else {
+            return ex.respond_error(Status::NotFound, "config not available").await;
+        };
+        let mut body: String<JSON_BODY_LEN> = String::new();
+        {
+            let store = config.lock().await;
+            let mut obj = ObjectWriter::new(&mut body).map_err(|_| HttpError::PayloadTooLarge)?;
+            for key in store.keys() {
+                let mut value: String<128> = String::new();
+                if store.write_value(key, &mut value).is_ok() {
+                    obj.field_with(key, |w| w.write_str(&value)).map_err(|_| HttpError::PayloadTooLarge)?;
+                }
+            }
+            obj.finish().map_err(|_| HttpError::PayloadTooLarge)?;
+        }
+        ex.respond(Status::Ok, "application/json", body.as_bytes()).await
+    }
+
+    async fn config_get<C: Read + Write>(&self, ex: &mut Exchange<'_, C>, key: &str) -> Result<(), HttpError> {
+        let Some(config) = self.config else {
+            return ex.respond_error(Status::NotFound, "config not available").await;
+        };
+        let mut value: String<256> = String::new();
+        let result = config.lock().await.write_value(key, &mut value);
+        if let Err(e) = result {
+            return ex.respond_error(e.status(), "unknown key").await;
+        }
+        let mut body: String<384> = String::new();
+        let mut obj = ObjectWriter::new(&mut body).map_err(|_| HttpError::PayloadTooLarge)?;
+        obj.field("key", key)
+            .and_then(|o| o.field_with("value", |w| w.write_str(&value)))
+            .map_err(|_| HttpError::PayloadTooLarge)?;
+        obj.finish().map_err(|_| HttpError::PayloadTooLarge)?;
+        ex.respond(Status::Ok, "application/json", body.as_bytes()).await
+    }
+
+    async fn config_set<C: Read + Write>(&self, ex: &mut Exchange<'_, C>, key: &str) -> Result<(), HttpError> {
+        let Some(config) = self.config else {
+            return ex.respond_error(Status::NotFound, "config not available").await;
+        };
+        let mut buf = [0u8; 512];
+        let Ok(value) = ex.read_json(&mut buf).await.and_then(|v| v.get("value").map_err(|_| HttpError::BadRequest)) else {
+            return ex.respond_error(Status::BadRequest, "expected {\"value\": ...}").await;
+        };
+        let result = config.lock().await.set_value(key, value);
+        match result {
+            Ok(()) => ex.respond(Status::NoContent, "application/json", b"").await,
+            Err(e) => {
+                let mut message: String<48> = String::new();
+                let _ = write!(message, "{}", e);
+                ex.respond_error(e.status(), &message).await
+            }
+        }
+    }
+
+    async fn files<C: Read + Write>(&self, ex: &mut Exchange<'_, C>, path: &str) -> Result<(), HttpError> {
+        let Some(fs) = self.fs else {
+            return ex.respond_error(Status::NotFound, "filesystem not available").await;
+        };
+        let mut dir = match fs.read_dir(path) {
+            Ok(dir) => dir,
+            Err(FsError::NotFound) => return ex.respond_error(Status::NotFound, "no such directory").await,
+            Err(e) => return Err(e.into()),
+        };
+
+        // 目录项数量未知，分块输出
+        ex.start_chunked(Status::Ok, &[("Content-Type", "application/json")]).await?;
+        ex.write(b"[").await?;
+        let mut first = true;
+        while let Some(entry) = dir.next()? {
+            let mut item: String<160> = String::new();
+            if !first {
+                let _ = item.push(',');
+            }
+            first = false;
+            let mut obj = ObjectWriter::new(&mut item).map_err(|_| HttpError::PayloadTooLarge)?;
+            obj.field("name", &entry.name)
+                .and_then(|o| o.field("size", &entry.size))
+                .and_then(|o| o.field("dir", &entry.is_dir()))
+                .map_err(|_| HttpError::PayloadTooLarge)?;
+            obj.finish().map_err(|_| HttpError::PayloadTooLarge)?;
+            ex.write(item.as_bytes()).await?;
+        }
+        ex.write(b"]").await?;
+        ex.finish().await
+    }
+}
+
+impl<S: ConfigStore> Default for ManagementApi<'_, S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_route() {
+        let p = match_route("/api/config/:key", "/api/config/wifi.ssid").unwrap();
+        assert_eq!(p.get("key"), Some("wifi.ssid"));
+        assert!(match_route("/api/config/:key", "/api/config/").is_none());
+        assert!(match_route("/api/config/:key", "/api/config/a/b").is_none());
+
+        let p = match_route("/static/*", "/static/css/app.css").unwrap();
+        assert_eq!(p.get("*"), Some("css/app.css"));
+        assert!(match_route("/api/system", "/api/systems").is_none());
+    }
+}
@@ -0,0 +1,231 @@
+//! Server-Sent Events
+//!
+//! 仪表盘通过 `EventSource` 订阅设备的实时遥测 (任务统计、传感器读数等)。
+//! 发布者把事件广播到每个订阅者的独立发送队列；队列消息体存放在
+//! 内存池中 (`PooledChannel`)，慢客户端只会丢弃自己的事件，不会阻塞发布者
+//! 或其他客户端。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! static EVENTS: SseHub<4, 8> = SseHub::new();
+//!
+//! // 发布 (任意任务)
+//! EVENTS.publish_fmt("sensor", format_args!("{{\"temp\":{}}}", milli_c));
+//!
+//! // HTTP 处理器
+//! (Method::Get, "/events") => EVENTS.serve(ex).await,
+//! ```
+
+use core::fmt::{self, Write as _};
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use embassy_time::{with_timeout, Duration};
+use embedded_io_async::{Read, Write};
+use heapless::{String, Vec};
+
+use super::{Exchange, HttpError, Status};
+use crate::sync::pooled::PooledChannel;
+
+/// 单个事件数据的最大长度
+pub const SSE_DATA_LEN: usize = 256;
+
+/// 事件名最大长度
+pub const SSE_EVENT_LEN: usize = 24;
+
+/// 无事件时发送保活注释的间隔
+pub const SSE_KEEPALIVE: Duration = Duration::from_secs(15);
+
+/// SSE 事件
+#[derive(Debug, Clone, Default)]
+pub struct SseMessage {
+    /// 事件 ID
+    pub id: u32,
+    /// 事件名 (为空时客户端按 "message" 处理)
+    pub event: String<SSE_EVENT_LEN>,
+    /// 事件数据 (可含换行)
+    pub data: Vec<u8, SSE_DATA_LEN>,
+}
+
+impl SseMessage {
+    /// 按 SSE 线上格式编码
+    ///
+    /// # 返回
+    /// 编码长度；`out` 不足时返回 `None`
+    pub fn encode(&self, out: &mut [u8]) -> Option<usize> {
+        let mut w = SliceWriter { buf: out, len: 0 };
+        write!(w, "id: {}\n", self.id).ok()?;
+        if !self.event.is_empty() {
+            write!(w, "event: {}\n", self.event).ok()?;
+        }
+        for line in self.data.split(|&b| b == b'\n') {
+            w.put(b"data: ")?;
+            w.put(line)?;
+            w.put(b"\n")?;
+        }
+        w.put(b"\n")?;
+        Some(w.len)
+    }
+}
+
+/// 写入定长缓冲区
+struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl SliceWriter<'_> {
+    fn put(&mut self, data: &[u8]) -> Option<()> {
+        let end = self.len + data.len();
+        self.buf.get_mut(self.len..end)?.copy_from_slice(data);
+        self.len = end;
+        Some(())
+    }
+}
+
+impl fmt::Write for SliceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.put(s.as_bytes()).ok_or(fmt::Error)
+    }
+}
+
+/// 事件中心统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SseStats {
+    /// 已发布事件
+    pub published: u32,
+    /// 因队列满丢弃的事件 (按订阅者计)
+    pub dropped: u32,
+    /// 当前订阅者数量
+    pub subscribers: u32,
+}
+
+/// SSE 事件中心
+///
+/// `CLIENTS` 为最大订阅者数，`QUEUE` 为每个订阅者的发送队列深度。
+pub struct SseHub<const CLIENTS: usize, const QUEUE: usize> {
+    queues: [PooledChannel<SseMessage, QUEUE>; CLIENTS],
+    active: [AtomicBool; CLIENTS],
+    next_id: AtomicU32,
+    published: AtomicU32,
+    dropped: AtomicU32,
+}
+
+impl<const CLIENTS: usize, const QUEUE: usize> SseHub<CLIENTS, QUEUE> {
+    /// 创建事件中心
+    pub const fn new() -> Self {
+        Self {
+            queues: [const { PooledChannel::new() }; CLIENTS],
+            active: [const { AtomicBool::new(false) }; CLIENTS],
+            next_id: AtomicU32::new(1),
+            published: AtomicU32::new(0),
+            dropped: AtomicU32::new(0),
+        }
+    }
+
+    /// 发布事件 (数据超长时截断，事件名超长时省略)
+    pub fn publish(&'static self, event: &str, data: &[u8]) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.published.fetch_add(1, Ordering::Relaxed);
+        for (queue, active) in self.queues.iter().zip(self.active.iter()) {
+            if !active.load(Ordering::Acquire) {
+                continue;
+            }
+            let Some(mut msg) = queue.alloc() else {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                continue;
+            };
+            msg.id = id;
+            let _ = msg.event.push_str(event);
+            let _ = msg.data.extend_from_slice(&data[..data.len().min(SSE_DATA_LEN)]);
+            queue.send_boxed(msg);
+        }
+    }
+
+    /// 发布格式化事件
+    pub fn publish_fmt(&'static self, event: &str, args: fmt::Arguments<'_>) {
+        let mut data: String<SSE_DATA_LEN> = String::new();
+        let _ = data.write_fmt(args);
+        self.publish(event, data.as_bytes());
+    }
+
+    /// 作为 HTTP 响应向一个订阅者推送事件，直到连接断开
+    ///
+    /// 订阅者已满时回复 503。
+    pub async fn serve<C: Read + Write>(&'static self, ex: &mut Exchange<'_, C>) -> Result<(), HttpError> {
+        let Some(slot) = self.subscribe() else {
+            return ex.respond(Status::ServiceUnavailable, "text/plain", b"too many subscribers").await;
+        };
+        let result = self.stream(slot, ex).await;
+        self.unsubscribe(slot);
+        result
+    }
+
+    async fn stream<C: Read + Write>(&'static self, slot: usize, ex: &mut Exchange<'_, C>) -> Result<(), HttpError> {
+        ex.start_chunked(
+            Status::Ok,
+            &[("Content-Type", "text/event-stream"), ("Cache-Control", "no-cache"), ("Connection", "keep-alive")],
+        )
+        .await?;
+        ex.write(b"retry: 3000\n\n").await?;
+        ex.flush().await?;
+
+        let mut frame = [0u8; SSE_DATA_LEN + SSE_EVENT_LEN + 64];
+        loop {
+            match with_timeout(SSE_KEEPALIVE, self.queues[slot].receive()).await {
+                Ok(msg) => {
+                    let len = msg.encode(&mut frame).ok_or(HttpError::PayloadTooLarge)?;
+                    drop(msg);
+                    ex.write(&frame[..len]).await?;
+                }
+                // 保活注释，同时用于探测断开的连接
+                Err(_) => ex.write(b":\n\n").await?,
+            }
+            ex.flush().await?;
+        }
+    }
+
+    /// 占用一个订阅槽位
+    fn subscribe(&self) -> Option<usize> {
+        self.active.iter().position(|a| {
+            a.compare_exchange(false, true, Ordering::AcqRel, Ordering::Relaxed).is_ok()
+        })
+    }
+
+    /// 释放槽位并清空残留事件
+    fn unsubscribe(&self, slot: usize) {
+        self.active[slot].store(false, Ordering::Release);
+        while self.queues[slot].try_receive().is_some() {}
+    }
+
+    /// 统计快照
+    pub fn stats(&self) -> SseStats {
+        SseStats {
+            published: self.published.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            subscribers: self.active.iter().filter(|a| a.load(Ordering::Relaxed)).count() as u32,
+        }
+    }
+}
+
+impl<const CLIENTS: usize, const QUEUE: usize> Default for SseHub<CLIENTS, QUEUE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_multiline_event() {
+        let mut msg = SseMessage { id: 7, ..Default::default() };
+        msg.event.push_str("stats").unwrap();
+        msg.data.extend_from_slice(b"a\nb").unwrap();
+        let mut out = [0u8; 64];
+        let len = msg.encode(&mut out).unwrap();
+        assert_eq!(&out[..len], b"id: 7\nevent: stats\ndata: a\ndata: b\n\n");
+        assert_eq!(msg.encode(&mut out[..8]), None);
+    }
+}
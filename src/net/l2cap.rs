@@ -0,0 +1,799 @@
+//! BLE L2CAP 面向连接通道 (LE Credit Based Flow Control)
+//!
+//! GATT 写入每包都要经过 ATT 层且受 MTU 限制，OTA 固件、文件同步等大块数据
+//! 吞吐很低。L2CAP CoC 直接在 L2CAP 层传输原始 SDU，由基于信用的流控保证
+//! 接收方缓冲区不溢出:
+//! - 发送: 应用写入的数据成为一个 SDU，按对端 MPS 切分为 K-frame
+//!   (首帧带 2 字节 SDU 长度)，每帧消耗一个对端授予的信用
+//! - 接收: 授予对端的信用数与接收缓冲区剩余空间绑定 (`空闲字节 / MPS`)，
+//!   应用读取腾出空间后批量归还信用 (`credit_threshold`)
+//! - 对端违反流控 (无信用发送、帧超过 MPS、SDU 超过 MTU、信用溢出) 时通道
+//!   进入错误状态，链路任务应断开通道
+//! - `CocStream` 实现 `embedded_io_async::Read`/`Write`，可直接交给 OTA、
+//!   文件同步等按字节流工作的上层
+//!
+//! 与具体协议栈无关: 链路任务在 trouble-host 的 L2CAP 信令与数据回调中调用
+//! `open` / `on_frame` / `on_credits`，并把 `outgoing` 产生的帧与信用包发出。
+//! PSM 的监听与通道开闭事件通过 `BleController::listen_l2cap` 等登记。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::l2cap::{CocChannel, CocConfig, Outgoing};
+//!
+//! static OTA: CocChannel<2048> = CocChannel::new(CocConfig::new(0x0080).with_mtu(512));
+//!
+//! // 链路任务 (对端连接请求到达后)
+//! let credits = OTA.open(req.mtu, req.mps, req.credits)?;
+//! respond(req, OTA.config().mtu, OTA.config().mps, credits).await;
+//! let mut frame = [0u8; 247];
+//! loop {
+//!     match select(OTA.outgoing(&mut frame), link.receive(&mut rx)).await {
+//!         Either::First(Outgoing::Frame(len)) => link.send_frame(&frame[..len]).await,
+//!         Either::First(Outgoing::Credits(n)) => link.send_credits(n).await,
+//!         Either::First(Outgoing::Disconnect) => break,
+//!         Either::Second(LinkPdu::Data(payload)) => OTA.on_frame(payload)?,
+//!         Either::Second(LinkPdu::Credits(n)) => OTA.on_credits(n)?,
+//!     }
+//! }
+//!
+//! // 应用任务: 按字节流读取固件
+//! let mut stream = OTA.stream();
+//! let n = stream.read(&mut chunk).await?;
+//! ```
+
+use core::cell::RefCell;
+use core::fmt;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embedded_io_async::{ErrorKind, ErrorType, Read, Write};
+use heapless::{Deque, Vec};
+
+use crate::sync::primitives::CriticalSignal;
+
+/// 规范允许的最小 MTU / MPS
+pub const COC_MIN_MTU: u16 = 23;
+
+/// 默认 SDU 最大长度
+pub const COC_DEFAULT_MTU: u16 = 512;
+
+/// 默认 K-frame 最大负载 (LE 数据长度 251 - 4 字节 L2CAP 基本头)
+pub const COC_DEFAULT_MPS: u16 = 247;
+
+/// 默认信用归还批量
+pub const COC_DEFAULT_CREDIT_THRESHOLD: u16 = 4;
+
+/// SDU 长度字段 (仅首帧)
+const SDU_HEADER: usize = 2;
+
+// ===== 错误 =====
+
+/// L2CAP CoC 错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CocError {
+    /// PSM 不在 LE 范围 (0x0001..=0x00FF)
+    InvalidPsm,
+    /// MTU / MPS / 缓冲区参数无效
+    InvalidParameter,
+    /// 通道未打开
+    NotConnected,
+    /// SDU 超过接收方 MTU
+    SduTooLarge,
+    /// 信用计数溢出 (超过 65535)
+    CreditOverflow,
+    /// 对端违反流控或分段规则
+    UnexpectedFrame,
+}
+
+impl fmt::Display for CocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPsm => write!(f, "Invalid PSM"),
+            Self::InvalidParameter => write!(f, "Invalid channel parameter"),
+            Self::NotConnected => write!(f, "Channel not connected"),
+            Self::SduTooLarge => write!(f, "SDU exceeds MTU"),
+            Self::CreditOverflow => write!(f, "Credit count overflow"),
+            Self::UnexpectedFrame => write!(f, "Unexpected K-frame"),
+        }
+    }
+}
+
+impl embedded_io_async::Error for CocError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::NotConnected => ErrorKind::NotConnected,
+            Self::InvalidPsm | Self::InvalidParameter => ErrorKind::InvalidInput,
+            _ => ErrorKind::InvalidData,
+        }
+    }
+}
+
+// ===== 配置 =====
+
+/// 本端通道参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CocConfig {
+    /// 协议/服务复用号 (LE 动态范围 0x0080..=0x00FF)
+    pub psm: u16,
+    /// 本端可接收的最大 SDU
+    pub mtu: u16,
+    /// 本端可接收的最大 K-frame 负载
+    pub mps: u16,
+    /// 累计可归还信用达到该值才发送信用包 (对端信用耗尽时立即归还)
+    pub credit_threshold: u16,
+}
+
+impl CocConfig {
+    /// 默认参数
+    pub const fn new(psm: u16) -> Self {
+        Self {
+            psm,
+            mtu: COC_DEFAULT_MTU,
+            mps: COC_DEFAULT_MPS,
+            credit_threshold: COC_DEFAULT_CREDIT_THRESHOLD,
+        }
+    }
+
+    /// 设置 MTU
+    pub const fn with_mtu(mut self, mtu: u16) -> Self {
+        self.mtu = mtu;
+        self
+    }
+
+    /// 设置 MPS
+    pub const fn with_mps(mut self, mps: u16) -> Self {
+        self.mps = mps;
+        self
+    }
+
+    /// 设置信用归还批量
+    pub const fn with_credit_threshold(mut self, threshold: u16) -> Self {
+        self.credit_threshold = threshold;
+        self
+    }
+
+    /// 校验参数 (`capacity` 为接收缓冲区字节数，至少容纳一帧)
+    pub fn validate(&self, capacity: usize) -> Result<(), CocError> {
+        if self.psm == 0 || self.psm > 0x00FF {
+            return Err(CocError::InvalidPsm);
+        }
+        if self.mtu < COC_MIN_MTU || self.mps < COC_MIN_MTU || (self.mps as usize) > capacity {
+            return Err(CocError::InvalidParameter);
+        }
+        Ok(())
+    }
+}
+
+impl Default for CocConfig {
+    fn default() -> Self {
+        Self::new(0x0080)
+    }
+}
+
+// ===== 信用流控 =====
+
+/// 双向信用计数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CreditFlow {
+    /// 对端授予、尚未使用的发送信用
+    pub tx: u16,
+    /// 已授予对端、尚未收到对应帧的接收信用
+    pub rx: u16,
+}
+
+impl CreditFlow {
+    /// 对端授予信用
+    pub fn grant_tx(&mut self, credits: u16) -> Result<(), CocError> {
+        self.tx = self.tx.checked_add(credits).ok_or(CocError::CreditOverflow)?;
+        Ok(())
+    }
+
+    /// 消耗一个发送信用
+    pub fn take_tx(&mut self) -> bool {
+        if self.tx == 0 {
+            return false;
+        }
+        self.tx -= 1;
+        true
+    }
+
+    /// 收到一帧 (对端必须持有信用)
+    pub fn on_rx(&mut self) -> Result<(), CocError> {
+        if self.rx == 0 {
+            return Err(CocError::UnexpectedFrame);
+        }
+        self.rx -= 1;
+        Ok(())
+    }
+
+    /// 按缓冲区可容纳的帧数补足对端信用
+    ///
+    /// # 返回
+    /// 需要发送给对端的信用数 (不足 `threshold` 且对端仍有信用时暂不归还)
+    pub fn replenish(&mut self, capacity_frames: u16, threshold: u16) -> Option<u16> {
+        let grant = capacity_frames.saturating_sub(self.rx);
+        if grant == 0 || (grant < threshold && self.rx > 0) {
+            return None;
+        }
+        self.rx += grant;
+        Some(grant)
+    }
+}
+
+// ===== 通道 =====
+
+/// 通道状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CocState {
+    /// 未打开
+    #[default]
+    Idle,
+    /// 已打开
+    Open,
+    /// 本端请求关闭，等待链路任务断开
+    Closing,
+    /// 已关闭 (剩余接收数据仍可读出)
+    Closed,
+    /// 对端违反流控，通道失效
+    Failed(CocError),
+}
+
+/// 链路任务需要发出的数据
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outgoing {
+    /// 一个 K-frame (已写入缓冲区的字节数)
+    Frame(usize),
+    /// LE Flow Control Credit 信用包
+    Credits(u16),
+    /// 发送断开请求
+    Disconnect,
+}
+
+/// 通道统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CocStats {
+    /// 发送的 SDU
+    pub tx_sdus: u32,
+    /// 接收的 SDU
+    pub rx_sdus: u32,
+    /// 发送的负载字节
+    pub tx_bytes: u32,
+    /// 接收的负载字节
+    pub rx_bytes: u32,
+    /// 有待发数据但无发送信用的次数
+    pub credit_stalls: u32,
+}
+
+struct Inner<const N: usize> {
+    state: CocState,
+    peer_mtu: u16,
+    peer_mps: u16,
+    flow: CreditFlow,
+    /// 待归还给对端的信用
+    pending_credits: u16,
+    /// 已按字节流排队、待应用读取的接收数据
+    rx: Deque<u8, N>,
+    /// 当前接收 SDU 剩余字节 (0 表示下一帧为首帧)
+    rx_remaining: u16,
+    /// 当前发送 SDU 及已分段偏移
+    tx: Vec<u8, N>,
+    tx_offset: usize,
+    stalled: bool,
+    stats: CocStats,
+}
+
+impl<const N: usize> Inner<N> {
+    const fn new() -> Self {
+        Self {
+            state: CocState::Idle,
+            peer_mtu: 0,
+            peer_mps: 0,
+            flow: CreditFlow { tx: 0, rx: 0 },
+            pending_credits: 0,
+            rx: Deque::new(),
+            rx_remaining: 0,
+            tx: Vec::new(),
+            tx_offset: 0,
+            stalled: false,
+            stats: CocStats { tx_sdus: 0, rx_sdus: 0, tx_bytes: 0, rx_bytes: 0, credit_stalls: 0 },
+        }
+    }
+
+    /// 接收缓冲区可再容纳的整帧数
+    fn capacity_frames(&self, mps: u16) -> u16 {
+        ((N - self.rx.len()) / mps as usize).min(u16::MAX as usize) as u16
+    }
+
+    fn fail(&mut self, error: CocError) -> CocError {
+        self.state = CocState::Failed(error);
+        error
+    }
+
+    fn receive(&mut self, config: &CocConfig, payload: &[u8]) -> Result<(), CocError> {
+        self.flow.on_rx()?;
+        if payload.len() > config.mps as usize {
+            return Err(CocError::UnexpectedFrame);
+        }
+
+        let data = if self.rx_remaining == 0 {
+            let header = payload.get(..SDU_HEADER).ok_or(CocError::UnexpectedFrame)?;
+            let sdu_len = u16::from_le_bytes([header[0], header[1]]);
+            if sdu_len > config.mtu {
+                return Err(CocError::SduTooLarge);
+            }
+            self.rx_remaining = sdu_len;
+            &payload[SDU_HEADER..]
+        } else {
+            payload
+        };
+        if data.len() > self.rx_remaining as usize {
+            return Err(CocError::UnexpectedFrame);
+        }
+
+        for &byte in data {
+            // 信用与缓冲区空间绑定，守约的对端不会触发
+            self.rx.push_back(byte).map_err(|_| CocError::UnexpectedFrame)?;
+        }
+        self.rx_remaining -= data.len() as u16;
+        self.stats.rx_bytes = self.stats.rx_bytes.wrapping_add(data.len() as u32);
+        if self.rx_remaining == 0 {
+            self.stats.rx_sdus = self.stats.rx_sdus.wrapping_add(1);
+        }
+        Ok(())
+    }
+
+    /// 从当前发送 SDU 切出下一帧
+    fn next_frame(&mut self, out: &mut [u8]) -> Option<usize> {
+        if self.tx.is_empty() {
+            return None;
+        }
+        if !self.flow.take_tx() {
+            if !self.stalled {
+                self.stalled = true;
+                self.stats.credit_stalls = self.stats.credit_stalls.wrapping_add(1);
+            }
+            return None;
+        }
+        self.stalled = false;
+
+        let room = out.len().min(self.peer_mps as usize);
+        let mut len = 0;
+        if self.tx_offset == 0 {
+            out[..SDU_HEADER].copy_from_slice(&(self.tx.len() as u16).to_le_bytes());
+            len = SDU_HEADER;
+        }
+        let take = (room - len).min(self.tx.len() - self.tx_offset);
+        out[len..len + take].copy_from_slice(&self.tx[self.tx_offset..self.tx_offset + take]);
+        self.tx_offset += take;
+        len += take;
+
+        if self.tx_offset == self.tx.len() {
+            self.stats.tx_sdus = self.stats.tx_sdus.wrapping_add(1);
+            self.stats.tx_bytes = self.stats.tx_bytes.wrapping_add(self.tx.len() as u32);
+            self.tx.clear();
+            self.tx_offset = 0;
+        }
+        Some(len)
+    }
+}
+
+/// L2CAP CoC 通道
+///
+/// `N` 为接收与发送缓冲区字节数，决定授予对端的信用数 (`N / MPS`) 以及
+/// 单次写入可形成的最大 SDU。可放在 `static` 中由链路任务与应用任务共享。
+pub struct CocChannel<const N: usize> {
+    config: CocConfig,
+    inner: BlockingMutex<CriticalSectionRawMutex, RefCell<Inner<N>>>,
+    /// 接收数据到达或通道关闭
+    rx_ready: CriticalSignal<()>,
+    /// 发送 SDU 已分段完毕
+    tx_done: CriticalSignal<()>,
+    /// 链路任务有新工作 (帧、信用、断开)
+    link: CriticalSignal<()>,
+}
+
+impl<const N: usize> CocChannel<N> {
+    /// 创建通道 (未打开)
+    pub const fn new(config: CocConfig) -> Self {
+        Self {
+            config,
+            inner: BlockingMutex::new(RefCell::new(Inner::new())),
+            rx_ready: CriticalSignal::new(),
+            tx_done: CriticalSignal::new(),
+            link: CriticalSignal::new(),
+        }
+    }
+
+    /// 本端参数
+    pub fn config(&self) -> &CocConfig {
+        &self.config
+    }
+
+    /// 当前状态
+    pub fn state(&self) -> CocState {
+        self.inner.lock(|inner| inner.borrow().state)
+    }
+
+    /// 当前信用
+    pub fn credits(&self) -> CreditFlow {
+        self.inner.lock(|inner| inner.borrow().flow)
+    }
+
+    /// 统计
+    pub fn stats(&self) -> CocStats {
+        self.inner.lock(|inner| inner.borrow().stats)
+    }
+
+    // ===== 链路侧 =====
+
+    /// 通道建立 (连接请求/响应交换完毕)
+    ///
+    /// # 参数
+    /// - `peer_mtu` / `peer_mps` / `peer_credits`: 对端在请求或响应中给出的参数
+    ///
+    /// # 返回
+    /// 本端初始授予对端的信用 (填入响应或请求)
+    pub fn open(&self, peer_mtu: u16, peer_mps: u16, peer_credits: u16) -> Result<u16, CocError> {
+        self.config.validate(N)?;
+        if peer_mtu < COC_MIN_MTU || peer_mps < COC_MIN_MTU {
+            return Err(CocError::InvalidParameter);
+        }
+        let credits = self.inner.lock(|inner| {
+            let mut inner = inner.borrow_mut();
+            let stats = inner.stats;
+            *inner = Inner::new();
+            inner.stats = stats;
+            inner.state = CocState::Open;
+            inner.peer_mtu = peer_mtu;
+            inner.peer_mps = peer_mps;
+            inner.flow.tx = peer_credits;
+            let frames = inner.capacity_frames(self.config.mps);
+            inner.flow.rx = frames;
+            frames
+        });
+        self.tx_done.signal(());
+        Ok(credits)
+    }
+
+    /// 收到一个 K-frame 负载 (不含 L2CAP 基本头)
+    ///
+    /// 返回错误时通道已失效，链路任务应断开。
+    pub fn on_frame(&self, payload: &[u8]) -> Result<(), CocError> {
+        let result = self.inner.lock(|inner| {
+            let mut inner = inner.borrow_mut();
+            if inner.state != CocState::Open {
+                return Err(CocError::NotConnected);
+            }
+            inner.receive(&self.config, payload).map_err(|e| inner.fail(e))
+        });
+        self.rx_ready.signal(());
+        result
+    }
+
+    /// 对端授予信用
+    pub fn on_credits(&self, credits: u16) -> Result<(), CocError> {
+        let result = self.inner.lock(|inner| {
+            let mut inner = inner.borrow_mut();
+            if inner.state != CocState::Open {
+                return Err(CocError::NotConnected);
+            }
+            inner.flow.grant_tx(credits).map_err(|e| inner.fail(e))
+        });
+        self.link.signal(());
+        if result.is_err() {
+            self.rx_ready.signal(());
+        }
+        result
+    }
+
+    /// 链路已断开 (任一方发起)
+    pub fn on_disconnected(&self) {
+        self.inner.lock(|inner| {
+            let mut inner = inner.borrow_mut();
+            if !matches!(inner.state, CocState::Failed(_)) {
+                inner.state = CocState::Closed;
+            }
+            inner.tx.clear();
+            inner.tx_offset = 0;
+        });
+        self.rx_ready.signal(());
+        self.tx_done.signal(());
+    }
+
+    /// 取出下一项待发送工作 (非阻塞)
+    ///
+    /// 信用包优先于数据帧，保证对端不会因等待信用而停滞。`out` 至少为对端 MPS。
+    pub fn try_outgoing(&self, out: &mut [u8]) -> Option<Outgoing> {
+        let (next, sdu_done) = self.inner.lock(|inner| {
+            let mut inner = inner.borrow_mut();
+            match inner.state {
+                CocState::Closing => (Some(Outgoing::Disconnect), false),
+                CocState::Open => {
+                    if inner.pending_credits > 0 {
+                        let credits = core::mem::take(&mut inner.pending_credits);
+                        return (Some(Outgoing::Credits(credits)), false);
+                    }
+                    match inner.next_frame(out) {
+                        Some(len) => (Some(Outgoing::Frame(len)), inner.tx.is_empty()),
+                        None => (None, false),
+                    }
+                }
+                _ => (None, false),
+            }
+        });
+        if sdu_done {
+            self.tx_done.signal(());
+        }
+        next
+    }
+
+    /// 等待下一项待发送工作
+    pub async fn outgoing(&self, out: &mut [u8]) -> Outgoing {
+        loop {
+            if let Some(next) = self.try_outgoing(out) {
+                return next;
+            }
+            self.link.wait().await;
+        }
+    }
+
+    // ===== 应用侧 =====
+
+    /// 读取已接收数据 (非阻塞)
+    ///
+    /// # 返回
+    /// - `None`: 暂无数据
+    /// - `Some(Ok(0))`: 通道已关闭且数据已读完
+    pub fn try_read(&self, buf: &mut [u8]) -> Option<Result<usize, CocError>> {
+        if buf.is_empty() {
+            return Some(Ok(0));
+        }
+        let (result, credits) = self.inner.lock(|inner| {
+            let mut inner = inner.borrow_mut();
+            let mut n = 0;
+            while n < buf.len() {
+                match inner.rx.pop_front() {
+                    Some(byte) => {
+                        buf[n] = byte;
+                        n += 1;
+                    }
+                    None => break,
+                }
+            }
+            if n > 0 {
+                let mut credits = false;
+                if inner.state == CocState::Open {
+                    let frames = inner.capacity_frames(self.config.mps);
+                    if let Some(grant) = inner.flow.replenish(frames, self.config.credit_threshold) {
+                        inner.pending_credits = inner.pending_credits.saturating_add(grant);
+                        credits = true;
+                    }
+                }
+                return (Some(Ok(n)), credits);
+            }
+            match inner.state {
+                CocState::Failed(e) => (Some(Err(e)), false),
+                CocState::Idle => (Some(Err(CocError::NotConnected)), false),
+                CocState::Closing | CocState::Closed => (Some(Ok(0)), false),
+                CocState::Open => (None, false),
+            }
+        });
+        if credits {
+            self.link.signal(());
+        }
+        result
+    }
+
+    /// 读取数据 (至少 1 字节，关闭后返回 0)
+    pub async fn read(&self, buf: &mut [u8]) -> Result<usize, CocError> {
+        loop {
+            if let Some(result) = self.try_read(buf) {
+                return result;
+            }
+            self.rx_ready.wait().await;
+        }
+    }
+
+    /// 将数据作为一个 SDU 排队发送 (非阻塞)
+    ///
+    /// 超过对端 MTU 或缓冲区的部分不被接受，由返回的字节数体现。
+    ///
+    /// # 返回
+    /// 上一个 SDU 尚未分段完毕时返回 `None`
+    pub fn try_write(&self, buf: &[u8]) -> Option<Result<usize, CocError>> {
+        let result = self.inner.lock(|inner| {
+            let mut inner = inner.borrow_mut();
+            if inner.state != CocState::Open {
+                return Some(Err(match inner.state {
+                    CocState::Failed(e) => e,
+                    _ => CocError::NotConnected,
+                }));
+            }
+            if buf.is_empty() {
+                return Some(Ok(0));
+            }
+            if !inner.tx.is_empty() {
+                return None;
+            }
+            let len = buf.len().min(inner.peer_mtu as usize).min(N);
+            // 容量由上方 min 保证
+            let _ = inner.tx.extend_from_slice(&buf[..len]);
+            Some(Ok(len))
+        });
+        if matches!(result, Some(Ok(n)) if n > 0) {
+            self.link.signal(());
+        }
+        result
+    }
+
+    /// 写入数据 (等待上一个 SDU 发出缓冲区)
+    pub async fn write(&self, buf: &[u8]) -> Result<usize, CocError> {
+        loop {
+            if let Some(result) = self.try_write(buf) {
+                return result;
+            }
+            self.tx_done.wait().await;
+        }
+    }
+
+    /// 等待已排队的 SDU 全部分段交给链路
+    pub async fn flush(&self) -> Result<(), CocError> {
+        loop {
+            let pending = self.inner.lock(|inner| {
+                let inner = inner.borrow();
+                match inner.state {
+                    CocState::Failed(e) => Err(e),
+                    _ => Ok(!inner.tx.is_empty()),
+                }
+            })?;
+            if !pending {
+                return Ok(());
+            }
+            self.tx_done.wait().await;
+        }
+    }
+
+    /// 请求关闭通道 (链路任务随后发送断开请求)
+    pub fn close(&self) {
+        self.inner.lock(|inner| {
+            let mut inner = inner.borrow_mut();
+            if inner.state == CocState::Open {
+                inner.state = CocState::Closing;
+            }
+        });
+        self.link.signal(());
+        self.rx_ready.signal(());
+    }
+
+    /// 字节流视图 (`embedded_io_async::Read`/`Write`)
+    pub fn stream(&self) -> CocStream<'_, N> {
+        CocStream { channel: self }
+    }
+}
+
+// ===== 字节流 =====
+
+/// `CocChannel` 的 `embedded_io_async` 适配
+pub struct CocStream<'c, const N: usize> {
+    channel: &'c CocChannel<N>,
+}
+
+impl<const N: usize> ErrorType for CocStream<'_, N> {
+    type Error = CocError;
+}
+
+impl<const N: usize> Read for CocStream<'_, N> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, CocError> {
+        self.channel.read(buf).await
+    }
+}
+
+impl<const N: usize> Write for CocStream<'_, N> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, CocError> {
+        self.channel.write(buf).await
+    }
+
+    async fn flush(&mut self) -> Result<(), CocError> {
+        self.channel.flush().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 把 `from` 的待发送工作交给 `to`，返回转发的帧数
+    fn pump<const A: usize, const B: usize>(from: &CocChannel<A>, to: &CocChannel<B>) -> usize {
+        let mut frame = [0u8; 64];
+        let mut frames = 0;
+        while let Some(next) = from.try_outgoing(&mut frame) {
+            match next {
+                Outgoing::Frame(len) => {
+                    to.on_frame(&frame[..len]).unwrap();
+                    frames += 1;
+                }
+                Outgoing::Credits(n) => to.on_credits(n).unwrap(),
+                Outgoing::Disconnect => to.on_disconnected(),
+            }
+        }
+        frames
+    }
+
+    #[test]
+    fn test_segmentation_and_credits() {
+        let config = CocConfig::new(0x0080).with_mtu(100).with_mps(32).with_credit_threshold(1);
+        let a: CocChannel<128> = CocChannel::new(config);
+        let b: CocChannel<128> = CocChannel::new(config);
+
+        // b 缓冲 128 字节 / MPS 32 = 4 帧信用
+        let b_credits = b.open(100, 32, 0).unwrap();
+        let a_credits = a.open(100, 32, b_credits).unwrap();
+        b.on_credits(a_credits).unwrap();
+        assert_eq!(b_credits, 4);
+
+        // 100 字节 SDU: 首帧 2 + 30，其后 32 + 32 + 6，共 4 帧
+        let data: [u8; 100] = core::array::from_fn(|i| i as u8);
+        assert_eq!(a.try_write(&data), Some(Ok(100)));
+        assert_eq!(a.try_write(&data), None);
+        assert_eq!(pump(&a, &b), 4);
+        assert_eq!(a.credits().tx, 0);
+
+        // 读出一部分后归还信用
+        let mut out = [0u8; 100];
+        assert_eq!(b.try_read(&mut out[..64]), Some(Ok(64)));
+        assert_eq!(b.credits().rx, 2);
+        pump(&b, &a);
+        assert_eq!(a.credits().tx, 2);
+        assert_eq!(b.try_read(&mut out[64..]), Some(Ok(36)));
+        assert_eq!(out, data);
+        assert_eq!(b.try_read(&mut out), None);
+        assert_eq!(b.stats().rx_sdus, 1);
+
+        // 信用不足时暂停并计数，归还后继续
+        assert_eq!(a.try_write(&data), Some(Ok(100)));
+        assert_eq!(pump(&a, &b), 2);
+        assert_eq!(a.stats().credit_stalls, 1);
+        pump(&b, &a);
+        assert_eq!(pump(&a, &b), 2);
+        assert_eq!(a.stats().tx_sdus, 2);
+    }
+
+    #[test]
+    fn test_protocol_violations() {
+        let b: CocChannel<64> = CocChannel::new(CocConfig::new(0x0081).with_mtu(40).with_mps(32));
+        assert_eq!(CocConfig::new(0x0100).validate(64), Err(CocError::InvalidPsm));
+        assert_eq!(CocConfig::new(0x0080).with_mps(128).validate(64), Err(CocError::InvalidParameter));
+
+        assert_eq!(b.open(23, 23, 0), Ok(2));
+        assert_eq!(b.on_frame(&[41, 0]), Err(CocError::SduTooLarge));
+        assert_eq!(b.state(), CocState::Failed(CocError::SduTooLarge));
+        assert_eq!(b.try_read(&mut [0; 4]), Some(Err(CocError::SduTooLarge)));
+
+        // 重新打开后，超出信用的帧被拒绝
+        b.open(23, 23, u16::MAX).unwrap();
+        b.on_frame(&[1, 0, 7]).unwrap();
+        b.on_frame(&[1, 0, 8]).unwrap();
+        assert_eq!(b.on_frame(&[1, 0, 9]), Err(CocError::UnexpectedFrame));
+
+        b.open(23, 23, u16::MAX).unwrap();
+        assert_eq!(b.on_credits(1), Err(CocError::CreditOverflow));
+    }
+
+    #[test]
+    fn test_close_drains_then_eof() {
+        let a: CocChannel<64> = CocChannel::new(CocConfig::new(0x0080).with_mps(32));
+        a.open(64, 32, 1).unwrap();
+        a.on_frame(&[3, 0, b'a', b'b', b'c']).unwrap();
+        a.close();
+
+        let mut frame = [0u8; 32];
+        assert_eq!(a.try_outgoing(&mut frame), Some(Outgoing::Disconnect));
+        a.on_disconnected();
+        assert_eq!(a.try_write(b"x"), Some(Err(CocError::NotConnected)));
+
+        let mut buf = [0u8; 8];
+        assert_eq!(a.try_read(&mut buf), Some(Ok(3)));
+        assert_eq!(a.try_read(&mut buf), Some(Ok(0)));
+    }
+}
@@ -2,8 +2,31 @@
 //!
 //! 提供 WiFi 和 BLE 网络功能支持:
 //! - WiFi STA/AP 模式连接管理
+//! - WiFi 国家码与信道规划 (可用信道、每信道最大发射功率，持久化到键值存储)
+//! - 连接监管: 按 RSSI、信标丢失、DHCP 与网关可达性分级并自动恢复
+//! - 断线自动重连 (指数退避、重新 DHCP，连接状态变化广播)
 //! - TCP/UDP Socket 通信 (基于 smoltcp + embassy-net)
+//! - DHCP 客户端 (租约信息、续租监管、获取失败时静态回退)
+//! - SoftAP DHCP 服务器 (配网门户无需外部路由器)
+//! - WiFi 配网 (SoftAP + 强制门户: DNS 劫持、表单/JSON 提交凭据、持久化后切换 STA)
+//! - DNS 解析缓存 (按 TTL 缓存、失败负缓存、手动清空、命中率统计)
+//! - DNS 解析器 (UDP 查询，可配置服务器与故障转移，A/AAAA 记录，按应答 TTL 缓存)
+//! - HTTP/1.1 服务器 (流式请求体、multipart 文件上传)
+//! - TLS 1.3 服务端 (HTTPS，自签名或预置证书，私钥存于凭据保管库)
+//! - UDP 设备发现 (魔术包请求，JSON 标识响应，无需 mDNS)
+//! - TCP 控制台 (Telnet/原始 TCP 访问 Shell，口令认证)
+//! - UDP 遥测组播 (指标快照差分编码，紧凑二进制报文)
+//! - 离线发件箱 (断网时 MQTT/HTTP 发布落盘，链路恢复后按序补发，TTL 与容量淘汰)
+//! - MQTT 3.1.1 客户端 (QoS 0/1 发布与订阅、保活心跳、收件通道)
+//! - MQTT 主题路由 (`+`/`#` 通配符匹配、按路由分发给异步处理器、负载反序列化与计数)
+//! - 硬件在环测试服务 (TCP/UDP 回显服务器、BLE 回显 GATT 服务)
+//! - WiFi/BLE 事件录制与确定性回放
+//! - 报文抓包 (驱动层镜像收发帧，pcap 格式写入文件或经 UDP 推送)
+//! - 队列深度与缓冲区大小集中调优 (编译期预设、运行时峰值报告)
 //! - BLE 广播和 GATT 服务 (基于 trouble-host 或 esp-wifi/ble)
+//! - BLE 接近检测 (RSSI 平滑、距离估算、区域进入/离开事件)
+//! - BLE 扫描结果流 (声明式广播过滤在协议栈内求值、去重窗口)
+//! - BLE L2CAP 面向连接通道 (信用流控，异步字节流读写，用于 OTA 与文件同步等大块传输)
 //!
 //! # Features
 //!
@@ -11,6 +34,7 @@
 //! - `ble` - 启用 BLE 功能 (使用 trouble-host)
 //! - `ble-esp` - 启用 BLE 功能 (使用 esp-wifi 内置)
 //! - `network` - 启用完整 TCP/IP 网络栈
+//! - `tls` - 启用 TLS 1.3 服务端与 HTTPS 监听
 //! - `coex` - WiFi + BLE 共存模式
 //!
 //! # 示例
@@ -30,28 +54,93 @@
 #![allow(unused_imports)]
 
 pub mod config;
+pub mod outbox;
+pub mod replay;
+pub mod router;
+pub mod tuning;
 
 #[cfg(feature = "wifi")]
 pub mod wifi;
 
+#[cfg(feature = "wifi")]
+pub mod supervisor;
+
+#[cfg(feature = "wifi")]
+pub mod reconnect;
+
+#[cfg(feature = "wifi")]
+pub mod regdomain;
+
 #[cfg(any(feature = "ble", feature = "ble-esp"))]
 pub mod ble;
 
+#[cfg(any(feature = "ble", feature = "ble-esp"))]
+pub mod proximity;
+
+#[cfg(any(feature = "ble", feature = "ble-esp"))]
+pub mod blescan;
+
+#[cfg(any(feature = "ble", feature = "ble-esp"))]
+pub mod l2cap;
+
 #[cfg(feature = "network")]
 pub mod tcp;
 
+#[cfg(feature = "network")]
+pub mod dhcp;
+
+#[cfg(feature = "network")]
+pub mod dhcp_server;
+
+#[cfg(all(feature = "wifi", feature = "network"))]
+pub mod provisioning;
+
+#[cfg(feature = "network")]
+pub mod dns;
+
+#[cfg(feature = "network")]
+pub mod resolver;
+
+#[cfg(feature = "network")]
+pub mod http;
+
+#[cfg(feature = "network")]
+pub mod mqtt;
+
+#[cfg(feature = "tls")]
+pub mod tls;
+
+#[cfg(feature = "network")]
+pub mod console;
+
+#[cfg(feature = "network")]
+pub mod discovery;
+
+#[cfg(feature = "network")]
+pub mod telemetry;
+
+#[cfg(feature = "network")]
+pub mod pcap;
+
+#[cfg(any(feature = "network", feature = "ble"))]
+pub mod testing;
+
 // ===== 公共类型重导出 =====
 
 #[cfg(feature = "wifi")]
-pub use wifi::{WifiController, WifiMode, WifiEvent, WifiError, ScanResult};
+pub use wifi::{WifiController, WifiMode, WifiEvent, WifiEventChannel, WifiError, ScanResult, ScanConfig, ScanType, EnterpriseConfig, EapMethod};
 
 #[cfg(any(feature = "ble", feature = "ble-esp"))]
-pub use ble::{BleController, BleEvent, BleError, AdvertiseConfig};
+pub use ble::{BleController, BleEvent, BleEventChannel, BleError, AdvertiseConfig};
 
 #[cfg(feature = "network")]
 pub use tcp::{TcpClient, TcpServer, UdpSocket, NetworkStack, NetworkError};
 
+#[cfg(feature = "network")]
+pub use mqtt::{MqttClient, MqttError, MqttInbox, QoS};
+
 pub use config::NetworkConfig;
+pub use tuning::{Tuning, TUNING};
 
 // ===== 网络初始化函数 =====
 
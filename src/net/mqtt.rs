@@ -0,0 +1,885 @@
+//! MQTT 3.1.1 客户端
+//!
+//! 基于 TCP 连接的轻量客户端，面向把传感器数据推送到 broker 的场景:
+//! - CONNECT (用户名/口令、遗嘱、clean session)、PUBLISH / SUBSCRIBE / UNSUBSCRIBE
+//! - QoS 0 与 QoS 1 (PUBLISH 等待 PUBACK；收到的 QoS 1 消息自动应答)
+//! - 空闲 `keep_alive` 后发送 PINGREQ，再过一个周期无应答判定连接失效
+//! - 收到的消息复制后放入 `MqttInbox` (`CriticalChannel`)，其他任务 `receive()` 即可；
+//!   通道满时丢弃并计数
+//! - 超过 `MQTT_MAX_PACKET_LEN` 的报文按剩余长度读走丢弃并计数，会话保持
+//!   (QoS 1 消息仍然应答，避免 broker 重发)
+//!
+//! 传输层为任意 `embedded_io_async::Read + Write`，通常是 `TcpClient`。客户端不自动重连:
+//! `run` 返回错误后由应用重新建立 TCP 连接并 `connect`，订阅需重新发起。
+//! 接收状态保存在客户端内，`poll` 可放在 `select` 中与发布请求并行等待。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::mqtt::{ConnectOptions, MqttClient, MqttInbox, QoS};
+//!
+//! static INBOX: MqttInbox = MqttInbox::new();
+//!
+//! let mut tcp = TcpClient::new(&stack, &mut rx, &mut tx);
+//! tcp.connect_host("broker.local", mqtt::MQTT_PORT).await?;
+//!
+//! let mut client = MqttClient::new(tcp, &INBOX);
+//! client.connect(&ConnectOptions::new(&IDENTITY.client_id())).await?;
+//! client.subscribe("dev/cmd/#", QoS::AtLeastOnce).await?;
+//! client.publish("sensors/temp", b"{\"c\":21.5}", QoS::AtLeastOnce).await?;
+//!
+//! loop {
+//!     match select(client.poll(), readings.receive()).await {
+//!         Either::First(result) => result?,
+//!         Either::Second(r) => client.publish("sensors/temp", &r.encode(), QoS::AtMostOnce).await?,
+//!     }
+//! }
+//!
+//! // 其他任务
+//! let msg = INBOX.receive().await;
+//! router.dispatch(&mut app, &msg.topic, &msg.payload).await;
+//! ```
+
+use core::fmt;
+
+use embassy_time::{with_deadline, with_timeout, Duration, Instant};
+use embedded_io_async::{Read, Write};
+use heapless::{String, Vec};
+
+use super::config::{
+    MQTT_ACK_TIMEOUT_SECS, MQTT_INBOX_DEPTH, MQTT_KEEP_ALIVE_SECS, MQTT_MAX_PACKET_LEN, MQTT_MAX_PAYLOAD_LEN,
+    MQTT_MAX_TOPIC_LEN,
+};
+use super::router::validate_filter;
+use crate::sync::primitives::CriticalChannel;
+use crate::util::log::*;
+
+/// MQTT 默认端口
+pub const MQTT_PORT: u16 = 1883;
+
+/// 协议级别 (3.1.1)
+const PROTOCOL_LEVEL: u8 = 4;
+
+/// 固定头最大长度 (类型 + 4 字节剩余长度)
+const MAX_HEADER_LEN: usize = 5;
+
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH: u8 = 0x30;
+const PUBACK: u8 = 0x40;
+const SUBSCRIBE: u8 = 0x82;
+const SUBACK: u8 = 0x90;
+const UNSUBSCRIBE: u8 = 0xA2;
+const UNSUBACK: u8 = 0xB0;
+const PINGREQ: u8 = 0xC0;
+const PINGRESP: u8 = 0xD0;
+const DISCONNECT: u8 = 0xE0;
+
+/// 收件通道
+pub type MqttInbox = CriticalChannel<InboundMessage, MQTT_INBOX_DEPTH>;
+
+// ===== 错误类型 =====
+
+/// MQTT 错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MqttError {
+    /// 传输层读写失败或连接关闭
+    Io,
+    /// 等待应答超时
+    Timeout,
+    /// broker 拒绝连接 (CONNACK 返回码)
+    ConnectionRefused(u8),
+    /// 报文格式错误或不符合协议
+    Protocol,
+    /// 报文超过缓冲区
+    PacketTooLarge,
+    /// 主题或过滤器无效
+    InvalidTopic,
+    /// 未连接
+    NotConnected,
+    /// broker 拒绝订阅
+    SubscribeRejected,
+    /// 保活超时 (PINGREQ 无应答)
+    KeepAliveTimeout,
+}
+
+impl fmt::Display for MqttError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io => write!(f, "Transport I/O error"),
+            Self::Timeout => write!(f, "Acknowledgement timeout"),
+            Self::ConnectionRefused(code) => write!(f, "Connection refused (code {})", code),
+            Self::Protocol => write!(f, "Protocol violation"),
+            Self::PacketTooLarge => write!(f, "Packet too large"),
+            Self::InvalidTopic => write!(f, "Invalid topic"),
+            Self::NotConnected => write!(f, "Not connected"),
+            Self::SubscribeRejected => write!(f, "Subscription rejected"),
+            Self::KeepAliveTimeout => write!(f, "Keep-alive timeout"),
+        }
+    }
+}
+
+// ===== 连接参数 =====
+
+/// 服务质量
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum QoS {
+    /// 至多一次
+    AtMostOnce = 0,
+    /// 至少一次
+    AtLeastOnce = 1,
+}
+
+impl QoS {
+    fn from_bits(bits: u8) -> Result<Self, MqttError> {
+        match bits {
+            0 => Ok(Self::AtMostOnce),
+            1 => Ok(Self::AtLeastOnce),
+            _ => Err(MqttError::Protocol),
+        }
+    }
+}
+
+/// 遗嘱消息
+#[derive(Debug, Clone, Copy)]
+pub struct Will<'a> {
+    /// 主题
+    pub topic: &'a str,
+    /// 负载
+    pub payload: &'a [u8],
+    /// 服务质量
+    pub qos: QoS,
+    /// 保留
+    pub retain: bool,
+}
+
+/// 连接参数
+#[derive(Debug, Clone, Copy)]
+pub struct ConnectOptions<'a> {
+    /// 客户端标识 (通常取 `IDENTITY.client_id()`)
+    pub client_id: &'a str,
+    /// 用户名
+    pub username: Option<&'a str>,
+    /// 口令
+    pub password: Option<&'a [u8]>,
+    /// 保活间隔 (秒，0 表示关闭)
+    pub keep_alive_secs: u16,
+    /// 清除会话
+    pub clean_session: bool,
+    /// 遗嘱
+    pub will: Option<Will<'a>>,
+}
+
+impl<'a> ConnectOptions<'a> {
+    /// 默认参数: clean session，`MQTT_KEEP_ALIVE_SECS` 保活
+    pub const fn new(client_id: &'a str) -> Self {
+        Self {
+            client_id,
+            username: None,
+            password: None,
+            keep_alive_secs: MQTT_KEEP_ALIVE_SECS,
+            clean_session: true,
+            will: None,
+        }
+    }
+
+    /// 设置用户名与口令
+    pub const fn with_credentials(mut self, username: &'a str, password: &'a [u8]) -> Self {
+        self.username = Some(username);
+        self.password = Some(password);
+        self
+    }
+
+    /// 设置保活间隔
+    pub const fn with_keep_alive(mut self, secs: u16) -> Self {
+        self.keep_alive_secs = secs;
+        self
+    }
+
+    /// 设置是否清除会话
+    pub const fn with_clean_session(mut self, clean: bool) -> Self {
+        self.clean_session = clean;
+        self
+    }
+
+    /// 设置遗嘱
+    pub const fn with_will(mut self, will: Will<'a>) -> Self {
+        self.will = Some(will);
+        self
+    }
+}
+
+/// 收到的发布消息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InboundMessage {
+    /// 主题
+    pub topic: String<MQTT_MAX_TOPIC_LEN>,
+    /// 负载
+    pub payload: Vec<u8, MQTT_MAX_PAYLOAD_LEN>,
+    /// 服务质量
+    pub qos: QoS,
+    /// 保留消息
+    pub retain: bool,
+}
+
+impl InboundMessage {
+    /// 负载文本
+    pub fn text(&self) -> Option<&str> {
+        core::str::from_utf8(&self.payload).ok()
+    }
+}
+
+// ===== 报文编解码 =====
+
+/// 报文写入器 (先写可变头与负载，`finish` 时补固定头)
+struct PacketWriter<'b> {
+    buf: &'b mut [u8],
+    len: usize,
+}
+
+impl<'b> PacketWriter<'b> {
+    fn new(buf: &'b mut [u8]) -> Self {
+        Self { buf, len: MAX_HEADER_LEN }
+    }
+
+    fn bytes(&mut self, data: &[u8]) -> Result<(), MqttError> {
+        let end = self.len + data.len();
+        self.buf.get_mut(self.len..end).ok_or(MqttError::PacketTooLarge)?.copy_from_slice(data);
+        self.len = end;
+        Ok(())
+    }
+
+    fn u16(&mut self, value: u16) -> Result<(), MqttError> {
+        self.bytes(&value.to_be_bytes())
+    }
+
+    /// 带 2 字节长度前缀的字符串/二进制
+    fn prefixed(&mut self, data: &[u8]) -> Result<(), MqttError> {
+        let len = u16::try_from(data.len()).map_err(|_| MqttError::PacketTooLarge)?;
+        self.u16(len)?;
+        self.bytes(data)
+    }
+
+    /// 补固定头并移到缓冲区开头，返回报文长度
+    fn finish(self, kind: u8) -> Result<usize, MqttError> {
+        let remaining = self.len - MAX_HEADER_LEN;
+        let mut header = [kind, 0, 0, 0, 0];
+        let mut n = 1;
+        let mut value = remaining;
+        loop {
+            let mut byte = (value % 128) as u8;
+            value /= 128;
+            if value > 0 {
+                byte |= 0x80;
+            }
+            header[n] = byte;
+            n += 1;
+            if value == 0 {
+                break;
+            }
+            if n == MAX_HEADER_LEN {
+                return Err(MqttError::PacketTooLarge);
+            }
+        }
+        self.buf.copy_within(MAX_HEADER_LEN..self.len, n);
+        self.buf[..n].copy_from_slice(&header[..n]);
+        Ok(n + remaining)
+    }
+}
+
+/// 编码 CONNECT
+pub fn encode_connect(options: &ConnectOptions<'_>, buf: &mut [u8]) -> Result<usize, MqttError> {
+    let mut flags = 0u8;
+    if options.clean_session {
+        flags |= 0x02;
+    }
+    if let Some(will) = &options.will {
+        flags |= 0x04 | ((will.qos as u8) << 3) | if will.retain { 0x20 } else { 0 };
+    }
+    if options.password.is_some() {
+        flags |= 0x40;
+    }
+    if options.username.is_some() {
+        flags |= 0x80;
+    }
+
+    let mut w = PacketWriter::new(buf);
+    w.prefixed(b"MQTT")?;
+    w.bytes(&[PROTOCOL_LEVEL, flags])?;
+    w.u16(options.keep_alive_secs)?;
+    w.prefixed(options.client_id.as_bytes())?;
+    if let Some(will) = &options.will {
+        w.prefixed(will.topic.as_bytes())?;
+        w.prefixed(will.payload)?;
+    }
+    if let Some(username) = options.username {
+        w.prefixed(username.as_bytes())?;
+    }
+    if let Some(password) = options.password {
+        w.prefixed(password)?;
+    }
+    w.finish(CONNECT)
+}
+
+/// 编码 PUBLISH (QoS 0 时忽略 `packet_id`)
+pub fn encode_publish(
+    topic: &str,
+    payload: &[u8],
+    qos: QoS,
+    retain: bool,
+    packet_id: u16,
+    buf: &mut [u8],
+) -> Result<usize, MqttError> {
+    validate_topic(topic)?;
+    let mut w = PacketWriter::new(buf);
+    w.prefixed(topic.as_bytes())?;
+    if qos != QoS::AtMostOnce {
+        w.u16(packet_id)?;
+    }
+    w.bytes(payload)?;
+    w.finish(PUBLISH | ((qos as u8) << 1) | retain as u8)
+}
+
+/// 编码 SUBSCRIBE (单个过滤器)
+pub fn encode_subscribe(filter: &str, qos: QoS, packet_id: u16, buf: &mut [u8]) -> Result<usize, MqttError> {
+    validate_filter(filter).map_err(|_| MqttError::InvalidTopic)?;
+    let mut w = PacketWriter::new(buf);
+    w.u16(packet_id)?;
+    w.prefixed(filter.as_bytes())?;
+    w.bytes(&[qos as u8])?;
+    w.finish(SUBSCRIBE)
+}
+
+/// 编码 UNSUBSCRIBE (单个过滤器)
+pub fn encode_unsubscribe(filter: &str, packet_id: u16, buf: &mut [u8]) -> Result<usize, MqttError> {
+    validate_filter(filter).map_err(|_| MqttError::InvalidTopic)?;
+    let mut w = PacketWriter::new(buf);
+    w.u16(packet_id)?;
+    w.prefixed(filter.as_bytes())?;
+    w.finish(UNSUBSCRIBE)
+}
+
+/// 发布主题: 非空、无通配符、无空字符
+fn validate_topic(topic: &str) -> Result<(), MqttError> {
+    if topic.is_empty() || topic.len() > u16::MAX as usize || topic.contains(['+', '#', '\0']) {
+        return Err(MqttError::InvalidTopic);
+    }
+    Ok(())
+}
+
+/// 完整报文的长度 (数据不足时返回 `None`)
+pub fn packet_len(buf: &[u8]) -> Result<Option<usize>, MqttError> {
+    Ok(fixed_header(buf)?.map(|(header, remaining)| header + remaining).filter(|&total| buf.len() >= total))
+}
+
+/// 固定头长度与剩余长度 (固定头不完整时返回 `None`)
+fn fixed_header(buf: &[u8]) -> Result<Option<(usize, usize)>, MqttError> {
+    let mut remaining = 0usize;
+    for i in 0..4 {
+        let Some(&byte) = buf.get(1 + i) else {
+            return Ok(None);
+        };
+        remaining |= ((byte & 0x7F) as usize) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok(Some((2 + i, remaining)));
+        }
+    }
+    Err(MqttError::Protocol)
+}
+
+/// 解码后的报文
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Packet<'a> {
+    /// 连接应答
+    ConnAck {
+        /// 会话已存在
+        session_present: bool,
+        /// 返回码 (0 为接受)
+        code: u8,
+    },
+    /// 发布
+    Publish {
+        /// 主题
+        topic: &'a str,
+        /// 负载
+        payload: &'a [u8],
+        /// 服务质量
+        qos: QoS,
+        /// 保留消息
+        retain: bool,
+        /// 报文标识 (QoS 0 为 0)
+        packet_id: u16,
+    },
+    /// 发布应答
+    PubAck(u16),
+    /// 订阅应答 (标识, 返回码)
+    SubAck(u16, u8),
+    /// 取消订阅应答
+    UnsubAck(u16),
+    /// 心跳应答
+    PingResp,
+}
+
+impl<'a> Packet<'a> {
+    /// 解码一个完整报文 (长度由 `packet_len` 给出)
+    pub fn decode(packet: &'a [u8]) -> Result<Self, MqttError> {
+        let header = *packet.first().ok_or(MqttError::Protocol)?;
+        let offset = (1..packet.len().min(MAX_HEADER_LEN))
+            .find(|&i| packet[i] & 0x80 == 0)
+            .ok_or(MqttError::Protocol)?
+            + 1;
+        let body = &packet[offset..];
+        let id = || -> Result<u16, MqttError> {
+            let bytes = body.get(..2).ok_or(MqttError::Protocol)?;
+            Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+        };
+
+        match header & 0xF0 {
+            CONNACK if body.len() == 2 => Ok(Self::ConnAck { session_present: body[0] & 1 != 0, code: body[1] }),
+            PUBLISH => {
+                let qos = QoS::from_bits((header >> 1) & 0x03)?;
+                let topic_len = id()? as usize;
+                let topic = body.get(2..2 + topic_len).ok_or(MqttError::Protocol)?;
+                let topic = core::str::from_utf8(topic).map_err(|_| MqttError::Protocol)?;
+                let mut rest = &body[2 + topic_len..];
+                let mut packet_id = 0;
+                if qos != QoS::AtMostOnce {
+                    let bytes = rest.get(..2).ok_or(MqttError::Protocol)?;
+                    packet_id = u16::from_be_bytes([bytes[0], bytes[1]]);
+                    rest = &rest[2..];
+                }
+                Ok(Self::Publish { topic, payload: rest, qos, retain: header & 1 != 0, packet_id })
+            }
+            PUBACK if body.len() == 2 => Ok(Self::PubAck(id()?)),
+            SUBACK if body.len() == 3 => Ok(Self::SubAck(id()?, body[2])),
+            UNSUBACK if body.len() == 2 => Ok(Self::UnsubAck(id()?)),
+            PINGRESP if body.is_empty() => Ok(Self::PingResp),
+            _ => Err(MqttError::Protocol),
+        }
+    }
+}
+
+// ===== 客户端 =====
+
+/// 客户端统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MqttStats {
+    /// 已发布的消息
+    pub published: u32,
+    /// 收到的消息
+    pub received: u32,
+    /// 收件通道满或超长而丢弃的消息
+    pub dropped: u32,
+    /// 发送的 PINGREQ
+    pub pings: u32,
+}
+
+/// 等待应答的请求 (附报文标识)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ack {
+    Connect,
+    Publish(u16),
+    Subscribe(u16),
+    Unsubscribe(u16),
+}
+
+/// MQTT 客户端
+pub struct MqttClient<'a, T> {
+    transport: T,
+    inbox: &'a MqttInbox,
+    connected: bool,
+    keep_alive: Option<Duration>,
+    next_id: u16,
+    last_tx: Instant,
+    ping_sent: Option<Instant>,
+    rx: [u8; MQTT_MAX_PACKET_LEN],
+    rx_len: usize,
+    tx: [u8; MQTT_MAX_PACKET_LEN],
+    stats: MqttStats,
+}
+
+impl<'a, T: Read + Write> MqttClient<'a, T> {
+    /// 创建客户端 (传输层须已建立连接)
+    pub fn new(transport: T, inbox: &'a MqttInbox) -> Self {
+        Self {
+            transport,
+            inbox,
+            connected: false,
+            keep_alive: None,
+            next_id: 0,
+            last_tx: Instant::now(),
+            ping_sent: None,
+            rx: [0; MQTT_MAX_PACKET_LEN],
+            rx_len: 0,
+            tx: [0; MQTT_MAX_PACKET_LEN],
+            stats: MqttStats::default(),
+        }
+    }
+
+    /// 是否已连接
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    /// 统计
+    pub fn stats(&self) -> MqttStats {
+        self.stats
+    }
+
+    /// 释放传输层
+    pub fn release(self) -> T {
+        self.transport
+    }
+
+    /// 发送 CONNECT 并等待 CONNACK
+    ///
+    /// # 返回
+    /// broker 是否保留了之前的会话
+    pub async fn connect(&mut self, options: &ConnectOptions<'_>) -> Result<bool, MqttError> {
+        self.rx_len = 0;
+        self.ping_sent = None;
+        self.keep_alive = (options.keep_alive_secs > 0).then(|| Duration::from_secs(options.keep_alive_secs as u64));
+        let len = encode_connect(options, &mut self.tx)?;
+        self.send(len).await?;
+
+        let code = self.wait_ack(Ack::Connect).await?;
+        if code & 0x7F != 0 {
+            log_warn!("MQTT connection refused: {}", code & 0x7F);
+            return Err(MqttError::ConnectionRefused(code & 0x7F));
+        }
+        self.connected = true;
+        log_info!("MQTT connected as {}", options.client_id);
+        Ok(code & 0x80 != 0)
+    }
+
+    /// 发布 (QoS 1 等待 PUBACK)
+    pub async fn publish(&mut self, topic: &str, payload: &[u8], qos: QoS) -> Result<(), MqttError> {
+        self.publish_with(topic, payload, qos, false).await
+    }
+
+    /// 发布保留消息
+    pub async fn publish_retained(&mut self, topic: &str, payload: &[u8], qos: QoS) -> Result<(), MqttError> {
+        self.publish_with(topic, payload, qos, true).await
+    }
+
+    async fn publish_with(&mut self, topic: &str, payload: &[u8], qos: QoS, retain: bool) -> Result<(), MqttError> {
+        self.ensure_connected()?;
+        let id = self.packet_id();
+        let len = encode_publish(topic, payload, qos, retain, id, &mut self.tx)?;
+        self.send(len).await?;
+        if qos == QoS::AtLeastOnce {
+            self.wait_ack(Ack::Publish(id)).await?;
+        }
+        self.stats.published += 1;
+        Ok(())
+    }
+
+    /// 订阅，返回 broker 授予的服务质量
+    pub async fn subscribe(&mut self, filter: &str, qos: QoS) -> Result<QoS, MqttError> {
+        self.ensure_connected()?;
+        let id = self.packet_id();
+        let len = encode_subscribe(filter, qos, id, &mut self.tx)?;
+        self.send(len).await?;
+        match self.wait_ack(Ack::Subscribe(id)).await? {
+            0x80 => Err(MqttError::SubscribeRejected),
+            code => QoS::from_bits(code),
+        }
+    }
+
+    /// 取消订阅
+    pub async fn unsubscribe(&mut self, filter: &str) -> Result<(), MqttError> {
+        self.ensure_connected()?;
+        let id = self.packet_id();
+        let len = encode_unsubscribe(filter, id, &mut self.tx)?;
+        self.send(len).await?;
+        self.wait_ack(Ack::Unsubscribe(id)).await.map(|_| ())
+    }
+
+    /// 发送 DISCONNECT (遗嘱不会发布)
+    pub async fn disconnect(&mut self) -> Result<(), MqttError> {
+        if !self.connected {
+            return Ok(());
+        }
+        self.connected = false;
+        self.tx[..2].copy_from_slice(&[DISCONNECT, 0]);
+        self.send(2).await
+    }
+
+    /// 处理一个收到的报文或一次保活
+    ///
+    /// 空闲达到保活间隔时发送 PINGREQ；再过一个间隔无应答返回 `KeepAliveTimeout`。
+    /// 出错后连接视为断开。
+    pub async fn poll(&mut self) -> Result<(), MqttError> {
+        self.ensure_connected()?;
+        let result = match self.keep_alive_deadline() {
+            Some(deadline) => match with_deadline(deadline, self.receive()).await {
+                Ok(result) => result.map(|_| ()),
+                Err(_) => self.keep_alive_tick().await,
+            },
+            None => self.receive().await.map(|_| ()),
+        };
+        if result.is_err() {
+            self.connected = false;
+        }
+        result
+    }
+
+    /// 持续处理收到的消息与保活，直到连接出错
+    pub async fn run(&mut self) -> MqttError {
+        loop {
+            if let Err(e) = self.poll().await {
+                log_warn!("MQTT connection lost: {}", e);
+                return e;
+            }
+        }
+    }
+
+    fn ensure_connected(&self) -> Result<(), MqttError> {
+        if self.connected {
+            Ok(())
+        } else {
+            Err(MqttError::NotConnected)
+        }
+    }
+
+    fn packet_id(&mut self) -> u16 {
+        // 报文标识不能为 0
+        self.next_id = self.next_id.wrapping_add(1).max(1);
+        self.next_id
+    }
+
+    fn keep_alive_deadline(&self) -> Option<Instant> {
+        let interval = self.keep_alive?;
+        Some(match self.ping_sent {
+            Some(sent) => sent + interval,
+            None => self.last_tx + interval,
+        })
+    }
+
+    async fn keep_alive_tick(&mut self) -> Result<(), MqttError> {
+        if self.ping_sent.is_some() {
+            return Err(MqttError::KeepAliveTimeout);
+        }
+        self.tx[..2].copy_from_slice(&[PINGREQ, 0]);
+        self.send(2).await?;
+        self.ping_sent = Some(Instant::now());
+        self.stats.pings += 1;
+        Ok(())
+    }
+
+    async fn send(&mut self, len: usize) -> Result<(), MqttError> {
+        self.transport.write_all(&self.tx[..len]).await.map_err(|_| MqttError::Io)?;
+        self.transport.flush().await.map_err(|_| MqttError::Io)?;
+        self.last_tx = Instant::now();
+        Ok(())
+    }
+
+    /// 等待指定应答，期间照常处理收到的消息
+    async fn wait_ack(&mut self, ack: Ack) -> Result<u8, MqttError> {
+        let timeout = Duration::from_secs(MQTT_ACK_TIMEOUT_SECS as u64);
+        let wait = async {
+            loop {
+                match self.receive().await? {
+                    Some((got, code)) if got == ack => return Ok(code),
+                    _ => {}
+                }
+            }
+        };
+        let result = with_timeout(timeout, wait).await.unwrap_or(Err(MqttError::Timeout));
+        if result.is_err() {
+            self.connected = false;
+        }
+        result
+    }
+
+    /// 读取并处理一个报文，是应答时返回 (应答, 返回码)
+    async fn receive(&mut self) -> Result<Option<(Ack, u8)>, MqttError> {
+        let len = self.read_packet().await?;
+        let result = self.handle(len).await;
+        self.rx.copy_within(len..self.rx_len, 0);
+        self.rx_len -= len;
+        result
+    }
+
+    /// 读取直到缓冲区中有完整报文，返回其长度
+    async fn read_packet(&mut self) -> Result<usize, MqttError> {
+        loop {
+            if let Some(len) = packet_len(&self.rx[..self.rx_len])? {
+                return Ok(len);
+            }
+            if self.rx_len == self.rx.len() {
+                self.discard_packet().await?;
+                continue;
+            }
+            let n = self.transport.read(&mut self.rx[self.rx_len..]).await.map_err(|_| MqttError::Io)?;
+            if n == 0 {
+                return Err(MqttError::Io);
+            }
+            self.rx_len += n;
+        }
+    }
+
+    /// 丢弃填满接收缓冲区仍不完整的报文: 按剩余长度读走其余部分，会话保持
+    async fn discard_packet(&mut self) -> Result<(), MqttError> {
+        let (header, remaining) = fixed_header(&self.rx[..self.rx_len])?.ok_or(MqttError::Protocol)?;
+        let total = header + remaining;
+
+        // QoS 1 的 PUBLISH 仍需应答，否则 broker 会反复重发同一条超长消息
+        let head = &self.rx[..self.rx_len];
+        let puback = (head[0] & 0xF6 == PUBLISH | 0x02)
+            .then(|| {
+                let topic_len = u16::from_be_bytes([*head.get(header)?, *head.get(header + 1)?]) as usize;
+                let id = head.get(header + 2 + topic_len..header + 4 + topic_len)?;
+                Some(u16::from_be_bytes([id[0], id[1]]))
+            })
+            .flatten();
+
+        let mut left = total - self.rx_len;
+        self.rx_len = 0;
+        while left > 0 {
+            let chunk = left.min(self.rx.len());
+            let n = self.transport.read(&mut self.rx[..chunk]).await.map_err(|_| MqttError::Io)?;
+            if n == 0 {
+                return Err(MqttError::Io);
+            }
+            left -= n;
+        }
+        self.stats.dropped += 1;
+        log_warn!("MQTT packet of {} bytes dropped (limit {})", total, MQTT_MAX_PACKET_LEN);
+
+        if let Some(id) = puback {
+            self.tx[..4].copy_from_slice(&[PUBACK, 2, (id >> 8) as u8, id as u8]);
+            self.send(4).await?;
+        }
+        Ok(())
+    }
+
+    /// 处理缓冲区开头的报文
+    async fn handle(&mut self, len: usize) -> Result<Option<(Ack, u8)>, MqttError> {
+        let ack = match Packet::decode(&self.rx[..len])? {
+            Packet::ConnAck { session_present, code } => Some((Ack::Connect, code | (session_present as u8) << 7)),
+            Packet::PubAck(id) => Some((Ack::Publish(id), 0)),
+            Packet::SubAck(id, code) => Some((Ack::Subscribe(id), code)),
+            Packet::UnsubAck(id) => Some((Ack::Unsubscribe(id), 0)),
+            Packet::PingResp => {
+                self.ping_sent = None;
+                None
+            }
+            Packet::Publish { topic, payload, qos, retain, packet_id } => {
+                self.stats.received += 1;
+                let message = String::try_from(topic).ok().zip(Vec::from_slice(payload).ok());
+                let accepted = message
+                    .map(|(topic, payload)| self.inbox.try_send(InboundMessage { topic, payload, qos, retain }).is_ok())
+                    .unwrap_or(false);
+                if !accepted {
+                    self.stats.dropped += 1;
+                    log_warn!("MQTT message on {} dropped", topic);
+                }
+                if qos == QoS::AtLeastOnce {
+                    self.tx[..4].copy_from_slice(&[PUBACK, 2, (packet_id >> 8) as u8, packet_id as u8]);
+                    self.send(4).await?;
+                }
+                None
+            }
+        };
+        Ok(ack)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_packets() {
+        let mut buf = [0u8; 128];
+        let options = ConnectOptions::new("dev1").with_keep_alive(30).with_credentials("u", b"p");
+        let len = encode_connect(&options, &mut buf).unwrap();
+        let expected: &[u8] = &[
+            0x10, 22, 0, 4, b'M', b'Q', b'T', b'T', 4, 0xC2, 0, 30, 0, 4, b'd', b'e', b'v', b'1', 0, 1, b'u',
+            0, 1, b'p',
+        ];
+        assert_eq!(&buf[..len], expected);
+
+        let len = encode_publish("a/b", b"hi", QoS::AtLeastOnce, true, 7, &mut buf).unwrap();
+        assert_eq!(&buf[..len], &[0x33, 9, 0, 3, b'a', b'/', b'b', 0, 7, b'h', b'i']);
+        assert_eq!(encode_publish("a/+", b"", QoS::AtMostOnce, false, 0, &mut buf), Err(MqttError::InvalidTopic));
+
+        // 剩余长度 ≥ 128 使用两字节
+        let payload = [0x55u8; 200];
+        let len = encode_publish("t", &payload, QoS::AtMostOnce, false, 0, &mut [0u8; 256][..]).unwrap();
+        assert_eq!(len, 3 + 3 + 200);
+        assert_eq!(encode_publish("t", &payload, QoS::AtMostOnce, false, 0, &mut buf), Err(MqttError::PacketTooLarge));
+
+        let len = encode_subscribe("dev/+/cmd", QoS::AtLeastOnce, 2, &mut buf).unwrap();
+        assert_eq!(&buf[..5], &[SUBSCRIBE, 14, 0, 2, 0]);
+        assert_eq!(buf[len - 1], 1);
+    }
+
+    #[test]
+    fn test_decode_packets() {
+        assert_eq!(packet_len(&[0x30]), Ok(None));
+        assert_eq!(packet_len(&[0x30, 0x80]), Ok(None));
+        assert_eq!(packet_len(&[0x30, 0x80, 0x01]), Ok(None));
+        assert_eq!(packet_len(&[0xD0, 0, 0x30]), Ok(Some(2)));
+        assert_eq!(packet_len(&[0x30, 0xFF, 0xFF, 0xFF, 0xFF]), Err(MqttError::Protocol));
+
+        let publish = [0x32, 9, 0, 3, b'a', b'/', b'b', 0, 9, b'o', b'n'];
+        assert_eq!(
+            Packet::decode(&publish),
+            Ok(Packet::Publish { topic: "a/b", payload: b"on", qos: QoS::AtLeastOnce, retain: false, packet_id: 9 })
+        );
+        assert_eq!(Packet::decode(&[0x20, 2, 1, 0]), Ok(Packet::ConnAck { session_present: true, code: 0 }));
+        assert_eq!(Packet::decode(&[0x90, 3, 0, 2, 0x80]), Ok(Packet::SubAck(2, 0x80)));
+        // QoS 2 不支持
+        assert_eq!(Packet::decode(&[0x34, 5, 0, 1, b'a', 0, 1]), Err(MqttError::Protocol));
+    }
+
+    /// 按脚本提供输入并记录输出的传输层
+    struct Script<'a> {
+        input: &'a [u8],
+        output: Vec<u8, 64>,
+    }
+
+    impl embedded_io_async::ErrorType for Script<'_> {
+        type Error = core::convert::Infallible;
+    }
+
+    impl Read for Script<'_> {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            let n = buf.len().min(self.input.len()).min(300);
+            buf[..n].copy_from_slice(&self.input[..n]);
+            self.input = &self.input[n..];
+            Ok(n)
+        }
+    }
+
+    impl Write for Script<'_> {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            let _ = self.output.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+    }
+
+    #[test]
+    fn test_oversized_packet_dropped() {
+        // 超长 QoS 1 PUBLISH (剩余长度 1300 = 0x94 0x0A)，随后一条正常的 QoS 0 PUBLISH
+        let mut input = [0u8; 1303 + 8];
+        input[..7].copy_from_slice(&[0x32, 0x94, 0x0A, 0, 1, b't', 0]);
+        input[7] = 7;
+        input[1303..].copy_from_slice(&[0x30, 6, 0, 1, b'x', b'o', b'k', b'!']);
+
+        let inbox = MqttInbox::new();
+        let mut client = MqttClient::new(Script { input: &input, output: Vec::new() }, &inbox);
+        client.connected = true;
+        embassy_futures::block_on(client.poll()).unwrap();
+
+        assert!(client.is_connected());
+        assert_eq!(client.stats().dropped, 1);
+        assert_eq!(client.stats().received, 1);
+        let message = inbox.try_receive().unwrap();
+        assert_eq!((message.topic.as_str(), message.payload.as_slice()), ("x", &b"ok!"[..]));
+        // 超长消息仍被应答
+        assert_eq!(client.release().output.as_slice(), &[PUBACK, 2, 0, 7]);
+    }
+}
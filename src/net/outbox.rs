@@ -0,0 +1,612 @@
+//! 离线发件箱 (存储转发)
+//!
+//! 弱信号场景下的 MQTT/HTTP 发布队列，把断网处理从应用代码收归到库中:
+//! - 链路不健康时发布的消息写入文件系统 (每条消息一个文件，掉电不丢)
+//! - 总字节数与条数有上限，超出时按 FIFO 淘汰最旧的消息
+//! - 每条消息带 TTL，过期消息在发送前丢弃
+//! - 订阅系统事件总线，连接监管器报告链路恢复 (`good`/`degraded`) 后自动按序补发
+//!
+//! 发送动作由应用实现 `Sink` (通常包装 MQTT 客户端或 HTTP POST)。
+//!
+//! # TTL 与墙上时钟
+//!
+//! 消息记录创建时的墙上时间，以便重启后仍能判断过期；创建时或发送时
+//! 墙上时钟未同步 (`sys::time::TIME`) 的消息不判定过期。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::outbox::{Outbox, OutboxConfig, SendError, Sink};
+//!
+//! struct Mqtt(MqttClient<'static, TcpClient<'static>>);
+//!
+//! impl Sink for Mqtt {
+//!     async fn send(&mut self, topic: &str, payload: &[u8]) -> Result<(), SendError> {
+//!         self.0.publish(topic, payload, QoS::AtLeastOnce).await.map_err(|_| SendError)
+//!     }
+//! }
+//!
+//! static OUTBOX: StaticCell<Outbox<'static>> = StaticCell::new();
+//! let outbox = OUTBOX.init(Outbox::open(&FS, OutboxConfig::new())?);
+//!
+//! // 发送任务: 监听链路状态并补发
+//! spawner.spawn(outbox_task(outbox, Mqtt(client)))?;
+//!
+//! // 持有客户端的任务: 在线且队列为空时直接发送，否则落盘
+//! outbox.send(&mut mqtt, "sensors/temp", b"{\"c\":21.5}", None).await?;
+//!
+//! // 其他任务: 落盘并唤醒发送任务
+//! outbox.publish("events/door", b"open", Some(Duration::from_secs(600)))?;
+//! ```
+
+use core::cell::Cell;
+use core::fmt::{self, Write as _};
+
+use embassy_futures::select::{select3, Either3};
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_time::{Duration, Timer};
+use heapless::String;
+
+use crate::fs::littlefs::{FileSystem, FsError, OpenOptions};
+use crate::sync::bus::{self, SystemEvent};
+use crate::sync::primitives::CriticalSignal;
+use crate::sys::time::{WallTime, TIME};
+use crate::util::log::*;
+
+/// 默认存储目录
+pub const OUTBOX_ROOT: &str = "/outbox";
+
+/// 主题最大长度
+pub const MAX_TOPIC_LEN: usize = 64;
+
+/// 单条消息负载上限
+pub const MAX_PAYLOAD: usize = 1024;
+
+/// 记录头长度
+pub const HEADER_LEN: usize = 16;
+
+/// 单条记录最大长度 (发送缓冲区大小)
+pub const MAX_RECORD: usize = HEADER_LEN + MAX_TOPIC_LEN + MAX_PAYLOAD;
+
+/// 记录魔数与版本
+const MAGIC: [u8; 2] = *b"OB";
+const VERSION: u8 = 1;
+
+/// 消息文件路径
+type RecordPath = String<48>;
+
+// ===== 错误类型 =====
+
+/// 发件箱错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutboxError {
+    /// 文件系统错误
+    Fs(FsError),
+    /// 主题过长或为空
+    InvalidTopic,
+    /// 负载过大
+    TooLarge,
+    /// 记录损坏
+    Corrupt,
+}
+
+impl fmt::Display for OutboxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fs(e) => write!(f, "Filesystem error: {}", e),
+            Self::InvalidTopic => write!(f, "Invalid topic"),
+            Self::TooLarge => write!(f, "Payload too large"),
+            Self::Corrupt => write!(f, "Outbox record corrupt"),
+        }
+    }
+}
+
+impl From<FsError> for OutboxError {
+    fn from(e: FsError) -> Self {
+        Self::Fs(e)
+    }
+}
+
+/// 发布结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    /// 已直接发送
+    Sent,
+    /// 已落盘 (消息序号)
+    Queued(u32),
+}
+
+/// 发送失败 (链路或服务端错误，消息保留待重试)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SendError;
+
+/// 消息发送端
+#[allow(async_fn_in_trait)]
+pub trait Sink {
+    /// 发送一条消息
+    async fn send(&mut self, topic: &str, payload: &[u8]) -> Result<(), SendError>;
+}
+
+// ===== 记录格式 =====
+
+/// 消息记录
+///
+/// 格式: 魔数 "OB" | 版本 u8 | 主题长度 u8 | TTL 秒 u32 | 创建时间 Unix 秒 u64 (0 = 未知) |
+/// 主题 | 负载，整数小端
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Record<'a> {
+    /// 主题
+    pub topic: &'a str,
+    /// 负载
+    pub payload: &'a [u8],
+    /// 存活时间 (`None` 为永不过期)
+    pub ttl: Option<Duration>,
+    /// 创建时的墙上时间
+    pub created: Option<WallTime>,
+}
+
+impl<'a> Record<'a> {
+    /// 编码到缓冲区，返回长度
+    pub fn encode(&self, buf: &mut [u8]) -> Result<usize, OutboxError> {
+        if self.topic.is_empty() || self.topic.len() > MAX_TOPIC_LEN {
+            return Err(OutboxError::InvalidTopic);
+        }
+        if self.payload.len() > MAX_PAYLOAD {
+            return Err(OutboxError::TooLarge);
+        }
+        let len = HEADER_LEN + self.topic.len() + self.payload.len();
+        let buf = buf.get_mut(..len).ok_or(OutboxError::TooLarge)?;
+        let ttl = self.ttl.map_or(0, |t| t.as_secs().clamp(1, u32::MAX as u64) as u32);
+        buf[..2].copy_from_slice(&MAGIC);
+        buf[2] = VERSION;
+        buf[3] = self.topic.len() as u8;
+        buf[4..8].copy_from_slice(&ttl.to_le_bytes());
+        buf[8..16].copy_from_slice(&self.created.map_or(0, |t| t.unix_secs()).to_le_bytes());
+        let (topic, payload) = buf[HEADER_LEN..].split_at_mut(self.topic.len());
+        topic.copy_from_slice(self.topic.as_bytes());
+        payload.copy_from_slice(self.payload);
+        Ok(len)
+    }
+
+    /// 解码
+    pub fn decode(buf: &'a [u8]) -> Result<Self, OutboxError> {
+        if buf.len() < HEADER_LEN || buf[..2] != MAGIC || buf[2] != VERSION {
+            return Err(OutboxError::Corrupt);
+        }
+        let topic_len = buf[3] as usize;
+        let ttl = u32::from_le_bytes([buf[4], buf[5], buf[6], buf[7]]);
+        let mut created = [0u8; 8];
+        created.copy_from_slice(&buf[8..16]);
+        let created = u64::from_le_bytes(created);
+        let topic = buf.get(HEADER_LEN..HEADER_LEN + topic_len).ok_or(OutboxError::Corrupt)?;
+        Ok(Self {
+            topic: core::str::from_utf8(topic).map_err(|_| OutboxError::Corrupt)?,
+            payload: &buf[HEADER_LEN + topic_len..],
+            ttl: (ttl != 0).then(|| Duration::from_secs(ttl as u64)),
+            created: (created != 0).then(|| WallTime::from_unix_secs(created)),
+        })
+    }
+
+    /// 在 `now` 时刻是否已过期
+    pub fn is_expired(&self, now: Option<WallTime>) -> bool {
+        match (self.ttl, self.created, now) {
+            (Some(ttl), Some(created), Some(now)) => now.saturating_since(created) >= ttl,
+            _ => false,
+        }
+    }
+}
+
+// ===== 配置 =====
+
+/// 发件箱配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutboxConfig {
+    /// 存储目录
+    pub root: &'static str,
+    /// 队列总字节数上限
+    pub max_bytes: u32,
+    /// 队列条数上限
+    pub max_messages: u32,
+    /// 默认 TTL (`None` 为永不过期)
+    pub default_ttl: Option<Duration>,
+    /// 链路健康但发送失败时的重试间隔
+    pub retry_interval: Duration,
+}
+
+impl OutboxConfig {
+    /// 默认配置: 64KB / 256 条，TTL 24 小时，30 秒重试
+    pub const fn new() -> Self {
+        Self {
+            root: OUTBOX_ROOT,
+            max_bytes: 64 * 1024,
+            max_messages: 256,
+            default_ttl: Some(Duration::from_secs(24 * 3600)),
+            retry_interval: Duration::from_secs(30),
+        }
+    }
+
+    /// 设置存储目录
+    pub const fn with_root(mut self, root: &'static str) -> Self {
+        self.root = root;
+        self
+    }
+
+    /// 设置容量上限
+    pub const fn with_capacity(mut self, max_bytes: u32, max_messages: u32) -> Self {
+        self.max_bytes = max_bytes;
+        self.max_messages = max_messages;
+        self
+    }
+
+    /// 设置默认 TTL
+    pub const fn with_ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.default_ttl = ttl;
+        self
+    }
+
+    /// 设置重试间隔
+    pub const fn with_retry_interval(mut self, interval: Duration) -> Self {
+        self.retry_interval = interval;
+        self
+    }
+}
+
+impl Default for OutboxConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ===== 统计 =====
+
+/// 发件箱统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OutboxStats {
+    /// 落盘的消息数
+    pub spooled: u32,
+    /// 发送成功的消息数 (含直接发送)
+    pub sent: u32,
+    /// 因容量淘汰的消息数
+    pub evicted: u32,
+    /// 过期丢弃的消息数
+    pub expired: u32,
+    /// 损坏丢弃的记录数
+    pub corrupt: u32,
+}
+
+/// 队列状态 (消息文件以序号命名，head..tail 为待发送区间)
+#[derive(Debug, Clone, Copy, Default)]
+struct State {
+    head: u32,
+    tail: u32,
+    bytes: u32,
+    online: bool,
+    stats: OutboxStats,
+}
+
+impl State {
+    fn len(&self) -> u32 {
+        self.tail.wrapping_sub(self.head)
+    }
+}
+
+/// 写入 `size` 字节前需要淘汰的最旧消息数 (返回 `None` 表示淘汰后仍无法容纳)
+fn evictions(mut sizes: impl Iterator<Item = u32>, len: u32, bytes: u32, size: u32, config: &OutboxConfig) -> Option<u32> {
+    if size > config.max_bytes || config.max_messages == 0 {
+        return None;
+    }
+    let (mut count, mut bytes, mut len) = (0, bytes, len);
+    while len >= config.max_messages || bytes + size > config.max_bytes {
+        bytes = bytes.saturating_sub(sizes.next()?);
+        len -= 1;
+        count += 1;
+    }
+    Some(count)
+}
+
+// ===== 发件箱 =====
+
+/// 持久化发件箱
+pub struct Outbox<'a> {
+    fs: &'a FileSystem,
+    config: OutboxConfig,
+    state: BlockingMutex<CriticalSectionRawMutex, Cell<State>>,
+    /// 有新消息或链路恢复时唤醒发送任务
+    wake: CriticalSignal<()>,
+}
+
+impl<'a> Outbox<'a> {
+    /// 打开 (扫描已有的消息文件恢复队列)
+    pub fn open(fs: &'a FileSystem, config: OutboxConfig) -> Result<Self, OutboxError> {
+        fs.create_dir_all(config.root)?;
+        let mut state = State::default();
+        let mut range: Option<(u32, u32)> = None;
+        let mut dir = fs.read_dir(config.root)?;
+        while let Some(entry) = dir.next()? {
+            let Some(seq) = entry.is_file().then(|| u32::from_str_radix(&entry.name, 16).ok()).flatten() else {
+                continue;
+            };
+            range = Some(range.map_or((seq, seq), |(lo, hi)| (lo.min(seq), hi.max(seq))));
+            state.bytes += entry.size;
+        }
+        if let Some((lo, hi)) = range {
+            state.head = lo;
+            state.tail = hi.wrapping_add(1);
+        }
+        log_info!("outbox: {} pending ({} bytes)", state.len(), state.bytes);
+        Ok(Self { fs, config, state: BlockingMutex::new(Cell::new(state)), wake: CriticalSignal::new() })
+    }
+
+    /// 当前配置
+    pub fn config(&self) -> &OutboxConfig {
+        &self.config
+    }
+
+    /// 待发送消息数
+    pub fn len(&self) -> u32 {
+        self.state.lock(|s| s.get().len())
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 待发送字节数
+    pub fn bytes(&self) -> u32 {
+        self.state.lock(|s| s.get().bytes)
+    }
+
+    /// 统计
+    pub fn stats(&self) -> OutboxStats {
+        self.state.lock(|s| s.get().stats)
+    }
+
+    /// 链路是否健康
+    pub fn is_online(&self) -> bool {
+        self.state.lock(|s| s.get().online)
+    }
+
+    /// 设置链路状态 (不使用连接监管器时由应用调用)
+    pub fn set_online(&self, online: bool) {
+        self.update(|s| s.online = online);
+        if online {
+            self.wake.signal(());
+        }
+    }
+
+    fn update<R>(&self, f: impl FnOnce(&mut State) -> R) -> R {
+        self.state.lock(|cell| {
+            let mut state = cell.get();
+            let result = f(&mut state);
+            cell.set(state);
+            result
+        })
+    }
+
+    fn path(&self, seq: u32) -> Result<RecordPath, OutboxError> {
+        let mut path = RecordPath::new();
+        write!(path, "{}/{:08x}", self.config.root, seq).map_err(|_| FsError::PathTooLong)?;
+        Ok(path)
+    }
+
+    fn file_size(&self, seq: u32) -> u32 {
+        self.path(seq).ok().and_then(|path| self.fs.metadata(&path).ok()).map_or(0, |m| m.size)
+    }
+
+    /// 发布消息: 写入队列并唤醒发送任务 (`ttl` 为 `None` 时使用默认 TTL)
+    ///
+    /// # 返回
+    /// 消息序号
+    pub fn publish(&self, topic: &str, payload: &[u8], ttl: Option<Duration>) -> Result<u32, OutboxError> {
+        let record = Record { topic, payload, ttl: ttl.or(self.config.default_ttl), created: TIME.now() };
+        let mut buf = [0u8; MAX_RECORD];
+        let len = record.encode(&mut buf)? as u32;
+
+        let (head, count, bytes) = self.state.lock(|s| {
+            let s = s.get();
+            (s.head, s.len(), s.bytes)
+        });
+        let sizes = (0..count).map(|i| self.file_size(head.wrapping_add(i)));
+        let evict = evictions(sizes, count, bytes, len, &self.config).ok_or(OutboxError::TooLarge)?;
+        for _ in 0..evict {
+            let seq = self.state.lock(|s| s.get().head);
+            self.drop_head(seq)?;
+            self.update(|s| s.stats.evicted += 1);
+            log_warn!("outbox: full, evicted #{}", seq);
+        }
+
+        let seq = self.state.lock(|s| s.get().tail);
+        let path = self.path(seq)?;
+        let mut tmp = path.clone();
+        tmp.push_str(".tmp").map_err(|_| FsError::PathTooLong)?;
+        {
+            let mut file = self.fs.open(&tmp, OpenOptions::write_only())?;
+            file.write_all(&buf[..len as usize])?;
+            file.sync()?;
+        }
+        self.fs.rename(&tmp, &path)?;
+        self.update(|s| {
+            s.tail = seq.wrapping_add(1);
+            s.bytes += len;
+            s.stats.spooled += 1;
+        });
+        self.wake.signal(());
+        Ok(seq)
+    }
+
+    /// 发送消息: 在线且队列为空时直接发送，离线、有积压 (保证顺序) 或发送失败时落盘
+    pub async fn send<S: Sink>(
+        &self,
+        sink: &mut S,
+        topic: &str,
+        payload: &[u8],
+        ttl: Option<Duration>,
+    ) -> Result<Delivery, OutboxError> {
+        if self.is_online() && self.is_empty() && sink.send(topic, payload).await.is_ok() {
+            self.update(|s| s.stats.sent += 1);
+            return Ok(Delivery::Sent);
+        }
+        self.publish(topic, payload, ttl).map(Delivery::Queued)
+    }
+
+    /// 删除队首消息 (文件已不存在时直接前移)
+    fn drop_head(&self, seq: u32) -> Result<(), OutboxError> {
+        let path = self.path(seq)?;
+        let size = self.file_size(seq);
+        match self.fs.remove(&path) {
+            Ok(()) | Err(FsError::NotFound) => {}
+            Err(e) => return Err(e.into()),
+        }
+        self.update(|s| {
+            if s.head == seq && s.len() > 0 {
+                s.head = seq.wrapping_add(1);
+                s.bytes = s.bytes.saturating_sub(size);
+            }
+        });
+        Ok(())
+    }
+
+    /// 读取一条消息到缓冲区，返回长度
+    fn read(&self, seq: u32, buf: &mut [u8]) -> Result<usize, OutboxError> {
+        let path = self.path(seq)?;
+        let mut file = self.fs.open(&path, OpenOptions::read_only())?;
+        let size = file.size() as usize;
+        let buf = buf.get_mut(..size).ok_or(OutboxError::Corrupt)?;
+        let mut filled = 0;
+        while filled < size {
+            match file.read(&mut buf[filled..])? {
+                0 => return Err(OutboxError::Corrupt),
+                n => filled += n,
+            }
+        }
+        Ok(size)
+    }
+
+    /// 按序补发，遇到发送失败时停止
+    ///
+    /// # 返回
+    /// 本次发送成功的消息数
+    pub async fn flush<S: Sink>(&self, sink: &mut S) -> Result<u32, OutboxError> {
+        let mut buf = [0u8; MAX_RECORD];
+        let mut sent = 0;
+        while !self.is_empty() {
+            let seq = self.state.lock(|s| s.get().head);
+            let len = match self.read(seq, &mut buf) {
+                Ok(len) => len,
+                Err(OutboxError::Fs(FsError::NotFound)) => {
+                    self.drop_head(seq)?;
+                    continue;
+                }
+                Err(OutboxError::Corrupt) => {
+                    self.drop_head(seq)?;
+                    self.update(|s| s.stats.corrupt += 1);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            let Ok(record) = Record::decode(&buf[..len]) else {
+                self.drop_head(seq)?;
+                self.update(|s| s.stats.corrupt += 1);
+                continue;
+            };
+            if record.is_expired(TIME.now()) {
+                log_debug!("outbox: #{} expired", seq);
+                self.drop_head(seq)?;
+                self.update(|s| s.stats.expired += 1);
+                continue;
+            }
+            if sink.send(record.topic, record.payload).await.is_err() {
+                log_debug!("outbox: send failed, {} pending", self.len());
+                break;
+            }
+            self.drop_head(seq)?;
+            self.update(|s| s.stats.sent += 1);
+            sent += 1;
+        }
+        Ok(sent)
+    }
+
+    /// 发送任务主循环
+    ///
+    /// 订阅事件总线跟踪 `interface` 的链路健康度，`good`/`degraded` 视为在线；
+    /// 在线时有新消息立即补发，发送失败后按 `retry_interval` 重试。
+    pub async fn run<S: Sink>(&self, sink: &mut S, interface: &'static str) -> ! {
+        let mut events = bus::subscribe();
+        if events.is_none() {
+            log_warn!("outbox: no bus subscriber slot, use set_online()");
+        }
+        loop {
+            if self.is_online() && !self.is_empty() {
+                match self.flush(sink).await {
+                    Ok(0) => {}
+                    Ok(_n) => {
+                        log_info!("outbox: flushed {}, {} pending", _n, self.len());
+                    }
+                    Err(_e) => {
+                        log_warn!("outbox: flush failed: {}", _e);
+                    }
+                }
+            }
+
+            let link = async {
+                match events.as_mut() {
+                    Some(sub) => sub.next_message_pure().await,
+                    None => core::future::pending().await,
+                }
+            };
+            let retry = Timer::after(self.config.retry_interval);
+            if let Either3::First(envelope) = select3(link, self.wake.wait(), retry).await {
+                if let SystemEvent::LinkHealthChanged { interface: iface, to, .. } = envelope.event {
+                    if iface == interface {
+                        self.update(|s| s.online = matches!(to, "good" | "degraded"));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_roundtrip_and_ttl() {
+        let created = WallTime::from_unix_secs(1_700_000_000);
+        let record = Record {
+            topic: "sensors/temp",
+            payload: b"{\"c\":21.5}",
+            ttl: Some(Duration::from_secs(60)),
+            created: Some(created),
+        };
+        let mut buf = [0u8; MAX_RECORD];
+        let len = record.encode(&mut buf).unwrap();
+        assert_eq!(len, HEADER_LEN + 12 + 10);
+        let decoded = Record::decode(&buf[..len]).unwrap();
+        assert_eq!(decoded, record);
+
+        assert!(!decoded.is_expired(Some(created.add(Duration::from_secs(59)))));
+        assert!(decoded.is_expired(Some(created.add(Duration::from_secs(60)))));
+        assert!(!decoded.is_expired(None));
+        assert!(!Record { created: None, ..record }.is_expired(Some(created.add(Duration::from_secs(3600)))));
+
+        buf[0] = b'X';
+        assert_eq!(Record::decode(&buf[..len]), Err(OutboxError::Corrupt));
+        assert_eq!(Record { topic: "", ..record }.encode(&mut buf), Err(OutboxError::InvalidTopic));
+    }
+
+    #[test]
+    fn test_evictions() {
+        let config = OutboxConfig::new().with_capacity(100, 3);
+        let sizes = [30u32, 20, 40];
+        assert_eq!(evictions(sizes.iter().copied(), 0, 0, 50, &config), Some(0));
+        // 字节上限: 已有 90 字节，写入 30 淘汰 1 条，写入 50 淘汰 2 条
+        assert_eq!(evictions(sizes.iter().copied(), 2, 90, 30, &config), Some(1));
+        assert_eq!(evictions(sizes.iter().copied(), 2, 90, 50, &config), Some(2));
+        // 条数上限
+        assert_eq!(evictions(sizes.iter().copied(), 3, 60, 10, &config), Some(1));
+        assert_eq!(evictions(sizes.iter().copied(), 0, 0, 101, &config), None);
+    }
+}
@@ -0,0 +1,532 @@
+//! 报文抓包 (pcap)
+//!
+//! 调试用: 在网络驱动与协议栈之间镜像收发的以太网帧，写成 pcap 格式，
+//! 无需外部分光器即可在 Wireshark 中分析协议问题:
+//! - `CaptureDriver` 包装任意 `embassy-net-driver` 驱动，对上层所有 socket 透明
+//! - 抓取模式: 仅头部 (`HEADER_SNAPLEN` 字节) 或完整负载
+//! - 固定大小的环形缓冲区，驱动路径只做一次拷贝，满时丢弃最旧的记录
+//! - 输出: `PcapFile` 写入文件系统 (超过上限时从头重写)，或 `stream_udp` 经 UDP 实时推送
+//! - 时间戳: 墙上时钟已同步时为 Unix 时间，否则为开机时间
+//!
+//! 主机端实时查看: `socat -u UDP-RECV:5555 - | wireshark -k -i -`
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::pcap::{Capture, CaptureDriver, CaptureMode, PcapFile};
+//!
+//! static CAPTURE: Capture<8192> = Capture::new(CaptureMode::Headers);
+//!
+//! let device = CaptureDriver::new(wifi_device, &CAPTURE);
+//! let (stack, runner) = embassy_net::new(device, config, resources, seed);
+//!
+//! CAPTURE.start();
+//! // 写入文件 (最多 256 KiB)
+//! let mut file = PcapFile::create(&fs, "/log/net.pcap", &CAPTURE, 256 * 1024)?;
+//! loop {
+//!     CAPTURE.wait().await;
+//!     file.drain(&CAPTURE)?;
+//! }
+//! ```
+
+use core::cell::RefCell;
+use core::fmt;
+use core::task::Context;
+
+use embassy_net_driver::{Capabilities, Driver, HardwareAddress, LinkState, RxToken, TxToken};
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_time::Instant;
+
+use crate::fs::littlefs::{File, FileSystem, FsError, OpenOptions, SeekFrom};
+use crate::sync::primitives::CriticalSignal;
+use crate::sys::time::TIME;
+
+/// 仅头部模式的截取长度 (以太网 + IPv4 + TCP 含选项)
+pub const HEADER_SNAPLEN: usize = 96;
+
+/// 完整模式的截取长度 (以太网 MTU)
+pub const FULL_SNAPLEN: usize = 1514;
+
+/// pcap 文件头长度
+pub const FILE_HEADER_LEN: usize = 24;
+
+/// pcap 记录头长度
+pub const RECORD_HEADER_LEN: usize = 16;
+
+/// 以太网链路类型
+const LINKTYPE_ETHERNET: u32 = 1;
+
+// ===== 格式 =====
+
+/// 抓取模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureMode {
+    /// 仅头部
+    Headers,
+    /// 完整负载
+    Full,
+}
+
+impl CaptureMode {
+    /// 截取长度
+    pub const fn snaplen(self) -> usize {
+        match self {
+            Self::Headers => HEADER_SNAPLEN,
+            Self::Full => FULL_SNAPLEN,
+        }
+    }
+}
+
+/// 报文方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// 接收
+    Rx,
+    /// 发送
+    Tx,
+}
+
+/// pcap 文件头 (微秒时间戳，小端)
+pub fn file_header(snaplen: usize) -> [u8; FILE_HEADER_LEN] {
+    let mut header = [0u8; FILE_HEADER_LEN];
+    header[0..4].copy_from_slice(&0xA1B2_C3D4u32.to_le_bytes());
+    header[4..6].copy_from_slice(&2u16.to_le_bytes());
+    header[6..8].copy_from_slice(&4u16.to_le_bytes());
+    header[16..20].copy_from_slice(&(snaplen as u32).to_le_bytes());
+    header[20..24].copy_from_slice(&LINKTYPE_ETHERNET.to_le_bytes());
+    header
+}
+
+/// pcap 记录头
+pub fn record_header(timestamp_us: u64, captured: usize, original: usize) -> [u8; RECORD_HEADER_LEN] {
+    let mut header = [0u8; RECORD_HEADER_LEN];
+    header[0..4].copy_from_slice(&((timestamp_us / 1_000_000) as u32).to_le_bytes());
+    header[4..8].copy_from_slice(&((timestamp_us % 1_000_000) as u32).to_le_bytes());
+    header[8..12].copy_from_slice(&(captured as u32).to_le_bytes());
+    header[12..16].copy_from_slice(&(original as u32).to_le_bytes());
+    header
+}
+
+// ===== 环形缓冲区 =====
+
+/// 按记录存取的字节环
+struct Ring<const N: usize> {
+    buf: [u8; N],
+    head: usize,
+    len: usize,
+}
+
+impl<const N: usize> Ring<N> {
+    const fn new() -> Self {
+        Self { buf: [0; N], head: 0, len: 0 }
+    }
+
+    fn write_at(&mut self, pos: usize, data: &[u8]) {
+        let start = pos % N;
+        let first = data.len().min(N - start);
+        self.buf[start..start + first].copy_from_slice(&data[..first]);
+        self.buf[..data.len() - first].copy_from_slice(&data[first..]);
+    }
+
+    fn read_at(&self, pos: usize, out: &mut [u8]) {
+        let start = pos % N;
+        let first = out.len().min(N - start);
+        out[..first].copy_from_slice(&self.buf[start..start + first]);
+        let rest = out.len() - first;
+        out[first..].copy_from_slice(&self.buf[..rest]);
+    }
+
+    /// 最旧记录的总长度
+    fn front_len(&self) -> Option<usize> {
+        if self.len < RECORD_HEADER_LEN {
+            return None;
+        }
+        let mut header = [0u8; RECORD_HEADER_LEN];
+        self.read_at(self.head, &mut header);
+        Some(RECORD_HEADER_LEN + u32::from_le_bytes([header[8], header[9], header[10], header[11]]) as usize)
+    }
+
+    fn skip(&mut self, n: usize) {
+        self.head = (self.head + n) % N;
+        self.len -= n;
+    }
+
+    /// 写入一条记录，空间不足时丢弃最旧的记录，返回丢弃数
+    fn push(&mut self, header: &[u8], data: &[u8]) -> u32 {
+        let total = header.len() + data.len();
+        let mut evicted = 0;
+        while N - self.len < total {
+            let Some(n) = self.front_len() else { break };
+            self.skip(n);
+            evicted += 1;
+        }
+        let tail = self.head + self.len;
+        self.write_at(tail, header);
+        self.write_at(tail + header.len(), data);
+        self.len += total;
+        evicted
+    }
+
+    /// 取出最旧的记录到 `out`，返回长度 (`out` 不足时留在缓冲区)
+    fn pop(&mut self, out: &mut [u8]) -> Option<usize> {
+        let n = self.front_len()?;
+        let dest = out.get_mut(..n)?;
+        self.read_at(self.head, dest);
+        self.skip(n);
+        Some(n)
+    }
+}
+
+// ===== 抓包 =====
+
+/// 抓包统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CaptureStats {
+    /// 接收帧
+    pub rx: u32,
+    /// 发送帧
+    pub tx: u32,
+    /// 被过滤的帧
+    pub filtered: u32,
+    /// 缓冲区满被丢弃的记录
+    pub dropped: u32,
+    /// 已输出的字节 (含 pcap 头)
+    pub written: u64,
+}
+
+impl fmt::Display for CaptureStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "rx={} tx={} filtered={} dropped={} written={}",
+            self.rx, self.tx, self.filtered, self.dropped, self.written
+        )
+    }
+}
+
+struct State<const N: usize> {
+    ring: Ring<N>,
+    running: bool,
+    mode: CaptureMode,
+    filter: Option<fn(Direction, &[u8]) -> bool>,
+    stats: CaptureStats,
+}
+
+/// 抓包缓冲区 (`N` 为环形缓冲区字节数)
+pub struct Capture<const N: usize> {
+    state: BlockingMutex<CriticalSectionRawMutex, RefCell<State<N>>>,
+    ready: CriticalSignal<()>,
+}
+
+impl<const N: usize> Capture<N> {
+    /// 创建 (默认停止)
+    pub const fn new(mode: CaptureMode) -> Self {
+        Self {
+            state: BlockingMutex::new(RefCell::new(State {
+                ring: Ring::new(),
+                running: false,
+                mode,
+                filter: None,
+                stats: CaptureStats { rx: 0, tx: 0, filtered: 0, dropped: 0, written: 0 },
+            })),
+            ready: CriticalSignal::new(),
+        }
+    }
+
+    /// 开始抓包
+    pub fn start(&self) {
+        self.state.lock(|s| s.borrow_mut().running = true);
+    }
+
+    /// 停止抓包 (已缓冲的记录仍可取出)
+    pub fn stop(&self) {
+        self.state.lock(|s| s.borrow_mut().running = false);
+    }
+
+    /// 是否正在抓包
+    pub fn is_running(&self) -> bool {
+        self.state.lock(|s| s.borrow().running)
+    }
+
+    /// 切换抓取模式
+    pub fn set_mode(&self, mode: CaptureMode) {
+        self.state.lock(|s| s.borrow_mut().mode = mode);
+    }
+
+    /// 当前截取长度
+    pub fn snaplen(&self) -> usize {
+        self.state.lock(|s| s.borrow().mode.snaplen())
+    }
+
+    /// 设置过滤函数 (返回 `false` 的帧不记录)，`None` 为记录全部
+    pub fn set_filter(&self, filter: Option<fn(Direction, &[u8]) -> bool>) {
+        self.state.lock(|s| s.borrow_mut().filter = filter);
+    }
+
+    /// 记录一帧 (驱动路径调用)
+    pub fn record(&self, direction: Direction, frame: &[u8]) {
+        self.record_at(direction, frame, Instant::now());
+    }
+
+    fn record_at(&self, direction: Direction, frame: &[u8], at: Instant) {
+        let recorded = self.state.lock(|s| {
+            let mut s = s.borrow_mut();
+            if !s.running {
+                return false;
+            }
+            match direction {
+                Direction::Rx => s.stats.rx = s.stats.rx.wrapping_add(1),
+                Direction::Tx => s.stats.tx = s.stats.tx.wrapping_add(1),
+            }
+            if s.filter.is_some_and(|f| !f(direction, frame)) {
+                s.stats.filtered = s.stats.filtered.wrapping_add(1);
+                return false;
+            }
+            let captured = frame.len().min(s.mode.snaplen()).min(N - RECORD_HEADER_LEN);
+            let timestamp = TIME.to_wall(at).map_or(at.as_micros(), |w| w.unix_micros());
+            let header = record_header(timestamp, captured, frame.len());
+            let evicted = s.ring.push(&header, &frame[..captured]);
+            s.stats.dropped = s.stats.dropped.wrapping_add(evicted);
+            true
+        });
+        if recorded {
+            self.ready.signal(());
+        }
+    }
+
+    /// 取出最旧的一条 pcap 记录 (记录头 + 数据) 到 `out`
+    ///
+    /// `out` 至少需要 `RECORD_HEADER_LEN + snaplen` 字节。
+    pub fn pop(&self, out: &mut [u8]) -> Option<usize> {
+        self.state.lock(|s| s.borrow_mut().ring.pop(out))
+    }
+
+    /// 等待新记录
+    pub async fn wait(&self) {
+        self.ready.wait().await;
+    }
+
+    fn add_written(&self, n: usize) {
+        self.state.lock(|s| s.borrow_mut().stats.written += n as u64);
+    }
+
+    /// 统计
+    pub fn stats(&self) -> CaptureStats {
+        self.state.lock(|s| s.borrow().stats)
+    }
+}
+
+// ===== 驱动包装 =====
+
+/// 镜像收发帧的驱动包装
+pub struct CaptureDriver<'c, D, const N: usize> {
+    inner: D,
+    capture: &'c Capture<N>,
+}
+
+impl<'c, D: Driver, const N: usize> CaptureDriver<'c, D, N> {
+    /// 包装驱动
+    pub fn new(inner: D, capture: &'c Capture<N>) -> Self {
+        Self { inner, capture }
+    }
+
+    /// 取回原驱动
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+}
+
+/// 接收令牌包装
+pub struct CaptureRx<'c, T, const N: usize> {
+    inner: T,
+    capture: &'c Capture<N>,
+}
+
+/// 发送令牌包装
+pub struct CaptureTx<'c, T, const N: usize> {
+    inner: T,
+    capture: &'c Capture<N>,
+}
+
+impl<T: RxToken, const N: usize> RxToken for CaptureRx<'_, T, N> {
+    fn consume<R, F>(self, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        let capture = self.capture;
+        self.inner.consume(|frame| {
+            capture.record(Direction::Rx, frame);
+            f(frame)
+        })
+    }
+}
+
+impl<T: TxToken, const N: usize> TxToken for CaptureTx<'_, T, N> {
+    fn consume<R, F>(self, len: usize, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        let capture = self.capture;
+        self.inner.consume(len, |frame| {
+            let result = f(frame);
+            capture.record(Direction::Tx, frame);
+            result
+        })
+    }
+}
+
+impl<'c, D: Driver, const N: usize> Driver for CaptureDriver<'c, D, N> {
+    type RxToken<'a>
+        = CaptureRx<'c, D::RxToken<'a>, N>
+    where
+        Self: 'a;
+    type TxToken<'a>
+        = CaptureTx<'c, D::TxToken<'a>, N>
+    where
+        Self: 'a;
+
+    fn receive(&mut self, cx: &mut Context) -> Option<(Self::RxToken<'_>, Self::TxToken<'_>)> {
+        let capture = self.capture;
+        self.inner
+            .receive(cx)
+            .map(|(rx, tx)| (CaptureRx { inner: rx, capture }, CaptureTx { inner: tx, capture }))
+    }
+
+    fn transmit(&mut self, cx: &mut Context) -> Option<Self::TxToken<'_>> {
+        let capture = self.capture;
+        self.inner.transmit(cx).map(|tx| CaptureTx { inner: tx, capture })
+    }
+
+    fn link_state(&mut self, cx: &mut Context) -> LinkState {
+        self.inner.link_state(cx)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    fn hardware_address(&self) -> HardwareAddress {
+        self.inner.hardware_address()
+    }
+}
+
+// ===== 输出 =====
+
+/// pcap 文件输出 (超过上限时从头重写)
+pub struct PcapFile<'f> {
+    file: File<'f>,
+    snaplen: usize,
+    max_bytes: u32,
+}
+
+impl<'f> PcapFile<'f> {
+    /// 创建 (覆盖已有文件) 并写入文件头
+    pub fn create<const N: usize>(
+        fs: &'f FileSystem,
+        path: &str,
+        capture: &Capture<N>,
+        max_bytes: u32,
+    ) -> Result<Self, FsError> {
+        let file = fs.open(path, OpenOptions::write_only())?;
+        let mut pcap = Self { file, snaplen: capture.snaplen(), max_bytes };
+        pcap.file.write_all(&file_header(pcap.snaplen))?;
+        capture.add_written(FILE_HEADER_LEN);
+        Ok(pcap)
+    }
+
+    /// 把缓冲的记录全部写入文件，返回写入的记录数
+    pub fn drain<const N: usize>(&mut self, capture: &Capture<N>) -> Result<usize, FsError> {
+        let mut record = [0u8; RECORD_HEADER_LEN + FULL_SNAPLEN];
+        let mut count = 0;
+        while let Some(n) = capture.pop(&mut record) {
+            if self.file.size() as usize + n > self.max_bytes as usize {
+                // 环形文件: 截断后重写文件头
+                self.file.truncate(0)?;
+                self.file.seek(SeekFrom::Start(0))?;
+                self.file.write_all(&file_header(self.snaplen))?;
+            }
+            self.file.write_all(&record[..n])?;
+            capture.add_written(n);
+            count += 1;
+        }
+        if count > 0 {
+            self.file.sync()?;
+        }
+        Ok(count)
+    }
+}
+
+/// 经 UDP 实时推送 (永不返回)
+///
+/// 先发送 pcap 文件头，之后每条记录一个数据报。主机端把收到的数据按序拼接即为 pcap 流。
+pub async fn stream_udp<const N: usize>(
+    stack: embassy_net::Stack<'_>,
+    capture: &Capture<N>,
+    endpoint: embassy_net::IpEndpoint,
+) -> ! {
+    use embassy_net::udp::{PacketMetadata, UdpSocket};
+
+    let mut rx_meta = [PacketMetadata::EMPTY; 1];
+    let mut rx = [0u8; 64];
+    let mut tx_meta = [PacketMetadata::EMPTY; 4];
+    let mut tx = [0u8; 2 * (RECORD_HEADER_LEN + FULL_SNAPLEN)];
+    let mut socket = UdpSocket::new(stack, &mut rx_meta, &mut rx, &mut tx_meta, &mut tx);
+    let _ = socket.bind(0);
+
+    if socket.send_to(&file_header(capture.snaplen()), endpoint).await.is_ok() {
+        capture.add_written(FILE_HEADER_LEN);
+    }
+    let mut record = [0u8; RECORD_HEADER_LEN + FULL_SNAPLEN];
+    loop {
+        capture.wait().await;
+        while let Some(n) = capture.pop(&mut record) {
+            if socket.send_to(&record[..n], endpoint).await.is_ok() {
+                capture.add_written(n);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_records_and_eviction() {
+        let capture: Capture<160> = Capture::new(CaptureMode::Headers);
+        capture.record(Direction::Rx, &[1; 40]);
+        assert_eq!(capture.stats().rx, 0);
+
+        capture.start();
+        let t = Instant::from_micros(3_000_042);
+        capture.record_at(Direction::Rx, &[1; 40], t);
+        capture.record_at(Direction::Tx, &[2; 200], t);
+        capture.record_at(Direction::Rx, &[3; 20], t);
+
+        // 第二帧截取为 96 字节，第一帧被挤出
+        let stats = capture.stats();
+        assert_eq!((stats.rx, stats.tx, stats.dropped), (2, 1, 1));
+        let mut out = [0u8; 256];
+        let n = capture.pop(&mut out).unwrap();
+        assert_eq!(n, RECORD_HEADER_LEN + HEADER_SNAPLEN);
+        assert_eq!(out[..RECORD_HEADER_LEN], record_header(3_000_042, HEADER_SNAPLEN, 200));
+        assert_eq!(out[RECORD_HEADER_LEN], 2);
+        assert_eq!(capture.pop(&mut out), Some(RECORD_HEADER_LEN + 20));
+        assert_eq!(capture.pop(&mut out), None);
+    }
+
+    #[test]
+    fn test_filter_and_file_header() {
+        let capture: Capture<256> = Capture::new(CaptureMode::Full);
+        capture.start();
+        capture.set_filter(Some(|dir, _| dir == Direction::Tx));
+        capture.record(Direction::Rx, &[0; 10]);
+        capture.record(Direction::Tx, &[0; 10]);
+        assert_eq!(capture.stats().filtered, 1);
+
+        let header = file_header(FULL_SNAPLEN);
+        assert_eq!(header[..4], [0xD4, 0xC3, 0xB2, 0xA1]);
+        assert_eq!(u32::from_le_bytes([header[16], header[17], header[18], header[19]]), 1514);
+    }
+}
@@ -0,0 +1,561 @@
+//! WiFi 配网 (SoftAP + 强制门户)
+//!
+//! 首次启动没有 STA 凭据时的常见流程:
+//! 1. `WifiController::start_ap` 开启热点，AP 接口跑 `dhcp_server` 分配地址
+//! 2. 门户 DNS 对所有 A 查询回答 AP 地址，手机/电脑的联网检测随之打开配网页面
+//! 3. HTTP 门户提供一个表单 (`GET /`)，`POST /provision` 接受表单或 JSON
+//!    (`{"ssid":"..","password":".."}`)，校验后写入键值存储
+//! 4. `switch_to_sta` 关闭热点并用新凭据连接，之后启动时直接 `StaCredentials::load`
+//!
+//! 门户中其余 GET 请求一律 302 重定向到首页。凭据与管制域一样保存在
+//! `wifi` 命名空间 (`KvStore`，LittleFS 分区)，`StaCredentials::clear` 用于恢复出厂。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::provisioning::{self, Portal, PortalBuffers, StaCredentials};
+//!
+//! let kv = KvStore::new(&fs);
+//! let creds = match StaCredentials::load(&kv)? {
+//!     Some(creds) => creds,
+//!     None => {
+//!         wifi.start_ap(&mut radio, ApConfig::new("Device-Setup", "")?).await?;
+//!         let portal = Portal::new(&kv, DEFAULT_AP_ADDRESS);
+//!         let mut dhcp: DhcpServer = DhcpServer::new(DhcpServerConfig::new(DEFAULT_AP_ADDRESS));
+//!         provisioning::run_portal(&ap_stack, buffers, &mut dhcp, &portal).await?
+//!     }
+//! };
+//! provisioning::switch_to_sta(&mut wifi, &mut radio, &creds).await?;
+//! ```
+
+use core::fmt::{self, Write as _};
+
+use embassy_futures::select::{select, select3, Either, Either3};
+use embassy_time::{with_timeout, Duration};
+use embedded_io_async::{Read, Write};
+use heapless::String;
+
+use super::config::WIFI_CONNECT_TIMEOUT_MS;
+use super::dhcp_server::{self, DhcpServer, DhcpServerBuffers};
+use super::http::{self, Exchange, Handler, HttpConfig, HttpError, Method, Request, Status};
+use super::resolver::{be16, skip_name, CLASS_IN, DNS_PORT, FLAG_QR, FLAG_RD, HEADER_LEN, MAX_MESSAGE_LEN};
+use super::tcp::{Ipv4Address, NetworkError, NetworkStack, UdpBuffers, UdpSocket};
+use super::wifi::{WifiController, WifiError};
+use crate::fs::kv::{KvError, KvStore};
+use crate::fs::littlefs::FsError;
+use crate::sync::primitives::CriticalSignal;
+use crate::util::json;
+use crate::util::log::*;
+
+/// 凭据所在命名空间 (与管制域相同)
+pub const KV_NAMESPACE: &str = "wifi";
+
+/// 凭据键
+pub const KV_KEY: &str = "sta";
+
+/// 提交凭据的路径
+pub const PROVISION_PATH: &str = "/provision";
+
+/// 门户 DNS 应答的 TTL (秒)，较短以便配网后客户端尽快恢复正常解析
+pub const CAPTIVE_DNS_TTL: u32 = 10;
+
+/// 门户 DNS Socket 缓冲区
+pub type CaptiveDnsBuffers = UdpBuffers<4, { MAX_MESSAGE_LEN * 2 }, { MAX_MESSAGE_LEN * 2 }>;
+
+/// 持久化格式版本
+const FORMAT_VERSION: u8 = 1;
+
+/// 编码后最大长度 (版本 + 两个长度字节 + SSID + 密码)
+const ENCODED_MAX_LEN: usize = 3 + 32 + 64;
+
+/// 请求体上限 (表单或 JSON)
+const MAX_BODY_LEN: usize = 256;
+
+const TYPE_A: u16 = 1;
+
+/// 配网页面
+const FORM_HTML: &str = concat!(
+    "<!DOCTYPE html><html><head><meta charset=utf-8>",
+    "<meta name=viewport content=\"width=device-width,initial-scale=1\"><title>WiFi Setup</title></head>",
+    "<body><h2>WiFi Setup</h2><form method=post action=/provision>",
+    "<p><input name=ssid placeholder=SSID maxlength=32 required></p>",
+    "<p><input name=password type=password placeholder=Password maxlength=64></p>",
+    "<p><button>Connect</button></p></form></body></html>",
+);
+
+/// 提交成功页面
+const DONE_HTML: &str = concat!(
+    "<!DOCTYPE html><html><head><meta charset=utf-8><title>WiFi Setup</title></head>",
+    "<body><h2>Saved</h2><p>The device is connecting to the network. ",
+    "This hotspot will now close.</p></body></html>",
+);
+
+// ===== 错误类型 =====
+
+/// 配网错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProvisionError {
+    /// SSID 为空或超过 32 字节
+    InvalidSsid,
+    /// 密码长度不合法 (WPA2 需 8 ~ 63 字符或 64 位十六进制)
+    InvalidPassword,
+    /// 请求体格式错误
+    BadRequest,
+    /// 持久化数据损坏
+    Corrupt,
+    /// 键值存储错误
+    Kv(KvError),
+    /// WiFi 错误
+    Wifi(WifiError),
+    /// 网络错误
+    Network(NetworkError),
+}
+
+impl fmt::Display for ProvisionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSsid => write!(f, "Invalid SSID"),
+            Self::InvalidPassword => write!(f, "Invalid password length"),
+            Self::BadRequest => write!(f, "Malformed provisioning request"),
+            Self::Corrupt => write!(f, "Stored credentials corrupt"),
+            Self::Kv(e) => write!(f, "KV store error: {}", e),
+            Self::Wifi(e) => write!(f, "WiFi error: {}", e),
+            Self::Network(e) => write!(f, "Network error: {}", e),
+        }
+    }
+}
+
+impl From<KvError> for ProvisionError {
+    fn from(e: KvError) -> Self {
+        Self::Kv(e)
+    }
+}
+
+impl From<WifiError> for ProvisionError {
+    fn from(e: WifiError) -> Self {
+        Self::Wifi(e)
+    }
+}
+
+impl From<NetworkError> for ProvisionError {
+    fn from(e: NetworkError) -> Self {
+        Self::Network(e)
+    }
+}
+
+// ===== 凭据 =====
+
+/// STA 凭据
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaCredentials {
+    /// 网络名称
+    pub ssid: String<32>,
+    /// 密码 (开放网络为空)
+    pub password: String<64>,
+}
+
+impl StaCredentials {
+    /// 创建并校验
+    pub fn new(ssid: &str, password: &str) -> Result<Self, ProvisionError> {
+        let creds = Self {
+            ssid: String::try_from(ssid).map_err(|_| ProvisionError::InvalidSsid)?,
+            password: String::try_from(password).map_err(|_| ProvisionError::InvalidPassword)?,
+        };
+        creds.validate()?;
+        Ok(creds)
+    }
+
+    /// 校验长度
+    pub fn validate(&self) -> Result<(), ProvisionError> {
+        if self.ssid.is_empty() {
+            return Err(ProvisionError::InvalidSsid);
+        }
+        let password = self.password.as_str();
+        let valid = match password.len() {
+            0 | 8..=63 => true,
+            64 => password.bytes().all(|b| b.is_ascii_hexdigit()),
+            _ => false,
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(ProvisionError::InvalidPassword)
+        }
+    }
+
+    /// 是否为开放网络
+    pub fn is_open(&self) -> bool {
+        self.password.is_empty()
+    }
+
+    /// 从 `application/x-www-form-urlencoded` 请求体解析
+    pub fn from_form(body: &str) -> Result<Self, ProvisionError> {
+        let ssid: String<32> = form_value(body, "ssid")?.ok_or(ProvisionError::InvalidSsid)?;
+        let password: String<64> = form_value(body, "password")?.unwrap_or_default();
+        Self::new(&ssid, &password)
+    }
+
+    /// 从 JSON 请求体解析 (`password` 可省略)
+    pub fn from_json(body: &str) -> Result<Self, ProvisionError> {
+        let value = json::parse(body).map_err(|_| ProvisionError::BadRequest)?;
+        let ssid: String<32> = value
+            .get("ssid")
+            .and_then(|v| v.to_string())
+            .map_err(|_| ProvisionError::InvalidSsid)?;
+        let password: String<64> = match value.get("password") {
+            Ok(v) => v.to_string().map_err(|_| ProvisionError::InvalidPassword)?,
+            Err(_) => String::new(),
+        };
+        Self::new(&ssid, &password)
+    }
+
+    /// 编码
+    pub fn encode(&self) -> heapless::Vec<u8, ENCODED_MAX_LEN> {
+        let mut out = heapless::Vec::new();
+        let _ = out.push(FORMAT_VERSION);
+        let _ = out.push(self.ssid.len() as u8);
+        let _ = out.extend_from_slice(self.ssid.as_bytes());
+        let _ = out.push(self.password.len() as u8);
+        let _ = out.extend_from_slice(self.password.as_bytes());
+        out
+    }
+
+    /// 解码
+    pub fn decode(data: &[u8]) -> Result<Self, ProvisionError> {
+        let field = |pos: usize| -> Result<(&str, usize), ProvisionError> {
+            let len = *data.get(pos).ok_or(ProvisionError::Corrupt)? as usize;
+            let bytes = data.get(pos + 1..pos + 1 + len).ok_or(ProvisionError::Corrupt)?;
+            let text = core::str::from_utf8(bytes).map_err(|_| ProvisionError::Corrupt)?;
+            Ok((text, pos + 1 + len))
+        };
+        if data.first() != Some(&FORMAT_VERSION) {
+            return Err(ProvisionError::Corrupt);
+        }
+        let (ssid, next) = field(1)?;
+        let (password, end) = field(next)?;
+        if end != data.len() {
+            return Err(ProvisionError::Corrupt);
+        }
+        Self::new(ssid, password).map_err(|_| ProvisionError::Corrupt)
+    }
+
+    /// 保存到键值存储
+    pub fn save(&self, kv: &KvStore<'_>) -> Result<(), ProvisionError> {
+        kv.set(KV_NAMESPACE, KV_KEY, &self.encode())?;
+        Ok(())
+    }
+
+    /// 从键值存储读取 (未配网返回 `None`)
+    pub fn load(kv: &KvStore<'_>) -> Result<Option<Self>, ProvisionError> {
+        if !kv.contains(KV_NAMESPACE, KV_KEY) {
+            return Ok(None);
+        }
+        let mut buf = [0u8; ENCODED_MAX_LEN];
+        let len = kv.get(KV_NAMESPACE, KV_KEY, &mut buf).map_err(|e| match e {
+            KvError::BufferTooSmall(_) => ProvisionError::Corrupt,
+            e => ProvisionError::Kv(e),
+        })?;
+        Self::decode(&buf[..len]).map(Some)
+    }
+
+    /// 删除已保存的凭据 (下次启动重新配网)
+    pub fn clear(kv: &KvStore<'_>) -> Result<(), ProvisionError> {
+        match kv.remove(KV_NAMESPACE, KV_KEY) {
+            Ok(()) | Err(KvError::Fs(FsError::NotFound)) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// 表单字段 (百分号解码，`+` 视为空格)
+fn form_value<const N: usize>(body: &str, key: &str) -> Result<Option<String<N>>, ProvisionError> {
+    let Some(raw) = body.split('&').find_map(|pair| {
+        let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+        (k == key).then_some(v)
+    }) else {
+        return Ok(None);
+    };
+
+    let mut bytes = heapless::Vec::<u8, N>::new();
+    let mut iter = raw.bytes();
+    while let Some(b) = iter.next() {
+        let value = match b {
+            b'+' => b' ',
+            b'%' => {
+                let hex = [iter.next(), iter.next()];
+                let [Some(hi), Some(lo)] = hex else {
+                    return Err(ProvisionError::BadRequest);
+                };
+                let digit = |c: u8| (c as char).to_digit(16).ok_or(ProvisionError::BadRequest);
+                (digit(hi)? * 16 + digit(lo)?) as u8
+            }
+            b => b,
+        };
+        bytes.push(value).map_err(|_| ProvisionError::BadRequest)?;
+    }
+    let text = core::str::from_utf8(&bytes).map_err(|_| ProvisionError::BadRequest)?;
+    Ok(Some(String::try_from(text).map_err(|_| ProvisionError::BadRequest)?))
+}
+
+// ===== HTTP 门户 =====
+
+/// 配网门户 HTTP 处理器
+pub struct Portal<'k> {
+    kv: &'k KvStore<'k>,
+    address: Ipv4Address,
+    received: CriticalSignal<StaCredentials>,
+}
+
+impl<'k> Portal<'k> {
+    /// 创建门户
+    ///
+    /// # 参数
+    /// - `kv`: 保存凭据的键值存储
+    /// - `address`: AP 接口地址 (重定向目标)
+    pub const fn new(kv: &'k KvStore<'k>, address: Ipv4Address) -> Self {
+        Self { kv, address, received: CriticalSignal::new() }
+    }
+
+    /// 等待用户提交有效凭据 (已保存)
+    pub async fn wait(&self) -> StaCredentials {
+        self.received.wait().await
+    }
+
+    async fn submit<C: Read + Write>(&self, req: &Request<'_>, ex: &mut Exchange<'_, C>) -> Result<(), HttpError> {
+        let mut buf = [0u8; MAX_BODY_LEN];
+        let body = ex.read_body_to(&mut buf).await?;
+        let body = core::str::from_utf8(body).map_err(|_| HttpError::BadRequest)?;
+        let is_json = req.content_type().is_some_and(|t| t.starts_with("application/json"));
+        let parsed = if is_json { StaCredentials::from_json(body) } else { StaCredentials::from_form(body) };
+
+        let creds = match parsed {
+            Ok(creds) => creds,
+            Err(e) => {
+                let mut message: String<48> = String::new();
+                let _ = write!(message, "{}", e);
+                return ex.respond(Status::BadRequest, "text/plain", message.as_bytes()).await;
+            }
+        };
+        if let Err(_e) = creds.save(self.kv) {
+            log_error!("Failed to save WiFi credentials: {}", _e);
+            return ex.respond(Status::InternalServerError, "text/plain", b"failed to save credentials").await;
+        }
+        log_info!("Provisioned WiFi network {}", creds.ssid.as_str());
+
+        if is_json {
+            ex.respond(Status::Ok, "application/json", b"{\"ok\":true}").await?;
+        } else {
+            ex.respond(Status::Ok, "text/html", DONE_HTML.as_bytes()).await?;
+        }
+        // 应答写出后再通知，避免热点在页面返回前关闭
+        ex.flush().await?;
+        self.received.signal(creds);
+        Ok(())
+    }
+}
+
+impl Handler for Portal<'_> {
+    async fn handle<C: Read + Write>(&self, req: &Request<'_>, ex: &mut Exchange<'_, C>) -> Result<(), HttpError> {
+        match (req.method(), req.path()) {
+            (Method::Get, "/") => ex.respond(Status::Ok, "text/html", FORM_HTML.as_bytes()).await,
+            (Method::Post, PROVISION_PATH) => self.submit(req, ex).await,
+            // 联网检测 (/generate_204、/hotspot-detect.html 等) 重定向到配网页面
+            (Method::Get, _) => {
+                let [a, b, c, d] = self.address.0;
+                let mut location: String<24> = String::new();
+                let _ = write!(location, "http://{}.{}.{}.{}/", a, b, c, d);
+                ex.start(Status::Found, &[("Location", location.as_str()), ("Cache-Control", "no-store")], 0).await
+            }
+            _ => Err(HttpError::UnsupportedMethod),
+        }
+    }
+}
+
+// ===== 门户 DNS =====
+
+/// 构造门户 DNS 应答: A 查询一律指向 `address`，其他类型返回无记录
+///
+/// # 返回
+/// 应答长度；非标准查询返回 `None` (不应答)
+pub fn captive_dns_reply(query: &[u8], address: Ipv4Address, out: &mut [u8]) -> Option<usize> {
+    if query.len() < HEADER_LEN {
+        return None;
+    }
+    let flags = be16(query, 2);
+    // 只处理标准查询 (QR=0, OPCODE=0) 且恰好一个问题
+    if flags & (FLAG_QR | 0x7800) != 0 || be16(query, 4) != 1 {
+        return None;
+    }
+    let question_end = skip_name(query, HEADER_LEN).ok()? + 4;
+    if question_end > query.len() {
+        return None;
+    }
+    let answer = be16(query, question_end - 4) == TYPE_A && be16(query, question_end - 2) == CLASS_IN;
+    let len = question_end + if answer { 16 } else { 0 };
+    if out.len() < len {
+        return None;
+    }
+
+    out[..question_end].copy_from_slice(&query[..question_end]);
+    // QR + AA + RA，保留 RD
+    let flags = FLAG_QR | 0x0400 | (flags & FLAG_RD) | 0x0080;
+    out[2..4].copy_from_slice(&flags.to_be_bytes());
+    out[6..8].copy_from_slice(&(answer as u16).to_be_bytes());
+    out[8..12].fill(0);
+    if answer {
+        let record = &mut out[question_end..len];
+        // 名称压缩指针指向问题中的域名
+        record[0..2].copy_from_slice(&(0xC000 | HEADER_LEN as u16).to_be_bytes());
+        record[2..4].copy_from_slice(&TYPE_A.to_be_bytes());
+        record[4..6].copy_from_slice(&CLASS_IN.to_be_bytes());
+        record[6..10].copy_from_slice(&CAPTIVE_DNS_TTL.to_be_bytes());
+        record[10..12].copy_from_slice(&4u16.to_be_bytes());
+        record[12..16].copy_from_slice(&address.0);
+    }
+    Some(len)
+}
+
+/// 门户 DNS 服务 (在 AP 接口上监听 53 端口)
+pub async fn run_captive_dns(
+    stack: &NetworkStack<'_>,
+    buffers: &mut CaptiveDnsBuffers,
+    address: Ipv4Address,
+) -> Result<(), NetworkError> {
+    let mut socket = UdpSocket::new(stack, buffers);
+    socket.bind(DNS_PORT).await?;
+    let mut rx = [0u8; MAX_MESSAGE_LEN];
+    let mut tx = [0u8; MAX_MESSAGE_LEN];
+    loop {
+        let (n, from) = match socket.recv_from(&mut rx).await {
+            Ok(r) => r,
+            Err(NetworkError::BufferFull) => continue,
+            Err(e) => return Err(e),
+        };
+        if let Some(len) = captive_dns_reply(&rx[..n], address, &mut tx) {
+            socket.send_to(&tx[..len], from).await?;
+        }
+    }
+}
+
+// ===== 流程 =====
+
+/// 门户所需的 Socket 缓冲区
+pub struct PortalBuffers {
+    /// DHCP 服务器
+    pub dhcp: DhcpServerBuffers,
+    /// 门户 DNS
+    pub dns: CaptiveDnsBuffers,
+}
+
+impl PortalBuffers {
+    /// 创建缓冲区 (约 5 KB，建议放在 `StaticCell` 中)
+    pub const fn new() -> Self {
+        Self { dhcp: DhcpServerBuffers::new(), dns: CaptiveDnsBuffers::new() }
+    }
+}
+
+impl Default for PortalBuffers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 运行配网门户直到收到凭据
+///
+/// 在 AP 接口的协议栈上同时运行 DHCP 服务器、门户 DNS 与 HTTP 门户 (2 个连接)。
+/// 凭据在返回前已保存；DHCP/DNS Socket 出错时返回错误。
+pub async fn run_portal<const N: usize>(
+    stack: &NetworkStack<'_>,
+    buffers: &mut PortalBuffers,
+    dhcp: &mut DhcpServer<N>,
+    portal: &Portal<'_>,
+) -> Result<StaCredentials, ProvisionError> {
+    log_info!("Provisioning portal started");
+    let config = HttpConfig::default().with_max_body(MAX_BODY_LEN);
+    let services = select(
+        dhcp_server::run(stack, &mut buffers.dhcp, dhcp),
+        run_captive_dns(stack, &mut buffers.dns, portal.address),
+    );
+    match select3(portal.wait(), http::listen::<2, _>(stack.stack(), &config, portal), services).await {
+        Either3::First(creds) => Ok(creds),
+        Either3::Second(never) => never,
+        Either3::Third(Either::First(result) | Either::Second(result)) => {
+            result?;
+            Err(ProvisionError::Network(NetworkError::SocketClosed))
+        }
+    }
+}
+
+/// 关闭热点并以新凭据连接 STA
+///
+/// 连接超时取 `WIFI_CONNECT_TIMEOUT_MS`；IP 地址仍由 STA 接口的 DHCP 客户端获取。
+pub async fn switch_to_sta(
+    wifi: &mut WifiController<'_>,
+    radio: &mut esp_radio::wifi::WifiController<'_>,
+    creds: &StaCredentials,
+) -> Result<(), ProvisionError> {
+    wifi.stop_ap(radio).await?;
+    wifi.set_credentials(&creds.ssid, &creds.password)?;
+    radio.set_config(&wifi.radio_config()).map_err(|_| WifiError::ConfigError)?;
+    if !radio.is_started().unwrap_or(false) {
+        radio.start_async().await.map_err(|_| WifiError::InternalError)?;
+    }
+
+    let timeout = Duration::from_millis(WIFI_CONNECT_TIMEOUT_MS as u64);
+    match with_timeout(timeout, radio.connect_async()).await {
+        Ok(Ok(())) => {
+            wifi.set_connected(true);
+            log_info!("Connected to provisioned network {}", creds.ssid.as_str());
+            Ok(())
+        }
+        Ok(Err(_)) => Err(WifiError::ConnectionFailed.into()),
+        Err(_) => Err(WifiError::Timeout.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_credentials_parse_and_encode() {
+        let creds = StaCredentials::from_form("ssid=My+Home%21&password=p%40ssw0rd%26x").unwrap();
+        assert_eq!((creds.ssid.as_str(), creds.password.as_str()), ("My Home!", "p@ssw0rd&x"));
+        assert_eq!(StaCredentials::decode(&creds.encode()), Ok(creds));
+
+        let open = StaCredentials::from_json(r#"{"ssid":"cafe \"guest\""}"#).unwrap();
+        assert!(open.is_open() && open.ssid == "cafe \"guest\"");
+
+        assert_eq!(StaCredentials::from_form("password=12345678"), Err(ProvisionError::InvalidSsid));
+        assert_eq!(StaCredentials::from_form("ssid=a&password=short"), Err(ProvisionError::InvalidPassword));
+        assert_eq!(StaCredentials::from_form("ssid=a%4"), Err(ProvisionError::BadRequest));
+        assert_eq!(StaCredentials::decode(&[FORMAT_VERSION, 5, b'a']), Err(ProvisionError::Corrupt));
+    }
+
+    #[test]
+    fn test_captive_dns_reply() {
+        let query = [
+            0x12, 0x34, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0, // 头部 (RD)
+            7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 3, b'c', b'o', b'm', 0, // 域名
+            0, 1, 0, 1, // A / IN
+        ];
+        let mut out = [0u8; 64];
+        let len = captive_dns_reply(&query, Ipv4Address::new(192, 168, 4, 1), &mut out).unwrap();
+        assert_eq!(len, query.len() + 16);
+        assert_eq!(&out[..2], &[0x12, 0x34]);
+        assert_eq!(be16(&out, 2), 0x8580);
+        assert_eq!(be16(&out, 6), 1);
+        assert_eq!(&out[len - 4..len], &[192, 168, 4, 1]);
+
+        // AAAA 查询: 无记录
+        let mut aaaa = query;
+        aaaa[query.len() - 3] = 28;
+        let len = captive_dns_reply(&aaaa, Ipv4Address::new(192, 168, 4, 1), &mut out).unwrap();
+        assert_eq!((len, be16(&out, 6)), (query.len(), 0));
+
+        // 应答报文不处理
+        let mut response = query;
+        response[2] |= 0x80;
+        assert_eq!(captive_dns_reply(&response, Ipv4Address::new(192, 168, 4, 1), &mut out), None);
+    }
+}
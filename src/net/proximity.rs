@@ -0,0 +1,413 @@
+//! BLE 距离与区域估计
+//!
+//! 在 BLE 扫描结果之上做存在检测的基础构件:
+//! - 每个对端独立的 RSSI 指数平滑 (EWMA)
+//! - 对数路径损耗模型估算距离，用 1 米处实测功率校准
+//! - 区域划分 (Immediate / Near / Far)，边界带迟滞，避免在边界处来回跳变
+//! - 区域进入/离开事件，超时未再扫描到的对端视为离开
+//!
+//! 距离估算只是量级参考 (人体遮挡、多径可造成数米误差)，区域判定更可靠。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::proximity::{Calibration, ProximityTracker, ZoneEvent};
+//!
+//! // 1 米处实测平均 RSSI -61 dBm
+//! let calibration = Calibration::new(-61).with_zones(0.5, 3.0);
+//! let mut tracker = ProximityTracker::<16>::new(calibration);
+//!
+//! // 扫描回调中
+//! for event in tracker.observe(report.addr, report.rssi, Instant::now()) {
+//!     log_info!("{} {} {}", MacAddr(event.addr), event.kind.name(), event.zone.name());
+//! }
+//!
+//! // 定期清理离开的对端
+//! for event in tracker.expire(Instant::now()) { /* ... */ }
+//! ```
+
+use embassy_time::{Duration, Instant};
+use heapless::Vec;
+
+use crate::util::dsp::smoothing::EmaF32;
+use crate::util::dsp::Filter;
+
+/// 默认平滑系数
+pub const DEFAULT_ALPHA: f32 = 0.3;
+
+/// 默认离开超时
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+// ===== 区域 =====
+
+/// 接近区域 (由近到远排序)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Zone {
+    /// 紧邻 (默认 < 0.5 米)
+    Immediate,
+    /// 附近 (默认 < 3 米)
+    Near,
+    /// 远处
+    Far,
+}
+
+impl Zone {
+    /// 区域名称
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Immediate => "immediate",
+            Self::Near => "near",
+            Self::Far => "far",
+        }
+    }
+}
+
+/// 区域事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZoneEventKind {
+    /// 进入区域
+    Enter,
+    /// 离开区域
+    Exit,
+}
+
+impl ZoneEventKind {
+    /// 事件名称
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Enter => "enter",
+            Self::Exit => "exit",
+        }
+    }
+}
+
+/// 区域事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZoneEvent {
+    /// 对端地址
+    pub addr: [u8; 6],
+    /// 区域
+    pub zone: Zone,
+    /// 进入或离开
+    pub kind: ZoneEventKind,
+}
+
+impl ZoneEvent {
+    const fn enter(addr: [u8; 6], zone: Zone) -> Self {
+        Self { addr, zone, kind: ZoneEventKind::Enter }
+    }
+
+    const fn exit(addr: [u8; 6], zone: Zone) -> Self {
+        Self { addr, zone, kind: ZoneEventKind::Exit }
+    }
+}
+
+// ===== 校准 =====
+
+/// 路径损耗校准
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Calibration {
+    /// 1 米处的 RSSI (dBm)
+    pub measured_power: i8,
+    /// 路径损耗指数 (自由空间 2.0，室内通常 2.0 ~ 4.0)
+    pub path_loss: f32,
+    /// Immediate 区域外边界 (米)
+    pub immediate_m: f32,
+    /// Near 区域外边界 (米)
+    pub near_m: f32,
+    /// 区域边界迟滞 (dB)
+    pub hysteresis_db: f32,
+}
+
+impl Calibration {
+    /// 使用 1 米处实测功率创建
+    pub const fn new(measured_power: i8) -> Self {
+        Self { measured_power, path_loss: 2.0, immediate_m: 0.5, near_m: 3.0, hysteresis_db: 3.0 }
+    }
+
+    /// 由 1 米处的一组 RSSI 采样计算实测功率 (平均值)
+    pub fn from_samples(samples: &[i8]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let sum: i32 = samples.iter().map(|&s| s as i32).sum();
+        Some(Self::new((sum / samples.len() as i32) as i8))
+    }
+
+    /// 设置路径损耗指数
+    pub const fn with_path_loss(mut self, exponent: f32) -> Self {
+        self.path_loss = exponent;
+        self
+    }
+
+    /// 设置区域边界 (米)
+    pub const fn with_zones(mut self, immediate_m: f32, near_m: f32) -> Self {
+        self.immediate_m = immediate_m;
+        self.near_m = near_m;
+        self
+    }
+
+    /// 设置迟滞 (dB)
+    pub const fn with_hysteresis_db(mut self, db: f32) -> Self {
+        self.hysteresis_db = db;
+        self
+    }
+
+    /// 由 RSSI 估算距离 (米)
+    pub fn distance_m(&self, rssi: f32) -> f32 {
+        pow10((self.measured_power as f32 - rssi) / (10.0 * self.path_loss))
+    }
+
+    /// 指定距离处的预期 RSSI
+    pub fn rssi_at(&self, distance_m: f32) -> f32 {
+        self.measured_power as f32 - 10.0 * self.path_loss * log10(distance_m)
+    }
+
+    /// 判定区域
+    ///
+    /// 已处于某区域时，边界向远离当前区域的方向偏移 `hysteresis_db`。
+    pub fn classify(&self, rssi: f32, current: Option<Zone>) -> Zone {
+        let shift = |boundary: Zone| match current {
+            Some(zone) if zone <= boundary => -self.hysteresis_db,
+            Some(_) => self.hysteresis_db,
+            None => 0.0,
+        };
+        if rssi >= self.rssi_at(self.immediate_m) + shift(Zone::Immediate) {
+            Zone::Immediate
+        } else if rssi >= self.rssi_at(self.near_m) + shift(Zone::Near) {
+            Zone::Near
+        } else {
+            Zone::Far
+        }
+    }
+}
+
+impl Default for Calibration {
+    /// 0 dBm 发射功率下常见的 1 米 RSSI
+    fn default() -> Self {
+        Self::new(-59)
+    }
+}
+
+// ===== 跟踪器 =====
+
+/// 对端接近状态
+#[derive(Debug, Clone, Copy)]
+pub struct PeerProximity {
+    /// 地址
+    pub addr: [u8; 6],
+    /// 平滑后的 RSSI
+    pub rssi: f32,
+    /// 估算距离 (米)
+    pub distance_m: f32,
+    /// 当前区域
+    pub zone: Zone,
+    /// 最后一次扫描到的时间
+    pub last_seen: Instant,
+}
+
+struct Peer {
+    addr: [u8; 6],
+    filter: EmaF32,
+    rssi: f32,
+    zone: Zone,
+    last_seen: Instant,
+}
+
+/// 接近跟踪器 (最多同时跟踪 `N` 个对端)
+pub struct ProximityTracker<const N: usize> {
+    calibration: Calibration,
+    alpha: f32,
+    timeout: Duration,
+    peers: Vec<Peer, N>,
+}
+
+impl<const N: usize> ProximityTracker<N> {
+    /// 创建
+    pub const fn new(calibration: Calibration) -> Self {
+        Self { calibration, alpha: DEFAULT_ALPHA, timeout: DEFAULT_TIMEOUT, peers: Vec::new() }
+    }
+
+    /// 设置平滑系数 (0.0 ~ 1.0，越小越平滑、响应越慢)
+    pub const fn with_alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// 设置离开超时
+    pub const fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 校准参数
+    pub fn calibration(&self) -> &Calibration {
+        &self.calibration
+    }
+
+    /// 记录一次扫描结果
+    ///
+    /// 首次出现时产生 Enter；区域变化时依次产生旧区域 Exit 与新区域 Enter。
+    /// 跟踪表已满时淘汰最久未出现的对端 (产生其 Exit)。
+    pub fn observe(&mut self, addr: [u8; 6], rssi: i8, now: Instant) -> Vec<ZoneEvent, 3> {
+        let mut events = Vec::new();
+
+        let index = match self.peers.iter().position(|p| p.addr == addr) {
+            Some(index) => index,
+            None => {
+                if self.peers.is_full() {
+                    if let Some(oldest) = (0..self.peers.len()).min_by_key(|&i| self.peers[i].last_seen) {
+                        let evicted = self.peers.swap_remove(oldest);
+                        let _ = events.push(ZoneEvent::exit(evicted.addr, evicted.zone));
+                    }
+                }
+                let mut filter = EmaF32::new(self.alpha);
+                let rssi = filter.update(rssi as f32);
+                let zone = self.calibration.classify(rssi, None);
+                let _ = self.peers.push(Peer { addr, filter, rssi, zone, last_seen: now });
+                let _ = events.push(ZoneEvent::enter(addr, zone));
+                return events;
+            }
+        };
+
+        let peer = &mut self.peers[index];
+        peer.rssi = peer.filter.update(rssi as f32);
+        peer.last_seen = now;
+        let zone = self.calibration.classify(peer.rssi, Some(peer.zone));
+        if zone != peer.zone {
+            let _ = events.push(ZoneEvent::exit(addr, peer.zone));
+            let _ = events.push(ZoneEvent::enter(addr, zone));
+            peer.zone = zone;
+        }
+        events
+    }
+
+    /// 移除超时未出现的对端
+    ///
+    /// # 返回
+    /// 被移除对端的 Exit 事件
+    pub fn expire(&mut self, now: Instant) -> Vec<ZoneEvent, N> {
+        let mut events = Vec::new();
+        let timeout = self.timeout;
+        let mut i = 0;
+        while i < self.peers.len() {
+            if now.saturating_duration_since(self.peers[i].last_seen) >= timeout {
+                let peer = self.peers.swap_remove(i);
+                let _ = events.push(ZoneEvent::exit(peer.addr, peer.zone));
+            } else {
+                i += 1;
+            }
+        }
+        events
+    }
+
+    /// 查询对端状态
+    pub fn get(&self, addr: &[u8; 6]) -> Option<PeerProximity> {
+        self.peers.iter().find(|p| p.addr == *addr).map(|p| self.snapshot(p))
+    }
+
+    /// 全部跟踪中的对端
+    pub fn peers(&self) -> impl Iterator<Item = PeerProximity> + '_ {
+        self.peers.iter().map(|p| self.snapshot(p))
+    }
+
+    /// 指定区域内的对端数量
+    pub fn count_in(&self, zone: Zone) -> usize {
+        self.peers.iter().filter(|p| p.zone == zone).count()
+    }
+
+    fn snapshot(&self, peer: &Peer) -> PeerProximity {
+        PeerProximity {
+            addr: peer.addr,
+            rssi: peer.rssi,
+            distance_m: self.calibration.distance_m(peer.rssi),
+            zone: peer.zone,
+            last_seen: peer.last_seen,
+        }
+    }
+}
+
+// ===== 数学辅助 (no_std 下无 libm) =====
+
+const LN_2: f32 = core::f32::consts::LN_2;
+const LN_10: f32 = core::f32::consts::LN_10;
+
+/// 常用对数 (x > 0)
+fn log10(x: f32) -> f32 {
+    if x <= 0.0 {
+        return f32::NEG_INFINITY;
+    }
+    // x = m * 2^e，m ∈ [1, 2)；ln(m) = 2·atanh((m-1)/(m+1))
+    let bits = x.to_bits();
+    let exponent = ((bits >> 23) & 0xff) as i32 - 127;
+    let m = f32::from_bits((bits & 0x007f_ffff) | 0x3f80_0000);
+    let s = (m - 1.0) / (m + 1.0);
+    let s2 = s * s;
+    let ln_m = 2.0 * s * (1.0 + s2 * (1.0 / 3.0 + s2 * (1.0 / 5.0 + s2 / 7.0)));
+    (exponent as f32 * LN_2 + ln_m) / LN_10
+}
+
+/// 10 的 x 次方 (结果限制在 f32 正规数范围内)
+fn pow10(x: f32) -> f32 {
+    let y = (x * LN_10).clamp(-87.0, 88.0);
+    // e^y = 2^k · e^r，|r| < ln2
+    let k = (y / LN_2) as i32;
+    let r = y - k as f32 * LN_2;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    for n in 1..=8 {
+        term *= r / n as f32;
+        sum += term;
+    }
+    sum * f32::from_bits(((k + 127) as u32) << 23)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        let diff = a - b;
+        diff < 1e-3 * b.max(1.0) && -diff < 1e-3 * b.max(1.0)
+    }
+
+    #[test]
+    fn test_distance_model() {
+        let calibration = Calibration::new(-59);
+        assert!(close(calibration.distance_m(-59.0), 1.0));
+        assert!(close(calibration.distance_m(-79.0), 10.0));
+        assert!(close(calibration.distance_m(-53.0), 0.501));
+        assert!(close(calibration.rssi_at(3.0), -68.542));
+
+        assert_eq!(Calibration::from_samples(&[-60, -62, -61]).map(|c| c.measured_power), Some(-61));
+
+        // 迟滞: Near 外边界约 -68.5 dBm，±3 dB
+        assert_eq!(calibration.classify(-70.0, None), Zone::Far);
+        assert_eq!(calibration.classify(-70.0, Some(Zone::Near)), Zone::Near);
+        assert_eq!(calibration.classify(-67.0, Some(Zone::Far)), Zone::Far);
+        assert_eq!(calibration.classify(-65.0, Some(Zone::Far)), Zone::Near);
+    }
+
+    #[test]
+    fn test_zone_events() {
+        let mut tracker = ProximityTracker::<2>::new(Calibration::new(-59)).with_alpha(1.0);
+        let t = Instant::from_secs(100);
+        let (a, b, c) = ([1; 6], [2; 6], [3; 6]);
+
+        assert_eq!(tracker.observe(a, -80, t).as_slice(), &[ZoneEvent::enter(a, Zone::Far)]);
+        assert!(tracker.observe(a, -70, t).is_empty());
+        assert_eq!(
+            tracker.observe(a, -45, t).as_slice(),
+            &[ZoneEvent::exit(a, Zone::Far), ZoneEvent::enter(a, Zone::Immediate)]
+        );
+
+        tracker.observe(b, -60, t + Duration::from_secs(5));
+        // 表满，淘汰最久未出现的 a
+        let events = tracker.observe(c, -60, t + Duration::from_secs(6));
+        assert_eq!(events.as_slice(), &[ZoneEvent::exit(a, Zone::Immediate), ZoneEvent::enter(c, Zone::Near)]);
+
+        let expired = tracker.expire(t + Duration::from_secs(15));
+        assert_eq!(expired.as_slice(), &[ZoneEvent::exit(b, Zone::Near)]);
+        assert_eq!(tracker.count_in(Zone::Near), 1);
+    }
+}
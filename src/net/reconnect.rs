@@ -0,0 +1,320 @@
+//! WiFi 自动重连
+//!
+//! `WifiController` 的 `auto_reconnect` 开关由这里的重连任务执行:
+//! - 等待 STA 断开，更新控制器状态并投递 `StaDisconnected` 事件
+//! - 按指数退避 (`WIFI_RECONNECT_INTERVAL_MS` 起，封顶 `max_delay`) 重新关联
+//! - 关联成功后重新运行 DHCP，取得地址才算恢复
+//! - 连续失败 `max_attempts` 次后进入冷却，冷却结束重新开始一轮
+//! - 连接状态变化经 `CriticalWatch` 广播，应用用 `watcher()` 订阅；IP 同步到 `sys::status`
+//!
+//! 与 `net::supervisor` 的分工: 监管器按链路质量决定是否主动重连/回退，
+//! 本任务只负责断开后的恢复。射频与 DHCP 操作由 `ReconnectLink` 实现
+//! (通常包装 `esp_radio::wifi::WifiController` 与 `NetworkStack`)。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::reconnect::{self, ConnectionState, ReconnectLink, ReconnectPolicy, Reconnector};
+//!
+//! struct Sta<'a> { radio: esp_radio::wifi::WifiController<'a>, stack: &'a NetworkStack<'a> }
+//!
+//! impl ReconnectLink for Sta<'_> {
+//!     async fn wait_disconnected(&mut self) -> DisconnectReason {
+//!         self.radio.wait_for_event(esp_radio::wifi::WifiEvent::StaDisconnected).await;
+//!         DisconnectReason::Unspecified
+//!     }
+//!     async fn reconnect(&mut self) -> Result<(), WifiError> {
+//!         self.radio.connect_async().await.map_err(|_| WifiError::ConnectionFailed)
+//!     }
+//!     async fn renew_ip(&mut self, timeout: Duration) -> Result<([u8; 4], [u8; 4]), WifiError> {
+//!         let ip = self.stack.restart_dhcp(timeout).await.map_err(|_| WifiError::Timeout)?;
+//!         Ok((ip.octets(), self.stack.gateway().map_or([0; 4], |g| g.octets())))
+//!     }
+//! }
+//!
+//! #[embassy_executor::task]
+//! async fn reconnect_task(wifi: &'static mut WifiController<'static>, mut sta: Sta<'static>) {
+//!     Reconnector::new(ReconnectPolicy::new()).run(wifi, &mut sta).await
+//! }
+//!
+//! // 应用侧
+//! let mut watcher = reconnect::watcher().unwrap();
+//! if let ConnectionState::Online { ip } = watcher.changed().await { /* ... */ }
+//! ```
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::watch::Receiver;
+use embassy_time::{Duration, Timer};
+
+use super::config::{DHCP_TIMEOUT_SECS, WIFI_MAX_RECONNECT_ATTEMPTS, WIFI_RECONNECT_INTERVAL_MS};
+use super::wifi::{DisconnectReason, WifiController, WifiError};
+use crate::sync::primitives::CriticalWatch;
+use crate::sys::status::STATUS;
+use crate::util::log::*;
+
+/// 同时订阅连接状态的观察者数量
+pub const CONNECTION_WATCHERS: usize = 4;
+
+// ===== 连接状态 =====
+
+/// 连接状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// 已断开
+    Disconnected {
+        /// 断开原因
+        reason: DisconnectReason,
+    },
+    /// 正在重连 (第 `attempt` 次)
+    Reconnecting {
+        /// 尝试次数 (从 1 开始)
+        attempt: u32,
+    },
+    /// 已关联，正在获取 IP
+    Associated,
+    /// 已获取 IP，可以通信
+    Online {
+        /// IP 地址
+        ip: [u8; 4],
+    },
+    /// 重连失败，等待下一次尝试
+    Backoff {
+        /// 已失败次数
+        attempt: u32,
+        /// 距下次尝试的时间 (毫秒)
+        delay_ms: u32,
+    },
+    /// 连续失败达到上限，冷却中
+    GaveUp,
+}
+
+/// 连接状态观察者
+pub type ConnectionWatcher<'a> = Receiver<'a, CriticalSectionRawMutex, ConnectionState, CONNECTION_WATCHERS>;
+
+static STATE: CriticalWatch<ConnectionState, CONNECTION_WATCHERS> = CriticalWatch::new();
+
+/// 订阅连接状态变化 (观察者已满时返回 `None`)
+pub fn watcher() -> Option<ConnectionWatcher<'static>> {
+    STATE.receiver()
+}
+
+/// 当前连接状态 (重连任务启动前为 `None`)
+pub fn current() -> Option<ConnectionState> {
+    STATE.try_get()
+}
+
+fn publish(state: ConnectionState) {
+    match state {
+        ConnectionState::Online { ip } => STATUS.set_ip(Some(ip)),
+        ConnectionState::Disconnected { .. } => STATUS.set_ip(None),
+        _ => {}
+    }
+    STATE.sender().send(state);
+}
+
+// ===== 退避策略 =====
+
+/// 重连策略
+#[derive(Debug, Clone, Copy)]
+pub struct ReconnectPolicy {
+    /// 首次重试前的等待
+    pub initial_delay: Duration,
+    /// 退避上限
+    pub max_delay: Duration,
+    /// 一轮中连续失败的次数上限
+    pub max_attempts: u32,
+    /// 达到上限后的冷却时间
+    pub cooldown: Duration,
+    /// 关联后等待 DHCP 的超时
+    pub dhcp_timeout: Duration,
+}
+
+impl ReconnectPolicy {
+    /// 默认策略
+    pub const fn new() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(WIFI_RECONNECT_INTERVAL_MS as u64),
+            max_delay: Duration::from_secs(120),
+            max_attempts: WIFI_MAX_RECONNECT_ATTEMPTS,
+            cooldown: Duration::from_secs(600),
+            dhcp_timeout: Duration::from_secs(DHCP_TIMEOUT_SECS as u64),
+        }
+    }
+
+    /// 设置首次重试等待与退避上限
+    pub const fn with_delays(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_delay = initial;
+        self.max_delay = max;
+        self
+    }
+
+    /// 设置连续失败上限与冷却时间
+    pub const fn with_max_attempts(mut self, attempts: u32, cooldown: Duration) -> Self {
+        self.max_attempts = attempts;
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// 设置 DHCP 超时
+    pub const fn with_dhcp_timeout(mut self, timeout: Duration) -> Self {
+        self.dhcp_timeout = timeout;
+        self
+    }
+
+    /// 第 `failures` 次失败后的等待 (每次翻倍，不超过 `max_delay`)
+    pub fn delay(&self, failures: u32) -> Duration {
+        let shift = failures.saturating_sub(1).min(16);
+        let ticks = self.initial_delay.as_ticks().saturating_mul(1 << shift);
+        Duration::from_ticks(ticks.min(self.max_delay.as_ticks()))
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ===== 重连任务 =====
+
+/// 射频与 DHCP 操作接口 (由应用实现)
+#[allow(async_fn_in_trait)]
+pub trait ReconnectLink {
+    /// 等待 STA 从 AP 断开
+    async fn wait_disconnected(&mut self) -> DisconnectReason;
+
+    /// 重新关联到 AP
+    async fn reconnect(&mut self) -> Result<(), WifiError>;
+
+    /// 重新运行 DHCP 并等待地址，返回 (IP, 网关)
+    async fn renew_ip(&mut self, timeout: Duration) -> Result<([u8; 4], [u8; 4]), WifiError>;
+}
+
+/// 重连统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ReconnectStats {
+    /// 断开次数
+    pub disconnects: u32,
+    /// 重连尝试次数
+    pub attempts: u32,
+    /// 成功恢复次数
+    pub recoveries: u32,
+    /// 关联成功但 DHCP 失败的次数
+    pub dhcp_failures: u32,
+    /// 进入冷却的次数
+    pub give_ups: u32,
+}
+
+/// 重连任务
+pub struct Reconnector {
+    policy: ReconnectPolicy,
+    stats: ReconnectStats,
+}
+
+impl Reconnector {
+    /// 创建重连任务
+    pub const fn new(policy: ReconnectPolicy) -> Self {
+        Self {
+            policy,
+            stats: ReconnectStats { disconnects: 0, attempts: 0, recoveries: 0, dhcp_failures: 0, give_ups: 0 },
+        }
+    }
+
+    /// 策略
+    pub fn policy(&self) -> &ReconnectPolicy {
+        &self.policy
+    }
+
+    /// 统计
+    pub fn stats(&self) -> ReconnectStats {
+        self.stats
+    }
+
+    /// 等待断开并恢复连接 (不返回)
+    ///
+    /// 控制器关闭自动重连时只记录断开，等待应用自行恢复
+    pub async fn run<L: ReconnectLink>(&mut self, wifi: &mut WifiController<'_>, link: &mut L) -> ! {
+        loop {
+            let reason = link.wait_disconnected().await;
+            self.stats.disconnects = self.stats.disconnects.wrapping_add(1);
+            wifi.mark_disconnected(reason);
+            publish(ConnectionState::Disconnected { reason });
+            if !wifi.auto_reconnect() {
+                log_info!("WiFi disconnected, auto reconnect disabled");
+                continue;
+            }
+            self.recover(wifi, link).await;
+        }
+    }
+
+    /// 重连直到取得 IP
+    async fn recover<L: ReconnectLink>(&mut self, wifi: &mut WifiController<'_>, link: &mut L) {
+        let mut failures = 0u32;
+        loop {
+            let attempt = wifi.begin_reconnect();
+            self.stats.attempts = self.stats.attempts.wrapping_add(1);
+            publish(ConnectionState::Reconnecting { attempt });
+
+            match self.attempt(wifi, link).await {
+                Ok(ip) => {
+                    self.stats.recoveries = self.stats.recoveries.wrapping_add(1);
+                    log_info!("WiFi reconnected after {} attempt(s)", attempt);
+                    publish(ConnectionState::Online { ip });
+                    return;
+                }
+                Err(_e) => {
+                    log_warn!("WiFi reconnect attempt {} failed: {}", attempt, _e);
+                }
+            }
+
+            failures += 1;
+            if failures >= self.policy.max_attempts.max(1) {
+                self.stats.give_ups = self.stats.give_ups.wrapping_add(1);
+                log_warn!("WiFi reconnect gave up after {} attempts", failures);
+                publish(ConnectionState::GaveUp);
+                Timer::after(self.policy.cooldown).await;
+                failures = 0;
+                continue;
+            }
+            let delay = self.policy.delay(failures);
+            publish(ConnectionState::Backoff { attempt: failures, delay_ms: delay.as_millis() as u32 });
+            Timer::after(delay).await;
+        }
+    }
+
+    async fn attempt<L: ReconnectLink>(
+        &mut self,
+        wifi: &mut WifiController<'_>,
+        link: &mut L,
+    ) -> Result<[u8; 4], WifiError> {
+        link.reconnect().await?;
+        wifi.set_connected(true);
+        publish(ConnectionState::Associated);
+
+        match link.renew_ip(self.policy.dhcp_timeout).await {
+            Ok((ip, gateway)) => {
+                wifi.set_ip_address(ip, gateway);
+                Ok(ip)
+            }
+            Err(e) => {
+                self.stats.dhcp_failures = self.stats.dhcp_failures.wrapping_add(1);
+                wifi.set_connected(false);
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_delay() {
+        let policy = ReconnectPolicy::new().with_delays(Duration::from_secs(2), Duration::from_secs(30));
+        let delays: [u64; 6] = core::array::from_fn(|i| policy.delay(i as u32 + 1).as_secs());
+        assert_eq!(delays, [2, 4, 8, 16, 30, 30]);
+        // 大次数不溢出
+        assert_eq!(policy.delay(u32::MAX), Duration::from_secs(30));
+    }
+}
@@ -0,0 +1,358 @@
+//! WiFi 国家码与信道规划
+//!
+//! 销往不同地区的设备需遵守当地 2.4 GHz 法规: 可用信道 (12/13/14 是否开放)
+//! 与最大发射功率。本模块描述一个管制域 (`RegDomain`):
+//! - 国家码 (ISO 3166 两字母，`01` 表示全球安全配置)
+//! - 每个信道是否允许及其最大发射功率 (dBm)
+//! - 国家码策略: 手动 (始终使用本地配置) 或自动 (关联后采用 AP 的国家信息)
+//!
+//! 内置预设: `01` 与 FCC 地区 1-11 信道、日本 1-14 信道 (14 仅 802.11b)、
+//! 其余国家 1-13 信道 (ETSI 及多数地区)。预设可按信道覆盖。
+//! 配置经 `KvStore` 持久化，在 `WifiController::set_country` 中于启动前应用。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::regdomain::{CountryCode, RegDomain};
+//!
+//! let kv = KvStore::new(&fs);
+//! let domain = RegDomain::load(&kv)?
+//!     .unwrap_or_else(|| RegDomain::for_country(CountryCode::new(*b"DE").unwrap()));
+//! controller.set_country(domain.with_tx_power(13, 14))?;
+//! domain.save(&kv)?;
+//! ```
+
+use core::fmt;
+
+use crate::fs::kv::{KvError, KvStore};
+
+/// 2.4 GHz 信道数
+pub const CHANNEL_COUNT: usize = 14;
+
+/// 持久化命名空间
+pub const KV_NAMESPACE: &str = "wifi";
+
+/// 持久化键名
+pub const KV_KEY: &str = "regdomain";
+
+/// 默认最大发射功率 (dBm，ESP32-S3 射频上限)
+pub const DEFAULT_TX_POWER_DBM: i8 = 20;
+
+/// 持久化格式: 版本 + 国家码 + 策略 + 信道掩码 + 各信道功率
+const ENCODED_LEN: usize = 1 + 2 + 1 + 2 + CHANNEL_COUNT;
+
+const FORMAT_VERSION: u8 = 1;
+
+/// 仅开放 1-11 信道的国家/地区 (FCC 及沿用其规则的地区)
+const FCC_COUNTRIES: [&[u8; 2]; 6] = [b"US", b"CA", b"MX", b"TW", b"PR", b"GU"];
+
+// ===== 国家码 =====
+
+/// 国家码 (ISO 3166-1 alpha-2，或 `01` 全球安全配置)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CountryCode([u8; 2]);
+
+impl CountryCode {
+    /// 全球安全配置
+    pub const WORLD: Self = Self(*b"01");
+
+    /// 创建 (字母转为大写；非两字母且非 `01` 时返回 `None`)
+    pub const fn new(code: [u8; 2]) -> Option<Self> {
+        if code[0] == b'0' && code[1] == b'1' {
+            return Some(Self::WORLD);
+        }
+        if !code[0].is_ascii_alphabetic() || !code[1].is_ascii_alphabetic() {
+            return None;
+        }
+        Some(Self([code[0].to_ascii_uppercase(), code[1].to_ascii_uppercase()]))
+    }
+
+    /// 从字符串解析
+    pub fn parse(code: &str) -> Option<Self> {
+        match code.as_bytes() {
+            [a, b] => Self::new([*a, *b]),
+            _ => None,
+        }
+    }
+
+    /// 原始字节 (`wifi_country_t.cc` 的前两字节)
+    pub const fn as_bytes(&self) -> [u8; 2] {
+        self.0
+    }
+
+    /// 字符串形式
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.0).unwrap_or("01")
+    }
+}
+
+impl fmt::Display for CountryCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 国家码策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CountryPolicy {
+    /// 始终使用本地配置
+    #[default]
+    Manual,
+    /// 关联后采用 AP 广播的国家信息
+    Auto,
+}
+
+// ===== 管制域 =====
+
+/// 管制域: 国家码 + 信道规划
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegDomain {
+    country: CountryCode,
+    policy: CountryPolicy,
+    /// 第 n 位对应信道 n+1
+    allowed: u16,
+    /// 各信道最大发射功率 (dBm)
+    tx_power: [i8; CHANNEL_COUNT],
+}
+
+impl RegDomain {
+    /// 全球安全配置 (1-11 信道)
+    pub const WORLD: Self = Self::with_range(CountryCode::WORLD, 11);
+
+    const fn with_range(country: CountryCode, last_channel: u8) -> Self {
+        Self {
+            country,
+            policy: CountryPolicy::Manual,
+            allowed: (1u16 << last_channel) - 1,
+            tx_power: [DEFAULT_TX_POWER_DBM; CHANNEL_COUNT],
+        }
+    }
+
+    /// 按国家码选择预设
+    pub fn for_country(country: CountryCode) -> Self {
+        let last_channel = match &country.0 {
+            b"01" => 11,
+            b"JP" => 14,
+            code if FCC_COUNTRIES.contains(&code) => 11,
+            _ => 13,
+        };
+        Self::with_range(country, last_channel)
+    }
+
+    /// 设置国家码策略
+    pub const fn with_policy(mut self, policy: CountryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// 开放或关闭信道 (超出 1-14 时忽略)
+    pub const fn with_channel(mut self, channel: u8, allowed: bool) -> Self {
+        if channel >= 1 && channel as usize <= CHANNEL_COUNT {
+            let bit = 1u16 << (channel - 1);
+            self.allowed = if allowed { self.allowed | bit } else { self.allowed & !bit };
+        }
+        self
+    }
+
+    /// 设置信道最大发射功率 (dBm，超出 1-14 时忽略)
+    pub const fn with_tx_power(mut self, channel: u8, dbm: i8) -> Self {
+        if channel >= 1 && channel as usize <= CHANNEL_COUNT {
+            self.tx_power[channel as usize - 1] = dbm;
+        }
+        self
+    }
+
+    /// 国家码
+    pub const fn country(&self) -> CountryCode {
+        self.country
+    }
+
+    /// 国家码策略
+    pub const fn policy(&self) -> CountryPolicy {
+        self.policy
+    }
+
+    /// 信道是否允许使用
+    pub const fn is_allowed(&self, channel: u8) -> bool {
+        channel >= 1 && channel as usize <= CHANNEL_COUNT && self.allowed & (1 << (channel - 1)) != 0
+    }
+
+    /// 信道最大发射功率 (dBm，不允许的信道返回 `None`)
+    pub const fn max_tx_power(&self, channel: u8) -> Option<i8> {
+        if self.is_allowed(channel) {
+            Some(self.tx_power[channel as usize - 1])
+        } else {
+            None
+        }
+    }
+
+    /// 信道最大发射功率，单位 0.25 dBm (`esp_wifi_set_max_tx_power` 的参数)
+    pub const fn max_tx_power_quarter_dbm(&self, channel: u8) -> Option<i8> {
+        match self.max_tx_power(channel) {
+            Some(dbm) => Some(dbm.saturating_mul(4)),
+            None => None,
+        }
+    }
+
+    /// 允许的信道
+    pub fn channels(&self) -> impl Iterator<Item = u8> + '_ {
+        (1..=CHANNEL_COUNT as u8).filter(|ch| self.is_allowed(*ch))
+    }
+
+    /// 连续信道段 (起始信道, 信道数)，对应 `wifi_country_t.schan/nchan`
+    ///
+    /// 允许的信道不连续时返回首段。
+    pub fn channel_range(&self) -> Option<(u8, u8)> {
+        let first = self.channels().next()?;
+        let count = (first..=CHANNEL_COUNT as u8).take_while(|ch| self.is_allowed(*ch)).count() as u8;
+        Some((first, count))
+    }
+
+    /// 所有允许信道中最低的功率上限 (`wifi_country_t.max_tx_power`)
+    pub fn max_tx_power_overall(&self) -> Option<i8> {
+        self.channels().filter_map(|ch| self.max_tx_power(ch)).min()
+    }
+
+    /// 检查信道 (AP 信道、指定信道扫描)
+    pub fn check_channel(&self, channel: u8) -> Result<(), RegDomainError> {
+        if self.is_allowed(channel) { Ok(()) } else { Err(RegDomainError::ChannelNotAllowed(channel)) }
+    }
+
+    // ===== 持久化 =====
+
+    /// 编码
+    pub fn encode(&self) -> [u8; ENCODED_LEN] {
+        let mut out = [0u8; ENCODED_LEN];
+        out[0] = FORMAT_VERSION;
+        out[1..3].copy_from_slice(&self.country.0);
+        out[3] = match self.policy {
+            CountryPolicy::Manual => 0,
+            CountryPolicy::Auto => 1,
+        };
+        out[4..6].copy_from_slice(&self.allowed.to_le_bytes());
+        for (dst, power) in out[6..].iter_mut().zip(self.tx_power) {
+            *dst = power as u8;
+        }
+        out
+    }
+
+    /// 解码
+    pub fn decode(data: &[u8]) -> Result<Self, RegDomainError> {
+        if data.len() != ENCODED_LEN || data[0] != FORMAT_VERSION {
+            return Err(RegDomainError::Corrupt);
+        }
+        let country = CountryCode::new([data[1], data[2]]).ok_or(RegDomainError::InvalidCountry)?;
+        let policy = match data[3] {
+            0 => CountryPolicy::Manual,
+            1 => CountryPolicy::Auto,
+            _ => return Err(RegDomainError::Corrupt),
+        };
+        let allowed = u16::from_le_bytes([data[4], data[5]]) & ((1 << CHANNEL_COUNT) - 1);
+        let mut tx_power = [0i8; CHANNEL_COUNT];
+        for (power, byte) in tx_power.iter_mut().zip(&data[6..]) {
+            *power = *byte as i8;
+        }
+        Ok(Self { country, policy, allowed, tx_power })
+    }
+
+    /// 保存到键值存储
+    pub fn save(&self, kv: &KvStore<'_>) -> Result<(), RegDomainError> {
+        kv.set(KV_NAMESPACE, KV_KEY, &self.encode())?;
+        Ok(())
+    }
+
+    /// 从键值存储读取 (未保存过返回 `None`)
+    pub fn load(kv: &KvStore<'_>) -> Result<Option<Self>, RegDomainError> {
+        if !kv.contains(KV_NAMESPACE, KV_KEY) {
+            return Ok(None);
+        }
+        let mut buf = [0u8; ENCODED_LEN];
+        let len = kv.get(KV_NAMESPACE, KV_KEY, &mut buf).map_err(|e| match e {
+            KvError::BufferTooSmall(_) => RegDomainError::Corrupt,
+            e => RegDomainError::Kv(e),
+        })?;
+        Self::decode(&buf[..len]).map(Some)
+    }
+}
+
+impl Default for RegDomain {
+    fn default() -> Self {
+        Self::WORLD
+    }
+}
+
+impl fmt::Display for RegDomain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({:?}):", self.country, self.policy)?;
+        for ch in self.channels() {
+            write!(f, " {}@{}dBm", ch, self.tx_power[ch as usize - 1])?;
+        }
+        Ok(())
+    }
+}
+
+/// 管制域错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegDomainError {
+    /// 国家码无效
+    InvalidCountry,
+    /// 信道在当前管制域不可用
+    ChannelNotAllowed(u8),
+    /// 持久化数据损坏
+    Corrupt,
+    /// 键值存储错误
+    Kv(KvError),
+}
+
+impl fmt::Display for RegDomainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidCountry => write!(f, "Invalid country code"),
+            Self::ChannelNotAllowed(ch) => write!(f, "Channel {} not allowed in regulatory domain", ch),
+            Self::Corrupt => write!(f, "Regulatory domain data corrupt"),
+            Self::Kv(e) => write!(f, "KV store error: {}", e),
+        }
+    }
+}
+
+impl From<KvError> for RegDomainError {
+    fn from(e: KvError) -> Self {
+        Self::Kv(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_and_overrides() {
+        let us = RegDomain::for_country(CountryCode::parse("us").unwrap());
+        assert_eq!(us.country().as_str(), "US");
+        assert_eq!(us.channel_range(), Some((1, 11)));
+        assert!(!us.is_allowed(12));
+
+        let jp = RegDomain::for_country(CountryCode::new(*b"JP").unwrap());
+        assert!(jp.is_allowed(14));
+
+        let de = RegDomain::for_country(CountryCode::new(*b"DE").unwrap()).with_tx_power(13, 14).with_channel(1, false);
+        assert_eq!(de.channel_range(), Some((2, 12)));
+        assert_eq!(de.max_tx_power(13), Some(14));
+        assert_eq!(de.max_tx_power_quarter_dbm(13), Some(56));
+        assert_eq!(de.max_tx_power(1), None);
+        assert_eq!(de.max_tx_power_overall(), Some(14));
+        assert_eq!(de.check_channel(14), Err(RegDomainError::ChannelNotAllowed(14)));
+
+        assert_eq!(CountryCode::parse("1A"), None);
+        assert_eq!(CountryCode::parse("01"), Some(CountryCode::WORLD));
+    }
+
+    #[test]
+    fn test_encode_roundtrip() {
+        let domain = RegDomain::for_country(CountryCode::new(*b"FR").unwrap())
+            .with_policy(CountryPolicy::Auto)
+            .with_tx_power(6, -2);
+        assert_eq!(RegDomain::decode(&domain.encode()), Ok(domain));
+        assert_eq!(RegDomain::decode(&[0u8; 3]), Err(RegDomainError::Corrupt));
+    }
+}
@@ -0,0 +1,549 @@
+//! WiFi/BLE 事件录制与确定性回放
+//!
+//! 现场复现的竞态问题往往依赖事件的先后顺序与间隔。本模块将带时间戳的
+//! `WifiEvent` / `BleEvent` 流录制到文件，之后在仿真模式下按原始时序
+//! 注入控制器 (`inject_event`)，驱动相同的状态迁移。
+//!
+//! # 文件格式
+//!
+//! `"NEVR"` + 版本 (1 字节)，之后为变长记录:
+//! `[长度 u8][时间戳 μs u64 LE][来源 u8][标签 u8][负载...]`，长度不含自身。
+//! 当前构建未启用的来源 (如仅 `wifi` 时的 BLE 事件) 在回放时跳过。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::replay::{EventRecorder, EventPlayer, ReplaySpeed, NetEvent};
+//!
+//! // 现场: 录制
+//! let mut recorder = EventRecorder::create(&fs, "/log/net.evr")?;
+//! loop {
+//!     let envelope = wifi.recv_envelope().await;
+//!     recorder.record_envelope(&envelope)?;
+//!     handle(envelope.event);
+//! }
+//!
+//! // 台架: 回放
+//! let mut player = EventPlayer::open(&fs, "/log/net.evr")?;
+//! let count = replay::replay(&mut player, &mut (&mut wifi, &mut ble), ReplaySpeed::RealTime).await?;
+//! ```
+
+use core::fmt;
+
+use embassy_time::{Duration, Instant, Timer};
+
+use crate::fs::littlefs::{File, FileSystem, FsError, OpenOptions};
+use crate::sync::bus::Envelope;
+
+#[cfg(any(feature = "ble", feature = "ble-esp"))]
+use super::ble::{self, BleController, BleEvent};
+#[cfg(feature = "wifi")]
+use super::wifi::{self, WifiController, WifiEvent};
+
+/// 文件魔数
+const MAGIC: &[u8; 4] = b"NEVR";
+
+/// 格式版本
+const VERSION: u8 = 1;
+
+/// 单条记录最大长度 (含长度字节)
+pub const MAX_RECORD: usize = 32;
+
+const SOURCE_WIFI: u8 = 0;
+const SOURCE_BLE: u8 = 1;
+
+/// 回放错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayError {
+    /// 文件系统错误
+    Fs(FsError),
+    /// 文件格式错误
+    Corrupt,
+    /// 不支持的格式版本
+    UnsupportedVersion,
+}
+
+impl From<FsError> for ReplayError {
+    fn from(e: FsError) -> Self {
+        ReplayError::Fs(e)
+    }
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fs(e) => write!(f, "Filesystem error: {:?}", e),
+            Self::Corrupt => write!(f, "Corrupt event recording"),
+            Self::UnsupportedVersion => write!(f, "Unsupported recording version"),
+        }
+    }
+}
+
+// ===== 事件 =====
+
+/// 可录制的网络事件
+#[derive(Debug, Clone)]
+pub enum NetEvent {
+    /// WiFi 事件
+    #[cfg(feature = "wifi")]
+    Wifi(WifiEvent),
+    /// BLE 事件
+    #[cfg(any(feature = "ble", feature = "ble-esp"))]
+    Ble(BleEvent),
+}
+
+#[cfg(feature = "wifi")]
+impl From<WifiEvent> for NetEvent {
+    fn from(event: WifiEvent) -> Self {
+        NetEvent::Wifi(event)
+    }
+}
+
+#[cfg(any(feature = "ble", feature = "ble-esp"))]
+impl From<BleEvent> for NetEvent {
+    fn from(event: BleEvent) -> Self {
+        NetEvent::Ble(event)
+    }
+}
+
+/// 带时间戳的事件
+#[derive(Debug, Clone)]
+pub struct RecordedEvent {
+    /// 相对录制开始的时间
+    pub at: Duration,
+    /// 事件
+    pub event: NetEvent,
+}
+
+// ===== 编解码 =====
+
+#[cfg(feature = "wifi")]
+const WIFI_REASONS: [wifi::DisconnectReason; 15] = {
+    use wifi::DisconnectReason::*;
+    [
+        Unspecified, AuthExpired, AuthLeave, AssocExpired, AssocTooMany, NotAuthenticated, NotAssociated,
+        AssocLeave, AssocNotAuth, BadChannel, BeaconTimeout, NoApFound, WrongPassword, ConnectionFail,
+        ApHandshakeFail,
+    ]
+};
+
+#[cfg(any(feature = "ble", feature = "ble-esp"))]
+const BLE_REASONS: [ble::DisconnectReason; 6] = {
+    use ble::DisconnectReason::*;
+    [
+        Unknown, RemoteUserTerminated, LocalHostTerminated, ConnectionTimeout, AuthenticationFailure,
+        UnacceptableConnectionParameters,
+    ]
+};
+
+/// 负载写入器
+struct Payload<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl Payload<'_> {
+    fn put(&mut self, data: &[u8]) {
+        self.buf[self.len..self.len + data.len()].copy_from_slice(data);
+        self.len += data.len();
+    }
+}
+
+/// 负载读取器
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], ReplayError> {
+        if self.data.len() < N {
+            return Err(ReplayError::Corrupt);
+        }
+        let (head, rest) = self.data.split_at(N);
+        self.data = rest;
+        let mut out = [0u8; N];
+        out.copy_from_slice(head);
+        Ok(out)
+    }
+
+    fn u8(&mut self) -> Result<u8, ReplayError> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, ReplayError> {
+        Ok(u16::from_le_bytes(self.take()?))
+    }
+}
+
+/// 编码一条记录，返回总长度 (含长度字节)
+pub fn encode(record: &RecordedEvent, out: &mut [u8; MAX_RECORD]) -> usize {
+    let mut p = Payload { buf: &mut out[..], len: 1 };
+    p.put(&record.at.as_micros().to_le_bytes());
+    match &record.event {
+        #[cfg(feature = "wifi")]
+        NetEvent::Wifi(event) => {
+            p.put(&[SOURCE_WIFI]);
+            match event {
+                WifiEvent::StaConnected => p.put(&[0]),
+                WifiEvent::StaDisconnected { reason } => {
+                    let code = WIFI_REASONS.iter().position(|r| r == reason).unwrap_or(0) as u8;
+                    p.put(&[1, code]);
+                }
+                WifiEvent::GotIp { ip, gateway, netmask } => {
+                    p.put(&[2]);
+                    p.put(ip);
+                    p.put(gateway);
+                    p.put(netmask);
+                }
+                WifiEvent::ScanDone { count } => p.put(&[3, (*count).min(u8::MAX as usize) as u8]),
+                WifiEvent::ApStaConnected { mac } => {
+                    p.put(&[4]);
+                    p.put(mac);
+                }
+                WifiEvent::ApStaDisconnected { mac } => {
+                    p.put(&[5]);
+                    p.put(mac);
+                }
+            }
+        }
+        #[cfg(any(feature = "ble", feature = "ble-esp"))]
+        NetEvent::Ble(event) => {
+            p.put(&[SOURCE_BLE]);
+            match event {
+                BleEvent::AdvertisingStarted => p.put(&[0]),
+                BleEvent::AdvertisingStopped => p.put(&[1]),
+                BleEvent::Connected { conn_handle, peer_addr } => {
+                    p.put(&[2]);
+                    p.put(&conn_handle.to_le_bytes());
+                    p.put(peer_addr);
+                }
+                BleEvent::Disconnected { conn_handle, reason } => {
+                    let code = BLE_REASONS.iter().position(|r| r == reason).unwrap_or(0) as u8;
+                    p.put(&[3]);
+                    p.put(&conn_handle.to_le_bytes());
+                    p.put(&[code]);
+                }
+                BleEvent::MtuUpdated { conn_handle, mtu } => {
+                    p.put(&[4]);
+                    p.put(&conn_handle.to_le_bytes());
+                    p.put(&mtu.to_le_bytes());
+                }
+                BleEvent::WriteRequest { conn_handle, attr_handle, len } => {
+                    p.put(&[5]);
+                    p.put(&conn_handle.to_le_bytes());
+                    p.put(&attr_handle.to_le_bytes());
+                    p.put(&(*len as u32).to_le_bytes());
+                }
+                BleEvent::ReadRequest { conn_handle, attr_handle } => {
+                    p.put(&[6]);
+                    p.put(&conn_handle.to_le_bytes());
+                    p.put(&attr_handle.to_le_bytes());
+                }
+                BleEvent::NotificationSent { conn_handle } => {
+                    p.put(&[7]);
+                    p.put(&conn_handle.to_le_bytes());
+                }
+                BleEvent::PairingComplete { conn_handle, bonded } => {
+                    p.put(&[8]);
+                    p.put(&conn_handle.to_le_bytes());
+                    p.put(&[*bonded as u8]);
+                }
+                BleEvent::ChannelOpened { conn_handle, psm } => {
+                    p.put(&[9]);
+                    p.put(&conn_handle.to_le_bytes());
+                    p.put(&psm.to_le_bytes());
+                }
+                BleEvent::ChannelClosed { conn_handle, psm } => {
+                    p.put(&[10]);
+                    p.put(&conn_handle.to_le_bytes());
+                    p.put(&psm.to_le_bytes());
+                }
+            }
+        }
+    }
+    let len = p.len;
+    out[0] = (len - 1) as u8;
+    len
+}
+
+/// 解码一条记录 (不含长度字节)
+///
+/// # 返回
+/// 来源未在当前构建中启用时返回 `Ok(None)`
+pub fn decode(data: &[u8]) -> Result<Option<RecordedEvent>, ReplayError> {
+    let mut r = Reader { data };
+    let at = Duration::from_micros(u64::from_le_bytes(r.take()?));
+    let source = r.u8()?;
+    let tag = r.u8()?;
+
+    let event = match source {
+        #[cfg(feature = "wifi")]
+        SOURCE_WIFI => NetEvent::Wifi(match tag {
+            0 => WifiEvent::StaConnected,
+            1 => WifiEvent::StaDisconnected {
+                reason: *WIFI_REASONS.get(r.u8()? as usize).ok_or(ReplayError::Corrupt)?,
+            },
+            2 => WifiEvent::GotIp { ip: r.take()?, gateway: r.take()?, netmask: r.take()? },
+            3 => WifiEvent::ScanDone { count: r.u8()? as usize },
+            4 => WifiEvent::ApStaConnected { mac: r.take()? },
+            5 => WifiEvent::ApStaDisconnected { mac: r.take()? },
+            _ => return Err(ReplayError::Corrupt),
+        }),
+        #[cfg(any(feature = "ble", feature = "ble-esp"))]
+        SOURCE_BLE => NetEvent::Ble(match tag {
+            0 => BleEvent::AdvertisingStarted,
+            1 => BleEvent::AdvertisingStopped,
+            2 => BleEvent::Connected { conn_handle: r.u16()?, peer_addr: r.take()? },
+            3 => BleEvent::Disconnected {
+                conn_handle: r.u16()?,
+                reason: *BLE_REASONS.get(r.u8()? as usize).ok_or(ReplayError::Corrupt)?,
+            },
+            4 => BleEvent::MtuUpdated { conn_handle: r.u16()?, mtu: r.u16()? },
+            5 => BleEvent::WriteRequest {
+                conn_handle: r.u16()?,
+                attr_handle: r.u16()?,
+                len: u32::from_le_bytes(r.take()?) as usize,
+            },
+            6 => BleEvent::ReadRequest { conn_handle: r.u16()?, attr_handle: r.u16()? },
+            7 => BleEvent::NotificationSent { conn_handle: r.u16()? },
+            8 => BleEvent::PairingComplete { conn_handle: r.u16()?, bonded: r.u8()? != 0 },
+            9 => BleEvent::ChannelOpened { conn_handle: r.u16()?, psm: r.u16()? },
+            10 => BleEvent::ChannelClosed { conn_handle: r.u16()?, psm: r.u16()? },
+            _ => return Err(ReplayError::Corrupt),
+        }),
+        #[allow(unreachable_patterns)]
+        SOURCE_WIFI | SOURCE_BLE => return Ok(None),
+        _ => return Err(ReplayError::Corrupt),
+    };
+    Ok(Some(RecordedEvent { at, event }))
+}
+
+// ===== 录制 =====
+
+/// 事件录制器
+pub struct EventRecorder<'a> {
+    file: File<'a>,
+    start: Instant,
+    count: u32,
+}
+
+impl<'a> EventRecorder<'a> {
+    /// 创建录制文件 (覆盖已有文件)，时间从此刻起算
+    pub fn create(fs: &'a FileSystem, path: &str) -> Result<Self, ReplayError> {
+        let mut file = fs.open(path, OpenOptions::write_only())?;
+        file.write_all(MAGIC)?;
+        file.write_all(&[VERSION])?;
+        Ok(Self { file, start: Instant::now(), count: 0 })
+    }
+
+    /// 以当前时间录制一个事件
+    pub fn record(&mut self, event: &NetEvent) -> Result<(), ReplayError> {
+        let at = Instant::now() - self.start;
+        self.record_at(at, event)
+    }
+
+    /// 按事件发布时间录制 (不受接收方处理延迟影响)
+    pub fn record_envelope<E: Clone + Into<NetEvent>>(&mut self, envelope: &Envelope<E>) -> Result<(), ReplayError> {
+        let at = envelope.timestamp.checked_duration_since(self.start).unwrap_or(Duration::from_ticks(0));
+        self.record_at(at, &envelope.event.clone().into())
+    }
+
+    /// 以指定时间录制一个事件
+    pub fn record_at(&mut self, at: Duration, event: &NetEvent) -> Result<(), ReplayError> {
+        let mut buf = [0u8; MAX_RECORD];
+        let len = encode(&RecordedEvent { at, event: event.clone() }, &mut buf);
+        self.file.write_all(&buf[..len])?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// 已录制事件数
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// 刷新到存储
+    pub fn sync(&mut self) -> Result<(), ReplayError> {
+        self.file.sync()?;
+        Ok(())
+    }
+
+    /// 结束录制，返回事件数
+    pub fn finish(mut self) -> Result<u32, ReplayError> {
+        self.file.sync()?;
+        Ok(self.count)
+    }
+}
+
+// ===== 回放 =====
+
+/// 事件读取器
+pub struct EventPlayer<'a> {
+    file: File<'a>,
+    skipped: u32,
+}
+
+impl<'a> EventPlayer<'a> {
+    /// 打开录制文件
+    pub fn open(fs: &'a FileSystem, path: &str) -> Result<Self, ReplayError> {
+        let mut file = fs.open(path, OpenOptions::read_only())?;
+        let mut header = [0u8; 5];
+        if file.read(&mut header)? != header.len() || &header[..4] != MAGIC {
+            return Err(ReplayError::Corrupt);
+        }
+        if header[4] != VERSION {
+            return Err(ReplayError::UnsupportedVersion);
+        }
+        Ok(Self { file, skipped: 0 })
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<bool, ReplayError> {
+        let mut filled = 0;
+        while filled < buf.len() {
+            match self.file.read(&mut buf[filled..])? {
+                0 if filled == 0 => return Ok(false),
+                0 => return Err(ReplayError::Corrupt),
+                n => filled += n,
+            }
+        }
+        Ok(true)
+    }
+
+    /// 读取下一个事件 (文件结束返回 `None`)
+    pub fn next_event(&mut self) -> Result<Option<RecordedEvent>, ReplayError> {
+        loop {
+            let mut len = [0u8; 1];
+            if !self.read_exact(&mut len)? {
+                return Ok(None);
+            }
+            let mut buf = [0u8; MAX_RECORD];
+            let body = buf.get_mut(..len[0] as usize).ok_or(ReplayError::Corrupt)?;
+            if !self.read_exact(body)? {
+                return Err(ReplayError::Corrupt);
+            }
+            match decode(body)? {
+                Some(record) => return Ok(Some(record)),
+                None => self.skipped += 1,
+            }
+        }
+    }
+
+    /// 因来源未启用而跳过的事件数
+    pub fn skipped(&self) -> u32 {
+        self.skipped
+    }
+}
+
+/// 回放速度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplaySpeed {
+    /// 按原始时间间隔
+    RealTime,
+    /// 按比例缩放间隔 (百分比，200 = 两倍速)
+    Scaled(u32),
+    /// 忽略间隔，依次注入
+    Immediate,
+}
+
+/// 事件注入目标
+pub trait EventSink {
+    /// 注入一个事件 (与该目标无关的事件应忽略)
+    #[allow(async_fn_in_trait)]
+    async fn inject(&mut self, event: &NetEvent);
+}
+
+#[cfg(feature = "wifi")]
+impl EventSink for WifiController<'_> {
+    async fn inject(&mut self, event: &NetEvent) {
+        #[allow(irrefutable_let_patterns)]
+        if let NetEvent::Wifi(event) = event {
+            self.inject_event(event.clone()).await;
+        }
+    }
+}
+
+#[cfg(any(feature = "ble", feature = "ble-esp"))]
+impl EventSink for BleController<'_> {
+    async fn inject(&mut self, event: &NetEvent) {
+        #[allow(irrefutable_let_patterns)]
+        if let NetEvent::Ble(event) = event {
+            self.inject_event(event.clone()).await;
+        }
+    }
+}
+
+impl<S: EventSink + ?Sized> EventSink for &mut S {
+    async fn inject(&mut self, event: &NetEvent) {
+        (**self).inject(event).await;
+    }
+}
+
+impl<A: EventSink, B: EventSink> EventSink for (A, B) {
+    async fn inject(&mut self, event: &NetEvent) {
+        self.0.inject(event).await;
+        self.1.inject(event).await;
+    }
+}
+
+/// 将录制的事件按时序注入目标
+///
+/// # 返回
+/// 注入的事件数
+pub async fn replay<S: EventSink>(
+    player: &mut EventPlayer<'_>,
+    sink: &mut S,
+    speed: ReplaySpeed,
+) -> Result<u32, ReplayError> {
+    let start = Instant::now();
+    let mut count = 0;
+    while let Some(record) = player.next_event()? {
+        let offset = match speed {
+            ReplaySpeed::RealTime => Some(record.at),
+            ReplaySpeed::Scaled(percent) => {
+                Some(Duration::from_micros(record.at.as_micros() * 100 / percent.max(1) as u64))
+            }
+            ReplaySpeed::Immediate => None,
+        };
+        if let Some(offset) = offset {
+            Timer::at(start + offset).await;
+        }
+        sink.inject(&record.event).await;
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(all(test, feature = "wifi"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codec_roundtrip() {
+        let record = RecordedEvent {
+            at: Duration::from_micros(1_234_567),
+            event: NetEvent::Wifi(WifiEvent::GotIp { ip: [10, 0, 0, 7], gateway: [10, 0, 0, 1], netmask: [255; 4] }),
+        };
+        let mut buf = [0u8; MAX_RECORD];
+        let len = encode(&record, &mut buf);
+        assert_eq!(buf[0] as usize, len - 1);
+
+        let decoded = decode(&buf[1..len]).unwrap().unwrap();
+        assert_eq!(decoded.at, record.at);
+        match decoded.event {
+            NetEvent::Wifi(WifiEvent::GotIp { ip, gateway, .. }) => {
+                assert_eq!(ip, [10, 0, 0, 7]);
+                assert_eq!(gateway, [10, 0, 0, 1]);
+            }
+            _ => panic!("unexpected event"),
+        }
+
+        let reason = wifi::DisconnectReason::BeaconTimeout;
+        let record = RecordedEvent { at: Duration::from_micros(5), event: NetEvent::Wifi(WifiEvent::StaDisconnected { reason }) };
+        let len = encode(&record, &mut buf);
+        assert!(matches!(
+            decode(&buf[1..len]).unwrap().unwrap().event,
+            NetEvent::Wifi(WifiEvent::StaDisconnected { reason: r }) if r == reason
+        ));
+        assert!(matches!(decode(&buf[1..4]), Err(ReplayError::Corrupt)));
+    }
+}
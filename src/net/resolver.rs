@@ -0,0 +1,572 @@
+//! DNS 解析器
+//!
+//! 基于 UDP Socket 的精简 DNS 客户端 (RFC 1035)，补足 embassy-net 内置查询
+//! 无法指定服务器、不返回 TTL 的问题:
+//! - 可配置 DNS 服务器 (留空时使用 DHCP/静态配置下发的服务器)，按顺序故障转移
+//! - A 与 AAAA 查询，跳过 CNAME 链取第一个匹配记录
+//! - 按应答 TTL 缓存: A 记录写入 `net::dns` 全局缓存 (与 `dns::resolve` 共用)，
+//!   AAAA 记录缓存在解析器内部 (协议栈仅启用 IPv4，AAAA 结果供上层转发或展示)
+//! - NXDOMAIN/无记录写入负缓存且不再询问其余服务器；超时不做负缓存
+//! - 每次查询使用硬件 RNG 生成的 ID，应答须与 ID、来源及问题段 (域名、类型) 全部一致
+//! - `dns::resolve` (及 `NetworkStack::dns_resolve` / `TcpClient::connect_host`)
+//!   缓存未命中时使用临时解析器与协议栈下发的服务器查询
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::resolver::{Resolver, ResolverBuffers, ResolverConfig};
+//!
+//! static BUFFERS: StaticCell<ResolverBuffers> = StaticCell::new();
+//!
+//! let config = ResolverConfig::new()
+//!     .with_server(Ipv4Address::new(1, 1, 1, 1))
+//!     .with_server(Ipv4Address::new(8, 8, 8, 8));
+//! let mut resolver = Resolver::new(&stack, BUFFERS.init(ResolverBuffers::new()), config).await?;
+//!
+//! let mut client = TcpClient::new(&stack, &mut rx, &mut tx);
+//! client.connect_hostname(&mut resolver, "broker.example.com", 1883).await?;
+//!
+//! let v6 = resolver.resolve_v6("example.com").await?;
+//! ```
+
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddrV4};
+
+use embassy_net::Stack;
+use esp_hal::rng::Rng;
+use embassy_time::{with_deadline, Duration, Instant};
+use heapless::Vec;
+
+use super::config::{DNS_CACHE_SIZE, DNS_MAX_SERVERS, DNS_QUERY_ATTEMPTS, DNS_QUERY_TIMEOUT_MS};
+use super::dns::{self, DnsCache, DnsCacheConfig, Lookup};
+use super::tcp::{Ipv4Address, NetworkError, NetworkStack, StackConfig, UdpBuffers, UdpSocket};
+use crate::util::log::*;
+
+/// DNS 服务端口
+pub const DNS_PORT: u16 = 53;
+
+/// UDP DNS 报文最大长度
+pub const MAX_MESSAGE_LEN: usize = 512;
+
+/// 解析器 Socket 缓冲区
+pub type ResolverBuffers = UdpBuffers<2, MAX_MESSAGE_LEN, MAX_MESSAGE_LEN>;
+
+pub(super) const HEADER_LEN: usize = 12;
+pub(super) const CLASS_IN: u16 = 1;
+/// 标志位: 递归查询 (RD)
+pub(super) const FLAG_RD: u16 = 0x0100;
+/// 标志位: 应答 (QR)
+pub(super) const FLAG_QR: u16 = 0x8000;
+const RCODE_NXDOMAIN: u8 = 3;
+/// 主机名最大长度 (不含结尾的点)
+const MAX_NAME_LEN: usize = 253;
+const MAX_LABEL_LEN: usize = 63;
+
+// ===== 报文 =====
+
+/// 查询类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryType {
+    /// IPv4 地址
+    A,
+    /// IPv6 地址
+    Aaaa,
+}
+
+impl QueryType {
+    /// 记录类型编码
+    pub const fn code(self) -> u16 {
+        match self {
+            Self::A => 1,
+            Self::Aaaa => 28,
+        }
+    }
+}
+
+/// 解析出的地址记录
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Answer {
+    /// 地址
+    pub addr: IpAddr,
+    /// 记录 TTL
+    pub ttl: Duration,
+}
+
+/// 应答分类
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Response {
+    /// 找到匹配类型的记录
+    Answer(Answer),
+    /// 域名存在但没有该类型记录
+    NoData,
+    /// 域名不存在
+    NxDomain,
+    /// 服务器错误 (RCODE)，可换下一个服务器重试
+    ServerFailure(u8),
+}
+
+/// 编码查询报文，返回报文长度
+pub fn encode_query(id: u16, host: &str, qtype: QueryType, buf: &mut [u8]) -> Result<usize, NetworkError> {
+    let host = host.strip_suffix('.').unwrap_or(host);
+    if host.is_empty() || host.len() > MAX_NAME_LEN {
+        return Err(NetworkError::InvalidAddress);
+    }
+    let len = HEADER_LEN + host.len() + 2 + 4;
+    if buf.len() < len {
+        return Err(NetworkError::BufferFull);
+    }
+
+    buf[..HEADER_LEN].fill(0);
+    buf[0..2].copy_from_slice(&id.to_be_bytes());
+    buf[2..4].copy_from_slice(&FLAG_RD.to_be_bytes());
+    buf[4..6].copy_from_slice(&1u16.to_be_bytes());
+
+    let mut pos = HEADER_LEN;
+    for label in host.split('.') {
+        if label.is_empty() || label.len() > MAX_LABEL_LEN {
+            return Err(NetworkError::InvalidAddress);
+        }
+        buf[pos] = label.len() as u8;
+        buf[pos + 1..pos + 1 + label.len()].copy_from_slice(label.as_bytes());
+        pos += 1 + label.len();
+    }
+    buf[pos] = 0;
+    pos += 1;
+    buf[pos..pos + 2].copy_from_slice(&qtype.code().to_be_bytes());
+    buf[pos + 2..pos + 4].copy_from_slice(&CLASS_IN.to_be_bytes());
+    Ok(pos + 4)
+}
+
+/// 解析应答报文
+///
+/// ID 不匹配、不是应答、问题段与查询 (`host`、`qtype`) 不符或格式错误时返回 `Err`，
+/// 调用者应丢弃该报文。
+pub fn parse_response(buf: &[u8], id: u16, host: &str, qtype: QueryType) -> Result<Response, NetworkError> {
+    const MALFORMED: NetworkError = NetworkError::DnsResolutionFailed;
+    if buf.len() < HEADER_LEN || be16(buf, 0) != id {
+        return Err(MALFORMED);
+    }
+    let flags = be16(buf, 2);
+    if flags & FLAG_QR == 0 || be16(buf, 4) != 1 {
+        return Err(MALFORMED);
+    }
+    // 问题段必须回显本次查询，防止伪造应答替换为其他域名或类型
+    let mut pos = match_name(buf, HEADER_LEN, host)?;
+    let question = buf.get(pos..pos + 4).ok_or(MALFORMED)?;
+    if be16(question, 0) != qtype.code() || be16(question, 2) != CLASS_IN {
+        return Err(MALFORMED);
+    }
+    pos += 4;
+    match (flags & 0x000F) as u8 {
+        0 => {}
+        RCODE_NXDOMAIN => return Ok(Response::NxDomain),
+        rcode => return Ok(Response::ServerFailure(rcode)),
+    }
+
+    let answers = be16(buf, 6);
+    for _ in 0..answers {
+        pos = skip_name(buf, pos)?;
+        let record = buf.get(pos..pos + 10).ok_or(MALFORMED)?;
+        let rtype = be16(record, 0);
+        let class = be16(record, 2);
+        let ttl = u32::from_be_bytes([record[4], record[5], record[6], record[7]]);
+        let rdlen = be16(record, 8) as usize;
+        pos += 10;
+        let rdata = buf.get(pos..pos + rdlen).ok_or(MALFORMED)?;
+        pos += rdlen;
+
+        // CNAME 等其他记录跳过，服务器会在同一应答中给出链尾的地址记录
+        if class != CLASS_IN || rtype != qtype.code() {
+            continue;
+        }
+        let addr = match (qtype, rdata) {
+            (QueryType::A, &[a, b, c, d]) => IpAddr::V4(Ipv4Addr::new(a, b, c, d)),
+            (QueryType::Aaaa, rdata) if rdata.len() == 16 => {
+                let mut octets = [0u8; 16];
+                octets.copy_from_slice(rdata);
+                IpAddr::V6(Ipv6Addr::from(octets))
+            }
+            _ => return Err(MALFORMED),
+        };
+        // RFC 2181: 最高位置位的 TTL 按 0 处理
+        let ttl = if ttl > i32::MAX as u32 { 0 } else { ttl };
+        return Ok(Response::Answer(Answer { addr, ttl: Duration::from_secs(ttl as u64) }));
+    }
+    Ok(Response::NoData)
+}
+
+/// 比较未压缩的域名与 `host` (不区分大小写)，返回其后的位置
+fn match_name(buf: &[u8], mut pos: usize, host: &str) -> Result<usize, NetworkError> {
+    let host = host.strip_suffix('.').unwrap_or(host);
+    let mut labels = host.split('.');
+    loop {
+        let len = *buf.get(pos).ok_or(NetworkError::DnsResolutionFailed)? as usize;
+        if len == 0 {
+            return match labels.next() {
+                None => Ok(pos + 1),
+                Some(_) => Err(NetworkError::DnsResolutionFailed),
+            };
+        }
+        let label = buf.get(pos + 1..pos + 1 + len).ok_or(NetworkError::DnsResolutionFailed)?;
+        match labels.next() {
+            Some(expected) if len <= MAX_LABEL_LEN && label.eq_ignore_ascii_case(expected.as_bytes()) => {
+                pos += 1 + len;
+            }
+            _ => return Err(NetworkError::DnsResolutionFailed),
+        }
+    }
+}
+
+pub(super) fn be16(buf: &[u8], pos: usize) -> u16 {
+    u16::from_be_bytes([buf[pos], buf[pos + 1]])
+}
+
+/// 跳过 (可能压缩的) 域名，返回其后的位置
+pub(super) fn skip_name(buf: &[u8], mut pos: usize) -> Result<usize, NetworkError> {
+    loop {
+        let len = *buf.get(pos).ok_or(NetworkError::DnsResolutionFailed)?;
+        match len & 0xC0 {
+            0xC0 => {
+                buf.get(pos + 1).ok_or(NetworkError::DnsResolutionFailed)?;
+                return Ok(pos + 2);
+            }
+            0x00 if len == 0 => return Ok(pos + 1),
+            0x00 => pos += 1 + len as usize,
+            _ => return Err(NetworkError::DnsResolutionFailed),
+        }
+    }
+}
+
+// ===== 配置 =====
+
+/// 解析器配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResolverConfig {
+    servers: [Ipv4Address; DNS_MAX_SERVERS],
+    server_count: usize,
+    /// 单次查询超时 (每个服务器每次尝试)
+    pub timeout: Duration,
+    /// 尝试轮数 (每轮依次询问全部服务器)
+    pub attempts: u8,
+}
+
+impl ResolverConfig {
+    /// 默认配置 (不指定服务器，使用协议栈下发的 DNS)
+    pub const fn new() -> Self {
+        Self {
+            servers: [Ipv4Address::UNSPECIFIED; DNS_MAX_SERVERS],
+            server_count: 0,
+            timeout: Duration::from_millis(DNS_QUERY_TIMEOUT_MS as u64),
+            attempts: DNS_QUERY_ATTEMPTS,
+        }
+    }
+
+    /// 追加 DNS 服务器 (超过 `DNS_MAX_SERVERS` 的忽略)
+    pub const fn with_server(mut self, server: Ipv4Address) -> Self {
+        if self.server_count < DNS_MAX_SERVERS {
+            self.servers[self.server_count] = server;
+            self.server_count += 1;
+        }
+        self
+    }
+
+    /// 设置单次查询超时
+    pub const fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 设置尝试轮数
+    pub const fn with_attempts(mut self, attempts: u8) -> Self {
+        self.attempts = attempts;
+        self
+    }
+
+    /// 已配置的服务器
+    pub fn servers(&self) -> &[Ipv4Address] {
+        &self.servers[..self.server_count]
+    }
+}
+
+impl Default for ResolverConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ===== 解析器 =====
+
+/// DNS 解析器
+///
+/// 独占一个 UDP Socket；并发解析需各自创建解析器。
+pub struct Resolver<'a> {
+    stack: Stack<'a>,
+    socket: UdpSocket<'a>,
+    config: ResolverConfig,
+    rng: Rng,
+    v6_cache: DnsCache<DNS_CACHE_SIZE, Ipv6Addr>,
+}
+
+impl<'a> Resolver<'a> {
+    /// 创建解析器并绑定临时端口
+    pub async fn new(
+        stack: &NetworkStack<'a>,
+        buffers: &'a mut ResolverBuffers,
+        config: ResolverConfig,
+    ) -> Result<Self, NetworkError> {
+        let mut socket = UdpSocket::new(stack, buffers);
+        socket.bind(0).await?;
+        Ok(Self {
+            stack: stack.stack(),
+            socket,
+            config,
+            rng: Rng::new(),
+            v6_cache: DnsCache::new(DnsCacheConfig::new()),
+        })
+    }
+
+    /// 获取配置
+    pub fn config(&self) -> &ResolverConfig {
+        &self.config
+    }
+
+    /// 替换配置 (如切换网络后更换服务器)
+    pub fn set_config(&mut self, config: ResolverConfig) {
+        self.config = config;
+    }
+
+    /// 当前生效的服务器: 优先使用配置，否则使用协议栈下发的 DNS
+    pub fn servers(&self) -> Vec<Ipv4Address, DNS_MAX_SERVERS> {
+        if self.config.server_count > 0 {
+            return self.config.servers().iter().copied().collect();
+        }
+        self.stack
+            .config_v4()
+            .map(|c| c.dns_servers.iter().take(DNS_MAX_SERVERS).map(|s| Ipv4Address::from(*s)).collect())
+            .unwrap_or_default()
+    }
+
+    /// 解析 A 记录 (经全局缓存)
+    pub async fn resolve(&mut self, host: &str) -> Result<Ipv4Address, NetworkError> {
+        if let Some(addr) = dns::parse_literal(host) {
+            return Ok(addr);
+        }
+        match dns::lookup(host) {
+            Lookup::Hit(addr) => return Ok(addr),
+            Lookup::Negative => return Err(NetworkError::DnsResolutionFailed),
+            Lookup::Miss => {}
+        }
+        self.fetch(host).await
+    }
+
+    /// 查询 A 记录并写入全局缓存 (成功按记录 TTL，域名不存在时负缓存)
+    async fn fetch(&mut self, host: &str) -> Result<Ipv4Address, NetworkError> {
+        match self.query(host, QueryType::A).await {
+            Ok(Answer { addr: IpAddr::V4(v4), ttl }) => {
+                let addr = Ipv4Address::from(v4);
+                dns::record(host, Ok(addr), Some(ttl));
+                Ok(addr)
+            }
+            Ok(_) => Err(NetworkError::DnsResolutionFailed),
+            Err(NetworkError::DnsResolutionFailed) => {
+                dns::record(host, Err(NetworkError::DnsResolutionFailed), None);
+                Err(NetworkError::DnsResolutionFailed)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 解析 AAAA 记录 (经解析器内部缓存)
+    pub async fn resolve_v6(&mut self, host: &str) -> Result<Ipv6Addr, NetworkError> {
+        if let Ok(addr) = host.parse::<Ipv6Addr>() {
+            return Ok(addr);
+        }
+        let now = Instant::now();
+        match self.v6_cache.lookup(host, now) {
+            Lookup::Hit(addr) => return Ok(addr),
+            Lookup::Negative => return Err(NetworkError::DnsResolutionFailed),
+            Lookup::Miss => {}
+        }
+
+        match self.query(host, QueryType::Aaaa).await {
+            Ok(Answer { addr: IpAddr::V6(v6), ttl }) => {
+                self.v6_cache.insert(host, v6, Some(ttl), Instant::now());
+                Ok(v6)
+            }
+            Ok(_) => Err(NetworkError::DnsResolutionFailed),
+            Err(NetworkError::DnsResolutionFailed) => {
+                self.v6_cache.insert_negative(host, Instant::now());
+                Err(NetworkError::DnsResolutionFailed)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 移除单个主机的 AAAA 缓存 (A 记录缓存见 `flush_host`)
+    pub fn flush_v6(&mut self, host: &str) -> bool {
+        self.v6_cache.flush_host(host)
+    }
+
+    /// 移除单个主机经本解析器缓存的全部记录 (`resolve` 写入的 A 记录与 AAAA 缓存)
+    ///
+    /// 连接解析出的地址失败后调用，下次解析重新查询而不是再次返回失效地址。
+    pub fn flush_host(&mut self, host: &str) -> bool {
+        let a = dns::flush_host(host);
+        let v6 = self.flush_v6(host);
+        a || v6
+    }
+
+    /// 直接查询 (不经缓存)
+    ///
+    /// 域名不存在或无该类型记录时立即返回 `DnsResolutionFailed`；
+    /// 服务器错误或超时则换下一个服务器，全部失败返回最后一个错误。
+    pub async fn query(&mut self, host: &str, qtype: QueryType) -> Result<Answer, NetworkError> {
+        let servers = self.servers();
+        if servers.is_empty() {
+            return Err(NetworkError::NotInitialized);
+        }
+
+        let mut packet = [0u8; MAX_MESSAGE_LEN];
+        let mut last_error = NetworkError::Timeout;
+        for _ in 0..self.config.attempts.max(1) {
+            for server in &servers {
+                // 每次查询使用新的随机 ID，增加伪造应答的难度
+                let id = self.rng.random() as u16;
+                let len = encode_query(id, host, qtype, &mut packet)?;
+                match self.exchange(*server, id, host, qtype, &mut packet, len).await {
+                    Ok(Response::Answer(answer)) => return Ok(answer),
+                    Ok(Response::NoData | Response::NxDomain) => {
+                        log_debug!("DNS: {} has no {:?} record", host, qtype);
+                        return Err(NetworkError::DnsResolutionFailed);
+                    }
+                    Ok(Response::ServerFailure(_rcode)) => {
+                        log_debug!("DNS: server {} rcode {}", server.to_std(), _rcode);
+                        last_error = NetworkError::DnsResolutionFailed;
+                    }
+                    Err(e) => {
+                        log_debug!("DNS: server {} failed: {}", server.to_std(), e);
+                        last_error = e;
+                    }
+                }
+            }
+        }
+        log_warn!("DNS: {} failed: {}", host, last_error);
+        Err(last_error)
+    }
+
+    /// 发送查询并等待匹配的应答 (忽略来源或 ID 不符的报文)
+    async fn exchange(
+        &mut self,
+        server: Ipv4Address,
+        id: u16,
+        host: &str,
+        qtype: QueryType,
+        packet: &mut [u8; MAX_MESSAGE_LEN],
+        len: usize,
+    ) -> Result<Response, NetworkError> {
+        let remote = SocketAddrV4::new(server.to_std(), DNS_PORT);
+        self.socket.send_to(&packet[..len], remote).await?;
+
+        let deadline = Instant::now() + self.config.timeout;
+        loop {
+            let received = with_deadline(deadline, self.socket.recv_from(packet))
+                .await
+                .map_err(|_| NetworkError::Timeout)?;
+            let Ok((n, from)) = received else {
+                continue;
+            };
+            if from != remote {
+                continue;
+            }
+            if let Ok(response) = parse_response(&packet[..n], id, host, qtype) {
+                return Ok(response);
+            }
+        }
+    }
+}
+
+/// 缓存未命中时使用临时解析器 (协议栈下发的服务器) 查询 A 记录，供 `dns::resolve` 使用
+///
+/// 解析器缓冲区位于调用者的 future 中，查询结束即释放 Socket。
+pub(super) async fn fetch_with_stack(stack: Stack<'_>, host: &str) -> Result<Ipv4Address, NetworkError> {
+    let mut buffers = ResolverBuffers::new();
+    let stack = NetworkStack::from_stack(stack, StackConfig::default());
+    let mut resolver = Resolver::new(&stack, &mut buffers, ResolverConfig::new()).await?;
+    resolver.fetch(host).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOST: &str = "www.example.com";
+
+    /// 构造应答: 问题段 + CNAME (压缩指针) + 地址记录
+    fn response(id: u16, qtype: QueryType, rdata: &[u8], ttl: u32) -> Vec<u8, MAX_MESSAGE_LEN> {
+        let mut buf = [0u8; MAX_MESSAGE_LEN];
+        let len = encode_query(id, HOST, qtype, &mut buf).unwrap();
+        let mut out: Vec<u8, MAX_MESSAGE_LEN> = Vec::from_slice(&buf[..len]).unwrap();
+        out[2] = 0x81;
+        out[3] = 0x80;
+        out[7] = 2;
+        // CNAME www.example.com -> example.com
+        out.extend_from_slice(&[0xC0, 12, 0, 5, 0, 1, 0, 0, 0, 60, 0, 2, 0xC0, 16]).unwrap();
+        out.extend_from_slice(&[0xC0, 16]).unwrap();
+        out.extend_from_slice(&qtype.code().to_be_bytes()).unwrap();
+        out.extend_from_slice(&[0, 1]).unwrap();
+        out.extend_from_slice(&ttl.to_be_bytes()).unwrap();
+        out.extend_from_slice(&(rdata.len() as u16).to_be_bytes()).unwrap();
+        out.extend_from_slice(rdata).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_encode_query() {
+        let mut buf = [0u8; 64];
+        let len = encode_query(0x1234, "a.bc.", QueryType::Aaaa, &mut buf).unwrap();
+        assert_eq!(
+            &buf[..len],
+            &[0x12, 0x34, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0, 1, b'a', 2, b'b', b'c', 0, 0, 28, 0, 1]
+        );
+        assert_eq!(encode_query(1, "a..b", QueryType::A, &mut buf), Err(NetworkError::InvalidAddress));
+        assert_eq!(encode_query(1, "", QueryType::A, &mut buf), Err(NetworkError::InvalidAddress));
+        assert_eq!(encode_query(1, "example.com", QueryType::A, &mut buf[..20]), Err(NetworkError::BufferFull));
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let a = response(7, QueryType::A, &[93, 184, 216, 34], 300);
+        assert_eq!(
+            parse_response(&a, 7, HOST, QueryType::A),
+            Ok(Response::Answer(Answer {
+                addr: IpAddr::V4(Ipv4Addr::new(93, 184, 216, 34)),
+                ttl: Duration::from_secs(300),
+            }))
+        );
+        // ID、问题类型或问题域名不符
+        assert!(parse_response(&a, 8, HOST, QueryType::A).is_err());
+        assert!(parse_response(&a, 7, HOST, QueryType::Aaaa).is_err());
+        assert!(parse_response(&a, 7, "www.example.org", QueryType::A).is_err());
+        assert!(parse_response(&a, 7, "example.com", QueryType::A).is_err());
+        assert!(parse_response(&a, 7, "WWW.Example.com.", QueryType::A).is_ok());
+
+        let mut empty = a.clone();
+        empty[7] = 0;
+        assert_eq!(parse_response(&empty, 7, HOST, QueryType::A), Ok(Response::NoData));
+
+        let v6 = Ipv6Addr::new(0x2606, 0x2800, 0x220, 1, 0x248, 0x1893, 0x25c8, 0x1946);
+        let aaaa = response(9, QueryType::Aaaa, &v6.octets(), u32::MAX);
+        assert_eq!(
+            parse_response(&aaaa, 9, HOST, QueryType::Aaaa),
+            Ok(Response::Answer(Answer { addr: IpAddr::V6(v6), ttl: Duration::from_secs(0) }))
+        );
+
+        let mut nx = a.clone();
+        nx[3] = 0x83;
+        assert_eq!(parse_response(&nx, 7, HOST, QueryType::A), Ok(Response::NxDomain));
+        nx[3] = 0x82;
+        assert_eq!(parse_response(&nx, 7, HOST, QueryType::A), Ok(Response::ServerFailure(2)));
+
+        // 截断的记录
+        assert!(parse_response(&a[..a.len() - 2], 7, HOST, QueryType::A).is_err());
+    }
+}
@@ -0,0 +1,367 @@
+//! MQTT 主题路由
+//!
+//! 把收到的发布消息按主题过滤器分发给处理器，应用无需在一个大 `match` 中
+//! 手工比较主题:
+//! - 过滤器支持 MQTT 通配符: `+` 匹配一级，`#` 匹配其后任意级 (含父级本身)
+//! - 以 `$` 开头的系统主题不被首级通配符匹配 (与 MQTT 3.1.1 一致)
+//! - 每条路由带一个应用定义的键，处理器按键区分路由，无需动态分配
+//! - 负载可经 `util::json` 反序列化为具体类型 (`Message::decode`)
+//! - 按路由统计消息数与处理失败数，另计未匹配的消息数
+//!
+//! 路由与具体 MQTT 客户端无关: 从 `MqttInbox` 取出消息后调用 `dispatch`，
+//! 订阅时用 `filters` 列出需要订阅的过滤器。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::mqtt::{MqttInbox, QoS};
+//! use rustrtos::net::router::{Handler, Message, RouterError, TopicRouter};
+//!
+//! static INBOX: MqttInbox = MqttInbox::new();
+//!
+//! #[derive(Clone, Copy)]
+//! enum Route { Setpoint, Command }
+//!
+//! struct App { setpoint: f32 }
+//!
+//! impl Handler<Route> for App {
+//!     async fn handle(&mut self, route: &Route, msg: &Message<'_>) -> Result<(), RouterError> {
+//!         match route {
+//!             Route::Setpoint => self.setpoint = msg.decode()?,
+//!             Route::Command => run_command(msg.text()?).await,
+//!         }
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let mut router: TopicRouter<Route, 8> = TopicRouter::new();
+//! router.add("dev/+/setpoint", Route::Setpoint)?;
+//! router.add("dev/cmd/#", Route::Command)?;
+//! for filter in router.filters() {
+//!     client.subscribe(filter, QoS::AtLeastOnce).await?;
+//! }
+//!
+//! // `client.poll()` / `client.run()` 在连接任务中把消息放入 INBOX
+//! loop {
+//!     let msg = INBOX.receive().await;
+//!     router.dispatch(&mut app, &msg.topic, &msg.payload).await;
+//! }
+//! ```
+
+use core::fmt;
+
+use heapless::Vec;
+
+use crate::util::json::{self, FromJson, JsonError, JsonValue};
+
+/// 主题最大长度 (MQTT 允许更长，此处按嵌入式场景限制)
+pub const MAX_FILTER_LEN: usize = 128;
+
+// ===== 错误类型 =====
+
+/// 路由错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouterError {
+    /// 过滤器无效 (空、过长、通配符位置错误)
+    InvalidFilter,
+    /// 路由表已满
+    Full,
+    /// 负载不是 UTF-8 文本
+    NotUtf8,
+    /// 负载 JSON 无效或类型不符
+    Payload(JsonError),
+    /// 处理器失败
+    Handler,
+}
+
+impl fmt::Display for RouterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidFilter => write!(f, "Invalid topic filter"),
+            Self::Full => write!(f, "Route table full"),
+            Self::NotUtf8 => write!(f, "Payload is not UTF-8"),
+            Self::Payload(e) => write!(f, "Payload error: {}", e),
+            Self::Handler => write!(f, "Handler failed"),
+        }
+    }
+}
+
+impl From<JsonError> for RouterError {
+    fn from(e: JsonError) -> Self {
+        Self::Payload(e)
+    }
+}
+
+// ===== 主题匹配 =====
+
+/// 检查主题过滤器
+///
+/// `#` 只能作为最后一级且独占该级，`+` 必须独占一级。
+pub fn validate_filter(filter: &str) -> Result<(), RouterError> {
+    if filter.is_empty() || filter.len() > MAX_FILTER_LEN {
+        return Err(RouterError::InvalidFilter);
+    }
+    let mut levels = filter.split('/').peekable();
+    while let Some(level) = levels.next() {
+        let valid = match level {
+            "#" => levels.peek().is_none(),
+            "+" => true,
+            _ => !level.contains(['+', '#']),
+        };
+        if !valid {
+            return Err(RouterError::InvalidFilter);
+        }
+    }
+    Ok(())
+}
+
+/// 主题是否匹配过滤器 (过滤器须已通过 `validate_filter`)
+pub fn topic_matches(filter: &str, topic: &str) -> bool {
+    if topic.starts_with('$') && (filter.starts_with('+') || filter.starts_with('#')) {
+        return false;
+    }
+    let mut topic_levels = topic.split('/');
+    for level in filter.split('/') {
+        match (level, topic_levels.next()) {
+            ("#", _) => return true,
+            ("+", Some(_)) => {}
+            (level, Some(t)) if level == t => {}
+            _ => return false,
+        }
+    }
+    topic_levels.next().is_none()
+}
+
+// ===== 消息 =====
+
+/// 收到的发布消息
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Message<'a> {
+    /// 主题
+    pub topic: &'a str,
+    /// 负载
+    pub payload: &'a [u8],
+}
+
+impl<'a> Message<'a> {
+    /// 创建
+    pub const fn new(topic: &'a str, payload: &'a [u8]) -> Self {
+        Self { topic, payload }
+    }
+
+    /// 负载文本
+    pub fn text(&self) -> Result<&'a str, RouterError> {
+        core::str::from_utf8(self.payload).map_err(|_| RouterError::NotUtf8)
+    }
+
+    /// 负载解析为 JSON
+    pub fn json(&self) -> Result<JsonValue<'a>, RouterError> {
+        Ok(json::parse(self.text()?)?)
+    }
+
+    /// 负载反序列化为指定类型
+    pub fn decode<T: FromJson<'a>>(&self) -> Result<T, RouterError> {
+        Ok(json::from_str(self.text()?)?)
+    }
+
+    /// 第 `index` 级主题 (如 `dev/+/setpoint` 中 `+` 对应的设备名)
+    pub fn level(&self, index: usize) -> Option<&'a str> {
+        self.topic.split('/').nth(index)
+    }
+}
+
+// ===== 处理器 =====
+
+/// 消息处理器
+#[allow(async_fn_in_trait)]
+pub trait Handler<K> {
+    /// 处理匹配 `route` 的消息
+    async fn handle(&mut self, route: &K, message: &Message<'_>) -> Result<(), RouterError>;
+}
+
+// ===== 路由表 =====
+
+/// 路由统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RouteStats {
+    /// 过滤器
+    pub filter: &'static str,
+    /// 匹配的消息数
+    pub messages: u32,
+    /// 处理失败数
+    pub errors: u32,
+}
+
+impl fmt::Display for RouteStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} messages={} errors={}", self.filter, self.messages, self.errors)
+    }
+}
+
+struct Route<K> {
+    key: K,
+    stats: RouteStats,
+}
+
+/// 主题路由表 (最多 `N` 条路由)
+pub struct TopicRouter<K, const N: usize> {
+    routes: Vec<Route<K>, N>,
+    unmatched: u32,
+}
+
+impl<K, const N: usize> TopicRouter<K, N> {
+    /// 创建空路由表
+    pub const fn new() -> Self {
+        Self { routes: Vec::new(), unmatched: 0 }
+    }
+
+    /// 添加路由 (同一消息可匹配多条路由，按添加顺序分发)
+    pub fn add(&mut self, filter: &'static str, key: K) -> Result<(), RouterError> {
+        validate_filter(filter)?;
+        let route = Route { key, stats: RouteStats { filter, messages: 0, errors: 0 } };
+        self.routes.push(route).map_err(|_| RouterError::Full)
+    }
+
+    /// 移除过滤器对应的全部路由，返回移除数量
+    pub fn remove(&mut self, filter: &str) -> usize {
+        let before = self.routes.len();
+        self.routes.retain(|route| route.stats.filter != filter);
+        before - self.routes.len()
+    }
+
+    /// 需要订阅的过滤器 (去重)
+    pub fn filters(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.routes.iter().enumerate().filter_map(|(i, route)| {
+            let filter = route.stats.filter;
+            (!self.routes[..i].iter().any(|r| r.stats.filter == filter)).then_some(filter)
+        })
+    }
+
+    /// 匹配主题的路由键
+    pub fn matches<'s>(&'s self, topic: &'s str) -> impl Iterator<Item = &'s K> + 's {
+        self.routes.iter().filter(move |route| topic_matches(route.stats.filter, topic)).map(|route| &route.key)
+    }
+
+    /// 分发消息，返回成功处理的路由数
+    pub async fn dispatch<H: Handler<K>>(&mut self, handler: &mut H, topic: &str, payload: &[u8]) -> usize {
+        let message = Message::new(topic, payload);
+        let mut matched = false;
+        let mut handled = 0;
+        for route in self.routes.iter_mut() {
+            if !topic_matches(route.stats.filter, topic) {
+                continue;
+            }
+            matched = true;
+            route.stats.messages = route.stats.messages.wrapping_add(1);
+            match handler.handle(&route.key, &message).await {
+                Ok(()) => handled += 1,
+                Err(_) => route.stats.errors = route.stats.errors.wrapping_add(1),
+            }
+        }
+        if !matched {
+            self.unmatched = self.unmatched.wrapping_add(1);
+        }
+        handled
+    }
+
+    /// 各路由统计
+    pub fn stats(&self) -> impl Iterator<Item = RouteStats> + '_ {
+        self.routes.iter().map(|route| route.stats)
+    }
+
+    /// 未匹配任何路由的消息数
+    pub fn unmatched(&self) -> u32 {
+        self.unmatched
+    }
+
+    /// 清零统计
+    pub fn reset_stats(&mut self) {
+        for route in self.routes.iter_mut() {
+            route.stats.messages = 0;
+            route.stats.errors = 0;
+        }
+        self.unmatched = 0;
+    }
+
+    /// 路由数
+    pub fn len(&self) -> usize {
+        self.routes.len()
+    }
+
+    /// 是否没有路由
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+}
+
+impl<K, const N: usize> Default for TopicRouter<K, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard_matching() {
+        assert!(topic_matches("dev/+/temp", "dev/a1/temp"));
+        assert!(!topic_matches("dev/+/temp", "dev/a1/b/temp"));
+        assert!(topic_matches("dev/#", "dev"));
+        assert!(topic_matches("dev/#", "dev/a/b"));
+        assert!(topic_matches("+/+", "a/"));
+        assert!(!topic_matches("dev/a", "dev/a/b"));
+        assert!(!topic_matches("#", "$SYS/uptime"));
+        assert!(topic_matches("$SYS/#", "$SYS/uptime"));
+
+        assert!(validate_filter("a/+/#").is_ok());
+        assert_eq!(validate_filter("a/#/b"), Err(RouterError::InvalidFilter));
+        assert_eq!(validate_filter("a/b+"), Err(RouterError::InvalidFilter));
+        assert_eq!(validate_filter(""), Err(RouterError::InvalidFilter));
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Key {
+        Setpoint,
+        All,
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        setpoint: Option<u32>,
+        all: usize,
+    }
+
+    impl Handler<Key> for Recorder {
+        async fn handle(&mut self, route: &Key, message: &Message<'_>) -> Result<(), RouterError> {
+            match route {
+                Key::Setpoint => self.setpoint = Some(message.decode()?),
+                Key::All => self.all += 1,
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_dispatch_and_stats() {
+        let mut router: TopicRouter<Key, 4> = TopicRouter::new();
+        router.add("dev/+/setpoint", Key::Setpoint).unwrap();
+        router.add("dev/#", Key::All).unwrap();
+        router.add("dev/#", Key::All).unwrap();
+        assert_eq!(router.filters().count(), 2);
+
+        use embassy_futures::block_on;
+        let mut app = Recorder::default();
+        assert_eq!(block_on(router.dispatch(&mut app, "dev/pump/setpoint", b"42")), 3);
+        assert_eq!(block_on(router.dispatch(&mut app, "dev/pump/setpoint", b"-1")), 2);
+        assert_eq!(block_on(router.dispatch(&mut app, "other", b"")), 0);
+
+        assert_eq!((app.setpoint, app.all), (Some(42), 4));
+        let first = router.stats().next().unwrap();
+        assert_eq!((first.messages, first.errors), (2, 1));
+        assert_eq!(router.unmatched(), 1);
+        assert_eq!(router.remove("dev/#"), 2);
+        assert_eq!(Message::new("dev/pump/setpoint", b"").level(1), Some("pump"));
+    }
+}
@@ -0,0 +1,562 @@
+//! 连接监管
+//!
+//! 把 WiFi 链路的原始统计转化为运维动作:
+//! - 周期采样 RSSI、丢失信标数、DHCP 续约失败次数，并定期 ping 网关 (RSSI 同步到 `sys::status`)
+//! - 按阈值 (RSSI 带迟滞) 把链路健康度分为 良好 / 降级 / 差 / 断开
+//! - 差或断开持续若干次采样后按升级阶梯执行动作: 重连 → 射频下电重启 → AP 回退
+//! - AP 回退保持一段时间后重新尝试 STA 连接
+//! - 健康度变化与每个动作都作为结构化事件发布到系统事件总线
+//!
+//! 监管器本身不操作射频: 采样与动作由应用实现 `SupervisedLink`
+//! (通常包装 `esp_radio::wifi::WifiController` 与 embassy-net 协议栈)。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::supervisor::{LinkAction, LinkSample, LinkSupervisor, SupervisedLink, SupervisorPolicy};
+//!
+//! struct Sta { radio: esp_radio::wifi::WifiController<'static>, stack: Stack<'static> }
+//!
+//! impl SupervisedLink for Sta {
+//!     async fn sample(&mut self) -> LinkSample {
+//!         LinkSample::associated(current_rssi()).with_missed_beacons(beacon_misses())
+//!     }
+//!     async fn ping_gateway(&mut self, timeout: Duration) -> bool { /* ICMP echo */ }
+//!     async fn apply(&mut self, action: LinkAction) -> Result<(), WifiError> { /* ... */ }
+//! }
+//!
+//! #[embassy_executor::task]
+//! async fn supervisor_task(mut sta: Sta) {
+//!     let mut supervisor = LinkSupervisor::new("wifi", SupervisorPolicy::new());
+//!     supervisor.run(&mut sta).await
+//! }
+//! ```
+
+use embassy_time::{Duration, Instant, Ticker};
+
+use super::wifi::WifiError;
+use crate::sync::bus::{self, SystemEvent};
+use crate::sys::status::STATUS;
+use crate::util::log::*;
+
+// ===== 健康度与动作 =====
+
+/// 链路健康度 (按严重程度排序)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum LinkHealth {
+    /// 良好
+    #[default]
+    Good,
+    /// 降级: 信号偏弱或偶发丢包
+    Degraded,
+    /// 差: 需要恢复动作
+    Poor,
+    /// 断开: 未关联到 AP
+    Down,
+}
+
+impl LinkHealth {
+    /// 名称 (用于事件与日志)
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Good => "good",
+            Self::Degraded => "degraded",
+            Self::Poor => "poor",
+            Self::Down => "down",
+        }
+    }
+}
+
+/// 恢复动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkAction {
+    /// 断开并重新连接 (AP 回退后用于恢复 STA 模式)
+    Reconnect,
+    /// 射频下电后重新初始化
+    PowerCycleRadio,
+    /// 切换到 AP 模式，保证设备仍可被访问
+    ApFallback,
+}
+
+impl LinkAction {
+    /// 名称 (用于事件与日志)
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Reconnect => "reconnect",
+            Self::PowerCycleRadio => "power_cycle",
+            Self::ApFallback => "ap_fallback",
+        }
+    }
+}
+
+// ===== 采样 =====
+
+/// 一次链路采样
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LinkSample {
+    /// 当前 RSSI (dBm)，未关联时为 `None`
+    pub rssi: Option<i8>,
+    /// 上次采样以来丢失的信标数
+    pub missed_beacons: u16,
+    /// 连续 DHCP 续约失败次数
+    pub dhcp_failures: u8,
+    /// 网关 ping 结果，本轮未探测时为 `None`
+    pub gateway_reachable: Option<bool>,
+}
+
+impl LinkSample {
+    /// 未关联
+    pub const fn down() -> Self {
+        Self { rssi: None, missed_beacons: 0, dhcp_failures: 0, gateway_reachable: None }
+    }
+
+    /// 已关联，信号强度为 `rssi`
+    pub const fn associated(rssi: i8) -> Self {
+        Self { rssi: Some(rssi), missed_beacons: 0, dhcp_failures: 0, gateway_reachable: None }
+    }
+
+    /// 设置丢失信标数
+    pub const fn with_missed_beacons(mut self, missed: u16) -> Self {
+        self.missed_beacons = missed;
+        self
+    }
+
+    /// 设置连续 DHCP 续约失败次数
+    pub const fn with_dhcp_failures(mut self, failures: u8) -> Self {
+        self.dhcp_failures = failures;
+        self
+    }
+
+    /// 设置网关 ping 结果
+    pub const fn with_gateway(mut self, reachable: bool) -> Self {
+        self.gateway_reachable = Some(reachable);
+        self
+    }
+}
+
+// ===== 策略 =====
+
+/// 监管策略
+#[derive(Debug, Clone, Copy)]
+pub struct SupervisorPolicy {
+    /// 采样周期
+    pub interval: Duration,
+    /// 每隔多少次采样 ping 一次网关 (0 表示不探测)
+    pub ping_every: u8,
+    /// ping 超时
+    pub ping_timeout: Duration,
+    /// 低于该 RSSI 视为降级 (dBm)
+    pub rssi_degraded: i8,
+    /// 低于该 RSSI 视为差 (dBm)
+    pub rssi_poor: i8,
+    /// 恢复到更好等级所需的额外余量 (dB)
+    pub hysteresis_db: u8,
+    /// 单次采样丢失信标达到该数量视为差 (达到一半视为降级)
+    pub missed_beacons_poor: u16,
+    /// 网关连续 ping 失败达到该次数视为差
+    pub ping_failures_poor: u8,
+    /// DHCP 连续续约失败达到该次数视为差
+    pub dhcp_failures_poor: u8,
+    /// 差 / 断开持续多少次采样后触发动作
+    pub trigger_samples: u8,
+    /// 两次动作之间的最短间隔
+    pub cooldown: Duration,
+    /// 升级前的重连次数
+    pub max_reconnects: u8,
+    /// 升级到 AP 回退前的射频重启次数
+    pub max_power_cycles: u8,
+    /// 是否允许 AP 回退 (禁用时阶梯从重连重新开始)
+    pub ap_fallback: bool,
+    /// AP 回退保持时长，之后重新尝试 STA 连接
+    pub ap_fallback_hold: Duration,
+}
+
+impl SupervisorPolicy {
+    /// 默认策略
+    ///
+    /// 每 5 秒采样、每 3 次采样 ping 网关；-70 / -80 dBm 分级；
+    /// 连续 3 次差触发动作，间隔至少 30 秒；重连 3 次、射频重启 1 次后 AP 回退 5 分钟。
+    pub const fn new() -> Self {
+        Self {
+            interval: Duration::from_secs(5),
+            ping_every: 3,
+            ping_timeout: Duration::from_secs(1),
+            rssi_degraded: -70,
+            rssi_poor: -80,
+            hysteresis_db: 3,
+            missed_beacons_poor: 8,
+            ping_failures_poor: 3,
+            dhcp_failures_poor: 2,
+            trigger_samples: 3,
+            cooldown: Duration::from_secs(30),
+            max_reconnects: 3,
+            max_power_cycles: 1,
+            ap_fallback: true,
+            ap_fallback_hold: Duration::from_secs(300),
+        }
+    }
+
+    /// 设置采样周期
+    pub const fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// 设置网关探测频率与超时
+    pub const fn with_ping(mut self, every: u8, timeout: Duration) -> Self {
+        self.ping_every = every;
+        self.ping_timeout = timeout;
+        self
+    }
+
+    /// 设置 RSSI 分级阈值
+    pub const fn with_rssi_thresholds(mut self, degraded: i8, poor: i8) -> Self {
+        self.rssi_degraded = degraded;
+        self.rssi_poor = poor;
+        self
+    }
+
+    /// 设置触发条件
+    pub const fn with_trigger(mut self, samples: u8, cooldown: Duration) -> Self {
+        self.trigger_samples = if samples == 0 { 1 } else { samples };
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// 设置升级阶梯
+    pub const fn with_escalation(mut self, reconnects: u8, power_cycles: u8) -> Self {
+        self.max_reconnects = reconnects;
+        self.max_power_cycles = power_cycles;
+        self
+    }
+
+    /// 设置 AP 回退 (`None` 表示禁用)
+    pub const fn with_ap_fallback(mut self, hold: Option<Duration>) -> Self {
+        match hold {
+            Some(hold) => {
+                self.ap_fallback = true;
+                self.ap_fallback_hold = hold;
+            }
+            None => self.ap_fallback = false,
+        }
+        self
+    }
+}
+
+impl Default for SupervisorPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ===== 监管器 =====
+
+/// 链路操作接口 (由应用实现)
+#[allow(async_fn_in_trait)]
+pub trait SupervisedLink {
+    /// 采样链路状态 (`gateway_reachable` 由监管器填写)
+    async fn sample(&mut self) -> LinkSample;
+
+    /// ping 网关，超时返回 `false`
+    async fn ping_gateway(&mut self, timeout: Duration) -> bool;
+
+    /// 执行恢复动作
+    async fn apply(&mut self, action: LinkAction) -> Result<(), WifiError>;
+}
+
+/// 监管统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SupervisorStats {
+    /// 采样次数
+    pub samples: u32,
+    /// 健康度变化次数
+    pub transitions: u32,
+    /// 重连次数
+    pub reconnects: u32,
+    /// 射频重启次数
+    pub power_cycles: u32,
+    /// AP 回退次数
+    pub ap_fallbacks: u32,
+    /// 执行失败的动作数
+    pub failed_actions: u32,
+}
+
+/// 链路监管器
+pub struct LinkSupervisor {
+    interface: &'static str,
+    policy: SupervisorPolicy,
+    health: LinkHealth,
+    /// 连续差 / 断开的采样数
+    bad_streak: u8,
+    /// 网关连续 ping 失败次数
+    ping_failures: u8,
+    /// 本轮故障已执行的动作数
+    attempts: u8,
+    last_action: Option<Instant>,
+    /// AP 回退开始时刻
+    fallback_since: Option<Instant>,
+    rounds: u32,
+    stats: SupervisorStats,
+}
+
+impl LinkSupervisor {
+    /// 创建监管器
+    pub const fn new(interface: &'static str, policy: SupervisorPolicy) -> Self {
+        Self {
+            interface,
+            policy,
+            health: LinkHealth::Good,
+            bad_streak: 0,
+            ping_failures: 0,
+            attempts: 0,
+            last_action: None,
+            fallback_since: None,
+            rounds: 0,
+            stats: SupervisorStats {
+                samples: 0,
+                transitions: 0,
+                reconnects: 0,
+                power_cycles: 0,
+                ap_fallbacks: 0,
+                failed_actions: 0,
+            },
+        }
+    }
+
+    /// 策略
+    pub fn policy(&self) -> &SupervisorPolicy {
+        &self.policy
+    }
+
+    /// 当前健康度
+    pub fn health(&self) -> LinkHealth {
+        self.health
+    }
+
+    /// 是否处于 AP 回退
+    pub fn in_fallback(&self) -> bool {
+        self.fallback_since.is_some()
+    }
+
+    /// 统计
+    pub fn stats(&self) -> SupervisorStats {
+        self.stats
+    }
+
+    /// 本轮采样是否需要 ping 网关
+    pub fn ping_due(&self) -> bool {
+        self.policy.ping_every > 0
+            && self.fallback_since.is_none()
+            && self.rounds.is_multiple_of(self.policy.ping_every as u32)
+    }
+
+    /// 处理一次采样，返回需要执行的动作
+    pub fn observe(&mut self, sample: &LinkSample, now: Instant) -> Option<LinkAction> {
+        self.rounds = self.rounds.wrapping_add(1);
+        self.stats.samples = self.stats.samples.wrapping_add(1);
+        match sample.gateway_reachable {
+            Some(true) => self.ping_failures = 0,
+            Some(false) => self.ping_failures = self.ping_failures.saturating_add(1),
+            None if sample.rssi.is_none() => self.ping_failures = 0,
+            None => {}
+        }
+
+        if let Some(since) = self.fallback_since {
+            // AP 模式下 STA 指标无意义，只等待保持时间结束
+            if now.saturating_duration_since(since) < self.policy.ap_fallback_hold {
+                return None;
+            }
+            self.fallback_since = None;
+            self.attempts = 0;
+            self.bad_streak = 0;
+            return Some(self.act(LinkAction::Reconnect, now));
+        }
+
+        let health = self.classify(sample);
+        if health != self.health {
+            bus::publish(SystemEvent::LinkHealthChanged {
+                interface: self.interface,
+                from: self.health.name(),
+                to: health.name(),
+                rssi: sample.rssi.unwrap_or(0),
+            });
+            self.health = health;
+            self.stats.transitions = self.stats.transitions.wrapping_add(1);
+        }
+
+        match health {
+            LinkHealth::Good => {
+                self.bad_streak = 0;
+                self.attempts = 0;
+                return None;
+            }
+            LinkHealth::Degraded => {
+                self.bad_streak = 0;
+                return None;
+            }
+            LinkHealth::Poor | LinkHealth::Down => {}
+        }
+
+        self.bad_streak = self.bad_streak.saturating_add(1);
+        if self.bad_streak < self.policy.trigger_samples {
+            return None;
+        }
+        if let Some(last) = self.last_action {
+            if now.saturating_duration_since(last) < self.policy.cooldown {
+                return None;
+            }
+        }
+
+        let reconnects = self.policy.max_reconnects;
+        let power_cycles = reconnects.saturating_add(self.policy.max_power_cycles);
+        if self.attempts >= power_cycles && !self.policy.ap_fallback {
+            self.attempts = 0;
+        }
+        let action = if self.attempts < reconnects {
+            LinkAction::Reconnect
+        } else if self.attempts < power_cycles {
+            LinkAction::PowerCycleRadio
+        } else {
+            LinkAction::ApFallback
+        };
+        self.bad_streak = 0;
+        Some(self.act(action, now))
+    }
+
+    /// 记录动作执行结果并发布事件
+    pub fn completed(&mut self, action: LinkAction, result: Result<(), WifiError>, now: Instant) {
+        let ok = result.is_ok();
+        bus::publish(SystemEvent::LinkAction { interface: self.interface, action: action.name(), attempt: self.attempts, ok });
+        if !ok {
+            self.stats.failed_actions = self.stats.failed_actions.wrapping_add(1);
+        }
+        if action == LinkAction::ApFallback && ok {
+            self.fallback_since = Some(now);
+        }
+        // 链路状态已被动作重置，重新积累
+        self.ping_failures = 0;
+    }
+
+    /// 按固定周期采样并执行动作 (不返回)
+    pub async fn run<L: SupervisedLink>(&mut self, link: &mut L) -> ! {
+        let mut ticker = Ticker::every(self.policy.interval);
+        loop {
+            ticker.next().await;
+            let mut sample = link.sample().await;
+            STATUS.set_rssi(sample.rssi);
+            if sample.rssi.is_some() && self.ping_due() {
+                sample.gateway_reachable = Some(link.ping_gateway(self.policy.ping_timeout).await);
+            }
+            let Some(action) = self.observe(&sample, Instant::now()) else {
+                continue;
+            };
+            log_warn!("{} link {}: {} (attempt {})", self.interface, self.health.name(), action.name(), self.attempts);
+            let result = link.apply(action).await;
+            if let Err(e) = result {
+                log_error!("{} {} failed: {}", self.interface, action.name(), e);
+            }
+            self.completed(action, result, Instant::now());
+            ticker.reset();
+        }
+    }
+
+    fn act(&mut self, action: LinkAction, now: Instant) -> LinkAction {
+        self.attempts = self.attempts.saturating_add(1);
+        self.last_action = Some(now);
+        let counter = match action {
+            LinkAction::Reconnect => &mut self.stats.reconnects,
+            LinkAction::PowerCycleRadio => &mut self.stats.power_cycles,
+            LinkAction::ApFallback => &mut self.stats.ap_fallbacks,
+        };
+        *counter = counter.wrapping_add(1);
+        action
+    }
+
+    fn classify(&self, sample: &LinkSample) -> LinkHealth {
+        let Some(rssi) = sample.rssi else {
+            return LinkHealth::Down;
+        };
+        let policy = &self.policy;
+        // 当前处于某等级或更差时，需多出迟滞余量才能回到更好的等级
+        let threshold = |level: LinkHealth, dbm: i8| {
+            let margin = if self.health >= level { policy.hysteresis_db as i16 } else { 0 };
+            dbm as i16 + margin
+        };
+        let rssi = rssi as i16;
+
+        if rssi < threshold(LinkHealth::Poor, policy.rssi_poor)
+            || sample.missed_beacons >= policy.missed_beacons_poor.max(1)
+            || self.ping_failures >= policy.ping_failures_poor.max(1)
+            || sample.dhcp_failures >= policy.dhcp_failures_poor.max(1)
+        {
+            LinkHealth::Poor
+        } else if rssi < threshold(LinkHealth::Degraded, policy.rssi_degraded)
+            || sample.missed_beacons >= (policy.missed_beacons_poor / 2).max(1)
+            || self.ping_failures > 0
+            || sample.dhcp_failures > 0
+        {
+            LinkHealth::Degraded
+        } else {
+            LinkHealth::Good
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_with_hysteresis() {
+        let mut sup = LinkSupervisor::new("wifi", SupervisorPolicy::new());
+        let t = Instant::from_secs(0);
+        sup.observe(&LinkSample::associated(-60), t);
+        assert_eq!(sup.health(), LinkHealth::Good);
+        sup.observe(&LinkSample::associated(-72), t);
+        assert_eq!(sup.health(), LinkHealth::Degraded);
+        // 需要高于 -70 + 3 dB 才恢复
+        sup.observe(&LinkSample::associated(-69), t);
+        assert_eq!(sup.health(), LinkHealth::Degraded);
+        sup.observe(&LinkSample::associated(-66), t);
+        assert_eq!(sup.health(), LinkHealth::Good);
+
+        sup.observe(&LinkSample::associated(-60).with_gateway(false), t);
+        assert_eq!(sup.health(), LinkHealth::Degraded);
+        sup.observe(&LinkSample::associated(-60).with_missed_beacons(9), t);
+        assert_eq!(sup.health(), LinkHealth::Poor);
+        sup.observe(&LinkSample::down(), t);
+        assert_eq!(sup.health(), LinkHealth::Down);
+    }
+
+    #[test]
+    fn test_escalation_ladder() {
+        let policy = SupervisorPolicy::new()
+            .with_trigger(2, Duration::from_secs(10))
+            .with_escalation(2, 1)
+            .with_ap_fallback(Some(Duration::from_secs(60)));
+        let mut sup = LinkSupervisor::new("wifi", policy);
+        let mut t = Instant::from_secs(100);
+        let mut actions = heapless::Vec::<LinkAction, 8>::new();
+        for _ in 0..16 {
+            if let Some(action) = sup.observe(&LinkSample::down(), t) {
+                sup.completed(action, Ok(()), t);
+                actions.push(action).unwrap();
+            }
+            t += Duration::from_secs(5);
+        }
+        assert_eq!(
+            actions.as_slice(),
+            &[LinkAction::Reconnect, LinkAction::Reconnect, LinkAction::PowerCycleRadio, LinkAction::ApFallback]
+        );
+        assert!(sup.in_fallback());
+
+        // 保持期结束后恢复 STA，链路良好时阶梯复位
+        t += Duration::from_secs(60);
+        assert_eq!(sup.observe(&LinkSample::down(), t), Some(LinkAction::Reconnect));
+        sup.completed(LinkAction::Reconnect, Ok(()), t);
+        assert!(!sup.in_fallback());
+        assert_eq!(sup.observe(&LinkSample::associated(-50), t), None);
+        assert_eq!(sup.health(), LinkHealth::Good);
+        assert_eq!(sup.stats().reconnects, 3);
+    }
+}
@@ -1,36 +1,62 @@
 //! TCP/IP 网络栈模块
 //!
 //! 基于 embassy-net 和 smoltcp 提供 TCP/UDP Socket 抽象。
+//! `NetworkStack` 持有 `embassy_net::Stack` (通常运行在 esp-radio 的
+//! `WifiDevice` 上)，`TcpClient`/`TcpServer`/`UdpSocket` 直接委托给
+//! embassy-net 的 Socket，错误统一映射为 `NetworkError`。
 //!
 //! # 功能
 //!
-//! - TCP 客户端/服务器
+//! - TCP 客户端/服务器 (实现 `embedded_io_async::Read`/`Write`)
 //! - UDP Socket
-//! - DNS 解析
-//! - DHCP 客户端
+//! - DNS 解析 (经 `net::dns` 缓存；指定服务器、AAAA 查询见 `net::resolver`)
+//! - DHCP 客户端或静态 IP (租约信息与监管见 `net::dhcp`)
+//!
+//! 协议栈的 `Runner` 必须在独立任务中持续运行，否则所有 Socket 都不会有进展。
 //!
 //! # 示例
 //!
-//! ```ignore
-//! use rustrtos::net::tcp::{TcpClient, NetworkStack};
+//! ```rust,ignore
+//! use rustrtos::net::tcp::{NetResources, NetworkStack, StackConfig, TcpClient, WifiRunner};
+//!
+//! static RESOURCES: StaticCell<NetResources> = StaticCell::new();
+//!
+//! #[embassy_executor::task]
+//! async fn net_task(mut runner: WifiRunner<'static>) {
+//!     runner.run().await
+//! }
 //!
-//! // 获取网络栈
-//! let stack = NetworkStack::new(wifi_device);
+//! let (_controller, interfaces) = esp_radio::wifi::new(radio, peripherals.WIFI, Default::default())?;
+//! let (stack, runner) = NetworkStack::new(
+//!     interfaces.sta,
+//!     StackConfig::default(),
+//!     RESOURCES.init(NetResources::new()),
+//!     seed,
+//! )?;
+//! spawner.spawn(net_task(runner)).ok();
+//! stack.wait_ready(Duration::from_secs(30)).await?;
 //!
-//! // TCP 客户端连接
-//! let mut client = TcpClient::new(&stack);
-//! client.connect("192.168.1.1:80").await?;
-//! client.write(b"GET / HTTP/1.1\r\n\r\n").await?;
+//! let mut client = TcpClient::new(&stack, &mut rx_buf, &mut tx_buf);
+//! client.connect_host("example.com", 80).await?;
+//! client.write_all(b"GET / HTTP/1.1\r\n\r\n").await?;
 //! ```
 
 use core::fmt;
-use core::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4};
-use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
-use embassy_sync::mutex::Mutex;
-use embassy_time::{Duration, Timer};
-use heapless::Vec;
+use core::net::{Ipv4Addr, SocketAddrV4};
+
+use embassy_net::driver::Driver;
+use embassy_net::tcp::{self, State, TcpSocket};
+use embassy_net::udp::{self, PacketMetadata};
+use embassy_net::{IpAddress, IpEndpoint, Ipv4Cidr, Runner, Stack, StackResources, StaticConfigV4};
+use embassy_time::{with_timeout, Duration};
+use esp_radio::wifi::WifiDevice;
+
+use crate::util::log::*;
 
 use super::config::*;
+use super::dhcp::{self, DhcpSnoop, Lease};
+use super::dns;
+use super::resolver::Resolver;
 
 // ===== 错误类型 =====
 
@@ -94,6 +120,72 @@ impl fmt::Display for NetworkError {
     }
 }
 
+impl embedded_io_async::Error for NetworkError {
+    fn kind(&self) -> embedded_io_async::ErrorKind {
+        use embedded_io_async::ErrorKind;
+        match self {
+            Self::ConnectionRefused => ErrorKind::ConnectionRefused,
+            Self::ConnectionReset => ErrorKind::ConnectionReset,
+            Self::Timeout => ErrorKind::TimedOut,
+            Self::InvalidAddress => ErrorKind::InvalidInput,
+            Self::SocketClosed => ErrorKind::BrokenPipe,
+            Self::OutOfMemory => ErrorKind::OutOfMemory,
+            Self::NotConnected => ErrorKind::NotConnected,
+            Self::AddressInUse => ErrorKind::AddrInUse,
+            Self::HostUnreachable | Self::NetworkUnreachable => ErrorKind::AddrNotAvailable,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+impl From<tcp::Error> for NetworkError {
+    fn from(e: tcp::Error) -> Self {
+        match e {
+            tcp::Error::ConnectionReset => Self::ConnectionReset,
+        }
+    }
+}
+
+impl From<tcp::ConnectError> for NetworkError {
+    fn from(e: tcp::ConnectError) -> Self {
+        match e {
+            tcp::ConnectError::InvalidState => Self::InternalError,
+            tcp::ConnectError::ConnectionReset => Self::ConnectionRefused,
+            tcp::ConnectError::TimedOut => Self::Timeout,
+            tcp::ConnectError::NoRoute => Self::HostUnreachable,
+        }
+    }
+}
+
+impl From<tcp::AcceptError> for NetworkError {
+    fn from(e: tcp::AcceptError) -> Self {
+        match e {
+            tcp::AcceptError::InvalidState => Self::InternalError,
+            tcp::AcceptError::InvalidPort => Self::InvalidAddress,
+            tcp::AcceptError::ConnectionReset => Self::ConnectionReset,
+        }
+    }
+}
+
+impl From<udp::BindError> for NetworkError {
+    fn from(e: udp::BindError) -> Self {
+        match e {
+            udp::BindError::InvalidState => Self::AddressInUse,
+            udp::BindError::NoRoute => Self::NetworkUnreachable,
+        }
+    }
+}
+
+impl From<udp::SendError> for NetworkError {
+    fn from(e: udp::SendError) -> Self {
+        match e {
+            udp::SendError::NoRoute => Self::HostUnreachable,
+            udp::SendError::SocketNotBound => Self::NotInitialized,
+            udp::SendError::PacketTooLarge => Self::BufferFull,
+        }
+    }
+}
+
 // ===== IP 地址类型 =====
 
 /// IPv4 地址
@@ -140,13 +232,22 @@ impl From<Ipv4Addr> for Ipv4Address {
 
 // ===== 网络栈 =====
 
+/// 协议栈 Socket 槽位数 (TCP + UDP，另含 DHCP 与 DNS 各一个)
+pub const STACK_SOCKETS: usize = MAX_TCP_SOCKETS + MAX_UDP_SOCKETS + 2;
+
+/// 协议栈资源 (通常放在 `StaticCell` 中)
+pub type NetResources = StackResources<STACK_SOCKETS>;
+
+/// WiFi STA 接口上的协议栈驱动
+pub type WifiRunner<'a> = Runner<'a, DhcpSnoop<WifiDevice<'a>>>;
+
 /// 网络栈状态
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum StackState {
-    /// 未初始化
+    /// 链路未就绪
     #[default]
     Uninitialized,
-    /// 已初始化但无 IP
+    /// 链路已连接但无 IP
     NoIp,
     /// 正在获取 IP (DHCP)
     GettingIp,
@@ -192,126 +293,205 @@ impl StackConfig {
             dns: Some(gateway), // 默认使用网关作为 DNS
         }
     }
+
+    /// 转换为 embassy-net 配置
+    ///
+    /// 静态配置缺少 IP 或掩码不连续时返回 `InvalidAddress`；未给出掩码时按 /24 处理。
+    pub fn to_embassy(&self) -> Result<embassy_net::Config, NetworkError> {
+        if self.dhcp {
+            return Ok(embassy_net::Config::dhcpv4(Default::default()));
+        }
+        let ip = self.static_ip.ok_or(NetworkError::InvalidAddress)?;
+        let netmask = self.netmask.unwrap_or(Ipv4Address::new(255, 255, 255, 0));
+        Ok(embassy_net::Config::ipv4_static(static_v4(ip, netmask, self.gateway, self.dns)?))
+    }
+}
+
+/// 子网掩码转换为前缀长度 (要求掩码连续)
+pub fn prefix_len(netmask: Ipv4Address) -> Result<u8, NetworkError> {
+    let mask = u32::from_be_bytes(netmask.0);
+    if mask.leading_ones() != mask.count_ones() {
+        return Err(NetworkError::InvalidAddress);
+    }
+    Ok(mask.count_ones() as u8)
+}
+
+fn static_v4(
+    ip: Ipv4Address,
+    netmask: Ipv4Address,
+    gateway: Option<Ipv4Address>,
+    dns: Option<Ipv4Address>,
+) -> Result<StaticConfigV4, NetworkError> {
+    let mut dns_servers = heapless::Vec::new();
+    if let Some(server) = dns.or(gateway) {
+        let _ = dns_servers.push(server.to_std());
+    }
+    Ok(StaticConfigV4 {
+        address: Ipv4Cidr::new(ip.to_std(), prefix_len(netmask)?),
+        gateway: gateway.map(|g| g.to_std()),
+        dns_servers,
+    })
+}
+
+fn endpoint_v4(endpoint: IpEndpoint) -> SocketAddrV4 {
+    match endpoint.addr {
+        IpAddress::Ipv4(ip) => SocketAddrV4::new(ip, endpoint.port),
+    }
 }
 
 /// 网络栈
 ///
-/// 封装 embassy-net 提供的网络功能。
+/// 封装 `embassy_net::Stack`。`Stack` 本身是可复制的句柄，
+/// 由本结构创建的 Socket 各自持有一份。
 pub struct NetworkStack<'a> {
-    /// 状态
-    state: StackState,
+    /// embassy-net 协议栈
+    stack: Stack<'a>,
     /// 配置
     config: StackConfig,
-    /// 本地 IP 地址
-    local_ip: Option<Ipv4Address>,
-    /// 网关地址
-    gateway: Option<Ipv4Address>,
-    /// DNS 服务器
-    dns_server: Option<Ipv4Address>,
-    /// 生命周期标记
-    _marker: core::marker::PhantomData<&'a ()>,
 }
 
 impl<'a> NetworkStack<'a> {
-    /// 创建新的网络栈
-    pub fn new(config: StackConfig) -> Self {
-        Self {
-            state: StackState::Uninitialized,
-            config,
-            local_ip: None,
-            gateway: None,
-            dns_server: None,
-            _marker: core::marker::PhantomData,
-        }
+    /// 在网络设备上创建协议栈
+    ///
+    /// 返回的 `Runner` 必须交给独立任务执行 `run()`。
+    /// `seed` 用于 TCP 初始序号与本地端口随机化，应取自硬件 RNG。
+    /// 设备被包装为 `DhcpSnoop`，以便记录 DHCP 租约信息。
+    pub fn new<D: Driver, const N: usize>(
+        device: D,
+        config: StackConfig,
+        resources: &'a mut StackResources<N>,
+        seed: u64,
+    ) -> Result<(Self, Runner<'a, DhcpSnoop<D>>), NetworkError> {
+        let (stack, runner) = embassy_net::new(DhcpSnoop::new(device), config.to_embassy()?, resources, seed);
+        Ok((Self { stack, config }, runner))
+    }
+
+    /// 包装已创建的协议栈
+    pub fn from_stack(stack: Stack<'a>, config: StackConfig) -> Self {
+        Self { stack, config }
+    }
+
+    /// 获取底层 embassy-net 协议栈
+    pub fn stack(&self) -> Stack<'a> {
+        self.stack
+    }
+
+    /// 获取配置
+    pub fn config(&self) -> &StackConfig {
+        &self.config
     }
 
-    /// 初始化网络栈
+    /// 等待链路就绪
     ///
-    /// **注意**: 此函数仅初始化状态。实际网络栈应通过 embassy-net 配置。
-    /// 参见 `examples/tcp_client.rs`。
+    /// WiFi 关联成功后链路才会 up，超时返回 `Timeout`。
     pub async fn init(&mut self) -> Result<(), NetworkError> {
-        // 状态管理层 - 实际网络栈通过 embassy_net::Stack 初始化
-        self.state = StackState::NoIp;
-        Ok(())
+        let timeout = Duration::from_millis(WIFI_CONNECT_TIMEOUT_MS as u64);
+        with_timeout(timeout, self.stack.wait_link_up())
+            .await
+            .map_err(|_| NetworkError::Timeout)
     }
 
-    /// 启动 DHCP 客户端
-    ///
-    /// **注意**: 此函数返回默认 IP。实际 DHCP 应通过 `embassy_net::DhcpConfig` 配置。
-    /// 成功获取 IP 后调用 `set_addresses()` 更新状态。
+    /// 启动 DHCP 客户端并等待获取 IP
     pub async fn start_dhcp(&mut self) -> Result<(), NetworkError> {
-        if self.state == StackState::Uninitialized {
+        if !self.stack.is_link_up() {
             return Err(NetworkError::NotInitialized);
         }
-
-        self.state = StackState::GettingIp;
-        
-        // 状态管理层 - 实际 DHCP 通过 embassy_net::DhcpConfig 完成
-        // 返回默认 IP 以允许测试
-        Timer::after(Duration::from_millis(100)).await;
-        
-        self.local_ip = Some(Ipv4Address::new(192, 168, 1, 100));
-        self.gateway = Some(Ipv4Address::new(192, 168, 1, 1));
-        self.dns_server = Some(Ipv4Address::new(8, 8, 8, 8));
-        self.state = StackState::Ready;
-
+        if !self.config.dhcp {
+            self.stack.set_config_v4(embassy_net::ConfigV4::Dhcp(Default::default()));
+            self.config.dhcp = true;
+        }
+        self.wait_ready(Duration::from_secs(DHCP_TIMEOUT_SECS as u64)).await?;
+        if let Some(_lease) = self.lease() {
+            log_info!("DHCP: got {} lease {}s", _lease.address.to_std(), _lease.lease_secs);
+        }
         Ok(())
     }
 
+    /// 重新运行 DHCP (重新关联 AP 后调用) 并等待地址
+    ///
+    /// 丢弃当前 IPv4 配置，DHCP 客户端从 DISCOVER 重新开始；静态 IP 时只等待配置就绪。
+    pub async fn restart_dhcp(&self, timeout: Duration) -> Result<Ipv4Address, NetworkError> {
+        if self.config.dhcp {
+            self.stack.set_config_v4(embassy_net::ConfigV4::Dhcp(Default::default()));
+        }
+        self.wait_ready(timeout).await?;
+        self.local_ip().ok_or(NetworkError::NotInitialized)
+    }
+
     /// 设置静态 IP
+    ///
+    /// 立即生效并停止 DHCP 客户端。
     pub async fn set_static_ip(
         &mut self,
         ip: Ipv4Address,
         netmask: Ipv4Address,
         gateway: Ipv4Address,
     ) -> Result<(), NetworkError> {
-        if self.state == StackState::Uninitialized {
-            return Err(NetworkError::NotInitialized);
-        }
-
-        self.local_ip = Some(ip);
-        self.gateway = Some(gateway);
-        self.state = StackState::Ready;
-
+        let config = StackConfig::with_static(ip, netmask, gateway);
+        let v4 = static_v4(ip, netmask, config.gateway, config.dns)?;
+        self.stack.set_config_v4(embassy_net::ConfigV4::Static(v4));
+        self.config = config;
         Ok(())
     }
 
+    /// 等待 IPv4 配置就绪
+    pub async fn wait_ready(&self, timeout: Duration) -> Result<(), NetworkError> {
+        with_timeout(timeout, self.stack.wait_config_up())
+            .await
+            .map_err(|_| NetworkError::Timeout)
+    }
+
     /// 获取当前状态
     pub fn state(&self) -> StackState {
-        self.state
+        if !self.stack.is_link_up() {
+            StackState::Uninitialized
+        } else if self.stack.is_config_up() {
+            StackState::Ready
+        } else if self.config.dhcp {
+            StackState::GettingIp
+        } else {
+            StackState::NoIp
+        }
     }
 
     /// 获取本地 IP 地址
     pub fn local_ip(&self) -> Option<Ipv4Address> {
-        self.local_ip
+        self.stack.config_v4().map(|c| Ipv4Address::from(c.address.address()))
     }
 
     /// 获取网关地址
     pub fn gateway(&self) -> Option<Ipv4Address> {
-        self.gateway
+        self.stack.config_v4().and_then(|c| c.gateway).map(Ipv4Address::from)
     }
 
     /// 获取 DNS 服务器
     pub fn dns_server(&self) -> Option<Ipv4Address> {
-        self.dns_server
+        self.stack
+            .config_v4()
+            .and_then(|c| c.dns_servers.first().copied())
+            .map(Ipv4Address::from)
+    }
+
+    /// 当前 DHCP 租约 (静态配置或尚未收到 ACK 时为 `None`)
+    pub fn lease(&self) -> Option<Lease> {
+        if self.stack.is_config_up() {
+            dhcp::lease()
+        } else {
+            None
+        }
     }
 
     /// 检查是否就绪
     pub fn is_ready(&self) -> bool {
-        self.state == StackState::Ready
+        self.stack.is_config_up()
     }
 
-    /// DNS 解析
-    ///
-    /// **注意**: 此函数返回错误。实际 DNS 解析应通过
-    /// `embassy_net::dns::DnsQueryType::A` 和 `Stack::dns_query()` 完成。
-    pub async fn dns_resolve(&self, _hostname: &str) -> Result<Ipv4Address, NetworkError> {
-        if self.state != StackState::Ready {
+    /// DNS 解析 (经 `net::dns` 缓存，未命中时由 `net::resolver` 查询)
+    pub async fn dns_resolve(&self, hostname: &str) -> Result<Ipv4Address, NetworkError> {
+        if !self.is_ready() {
             return Err(NetworkError::NotInitialized);
         }
-
-        // 状态管理层 - 实际 DNS 解析通过 embassy_net Stack 完成
-        Err(NetworkError::DnsResolutionFailed)
+        dns::resolve(self.stack, hostname).await
     }
 }
 
@@ -331,58 +511,53 @@ pub enum TcpState {
     Closing,
 }
 
+impl From<State> for TcpState {
+    fn from(state: State) -> Self {
+        match state {
+            State::Closed | State::TimeWait => Self::Closed,
+            State::Listen | State::SynSent | State::SynReceived => Self::Connecting,
+            // CloseWait: 对端已关闭发送方向，本端仍可写
+            State::Established | State::CloseWait => Self::Connected,
+            State::FinWait1 | State::FinWait2 | State::Closing | State::LastAck => Self::Closing,
+        }
+    }
+}
+
 /// TCP 客户端
+///
+/// 包装 `embassy_net::tcp::TcpSocket`，收发缓冲区由调用者提供。
 pub struct TcpClient<'a> {
-    /// 状态
-    state: TcpState,
-    /// 本地端口
-    local_port: u16,
-    /// 远程地址
-    remote_addr: Option<SocketAddrV4>,
-    /// 接收缓冲区
-    rx_buffer: Vec<u8, TCP_RX_BUFFER_SIZE>,
-    /// 发送缓冲区
-    tx_buffer: Vec<u8, TCP_TX_BUFFER_SIZE>,
-    /// 网络栈引用
-    _stack: core::marker::PhantomData<&'a ()>,
+    /// 协议栈句柄 (DNS 解析用)
+    stack: Stack<'a>,
+    /// 底层 Socket
+    socket: TcpSocket<'a>,
 }
 
 impl<'a> TcpClient<'a> {
     /// 创建新的 TCP 客户端
-    pub fn new() -> Self {
-        Self {
-            state: TcpState::Closed,
-            local_port: 0,
-            remote_addr: None,
-            rx_buffer: Vec::new(),
-            tx_buffer: Vec::new(),
-            _stack: core::marker::PhantomData,
-        }
+    ///
+    /// 默认启用 `TCP_KEEPALIVE_INTERVAL_SECS` 间隔的保活。
+    pub fn new(stack: &NetworkStack<'a>, rx_buffer: &'a mut [u8], tx_buffer: &'a mut [u8]) -> Self {
+        let mut socket = TcpSocket::new(stack.stack(), rx_buffer, tx_buffer);
+        socket.set_keep_alive(Some(Duration::from_secs(TCP_KEEPALIVE_INTERVAL_SECS as u64)));
+        Self { stack: stack.stack(), socket }
     }
 
     /// 连接到远程地址
     ///
-    /// **注意**: 此函数仅更新状态。实际 TCP 连接应通过
-    /// `embassy_net::tcp::TcpSocket::connect()` 完成。
+    /// `TCP_CONNECT_TIMEOUT_SECS` 内未建立连接则中止并返回 `Timeout`。
     pub async fn connect(&mut self, addr: SocketAddrV4) -> Result<(), NetworkError> {
-        if self.state != TcpState::Closed {
+        if self.state() != TcpState::Closed {
             return Err(NetworkError::InternalError);
         }
-
-        self.state = TcpState::Connecting;
-        self.remote_addr = Some(addr);
-
-        // 状态管理层 - 实际连接通过 embassy_net::tcp::TcpSocket 完成
         let timeout = Duration::from_secs(TCP_CONNECT_TIMEOUT_SECS as u64);
-        let _ = timeout; // 仅用于类型检查
-        
-        // 状态转换延迟
-        Timer::after(Duration::from_millis(100)).await;
-        
-        self.state = TcpState::Connected;
-        self.local_port = 49152; // 动态端口
-
-        Ok(())
+        match with_timeout(timeout, self.socket.connect((*addr.ip(), addr.port()))).await {
+            Ok(result) => result.map_err(NetworkError::from),
+            Err(_) => {
+                self.socket.abort();
+                Err(NetworkError::Timeout)
+            }
+        }
     }
 
     /// 连接到 IP 和端口
@@ -391,130 +566,193 @@ impl<'a> TcpClient<'a> {
         self.connect(addr).await
     }
 
+    /// 解析主机名 (经 DNS 缓存) 后连接
+    ///
+    /// 连接失败时移除该主机的缓存，下次重连重新解析。
+    pub async fn connect_host(&mut self, host: &str, port: u16) -> Result<(), NetworkError> {
+        let ip = dns::resolve(self.stack, host).await?;
+        let result = self.connect_to(ip, port).await;
+        if result.is_err() {
+            dns::flush_host(host);
+        }
+        result
+    }
+
+    /// 经指定解析器 (可配置 DNS 服务器、按记录 TTL 缓存) 解析主机名后连接
+    ///
+    /// 连接失败时经 `Resolver::flush_host` 移除该解析器缓存的记录。
+    pub async fn connect_hostname(
+        &mut self,
+        resolver: &mut Resolver<'_>,
+        host: &str,
+        port: u16,
+    ) -> Result<(), NetworkError> {
+        let ip = resolver.resolve(host).await?;
+        let result = self.connect_to(ip, port).await;
+        if result.is_err() {
+            resolver.flush_host(host);
+        }
+        result
+    }
+
     /// 发送数据
     ///
-    /// **注意**: 此函数返回数据长度但不真正发送。实际发送应通过
-    /// `embassy_net::tcp::TcpSocket::write()` 完成。
+    /// 返回写入发送缓冲区的字节数，可能小于 `data.len()`。
     pub async fn write(&mut self, data: &[u8]) -> Result<usize, NetworkError> {
-        if self.state != TcpState::Connected {
+        if !self.socket.may_send() {
             return Err(NetworkError::NotConnected);
         }
+        Ok(self.socket.write(data).await?)
+    }
 
-        // 状态管理层 - 实际发送通过 embassy_net::tcp::TcpSocket 完成
-        Ok(data.len())
+    /// 发送全部数据
+    pub async fn write_all(&mut self, mut data: &[u8]) -> Result<(), NetworkError> {
+        while !data.is_empty() {
+            match self.write(data).await? {
+                0 => return Err(NetworkError::SocketClosed),
+                n => data = &data[n..],
+            }
+        }
+        Ok(())
+    }
+
+    /// 等待发送缓冲区中的数据全部被对端确认
+    pub async fn flush(&mut self) -> Result<(), NetworkError> {
+        Ok(self.socket.flush().await?)
     }
 
     /// 接收数据
     ///
-    /// **注意**: 此函数返回 0 字节。实际接收应通过
-    /// `embassy_net::tcp::TcpSocket::read()` 完成。
+    /// 返回 0 表示对端已关闭连接。
     pub async fn read(&mut self, buf: &mut [u8]) -> Result<usize, NetworkError> {
-        if self.state != TcpState::Connected {
+        if self.state() == TcpState::Closed {
             return Err(NetworkError::NotConnected);
         }
+        Ok(self.socket.read(buf).await?)
+    }
 
-        // 状态管理层 - 实际接收通过 embassy_net::tcp::TcpSocket 完成
-        let _ = buf; // 仅用于类型检查
-        Ok(0)
+    /// 设置空闲超时 (超时未收到任何数据则中止连接)
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.socket.set_timeout(timeout);
     }
 
     /// 关闭连接
     ///
-    /// **注意**: 此函数仅更新状态。实际关闭应通过
-    /// `embassy_net::tcp::TcpSocket::close()` 完成。
+    /// 发送 FIN 并等待已排队数据发送完毕。
     pub async fn close(&mut self) -> Result<(), NetworkError> {
-        if self.state == TcpState::Closed {
+        if self.state() == TcpState::Closed {
             return Ok(());
         }
+        self.socket.close();
+        self.flush().await
+    }
 
-        self.state = TcpState::Closing;
-        
-        // 状态管理层 - 实际关闭通过 embassy_net::tcp::TcpSocket 完成
-        
-        self.state = TcpState::Closed;
-        self.remote_addr = None;
-        self.rx_buffer.clear();
-        self.tx_buffer.clear();
-
-        Ok(())
+    /// 立即中止连接 (发送 RST)
+    pub fn abort(&mut self) {
+        self.socket.abort();
     }
 
     /// 获取状态
     pub fn state(&self) -> TcpState {
-        self.state
+        TcpState::from(self.socket.state())
     }
 
     /// 检查是否已连接
     pub fn is_connected(&self) -> bool {
-        self.state == TcpState::Connected
+        self.state() == TcpState::Connected
     }
 
     /// 获取远程地址
     pub fn remote_addr(&self) -> Option<SocketAddrV4> {
-        self.remote_addr
+        self.socket.remote_endpoint().map(endpoint_v4)
     }
 
     /// 获取本地端口
     pub fn local_port(&self) -> u16 {
-        self.local_port
+        self.socket.local_endpoint().map(|e| e.port).unwrap_or(0)
+    }
+
+    /// 获取底层 Socket
+    pub fn socket(&mut self) -> &mut TcpSocket<'a> {
+        &mut self.socket
     }
 }
 
-impl<'a> Default for TcpClient<'a> {
-    fn default() -> Self {
-        Self::new()
+impl embedded_io_async::ErrorType for TcpClient<'_> {
+    type Error = NetworkError;
+}
+
+impl embedded_io_async::Read for TcpClient<'_> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        TcpClient::read(self, buf).await
+    }
+}
+
+impl embedded_io_async::Write for TcpClient<'_> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        TcpClient::write(self, buf).await
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        TcpClient::flush(self).await
     }
 }
 
 // ===== TCP Server =====
 
 /// TCP 服务器
+///
+/// embassy-net 的监听是逐 Socket 的: 每次 `accept()` 在调用者提供的缓冲区上
+/// 新建一个 Socket 等待连接，同时服务多个客户端需并发调用多次。
 pub struct TcpServer<'a> {
+    /// 协议栈句柄
+    stack: Stack<'a>,
     /// 监听端口
     port: u16,
     /// 是否正在监听
     listening: bool,
-    /// 生命周期标记
-    _marker: core::marker::PhantomData<&'a ()>,
 }
 
 impl<'a> TcpServer<'a> {
     /// 创建新的 TCP 服务器
-    pub fn new(port: u16) -> Self {
+    pub fn new(stack: &NetworkStack<'a>, port: u16) -> Self {
         Self {
+            stack: stack.stack(),
             port,
             listening: false,
-            _marker: core::marker::PhantomData,
         }
     }
 
     /// 开始监听
-    ///
-    /// **注意**: 此函数仅更新状态。实际监听应通过
-    /// `embassy_net::tcp::TcpSocket::accept()` 完成。
     pub async fn listen(&mut self) -> Result<(), NetworkError> {
-        // 状态管理层 - 实际监听通过 embassy_net::tcp::TcpSocket 完成
+        if self.port == 0 {
+            return Err(NetworkError::InvalidAddress);
+        }
         self.listening = true;
         Ok(())
     }
 
     /// 接受连接
     ///
-    /// **注意**: 此函数永远等待。实际接受应通过
-    /// `embassy_net::tcp::TcpSocket::accept()` 完成。
-    pub async fn accept(&mut self) -> Result<TcpClient<'a>, NetworkError> {
+    /// 等待直到有客户端连入，返回的 `TcpClient` 使用给定缓冲区。
+    /// 每次调用新建独立的 Socket，可在同一服务器上用不同缓冲区并发调用。
+    pub async fn accept(
+        &self,
+        rx_buffer: &'a mut [u8],
+        tx_buffer: &'a mut [u8],
+    ) -> Result<TcpClient<'a>, NetworkError> {
         if !self.listening {
             return Err(NetworkError::NotInitialized);
         }
-
-        // 状态管理层 - 实际接受通过 embassy_net::tcp::TcpSocket 完成
-        // 此处永远等待，应用层应直接使用 embassy-net
-        loop {
-            Timer::after(Duration::from_millis(100)).await;
-        }
+        let mut socket = TcpSocket::new(self.stack, rx_buffer, tx_buffer);
+        socket.set_keep_alive(Some(Duration::from_secs(TCP_KEEPALIVE_INTERVAL_SECS as u64)));
+        socket.accept(self.port).await?;
+        Ok(TcpClient { stack: self.stack, socket })
     }
 
     /// 停止监听
+    ///
+    /// 已接受的连接不受影响。
     pub async fn close(&mut self) -> Result<(), NetworkError> {
         self.listening = false;
         Ok(())
@@ -533,74 +771,93 @@ impl<'a> TcpServer<'a> {
 
 // ===== UDP Socket =====
 
+/// 每个方向的 UDP 报文元数据槽位数
+pub const UDP_PACKET_SLOTS: usize = 8;
+
+/// UDP Socket 缓冲区 (元数据槽位 + 收发字节缓冲)
+pub struct UdpBuffers<const M: usize, const RX: usize, const TX: usize> {
+    rx_meta: [PacketMetadata; M],
+    rx: [u8; RX],
+    tx_meta: [PacketMetadata; M],
+    tx: [u8; TX],
+}
+
+/// 按 `net::config` 调优参数确定大小的 UDP 缓冲区
+pub type DefaultUdpBuffers = UdpBuffers<UDP_PACKET_SLOTS, UDP_RX_BUFFER_SIZE, UDP_TX_BUFFER_SIZE>;
+
+impl<const M: usize, const RX: usize, const TX: usize> UdpBuffers<M, RX, TX> {
+    /// 创建空缓冲区
+    pub const fn new() -> Self {
+        Self {
+            rx_meta: [PacketMetadata::EMPTY; M],
+            rx: [0; RX],
+            tx_meta: [PacketMetadata::EMPTY; M],
+            tx: [0; TX],
+        }
+    }
+}
+
+impl<const M: usize, const RX: usize, const TX: usize> Default for UdpBuffers<M, RX, TX> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// UDP Socket
+///
+/// 包装 `embassy_net::udp::UdpSocket`。
 pub struct UdpSocket<'a> {
+    /// 底层 Socket
+    socket: udp::UdpSocket<'a>,
     /// 本地端口
     local_port: u16,
-    /// 是否已绑定
-    bound: bool,
-    /// 接收缓冲区
-    rx_buffer: Vec<u8, UDP_RX_BUFFER_SIZE>,
-    /// 生命周期标记
-    _marker: core::marker::PhantomData<&'a ()>,
 }
 
 impl<'a> UdpSocket<'a> {
     /// 创建新的 UDP Socket
-    pub fn new() -> Self {
+    pub fn new<const M: usize, const RX: usize, const TX: usize>(
+        stack: &NetworkStack<'a>,
+        buffers: &'a mut UdpBuffers<M, RX, TX>,
+    ) -> Self {
+        let UdpBuffers { rx_meta, rx, tx_meta, tx } = buffers;
         Self {
+            socket: udp::UdpSocket::new(stack.stack(), rx_meta, rx, tx_meta, tx),
             local_port: 0,
-            bound: false,
-            rx_buffer: Vec::new(),
-            _marker: core::marker::PhantomData,
         }
     }
 
-    /// 绑定到端口
-    ///
-    /// **注意**: 此函数仅更新状态。实际绑定应通过
-    /// `embassy_net::udp::UdpSocket::bind()` 完成。
+    /// 绑定到端口 (0 表示由协议栈分配临时端口)
     pub async fn bind(&mut self, port: u16) -> Result<(), NetworkError> {
-        // 状态管理层 - 实际绑定通过 embassy_net::udp::UdpSocket 完成
-        self.local_port = port;
-        self.bound = true;
+        self.socket.bind(port)?;
+        self.local_port = self.socket.endpoint().port;
         Ok(())
     }
 
     /// 发送数据到指定地址
-    ///
-    /// **注意**: 此函数返回数据长度但不真正发送。实际发送应通过
-    /// `embassy_net::udp::UdpSocket::send_to()` 完成。
     pub async fn send_to(&self, data: &[u8], addr: SocketAddrV4) -> Result<usize, NetworkError> {
-        if !self.bound {
+        if !self.is_bound() {
             return Err(NetworkError::NotInitialized);
         }
-
-        // 状态管理层 - 实际发送通过 embassy_net::udp::UdpSocket 完成
-        let _ = addr; // 仅用于类型检查
+        self.socket.send_to(data, (*addr.ip(), addr.port())).await?;
         Ok(data.len())
     }
 
     /// 接收数据
     ///
-    /// **注意**: 此函数永远等待。实际接收应通过
-    /// `embassy_net::udp::UdpSocket::recv_from()` 完成。
+    /// 报文大于 `buf` 时丢弃并返回 `BufferFull`。
     pub async fn recv_from(&mut self, buf: &mut [u8]) -> Result<(usize, SocketAddrV4), NetworkError> {
-        if !self.bound {
+        if !self.is_bound() {
             return Err(NetworkError::NotInitialized);
         }
-
-        // 状态管理层 - 实际接收通过 embassy_net::udp::UdpSocket 完成
-        // 此处永远等待，应用层应直接使用 embassy-net
-        let _ = buf; // 仅用于类型检查
-        loop {
-            Timer::after(Duration::from_millis(100)).await;
+        match self.socket.recv_from(buf).await {
+            Ok((n, meta)) => Ok((n, endpoint_v4(meta.endpoint))),
+            Err(udp::RecvError::Truncated) => Err(NetworkError::BufferFull),
         }
     }
 
     /// 关闭 Socket
     pub async fn close(&mut self) -> Result<(), NetworkError> {
-        self.bound = false;
+        self.socket.close();
         self.local_port = 0;
         Ok(())
     }
@@ -612,13 +869,7 @@ impl<'a> UdpSocket<'a> {
 
     /// 检查是否已绑定
     pub fn is_bound(&self) -> bool {
-        self.bound
-    }
-}
-
-impl<'a> Default for UdpSocket<'a> {
-    fn default() -> Self {
-        Self::new()
+        self.socket.is_open()
     }
 }
 
@@ -642,3 +893,38 @@ pub struct NetworkStats {
     /// 丢弃的数据包
     pub dropped: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_len() {
+        assert_eq!(prefix_len(Ipv4Address::new(255, 255, 255, 0)), Ok(24));
+        assert_eq!(prefix_len(Ipv4Address::new(255, 255, 240, 0)), Ok(20));
+        assert_eq!(prefix_len(Ipv4Address::UNSPECIFIED), Ok(0));
+        assert_eq!(prefix_len(Ipv4Address::new(255, 0, 255, 0)), Err(NetworkError::InvalidAddress));
+    }
+
+    #[test]
+    fn test_static_config() {
+        let config = StackConfig::with_static(
+            Ipv4Address::new(192, 168, 4, 10),
+            Ipv4Address::new(255, 255, 255, 0),
+            Ipv4Address::new(192, 168, 4, 1),
+        );
+        let v4 = static_v4(
+            config.static_ip.unwrap(),
+            config.netmask.unwrap(),
+            config.gateway,
+            config.dns,
+        )
+        .unwrap();
+        assert_eq!(v4.address.prefix_len(), 24);
+        assert_eq!(v4.gateway, Some(Ipv4Addr::new(192, 168, 4, 1)));
+        assert_eq!(v4.dns_servers.as_slice(), &[Ipv4Addr::new(192, 168, 4, 1)]);
+
+        let missing_ip = StackConfig { dhcp: false, ..StackConfig::default() };
+        assert!(missing_ip.to_embassy().is_err());
+    }
+}
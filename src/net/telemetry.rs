@@ -0,0 +1,654 @@
+//! UDP 遥测发布
+//!
+//! 按固定频率对注册的指标做快照，与上一次快照做差分编码后以 UDP 组播发出，
+//! 主机侧仪表板加入组播组即可接收网段内全部设备的数据。
+//!
+//! # 报文格式 (版本 1，多字节整数均为小端)
+//!
+//! | 偏移 | 长度 | 字段 |
+//! |------|------|------|
+//! | 0 | 2 | 魔数 `"RT"` |
+//! | 2 | 1 | 版本 (`1`) |
+//! | 3 | 1 | 类型: `0` 差分帧、`1` 关键帧、`2` 模式帧 |
+//! | 4 | 6 | 设备 MAC |
+//! | 10 | 4 | 序号 (每个报文加 1，含模式帧) |
+//! | 14 | 4 | 时间戳 (启动后毫秒，回绕) |
+//! | 18 | 2 | 条目数 |
+//! | 20 | .. | 条目 |
+//!
+//! 条目使用 LEB128 变长整数 (`varint`)，有符号值先做 zigzag 映射:
+//! - 关键帧: 按指标序号依次为 `zigzag(值)`，条目数等于指标总数
+//! - 差分帧: 仅包含变化的指标，每项为 `varint(序号间隔)` + `zigzag(新值 - 旧值)`；
+//!   序号间隔是与上一条目序号之差 (首项为序号本身)
+//! - 模式帧: 每项为 `varint(名称长度)` + UTF-8 名称，按指标序号排列
+//!
+//! 每 `keyframe_every` 个快照发送一次 "模式帧 + 关键帧"。主机发现序号不连续
+//! 或尚未收到关键帧时，应丢弃差分帧直到下一个关键帧。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::telemetry::{metrics, Metric, Telemetry, TelemetryConfig};
+//!
+//! fn rx_frames() -> i64 { RX_FRAMES.load(Ordering::Relaxed) as i64 }
+//!
+//! static METRICS: &[Metric] = &[
+//!     metrics::UPTIME,
+//!     metrics::HEAP_USED,
+//!     metrics::PSRAM_USED,
+//!     Metric::new("rx_frames", rx_frames),
+//! ];
+//! static TELEMETRY: Telemetry = Telemetry::new(METRICS, TelemetryConfig::new());
+//!
+//! TELEMETRY.run(stack, mac).await;
+//! ```
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use embassy_time::{Duration, Instant, Ticker};
+
+use crate::util::log::*;
+
+/// 默认组播组
+pub const TELEMETRY_GROUP: [u8; 4] = [239, 255, 77, 77];
+
+/// 默认端口
+pub const TELEMETRY_PORT: u16 = 7365;
+
+/// 报文魔数
+pub const TELEMETRY_MAGIC: [u8; 2] = *b"RT";
+
+/// 协议版本
+pub const PROTOCOL_VERSION: u8 = 1;
+
+/// 报文头长度
+pub const HEADER_LEN: usize = 20;
+
+/// 最多指标数量
+pub const MAX_METRICS: usize = 64;
+
+/// 最大报文长度 (关键帧最坏情况 20 + 64 × 10 字节)
+pub const MAX_PACKET_LEN: usize = 672;
+
+// ===== 错误类型 =====
+
+/// 遥测错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TelemetryError {
+    /// 输出缓冲区不足
+    BufferTooSmall,
+    /// 报文格式错误
+    Malformed,
+    /// 魔数或版本不匹配
+    UnsupportedVersion,
+    /// 差分帧之前没有关键帧 (或序号不连续)
+    OutOfSync,
+    /// 指标数量超过上限
+    TooManyMetrics,
+}
+
+impl core::fmt::Display for TelemetryError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::BufferTooSmall => write!(f, "Buffer too small"),
+            Self::Malformed => write!(f, "Malformed packet"),
+            Self::UnsupportedVersion => write!(f, "Unsupported version"),
+            Self::OutOfSync => write!(f, "Out of sync"),
+            Self::TooManyMetrics => write!(f, "Too many metrics"),
+        }
+    }
+}
+
+// ===== 指标 =====
+
+/// 指标
+#[derive(Clone, Copy)]
+pub struct Metric {
+    /// 名称 (出现在模式帧中)
+    pub name: &'static str,
+    /// 读取当前值
+    pub read: fn() -> i64,
+}
+
+impl Metric {
+    /// 创建指标
+    pub const fn new(name: &'static str, read: fn() -> i64) -> Self {
+        Self { name, read }
+    }
+}
+
+/// 内置指标
+pub mod metrics {
+    use super::Metric;
+
+    /// 运行时间 (秒)
+    pub const UPTIME: Metric = Metric::new("uptime_s", || embassy_time::Instant::now().as_secs() as i64);
+
+    /// 堆已用字节数
+    pub const HEAP_USED: Metric = Metric::new("heap_used", || esp_alloc::HEAP.used() as i64);
+
+    /// PSRAM 已用字节数
+    pub const PSRAM_USED: Metric = Metric::new("psram_used", || crate::mem::psram::stats().used as i64);
+
+    /// 高优先级采样任务累计采样次数
+    pub const SENSOR_SAMPLES: Metric =
+        Metric::new("sensor_samples", || crate::tasks::critical::get_sample_count() as i64);
+
+    /// DNS 缓存命中率 (百分比)
+    pub const DNS_HIT_RATE: Metric = Metric::new("dns_hit_rate", || crate::net::dns::stats().hit_rate_percent() as i64);
+
+    /// DNS 缓存未命中次数
+    pub const DNS_MISSES: Metric = Metric::new("dns_misses", || crate::net::dns::stats().misses as i64);
+}
+
+// ===== 报文类型 =====
+
+/// 报文类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketKind {
+    /// 差分帧
+    Delta,
+    /// 关键帧
+    Keyframe,
+    /// 模式帧 (指标名称)
+    Schema,
+}
+
+impl PacketKind {
+    const fn code(&self) -> u8 {
+        match self {
+            Self::Delta => 0,
+            Self::Keyframe => 1,
+            Self::Schema => 2,
+        }
+    }
+
+    const fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(Self::Delta),
+            1 => Some(Self::Keyframe),
+            2 => Some(Self::Schema),
+            _ => None,
+        }
+    }
+}
+
+/// 报文头
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header {
+    /// 类型
+    pub kind: PacketKind,
+    /// 设备 MAC
+    pub device: [u8; 6],
+    /// 序号
+    pub seq: u32,
+    /// 时间戳 (毫秒)
+    pub timestamp_ms: u32,
+    /// 条目数
+    pub count: u16,
+}
+
+impl Header {
+    fn write(&self, out: &mut [u8]) -> Result<(), TelemetryError> {
+        let out = out.get_mut(..HEADER_LEN).ok_or(TelemetryError::BufferTooSmall)?;
+        out[0..2].copy_from_slice(&TELEMETRY_MAGIC);
+        out[2] = PROTOCOL_VERSION;
+        out[3] = self.kind.code();
+        out[4..10].copy_from_slice(&self.device);
+        out[10..14].copy_from_slice(&self.seq.to_le_bytes());
+        out[14..18].copy_from_slice(&self.timestamp_ms.to_le_bytes());
+        out[18..20].copy_from_slice(&self.count.to_le_bytes());
+        Ok(())
+    }
+
+    /// 解析报文头
+    pub fn parse(packet: &[u8]) -> Result<Self, TelemetryError> {
+        let h = packet.get(..HEADER_LEN).ok_or(TelemetryError::Malformed)?;
+        if h[0..2] != TELEMETRY_MAGIC || h[2] != PROTOCOL_VERSION {
+            return Err(TelemetryError::UnsupportedVersion);
+        }
+        let mut device = [0; 6];
+        device.copy_from_slice(&h[4..10]);
+        Ok(Self {
+            kind: PacketKind::from_code(h[3]).ok_or(TelemetryError::Malformed)?,
+            device,
+            seq: u32::from_le_bytes([h[10], h[11], h[12], h[13]]),
+            timestamp_ms: u32::from_le_bytes([h[14], h[15], h[16], h[17]]),
+            count: u16::from_le_bytes([h[18], h[19]]),
+        })
+    }
+}
+
+// ===== 变长整数 =====
+
+struct Writer<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl Writer<'_> {
+    fn varint(&mut self, mut value: u64) -> Result<(), TelemetryError> {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            let slot = self.buf.get_mut(self.pos).ok_or(TelemetryError::BufferTooSmall)?;
+            *slot = if value == 0 { byte } else { byte | 0x80 };
+            self.pos += 1;
+            if value == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    fn signed(&mut self, value: i64) -> Result<(), TelemetryError> {
+        self.varint(((value << 1) ^ (value >> 63)) as u64)
+    }
+
+    fn bytes(&mut self, data: &[u8]) -> Result<(), TelemetryError> {
+        let end = self.pos + data.len();
+        self.buf.get_mut(self.pos..end).ok_or(TelemetryError::BufferTooSmall)?.copy_from_slice(data);
+        self.pos = end;
+        Ok(())
+    }
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn varint(&mut self) -> Result<u64, TelemetryError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self.buf.get(self.pos).ok_or(TelemetryError::Malformed)?;
+            self.pos += 1;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(TelemetryError::Malformed)
+    }
+
+    fn signed(&mut self) -> Result<i64, TelemetryError> {
+        let raw = self.varint()?;
+        Ok((raw >> 1) as i64 ^ -((raw & 1) as i64))
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], TelemetryError> {
+        let data = self.buf.get(self.pos..self.pos + len).ok_or(TelemetryError::Malformed)?;
+        self.pos += len;
+        Ok(data)
+    }
+}
+
+// ===== 编码器 =====
+
+/// 差分编码器 (设备侧)
+pub struct Encoder<const N: usize> {
+    device: [u8; 6],
+    previous: [i64; N],
+    seq: u32,
+    keyed: bool,
+}
+
+impl<const N: usize> Encoder<N> {
+    /// 创建
+    pub const fn new(device: [u8; 6]) -> Self {
+        Self { device, previous: [0; N], seq: 0, keyed: false }
+    }
+
+    /// 下一个报文序号
+    pub fn seq(&self) -> u32 {
+        self.seq
+    }
+
+    fn header(&mut self, kind: PacketKind, timestamp_ms: u32, count: usize, out: &mut [u8]) -> Result<(), TelemetryError> {
+        let header = Header { kind, device: self.device, seq: self.seq, timestamp_ms, count: count as u16 };
+        header.write(out)?;
+        self.seq = self.seq.wrapping_add(1);
+        Ok(())
+    }
+
+    /// 编码模式帧
+    pub fn schema(&mut self, metrics: &[Metric], timestamp_ms: u32, out: &mut [u8]) -> Result<usize, TelemetryError> {
+        let mut w = Writer { buf: out, pos: HEADER_LEN };
+        for metric in metrics.iter().take(N) {
+            w.varint(metric.name.len() as u64)?;
+            w.bytes(metric.name.as_bytes())?;
+        }
+        let len = w.pos;
+        self.header(PacketKind::Schema, timestamp_ms, metrics.len().min(N), out)?;
+        Ok(len)
+    }
+
+    /// 编码快照
+    ///
+    /// `keyframe` 为 `false` 且已有基准时只写入变化的指标。
+    pub fn snapshot(
+        &mut self,
+        values: &[i64],
+        keyframe: bool,
+        timestamp_ms: u32,
+        out: &mut [u8],
+    ) -> Result<usize, TelemetryError> {
+        if values.len() > N {
+            return Err(TelemetryError::TooManyMetrics);
+        }
+        let keyframe = keyframe || !self.keyed;
+        let mut w = Writer { buf: out, pos: HEADER_LEN };
+        let mut count = 0;
+        let mut last = 0;
+        for (index, (&value, previous)) in values.iter().zip(self.previous.iter()).enumerate() {
+            if keyframe {
+                w.signed(value)?;
+            } else if value != *previous {
+                w.varint((index - last) as u64)?;
+                w.signed(value.wrapping_sub(*previous))?;
+                last = index;
+            } else {
+                continue;
+            }
+            count += 1;
+        }
+        let len = w.pos;
+        let kind = if keyframe { PacketKind::Keyframe } else { PacketKind::Delta };
+        self.header(kind, timestamp_ms, count, out)?;
+        self.previous[..values.len()].copy_from_slice(values);
+        self.keyed = true;
+        Ok(len)
+    }
+}
+
+// ===== 解码器 =====
+
+/// 差分解码器 (主机侧参考实现，也用于测试)
+pub struct Decoder<const N: usize> {
+    values: [i64; N],
+    count: usize,
+    next_seq: Option<u32>,
+    synced: bool,
+}
+
+impl<const N: usize> Decoder<N> {
+    /// 创建
+    pub const fn new() -> Self {
+        Self { values: [0; N], count: 0, next_seq: None, synced: false }
+    }
+
+    /// 当前值 (按指标序号)
+    pub fn values(&self) -> &[i64] {
+        &self.values[..self.count]
+    }
+
+    /// 应用一个关键帧或差分帧
+    ///
+    /// 模式帧请用 `schema_names` 解析；此处直接跳过。
+    pub fn apply(&mut self, packet: &[u8]) -> Result<Header, TelemetryError> {
+        let header = Header::parse(packet)?;
+        let in_order = self.next_seq == Some(header.seq);
+        self.next_seq = Some(header.seq.wrapping_add(1));
+        if !in_order {
+            self.synced = false;
+        }
+
+        let mut r = Reader { buf: packet, pos: HEADER_LEN };
+        match header.kind {
+            PacketKind::Schema => {}
+            PacketKind::Keyframe => {
+                let count = header.count as usize;
+                if count > N {
+                    return Err(TelemetryError::TooManyMetrics);
+                }
+                for slot in self.values[..count].iter_mut() {
+                    *slot = r.signed()?;
+                }
+                self.count = count;
+                self.synced = true;
+            }
+            PacketKind::Delta => {
+                if !self.synced {
+                    return Err(TelemetryError::OutOfSync);
+                }
+                let mut index = 0;
+                for _ in 0..header.count {
+                    index += r.varint()? as usize;
+                    let delta = r.signed()?;
+                    let slot = self.values[..self.count].get_mut(index).ok_or(TelemetryError::Malformed)?;
+                    *slot = slot.wrapping_add(delta);
+                }
+            }
+        }
+        Ok(header)
+    }
+}
+
+impl<const N: usize> Default for Decoder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 解析模式帧中的指标名称
+pub fn schema_names(packet: &[u8]) -> Result<impl Iterator<Item = Result<&str, TelemetryError>>, TelemetryError> {
+    let header = Header::parse(packet)?;
+    if header.kind != PacketKind::Schema {
+        return Err(TelemetryError::Malformed);
+    }
+    let mut r = Reader { buf: packet, pos: HEADER_LEN };
+    Ok((0..header.count).map(move |_| {
+        let len = r.varint()? as usize;
+        core::str::from_utf8(r.bytes(len)?).map_err(|_| TelemetryError::Malformed)
+    }))
+}
+
+// ===== 发布器 =====
+
+/// 遥测配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TelemetryConfig {
+    /// 快照间隔
+    pub interval: Duration,
+    /// 每多少个快照发送一次模式帧 + 关键帧
+    pub keyframe_every: u32,
+    /// 目标地址 (组播组或单播主机)
+    pub group: [u8; 4],
+    /// 目标端口
+    pub port: u16,
+}
+
+impl TelemetryConfig {
+    /// 默认配置: 每秒一次，每 30 个快照一个关键帧
+    pub const fn new() -> Self {
+        Self { interval: Duration::from_secs(1), keyframe_every: 30, group: TELEMETRY_GROUP, port: TELEMETRY_PORT }
+    }
+
+    /// 设置快照间隔
+    pub const fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// 设置关键帧间隔
+    pub const fn with_keyframe_every(mut self, snapshots: u32) -> Self {
+        self.keyframe_every = snapshots;
+        self
+    }
+
+    /// 设置目标地址与端口
+    pub const fn with_destination(mut self, group: [u8; 4], port: u16) -> Self {
+        self.group = group;
+        self.port = port;
+        self
+    }
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 发布统计快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TelemetryStats {
+    /// 已发送报文
+    pub packets: u32,
+    /// 已发送字节
+    pub bytes: u32,
+    /// 发送失败
+    pub errors: u32,
+}
+
+/// 遥测发布器
+pub struct Telemetry {
+    metrics: &'static [Metric],
+    config: TelemetryConfig,
+    packets: AtomicU32,
+    bytes: AtomicU32,
+    errors: AtomicU32,
+}
+
+impl Telemetry {
+    /// 创建 (超过 `MAX_METRICS` 的指标被忽略)
+    pub const fn new(metrics: &'static [Metric], config: TelemetryConfig) -> Self {
+        Self { metrics, config, packets: AtomicU32::new(0), bytes: AtomicU32::new(0), errors: AtomicU32::new(0) }
+    }
+
+    /// 配置
+    pub fn config(&self) -> &TelemetryConfig {
+        &self.config
+    }
+
+    /// 统计快照
+    pub fn stats(&self) -> TelemetryStats {
+        TelemetryStats {
+            packets: self.packets.load(Ordering::Relaxed),
+            bytes: self.bytes.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 运行发布循环 (不返回)
+    ///
+    /// `mac` 写入报文头用于区分设备。
+    pub async fn run(&self, stack: embassy_net::Stack<'_>, mac: [u8; 6]) -> ! {
+        use embassy_net::udp::{PacketMetadata, UdpSocket};
+        use embassy_net::{IpEndpoint, Ipv4Address};
+
+        let mut rx_meta = [PacketMetadata::EMPTY; 1];
+        let mut tx_meta = [PacketMetadata::EMPTY; 4];
+        let mut rx = [0u8; 16];
+        let mut tx = [0u8; MAX_PACKET_LEN * 2];
+        let mut socket = UdpSocket::new(stack, &mut rx_meta, &mut rx, &mut tx_meta, &mut tx);
+        if socket.bind(0).is_err() {
+            log_warn!("Telemetry: no local port");
+            core::future::pending::<()>().await;
+        }
+
+        let [a, b, c, d] = self.config.group;
+        let endpoint = IpEndpoint::new(Ipv4Address::new(a, b, c, d).into(), self.config.port);
+        let metrics = &self.metrics[..self.metrics.len().min(MAX_METRICS)];
+        let mut encoder = Encoder::<MAX_METRICS>::new(mac);
+        let mut values = [0i64; MAX_METRICS];
+        let mut packet = [0u8; MAX_PACKET_LEN];
+        let mut ticker = Ticker::every(self.config.interval);
+        let mut snapshots = 0u32;
+
+        log_info!(
+            "Telemetry: {} metrics to {}:{}",
+            metrics.len(),
+            crate::util::fmt::Ipv4(self.config.group),
+            self.config.port
+        );
+        loop {
+            ticker.next().await;
+            let now = Instant::now().as_millis() as u32;
+            for (value, metric) in values.iter_mut().zip(metrics) {
+                *value = (metric.read)();
+            }
+
+            let keyframe = snapshots == 0;
+            snapshots = (snapshots + 1) % self.config.keyframe_every.max(1);
+            if keyframe {
+                if let Ok(len) = encoder.schema(metrics, now, &mut packet) {
+                    self.send(&mut socket, &packet[..len], endpoint).await;
+                }
+            }
+            match encoder.snapshot(&values[..metrics.len()], keyframe, now, &mut packet) {
+                Ok(len) => self.send(&mut socket, &packet[..len], endpoint).await,
+                Err(_) => {
+                    self.errors.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+
+    async fn send(&self, socket: &mut embassy_net::udp::UdpSocket<'_>, packet: &[u8], endpoint: embassy_net::IpEndpoint) {
+        if socket.send_to(packet, endpoint).await.is_ok() {
+            self.packets.fetch_add(1, Ordering::Relaxed);
+            self.bytes.fetch_add(packet.len() as u32, Ordering::Relaxed);
+        } else {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEVICE: [u8; 6] = [0x7c, 0xdf, 0xa1, 0, 0x12, 0x34];
+
+    #[test]
+    fn test_delta_roundtrip() {
+        let mut encoder = Encoder::<4>::new(DEVICE);
+        let mut decoder = Decoder::<4>::new();
+        let mut packet = [0u8; 128];
+
+        // 首帧总是关键帧
+        let len = encoder.snapshot(&[100, -5, 1 << 40, 0], false, 10, &mut packet).unwrap();
+        let header = decoder.apply(&packet[..len]).unwrap();
+        assert_eq!((header.kind, header.device, header.count), (PacketKind::Keyframe, DEVICE, 4));
+        assert_eq!(decoder.values(), &[100, -5, 1 << 40, 0]);
+
+        // 只有变化的指标进入差分帧
+        let len = encoder.snapshot(&[101, -5, 1 << 40, -300], false, 20, &mut packet).unwrap();
+        assert_eq!(len, HEADER_LEN + 2 + 3);
+        decoder.apply(&packet[..len]).unwrap();
+        assert_eq!(decoder.values(), &[101, -5, 1 << 40, -300]);
+
+        let len = encoder.snapshot(&[101, -5, 1 << 40, -300], false, 30, &mut packet).unwrap();
+        assert_eq!((len, Header::parse(&packet).unwrap().count), (HEADER_LEN, 0));
+    }
+
+    #[test]
+    fn test_resync_and_schema() {
+        let metrics = [Metric::new("uptime_s", || 0), Metric::new("rx", || 0)];
+        let mut encoder = Encoder::<4>::new(DEVICE);
+        let mut decoder = Decoder::<4>::new();
+        let mut packet = [0u8; 128];
+
+        let len = encoder.schema(&metrics, 0, &mut packet).unwrap();
+        let names: heapless::Vec<&str, 4> = schema_names(&packet[..len]).unwrap().map(|n| n.unwrap()).collect();
+        assert_eq!(names.as_slice(), &["uptime_s", "rx"]);
+        drop(names);
+        decoder.apply(&packet[..len]).unwrap();
+
+        let len = encoder.snapshot(&[1, 2], false, 0, &mut packet).unwrap();
+        decoder.apply(&packet[..len]).unwrap();
+
+        // 丢失一个差分帧后，后续差分帧被拒绝，直到关键帧
+        encoder.snapshot(&[2, 2], false, 0, &mut packet).unwrap();
+        let len = encoder.snapshot(&[3, 2], false, 0, &mut packet).unwrap();
+        assert_eq!(decoder.apply(&packet[..len]), Err(TelemetryError::OutOfSync));
+        let len = encoder.snapshot(&[4, 5], true, 0, &mut packet).unwrap();
+        decoder.apply(&packet[..len]).unwrap();
+        assert_eq!(decoder.values(), &[4, 5]);
+
+        assert_eq!(Header::parse(b"XX\x01\x00"), Err(TelemetryError::Malformed));
+        packet[2] = 9;
+        assert_eq!(Header::parse(&packet), Err(TelemetryError::UnsupportedVersion));
+    }
+}
@@ -0,0 +1,437 @@
+//! 硬件在环测试服务
+//!
+//! 内置回显服务，两台设备 (或设备 + 主机脚本) 无需外部基础设施即可对
+//! 网络/BLE 协议栈做自动化集成测试:
+//! - TCP 回显服务器 (RFC 862，默认端口 7，多连接)
+//! - UDP 回显服务器
+//! - TCP 回显客户端检查 (`tcp_echo_check`，对端为另一台设备或主机)
+//! - BLE 回显 GATT 服务 (写入特征的数据原样以通知返回，需 `ble` feature)
+//!
+//! 主机侧可直接用 `nc <ip> 7` / `nc -u <ip> 7` 验证。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::testing::{EchoServer, EchoCheck, tcp_echo_check};
+//!
+//! static ECHO: EchoServer = EchoServer::new();
+//!
+//! // 设备 A
+//! join(ECHO.tcp::<2>(stack, 7), ECHO.udp(stack, 7)).await;
+//!
+//! // 设备 B
+//! let report = tcp_echo_check(stack, peer_endpoint, &EchoCheck::default()).await?;
+//! assert_eq!(report.mismatches, 0);
+//! ```
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use embassy_time::{Duration, Instant};
+use embedded_io_async::{Read, Write};
+
+use crate::util::log::*;
+
+/// 默认回显端口 (RFC 862)
+pub const ECHO_PORT: u16 = 7;
+
+/// 单次收发缓冲区大小
+pub const ECHO_BUF_LEN: usize = 1024;
+
+// ===== 流回显 =====
+
+/// 将读到的数据原样写回，直到对端关闭
+///
+/// # 返回
+/// 回显的总字节数
+pub async fn echo_stream<T: Read + Write>(io: &mut T, buf: &mut [u8]) -> Result<usize, T::Error> {
+    let mut total = 0;
+    loop {
+        let n = io.read(buf).await?;
+        if n == 0 {
+            io.flush().await?;
+            return Ok(total);
+        }
+        io.write_all(&buf[..n]).await?;
+        io.flush().await?;
+        total += n;
+    }
+}
+
+/// 回显统计快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EchoStats {
+    /// 已接受的 TCP 连接
+    pub tcp_connections: u32,
+    /// TCP 回显字节数
+    pub tcp_bytes: u32,
+    /// UDP 回显数据报数
+    pub udp_datagrams: u32,
+    /// UDP 回显字节数
+    pub udp_bytes: u32,
+    /// 错误次数
+    pub errors: u32,
+}
+
+/// 回显服务器
+pub struct EchoServer {
+    tcp_connections: AtomicU32,
+    tcp_bytes: AtomicU32,
+    udp_datagrams: AtomicU32,
+    udp_bytes: AtomicU32,
+    errors: AtomicU32,
+}
+
+impl EchoServer {
+    /// 创建
+    pub const fn new() -> Self {
+        Self {
+            tcp_connections: AtomicU32::new(0),
+            tcp_bytes: AtomicU32::new(0),
+            udp_datagrams: AtomicU32::new(0),
+            udp_bytes: AtomicU32::new(0),
+            errors: AtomicU32::new(0),
+        }
+    }
+
+    /// 统计快照
+    pub fn stats(&self) -> EchoStats {
+        EchoStats {
+            tcp_connections: self.tcp_connections.load(Ordering::Relaxed),
+            tcp_bytes: self.tcp_bytes.load(Ordering::Relaxed),
+            udp_datagrams: self.udp_datagrams.load(Ordering::Relaxed),
+            udp_bytes: self.udp_bytes.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Default for EchoServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ===== TCP/UDP (network) =====
+
+#[cfg(feature = "network")]
+impl EchoServer {
+    /// 运行 TCP 回显服务 (`N` 个并发连接)
+    pub async fn tcp<const N: usize>(&self, stack: embassy_net::Stack<'_>, port: u16) -> ! {
+        async fn accept_loop(server: &EchoServer, stack: embassy_net::Stack<'_>, port: u16) -> ! {
+            let mut rx = [0u8; ECHO_BUF_LEN];
+            let mut tx = [0u8; ECHO_BUF_LEN];
+            let mut buf = [0u8; ECHO_BUF_LEN];
+            loop {
+                let mut socket = embassy_net::tcp::TcpSocket::new(stack, &mut rx, &mut tx);
+                socket.set_timeout(Some(Duration::from_secs(30)));
+                if socket.accept(port).await.is_err() {
+                    continue;
+                }
+                server.tcp_connections.fetch_add(1, Ordering::Relaxed);
+                match echo_stream(&mut socket, &mut buf).await {
+                    Ok(n) => {
+                        server.tcp_bytes.fetch_add(n as u32, Ordering::Relaxed);
+                    }
+                    Err(e) => {
+                        server.errors.fetch_add(1, Ordering::Relaxed);
+                        log_debug!("TCP echo closed: {:?}", e);
+                    }
+                }
+                socket.close();
+                let _ = socket.flush().await;
+            }
+        }
+
+        log_info!("TCP echo listening on port {} ({} connections)", port, N);
+        let loops: [_; N] = core::array::from_fn(|_| accept_loop(self, stack, port));
+        embassy_futures::join::join_array(loops).await;
+        unreachable!()
+    }
+
+    /// 运行 UDP 回显服务
+    pub async fn udp(&self, stack: embassy_net::Stack<'_>, port: u16) -> ! {
+        use embassy_net::udp::{PacketMetadata, UdpSocket};
+
+        let mut rx_meta = [PacketMetadata::EMPTY; 4];
+        let mut tx_meta = [PacketMetadata::EMPTY; 4];
+        let mut rx = [0u8; ECHO_BUF_LEN * 2];
+        let mut tx = [0u8; ECHO_BUF_LEN * 2];
+        let mut buf = [0u8; ECHO_BUF_LEN];
+        let mut socket = UdpSocket::new(stack, &mut rx_meta, &mut rx, &mut tx_meta, &mut tx);
+        if socket.bind(port).is_err() {
+            log_warn!("UDP echo: port {} unavailable", port);
+            core::future::pending::<()>().await;
+        }
+
+        log_info!("UDP echo listening on port {}", port);
+        loop {
+            let result = match socket.recv_from(&mut buf).await {
+                Ok((n, meta)) => socket.send_to(&buf[..n], meta).await.map(|_| n).map_err(|_| ()),
+                Err(_) => Err(()),
+            };
+            match result {
+                Ok(n) => {
+                    self.udp_datagrams.fetch_add(1, Ordering::Relaxed);
+                    self.udp_bytes.fetch_add(n as u32, Ordering::Relaxed);
+                }
+                Err(()) => {
+                    self.errors.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+}
+
+// ===== 回显检查 =====
+
+/// 回显检查参数
+#[derive(Debug, Clone, Copy)]
+pub struct EchoCheck {
+    /// 往返次数
+    pub rounds: u32,
+    /// 每次负载长度
+    pub payload_len: usize,
+    /// 单次往返超时
+    pub timeout: Duration,
+}
+
+impl Default for EchoCheck {
+    fn default() -> Self {
+        Self { rounds: 100, payload_len: 256, timeout: Duration::from_secs(2) }
+    }
+}
+
+impl EchoCheck {
+    /// 设置往返次数
+    pub fn with_rounds(mut self, rounds: u32) -> Self {
+        self.rounds = rounds;
+        self
+    }
+
+    /// 设置负载长度 (不超过 `ECHO_BUF_LEN`)
+    pub fn with_payload_len(mut self, len: usize) -> Self {
+        self.payload_len = len.clamp(1, ECHO_BUF_LEN);
+        self
+    }
+
+    /// 设置单次往返超时
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// 回显检查结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EchoReport {
+    /// 完成的往返次数
+    pub rounds: u32,
+    /// 内容不一致的往返次数
+    pub mismatches: u32,
+    /// 最短往返时间 (μs)
+    pub min_rtt_us: u32,
+    /// 平均往返时间 (μs)
+    pub avg_rtt_us: u32,
+    /// 最长往返时间 (μs)
+    pub max_rtt_us: u32,
+}
+
+/// 第 `round` 轮的测试数据
+fn fill_pattern(buf: &mut [u8], round: u32) {
+    for (i, b) in buf.iter_mut().enumerate() {
+        *b = (i as u32).wrapping_mul(7).wrapping_add(round) as u8;
+    }
+}
+
+/// 回显检查错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EchoError {
+    /// 连接失败
+    Connect,
+    /// 读写错误
+    Io,
+    /// 对端提前关闭
+    Closed,
+    /// 往返超时
+    Timeout,
+}
+
+impl core::fmt::Display for EchoError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Connect => write!(f, "Echo peer unreachable"),
+            Self::Io => write!(f, "Echo I/O error"),
+            Self::Closed => write!(f, "Echo peer closed connection"),
+            Self::Timeout => write!(f, "Echo round trip timed out"),
+        }
+    }
+}
+
+/// 对回显对端执行往返检查
+///
+/// 任一往返失败 (超时、连接断开) 时返回错误；内容不一致计入 `mismatches`。
+pub async fn echo_check<T: Read + Write>(io: &mut T, check: &EchoCheck) -> Result<EchoReport, EchoError> {
+    use embedded_io_async::ReadExactError;
+
+    let len = check.payload_len.clamp(1, ECHO_BUF_LEN);
+    let mut sent = [0u8; ECHO_BUF_LEN];
+    let mut received = [0u8; ECHO_BUF_LEN];
+    let mut report = EchoReport { min_rtt_us: u32::MAX, ..Default::default() };
+    let mut total_us = 0u64;
+
+    for round in 0..check.rounds {
+        fill_pattern(&mut sent[..len], round);
+        let start = Instant::now();
+        let exchange = async {
+            io.write_all(&sent[..len]).await.map_err(|_| EchoError::Io)?;
+            io.flush().await.map_err(|_| EchoError::Io)?;
+            io.read_exact(&mut received[..len]).await.map_err(|e| match e {
+                ReadExactError::UnexpectedEof => EchoError::Closed,
+                ReadExactError::Other(_) => EchoError::Io,
+            })
+        };
+        embassy_time::with_timeout(check.timeout, exchange)
+            .await
+            .map_err(|_| EchoError::Timeout)??;
+
+        let rtt = start.elapsed().as_micros() as u32;
+        total_us += rtt as u64;
+        report.min_rtt_us = report.min_rtt_us.min(rtt);
+        report.max_rtt_us = report.max_rtt_us.max(rtt);
+        if sent[..len] != received[..len] {
+            report.mismatches += 1;
+        }
+        report.rounds += 1;
+    }
+
+    report.avg_rtt_us = total_us.checked_div(report.rounds as u64).unwrap_or(0) as u32;
+    if report.rounds == 0 {
+        report.min_rtt_us = 0;
+    }
+    Ok(report)
+}
+
+/// 连接到 TCP 回显对端并执行往返检查
+#[cfg(feature = "network")]
+pub async fn tcp_echo_check(
+    stack: embassy_net::Stack<'_>,
+    remote: embassy_net::IpEndpoint,
+    check: &EchoCheck,
+) -> Result<EchoReport, EchoError> {
+    let mut rx = [0u8; ECHO_BUF_LEN];
+    let mut tx = [0u8; ECHO_BUF_LEN];
+    let mut socket = embassy_net::tcp::TcpSocket::new(stack, &mut rx, &mut tx);
+    socket.set_timeout(Some(check.timeout));
+    socket.connect(remote).await.map_err(|_| EchoError::Connect)?;
+    let result = echo_check(&mut socket, check).await;
+    socket.close();
+    let _ = socket.flush().await;
+    result
+}
+
+// ===== BLE 回显 (trouble-host) =====
+
+/// BLE 回显负载上限 (MTU 247 - 3 字节 ATT 头)
+#[cfg(feature = "ble")]
+pub const BLE_ECHO_LEN: usize = 244;
+
+/// BLE 回显 GATT 服务
+///
+/// 写入 `data` 特征 (有/无响应均可) 的内容以通知原样返回，也可读回最后一次写入的值。
+/// 需加入应用的 `#[gatt_server]`:
+///
+/// ```rust,ignore
+/// #[gatt_server]
+/// struct Server {
+///     echo: EchoService,
+/// }
+/// ```
+#[cfg(feature = "ble")]
+#[trouble_host::prelude::gatt_service(uuid = "6e400001-b5a3-f393-e0a9-e50e24dcca9f")]
+pub struct EchoService {
+    /// 回显数据
+    #[characteristic(uuid = "6e400002-b5a3-f393-e0a9-e50e24dcca9f", read, write, write_without_response, notify)]
+    pub data: heapless::Vec<u8, BLE_ECHO_LEN>,
+}
+
+/// 处理一个连接上的 GATT 事件，回显写入 `service.data` 的数据 (连接断开时返回)
+///
+/// # 返回
+/// 回显的数据包数
+#[cfg(feature = "ble")]
+pub async fn ble_echo<P: trouble_host::prelude::PacketPool>(
+    service: &EchoService,
+    conn: &trouble_host::prelude::GattConnection<'_, '_, P>,
+) -> u32 {
+    use trouble_host::prelude::{GattConnectionEvent, GattEvent};
+
+    let data = service.data;
+    let mut echoed = 0;
+    loop {
+        let event = match conn.next().await {
+            GattConnectionEvent::Disconnected { reason } => {
+                log_debug!("BLE echo disconnected: {:?}", reason);
+                return echoed;
+            }
+            GattConnectionEvent::Gatt { event } => event,
+            _ => continue,
+        };
+
+        let mut payload: heapless::Vec<u8, BLE_ECHO_LEN> = heapless::Vec::new();
+        if let GattEvent::Write(ev) = &event {
+            if ev.handle() == data.handle {
+                let _ = payload.extend_from_slice(&ev.data()[..ev.data().len().min(BLE_ECHO_LEN)]);
+            }
+        }
+
+        // 先应答写请求，再发送通知
+        if let Ok(reply) = event.accept() {
+            reply.send().await;
+        }
+        if !payload.is_empty() {
+            if data.notify(conn, &payload).await.is_err() {
+                return echoed;
+            }
+            echoed += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Loopback {
+        pending: heapless::Vec<u8, 64>,
+    }
+
+    impl embedded_io_async::ErrorType for Loopback {
+        type Error = core::convert::Infallible;
+    }
+
+    impl Read for Loopback {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            let n = buf.len().min(self.pending.len()).min(5);
+            buf[..n].copy_from_slice(&self.pending[..n]);
+            let rest: heapless::Vec<u8, 64> = self.pending[n..].iter().copied().collect();
+            self.pending = rest;
+            Ok(n)
+        }
+    }
+
+    impl Write for Loopback {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            let _ = self.pending.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+    }
+
+    #[test]
+    fn test_echo_check_loopback() {
+        let mut io = Loopback { pending: heapless::Vec::new() };
+        let check = EchoCheck::default().with_rounds(3).with_payload_len(32);
+        let report = embassy_futures::block_on(echo_check(&mut io, &check)).unwrap();
+        assert_eq!(report.rounds, 3);
+        assert_eq!(report.mismatches, 0);
+    }
+}
@@ -0,0 +1,322 @@
+//! 服务端身份: 证书链与 ECDSA P-256 私钥
+//!
+//! - 私钥以 `SecretKind::Key` (32 字节标量) 保存在凭据保管库中
+//! - 证书链可以是厂商 / 企业 CA 签发后预置到文件系统的 DER 文件 (多张证书直接拼接，叶证书在前)，
+//!   也可以在设备上由私钥生成自签名证书 (ECDSA 签名是确定性的，同一私钥每次生成的证书相同)
+
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey};
+use sha2::{Digest, Sha256};
+
+use super::TlsError;
+use crate::crypto::Entropy;
+use crate::sys::vault::{SecretKind, Vault, VaultError};
+
+/// 自签名证书最大长度 (通用名 64 字节时约 420 字节)
+pub const SELF_SIGNED_MAX_LEN: usize = 512;
+
+/// 自签名证书通用名最大长度
+pub const MAX_COMMON_NAME_LEN: usize = 64;
+
+/// DER 编码 ECDSA 签名最大长度
+pub(crate) const MAX_DER_SIGNATURE_LEN: usize = 72;
+
+/// TLS 服务端身份
+pub struct TlsIdentity<'a> {
+    chain: &'a [u8],
+    key: SigningKey,
+}
+
+impl<'a> TlsIdentity<'a> {
+    /// 由 DER 证书链与私钥创建
+    ///
+    /// `chain` 为一张或多张拼接的 DER 证书，叶证书在前。
+    pub fn new(chain: &'a [u8], key: SigningKey) -> Result<Self, TlsError> {
+        let mut rest = chain;
+        if rest.is_empty() {
+            return Err(TlsError::InvalidIdentity);
+        }
+        while !rest.is_empty() {
+            let len = der_element_len(rest).ok_or(TlsError::InvalidIdentity)?;
+            // TLS 证书条目长度为 u24，此处进一步限制为 u16 足以覆盖嵌入式场景
+            if rest[0] != 0x30 || len > u16::MAX as usize {
+                return Err(TlsError::InvalidIdentity);
+            }
+            rest = &rest[len..];
+        }
+        Ok(Self { chain, key })
+    }
+
+    /// 使用保管库中名为 `name` 的私钥
+    pub fn from_vault<const N: usize>(vault: &Vault<N>, name: &str, chain: &'a [u8]) -> Result<Self, TlsError> {
+        Self::new(chain, load_key(vault, name)?)
+    }
+
+    /// 签名私钥
+    pub fn signing_key(&self) -> &SigningKey {
+        &self.key
+    }
+
+    /// 逐张遍历证书 (DER)
+    pub(crate) fn certificates(&self) -> impl Iterator<Item = &'a [u8]> {
+        let mut rest = self.chain;
+        core::iter::from_fn(move || {
+            let len = der_element_len(rest)?;
+            let (cert, tail) = rest.split_at(len);
+            rest = tail;
+            Some(cert)
+        })
+    }
+}
+
+/// 生成新的 P-256 私钥
+pub fn generate_key(entropy: &mut impl Entropy) -> SigningKey {
+    let mut bytes = [0u8; 32];
+    loop {
+        entropy.fill(&mut bytes);
+        // 标量为 0 或不小于群阶时重新抽取 (概率约 2^-32)
+        if let Ok(key) = SigningKey::from_slice(&bytes) {
+            bytes.fill(0);
+            return key;
+        }
+    }
+}
+
+/// 从保管库读取私钥
+pub fn load_key<const N: usize>(vault: &Vault<N>, name: &str) -> Result<SigningKey, TlsError> {
+    vault
+        .with_secret(name, |kind, data| match kind {
+            SecretKind::Key => SigningKey::from_slice(data).map_err(|_| TlsError::InvalidIdentity),
+            _ => Err(TlsError::InvalidIdentity),
+        })
+        .map_err(|_| TlsError::InvalidIdentity)?
+}
+
+/// 确保保管库中存在名为 `name` 的 TLS 私钥，不存在时生成
+///
+/// 返回是否新生成了私钥 (调用方据此决定是否持久化保管库、重新生成证书)。
+pub fn provision_key<const N: usize>(
+    vault: &Vault<N>,
+    name: &str,
+    entropy: &mut impl Entropy,
+) -> Result<bool, VaultError> {
+    if vault.contains(name) {
+        return Ok(false);
+    }
+    let key = generate_key(entropy);
+    let mut bytes: [u8; 32] = key.to_bytes().into();
+    let result = vault.set_secret(name, SecretKind::Key, &bytes);
+    bytes.fill(0);
+    result.map(|_| true)
+}
+
+// ===== DER 编码 =====
+
+/// 解析 DER 元素总长度 (标签 + 长度 + 内容)
+fn der_element_len(buf: &[u8]) -> Option<usize> {
+    let first = *buf.get(1)?;
+    let (header, len) = match first {
+        0..=0x7f => (2, first as usize),
+        0x81 => (3, *buf.get(2)? as usize),
+        0x82 => (4, u16::from_be_bytes([*buf.get(2)?, *buf.get(3)?]) as usize),
+        0x83 => (5, u32::from_be_bytes([0, *buf.get(2)?, *buf.get(3)?, *buf.get(4)?]) as usize),
+        _ => return None,
+    };
+    let total = header + len;
+    (total <= buf.len()).then_some(total)
+}
+
+/// 长度字段字节数
+const fn der_len_size(len: usize) -> usize {
+    match len {
+        0..=0x7f => 1,
+        0x80..=0xff => 2,
+        _ => 3,
+    }
+}
+
+/// 元素总长度
+const fn tlv_len(content: usize) -> usize {
+    1 + der_len_size(content) + content
+}
+
+/// 顺序写入的 DER 缓冲区
+struct DerWriter<'o> {
+    out: &'o mut [u8],
+    pos: usize,
+}
+
+impl DerWriter<'_> {
+    fn bytes(&mut self, data: &[u8]) -> Result<(), TlsError> {
+        let end = self.pos + data.len();
+        self.out.get_mut(self.pos..end).ok_or(TlsError::BufferTooSmall)?.copy_from_slice(data);
+        self.pos = end;
+        Ok(())
+    }
+
+    fn header(&mut self, tag: u8, len: usize) -> Result<(), TlsError> {
+        match len {
+            0..=0x7f => self.bytes(&[tag, len as u8]),
+            0x80..=0xff => self.bytes(&[tag, 0x81, len as u8]),
+            _ => self.bytes(&[tag, 0x82, (len >> 8) as u8, len as u8]),
+        }
+    }
+
+    fn tlv(&mut self, tag: u8, content: &[u8]) -> Result<(), TlsError> {
+        self.header(tag, content.len())?;
+        self.bytes(content)
+    }
+}
+
+/// ecdsa-with-SHA256 AlgorithmIdentifier
+const ECDSA_SHA256_ALG: [u8; 12] = [0x30, 0x0a, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+
+/// SubjectPublicKeyInfo 前缀 (id-ecPublicKey, prime256v1, BIT STRING 头)
+const P256_SPKI_PREFIX: [u8; 26] = [
+    0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x08, 0x2a, 0x86,
+    0x48, 0xce, 0x3d, 0x03, 0x01, 0x07, 0x03, 0x42, 0x00,
+];
+
+/// 有效期: 2025-01-01 起，无明确到期时间 (RFC 5280 §4.1.2.5 的 99991231235959Z)
+const VALIDITY: &[u8] = b"\x30\x20\x17\x0d250101000000Z\x18\x0f99991231235959Z";
+
+/// Name: SEQUENCE { SET { SEQUENCE { id-at-commonName, UTF8String } } }
+fn write_name(w: &mut DerWriter<'_>, cn: &[u8]) -> Result<(), TlsError> {
+    let attr = 5 + tlv_len(cn.len());
+    w.header(0x30, tlv_len(tlv_len(attr)))?;
+    w.header(0x31, tlv_len(attr))?;
+    w.header(0x30, attr)?;
+    w.bytes(&[0x06, 0x03, 0x55, 0x04, 0x03])?;
+    w.tlv(0x0c, cn)
+}
+
+const fn name_len(cn: usize) -> usize {
+    tlv_len(tlv_len(tlv_len(5 + tlv_len(cn))))
+}
+
+/// 编码 DER INTEGER (无符号大端，去除前导零，必要时补 0x00)
+fn write_uint(w: &mut DerWriter<'_>, value: &[u8]) -> Result<(), TlsError> {
+    let start = value.iter().position(|&b| b != 0).unwrap_or(value.len() - 1);
+    let value = &value[start..];
+    let pad = value[0] & 0x80 != 0;
+    w.header(0x02, value.len() + pad as usize)?;
+    if pad {
+        w.bytes(&[0])?;
+    }
+    w.bytes(value)
+}
+
+/// ECDSA 签名编码为 DER `SEQUENCE { r INTEGER, s INTEGER }`
+pub(crate) fn signature_der(sig: &Signature, out: &mut [u8; MAX_DER_SIGNATURE_LEN]) -> usize {
+    let bytes = sig.to_bytes();
+    let mut body = [0u8; MAX_DER_SIGNATURE_LEN - 2];
+    let mut w = DerWriter { out: &mut body, pos: 0 };
+    // r、s 各至多 33 字节，缓冲区足够
+    let _ = write_uint(&mut w, &bytes[..32]);
+    let _ = write_uint(&mut w, &bytes[32..]);
+    let len = w.pos;
+    out[0] = 0x30;
+    out[1] = len as u8;
+    out[2..2 + len].copy_from_slice(&body[..len]);
+    2 + len
+}
+
+/// 生成自签名 X.509 v3 证书 (DER)
+///
+/// 主题与颁发者均为 `CN=<common_name>`，并带有同名的 subjectAltName dNSName，
+/// 序列号由公钥摘要派生。返回写入 `out` 的字节数。
+pub fn self_signed(key: &SigningKey, common_name: &str, out: &mut [u8]) -> Result<usize, TlsError> {
+    let cn = common_name.as_bytes();
+    if cn.is_empty() || cn.len() > MAX_COMMON_NAME_LEN {
+        return Err(TlsError::InvalidIdentity);
+    }
+
+    let point = key.verifying_key().to_encoded_point(false);
+    let public = point.as_bytes();
+    let digest = Sha256::digest(public);
+    let mut serial = [0u8; 8];
+    serial.copy_from_slice(&digest[..8]);
+    // 正数且最高字节非零，保证 8 字节最短编码
+    serial[0] = (serial[0] & 0x7f) | 0x40;
+
+    // extensions: [3] { SEQUENCE { SEQUENCE { subjectAltName, OCTET STRING { SEQUENCE { [2] dNSName } } } } }
+    let general_names = tlv_len(cn.len());
+    let san_value = tlv_len(general_names);
+    let san_ext = 5 + tlv_len(san_value);
+    let extensions = tlv_len(tlv_len(san_ext));
+
+    let tbs = 5 + tlv_len(serial.len()) + ECDSA_SHA256_ALG.len() + 2 * name_len(cn.len()) + VALIDITY.len()
+        + P256_SPKI_PREFIX.len() + public.len() + tlv_len(extensions);
+
+    // 外层 SEQUENCE 头最长 4 字节，先在其后写入 TBSCertificate
+    const OUTER: usize = 4;
+    let mut w = DerWriter { out, pos: OUTER };
+    w.header(0x30, tbs)?;
+    let tbs_start = OUTER;
+    w.bytes(&[0xa0, 0x03, 0x02, 0x01, 0x02])?;
+    w.tlv(0x02, &serial)?;
+    w.bytes(&ECDSA_SHA256_ALG)?;
+    write_name(&mut w, cn)?;
+    w.bytes(VALIDITY)?;
+    write_name(&mut w, cn)?;
+    w.bytes(&P256_SPKI_PREFIX)?;
+    w.bytes(public)?;
+    w.header(0xa3, extensions)?;
+    w.header(0x30, tlv_len(san_ext))?;
+    w.header(0x30, san_ext)?;
+    w.bytes(&[0x06, 0x03, 0x55, 0x1d, 0x11])?;
+    w.header(0x04, san_value)?;
+    w.header(0x30, general_names)?;
+    w.tlv(0x82, cn)?;
+    let tbs_end = w.pos;
+
+    let sig: Signature = key.sign(&w.out[tbs_start..tbs_end]);
+    let mut der = [0u8; MAX_DER_SIGNATURE_LEN];
+    let der_len = signature_der(&sig, &mut der);
+    w.bytes(&ECDSA_SHA256_ALG)?;
+    w.header(0x03, der_len + 1)?;
+    w.bytes(&[0])?;
+    w.bytes(&der[..der_len])?;
+    let end = w.pos;
+
+    // 回填外层 SEQUENCE 头并前移内容
+    let content = end - tbs_start;
+    let header = 1 + der_len_size(content);
+    let out = w.out;
+    out.copy_within(tbs_start..end, header);
+    let mut w = DerWriter { out, pos: 0 };
+    w.header(0x30, content)?;
+    Ok(header + content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p256::ecdsa::signature::Verifier;
+
+    #[test]
+    fn test_self_signed_structure() {
+        let key = SigningKey::from_slice(&[0x11; 32]).unwrap();
+        let mut buf = [0u8; SELF_SIGNED_MAX_LEN];
+        let len = self_signed(&key, "rustrtos.local", &mut buf).unwrap();
+        let cert = &buf[..len];
+        assert_eq!(der_element_len(cert), Some(len));
+
+        // TBSCertificate 之后是算法标识与签名，签名可用证书内的公钥验证
+        let tbs_len = der_element_len(&cert[4..]).unwrap();
+        let tbs = &cert[4..4 + tbs_len];
+        let rest = &cert[4 + tbs_len..];
+        assert_eq!(&rest[..12], &ECDSA_SHA256_ALG);
+        assert_eq!(rest[12], 0x03);
+        let sig_der = &rest[15..];
+        let sig = Signature::from_der(sig_der).unwrap();
+        assert!(key.verifying_key().verify(tbs, &sig).is_ok());
+
+        let identity = TlsIdentity::new(cert, key).unwrap();
+        assert_eq!(identity.certificates().count(), 1);
+        assert_eq!(
+            TlsIdentity::new(&cert[..len - 1], SigningKey::from_slice(&[0x11; 32]).unwrap()).err(),
+            Some(TlsError::InvalidIdentity)
+        );
+    }
+}
@@ -0,0 +1,291 @@
+//! TLS 1.3 握手消息编解码
+//!
+//! 服务端只需解析 ClientHello 与 Finished，其余消息只编码不解析。
+
+use super::TlsError;
+
+// ----- 握手消息类型 -----
+
+pub(crate) const CLIENT_HELLO: u8 = 1;
+pub(crate) const SERVER_HELLO: u8 = 2;
+pub(crate) const ENCRYPTED_EXTENSIONS: u8 = 8;
+pub(crate) const CERTIFICATE: u8 = 11;
+pub(crate) const CERTIFICATE_VERIFY: u8 = 15;
+pub(crate) const FINISHED: u8 = 20;
+pub(crate) const KEY_UPDATE: u8 = 24;
+
+// ----- 参数 -----
+
+/// TLS_AES_128_GCM_SHA256
+pub(crate) const CIPHER_AES_128_GCM_SHA256: u16 = 0x1301;
+/// ecdsa_secp256r1_sha256
+pub(crate) const SIG_ECDSA_P256_SHA256: u16 = 0x0403;
+/// TLS 1.3 版本号
+pub(crate) const TLS13: u16 = 0x0304;
+
+const EXT_SIGNATURE_ALGORITHMS: u16 = 13;
+const EXT_SUPPORTED_VERSIONS: u16 = 43;
+const EXT_KEY_SHARE: u16 = 51;
+
+/// 密钥交换群
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NamedGroup {
+    /// x25519
+    X25519,
+    /// secp256r1 (P-256)
+    Secp256r1,
+}
+
+impl NamedGroup {
+    /// 协议编号
+    pub const fn id(self) -> u16 {
+        match self {
+            NamedGroup::X25519 => 0x001d,
+            NamedGroup::Secp256r1 => 0x0017,
+        }
+    }
+
+    /// 公钥长度 (P-256 为未压缩点)
+    pub const fn key_len(self) -> usize {
+        match self {
+            NamedGroup::X25519 => 32,
+            NamedGroup::Secp256r1 => 65,
+        }
+    }
+
+    const fn from_id(id: u16) -> Option<Self> {
+        match id {
+            0x001d => Some(NamedGroup::X25519),
+            0x0017 => Some(NamedGroup::Secp256r1),
+            _ => None,
+        }
+    }
+}
+
+/// 解析后的 ClientHello (仅保留服务端需要的字段)
+#[derive(Debug)]
+pub(crate) struct ClientHello<'a> {
+    /// legacy_session_id (需原样回显)
+    pub session_id: &'a [u8],
+    /// 选中的密钥交换群
+    pub group: NamedGroup,
+    /// 客户端在该群上的公钥
+    pub key_share: &'a [u8],
+}
+
+/// 大端字节流读取器
+struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], TlsError> {
+        if n > self.buf.len() {
+            return Err(TlsError::Decode);
+        }
+        let (head, tail) = self.buf.split_at(n);
+        self.buf = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, TlsError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, TlsError> {
+        let b = self.take(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn vec8(&mut self) -> Result<&'a [u8], TlsError> {
+        let n = self.u8()? as usize;
+        self.take(n)
+    }
+
+    fn vec16(&mut self) -> Result<&'a [u8], TlsError> {
+        let n = self.u16()? as usize;
+        self.take(n)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+}
+
+/// u16 列表是否包含 `value`
+fn contains_u16(list: &[u8], value: u16) -> bool {
+    list.chunks_exact(2).any(|c| u16::from_be_bytes([c[0], c[1]]) == value)
+}
+
+/// 握手消息头: 类型 + u24 长度
+pub(crate) fn message_header(kind: u8, len: usize) -> [u8; 4] {
+    [kind, (len >> 16) as u8, (len >> 8) as u8, len as u8]
+}
+
+/// 拆分一个完整的握手消息，返回 (类型, 消息体, 消息总长)
+pub(crate) fn split_message(buf: &[u8]) -> Option<(u8, &[u8], usize)> {
+    if buf.len() < 4 {
+        return None;
+    }
+    let len = u32::from_be_bytes([0, buf[1], buf[2], buf[3]]) as usize;
+    let body = buf.get(4..4 + len)?;
+    Some((buf[0], body, 4 + len))
+}
+
+/// 握手消息完整长度 (头部不足时返回 None)
+pub(crate) fn message_len(buf: &[u8]) -> Option<usize> {
+    (buf.len() >= 4).then(|| 4 + u32::from_be_bytes([0, buf[1], buf[2], buf[3]]) as usize)
+}
+
+/// 解析 ClientHello 消息体
+///
+/// 要求客户端支持 TLS 1.3、`TLS_AES_128_GCM_SHA256` 与 `ecdsa_secp256r1_sha256`，
+/// 并且已为 x25519 或 secp256r1 提供密钥共享 (不实现 HelloRetryRequest)。
+pub(crate) fn parse_client_hello(body: &[u8]) -> Result<ClientHello<'_>, TlsError> {
+    let mut r = Reader { buf: body };
+    let _legacy_version = r.u16()?;
+    let _random = r.take(32)?;
+    let session_id = r.vec8()?;
+    if session_id.len() > 32 {
+        return Err(TlsError::Decode);
+    }
+    let suites = r.vec16()?;
+    let compression = r.vec8()?;
+    if !compression.contains(&0) {
+        return Err(TlsError::Decode);
+    }
+
+    let mut tls13 = false;
+    let mut signature_ok = false;
+    let mut share: Option<(NamedGroup, &[u8])> = None;
+
+    let mut exts = Reader { buf: r.vec16()? };
+    while !exts.is_empty() {
+        let kind = exts.u16()?;
+        let mut data = Reader { buf: exts.vec16()? };
+        match kind {
+            EXT_SUPPORTED_VERSIONS => tls13 = contains_u16(data.vec8()?, TLS13),
+            EXT_SIGNATURE_ALGORITHMS => signature_ok = contains_u16(data.vec16()?, SIG_ECDSA_P256_SHA256),
+            EXT_KEY_SHARE => {
+                let mut entries = Reader { buf: data.vec16()? };
+                while !entries.is_empty() {
+                    let group = NamedGroup::from_id(entries.u16()?);
+                    let key = entries.vec16()?;
+                    match group {
+                        // x25519 优先 (计算量远小于 P-256)
+                        Some(g @ NamedGroup::X25519) if key.len() == g.key_len() => share = Some((g, key)),
+                        Some(g @ NamedGroup::Secp256r1) if key.len() == g.key_len() && share.is_none() => {
+                            share = Some((g, key))
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !tls13 {
+        return Err(TlsError::ProtocolVersion);
+    }
+    if !contains_u16(suites, CIPHER_AES_128_GCM_SHA256) || !signature_ok {
+        return Err(TlsError::HandshakeFailure);
+    }
+    let (group, key_share) = share.ok_or(TlsError::HandshakeFailure)?;
+    Ok(ClientHello { session_id, group, key_share })
+}
+
+/// 编码 ServerHello 消息，返回长度
+pub(crate) fn server_hello(
+    out: &mut [u8],
+    random: &[u8; 32],
+    session_id: &[u8],
+    group: NamedGroup,
+    public: &[u8],
+) -> Result<usize, TlsError> {
+    let ext_len = 6 + 8 + public.len();
+    let body = 2 + 32 + 1 + session_id.len() + 2 + 1 + 2 + ext_len;
+    let total = 4 + body;
+    let out = out.get_mut(..total).ok_or(TlsError::BufferTooSmall)?;
+
+    out[..4].copy_from_slice(&message_header(SERVER_HELLO, body));
+    out[4..6].copy_from_slice(&[0x03, 0x03]);
+    out[6..38].copy_from_slice(random);
+    out[38] = session_id.len() as u8;
+    let mut p = 39 + session_id.len();
+    out[39..p].copy_from_slice(session_id);
+    out[p..p + 2].copy_from_slice(&CIPHER_AES_128_GCM_SHA256.to_be_bytes());
+    out[p + 2] = 0;
+    out[p + 3..p + 5].copy_from_slice(&(ext_len as u16).to_be_bytes());
+    p += 5;
+    out[p..p + 6].copy_from_slice(&[0x00, 0x2b, 0x00, 0x02, 0x03, 0x04]);
+    p += 6;
+    out[p..p + 2].copy_from_slice(&EXT_KEY_SHARE.to_be_bytes());
+    out[p + 2..p + 4].copy_from_slice(&((4 + public.len()) as u16).to_be_bytes());
+    out[p + 4..p + 6].copy_from_slice(&group.id().to_be_bytes());
+    out[p + 6..p + 8].copy_from_slice(&(public.len() as u16).to_be_bytes());
+    out[p + 8..].copy_from_slice(public);
+    Ok(total)
+}
+
+/// CertificateVerify 待签名内容: 64 个空格 | 上下文串 | 0 | 摘要
+pub(crate) fn certificate_verify_content(transcript: &[u8; 32]) -> [u8; 130] {
+    const CONTEXT: &[u8; 33] = b"TLS 1.3, server CertificateVerify";
+    let mut content = [0x20u8; 130];
+    content[64..97].copy_from_slice(CONTEXT);
+    content[97] = 0;
+    content[98..].copy_from_slice(transcript);
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 构造 ClientHello 消息体
+    fn client_hello(versions: &[u8], shares: &[(u16, usize)]) -> heapless::Vec<u8, 512> {
+        let mut ks: heapless::Vec<u8, 256> = heapless::Vec::new();
+        for &(group, len) in shares {
+            ks.extend_from_slice(&group.to_be_bytes()).unwrap();
+            ks.extend_from_slice(&(len as u16).to_be_bytes()).unwrap();
+            ks.extend_from_slice(&[4u8; 65][..len]).unwrap();
+        }
+        let mut exts: heapless::Vec<u8, 384> = heapless::Vec::new();
+        exts.extend_from_slice(&[0x00, 0x2b, 0x00, versions.len() as u8 + 1, versions.len() as u8]).unwrap();
+        exts.extend_from_slice(versions).unwrap();
+        exts.extend_from_slice(&[0x00, 0x0d, 0x00, 0x06, 0x00, 0x04, 0x08, 0x04, 0x04, 0x03]).unwrap();
+        exts.extend_from_slice(&[0x00, 0x33]).unwrap();
+        exts.extend_from_slice(&((ks.len() + 2) as u16).to_be_bytes()).unwrap();
+        exts.extend_from_slice(&(ks.len() as u16).to_be_bytes()).unwrap();
+        exts.extend_from_slice(&ks).unwrap();
+
+        let mut body: heapless::Vec<u8, 512> = heapless::Vec::new();
+        body.extend_from_slice(&[0x03, 0x03]).unwrap();
+        body.extend_from_slice(&[0xaa; 32]).unwrap();
+        body.extend_from_slice(&[2, 0x55, 0x66]).unwrap();
+        body.extend_from_slice(&[0x00, 0x04, 0x13, 0x02, 0x13, 0x01]).unwrap();
+        body.extend_from_slice(&[1, 0]).unwrap();
+        body.extend_from_slice(&(exts.len() as u16).to_be_bytes()).unwrap();
+        body.extend_from_slice(&exts).unwrap();
+        body
+    }
+
+    #[test]
+    fn test_parse_client_hello() {
+        let body = client_hello(&[0x03, 0x04, 0x03, 0x03], &[(0x0017, 65), (0x001d, 32)]);
+        let hello = parse_client_hello(&body).unwrap();
+        assert_eq!(hello.session_id, &[0x55, 0x66]);
+        assert_eq!(hello.group, NamedGroup::X25519);
+        assert_eq!(hello.key_share.len(), 32);
+
+        let body = client_hello(&[0x03, 0x04], &[(0x0017, 65)]);
+        assert_eq!(parse_client_hello(&body).unwrap().group, NamedGroup::Secp256r1);
+
+        // 仅 TLS 1.2 / 没有可用的密钥共享 / 截断
+        let body = client_hello(&[0x03, 0x03], &[(0x001d, 32)]);
+        assert_eq!(parse_client_hello(&body).unwrap_err(), TlsError::ProtocolVersion);
+        let body = client_hello(&[0x03, 0x04], &[(0x0018, 40)]);
+        assert_eq!(parse_client_hello(&body).unwrap_err(), TlsError::HandshakeFailure);
+        assert_eq!(parse_client_hello(&body[..body.len() - 1]).unwrap_err(), TlsError::Decode);
+    }
+}
@@ -0,0 +1,191 @@
+//! TLS 1.3 密钥调度与记录保护 (RFC 8446 §7)
+//!
+//! 仅支持 `TLS_AES_128_GCM_SHA256`: HKDF-SHA256 派生密钥，AES-128-GCM 保护记录。
+
+use aes_gcm::aead::{AeadInPlace, KeyInit};
+use aes_gcm::{Aes128Gcm, Nonce, Tag};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use super::TlsError;
+
+/// 摘要 / 秘密长度 (SHA-256)
+pub(crate) const HASH_LEN: usize = 32;
+
+/// AEAD 认证标签长度
+pub(crate) const TAG_LEN: usize = 16;
+
+const KEY_LEN: usize = 16;
+const IV_LEN: usize = 12;
+
+/// HKDF-Extract
+pub(crate) fn extract(salt: &[u8], ikm: &[u8]) -> [u8; HASH_LEN] {
+    Hkdf::<Sha256>::extract(Some(salt), ikm).0.into()
+}
+
+/// HKDF-Expand-Label
+pub(crate) fn expand_label(secret: &[u8; HASH_LEN], label: &[u8], context: &[u8], out: &mut [u8]) {
+    const PREFIX: &[u8] = b"tls13 ";
+    // u16 长度 + u8 标签长度 + 标签 (最长 "tls13 s hs traffic") + u8 上下文长度 + 摘要
+    let mut info = [0u8; 2 + 1 + 18 + 1 + HASH_LEN];
+    let label_len = PREFIX.len() + label.len();
+    let n = 2 + 1 + label_len + 1 + context.len();
+    info[..2].copy_from_slice(&(out.len() as u16).to_be_bytes());
+    info[2] = label_len as u8;
+    info[3..3 + PREFIX.len()].copy_from_slice(PREFIX);
+    info[3 + PREFIX.len()..3 + label_len].copy_from_slice(label);
+    info[3 + label_len] = context.len() as u8;
+    info[4 + label_len..n].copy_from_slice(context);
+
+    // PRK 长度等于摘要长度，from_prk 与 expand 均不会失败
+    let hk = Hkdf::<Sha256>::from_prk(secret).expect("prk length");
+    hk.expand(&info[..n], out).expect("okm length");
+}
+
+/// Derive-Secret(secret, label, transcript_hash)
+pub(crate) fn derive_secret(secret: &[u8; HASH_LEN], label: &[u8], transcript: &[u8; HASH_LEN]) -> [u8; HASH_LEN] {
+    let mut out = [0u8; HASH_LEN];
+    expand_label(secret, label, transcript, &mut out);
+    out
+}
+
+/// Finished 消息的 verify_data
+pub(crate) fn finished_mac(base: &[u8; HASH_LEN], transcript: &[u8; HASH_LEN]) -> [u8; HASH_LEN] {
+    let mut key = [0u8; HASH_LEN];
+    expand_label(base, b"finished", &[], &mut key);
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&key).expect("hmac key");
+    mac.update(transcript);
+    mac.finalize().into_bytes().into()
+}
+
+/// KeyUpdate 后的下一代流量秘密
+pub(crate) fn next_secret(secret: &[u8; HASH_LEN]) -> [u8; HASH_LEN] {
+    let mut out = [0u8; HASH_LEN];
+    expand_label(secret, b"traffic upd", &[], &mut out);
+    out
+}
+
+/// 握手阶段秘密
+pub(crate) struct HandshakeSecrets {
+    /// 握手秘密 (用于派生主秘密)
+    pub secret: [u8; HASH_LEN],
+    /// 客户端握手流量秘密
+    pub client: [u8; HASH_LEN],
+    /// 服务端握手流量秘密
+    pub server: [u8; HASH_LEN],
+}
+
+impl HandshakeSecrets {
+    /// 由 (EC)DHE 共享秘密与 ClientHello..ServerHello 摘要派生
+    pub fn derive(shared: &[u8], transcript: &[u8; HASH_LEN]) -> Self {
+        let early = extract(&[0u8; HASH_LEN], &[0u8; HASH_LEN]);
+        let salt = derive_secret(&early, b"derived", &empty_hash());
+        let secret = extract(&salt, shared);
+        Self {
+            client: derive_secret(&secret, b"c hs traffic", transcript),
+            server: derive_secret(&secret, b"s hs traffic", transcript),
+            secret,
+        }
+    }
+
+    /// 派生应用流量秘密 (客户端, 服务端)，摘要截至服务端 Finished
+    pub fn application(&self, transcript: &[u8; HASH_LEN]) -> ([u8; HASH_LEN], [u8; HASH_LEN]) {
+        let salt = derive_secret(&self.secret, b"derived", &empty_hash());
+        let master = extract(&salt, &[0u8; HASH_LEN]);
+        (
+            derive_secret(&master, b"c ap traffic", transcript),
+            derive_secret(&master, b"s ap traffic", transcript),
+        )
+    }
+}
+
+fn empty_hash() -> [u8; HASH_LEN] {
+    Sha256::digest([]).into()
+}
+
+/// 单方向记录保护状态
+pub(crate) struct TrafficKeys {
+    cipher: Aes128Gcm,
+    iv: [u8; IV_LEN],
+    seq: u64,
+}
+
+impl TrafficKeys {
+    /// 由流量秘密派生密钥与 IV，序号清零
+    pub fn new(secret: &[u8; HASH_LEN]) -> Self {
+        let mut key = [0u8; KEY_LEN];
+        let mut iv = [0u8; IV_LEN];
+        expand_label(secret, b"key", &[], &mut key);
+        expand_label(secret, b"iv", &[], &mut iv);
+        Self { cipher: Aes128Gcm::new(&key.into()), iv, seq: 0 }
+    }
+
+    /// 每记录 nonce = IV xor 序号
+    fn nonce(&mut self) -> Result<[u8; IV_LEN], TlsError> {
+        let mut nonce = self.iv;
+        for (n, s) in nonce[IV_LEN - 8..].iter_mut().zip(self.seq.to_be_bytes()) {
+            *n ^= s;
+        }
+        self.seq = self.seq.checked_add(1).ok_or(TlsError::SequenceOverflow)?;
+        Ok(nonce)
+    }
+
+    /// 原地加密，返回认证标签
+    pub fn seal(&mut self, header: &[u8], buf: &mut [u8]) -> Result<[u8; TAG_LEN], TlsError> {
+        let nonce = self.nonce()?;
+        self.cipher
+            .encrypt_in_place_detached(Nonce::from_slice(&nonce), header, buf)
+            .map(Into::into)
+            .map_err(|_| TlsError::RecordOverflow)
+    }
+
+    /// 原地解密并校验认证标签
+    pub fn open(&mut self, header: &[u8], buf: &mut [u8], tag: &[u8]) -> Result<(), TlsError> {
+        let nonce = self.nonce()?;
+        self.cipher
+            .decrypt_in_place_detached(Nonce::from_slice(&nonce), header, buf, Tag::from_slice(tag))
+            .map_err(|_| TlsError::BadRecordMac)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 8448 §3: 无 PSK 时的 early secret 与 "derived" 秘密
+    const EARLY: [u8; 32] = [
+        0x33, 0xad, 0x0a, 0x1c, 0x60, 0x7e, 0xc0, 0x3b, 0x09, 0xe6, 0xcd, 0x98, 0x93, 0x68, 0x0c, 0xe2,
+        0x10, 0xad, 0xf3, 0x00, 0xaa, 0x1f, 0x26, 0x60, 0xe1, 0xb2, 0x2e, 0x10, 0xf1, 0x70, 0xf9, 0x2a,
+    ];
+    const DERIVED: [u8; 32] = [
+        0x6f, 0x26, 0x15, 0xa1, 0x08, 0xc7, 0x02, 0xc5, 0x67, 0x8f, 0x54, 0xfc, 0x9d, 0xba, 0xb6, 0x97,
+        0x16, 0xc0, 0x76, 0x18, 0x9c, 0x48, 0x25, 0x0c, 0xeb, 0xea, 0xc3, 0x57, 0x6c, 0x36, 0x11, 0xba,
+    ];
+
+    #[test]
+    fn test_key_schedule_rfc8448() {
+        let early = extract(&[0u8; 32], &[0u8; 32]);
+        assert_eq!(early, EARLY);
+        assert_eq!(derive_secret(&early, b"derived", &empty_hash()), DERIVED);
+    }
+
+    #[test]
+    fn test_record_roundtrip() {
+        let secret = [7u8; 32];
+        let mut tx = TrafficKeys::new(&secret);
+        let mut rx = TrafficKeys::new(&secret);
+        let header = [23, 3, 3, 0, 21];
+        let mut buf = *b"hello";
+        let tag = tx.seal(&header, &mut buf).unwrap();
+        assert_ne!(&buf, b"hello");
+        rx.open(&header, &mut buf, &tag).unwrap();
+        assert_eq!(&buf, b"hello");
+
+        // 序号已前进，重放同一记录必须失败
+        let tag = tx.seal(&header, &mut buf).unwrap();
+        let mut replay = buf;
+        rx.open(&header, &mut buf, &tag).unwrap();
+        assert_eq!(rx.open(&header, &mut replay, &tag), Err(TlsError::BadRecordMac));
+    }
+}
@@ -0,0 +1,614 @@
+//! TLS 1.3 服务端
+//!
+//! 为设备上的 HTTP 服务器 (配网页面、管理接口) 提供传输加密，避免凭据与配置在客户网络中明文传输。
+//! 特性：
+//! - 仅实现 TLS 1.3 (RFC 8446)，密码套件 `TLS_AES_128_GCM_SHA256`
+//! - 密钥交换 x25519 / secp256r1，服务端证书签名 `ecdsa_secp256r1_sha256`
+//! - 证书链可预置 (CA 签发) 或由设备私钥生成自签名证书，私钥保存在凭据保管库
+//! - 零分配: 记录缓冲区由调用方提供，`TlsStream` 实现 `embedded-io-async` 的 `Read`/`Write`，
+//!   可直接交给 `http::serve_connection`
+//! - 支持对端 KeyUpdate 与 close_notify
+//!
+//! 不支持: 客户端证书、会话恢复 / 0-RTT、HelloRetryRequest (客户端必须在首个
+//! ClientHello 中给出 x25519 或 secp256r1 密钥共享，主流浏览器与 curl/OpenSSL 均满足)。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::http::{self, HttpConfig, HTTPS_PORT};
+//! use rustrtos::net::tls::{self, TlsIdentity};
+//! use rustrtos::sys::vault::VAULT;
+//!
+//! let mut rng = esp_hal::rng::Rng::new();
+//! if tls::provision_key(&VAULT, "tls", &mut rng)? {
+//!     VAULT.save(&fs, "/sys/vault.bin")?;
+//! }
+//!
+//! // 未预置 CA 证书时使用自签名证书
+//! static CERT: StaticCell<[u8; tls::SELF_SIGNED_MAX_LEN]> = StaticCell::new();
+//! let cert = CERT.init([0; tls::SELF_SIGNED_MAX_LEN]);
+//! let key = tls::load_key(&VAULT, "tls")?;
+//! let len = tls::self_signed(&key, "rustrtos.local", cert)?;
+//! let identity = TlsIdentity::new(&cert[..len], key)?;
+//!
+//! http::listen_tls::<2, _>(stack, &HttpConfig::default().with_port(HTTPS_PORT), &identity, &Api).await;
+//! ```
+
+pub mod cert;
+mod handshake;
+mod keys;
+
+use core::fmt;
+
+use embedded_io_async::{ErrorKind, ErrorType, Read, ReadExactError, Write};
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::Signature;
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use sha2::{Digest, Sha256};
+
+use crate::crypto::{constant_time_eq, Entropy};
+use handshake::NamedGroup;
+use keys::{HandshakeSecrets, TrafficKeys, HASH_LEN, TAG_LEN};
+
+pub use cert::{generate_key, load_key, provision_key, self_signed, TlsIdentity, MAX_COMMON_NAME_LEN, SELF_SIGNED_MAX_LEN};
+
+/// 单条记录明文上限
+pub const MAX_PLAINTEXT: usize = 16384;
+
+/// 单条记录密文上限 (RFC 8446 §5.2)
+pub const MAX_CIPHERTEXT: usize = MAX_PLAINTEXT + 256;
+
+/// 推荐的接收缓冲区大小 (可容纳任意合规记录)
+pub const RX_BUFFER_LEN: usize = MAX_CIPHERTEXT;
+
+/// 推荐的发送缓冲区大小 (发送方向的记录大小由本端决定)
+pub const TX_BUFFER_LEN: usize = 2048;
+
+/// 发送缓冲区下限 (需容纳 ServerHello)
+pub const MIN_TX_BUFFER_LEN: usize = 256;
+
+/// 记录头长度
+const HEADER_LEN: usize = 5;
+
+// ----- 记录类型 -----
+
+const CHANGE_CIPHER_SPEC: u8 = 20;
+const ALERT: u8 = 21;
+const HANDSHAKE: u8 = 22;
+const APPLICATION_DATA: u8 = 23;
+
+/// close_notify 告警描述
+const CLOSE_NOTIFY: u8 = 0;
+
+// ===== 错误类型 =====
+
+/// TLS 错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsError {
+    /// 底层连接 I/O 错误
+    Io,
+    /// 对端在握手或记录中途关闭连接
+    Closed,
+    /// 记录超出长度限制或缓冲区
+    RecordOverflow,
+    /// 消息格式错误
+    Decode,
+    /// 收到当前状态不允许的消息
+    UnexpectedMessage,
+    /// 客户端不支持 TLS 1.3
+    ProtocolVersion,
+    /// 没有共同支持的算法参数
+    HandshakeFailure,
+    /// 密钥共享等参数无效
+    IllegalParameter,
+    /// 记录认证失败
+    BadRecordMac,
+    /// Finished 校验失败
+    DecryptError,
+    /// 对端发送的致命告警 (告警描述码)
+    Alert(u8),
+    /// 记录序号耗尽
+    SequenceOverflow,
+    /// 调用方提供的缓冲区过小
+    BufferTooSmall,
+    /// 证书链或私钥无效
+    InvalidIdentity,
+}
+
+impl TlsError {
+    /// 需要发给对端的告警描述码
+    fn alert(self) -> Option<u8> {
+        match self {
+            Self::UnexpectedMessage => Some(10),
+            Self::BadRecordMac => Some(20),
+            Self::RecordOverflow => Some(22),
+            Self::HandshakeFailure => Some(40),
+            Self::IllegalParameter => Some(47),
+            Self::Decode => Some(50),
+            Self::DecryptError => Some(51),
+            Self::ProtocolVersion => Some(70),
+            Self::SequenceOverflow | Self::BufferTooSmall | Self::InvalidIdentity => Some(80),
+            Self::Io | Self::Closed | Self::Alert(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for TlsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io => write!(f, "Connection I/O error"),
+            Self::Closed => write!(f, "Connection closed by peer"),
+            Self::RecordOverflow => write!(f, "Record overflow"),
+            Self::Decode => write!(f, "Malformed message"),
+            Self::UnexpectedMessage => write!(f, "Unexpected message"),
+            Self::ProtocolVersion => write!(f, "TLS 1.3 not offered by peer"),
+            Self::HandshakeFailure => write!(f, "No common cipher suite, group or signature scheme"),
+            Self::IllegalParameter => write!(f, "Illegal parameter"),
+            Self::BadRecordMac => write!(f, "Record authentication failed"),
+            Self::DecryptError => write!(f, "Finished verification failed"),
+            Self::Alert(code) => write!(f, "Fatal alert from peer: {}", code),
+            Self::SequenceOverflow => write!(f, "Record sequence number exhausted"),
+            Self::BufferTooSmall => write!(f, "Buffer too small"),
+            Self::InvalidIdentity => write!(f, "Invalid certificate chain or private key"),
+        }
+    }
+}
+
+impl embedded_io_async::Error for TlsError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Closed | Self::Alert(_) => ErrorKind::ConnectionReset,
+            Self::BufferTooSmall => ErrorKind::OutOfMemory,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+impl<E> From<ReadExactError<E>> for TlsError {
+    fn from(e: ReadExactError<E>) -> Self {
+        match e {
+            ReadExactError::UnexpectedEof => TlsError::Closed,
+            ReadExactError::Other(_) => TlsError::Io,
+        }
+    }
+}
+
+// ===== 记录层 =====
+
+/// 记录层: 底层连接 + 收发缓冲区
+///
+/// 接收的记录负载位于 `rx[..len]`，待发送的明文写在 `tx[HEADER_LEN..]`。
+struct Records<'b, T> {
+    io: T,
+    rx: &'b mut [u8],
+    tx: &'b mut [u8],
+}
+
+impl<T: Read + Write> Records<'_, T> {
+    /// 单条加密记录可承载的明文长度
+    fn capacity(&self) -> usize {
+        (self.tx.len() - HEADER_LEN - 1 - TAG_LEN).min(MAX_PLAINTEXT)
+    }
+
+    /// 读取一条原始记录，负载写入 `rx[at..]`
+    async fn read_raw(&mut self, at: usize) -> Result<([u8; HEADER_LEN], usize), TlsError> {
+        let mut header = [0u8; HEADER_LEN];
+        self.io.read_exact(&mut header).await?;
+        if header[1] != 3 {
+            return Err(TlsError::Decode);
+        }
+        let len = u16::from_be_bytes([header[3], header[4]]) as usize;
+        if len > MAX_CIPHERTEXT || at + len > self.rx.len() {
+            return Err(TlsError::RecordOverflow);
+        }
+        self.io.read_exact(&mut self.rx[at..at + len]).await?;
+        Ok((header, len))
+    }
+
+    /// 读取明文 ClientHello (可跨多条记录)，返回消息长度
+    async fn read_client_hello(&mut self) -> Result<usize, TlsError> {
+        let mut filled = 0;
+        loop {
+            let (header, len) = self.read_raw(filled).await?;
+            match header[0] {
+                HANDSHAKE if len > 0 => {}
+                ALERT if len == 2 => return Err(TlsError::Alert(self.rx[filled + 1])),
+                _ => return Err(TlsError::UnexpectedMessage),
+            }
+            filled += len;
+            if let Some(total) = handshake::message_len(&self.rx[..filled]) {
+                if self.rx[0] != handshake::CLIENT_HELLO || total < filled {
+                    return Err(TlsError::UnexpectedMessage);
+                }
+                if total == filled {
+                    return Ok(total);
+                }
+                if total > self.rx.len() {
+                    return Err(TlsError::RecordOverflow);
+                }
+            }
+        }
+    }
+
+    /// 读取并解密一条记录，返回 (内层类型, 明文长度)，明文位于 `rx[..len]`
+    ///
+    /// 握手阶段忽略兼容模式的 ChangeCipherSpec，并接受明文告警。
+    async fn read_sealed(&mut self, keys: &mut TrafficKeys, handshaking: bool) -> Result<(u8, usize), TlsError> {
+        loop {
+            let (header, len) = self.read_raw(0).await?;
+            match header[0] {
+                APPLICATION_DATA => {}
+                CHANGE_CIPHER_SPEC if handshaking && len == 1 && self.rx[0] == 1 => continue,
+                ALERT if handshaking && len == 2 => return Err(TlsError::Alert(self.rx[1])),
+                _ => return Err(TlsError::UnexpectedMessage),
+            }
+            if len <= TAG_LEN || len > MAX_PLAINTEXT + 1 + TAG_LEN {
+                return Err(TlsError::RecordOverflow);
+            }
+            let (body, tag) = self.rx[..len].split_at_mut(len - TAG_LEN);
+            keys.open(&header, body, tag)?;
+            // 内层明文: 内容 | 类型 | 零填充
+            let end = body.iter().rposition(|&b| b != 0).ok_or(TlsError::UnexpectedMessage)?;
+            return Ok((body[end], end));
+        }
+    }
+
+    /// 发送明文记录 (内容位于 `tx[HEADER_LEN..HEADER_LEN + len]`)
+    async fn write_plain(&mut self, kind: u8, len: usize) -> Result<(), TlsError> {
+        self.tx[..HEADER_LEN].copy_from_slice(&[kind, 3, 3, (len >> 8) as u8, len as u8]);
+        self.io.write_all(&self.tx[..HEADER_LEN + len]).await.map_err(|_| TlsError::Io)
+    }
+
+    /// 加密并发送记录 (内容位于 `tx[HEADER_LEN..HEADER_LEN + len]`)
+    async fn write_sealed(&mut self, keys: &mut TrafficKeys, kind: u8, len: usize) -> Result<(), TlsError> {
+        let inner = len + 1;
+        let total = inner + TAG_LEN;
+        let header = [APPLICATION_DATA, 3, 3, (total >> 8) as u8, total as u8];
+        self.tx[HEADER_LEN + len] = kind;
+        let tag = keys.seal(&header, &mut self.tx[HEADER_LEN..HEADER_LEN + inner])?;
+        self.tx[HEADER_LEN + inner..HEADER_LEN + total].copy_from_slice(&tag);
+        self.tx[..HEADER_LEN].copy_from_slice(&header);
+        self.io.write_all(&self.tx[..HEADER_LEN + total]).await.map_err(|_| TlsError::Io)
+    }
+
+    /// 追加握手数据到待发送的加密记录，写满即发送
+    async fn push(
+        &mut self,
+        fill: &mut usize,
+        keys: &mut TrafficKeys,
+        transcript: &mut Sha256,
+        mut data: &[u8],
+    ) -> Result<(), TlsError> {
+        transcript.update(data);
+        let capacity = self.capacity();
+        while !data.is_empty() {
+            let n = (capacity - *fill).min(data.len());
+            self.tx[HEADER_LEN + *fill..HEADER_LEN + *fill + n].copy_from_slice(&data[..n]);
+            *fill += n;
+            data = &data[n..];
+            if *fill == capacity {
+                self.write_sealed(keys, HANDSHAKE, capacity).await?;
+                *fill = 0;
+            }
+        }
+        Ok(())
+    }
+
+    /// 向对端发送致命告警 (尽力而为) 并返回原错误
+    async fn abort(&mut self, keys: Option<&mut TrafficKeys>, error: TlsError) -> TlsError {
+        if let Some(description) = error.alert() {
+            self.tx[HEADER_LEN] = 2;
+            self.tx[HEADER_LEN + 1] = description;
+            let _ = match keys {
+                Some(keys) => self.write_sealed(keys, ALERT, 2).await,
+                None => self.write_plain(ALERT, 2).await,
+            };
+            let _ = self.io.flush().await;
+        }
+        error
+    }
+}
+
+// ===== 握手 =====
+
+/// 当前握手摘要
+fn transcript_hash(transcript: &Sha256) -> [u8; HASH_LEN] {
+    transcript.clone().finalize().into()
+}
+
+/// 生成临时密钥并计算共享秘密，返回 (本端公钥, 共享秘密)
+fn key_exchange(
+    group: NamedGroup,
+    peer: &[u8],
+    entropy: &mut impl Entropy,
+) -> Result<([u8; 65], [u8; HASH_LEN]), TlsError> {
+    let mut public = [0u8; 65];
+    let mut secret = [0u8; 32];
+    let mut shared = [0u8; HASH_LEN];
+    match group {
+        NamedGroup::X25519 => {
+            entropy.fill(&mut secret);
+            let ours = x25519_dalek::StaticSecret::from(secret);
+            let mut theirs = [0u8; 32];
+            theirs.copy_from_slice(peer);
+            let dh = ours.diffie_hellman(&x25519_dalek::PublicKey::from(theirs));
+            // 拒绝小子群点 (共享秘密全零)
+            if !dh.was_contributory() {
+                return Err(TlsError::IllegalParameter);
+            }
+            public[..32].copy_from_slice(x25519_dalek::PublicKey::from(&ours).as_bytes());
+            shared.copy_from_slice(dh.as_bytes());
+        }
+        NamedGroup::Secp256r1 => {
+            let theirs = p256::PublicKey::from_sec1_bytes(peer).map_err(|_| TlsError::IllegalParameter)?;
+            let ours = loop {
+                entropy.fill(&mut secret);
+                if let Ok(key) = p256::SecretKey::from_slice(&secret) {
+                    break key;
+                }
+            };
+            let dh = p256::ecdh::diffie_hellman(ours.to_nonzero_scalar(), theirs.as_affine());
+            public.copy_from_slice(ours.public_key().to_encoded_point(false).as_bytes());
+            shared.copy_from_slice(dh.raw_secret_bytes());
+        }
+    }
+    secret.fill(0);
+    Ok((public, shared))
+}
+
+/// 应用流量秘密 (客户端, 服务端)
+type ApplicationSecrets = ([u8; HASH_LEN], [u8; HASH_LEN]);
+
+/// 服务端完整握手
+async fn handshake<T: Read + Write>(
+    rec: &mut Records<'_, T>,
+    identity: &TlsIdentity<'_>,
+    entropy: &mut impl Entropy,
+) -> Result<ApplicationSecrets, TlsError> {
+    // ----- ClientHello -----
+    let hello_len = match rec.read_client_hello().await {
+        Ok(n) => n,
+        Err(e) => return Err(rec.abort(None, e).await),
+    };
+    let mut transcript = Sha256::new();
+    transcript.update(&rec.rx[..hello_len]);
+
+    let mut session_id = [0u8; 32];
+    let (session_id_len, group, exchange) = match handshake::parse_client_hello(&rec.rx[4..hello_len]) {
+        Ok(hello) => {
+            session_id[..hello.session_id.len()].copy_from_slice(hello.session_id);
+            (hello.session_id.len(), hello.group, key_exchange(hello.group, hello.key_share, entropy))
+        }
+        Err(e) => return Err(rec.abort(None, e).await),
+    };
+    let (public, shared) = match exchange {
+        Ok(v) => v,
+        Err(e) => return Err(rec.abort(None, e).await),
+    };
+
+    // ----- ServerHello (明文) -----
+    let mut random = [0u8; 32];
+    entropy.fill(&mut random);
+    let public = &public[..group.key_len()];
+    let len = handshake::server_hello(&mut rec.tx[HEADER_LEN..], &random, &session_id[..session_id_len], group, public)?;
+    transcript.update(&rec.tx[HEADER_LEN..HEADER_LEN + len]);
+    rec.write_plain(HANDSHAKE, len).await?;
+    if session_id_len > 0 {
+        // 中间设备兼容模式 (RFC 8446 §D.4)
+        rec.tx[HEADER_LEN] = 1;
+        rec.write_plain(CHANGE_CIPHER_SPEC, 1).await?;
+    }
+
+    let secrets = HandshakeSecrets::derive(&shared, &transcript_hash(&transcript));
+    let mut write = TrafficKeys::new(&secrets.server);
+    let mut read = TrafficKeys::new(&secrets.client);
+
+    // ----- EncryptedExtensions .. Finished (加密) -----
+    if let Err(e) = server_flight(rec, identity, &secrets, &mut write, &mut transcript).await {
+        return Err(rec.abort(Some(&mut write), e).await);
+    }
+    let hash = transcript_hash(&transcript);
+    let application = secrets.application(&hash);
+    let expected = keys::finished_mac(&secrets.client, &hash);
+
+    // ----- 客户端 Finished -----
+    let verified = match rec.read_sealed(&mut read, true).await {
+        Ok((HANDSHAKE, len)) => match handshake::split_message(&rec.rx[..len]) {
+            Some((handshake::FINISHED, body, total)) if total == len => {
+                if constant_time_eq(body, &expected) {
+                    Ok(())
+                } else {
+                    Err(TlsError::DecryptError)
+                }
+            }
+            _ => Err(TlsError::UnexpectedMessage),
+        },
+        Ok(_) => Err(TlsError::UnexpectedMessage),
+        Err(e) => Err(e),
+    };
+    match verified {
+        Ok(()) => Ok(application),
+        Err(e) => Err(rec.abort(Some(&mut write), e).await),
+    }
+}
+
+/// 发送 EncryptedExtensions、Certificate、CertificateVerify 与 Finished
+async fn server_flight<T: Read + Write>(
+    rec: &mut Records<'_, T>,
+    identity: &TlsIdentity<'_>,
+    secrets: &HandshakeSecrets,
+    keys: &mut TrafficKeys,
+    transcript: &mut Sha256,
+) -> Result<(), TlsError> {
+    let mut fill = 0;
+    let u24 = |n: usize| [(n >> 16) as u8, (n >> 8) as u8, n as u8];
+
+    rec.push(&mut fill, keys, transcript, &handshake::message_header(handshake::ENCRYPTED_EXTENSIONS, 2))
+        .await?;
+    rec.push(&mut fill, keys, transcript, &[0, 0]).await?;
+
+    // Certificate: 空上下文 | u24 列表长度 | {u24 长度, DER, 空扩展}*
+    let list_len: usize = identity.certificates().map(|c| 3 + c.len() + 2).sum();
+    rec.push(&mut fill, keys, transcript, &handshake::message_header(handshake::CERTIFICATE, 1 + 3 + list_len))
+        .await?;
+    rec.push(&mut fill, keys, transcript, &[0]).await?;
+    rec.push(&mut fill, keys, transcript, &u24(list_len)).await?;
+    for certificate in identity.certificates() {
+        rec.push(&mut fill, keys, transcript, &u24(certificate.len())).await?;
+        rec.push(&mut fill, keys, transcript, certificate).await?;
+        rec.push(&mut fill, keys, transcript, &[0, 0]).await?;
+    }
+
+    // CertificateVerify
+    let content = handshake::certificate_verify_content(&transcript_hash(transcript));
+    let signature: Signature = identity.signing_key().sign(&content);
+    let mut der = [0u8; cert::MAX_DER_SIGNATURE_LEN];
+    let der_len = cert::signature_der(&signature, &mut der);
+    rec.push(&mut fill, keys, transcript, &handshake::message_header(handshake::CERTIFICATE_VERIFY, 4 + der_len))
+        .await?;
+    let scheme = handshake::SIG_ECDSA_P256_SHA256.to_be_bytes();
+    rec.push(&mut fill, keys, transcript, &[scheme[0], scheme[1], 0, der_len as u8]).await?;
+    rec.push(&mut fill, keys, transcript, &der[..der_len]).await?;
+
+    // Finished
+    let verify_data = keys::finished_mac(&secrets.server, &transcript_hash(transcript));
+    rec.push(&mut fill, keys, transcript, &handshake::message_header(handshake::FINISHED, HASH_LEN)).await?;
+    rec.push(&mut fill, keys, transcript, &verify_data).await?;
+
+    if fill > 0 {
+        rec.write_sealed(keys, HANDSHAKE, fill).await?;
+    }
+    rec.io.flush().await.map_err(|_| TlsError::Io)
+}
+
+/// 在已建立的连接上完成 TLS 服务端握手
+///
+/// `rx` 至少应能容纳对端最大记录 (推荐 `RX_BUFFER_LEN`)，`tx` 决定本端记录大小
+/// (推荐 `TX_BUFFER_LEN`，不小于 `MIN_TX_BUFFER_LEN`)。握手本身不设超时，
+/// 调用方应使用 `embassy_time::with_timeout` 包裹。
+pub async fn accept<'b, T: Read + Write>(
+    io: T,
+    identity: &TlsIdentity<'_>,
+    entropy: &mut impl Entropy,
+    rx: &'b mut [u8],
+    tx: &'b mut [u8],
+) -> Result<TlsStream<'b, T>, TlsError> {
+    if tx.len() < MIN_TX_BUFFER_LEN {
+        return Err(TlsError::BufferTooSmall);
+    }
+    let mut records = Records { io, rx, tx };
+    let (client, server) = handshake(&mut records, identity, entropy).await?;
+    Ok(TlsStream {
+        records,
+        read: TrafficKeys::new(&client),
+        write: TrafficKeys::new(&server),
+        read_secret: client,
+        write_secret: server,
+        pending: (0, 0),
+        peer_closed: false,
+    })
+}
+
+// ===== 加密连接 =====
+
+/// 已完成握手的 TLS 连接
+pub struct TlsStream<'b, T> {
+    records: Records<'b, T>,
+    read: TrafficKeys,
+    write: TrafficKeys,
+    read_secret: [u8; HASH_LEN],
+    write_secret: [u8; HASH_LEN],
+    /// 未读完的应用数据 (`rx` 中的区间)
+    pending: (usize, usize),
+    peer_closed: bool,
+}
+
+impl<T: Read + Write> TlsStream<'_, T> {
+    /// 发送 close_notify 并冲刷底层连接
+    pub async fn close(&mut self) -> Result<(), TlsError> {
+        self.records.tx[HEADER_LEN] = 1;
+        self.records.tx[HEADER_LEN + 1] = CLOSE_NOTIFY;
+        self.records.write_sealed(&mut self.write, ALERT, 2).await?;
+        self.records.io.flush().await.map_err(|_| TlsError::Io)
+    }
+
+    /// 对端是否已发送 close_notify
+    pub fn peer_closed(&self) -> bool {
+        self.peer_closed
+    }
+
+    /// 处理握手后消息 (仅 KeyUpdate)
+    async fn post_handshake(&mut self, len: usize) -> Result<(), TlsError> {
+        let (kind, body, total) = handshake::split_message(&self.records.rx[..len]).ok_or(TlsError::Decode)?;
+        // KeyUpdate 之后的数据使用新密钥，必须是记录中的最后一条消息
+        if kind != handshake::KEY_UPDATE || total != len || body.len() != 1 {
+            return Err(TlsError::UnexpectedMessage);
+        }
+        let update_requested = match body[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(TlsError::IllegalParameter),
+        };
+
+        self.read_secret = keys::next_secret(&self.read_secret);
+        self.read = TrafficKeys::new(&self.read_secret);
+        if update_requested {
+            let message = handshake::message_header(handshake::KEY_UPDATE, 1);
+            self.records.tx[HEADER_LEN..HEADER_LEN + 4].copy_from_slice(&message);
+            self.records.tx[HEADER_LEN + 4] = 0;
+            self.records.write_sealed(&mut self.write, HANDSHAKE, 5).await?;
+            self.write_secret = keys::next_secret(&self.write_secret);
+            self.write = TrafficKeys::new(&self.write_secret);
+        }
+        Ok(())
+    }
+}
+
+impl<T> ErrorType for TlsStream<'_, T> {
+    type Error = TlsError;
+}
+
+impl<T: Read + Write> Read for TlsStream<'_, T> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, TlsError> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            let (start, end) = self.pending;
+            if start < end {
+                let n = (end - start).min(buf.len());
+                buf[..n].copy_from_slice(&self.records.rx[start..start + n]);
+                self.pending.0 += n;
+                return Ok(n);
+            }
+            if self.peer_closed {
+                return Ok(0);
+            }
+
+            let (kind, len) = self.records.read_sealed(&mut self.read, false).await?;
+            match kind {
+                APPLICATION_DATA => self.pending = (0, len),
+                ALERT if len == 2 && self.records.rx[1] == CLOSE_NOTIFY => self.peer_closed = true,
+                ALERT if len == 2 => return Err(TlsError::Alert(self.records.rx[1])),
+                HANDSHAKE => self.post_handshake(len).await?,
+                _ => return Err(TlsError::UnexpectedMessage),
+            }
+        }
+    }
+}
+
+impl<T: Read + Write> Write for TlsStream<'_, T> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, TlsError> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let n = buf.len().min(self.records.capacity());
+        self.records.tx[HEADER_LEN..HEADER_LEN + n].copy_from_slice(&buf[..n]);
+        self.records.write_sealed(&mut self.write, APPLICATION_DATA, n).await?;
+        Ok(n)
+    }
+
+    async fn flush(&mut self) -> Result<(), TlsError> {
+        self.records.io.flush().await.map_err(|_| TlsError::Io)
+    }
+}
@@ -0,0 +1,410 @@
+//! 网络队列与缓冲区调优
+//!
+//! 所有队列深度与缓冲区大小集中在 `Tuning` 中，编译期通过 feature 选择预设:
+//! - 默认: `Tuning::DEFAULT`
+//! - `tuning-low-memory`: `Tuning::LOW_MEMORY` (单连接、小缓冲区)
+//! - `tuning-throughput`: `Tuning::THROUGHPUT` (大 TCP 窗口、更深的事件队列)
+//!
+//! `net::config` 中的 `WIFI_EVENT_QUEUE_SIZE`、`TCP_RX_BUFFER_SIZE` 等常量均取自
+//! `TUNING`，修改预设即可统一调整。
+//!
+//! 各项的内存开销:
+//!
+//! | 项 | 开销 |
+//! |----|------|
+//! | `wifi_event_queue` | 深度 × `Envelope<WifiEvent>` |
+//! | `wifi_scan_results` | 数量 × `ScanResult` |
+//! | `ble_event_queue` | 深度 × `Envelope<BleEvent>` |
+//! | `ble_connections` | 数量 × (`ConnectionInfo` + `GattContext`) |
+//! | `tcp_rx_buffer` / `tcp_tx_buffer` | 大小 × `tcp_sockets` |
+//! | `udp_rx_buffer` / `udp_tx_buffer` | 大小 × `udp_sockets` |
+//! | `net_buffer_pool` | 数量 × `net_buffer_size` |
+//!
+//! 运行时各处通过 `record` 上报实际用量，`report()` 给出配置值、峰值与内存开销，
+//! 用于判断预设是否过大或过小。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::net::tuning;
+//!
+//! // 应用层 (embassy-net socket) 上报接收缓冲区水位
+//! tuning::record(tuning::Knob::TcpRxBuffer, socket.recv_queue());
+//!
+//! for row in tuning::report().rows() {
+//!     log_info!("{}: {} / peak {:?} ({} bytes)", row.knob.name(), row.configured, row.peak, row.bytes);
+//! }
+//! ```
+
+use core::fmt;
+
+use portable_atomic::{AtomicUsize, Ordering};
+
+use crate::util::fmt::Bytes;
+
+#[cfg(all(feature = "tuning-low-memory", feature = "tuning-throughput"))]
+compile_error!("features `tuning-low-memory` and `tuning-throughput` are mutually exclusive");
+
+/// 队列深度与缓冲区大小
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tuning {
+    /// WiFi 事件队列深度
+    pub wifi_event_queue: usize,
+    /// WiFi 扫描结果最大数量
+    pub wifi_scan_results: usize,
+    /// BLE 事件队列深度
+    pub ble_event_queue: usize,
+    /// BLE 最大连接数
+    pub ble_connections: usize,
+    /// 每个 TCP socket 的接收缓冲区 (字节)
+    pub tcp_rx_buffer: usize,
+    /// 每个 TCP socket 的发送缓冲区 (字节)
+    pub tcp_tx_buffer: usize,
+    /// 每个 UDP socket 的接收缓冲区 (字节)
+    pub udp_rx_buffer: usize,
+    /// 每个 UDP socket 的发送缓冲区 (字节)
+    pub udp_tx_buffer: usize,
+    /// 最大 TCP socket 数
+    pub tcp_sockets: usize,
+    /// 最大 UDP socket 数
+    pub udp_sockets: usize,
+    /// 网络缓冲区池数量
+    pub net_buffer_pool: usize,
+    /// 单个网络缓冲区大小 (字节)
+    pub net_buffer_size: usize,
+}
+
+impl Tuning {
+    /// 默认预设
+    pub const DEFAULT: Self = Self {
+        wifi_event_queue: 8,
+        wifi_scan_results: 16,
+        ble_event_queue: 8,
+        ble_connections: 3,
+        tcp_rx_buffer: 4096,
+        tcp_tx_buffer: 4096,
+        udp_rx_buffer: 2048,
+        udp_tx_buffer: 2048,
+        tcp_sockets: 4,
+        udp_sockets: 4,
+        net_buffer_pool: 16,
+        net_buffer_size: 1536,
+    };
+
+    /// 低内存预设
+    pub const LOW_MEMORY: Self = Self {
+        wifi_event_queue: 4,
+        wifi_scan_results: 8,
+        ble_event_queue: 4,
+        ble_connections: 1,
+        tcp_rx_buffer: 1536,
+        tcp_tx_buffer: 1536,
+        udp_rx_buffer: 1024,
+        udp_tx_buffer: 1024,
+        tcp_sockets: 2,
+        udp_sockets: 2,
+        net_buffer_pool: 8,
+        net_buffer_size: 1536,
+    };
+
+    /// 高吞吐预设
+    pub const THROUGHPUT: Self = Self {
+        wifi_event_queue: 16,
+        wifi_scan_results: 16,
+        ble_event_queue: 16,
+        ble_connections: 3,
+        tcp_rx_buffer: 16384,
+        tcp_tx_buffer: 16384,
+        udp_rx_buffer: 4096,
+        udp_tx_buffer: 4096,
+        tcp_sockets: 4,
+        udp_sockets: 4,
+        net_buffer_pool: 32,
+        net_buffer_size: 1536,
+    };
+
+    /// 按 feature 选择的预设
+    pub const fn selected() -> Self {
+        if cfg!(feature = "tuning-low-memory") {
+            Self::LOW_MEMORY
+        } else if cfg!(feature = "tuning-throughput") {
+            Self::THROUGHPUT
+        } else {
+            Self::DEFAULT
+        }
+    }
+
+    /// 预设名称
+    pub const fn preset_name() -> &'static str {
+        if cfg!(feature = "tuning-low-memory") {
+            "low-memory"
+        } else if cfg!(feature = "tuning-throughput") {
+            "throughput"
+        } else {
+            "default"
+        }
+    }
+
+    /// 某一项的配置值
+    pub const fn get(&self, knob: Knob) -> usize {
+        match knob {
+            Knob::WifiEventQueue => self.wifi_event_queue,
+            Knob::WifiScanResults => self.wifi_scan_results,
+            Knob::BleEventQueue => self.ble_event_queue,
+            Knob::BleConnections => self.ble_connections,
+            Knob::TcpRxBuffer => self.tcp_rx_buffer,
+            Knob::TcpTxBuffer => self.tcp_tx_buffer,
+            Knob::UdpRxBuffer => self.udp_rx_buffer,
+            Knob::UdpTxBuffer => self.udp_tx_buffer,
+            Knob::TcpSockets => self.tcp_sockets,
+            Knob::UdpSockets => self.udp_sockets,
+            Knob::NetBufferPool => self.net_buffer_pool,
+        }
+    }
+
+    /// 某一项占用的静态内存 (字节)
+    ///
+    /// socket 数量本身不计开销，其缓冲区计入对应的缓冲区项
+    pub const fn bytes(&self, knob: Knob) -> usize {
+        match knob {
+            Knob::WifiEventQueue => self.wifi_event_queue * WIFI_EVENT_BYTES,
+            Knob::WifiScanResults => self.wifi_scan_results * SCAN_RESULT_BYTES,
+            Knob::BleEventQueue => self.ble_event_queue * BLE_EVENT_BYTES,
+            Knob::BleConnections => self.ble_connections * BLE_CONNECTION_BYTES,
+            Knob::TcpRxBuffer => self.tcp_rx_buffer * self.tcp_sockets,
+            Knob::TcpTxBuffer => self.tcp_tx_buffer * self.tcp_sockets,
+            Knob::UdpRxBuffer => self.udp_rx_buffer * self.udp_sockets,
+            Knob::UdpTxBuffer => self.udp_tx_buffer * self.udp_sockets,
+            Knob::TcpSockets | Knob::UdpSockets => 0,
+            Knob::NetBufferPool => self.net_buffer_pool * self.net_buffer_size,
+        }
+    }
+
+    /// 全部项的内存开销合计
+    pub const fn total_bytes(&self) -> usize {
+        let mut total = 0;
+        let mut i = 0;
+        while i < Knob::ALL.len() {
+            total += self.bytes(Knob::ALL[i]);
+            i += 1;
+        }
+        total
+    }
+}
+
+/// 当前构建使用的调优参数
+pub const TUNING: Tuning = Tuning::selected();
+
+// ===== 单元开销 (未启用的子系统计 0) =====
+
+#[cfg(feature = "wifi")]
+const WIFI_EVENT_BYTES: usize = core::mem::size_of::<crate::sync::bus::Envelope<super::wifi::WifiEvent>>();
+#[cfg(not(feature = "wifi"))]
+const WIFI_EVENT_BYTES: usize = 0;
+
+#[cfg(feature = "wifi")]
+const SCAN_RESULT_BYTES: usize = core::mem::size_of::<super::wifi::ScanResult>();
+#[cfg(not(feature = "wifi"))]
+const SCAN_RESULT_BYTES: usize = 0;
+
+#[cfg(any(feature = "ble", feature = "ble-esp"))]
+const BLE_EVENT_BYTES: usize = core::mem::size_of::<crate::sync::bus::Envelope<super::ble::BleEvent>>();
+#[cfg(not(any(feature = "ble", feature = "ble-esp")))]
+const BLE_EVENT_BYTES: usize = 0;
+
+#[cfg(any(feature = "ble", feature = "ble-esp"))]
+const BLE_CONNECTION_BYTES: usize = core::mem::size_of::<super::ble::Connection>();
+#[cfg(not(any(feature = "ble", feature = "ble-esp")))]
+const BLE_CONNECTION_BYTES: usize = 0;
+
+// ===== 调优项 =====
+
+/// 调优项
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Knob {
+    /// WiFi 事件队列深度
+    WifiEventQueue,
+    /// WiFi 扫描结果数量
+    WifiScanResults,
+    /// BLE 事件队列深度
+    BleEventQueue,
+    /// BLE 连接数
+    BleConnections,
+    /// TCP 接收缓冲区
+    TcpRxBuffer,
+    /// TCP 发送缓冲区
+    TcpTxBuffer,
+    /// UDP 接收缓冲区
+    UdpRxBuffer,
+    /// UDP 发送缓冲区
+    UdpTxBuffer,
+    /// TCP socket 数
+    TcpSockets,
+    /// UDP socket 数
+    UdpSockets,
+    /// 网络缓冲区池
+    NetBufferPool,
+}
+
+impl Knob {
+    /// 全部调优项
+    pub const ALL: [Knob; 11] = [
+        Knob::WifiEventQueue,
+        Knob::WifiScanResults,
+        Knob::BleEventQueue,
+        Knob::BleConnections,
+        Knob::TcpRxBuffer,
+        Knob::TcpTxBuffer,
+        Knob::UdpRxBuffer,
+        Knob::UdpTxBuffer,
+        Knob::TcpSockets,
+        Knob::UdpSockets,
+        Knob::NetBufferPool,
+    ];
+
+    /// 名称 (与 `Tuning` 字段名一致)
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::WifiEventQueue => "wifi_event_queue",
+            Self::WifiScanResults => "wifi_scan_results",
+            Self::BleEventQueue => "ble_event_queue",
+            Self::BleConnections => "ble_connections",
+            Self::TcpRxBuffer => "tcp_rx_buffer",
+            Self::TcpTxBuffer => "tcp_tx_buffer",
+            Self::UdpRxBuffer => "udp_rx_buffer",
+            Self::UdpTxBuffer => "udp_tx_buffer",
+            Self::TcpSockets => "tcp_sockets",
+            Self::UdpSockets => "udp_sockets",
+            Self::NetBufferPool => "net_buffer_pool",
+        }
+    }
+
+    const fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+// ===== 峰值用量 =====
+
+/// 峰值用量 (`usize::MAX` 表示从未上报)
+static PEAKS: [AtomicUsize; Knob::ALL.len()] = [const { AtomicUsize::new(usize::MAX) }; Knob::ALL.len()];
+
+/// 上报某一项的当前用量 (队列长度、已用字节、活动连接数)
+#[inline]
+pub fn record(knob: Knob, used: usize) {
+    let peak = &PEAKS[knob.index()];
+    // 首次上报替换哨兵值，之后取最大值
+    let _ = peak.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |current| {
+        (current == usize::MAX || used > current).then_some(used)
+    });
+}
+
+/// 某一项的峰值用量 (从未上报时为 `None`)
+pub fn peak(knob: Knob) -> Option<usize> {
+    let peak = PEAKS[knob.index()].load(Ordering::Relaxed);
+    (peak != usize::MAX).then_some(peak)
+}
+
+/// 清除峰值记录
+pub fn reset_peaks() {
+    PEAKS.iter().for_each(|peak| peak.store(usize::MAX, Ordering::Relaxed));
+}
+
+// ===== 报告 =====
+
+/// 报告中的一行
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReportRow {
+    /// 调优项
+    pub knob: Knob,
+    /// 配置值
+    pub configured: usize,
+    /// 峰值用量
+    pub peak: Option<usize>,
+    /// 静态内存开销 (字节)
+    pub bytes: usize,
+}
+
+impl ReportRow {
+    /// 峰值是否达到配置上限 (可能发生过丢弃或阻塞)
+    pub fn saturated(&self) -> bool {
+        self.peak.is_some_and(|peak| peak >= self.configured)
+    }
+}
+
+/// 配置与峰值用量报告
+#[derive(Debug, Clone, Copy)]
+pub struct TuningReport {
+    tuning: Tuning,
+    peaks: [Option<usize>; Knob::ALL.len()],
+}
+
+impl TuningReport {
+    /// 各项明细
+    pub fn rows(&self) -> impl Iterator<Item = ReportRow> + '_ {
+        Knob::ALL.iter().map(|&knob| ReportRow {
+            knob,
+            configured: self.tuning.get(knob),
+            peak: self.peaks[knob.index()],
+            bytes: self.tuning.bytes(knob),
+        })
+    }
+
+    /// 内存开销合计
+    pub fn total_bytes(&self) -> usize {
+        self.tuning.total_bytes()
+    }
+}
+
+impl fmt::Display for TuningReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "tuning: {} ({})", Tuning::preset_name(), Bytes(self.total_bytes() as u64))?;
+        for row in self.rows() {
+            write!(f, "  {:<18} {:>6}", row.knob.name(), row.configured)?;
+            match row.peak {
+                Some(peak) => write!(f, "  peak {:>6}", peak)?,
+                None => write!(f, "  peak {:>6}", "-")?,
+            }
+            writeln!(f, "  {}{}", Bytes(row.bytes as u64), if row.saturated() { "  (saturated)" } else { "" })?;
+        }
+        Ok(())
+    }
+}
+
+/// 生成当前配置与峰值用量报告
+pub fn report() -> TuningReport {
+    report_for(TUNING)
+}
+
+fn report_for(tuning: Tuning) -> TuningReport {
+    let mut peaks = [None; Knob::ALL.len()];
+    for knob in Knob::ALL {
+        peaks[knob.index()] = peak(knob);
+    }
+    TuningReport { tuning, peaks }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_and_report() {
+        assert_eq!(Tuning::DEFAULT.bytes(Knob::TcpRxBuffer), 4096 * 4);
+        assert_eq!(Tuning::DEFAULT.bytes(Knob::NetBufferPool), 16 * 1536);
+        assert!(Tuning::LOW_MEMORY.total_bytes() < Tuning::DEFAULT.total_bytes());
+        assert!(Tuning::DEFAULT.total_bytes() < Tuning::THROUGHPUT.total_bytes());
+
+        reset_peaks();
+        record(Knob::WifiEventQueue, 3);
+        record(Knob::WifiEventQueue, 0);
+        record(Knob::BleConnections, 0);
+        record(Knob::BleConnections, Tuning::DEFAULT.ble_connections);
+        let report = report_for(Tuning::DEFAULT);
+        let row = |knob: Knob| report.rows().find(|r| r.knob == knob).unwrap();
+        assert_eq!(row(Knob::WifiEventQueue).peak, Some(3));
+        assert!(!row(Knob::WifiEventQueue).saturated());
+        assert!(row(Knob::BleConnections).saturated());
+        assert_eq!(row(Knob::TcpSockets).peak, None);
+    }
+}
@@ -5,10 +5,10 @@
 //! # 功能
 //!
 //! - WiFi 网络扫描
-//! - STA 模式连接到 AP
-//! - AP 模式创建热点
+//! - STA 模式连接到 AP (WPA2-Personal / WPA2-Enterprise)
+//! - AP 模式创建热点 (SoftAP，接入的客户端由 `net::dhcp_server` 分配地址)
 //! - 连接状态监控
-//! - 自动重连
+//! - 自动重连 (指数退避，见 `net::reconnect`)
 //!
 //! # 示例
 //!
@@ -32,6 +32,11 @@ use embassy_time::{Duration, Timer};
 use heapless::{String, Vec};
 
 use super::config::*;
+use super::regdomain::RegDomain;
+use super::tuning::{self, Knob};
+use crate::power::lifecycle::{DriverLifecycle, LifecycleError, SleepKind};
+use crate::power::peripherals::{self, KeepAlive, Peripheral};
+use crate::sync::bus::Envelope;
 
 // ===== 错误类型 =====
 
@@ -60,6 +65,8 @@ pub enum WifiError {
     OutOfMemory,
     /// 不支持的操作
     Unsupported,
+    /// 当前状态不允许该操作 (如启动后修改国家码)
+    InvalidState,
 }
 
 impl fmt::Display for WifiError {
@@ -76,6 +83,7 @@ impl fmt::Display for WifiError {
             Self::ScanFailed => write!(f, "Scan failed"),
             Self::OutOfMemory => write!(f, "Out of memory"),
             Self::Unsupported => write!(f, "Unsupported operation"),
+            Self::InvalidState => write!(f, "Invalid state for operation"),
         }
     }
 }
@@ -134,6 +142,9 @@ pub enum WifiEvent {
     },
 }
 
+/// WiFi 事件通道 (事件附带序号、时间戳与来源)
+pub type WifiEventChannel = Channel<CriticalSectionRawMutex, Envelope<WifiEvent>, WIFI_EVENT_QUEUE_SIZE>;
+
 /// 断开连接原因
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DisconnectReason {
@@ -214,6 +225,145 @@ pub enum AuthMode {
     Enterprise,
 }
 
+impl From<esp_radio::wifi::AuthMethod> for AuthMode {
+    fn from(method: esp_radio::wifi::AuthMethod) -> Self {
+        use esp_radio::wifi::AuthMethod;
+        match method {
+            AuthMethod::None => AuthMode::Open,
+            AuthMethod::Wep => AuthMode::Wep,
+            AuthMethod::Wpa => AuthMode::WpaPsk,
+            AuthMethod::Wpa2Personal => AuthMode::Wpa2Psk,
+            AuthMethod::WpaWpa2Personal => AuthMode::WpaWpa2Psk,
+            AuthMethod::Wpa3Personal => AuthMode::Wpa3Psk,
+            AuthMethod::Wpa2Wpa3Personal => AuthMode::Wpa2Wpa3Psk,
+            _ => AuthMode::Enterprise,
+        }
+    }
+}
+
+// ===== 扫描配置 =====
+
+/// 扫描方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanType {
+    /// 主动扫描 (发送探测请求)，每信道驻留 `min_dwell_ms`..`max_dwell_ms`
+    Active {
+        /// 最短驻留时间 (毫秒)
+        min_dwell_ms: u32,
+        /// 最长驻留时间 (毫秒)
+        max_dwell_ms: u32,
+    },
+    /// 被动扫描 (仅监听信标)，每信道驻留 `dwell_ms`
+    Passive {
+        /// 驻留时间 (毫秒)
+        dwell_ms: u32,
+    },
+}
+
+impl Default for ScanType {
+    fn default() -> Self {
+        ScanType::Active { min_dwell_ms: 10, max_dwell_ms: 20 }
+    }
+}
+
+/// 扫描配置
+#[derive(Debug, Clone, Default)]
+pub struct ScanConfig {
+    /// 扫描方式
+    pub scan_type: ScanType,
+    /// 仅扫描指定信道 (None = 全部信道)
+    pub channel: Option<u8>,
+    /// 仅保留指定 SSID
+    pub ssid: Option<String<32>>,
+    /// 是否保留隐藏网络 (空 SSID)
+    pub show_hidden: bool,
+    /// 最低信号强度 (dBm)
+    pub min_rssi: Option<i8>,
+}
+
+impl ScanConfig {
+    /// 设置扫描方式
+    pub fn with_scan_type(mut self, scan_type: ScanType) -> Self {
+        self.scan_type = scan_type;
+        self
+    }
+
+    /// 被动扫描，每信道驻留指定时间
+    pub fn passive(self, dwell_ms: u32) -> Self {
+        self.with_scan_type(ScanType::Passive { dwell_ms })
+    }
+
+    /// 设置信道
+    pub fn with_channel(mut self, channel: u8) -> Self {
+        self.channel = Some(channel);
+        self
+    }
+
+    /// 设置 SSID 过滤
+    pub fn with_ssid(mut self, ssid: &str) -> Self {
+        self.ssid = String::try_from(ssid).ok();
+        self
+    }
+
+    /// 设置是否保留隐藏网络
+    pub fn with_show_hidden(mut self, show: bool) -> Self {
+        self.show_hidden = show;
+        self
+    }
+
+    /// 设置最低信号强度
+    pub fn with_min_rssi(mut self, rssi: i8) -> Self {
+        self.min_rssi = Some(rssi);
+        self
+    }
+
+    /// 结果是否通过过滤条件
+    pub fn accepts(&self, result: &ScanResult) -> bool {
+        if result.ssid.is_empty() && !self.show_hidden {
+            return false;
+        }
+        if let Some(ssid) = &self.ssid {
+            if result.ssid != *ssid {
+                return false;
+            }
+        }
+        if let Some(channel) = self.channel {
+            if result.channel != channel {
+                return false;
+            }
+        }
+        self.min_rssi.is_none_or(|min| result.rssi >= min)
+    }
+}
+
+/// 合并一条扫描结果
+///
+/// 相同 BSSID 只保留信号最强的一条 (SSID 为空时用已知名称补全)；
+/// 列表已满时替换信号最弱且弱于新结果的条目。
+pub fn merge_scan_result<const N: usize>(results: &mut Vec<ScanResult, N>, result: ScanResult) {
+    if let Some(existing) = results.iter_mut().find(|r| r.bssid == result.bssid) {
+        let known_ssid = if result.ssid.is_empty() { existing.ssid.clone() } else { result.ssid.clone() };
+        if result.rssi > existing.rssi {
+            *existing = result;
+        }
+        existing.ssid = known_ssid;
+        return;
+    }
+
+    if let Err(result) = results.push(result) {
+        if let Some(weakest) = results.iter_mut().min_by_key(|r| r.rssi) {
+            if result.rssi > weakest.rssi {
+                *weakest = result;
+            }
+        }
+    }
+}
+
+/// 按信号强度降序排序
+pub fn sort_by_rssi(results: &mut [ScanResult]) {
+    results.sort_unstable_by_key(|r| core::cmp::Reverse(r.rssi));
+}
+
 // ===== WiFi 状态 =====
 
 /// WiFi 连接状态
@@ -238,6 +388,114 @@ pub enum WifiState {
     Disconnected,
 }
 
+// ===== 企业级认证 =====
+
+/// EAP 认证方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EapMethod {
+    /// PEAP (MSCHAPv2 内层认证)
+    #[default]
+    Peap,
+    /// EAP-TTLS (MSCHAPv2 内层认证)
+    Ttls,
+}
+
+/// WPA2-Enterprise 配置
+///
+/// CA 证书用于校验认证服务器；未提供时不校验 (仅建议在测试网络中使用)。
+/// PEM 格式证书需包含结尾的 `\0` (mbedtls 要求)，例如:
+///
+/// ```ignore
+/// // ca.pem 末尾追加一个 NUL 字节: `printf '\0' >> ca.pem`
+/// static CA: &[u8] = include_bytes!("ca.pem");
+///
+/// let enterprise = EnterpriseConfig::new("alice", "secret")
+///     .with_identity("anonymous@corp.example")
+///     .with_ca_cert(CA);
+/// wifi.connect_enterprise("CorpNet", enterprise).await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct EnterpriseConfig {
+    /// 外层身份 (明文发送，默认与用户名相同)
+    pub identity: String<64>,
+    /// 用户名 (隧道内发送)
+    pub username: String<64>,
+    /// 密码
+    pub password: String<64>,
+    /// 认证服务器 CA 证书 (PEM 或 DER)
+    pub ca_cert: Option<&'static [u8]>,
+    /// EAP 方法
+    pub method: EapMethod,
+}
+
+impl EnterpriseConfig {
+    /// 使用用户名和密码创建 (外层身份取用户名)
+    pub fn new(username: &str, password: &str) -> Self {
+        Self {
+            identity: String::try_from(username).unwrap_or_default(),
+            username: String::try_from(username).unwrap_or_default(),
+            password: String::try_from(password).unwrap_or_default(),
+            ca_cert: None,
+            method: EapMethod::Peap,
+        }
+    }
+
+    /// 设置外层身份 (如 `anonymous@realm`，避免明文暴露用户名)
+    pub fn with_identity(mut self, identity: &str) -> Self {
+        self.identity.clear();
+        let _ = self.identity.push_str(identity);
+        self
+    }
+
+    /// 设置认证服务器 CA 证书
+    pub fn with_ca_cert(mut self, ca_cert: &'static [u8]) -> Self {
+        self.ca_cert = Some(ca_cert);
+        self
+    }
+
+    /// 设置 EAP 方法
+    pub fn with_method(mut self, method: EapMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// 检查配置是否完整
+    ///
+    /// 用户名、密码与外层身份不能为空 (超长会在创建时被截为空)；
+    /// PEM 证书必须以 `\0` 结尾，DER 证书必须以 SEQUENCE 标签开头。
+    pub fn validate(&self) -> Result<(), WifiError> {
+        if self.identity.is_empty() || self.username.is_empty() || self.password.is_empty() {
+            return Err(WifiError::ConfigError);
+        }
+        match self.ca_cert {
+            Some(cert) if cert.starts_with(b"-----BEGIN") && cert.last() != Some(&0) => Err(WifiError::ConfigError),
+            Some(cert) if !cert.starts_with(b"-----BEGIN") && cert.first() != Some(&0x30) => {
+                Err(WifiError::ConfigError)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// 转换为 esp-radio 的 EAP 客户端配置
+    pub fn to_radio(&self, ssid: &str) -> esp_radio::wifi::ModeConfig {
+        use esp_radio::wifi::{AuthMethod, EapClientConfig, ModeConfig, TtlsPhase2Method};
+
+        let mut config = EapClientConfig::default()
+            .with_ssid(ssid.into())
+            .with_auth_method(AuthMethod::Wpa2Enterprise)
+            .with_identity(self.identity.as_str().into())
+            .with_username(self.username.as_str().into())
+            .with_password(self.password.as_str().into());
+        if let Some(cert) = self.ca_cert {
+            config = config.with_ca_cert(cert);
+        }
+        if self.method == EapMethod::Ttls {
+            config = config.with_ttls_phase2_method(TtlsPhase2Method::Mschapv2);
+        }
+        ModeConfig::EapClient(config)
+    }
+}
+
 // ===== WiFi 控制器 =====
 
 /// WiFi 控制器
@@ -257,7 +515,7 @@ pub struct WifiController<'a> {
     /// 网关地址
     gateway: Option<[u8; 4]>,
     /// 事件通道
-    event_channel: &'a Channel<CriticalSectionRawMutex, WifiEvent, WIFI_EVENT_QUEUE_SIZE>,
+    event_channel: &'a WifiEventChannel,
     /// 连接信号
     connected_signal: &'a Signal<CriticalSectionRawMutex, bool>,
     /// 扫描结果
@@ -266,6 +524,16 @@ pub struct WifiController<'a> {
     reconnect_count: u32,
     /// 自动重连启用
     auto_reconnect: bool,
+    /// 企业级认证配置 (WPA2-Enterprise 网络)
+    enterprise: Option<EnterpriseConfig>,
+    /// 国家码与信道规划
+    regdomain: RegDomain,
+    /// 射频保活句柄 (`init` 后持有)
+    power: Option<KeepAlive>,
+    /// 运行中的 SoftAP 配置
+    ap: Option<ApConfig>,
+    /// 已接入 SoftAP 的客户端
+    ap_stations: Vec<[u8; 6], WIFI_AP_MAX_CLIENTS>,
 }
 
 impl<'a> WifiController<'a> {
@@ -275,7 +543,7 @@ impl<'a> WifiController<'a> {
     ///
     /// 此函数需要在系统初始化时调用，传入所需的外设和静态分配的通道。
     pub fn new(
-        event_channel: &'a Channel<CriticalSectionRawMutex, WifiEvent, WIFI_EVENT_QUEUE_SIZE>,
+        event_channel: &'a WifiEventChannel,
         connected_signal: &'a Signal<CriticalSectionRawMutex, bool>,
     ) -> Self {
         Self {
@@ -290,6 +558,11 @@ impl<'a> WifiController<'a> {
             scan_results: Vec::new(),
             reconnect_count: 0,
             auto_reconnect: true,
+            enterprise: None,
+            regdomain: RegDomain::WORLD,
+            power: None,
+            ap: None,
+            ap_stations: Vec::new(),
         }
     }
 
@@ -304,6 +577,7 @@ impl<'a> WifiController<'a> {
     pub async fn init(&mut self) -> Result<(), WifiError> {
         // esp-radio 的初始化在更高层完成
         // 这里只是设置本地状态
+        self.power.get_or_insert_with(|| peripherals::keep_alive(Peripheral::Wifi));
         self.state = WifiState::Idle;
         Ok(())
     }
@@ -322,6 +596,24 @@ impl<'a> WifiController<'a> {
         Ok(())
     }
 
+    /// 设置国家码与信道规划 (须在启动扫描/连接前调用)
+    ///
+    /// **注意**: 这只更新内部状态。实际应用应在 esp-radio 启动前按
+    /// `RegDomain::channel_range()`、`max_tx_power_overall()` 设置国家信息，
+    /// 切换信道时按 `max_tx_power_quarter_dbm()` 限制发射功率。
+    pub fn set_country(&mut self, regdomain: RegDomain) -> Result<(), WifiError> {
+        if !matches!(self.state, WifiState::Uninitialized | WifiState::Idle) {
+            return Err(WifiError::InvalidState);
+        }
+        self.regdomain = regdomain;
+        Ok(())
+    }
+
+    /// 当前管制域
+    pub fn regdomain(&self) -> &RegDomain {
+        &self.regdomain
+    }
+
     /// 获取当前模式
     pub fn mode(&self) -> WifiMode {
         self.mode
@@ -334,8 +626,8 @@ impl<'a> WifiController<'a> {
 
     /// 扫描周围的 WiFi 网络
     ///
-    /// **注意**: 此函数仅管理状态。实际扫描操作应通过 esp-radio API 完成。
-    /// 请参考 `examples/wifi_scan.rs`。
+    /// **注意**: 此函数仅管理状态，结果为空。实际扫描请使用 `scan_with()`，
+    /// 或由外部扫描后通过 `ingest_scan()` 导入。
     pub async fn scan(&mut self) -> Result<&[ScanResult], WifiError> {
         if self.state == WifiState::Uninitialized {
             return Err(WifiError::NotInitialized);
@@ -351,13 +643,77 @@ impl<'a> WifiController<'a> {
         self.state = WifiState::Idle;
         
         // 发送扫描完成事件
-        let _ = self.event_channel.try_send(WifiEvent::ScanDone {
+        self.emit(WifiEvent::ScanDone {
             count: self.scan_results.len(),
         });
 
         Ok(&self.scan_results)
     }
 
+    /// 通过 esp-radio 扫描周围的 WiFi 网络
+    ///
+    /// 结果按配置过滤、相同 BSSID 合并，并按信号强度降序排列。
+    pub async fn scan_with(
+        &mut self,
+        radio: &mut esp_radio::wifi::WifiController<'_>,
+        config: &ScanConfig,
+    ) -> Result<&[ScanResult], WifiError> {
+        use esp_radio::wifi::{ScanConfig as RadioScanConfig, ScanTypeConfig};
+
+        if self.state == WifiState::Uninitialized {
+            return Err(WifiError::NotInitialized);
+        }
+
+        let scan_type = match config.scan_type {
+            ScanType::Active { min_dwell_ms, max_dwell_ms } => ScanTypeConfig::Active {
+                min: core::time::Duration::from_millis(min_dwell_ms as u64),
+                max: core::time::Duration::from_millis(max_dwell_ms as u64),
+            },
+            ScanType::Passive { dwell_ms } => {
+                ScanTypeConfig::Passive(core::time::Duration::from_millis(dwell_ms as u64))
+            }
+        };
+        let mut radio_config = RadioScanConfig::default()
+            .with_scan_type(scan_type)
+            .with_show_hidden(config.show_hidden);
+        if let Some(channel) = config.channel {
+            self.regdomain.check_channel(channel).map_err(|_| WifiError::ConfigError)?;
+            radio_config = radio_config.with_channel(channel);
+        }
+        if let Some(ssid) = &config.ssid {
+            radio_config = radio_config.with_ssid(ssid.as_str());
+        }
+
+        self.state = WifiState::Scanning;
+        let found = radio.scan_with_config_async(radio_config).await;
+        self.state = WifiState::Idle;
+        let found = found.map_err(|_| WifiError::ScanFailed)?;
+
+        let results = found.iter().map(|ap| ScanResult {
+            ssid: String::try_from(ap.ssid.as_str()).unwrap_or_default(),
+            bssid: ap.bssid,
+            rssi: ap.signal_strength,
+            channel: ap.channel,
+            auth_mode: ap.auth_method.map(AuthMode::from).unwrap_or_default(),
+        });
+        Ok(self.ingest_scan(results, config))
+    }
+
+    /// 导入扫描结果 (替换上次结果，过滤、合并并排序)
+    pub fn ingest_scan<I: IntoIterator<Item = ScanResult>>(&mut self, results: I, config: &ScanConfig) -> &[ScanResult] {
+        self.scan_results.clear();
+        for result in results.into_iter().filter(|r| config.accepts(r)) {
+            merge_scan_result(&mut self.scan_results, result);
+        }
+        sort_by_rssi(&mut self.scan_results);
+        tuning::record(Knob::WifiScanResults, self.scan_results.len());
+
+        self.emit(WifiEvent::ScanDone {
+            count: self.scan_results.len(),
+        });
+        &self.scan_results
+    }
+
     /// 连接到指定的 WiFi 网络
     ///
     /// # 参数
@@ -374,7 +730,70 @@ impl<'a> WifiController<'a> {
         let _ = self.ssid.push_str(ssid);
         self.password.clear();
         let _ = self.password.push_str(password);
+        self.enterprise = None;
+
+        self.begin_connect().await
+    }
+
+    /// 设置 STA 凭据但不等待连接
+    ///
+    /// 由外部控制器自行 `connect_async()` 时使用 (例如配网完成后切换到 STA)，
+    /// 射频配置通过 `radio_config()` 获取。
+    pub fn set_credentials(&mut self, ssid: &str, password: &str) -> Result<(), WifiError> {
+        if ssid.is_empty() || ssid.len() > self.ssid.capacity() || password.len() > self.password.capacity() {
+            return Err(WifiError::ConfigError);
+        }
+        self.ssid.clear();
+        let _ = self.ssid.push_str(ssid);
+        self.password.clear();
+        let _ = self.password.push_str(password);
+        self.enterprise = None;
+        Ok(())
+    }
+
+    /// 连接到 WPA2-Enterprise 网络
+    ///
+    /// 配置不完整时返回 `ConfigError`；射频配置通过 `radio_config()` 获取。
+    pub async fn connect_enterprise(&mut self, ssid: &str, config: EnterpriseConfig) -> Result<(), WifiError> {
+        if self.state == WifiState::Uninitialized {
+            return Err(WifiError::NotInitialized);
+        }
+        config.validate()?;
+
+        self.ssid.clear();
+        let _ = self.ssid.push_str(ssid);
+        self.password.clear();
+        self.enterprise = Some(config);
+
+        self.begin_connect().await
+    }
+
+    /// 当前凭据对应的 esp-radio STA 配置
+    ///
+    /// 企业级网络返回 `ModeConfig::EapClient`，其余返回 `ModeConfig::Client`，
+    /// 交给 `esp_radio::wifi::WifiController::set_config()` 后再 `connect_async()`。
+    pub fn radio_config(&self) -> esp_radio::wifi::ModeConfig {
+        use esp_radio::wifi::ModeConfig;
+
+        match &self.enterprise {
+            Some(enterprise) => enterprise.to_radio(&self.ssid),
+            None => ModeConfig::Client(self.client_config()),
+        }
+    }
+
+    fn client_config(&self) -> esp_radio::wifi::ClientConfig {
+        esp_radio::wifi::ClientConfig::default()
+            .with_ssid(self.ssid.as_str().into())
+            .with_password(self.password.as_str().into())
+    }
+
+    /// 企业级认证配置
+    pub fn enterprise(&self) -> Option<&EnterpriseConfig> {
+        self.enterprise.as_ref()
+    }
 
+    /// 进入连接状态并等待外部控制器的连接信号
+    async fn begin_connect(&mut self) -> Result<(), WifiError> {
         self.state = WifiState::Connecting;
         self.reconnect_count = 0;
 
@@ -399,7 +818,7 @@ impl<'a> WifiController<'a> {
                 self.state = WifiState::Connected;
                 
                 // 发送连接事件
-                let _ = self.event_channel.try_send(WifiEvent::StaConnected);
+                self.emit(WifiEvent::StaConnected);
                 
                 return Ok(());
             } else {
@@ -422,7 +841,7 @@ impl<'a> WifiController<'a> {
         self.ip_address = None;
         self.gateway = None;
 
-        let _ = self.event_channel.try_send(WifiEvent::StaDisconnected {
+        self.emit(WifiEvent::StaDisconnected {
             reason: DisconnectReason::AssocLeave,
         });
 
@@ -483,7 +902,7 @@ impl<'a> WifiController<'a> {
         self.gateway = Some(gateway);
         self.state = WifiState::Ready;
         
-        let _ = self.event_channel.try_send(WifiEvent::GotIp {
+        self.emit(WifiEvent::GotIp {
             ip,
             gateway,
             netmask: [255, 255, 255, 0], // 默认子网掩码
@@ -494,7 +913,7 @@ impl<'a> WifiController<'a> {
     pub fn set_connected(&mut self, connected: bool) {
         if connected {
             self.state = WifiState::Connected;
-            let _ = self.event_channel.try_send(WifiEvent::StaConnected);
+            self.emit(WifiEvent::StaConnected);
         } else {
             self.state = WifiState::Disconnected;
             self.ip_address = None;
@@ -508,11 +927,130 @@ impl<'a> WifiController<'a> {
         matches!(self.state, WifiState::Connected | WifiState::GettingIp | WifiState::Ready)
     }
 
-    /// 启用/禁用自动重连
+    /// 启用/禁用自动重连 (由 `net::reconnect` 的重连任务执行)
     pub fn set_auto_reconnect(&mut self, enabled: bool) {
         self.auto_reconnect = enabled;
     }
 
+    /// 是否启用自动重连
+    pub fn auto_reconnect(&self) -> bool {
+        self.auto_reconnect
+    }
+
+    /// 记录 STA 断开 (由外部控制器回调或重连任务调用)
+    pub fn mark_disconnected(&mut self, reason: DisconnectReason) {
+        self.state = WifiState::Disconnected;
+        self.ip_address = None;
+        self.gateway = None;
+        self.emit(WifiEvent::StaDisconnected { reason });
+        self.connected_signal.signal(false);
+    }
+
+    /// 开始一次重连，返回本轮重连次数 (`begin_connect` 时清零)
+    pub fn begin_reconnect(&mut self) -> u32 {
+        self.state = WifiState::Connecting;
+        self.reconnect_count = self.reconnect_count.saturating_add(1);
+        self.reconnect_count
+    }
+
+    /// 本轮重连次数
+    pub fn reconnect_count(&self) -> u32 {
+        self.reconnect_count
+    }
+
+    /// 启动 SoftAP
+    ///
+    /// 已设置 STA 凭据时以 AP+STA 共存模式启动 (AP 跟随 STA 所在信道)，
+    /// 否则只启动 AP。AP 接口的静态地址与 DHCP 服务见 `net::dhcp_server`。
+    pub async fn start_ap(
+        &mut self,
+        radio: &mut esp_radio::wifi::WifiController<'_>,
+        config: ApConfig,
+    ) -> Result<(), WifiError> {
+        use esp_radio::wifi::ModeConfig;
+
+        if self.state == WifiState::Uninitialized {
+            return Err(WifiError::NotInitialized);
+        }
+        config.validate(&self.regdomain)?;
+
+        let with_sta = matches!(self.mode, WifiMode::Sta | WifiMode::ApSta) && !self.ssid.is_empty();
+        let mode_config = if with_sta {
+            // esp-radio 不支持企业级 STA 与 AP 共存
+            if self.enterprise.is_some() {
+                return Err(WifiError::Unsupported);
+            }
+            ModeConfig::ApSta(self.client_config(), config.to_radio())
+        } else {
+            ModeConfig::AccessPoint(config.to_radio())
+        };
+        radio.set_config(&mode_config).map_err(|_| WifiError::ConfigError)?;
+        if !radio.is_started().unwrap_or(false) {
+            radio.start_async().await.map_err(|_| WifiError::InternalError)?;
+        }
+
+        self.mode = if with_sta { WifiMode::ApSta } else { WifiMode::Ap };
+        self.ap_stations.clear();
+        self.ap = Some(config);
+        Ok(())
+    }
+
+    /// 停止 SoftAP (AP+STA 模式下保留 STA)
+    ///
+    /// 仍接入的客户端逐个投递 `ApStaDisconnected`。
+    pub async fn stop_ap(&mut self, radio: &mut esp_radio::wifi::WifiController<'_>) -> Result<(), WifiError> {
+        if self.ap.is_none() {
+            return Ok(());
+        }
+        if self.mode == WifiMode::ApSta {
+            radio.set_config(&self.radio_config()).map_err(|_| WifiError::ConfigError)?;
+            self.mode = WifiMode::Sta;
+        } else {
+            radio.stop_async().await.map_err(|_| WifiError::InternalError)?;
+            self.mode = WifiMode::None;
+        }
+        self.ap = None;
+        while let Some(mac) = self.ap_stations.pop() {
+            self.emit(WifiEvent::ApStaDisconnected { mac });
+        }
+        Ok(())
+    }
+
+    /// 运行中的 SoftAP 配置
+    pub fn ap_config(&self) -> Option<&ApConfig> {
+        self.ap.as_ref()
+    }
+
+    /// SoftAP 是否在运行
+    pub fn is_ap_active(&self) -> bool {
+        self.ap.is_some()
+    }
+
+    /// 已接入 SoftAP 的客户端 MAC
+    pub fn ap_stations(&self) -> &[[u8; 6]] {
+        &self.ap_stations
+    }
+
+    /// 记录客户端接入 SoftAP (由外部控制器回调调用)
+    pub fn ap_station_connected(&mut self, mac: [u8; 6]) -> Result<(), WifiError> {
+        if self.ap.is_none() {
+            return Err(WifiError::InvalidState);
+        }
+        if !self.ap_stations.contains(&mac) {
+            self.ap_stations.push(mac).map_err(|_| WifiError::OutOfMemory)?;
+        }
+        self.emit(WifiEvent::ApStaConnected { mac });
+        Ok(())
+    }
+
+    /// 记录客户端离开 SoftAP (由外部控制器回调调用)
+    pub fn ap_station_disconnected(&mut self, mac: [u8; 6]) {
+        if let Some(pos) = self.ap_stations.iter().position(|m| *m == mac) {
+            self.ap_stations.swap_remove(pos);
+            self.emit(WifiEvent::ApStaDisconnected { mac });
+        }
+    }
+
     /// 获取扫描结果
     pub fn scan_results(&self) -> &[ScanResult] {
         &self.scan_results
@@ -520,12 +1058,76 @@ impl<'a> WifiController<'a> {
 
     /// 接收 WiFi 事件
     pub async fn recv_event(&self) -> WifiEvent {
-        self.event_channel.receive().await
+        self.event_channel.receive().await.event
     }
 
     /// 尝试接收 WiFi 事件 (非阻塞)
     pub fn try_recv_event(&self) -> Option<WifiEvent> {
-        self.event_channel.try_receive().ok()
+        self.event_channel.try_receive().ok().map(|envelope| envelope.event)
+    }
+
+    /// 接收带序号与时间戳的事件
+    pub async fn recv_envelope(&self) -> Envelope<WifiEvent> {
+        self.event_channel.receive().await
+    }
+
+    /// 投递事件 (通道满时丢弃)
+    fn emit(&self, event: WifiEvent) {
+        let _ = self.event_channel.try_send(Envelope::new("wifi", event));
+        tuning::record(Knob::WifiEventQueue, self.event_channel.len());
+    }
+
+    /// 注入事件 (仿真/回放模式)
+    ///
+    /// 按外部驱动回调的方式更新状态后投递到事件通道，用于在无射频的情况下
+    /// 复现录制的事件序列。参见 `net::replay`。
+    pub async fn inject_event(&mut self, event: WifiEvent) {
+        match &event {
+            WifiEvent::StaConnected => {
+                self.state = WifiState::Connected;
+                self.connected_signal.signal(true);
+            }
+            WifiEvent::StaDisconnected { .. } => {
+                self.state = WifiState::Disconnected;
+                self.ip_address = None;
+                self.gateway = None;
+                self.connected_signal.signal(false);
+            }
+            WifiEvent::GotIp { ip, gateway, .. } => {
+                self.ip_address = Some(*ip);
+                self.gateway = Some(*gateway);
+                self.state = WifiState::Ready;
+            }
+            WifiEvent::ScanDone { .. } => {
+                if self.state == WifiState::Scanning {
+                    self.state = WifiState::Idle;
+                }
+            }
+            WifiEvent::ApStaConnected { mac } => {
+                if !self.ap_stations.contains(mac) {
+                    let _ = self.ap_stations.push(*mac);
+                }
+            }
+            WifiEvent::ApStaDisconnected { mac } => {
+                self.ap_stations.retain(|m| m != mac);
+            }
+        }
+        self.event_channel.send(Envelope::new("wifi", event)).await;
+        tuning::record(Knob::WifiEventQueue, self.event_channel.len());
+    }
+}
+
+impl DriverLifecycle for WifiController<'_> {
+    fn name(&self) -> &'static str {
+        "wifi"
+    }
+
+    /// 浅睡眠期间由 esp-radio 的 modem sleep 保持关联；深度睡眠与关闭前断开
+    async fn suspend(&mut self, kind: SleepKind) -> Result<(), LifecycleError> {
+        if kind == SleepKind::Deep && self.is_connected() {
+            self.disconnect().await.map_err(|_| LifecycleError::InvalidState)?;
+        }
+        Ok(())
     }
 }
 
@@ -534,7 +1136,7 @@ impl<'a> WifiController<'a> {
 /// AP 模式配置
 #[derive(Debug, Clone)]
 pub struct ApConfig {
-    /// SSID
+    /// SSID (默认 `<设备名>-AP`)
     pub ssid: String<32>,
     /// 密码 (空字符串表示开放网络)
     pub password: String<64>,
@@ -546,10 +1148,67 @@ pub struct ApConfig {
     pub hidden: bool,
 }
 
+impl ApConfig {
+    /// 以指定 SSID 与密码创建 (密码为空时为开放网络)
+    pub fn new(ssid: &str, password: &str) -> Result<Self, WifiError> {
+        Ok(Self {
+            ssid: String::try_from(ssid).map_err(|_| WifiError::ConfigError)?,
+            password: String::try_from(password).map_err(|_| WifiError::ConfigError)?,
+            channel: 1,
+            max_clients: 4,
+            hidden: false,
+        })
+    }
+
+    /// 设置信道
+    pub fn with_channel(mut self, channel: u8) -> Self {
+        self.channel = channel;
+        self
+    }
+
+    /// 设置最大客户端数量
+    pub fn with_max_clients(mut self, max_clients: u8) -> Self {
+        self.max_clients = max_clients;
+        self
+    }
+
+    /// 隐藏 SSID
+    pub fn with_hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    /// 检查配置 (SSID 非空、WPA2 密码至少 8 个字符、客户端数与信道合法)
+    pub fn validate(&self, regdomain: &RegDomain) -> Result<(), WifiError> {
+        if self.ssid.is_empty()
+            || (!self.password.is_empty() && self.password.len() < 8)
+            || self.max_clients == 0
+            || self.max_clients as usize > WIFI_AP_MAX_CLIENTS
+        {
+            return Err(WifiError::ConfigError);
+        }
+        regdomain.check_channel(self.channel).map_err(|_| WifiError::ConfigError)
+    }
+
+    /// 转换为 esp-radio 的 AP 配置 (有密码时使用 WPA2-Personal)
+    pub fn to_radio(&self) -> esp_radio::wifi::AccessPointConfig {
+        use esp_radio::wifi::{AccessPointConfig, AuthMethod};
+
+        let auth = if self.password.is_empty() { AuthMethod::None } else { AuthMethod::Wpa2Personal };
+        AccessPointConfig::default()
+            .with_ssid(self.ssid.as_str().into())
+            .with_ssid_hidden(self.hidden)
+            .with_channel(self.channel)
+            .with_auth_method(auth)
+            .with_password(self.password.as_str().into())
+            .with_max_connections(self.max_clients as u16)
+    }
+}
+
 impl Default for ApConfig {
     fn default() -> Self {
         Self {
-            ssid: String::try_from("RustRTOS-AP").unwrap_or_default(),
+            ssid: crate::sys::identity::IDENTITY.ap_ssid(),
             password: String::new(),
             channel: 1,
             max_clients: 4,
@@ -580,3 +1239,76 @@ pub struct WifiStats {
     /// 连接时长 (秒)
     pub connected_time: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ap(ssid: &str, last: u8, rssi: i8) -> ScanResult {
+        ScanResult {
+            ssid: String::try_from(ssid).unwrap(),
+            bssid: [0, 1, 2, 3, 4, last],
+            rssi,
+            channel: 6,
+            auth_mode: AuthMode::Wpa2Psk,
+        }
+    }
+
+    #[test]
+    fn test_merge_and_sort() {
+        let mut results: Vec<ScanResult, 3> = Vec::new();
+        let config = ScanConfig::default();
+        for r in [ap("a", 1, -70), ap("", 1, -60), ap("b", 2, -50), ap("", 3, -40), ap("c", 4, -80)] {
+            if config.accepts(&r) {
+                merge_scan_result(&mut results, r);
+            }
+        }
+        sort_by_rssi(&mut results);
+        let names: Vec<&str, 3> = results.iter().map(|r| r.ssid.as_str()).collect();
+        assert_eq!(names.as_slice(), &["b", "a", "c"]);
+        drop(names);
+
+        // 同一 BSSID 的隐藏帧信号更强时替换，但保留已知名称
+        merge_scan_result(&mut results, ap("", 1, -60));
+        assert_eq!(results.iter().find(|r| r.bssid[5] == 1).unwrap().rssi, -60);
+        assert_eq!(results.iter().find(|r| r.bssid[5] == 1).unwrap().ssid.as_str(), "a");
+
+        // 列表已满时替换最弱条目
+        merge_scan_result(&mut results, ap("d", 5, -55));
+        assert!(results.iter().all(|r| r.ssid.as_str() != "c"));
+    }
+
+    #[test]
+    fn test_ap_config_validate() {
+        let domain = RegDomain::WORLD;
+        assert!(ApConfig::new("setup", "").unwrap().validate(&domain).is_ok());
+        assert!(ApConfig::new("setup", "password").unwrap().with_channel(6).validate(&domain).is_ok());
+        assert_eq!(ApConfig::new("setup", "short").unwrap().validate(&domain), Err(WifiError::ConfigError));
+        assert_eq!(ApConfig::new("", "").unwrap().validate(&domain), Err(WifiError::ConfigError));
+        let crowded = ApConfig::new("setup", "").unwrap().with_max_clients(WIFI_AP_MAX_CLIENTS as u8 + 1);
+        assert_eq!(crowded.validate(&domain), Err(WifiError::ConfigError));
+        assert_eq!(ApConfig::new("setup", "").unwrap().with_channel(14).validate(&domain), Err(WifiError::ConfigError));
+    }
+
+    #[test]
+    fn test_enterprise_validate() {
+        let config = EnterpriseConfig::new("alice", "secret");
+        assert_eq!(config.identity.as_str(), "alice");
+        assert_eq!(config.validate(), Ok(()));
+        assert_eq!(EnterpriseConfig::new("alice", "").validate(), Err(WifiError::ConfigError));
+
+        let pem = config.clone().with_ca_cert(b"-----BEGIN CERTIFICATE-----\n...\n-----END CERTIFICATE-----\n\0");
+        assert_eq!(pem.validate(), Ok(()));
+        let unterminated = config.clone().with_ca_cert(b"-----BEGIN CERTIFICATE-----\n");
+        assert_eq!(unterminated.validate(), Err(WifiError::ConfigError));
+        assert_eq!(config.with_ca_cert(&[0x30, 0x82, 0x01]).validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_scan_filter() {
+        let config = ScanConfig::default().with_ssid("lab").with_min_rssi(-75);
+        assert!(config.accepts(&ap("lab", 1, -70)));
+        assert!(!config.accepts(&ap("lab", 1, -80)));
+        assert!(!config.accepts(&ap("other", 1, -40)));
+    }
+}
@@ -0,0 +1,451 @@
+//! 驱动生命周期 (挂起、恢复、关闭)
+//!
+//! 进入浅睡眠/深度睡眠或重启之前，驱动需要先完成收尾: 停止 DMA 与定时器、
+//! 让总线控制器离开总线、把射频状态同步给上层。过去这些工作靠 drop 驱动完成，
+//! 睡眠前无法保证执行，重启前也不知道是否已经结束。本模块统一为:
+//! - `DriverLifecycle`: 驱动实现的异步 `suspend` / `resume` / `shutdown`，
+//!   crate 内的驱动与网络控制器均已实现，未覆盖的方法默认什么都不做
+//! - `LIFECYCLE`: 全局协调器。持有驱动的任务登记 `LifecycleHandle`，
+//!   收到通知后对驱动执行对应操作并回报结果；协调器等待全部回报 (有超时)
+//! - `light_sleep` / `deep_sleep`: 先挂起驱动再进入睡眠，浅睡眠唤醒后恢复
+//! - `sys::reboot` 在参与者收尾之后、复位之前执行 `shutdown`
+//!
+//! 驱动通常由各自的任务独占 (`&mut`)，协调器不直接持有驱动，而是通知任务代为执行，
+//! 因此 `DriverLifecycle` 可以使用 async 方法且不需要对象安全。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::power::lifecycle::{self, DriverLifecycle, Transition, LIFECYCLE};
+//!
+//! #[embassy_executor::task]
+//! async fn can_task(mut can: CanBus<'static>) {
+//!     let mut lifecycle = LIFECYCLE.register().unwrap();
+//!     loop {
+//!         match select(can.run(&CAN_RX, &CAN_TX), lifecycle.next()).await {
+//!             Either::First(never) => never,
+//!             Either::Second(transition) => {
+//!                 let result = can.apply(transition).await;
+//!                 lifecycle.complete(result);
+//!             }
+//!         }
+//!     }
+//! }
+//!
+//! // 电源管理: 空闲时浅睡眠 10 秒
+//! let timer = TimerWakeupSource::new(core::time::Duration::from_secs(10));
+//! let summary = lifecycle::light_sleep(&mut rtc, &[&timer], lifecycle::DEFAULT_TIMEOUT).await;
+//! if !summary.is_clean() {
+//!     log_warn!("{} driver(s) failed to suspend", summary.failed);
+//! }
+//! ```
+
+use core::cell::RefCell;
+use core::fmt;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_sync::watch::Receiver;
+use embassy_time::{with_timeout, Duration, Instant};
+use esp_hal::rtc_cntl::sleep::WakeSource;
+use esp_hal::rtc_cntl::Rtc;
+
+use crate::sync::primitives::{CriticalMutex, CriticalSignal, CriticalWatch};
+use crate::util::log::*;
+
+/// 最大登记驱动数
+pub const MAX_DRIVERS: usize = 12;
+
+/// 默认等待驱动回报的时间
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(500);
+
+// ===== 类型 =====
+
+/// 睡眠类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SleepKind {
+    /// 浅睡眠 (RAM 与外设寄存器保持，唤醒后继续执行)
+    Light,
+    /// 深度睡眠 (只保留 RTC 域，唤醒即复位)
+    Deep,
+}
+
+/// 生命周期操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    /// 挂起
+    Suspend(SleepKind),
+    /// 从挂起恢复
+    Resume,
+    /// 关闭 (重启前)
+    Shutdown,
+}
+
+impl Transition {
+    /// 操作名称
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Suspend(SleepKind::Light) => "suspend-light",
+            Self::Suspend(SleepKind::Deep) => "suspend-deep",
+            Self::Resume => "resume",
+            Self::Shutdown => "shutdown",
+        }
+    }
+}
+
+/// 生命周期错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LifecycleError {
+    /// 驱动忙，无法在当前时刻挂起
+    Busy,
+    /// 操作超时
+    Timeout,
+    /// 硬件操作失败
+    Hardware,
+    /// 当前状态不支持该操作
+    InvalidState,
+}
+
+impl fmt::Display for LifecycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Busy => write!(f, "Driver busy"),
+            Self::Timeout => write!(f, "Lifecycle operation timed out"),
+            Self::Hardware => write!(f, "Hardware error"),
+            Self::InvalidState => write!(f, "Invalid driver state"),
+        }
+    }
+}
+
+// ===== 驱动接口 =====
+
+/// 驱动生命周期
+///
+/// 所有方法默认成功且不做任何事；`shutdown` 默认按深度睡眠挂起处理。
+/// 重复调用 (已挂起时再次挂起) 应直接成功。
+#[allow(async_fn_in_trait)]
+pub trait DriverLifecycle {
+    /// 驱动名称 (日志用)
+    fn name(&self) -> &'static str;
+
+    /// 挂起: 停止产生中断与 DMA 传输，保存恢复所需的状态
+    async fn suspend(&mut self, _kind: SleepKind) -> Result<(), LifecycleError> {
+        Ok(())
+    }
+
+    /// 从浅睡眠恢复到挂起前的状态
+    async fn resume(&mut self) -> Result<(), LifecycleError> {
+        Ok(())
+    }
+
+    /// 关闭: 重启前的最终收尾，之后驱动不再使用
+    async fn shutdown(&mut self) -> Result<(), LifecycleError> {
+        self.suspend(SleepKind::Deep).await
+    }
+
+    /// 执行 `transition` 对应的操作
+    async fn apply(&mut self, transition: Transition) -> Result<(), LifecycleError> {
+        let result = match transition {
+            Transition::Suspend(kind) => self.suspend(kind).await,
+            Transition::Resume => self.resume().await,
+            Transition::Shutdown => self.shutdown().await,
+        };
+        if let Err(_e) = result {
+            log_warn!("{}: {} failed: {}", self.name(), transition.name(), _e);
+        }
+        result
+    }
+}
+
+// ===== 协调器 =====
+
+/// 一次操作的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransitionSummary {
+    /// 操作
+    pub transition: Transition,
+    /// 参与的驱动数量
+    pub drivers: u8,
+    /// 回报失败的驱动数量
+    pub failed: u8,
+    /// 超时仍未回报的驱动数量
+    pub pending: u8,
+    /// 实际等待时长
+    pub waited: Duration,
+}
+
+impl TransitionSummary {
+    /// 全部驱动按时成功
+    pub fn is_clean(&self) -> bool {
+        self.failed == 0 && self.pending == 0
+    }
+}
+
+struct State {
+    /// 已登记的驱动
+    registered: u8,
+    /// 当前操作尚未回报的驱动
+    pending: u8,
+    /// 当前操作回报失败的驱动
+    failed: u8,
+    /// 操作序号 (每次操作加一)
+    generation: u32,
+}
+
+/// 生命周期协调器
+pub struct Lifecycle {
+    state: BlockingMutex<CriticalSectionRawMutex, RefCell<State>>,
+    notify: CriticalWatch<(u32, Transition), MAX_DRIVERS>,
+    done: CriticalSignal<()>,
+    /// 串行化操作 (电源管理与重启可能同时发起)
+    busy: CriticalMutex<()>,
+}
+
+/// 全局生命周期协调器
+pub static LIFECYCLE: Lifecycle = Lifecycle::new();
+
+impl Lifecycle {
+    /// 创建
+    pub const fn new() -> Self {
+        Self {
+            state: BlockingMutex::new(RefCell::new(State { registered: 0, pending: 0, failed: 0, generation: 0 })),
+            notify: CriticalWatch::new(),
+            done: CriticalSignal::new(),
+            busy: CriticalMutex::new(()),
+        }
+    }
+
+    /// 登记驱动
+    ///
+    /// # 返回
+    /// 登记数已满时返回 `None`
+    pub fn register(&'static self) -> Option<LifecycleHandle> {
+        let receiver = self.notify.receiver()?;
+        let joined = self.state.lock(|state| {
+            let mut state = state.borrow_mut();
+            state.registered += 1;
+            state.generation
+        });
+        Some(LifecycleHandle { lifecycle: self, receiver, joined, current: None })
+    }
+
+    /// 已登记的驱动数量
+    pub fn registered(&self) -> u8 {
+        self.state.lock(|state| state.borrow().registered)
+    }
+
+    /// 通知全部驱动执行 `transition`，等待回报 (最长 `timeout`)
+    pub async fn transition(&self, transition: Transition, timeout: Duration) -> TransitionSummary {
+        let _busy = self.busy.lock().await;
+        let start = Instant::now();
+        self.done.reset();
+        let (generation, drivers) = self.state.lock(|state| {
+            let mut state = state.borrow_mut();
+            state.generation = state.generation.wrapping_add(1);
+            state.pending = state.registered;
+            state.failed = 0;
+            (state.generation, state.registered)
+        });
+
+        if drivers > 0 {
+            log_info!("lifecycle: {} ({} drivers)", transition.name(), drivers);
+            self.notify.sender().send((generation, transition));
+            if with_timeout(timeout, self.done.wait()).await.is_err() {
+                log_warn!("lifecycle: {} driver(s) did not finish {}", self.pending(), transition.name());
+            }
+        }
+
+        let (failed, pending) = self.state.lock(|state| {
+            let state = state.borrow();
+            (state.failed, state.pending)
+        });
+        TransitionSummary { transition, drivers, failed, pending, waited: start.elapsed() }
+    }
+
+    /// 挂起全部驱动
+    pub async fn suspend(&self, kind: SleepKind, timeout: Duration) -> TransitionSummary {
+        self.transition(Transition::Suspend(kind), timeout).await
+    }
+
+    /// 恢复全部驱动
+    pub async fn resume(&self, timeout: Duration) -> TransitionSummary {
+        self.transition(Transition::Resume, timeout).await
+    }
+
+    /// 关闭全部驱动
+    pub async fn shutdown(&self, timeout: Duration) -> TransitionSummary {
+        self.transition(Transition::Shutdown, timeout).await
+    }
+
+    /// 当前操作尚未回报的驱动数量
+    pub fn pending(&self) -> u8 {
+        self.state.lock(|state| state.borrow().pending)
+    }
+
+    /// 驱动回报 (过期的操作序号忽略)
+    fn complete(&self, generation: u32, ok: bool) {
+        self.state.lock(|state| {
+            let mut state = state.borrow_mut();
+            if state.generation != generation || state.pending == 0 {
+                return;
+            }
+            state.pending -= 1;
+            if !ok {
+                state.failed += 1;
+            }
+            if state.pending == 0 {
+                self.done.signal(());
+            }
+        });
+    }
+
+    /// 驱动注销 (正在执行的操作视为成功完成)
+    fn leave(&self, current: Option<u32>) {
+        if let Some(generation) = current {
+            self.complete(generation, true);
+        }
+        self.state.lock(|state| state.borrow_mut().registered -= 1);
+    }
+}
+
+impl Default for Lifecycle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 已登记驱动的通知句柄
+///
+/// 收到操作后对驱动执行并调用 `complete`；句柄被 drop 视为已完成并注销。
+/// 登记之后、`next` 之前已经开始的操作不计入该驱动。
+pub struct LifecycleHandle {
+    lifecycle: &'static Lifecycle,
+    receiver: Receiver<'static, CriticalSectionRawMutex, (u32, Transition), MAX_DRIVERS>,
+    /// 登记时的操作序号
+    joined: u32,
+    /// 已收到、尚未回报的操作序号
+    current: Option<u32>,
+}
+
+impl LifecycleHandle {
+    /// 等待下一次操作
+    pub async fn next(&mut self) -> Transition {
+        loop {
+            let (generation, transition) = self.receiver.changed().await;
+            if generation.wrapping_sub(self.joined) as i32 > 0 {
+                self.current = Some(generation);
+                return transition;
+            }
+        }
+    }
+
+    /// 回报操作结果
+    pub fn complete(&mut self, result: Result<(), LifecycleError>) {
+        if let Some(generation) = self.current.take() {
+            self.lifecycle.complete(generation, result.is_ok());
+        }
+    }
+
+    /// 等待下一次操作，对 `driver` 执行并回报
+    pub async fn serve<D: DriverLifecycle>(&mut self, driver: &mut D) -> Transition {
+        let transition = self.next().await;
+        let result = driver.apply(transition).await;
+        self.complete(result);
+        transition
+    }
+}
+
+impl Drop for LifecycleHandle {
+    fn drop(&mut self) {
+        self.lifecycle.leave(self.current.take());
+    }
+}
+
+// ===== 睡眠 =====
+
+/// 挂起驱动、进入浅睡眠，唤醒后恢复驱动
+///
+/// 返回挂起阶段的结果；恢复失败只记录日志
+pub async fn light_sleep(rtc: &mut Rtc<'_>, sources: &[&dyn WakeSource], timeout: Duration) -> TransitionSummary {
+    let summary = LIFECYCLE.suspend(SleepKind::Light, timeout).await;
+    rtc.sleep_light(sources);
+    let resumed = LIFECYCLE.resume(timeout).await;
+    if !resumed.is_clean() {
+        log_warn!("lifecycle: resume incomplete ({} failed, {} pending)", resumed.failed, resumed.pending);
+    }
+    summary
+}
+
+/// 挂起驱动后进入深度睡眠 (唤醒即复位)
+pub async fn deep_sleep(rtc: &mut Rtc<'_>, sources: &[&dyn WakeSource], timeout: Duration) -> ! {
+    LIFECYCLE.suspend(SleepKind::Deep, timeout).await;
+    rtc.sleep_deep(sources)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fake {
+        suspended: bool,
+        fail_resume: bool,
+    }
+
+    impl DriverLifecycle for Fake {
+        fn name(&self) -> &'static str {
+            "fake"
+        }
+
+        async fn suspend(&mut self, _kind: SleepKind) -> Result<(), LifecycleError> {
+            self.suspended = true;
+            Ok(())
+        }
+
+        async fn resume(&mut self) -> Result<(), LifecycleError> {
+            if self.fail_resume {
+                return Err(LifecycleError::Hardware);
+            }
+            self.suspended = false;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_transition_accounting() {
+        static LC: Lifecycle = Lifecycle::new();
+        let mut a = LC.register().unwrap();
+        let mut b = LC.register().unwrap();
+        let mut fa = Fake { suspended: false, fail_resume: false };
+        let mut fb = Fake { suspended: false, fail_resume: true };
+
+        let summary = embassy_futures::block_on(async {
+            let request = LC.suspend(SleepKind::Light, Duration::from_secs(1));
+            let serve = embassy_futures::join::join(a.serve(&mut fa), b.serve(&mut fb));
+            embassy_futures::join::join(request, serve).await.0
+        });
+        assert!(summary.is_clean());
+        assert_eq!(summary.drivers, 2);
+        assert!(fa.suspended && fb.suspended);
+
+        let summary = embassy_futures::block_on(async {
+            let request = LC.resume(Duration::from_secs(1));
+            let serve = embassy_futures::join::join(a.serve(&mut fa), b.serve(&mut fb));
+            embassy_futures::join::join(request, serve).await.0
+        });
+        assert_eq!((summary.failed, summary.pending), (1, 0));
+        assert!(!fa.suspended && fb.suspended);
+
+        // 未回报的驱动被 drop 视为完成
+        drop(b);
+        let summary = embassy_futures::block_on(async {
+            let request = LC.shutdown(Duration::from_secs(1));
+            let serve = async {
+                a.next().await;
+                drop(a);
+            };
+            embassy_futures::join::join(request, serve).await.0
+        });
+        assert_eq!((summary.drivers, summary.pending), (1, 0));
+        assert_eq!(LC.registered(), 0);
+    }
+}
@@ -0,0 +1,13 @@
+//! 电源管理模块
+//!
+//! - `peripherals`: 外设电源/时钟门控 (按驱动使用情况自动关闭未使用外设、显式保活句柄)
+//! - `schedule`: 跨深度睡眠的周期调度 (RTC 内存保存调度表，唤醒后先执行到期任务再按最近到期时间睡眠)
+//! - `lifecycle`: 驱动生命周期 (睡眠前挂起、唤醒后恢复、重启前关闭，统一等待驱动完成)
+
+pub mod peripherals;
+pub mod schedule;
+pub mod lifecycle;
+
+pub use peripherals::{KeepAlive, Peripheral, PeripheralState};
+pub use schedule::{ScheduleError, Scheduler};
+pub use lifecycle::{DriverLifecycle, LifecycleError, SleepKind, Transition, LIFECYCLE};
@@ -0,0 +1,410 @@
+//! 外设电源与时钟门控
+//!
+//! 按引用计数跟踪外设的使用情况: crate 内的驱动在创建时持有 `KeepAlive`，
+//! drop 时释放；应用也可以显式持有 `KeepAlive`，防止外设在空闲时被关闭。
+//! 引用计数归零时自动关闭时钟并把外设保持在复位状态，再次获取时先解除复位、
+//! 开启时钟再返回句柄。
+//!
+//! - 只门控经 `manage` 登记的外设，其余外设只计数、不写寄存器。直接用 esp-hal
+//!   创建的驱动不经过这里，登记前需确认应用没有在 crate 之外使用该外设
+//!   (或为其持有 `KeepAlive`)
+//! - 时钟门控通过 SYSTEM 的 `PERIP_CLK_EN0/1` 与 `PERIP_RST_EN0/1` 完成
+//! - 无线 (WiFi/BT) 的时钟与电源域由 esp-radio 管理，门控通过 `set_hook`
+//!   注册的回调完成 (例如停止控制器、关闭射频)；未注册回调时只计数
+//! - 每个外设记录门控次数与累计关闭时长，可配合电流测量评估收益
+//!
+//! 持有 `KeepAlive` 期间外设保持上电；驱动结构体应把句柄放在最后一个字段，
+//! 保证先 drop 驱动本身、再关闭外设。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::power::peripherals::{self, Peripheral};
+//!
+//! // 启动时登记由本模块管理的外设，当前未被使用的立即关闭
+//! peripherals::manage(&[Peripheral::Adc, Peripheral::Rmt, Peripheral::LcdCam, Peripheral::Bt]);
+//! peripherals::set_hook(Peripheral::Bt, |on| if !on { ble_shutdown() });
+//!
+//! // 应用直接使用 ADC 期间保持供电，句柄 drop 后自动关闭
+//! let adc_power = peripherals::keep_alive(Peripheral::Adc);
+//! let sample = read_battery_adc();
+//! drop(adc_power);
+//!
+//! for (peripheral, state) in peripherals::snapshot() {
+//!     log_info!("{}: users={} gated={}", peripheral.name(), state.users, state.gated);
+//! }
+//! ```
+
+use core::cell::RefCell;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_time::{Duration, Instant};
+use heapless::Vec;
+
+use crate::util::log::*;
+
+// ===== 外设 =====
+
+/// 可门控的外设
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Peripheral {
+    /// SAR ADC (APB_SARADC)
+    Adc,
+    /// 红外/LED 串行收发 (RMT)
+    Rmt,
+    /// 脉冲计数器
+    Pcnt,
+    /// LED PWM
+    Ledc,
+    /// 电机控制 PWM 0
+    Mcpwm0,
+    /// 电机控制 PWM 1
+    Mcpwm1,
+    /// TWAI (CAN)
+    Twai,
+    /// I2S 0
+    I2s0,
+    /// I2S 1
+    I2s1,
+    /// LCD/摄像头接口
+    LcdCam,
+    /// WiFi 射频
+    Wifi,
+    /// 蓝牙射频
+    Bt,
+}
+
+/// 外设数量
+pub const PERIPHERAL_COUNT: usize = 12;
+
+/// 门控方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gate {
+    /// SYSTEM 外设时钟/复位寄存器组 (0: `PERIP_*_EN0`，1: `PERIP_*_EN1`) 中的位
+    Clock { bank: u8, mask: u32 },
+    /// 只调用 `set_hook` 注册的回调
+    Hook,
+}
+
+impl Peripheral {
+    /// 全部外设
+    pub const ALL: [Peripheral; PERIPHERAL_COUNT] = [
+        Self::Adc,
+        Self::Rmt,
+        Self::Pcnt,
+        Self::Ledc,
+        Self::Mcpwm0,
+        Self::Mcpwm1,
+        Self::Twai,
+        Self::I2s0,
+        Self::I2s1,
+        Self::LcdCam,
+        Self::Wifi,
+        Self::Bt,
+    ];
+
+    /// 名称
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Adc => "adc",
+            Self::Rmt => "rmt",
+            Self::Pcnt => "pcnt",
+            Self::Ledc => "ledc",
+            Self::Mcpwm0 => "mcpwm0",
+            Self::Mcpwm1 => "mcpwm1",
+            Self::Twai => "twai",
+            Self::I2s0 => "i2s0",
+            Self::I2s1 => "i2s1",
+            Self::LcdCam => "lcd_cam",
+            Self::Wifi => "wifi",
+            Self::Bt => "bt",
+        }
+    }
+
+    /// 门控方式 (位定义见 ESP32-S3 TRM 系统寄存器章节)
+    pub const fn gate(&self) -> Gate {
+        match self {
+            Self::Adc => Gate::Clock { bank: 0, mask: 1 << 28 },
+            Self::Rmt => Gate::Clock { bank: 0, mask: 1 << 9 },
+            Self::Pcnt => Gate::Clock { bank: 0, mask: 1 << 10 },
+            Self::Ledc => Gate::Clock { bank: 0, mask: 1 << 11 },
+            Self::Mcpwm0 => Gate::Clock { bank: 0, mask: 1 << 17 },
+            Self::Mcpwm1 => Gate::Clock { bank: 0, mask: 1 << 20 },
+            Self::Twai => Gate::Clock { bank: 0, mask: 1 << 19 },
+            Self::I2s0 => Gate::Clock { bank: 0, mask: 1 << 4 },
+            Self::I2s1 => Gate::Clock { bank: 0, mask: 1 << 21 },
+            Self::LcdCam => Gate::Clock { bank: 1, mask: 1 << 8 },
+            Self::Wifi | Self::Bt => Gate::Hook,
+        }
+    }
+
+    const fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+// ===== 寄存器 =====
+
+/// SYSTEM 外设时钟/复位寄存器
+mod regs {
+    /// SYSTEM 基地址
+    const SYSTEM_BASE: usize = 0x600C_0000;
+    /// PERIP_CLK_EN0 / PERIP_CLK_EN1
+    const CLK_EN: [usize; 2] = [SYSTEM_BASE + 0x18, SYSTEM_BASE + 0x1C];
+    /// PERIP_RST_EN0 / PERIP_RST_EN1
+    const RST_EN: [usize; 2] = [SYSTEM_BASE + 0x20, SYSTEM_BASE + 0x24];
+
+    fn modify(address: usize, f: impl FnOnce(u32) -> u32) {
+        let reg = address as *mut u32;
+        // Safety: SYSTEM 寄存器地址固定；调用者在临界区内，与 esp-hal 的时钟控制互斥
+        unsafe { reg.write_volatile(f(reg.read_volatile())) }
+    }
+
+    /// 开启时钟并解除复位
+    pub fn ungate(bank: u8, mask: u32) {
+        let bank = bank as usize & 1;
+        modify(CLK_EN[bank], |v| v | mask);
+        modify(RST_EN[bank], |v| v & !mask);
+    }
+
+    /// 进入复位并关闭时钟
+    pub fn gate(bank: u8, mask: u32) {
+        let bank = bank as usize & 1;
+        modify(RST_EN[bank], |v| v | mask);
+        modify(CLK_EN[bank], |v| v & !mask);
+    }
+}
+
+// ===== 状态 =====
+
+/// 外设状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeripheralState {
+    /// 持有 `KeepAlive` 的数量
+    pub users: u16,
+    /// 是否由本模块门控
+    pub managed: bool,
+    /// 当前是否已关闭
+    pub gated: bool,
+    /// 累计门控次数
+    pub gate_count: u32,
+    /// 累计关闭时长 (含当前这一段)
+    pub gated_for: Duration,
+}
+
+/// 状态切换
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Transition {
+    Gate,
+    Ungate,
+}
+
+/// 外设登记表
+struct Registry {
+    users: [u16; PERIPHERAL_COUNT],
+    managed: u32,
+    gated_since: [Option<Instant>; PERIPHERAL_COUNT],
+    gate_count: [u32; PERIPHERAL_COUNT],
+    gated_total: [Duration; PERIPHERAL_COUNT],
+    hooks: [Option<fn(bool)>; PERIPHERAL_COUNT],
+}
+
+impl Registry {
+    const fn new() -> Self {
+        Self {
+            users: [0; PERIPHERAL_COUNT],
+            managed: 0,
+            gated_since: [None; PERIPHERAL_COUNT],
+            gate_count: [0; PERIPHERAL_COUNT],
+            gated_total: [Duration::from_ticks(0); PERIPHERAL_COUNT],
+            hooks: [None; PERIPHERAL_COUNT],
+        }
+    }
+
+    fn is_managed(&self, i: usize) -> bool {
+        self.managed & (1 << i) != 0
+    }
+
+    fn gate(&mut self, i: usize, now: Instant) -> Option<Transition> {
+        if !self.is_managed(i) || self.users[i] != 0 || self.gated_since[i].is_some() {
+            return None;
+        }
+        self.gated_since[i] = Some(now);
+        self.gate_count[i] += 1;
+        Some(Transition::Gate)
+    }
+
+    fn ungate(&mut self, i: usize, now: Instant) -> Option<Transition> {
+        let since = self.gated_since[i].take()?;
+        self.gated_total[i] += now.saturating_duration_since(since);
+        Some(Transition::Ungate)
+    }
+
+    fn acquire(&mut self, i: usize, now: Instant) -> Option<Transition> {
+        self.users[i] = self.users[i].saturating_add(1);
+        self.ungate(i, now)
+    }
+
+    fn release(&mut self, i: usize, now: Instant) -> Option<Transition> {
+        self.users[i] = self.users[i].saturating_sub(1);
+        self.gate(i, now)
+    }
+
+    fn manage(&mut self, i: usize, now: Instant) -> Option<Transition> {
+        self.managed |= 1 << i;
+        self.gate(i, now)
+    }
+
+    fn unmanage(&mut self, i: usize, now: Instant) -> Option<Transition> {
+        self.managed &= !(1 << i);
+        self.ungate(i, now)
+    }
+
+    fn state(&self, i: usize, now: Instant) -> PeripheralState {
+        let current = self.gated_since[i].map_or(Duration::from_ticks(0), |since| now.saturating_duration_since(since));
+        PeripheralState {
+            users: self.users[i],
+            managed: self.is_managed(i),
+            gated: self.gated_since[i].is_some(),
+            gate_count: self.gate_count[i],
+            gated_for: self.gated_total[i] + current,
+        }
+    }
+}
+
+static REGISTRY: BlockingMutex<CriticalSectionRawMutex, RefCell<Registry>> =
+    BlockingMutex::new(RefCell::new(Registry::new()));
+
+/// 在登记表上执行操作并应用状态切换
+///
+/// 寄存器在临界区内修改；回调在临界区外调用，可以执行较慢的收尾
+fn update(peripheral: Peripheral, op: impl FnOnce(&mut Registry, usize, Instant) -> Option<Transition>) {
+    let i = peripheral.index();
+    let (transition, hook) = REGISTRY.lock(|registry| {
+        let mut registry = registry.borrow_mut();
+        let transition = op(&mut registry, i, Instant::now());
+        if let (Some(transition), Gate::Clock { bank, mask }) = (transition, peripheral.gate()) {
+            match transition {
+                Transition::Gate => regs::gate(bank, mask),
+                Transition::Ungate => regs::ungate(bank, mask),
+            }
+        }
+        (transition, registry.hooks[i])
+    });
+
+    if let Some(transition) = transition {
+        let on = transition == Transition::Ungate;
+        if let Some(hook) = hook {
+            hook(on);
+        }
+        log_debug!("power: {} {}", peripheral.name(), if on { "on" } else { "gated" });
+    }
+}
+
+// ===== 公共接口 =====
+
+/// 外设保活句柄
+///
+/// 持有期间外设保持上电，最后一个句柄 drop 时受管外设被关闭
+#[must_use = "dropping the handle releases the peripheral immediately"]
+pub struct KeepAlive {
+    peripheral: Peripheral,
+}
+
+impl KeepAlive {
+    /// 对应的外设
+    pub fn peripheral(&self) -> Peripheral {
+        self.peripheral
+    }
+}
+
+impl Drop for KeepAlive {
+    fn drop(&mut self) {
+        update(self.peripheral, Registry::release);
+    }
+}
+
+/// 获取外设保活句柄 (外设已关闭时先恢复时钟)
+pub fn keep_alive(peripheral: Peripheral) -> KeepAlive {
+    update(peripheral, Registry::acquire);
+    KeepAlive { peripheral }
+}
+
+/// 登记由本模块门控的外设，当前没有使用者的立即关闭
+pub fn manage(peripherals: &[Peripheral]) {
+    for &peripheral in peripherals {
+        update(peripheral, Registry::manage);
+    }
+}
+
+/// 取消门控 (已关闭的外设恢复时钟)
+pub fn unmanage(peripheral: Peripheral) {
+    update(peripheral, Registry::unmanage);
+}
+
+/// 注册门控回调 (`true` 表示恢复，`false` 表示关闭)
+///
+/// 无线外设只通过回调门控；时钟门控的外设在写寄存器之后调用
+pub fn set_hook(peripheral: Peripheral, hook: fn(bool)) {
+    REGISTRY.lock(|registry| registry.borrow_mut().hooks[peripheral.index()] = Some(hook));
+}
+
+/// 查询外设状态
+pub fn state(peripheral: Peripheral) -> PeripheralState {
+    REGISTRY.lock(|registry| registry.borrow().state(peripheral.index(), Instant::now()))
+}
+
+/// 所有外设的状态
+pub fn snapshot() -> Vec<(Peripheral, PeripheralState), PERIPHERAL_COUNT> {
+    let now = Instant::now();
+    REGISTRY.lock(|registry| {
+        let registry = registry.borrow();
+        Peripheral::ALL
+            .iter()
+            .map(|&p| (p, registry.state(p.index(), now)))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refcount_gating() {
+        let mut registry = Registry::new();
+        let t0 = Instant::from_millis(0);
+        let rmt = Peripheral::Rmt.index();
+
+        // 未登记时只计数
+        assert_eq!(registry.acquire(rmt, t0), None);
+        assert_eq!(registry.release(rmt, t0), None);
+
+        // 登记后空闲外设立即关闭，获取时恢复
+        assert_eq!(registry.manage(rmt, t0), Some(Transition::Gate));
+        assert_eq!(registry.acquire(rmt, Instant::from_millis(500)), Some(Transition::Ungate));
+        assert_eq!(registry.acquire(rmt, Instant::from_millis(600)), None);
+        assert_eq!(registry.release(rmt, Instant::from_millis(700)), None);
+        assert_eq!(registry.release(rmt, Instant::from_millis(800)), Some(Transition::Gate));
+
+        let state = registry.state(rmt, Instant::from_millis(1000));
+        assert!(state.gated && state.managed);
+        assert_eq!(state.users, 0);
+        assert_eq!(state.gate_count, 2);
+        assert_eq!(state.gated_for, Duration::from_millis(700));
+
+        assert_eq!(registry.unmanage(rmt, Instant::from_millis(1000)), Some(Transition::Ungate));
+        assert!(!registry.state(rmt, Instant::from_millis(1000)).gated);
+    }
+
+    #[test]
+    fn test_gate_table() {
+        assert_eq!(Peripheral::ALL.len(), PERIPHERAL_COUNT);
+        for (i, p) in Peripheral::ALL.iter().enumerate() {
+            assert_eq!(p.index(), i);
+        }
+        assert_eq!(Peripheral::LcdCam.gate(), Gate::Clock { bank: 1, mask: 1 << 8 });
+        assert_eq!(Peripheral::Wifi.gate(), Gate::Hook);
+    }
+}
@@ -0,0 +1,536 @@
+//! 跨深度睡眠的周期调度
+//!
+//! 电池供电的周期上报设备每次醒来只做少量工作再回到深度睡眠，普通定时器随睡眠
+//! 全部丢失。本模块把调度表放在 RTC 快速内存 (深度睡眠与软件复位后保留)，
+//! 时间基准取 RTC 定时器 (深度睡眠期间持续计数):
+//! - `every(id, period)` 登记周期任务，重复登记同一 id 且周期不变时保留原到期时间
+//! - 唤醒后在正常启动流程之前调用 `dispatch`，按到期先后执行到期任务；
+//!   下次到期时间按原相位推进，睡眠过久错过的周期只补跑一次并计入 `missed`
+//! - `sleep` 以最近的到期时间设置定时器唤醒并进入深度睡眠；`suspend_and_sleep` 先挂起驱动
+//! - 调度表 (id 与周期) 可镜像到键值存储: 掉电后 RTC 内存丢失，从镜像恢复的任务视为立即到期。
+//!   只在登记/取消改变调度表时写入，避免每次唤醒都擦写 Flash
+//!
+//! RTC 内存中的记录带魔数与 CRC，且记录保存时刻的 RTC 时间；读到的时间早于保存时刻
+//! (RTC 定时器被复位或重设) 时丢弃记录，退回镜像。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::power::schedule::Scheduler;
+//!
+//! const REPORT: u8 = 1;
+//! const CALIBRATE: u8 = 2;
+//!
+//! let mut rtc = Rtc::new(peripherals.LPWR);
+//! let mut sched = Scheduler::restore(&rtc, Some(&kv));
+//! sched.every(&rtc, REPORT, Duration::from_secs(6 * 3600))?;
+//! sched.every(&rtc, CALIBRATE, Duration::from_secs(24 * 3600))?;
+//! sched.save_mirror(&kv)?;
+//!
+//! // 启动早期: 先执行到期任务
+//! sched.dispatch(&rtc, |id| async move {
+//!     match id {
+//!         REPORT => report().await,
+//!         CALIBRATE => calibrate().await,
+//!         _ => {}
+//!     }
+//! }).await;
+//!
+//! // 无需常驻时直接回到深度睡眠
+//! sched.suspend_and_sleep(&mut rtc, None).await;
+//! ```
+
+use core::fmt;
+use core::future::Future;
+
+use embassy_time::Duration;
+use esp_hal::rtc_cntl::sleep::TimerWakeupSource;
+use esp_hal::rtc_cntl::{wakeup_cause, Rtc, SleepSource};
+use heapless::Vec;
+
+use crate::fs::idf_nvs::crc32_le;
+use crate::fs::kv::{KvError, KvStore};
+use crate::power::lifecycle::{self, SleepKind, LIFECYCLE};
+use crate::util::log::*;
+
+/// 最大调度任务数
+pub const MAX_SCHEDULES: usize = 8;
+
+/// 最短周期
+pub const MIN_PERIOD: Duration = Duration::from_secs(1);
+
+/// 没有任何任务时 `sleep` 的默认唤醒间隔
+pub const IDLE_WAKE: Duration = Duration::from_secs(24 * 3600);
+
+/// 键值存储镜像位置
+pub const KV_NAMESPACE: &str = "sched";
+const KV_KEY: &str = "table";
+
+/// RTC 记录魔数 ("SCH1")
+const RECORD_MAGIC: u32 = 0x5343_4831;
+/// 每个任务占用的字数
+const ENTRY_WORDS: usize = 6;
+/// 头部: 魔数、数量、保存时刻 (2 字)
+const HEADER_WORDS: usize = 4;
+/// RTC 记录总字数 (末尾为 CRC)
+const RECORD_WORDS: usize = HEADER_WORDS + MAX_SCHEDULES * ENTRY_WORDS + 1;
+/// 镜像中每个任务的字节数 (id + 周期秒数)
+const MIRROR_ENTRY_LEN: usize = 5;
+
+/// 调度错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleError {
+    /// 任务数已满
+    Full,
+    /// 周期过短
+    InvalidPeriod,
+    /// 镜像读写失败
+    Storage(KvError),
+}
+
+impl fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full => write!(f, "Schedule table full"),
+            Self::InvalidPeriod => write!(f, "Invalid schedule period"),
+            Self::Storage(e) => write!(f, "Schedule mirror error: {}", e),
+        }
+    }
+}
+
+impl From<KvError> for ScheduleError {
+    fn from(e: KvError) -> Self {
+        Self::Storage(e)
+    }
+}
+
+// ===== 时钟 =====
+
+/// 跨深度睡眠持续计数的时钟 (微秒)
+pub trait RtcClock {
+    /// 当前时间
+    fn now_us(&self) -> u64;
+}
+
+impl RtcClock for Rtc<'_> {
+    fn now_us(&self) -> u64 {
+        self.current_time_us()
+    }
+}
+
+/// 是否由定时器从深度睡眠唤醒
+pub fn woke_by_timer() -> bool {
+    matches!(wakeup_cause(), SleepSource::Timer)
+}
+
+// ===== 调度表 =====
+
+/// 调度任务
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduleEntry {
+    /// 任务编号 (由应用定义)
+    pub id: u8,
+    /// 周期 (秒)
+    pub period_secs: u32,
+    /// 下次到期时刻 (RTC 微秒)
+    pub next_due_us: u64,
+    /// 累计执行次数
+    pub runs: u32,
+    /// 累计错过的周期数 (睡眠过久或任务执行过慢)
+    pub missed: u32,
+}
+
+impl ScheduleEntry {
+    fn period_us(&self) -> u64 {
+        self.period_secs as u64 * 1_000_000
+    }
+
+    /// 距下次到期的时间 (已到期为 0)
+    pub fn remaining(&self, now_us: u64) -> Duration {
+        Duration::from_micros(self.next_due_us.saturating_sub(now_us))
+    }
+
+    /// 是否已到期
+    pub fn is_due(&self, now_us: u64) -> bool {
+        now_us >= self.next_due_us
+    }
+}
+
+/// 调度表 (纯数据，时间由调用者传入)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleTable {
+    entries: Vec<ScheduleEntry, MAX_SCHEDULES>,
+}
+
+impl ScheduleTable {
+    /// 创建空表
+    pub const fn new() -> Self {
+        Self { entries: Vec::new() }
+    }
+
+    /// 登记周期任务，首次到期为 `now + period`
+    ///
+    /// 已存在且周期相同时保持原到期时间，返回 `false`；新增或周期变化返回 `true`。
+    pub fn every(&mut self, id: u8, period: Duration, now_us: u64) -> Result<bool, ScheduleError> {
+        if period < MIN_PERIOD || period.as_secs() > u32::MAX as u64 {
+            return Err(ScheduleError::InvalidPeriod);
+        }
+        let period_secs = period.as_secs() as u32;
+        let next_due_us = now_us + period_secs as u64 * 1_000_000;
+        if let Some(entry) = self.get_mut(id) {
+            if entry.period_secs == period_secs {
+                return Ok(false);
+            }
+            entry.period_secs = period_secs;
+            entry.next_due_us = next_due_us;
+            return Ok(true);
+        }
+        self.entries
+            .push(ScheduleEntry { id, period_secs, next_due_us, runs: 0, missed: 0 })
+            .map_err(|_| ScheduleError::Full)?;
+        Ok(true)
+    }
+
+    /// 取消任务，返回是否存在
+    pub fn cancel(&mut self, id: u8) -> bool {
+        match self.entries.iter().position(|e| e.id == id) {
+            Some(index) => {
+                self.entries.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 查询任务
+    pub fn get(&self, id: u8) -> Option<&ScheduleEntry> {
+        self.entries.iter().find(|e| e.id == id)
+    }
+
+    fn get_mut(&mut self, id: u8) -> Option<&mut ScheduleEntry> {
+        self.entries.iter_mut().find(|e| e.id == id)
+    }
+
+    /// 全部任务
+    pub fn entries(&self) -> &[ScheduleEntry] {
+        &self.entries
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 到期任务 (按到期先后排序)
+    pub fn due(&self, now_us: u64) -> Vec<u8, MAX_SCHEDULES> {
+        let mut due: Vec<&ScheduleEntry, MAX_SCHEDULES> = self.entries.iter().filter(|e| e.is_due(now_us)).collect();
+        due.sort_unstable_by_key(|e| (e.next_due_us, e.id));
+        due.iter().map(|e| e.id).collect()
+    }
+
+    /// 标记任务已执行: 到期时间按原相位推进到 `now` 之后
+    pub fn complete(&mut self, id: u8, now_us: u64) {
+        let Some(entry) = self.get_mut(id) else {
+            return;
+        };
+        entry.runs = entry.runs.saturating_add(1);
+        if now_us < entry.next_due_us {
+            return;
+        }
+        let period_us = entry.period_us();
+        let skipped = (now_us - entry.next_due_us) / period_us;
+        entry.missed = entry.missed.saturating_add(skipped.min(u32::MAX as u64) as u32);
+        entry.next_due_us += (skipped + 1) * period_us;
+    }
+
+    /// 距最近一次到期的时间 (无任务返回 `None`)
+    pub fn next_wake(&self, now_us: u64) -> Option<Duration> {
+        self.entries.iter().map(|e| e.remaining(now_us)).min()
+    }
+
+    /// 编码为 RTC 记录
+    fn encode(&self, now_us: u64) -> [u32; RECORD_WORDS] {
+        let mut words = [0u32; RECORD_WORDS];
+        words[0] = RECORD_MAGIC;
+        words[1] = self.entries.len() as u32;
+        words[2] = now_us as u32;
+        words[3] = (now_us >> 32) as u32;
+        for (i, e) in self.entries.iter().enumerate() {
+            let w = &mut words[HEADER_WORDS + i * ENTRY_WORDS..][..ENTRY_WORDS];
+            w.copy_from_slice(&[
+                e.id as u32,
+                e.period_secs,
+                e.next_due_us as u32,
+                (e.next_due_us >> 32) as u32,
+                e.runs,
+                e.missed,
+            ]);
+        }
+        words[RECORD_WORDS - 1] = record_crc(&words);
+        words
+    }
+
+    /// 解析 RTC 记录 (上电后内容随机；时钟早于保存时刻说明 RTC 定时器已复位)
+    fn decode(words: &[u32; RECORD_WORDS], now_us: u64) -> Option<Self> {
+        let count = words[1] as usize;
+        let saved_us = words[2] as u64 | ((words[3] as u64) << 32);
+        if words[0] != RECORD_MAGIC
+            || count > MAX_SCHEDULES
+            || words[RECORD_WORDS - 1] != record_crc(words)
+            || now_us < saved_us
+        {
+            return None;
+        }
+        let mut table = Self::new();
+        for i in 0..count {
+            let w = &words[HEADER_WORDS + i * ENTRY_WORDS..][..ENTRY_WORDS];
+            let _ = table.entries.push(ScheduleEntry {
+                id: w[0] as u8,
+                period_secs: w[1],
+                next_due_us: w[2] as u64 | ((w[3] as u64) << 32),
+                runs: w[4],
+                missed: w[5],
+            });
+        }
+        Some(table)
+    }
+
+    /// 编码镜像 (只含 id 与周期)，返回长度
+    fn encode_mirror(&self, buf: &mut [u8; MAX_SCHEDULES * MIRROR_ENTRY_LEN]) -> usize {
+        for (i, e) in self.entries.iter().enumerate() {
+            let chunk = &mut buf[i * MIRROR_ENTRY_LEN..][..MIRROR_ENTRY_LEN];
+            chunk[0] = e.id;
+            chunk[1..].copy_from_slice(&e.period_secs.to_le_bytes());
+        }
+        self.entries.len() * MIRROR_ENTRY_LEN
+    }
+
+    /// 从镜像恢复，全部任务视为立即到期
+    fn decode_mirror(data: &[u8], now_us: u64) -> Self {
+        let mut table = Self::new();
+        for chunk in data.chunks_exact(MIRROR_ENTRY_LEN).take(MAX_SCHEDULES) {
+            let period_secs = u32::from_le_bytes([chunk[1], chunk[2], chunk[3], chunk[4]]);
+            if period_secs == 0 {
+                continue;
+            }
+            let _ = table.entries.push(ScheduleEntry {
+                id: chunk[0],
+                period_secs,
+                next_due_us: now_us,
+                runs: 0,
+                missed: 0,
+            });
+        }
+        table
+    }
+}
+
+impl Default for ScheduleTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn record_crc(words: &[u32; RECORD_WORDS]) -> u32 {
+    words[..RECORD_WORDS - 1]
+        .iter()
+        .fold(0xFFFF_FFFF, |crc, w| crc32_le(crc, &w.to_le_bytes()))
+}
+
+// ===== RTC 保留内存 =====
+
+/// 调度记录 (RTC 快速内存，深度睡眠与软件复位后保留，上电后内容随机)
+#[esp_hal::ram(unstable(rtc_fast, persistent))]
+static mut RECORD: [u32; RECORD_WORDS] = [0; RECORD_WORDS];
+
+/// 调度表来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Restored {
+    /// RTC 内存 (深度睡眠或软件复位后)
+    Rtc,
+    /// 键值存储镜像 (掉电后)，任务立即到期
+    Mirror,
+    /// 无记录
+    Empty,
+}
+
+// ===== 调度器 =====
+
+/// 跨深度睡眠的调度器
+pub struct Scheduler {
+    table: ScheduleTable,
+    restored: Restored,
+    /// 调度表结构 (id/周期) 已变化，镜像待写入
+    mirror_dirty: bool,
+}
+
+impl Scheduler {
+    /// 启动时恢复调度表: 优先 RTC 内存，其次键值存储镜像
+    pub fn restore(clock: &impl RtcClock, kv: Option<&KvStore<'_>>) -> Self {
+        let now = clock.now_us();
+        // Safety: 启动阶段单线程访问；写入只在 persist 中进行
+        let words = unsafe { core::ptr::addr_of!(RECORD).read() };
+        if let Some(table) = ScheduleTable::decode(&words, now) {
+            return Self { table, restored: Restored::Rtc, mirror_dirty: false };
+        }
+
+        let mut buf = [0u8; MAX_SCHEDULES * MIRROR_ENTRY_LEN];
+        match kv.map(|kv| kv.get(KV_NAMESPACE, KV_KEY, &mut buf)) {
+            Some(Ok(len)) if len > 0 => {
+                let table = ScheduleTable::decode_mirror(&buf[..len], now);
+                log_info!("schedule: restored {} task(s) from mirror, all due", table.entries().len());
+                Self { table, restored: Restored::Mirror, mirror_dirty: false }
+            }
+            _ => Self { table: ScheduleTable::new(), restored: Restored::Empty, mirror_dirty: false },
+        }
+    }
+
+    /// 调度表来源
+    pub fn restored(&self) -> Restored {
+        self.restored
+    }
+
+    /// 调度表
+    pub fn table(&self) -> &ScheduleTable {
+        &self.table
+    }
+
+    /// 登记周期任务 (已存在且周期相同时保留原到期时间)
+    pub fn every(&mut self, clock: &impl RtcClock, id: u8, period: Duration) -> Result<(), ScheduleError> {
+        let now = clock.now_us();
+        if self.table.every(id, period, now)? {
+            self.mirror_dirty = true;
+            self.persist(now);
+        }
+        Ok(())
+    }
+
+    /// 取消任务
+    pub fn cancel(&mut self, clock: &impl RtcClock, id: u8) -> bool {
+        let removed = self.table.cancel(id);
+        if removed {
+            self.mirror_dirty = true;
+            self.persist(clock.now_us());
+        }
+        removed
+    }
+
+    /// 当前到期的任务
+    pub fn due(&self, clock: &impl RtcClock) -> Vec<u8, MAX_SCHEDULES> {
+        self.table.due(clock.now_us())
+    }
+
+    /// 按到期先后执行到期任务，返回执行数量
+    ///
+    /// 每个任务完成后立即写回 RTC 内存，执行中途复位不会重复已完成的任务。
+    pub async fn dispatch<F, Fut>(&mut self, clock: &impl RtcClock, mut run: F) -> usize
+    where
+        F: FnMut(u8) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let due = self.table.due(clock.now_us());
+        for &id in &due {
+            run(id).await;
+            let now = clock.now_us();
+            self.table.complete(id, now);
+            self.persist(now);
+            if let Some(_e) = self.table.get(id) {
+                log_debug!("schedule: task {} done, next in {}s (missed {})", id, _e.remaining(now).as_secs(), _e.missed);
+            }
+        }
+        due.len()
+    }
+
+    /// 距最近一次到期的时间
+    pub fn next_wake(&self, clock: &impl RtcClock) -> Option<Duration> {
+        self.table.next_wake(clock.now_us())
+    }
+
+    /// 写入键值存储镜像 (仅在调度表结构变化后写入)
+    pub fn save_mirror(&mut self, kv: &KvStore<'_>) -> Result<(), ScheduleError> {
+        if !self.mirror_dirty {
+            return Ok(());
+        }
+        let mut buf = [0u8; MAX_SCHEDULES * MIRROR_ENTRY_LEN];
+        let len = self.table.encode_mirror(&mut buf);
+        kv.set(KV_NAMESPACE, KV_KEY, &buf[..len])?;
+        self.mirror_dirty = false;
+        Ok(())
+    }
+
+    /// 写回 RTC 内存
+    fn persist(&self, now_us: u64) {
+        let words = self.table.encode(now_us);
+        // Safety: 调度器为唯一写入者
+        unsafe { core::ptr::addr_of_mut!(RECORD).write(words) };
+    }
+
+    /// 进入深度睡眠，在最近一次到期时 (不超过 `max`，默认 `IDLE_WAKE`) 由定时器唤醒
+    pub fn sleep(&self, rtc: &mut Rtc<'_>, max: Option<Duration>) -> ! {
+        let now = rtc.now_us();
+        self.persist(now);
+        let limit = max.unwrap_or(IDLE_WAKE);
+        let wait = self.table.next_wake(now).map_or(limit, |d| d.min(limit));
+        log_info!("schedule: deep sleep for {}s", wait.as_secs());
+        let timer = TimerWakeupSource::new(core::time::Duration::from_micros(wait.as_micros()));
+        rtc.sleep_deep(&[&timer])
+    }
+
+    /// 先挂起已登记的驱动 (`power::lifecycle`)，再按 `sleep` 进入深度睡眠
+    pub async fn suspend_and_sleep(&self, rtc: &mut Rtc<'_>, max: Option<Duration>) -> ! {
+        LIFECYCLE.suspend(SleepKind::Deep, lifecycle::DEFAULT_TIMEOUT).await;
+        self.sleep(rtc, max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const S: u64 = 1_000_000;
+
+    #[test]
+    fn test_due_and_catch_up() {
+        let mut table = ScheduleTable::new();
+        assert_eq!(table.every(1, Duration::from_secs(3600), 0), Ok(true));
+        assert_eq!(table.every(2, Duration::from_secs(600), 0), Ok(true));
+        assert_eq!(table.every(2, Duration::from_secs(600), 500 * S), Ok(false));
+        assert_eq!(table.every(3, Duration::from_millis(10), 0), Err(ScheduleError::InvalidPeriod));
+
+        assert!(table.due(599 * S).is_empty());
+        assert_eq!(table.next_wake(599 * S), Some(Duration::from_secs(1)));
+
+        // 睡了 2 小时 5 分钟: 两个都到期，按到期先后执行，错过的周期只补跑一次
+        let now = 7500 * S;
+        assert_eq!(table.due(now).as_slice(), &[2, 1]);
+        table.complete(2, now);
+        table.complete(1, now);
+        let e2 = table.get(2).unwrap();
+        assert_eq!((e2.next_due_us, e2.missed, e2.runs), (7800 * S, 11, 1));
+        let e1 = table.get(1).unwrap();
+        assert_eq!((e1.next_due_us, e1.missed), (3 * 3600 * S, 1));
+        assert_eq!(table.next_wake(now), Some(Duration::from_secs(300)));
+    }
+
+    #[test]
+    fn test_rtc_record_and_mirror() {
+        let mut table = ScheduleTable::new();
+        table.every(7, Duration::from_secs(6 * 3600), 10 * S).unwrap();
+        table.every(9, Duration::from_secs(60), 10 * S).unwrap();
+        table.complete(9, 75 * S);
+
+        let words = table.encode(80 * S);
+        assert_eq!(ScheduleTable::decode(&words, 90 * S), Some(table.clone()));
+        // RTC 定时器复位
+        assert_eq!(ScheduleTable::decode(&words, 5 * S), None);
+        let mut corrupted = words;
+        corrupted[HEADER_WORDS + 1] ^= 1;
+        assert_eq!(ScheduleTable::decode(&corrupted, 90 * S), None);
+
+        let mut buf = [0u8; MAX_SCHEDULES * MIRROR_ENTRY_LEN];
+        let len = table.encode_mirror(&mut buf);
+        let mirrored = ScheduleTable::decode_mirror(&buf[..len], 3 * S);
+        assert_eq!(mirrored.due(3 * S).as_slice(), &[7, 9]);
+        assert_eq!(mirrored.get(7).unwrap().period_secs, 6 * 3600);
+    }
+}
@@ -0,0 +1,731 @@
+//! 串口文件传输
+//!
+//! 经 UART / USB-Serial-JTAG 对 VFS 推送、拉取、列出和删除文件，
+//! 无需 WiFi 或重新烧录即可从主机脚本更新资源与配置。
+//!
+//! 帧格式 (COBS 编码，0x00 分隔):
+//! - 请求: `[op][seq][参数...][crc16_ccitt: u16 LE]`
+//! - 应答: `[op | 0x80][seq][status][结果...][crc16_ccitt: u16 LE]`
+//!
+//! 多字节整数均为小端。`seq` 由主机选择并原样返回，用于丢弃迟到的应答；
+//! 帧校验失败时设备不应答，主机超时重发即可。
+//!
+//! | op | 命令 | 参数 | 结果 |
+//! |----|------|------|------|
+//! | 0x00 | PING | - | `version: u8, max_chunk: u16` |
+//! | 0x01 | PUSH_BEGIN | `flags: u8, size: u32, crc32: u32, path` | `offset: u32, crc32: u32` |
+//! | 0x02 | PUSH_DATA | `offset: u32, data` | `next: u32` |
+//! | 0x03 | PUSH_END | - | `size: u32` |
+//! | 0x04 | PULL | `offset: u32, len: u16, path` | `size: u32, data` |
+//! | 0x05 | STAT | `path` | `type: u8, size: u32, crc32: u32` |
+//! | 0x06 | LIST | `index: u32, path` | `type: u8, size: u32, name` |
+//! | 0x07 | DELETE | `path` | - |
+//!
+//! 推送先写入 `<path>.part`，`PUSH_END` 回读整个文件校验 CRC32
+//! (`fs::idf_nvs::crc32_le`，与 zlib 相同) 后才重命名为目标文件，
+//! 中途断开不会留下半个配置文件。再次 `PUSH_BEGIN` 同一文件时返回已写入的
+//! 长度与其 CRC32，主机核对前缀一致后从该偏移续传；不一致时设置
+//! `FLAG_RESTART` 重新开始。`PUSH_DATA` 偏移小于当前进度视为重发，直接确认；
+//! 超前时返回 `OutOfOrder`，主机重新 `PUSH_BEGIN` 取得进度。失败应答不带结果。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::protocols::filexfer::FileServer;
+//!
+//! static SERVER: StaticCell<FileServer<'static, 'static>> = StaticCell::new();
+//!
+//! #[embassy_executor::task]
+//! async fn filexfer_task(uart: Uart<'static, Async>, vfs: &'static Vfs<'static>) {
+//!     let server = SERVER.init(FileServer::new(vfs));
+//!     server.run(&mut uart).await
+//! }
+//! ```
+
+use core::fmt;
+
+use embedded_io_async::{Read, Write};
+
+use super::framing::{cobs, crc16_ccitt, CobsDecoder, FramingError};
+use crate::fs::idf_nvs::crc32_le;
+use crate::fs::littlefs::{FileType, FsError};
+use crate::fs::storage::StorageError;
+use crate::fs::vfs::{Vfs, MAX_MOUNTS};
+use crate::util::log::*;
+
+/// 协议版本
+pub const PROTOCOL_VERSION: u8 = 1;
+
+/// 单次 `PUSH_DATA` / `PULL` 的数据上限
+pub const MAX_CHUNK: usize = 512;
+
+/// 路径长度上限 (与 `Metadata::name` 一致)
+pub const MAX_PATH: usize = 64;
+
+/// 解码后帧长度上限
+pub const MAX_FRAME: usize = 16 + MAX_CHUNK + MAX_PATH;
+
+/// 编码后帧长度上限
+pub const MAX_ENCODED_FRAME: usize = cobs::max_encoded_len(MAX_FRAME);
+
+/// 推送临时文件后缀
+pub const PART_SUFFIX: &str = ".part";
+
+/// `PUSH_BEGIN` 标志: 丢弃已有的临时文件
+pub const FLAG_RESTART: u8 = 0x01;
+
+/// 应答标志位
+const RESPONSE: u8 = 0x80;
+
+/// 帧头 (op + seq) 与帧尾 CRC 开销
+const FRAME_OVERHEAD: usize = 4;
+
+// ===== 命令与状态 =====
+
+/// 命令码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Op {
+    /// 探测设备
+    Ping = 0x00,
+    /// 开始推送
+    PushBegin = 0x01,
+    /// 推送数据块
+    PushData = 0x02,
+    /// 结束推送并校验
+    PushEnd = 0x03,
+    /// 拉取数据块
+    Pull = 0x04,
+    /// 查询文件信息
+    Stat = 0x05,
+    /// 列出目录条目
+    List = 0x06,
+    /// 删除文件或空目录
+    Delete = 0x07,
+}
+
+impl Op {
+    /// 从命令码解析
+    pub const fn from_u8(value: u8) -> Option<Self> {
+        Some(match value {
+            0x00 => Self::Ping,
+            0x01 => Self::PushBegin,
+            0x02 => Self::PushData,
+            0x03 => Self::PushEnd,
+            0x04 => Self::Pull,
+            0x05 => Self::Stat,
+            0x06 => Self::List,
+            0x07 => Self::Delete,
+            _ => return None,
+        })
+    }
+}
+
+/// 应答状态码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Status {
+    /// 成功
+    Ok = 0,
+    /// 参数长度或格式错误
+    BadRequest = 1,
+    /// 未知命令
+    UnknownOp = 2,
+    /// 路径非法或过长
+    InvalidPath = 3,
+    /// 文件不存在
+    NotFound = 4,
+    /// 没有进行中的推送
+    NoSession = 5,
+    /// 数据块偏移超前于当前进度
+    OutOfOrder = 6,
+    /// 文件 CRC32 校验失败
+    CrcMismatch = 7,
+    /// 文件长度与声明不符
+    SizeMismatch = 8,
+    /// 挂载点只读
+    ReadOnly = 9,
+    /// 空间不足
+    NoSpace = 10,
+    /// 文件系统错误
+    Io = 11,
+    /// 目录已列举完毕
+    EndOfDir = 12,
+}
+
+impl From<FsError> for Status {
+    fn from(e: FsError) -> Self {
+        match e {
+            FsError::NotFound => Self::NotFound,
+            FsError::Storage(StorageError::WriteProtected) => Self::ReadOnly,
+            FsError::NoSpace | FsError::Full => Self::NoSpace,
+            FsError::InvalidParam | FsError::PathTooLong | FsError::NameTooLong => Self::InvalidPath,
+            _ => Self::Io,
+        }
+    }
+}
+
+/// 传输错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XferError {
+    /// 串口读写失败
+    Io,
+    /// 帧编码错误
+    Framing(FramingError),
+    /// 帧过短
+    Truncated,
+    /// 帧校验失败
+    ChecksumMismatch,
+}
+
+impl From<FramingError> for XferError {
+    fn from(e: FramingError) -> Self {
+        Self::Framing(e)
+    }
+}
+
+impl fmt::Display for XferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XferError::Io => write!(f, "Serial I/O error"),
+            XferError::Framing(e) => write!(f, "Framing error: {}", e),
+            XferError::Truncated => write!(f, "Frame too short"),
+            XferError::ChecksumMismatch => write!(f, "Frame checksum mismatch"),
+        }
+    }
+}
+
+// ===== 服务端 =====
+
+/// 进行中的推送
+struct PushSession {
+    path: heapless::String<MAX_PATH>,
+    size: u32,
+    crc: u32,
+    written: u32,
+}
+
+/// 服务端统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct XferStats {
+    /// 已处理的请求
+    pub requests: u32,
+    /// 丢弃的坏帧 (编码或校验错误)
+    pub bad_frames: u32,
+    /// 完成并通过校验的推送
+    pub pushes: u32,
+    /// 续传 (从非零偏移继续) 次数
+    pub resumes: u32,
+    /// CRC32 校验失败的推送
+    pub crc_failures: u32,
+}
+
+/// 文件传输服务端
+///
+/// 包含约 3 KB 收发缓冲区，建议放在 `StaticCell` 中
+pub struct FileServer<'v, 'a, const N: usize = MAX_MOUNTS> {
+    vfs: &'v Vfs<'a, N>,
+    session: Option<PushSession>,
+    stats: XferStats,
+    decoder: CobsDecoder<MAX_ENCODED_FRAME>,
+    response: [u8; MAX_FRAME],
+    tx: [u8; MAX_ENCODED_FRAME],
+}
+
+impl<'v, 'a, const N: usize> FileServer<'v, 'a, N> {
+    /// 创建服务端
+    pub const fn new(vfs: &'v Vfs<'a, N>) -> Self {
+        Self {
+            vfs,
+            session: None,
+            stats: XferStats { requests: 0, bad_frames: 0, pushes: 0, resumes: 0, crc_failures: 0 },
+            decoder: CobsDecoder::new(),
+            response: [0; MAX_FRAME],
+            tx: [0; MAX_ENCODED_FRAME],
+        }
+    }
+
+    /// 统计
+    pub fn stats(&self) -> XferStats {
+        self.stats
+    }
+
+    /// 进行中推送的目标路径与进度 `(path, written, size)`
+    pub fn pending_push(&self) -> Option<(&str, u32, u32)> {
+        self.session.as_ref().map(|s| (s.path.as_str(), s.written, s.size))
+    }
+
+    /// 持续处理请求 (永不返回)
+    pub async fn run<T: Read + Write>(&mut self, transport: &mut T) -> ! {
+        log_info!("File transfer server started (protocol v{})", PROTOCOL_VERSION);
+        loop {
+            if let Err(_e) = self.poll(transport).await {
+                log_warn!("File transfer: {}", _e);
+            }
+        }
+    }
+
+    /// 读取串口数据并应答其中的完整帧
+    ///
+    /// 坏帧计入统计后丢弃，只有串口错误才返回
+    pub async fn poll<T: Read + Write>(&mut self, transport: &mut T) -> Result<(), XferError> {
+        let mut rx = [0u8; 64];
+        let n = transport.read(&mut rx).await.map_err(|_| XferError::Io)?;
+        for &byte in &rx[..n] {
+            let len = match self.decoder.push(byte) {
+                Ok(Some(frame)) => {
+                    // 帧在下一次 push 前有效，先复制出来再处理
+                    let mut request = [0u8; MAX_FRAME];
+                    request[..frame.len()].copy_from_slice(frame);
+                    let len = frame.len();
+                    match self.handle(&request[..len]) {
+                        Ok(len) => len,
+                        Err(_) => {
+                            self.stats.bad_frames += 1;
+                            continue;
+                        }
+                    }
+                }
+                Ok(None) => continue,
+                Err(_) => {
+                    self.stats.bad_frames += 1;
+                    continue;
+                }
+            };
+            let encoded = cobs::encode(&self.response[..len], &mut self.tx)?;
+            transport.write_all(&self.tx[..encoded]).await.map_err(|_| XferError::Io)?;
+            transport.flush().await.map_err(|_| XferError::Io)?;
+        }
+        Ok(())
+    }
+
+    /// 处理一个已解码的请求帧，应答 (未编码) 写入内部缓冲区
+    ///
+    /// # 返回
+    /// 应答长度
+    pub fn handle(&mut self, frame: &[u8]) -> Result<usize, XferError> {
+        if frame.len() < FRAME_OVERHEAD {
+            return Err(XferError::Truncated);
+        }
+        let (body, crc) = frame.split_at(frame.len() - 2);
+        if crc16_ccitt(body) != u16::from_le_bytes([crc[0], crc[1]]) {
+            return Err(XferError::ChecksumMismatch);
+        }
+        self.stats.requests += 1;
+
+        let (op, seq, args) = (body[0], body[1], &body[2..]);
+        self.response[0] = op | RESPONSE;
+        self.response[1] = seq;
+        let mut out = Reply { buf: &mut self.response, len: 3 };
+        let status = match Op::from_u8(op) {
+            Some(op) => Self::dispatch(self.vfs, &mut self.session, &mut self.stats, op, args, &mut out),
+            None => Err(Status::UnknownOp),
+        };
+        let mut len = match status {
+            Ok(()) => out.len,
+            Err(_) => 3,
+        };
+        self.response[2] = status.err().unwrap_or(Status::Ok) as u8;
+        let crc = crc16_ccitt(&self.response[..len]);
+        self.response[len..len + 2].copy_from_slice(&crc.to_le_bytes());
+        len += 2;
+        Ok(len)
+    }
+
+    /// 应答缓冲区
+    pub fn response(&self) -> &[u8] {
+        &self.response
+    }
+
+    fn dispatch(
+        vfs: &Vfs<'a, N>,
+        session: &mut Option<PushSession>,
+        stats: &mut XferStats,
+        op: Op,
+        args: &[u8],
+        out: &mut Reply<'_>,
+    ) -> Result<(), Status> {
+        match op {
+            Op::Ping => {
+                out.put(&[PROTOCOL_VERSION])?;
+                out.put(&(MAX_CHUNK as u16).to_le_bytes())
+            }
+            Op::PushBegin => {
+                let flags = *args.first().ok_or(Status::BadRequest)?;
+                let size = le32(args, 1)?;
+                let crc = le32(args, 5)?;
+                let path = parse_path(args.get(9..).ok_or(Status::BadRequest)?)?;
+                let part = part_path(path)?;
+
+                let mut written = match vfs.metadata(&part) {
+                    Ok(meta) if meta.is_file() => meta.size,
+                    Ok(_) => return Err(Status::InvalidPath),
+                    Err(FsError::NotFound) => 0,
+                    Err(e) => return Err(e.into()),
+                };
+                if flags & FLAG_RESTART != 0 || written > size || written == 0 {
+                    vfs.write(&part, &[], false)?;
+                    written = 0;
+                }
+                let prefix_crc = file_crc(vfs, &part, written)?;
+                if written > 0 {
+                    stats.resumes += 1;
+                    log_info!("Resuming push of {} at {}/{}", path, written, size);
+                }
+                *session = Some(PushSession {
+                    path: heapless::String::try_from(path).map_err(|_| Status::InvalidPath)?,
+                    size,
+                    crc,
+                    written,
+                });
+                out.put(&written.to_le_bytes())?;
+                out.put(&prefix_crc.to_le_bytes())
+            }
+            Op::PushData => {
+                let push = session.as_mut().ok_or(Status::NoSession)?;
+                let offset = le32(args, 0)?;
+                let data = &args[4..];
+                if offset > push.written {
+                    return Err(Status::OutOfOrder);
+                }
+                // 偏移小于进度: 主机未收到上次应答而重发，只写入新增部分
+                let skip = (push.written - offset) as usize;
+                if skip < data.len() {
+                    let fresh = &data[skip..];
+                    if push.written as usize + fresh.len() > push.size as usize {
+                        return Err(Status::SizeMismatch);
+                    }
+                    vfs.write(&part_path(&push.path)?, fresh, true)?;
+                    push.written += fresh.len() as u32;
+                }
+                out.put(&push.written.to_le_bytes())
+            }
+            Op::PushEnd => {
+                let push = session.take().ok_or(Status::NoSession)?;
+                let part = part_path(&push.path)?;
+                if push.written != push.size {
+                    *session = Some(push);
+                    return Err(Status::SizeMismatch);
+                }
+                if file_crc(vfs, &part, push.size)? != push.crc {
+                    stats.crc_failures += 1;
+                    log_warn!("Push of {} failed CRC check", push.path.as_str());
+                    vfs.remove(&part)?;
+                    return Err(Status::CrcMismatch);
+                }
+                match vfs.remove(&push.path) {
+                    Ok(()) | Err(FsError::NotFound) => {}
+                    Err(e) => return Err(e.into()),
+                }
+                vfs.rename(&part, &push.path)?;
+                stats.pushes += 1;
+                log_info!("Pushed {} ({} bytes)", push.path.as_str(), push.size);
+                out.put(&push.size.to_le_bytes())
+            }
+            Op::Pull => {
+                let offset = le32(args, 0)?;
+                let len = args.get(4..6).ok_or(Status::BadRequest)?;
+                let len = u16::from_le_bytes([len[0], len[1]]);
+                let path = parse_path(&args[6..])?;
+                let meta = vfs.metadata(path)?;
+                if !meta.is_file() {
+                    return Err(Status::InvalidPath);
+                }
+                out.put(&meta.size.to_le_bytes())?;
+                let len = (len as usize).min(MAX_CHUNK).min(meta.size.saturating_sub(offset) as usize);
+                let n = vfs.read(path, offset, out.reserve(len)?)?;
+                out.len -= len - n;
+                Ok(())
+            }
+            Op::Stat => {
+                let path = parse_path(args)?;
+                let meta = vfs.metadata(path)?;
+                let crc = if meta.is_file() { file_crc(vfs, path, meta.size)? } else { 0 };
+                out.put(&[file_type(meta.file_type)])?;
+                out.put(&meta.size.to_le_bytes())?;
+                out.put(&crc.to_le_bytes())
+            }
+            Op::List => {
+                let index = le32(args, 0)?;
+                let path = parse_path(&args[4..])?;
+                let meta = vfs.dir_entry(path, index)?.ok_or(Status::EndOfDir)?;
+                out.put(&[file_type(meta.file_type)])?;
+                out.put(&meta.size.to_le_bytes())?;
+                out.put(meta.name.as_bytes())
+            }
+            Op::Delete => {
+                let path = parse_path(args)?;
+                vfs.remove(path)?;
+                // 删除推送目标时一并放弃进行中的推送
+                if session.as_ref().is_some_and(|s| s.path == path) {
+                    *session = None;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// 应答写入游标
+struct Reply<'b> {
+    buf: &'b mut [u8; MAX_FRAME],
+    len: usize,
+}
+
+impl Reply<'_> {
+    fn put(&mut self, data: &[u8]) -> Result<(), Status> {
+        self.reserve(data.len())?.copy_from_slice(data);
+        Ok(())
+    }
+
+    /// 预留 `len` 字节 (尾部留出 CRC)
+    fn reserve(&mut self, len: usize) -> Result<&mut [u8], Status> {
+        if self.len + len + 2 > MAX_FRAME {
+            return Err(Status::BadRequest);
+        }
+        let start = self.len;
+        self.len += len;
+        Ok(&mut self.buf[start..start + len])
+    }
+}
+
+// ===== 辅助函数 =====
+
+fn le32(data: &[u8], offset: usize) -> Result<u32, Status> {
+    let bytes = data.get(offset..offset + 4).ok_or(Status::BadRequest)?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// 解析并检查路径 (绝对路径，不含 `..` 与空字符)
+fn parse_path(data: &[u8]) -> Result<&str, Status> {
+    let path = core::str::from_utf8(data).map_err(|_| Status::InvalidPath)?;
+    let valid = path.starts_with('/')
+        && path.len() <= MAX_PATH
+        && !path.contains('\0')
+        && !path.split('/').any(|part| part == "..");
+    if valid {
+        Ok(path)
+    } else {
+        Err(Status::InvalidPath)
+    }
+}
+
+fn part_path(path: &str) -> Result<heapless::String<{ MAX_PATH + 5 }>, Status> {
+    let mut part = heapless::String::new();
+    part.push_str(path).map_err(|_| Status::InvalidPath)?;
+    part.push_str(PART_SUFFIX).map_err(|_| Status::InvalidPath)?;
+    Ok(part)
+}
+
+/// 文件前 `len` 字节的 CRC32
+fn file_crc<const N: usize>(vfs: &Vfs<'_, N>, path: &str, len: u32) -> Result<u32, Status> {
+    let mut buf = [0u8; 256];
+    let mut crc = 0;
+    let mut offset = 0;
+    while offset < len {
+        let want = buf.len().min((len - offset) as usize);
+        let n = vfs.read(path, offset, &mut buf[..want])?;
+        if n == 0 {
+            return Err(Status::SizeMismatch);
+        }
+        crc = crc32_le(crc, &buf[..n]);
+        offset += n as u32;
+    }
+    Ok(crc)
+}
+
+fn file_type(file_type: FileType) -> u8 {
+    match file_type {
+        FileType::File => 0,
+        FileType::Directory => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::littlefs::{FsConfig, Metadata};
+    use crate::fs::vfs::MountedFs;
+    use core::cell::RefCell;
+
+    static CONFIG: FsConfig = FsConfig {
+        block_size: 4096,
+        block_count: 16,
+        read_size: 256,
+        prog_size: 256,
+        cache_size: 512,
+        lookahead_size: 16,
+        block_cycles: 500,
+        extent_blocks: 0,
+    };
+
+    /// 单文件内存文件系统
+    #[derive(Default)]
+    struct MemFs {
+        files: RefCell<heapless::Vec<(heapless::String<MAX_PATH>, heapless::Vec<u8, 1024>), 4>>,
+    }
+
+    impl MemFs {
+        fn find(&self, path: &str) -> Option<usize> {
+            self.files.borrow().iter().position(|(p, _)| p == path)
+        }
+    }
+
+    impl MountedFs for MemFs {
+        fn is_mounted(&self) -> bool {
+            true
+        }
+        fn config(&self) -> &FsConfig {
+            &CONFIG
+        }
+        fn free_blocks(&self) -> Result<u32, FsError> {
+            Ok(0)
+        }
+        fn read_at(&self, path: &str, offset: u32, buffer: &mut [u8]) -> Result<usize, FsError> {
+            let files = self.files.borrow();
+            let data = &files[self.find(path).ok_or(FsError::NotFound)?].1;
+            let data = data.get(offset as usize..).unwrap_or(&[]);
+            let n = data.len().min(buffer.len());
+            buffer[..n].copy_from_slice(&data[..n]);
+            Ok(n)
+        }
+        fn write_file(&self, path: &str, data: &[u8], append: bool) -> Result<(), FsError> {
+            let index = match self.find(path) {
+                Some(i) => i,
+                None => {
+                    let name = heapless::String::try_from(path).map_err(|_| FsError::NameTooLong)?;
+                    self.files.borrow_mut().push((name, heapless::Vec::new())).map_err(|_| FsError::Full)?;
+                    self.files.borrow().len() - 1
+                }
+            };
+            let file = &mut self.files.borrow_mut()[index].1;
+            if !append {
+                file.clear();
+            }
+            file.extend_from_slice(data).map_err(|_| FsError::NoSpace)
+        }
+        fn metadata(&self, path: &str) -> Result<Metadata, FsError> {
+            let files = self.files.borrow();
+            let (name, data) = &files[self.find(path).ok_or(FsError::NotFound)?];
+            Ok(Metadata { file_type: FileType::File, size: data.len() as u32, name: name.clone() })
+        }
+        fn remove(&self, path: &str) -> Result<(), FsError> {
+            let index = self.find(path).ok_or(FsError::NotFound)?;
+            self.files.borrow_mut().swap_remove(index);
+            Ok(())
+        }
+        fn rename(&self, old_path: &str, new_path: &str) -> Result<(), FsError> {
+            let index = self.find(old_path).ok_or(FsError::NotFound)?;
+            self.files.borrow_mut()[index].0 = heapless::String::try_from(new_path).map_err(|_| FsError::NameTooLong)?;
+            Ok(())
+        }
+        fn create_dir_all(&self, _: &str) -> Result<(), FsError> {
+            Ok(())
+        }
+        fn dir_entry(&self, _: &str, index: u32) -> Result<Option<Metadata>, FsError> {
+            let files = self.files.borrow();
+            Ok(files.get(index as usize).map(|(name, data)| Metadata {
+                file_type: FileType::File,
+                size: data.len() as u32,
+                name: name.clone(),
+            }))
+        }
+    }
+
+    fn request(op: Op, seq: u8, args: &[u8]) -> heapless::Vec<u8, MAX_FRAME> {
+        let mut frame = heapless::Vec::new();
+        frame.extend_from_slice(&[op as u8, seq]).unwrap();
+        frame.extend_from_slice(args).unwrap();
+        let crc = crc16_ccitt(&frame);
+        frame.extend_from_slice(&crc.to_le_bytes()).unwrap();
+        frame
+    }
+
+    /// 发送请求，返回 (状态, 结果)
+    fn call<'s, const N: usize>(server: &'s mut FileServer<'_, '_, N>, op: Op, args: &[u8]) -> (u8, &'s [u8]) {
+        let len = server.handle(&request(op, 7, args)).unwrap();
+        let response = &server.response()[..len];
+        assert_eq!((response[0], response[1]), (op as u8 | RESPONSE, 7));
+        assert_eq!(crc16_ccitt(&response[..len - 2]).to_le_bytes(), response[len - 2..]);
+        (response[2], &response[3..len - 2])
+    }
+
+    fn begin_args(flags: u8, data: &[u8], path: &str) -> heapless::Vec<u8, 80> {
+        let mut args = heapless::Vec::new();
+        args.push(flags).unwrap();
+        args.extend_from_slice(&(data.len() as u32).to_le_bytes()).unwrap();
+        args.extend_from_slice(&crc32_le(0, data).to_le_bytes()).unwrap();
+        args.extend_from_slice(path.as_bytes()).unwrap();
+        args
+    }
+
+    fn data_args(offset: usize, data: &[u8]) -> heapless::Vec<u8, 600> {
+        let mut args = heapless::Vec::new();
+        args.extend_from_slice(&(offset as u32).to_le_bytes()).unwrap();
+        args.extend_from_slice(data).unwrap();
+        args
+    }
+
+    #[test]
+    fn test_push_resume_and_pull() {
+        let fs = MemFs::default();
+        let mut vfs: Vfs<'_, 1> = Vfs::new();
+        vfs.register("/cfg", &fs).unwrap();
+        let content = b"{\"ssid\":\"lab\",\"channel\":6}";
+
+        // 第一次会话只写入前 10 字节后中断
+        let mut server = FileServer::new(&vfs);
+        assert_eq!(call(&mut server, Op::PushBegin, &begin_args(0, content, "/cfg/app.json")), (0, &[0u8; 8][..]));
+        assert_eq!(call(&mut server, Op::PushData, &data_args(0, &content[..10])).0, Status::Ok as u8);
+        // 重发同一块不会重复写入
+        assert_eq!(call(&mut server, Op::PushData, &data_args(0, &content[..10])), (0, &10u32.to_le_bytes()[..]));
+        assert_eq!(call(&mut server, Op::PushData, &data_args(20, &content[20..])).0, Status::OutOfOrder as u8);
+
+        // 新会话从已写入位置续传，返回前缀 CRC 供主机核对
+        let mut server = FileServer::new(&vfs);
+        let (status, result) = call(&mut server, Op::PushBegin, &begin_args(0, content, "/cfg/app.json"));
+        assert_eq!(status, Status::Ok as u8);
+        assert_eq!(le32(result, 0), Ok(10));
+        assert_eq!(le32(result, 4), Ok(crc32_le(0, &content[..10])));
+        assert_eq!(call(&mut server, Op::PushData, &data_args(10, &content[10..])).0, Status::Ok as u8);
+        assert_eq!(call(&mut server, Op::PushEnd, &[]).0, Status::Ok as u8);
+        assert_eq!(server.stats().resumes, 1);
+        assert!(!vfs.exists("/cfg/app.json.part").unwrap());
+
+        let (status, result) = call(&mut server, Op::Stat, b"/cfg/app.json");
+        assert_eq!(status, Status::Ok as u8);
+        assert_eq!(le32(result, 5), Ok(crc32_le(0, content)));
+
+        let mut pull = data_args(4, &8u16.to_le_bytes());
+        pull.extend_from_slice(b"/cfg/app.json").unwrap();
+        let (_, result) = call(&mut server, Op::Pull, &pull);
+        assert_eq!(le32(result, 0), Ok(content.len() as u32));
+        assert_eq!(&result[4..], &content[4..12]);
+    }
+
+    #[test]
+    fn test_rejects_bad_requests() {
+        let fs = MemFs::default();
+        let mut vfs: Vfs<'_, 1> = Vfs::new();
+        vfs.register("/cfg", &fs).unwrap();
+        let mut server = FileServer::new(&vfs);
+
+        // 校验错误的帧不应答
+        let mut frame = request(Op::Ping, 1, &[]);
+        frame[2] ^= 0xFF;
+        assert_eq!(server.handle(&frame), Err(XferError::ChecksumMismatch));
+
+        assert_eq!(call(&mut server, Op::PushData, &data_args(0, b"x")).0, Status::NoSession as u8);
+        assert_eq!(call(&mut server, Op::Delete, b"/cfg/../etc").0, Status::InvalidPath as u8);
+        assert_eq!(call(&mut server, Op::Stat, b"/cfg/missing").0, Status::NotFound as u8);
+        assert_eq!(call(&mut server, Op::List, &data_args(0, b"/cfg")).0, Status::EndOfDir as u8);
+
+        // CRC 不符时丢弃临时文件
+        let mut args = begin_args(FLAG_RESTART, b"abc", "/cfg/a.bin");
+        args[5] ^= 1;
+        call(&mut server, Op::PushBegin, &args);
+        call(&mut server, Op::PushData, &data_args(0, b"abc"));
+        assert_eq!(call(&mut server, Op::PushEnd, &[]).0, Status::CrcMismatch as u8);
+        assert!(!vfs.exists("/cfg/a.bin.part").unwrap() && !vfs.exists("/cfg/a.bin").unwrap());
+    }
+}
@@ -0,0 +1,384 @@
+//! 帧编解码
+//!
+//! 在字节流 (UART、SPI、TCP) 上划分消息边界:
+//! - `cobs`: COBS 编码，0x00 作为帧分隔符，开销 ≤ 1 + n/254 字节
+//! - `slip`: SLIP (RFC 1055)，0xC0 作为帧分隔符
+//! - `LengthCrc`: 2 字节长度前缀 + 负载 + CRC-16/CCITT，适合定长传输 (SPI)
+//!
+//! 编码函数写入调用方提供的缓冲区；解码器逐字节喂入，
+//! 帧完整时返回帧内容的切片 (在下一次 `push` 前有效)。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::protocols::framing::{cobs, CobsDecoder};
+//!
+//! let n = cobs::encode(b"hello", &mut out)?;
+//! uart.write_all(&out[..n]).await?;
+//!
+//! let mut decoder = CobsDecoder::<256>::new();
+//! for &b in received {
+//!     if let Some(frame) = decoder.push(b)? {
+//!         handle(frame);
+//!     }
+//! }
+//! ```
+
+use core::fmt;
+
+/// 编解码错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FramingError {
+    /// 输出缓冲区不足
+    BufferTooSmall,
+    /// 帧超过解码器容量
+    FrameTooLong,
+    /// 编码数据非法
+    InvalidEncoding,
+    /// 校验失败
+    ChecksumMismatch,
+}
+
+impl fmt::Display for FramingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FramingError::BufferTooSmall => write!(f, "Output buffer too small"),
+            FramingError::FrameTooLong => write!(f, "Frame exceeds decoder capacity"),
+            FramingError::InvalidEncoding => write!(f, "Invalid frame encoding"),
+            FramingError::ChecksumMismatch => write!(f, "Frame checksum mismatch"),
+        }
+    }
+}
+
+/// CRC-16/CCITT-FALSE (多项式 0x1021，初值 0xFFFF)
+pub fn crc16_ccitt(data: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    for &b in data {
+        crc ^= (b as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+// ===== COBS =====
+
+/// COBS 编解码
+pub mod cobs {
+    use super::FramingError;
+
+    /// 编码后最大长度 (含结尾 0x00)
+    pub const fn max_encoded_len(len: usize) -> usize {
+        len + len / 254 + 2
+    }
+
+    /// 编码并追加帧分隔符 0x00
+    ///
+    /// # 返回
+    /// 写入 `out` 的字节数
+    pub fn encode(input: &[u8], out: &mut [u8]) -> Result<usize, FramingError> {
+        if out.len() < max_encoded_len(input.len()) {
+            return Err(FramingError::BufferTooSmall);
+        }
+        let mut code_idx = 0;
+        let mut write = 1;
+        let mut code = 1u8;
+
+        for &b in input {
+            if b == 0 {
+                out[code_idx] = code;
+                code_idx = write;
+                write += 1;
+                code = 1;
+            } else {
+                out[write] = b;
+                write += 1;
+                code += 1;
+                if code == 0xFF {
+                    out[code_idx] = code;
+                    code_idx = write;
+                    write += 1;
+                    code = 1;
+                }
+            }
+        }
+        out[code_idx] = code;
+        out[write] = 0;
+        Ok(write + 1)
+    }
+
+    /// 解码一个完整帧 (不含分隔符)
+    pub fn decode(input: &[u8], out: &mut [u8]) -> Result<usize, FramingError> {
+        let mut read = 0;
+        let mut write = 0;
+        while read < input.len() {
+            let code = input[read];
+            if code == 0 {
+                return Err(FramingError::InvalidEncoding);
+            }
+            read += 1;
+            let run = code as usize - 1;
+            if read + run > input.len() {
+                return Err(FramingError::InvalidEncoding);
+            }
+            if write + run > out.len() {
+                return Err(FramingError::BufferTooSmall);
+            }
+            out[write..write + run].copy_from_slice(&input[read..read + run]);
+            write += run;
+            read += run;
+            if code != 0xFF && read < input.len() {
+                if write >= out.len() {
+                    return Err(FramingError::BufferTooSmall);
+                }
+                out[write] = 0;
+                write += 1;
+            }
+        }
+        Ok(write)
+    }
+}
+
+/// COBS 流式解码器
+pub struct CobsDecoder<const N: usize> {
+    raw: [u8; N],
+    raw_len: usize,
+    frame: [u8; N],
+    overflow: bool,
+}
+
+impl<const N: usize> CobsDecoder<N> {
+    /// 创建解码器
+    pub const fn new() -> Self {
+        Self { raw: [0; N], raw_len: 0, frame: [0; N], overflow: false }
+    }
+
+    /// 喂入一个字节
+    pub fn push(&mut self, byte: u8) -> Result<Option<&[u8]>, FramingError> {
+        if byte != 0 {
+            if self.raw_len == N {
+                self.overflow = true;
+            } else {
+                self.raw[self.raw_len] = byte;
+                self.raw_len += 1;
+            }
+            return Ok(None);
+        }
+
+        let len = core::mem::take(&mut self.raw_len);
+        if core::mem::take(&mut self.overflow) {
+            return Err(FramingError::FrameTooLong);
+        }
+        if len == 0 {
+            return Ok(None);
+        }
+        let n = cobs::decode(&self.raw[..len], &mut self.frame)?;
+        Ok(Some(&self.frame[..n]))
+    }
+
+    /// 丢弃未完成的帧
+    pub fn reset(&mut self) {
+        self.raw_len = 0;
+        self.overflow = false;
+    }
+}
+
+impl<const N: usize> Default for CobsDecoder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ===== SLIP =====
+
+/// SLIP 编解码
+pub mod slip {
+    use super::FramingError;
+
+    /// 帧结束
+    pub const END: u8 = 0xC0;
+    /// 转义
+    pub const ESC: u8 = 0xDB;
+    /// 转义后的 END
+    pub const ESC_END: u8 = 0xDC;
+    /// 转义后的 ESC
+    pub const ESC_ESC: u8 = 0xDD;
+
+    /// 编码 (帧首尾各一个 END)
+    pub fn encode(input: &[u8], out: &mut [u8]) -> Result<usize, FramingError> {
+        let mut write = 0;
+        let mut put = |b: u8| -> Result<(), FramingError> {
+            *out.get_mut(write).ok_or(FramingError::BufferTooSmall)? = b;
+            write += 1;
+            Ok(())
+        };
+        put(END)?;
+        for &b in input {
+            match b {
+                END => {
+                    put(ESC)?;
+                    put(ESC_END)?;
+                }
+                ESC => {
+                    put(ESC)?;
+                    put(ESC_ESC)?;
+                }
+                _ => put(b)?,
+            }
+        }
+        put(END)?;
+        Ok(write)
+    }
+}
+
+/// SLIP 流式解码器
+pub struct SlipDecoder<const N: usize> {
+    frame: [u8; N],
+    len: usize,
+    escaped: bool,
+    error: Option<FramingError>,
+}
+
+impl<const N: usize> SlipDecoder<N> {
+    /// 创建解码器
+    pub const fn new() -> Self {
+        Self { frame: [0; N], len: 0, escaped: false, error: None }
+    }
+
+    /// 喂入一个字节
+    pub fn push(&mut self, byte: u8) -> Result<Option<&[u8]>, FramingError> {
+        if byte == slip::END {
+            let len = core::mem::take(&mut self.len);
+            self.escaped = false;
+            if let Some(e) = self.error.take() {
+                return Err(e);
+            }
+            return Ok(if len == 0 { None } else { Some(&self.frame[..len]) });
+        }
+
+        let value = if self.escaped {
+            self.escaped = false;
+            match byte {
+                slip::ESC_END => slip::END,
+                slip::ESC_ESC => slip::ESC,
+                _ => {
+                    self.error = Some(FramingError::InvalidEncoding);
+                    return Ok(None);
+                }
+            }
+        } else if byte == slip::ESC {
+            self.escaped = true;
+            return Ok(None);
+        } else {
+            byte
+        };
+
+        if self.len == N {
+            self.error = Some(FramingError::FrameTooLong);
+        } else {
+            self.frame[self.len] = value;
+            self.len += 1;
+        }
+        Ok(None)
+    }
+}
+
+impl<const N: usize> Default for SlipDecoder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ===== 长度前缀 + CRC =====
+
+/// 长度前缀 + CRC 帧
+///
+/// 格式: `[len: u16 LE][payload][crc16_ccitt(payload): u16 LE]`
+pub struct LengthCrc;
+
+impl LengthCrc {
+    /// 帧头 + 帧尾开销
+    pub const OVERHEAD: usize = 4;
+
+    /// 编码
+    pub fn encode(payload: &[u8], out: &mut [u8]) -> Result<usize, FramingError> {
+        let total = payload.len() + Self::OVERHEAD;
+        if payload.len() > u16::MAX as usize || out.len() < total {
+            return Err(FramingError::BufferTooSmall);
+        }
+        out[0..2].copy_from_slice(&(payload.len() as u16).to_le_bytes());
+        out[2..2 + payload.len()].copy_from_slice(payload);
+        out[2 + payload.len()..total].copy_from_slice(&crc16_ccitt(payload).to_le_bytes());
+        Ok(total)
+    }
+
+    /// 从缓冲区开头解码一帧 (其后的填充字节被忽略)
+    ///
+    /// # 返回
+    /// 负载切片
+    pub fn decode(data: &[u8]) -> Result<&[u8], FramingError> {
+        if data.len() < Self::OVERHEAD {
+            return Err(FramingError::InvalidEncoding);
+        }
+        let len = u16::from_le_bytes([data[0], data[1]]) as usize;
+        if data.len() < len + Self::OVERHEAD {
+            return Err(FramingError::InvalidEncoding);
+        }
+        let payload = &data[2..2 + len];
+        let crc = u16::from_le_bytes([data[2 + len], data[3 + len]]);
+        if crc16_ccitt(payload) != crc {
+            return Err(FramingError::ChecksumMismatch);
+        }
+        Ok(payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cobs_roundtrip() {
+        let mut enc = [0u8; 16];
+        let n = cobs::encode(&[0x11, 0x00, 0x22, 0x00], &mut enc).unwrap();
+        assert_eq!(&enc[..n], &[0x02, 0x11, 0x02, 0x22, 0x01, 0x00]);
+
+        let mut dec = CobsDecoder::<16>::new();
+        let mut result = None;
+        for &b in &enc[..n] {
+            if let Some(frame) = dec.push(b).unwrap() {
+                let mut copy = [0u8; 4];
+                copy.copy_from_slice(frame);
+                result = Some(copy);
+            }
+        }
+        assert_eq!(result, Some([0x11, 0x00, 0x22, 0x00]));
+    }
+
+    #[test]
+    fn test_slip_escape() {
+        let mut enc = [0u8; 16];
+        let n = slip::encode(&[0xC0, 0x01, 0xDB], &mut enc).unwrap();
+        assert_eq!(&enc[..n], &[0xC0, 0xDB, 0xDC, 0x01, 0xDB, 0xDD, 0xC0]);
+
+        let mut dec = SlipDecoder::<8>::new();
+        let mut last = None;
+        for &b in &enc[..n] {
+            if let Some(frame) = dec.push(b).unwrap() {
+                last = Some(frame.len());
+            }
+        }
+        assert_eq!(last, Some(3));
+    }
+
+    #[test]
+    fn test_length_crc() {
+        assert_eq!(crc16_ccitt(b"123456789"), 0x29B1);
+        let mut buf = [0u8; 16];
+        let n = LengthCrc::encode(b"abc", &mut buf).unwrap();
+        assert_eq!(LengthCrc::decode(&buf).unwrap(), b"abc");
+        buf[3] ^= 1;
+        assert_eq!(LengthCrc::decode(&buf[..n]), Err(FramingError::ChecksumMismatch));
+    }
+}
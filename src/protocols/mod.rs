@@ -0,0 +1,9 @@
+//! 通信协议模块
+//!
+//! 与具体外设解耦的协议实现，传输层通过 `embedded-io-async` trait 注入:
+//! - `modbus`: Modbus RTU 从站、Modbus TCP 服务器/客户端
+//! - `framing`: COBS / SLIP / 长度前缀 + CRC 帧编解码
+//! - `filexfer`: 串口文件推送/拉取 (COBS 帧，CRC32 校验，断点续传)
+pub mod modbus;
+pub mod framing;
+pub mod filexfer;
@@ -0,0 +1,111 @@
+//! Modbus 协议
+//!
+//! # 特性
+//! - 寄存器映射抽象 (`RegisterMap`)，内置静态数组实现 `RegisterBank`
+//! - 支持功能码 0x01/0x02/0x03/0x04/0x05/0x06/0x0F/0x10
+//! - RTU 从站: CRC16 校验、按波特率计算的 t1.5/t3.5 帧间隔、广播地址
+//! - TCP 服务器/客户端: MBAP 帧、多连接并发 (与 RTU 共享寄存器映射)
+//! - 写操作完成后异步回调 (`WriteHandler`)
+//!
+//! 寄存器映射放在 `CriticalMutex` 中，协议任务与应用任务共享访问。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::protocols::modbus::{RegisterBank, RtuSlave, RtuTiming};
+//!
+//! static REGS: CriticalMutex<RegisterBank<16, 16, 64, 32>> =
+//!     CriticalMutex::new(RegisterBank::new());
+//!
+//! #[embassy_executor::task]
+//! async fn modbus_task(uart: Uart<'static, Async>) {
+//!     let mut slave = RtuSlave::new(uart, 0x11, RtuTiming::from_baud(115_200));
+//!     slave.run(&REGS, &mut ()).await;
+//! }
+//! ```
+
+pub mod registers;
+pub mod pdu;
+pub mod rtu;
+pub mod tcp;
+
+use core::fmt;
+
+pub use registers::{RegisterBank, RegisterMap, WriteEvent, WriteHandler, WriteKind};
+pub use pdu::{crc16, process_request, MAX_PDU_LEN};
+pub use rtu::{RtuSlave, RtuTiming};
+pub use tcp::{serve_connection, MbapHeader, ModbusTcpClient, MODBUS_TCP_PORT};
+
+/// Modbus 异常码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ExceptionCode {
+    /// 不支持的功能码
+    IllegalFunction = 0x01,
+    /// 非法数据地址
+    IllegalDataAddress = 0x02,
+    /// 非法数据值
+    IllegalDataValue = 0x03,
+    /// 从站设备故障
+    ServerDeviceFailure = 0x04,
+}
+
+impl ExceptionCode {
+    /// 从原始值转换
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0x01 => Some(ExceptionCode::IllegalFunction),
+            0x02 => Some(ExceptionCode::IllegalDataAddress),
+            0x03 => Some(ExceptionCode::IllegalDataValue),
+            0x04 => Some(ExceptionCode::ServerDeviceFailure),
+            _ => None,
+        }
+    }
+}
+
+/// Modbus 错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModbusError {
+    /// 传输层读写失败
+    Io,
+    /// CRC 校验失败
+    Crc,
+    /// 帧格式错误 (过短/过长/字段不一致)
+    InvalidFrame,
+    /// 等待响应超时
+    Timeout,
+    /// 对端返回异常响应
+    Exception(ExceptionCode),
+}
+
+impl fmt::Display for ModbusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModbusError::Io => write!(f, "Transport I/O error"),
+            ModbusError::Crc => write!(f, "CRC mismatch"),
+            ModbusError::InvalidFrame => write!(f, "Invalid frame"),
+            ModbusError::Timeout => write!(f, "Response timeout"),
+            ModbusError::Exception(code) => write!(f, "Exception response: {:#04x}", *code as u8),
+        }
+    }
+}
+
+/// 功能码
+pub mod function {
+    /// 读线圈
+    pub const READ_COILS: u8 = 0x01;
+    /// 读离散输入
+    pub const READ_DISCRETE_INPUTS: u8 = 0x02;
+    /// 读保持寄存器
+    pub const READ_HOLDING_REGISTERS: u8 = 0x03;
+    /// 读输入寄存器
+    pub const READ_INPUT_REGISTERS: u8 = 0x04;
+    /// 写单个线圈
+    pub const WRITE_SINGLE_COIL: u8 = 0x05;
+    /// 写单个寄存器
+    pub const WRITE_SINGLE_REGISTER: u8 = 0x06;
+    /// 写多个线圈
+    pub const WRITE_MULTIPLE_COILS: u8 = 0x0F;
+    /// 写多个寄存器
+    pub const WRITE_MULTIPLE_REGISTERS: u8 = 0x10;
+}
@@ -0,0 +1,255 @@
+//! PDU 处理与 CRC16
+//!
+//! PDU (功能码 + 数据) 与传输方式无关，RTU 和 TCP 都调用 `process_request`。
+
+use super::function::*;
+use super::{ExceptionCode, RegisterMap, WriteEvent, WriteKind};
+
+/// 最大 PDU 长度
+pub const MAX_PDU_LEN: usize = 253;
+
+/// 单次读取位的最大数量
+const MAX_READ_BITS: u16 = 2000;
+/// 单次读取寄存器的最大数量
+const MAX_READ_REGISTERS: u16 = 125;
+/// 单次写入线圈的最大数量
+const MAX_WRITE_COILS: u16 = 1968;
+/// 单次写入寄存器的最大数量
+const MAX_WRITE_REGISTERS: u16 = 123;
+
+// ===== CRC16 =====
+
+const fn build_crc_table() -> [u16; 256] {
+    let mut table = [0u16; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u16;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xA001 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+static CRC_TABLE: [u16; 256] = build_crc_table();
+
+/// Modbus CRC16 (多项式 0xA001，初值 0xFFFF)
+///
+/// 线上以小端序发送 (低字节在前)
+pub fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    for &b in data {
+        crc = (crc >> 8) ^ CRC_TABLE[((crc ^ b as u16) & 0xFF) as usize];
+    }
+    crc
+}
+
+// ===== 请求处理 =====
+
+#[inline]
+fn be16(data: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([data[offset], data[offset + 1]])
+}
+
+/// 检查地址范围 [start, start + qty) 不越过 0xFFFF
+#[inline]
+fn check_range(start: u16, qty: u16, max: u16) -> Result<(), ExceptionCode> {
+    if qty == 0 || qty > max {
+        return Err(ExceptionCode::IllegalDataValue);
+    }
+    if start as u32 + qty as u32 > 0x1_0000 {
+        return Err(ExceptionCode::IllegalDataAddress);
+    }
+    Ok(())
+}
+
+/// 处理一个请求 PDU
+///
+/// # 参数
+/// - `map`: 寄存器映射
+/// - `request`: 请求 PDU (功能码 + 数据)
+/// - `response`: 响应 PDU 缓冲区
+///
+/// # 返回
+/// (响应 PDU 长度, 写操作通知)。出错时写入异常响应 (功能码 | 0x80 + 异常码)。
+pub fn process_request<M: RegisterMap + ?Sized>(
+    map: &mut M,
+    request: &[u8],
+    response: &mut [u8; MAX_PDU_LEN],
+) -> (usize, Option<WriteEvent>) {
+    let function = request.first().copied().unwrap_or(0);
+    match handle(map, request, response) {
+        Ok(result) => result,
+        Err(code) => {
+            response[0] = function | 0x80;
+            response[1] = code as u8;
+            (2, None)
+        }
+    }
+}
+
+fn handle<M: RegisterMap + ?Sized>(
+    map: &mut M,
+    req: &[u8],
+    resp: &mut [u8; MAX_PDU_LEN],
+) -> Result<(usize, Option<WriteEvent>), ExceptionCode> {
+    let function = *req.first().ok_or(ExceptionCode::IllegalFunction)?;
+    resp[0] = function;
+
+    match function {
+        READ_COILS | READ_DISCRETE_INPUTS => {
+            if req.len() != 5 {
+                return Err(ExceptionCode::IllegalDataValue);
+            }
+            let (start, qty) = (be16(req, 1), be16(req, 3));
+            check_range(start, qty, MAX_READ_BITS)?;
+
+            let byte_count = qty.div_ceil(8) as usize;
+            resp[1] = byte_count as u8;
+            resp[2..2 + byte_count].fill(0);
+            for i in 0..qty {
+                let bit = if function == READ_COILS {
+                    map.read_coil(start + i)?
+                } else {
+                    map.read_discrete_input(start + i)?
+                };
+                if bit {
+                    resp[2 + (i / 8) as usize] |= 1 << (i % 8);
+                }
+            }
+            Ok((2 + byte_count, None))
+        }
+
+        READ_HOLDING_REGISTERS | READ_INPUT_REGISTERS => {
+            if req.len() != 5 {
+                return Err(ExceptionCode::IllegalDataValue);
+            }
+            let (start, qty) = (be16(req, 1), be16(req, 3));
+            check_range(start, qty, MAX_READ_REGISTERS)?;
+
+            resp[1] = (qty * 2) as u8;
+            for i in 0..qty {
+                let value = if function == READ_HOLDING_REGISTERS {
+                    map.read_holding(start + i)?
+                } else {
+                    map.read_input(start + i)?
+                };
+                let offset = 2 + i as usize * 2;
+                resp[offset..offset + 2].copy_from_slice(&value.to_be_bytes());
+            }
+            Ok((2 + qty as usize * 2, None))
+        }
+
+        WRITE_SINGLE_COIL => {
+            if req.len() != 5 {
+                return Err(ExceptionCode::IllegalDataValue);
+            }
+            let (addr, raw) = (be16(req, 1), be16(req, 3));
+            let value = match raw {
+                0xFF00 => true,
+                0x0000 => false,
+                _ => return Err(ExceptionCode::IllegalDataValue),
+            };
+            map.write_coil(addr, value)?;
+            resp[..5].copy_from_slice(req);
+            Ok((5, Some(WriteEvent { kind: WriteKind::Coils, start: addr, count: 1 })))
+        }
+
+        WRITE_SINGLE_REGISTER => {
+            if req.len() != 5 {
+                return Err(ExceptionCode::IllegalDataValue);
+            }
+            let (addr, value) = (be16(req, 1), be16(req, 3));
+            map.write_holding(addr, value)?;
+            resp[..5].copy_from_slice(req);
+            Ok((5, Some(WriteEvent { kind: WriteKind::HoldingRegisters, start: addr, count: 1 })))
+        }
+
+        WRITE_MULTIPLE_COILS => {
+            if req.len() < 6 {
+                return Err(ExceptionCode::IllegalDataValue);
+            }
+            let (start, qty, byte_count) = (be16(req, 1), be16(req, 3), req[5] as usize);
+            check_range(start, qty, MAX_WRITE_COILS)?;
+            if byte_count != qty.div_ceil(8) as usize || req.len() != 6 + byte_count {
+                return Err(ExceptionCode::IllegalDataValue);
+            }
+            // 先校验全部地址，避免部分写入
+            for i in 0..qty {
+                map.read_coil(start + i)?;
+            }
+            for i in 0..qty {
+                let bit = req[6 + (i / 8) as usize] & (1 << (i % 8)) != 0;
+                map.write_coil(start + i, bit)?;
+            }
+            resp[1..5].copy_from_slice(&req[1..5]);
+            Ok((5, Some(WriteEvent { kind: WriteKind::Coils, start, count: qty })))
+        }
+
+        WRITE_MULTIPLE_REGISTERS => {
+            if req.len() < 6 {
+                return Err(ExceptionCode::IllegalDataValue);
+            }
+            let (start, qty, byte_count) = (be16(req, 1), be16(req, 3), req[5] as usize);
+            check_range(start, qty, MAX_WRITE_REGISTERS)?;
+            if byte_count != qty as usize * 2 || req.len() != 6 + byte_count {
+                return Err(ExceptionCode::IllegalDataValue);
+            }
+            for i in 0..qty {
+                map.read_holding(start + i)?;
+            }
+            for i in 0..qty {
+                map.write_holding(start + i, be16(req, 6 + i as usize * 2))?;
+            }
+            resp[1..5].copy_from_slice(&req[1..5]);
+            Ok((5, Some(WriteEvent { kind: WriteKind::HoldingRegisters, start, count: qty })))
+        }
+
+        _ => Err(ExceptionCode::IllegalFunction),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::modbus::RegisterBank;
+
+    #[test]
+    fn test_crc16() {
+        // 01 03 00 00 00 0A -> C5 CD
+        assert_eq!(crc16(&[0x01, 0x03, 0x00, 0x00, 0x00, 0x0A]), 0xCDC5);
+    }
+
+    #[test]
+    fn test_read_write_registers() {
+        let mut bank: RegisterBank<8, 0, 4, 0> = RegisterBank::new();
+        let mut resp = [0u8; MAX_PDU_LEN];
+
+        let (len, event) = process_request(&mut bank, &[0x10, 0, 1, 0, 2, 4, 0x12, 0x34, 0x56, 0x78], &mut resp);
+        assert_eq!(len, 5);
+        assert_eq!(event, Some(WriteEvent { kind: WriteKind::HoldingRegisters, start: 1, count: 2 }));
+
+        let (len, _) = process_request(&mut bank, &[0x03, 0, 0, 0, 3], &mut resp);
+        assert_eq!(&resp[..len], &[0x03, 6, 0, 0, 0x12, 0x34, 0x56, 0x78]);
+    }
+
+    #[test]
+    fn test_exceptions() {
+        let mut bank: RegisterBank<8, 0, 4, 0> = RegisterBank::new();
+        let mut resp = [0u8; MAX_PDU_LEN];
+
+        let (len, _) = process_request(&mut bank, &[0x03, 0, 3, 0, 2], &mut resp);
+        assert_eq!(&resp[..len], &[0x83, 0x02]);
+
+        let (len, _) = process_request(&mut bank, &[0x2B], &mut resp);
+        assert_eq!(&resp[..len], &[0xAB, 0x01]);
+
+        // 部分越界的写入不修改任何寄存器
+        process_request(&mut bank, &[0x10, 0, 3, 0, 2, 4, 0, 1, 0, 2], &mut resp);
+        assert_eq!(bank.holding, [0; 4]);
+    }
+}
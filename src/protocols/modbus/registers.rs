@@ -0,0 +1,137 @@
+//! 寄存器映射
+//!
+//! RTU 与 TCP 共享同一套寄存器映射抽象。
+
+use super::ExceptionCode;
+
+/// 写操作类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteKind {
+    /// 线圈
+    Coils,
+    /// 保持寄存器
+    HoldingRegisters,
+}
+
+/// 写操作通知
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteEvent {
+    /// 写入类型
+    pub kind: WriteKind,
+    /// 起始地址
+    pub start: u16,
+    /// 数量
+    pub count: u16,
+}
+
+/// 寄存器映射
+///
+/// 地址越界时返回 `ExceptionCode::IllegalDataAddress`，
+/// 值不被接受时返回 `ExceptionCode::IllegalDataValue`。
+pub trait RegisterMap {
+    /// 读线圈
+    fn read_coil(&self, addr: u16) -> Result<bool, ExceptionCode>;
+
+    /// 读离散输入
+    fn read_discrete_input(&self, addr: u16) -> Result<bool, ExceptionCode>;
+
+    /// 读保持寄存器
+    fn read_holding(&self, addr: u16) -> Result<u16, ExceptionCode>;
+
+    /// 读输入寄存器
+    fn read_input(&self, addr: u16) -> Result<u16, ExceptionCode>;
+
+    /// 写线圈
+    fn write_coil(&mut self, addr: u16, value: bool) -> Result<(), ExceptionCode>;
+
+    /// 写保持寄存器
+    fn write_holding(&mut self, addr: u16, value: u16) -> Result<(), ExceptionCode>;
+}
+
+/// 写操作回调
+///
+/// 在寄存器写入完成、互斥锁释放后调用，可以执行异步操作 (例如写入 Flash)
+#[allow(async_fn_in_trait)]
+pub trait WriteHandler {
+    /// 写操作完成
+    async fn on_write(&mut self, event: WriteEvent);
+}
+
+/// 不需要回调时使用 `()`
+impl WriteHandler for () {
+    async fn on_write(&mut self, _event: WriteEvent) {}
+}
+
+/// 基于静态数组的寄存器映射 (地址从 0 开始)
+///
+/// # Type Parameters
+/// * `C` - 线圈数量
+/// * `D` - 离散输入数量
+/// * `H` - 保持寄存器数量
+/// * `I` - 输入寄存器数量
+pub struct RegisterBank<const C: usize, const D: usize, const H: usize, const I: usize> {
+    /// 线圈
+    pub coils: [bool; C],
+    /// 离散输入 (只读，由应用更新)
+    pub discrete_inputs: [bool; D],
+    /// 保持寄存器
+    pub holding: [u16; H],
+    /// 输入寄存器 (只读，由应用更新)
+    pub input: [u16; I],
+}
+
+impl<const C: usize, const D: usize, const H: usize, const I: usize> RegisterBank<C, D, H, I> {
+    /// 创建全零寄存器映射
+    pub const fn new() -> Self {
+        Self {
+            coils: [false; C],
+            discrete_inputs: [false; D],
+            holding: [0; H],
+            input: [0; I],
+        }
+    }
+}
+
+impl<const C: usize, const D: usize, const H: usize, const I: usize> Default for RegisterBank<C, D, H, I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[inline]
+fn get<T: Copy>(slice: &[T], addr: u16) -> Result<T, ExceptionCode> {
+    slice.get(addr as usize).copied().ok_or(ExceptionCode::IllegalDataAddress)
+}
+
+#[inline]
+fn set<T>(slice: &mut [T], addr: u16, value: T) -> Result<(), ExceptionCode> {
+    let slot = slice.get_mut(addr as usize).ok_or(ExceptionCode::IllegalDataAddress)?;
+    *slot = value;
+    Ok(())
+}
+
+impl<const C: usize, const D: usize, const H: usize, const I: usize> RegisterMap for RegisterBank<C, D, H, I> {
+    fn read_coil(&self, addr: u16) -> Result<bool, ExceptionCode> {
+        get(&self.coils, addr)
+    }
+
+    fn read_discrete_input(&self, addr: u16) -> Result<bool, ExceptionCode> {
+        get(&self.discrete_inputs, addr)
+    }
+
+    fn read_holding(&self, addr: u16) -> Result<u16, ExceptionCode> {
+        get(&self.holding, addr)
+    }
+
+    fn read_input(&self, addr: u16) -> Result<u16, ExceptionCode> {
+        get(&self.input, addr)
+    }
+
+    fn write_coil(&mut self, addr: u16, value: bool) -> Result<(), ExceptionCode> {
+        set(&mut self.coils, addr, value)
+    }
+
+    fn write_holding(&mut self, addr: u16, value: u16) -> Result<(), ExceptionCode> {
+        set(&mut self.holding, addr, value)
+    }
+}
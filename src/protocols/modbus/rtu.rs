@@ -0,0 +1,223 @@
+//! Modbus RTU 从站
+//!
+//! 帧边界按规范以 t3.5 静默判定: 收到首字节后，任意一次读取超过 t3.5
+//! 没有新数据即认为帧结束。
+
+use embassy_time::{with_timeout, Duration};
+use embedded_io_async::{Read, Write};
+
+use crate::sync::primitives::CriticalMutex;
+use crate::util::log::*;
+
+use super::pdu::{crc16, process_request, MAX_PDU_LEN};
+use super::{ModbusError, RegisterMap, WriteHandler};
+
+/// RTU 帧最大长度 (地址 + PDU + CRC)
+pub const MAX_ADU_LEN: usize = 1 + MAX_PDU_LEN + 2;
+
+/// 广播地址
+pub const BROADCAST_ADDRESS: u8 = 0;
+
+/// RTU 帧间隔
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RtuTiming {
+    /// 字符间最大间隔 t1.5
+    pub char_timeout: Duration,
+    /// 帧间静默 t3.5
+    pub frame_timeout: Duration,
+}
+
+impl RtuTiming {
+    /// 按波特率计算 (11 位/字符)
+    ///
+    /// 波特率高于 19200 时按规范使用固定值 750μs / 1750μs；
+    /// 为 0 (未知，如 USB 虚拟串口) 时同样使用固定值
+    pub const fn from_baud(baud: u32) -> Self {
+        if baud == 0 || baud > 19_200 {
+            Self {
+                char_timeout: Duration::from_micros(750),
+                frame_timeout: Duration::from_micros(1750),
+            }
+        } else {
+            let char_us = 11_000_000 / baud as u64;
+            Self {
+                char_timeout: Duration::from_micros(char_us * 3 / 2),
+                frame_timeout: Duration::from_micros(char_us * 7 / 2),
+            }
+        }
+    }
+}
+
+/// 从站统计
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RtuStats {
+    /// 已处理的本站请求
+    pub requests: u32,
+    /// 广播请求
+    pub broadcasts: u32,
+    /// CRC 错误
+    pub crc_errors: u32,
+    /// 格式错误或超长帧
+    pub invalid_frames: u32,
+    /// 发给其他从站的帧
+    pub ignored: u32,
+}
+
+/// Modbus RTU 从站
+pub struct RtuSlave<T> {
+    transport: T,
+    address: u8,
+    timing: RtuTiming,
+    stats: RtuStats,
+    rx: [u8; MAX_ADU_LEN],
+    tx: [u8; MAX_ADU_LEN],
+}
+
+impl<T: Read + Write> RtuSlave<T> {
+    /// 创建从站
+    ///
+    /// # 参数
+    /// - `transport`: 串口 (例如 esp-hal 异步 UART)
+    /// - `address`: 从站地址 (1 ~ 247)
+    /// - `timing`: 帧间隔
+    pub fn new(transport: T, address: u8, timing: RtuTiming) -> Self {
+        Self {
+            transport,
+            address,
+            timing,
+            stats: RtuStats::default(),
+            rx: [0; MAX_ADU_LEN],
+            tx: [0; MAX_ADU_LEN],
+        }
+    }
+
+    /// 从站地址
+    pub fn address(&self) -> u8 {
+        self.address
+    }
+
+    /// 统计信息
+    pub fn stats(&self) -> &RtuStats {
+        &self.stats
+    }
+
+    /// 释放串口
+    pub fn release(self) -> T {
+        self.transport
+    }
+
+    /// 持续处理请求 (永不返回)
+    pub async fn run<M, H>(&mut self, map: &CriticalMutex<M>, handler: &mut H) -> !
+    where
+        M: RegisterMap,
+        H: WriteHandler,
+    {
+        log_info!("Modbus RTU slave started, address {}", self.address);
+        loop {
+            // 帧错误已计入统计，继续等待下一帧
+            let _ = self.poll(map, handler).await;
+        }
+    }
+
+    /// 接收并处理一帧
+    pub async fn poll<M, H>(&mut self, map: &CriticalMutex<M>, handler: &mut H) -> Result<(), ModbusError>
+    where
+        M: RegisterMap,
+        H: WriteHandler,
+    {
+        let len = self.read_frame().await?;
+        if len < 4 {
+            self.stats.invalid_frames += 1;
+            return Err(ModbusError::InvalidFrame);
+        }
+
+        let crc = u16::from_le_bytes([self.rx[len - 2], self.rx[len - 1]]);
+        if crc16(&self.rx[..len - 2]) != crc {
+            self.stats.crc_errors += 1;
+            return Err(ModbusError::Crc);
+        }
+
+        let address = self.rx[0];
+        let broadcast = address == BROADCAST_ADDRESS;
+        if !broadcast && address != self.address {
+            self.stats.ignored += 1;
+            return Ok(());
+        }
+
+        let mut pdu = [0u8; MAX_PDU_LEN];
+        let (pdu_len, event) = {
+            let mut regs = map.lock().await;
+            process_request(&mut *regs, &self.rx[1..len - 2], &mut pdu)
+        };
+
+        if broadcast {
+            // 广播请求不应答
+            self.stats.broadcasts += 1;
+        } else {
+            self.stats.requests += 1;
+            self.tx[0] = self.address;
+            self.tx[1..1 + pdu_len].copy_from_slice(&pdu[..pdu_len]);
+            let crc = crc16(&self.tx[..1 + pdu_len]);
+            self.tx[1 + pdu_len..3 + pdu_len].copy_from_slice(&crc.to_le_bytes());
+
+            self.transport
+                .write_all(&self.tx[..3 + pdu_len])
+                .await
+                .map_err(|_| ModbusError::Io)?;
+            self.transport.flush().await.map_err(|_| ModbusError::Io)?;
+        }
+
+        if let Some(event) = event {
+            handler.on_write(event).await;
+        }
+        Ok(())
+    }
+
+    /// 读取一帧 (以 t3.5 静默或传输层 EOF 为界)
+    async fn read_frame(&mut self) -> Result<usize, ModbusError> {
+        // 等待帧首字节，不设超时；传输层已关闭时返回 0
+        let mut len = self.transport.read(&mut self.rx).await.map_err(|_| ModbusError::Io)?;
+        if len == 0 {
+            return Err(ModbusError::Io);
+        }
+        let mut overflow = false;
+
+        loop {
+            if len == self.rx.len() {
+                // 超长帧: 继续读取直到静默，然后丢弃
+                overflow = true;
+                len = 0;
+            }
+            match with_timeout(self.timing.frame_timeout, self.transport.read(&mut self.rx[len..])).await {
+                // EOF 与静默一样结束本帧，下一次读取再报告连接关闭
+                Ok(Ok(0)) | Err(_) => break,
+                Ok(Ok(n)) => len += n,
+                Ok(Err(_)) => return Err(ModbusError::Io),
+            }
+        }
+
+        if overflow {
+            self.stats.invalid_frames += 1;
+            return Err(ModbusError::InvalidFrame);
+        }
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timing_from_baud() {
+        let fast = RtuTiming::from_baud(115_200);
+        assert_eq!(fast.frame_timeout, Duration::from_micros(1750));
+
+        // 9600: 1 字符 = 1145μs
+        let slow = RtuTiming::from_baud(9600);
+        assert_eq!(slow.char_timeout, Duration::from_micros(1717));
+        assert_eq!(slow.frame_timeout, Duration::from_micros(4007));
+
+        assert_eq!(RtuTiming::from_baud(0), fast);
+    }
+}
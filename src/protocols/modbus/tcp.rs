@@ -0,0 +1,368 @@
+//! Modbus TCP 服务器与客户端
+//!
+//! MBAP 帧头 (7 字节，大端):
+//!
+//! | 偏移 | 长度 | 字段 |
+//! |------|------|------|
+//! | 0    | 2    | 事务 ID |
+//! | 2    | 2    | 协议 ID (0) |
+//! | 4    | 2    | 后续长度 (单元 ID + PDU) |
+//! | 6    | 1    | 单元 ID |
+//!
+//! 会话处理基于 `embedded-io-async`，可直接用于 `embassy_net::tcp::TcpSocket`。
+//! 启用 `network` feature 后 `listen` 基于 `TcpServer` 提供多连接并发服务器。
+
+use embassy_time::{with_timeout, Duration};
+use embedded_io_async::{Read, ReadExactError, Write};
+
+use crate::sync::primitives::CriticalMutex;
+
+use super::function::*;
+use super::pdu::{process_request, MAX_PDU_LEN};
+use super::{ExceptionCode, ModbusError, RegisterMap, WriteHandler};
+
+/// MBAP 帧头长度
+pub const MBAP_HEADER_LEN: usize = 7;
+
+/// Modbus TCP 默认端口
+pub const MODBUS_TCP_PORT: u16 = 502;
+
+/// Modbus TCP 帧最大长度
+pub const MAX_TCP_ADU_LEN: usize = MBAP_HEADER_LEN + MAX_PDU_LEN;
+
+/// MBAP 帧头
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MbapHeader {
+    /// 事务 ID
+    pub transaction_id: u16,
+    /// 后续长度 (单元 ID + PDU)
+    pub length: u16,
+    /// 单元 ID
+    pub unit_id: u8,
+}
+
+impl MbapHeader {
+    /// 解析帧头
+    pub fn parse(data: &[u8; MBAP_HEADER_LEN]) -> Result<Self, ModbusError> {
+        let protocol = u16::from_be_bytes([data[2], data[3]]);
+        let length = u16::from_be_bytes([data[4], data[5]]);
+        if protocol != 0 || length < 2 || length as usize > MAX_PDU_LEN + 1 {
+            return Err(ModbusError::InvalidFrame);
+        }
+        Ok(Self {
+            transaction_id: u16::from_be_bytes([data[0], data[1]]),
+            length,
+            unit_id: data[6],
+        })
+    }
+
+    /// 编码帧头
+    pub fn encode(&self) -> [u8; MBAP_HEADER_LEN] {
+        let mut out = [0u8; MBAP_HEADER_LEN];
+        out[0..2].copy_from_slice(&self.transaction_id.to_be_bytes());
+        out[4..6].copy_from_slice(&self.length.to_be_bytes());
+        out[6] = self.unit_id;
+        out
+    }
+
+    /// PDU 长度
+    pub fn pdu_len(&self) -> usize {
+        self.length as usize - 1
+    }
+}
+
+fn map_read_err<E>(e: ReadExactError<E>) -> ModbusError {
+    match e {
+        ReadExactError::UnexpectedEof => ModbusError::InvalidFrame,
+        ReadExactError::Other(_) => ModbusError::Io,
+    }
+}
+
+// ===== 服务器 =====
+
+/// 处理一个 TCP 连接上的全部请求
+///
+/// 对端正常关闭 (帧头处 EOF) 时返回 `Ok(())`。
+/// 单元 ID 不做过滤: TCP 直连时通常为 0xFF 或 0。
+pub async fn serve_connection<T, M, H>(
+    conn: &mut T,
+    map: &CriticalMutex<M>,
+    handler: &mut H,
+) -> Result<(), ModbusError>
+where
+    T: Read + Write,
+    M: RegisterMap,
+    H: WriteHandler,
+{
+    let mut header = [0u8; MBAP_HEADER_LEN];
+    let mut request = [0u8; MAX_PDU_LEN];
+    let mut response = [0u8; MAX_PDU_LEN];
+
+    loop {
+        match conn.read_exact(&mut header).await {
+            Ok(()) => {}
+            Err(ReadExactError::UnexpectedEof) => return Ok(()),
+            Err(ReadExactError::Other(_)) => return Err(ModbusError::Io),
+        }
+        let mbap = MbapHeader::parse(&header)?;
+        let pdu = &mut request[..mbap.pdu_len()];
+        conn.read_exact(pdu).await.map_err(map_read_err)?;
+
+        let (len, event) = {
+            let mut regs = map.lock().await;
+            process_request(&mut *regs, pdu, &mut response)
+        };
+
+        let reply = MbapHeader { length: len as u16 + 1, ..mbap };
+        conn.write_all(&reply.encode()).await.map_err(|_| ModbusError::Io)?;
+        conn.write_all(&response[..len]).await.map_err(|_| ModbusError::Io)?;
+        conn.flush().await.map_err(|_| ModbusError::Io)?;
+
+        if let Some(event) = event {
+            handler.on_write(event).await;
+        }
+    }
+}
+
+/// 多连接 Modbus TCP 服务器
+///
+/// 在一个 `TcpServer` 上并发运行 `N` 个 accept 循环，每个循环独占一组缓冲区，
+/// 因此最多同时服务 `N` 个客户端。每个连接持有一份 `handler` 克隆。
+/// 正常情况下不返回；端口无效等无法监听的错误才返回。
+#[cfg(feature = "network")]
+pub async fn listen<const N: usize, M, H>(
+    stack: &crate::net::tcp::NetworkStack<'_>,
+    port: u16,
+    map: &CriticalMutex<M>,
+    handler: H,
+) -> crate::net::tcp::NetworkError
+where
+    M: RegisterMap,
+    H: WriteHandler + Clone,
+{
+    use crate::net::tcp::{NetworkError, TcpServer};
+    use crate::util::log::*;
+
+    async fn accept_loop<M: RegisterMap, H: WriteHandler>(
+        server: &TcpServer<'_>,
+        map: &CriticalMutex<M>,
+        mut handler: H,
+    ) -> NetworkError {
+        let mut rx = [0u8; 512];
+        let mut tx = [0u8; 512];
+        loop {
+            let mut client = match server.accept(&mut rx, &mut tx).await {
+                Ok(client) => client,
+                Err(NetworkError::NotInitialized) => return NetworkError::NotInitialized,
+                Err(_) => continue,
+            };
+            client.set_timeout(Some(Duration::from_secs(60)));
+            log_debug!("Modbus TCP client connected");
+            let _ = serve_connection(&mut client, map, &mut handler).await;
+            let _ = client.close().await;
+            log_debug!("Modbus TCP client disconnected");
+        }
+    }
+
+    let mut server = TcpServer::new(stack, port);
+    if let Err(e) = server.listen().await {
+        log_error!("Modbus TCP server failed to listen: {}", e);
+        return e;
+    }
+    log_info!("Modbus TCP server listening on port {} ({} connections)", port, N);
+    let loops: [_; N] = core::array::from_fn(|_| accept_loop(&server, map, handler.clone()));
+    embassy_futures::select::select_array(loops).await.0
+}
+
+// ===== 客户端 =====
+
+/// Modbus TCP 客户端
+pub struct ModbusTcpClient<T> {
+    transport: T,
+    unit_id: u8,
+    transaction_id: u16,
+    timeout: Duration,
+}
+
+impl<T: Read + Write> ModbusTcpClient<T> {
+    /// 创建客户端 (传入已连接的 TCP socket)
+    pub fn new(transport: T, unit_id: u8) -> Self {
+        Self {
+            transport,
+            unit_id,
+            transaction_id: 0,
+            timeout: Duration::from_secs(1),
+        }
+    }
+
+    /// 设置响应超时
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 释放传输层
+    pub fn release(self) -> T {
+        self.transport
+    }
+
+    /// 读保持寄存器
+    pub async fn read_holding_registers(&mut self, start: u16, out: &mut [u16]) -> Result<(), ModbusError> {
+        self.read_registers(READ_HOLDING_REGISTERS, start, out).await
+    }
+
+    /// 读输入寄存器
+    pub async fn read_input_registers(&mut self, start: u16, out: &mut [u16]) -> Result<(), ModbusError> {
+        self.read_registers(READ_INPUT_REGISTERS, start, out).await
+    }
+
+    /// 读线圈
+    pub async fn read_coils(&mut self, start: u16, out: &mut [bool]) -> Result<(), ModbusError> {
+        self.read_bits(READ_COILS, start, out).await
+    }
+
+    /// 读离散输入
+    pub async fn read_discrete_inputs(&mut self, start: u16, out: &mut [bool]) -> Result<(), ModbusError> {
+        self.read_bits(READ_DISCRETE_INPUTS, start, out).await
+    }
+
+    /// 写单个线圈
+    pub async fn write_single_coil(&mut self, addr: u16, value: bool) -> Result<(), ModbusError> {
+        let raw: u16 = if value { 0xFF00 } else { 0x0000 };
+        let mut req = [WRITE_SINGLE_COIL, 0, 0, 0, 0];
+        req[1..3].copy_from_slice(&addr.to_be_bytes());
+        req[3..5].copy_from_slice(&raw.to_be_bytes());
+        let mut resp = [0u8; MAX_PDU_LEN];
+        self.transact(&req, &mut resp).await?;
+        Ok(())
+    }
+
+    /// 写单个寄存器
+    pub async fn write_single_register(&mut self, addr: u16, value: u16) -> Result<(), ModbusError> {
+        let mut req = [WRITE_SINGLE_REGISTER, 0, 0, 0, 0];
+        req[1..3].copy_from_slice(&addr.to_be_bytes());
+        req[3..5].copy_from_slice(&value.to_be_bytes());
+        let mut resp = [0u8; MAX_PDU_LEN];
+        self.transact(&req, &mut resp).await?;
+        Ok(())
+    }
+
+    /// 写多个寄存器 (最多 123 个)
+    pub async fn write_multiple_registers(&mut self, start: u16, values: &[u16]) -> Result<(), ModbusError> {
+        if values.is_empty() || values.len() > 123 {
+            return Err(ModbusError::InvalidFrame);
+        }
+        let mut req = [0u8; MAX_PDU_LEN];
+        req[0] = WRITE_MULTIPLE_REGISTERS;
+        req[1..3].copy_from_slice(&start.to_be_bytes());
+        req[3..5].copy_from_slice(&(values.len() as u16).to_be_bytes());
+        req[5] = (values.len() * 2) as u8;
+        for (i, v) in values.iter().enumerate() {
+            req[6 + i * 2..8 + i * 2].copy_from_slice(&v.to_be_bytes());
+        }
+        let mut resp = [0u8; MAX_PDU_LEN];
+        self.transact(&req[..6 + values.len() * 2], &mut resp).await?;
+        Ok(())
+    }
+
+    async fn read_registers(&mut self, function: u8, start: u16, out: &mut [u16]) -> Result<(), ModbusError> {
+        if out.is_empty() || out.len() > 125 {
+            return Err(ModbusError::InvalidFrame);
+        }
+        let req = read_request(function, start, out.len() as u16);
+        let mut resp = [0u8; MAX_PDU_LEN];
+        let len = self.transact(&req, &mut resp).await?;
+        if len != 2 + out.len() * 2 || resp[1] as usize != out.len() * 2 {
+            return Err(ModbusError::InvalidFrame);
+        }
+        for (i, v) in out.iter_mut().enumerate() {
+            *v = u16::from_be_bytes([resp[2 + i * 2], resp[3 + i * 2]]);
+        }
+        Ok(())
+    }
+
+    async fn read_bits(&mut self, function: u8, start: u16, out: &mut [bool]) -> Result<(), ModbusError> {
+        if out.is_empty() || out.len() > 2000 {
+            return Err(ModbusError::InvalidFrame);
+        }
+        let req = read_request(function, start, out.len() as u16);
+        let mut resp = [0u8; MAX_PDU_LEN];
+        let len = self.transact(&req, &mut resp).await?;
+        let byte_count = out.len().div_ceil(8);
+        if len != 2 + byte_count || resp[1] as usize != byte_count {
+            return Err(ModbusError::InvalidFrame);
+        }
+        for (i, v) in out.iter_mut().enumerate() {
+            *v = resp[2 + i / 8] & (1 << (i % 8)) != 0;
+        }
+        Ok(())
+    }
+
+    /// 发送请求并等待匹配的响应
+    ///
+    /// # 返回
+    /// 响应 PDU 长度
+    async fn transact(&mut self, request: &[u8], response: &mut [u8; MAX_PDU_LEN]) -> Result<usize, ModbusError> {
+        self.transaction_id = self.transaction_id.wrapping_add(1);
+        let header = MbapHeader {
+            transaction_id: self.transaction_id,
+            length: request.len() as u16 + 1,
+            unit_id: self.unit_id,
+        };
+        self.transport.write_all(&header.encode()).await.map_err(|_| ModbusError::Io)?;
+        self.transport.write_all(request).await.map_err(|_| ModbusError::Io)?;
+        self.transport.flush().await.map_err(|_| ModbusError::Io)?;
+
+        let timeout = self.timeout;
+        with_timeout(timeout, self.receive(request[0], response))
+            .await
+            .map_err(|_| ModbusError::Timeout)?
+    }
+
+    async fn receive(&mut self, function: u8, response: &mut [u8; MAX_PDU_LEN]) -> Result<usize, ModbusError> {
+        let mut raw = [0u8; MBAP_HEADER_LEN];
+        self.transport.read_exact(&mut raw).await.map_err(map_read_err)?;
+        let header = MbapHeader::parse(&raw)?;
+        let len = header.pdu_len();
+        self.transport.read_exact(&mut response[..len]).await.map_err(map_read_err)?;
+
+        if header.transaction_id != self.transaction_id {
+            return Err(ModbusError::InvalidFrame);
+        }
+        if response[0] == function | 0x80 {
+            let code = ExceptionCode::from_u8(response[1]).unwrap_or(ExceptionCode::ServerDeviceFailure);
+            return Err(ModbusError::Exception(code));
+        }
+        if response[0] != function {
+            return Err(ModbusError::InvalidFrame);
+        }
+        Ok(len)
+    }
+}
+
+fn read_request(function: u8, start: u16, qty: u16) -> [u8; 5] {
+    let mut req = [function, 0, 0, 0, 0];
+    req[1..3].copy_from_slice(&start.to_be_bytes());
+    req[3..5].copy_from_slice(&qty.to_be_bytes());
+    req
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mbap_roundtrip() {
+        let header = MbapHeader { transaction_id: 0x1234, length: 6, unit_id: 0xFF };
+        let raw = header.encode();
+        assert_eq!(raw, [0x12, 0x34, 0, 0, 0, 6, 0xFF]);
+        assert_eq!(MbapHeader::parse(&raw), Ok(header));
+        assert_eq!(header.pdu_len(), 5);
+    }
+
+    #[test]
+    fn test_mbap_rejects_bad_protocol() {
+        assert_eq!(MbapHeader::parse(&[0, 1, 0, 1, 0, 6, 1]), Err(ModbusError::InvalidFrame));
+        assert_eq!(MbapHeader::parse(&[0, 1, 0, 0, 0, 1, 1]), Err(ModbusError::InvalidFrame));
+    }
+}
@@ -0,0 +1,344 @@
+//! 系统事件总线
+//!
+//! 基于 `CriticalPubSub` 的全局广播通道，各子系统把状态变化发布到总线，
+//! 监控、日志、遥测等任务订阅后统一处理。
+//!
+//! 发布使用 `publish_immediate`: 总线满时丢弃最旧事件，发布方永不阻塞，
+//! 可以在高优先级任务中调用。
+//!
+//! 总线上的事件包装在 `Envelope` 中，附带全局递增序号、发布时间戳和来源子系统。
+//! WiFi/BLE 控制器的事件通道使用同一信封，事后可以按序号还原事件先后顺序、
+//! 用 `SequenceTracker` 统计丢失事件与事件间隔。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::sync::bus::{self, SequenceTracker, SystemEvent};
+//!
+//! let mut sub = bus::subscribe().unwrap();
+//! let mut tracker = SequenceTracker::new();
+//! loop {
+//!     let envelope = sub.next_message_pure().await;
+//!     let gap = tracker.observe(&envelope);
+//!     if gap.missed > 0 {
+//!         log_warn!("bus: {} event(s) dropped", gap.missed);
+//!     }
+//!     if let SystemEvent::StateChanged { machine, to, .. } = envelope.event {
+//!         log_info!("#{} {} -> {}", envelope.seq, machine, to);
+//!     }
+//! }
+//! ```
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::pubsub::{PubSubChannel, Subscriber};
+use embassy_time::{Duration, Instant};
+use portable_atomic::{AtomicU32, Ordering};
+
+use super::primitives::CriticalPubSub;
+
+/// 总线缓冲容量
+pub const BUS_CAPACITY: usize = 16;
+
+/// 最大订阅者数量
+pub const BUS_SUBSCRIBERS: usize = 8;
+
+/// 最大 (非即时) 发布者数量
+pub const BUS_PUBLISHERS: usize = 4;
+
+/// 系统事件
+///
+/// 负载只使用基础类型和 `&'static str`，保证 `Copy` 且与具体子系统解耦
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemEvent {
+    /// 状态机发生状态转换
+    StateChanged {
+        /// 状态机名称
+        machine: &'static str,
+        /// 原状态
+        from: &'static str,
+        /// 新状态
+        to: &'static str,
+        /// 触发事件
+        event: &'static str,
+    },
+    /// 认证成功
+    AccessGranted {
+        /// 服务名称 (如 "http"、"console")
+        service: &'static str,
+        /// 匹配的令牌名或用户名
+        principal: &'static str,
+        /// 来源键 (IPv4 地址或凭据摘要，0 表示未知)
+        source: u32,
+    },
+    /// 认证失败
+    AccessDenied {
+        /// 服务名称
+        service: &'static str,
+        /// 来源键
+        source: u32,
+        /// 当前连续失败次数
+        failures: u8,
+    },
+    /// 来源因连续失败被锁定
+    AccessLocked {
+        /// 服务名称
+        service: &'static str,
+        /// 来源键
+        source: u32,
+        /// 锁定时长 (秒)
+        seconds: u32,
+    },
+    /// 链路健康度变化
+    LinkHealthChanged {
+        /// 接口名称 (如 "wifi")
+        interface: &'static str,
+        /// 原健康度
+        from: &'static str,
+        /// 新健康度
+        to: &'static str,
+        /// 当前 RSSI (dBm，未关联时为 0)
+        rssi: i8,
+    },
+    /// 链路监管执行了恢复动作
+    LinkAction {
+        /// 接口名称
+        interface: &'static str,
+        /// 动作名称 (如 "reconnect")
+        action: &'static str,
+        /// 本轮故障中的第几次动作 (从 1 开始)
+        attempt: u8,
+        /// 动作是否执行成功
+        ok: bool,
+    },
+    /// 墙上时钟首次同步或发生跳变
+    TimeAdjusted {
+        /// 时间来源 (如 "sntp")
+        source: &'static str,
+        /// 变化量 (毫秒，正数表示向前拨)
+        delta_ms: i64,
+        /// 是否为首次同步
+        first: bool,
+    },
+    /// 定时器中断处理延迟超过 SLA 或错过节拍
+    TimingViolation {
+        /// 违规类别 ("latency" / "overrun")
+        kind: &'static str,
+        /// 处理延迟 (微秒)
+        latency_us: u32,
+        /// 错过的节拍数 (仅 "overrun")
+        missed: u32,
+    },
+    /// 启动编排完成
+    BootCompleted {
+        /// 就绪状态 ("ready" / "degraded" / "failed")
+        readiness: &'static str,
+        /// 未成功的步骤数
+        failed: u8,
+        /// 启动总耗时 (毫秒)
+        elapsed_ms: u32,
+    },
+    /// 即将重启，订阅者应在宽限期内完成收尾
+    ShutdownRequested {
+        /// 重启原因
+        reason: &'static str,
+        /// 宽限期 (毫秒)
+        grace_ms: u32,
+    },
+    /// ULP 协处理器更新了邮箱数据
+    UlpMailbox {
+        /// ULP 侧更新序号
+        seq: u32,
+        /// 是否由 ULP 唤醒主核
+        wake: bool,
+    },
+    /// 试用中的配置未通过自检，已回滚
+    ConfigRollback {
+        /// 被放弃的配置代号
+        from: u32,
+        /// 恢复的配置代号 (0 表示没有上一代)
+        to: u32,
+    },
+}
+
+impl SystemEvent {
+    /// 事件来源子系统
+    pub const fn source(&self) -> &'static str {
+        match self {
+            Self::StateChanged { .. } => "fsm",
+            Self::AccessGranted { .. } | Self::AccessDenied { .. } | Self::AccessLocked { .. } => "auth",
+            Self::LinkHealthChanged { .. } | Self::LinkAction { .. } => "link",
+            Self::TimeAdjusted { .. } => "time",
+            Self::TimingViolation { .. } => "timing",
+            Self::BootCompleted { .. } => "boot",
+            Self::ShutdownRequested { .. } => "reboot",
+            Self::UlpMailbox { .. } => "ulp",
+            Self::ConfigRollback { .. } => "config",
+        }
+    }
+}
+
+// ===== 事件信封 =====
+
+/// 全局事件序号 (总线与各控制器事件通道共用)
+static SEQUENCE: AtomicU32 = AtomicU32::new(0);
+
+/// 分配下一个事件序号 (从 1 开始，溢出后回绕)
+fn next_seq() -> u32 {
+    SEQUENCE.fetch_add(1, Ordering::Relaxed).wrapping_add(1)
+}
+
+/// 带序号、时间戳和来源的事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Envelope<E> {
+    /// 全局递增序号
+    pub seq: u32,
+    /// 发布时间
+    pub timestamp: Instant,
+    /// 来源子系统 (如 "wifi"、"auth")
+    pub source: &'static str,
+    /// 事件本体
+    pub event: E,
+}
+
+impl<E> Envelope<E> {
+    /// 以当前时间和下一个全局序号封装事件
+    pub fn new(source: &'static str, event: E) -> Self {
+        Self::at(next_seq(), Instant::now(), source, event)
+    }
+
+    /// 指定序号与时间封装 (回放或测试)
+    pub const fn at(seq: u32, timestamp: Instant, source: &'static str, event: E) -> Self {
+        Self { seq, timestamp, source, event }
+    }
+
+    /// 距 `earlier` 的时间间隔 (`earlier` 更晚时为 0)
+    pub fn since<T>(&self, earlier: &Envelope<T>) -> Duration {
+        self.timestamp.checked_duration_since(earlier.timestamp).unwrap_or(Duration::from_ticks(0))
+    }
+
+    /// 是否在 `other` 之前发布 (按序号比较，容忍回绕)
+    pub fn precedes<T>(&self, other: &Envelope<T>) -> bool {
+        (other.seq.wrapping_sub(self.seq) as i32) > 0
+    }
+
+    /// 从发布到现在经过的时间 (投递延迟)
+    pub fn age(&self) -> Duration {
+        self.timestamp.elapsed()
+    }
+
+    /// 转换事件本体，保留序号、时间戳和来源
+    pub fn map<T>(self, f: impl FnOnce(E) -> T) -> Envelope<T> {
+        Envelope { seq: self.seq, timestamp: self.timestamp, source: self.source, event: f(self.event) }
+    }
+}
+
+/// 相邻两次观察之间的差异
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SequenceGap {
+    /// 两次观察之间缺失的事件数 (被覆盖、发往其他通道或被过滤)
+    pub missed: u32,
+    /// 与上一个事件的时间间隔 (首个事件为 `None`)
+    pub interval: Option<Duration>,
+}
+
+/// 事件序号跟踪
+///
+/// 订阅者逐个观察收到的信封，得到缺失事件数与事件间隔。
+/// 由于序号全局共享，只订阅部分事件源时 `missed` 也包含其他来源的事件。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SequenceTracker {
+    last: Option<(u32, Instant)>,
+    missed: u32,
+}
+
+impl SequenceTracker {
+    /// 创建
+    pub const fn new() -> Self {
+        Self { last: None, missed: 0 }
+    }
+
+    /// 观察一个事件
+    ///
+    /// 序号不晚于上一个事件 (重复或乱序) 时不更新状态
+    pub fn observe<E>(&mut self, envelope: &Envelope<E>) -> SequenceGap {
+        let Some((seq, at)) = self.last else {
+            self.last = Some((envelope.seq, envelope.timestamp));
+            return SequenceGap::default();
+        };
+        let delta = envelope.seq.wrapping_sub(seq);
+        if delta as i32 <= 0 {
+            return SequenceGap::default();
+        }
+        self.last = Some((envelope.seq, envelope.timestamp));
+        let missed = delta - 1;
+        self.missed = self.missed.saturating_add(missed);
+        SequenceGap {
+            missed,
+            interval: Some(envelope.timestamp.checked_duration_since(at).unwrap_or(Duration::from_ticks(0))),
+        }
+    }
+
+    /// 累计缺失事件数
+    pub fn total_missed(&self) -> u32 {
+        self.missed
+    }
+}
+
+// ===== 总线 =====
+
+/// 事件总线类型
+pub type EventBus = CriticalPubSub<Envelope<SystemEvent>, BUS_CAPACITY, BUS_SUBSCRIBERS, BUS_PUBLISHERS>;
+
+/// 事件订阅者类型
+pub type EventSubscriber = Subscriber<
+    'static,
+    CriticalSectionRawMutex,
+    Envelope<SystemEvent>,
+    BUS_CAPACITY,
+    BUS_SUBSCRIBERS,
+    BUS_PUBLISHERS,
+>;
+
+/// 全局事件总线
+pub static EVENT_BUS: EventBus = PubSubChannel::new();
+
+/// 发布事件 (非阻塞，满时覆盖最旧事件)
+///
+/// 自动附加序号、时间戳和 `SystemEvent::source`
+#[inline]
+pub fn publish(event: SystemEvent) {
+    EVENT_BUS.immediate_publisher().publish_immediate(Envelope::new(event.source(), event));
+}
+
+/// 订阅事件总线
+///
+/// # 返回
+/// 订阅者数量已达 `BUS_SUBSCRIBERS` 时返回 `None`
+pub fn subscribe() -> Option<EventSubscriber> {
+    EVENT_BUS.subscriber().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequence_tracker() {
+        let t0 = Instant::from_millis(100);
+        let first = Envelope::at(u32::MAX - 1, t0, "wifi", ());
+        let second = Envelope::at(u32::MAX, t0 + Duration::from_millis(5), "ble", ());
+        // 序号回绕
+        let third = Envelope::at(3, t0 + Duration::from_millis(12), "wifi", ());
+        assert!(first.precedes(&second) && second.precedes(&third) && !third.precedes(&first));
+        assert_eq!(third.since(&first), Duration::from_millis(12));
+        assert_eq!(first.since(&third), Duration::from_ticks(0));
+
+        let mut tracker = SequenceTracker::new();
+        assert_eq!(tracker.observe(&first), SequenceGap::default());
+        assert_eq!(tracker.observe(&second), SequenceGap { missed: 0, interval: Some(Duration::from_millis(5)) });
+        assert_eq!(tracker.observe(&third).missed, 3);
+        // 重复/乱序不更新
+        assert_eq!(tracker.observe(&second), SequenceGap::default());
+        assert_eq!(tracker.total_missed(), 3);
+    }
+}
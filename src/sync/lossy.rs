@@ -0,0 +1,246 @@
+//! 溢出策略通道
+//!
+//! `CriticalChannel` 满时发送方阻塞 (或 `try_send` 失败)，适合命令类消息;
+//! 传感器数据流则应丢弃过时数据、保证生产者永不阻塞。本通道按通道配置溢出策略:
+//! - `DropOldest`: 覆盖最旧的消息，保留最近 `N` 条
+//! - `KeepLatest`: 只保留最新一条 (合并)，消费者总是读到当前值
+//! - `DropNewest`: 丢弃新消息并计数，保留已排队的数据
+//!
+//! 丢弃次数、队列峰值等统计通过 `stats` 获取。适合多生产者、单消费者。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::sync::lossy::{LossyChannel, OverflowPolicy};
+//!
+//! static IMU: LossyChannel<ImuSample, 8> = LossyChannel::new(OverflowPolicy::DropOldest);
+//!
+//! // 采样中断 / 高优先级任务: 永不阻塞
+//! IMU.send(sample);
+//!
+//! // 处理任务
+//! let sample = IMU.receive().await;
+//! log_debug!("imu dropped={}", IMU.stats().dropped);
+//! ```
+
+use core::cell::RefCell;
+use core::fmt;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use heapless::Deque;
+
+use super::primitives::CriticalSignal;
+
+/// 溢出策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// 覆盖最旧的消息
+    DropOldest,
+    /// 只保留最新一条
+    KeepLatest,
+    /// 丢弃新消息
+    DropNewest,
+}
+
+impl OverflowPolicy {
+    /// 策略名称
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::DropOldest => "drop-oldest",
+            Self::KeepLatest => "keep-latest",
+            Self::DropNewest => "drop-newest",
+        }
+    }
+}
+
+impl fmt::Display for OverflowPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 通道统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LossyStats {
+    /// 溢出策略
+    pub policy: OverflowPolicy,
+    /// 发送次数 (含被丢弃的)
+    pub sent: u32,
+    /// 接收次数
+    pub received: u32,
+    /// 丢弃的消息数
+    pub dropped: u32,
+    /// 队列长度峰值
+    pub high_water: u16,
+}
+
+impl LossyStats {
+    /// 丢弃率 (千分比)
+    pub fn drop_permille(&self) -> u32 {
+        if self.sent == 0 {
+            return 0;
+        }
+        (self.dropped as u64 * 1000 / self.sent as u64) as u32
+    }
+}
+
+impl fmt::Display for LossyStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} sent={} received={} dropped={} high_water={}",
+            self.policy, self.sent, self.received, self.dropped, self.high_water
+        )
+    }
+}
+
+struct State<T, const N: usize> {
+    queue: Deque<T, N>,
+    stats: LossyStats,
+}
+
+/// 溢出策略通道 (容量 `N`)
+pub struct LossyChannel<T, const N: usize> {
+    state: BlockingMutex<CriticalSectionRawMutex, RefCell<State<T, N>>>,
+    ready: CriticalSignal<()>,
+}
+
+impl<T, const N: usize> LossyChannel<T, N> {
+    /// 创建通道
+    pub const fn new(policy: OverflowPolicy) -> Self {
+        Self {
+            state: BlockingMutex::new(RefCell::new(State {
+                queue: Deque::new(),
+                stats: LossyStats { policy, sent: 0, received: 0, dropped: 0, high_water: 0 },
+            })),
+            ready: CriticalSignal::new(),
+        }
+    }
+
+    /// 发送 (永不阻塞)
+    ///
+    /// # 返回
+    /// 因溢出被丢弃的消息: `DropOldest`/`KeepLatest` 为被替换的旧消息，
+    /// `DropNewest` 为本次发送的消息
+    pub fn send(&self, value: T) -> Option<T> {
+        let discarded = self.state.lock(|state| {
+            let mut state = state.borrow_mut();
+            let state = &mut *state;
+            state.stats.sent = state.stats.sent.wrapping_add(1);
+
+            let full = match state.stats.policy {
+                OverflowPolicy::KeepLatest => !state.queue.is_empty(),
+                _ => state.queue.is_full(),
+            };
+            let discarded = if !full {
+                None
+            } else if state.stats.policy == OverflowPolicy::DropNewest {
+                state.stats.dropped = state.stats.dropped.saturating_add(1);
+                return Some(value);
+            } else {
+                state.stats.dropped = state.stats.dropped.saturating_add(1);
+                state.queue.pop_front()
+            };
+
+            // 已腾出位置，入队一定成功
+            let _ = state.queue.push_back(value);
+            state.stats.high_water = state.stats.high_water.max(state.queue.len() as u16);
+            discarded
+        });
+        self.ready.signal(());
+        discarded
+    }
+
+    /// 尝试接收 (非阻塞)
+    pub fn try_receive(&self) -> Option<T> {
+        self.state.lock(|state| {
+            let mut state = state.borrow_mut();
+            let value = state.queue.pop_front()?;
+            state.stats.received = state.stats.received.wrapping_add(1);
+            Some(value)
+        })
+    }
+
+    /// 接收 (队列为空时等待)
+    pub async fn receive(&self) -> T {
+        loop {
+            if let Some(value) = self.try_receive() {
+                return value;
+            }
+            self.ready.wait().await;
+        }
+    }
+
+    /// 清空队列 (统计保留)
+    pub fn clear(&self) {
+        self.state.lock(|state| state.borrow_mut().queue.clear());
+    }
+
+    /// 队列中等待的消息数
+    pub fn len(&self) -> usize {
+        self.state.lock(|state| state.borrow().queue.len())
+    }
+
+    /// 队列是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 溢出策略
+    pub fn policy(&self) -> OverflowPolicy {
+        self.state.lock(|state| state.borrow().stats.policy)
+    }
+
+    /// 统计
+    pub fn stats(&self) -> LossyStats {
+        self.state.lock(|state| state.borrow().stats)
+    }
+
+    /// 清零统计 (保留策略)
+    pub fn reset_stats(&self) {
+        self.state.lock(|state| {
+            let mut state = state.borrow_mut();
+            let policy = state.stats.policy;
+            state.stats = LossyStats { policy, sent: 0, received: 0, dropped: 0, high_water: state.queue.len() as u16 };
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overflow_policies() {
+        let oldest: LossyChannel<u8, 2> = LossyChannel::new(OverflowPolicy::DropOldest);
+        assert_eq!(oldest.send(1), None);
+        assert_eq!(oldest.send(2), None);
+        assert_eq!(oldest.send(3), Some(1));
+        assert_eq!((oldest.try_receive(), oldest.try_receive(), oldest.try_receive()), (Some(2), Some(3), None));
+
+        let latest: LossyChannel<u8, 4> = LossyChannel::new(OverflowPolicy::KeepLatest);
+        latest.send(1);
+        assert_eq!(latest.send(2), Some(1));
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest.try_receive(), Some(2));
+
+        let newest: LossyChannel<u8, 2> = LossyChannel::new(OverflowPolicy::DropNewest);
+        newest.send(1);
+        newest.send(2);
+        assert_eq!(newest.send(3), Some(3));
+        assert_eq!(newest.try_receive(), Some(1));
+
+        let stats = newest.stats();
+        assert_eq!((stats.sent, stats.received, stats.dropped, stats.high_water), (3, 1, 1, 2));
+        assert_eq!(stats.drop_permille(), 333);
+    }
+
+    #[test]
+    fn test_receive_waits_for_send() {
+        static CHANNEL: LossyChannel<u32, 4> = LossyChannel::new(OverflowPolicy::DropOldest);
+        CHANNEL.send(7);
+        assert_eq!(embassy_futures::block_on(CHANNEL.receive()), 7);
+        assert!(CHANNEL.is_empty());
+    }
+}
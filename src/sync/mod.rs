@@ -5,9 +5,22 @@
 //! - `CriticalChannel`: MPMC 消息队列
 //! - `CriticalMutex`: 异步互斥锁
 //! - `RingBuffer`: 零拷贝环形缓冲区
+//! - `bus`: 系统事件总线
+//! - `PooledChannel`: 消息体存放在内存池中的零拷贝通道
+//! - `LossyChannel`: 按溢出策略丢弃数据的通道 (覆盖最旧、只保留最新、丢弃新消息)
+
+#![cfg_attr(
+    all(feature = "panic-free", not(test)),
+    deny(clippy::unwrap_used, clippy::expect_used, clippy::panic, clippy::unreachable, clippy::todo, clippy::unimplemented)
+)]
 
 pub mod primitives;
 pub mod ringbuffer;
+pub mod bus;
+pub mod pooled;
+pub mod lossy;
 
 pub use primitives::{CriticalSignal, CriticalChannel, CriticalMutex};
 pub use ringbuffer::RingBuffer;
+pub use pooled::{Pooled, PooledChannel};
+pub use lossy::{LossyChannel, LossyStats, OverflowPolicy};
@@ -0,0 +1,105 @@
+//! 池化通道
+//!
+//! 消息体存放在 `MemoryPool` 中，通道只传递 `PoolBox` 句柄 (指针 + 索引)。
+//! 适合较大的消息 (CAN 帧、网络包等): 入队出队不拷贝消息体，
+//! 内存占用固定且在编译期确定。
+//!
+//! 内存池容量与队列容量相同，因此分配成功后入队一定成功。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! static RX: PooledChannel<Packet, 16> = PooledChannel::new();
+//!
+//! // 生产者 (可在高优先级任务中调用)
+//! if RX.try_send(packet).is_err() { /* 池已满，丢弃 */ }
+//!
+//! // 消费者
+//! let packet = RX.receive().await;
+//! process(&packet);
+//! // drop 时槽位归还内存池
+//! ```
+
+use crate::mem::pool::{Backend, DramPool, PoolBox, PoolStats};
+
+use super::primitives::CriticalChannel;
+
+/// 池化消息句柄
+pub type Pooled<T, const N: usize> = PoolBox<'static, T, N, { Backend::Dram as u8 }>;
+
+/// 池化通道
+pub struct PooledChannel<T: 'static, const N: usize> {
+    pool: DramPool<T, N>,
+    queue: CriticalChannel<Pooled<T, N>, N>,
+}
+
+impl<T: Send + 'static, const N: usize> PooledChannel<T, N> {
+    /// 创建池化通道
+    pub const fn new() -> Self {
+        Self {
+            pool: DramPool::new(),
+            queue: CriticalChannel::new(),
+        }
+    }
+
+    /// 发送消息 (非阻塞)
+    ///
+    /// # 返回
+    /// 内存池已满时原样返回消息
+    pub fn try_send(&'static self, value: T) -> Result<(), T> {
+        let Ok(mut slot) = self.pool.alloc() else {
+            return Err(value);
+        };
+        // SAFETY: 槽位刚分配、尚未初始化，写入后才交给接收方
+        unsafe { slot.as_mut_ptr().write(value) };
+        self.send_boxed(slot);
+        Ok(())
+    }
+
+    /// 预先分配槽位，原地填充后通过 `send_boxed` 发送
+    ///
+    /// 避免消息在栈上构造后再拷贝进池
+    pub fn alloc(&'static self) -> Option<Pooled<T, N>>
+    where
+        T: Default,
+    {
+        self.pool.alloc_init(T::default()).ok()
+    }
+
+    /// 发送已分配的句柄
+    pub fn send_boxed(&'static self, boxed: Pooled<T, N>) {
+        // 池容量 == 队列容量，已分配的句柄一定能入队
+        let _ = self.queue.try_send(boxed);
+    }
+
+    /// 接收消息
+    pub async fn receive(&self) -> Pooled<T, N> {
+        self.queue.receive().await
+    }
+
+    /// 尝试接收 (非阻塞)
+    pub fn try_receive(&self) -> Option<Pooled<T, N>> {
+        self.queue.try_receive().ok()
+    }
+
+    /// 队列中等待的消息数
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// 队列是否为空
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// 内存池统计
+    pub fn pool_stats(&self) -> PoolStats {
+        self.pool.stats()
+    }
+}
+
+impl<T: Send + 'static, const N: usize> Default for PooledChannel<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -3,6 +3,7 @@
 //! 高性能单生产者单消费者 (SPSC) 环形缓冲区
 //! 特点:
 //! - 零拷贝读写 (返回切片引用)
+//! - 向量化读写 (跨回绕点时返回两段切片)
 //! - 无锁实现 (使用原子操作)
 //! - 缓存友好的内存布局
 //! - 编译时确定容量
@@ -56,11 +57,10 @@ unsafe impl<T: Send, const N: usize> Sync for RingBuffer<T, N> {}
 impl<T, const N: usize> RingBuffer<T, N> {
     /// 创建新的空环形缓冲区
     ///
-    /// # Panics
-    /// 编译时检查 N 必须是 2 的幂
+    /// N 必须是 2 的幂，否则编译失败
     pub const fn new() -> Self {
         // 编译时检查: N 必须是 2 的幂
-        assert!(N > 0 && (N & (N - 1)) == 0, "N must be a power of 2");
+        const { assert!(N > 0 && (N & (N - 1)) == 0, "N must be a power of 2") };
         
         Self {
             buffer: UnsafeCell::new(unsafe { MaybeUninit::uninit().assume_init() }),
@@ -116,35 +116,51 @@ impl<T, const N: usize> RingBuffer<T, N> {
 }
 
 impl<T: Copy, const N: usize> RingBuffer<T, N> {
+    /// 从 `start` 开始的 `len` 个元素在存储中的位置
+    ///
+    /// 返回 (起始下标, 第一段长度, 第二段长度)，第二段总是从下标 0 开始
+    #[inline(always)]
+    const fn regions(&self, start: usize, len: usize) -> (usize, usize, usize) {
+        let idx = start & self.mask();
+        let first = if len < N - idx { len } else { N - idx };
+        (idx, first, len - first)
+    }
+
     /// 获取可写入的连续切片 (零拷贝)
     ///
+    /// 只返回回绕点之前的部分，需要全部空闲空间时使用 `write_slices`
+    ///
     /// # Safety
     /// - 只能由单个生产者调用
     /// - 写入后必须调用 `commit_write`
     #[inline]
+    #[allow(clippy::mut_from_ref)]
     pub unsafe fn write_slice(&self) -> &mut [T] {
+        self.write_slices().0
+    }
+
+    /// 获取全部可写入空间 (向量化 IO)
+    ///
+    /// 空闲区域跨过回绕点时分为两段，按顺序先写第一段再写第二段；
+    /// 不跨回绕点时第二段为空。
+    ///
+    /// # Safety
+    /// - 只能由单个生产者调用
+    /// - 写入后必须调用 `commit_write`，长度不超过两段之和
+    #[inline]
+    #[allow(clippy::mut_from_ref)] // SPSC: 空闲区域只由生产者访问
+    pub unsafe fn write_slices(&self) -> (&mut [T], &mut [T]) {
         let head = self.head.load(Ordering::Relaxed);
         let tail = self.tail.load(Ordering::Acquire);
-        
+
         let available = N - head.wrapping_sub(tail);
-        if available == 0 {
-            return &mut [];
-        }
-        
-        let head_idx = head & self.mask();
-        let tail_idx = tail & self.mask();
-        
-        // 计算连续可写区域
-        let contiguous = if head_idx >= tail_idx {
-            // head 在 tail 后面或相等
-            N - head_idx
-        } else {
-            // head 回绕到 tail 前面
-            tail_idx - head_idx
-        }.min(available);
-        
-        let ptr = (*self.buffer.get()).as_mut_ptr().add(head_idx) as *mut T;
-        core::slice::from_raw_parts_mut(ptr, contiguous)
+        let (idx, first, second) = self.regions(head, available);
+
+        let base = (*self.buffer.get()).as_mut_ptr() as *mut T;
+        (
+            core::slice::from_raw_parts_mut(base.add(idx), first),
+            core::slice::from_raw_parts_mut(base, second),
+        )
     }
     
     /// 提交写入
@@ -153,7 +169,7 @@ impl<T: Copy, const N: usize> RingBuffer<T, N> {
     /// * `len` - 实际写入的字节数
     ///
     /// # Safety
-    /// `len` 不能超过 `write_slice` 返回的切片长度
+    /// `len` 不能超过 `write_slices` 返回的两段长度之和
     #[inline(always)]
     pub unsafe fn commit_write(&self, len: usize) {
         let head = self.head.load(Ordering::Relaxed);
@@ -162,31 +178,36 @@ impl<T: Copy, const N: usize> RingBuffer<T, N> {
     
     /// 获取可读取的连续切片 (零拷贝)
     ///
+    /// 只返回回绕点之前的部分，需要全部数据时使用 `read_slices`
+    ///
     /// # Safety
     /// - 只能由单个消费者调用
     /// - 读取后必须调用 `commit_read`
     #[inline]
     pub unsafe fn read_slice(&self) -> &[T] {
+        self.read_slices().0
+    }
+
+    /// 获取全部可读取数据 (向量化 IO)
+    ///
+    /// 数据跨过回绕点时分为两段，第一段在前；不跨回绕点时第二段为空。
+    ///
+    /// # Safety
+    /// - 只能由单个消费者调用
+    /// - 读取后必须调用 `commit_read`，长度不超过两段之和
+    #[inline]
+    pub unsafe fn read_slices(&self) -> (&[T], &[T]) {
         let head = self.head.load(Ordering::Acquire);
         let tail = self.tail.load(Ordering::Relaxed);
-        
+
         let available = head.wrapping_sub(tail);
-        if available == 0 {
-            return &[];
-        }
-        
-        let head_idx = head & self.mask();
-        let tail_idx = tail & self.mask();
-        
-        // 计算连续可读区域
-        let contiguous = if head_idx > tail_idx {
-            head_idx - tail_idx
-        } else {
-            N - tail_idx
-        }.min(available);
-        
-        let ptr = (*self.buffer.get()).as_ptr().add(tail_idx) as *const T;
-        core::slice::from_raw_parts(ptr, contiguous)
+        let (idx, first, second) = self.regions(tail, available);
+
+        let base = (*self.buffer.get()).as_ptr() as *const T;
+        (
+            core::slice::from_raw_parts(base.add(idx), first),
+            core::slice::from_raw_parts(base, second),
+        )
     }
     
     /// 提交读取
@@ -195,7 +216,7 @@ impl<T: Copy, const N: usize> RingBuffer<T, N> {
     /// * `len` - 实际读取的字节数
     ///
     /// # Safety
-    /// `len` 不能超过 `read_slice` 返回的切片长度
+    /// `len` 不能超过 `read_slices` 返回的两段长度之和
     #[inline(always)]
     pub unsafe fn commit_read(&self, len: usize) {
         let tail = self.tail.load(Ordering::Relaxed);
@@ -281,59 +302,68 @@ pub type RingBuffer1K = RingBuffer<u8, 1024>;
 /// 4096 字节环形缓冲区
 pub type RingBuffer4K = RingBuffer<u8, 4096>;
 
-// ===== 扩展方法 =====
+// ===== 批量读写 =====
+
+/// 把 `src` 依次复制到两段切片中
+///
+/// # Returns
+/// 实际复制的元素数 (不超过两段长度之和)
+pub fn copy_to_slices<T: Copy>(src: &[T], dst: (&mut [T], &mut [T])) -> usize {
+    let (a, b) = dst;
+    let first = a.len().min(src.len());
+    a[..first].copy_from_slice(&src[..first]);
+    let second = b.len().min(src.len() - first);
+    b[..second].copy_from_slice(&src[first..first + second]);
+    first + second
+}
+
+/// 把两段切片中的数据依次复制到 `dst`
+///
+/// # Returns
+/// 实际复制的元素数 (不超过 `dst` 长度)
+pub fn copy_from_slices<T: Copy>(src: (&[T], &[T]), dst: &mut [T]) -> usize {
+    let (a, b) = src;
+    let first = a.len().min(dst.len());
+    dst[..first].copy_from_slice(&a[..first]);
+    let second = b.len().min(dst.len() - first);
+    dst[first..first + second].copy_from_slice(&b[..second]);
+    first + second
+}
 
-impl<const N: usize> RingBuffer<u8, N> {
-    /// 批量写入数据
+impl<T: Copy, const N: usize> RingBuffer<T, N> {
+    /// 批量写入数据 (回绕时一次完成两段复制)
     ///
     /// # Returns
-    /// 实际写入的字节数
-    pub fn write(&self, data: &[u8]) -> usize {
-        let mut written = 0;
-        let mut remaining = data;
-        
-        while !remaining.is_empty() && !self.is_full() {
-            let slice = unsafe { self.write_slice() };
-            if slice.is_empty() {
-                break;
-            }
-            
-            let to_write = slice.len().min(remaining.len());
-            slice[..to_write].copy_from_slice(&remaining[..to_write]);
-            
-            unsafe { self.commit_write(to_write) };
-            
-            written += to_write;
-            remaining = &remaining[to_write..];
+    /// 实际写入的元素数
+    pub fn write(&self, data: &[T]) -> usize {
+        // Safety: 生产者端，提交长度不超过 write_slices 返回的空间
+        unsafe {
+            let written = copy_to_slices(data, self.write_slices());
+            self.commit_write(written);
+            written
         }
-        
-        written
     }
-    
-    /// 批量读取数据
+
+    /// 批量读取数据 (回绕时一次完成两段复制)
     ///
     /// # Returns
-    /// 实际读取的字节数
-    pub fn read(&self, buffer: &mut [u8]) -> usize {
-        let mut read_total = 0;
-        let mut remaining = buffer;
-        
-        while !remaining.is_empty() && !self.is_empty() {
-            let slice = unsafe { self.read_slice() };
-            if slice.is_empty() {
-                break;
-            }
-            
-            let to_read = slice.len().min(remaining.len());
-            remaining[..to_read].copy_from_slice(&slice[..to_read]);
-            
-            unsafe { self.commit_read(to_read) };
-            
-            read_total += to_read;
-            remaining = &mut remaining[to_read..];
+    /// 实际读取的元素数
+    pub fn read(&self, buffer: &mut [T]) -> usize {
+        // Safety: 消费者端，提交长度不超过 read_slices 返回的数据
+        unsafe {
+            let read = copy_from_slices(self.read_slices(), buffer);
+            self.commit_read(read);
+            read
         }
-        
-        read_total
+    }
+
+    /// 复制数据但不移出
+    ///
+    /// # Returns
+    /// 复制的元素数
+    pub fn peek(&self, buffer: &mut [T]) -> usize {
+        // Safety: 消费者端只读访问，不提交
+        unsafe { copy_from_slices(self.read_slices(), buffer) }
     }
 }
 
@@ -365,4 +395,88 @@ mod tests {
         buf.clear();
         assert!(buf.is_empty());
     }
+    
+    #[test]
+    fn test_vectored_wrap() {
+        let buf: RingBuffer<u8, 8> = RingBuffer::new();
+        assert_eq!(buf.write(&[0, 1, 2, 3, 4, 5]), 6);
+        let mut out = [0u8; 4];
+        assert_eq!(buf.read(&mut out), 4);
+
+        // 空闲区域 [6, 8) + [0, 4)，直接写入两段后提交 (切片在提交前释放)
+        assert_eq!(unsafe { buf.write_slice() }.len(), 2);
+        {
+            let (a, b) = unsafe { buf.write_slices() };
+            assert_eq!((a.len(), b.len()), (2, 4));
+            a.copy_from_slice(&[6, 7]);
+            b[..2].copy_from_slice(&[8, 9]);
+        }
+        unsafe { buf.commit_write(4) };
+        assert_eq!(buf.write(&[10, 11]), 2);
+
+        // 数据 [4, 8) + [0, 4)
+        {
+            let (a, b) = unsafe { buf.read_slices() };
+            assert_eq!((a, b), (&[4, 5, 6, 7][..], &[8, 9, 10, 11][..]));
+        }
+
+        let mut out = [0u8; 8];
+        assert_eq!(buf.peek(&mut out), 8);
+        assert_eq!(out, [4, 5, 6, 7, 8, 9, 10, 11]);
+        assert_eq!(buf.read(&mut out[..5]), 5);
+        assert_eq!(&out[..5], &[4, 5, 6, 7, 8]);
+        assert_eq!(buf.len(), 3);
+    }
+
+    #[test]
+    fn test_wrap_properties() {
+        // 随机长度读写，检查两段切片的不变量与数据顺序
+        let buf: RingBuffer<u32, 16> = RingBuffer::new();
+        let mut seed = 0x2545_f491_u32;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed
+        };
+        let (mut produced, mut consumed) = (0u32, 0u32);
+        let mut chunk = [0u32; 24];
+
+        for _ in 0..2000 {
+            // 切片只在各自的块内存活，不与 write/read 的访问重叠
+            let free = {
+                let (a, b) = unsafe { buf.write_slices() };
+                assert_eq!(a.len() + b.len(), buf.available_write());
+                // 第二段非空时，第一段必须恰好写到存储末尾，第二段从存储开头开始
+                assert!(b.is_empty() || a.as_ptr_range().end == b.as_ptr().wrapping_add(16));
+                a.len() + b.len()
+            };
+
+            let n = (next() % 24) as usize;
+            for (i, v) in chunk[..n].iter_mut().enumerate() {
+                *v = produced + i as u32;
+            }
+            let written = buf.write(&chunk[..n]);
+            assert_eq!(written, n.min(free));
+            produced += written as u32;
+
+            {
+                let (a, b) = unsafe { buf.read_slices() };
+                assert_eq!(a.len() + b.len(), buf.available_read());
+                assert_eq!(a.len() + b.len(), (produced - consumed) as usize);
+                assert!(b.is_empty() || a.as_ptr_range().end == b.as_ptr().wrapping_add(16));
+                for (i, v) in a.iter().chain(b.iter()).enumerate() {
+                    assert_eq!(*v, consumed + i as u32);
+                }
+            }
+
+            let n = (next() % 24) as usize;
+            let read = buf.read(&mut chunk[..n]);
+            assert_eq!(read, n.min(produced as usize - consumed as usize));
+            for (i, v) in chunk[..read].iter().enumerate() {
+                assert_eq!(*v, consumed + i as u32);
+            }
+            consumed += read as u32;
+        }
+    }
 }
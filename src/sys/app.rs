@@ -0,0 +1,446 @@
+//! 应用描述符 (esp_app_desc_t)
+//!
+//! 二级引导程序和 OTA 通过镜像开头的应用描述符识别固件版本。本模块提供:
+//! - `AppDescBuilder`: const 构建器，编译期校验字段长度 (超长直接编译失败)
+//! - `app_desc!`: 在 `.flash.appdesc` 段放置描述符 (替代 `esp_bootloader_esp_idf::esp_app_desc!`)
+//! - 运行时读取: 当前运行镜像与另一 OTA 槽位的描述符，用于版本比较
+//!
+//! `app_elf_sha256` 由 espflash/esptool 生成镜像时回填，构建器保持为 0。
+//! 两个宏导出同名符号 `esp_app_desc`，`running()` 对两者都有效。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! // main.rs
+//! rustrtos::app_desc!();
+//!
+//! // 运行时
+//! use rustrtos::sys::app;
+//!
+//! let table = PartitionTable::default_16mb_ota();
+//! let running = app::running();
+//! if let Ok(other) = app::inactive(&table, FLASH_SIZE) {
+//!     if other.is_upgrade_from(running) {
+//!         log_info!("slot has newer firmware {}", other.version());
+//!     }
+//! }
+//! ```
+
+use core::cmp::Ordering;
+use core::fmt;
+
+use crate::fs::partition::{AppSubType, Partition, PartitionTable, PartitionType};
+use crate::fs::storage::{self, FlashStorage, StorageError};
+
+/// 描述符魔数
+pub const APP_DESC_MAGIC: u32 = 0xABCD_5432;
+
+/// 描述符长度
+pub const APP_DESC_LEN: usize = 256;
+
+/// 描述符在镜像中的偏移 (镜像头 24 字节 + 首段头 8 字节)
+pub const APP_DESC_OFFSET: usize = 0x20;
+
+/// 镜像头魔数
+const IMAGE_MAGIC: u8 = 0xE9;
+
+/// ESP32-S3 芯片 ID (镜像扩展头)
+const CHIP_ID_ESP32S3: u16 = 0x0009;
+
+/// 应用描述符错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppError {
+    /// 分区表中没有对应槽位
+    NoSlot,
+    /// 槽位中没有有效镜像 (镜像头魔数或芯片 ID 不符)
+    InvalidImage,
+    /// 描述符魔数不符或字段无效
+    InvalidDesc,
+    /// 读取 Flash 失败
+    Storage(StorageError),
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoSlot => write!(f, "No such app slot"),
+            Self::InvalidImage => write!(f, "No valid image in slot"),
+            Self::InvalidDesc => write!(f, "Invalid app descriptor"),
+            Self::Storage(e) => write!(f, "App descriptor read failed: {}", e),
+        }
+    }
+}
+
+impl From<StorageError> for AppError {
+    fn from(e: StorageError) -> Self {
+        Self::Storage(e)
+    }
+}
+
+// ===== 描述符 =====
+
+/// 应用描述符 (布局与 ESP-IDF `esp_app_desc_t` 一致)
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct AppDesc {
+    magic_word: u32,
+    secure_version: u32,
+    reserv1: [u32; 2],
+    version: [u8; 32],
+    project_name: [u8; 32],
+    time: [u8; 16],
+    date: [u8; 16],
+    idf_ver: [u8; 32],
+    app_elf_sha256: [u8; 32],
+    min_efuse_blk_rev_full: u16,
+    max_efuse_blk_rev_full: u16,
+    mmu_page_size: u8,
+    reserv3: [u8; 3],
+    reserv2: [u32; 18],
+}
+
+const _: () = assert!(core::mem::size_of::<AppDesc>() == APP_DESC_LEN);
+
+impl AppDesc {
+    /// 创建构建器
+    pub const fn builder(version: &str, project_name: &str) -> AppDescBuilder {
+        AppDescBuilder::new(version, project_name)
+    }
+
+    /// 从原始字节解析 (小端)
+    pub fn from_bytes(data: &[u8]) -> Result<Self, AppError> {
+        if data.len() < APP_DESC_LEN {
+            return Err(AppError::InvalidDesc);
+        }
+        let u32_at = |at: usize| u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]]);
+        let u16_at = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+        fn field<const N: usize>(data: &[u8], at: usize) -> [u8; N] {
+            let mut out = [0u8; N];
+            out.copy_from_slice(&data[at..at + N]);
+            out
+        }
+
+        if u32_at(0) != APP_DESC_MAGIC {
+            return Err(AppError::InvalidDesc);
+        }
+        let desc = Self {
+            magic_word: APP_DESC_MAGIC,
+            secure_version: u32_at(4),
+            reserv1: [0; 2],
+            version: field(data, 16),
+            project_name: field(data, 48),
+            time: field(data, 80),
+            date: field(data, 96),
+            idf_ver: field(data, 112),
+            app_elf_sha256: field(data, 144),
+            min_efuse_blk_rev_full: u16_at(176),
+            max_efuse_blk_rev_full: u16_at(178),
+            mmu_page_size: data[180],
+            reserv3: [0; 3],
+            reserv2: [0; 18],
+        };
+        // 字符串字段必须以 NUL 结尾且为 UTF-8
+        if field_str(&desc.version).is_none() || field_str(&desc.project_name).is_none() {
+            return Err(AppError::InvalidDesc);
+        }
+        Ok(desc)
+    }
+
+    /// 编码为原始字节 (小端)
+    pub fn to_bytes(&self) -> [u8; APP_DESC_LEN] {
+        let mut out = [0u8; APP_DESC_LEN];
+        out[0..4].copy_from_slice(&self.magic_word.to_le_bytes());
+        out[4..8].copy_from_slice(&self.secure_version.to_le_bytes());
+        out[16..48].copy_from_slice(&self.version);
+        out[48..80].copy_from_slice(&self.project_name);
+        out[80..96].copy_from_slice(&self.time);
+        out[96..112].copy_from_slice(&self.date);
+        out[112..144].copy_from_slice(&self.idf_ver);
+        out[144..176].copy_from_slice(&self.app_elf_sha256);
+        out[176..178].copy_from_slice(&self.min_efuse_blk_rev_full.to_le_bytes());
+        out[178..180].copy_from_slice(&self.max_efuse_blk_rev_full.to_le_bytes());
+        out[180] = self.mmu_page_size;
+        out
+    }
+
+    /// 版本字符串
+    pub fn version(&self) -> &str {
+        field_str(&self.version).unwrap_or("")
+    }
+
+    /// 项目名
+    pub fn project_name(&self) -> &str {
+        field_str(&self.project_name).unwrap_or("")
+    }
+
+    /// 编译时间
+    pub fn time(&self) -> &str {
+        field_str(&self.time).unwrap_or("")
+    }
+
+    /// 编译日期
+    pub fn date(&self) -> &str {
+        field_str(&self.date).unwrap_or("")
+    }
+
+    /// 安全版本 (防回滚计数)
+    pub fn secure_version(&self) -> u32 {
+        self.secure_version
+    }
+
+    /// ELF SHA-256 (生成镜像时回填)
+    pub fn elf_sha256(&self) -> &[u8; 32] {
+        &self.app_elf_sha256
+    }
+
+    /// 比较版本号
+    ///
+    /// 按 `.` 分隔的数字逐段比较，忽略前缀 `v` 与 `-`/`+` 之后的后缀；
+    /// 任一版本无法解析时返回 `None`
+    pub fn compare_version(&self, other: &AppDesc) -> Option<Ordering> {
+        compare_versions(self.version(), other.version())
+    }
+
+    /// 是否可作为 `running` 的升级: 同一项目、版本更高且安全版本不回退
+    pub fn is_upgrade_from(&self, running: &AppDesc) -> bool {
+        self.project_name() == running.project_name()
+            && self.secure_version >= running.secure_version
+            && self.compare_version(running) == Some(Ordering::Greater)
+    }
+}
+
+impl fmt::Debug for AppDesc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AppDesc")
+            .field("project_name", &self.project_name())
+            .field("version", &self.version())
+            .field("secure_version", &self.secure_version)
+            .field("date", &self.date())
+            .field("time", &self.time())
+            .finish()
+    }
+}
+
+/// NUL 结尾的字符串字段
+fn field_str(field: &[u8]) -> Option<&str> {
+    let len = field.iter().position(|&b| b == 0)?;
+    core::str::from_utf8(&field[..len]).ok()
+}
+
+/// 解析版本号的数字段
+fn version_parts(version: &str) -> Option<[u32; 4]> {
+    let version = version.strip_prefix('v').unwrap_or(version);
+    let core = version.split(['-', '+']).next()?;
+    let mut parts = [0u32; 4];
+    for (i, part) in core.split('.').enumerate() {
+        *parts.get_mut(i)? = part.parse().ok()?;
+    }
+    Some(parts)
+}
+
+/// 比较两个版本字符串
+pub fn compare_versions(a: &str, b: &str) -> Option<Ordering> {
+    Some(version_parts(a)?.cmp(&version_parts(b)?))
+}
+
+// ===== 构建器 =====
+
+/// 应用描述符构建器 (全部为 const fn)
+#[derive(Clone, Copy)]
+pub struct AppDescBuilder {
+    desc: AppDesc,
+}
+
+impl AppDescBuilder {
+    /// 创建构建器 (版本与项目名通常取自 `CARGO_PKG_VERSION`/`CARGO_PKG_NAME`)
+    ///
+    /// # Panics
+    /// 版本或项目名超过 31 字节 (const 上下文中为编译错误)
+    pub const fn new(version: &str, project_name: &str) -> Self {
+        Self {
+            desc: AppDesc {
+                magic_word: APP_DESC_MAGIC,
+                secure_version: 0,
+                reserv1: [0; 2],
+                version: str_field(version),
+                project_name: str_field(project_name),
+                time: [0; 16],
+                date: [0; 16],
+                idf_ver: str_field("esp-hal"),
+                app_elf_sha256: [0; 32],
+                min_efuse_blk_rev_full: 0,
+                max_efuse_blk_rev_full: 199,
+                // log2(64KB)
+                mmu_page_size: 16,
+                reserv3: [0; 3],
+                reserv2: [0; 18],
+            },
+        }
+    }
+
+    /// 安全版本 (防回滚)
+    pub const fn with_secure_version(mut self, secure_version: u32) -> Self {
+        self.desc.secure_version = secure_version;
+        self
+    }
+
+    /// 编译时间与日期 (如 "12:00:00"、"Jan  1 2026")
+    pub const fn with_build_time(mut self, time: &str, date: &str) -> Self {
+        self.desc.time = str_field(time);
+        self.desc.date = str_field(date);
+        self
+    }
+
+    /// 工具链/框架版本字段
+    pub const fn with_idf_ver(mut self, idf_ver: &str) -> Self {
+        self.desc.idf_ver = str_field(idf_ver);
+        self
+    }
+
+    /// 允许运行的芯片 eFuse 版本范围 (主版本 * 100 + 次版本)
+    pub const fn with_efuse_rev(mut self, min: u16, max: u16) -> Self {
+        self.desc.min_efuse_blk_rev_full = min;
+        self.desc.max_efuse_blk_rev_full = max;
+        self
+    }
+
+    /// 构建
+    pub const fn build(self) -> AppDesc {
+        self.desc
+    }
+}
+
+/// 把字符串复制到定长、NUL 结尾的字段
+const fn str_field<const N: usize>(s: &str) -> [u8; N] {
+    let bytes = s.as_bytes();
+    assert!(bytes.len() < N, "app descriptor field too long");
+    let mut out = [0u8; N];
+    let mut i = 0;
+    while i < bytes.len() {
+        assert!(bytes[i] != 0, "app descriptor field contains NUL");
+        out[i] = bytes[i];
+        i += 1;
+    }
+    out
+}
+
+/// 在 `.flash.appdesc` 段放置应用描述符
+///
+/// 默认使用调用方 crate 的 `CARGO_PKG_VERSION`/`CARGO_PKG_NAME`，
+/// 也可以传入构建器表达式
+#[macro_export]
+macro_rules! app_desc {
+    () => {
+        $crate::app_desc!($crate::sys::app::AppDesc::builder(
+            env!("CARGO_PKG_VERSION"),
+            env!("CARGO_PKG_NAME")
+        ));
+    };
+    ($builder:expr) => {
+        #[export_name = "esp_app_desc"]
+        #[link_section = ".flash.appdesc"]
+        #[used]
+        static ESP_APP_DESC: $crate::sys::app::AppDesc = $builder.build();
+    };
+}
+
+// ===== 运行时读取 =====
+
+extern "C" {
+    /// `app_desc!` 放置的描述符
+    #[link_name = "esp_app_desc"]
+    static RUNNING_DESC: AppDesc;
+}
+
+/// 当前运行镜像的描述符
+pub fn running() -> &'static AppDesc {
+    // Safety: 由 `app_desc!` 定义，只读
+    unsafe { &*core::ptr::addr_of!(RUNNING_DESC) }
+}
+
+/// 当前运行的应用分区 (根据描述符在 Flash 中的物理地址确定)
+pub fn running_slot(table: &PartitionTable) -> Option<&Partition> {
+    let address = storage::flash_address_of(running())?;
+    table
+        .find_by_type(PartitionType::App)
+        .find(|p| address >= p.offset && address < p.end_offset())
+}
+
+/// 下一个 OTA 目标槽位: 未在运行的 OTA 应用分区中编号最小的一个
+pub fn inactive_slot(table: &PartitionTable) -> Option<&Partition> {
+    let running = running_slot(table).map(|p| p.offset);
+    table
+        .find_by_type(PartitionType::App)
+        .filter(|p| matches!(p.app_subtype(), Some(AppSubType::Ota(_))))
+        .find(|p| Some(p.offset) != running)
+}
+
+/// 读取指定应用分区中镜像的描述符
+pub fn read_slot(partition: &Partition, total_flash_size: u32) -> Result<AppDesc, AppError> {
+    if !partition.is_app() {
+        return Err(AppError::NoSlot);
+    }
+    let mut storage = FlashStorage::from_partition(partition, total_flash_size);
+    storage.init()?;
+
+    let mut buffer = [0u8; APP_DESC_OFFSET + APP_DESC_LEN];
+    storage.read_block(0, &mut buffer)?;
+    check_image_header(&buffer)?;
+    AppDesc::from_bytes(&buffer[APP_DESC_OFFSET..])
+}
+
+/// 读取另一 OTA 槽位的描述符
+pub fn inactive(table: &PartitionTable, total_flash_size: u32) -> Result<AppDesc, AppError> {
+    read_slot(inactive_slot(table).ok_or(AppError::NoSlot)?, total_flash_size)
+}
+
+/// 检查镜像头 (魔数、芯片 ID)
+pub(crate) fn check_image_header(image: &[u8]) -> Result<(), AppError> {
+    // 镜像头: magic(1) segments(1) spi_mode(1) spi_speed_size(1) entry(4)，
+    // 扩展头: wp_pin(1) drive(3) chip_id(2) ...
+    let chip_id = u16::from_le_bytes([image[12], image[13]]);
+    if image[0] != IMAGE_MAGIC || image[1] == 0 || chip_id != CHIP_ID_ESP32S3 {
+        return Err(AppError::InvalidImage);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_desc_roundtrip() {
+        const DESC: AppDesc = AppDesc::builder("1.4.2", "rustrtos")
+            .with_secure_version(3)
+            .with_build_time("12:00:00", "Oct 16 2026")
+            .build();
+
+        let bytes = DESC.to_bytes();
+        let parsed = AppDesc::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed, DESC);
+        assert_eq!((parsed.version(), parsed.project_name(), parsed.date()), ("1.4.2", "rustrtos", "Oct 16 2026"));
+
+        let mut bad = bytes;
+        bad[0] = 0;
+        assert_eq!(AppDesc::from_bytes(&bad), Err(AppError::InvalidDesc));
+        // 版本字段没有 NUL 结尾
+        let mut bad = bytes;
+        bad[16..48].fill(b'9');
+        assert_eq!(AppDesc::from_bytes(&bad), Err(AppError::InvalidDesc));
+    }
+
+    #[test]
+    fn test_version_compare() {
+        assert_eq!(compare_versions("1.10.0", "1.9.3"), Some(Ordering::Greater));
+        assert_eq!(compare_versions("v2.0", "2.0.0-rc1"), Some(Ordering::Equal));
+        assert_eq!(compare_versions("1.2.x", "1.2.0"), None);
+
+        let running = AppDesc::builder("1.2.0", "rustrtos").with_secure_version(2).build();
+        assert!(AppDesc::builder("1.3.0", "rustrtos").with_secure_version(2).build().is_upgrade_from(&running));
+        // 安全版本回退、项目不同、版本更低都不算升级
+        assert!(!AppDesc::builder("1.3.0", "rustrtos").with_secure_version(1).build().is_upgrade_from(&running));
+        assert!(!AppDesc::builder("1.3.0", "other").with_secure_version(2).build().is_upgrade_from(&running));
+        assert!(!AppDesc::builder("1.1.0", "rustrtos").with_secure_version(2).build().is_upgrade_from(&running));
+    }
+}
@@ -0,0 +1,377 @@
+//! 访问控制
+//!
+//! HTTP 管理接口与 TCP 控制台共用的认证层:
+//! - API 令牌与用户名/口令两种凭据，均由凭据保管库 (`sys::vault::VAULT`) 校验，常量时间比较
+//! - 白名单策略: 只接受策略中列出的令牌名 / 用户名
+//! - 按来源 (客户端地址或用户名摘要) 统计连续失败，达到上限后锁定，重复锁定时时长翻倍
+//! - 认证成功、失败与锁定均作为审计事件发布到系统事件总线
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::sys::auth::{AuthPolicy, Authenticator, Credential};
+//!
+//! static ADMIN: Authenticator = Authenticator::new(
+//!     "http",
+//!     AuthPolicy::new().with_tokens(&["api"]).with_users(&["admin"]),
+//! );
+//!
+//! match ADMIN.authenticate(client_ip, Credential::Token(token)) {
+//!     Ok(principal) => log_info!("{} authenticated", principal),
+//!     Err(AuthError::LockedOut { retry_after_secs }) => { /* 429 */ }
+//!     Err(_) => { /* 401 */ }
+//! }
+//! ```
+
+use core::cell::RefCell;
+use core::fmt;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_time::{Duration, Instant};
+use heapless::Vec;
+
+use crate::sync::bus::{self, SystemEvent};
+use crate::sys::vault::VAULT;
+
+/// 同时跟踪的来源数量
+pub const MAX_TRACKED: usize = 8;
+
+/// 来源未知时使用的键
+pub const UNKNOWN_SOURCE: u32 = 0;
+
+/// 认证错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthError {
+    /// 请求未携带凭据
+    Missing,
+    /// 凭据无效
+    Invalid,
+    /// 来源已被锁定
+    LockedOut {
+        /// 剩余锁定时间 (秒，向上取整)
+        retry_after_secs: u32,
+    },
+    /// 策略中的凭据均未在保管库中配置
+    NotProvisioned,
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing => write!(f, "Credentials required"),
+            Self::Invalid => write!(f, "Invalid credentials"),
+            Self::LockedOut { retry_after_secs } => write!(f, "Locked out, retry after {} s", retry_after_secs),
+            Self::NotProvisioned => write!(f, "No credentials provisioned"),
+        }
+    }
+}
+
+/// 待校验的凭据
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Credential<'a> {
+    /// API 令牌
+    Token(&'a [u8]),
+    /// 用户名 + 口令
+    Password {
+        /// 用户名 (保管库中的口令名称)
+        user: &'a str,
+        /// 口令
+        password: &'a [u8],
+    },
+}
+
+impl Credential<'_> {
+    /// 没有来源地址时的锁定键: 口令按用户名、令牌共用一个键
+    pub fn subject_key(&self) -> u32 {
+        // FNV-1a
+        let subject: &[u8] = match self {
+            Credential::Token(_) => b"\0token",
+            Credential::Password { user, .. } => user.as_bytes(),
+        };
+        let hash = subject.iter().fold(0x811c_9dc5u32, |h, &b| (h ^ b as u32).wrapping_mul(0x0100_0193));
+        // 避开 UNKNOWN_SOURCE
+        hash | 1
+    }
+}
+
+/// 认证策略
+#[derive(Debug, Clone, Copy)]
+pub struct AuthPolicy {
+    /// 接受的令牌名称
+    pub tokens: &'static [&'static str],
+    /// 接受的用户名
+    pub users: &'static [&'static str],
+    /// 触发锁定的连续失败次数
+    pub max_failures: u8,
+    /// 首次锁定时长
+    pub lockout: Duration,
+    /// 锁定时长上限 (重复锁定时翻倍)
+    pub max_lockout: Duration,
+}
+
+impl AuthPolicy {
+    /// 默认策略: 不接受任何凭据，5 次失败锁定 60 秒，最长 1 小时
+    pub const fn new() -> Self {
+        Self {
+            tokens: &[],
+            users: &[],
+            max_failures: 5,
+            lockout: Duration::from_secs(60),
+            max_lockout: Duration::from_secs(3600),
+        }
+    }
+
+    /// 设置接受的令牌名称
+    pub const fn with_tokens(mut self, tokens: &'static [&'static str]) -> Self {
+        self.tokens = tokens;
+        self
+    }
+
+    /// 设置接受的用户名
+    pub const fn with_users(mut self, users: &'static [&'static str]) -> Self {
+        self.users = users;
+        self
+    }
+
+    /// 设置触发锁定的失败次数
+    pub const fn with_max_failures(mut self, failures: u8) -> Self {
+        self.max_failures = if failures == 0 { 1 } else { failures };
+        self
+    }
+
+    /// 设置锁定时长与上限
+    pub const fn with_lockout(mut self, lockout: Duration, max_lockout: Duration) -> Self {
+        self.lockout = lockout;
+        self.max_lockout = max_lockout;
+        self
+    }
+
+    /// 第 `count` 次锁定的时长
+    fn lockout_for(&self, count: u8) -> Duration {
+        let shift = count.saturating_sub(1).min(16) as u32;
+        let ticks = self.lockout.as_ticks().saturating_mul(1 << shift);
+        Duration::from_ticks(ticks.min(self.max_lockout.as_ticks()))
+    }
+}
+
+impl Default for AuthPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 单个来源的失败记录
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    key: u32,
+    failures: u8,
+    lockouts: u8,
+    locked_until: Option<Instant>,
+    last_seen: Instant,
+}
+
+/// 认证器
+pub struct Authenticator<const N: usize = MAX_TRACKED> {
+    service: &'static str,
+    policy: AuthPolicy,
+    slots: BlockingMutex<CriticalSectionRawMutex, RefCell<Vec<Slot, N>>>,
+}
+
+impl<const N: usize> Authenticator<N> {
+    /// 创建认证器，`service` 用于审计事件
+    pub const fn new(service: &'static str, policy: AuthPolicy) -> Self {
+        Self { service, policy, slots: BlockingMutex::new(RefCell::new(Vec::new())) }
+    }
+
+    /// 服务名称
+    pub fn service(&self) -> &'static str {
+        self.service
+    }
+
+    /// 认证策略
+    pub fn policy(&self) -> &AuthPolicy {
+        &self.policy
+    }
+
+    /// 策略中是否至少有一项凭据已配置
+    pub fn provisioned(&self) -> bool {
+        self.policy.tokens.iter().chain(self.policy.users).any(|name| VAULT.contains(name))
+    }
+
+    /// 来源剩余锁定时间
+    pub fn locked_out(&self, source: u32) -> Option<Duration> {
+        self.locked_at(source, Instant::now())
+    }
+
+    fn locked_at(&self, source: u32, now: Instant) -> Option<Duration> {
+        self.slots.lock(|slots| {
+            let slots = slots.borrow();
+            let until = slots.iter().find(|s| s.key == source)?.locked_until?;
+            (until > now).then(|| until - now)
+        })
+    }
+
+    /// 校验凭据，成功时返回策略中匹配的名称
+    ///
+    /// `source` 标识请求来源 (如 IPv4 地址)，用于失败计数与锁定。
+    pub fn authenticate(&self, source: u32, credential: Credential<'_>) -> Result<&'static str, AuthError> {
+        self.authenticate_at(source, credential, Instant::now())
+    }
+
+    fn authenticate_at(&self, source: u32, credential: Credential<'_>, now: Instant) -> Result<&'static str, AuthError> {
+        if let Some(remaining) = self.locked_at(source, now) {
+            let retry_after_secs = remaining.as_millis().div_ceil(1000) as u32;
+            return Err(AuthError::LockedOut { retry_after_secs });
+        }
+        if !self.provisioned() {
+            return Err(AuthError::NotProvisioned);
+        }
+
+        match self.verify(credential) {
+            Some(principal) => {
+                self.slots.lock(|slots| slots.borrow_mut().retain(|s| s.key != source));
+                bus::publish(SystemEvent::AccessGranted { service: self.service, principal, source });
+                Ok(principal)
+            }
+            None => {
+                self.record_failure(source, now);
+                Err(AuthError::Invalid)
+            }
+        }
+    }
+
+    /// 校验凭据 (不修改失败计数)
+    fn verify(&self, credential: Credential<'_>) -> Option<&'static str> {
+        match credential {
+            Credential::Token(token) => {
+                // 逐个比较全部令牌，耗时与命中位置无关
+                let mut matched = None;
+                for &name in self.policy.tokens {
+                    let ok = VAULT.verify_token(name, token).unwrap_or(false);
+                    if ok && matched.is_none() {
+                        matched = Some(name);
+                    }
+                }
+                matched
+            }
+            Credential::Password { user, password } => match self.policy.users.iter().find(|&&u| u == user) {
+                Some(&name) => VAULT.verify_password(name, password).unwrap_or(false).then_some(name),
+                None => {
+                    // 未知用户同样执行一次口令摘要，避免通过耗时枚举用户名
+                    if let Some(&name) = self.policy.users.first() {
+                        let _ = VAULT.verify_password(name, password);
+                    }
+                    None
+                }
+            },
+        }
+    }
+
+    /// 累计失败，达到上限时锁定来源
+    fn record_failure(&self, source: u32, now: Instant) {
+        let policy = &self.policy;
+        let (failures, lockout) = self.slots.lock(|slots| {
+            let mut slots = slots.borrow_mut();
+            let index = match slots.iter().position(|s| s.key == source) {
+                Some(i) => i,
+                None => {
+                    let slot = Slot { key: source, failures: 0, lockouts: 0, locked_until: None, last_seen: now };
+                    if slots.push(slot).is_err() {
+                        // 表满: 淘汰最久未活动的未锁定来源 (都已锁定时淘汰最旧的)
+                        let victim = slots
+                            .iter()
+                            .enumerate()
+                            .min_by_key(|(_, s)| (s.locked_until.is_some_and(|t| t > now), s.last_seen))
+                            .map(|(i, _)| i)
+                            .unwrap_or(0);
+                        slots[victim] = slot;
+                        victim
+                    } else {
+                        slots.len() - 1
+                    }
+                }
+            };
+            let slot = &mut slots[index];
+            slot.last_seen = now;
+            slot.failures = slot.failures.saturating_add(1);
+            let failures = slot.failures;
+            if slot.failures < policy.max_failures {
+                return (failures, None);
+            }
+            slot.failures = 0;
+            slot.lockouts = slot.lockouts.saturating_add(1);
+            let duration = policy.lockout_for(slot.lockouts);
+            slot.locked_until = Some(now + duration);
+            (failures, Some(duration))
+        });
+
+        bus::publish(SystemEvent::AccessDenied { service: self.service, source, failures });
+        if let Some(duration) = lockout {
+            bus::publish(SystemEvent::AccessLocked { service: self.service, source, seconds: duration.as_secs() as u32 });
+        }
+    }
+
+    /// 清除全部失败记录与锁定
+    pub fn reset(&self) {
+        self.slots.lock(|slots| slots.borrow_mut().clear());
+    }
+}
+
+impl<const N: usize> fmt::Debug for Authenticator<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Authenticator").field("service", &self.service).field("policy", &self.policy).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sys::vault::SecretKind;
+
+    #[test]
+    fn test_token_lockout_and_backoff() {
+        VAULT.set_secret("auth-test", SecretKind::Token, b"s3cret").unwrap();
+        let auth: Authenticator<2> = Authenticator::new(
+            "test",
+            AuthPolicy::new()
+                .with_tokens(&["missing", "auth-test"])
+                .with_max_failures(2)
+                .with_lockout(Duration::from_secs(10), Duration::from_secs(15)),
+        );
+        let t0 = Instant::from_secs(1000);
+        let good = Credential::Token(b"s3cret");
+        let bad = Credential::Token(b"guess");
+
+        assert_eq!(auth.authenticate_at(7, good, t0), Ok("auth-test"));
+        assert_eq!(auth.authenticate_at(7, bad, t0), Err(AuthError::Invalid));
+        assert_eq!(auth.authenticate_at(7, bad, t0), Err(AuthError::Invalid));
+        // 锁定期间正确凭据也被拒绝，其他来源不受影响
+        assert_eq!(
+            auth.authenticate_at(7, good, t0 + Duration::from_millis(500)),
+            Err(AuthError::LockedOut { retry_after_secs: 10 })
+        );
+        assert_eq!(auth.authenticate_at(8, good, t0), Ok("auth-test"));
+
+        // 第二次锁定时长翻倍但受上限约束
+        let t1 = t0 + Duration::from_secs(10);
+        assert_eq!(auth.authenticate_at(7, bad, t1), Err(AuthError::Invalid));
+        assert_eq!(auth.authenticate_at(7, bad, t1), Err(AuthError::Invalid));
+        assert_eq!(auth.locked_at(7, t1), Some(Duration::from_secs(15)));
+
+        // 成功后清除记录
+        let t2 = t1 + Duration::from_secs(15);
+        assert_eq!(auth.authenticate_at(7, good, t2), Ok("auth-test"));
+        assert_eq!(auth.locked_at(7, t2), None);
+        VAULT.remove("auth-test").unwrap();
+    }
+
+    #[test]
+    fn test_subject_key() {
+        let a = Credential::Password { user: "admin", password: b"x" };
+        let b = Credential::Password { user: "admin", password: b"y" };
+        assert_eq!(a.subject_key(), b.subject_key());
+        assert_ne!(a.subject_key(), Credential::Token(b"x").subject_key());
+        assert_ne!(a.subject_key(), UNKNOWN_SOURCE);
+    }
+}
@@ -0,0 +1,658 @@
+//! 启动编排
+//!
+//! 子系统以"启动步骤"登记名称、依赖、超时与重试策略，框架按依赖拓扑顺序
+//! 逐个初始化，取代 main 中手工维护的初始化顺序 (定时器 → esp-rtos → 射频 → 子系统)。
+//!
+//! # 特性
+//! - 依赖排序 (同层按登记顺序)，检测未知依赖与循环依赖
+//! - 每步超时与重试 (固定间隔)
+//! - 可选步骤失败时降级运行，依赖它的步骤被跳过
+//! - 记录每步耗时与尝试次数，最终就绪状态发布到事件总线 (`SystemEvent::BootCompleted`)
+//! - 首次启动供应: 按键值存储中的镜像标记区分"烧录后首次启动"与"OTA 后首次启动"，
+//!   供应步骤 (格式化数据分区、生成设备密钥等) 只在对应场景执行
+//! - 可配置的启动横幅 (标题、镜像信息、启动类型与自定义行)
+//!
+//! 初始化动作由调用方的分发函数按步骤名执行，框架本身不分配内存。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::sys::boot::{BootPlan, BootStep, StepError};
+//!
+//! let mut plan = BootPlan::<8>::new();
+//! plan.add(BootStep::new("fs"))?;
+//! plan.add(BootStep::new("wifi").optional().with_timeout(Duration::from_secs(15)).with_retries(2))?;
+//! plan.add(BootStep::new("http").after(&["fs", "wifi"]).optional())?;
+//!
+//! let report = plan.run(|step| async move {
+//!     match step {
+//!         "fs" => fs.mount().map_err(|_| StepError),
+//!         "wifi" => wifi.connect().await.map_err(|_| StepError),
+//!         "http" => { spawner.spawn(http_task()).map_err(|_| StepError) }
+//!         _ => Ok(()),
+//!     }
+//! }).await?;
+//! log_info!("boot: {}", report.readiness().name());
+//! ```
+//!
+//! 首次启动供应 (取代 main 开头复制粘贴的"是否首次启动"判断):
+//!
+//! ```rust,ignore
+//! use rustrtos::sys::boot::{self, Banner, BootPlan, BootStep, Provision};
+//!
+//! let mut plan = BootPlan::<8>::new();
+//! plan.banner(Banner::new("Gateway").with_lines(&[("board", "N16R8")]));
+//! plan.add(BootStep::new("format").provisioning(Provision::FirstFlash))?;
+//! plan.add(BootStep::new("keys").after(&["format"]).provisioning(Provision::FirstFlash))?;
+//! plan.add(BootStep::new("migrate").provisioning(Provision::AfterOta))?;
+//! plan.add(BootStep::new("fs").after(&["format"]))?;
+//!
+//! // 数据分区无法挂载时视为烧录后首次启动
+//! let mounted = fs.mount().is_ok();
+//! let kind = boot::detect(mounted.then_some(&kv));
+//! let report = plan.run_for(kind, |step| async move { /* 按步骤名分发 */ }).await?;
+//! if report.provisioned() {
+//!     boot::commit(&kv)?;
+//! }
+//! ```
+
+use core::fmt;
+use core::future::Future;
+
+use embassy_time::{with_timeout, Duration, Instant, Timer};
+
+use crate::fs::kv::{KvError, KvStore};
+use crate::sync::bus::{self, SystemEvent};
+use crate::util::log::*;
+
+/// 默认单步超时
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 默认重试间隔
+pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// 启动编排错误 (计划无效)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootError {
+    /// 步骤数量超出容量
+    Full,
+    /// 步骤名重复
+    Duplicate(&'static str),
+    /// 依赖了未登记的步骤 (步骤名, 依赖名)
+    UnknownDependency(&'static str, &'static str),
+    /// 存在循环依赖 (循环中的某个步骤)
+    Cycle(&'static str),
+}
+
+impl fmt::Display for BootError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full => write!(f, "Too many boot steps"),
+            Self::Duplicate(name) => write!(f, "Duplicate boot step: {}", name),
+            Self::UnknownDependency(name, dep) => write!(f, "Boot step {} depends on unknown {}", name, dep),
+            Self::Cycle(name) => write!(f, "Dependency cycle at boot step {}", name),
+        }
+    }
+}
+
+/// 初始化动作失败
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepError;
+
+// ===== 步骤 =====
+
+/// 启动步骤
+#[derive(Debug, Clone, Copy)]
+pub struct BootStep {
+    /// 步骤名
+    pub name: &'static str,
+    /// 依赖的步骤
+    pub deps: &'static [&'static str],
+    /// 单次尝试超时
+    pub timeout: Duration,
+    /// 失败后的重试次数
+    pub retries: u8,
+    /// 重试间隔
+    pub retry_delay: Duration,
+    /// 失败时是否允许降级运行
+    pub optional: bool,
+    /// 供应步骤的执行场景 (`None` 为每次启动都执行)
+    pub provision: Option<Provision>,
+}
+
+impl BootStep {
+    /// 创建必需步骤 (无依赖、默认超时、不重试)
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            deps: &[],
+            timeout: DEFAULT_TIMEOUT,
+            retries: 0,
+            retry_delay: DEFAULT_RETRY_DELAY,
+            optional: false,
+            provision: None,
+        }
+    }
+
+    /// 设置依赖
+    pub const fn after(mut self, deps: &'static [&'static str]) -> Self {
+        self.deps = deps;
+        self
+    }
+
+    /// 设置超时
+    pub const fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 设置重试次数
+    pub const fn with_retries(mut self, retries: u8) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// 设置重试间隔
+    pub const fn with_retry_delay(mut self, delay: Duration) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// 标记为可选 (失败时降级运行)
+    pub const fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+
+    /// 标记为供应步骤 (只在对应的首次启动场景执行)
+    pub const fn provisioning(mut self, when: Provision) -> Self {
+        self.provision = Some(when);
+        self
+    }
+}
+
+/// 步骤结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepStatus {
+    /// 初始化成功
+    Ok,
+    /// 所有尝试均失败
+    Failed,
+    /// 最后一次尝试超时
+    TimedOut,
+    /// 依赖未就绪，未执行
+    Skipped,
+    /// 供应步骤，本次启动不需要执行
+    NotNeeded,
+}
+
+impl StepStatus {
+    /// 状态名称
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Failed => "failed",
+            Self::TimedOut => "timeout",
+            Self::Skipped => "skipped",
+            Self::NotNeeded => "not needed",
+        }
+    }
+
+    /// 是否成功 (不需要执行的供应步骤视为成功，依赖它的步骤照常执行)
+    pub const fn is_ok(&self) -> bool {
+        matches!(self, Self::Ok | Self::NotNeeded)
+    }
+}
+
+/// 单个步骤的执行记录
+#[derive(Debug, Clone, Copy)]
+pub struct StepOutcome {
+    /// 步骤名
+    pub name: &'static str,
+    /// 结果
+    pub status: StepStatus,
+    /// 尝试次数 (跳过时为 0)
+    pub attempts: u8,
+    /// 总耗时 (含重试间隔)
+    pub elapsed: Duration,
+    /// 是否为可选步骤
+    pub optional: bool,
+    /// 是否为供应步骤
+    pub provisioning: bool,
+}
+
+/// 系统就绪状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Readiness {
+    /// 所有步骤成功
+    Ready,
+    /// 必需步骤全部成功，部分可选步骤失败或跳过
+    Degraded,
+    /// 有必需步骤失败或被跳过
+    Failed,
+}
+
+impl Readiness {
+    /// 状态名称
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Ready => "ready",
+            Self::Degraded => "degraded",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// 启动报告
+#[derive(Debug, Clone)]
+pub struct BootReport<const N: usize> {
+    /// 按执行顺序的步骤记录
+    pub steps: heapless::Vec<StepOutcome, N>,
+    /// 总耗时
+    pub elapsed: Duration,
+    /// 启动类型
+    pub kind: BootKind,
+}
+
+impl<const N: usize> BootReport<N> {
+    /// 最终就绪状态
+    pub fn readiness(&self) -> Readiness {
+        let mut readiness = Readiness::Ready;
+        for step in self.steps.iter().filter(|s| !s.status.is_ok()) {
+            if !step.optional {
+                return Readiness::Failed;
+            }
+            readiness = Readiness::Degraded;
+        }
+        readiness
+    }
+
+    /// 查询步骤记录
+    pub fn step(&self, name: &str) -> Option<&StepOutcome> {
+        self.steps.iter().find(|s| s.name == name)
+    }
+
+    /// 未成功的步骤数
+    pub fn failed(&self) -> usize {
+        self.steps.iter().filter(|s| !s.status.is_ok()).count()
+    }
+
+    /// 本次启动需要的供应步骤是否全部成功 (成功后应调用 `commit`)
+    ///
+    /// 普通启动总是返回 `false`，无需写入标记。
+    pub fn provisioned(&self) -> bool {
+        self.kind != BootKind::Normal && self.steps.iter().filter(|s| s.provisioning).all(|s| s.status.is_ok())
+    }
+}
+
+// ===== 首次启动检测 =====
+
+/// 镜像标记所在的键值存储命名空间
+pub const KV_NAMESPACE: &str = "boot";
+
+/// 已完成供应的镜像摘要
+pub const KV_IMAGE: &str = "image";
+
+/// 启动类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootKind {
+    /// 烧录 (或擦除数据分区) 后首次启动
+    FirstFlash,
+    /// OTA 后首次启动
+    AfterOta,
+    /// 普通启动
+    Normal,
+}
+
+impl BootKind {
+    /// 类型名称
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::FirstFlash => "first boot after flash",
+            Self::AfterOta => "first boot after OTA",
+            Self::Normal => "normal",
+        }
+    }
+
+    /// 按已记录的镜像摘要判断
+    ///
+    /// 通过串口重新烧录但未擦除数据分区时，与 OTA 一样识别为 `AfterOta`。
+    pub fn classify(recorded: Option<&[u8; 32]>, running: &[u8; 32]) -> Self {
+        match recorded {
+            None => Self::FirstFlash,
+            Some(sha) if sha == running => Self::Normal,
+            Some(_) => Self::AfterOta,
+        }
+    }
+}
+
+/// 供应步骤的执行场景
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provision {
+    /// 仅烧录后首次启动
+    FirstFlash,
+    /// 仅 OTA 后首次启动
+    AfterOta,
+    /// 两种首次启动都执行
+    Always,
+}
+
+impl Provision {
+    /// 本次启动是否需要执行
+    pub const fn applies(&self, kind: BootKind) -> bool {
+        matches!(
+            (self, kind),
+            (Self::FirstFlash, BootKind::FirstFlash)
+                | (Self::AfterOta, BootKind::AfterOta)
+                | (Self::Always, BootKind::FirstFlash | BootKind::AfterOta)
+        )
+    }
+}
+
+/// 检测启动类型
+///
+/// `kv` 为 `None` (数据分区尚不可用) 或没有标记时视为烧录后首次启动。
+pub fn detect(kv: Option<&KvStore<'_>>) -> BootKind {
+    let mut sha = [0u8; 32];
+    let recorded = match kv.map(|kv| kv.get(KV_NAMESPACE, KV_IMAGE, &mut sha)) {
+        Some(Ok(32)) => Some(&sha),
+        _ => None,
+    };
+    BootKind::classify(recorded, crate::sys::app::running().elf_sha256())
+}
+
+/// 记录运行镜像已完成供应，之后的启动识别为普通启动
+pub fn commit(kv: &KvStore<'_>) -> Result<(), KvError> {
+    kv.set(KV_NAMESPACE, KV_IMAGE, crate::sys::app::running().elf_sha256())
+}
+
+// ===== 启动横幅 =====
+
+/// 启动横幅
+#[derive(Debug, Clone, Copy)]
+pub struct Banner {
+    /// 标题
+    pub title: &'static str,
+    /// 是否输出运行镜像的名称、版本与构建时间
+    pub image: bool,
+    /// 自定义行 (名称, 值)
+    pub lines: &'static [(&'static str, &'static str)],
+}
+
+impl Banner {
+    /// 创建 (默认输出镜像信息)
+    pub const fn new(title: &'static str) -> Self {
+        Self { title, image: true, lines: &[] }
+    }
+
+    /// 设置自定义行
+    pub const fn with_lines(mut self, lines: &'static [(&'static str, &'static str)]) -> Self {
+        self.lines = lines;
+        self
+    }
+
+    /// 不输出镜像信息
+    pub const fn without_image(mut self) -> Self {
+        self.image = false;
+        self
+    }
+
+    /// 输出到日志
+    pub fn log(&self, _kind: BootKind) {
+        log_info!("===== {} =====", self.title);
+        if self.image {
+            let _app = crate::sys::app::running();
+            log_info!("image: {} {} ({} {})", _app.project_name(), _app.version(), _app.date(), _app.time());
+        }
+        log_info!("boot: {}", _kind.name());
+        for (_name, _value) in self.lines {
+            log_info!("{}: {}", _name, _value);
+        }
+    }
+}
+
+// ===== 计划 =====
+
+/// 启动计划
+pub struct BootPlan<const N: usize> {
+    steps: heapless::Vec<BootStep, N>,
+    banner: Option<Banner>,
+}
+
+impl<const N: usize> BootPlan<N> {
+    /// 创建空计划
+    pub const fn new() -> Self {
+        Self { steps: heapless::Vec::new(), banner: None }
+    }
+
+    /// 设置启动横幅 (执行步骤前输出)
+    pub fn banner(&mut self, banner: Banner) -> &mut Self {
+        self.banner = Some(banner);
+        self
+    }
+
+    /// 登记步骤
+    pub fn add(&mut self, step: BootStep) -> Result<&mut Self, BootError> {
+        if self.index_of(step.name).is_some() {
+            return Err(BootError::Duplicate(step.name));
+        }
+        self.steps.push(step).map_err(|_| BootError::Full)?;
+        Ok(self)
+    }
+
+    /// 已登记的步骤数
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    fn index_of(&self, name: &str) -> Option<usize> {
+        self.steps.iter().position(|s| s.name == name)
+    }
+
+    /// 计算执行顺序 (步骤下标)
+    ///
+    /// 每轮选出依赖已全部排入的第一个步骤，保证同层按登记顺序执行
+    pub fn order(&self) -> Result<heapless::Vec<usize, N>, BootError> {
+        for step in &self.steps {
+            if let Some(dep) = step.deps.iter().find(|d| self.index_of(d).is_none()) {
+                return Err(BootError::UnknownDependency(step.name, dep));
+            }
+        }
+
+        let mut placed = [false; N];
+        let mut order = heapless::Vec::new();
+        while order.len() < self.steps.len() {
+            let next = self.steps.iter().enumerate().position(|(i, step)| {
+                !placed[i] && step.deps.iter().all(|d| self.index_of(d).is_some_and(|j| placed[j]))
+            });
+            let Some(i) = next else {
+                // 剩余步骤互相依赖
+                let stuck = (0..self.steps.len()).find(|&i| !placed[i]).unwrap_or(0);
+                return Err(BootError::Cycle(self.steps[stuck].name));
+            };
+            placed[i] = true;
+            let _ = order.push(i);
+        }
+        Ok(order)
+    }
+
+    /// 按顺序执行所有步骤 (普通启动，供应步骤不执行)
+    ///
+    /// `init` 按步骤名执行初始化动作，每次尝试调用一次。依赖未成功的步骤被跳过。
+    /// 结束后发布 `SystemEvent::BootCompleted`。
+    pub async fn run<F, Fut>(&self, init: F) -> Result<BootReport<N>, BootError>
+    where
+        F: FnMut(&'static str) -> Fut,
+        Fut: Future<Output = Result<(), StepError>>,
+    {
+        self.run_for(BootKind::Normal, init).await
+    }
+
+    /// 按启动类型执行所有步骤
+    ///
+    /// 与 `run` 相同，但供应步骤只在其场景与 `kind` 相符时执行，否则记为 `NotNeeded`。
+    pub async fn run_for<F, Fut>(&self, kind: BootKind, mut init: F) -> Result<BootReport<N>, BootError>
+    where
+        F: FnMut(&'static str) -> Fut,
+        Fut: Future<Output = Result<(), StepError>>,
+    {
+        let order = self.order()?;
+        if let Some(banner) = &self.banner {
+            banner.log(kind);
+        }
+        let boot_start = Instant::now();
+        let mut report = BootReport { steps: heapless::Vec::new(), elapsed: Duration::from_ticks(0), kind };
+
+        for &i in &order {
+            let step = &self.steps[i];
+            let deps_ok = step
+                .deps
+                .iter()
+                .all(|d| report.step(d).is_some_and(|s| s.status.is_ok()));
+
+            let start = Instant::now();
+            let needed = step.provision.is_none_or(|when| when.applies(kind));
+            let (status, attempts) = if !needed {
+                (StepStatus::NotNeeded, 0)
+            } else if deps_ok {
+                run_step(step, &mut init).await
+            } else {
+                (StepStatus::Skipped, 0)
+            };
+            let outcome = StepOutcome {
+                name: step.name,
+                status,
+                attempts,
+                elapsed: start.elapsed(),
+                optional: step.optional,
+                provisioning: step.provision.is_some(),
+            };
+
+            match status {
+                StepStatus::NotNeeded => {
+                    log_debug!("boot: {} not needed ({})", step.name, kind.name());
+                }
+                StepStatus::Ok => {
+                    log_info!("boot: {} ok ({})", step.name, crate::util::fmt::Elapsed(outcome.elapsed));
+                }
+                _ if step.optional => {
+                    log_warn!("boot: {} {} (optional, degraded)", step.name, status.name());
+                }
+                _ => {
+                    log_error!("boot: {} {} after {} attempt(s)", step.name, status.name(), attempts);
+                }
+            }
+            let _ = report.steps.push(outcome);
+        }
+
+        report.elapsed = boot_start.elapsed();
+        let readiness = report.readiness();
+        log_info!("boot: {} in {}", readiness.name(), crate::util::fmt::Elapsed(report.elapsed));
+        bus::publish(SystemEvent::BootCompleted {
+            readiness: readiness.name(),
+            failed: report.failed().min(u8::MAX as usize) as u8,
+            elapsed_ms: report.elapsed.as_millis().min(u32::MAX as u64) as u32,
+        });
+        Ok(report)
+    }
+}
+
+impl<const N: usize> Default for BootPlan<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 执行单个步骤 (含重试)，返回 (结果, 尝试次数)
+async fn run_step<F, Fut>(step: &BootStep, init: &mut F) -> (StepStatus, u8)
+where
+    F: FnMut(&'static str) -> Fut,
+    Fut: Future<Output = Result<(), StepError>>,
+{
+    let mut status = StepStatus::Failed;
+    for attempt in 1..=step.retries.saturating_add(1) {
+        if attempt > 1 {
+            Timer::after(step.retry_delay).await;
+        }
+        status = match with_timeout(step.timeout, init(step.name)).await {
+            Ok(Ok(())) => return (StepStatus::Ok, attempt),
+            Ok(Err(StepError)) => StepStatus::Failed,
+            Err(_) => StepStatus::TimedOut,
+        };
+        log_debug!("boot: {} attempt {} {}", step.name, attempt, status.name());
+    }
+    (status, step.retries.saturating_add(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order() {
+        let mut plan = BootPlan::<6>::new();
+        plan.add(BootStep::new("http").after(&["net", "fs"])).unwrap();
+        plan.add(BootStep::new("net").after(&["radio"])).unwrap();
+        plan.add(BootStep::new("fs")).unwrap();
+        plan.add(BootStep::new("radio")).unwrap();
+        let names: heapless::Vec<&str, 6> = plan.order().unwrap().iter().map(|&i| plan.steps[i].name).collect();
+        assert_eq!(names.as_slice(), &["fs", "radio", "net", "http"]);
+
+        assert_eq!(plan.add(BootStep::new("fs")).err(), Some(BootError::Duplicate("fs")));
+        plan.add(BootStep::new("ota").after(&["cloud"])).unwrap();
+        assert_eq!(plan.order(), Err(BootError::UnknownDependency("ota", "cloud")));
+
+        let mut cyclic = BootPlan::<2>::new();
+        cyclic.add(BootStep::new("a").after(&["b"])).unwrap();
+        cyclic.add(BootStep::new("b").after(&["a"])).unwrap();
+        assert_eq!(cyclic.order(), Err(BootError::Cycle("a")));
+    }
+
+    #[test]
+    fn test_run_degraded() {
+        let mut plan = BootPlan::<4>::new();
+        plan.add(BootStep::new("fs")).unwrap();
+        plan.add(BootStep::new("wifi").optional().with_retries(2).with_retry_delay(Duration::from_millis(1))).unwrap();
+        plan.add(BootStep::new("http").after(&["wifi"]).optional()).unwrap();
+
+        let report = embassy_futures::block_on(plan.run(|step| async move {
+            if step == "wifi" { Err(StepError) } else { Ok(()) }
+        }))
+        .unwrap();
+
+        assert_eq!(report.readiness(), Readiness::Degraded);
+        assert_eq!(report.step("wifi").map(|s| (s.status, s.attempts)), Some((StepStatus::Failed, 3)));
+        assert_eq!(report.step("http").map(|s| s.status), Some(StepStatus::Skipped));
+        assert_eq!(report.failed(), 2);
+    }
+
+    #[test]
+    fn test_provisioning_steps() {
+        let image = [7u8; 32];
+        assert_eq!(BootKind::classify(None, &image), BootKind::FirstFlash);
+        assert_eq!(BootKind::classify(Some(&[1; 32]), &image), BootKind::AfterOta);
+        assert_eq!(BootKind::classify(Some(&image), &image), BootKind::Normal);
+
+        let mut plan = BootPlan::<4>::new();
+        plan.add(BootStep::new("format").provisioning(Provision::FirstFlash)).unwrap();
+        plan.add(BootStep::new("migrate").provisioning(Provision::AfterOta)).unwrap();
+        plan.add(BootStep::new("fs").after(&["format"])).unwrap();
+
+        let run = |kind| embassy_futures::block_on(plan.run_for(kind, |_| async { Ok(()) })).unwrap();
+        let first = run(BootKind::FirstFlash);
+        assert_eq!(first.step("format").map(|s| s.status), Some(StepStatus::Ok));
+        assert_eq!(first.step("migrate").map(|s| s.status), Some(StepStatus::NotNeeded));
+        assert_eq!(first.step("fs").map(|s| s.status), Some(StepStatus::Ok));
+        assert!(first.provisioned());
+        assert_eq!(first.readiness(), Readiness::Ready);
+
+        let normal = run(BootKind::Normal);
+        assert_eq!(normal.step("format").map(|s| s.attempts), Some(0));
+        assert!(!normal.provisioned());
+    }
+}
@@ -0,0 +1,259 @@
+//! 运行时能力登记
+//!
+//! 汇总"编译进固件的功能"与"启动时初始化成功的功能"，供 Shell (`caps`)、
+//! HTTP 管理面 (`/api/system`) 与 BLE 设备信息服务对外公布，主机工具据此适配
+//! 设备的实际能力:
+//! - 编译期能力由 Cargo feature 决定 (`COMPILED`)
+//! - 运行期就绪状态由初始化代码登记，或按启动报告中与能力同名的步骤自动登记
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::sys::{self, caps::{self, Caps}};
+//!
+//! let report = plan.run(init).await?;
+//! caps::record_boot(&report);          // "wifi"/"fs"/... 步骤结果
+//! caps::mark_ready(Caps::PSRAM);
+//!
+//! let caps = sys::capabilities();
+//! if caps.ready.contains(Caps::NETWORK) {
+//!     spawner.spawn(telemetry_task())?;
+//! }
+//! ```
+
+use core::fmt;
+use core::ops::{BitAnd, BitOr};
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use super::boot::BootReport;
+use crate::util::json::{ArrayWriter, ObjectWriter, ToJson};
+
+/// 能力位集合
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Caps(pub u32);
+
+impl Caps {
+    /// 空集合
+    pub const NONE: Caps = Caps(0);
+    /// WiFi
+    pub const WIFI: Caps = Caps(1 << 0);
+    /// BLE
+    pub const BLE: Caps = Caps(1 << 1);
+    /// TCP/IP 网络栈
+    pub const NETWORK: Caps = Caps(1 << 2);
+    /// TLS/HTTPS
+    pub const TLS: Caps = Caps(1 << 3);
+    /// WiFi + BLE 共存
+    pub const COEX: Caps = Caps(1 << 4);
+    /// 文件系统
+    pub const FS: Caps = Caps(1 << 5);
+    /// PSRAM
+    pub const PSRAM: Caps = Caps(1 << 6);
+    /// OTA 升级
+    pub const OTA: Caps = Caps(1 << 7);
+    /// 加密 NVS 读取
+    pub const NVS_ENCRYPTION: Caps = Caps(1 << 8);
+    /// C 语言接口
+    pub const FFI: Caps = Caps(1 << 9);
+    /// 日志输出
+    pub const LOGGING: Caps = Caps(1 << 10);
+    /// 生产锁定
+    pub const PRODUCTION: Caps = Caps(1 << 11);
+    /// 无 panic 构建
+    pub const PANIC_FREE: Caps = Caps(1 << 12);
+
+    /// 原始位
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// 是否包含全部指定能力
+    pub const fn contains(self, other: Caps) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// 是否为空
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// 并集
+    pub const fn union(self, other: Caps) -> Caps {
+        Caps(self.0 | other.0)
+    }
+
+    /// 按名称查找单个能力
+    pub fn from_name(name: &str) -> Option<Caps> {
+        NAMES.iter().find(|(_, n)| *n == name).map(|(cap, _)| *cap)
+    }
+
+    /// 遍历集合中的能力名称
+    pub fn names(self) -> impl Iterator<Item = &'static str> {
+        NAMES.iter().filter(move |(cap, _)| self.contains(*cap)).map(|(_, name)| *name)
+    }
+}
+
+impl BitOr for Caps {
+    type Output = Caps;
+
+    fn bitor(self, rhs: Caps) -> Caps {
+        self.union(rhs)
+    }
+}
+
+impl BitAnd for Caps {
+    type Output = Caps;
+
+    fn bitand(self, rhs: Caps) -> Caps {
+        Caps(self.0 & rhs.0)
+    }
+}
+
+impl ToJson for Caps {
+    fn write_json<W: fmt::Write + ?Sized>(&self, w: &mut W) -> fmt::Result {
+        let mut array = ArrayWriter::new(w)?;
+        for name in self.names() {
+            array.item(name)?;
+        }
+        array.finish()
+    }
+}
+
+/// 能力名称 (也是对应启动步骤名)
+const NAMES: [(Caps, &str); 13] = [
+    (Caps::WIFI, "wifi"),
+    (Caps::BLE, "ble"),
+    (Caps::NETWORK, "network"),
+    (Caps::TLS, "tls"),
+    (Caps::COEX, "coex"),
+    (Caps::FS, "fs"),
+    (Caps::PSRAM, "psram"),
+    (Caps::OTA, "ota"),
+    (Caps::NVS_ENCRYPTION, "nvs-encryption"),
+    (Caps::FFI, "ffi"),
+    (Caps::LOGGING, "logging"),
+    (Caps::PRODUCTION, "production"),
+    (Caps::PANIC_FREE, "panic-free"),
+];
+
+/// 编译进固件的能力
+pub const COMPILED: Caps = compiled();
+
+const fn compiled() -> Caps {
+    let mut caps = Caps::FS.union(Caps::PSRAM).union(Caps::OTA);
+    let features = [
+        (cfg!(feature = "wifi"), Caps::WIFI),
+        (cfg!(any(feature = "ble", feature = "ble-esp")), Caps::BLE),
+        (cfg!(feature = "network"), Caps::NETWORK),
+        (cfg!(feature = "tls"), Caps::TLS),
+        (cfg!(feature = "coex"), Caps::COEX),
+        (cfg!(feature = "nvs-encryption"), Caps::NVS_ENCRYPTION),
+        (cfg!(feature = "ffi"), Caps::FFI),
+        (cfg!(any(feature = "log-println", feature = "log-defmt", feature = "dev")), Caps::LOGGING),
+        (cfg!(feature = "production"), Caps::PRODUCTION),
+        (cfg!(feature = "panic-free"), Caps::PANIC_FREE),
+    ];
+    let mut i = 0;
+    while i < features.len() {
+        if features[i].0 {
+            caps = caps.union(features[i].1);
+        }
+        i += 1;
+    }
+    caps
+}
+
+// ===== 运行期登记 =====
+
+/// 初始化成功的能力位
+static READY: AtomicU32 = AtomicU32::new(0);
+
+/// 登记能力已就绪
+pub fn mark_ready(caps: Caps) {
+    READY.fetch_or(caps.0, Ordering::Relaxed);
+}
+
+/// 登记能力不可用 (初始化失败或运行中关闭)
+pub fn mark_unavailable(caps: Caps) {
+    READY.fetch_and(!caps.0, Ordering::Relaxed);
+}
+
+/// 按启动报告登记: 名称与能力相同的步骤成功则就绪，否则不可用
+pub fn record_boot<const N: usize>(report: &BootReport<N>) {
+    for step in &report.steps {
+        if let Some(cap) = Caps::from_name(step.name) {
+            if step.status.is_ok() {
+                mark_ready(cap);
+            } else {
+                mark_unavailable(cap);
+            }
+        }
+    }
+}
+
+/// 设备能力快照
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// 编译进固件的能力
+    pub compiled: Caps,
+    /// 初始化成功的能力 (总是 `compiled` 的子集)
+    pub ready: Caps,
+}
+
+impl Capabilities {
+    /// 能力是否可用 (已编译且已就绪)
+    pub const fn is_available(&self, caps: Caps) -> bool {
+        self.ready.contains(caps)
+    }
+
+    /// 紧凑二进制表示 (BLE 特征值): 编译位 u32 + 就绪位 u32，小端
+    pub fn to_bytes(&self) -> [u8; 8] {
+        let mut bytes = [0u8; 8];
+        bytes[..4].copy_from_slice(&self.compiled.0.to_le_bytes());
+        bytes[4..].copy_from_slice(&self.ready.0.to_le_bytes());
+        bytes
+    }
+}
+
+impl ToJson for Capabilities {
+    fn write_json<W: fmt::Write + ?Sized>(&self, w: &mut W) -> fmt::Result {
+        let mut obj = ObjectWriter::new(w)?;
+        obj.field("compiled", &self.compiled)?.field("ready", &self.ready)?;
+        obj.finish()
+    }
+}
+
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for name in self.compiled.names() {
+            let ready = Caps::from_name(name).is_some_and(|cap| self.ready.contains(cap));
+            writeln!(f, "{:<16} {}", name, if ready { "ready" } else { "compiled" })?;
+        }
+        Ok(())
+    }
+}
+
+/// 当前设备能力
+pub fn capabilities() -> Capabilities {
+    Capabilities { compiled: COMPILED, ready: Caps(READY.load(Ordering::Relaxed)) & COMPILED }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::json;
+
+    #[test]
+    fn test_caps_names_and_json() {
+        let caps = Caps::WIFI | Caps::FS;
+        assert!(caps.contains(Caps::FS));
+        assert!(!caps.contains(Caps::WIFI | Caps::BLE));
+        assert_eq!(Caps::from_name("nvs-encryption"), Some(Caps::NVS_ENCRYPTION));
+        assert_eq!(Caps::from_name("http"), None);
+
+        let snapshot = Capabilities { compiled: caps | Caps::PSRAM, ready: caps };
+        let out: heapless::String<96> = json::to_string(&snapshot).unwrap();
+        assert_eq!(out.as_str(), r#"{"compiled":["wifi","fs","psram"],"ready":["wifi","fs"]}"#);
+        assert_eq!(snapshot.to_bytes(), [0x61, 0, 0, 0, 0x21, 0, 0, 0]);
+    }
+}
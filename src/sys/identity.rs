@@ -0,0 +1,308 @@
+//! 设备标识
+//!
+//! 为各网络服务提供统一、稳定的设备身份，取代各处硬编码的名称:
+//! - 设备 ID: 由 eFuse 出厂 MAC 派生，重刷固件、擦除文件系统后保持不变
+//! - 设备名: 用户可指定并持久化；未指定时为 `rustrtos-<MAC 后 3 字节>`
+//! - 派生名称: BLE 广播名、mDNS 主机名、MQTT client-id、AP SSID 统一从这里取
+//!
+//! 设备名限定为主机名安全字符 (字母、数字、`-`)，可直接用作 mDNS 主机名。
+//! 持久化沿用文件系统 (与 `vault` 相同的写临时文件后重命名)。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::sys::identity::{IDENTITY, IDENTITY_PATH};
+//!
+//! let _ = IDENTITY.load(&fs, IDENTITY_PATH);
+//! log_info!("{} ({})", IDENTITY.name(), IDENTITY.id());
+//!
+//! IDENTITY.set_name("pump-3")?;
+//! IDENTITY.save(&fs, IDENTITY_PATH)?;
+//!
+//! let adv = AdvertiseConfig::default(); // 广播名即 "pump-3"
+//! ```
+
+use core::cell::RefCell;
+use core::fmt::{self, Write as _};
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use heapless::String;
+
+use crate::fs::littlefs::{FileSystem, FsError, OpenOptions};
+
+/// 设备名最大长度
+pub const MAX_NAME_LEN: usize = 24;
+
+/// BLE 广播名最大长度 (需与 UUID、标志一起放进 31 字节广播包)
+pub const MAX_BLE_NAME_LEN: usize = 20;
+
+/// 默认持久化路径
+pub const IDENTITY_PATH: &str = "/sys/identity.bin";
+
+/// 持久化文件魔数
+const FILE_MAGIC: &[u8; 4] = b"IDN1";
+
+/// 设备名
+pub type DeviceName = String<MAX_NAME_LEN>;
+
+/// 标识错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdentityError {
+    /// 名称为空、过长或包含非法字符
+    InvalidName,
+    /// 持久化数据损坏
+    Corrupt,
+    /// 文件系统错误
+    Fs(FsError),
+}
+
+impl fmt::Display for IdentityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidName => write!(f, "Invalid device name"),
+            Self::Corrupt => write!(f, "Identity data corrupt"),
+            Self::Fs(e) => write!(f, "Filesystem error: {:?}", e),
+        }
+    }
+}
+
+impl From<FsError> for IdentityError {
+    fn from(e: FsError) -> Self {
+        Self::Fs(e)
+    }
+}
+
+// ===== 设备 ID =====
+
+/// 设备 ID (出厂 MAC)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DeviceId([u8; 6]);
+
+impl DeviceId {
+    /// 从 MAC 地址创建
+    pub const fn from_mac(mac: [u8; 6]) -> Self {
+        Self(mac)
+    }
+
+    /// 读取 eFuse 中的出厂 MAC
+    pub fn read_efuse() -> Self {
+        Self(esp_hal::efuse::Efuse::read_base_mac_address())
+    }
+
+    /// MAC 地址
+    pub const fn mac(&self) -> [u8; 6] {
+        self.0
+    }
+
+    /// 短后缀 (MAC 后 3 字节的十六进制，如 `a1b2c3`)
+    pub fn suffix(&self) -> String<6> {
+        let mut s = String::new();
+        let [.., d, e, f] = self.0;
+        let _ = write!(s, "{:02x}{:02x}{:02x}", d, e, f);
+        s
+    }
+}
+
+/// 12 位小写十六进制，无分隔符
+impl fmt::Display for DeviceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|b| write!(f, "{:02x}", b))
+    }
+}
+
+/// 检查设备名是否合法 (主机名规则: 字母、数字、`-`，不以 `-` 开头或结尾)
+pub fn validate_name(name: &str) -> Result<(), IdentityError> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && !name.starts_with('-')
+        && !name.ends_with('-')
+        && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-');
+    if valid { Ok(()) } else { Err(IdentityError::InvalidName) }
+}
+
+// ===== 设备标识 =====
+
+#[derive(Debug, Clone)]
+struct State {
+    id: Option<DeviceId>,
+    name: Option<DeviceName>,
+}
+
+/// 设备标识
+pub struct Identity {
+    state: BlockingMutex<CriticalSectionRawMutex, RefCell<State>>,
+}
+
+/// 全局设备标识
+pub static IDENTITY: Identity = Identity::new();
+
+impl Identity {
+    /// 创建 (首次访问时读取 eFuse)
+    pub const fn new() -> Self {
+        Self { state: BlockingMutex::new(RefCell::new(State { id: None, name: None })) }
+    }
+
+    /// 使用指定 MAC 创建 (测试或外置 MAC 芯片)
+    pub const fn with_mac(mac: [u8; 6]) -> Self {
+        Self { state: BlockingMutex::new(RefCell::new(State { id: Some(DeviceId::from_mac(mac)), name: None })) }
+    }
+
+    /// 设备 ID
+    pub fn id(&self) -> DeviceId {
+        self.state.lock(|s| *s.borrow_mut().id.get_or_insert_with(DeviceId::read_efuse))
+    }
+
+    /// 设备名 (用户指定，否则为默认名)
+    pub fn name(&self) -> DeviceName {
+        self.assigned_name().unwrap_or_else(|| self.default_name())
+    }
+
+    /// 用户指定的设备名
+    pub fn assigned_name(&self) -> Option<DeviceName> {
+        self.state.lock(|s| s.borrow().name.clone())
+    }
+
+    /// 默认设备名 (`rustrtos-a1b2c3`)
+    pub fn default_name(&self) -> DeviceName {
+        let mut name = DeviceName::new();
+        let _ = write!(name, "{}-{}", crate::NAME, self.id().suffix());
+        name
+    }
+
+    /// 设置设备名 (需调用 `save` 持久化)
+    pub fn set_name(&self, name: &str) -> Result<(), IdentityError> {
+        validate_name(name)?;
+        let name = DeviceName::try_from(name).map_err(|_| IdentityError::InvalidName)?;
+        self.state.lock(|s| s.borrow_mut().name = Some(name));
+        Ok(())
+    }
+
+    /// 恢复默认设备名
+    pub fn clear_name(&self) {
+        self.state.lock(|s| s.borrow_mut().name = None);
+    }
+
+    /// mDNS 主机名 (小写设备名，不含 `.local`)
+    pub fn hostname(&self) -> DeviceName {
+        let mut host = self.name();
+        host.make_ascii_lowercase();
+        host
+    }
+
+    /// BLE 广播名 (设备名截断到 `MAX_BLE_NAME_LEN`)
+    pub fn ble_name(&self) -> String<MAX_BLE_NAME_LEN> {
+        let name = self.name();
+        String::try_from(&name[..name.len().min(MAX_BLE_NAME_LEN)]).unwrap_or_default()
+    }
+
+    /// MQTT client-id (`<主机名>-<设备 ID>`，改名后仍全局唯一)
+    pub fn client_id(&self) -> String<{ MAX_NAME_LEN + 13 }> {
+        let mut id = String::new();
+        let _ = write!(id, "{}-{}", self.hostname(), self.id());
+        id
+    }
+
+    /// 配网热点 SSID (`<设备名>-AP`)
+    pub fn ap_ssid(&self) -> String<32> {
+        let mut ssid = String::new();
+        let _ = write!(ssid, "{}-AP", self.name());
+        ssid
+    }
+
+    // ===== 持久化 =====
+
+    /// 编码: 魔数 + 名称长度 (0 表示未指定) + 名称
+    fn encode(&self, out: &mut [u8; FILE_MAGIC.len() + 1 + MAX_NAME_LEN]) -> usize {
+        let name = self.assigned_name();
+        let name = name.as_deref().unwrap_or("");
+        out[..4].copy_from_slice(FILE_MAGIC);
+        out[4] = name.len() as u8;
+        out[5..5 + name.len()].copy_from_slice(name.as_bytes());
+        5 + name.len()
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<(), IdentityError> {
+        let rest = data.strip_prefix(FILE_MAGIC).ok_or(IdentityError::Corrupt)?;
+        let (&len, name) = rest.split_first().ok_or(IdentityError::Corrupt)?;
+        if name.len() != len as usize {
+            return Err(IdentityError::Corrupt);
+        }
+        if name.is_empty() {
+            self.clear_name();
+            return Ok(());
+        }
+        let name = core::str::from_utf8(name).map_err(|_| IdentityError::Corrupt)?;
+        self.set_name(name).map_err(|_| IdentityError::Corrupt)
+    }
+
+    /// 保存到文件
+    pub fn save(&self, fs: &FileSystem, path: &str) -> Result<(), IdentityError> {
+        let mut buf = [0u8; FILE_MAGIC.len() + 1 + MAX_NAME_LEN];
+        let len = self.encode(&mut buf);
+        let mut tmp: String<64> = String::new();
+        tmp.push_str(path).and_then(|_| tmp.push_str(".tmp")).map_err(|_| FsError::PathTooLong)?;
+        {
+            let mut file = fs.open(&tmp, OpenOptions::write_only())?;
+            file.write_all(&buf[..len])?;
+            file.sync()?;
+        }
+        if fs.exists(path)? {
+            fs.remove(path)?;
+        }
+        fs.rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// 从文件加载 (文件不存在时返回 `Fs(NotFound)`，保持默认名)
+    pub fn load(&self, fs: &FileSystem, path: &str) -> Result<(), IdentityError> {
+        let mut buf = [0u8; FILE_MAGIC.len() + 1 + MAX_NAME_LEN + 1];
+        let mut file = fs.open(path, OpenOptions::read_only())?;
+        let mut len = 0;
+        while len < buf.len() {
+            match file.read(&mut buf[len..])? {
+                0 => break,
+                n => len += n,
+            }
+        }
+        self.decode(&buf[..len])
+    }
+}
+
+impl Default for Identity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        let identity = Identity::with_mac([0x7c, 0xdf, 0xa1, 0x00, 0x12, 0xab]);
+        assert_eq!(identity.name(), "rustrtos-0012ab");
+        assert_eq!(identity.client_id(), "rustrtos-0012ab-7cdfa10012ab");
+
+        assert_eq!(identity.set_name("-pump"), Err(IdentityError::InvalidName));
+        assert_eq!(identity.set_name("pump 3"), Err(IdentityError::InvalidName));
+        identity.set_name("Pump-3-Basement-Eastwing").unwrap();
+        assert_eq!(identity.hostname(), "pump-3-basement-eastwing");
+        assert_eq!(identity.ble_name(), "Pump-3-Basement-East");
+        assert_eq!(identity.ap_ssid(), "Pump-3-Basement-Eastwing-AP");
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let identity = Identity::with_mac([0; 6]);
+        identity.set_name("pump-3").unwrap();
+        let mut buf = [0u8; 29];
+        let len = identity.encode(&mut buf);
+
+        let restored = Identity::with_mac([0; 6]);
+        restored.decode(&buf[..len]).unwrap();
+        assert_eq!(restored.assigned_name().as_deref(), Some("pump-3"));
+        assert_eq!(restored.decode(&buf[..len - 1]), Err(IdentityError::Corrupt));
+    }
+}
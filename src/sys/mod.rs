@@ -0,0 +1,45 @@
+//! 系统服务模块
+//!
+//! 提供与具体外设无关的系统级服务:
+//! - `security`: 调试器检测、生产锁定、安全下载模式状态
+//! - `vault`: 凭据保险库 (口令哈希、令牌、密钥，持久化到文件系统)
+//! - `shell`: 命令行 Shell (行编辑、命令分发，串口/TCP 共用)
+//! - `auth`: 访问控制 (令牌/口令认证、失败锁定、审计事件)
+//! - `identity`: 设备标识 (eFuse MAC 派生的设备 ID、可持久化的设备名)
+//! - `time`: 时间服务 (单调时钟与墙上时钟映射、跳变通知、按墙上时间调度)
+//! - `rtc`: 外部实时时钟 (DS3231/PCF8563 驱动、启动恢复与授时后写回、漂移统计)
+//! - `timing`: 时间驱动诊断 (定时器中断次数、处理延迟、节拍溢出检测)
+//! - `boot`: 启动编排 (按依赖顺序初始化子系统、超时重试、降级运行、首次启动供应、启动横幅)
+//! - `reboot`: 有序重启 (关机通知、限时收尾、记录重启原因)
+//! - `app`: 应用描述符 (const 构建器、运行/备用槽位的版本读取与比较)
+//! - `ota`: OTA 固件更新 (otadata 槽位选择、流式写入与 SHA-256 校验、未确认镜像回滚)
+//! - `caps`: 能力登记 (编译进固件与启动成功的功能，供主机工具查询)
+//! - `status`: 实时状态订阅 (IP、RSSI、空闲堆、电量的变化合并广播，供 UI 任务等待)
+
+pub mod security;
+pub mod vault;
+pub mod shell;
+pub mod auth;
+pub mod time;
+pub mod timing;
+pub mod rtc;
+pub mod identity;
+pub mod boot;
+pub mod reboot;
+pub mod app;
+pub mod ota;
+pub mod caps;
+pub mod status;
+
+pub use app::{AppDesc, AppDescBuilder, AppError};
+pub use auth::{AuthError, AuthPolicy, Authenticator, Credential};
+pub use caps::{capabilities, Capabilities, Caps};
+pub use boot::{Banner, BootKind, BootPlan, BootReport, BootStep, Provision, Readiness};
+pub use ota::{BootCheck, Ota, OtaError, OtaState, OtaUpdater};
+pub use identity::{DeviceId, Identity, IdentityError, IDENTITY};
+pub use reboot::{RebootReason, RebootRecord, SHUTDOWN};
+pub use rtc::{DateTime, Ds3231, ExternalRtc, Pcf8563, RtcError, RtcStats, RtcSync};
+pub use status::{Fields, LiveStatus, Snapshot, StatusWatcher, STATUS};
+pub use security::{SecurityPolicy, SecurityReport, SecurityStatus};
+pub use time::{TimeService, TimeSource, WallTime, TIME};
+pub use timing::{TimingConfig, TimingStats};
@@ -0,0 +1,738 @@
+//! OTA 固件更新 (ota_0 / ota_1 + otadata)
+//!
+//! 与 ESP-IDF 二级引导程序兼容的 OTA 流程:
+//! - `Ota`: 读写 otadata 分区 (两个扇区各存一个 `esp_ota_select_entry_t`，
+//!   序号最大且 CRC 有效的条目决定启动槽位: `(seq - 1) % 槽位数`)
+//! - `OtaUpdater`: 向备用槽位流式写入镜像 (`begin` / `write` / `finalize`)，
+//!   逐扇区擦写，同时计算 SHA-256；`finalize` 校验镜像头、应用描述符
+//!   (项目名、安全版本)、镜像末尾附加的摘要，并要求厂商签名的
+//!   `FirmwareManifest` 验证通过、其中的大小与摘要与写入内容一致、且其版本允许
+//!   从运行固件升级 (`min_version` ≤ 运行版本 < `version`，拒绝重放旧清单降级)，
+//!   全部通过后才把新条目写入非活动扇区，下次复位即从新槽位启动
+//! - 启动回滚: 新镜像首次启动时 `check_boot` 把状态从 NEW 改为 PENDING_VERIFY；
+//!   若再次启动时仍为 PENDING_VERIFY (上次启动未确认就复位了)，标记为 ABORTED
+//!   并切回上一个槽位。自检通过后调用 `mark_valid` 确认镜像
+//!
+//! 引导程序开启 `CONFIG_BOOTLOADER_APP_ROLLBACK_ENABLE` 时会自行完成同样的回滚，
+//! 两者使用相同的状态机，可以共存。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::sys::ota::{BootCheck, Ota};
+//! use rustrtos::sys::reboot::{self, RebootReason};
+//!
+//! let table = PartitionTable::default_16mb_ota();
+//! let mut ota = Ota::new(&table, FLASH_SIZE)?;
+//!
+//! // 启动时: 未确认的镜像再次启动则回滚
+//! if let BootCheck::RolledBack { .. } = ota.check_boot()? {
+//!     reboot::reboot(RebootReason::Recovery).await;
+//! }
+//! selftest::gate_ota(&mut checks, &kv, || { let _ = ota.mark_valid(); }).await;
+//!
+//! // 通过 WiFi 下载新固件: 先取签名清单，再流式写入镜像
+//! let mut client = TcpClient::new(stack, &mut rx_buf, &mut tx_buf);
+//! client.connect(server).await?;
+//! let mut header = [0u8; MANIFEST_LEN];
+//! client.read_exact(&mut header).await?;
+//! let manifest = FirmwareManifest::parse(&header)?;
+//! let mut updater = ota.begin()?;
+//! let mut chunk = [0u8; 1024];
+//! loop {
+//!     let n = client.read(&mut chunk).await?;
+//!     if n == 0 { break; }
+//!     updater.write(&chunk[..n])?;
+//! }
+//! // FIRMWARE_VERSION: 本固件发布时清单中的版本号 (构建时写入)
+//! let desc = updater.finalize(&mut ota, &manifest, &ring, FIRMWARE_VERSION)?;
+//! log_info!("OTA: {} written, rebooting", desc.version());
+//! reboot::reboot(RebootReason::Update).await;
+//! ```
+
+use core::fmt;
+
+use sha2::{Digest, Sha256};
+
+use crate::crypto::{CryptoError, FirmwareManifest, KeyRing};
+use crate::fs::idf_nvs::crc32_le;
+use crate::fs::partition::{AppSubType, DataSubType, Partition, PartitionTable};
+use crate::fs::storage::{FlashStorage, StorageError};
+use crate::sys::app::{self, AppDesc, AppError, APP_DESC_LEN, APP_DESC_OFFSET};
+use crate::util::log::*;
+
+/// otadata 扇区大小 (每个扇区存一个选择条目)
+pub const OTADATA_SECTOR_SIZE: u32 = 0x1000;
+
+/// 选择条目长度 (`esp_ota_select_entry_t`)
+pub const OTA_ENTRY_LEN: usize = 32;
+
+/// 最多支持的 OTA 槽位数 (ota_0 .. ota_15)
+pub const MAX_OTA_SLOTS: usize = 16;
+
+/// SHA-256 摘要长度
+pub const SHA256_LEN: usize = 32;
+
+/// 镜像写入缓冲 (一个 Flash 扇区)
+const WRITE_BLOCK: usize = 4096;
+
+/// 镜像头中 "已附加摘要" 标志的偏移
+const HASH_APPENDED_OFFSET: usize = 23;
+
+// ===== 错误 =====
+
+/// OTA 错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OtaError {
+    /// 分区表中没有 otadata 分区
+    NoOtaData,
+    /// 没有可写入的 OTA 槽位
+    NoSlot,
+    /// 镜像超过槽位大小
+    TooLarge,
+    /// 镜像头或应用描述符无效
+    InvalidImage,
+    /// 镜像属于其他项目
+    WrongProject,
+    /// 清单版本不允许覆盖运行固件，或安全版本低于运行镜像 (防回退)
+    Downgrade,
+    /// SHA-256 校验失败
+    DigestMismatch,
+    /// 固件清单签名验证失败
+    Signature(CryptoError),
+    /// 当前没有待确认的镜像
+    NotPending,
+    /// Flash 访问失败
+    Storage(StorageError),
+}
+
+impl fmt::Display for OtaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoOtaData => write!(f, "No otadata partition"),
+            Self::NoSlot => write!(f, "No OTA slot available"),
+            Self::TooLarge => write!(f, "Image larger than OTA slot"),
+            Self::InvalidImage => write!(f, "Invalid firmware image"),
+            Self::WrongProject => write!(f, "Image built for another project"),
+            Self::Downgrade => write!(f, "Image version is not allowed over running firmware"),
+            Self::DigestMismatch => write!(f, "Image SHA-256 mismatch"),
+            Self::Signature(e) => write!(f, "Manifest verification failed: {}", e),
+            Self::NotPending => write!(f, "No image pending verification"),
+            Self::Storage(e) => write!(f, "OTA flash access failed: {}", e),
+        }
+    }
+}
+
+impl From<StorageError> for OtaError {
+    fn from(e: StorageError) -> Self {
+        Self::Storage(e)
+    }
+}
+
+impl From<CryptoError> for OtaError {
+    fn from(e: CryptoError) -> Self {
+        Self::Signature(e)
+    }
+}
+
+impl From<AppError> for OtaError {
+    fn from(e: AppError) -> Self {
+        match e {
+            AppError::NoSlot => Self::NoSlot,
+            AppError::InvalidImage | AppError::InvalidDesc => Self::InvalidImage,
+            AppError::Storage(e) => Self::Storage(e),
+        }
+    }
+}
+
+// ===== otadata 条目 =====
+
+/// 镜像状态 (数值与 `esp_ota_img_states_t` 一致)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum OtaState {
+    /// 新写入，尚未启动过
+    New = 0,
+    /// 已启动，等待应用确认
+    PendingVerify = 1,
+    /// 已确认
+    Valid = 2,
+    /// 无效
+    Invalid = 3,
+    /// 确认前复位 (已回滚)
+    Aborted = 4,
+    /// 未设置 (旧版引导程序写入的条目)
+    Undefined = 0xFFFF_FFFF,
+}
+
+impl OtaState {
+    /// 从原始值转换
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            0 => Self::New,
+            1 => Self::PendingVerify,
+            2 => Self::Valid,
+            3 => Self::Invalid,
+            4 => Self::Aborted,
+            _ => Self::Undefined,
+        }
+    }
+
+    /// 引导程序是否可以选择该条目
+    pub const fn is_bootable(&self) -> bool {
+        !matches!(self, Self::Invalid | Self::Aborted)
+    }
+
+    /// 状态名称
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::New => "new",
+            Self::PendingVerify => "pending-verify",
+            Self::Valid => "valid",
+            Self::Invalid => "invalid",
+            Self::Aborted => "aborted",
+            Self::Undefined => "undefined",
+        }
+    }
+}
+
+/// otadata 选择条目
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OtaEntry {
+    /// 启动序号 (从 1 开始)
+    pub seq: u32,
+    /// 镜像状态
+    pub state: OtaState,
+}
+
+impl OtaEntry {
+    /// 创建条目
+    pub const fn new(seq: u32, state: OtaState) -> Self {
+        Self { seq, state }
+    }
+
+    /// 序号对应的槽位
+    pub const fn slot(&self, slot_count: u8) -> u8 {
+        ((self.seq - 1) % slot_count as u32) as u8
+    }
+
+    /// 解析条目 (擦除状态或 CRC 不符返回 `None`)
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        if data.len() < OTA_ENTRY_LEN {
+            return None;
+        }
+        let seq = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+        let state = u32::from_le_bytes([data[24], data[25], data[26], data[27]]);
+        let crc = u32::from_le_bytes([data[28], data[29], data[30], data[31]]);
+        if seq == 0 || seq == u32::MAX || crc != crc32_le(u32::MAX, &seq.to_le_bytes()) {
+            return None;
+        }
+        Some(Self { seq, state: OtaState::from_u32(state) })
+    }
+
+    /// 编码条目 (标签字段保持擦除状态)
+    pub fn encode(&self) -> [u8; OTA_ENTRY_LEN] {
+        let mut data = [0xFFu8; OTA_ENTRY_LEN];
+        data[0..4].copy_from_slice(&self.seq.to_le_bytes());
+        data[24..28].copy_from_slice(&(self.state as u32).to_le_bytes());
+        data[28..32].copy_from_slice(&crc32_le(u32::MAX, &self.seq.to_le_bytes()).to_le_bytes());
+        data
+    }
+}
+
+/// 选择活动条目: 可启动且序号最大的条目，序号相同时取扇区 0
+///
+/// 返回 (扇区号, 条目)
+pub fn select_active(entries: &[Option<OtaEntry>; 2]) -> Option<(usize, OtaEntry)> {
+    entries
+        .iter()
+        .enumerate()
+        .filter_map(|(sector, e)| e.filter(|e| e.state.is_bootable()).map(|e| (sector, e)))
+        .fold(None, |best: Option<(usize, OtaEntry)>, (sector, e)| match best {
+            Some((_, b)) if b.seq >= e.seq => best,
+            _ => Some((sector, e)),
+        })
+}
+
+/// 切换到指定槽位的新序号: 大于当前序号且映射到该槽位的最小值
+pub const fn next_seq(current: u32, slot: u8, slot_count: u8) -> u32 {
+    let count = slot_count as u32;
+    let base = if current == 0 { 0 } else { (current - 1) / count * count };
+    let seq = base + slot as u32 + 1;
+    if seq > current { seq } else { seq + count }
+}
+
+// ===== 启动检查 =====
+
+/// 启动检查结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootCheck {
+    /// 运行的镜像已确认 (或不是从 OTA 槽位启动)
+    Confirmed,
+    /// 新镜像首次启动，等待 `mark_valid`
+    Trial,
+    /// 上次启动未确认，已切回指定槽位，需要重启
+    RolledBack {
+        /// 回滚目标槽位
+        to: u8,
+    },
+}
+
+// ===== otadata 管理 =====
+
+/// OTA 管理 (otadata 读写与槽位选择)
+pub struct Ota<'t> {
+    table: &'t PartitionTable,
+    total_flash_size: u32,
+    otadata: FlashStorage,
+    slot_count: u8,
+}
+
+impl<'t> Ota<'t> {
+    /// 打开分区表中的 otadata 分区
+    pub fn new(table: &'t PartitionTable, total_flash_size: u32) -> Result<Self, OtaError> {
+        let partition = table.find_data_by_subtype(DataSubType::Ota).ok_or(OtaError::NoOtaData)?;
+        if partition.size < 2 * OTADATA_SECTOR_SIZE {
+            return Err(OtaError::NoOtaData);
+        }
+        let slot_count = table.partitions().iter().filter(|p| slot_index(p).is_some()).count();
+        if slot_count == 0 {
+            return Err(OtaError::NoSlot);
+        }
+
+        let mut otadata = FlashStorage::from_partition(partition, total_flash_size);
+        otadata.init()?;
+        Ok(Self { table, total_flash_size, otadata, slot_count: slot_count.min(MAX_OTA_SLOTS) as u8 })
+    }
+
+    /// OTA 槽位数
+    pub fn slot_count(&self) -> u8 {
+        self.slot_count
+    }
+
+    /// 指定编号的 OTA 槽位
+    pub fn slot(&self, index: u8) -> Option<&'t Partition> {
+        self.table.partitions().iter().find(|p| slot_index(p) == Some(index))
+    }
+
+    /// 当前运行的 OTA 槽位 (从 factory 分区启动时为 `None`)
+    pub fn running_slot(&self) -> Option<u8> {
+        app::running_slot(self.table).and_then(slot_index)
+    }
+
+    /// 读取两个扇区的条目
+    pub fn entries(&self) -> Result<[Option<OtaEntry>; 2], OtaError> {
+        let mut entries = [None; 2];
+        for (sector, entry) in entries.iter_mut().enumerate() {
+            let mut data = [0u8; OTA_ENTRY_LEN];
+            self.otadata.read_block(sector as u32, &mut data)?;
+            *entry = OtaEntry::decode(&data);
+        }
+        Ok(entries)
+    }
+
+    /// 活动条目 (扇区号, 条目)
+    pub fn active(&self) -> Result<Option<(usize, OtaEntry)>, OtaError> {
+        Ok(select_active(&self.entries()?))
+    }
+
+    /// 下次复位将启动的 OTA 槽位
+    pub fn boot_slot(&self) -> Result<Option<u8>, OtaError> {
+        Ok(self.active()?.map(|(_, e)| e.slot(self.slot_count)))
+    }
+
+    /// 设置下次复位启动的槽位 (写入非活动扇区)
+    pub fn set_boot(&mut self, index: u8, state: OtaState) -> Result<OtaEntry, OtaError> {
+        if index >= self.slot_count || self.slot(index).is_none() {
+            return Err(OtaError::NoSlot);
+        }
+        let active = self.active()?;
+        let current = active.map_or(0, |(_, e)| e.seq);
+        let sector = active.map_or(0, |(s, _)| s ^ 1);
+        let entry = OtaEntry::new(next_seq(current, index, self.slot_count), state);
+        self.write_entry(sector, &entry)?;
+        log_info!("OTA: boot slot set to ota_{} (seq {})", index, entry.seq);
+        Ok(entry)
+    }
+
+    /// 确认运行中的镜像 (PENDING_VERIFY / NEW → VALID)
+    pub fn mark_valid(&mut self) -> Result<(), OtaError> {
+        let (sector, entry) = self.running_entry()?.ok_or(OtaError::NotPending)?;
+        match entry.state {
+            OtaState::New | OtaState::PendingVerify => {
+                self.write_entry(sector, &OtaEntry::new(entry.seq, OtaState::Valid))?;
+                log_info!("OTA: ota_{} marked valid", entry.slot(self.slot_count));
+                Ok(())
+            }
+            OtaState::Valid | OtaState::Undefined => Ok(()),
+            _ => Err(OtaError::NotPending),
+        }
+    }
+
+    /// 启动检查: 推进首次启动状态，必要时回滚
+    pub fn check_boot(&mut self) -> Result<BootCheck, OtaError> {
+        let Some((sector, entry)) = self.running_entry()? else {
+            return Ok(BootCheck::Confirmed);
+        };
+        match entry.state {
+            OtaState::New => {
+                self.write_entry(sector, &OtaEntry::new(entry.seq, OtaState::PendingVerify))?;
+                log_info!("OTA: first boot of ota_{}, pending verification", entry.slot(self.slot_count));
+                Ok(BootCheck::Trial)
+            }
+            OtaState::PendingVerify => self.rollback().map(|to| BootCheck::RolledBack { to }),
+            _ => Ok(BootCheck::Confirmed),
+        }
+    }
+
+    /// 放弃运行中的镜像，切回另一个带有效镜像的槽位
+    ///
+    /// 返回回滚目标槽位，调用者随后应重启
+    pub fn rollback(&mut self) -> Result<u8, OtaError> {
+        let running = self.running_slot().ok_or(OtaError::NoSlot)?;
+        let target = (1..self.slot_count)
+            .map(|i| (running + self.slot_count - i) % self.slot_count)
+            .find(|&i| self.slot(i).is_some_and(|p| app::read_slot(p, self.total_flash_size).is_ok()))
+            .ok_or(OtaError::NoSlot)?;
+
+        // 先切换启动槽位，再标记放弃，中途掉电最多保留待验证状态
+        let (sector, entry) = self.running_entry()?.ok_or(OtaError::NotPending)?;
+        self.set_boot(target, OtaState::Valid)?;
+        self.write_entry(sector, &OtaEntry::new(entry.seq, OtaState::Aborted))?;
+        log_warn!("OTA: ota_{} not confirmed, rolled back to ota_{}", running, target);
+        Ok(target)
+    }
+
+    /// 开始向备用槽位写入新镜像
+    pub fn begin(&self) -> Result<OtaUpdater, OtaError> {
+        let partition = app::inactive_slot(self.table).ok_or(OtaError::NoSlot)?;
+        let slot = slot_index(partition).ok_or(OtaError::NoSlot)?;
+        let mut storage = FlashStorage::from_partition(partition, self.total_flash_size);
+        storage.init()?;
+        log_info!("OTA: writing ota_{} ({} bytes)", slot, partition.size);
+        Ok(OtaUpdater::new(slot, partition.size, storage))
+    }
+
+    /// 指向运行槽位的活动条目
+    fn running_entry(&self) -> Result<Option<(usize, OtaEntry)>, OtaError> {
+        let Some(running) = self.running_slot() else {
+            return Ok(None);
+        };
+        Ok(self.active()?.filter(|(_, e)| e.slot(self.slot_count) == running))
+    }
+
+    fn write_entry(&mut self, sector: usize, entry: &OtaEntry) -> Result<(), OtaError> {
+        self.otadata.erase_block(sector as u32)?;
+        self.otadata.write_block(sector as u32, &entry.encode())?;
+        Ok(())
+    }
+}
+
+/// 分区的 OTA 槽位编号
+fn slot_index(partition: &Partition) -> Option<u8> {
+    match partition.app_subtype() {
+        Some(AppSubType::Ota(n)) if partition.is_app() => Some(n),
+        _ => None,
+    }
+}
+
+// ===== 摘要 =====
+
+/// 镜像摘要: 同时计算整体摘要与去掉末尾 32 字节的摘要
+///
+/// ESP 镜像开启 `hash_appended` 时，末尾 32 字节是前面所有内容的 SHA-256。
+/// 流式写入时无法提前知道哪部分是末尾，因此始终保留最后 32 字节，
+/// 被后续数据挤出时才计入 `body`。
+#[derive(Clone)]
+pub struct ImageDigest {
+    body: Sha256,
+    whole: Sha256,
+    tail: [u8; SHA256_LEN],
+    tail_len: usize,
+}
+
+impl ImageDigest {
+    /// 创建摘要
+    pub fn new() -> Self {
+        Self { body: Sha256::new(), whole: Sha256::new(), tail: [0; SHA256_LEN], tail_len: 0 }
+    }
+
+    /// 追加数据
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.whole.update(data);
+
+        let total = self.tail_len + data.len();
+        if total > SHA256_LEN {
+            let evict = total - SHA256_LEN;
+            let from_tail = evict.min(self.tail_len);
+            self.body.update(&self.tail[..from_tail]);
+            self.tail.copy_within(from_tail..self.tail_len, 0);
+            self.tail_len -= from_tail;
+
+            let from_data = evict - from_tail;
+            self.body.update(&data[..from_data]);
+            data = &data[from_data..];
+        }
+        self.tail[self.tail_len..self.tail_len + data.len()].copy_from_slice(data);
+        self.tail_len += data.len();
+    }
+
+    /// 末尾附加的摘要是否与前面内容一致
+    pub fn appended_matches(&self) -> bool {
+        self.tail_len == SHA256_LEN && self.body.clone().finalize().as_slice() == self.tail
+    }
+
+    /// 全部内容的摘要
+    pub fn finalize(self) -> [u8; SHA256_LEN] {
+        self.whole.finalize().into()
+    }
+}
+
+impl Default for ImageDigest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ===== 镜像写入 =====
+
+/// 镜像写入器 (由 `Ota::begin` 创建)
+pub struct OtaUpdater {
+    slot: u8,
+    capacity: u32,
+    storage: FlashStorage,
+    buffer: [u8; WRITE_BLOCK],
+    fill: usize,
+    block: u32,
+    written: u32,
+    header: [u8; APP_DESC_OFFSET + APP_DESC_LEN],
+    digest: ImageDigest,
+}
+
+impl OtaUpdater {
+    fn new(slot: u8, capacity: u32, storage: FlashStorage) -> Self {
+        Self {
+            slot,
+            capacity,
+            storage,
+            buffer: [0xFF; WRITE_BLOCK],
+            fill: 0,
+            block: 0,
+            written: 0,
+            header: [0; APP_DESC_OFFSET + APP_DESC_LEN],
+            digest: ImageDigest::new(),
+        }
+    }
+
+    /// 目标槽位
+    pub fn slot(&self) -> u8 {
+        self.slot
+    }
+
+    /// 已接收字节数
+    pub fn written(&self) -> u32 {
+        self.written
+    }
+
+    /// 写入镜像数据 (任意分片)
+    pub fn write(&mut self, mut data: &[u8]) -> Result<(), OtaError> {
+        if self.written as u64 + data.len() as u64 > self.capacity as u64 {
+            return Err(OtaError::TooLarge);
+        }
+
+        let at = self.written as usize;
+        if at < self.header.len() {
+            let n = data.len().min(self.header.len() - at);
+            self.header[at..at + n].copy_from_slice(&data[..n]);
+            // 首块数据到齐即检查镜像头，尽早拒绝错误文件
+            if at + n == self.header.len() {
+                app::check_image_header(&self.header)?;
+            }
+        }
+        self.digest.update(data);
+        self.written += data.len() as u32;
+
+        while !data.is_empty() {
+            let n = data.len().min(WRITE_BLOCK - self.fill);
+            self.buffer[self.fill..self.fill + n].copy_from_slice(&data[..n]);
+            self.fill += n;
+            data = &data[n..];
+            if self.fill == WRITE_BLOCK {
+                self.flush_block()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 结束写入: 校验镜像并设置为下次启动槽位
+    ///
+    /// `manifest` 须由 `ring` 中的密钥签名，其镜像大小与 SHA-256 与写入的内容一致，
+    /// 且允许从 `running_version` (运行固件的清单版本号) 升级；
+    /// 任一校验失败都不会修改 otadata。成功后返回新镜像的描述符。
+    pub fn finalize<const N: usize>(
+        mut self,
+        ota: &mut Ota<'_>,
+        manifest: &FirmwareManifest,
+        ring: &KeyRing<N>,
+        running_version: u32,
+    ) -> Result<AppDesc, OtaError> {
+        check_manifest(manifest, ring, self.written, running_version)?;
+        if (self.written as usize) < self.header.len() {
+            return Err(OtaError::InvalidImage);
+        }
+        if self.fill > 0 {
+            self.flush_block()?;
+        }
+
+        app::check_image_header(&self.header)?;
+        let desc = AppDesc::from_bytes(&self.header[APP_DESC_OFFSET..])?;
+        let running = app::running();
+        if desc.project_name() != running.project_name() {
+            return Err(OtaError::WrongProject);
+        }
+        if desc.secure_version() < running.secure_version() {
+            return Err(OtaError::Downgrade);
+        }
+
+        let hash_appended = self.header[HASH_APPENDED_OFFSET] == 1;
+        if hash_appended && !self.digest.appended_matches() {
+            return Err(OtaError::DigestMismatch);
+        }
+        if manifest.verify_digest(&self.digest.finalize()).is_err() {
+            return Err(OtaError::DigestMismatch);
+        }
+
+        // 写回后再读一次描述符，确认 Flash 内容
+        let partition = ota.slot(self.slot).ok_or(OtaError::NoSlot)?;
+        if app::read_slot(partition, ota.total_flash_size)? != desc {
+            return Err(OtaError::InvalidImage);
+        }
+
+        ota.set_boot(self.slot, OtaState::New)?;
+        log_info!("OTA: ota_{} verified ({} bytes, version {})", self.slot, self.written, desc.version());
+        Ok(desc)
+    }
+
+    /// 放弃写入 (已写入的内容保留，但不会被选为启动槽位)
+    pub fn abort(self) {
+        log_warn!("OTA: update of ota_{} aborted after {} bytes", self.slot, self.written);
+    }
+
+    fn flush_block(&mut self) -> Result<(), OtaError> {
+        self.storage.erase_block(self.block)?;
+        self.storage.write_block(self.block, &self.buffer[..self.fill])?;
+        self.block += 1;
+        self.fill = 0;
+        self.buffer.fill(0xFF);
+        Ok(())
+    }
+}
+
+/// 校验清单签名、镜像大小与版本 (防回滚)
+fn check_manifest<const N: usize>(
+    manifest: &FirmwareManifest,
+    ring: &KeyRing<N>,
+    written: u32,
+    running_version: u32,
+) -> Result<(), OtaError> {
+    manifest.verify(ring)?;
+    if manifest.image_size != written {
+        return Err(OtaError::InvalidImage);
+    }
+    if !manifest.allows_upgrade_from(running_version) {
+        return Err(OtaError::Downgrade);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_encoding() {
+        let entry = OtaEntry::new(1, OtaState::New);
+        let data = entry.encode();
+        assert_eq!(&data[28..32], &0x4743_989Au32.to_le_bytes());
+        assert_eq!(OtaEntry::decode(&data), Some(entry));
+        assert_eq!(OtaEntry::decode(&[0xFF; OTA_ENTRY_LEN]), None);
+
+        let mut corrupt = OtaEntry::new(2, OtaState::Valid).encode();
+        assert_eq!(&corrupt[28..32], &0x55F6_3774u32.to_le_bytes());
+        corrupt[0] = 3;
+        assert_eq!(OtaEntry::decode(&corrupt), None);
+    }
+
+    #[test]
+    fn test_slot_selection() {
+        let a = OtaEntry::new(3, OtaState::Valid);
+        let b = OtaEntry::new(4, OtaState::PendingVerify);
+        assert_eq!(select_active(&[Some(a), Some(b)]), Some((1, b)));
+        assert_eq!(select_active(&[Some(a), Some(OtaEntry::new(4, OtaState::Aborted))]), Some((0, a)));
+        assert_eq!(select_active(&[Some(a), Some(a)]), Some((0, a)));
+        assert_eq!(select_active(&[None, None]), None);
+
+        assert_eq!(next_seq(0, 0, 2), 1);
+        assert_eq!(next_seq(0, 1, 2), 2);
+        assert_eq!(next_seq(1, 1, 2), 2);
+        assert_eq!(next_seq(2, 0, 2), 3);
+        assert_eq!(next_seq(3, 0, 2), 5);
+        assert_eq!(OtaEntry::new(next_seq(7, 1, 2), OtaState::New).slot(2), 1);
+    }
+
+    #[test]
+    fn test_appended_digest() {
+        let body = [0x5Au8; 5000];
+        let hash: [u8; 32] = Sha256::digest(body).into();
+
+        let mut digest = ImageDigest::new();
+        for chunk in body.chunks(7).chain(hash.chunks(5)) {
+            digest.update(chunk);
+        }
+        assert!(digest.appended_matches());
+
+        let mut image = [0u8; 5032];
+        image[..5000].copy_from_slice(&body);
+        image[5000..].copy_from_slice(&hash);
+        image[10] ^= 1;
+        let mut digest = ImageDigest::new();
+        digest.update(&image);
+        assert!(!digest.appended_matches());
+        assert_eq!(digest.finalize(), <[u8; 32]>::from(Sha256::digest(image)));
+    }
+
+    #[test]
+    fn test_manifest_rejects_downgrade() {
+        use crate::crypto::manifest::{MANIFEST_LEN, MANIFEST_SIGNED_LEN};
+        use crate::crypto::{sha256, SignatureAlgorithm, TrustedKey};
+        use p256::ecdsa::{signature::Signer, Signature, SigningKey};
+
+        let signer = SigningKey::from_slice(&[0x11; 32]).unwrap();
+        let public = signer.verifying_key().to_encoded_point(false);
+        let mut ring: KeyRing<1> = KeyRing::new();
+        ring.add(TrustedKey::p256(3, public.as_bytes()).unwrap()).unwrap();
+
+        let image = [0xA5u8; 100];
+        let signed = |version: u32, min_version: u32| {
+            let mut data = [0u8; MANIFEST_LEN];
+            data[0..4].copy_from_slice(b"RTMF");
+            data[4..6].copy_from_slice(&1u16.to_le_bytes());
+            data[6] = 3;
+            data[7] = SignatureAlgorithm::EcdsaP256.as_u8();
+            data[8..12].copy_from_slice(&version.to_le_bytes());
+            data[12..16].copy_from_slice(&(image.len() as u32).to_le_bytes());
+            data[16..48].copy_from_slice(&sha256(&image));
+            data[48..52].copy_from_slice(&min_version.to_le_bytes());
+            let signature: Signature = signer.sign(&data[..MANIFEST_SIGNED_LEN]);
+            data[MANIFEST_SIGNED_LEN..].copy_from_slice(&signature.to_bytes());
+            FirmwareManifest::parse(&data).unwrap()
+        };
+
+        // 运行版本 5: 新版本通过，签名有效的旧清单或同版本重放被拒绝
+        assert_eq!(check_manifest(&signed(6, 2), &ring, 100, 5), Ok(()));
+        assert_eq!(check_manifest(&signed(4, 2), &ring, 100, 5), Err(OtaError::Downgrade));
+        assert_eq!(check_manifest(&signed(5, 2), &ring, 100, 5), Err(OtaError::Downgrade));
+        assert_eq!(check_manifest(&signed(9, 7), &ring, 100, 5), Err(OtaError::Downgrade));
+        assert_eq!(check_manifest(&signed(6, 2), &ring, 99, 5), Err(OtaError::InvalidImage));
+    }
+}
@@ -0,0 +1,372 @@
+//! 有序关机与重启
+//!
+//! `reboot(reason)` 不直接复位芯片，而是:
+//! 1. 发布 `SystemEvent::ShutdownRequested` 并通知所有已登记的参与者
+//! 2. 等待参与者完成收尾 (刷写文件、关闭连接、保存计数器)，最长等待宽限期
+//! 3. 关闭已登记的驱动 (`power::lifecycle`，停止 DMA、总线离线、射频断开)
+//! 4. 把重启原因写入 RTC 保留内存，然后软件复位
+//!
+//! 下次启动时 `take_last_reboot()` 读出上次的原因，供诊断与遥测上报；
+//! 读不到记录说明是上电、看门狗或异常复位。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::sys::reboot::{self, RebootReason, SHUTDOWN};
+//!
+//! // 数据记录任务: 收到关机通知后刷写文件
+//! let mut shutdown = SHUTDOWN.participant().unwrap();
+//! loop {
+//!     match select(sample.next(), shutdown.wait()).await {
+//!         Either::First(s) => logger.append(s)?,
+//!         Either::Second(_) => {
+//!             logger.flush()?;
+//!             shutdown.ready();
+//!             return;
+//!         }
+//!     }
+//! }
+//!
+//! // OTA 完成
+//! reboot::reboot(RebootReason::Update).await;
+//!
+//! // 启动时
+//! if let Some(last) = reboot::take_last_reboot() {
+//!     log_info!("last reboot: {}", last.reason.name());
+//! }
+//! ```
+
+use core::cell::RefCell;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_sync::watch::Receiver;
+use embassy_time::{with_timeout, Duration, Instant};
+
+use crate::power::lifecycle;
+use crate::sync::bus::{self, SystemEvent};
+use crate::sync::primitives::{CriticalSignal, CriticalWatch};
+use crate::util::log::*;
+
+/// 默认宽限期
+pub const DEFAULT_GRACE: Duration = Duration::from_secs(3);
+
+/// 最大参与者数量
+pub const MAX_PARTICIPANTS: usize = 8;
+
+/// 重启记录魔数 ("RBT1")
+const RECORD_MAGIC: u32 = 0x5254_4231;
+
+// ===== 重启原因 =====
+
+/// 重启原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RebootReason {
+    /// 用户请求 (Shell、管理接口)
+    Requested,
+    /// 固件更新完成
+    Update,
+    /// 配置变更需要重启生效
+    ConfigChange,
+    /// 监管恢复 (如链路多次恢复失败)
+    Recovery,
+    /// 检测到不可恢复的故障
+    Fault,
+    /// 恢复出厂设置
+    FactoryReset,
+}
+
+impl RebootReason {
+    /// 原因名称
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Requested => "requested",
+            Self::Update => "update",
+            Self::ConfigChange => "config",
+            Self::Recovery => "recovery",
+            Self::Fault => "fault",
+            Self::FactoryReset => "factory-reset",
+        }
+    }
+
+    /// 持久化编码
+    pub const fn code(&self) -> u8 {
+        match self {
+            Self::Requested => 1,
+            Self::Update => 2,
+            Self::ConfigChange => 3,
+            Self::Recovery => 4,
+            Self::Fault => 5,
+            Self::FactoryReset => 6,
+        }
+    }
+
+    /// 从编码解析
+    pub const fn from_code(code: u8) -> Option<Self> {
+        match code {
+            1 => Some(Self::Requested),
+            2 => Some(Self::Update),
+            3 => Some(Self::ConfigChange),
+            4 => Some(Self::Recovery),
+            5 => Some(Self::Fault),
+            6 => Some(Self::FactoryReset),
+            _ => None,
+        }
+    }
+}
+
+// ===== 关机协调 =====
+
+/// 关机结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShutdownSummary {
+    /// 登记的参与者数量
+    pub participants: u8,
+    /// 宽限期结束时仍未完成的参与者数量
+    pub pending: u8,
+    /// 实际等待时长
+    pub waited: Duration,
+}
+
+struct State {
+    /// 已登记的参与者
+    participants: u8,
+    /// 尚未完成收尾的参与者 (关机开始后有效)
+    pending: u8,
+    /// 关机已开始
+    requested: Option<RebootReason>,
+}
+
+/// 关机协调器
+pub struct Shutdown {
+    state: BlockingMutex<CriticalSectionRawMutex, RefCell<State>>,
+    notify: CriticalWatch<RebootReason, MAX_PARTICIPANTS>,
+    all_ready: CriticalSignal<()>,
+}
+
+/// 全局关机协调器
+pub static SHUTDOWN: Shutdown = Shutdown::new();
+
+impl Shutdown {
+    /// 创建
+    pub const fn new() -> Self {
+        Self {
+            state: BlockingMutex::new(RefCell::new(State { participants: 0, pending: 0, requested: None })),
+            notify: CriticalWatch::new(),
+            all_ready: CriticalSignal::new(),
+        }
+    }
+
+    /// 登记参与者
+    ///
+    /// # 返回
+    /// 参与者已满或关机已开始时返回 `None`
+    pub fn participant(&'static self) -> Option<Participant> {
+        let receiver = self.notify.receiver()?;
+        self.state.lock(|state| {
+            let mut state = state.borrow_mut();
+            if state.requested.is_some() {
+                return None;
+            }
+            state.participants += 1;
+            Some(Participant { shutdown: self, receiver })
+        })
+    }
+
+    /// 是否已开始关机
+    pub fn is_requested(&self) -> bool {
+        self.state.lock(|state| state.borrow().requested.is_some())
+    }
+
+    /// 开始关机并等待参与者完成 (最长 `grace`)
+    ///
+    /// 重复调用时后来者只等待，不会再次通知
+    pub async fn request(&self, reason: RebootReason, grace: Duration) -> ShutdownSummary {
+        let start = Instant::now();
+        let (first, participants) = self.state.lock(|state| {
+            let mut state = state.borrow_mut();
+            let first = state.requested.is_none();
+            if first {
+                state.requested = Some(reason);
+                state.pending = state.participants;
+            }
+            (first, state.participants)
+        });
+
+        if first {
+            log_warn!("shutdown: {} ({} participants)", reason.name(), participants);
+            bus::publish(SystemEvent::ShutdownRequested {
+                reason: reason.name(),
+                grace_ms: grace.as_millis().min(u32::MAX as u64) as u32,
+            });
+            self.notify.sender().send(reason);
+        }
+
+        if self.pending() > 0 && with_timeout(grace, self.all_ready.wait()).await.is_err() {
+            log_warn!("shutdown: {} participant(s) did not finish in time", self.pending());
+        }
+        ShutdownSummary { participants, pending: self.pending(), waited: start.elapsed() }
+    }
+
+    /// 尚未完成收尾的参与者数量
+    pub fn pending(&self) -> u8 {
+        self.state.lock(|state| state.borrow().pending)
+    }
+
+    /// 参与者退出 (关机期间退出即视为收尾完成)
+    fn leave(&self) {
+        self.state.lock(|state| {
+            let mut state = state.borrow_mut();
+            state.participants -= 1;
+            if state.requested.is_some() && state.pending > 0 {
+                state.pending = state.pending.saturating_sub(1);
+                if state.pending == 0 {
+                    self.all_ready.signal(());
+                }
+            }
+        });
+    }
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 关机参与者
+///
+/// 收到通知后完成收尾并调用 `ready`；被 drop 也视为已完成
+pub struct Participant {
+    shutdown: &'static Shutdown,
+    receiver: Receiver<'static, CriticalSectionRawMutex, RebootReason, MAX_PARTICIPANTS>,
+}
+
+impl Participant {
+    /// 等待关机通知
+    pub async fn wait(&mut self) -> RebootReason {
+        self.receiver.changed().await
+    }
+
+    /// 是否已收到关机通知 (不等待)
+    pub fn is_requested(&self) -> bool {
+        self.shutdown.is_requested()
+    }
+
+    /// 收尾完成
+    pub fn ready(self) {}
+}
+
+impl Drop for Participant {
+    fn drop(&mut self) {
+        self.shutdown.leave();
+    }
+}
+
+// ===== 重启记录 =====
+
+/// 上次重启记录
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RebootRecord {
+    /// 原因
+    pub reason: RebootReason,
+    /// 关机时未按时完成的参与者与驱动数量
+    pub pending: u8,
+    /// 连续有序重启次数 (中间出现上电或异常复位时从 1 重新计数)
+    pub count: u32,
+}
+
+impl RebootRecord {
+    /// 编码为保留内存中的字
+    fn encode(&self) -> [u32; 3] {
+        [RECORD_MAGIC, (self.pending as u32) << 8 | self.reason.code() as u32, self.count]
+    }
+
+    /// 从保留内存解析 (上电后内容随机，以魔数判断)
+    fn decode(raw: [u32; 3]) -> Option<Self> {
+        if raw[0] != RECORD_MAGIC {
+            return None;
+        }
+        Some(Self {
+            reason: RebootReason::from_code(raw[1] as u8)?,
+            pending: (raw[1] >> 8) as u8,
+            count: raw[2],
+        })
+    }
+}
+
+/// 重启记录 (RTC 快速内存，软件复位后保留，上电后内容随机)
+#[esp_hal::ram(unstable(rtc_fast, persistent))]
+static mut RECORD: [u32; 3] = [0; 3];
+
+/// 本次启动读出的记录 (用于累计连续重启次数)
+static LAST: BlockingMutex<CriticalSectionRawMutex, RefCell<Option<RebootRecord>>> =
+    BlockingMutex::new(RefCell::new(None));
+
+/// 读取并清除上次重启记录 (启动时调用一次)
+///
+/// 返回 `None` 表示上次是上电、看门狗或异常复位
+pub fn take_last_reboot() -> Option<RebootRecord> {
+    // Safety: 启动阶段单线程访问；写入只发生在复位前
+    let raw = unsafe { core::ptr::addr_of_mut!(RECORD).replace([0; 3]) };
+    let record = RebootRecord::decode(raw);
+    LAST.lock(|last| *last.borrow_mut() = record);
+    record
+}
+
+/// 上次重启记录 (`take_last_reboot` 之后可重复查询)
+pub fn last_reboot() -> Option<RebootRecord> {
+    LAST.lock(|last| *last.borrow())
+}
+
+// ===== 重启 =====
+
+/// 有序重启 (默认宽限期)
+pub async fn reboot(reason: RebootReason) -> ! {
+    reboot_with_grace(reason, DEFAULT_GRACE).await
+}
+
+/// 有序重启: 通知参与者、等待收尾 (最长 `grace`)、关闭驱动、记录原因后复位
+pub async fn reboot_with_grace(reason: RebootReason, grace: Duration) -> ! {
+    let summary = SHUTDOWN.request(reason, grace).await;
+    let drivers = lifecycle::LIFECYCLE.shutdown(lifecycle::DEFAULT_TIMEOUT).await;
+    let record = RebootRecord {
+        reason,
+        pending: summary.pending.saturating_add(drivers.pending),
+        count: last_reboot().map_or(1, |r| r.count.saturating_add(1)),
+    };
+    log_info!("rebooting: {} after {}", reason.name(), crate::util::fmt::Elapsed(summary.waited));
+
+    // Safety: 复位前的最后一次写入
+    unsafe { core::ptr::addr_of_mut!(RECORD).write(record.encode()) };
+    esp_hal::system::software_reset()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_roundtrip() {
+        let record = RebootRecord { reason: RebootReason::Update, pending: 2, count: 7 };
+        assert_eq!(RebootRecord::decode(record.encode()), Some(record));
+        assert_eq!(RebootRecord::decode([0xDEAD_BEEF, 1, 1]), None);
+        assert_eq!(RebootRecord::decode([RECORD_MAGIC, 0x99, 1]), None);
+    }
+
+    #[test]
+    fn test_shutdown_accounting() {
+        static SD: Shutdown = Shutdown::new();
+        let a = SD.participant().unwrap();
+        let b = SD.participant().unwrap();
+        drop(b);
+
+        let summary = embassy_futures::block_on(async {
+            let request = SD.request(RebootReason::Requested, Duration::from_secs(1));
+            let finish = async { a.ready() };
+            embassy_futures::join::join(request, finish).await.0
+        });
+        assert_eq!((summary.participants, summary.pending), (1, 0));
+        assert!(SD.participant().is_none());
+    }
+}
@@ -0,0 +1,650 @@
+//! 外部实时时钟 (DS3231 / PCF8563)
+//!
+//! 很多板子给 S3 配一颗带纽扣电池的 I2C RTC，断电后仍能保持日历。本模块:
+//! - `ExternalRtc`: 统一接口 (读/写 `DateTime`)，内置 `Ds3231`、`Pcf8563` 驱动
+//!   (基于 `embedded_hal_async::i2c::I2c`，振荡器停止 / 低电压标志置位时读数视为无效)
+//! - `RtcSync`: 与时间服务 (`sys::time::TIME`) 集成
+//!   - `restore`: 启动时从 RTC 读取日历，以 `TimeSource::Rtc` 设置墙上时钟
+//!   - `write_back`: SNTP 同步后在整秒边界写回 RTC，写回前测量 RTC 偏差
+//!   - `run`: 后台任务，每次网络授时后自动写回 (限制最小间隔)
+//! - 漂移统计: 每次写回前 RTC 相对网络时间的偏差 (ms) 与换算的漂移率 (ppb)，
+//!   全局可查 (`stats`)，用于判断晶振质量或电池是否失效
+//!
+//! RTC 只有秒级分辨率。测量偏差时轮询等待 RTC 秒进位，把进位时刻与墙上时钟比较，
+//! 精度约为轮询间隔 (`EDGE_POLL`)。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::sys::rtc::{self, Ds3231, RtcSync};
+//!
+//! #[embassy_executor::task]
+//! async fn rtc_task(i2c: I2c<'static, Async>) {
+//!     let mut sync = RtcSync::new(Ds3231::new(i2c));
+//!     match sync.restore().await {
+//!         Ok(t) => log_info!("clock restored from rtc: {}", t),
+//!         Err(e) => log_warn!("rtc unusable: {}", e),
+//!     }
+//!     sync.run().await
+//! }
+//!
+//! // Shell / 遥测
+//! log_info!("rtc: {}", rtc::stats());
+//! ```
+
+use core::cell::Cell;
+use core::fmt;
+
+use embassy_futures::select::select;
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_time::{Duration, Instant, Timer};
+use embedded_hal_async::i2c::I2c;
+
+use crate::sys::time::{TimeSource, WallTime, TIME};
+use crate::util::log::*;
+
+/// 等待秒进位时的轮询间隔
+pub const EDGE_POLL: Duration = Duration::from_millis(5);
+
+/// 两次自动写回的默认最小间隔
+pub const DEFAULT_WRITE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// `run` 检查新授时的间隔 (小幅校正不会通知观察者)
+const SYNC_POLL: Duration = Duration::from_secs(60);
+
+/// RTC 支持的最早年份
+pub const MIN_YEAR: u16 = 2000;
+
+/// RTC 支持的最晚年份 (两位 BCD 年 + 世纪位)
+pub const MAX_YEAR: u16 = 2199;
+
+const SECS_PER_DAY: u64 = 86_400;
+
+// ===== 错误 =====
+
+/// RTC 错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RtcError {
+    /// I2C 传输失败
+    Bus,
+    /// 振荡器曾停止或电池电压过低，时间不可信
+    Invalid,
+    /// 寄存器内容不是合法日期
+    Corrupt,
+    /// 时间超出 RTC 可表示范围
+    OutOfRange,
+    /// 墙上时钟尚未同步
+    NotSynced,
+    /// 等待秒进位超时
+    Stalled,
+}
+
+impl fmt::Display for RtcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bus => write!(f, "RTC bus error"),
+            Self::Invalid => write!(f, "RTC time lost (oscillator stopped or low battery)"),
+            Self::Corrupt => write!(f, "RTC registers hold an invalid date"),
+            Self::OutOfRange => write!(f, "Time outside RTC range"),
+            Self::NotSynced => write!(f, "Wall clock not synced"),
+            Self::Stalled => write!(f, "RTC seconds not advancing"),
+        }
+    }
+}
+
+// ===== 日历 =====
+
+/// 日历时间 (UTC)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DateTime {
+    /// 年
+    pub year: u16,
+    /// 月 (1-12)
+    pub month: u8,
+    /// 日 (1-31)
+    pub day: u8,
+    /// 时 (0-23)
+    pub hour: u8,
+    /// 分 (0-59)
+    pub minute: u8,
+    /// 秒 (0-59)
+    pub second: u8,
+}
+
+impl DateTime {
+    /// 从 Unix 秒数转换
+    pub const fn from_unix_secs(secs: u64) -> Self {
+        let days = (secs / SECS_PER_DAY) as i64;
+        let rem = (secs % SECS_PER_DAY) as u32;
+        // civil_from_days (H. Hinnant)
+        let z = days + 719_468;
+        let era = z / 146_097;
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+        let year = (yoe + era * 400 + if month <= 2 { 1 } else { 0 }) as u16;
+        Self {
+            year,
+            month,
+            day,
+            hour: (rem / 3600) as u8,
+            minute: (rem / 60 % 60) as u8,
+            second: (rem % 60) as u8,
+        }
+    }
+
+    /// Unix 秒数
+    pub const fn unix_secs(&self) -> u64 {
+        self.days() as u64 * SECS_PER_DAY + self.hour as u64 * 3600 + self.minute as u64 * 60 + self.second as u64
+    }
+
+    /// 星期 (0 = 周日)
+    pub const fn weekday(&self) -> u8 {
+        ((self.days() + 4) % 7) as u8
+    }
+
+    /// 字段是否构成合法日期 (含闰年)
+    pub const fn is_valid(&self) -> bool {
+        self.month >= 1
+            && self.month <= 12
+            && self.day >= 1
+            && self.day <= days_in_month(self.year, self.month)
+            && self.hour < 24
+            && self.minute < 60
+            && self.second < 60
+    }
+
+    /// 从墙上时间转换 (截断到秒)
+    pub const fn from_wall(wall: WallTime) -> Self {
+        Self::from_unix_secs(wall.unix_secs())
+    }
+
+    /// 转换为墙上时间
+    pub const fn to_wall(&self) -> WallTime {
+        WallTime::from_unix_secs(self.unix_secs())
+    }
+
+    /// 1970-01-01 起的天数 (days_from_civil)
+    const fn days(&self) -> i64 {
+        let y = self.year as i64 - if self.month <= 2 { 1 } else { 0 };
+        let era = y / 400;
+        let yoe = y - era * 400;
+        let m = self.month as i64;
+        let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + self.day as i64 - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146_097 + doe - 719_468
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+const fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+const fn bcd(value: u8) -> u8 {
+    ((value / 10) << 4) | (value % 10)
+}
+
+const fn from_bcd(value: u8) -> Option<u8> {
+    if value & 0x0F > 9 || value >> 4 > 9 {
+        return None;
+    }
+    Some((value >> 4) * 10 + (value & 0x0F))
+}
+
+fn decoded(value: Option<u8>) -> Result<u8, RtcError> {
+    value.ok_or(RtcError::Corrupt)
+}
+
+fn checked(time: DateTime) -> Result<DateTime, RtcError> {
+    if time.is_valid() { Ok(time) } else { Err(RtcError::Corrupt) }
+}
+
+// ===== 驱动接口 =====
+
+/// 外部 RTC 芯片
+#[allow(async_fn_in_trait)]
+pub trait ExternalRtc {
+    /// 芯片名称
+    fn name(&self) -> &'static str;
+
+    /// 读取日历 (时间丢失时返回 `RtcError::Invalid`)
+    async fn read(&mut self) -> Result<DateTime, RtcError>;
+
+    /// 写入日历并清除时间丢失标志
+    async fn write(&mut self, time: &DateTime) -> Result<(), RtcError>;
+}
+
+// ===== DS3231 =====
+
+/// DS3231 (温补晶振，±2ppm)
+pub struct Ds3231<I> {
+    i2c: I,
+}
+
+impl<I> Ds3231<I> {
+    /// I2C 地址
+    pub const ADDRESS: u8 = 0x68;
+    const REG_TIME: u8 = 0x00;
+    const REG_STATUS: u8 = 0x0F;
+    const STATUS_OSF: u8 = 0x80;
+
+    /// 创建驱动
+    pub fn new(i2c: I) -> Self {
+        Self { i2c }
+    }
+
+    /// 释放总线
+    pub fn release(self) -> I {
+        self.i2c
+    }
+
+    /// 解析时间寄存器 (0x00-0x06)
+    pub fn decode(regs: &[u8; 7]) -> Result<DateTime, RtcError> {
+        let hour = if regs[2] & 0x40 != 0 {
+            // 12 小时制: bit5 = PM
+            let h = decoded(from_bcd(regs[2] & 0x1F))? % 12;
+            if regs[2] & 0x20 != 0 { h + 12 } else { h }
+        } else {
+            decoded(from_bcd(regs[2] & 0x3F))?
+        };
+        let century = if regs[5] & 0x80 != 0 { 100 } else { 0 };
+        checked(DateTime {
+            year: MIN_YEAR + century + decoded(from_bcd(regs[6]))? as u16,
+            month: decoded(from_bcd(regs[5] & 0x1F))?,
+            day: decoded(from_bcd(regs[4] & 0x3F))?,
+            hour,
+            minute: decoded(from_bcd(regs[1] & 0x7F))?,
+            second: decoded(from_bcd(regs[0] & 0x7F))?,
+        })
+    }
+
+    /// 编码时间寄存器 (24 小时制，星期 1-7)
+    pub fn encode(time: &DateTime) -> Result<[u8; 7], RtcError> {
+        if !time.is_valid() || time.year < MIN_YEAR || time.year > MAX_YEAR {
+            return Err(RtcError::OutOfRange);
+        }
+        let offset = time.year - MIN_YEAR;
+        let century = if offset >= 100 { 0x80 } else { 0 };
+        Ok([
+            bcd(time.second),
+            bcd(time.minute),
+            bcd(time.hour),
+            time.weekday() + 1,
+            bcd(time.day),
+            bcd(time.month) | century,
+            bcd((offset % 100) as u8),
+        ])
+    }
+}
+
+impl<I: I2c> ExternalRtc for Ds3231<I> {
+    fn name(&self) -> &'static str {
+        "ds3231"
+    }
+
+    async fn read(&mut self) -> Result<DateTime, RtcError> {
+        let mut status = [0u8; 1];
+        self.i2c.write_read(Self::ADDRESS, &[Self::REG_STATUS], &mut status).await.map_err(|_| RtcError::Bus)?;
+        if status[0] & Self::STATUS_OSF != 0 {
+            return Err(RtcError::Invalid);
+        }
+        let mut regs = [0u8; 7];
+        self.i2c.write_read(Self::ADDRESS, &[Self::REG_TIME], &mut regs).await.map_err(|_| RtcError::Bus)?;
+        Self::decode(&regs)
+    }
+
+    async fn write(&mut self, time: &DateTime) -> Result<(), RtcError> {
+        let regs = Self::encode(time)?;
+        let mut frame = [0u8; 8];
+        frame[0] = Self::REG_TIME;
+        frame[1..].copy_from_slice(&regs);
+        self.i2c.write(Self::ADDRESS, &frame).await.map_err(|_| RtcError::Bus)?;
+
+        let mut status = [0u8; 1];
+        self.i2c.write_read(Self::ADDRESS, &[Self::REG_STATUS], &mut status).await.map_err(|_| RtcError::Bus)?;
+        if status[0] & Self::STATUS_OSF != 0 {
+            let cleared = status[0] & !Self::STATUS_OSF;
+            self.i2c.write(Self::ADDRESS, &[Self::REG_STATUS, cleared]).await.map_err(|_| RtcError::Bus)?;
+        }
+        Ok(())
+    }
+}
+
+// ===== PCF8563 =====
+
+/// PCF8563 (低功耗，外部 32.768kHz 晶振)
+pub struct Pcf8563<I> {
+    i2c: I,
+}
+
+impl<I> Pcf8563<I> {
+    /// I2C 地址
+    pub const ADDRESS: u8 = 0x51;
+    const REG_TIME: u8 = 0x02;
+    const SECONDS_VL: u8 = 0x80;
+
+    /// 创建驱动
+    pub fn new(i2c: I) -> Self {
+        Self { i2c }
+    }
+
+    /// 释放总线
+    pub fn release(self) -> I {
+        self.i2c
+    }
+
+    /// 解析时间寄存器 (0x02-0x08)
+    ///
+    /// 世纪位约定不统一 (Linux 驱动视其为 19xx)，这里忽略，年份固定在 2000-2099。
+    pub fn decode(regs: &[u8; 7]) -> Result<DateTime, RtcError> {
+        if regs[0] & Self::SECONDS_VL != 0 {
+            return Err(RtcError::Invalid);
+        }
+        checked(DateTime {
+            year: MIN_YEAR + decoded(from_bcd(regs[6]))? as u16,
+            month: decoded(from_bcd(regs[5] & 0x1F))?,
+            day: decoded(from_bcd(regs[3] & 0x3F))?,
+            hour: decoded(from_bcd(regs[2] & 0x3F))?,
+            minute: decoded(from_bcd(regs[1] & 0x7F))?,
+            second: decoded(from_bcd(regs[0] & 0x7F))?,
+        })
+    }
+
+    /// 编码时间寄存器 (同时清除 VL 标志)
+    pub fn encode(time: &DateTime) -> Result<[u8; 7], RtcError> {
+        if !time.is_valid() || time.year < MIN_YEAR || time.year >= MIN_YEAR + 100 {
+            return Err(RtcError::OutOfRange);
+        }
+        Ok([
+            bcd(time.second),
+            bcd(time.minute),
+            bcd(time.hour),
+            bcd(time.day),
+            time.weekday(),
+            bcd(time.month),
+            bcd((time.year - MIN_YEAR) as u8),
+        ])
+    }
+}
+
+impl<I: I2c> ExternalRtc for Pcf8563<I> {
+    fn name(&self) -> &'static str {
+        "pcf8563"
+    }
+
+    async fn read(&mut self) -> Result<DateTime, RtcError> {
+        let mut regs = [0u8; 7];
+        self.i2c.write_read(Self::ADDRESS, &[Self::REG_TIME], &mut regs).await.map_err(|_| RtcError::Bus)?;
+        Self::decode(&regs)
+    }
+
+    async fn write(&mut self, time: &DateTime) -> Result<(), RtcError> {
+        let regs = Self::encode(time)?;
+        let mut frame = [0u8; 8];
+        frame[0] = Self::REG_TIME;
+        frame[1..].copy_from_slice(&regs);
+        self.i2c.write(Self::ADDRESS, &frame).await.map_err(|_| RtcError::Bus)
+    }
+}
+
+// ===== 漂移统计 =====
+
+/// RTC 状态与漂移统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RtcStats {
+    /// 芯片名称 (未初始化时为空)
+    pub chip: &'static str,
+    /// 启动时是否成功从 RTC 恢复时间
+    pub restored: bool,
+    /// 写回次数
+    pub writes: u32,
+    /// 偏差测量次数
+    pub samples: u32,
+    /// 最近一次偏差 (ms，正数表示 RTC 走快)
+    pub last_offset_ms: i32,
+    /// 偏差绝对值最大值 (ms)
+    pub max_offset_ms: i32,
+    /// 最近一个写回周期的漂移率 (ppb)
+    pub drift_ppb: Option<i32>,
+    /// 读写失败次数
+    pub errors: u32,
+}
+
+impl fmt::Display for RtcStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} restored={} writes={} offset={}ms max={}ms errors={}",
+            if self.chip.is_empty() { "none" } else { self.chip },
+            self.restored,
+            self.writes,
+            self.last_offset_ms,
+            self.max_offset_ms,
+            self.errors
+        )?;
+        if let Some(ppb) = self.drift_ppb {
+            let sign = if ppb < 0 { "-" } else { "" };
+            write!(f, " drift={}{}.{:03}ppm", sign, ppb.unsigned_abs() / 1000, ppb.unsigned_abs() % 1000)?;
+        }
+        Ok(())
+    }
+}
+
+static STATS: BlockingMutex<CriticalSectionRawMutex, Cell<RtcStats>> = BlockingMutex::new(Cell::new(RtcStats {
+    chip: "",
+    restored: false,
+    writes: 0,
+    samples: 0,
+    last_offset_ms: 0,
+    max_offset_ms: 0,
+    drift_ppb: None,
+    errors: 0,
+}));
+
+/// 当前 RTC 统计
+pub fn stats() -> RtcStats {
+    STATS.lock(|s| s.get())
+}
+
+fn update_stats(f: impl FnOnce(&mut RtcStats)) {
+    STATS.lock(|cell| {
+        let mut s = cell.get();
+        f(&mut s);
+        cell.set(s);
+    });
+}
+
+/// 偏差在 `elapsed` 内累积所对应的漂移率 (ppb)
+pub fn drift_ppb(offset_us: i64, elapsed: Duration) -> Option<i32> {
+    let elapsed_us = elapsed.as_micros() as i64;
+    if elapsed_us == 0 {
+        return None;
+    }
+    i32::try_from(offset_us.saturating_mul(1_000_000_000) / elapsed_us).ok()
+}
+
+// ===== 与时间服务同步 =====
+
+/// RTC 与墙上时钟同步
+pub struct RtcSync<R> {
+    rtc: R,
+    min_interval: Duration,
+    /// 上次写回的单调时刻 (用于计算漂移率)
+    written_at: Option<Instant>,
+    /// 已写回的授时时刻 (避免同一次授时重复写回)
+    synced_at: Option<Instant>,
+}
+
+impl<R: ExternalRtc> RtcSync<R> {
+    /// 创建
+    pub fn new(rtc: R) -> Self {
+        let chip = rtc.name();
+        update_stats(|s| s.chip = chip);
+        Self { rtc, min_interval: DEFAULT_WRITE_INTERVAL, written_at: None, synced_at: None }
+    }
+
+    /// 设置自动写回的最小间隔
+    pub fn with_min_interval(mut self, interval: Duration) -> Self {
+        self.min_interval = interval;
+        self
+    }
+
+    /// 底层驱动
+    pub fn rtc(&mut self) -> &mut R {
+        &mut self.rtc
+    }
+
+    /// 启动时从 RTC 恢复墙上时钟 (已由其他来源同步时只读取不设置)
+    pub async fn restore(&mut self) -> Result<DateTime, RtcError> {
+        let time = self.rtc.read().await.inspect_err(|_| update_stats(|s| s.errors += 1))?;
+        if !TIME.is_synced() {
+            TIME.set(time.to_wall(), TimeSource::Rtc);
+        }
+        update_stats(|s| s.restored = true);
+        Ok(time)
+    }
+
+    /// 把墙上时钟写回 RTC
+    ///
+    /// 先测量 RTC 偏差 (RTC 时间有效时)，再在下一个整秒边界写入。
+    /// 返回写回前的偏差 (μs)，RTC 时间无效时为 `None`。
+    pub async fn write_back(&mut self) -> Result<Option<i64>, RtcError> {
+        if !TIME.is_synced() {
+            return Err(RtcError::NotSynced);
+        }
+        let offset = match self.measure_offset().await {
+            Ok(offset) => Some(offset),
+            Err(RtcError::Invalid) | Err(RtcError::Corrupt) => None,
+            Err(e) => {
+                update_stats(|s| s.errors += 1);
+                return Err(e);
+            }
+        };
+        if let Some(offset) = offset {
+            let ppb = self.written_at.and_then(|at| drift_ppb(offset, at.elapsed()));
+            let offset_ms = (offset / 1000).clamp(i32::MIN as i64, i32::MAX as i64) as i32;
+            update_stats(|s| {
+                s.samples += 1;
+                s.last_offset_ms = offset_ms;
+                s.max_offset_ms = s.max_offset_ms.max(offset_ms.saturating_abs());
+                if ppb.is_some() {
+                    s.drift_ppb = ppb;
+                }
+            });
+            log_debug!("RTC offset {} ms before write-back", offset_ms);
+        }
+
+        let now = TIME.now().ok_or(RtcError::NotSynced)?;
+        let target = WallTime::from_unix_secs(now.unix_secs() + 1);
+        TIME.sleep_until(target).await;
+        self.rtc
+            .write(&DateTime::from_wall(target))
+            .await
+            .inspect_err(|_| update_stats(|s| s.errors += 1))?;
+        self.written_at = Some(Instant::now());
+        update_stats(|s| s.writes += 1);
+        Ok(offset)
+    }
+
+    /// 后台任务: 每次网络授时后写回 RTC (间隔不小于 `min_interval`)
+    pub async fn run(&mut self) -> ! {
+        let mut watcher = TIME.watcher();
+        loop {
+            if let Some((TimeSource::Sntp, at)) = TIME.last_sync() {
+                let due = self.written_at.is_none_or(|w| w.elapsed() >= self.min_interval);
+                if due && self.synced_at != Some(at) {
+                    match self.write_back().await {
+                        Ok(_) => self.synced_at = Some(at),
+                        Err(_e) => {
+                            log_warn!("RTC write-back failed: {}", _e);
+                        }
+                    }
+                }
+            }
+            match watcher.as_mut() {
+                Some(w) => {
+                    select(w.changed(), Timer::after(SYNC_POLL)).await;
+                }
+                None => Timer::after(SYNC_POLL).await,
+            }
+        }
+    }
+
+    /// RTC 相对墙上时钟的偏差 (μs，正数表示 RTC 走快)
+    ///
+    /// 轮询到 RTC 秒进位，进位时刻 RTC 时间为整秒。
+    async fn measure_offset(&mut self) -> Result<i64, RtcError> {
+        let first = self.rtc.read().await?;
+        let deadline = Instant::now() + Duration::from_millis(1100);
+        loop {
+            Timer::after(EDGE_POLL).await;
+            let time = self.rtc.read().await?;
+            if time != first {
+                let now = TIME.now().ok_or(RtcError::NotSynced)?;
+                return Ok(time.unix_secs() as i64 * 1_000_000 - now.unix_micros() as i64);
+            }
+            if Instant::now() >= deadline {
+                return Err(RtcError::Stalled);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calendar() {
+        // 2024-02-29T12:34:56Z (周四)
+        let t = DateTime::from_unix_secs(1_709_210_096);
+        assert_eq!(t, DateTime { year: 2024, month: 2, day: 29, hour: 12, minute: 34, second: 56 });
+        assert_eq!(t.unix_secs(), 1_709_210_096);
+        assert_eq!(t.weekday(), 4);
+        assert!(!DateTime { day: 29, year: 2023, ..t }.is_valid());
+        assert_eq!(DateTime::from_unix_secs(0).weekday(), 4);
+        assert_eq!(DateTime::from_unix_secs(4_102_444_800).year, 2100);
+    }
+
+    #[test]
+    fn test_register_codecs() {
+        let t = DateTime { year: 2031, month: 12, day: 31, hour: 23, minute: 59, second: 58 };
+        let regs = Ds3231::<()>::encode(&t).unwrap();
+        assert_eq!(regs, [0x58, 0x59, 0x23, 4, 0x31, 0x12, 0x31]);
+        assert_eq!(Ds3231::<()>::decode(&regs), Ok(t));
+        // 12 小时制 11 PM
+        assert_eq!(Ds3231::<()>::decode(&[0x58, 0x59, 0x40 | 0x20 | 0x11, 4, 0x31, 0x12, 0x31]), Ok(t));
+
+        let regs = Pcf8563::<()>::encode(&t).unwrap();
+        assert_eq!(Pcf8563::<()>::decode(&regs), Ok(t));
+        assert_eq!(Pcf8563::<()>::decode(&[0x80 | regs[0], regs[1], regs[2], regs[3], regs[4], regs[5], regs[6]]), Err(RtcError::Invalid));
+        assert_eq!(Ds3231::<()>::decode(&[0x5A, 0, 0, 1, 1, 1, 0]), Err(RtcError::Corrupt));
+    }
+
+    #[test]
+    fn test_drift_rate() {
+        // 一天快 0.1728 秒 = 2ppm
+        assert_eq!(drift_ppb(172_800, Duration::from_secs(86_400)), Some(2_000));
+        assert_eq!(drift_ppb(-864, Duration::from_secs(86_400)), Some(-10));
+        assert_eq!(drift_ppb(1, Duration::from_secs(0)), None);
+    }
+}
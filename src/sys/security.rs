@@ -0,0 +1,268 @@
+//! 安全与生产锁定
+//!
+//! 提供以下功能:
+//! - 检测 JTAG/OCD 调试器是否已连接
+//! - 读取 eFuse 安全状态 (安全启动、Flash 加密、下载模式)
+//! - 生产构建中运行时禁用 USB-Serial-JTAG
+//! - 篡改标志: 一旦检测到调试器，拒绝暴露 Shell / GDB stub 等调试接口
+//!
+//! # 生产构建
+//!
+//! 启用 `production` feature 后，`SecurityPolicy::default()` 返回生产策略:
+//! 启动时禁用 USB-JTAG，检测到调试器即置位篡改标志。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::sys::security::{self, SecurityPolicy};
+//!
+//! let report = security::enforce(&SecurityPolicy::default());
+//! if security::debug_interfaces_allowed() {
+//!     // 启动 Shell
+//! }
+//! ```
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use esp_hal::efuse::Efuse;
+
+/// 篡改标志 (检测到调试器后置位，直到复位)
+static TAMPER_DETECTED: AtomicBool = AtomicBool::new(false);
+
+/// 调试接口是否已被策略锁定
+static DEBUG_LOCKED: AtomicBool = AtomicBool::new(false);
+
+/// OCD 调试控制寄存器 (ERI 地址 DCRSET)
+const ERI_DCRSET: u32 = 0x0010_200C;
+
+/// DCR.EnableOCD 位
+const OCDDCR_ENABLEOCD: u32 = 1 << 0;
+
+/// 安全下载模式 / 下载模式状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadMode {
+    /// 正常下载模式 (可读写 Flash)
+    Enabled,
+    /// 安全下载模式 (仅允许有限命令)
+    Secure,
+    /// 下载模式已永久禁用
+    Disabled,
+}
+
+/// 芯片安全状态 (eFuse)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecurityStatus {
+    /// 安全启动已启用
+    pub secure_boot: bool,
+    /// Flash 加密已启用
+    pub flash_encryption: bool,
+    /// USB-Serial-JTAG 已被 eFuse 永久禁用
+    pub usb_jtag_efuse_disabled: bool,
+    /// 引脚 JTAG 已被 eFuse 禁用
+    pub pad_jtag_efuse_disabled: bool,
+    /// 下载模式状态
+    pub download_mode: DownloadMode,
+}
+
+impl SecurityStatus {
+    /// 从 eFuse 读取当前状态
+    pub fn read() -> Self {
+        let download_mode = if Efuse::read_bit(esp_hal::efuse::DIS_DOWNLOAD_MODE) {
+            DownloadMode::Disabled
+        } else if Efuse::read_bit(esp_hal::efuse::ENABLE_SECURITY_DOWNLOAD) {
+            DownloadMode::Secure
+        } else {
+            DownloadMode::Enabled
+        };
+
+        // SPI_BOOT_CRYPT_CNT 奇数个位置位表示加密启用
+        let crypt_cnt: u8 = Efuse::read_field_le(esp_hal::efuse::SPI_BOOT_CRYPT_CNT);
+
+        Self {
+            secure_boot: Efuse::read_bit(esp_hal::efuse::SECURE_BOOT_EN),
+            flash_encryption: crypt_cnt.count_ones() % 2 == 1,
+            usb_jtag_efuse_disabled: Efuse::read_bit(esp_hal::efuse::DIS_USB_JTAG),
+            pad_jtag_efuse_disabled: Efuse::read_bit(esp_hal::efuse::DIS_PAD_JTAG),
+            download_mode,
+        }
+    }
+
+    /// 芯片是否已完成生产级加固
+    pub fn is_hardened(&self) -> bool {
+        self.secure_boot
+            && self.flash_encryption
+            && self.download_mode != DownloadMode::Enabled
+    }
+}
+
+/// 安全策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecurityPolicy {
+    /// 启动时运行时禁用 USB-Serial-JTAG
+    pub disable_usb_jtag: bool,
+    /// 检测到调试器时置位篡改标志
+    pub tamper_on_debugger: bool,
+    /// 锁定 Shell / GDB stub 等调试接口
+    pub lock_debug_interfaces: bool,
+}
+
+impl SecurityPolicy {
+    /// 开发策略: 不做任何限制
+    pub const fn development() -> Self {
+        Self {
+            disable_usb_jtag: false,
+            tamper_on_debugger: false,
+            lock_debug_interfaces: false,
+        }
+    }
+
+    /// 生产策略: 禁用 JTAG，检测篡改，锁定调试接口
+    pub const fn production() -> Self {
+        Self {
+            disable_usb_jtag: true,
+            tamper_on_debugger: true,
+            lock_debug_interfaces: true,
+        }
+    }
+}
+
+impl Default for SecurityPolicy {
+    fn default() -> Self {
+        if cfg!(feature = "production") {
+            Self::production()
+        } else {
+            Self::development()
+        }
+    }
+}
+
+/// 策略执行结果
+#[derive(Debug, Clone, Copy)]
+pub struct SecurityReport {
+    /// eFuse 安全状态
+    pub status: SecurityStatus,
+    /// 执行时是否检测到调试器
+    pub debugger_attached: bool,
+    /// USB-JTAG 是否已被运行时禁用
+    pub usb_jtag_disabled: bool,
+    /// 是否已置位篡改标志
+    pub tampered: bool,
+}
+
+impl SecurityReport {
+    /// 是否允许暴露调试接口
+    pub fn allows_debug_interfaces(&self, policy: &SecurityPolicy) -> bool {
+        !self.tampered && !policy.lock_debug_interfaces
+    }
+}
+
+/// 检测调试器是否已连接
+///
+/// 读取 Xtensa OCD 的 DCR 寄存器，OpenOCD 连接时会置位 EnableOCD。
+#[inline]
+pub fn is_debugger_attached() -> bool {
+    #[cfg(target_arch = "xtensa")]
+    {
+        let dcr: u32;
+        unsafe {
+            core::arch::asm!(
+                "rer {0}, {1}",
+                out(reg) dcr,
+                in(reg) ERI_DCRSET,
+                options(nostack, preserves_flags)
+            );
+        }
+        (dcr & OCDDCR_ENABLEOCD) != 0
+    }
+    #[cfg(not(target_arch = "xtensa"))]
+    {
+        false
+    }
+}
+
+/// 运行时禁用 USB-Serial-JTAG
+///
+/// 关闭 USB 焊盘，直到下次复位。注意这同样会断开 USB 串口日志。
+pub fn disable_usb_jtag() {
+    esp_hal::peripherals::USB_DEVICE::regs()
+        .conf0()
+        .modify(|_, w| w.usb_pad_enable().clear_bit());
+}
+
+/// 执行安全策略
+///
+/// 应在启动早期、任何调试接口开启前调用一次。
+pub fn enforce(policy: &SecurityPolicy) -> SecurityReport {
+    let status = SecurityStatus::read();
+    let debugger_attached = is_debugger_attached();
+
+    if policy.tamper_on_debugger && debugger_attached {
+        TAMPER_DETECTED.store(true, Ordering::Release);
+    }
+
+    let usb_jtag_disabled = if policy.disable_usb_jtag && !status.usb_jtag_efuse_disabled {
+        disable_usb_jtag();
+        true
+    } else {
+        status.usb_jtag_efuse_disabled
+    };
+
+    DEBUG_LOCKED.store(policy.lock_debug_interfaces, Ordering::Release);
+
+    SecurityReport {
+        status,
+        debugger_attached,
+        usb_jtag_disabled,
+        tampered: TAMPER_DETECTED.load(Ordering::Acquire),
+    }
+}
+
+/// 周期性检查调试器 (可在后台任务中调用)
+///
+/// # 返回
+/// 当前是否处于篡改状态
+pub fn poll_tamper(policy: &SecurityPolicy) -> bool {
+    if policy.tamper_on_debugger && is_debugger_attached() {
+        TAMPER_DETECTED.store(true, Ordering::Release);
+    }
+    is_tampered()
+}
+
+/// 是否检测到篡改
+#[inline]
+pub fn is_tampered() -> bool {
+    TAMPER_DETECTED.load(Ordering::Acquire)
+}
+
+/// 是否允许暴露调试接口 (Shell、GDB stub)
+#[inline]
+pub fn debug_interfaces_allowed() -> bool {
+    !is_tampered() && !DEBUG_LOCKED.load(Ordering::Acquire)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_presets() {
+        let dev = SecurityPolicy::development();
+        assert!(!dev.disable_usb_jtag && !dev.lock_debug_interfaces);
+
+        let prod = SecurityPolicy::production();
+        assert!(prod.disable_usb_jtag && prod.tamper_on_debugger && prod.lock_debug_interfaces);
+    }
+
+    #[test]
+    fn test_hardened_status() {
+        let status = SecurityStatus {
+            secure_boot: true,
+            flash_encryption: true,
+            usb_jtag_efuse_disabled: false,
+            pad_jtag_efuse_disabled: false,
+            download_mode: DownloadMode::Secure,
+        };
+        assert!(status.is_hardened());
+        assert!(!SecurityStatus { download_mode: DownloadMode::Enabled, ..status }.is_hardened());
+    }
+}
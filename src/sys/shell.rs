@@ -0,0 +1,425 @@
+//! 命令行 Shell
+//!
+//! 传输无关的行编辑 + 命令分发，串口、TCP 控制台共用同一套命令表。
+//! 命令为同步函数，输出写入 `fmt::Write`，由会话循环统一发送。
+//!
+//! 调试接口被安全策略锁定 (`security::debug_interfaces_allowed`) 时拒绝会话。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::sys::shell::{Command, Shell, ShellError, SessionConfig};
+//!
+//! fn led(args: &[&str], out: &mut dyn core::fmt::Write) -> Result<(), ShellError> {
+//!     let on = matches!(args, ["on"]);
+//!     LED_CONTROL.signal(on);
+//!     writeln!(out, "led {}", if on { "on" } else { "off" }).map_err(|_| ShellError::Output)
+//! }
+//!
+//! static COMMANDS: &[Command] = &[Command::new("led", "led <on|off>", led)];
+//! static SHELL: Shell = Shell::new(COMMANDS);
+//!
+//! shell::run_session(&mut uart, &SHELL, &SessionConfig::default()).await?;
+//! ```
+
+use core::fmt::{self, Write as _};
+
+use embassy_time::{with_timeout, Duration};
+use embedded_io_async::{Read, Write};
+use heapless::{String, Vec};
+
+use super::security;
+use crate::util::fmt::{Bytes, Elapsed};
+
+/// 单行最大长度
+pub const MAX_LINE: usize = 96;
+
+/// 最多参数个数 (含命令名)
+pub const MAX_ARGS: usize = 8;
+
+/// 单条命令输出缓冲区大小
+pub const OUTPUT_LEN: usize = 1024;
+
+/// Shell 错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellError {
+    /// 未知命令
+    UnknownCommand,
+    /// 参数错误
+    InvalidArgs,
+    /// 命令执行失败
+    Failed,
+    /// 调试接口被锁定或认证失败
+    Denied,
+    /// 输出缓冲区不足
+    Output,
+    /// 会话空闲超时
+    Timeout,
+    /// 传输错误
+    Io,
+}
+
+impl fmt::Display for ShellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownCommand => write!(f, "Unknown command"),
+            Self::InvalidArgs => write!(f, "Invalid arguments"),
+            Self::Failed => write!(f, "Command failed"),
+            Self::Denied => write!(f, "Access denied"),
+            Self::Output => write!(f, "Output truncated"),
+            Self::Timeout => write!(f, "Session timed out"),
+            Self::Io => write!(f, "Transport error"),
+        }
+    }
+}
+
+impl From<fmt::Error> for ShellError {
+    fn from(_: fmt::Error) -> Self {
+        ShellError::Output
+    }
+}
+
+// ===== 命令 =====
+
+/// 命令处理函数 (参数不含命令名)
+pub type CommandFn = fn(args: &[&str], out: &mut dyn fmt::Write) -> Result<(), ShellError>;
+
+/// 命令
+pub struct Command {
+    /// 命令名
+    pub name: &'static str,
+    /// 用法说明
+    pub usage: &'static str,
+    /// 处理函数
+    pub run: CommandFn,
+}
+
+impl Command {
+    /// 创建命令
+    pub const fn new(name: &'static str, usage: &'static str, run: CommandFn) -> Self {
+        Self { name, usage, run }
+    }
+}
+
+fn cmd_version(_: &[&str], out: &mut dyn fmt::Write) -> Result<(), ShellError> {
+    writeln!(out, "{} {}", crate::NAME, crate::VERSION)?;
+    Ok(())
+}
+
+fn cmd_uptime(_: &[&str], out: &mut dyn fmt::Write) -> Result<(), ShellError> {
+    writeln!(out, "up {}", Elapsed::from_millis(embassy_time::Instant::now().as_millis()))?;
+    Ok(())
+}
+
+fn cmd_mem(_: &[&str], out: &mut dyn fmt::Write) -> Result<(), ShellError> {
+    let psram = crate::mem::psram::stats();
+    writeln!(
+        out,
+        "psram: {} used / {} total ({} free)",
+        Bytes(psram.used as u64),
+        Bytes(psram.total as u64),
+        Bytes(psram.free as u64)
+    )?;
+    for usage in crate::mem::psram::top::<8>() {
+        writeln!(out, "  {:<12} {} (peak {})", usage.tag, Bytes(usage.current as u64), Bytes(usage.peak as u64))?;
+    }
+    Ok(())
+}
+
+fn cmd_security(_: &[&str], out: &mut dyn fmt::Write) -> Result<(), ShellError> {
+    let status = security::SecurityStatus::read();
+    writeln!(out, "secure boot:      {}", status.secure_boot)?;
+    writeln!(out, "flash encryption: {}", status.flash_encryption)?;
+    writeln!(out, "tampered:         {}", security::is_tampered())?;
+    Ok(())
+}
+
+fn cmd_caps(_: &[&str], out: &mut dyn fmt::Write) -> Result<(), ShellError> {
+    write!(out, "{}", super::caps::capabilities())?;
+    Ok(())
+}
+
+fn cmd_selftest(args: &[&str], out: &mut dyn fmt::Write) -> Result<(), ShellError> {
+    use crate::test::selftest;
+    match args {
+        ["run"] => {
+            selftest::request();
+            writeln!(out, "selftest requested")?;
+        }
+        [] => selftest::with_last_report(|report| match report {
+            Some(report) => write!(out, "{}", report),
+            None => writeln!(out, "no selftest report"),
+        })?,
+        _ => return Err(ShellError::InvalidArgs),
+    }
+    Ok(())
+}
+
+/// 内置命令 (`help` 与 `exit` 由 Shell 直接处理)
+pub static BUILTINS: &[Command] = &[
+    Command::new("version", "version", cmd_version),
+    Command::new("uptime", "uptime", cmd_uptime),
+    Command::new("mem", "mem", cmd_mem),
+    Command::new("security", "security", cmd_security),
+    Command::new("caps", "caps", cmd_caps),
+    Command::new("selftest", "selftest [run]", cmd_selftest),
+];
+
+// ===== Shell =====
+
+/// 命令执行结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// 继续会话
+    Continue,
+    /// 结束会话
+    Exit,
+}
+
+/// 命令分发器
+pub struct Shell {
+    commands: &'static [Command],
+}
+
+impl Shell {
+    /// 创建 (应用命令优先于内置命令)
+    pub const fn new(commands: &'static [Command]) -> Self {
+        Self { commands }
+    }
+
+    fn find(&self, name: &str) -> Option<&'static Command> {
+        self.commands.iter().chain(BUILTINS.iter()).find(|c| c.name == name)
+    }
+
+    /// 执行一行命令
+    pub fn execute(&self, line: &str, out: &mut dyn fmt::Write) -> Result<Outcome, ShellError> {
+        let args: Vec<&str, MAX_ARGS> = line.split_whitespace().take(MAX_ARGS).collect();
+        let Some((&name, rest)) = args.split_first() else {
+            return Ok(Outcome::Continue);
+        };
+        match name {
+            "exit" | "quit" => Ok(Outcome::Exit),
+            "help" => {
+                writeln!(out, "help\r\nexit")?;
+                for c in self.commands.iter().chain(BUILTINS.iter()) {
+                    writeln!(out, "{}", c.usage)?;
+                }
+                Ok(Outcome::Continue)
+            }
+            _ => {
+                let command = self.find(name).ok_or(ShellError::UnknownCommand)?;
+                (command.run)(rest, out)?;
+                Ok(Outcome::Continue)
+            }
+        }
+    }
+}
+
+// ===== 行编辑 =====
+
+/// 行编辑事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditEvent {
+    /// 无需回显
+    None,
+    /// 回显字符
+    Echo(u8),
+    /// 删除一个字符
+    Erase,
+    /// 一行输入完成
+    Submit,
+}
+
+/// 行编辑器
+#[derive(Default)]
+pub struct LineEditor {
+    line: String<MAX_LINE>,
+    last_cr: bool,
+}
+
+impl LineEditor {
+    /// 创建
+    pub const fn new() -> Self {
+        Self { line: String::new(), last_cr: false }
+    }
+
+    /// 输入一个字节
+    pub fn push(&mut self, byte: u8) -> EditEvent {
+        let after_cr = core::mem::replace(&mut self.last_cr, byte == b'\r');
+        match byte {
+            b'\r' => EditEvent::Submit,
+            // CRLF 只提交一次
+            b'\n' if after_cr => EditEvent::None,
+            b'\n' => EditEvent::Submit,
+            0x08 | 0x7F => {
+                if self.line.pop().is_some() { EditEvent::Erase } else { EditEvent::None }
+            }
+            0x20..=0x7E => {
+                if self.line.push(byte as char).is_ok() { EditEvent::Echo(byte) } else { EditEvent::None }
+            }
+            _ => EditEvent::None,
+        }
+    }
+
+    /// 当前行
+    pub fn line(&self) -> &str {
+        &self.line
+    }
+
+    /// 清空当前行
+    pub fn clear(&mut self) {
+        self.line.clear();
+    }
+}
+
+// ===== 会话 =====
+
+/// 会话配置
+#[derive(Debug, Clone, Copy)]
+pub struct SessionConfig {
+    /// 提示符
+    pub prompt: &'static str,
+    /// 是否回显输入
+    pub echo: bool,
+    /// 空闲超时
+    pub idle_timeout: Duration,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self { prompt: "> ", echo: true, idle_timeout: Duration::from_secs(300) }
+    }
+}
+
+impl SessionConfig {
+    /// 设置提示符
+    pub fn with_prompt(mut self, prompt: &'static str) -> Self {
+        self.prompt = prompt;
+        self
+    }
+
+    /// 设置回显
+    pub fn with_echo(mut self, echo: bool) -> Self {
+        self.echo = echo;
+        self
+    }
+
+    /// 设置空闲超时
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+}
+
+/// 写出文本 (`\n` 转为 `\r\n`)
+pub async fn write_text<T: Write>(io: &mut T, text: &str) -> Result<(), ShellError> {
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            io.write_all(b"\r\n").await.map_err(|_| ShellError::Io)?;
+        }
+        io.write_all(line.trim_end_matches('\r').as_bytes()).await.map_err(|_| ShellError::Io)?;
+    }
+    Ok(())
+}
+
+/// 读取一行 (`echo` 为 false 时不回显，用于口令输入)
+pub async fn read_line<'e, T: Read + Write>(
+    io: &mut T,
+    editor: &'e mut LineEditor,
+    echo: bool,
+    timeout: Duration,
+) -> Result<&'e str, ShellError> {
+    editor.clear();
+    let mut buf = [0u8; 32];
+    loop {
+        let n = with_timeout(timeout, io.read(&mut buf))
+            .await
+            .map_err(|_| ShellError::Timeout)?
+            .map_err(|_| ShellError::Io)?;
+        if n == 0 {
+            return Err(ShellError::Io);
+        }
+        for &byte in &buf[..n] {
+            let echoed: &[u8] = match editor.push(byte) {
+                EditEvent::Submit => {
+                    io.write_all(b"\r\n").await.map_err(|_| ShellError::Io)?;
+                    io.flush().await.map_err(|_| ShellError::Io)?;
+                    return Ok(editor.line());
+                }
+                EditEvent::Echo(b) if echo => &[b],
+                EditEvent::Erase if echo => b"\x08 \x08",
+                _ => &[],
+            };
+            if !echoed.is_empty() {
+                io.write_all(echoed).await.map_err(|_| ShellError::Io)?;
+            }
+        }
+        io.flush().await.map_err(|_| ShellError::Io)?;
+    }
+}
+
+/// 运行交互会话，直到 `exit`、空闲超时或连接断开
+pub async fn run_session<T: Read + Write>(io: &mut T, shell: &Shell, config: &SessionConfig) -> Result<(), ShellError> {
+    if !security::debug_interfaces_allowed() {
+        let _ = write_text(io, "shell disabled by security policy\n").await;
+        return Err(ShellError::Denied);
+    }
+
+    let mut editor = LineEditor::new();
+    let mut output: String<OUTPUT_LEN> = String::new();
+    loop {
+        write_text(io, config.prompt).await?;
+        io.flush().await.map_err(|_| ShellError::Io)?;
+        let line = read_line(io, &mut editor, config.echo, config.idle_timeout).await?;
+
+        output.clear();
+        let outcome = match shell.execute(line, &mut output) {
+            Ok(outcome) => outcome,
+            Err(ShellError::Output) => {
+                let _ = output.push_str("\n(output truncated)\n");
+                Outcome::Continue
+            }
+            Err(e) => {
+                let _ = writeln!(output, "error: {}", e);
+                Outcome::Continue
+            }
+        };
+        write_text(io, &output).await?;
+        if outcome == Outcome::Exit {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn echo(args: &[&str], out: &mut dyn fmt::Write) -> Result<(), ShellError> {
+        writeln!(out, "{}", args.join(" "))?;
+        Ok(())
+    }
+
+    static COMMANDS: &[Command] = &[Command::new("echo", "echo <text>", echo)];
+
+    #[test]
+    fn test_execute() {
+        let shell = Shell::new(COMMANDS);
+        let mut out: String<64> = String::new();
+        assert_eq!(shell.execute("  echo a  b ", &mut out), Ok(Outcome::Continue));
+        assert_eq!(out.as_str(), "a b\n");
+        assert_eq!(shell.execute("nope", &mut out), Err(ShellError::UnknownCommand));
+        assert_eq!(shell.execute("exit", &mut out), Ok(Outcome::Exit));
+    }
+
+    #[test]
+    fn test_line_editor() {
+        let mut editor = LineEditor::new();
+        for &b in b"lx\x7fs" {
+            editor.push(b);
+        }
+        assert_eq!(editor.push(b'\r'), EditEvent::Submit);
+        assert_eq!(editor.push(b'\n'), EditEvent::None);
+        assert_eq!(editor.line(), "ls");
+    }
+}
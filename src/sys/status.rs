@@ -0,0 +1,326 @@
+//! 实时状态订阅
+//!
+//! 把显示/UI 关心的几个实时值 (IP 地址、RSSI、空闲堆、电池电量) 汇总到一个
+//! `CriticalWatch<Snapshot>`，UI 任务 `await` 变化即可，不必按各自节奏轮询各子系统:
+//! - 各子系统在采样处调用 `set_*` (重连任务写 IP，链路监管器写 RSSI，主循环写堆)
+//! - 变化合并: 与当前值差异小于 `Coalescing` 粒度的更新直接丢弃，不唤醒观察者
+//! - `Watch` 只保留最新快照，观察者来不及处理的中间值自然合并
+//! - `Fields` 标出两次快照之间变化的字段，只关心部分字段的观察者用 `changed_and` 过滤
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::sys::status::{Fields, STATUS};
+//!
+//! // 电池采样任务
+//! STATUS.set_battery(Some(gauge.percent()));
+//!
+//! // 显示任务: 只在 IP 或电量变化时重绘状态栏
+//! let mut watcher = STATUS.watcher().unwrap();
+//! let mut shown = STATUS.snapshot();
+//! loop {
+//!     let last = shown;
+//!     shown = watcher.changed_and(|s| s.changed_fields(&last).intersects(Fields::IP | Fields::BATTERY)).await;
+//!     draw_status_bar(&shown);
+//! }
+//! ```
+
+use core::ops::BitOr;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::watch::Receiver;
+
+use crate::sync::primitives::CriticalWatch;
+
+/// 同时订阅状态变化的观察者数量
+pub const STATUS_WATCHERS: usize = 4;
+
+// ===== 快照 =====
+
+/// 状态字段集合
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Fields(pub u8);
+
+impl Fields {
+    /// 空集合
+    pub const NONE: Fields = Fields(0);
+    /// IP 地址
+    pub const IP: Fields = Fields(1 << 0);
+    /// 信号强度
+    pub const RSSI: Fields = Fields(1 << 1);
+    /// 空闲堆
+    pub const HEAP_FREE: Fields = Fields(1 << 2);
+    /// 电池电量
+    pub const BATTERY: Fields = Fields(1 << 3);
+    /// 全部字段
+    pub const ALL: Fields = Fields(0x0F);
+
+    /// 是否包含全部指定字段
+    pub const fn contains(self, other: Fields) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// 是否与指定字段有交集
+    pub const fn intersects(self, other: Fields) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// 是否为空
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for Fields {
+    type Output = Fields;
+
+    fn bitor(self, rhs: Fields) -> Fields {
+        Fields(self.0 | rhs.0)
+    }
+}
+
+/// 状态快照 (尚未上报的字段为 `None`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    /// STA 的 IP 地址 (未联网为 `None`)
+    pub ip: Option<[u8; 4]>,
+    /// 当前 AP 的信号强度 (dBm，未关联为 `None`)
+    pub rssi: Option<i8>,
+    /// 空闲堆 (字节)
+    pub heap_free: Option<usize>,
+    /// 电池电量 (0-100%，无电池为 `None`)
+    pub battery: Option<u8>,
+}
+
+impl Snapshot {
+    /// 空快照
+    pub const EMPTY: Snapshot = Snapshot { ip: None, rssi: None, heap_free: None, battery: None };
+
+    /// 与 `previous` 相比变化的字段
+    pub fn changed_fields(&self, previous: &Snapshot) -> Fields {
+        let mut fields = Fields::NONE;
+        for (changed, field) in [
+            (self.ip != previous.ip, Fields::IP),
+            (self.rssi != previous.rssi, Fields::RSSI),
+            (self.heap_free != previous.heap_free, Fields::HEAP_FREE),
+            (self.battery != previous.battery, Fields::BATTERY),
+        ] {
+            if changed {
+                fields = fields | field;
+            }
+        }
+        fields
+    }
+}
+
+// ===== 变化合并 =====
+
+/// 变化合并粒度
+///
+/// 新旧值都存在且差异小于粒度时视为未变化；出现/消失 (`Some` ↔ `None`) 总是上报
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Coalescing {
+    /// RSSI 粒度 (dB)
+    pub rssi_db: u8,
+    /// 空闲堆粒度 (字节)
+    pub heap_bytes: usize,
+    /// 电量粒度 (%)
+    pub battery_pct: u8,
+}
+
+impl Coalescing {
+    /// 默认粒度: RSSI 3 dB、堆 1 KB、电量 1%
+    pub const fn new() -> Self {
+        Self { rssi_db: 3, heap_bytes: 1024, battery_pct: 1 }
+    }
+
+    /// 设置 RSSI 粒度
+    pub const fn with_rssi_db(mut self, db: u8) -> Self {
+        self.rssi_db = db;
+        self
+    }
+
+    /// 设置空闲堆粒度
+    pub const fn with_heap_bytes(mut self, bytes: usize) -> Self {
+        self.heap_bytes = bytes;
+        self
+    }
+
+    /// 设置电量粒度
+    pub const fn with_battery_pct(mut self, pct: u8) -> Self {
+        self.battery_pct = pct;
+        self
+    }
+
+    /// RSSI 变化是否需要上报
+    pub fn rssi_changed(&self, old: Option<i8>, new: Option<i8>) -> bool {
+        exceeds(old, new, |a, b| a.abs_diff(b) >= self.rssi_db.max(1))
+    }
+
+    /// 空闲堆变化是否需要上报
+    pub fn heap_changed(&self, old: Option<usize>, new: Option<usize>) -> bool {
+        exceeds(old, new, |a, b| a.abs_diff(b) >= self.heap_bytes.max(1))
+    }
+
+    /// 电量变化是否需要上报 (到达 0% / 100% 总是上报)
+    pub fn battery_changed(&self, old: Option<u8>, new: Option<u8>) -> bool {
+        exceeds(old, new, |a, b| a != b && (a.abs_diff(b) >= self.battery_pct.max(1) || b == 0 || b == 100))
+    }
+}
+
+impl Default for Coalescing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn exceeds<T: Copy + PartialEq>(old: Option<T>, new: Option<T>, f: impl Fn(T, T) -> bool) -> bool {
+    match (old, new) {
+        (Some(a), Some(b)) => f(a, b),
+        (a, b) => a != b,
+    }
+}
+
+// ===== 状态中心 =====
+
+/// 状态观察者
+pub type StatusWatcher<'a> = Receiver<'a, CriticalSectionRawMutex, Snapshot, STATUS_WATCHERS>;
+
+/// 实时状态中心
+pub struct LiveStatus {
+    state: CriticalWatch<Snapshot, STATUS_WATCHERS>,
+    coalescing: Coalescing,
+}
+
+/// 全局状态中心
+pub static STATUS: LiveStatus = LiveStatus::new(Coalescing::new());
+
+impl LiveStatus {
+    /// 创建 (尚无快照)
+    pub const fn new(coalescing: Coalescing) -> Self {
+        Self { state: CriticalWatch::new(), coalescing }
+    }
+
+    /// 合并粒度
+    pub fn coalescing(&self) -> &Coalescing {
+        &self.coalescing
+    }
+
+    /// 订阅状态变化 (观察者已满时返回 `None`)
+    pub fn watcher(&self) -> Option<StatusWatcher<'_>> {
+        self.state.receiver()
+    }
+
+    /// 当前快照
+    pub fn snapshot(&self) -> Snapshot {
+        self.state.try_get().unwrap_or(Snapshot::EMPTY)
+    }
+
+    /// 更新 IP 地址
+    pub fn set_ip(&self, ip: Option<[u8; 4]>) {
+        self.update(|s| {
+            let changed = s.ip != ip;
+            s.ip = ip;
+            changed
+        });
+    }
+
+    /// 更新信号强度
+    pub fn set_rssi(&self, rssi: Option<i8>) {
+        let c = self.coalescing;
+        self.update(|s| {
+            let changed = c.rssi_changed(s.rssi, rssi);
+            if changed {
+                s.rssi = rssi;
+            }
+            changed
+        });
+    }
+
+    /// 更新空闲堆
+    pub fn set_heap_free(&self, bytes: usize) {
+        let c = self.coalescing;
+        self.update(|s| {
+            let changed = c.heap_changed(s.heap_free, Some(bytes));
+            if changed {
+                s.heap_free = Some(bytes);
+            }
+            changed
+        });
+    }
+
+    /// 读取全局堆的空闲字节数并更新
+    pub fn sample_heap(&self) {
+        self.set_heap_free(esp_alloc::HEAP.free());
+    }
+
+    /// 更新电池电量 (超过 100% 按 100% 处理)
+    pub fn set_battery(&self, percent: Option<u8>) {
+        let percent = percent.map(|p| p.min(100));
+        let c = self.coalescing;
+        self.update(|s| {
+            let changed = c.battery_changed(s.battery, percent);
+            if changed {
+                s.battery = percent;
+            }
+            changed
+        });
+    }
+
+    /// 修改快照，`f` 返回 `true` 时通知观察者
+    fn update(&self, f: impl Fn(&mut Snapshot) -> bool) {
+        self.state.sender().send_if_modified(|current| {
+            let mut snapshot = current.unwrap_or(Snapshot::EMPTY);
+            let changed = f(&mut snapshot);
+            if changed {
+                *current = Some(snapshot);
+            }
+            changed
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coalescing() {
+        let c = Coalescing::new();
+        assert!(!c.rssi_changed(Some(-60), Some(-62)));
+        assert!(c.rssi_changed(Some(-60), Some(-63)));
+        assert!(c.rssi_changed(Some(-60), None));
+        assert!(c.rssi_changed(None, Some(-90)));
+        assert!(!c.heap_changed(Some(50_000), Some(50_900)));
+        assert!(c.heap_changed(Some(50_000), Some(48_000)));
+
+        let coarse = c.with_battery_pct(5);
+        assert!(!coarse.battery_changed(Some(80), Some(77)));
+        assert!(coarse.battery_changed(Some(80), Some(75)));
+        assert!(coarse.battery_changed(Some(98), Some(100)));
+        assert!(!coarse.battery_changed(Some(100), Some(100)));
+    }
+
+    #[test]
+    fn test_live_status() {
+        let status = LiveStatus::new(Coalescing::new());
+        let mut watcher = status.watcher().unwrap();
+        assert_eq!(status.snapshot(), Snapshot::EMPTY);
+
+        status.set_ip(Some([192, 168, 1, 20]));
+        status.set_rssi(Some(-55));
+        let first = watcher.try_changed().unwrap();
+        assert_eq!(first.changed_fields(&Snapshot::EMPTY), Fields::IP | Fields::RSSI);
+
+        // 粒度以内的抖动不唤醒观察者
+        status.set_rssi(Some(-56));
+        status.set_ip(Some([192, 168, 1, 20]));
+        assert_eq!(watcher.try_changed(), None);
+
+        status.set_battery(Some(150));
+        let second = watcher.try_changed().unwrap();
+        assert_eq!(second.battery, Some(100));
+        assert_eq!(second.rssi, Some(-55));
+        assert_eq!(second.changed_fields(&first), Fields::BATTERY);
+    }
+}
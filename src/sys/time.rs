@@ -0,0 +1,372 @@
+//! 系统时间服务
+//!
+//! 同时提供两个时钟，避免混用:
+//! - 单调时钟: `embassy_time::Instant`，自启动起单调递增，用于超时与间隔
+//! - 墙上时钟: Unix 纪元时间，由 SNTP / RTC / 手动设置建立映射，可能跳变
+//!
+//! 墙上时钟 = 单调时钟 + 偏移。每次设置时间都会重新计算偏移；首次同步或偏移变化
+//! 超过 `STEP_THRESHOLD` 时视为跳变，发布 `SystemEvent::TimeAdjusted` 并通知观察者。
+//! `sleep_until` 在跳变后重新换算截止时刻，不会因为时间被拨动而提前或永久错过。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::sys::time::{TimeSource, WallTime, TIME};
+//!
+//! // SNTP 客户端收到响应
+//! TIME.set(WallTime::from_ntp(seconds, fraction), TimeSource::Sntp);
+//!
+//! // 每天 03:30 (UTC) 执行维护
+//! loop {
+//!     let now = TIME.wait_synced().await;
+//!     TIME.sleep_until(now.next_daily(3 * 3600 + 30 * 60)).await;
+//!     run_maintenance().await;
+//! }
+//! ```
+
+use core::cell::Cell;
+use core::fmt;
+
+use embassy_futures::select::select;
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_sync::watch::Receiver;
+use embassy_time::{Duration, Instant, Timer};
+
+use crate::sync::bus::{self, SystemEvent};
+use crate::sync::primitives::CriticalWatch;
+
+/// 偏移变化超过该值视为跳变 (与 NTP 的步进阈值一致)
+pub const STEP_THRESHOLD: Duration = Duration::from_millis(128);
+
+/// 同时等待时间变化的观察者数量
+pub const TIME_WATCHERS: usize = 4;
+
+/// 观察者名额用尽时 `sleep_until` 的重新检查间隔
+const RECHECK: Duration = Duration::from_secs(60);
+
+/// NTP 纪元 (1900) 到 Unix 纪元 (1970) 的秒数
+const NTP_UNIX_OFFSET: u64 = 2_208_988_800;
+
+const MICROS_PER_SEC: u64 = 1_000_000;
+
+const SECS_PER_DAY: u64 = 86_400;
+
+// ===== 墙上时间 =====
+
+/// 墙上时间 (Unix 纪元起的微秒数，UTC)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WallTime(u64);
+
+impl WallTime {
+    /// Unix 纪元
+    pub const EPOCH: Self = Self(0);
+
+    /// 从 Unix 微秒创建
+    pub const fn from_unix_micros(us: u64) -> Self {
+        Self(us)
+    }
+
+    /// 从 Unix 秒创建
+    pub const fn from_unix_secs(secs: u64) -> Self {
+        Self(secs * MICROS_PER_SEC)
+    }
+
+    /// 从 NTP 时间戳创建 (1900 纪元秒 + 2^-32 秒小数)
+    ///
+    /// 按 NTP 时代 0 解释，有效至 2036 年。
+    pub const fn from_ntp(seconds: u32, fraction: u32) -> Self {
+        let secs = (seconds as u64).saturating_sub(NTP_UNIX_OFFSET);
+        let frac_us = (fraction as u64 * MICROS_PER_SEC) >> 32;
+        Self(secs * MICROS_PER_SEC + frac_us)
+    }
+
+    /// Unix 微秒
+    pub const fn unix_micros(self) -> u64 {
+        self.0
+    }
+
+    /// Unix 秒
+    pub const fn unix_secs(self) -> u64 {
+        self.0 / MICROS_PER_SEC
+    }
+
+    /// 当天已过的秒数 (UTC)
+    pub const fn seconds_of_day(self) -> u32 {
+        (self.unix_secs() % SECS_PER_DAY) as u32
+    }
+
+    /// 下一个 (严格晚于当前时刻) 当天第 `seconds_of_day` 秒的时刻 (UTC)
+    pub const fn next_daily(self, seconds_of_day: u32) -> Self {
+        let secs = self.unix_secs();
+        let day_start = secs - secs % SECS_PER_DAY;
+        let mut target = day_start + seconds_of_day as u64 % SECS_PER_DAY;
+        if target * MICROS_PER_SEC <= self.0 {
+            target += SECS_PER_DAY;
+        }
+        Self::from_unix_secs(target)
+    }
+
+    /// 加上时长
+    pub const fn add(self, duration: Duration) -> Self {
+        Self(self.0.saturating_add(duration.as_micros()))
+    }
+
+    /// 距较早时刻的时长 (`earlier` 更晚时为 0)
+    pub const fn saturating_since(self, earlier: Self) -> Duration {
+        Duration::from_micros(self.0.saturating_sub(earlier.0))
+    }
+}
+
+impl fmt::Display for WallTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{:06}", self.unix_secs(), self.0 % MICROS_PER_SEC)
+    }
+}
+
+// ===== 时间来源与变化 =====
+
+/// 时间来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeSource {
+    /// 网络授时
+    Sntp,
+    /// 外部实时时钟芯片
+    Rtc,
+    /// 手动设置 (Shell、管理接口)
+    Manual,
+}
+
+impl TimeSource {
+    /// 名称
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Sntp => "sntp",
+            Self::Rtc => "rtc",
+            Self::Manual => "manual",
+        }
+    }
+}
+
+/// 墙上时钟映射的一次变化
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeChange {
+    /// 映射代数 (每次跳变加 1)
+    pub generation: u32,
+    /// 墙上时钟变化量 (μs，正数表示向前拨)
+    pub delta_us: i64,
+    /// 来源
+    pub source: TimeSource,
+    /// 是否为首次同步
+    pub first: bool,
+}
+
+/// 时间变化观察者
+pub type TimeWatcher<'a> = Receiver<'a, CriticalSectionRawMutex, TimeChange, TIME_WATCHERS>;
+
+#[derive(Debug, Clone, Copy)]
+struct Mapping {
+    /// 墙上时钟 - 单调时钟 (μs)，未同步时为 `None`
+    offset_us: Option<i64>,
+    generation: u32,
+    source: TimeSource,
+    synced_at: Instant,
+}
+
+// ===== 时间服务 =====
+
+/// 时间服务
+pub struct TimeService {
+    mapping: BlockingMutex<CriticalSectionRawMutex, Cell<Mapping>>,
+    changes: CriticalWatch<TimeChange, TIME_WATCHERS>,
+}
+
+/// 全局时间服务
+pub static TIME: TimeService = TimeService::new();
+
+impl TimeService {
+    /// 创建 (未同步)
+    pub const fn new() -> Self {
+        Self {
+            mapping: BlockingMutex::new(Cell::new(Mapping {
+                offset_us: None,
+                generation: 0,
+                source: TimeSource::Manual,
+                synced_at: Instant::from_ticks(0),
+            })),
+            changes: CriticalWatch::new(),
+        }
+    }
+
+    /// 单调时钟
+    pub fn monotonic(&self) -> Instant {
+        Instant::now()
+    }
+
+    /// 当前墙上时间，未同步时返回 `None`
+    pub fn now(&self) -> Option<WallTime> {
+        self.to_wall(Instant::now())
+    }
+
+    /// 是否已同步
+    pub fn is_synced(&self) -> bool {
+        self.mapping.lock(|m| m.get().offset_us.is_some())
+    }
+
+    /// 映射代数 (比较前后两次读数可判断期间是否发生跳变)
+    pub fn generation(&self) -> u32 {
+        self.mapping.lock(|m| m.get().generation)
+    }
+
+    /// 最近一次设置的来源与单调时刻
+    pub fn last_sync(&self) -> Option<(TimeSource, Instant)> {
+        self.mapping.lock(|m| {
+            let m = m.get();
+            m.offset_us.map(|_| (m.source, m.synced_at))
+        })
+    }
+
+    /// 单调时刻 → 墙上时间 (按当前映射)
+    pub fn to_wall(&self, instant: Instant) -> Option<WallTime> {
+        let offset = self.mapping.lock(|m| m.get().offset_us)?;
+        let us = (instant.as_micros() as i64).checked_add(offset)?;
+        (us >= 0).then(|| WallTime::from_unix_micros(us as u64))
+    }
+
+    /// 墙上时间 → 单调时刻 (按当前映射；早于启动时返回 `None`)
+    pub fn to_instant(&self, wall: WallTime) -> Option<Instant> {
+        let offset = self.mapping.lock(|m| m.get().offset_us)?;
+        let us = (wall.unix_micros() as i64).checked_sub(offset)?;
+        (us >= 0).then(|| Instant::from_micros(us as u64))
+    }
+
+    /// 设置墙上时间
+    ///
+    /// # 返回
+    /// 首次同步或跳变超过 `STEP_THRESHOLD` 时返回变化 (同时发布事件并通知观察者)
+    pub fn set(&self, wall: WallTime, source: TimeSource) -> Option<TimeChange> {
+        self.set_at(wall, source, Instant::now())
+    }
+
+    fn set_at(&self, wall: WallTime, source: TimeSource, now: Instant) -> Option<TimeChange> {
+        let offset = wall.unix_micros() as i64 - now.as_micros() as i64;
+        let change = self.mapping.lock(|cell| {
+            let mut m = cell.get();
+            let previous = m.offset_us;
+            m.offset_us = Some(offset);
+            m.source = source;
+            m.synced_at = now;
+            let delta_us = offset - previous.unwrap_or(offset);
+            let jumped = previous.is_none() || delta_us.unsigned_abs() >= STEP_THRESHOLD.as_micros();
+            if jumped {
+                m.generation = m.generation.wrapping_add(1);
+            }
+            cell.set(m);
+            jumped.then_some(TimeChange { generation: m.generation, delta_us, source, first: previous.is_none() })
+        })?;
+
+        bus::publish(SystemEvent::TimeAdjusted {
+            source: source.name(),
+            delta_ms: change.delta_us / 1000,
+            first: change.first,
+        });
+        self.changes.sender().send(change);
+        Some(change)
+    }
+
+    /// 获取时间变化观察者 (名额用尽时返回 `None`)
+    pub fn watcher(&self) -> Option<TimeWatcher<'_>> {
+        self.changes.receiver()
+    }
+
+    /// 等待首次同步，返回当前墙上时间
+    pub async fn wait_synced(&self) -> WallTime {
+        let mut watcher = self.watcher();
+        loop {
+            if let Some(now) = self.now() {
+                return now;
+            }
+            match watcher.as_mut() {
+                Some(w) => {
+                    w.changed().await;
+                }
+                None => Timer::after(Duration::from_secs(1)).await,
+            }
+        }
+    }
+
+    /// 睡眠到墙上时间 `target`
+    ///
+    /// 未同步时先等待同步；时间跳变后按新映射重新计算截止时刻。
+    /// 时间被向前拨过 `target` 时立即返回，向后拨时继续等待到新映射下的 `target`。
+    pub async fn sleep_until(&self, target: WallTime) {
+        let mut watcher = self.watcher();
+        loop {
+            let now = Instant::now();
+            let deadline = match self.to_wall(now) {
+                Some(wall) if wall >= target => return,
+                Some(wall) => now + target.saturating_since(wall),
+                None => now + RECHECK,
+            };
+            match watcher.as_mut() {
+                Some(w) => {
+                    select(Timer::at(deadline), w.changed()).await;
+                }
+                None => Timer::at(deadline.min(now + RECHECK)).await,
+            }
+        }
+    }
+
+    /// 睡眠到下一个当天第 `seconds_of_day` 秒 (UTC)
+    pub async fn sleep_until_daily(&self, seconds_of_day: u32) -> WallTime {
+        let target = self.wait_synced().await.next_daily(seconds_of_day);
+        self.sleep_until(target).await;
+        target
+    }
+}
+
+impl Default for TimeService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wall_time() {
+        // 2024-01-01T00:00:00Z
+        let t = WallTime::from_ntp(3_913_056_000, 1 << 31);
+        assert_eq!(t.unix_secs(), 1_704_067_200);
+        assert_eq!(t.unix_micros() % 1_000_000, 500_000);
+        assert_eq!(t.next_daily(3600), WallTime::from_unix_secs(1_704_067_200 + 3600));
+        assert_eq!(t.next_daily(0), WallTime::from_unix_secs(1_704_067_200 + 86_400));
+        assert_eq!(t.add(Duration::from_secs(90)).seconds_of_day(), 90);
+    }
+
+    #[test]
+    fn test_mapping_jumps() {
+        let time = TimeService::new();
+        let boot = Instant::from_secs(10);
+        assert_eq!(time.to_wall(boot), None);
+
+        let first = time.set_at(WallTime::from_unix_secs(1_000_000), TimeSource::Rtc, boot).unwrap();
+        assert!(first.first);
+        assert_eq!(time.to_wall(boot + Duration::from_secs(5)), Some(WallTime::from_unix_secs(1_000_005)));
+        assert_eq!(time.to_instant(WallTime::from_unix_secs(1_000_020)), Some(Instant::from_secs(30)));
+
+        // 小幅校正不算跳变
+        let later = boot + Duration::from_secs(60);
+        let wall = WallTime::from_unix_secs(1_000_060).add(Duration::from_millis(50));
+        assert_eq!(time.set_at(wall, TimeSource::Sntp, later), None);
+        assert_eq!(time.generation(), 1);
+
+        // 向后拨 5 秒
+        let change = time.set_at(WallTime::from_unix_secs(1_000_055), TimeSource::Sntp, later).unwrap();
+        assert_eq!(change.delta_us, -5_050_000);
+        assert_eq!(change.generation, 2);
+        assert!(!change.first);
+    }
+}
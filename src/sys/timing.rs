@@ -0,0 +1,283 @@
+//! 时间驱动诊断
+//!
+//! embassy-time 的驱动由 esp-rtos 提供，本模块在其上统计定时器中断的响应情况:
+//! - 中断次数: 观测到的告警触发次数
+//! - 处理延迟: 告警预定时刻到实际唤醒的时间差 (最大值、最近值)
+//! - 超限: 延迟超过 SLA 阈值的次数
+//! - 溢出: 延迟超过一个周期 (如 Flash 擦写期间 CPU 停顿)，并累计错过的节拍数
+//!
+//! 监测任务以固定周期设置告警并测量唤醒延迟，应在最高优先级执行器上运行，
+//! 使测得的延迟主要反映驱动与中断层而非任务调度。自定义定时器中断也可调用
+//! `record_alarm` 上报。超限与溢出通过 `SystemEvent::TimingViolation` 发布
+//! (按 `report_interval` 限频，统计不受限频影响)。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::sys::timing::{self, TimingConfig};
+//!
+//! #[embassy_executor::task]
+//! async fn timing_task() {
+//!     timing::run(TimingConfig::new().with_latency_sla(Duration::from_micros(200))).await
+//! }
+//!
+//! high_prio_spawner.must_spawn(timing_task());
+//! log_info!("{}", timing::stats());
+//! ```
+
+use core::cell::Cell;
+use core::fmt;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_time::{Duration, Instant, Timer};
+
+use crate::sync::bus::{self, SystemEvent};
+
+// ===== 配置 =====
+
+/// 监测配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimingConfig {
+    /// 监测告警周期
+    pub period: Duration,
+    /// 处理延迟 SLA (超过即计为超限)
+    pub latency_sla: Duration,
+    /// 两次事件发布的最小间隔
+    pub report_interval: Duration,
+}
+
+impl TimingConfig {
+    /// 默认配置: 1ms 周期、500us SLA、每秒最多发布一次
+    pub const fn new() -> Self {
+        Self {
+            period: Duration::from_millis(1),
+            latency_sla: Duration::from_micros(500),
+            report_interval: Duration::from_secs(1),
+        }
+    }
+
+    /// 设置监测周期
+    pub const fn with_period(mut self, period: Duration) -> Self {
+        self.period = period;
+        self
+    }
+
+    /// 设置延迟 SLA
+    pub const fn with_latency_sla(mut self, sla: Duration) -> Self {
+        self.latency_sla = sla;
+        self
+    }
+
+    /// 设置事件发布最小间隔
+    pub const fn with_report_interval(mut self, interval: Duration) -> Self {
+        self.report_interval = interval;
+        self
+    }
+}
+
+impl Default for TimingConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ===== 统计 =====
+
+/// 时序违规
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    /// 处理延迟超过 SLA
+    Latency {
+        /// 延迟 (微秒)
+        latency_us: u32,
+    },
+    /// 延迟超过一个周期，错过了节拍
+    Overrun {
+        /// 延迟 (微秒)
+        latency_us: u32,
+        /// 错过的节拍数
+        missed: u32,
+    },
+}
+
+impl Violation {
+    /// 类别名称 (事件中的 `kind`)
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::Latency { .. } => "latency",
+            Self::Overrun { .. } => "overrun",
+        }
+    }
+
+    fn to_event(self) -> SystemEvent {
+        let (latency_us, missed) = match self {
+            Self::Latency { latency_us } => (latency_us, 0),
+            Self::Overrun { latency_us, missed } => (latency_us, missed),
+        };
+        SystemEvent::TimingViolation { kind: self.kind(), latency_us, missed }
+    }
+}
+
+/// 驱动统计快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimingStats {
+    /// 观测到的定时器中断次数
+    pub interrupts: u32,
+    /// 最近一次处理延迟 (微秒)
+    pub last_latency_us: u32,
+    /// 最大处理延迟 (微秒)
+    pub max_latency_us: u32,
+    /// 超过 SLA 的次数 (含溢出)
+    pub sla_violations: u32,
+    /// 溢出次数
+    pub overruns: u32,
+    /// 累计错过的节拍数
+    pub missed_ticks: u32,
+}
+
+impl TimingStats {
+    /// 记录一次告警的处理延迟，返回违规类别
+    pub fn record(&mut self, latency: Duration, config: &TimingConfig) -> Option<Violation> {
+        let latency_us = latency.as_micros().min(u32::MAX as u64) as u32;
+        self.interrupts = self.interrupts.wrapping_add(1);
+        self.last_latency_us = latency_us;
+        self.max_latency_us = self.max_latency_us.max(latency_us);
+
+        if latency <= config.latency_sla {
+            return None;
+        }
+        self.sla_violations = self.sla_violations.saturating_add(1);
+
+        let period = config.period.as_ticks().max(1);
+        let missed = (latency.as_ticks() / period).min(u32::MAX as u64) as u32;
+        if missed == 0 {
+            return Some(Violation::Latency { latency_us });
+        }
+        self.overruns = self.overruns.saturating_add(1);
+        self.missed_ticks = self.missed_ticks.saturating_add(missed);
+        Some(Violation::Overrun { latency_us, missed })
+    }
+}
+
+impl fmt::Display for TimingStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "irq={} latency={}us max={}us sla_violations={} overruns={} missed_ticks={}",
+            self.interrupts,
+            self.last_latency_us,
+            self.max_latency_us,
+            self.sla_violations,
+            self.overruns,
+            self.missed_ticks
+        )
+    }
+}
+
+// ===== 全局状态 =====
+
+#[derive(Clone, Copy)]
+struct State {
+    stats: TimingStats,
+    config: TimingConfig,
+    last_report: Option<Instant>,
+}
+
+static STATE: BlockingMutex<CriticalSectionRawMutex, Cell<State>> = BlockingMutex::new(Cell::new(State {
+    stats: TimingStats {
+        interrupts: 0,
+        last_latency_us: 0,
+        max_latency_us: 0,
+        sla_violations: 0,
+        overruns: 0,
+        missed_ticks: 0,
+    },
+    config: TimingConfig::new(),
+    last_report: None,
+}));
+
+/// 设置全局阈值 (`run` 启动时自动调用)
+pub fn configure(config: TimingConfig) {
+    STATE.lock(|cell| {
+        let mut state = cell.get();
+        state.config = config;
+        cell.set(state);
+    });
+}
+
+/// 上报一次告警: 预定时刻 `scheduled`，实际处理时刻 `fired`
+///
+/// 可在中断上下文调用。违规时按限频发布 `SystemEvent::TimingViolation`。
+pub fn record_alarm(scheduled: Instant, fired: Instant) -> Option<Violation> {
+    let latency = fired.checked_duration_since(scheduled).unwrap_or(Duration::from_ticks(0));
+    let (violation, publish) = STATE.lock(|cell| {
+        let mut state = cell.get();
+        let violation = state.stats.record(latency, &state.config);
+        let due = state.last_report.is_none_or(|at| fired >= at + state.config.report_interval);
+        let publish = violation.is_some() && due;
+        if publish {
+            state.last_report = Some(fired);
+        }
+        cell.set(state);
+        (violation, publish)
+    });
+    if let Some(v) = violation.filter(|_| publish) {
+        bus::publish(v.to_event());
+    }
+    violation
+}
+
+/// 当前统计
+pub fn stats() -> TimingStats {
+    STATE.lock(|cell| cell.get().stats)
+}
+
+/// 清零统计 (如发布统计后开始新的观测窗口)
+pub fn reset() {
+    STATE.lock(|cell| {
+        let mut state = cell.get();
+        state.stats = TimingStats::default();
+        cell.set(state);
+    });
+}
+
+/// 监测任务主体 (不返回)
+///
+/// 错过节拍后从当前时刻重新对齐，不补发积压的告警。
+pub async fn run(config: TimingConfig) -> ! {
+    configure(config);
+    let mut next = Instant::now() + config.period;
+    loop {
+        Timer::at(next).await;
+        let now = Instant::now();
+        record_alarm(next, now);
+        next += config.period;
+        if next <= now {
+            next = now + config.period;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_classifies_latency() {
+        let config = TimingConfig::new().with_period(Duration::from_millis(1)).with_latency_sla(Duration::from_micros(200));
+        let mut stats = TimingStats::default();
+
+        assert_eq!(stats.record(Duration::from_micros(150), &config), None);
+        assert_eq!(stats.record(Duration::from_micros(400), &config), Some(Violation::Latency { latency_us: 400 }));
+        assert_eq!(
+            stats.record(Duration::from_micros(3500), &config),
+            Some(Violation::Overrun { latency_us: 3500, missed: 3 })
+        );
+
+        assert_eq!(stats.interrupts, 3);
+        assert_eq!(stats.max_latency_us, 3500);
+        assert_eq!(stats.last_latency_us, 3500);
+        assert_eq!((stats.sla_violations, stats.overruns, stats.missed_ticks), (2, 1, 3));
+    }
+}
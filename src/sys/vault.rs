@@ -0,0 +1,449 @@
+//! 凭据保管库
+//!
+//! 集中保存设备上的敏感凭据，供 Shell 控制台、HTTP 认证、TLS 等服务共用:
+//! - 口令只保存加盐迭代 SHA-256 摘要，验证使用常量时间比较
+//! - API 令牌、私钥等原始秘密通过回调访问，避免在栈上散落副本
+//! - 删除时清零
+//! - 可持久化到文件系统 (写临时文件后重命名，掉电安全)
+//!
+//! # 存储安全
+//!
+//! 持久化文件**不加密**: 口令只有加盐摘要，但 API 令牌、私钥等秘密以明文写入，
+//! 文件末尾的 SHA-256 只用于检测损坏，不能防篡改。能读出 Flash 的人即可取得这些秘密，
+//! 因此量产设备应启用 Flash 加密 (见 `sys::security`)，否则不要用 `save` 持久化原始秘密。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::sys::vault::{VAULT, SecretKind};
+//!
+//! VAULT.set_password("shell", b"correct horse", salt)?;
+//! assert!(VAULT.verify_password("shell", b"correct horse")?);
+//!
+//! VAULT.set_secret("api", SecretKind::Token, token)?;
+//! VAULT.save(&fs, "/sys/vault.bin")?;
+//! ```
+
+use core::cell::RefCell;
+use core::fmt;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use heapless::{String, Vec};
+use sha2::{Digest, Sha256};
+
+use crate::crypto::constant_time_eq;
+use crate::fs::littlefs::{FileSystem, FsError, OpenOptions};
+
+/// 凭据名最大长度
+pub const MAX_NAME_LEN: usize = 24;
+
+/// 秘密最大长度
+pub const MAX_SECRET_LEN: usize = 96;
+
+/// 口令盐长度
+pub const SALT_LEN: usize = 16;
+
+/// 口令摘要迭代次数
+pub const HASH_ROUNDS: u32 = 1000;
+
+/// 持久化文件魔数
+const FILE_MAGIC: &[u8; 4] = b"VLT1";
+
+/// 凭据错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VaultError {
+    /// 凭据不存在
+    NotFound,
+    /// 保管库已满
+    Full,
+    /// 名称无效或过长
+    InvalidName,
+    /// 秘密过长
+    TooLarge,
+    /// 凭据类型不符
+    WrongKind,
+    /// 持久化数据损坏
+    Corrupt,
+    /// 文件系统错误
+    Fs(FsError),
+}
+
+impl fmt::Display for VaultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "Credential not found"),
+            Self::Full => write!(f, "Vault full"),
+            Self::InvalidName => write!(f, "Invalid credential name"),
+            Self::TooLarge => write!(f, "Secret too large"),
+            Self::WrongKind => write!(f, "Wrong credential kind"),
+            Self::Corrupt => write!(f, "Vault data corrupt"),
+            Self::Fs(e) => write!(f, "Filesystem error: {:?}", e),
+        }
+    }
+}
+
+impl From<FsError> for VaultError {
+    fn from(e: FsError) -> Self {
+        VaultError::Fs(e)
+    }
+}
+
+/// 凭据类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretKind {
+    /// 口令 (保存盐 + 摘要)
+    Password,
+    /// API 令牌
+    Token,
+    /// 密钥材料
+    Key,
+}
+
+impl SecretKind {
+    const fn as_u8(self) -> u8 {
+        match self {
+            SecretKind::Password => 0,
+            SecretKind::Token => 1,
+            SecretKind::Key => 2,
+        }
+    }
+
+    const fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            0 => Some(SecretKind::Password),
+            1 => Some(SecretKind::Token),
+            2 => Some(SecretKind::Key),
+            _ => None,
+        }
+    }
+}
+
+struct Entry {
+    name: String<MAX_NAME_LEN>,
+    kind: SecretKind,
+    data: Vec<u8, MAX_SECRET_LEN>,
+}
+
+impl Entry {
+    fn wipe(&mut self) {
+        for b in self.data.iter_mut() {
+            // volatile 写避免被优化掉
+            unsafe { core::ptr::write_volatile(b, 0) };
+        }
+        self.data.clear();
+    }
+}
+
+/// 计算口令摘要: H0 = SHA256(salt || pw)，Hi = SHA256(Hi-1 || salt || pw)
+fn hash_password(salt: &[u8], password: &[u8]) -> [u8; 32] {
+    let mut digest: [u8; 32] = Sha256::new().chain_update(salt).chain_update(password).finalize().into();
+    for _ in 1..HASH_ROUNDS {
+        digest = Sha256::new()
+            .chain_update(digest)
+            .chain_update(salt)
+            .chain_update(password)
+            .finalize()
+            .into();
+    }
+    digest
+}
+
+/// 凭据保管库
+pub struct Vault<const N: usize> {
+    entries: BlockingMutex<CriticalSectionRawMutex, RefCell<Vec<Entry, N>>>,
+}
+
+impl<const N: usize> Vault<N> {
+    /// 创建空保管库
+    pub const fn new() -> Self {
+        Self { entries: BlockingMutex::new(RefCell::new(Vec::new())) }
+    }
+
+    fn store(&self, name: &str, kind: SecretKind, data: &[u8]) -> Result<(), VaultError> {
+        if name.is_empty() {
+            return Err(VaultError::InvalidName);
+        }
+        let name = String::try_from(name).map_err(|_| VaultError::InvalidName)?;
+        let data = Vec::from_slice(data).map_err(|_| VaultError::TooLarge)?;
+        self.entries.lock(|entries| {
+            let mut entries = entries.borrow_mut();
+            if let Some(entry) = entries.iter_mut().find(|e| e.name == name) {
+                entry.wipe();
+                entry.kind = kind;
+                entry.data = data;
+                return Ok(());
+            }
+            entries.push(Entry { name, kind, data }).map_err(|_| VaultError::Full)
+        })
+    }
+
+    /// 设置口令 (`salt` 应来自硬件随机数)
+    pub fn set_password(&self, name: &str, password: &[u8], salt: [u8; SALT_LEN]) -> Result<(), VaultError> {
+        let mut record = [0u8; SALT_LEN + 32];
+        record[..SALT_LEN].copy_from_slice(&salt);
+        record[SALT_LEN..].copy_from_slice(&hash_password(&salt, password));
+        self.store(name, SecretKind::Password, &record)
+    }
+
+    /// 验证口令
+    pub fn verify_password(&self, name: &str, password: &[u8]) -> Result<bool, VaultError> {
+        let mut record = [0u8; SALT_LEN + 32];
+        self.with_secret(name, |kind, data| {
+            if kind != SecretKind::Password || data.len() != record.len() {
+                return Err(VaultError::WrongKind);
+            }
+            record.copy_from_slice(data);
+            Ok(())
+        })??;
+        // 摘要计算较慢，在临界区外进行
+        let digest = hash_password(&record[..SALT_LEN], password);
+        Ok(constant_time_eq(&digest, &record[SALT_LEN..]))
+    }
+
+    /// 保存原始秘密
+    pub fn set_secret(&self, name: &str, kind: SecretKind, secret: &[u8]) -> Result<(), VaultError> {
+        self.store(name, kind, secret)
+    }
+
+    /// 在临界区内访问秘密
+    pub fn with_secret<R>(&self, name: &str, f: impl FnOnce(SecretKind, &[u8]) -> R) -> Result<R, VaultError> {
+        self.entries.lock(|entries| {
+            let entries = entries.borrow();
+            let entry = entries.iter().find(|e| e.name == name).ok_or(VaultError::NotFound)?;
+            Ok(f(entry.kind, &entry.data))
+        })
+    }
+
+    /// 常量时间比较令牌
+    pub fn verify_token(&self, name: &str, token: &[u8]) -> Result<bool, VaultError> {
+        self.with_secret(name, |kind, data| match kind {
+            SecretKind::Token => Ok(constant_time_eq(data, token)),
+            _ => Err(VaultError::WrongKind),
+        })?
+    }
+
+    /// 是否存在凭据
+    pub fn contains(&self, name: &str) -> bool {
+        self.with_secret(name, |_, _| ()).is_ok()
+    }
+
+    /// 删除凭据 (清零)
+    pub fn remove(&self, name: &str) -> Result<(), VaultError> {
+        self.entries.lock(|entries| {
+            let mut entries = entries.borrow_mut();
+            let index = entries.iter().position(|e| e.name == name).ok_or(VaultError::NotFound)?;
+            entries[index].wipe();
+            entries.swap_remove(index);
+            Ok(())
+        })
+    }
+
+    /// 清空 (清零全部秘密)
+    pub fn clear(&self) {
+        self.entries.lock(|entries| {
+            let mut entries = entries.borrow_mut();
+            entries.iter_mut().for_each(Entry::wipe);
+            entries.clear();
+        });
+    }
+
+    /// 凭据数量
+    pub fn len(&self) -> usize {
+        self.entries.lock(|entries| entries.borrow().len())
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // ----- 持久化 -----
+
+    /// 序列化: 魔数 | 数量 | {名称长度, 名称, 类型, 数据长度, 数据}* | SHA-256
+    ///
+    /// 逐条在临界区内拷贝，写出 (可能是 Flash 操作) 在临界区外进行
+    fn encode<F>(&self, mut sink: F) -> Result<(), VaultError>
+    where
+        F: FnMut(&[u8]) -> Result<(), VaultError>,
+    {
+        fn emit<F: FnMut(&[u8]) -> Result<(), VaultError>>(h: &mut Sha256, sink: &mut F, data: &[u8]) -> Result<(), VaultError> {
+            h.update(data);
+            sink(data)
+        }
+
+        let mut hasher = Sha256::new();
+        let count = self.len();
+        emit(&mut hasher, &mut sink, FILE_MAGIC)?;
+        emit(&mut hasher, &mut sink, &[count as u8])?;
+
+        let mut record = [0u8; 3 + MAX_NAME_LEN + MAX_SECRET_LEN];
+        let mut result = Ok(());
+        for i in 0..count {
+            let len = self.entries.lock(|entries| {
+                let entries = entries.borrow();
+                let e = entries.get(i)?;
+                let (name_len, data_len) = (e.name.len(), e.data.len());
+                record[0] = name_len as u8;
+                record[1..1 + name_len].copy_from_slice(e.name.as_bytes());
+                record[1 + name_len] = e.kind.as_u8();
+                record[2 + name_len] = data_len as u8;
+                record[3 + name_len..3 + name_len + data_len].copy_from_slice(&e.data);
+                Some(3 + name_len + data_len)
+            });
+            // 序列化期间被并发删除
+            let Some(len) = len else {
+                result = Err(VaultError::Corrupt);
+                break;
+            };
+            result = emit(&mut hasher, &mut sink, &record[..len]);
+            if result.is_err() {
+                break;
+            }
+        }
+        record.iter_mut().for_each(|b| *b = 0);
+        result?;
+
+        let digest: [u8; 32] = hasher.finalize().into();
+        sink(&digest)
+    }
+
+    /// 反序列化 (`source` 精确读取指定长度)，成功后替换当前内容
+    fn decode<F>(&self, mut source: F) -> Result<(), VaultError>
+    where
+        F: FnMut(&mut [u8]) -> Result<(), VaultError>,
+    {
+        let mut hasher = Sha256::new();
+        let mut read = |h: &mut Sha256, buf: &mut [u8]| -> Result<(), VaultError> {
+            source(buf)?;
+            h.update(&*buf);
+            Ok(())
+        };
+
+        let mut header = [0u8; 5];
+        read(&mut hasher, &mut header)?;
+        if &header[..4] != FILE_MAGIC {
+            return Err(VaultError::Corrupt);
+        }
+
+        let mut entries: Vec<Entry, N> = Vec::new();
+        for _ in 0..header[4] {
+            let mut len = [0u8; 1];
+            read(&mut hasher, &mut len)?;
+            let mut name = [0u8; MAX_NAME_LEN];
+            let name = name.get_mut(..len[0] as usize).ok_or(VaultError::Corrupt)?;
+            read(&mut hasher, name)?;
+            let name = core::str::from_utf8(name).map_err(|_| VaultError::Corrupt)?;
+
+            let mut meta = [0u8; 2];
+            read(&mut hasher, &mut meta)?;
+            let kind = SecretKind::from_u8(meta[0]).ok_or(VaultError::Corrupt)?;
+            let mut data: Vec<u8, MAX_SECRET_LEN> = Vec::new();
+            data.resize_default(meta[1] as usize).map_err(|_| VaultError::Corrupt)?;
+            read(&mut hasher, &mut data)?;
+
+            let name = String::try_from(name).map_err(|_| VaultError::Corrupt)?;
+            entries.push(Entry { name, kind, data }).map_err(|_| VaultError::Full)?;
+        }
+
+        let mut digest = [0u8; 32];
+        source(&mut digest)?;
+        let expected: [u8; 32] = hasher.finalize().into();
+        if !constant_time_eq(&expected, &digest) {
+            entries.iter_mut().for_each(Entry::wipe);
+            return Err(VaultError::Corrupt);
+        }
+
+        self.clear();
+        self.entries.lock(|e| *e.borrow_mut() = entries);
+        Ok(())
+    }
+
+    /// 保存到文件 (明文，见模块文档 "存储安全")
+    ///
+    /// 先写 `<path>.tmp` 再重命名覆盖 `path`，任何时刻掉电都保留旧文件或新文件之一。
+    pub fn save(&self, fs: &FileSystem, path: &str) -> Result<(), VaultError> {
+        let mut tmp: String<64> = String::new();
+        tmp.push_str(path).and_then(|_| tmp.push_str(".tmp")).map_err(|_| FsError::PathTooLong)?;
+        {
+            let mut file = fs.open(&tmp, OpenOptions::write_only())?;
+            self.encode(|data| file.write_all(data).map_err(VaultError::from))?;
+            file.sync()?;
+        }
+        fs.rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// 从文件加载
+    pub fn load(&self, fs: &FileSystem, path: &str) -> Result<(), VaultError> {
+        let mut file = fs.open(path, OpenOptions::read_only())?;
+        self.decode(|buf| {
+            let mut filled = 0;
+            while filled < buf.len() {
+                match file.read(&mut buf[filled..])? {
+                    0 => return Err(VaultError::Corrupt),
+                    n => filled += n,
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+impl<const N: usize> Default for Vault<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 全局凭据保管库
+pub static VAULT: Vault<8> = Vault::new();
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_password_roundtrip() {
+        let vault: Vault<2> = Vault::new();
+        vault.set_password("shell", b"hunter2", [7; SALT_LEN]).unwrap();
+        assert!(vault.verify_password("shell", b"hunter2").unwrap());
+        assert!(!vault.verify_password("shell", b"hunter3").unwrap());
+        assert_eq!(vault.verify_password("nope", b"x"), Err(VaultError::NotFound));
+
+        vault.set_secret("api", SecretKind::Token, b"tok").unwrap();
+        assert_eq!(vault.verify_password("api", b"tok"), Err(VaultError::WrongKind));
+        assert_eq!(vault.set_secret("x", SecretKind::Key, b"k"), Err(VaultError::Full));
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let vault: Vault<2> = Vault::new();
+        vault.set_secret("api", SecretKind::Token, b"abc").unwrap();
+        let mut buf = [0u8; 256];
+        let mut len = 0;
+        vault
+            .encode(|data| {
+                buf[len..len + data.len()].copy_from_slice(data);
+                len += data.len();
+                Ok(())
+            })
+            .unwrap();
+
+        let read_from = |image: &[u8]| {
+            let restored: Vault<2> = Vault::new();
+            let mut pos = 0;
+            restored
+                .decode(|out| {
+                    let src = image.get(pos..pos + out.len()).ok_or(VaultError::Corrupt)?;
+                    out.copy_from_slice(src);
+                    pos += out.len();
+                    Ok(())
+                })
+                .map(|_| restored.verify_token("api", b"abc").unwrap())
+        };
+        assert_eq!(read_from(&buf[..len]), Ok(true));
+        buf[7] ^= 1;
+        assert_eq!(read_from(&buf[..len]), Err(VaultError::Corrupt));
+    }
+}
@@ -0,0 +1,272 @@
+//! 任务调度基准
+//!
+//! 测量 CPU 密集工作对同一执行器上周期任务唤醒延迟的影响，对比两种执行方式:
+//! - 直接执行: 工作一次跑完，期间执行器无法调度其他任务
+//! - 分片执行: 经 `budget::Budget` 按时间片让出
+//!
+//! 探针与工作负载在同一任务内 `join`，竞争方式与同一执行器上的两个任务一致。
+//! 在目标执行器 (例如 Priority3 的 InterruptExecutor) 上的任务中调用 `run`，
+//! 报告给出延迟的 p50 / p99 / 最大值以及分片带来的总耗时开销。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::tasks::bench::{self, BudgetBenchConfig};
+//!
+//! #[embassy_executor::task]
+//! async fn bench_task() {
+//!     let report = bench::run(&BudgetBenchConfig::new(Duration::from_micros(500))).await;
+//!     log_info!("{}", report);
+//! }
+//! ```
+
+use core::cell::Cell;
+use core::fmt;
+
+use embassy_futures::join::join;
+use embassy_time::{Duration, Instant, Timer};
+
+use super::budget::{Budget, BudgetStats};
+
+/// 延迟直方图桶上界 (μs)，最后一个桶收集更大的值
+pub const BUCKET_BOUNDS_US: [u32; 12] = [10, 20, 50, 100, 200, 500, 1_000, 2_000, 5_000, 10_000, 20_000, 50_000];
+
+/// 单个工作单元处理的字节数
+const UNIT_LEN: usize = 512;
+
+// ===== 延迟直方图 =====
+
+/// 延迟直方图
+#[derive(Debug, Clone, Default)]
+pub struct LatencyHistogram {
+    buckets: [u32; BUCKET_BOUNDS_US.len() + 1],
+    samples: u32,
+    max_us: u32,
+}
+
+impl LatencyHistogram {
+    /// 创建
+    pub const fn new() -> Self {
+        Self { buckets: [0; BUCKET_BOUNDS_US.len() + 1], samples: 0, max_us: 0 }
+    }
+
+    /// 记录一次延迟
+    pub fn record(&mut self, us: u32) {
+        let bucket = BUCKET_BOUNDS_US.iter().position(|&bound| us <= bound).unwrap_or(BUCKET_BOUNDS_US.len());
+        self.buckets[bucket] += 1;
+        self.samples += 1;
+        self.max_us = self.max_us.max(us);
+    }
+
+    /// 百分位 (按桶上界估计，不超过最大值)
+    pub fn percentile(&self, percent: u32) -> u32 {
+        if self.samples == 0 {
+            return 0;
+        }
+        let rank = (self.samples as u64 * percent.min(100) as u64).div_ceil(100).max(1);
+        let mut seen = 0u64;
+        for (i, &count) in self.buckets.iter().enumerate() {
+            seen += count as u64;
+            if seen >= rank {
+                return BUCKET_BOUNDS_US.get(i).copied().unwrap_or(self.max_us).min(self.max_us);
+            }
+        }
+        self.max_us
+    }
+
+    /// 汇总
+    pub fn report(&self) -> LatencyReport {
+        LatencyReport {
+            samples: self.samples,
+            p50_us: self.percentile(50),
+            p99_us: self.percentile(99),
+            max_us: self.max_us,
+        }
+    }
+}
+
+/// 延迟汇总
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LatencyReport {
+    /// 采样次数
+    pub samples: u32,
+    /// 中位数 (μs，桶上界)
+    pub p50_us: u32,
+    /// 99 分位 (μs，桶上界)
+    pub p99_us: u32,
+    /// 最大值 (μs)
+    pub max_us: u32,
+}
+
+impl fmt::Display for LatencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} samples, p50 <= {} us, p99 <= {} us, max {} us",
+            self.samples, self.p50_us, self.p99_us, self.max_us
+        )
+    }
+}
+
+// ===== 配置与结果 =====
+
+/// 基准配置
+#[derive(Debug, Clone, Copy)]
+pub struct BudgetBenchConfig {
+    /// 时间片预算
+    pub slice: Duration,
+    /// 探针唤醒周期
+    pub probe_period: Duration,
+    /// 工作单元数 (每个单元处理 512 字节)
+    pub units: u32,
+}
+
+impl BudgetBenchConfig {
+    /// 创建配置 (探针周期 1ms，4000 个工作单元)
+    pub fn new(slice: Duration) -> Self {
+        Self { slice, probe_period: Duration::from_millis(1), units: 4000 }
+    }
+
+    /// 设置探针周期
+    pub fn with_probe_period(mut self, period: Duration) -> Self {
+        self.probe_period = period;
+        self
+    }
+
+    /// 设置工作单元数
+    pub fn with_units(mut self, units: u32) -> Self {
+        self.units = units.max(1);
+        self
+    }
+}
+
+/// 基准报告
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BudgetBenchReport {
+    /// 时间片预算 (μs)
+    pub slice_us: u32,
+    /// 直接执行期间的探针延迟
+    pub direct: LatencyReport,
+    /// 分片执行期间的探针延迟
+    pub sliced: LatencyReport,
+    /// 直接执行总耗时 (μs)
+    pub direct_us: u32,
+    /// 分片执行总耗时 (μs，含让出期间探针的运行时间)
+    pub sliced_us: u32,
+    /// 分片统计
+    pub budget: BudgetStats,
+}
+
+impl BudgetBenchReport {
+    /// 分片执行相对直接执行的耗时比 (x100)
+    pub fn overhead_x100(&self) -> u32 {
+        if self.direct_us == 0 {
+            return 0;
+        }
+        (self.sliced_us as u64 * 100 / self.direct_us as u64) as u32
+    }
+}
+
+impl fmt::Display for BudgetBenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "slice budget: {} us", self.slice_us)?;
+        writeln!(f, "direct: {} us, probe {}", self.direct_us, self.direct)?;
+        writeln!(f, "sliced: {} us, probe {}", self.sliced_us, self.sliced)?;
+        let x = self.overhead_x100();
+        write!(
+            f,
+            "sliced/direct {}.{:02}x, {} yields, max slice {} us",
+            x / 100,
+            x % 100,
+            self.budget.yields,
+            self.budget.max_slice_us
+        )
+    }
+}
+
+// ===== 运行 =====
+
+/// 一个工作单元: 对缓冲区做 FNV 散列并回写 (模拟哈希 / 压缩类负载)
+fn work_unit(buf: &mut [u8; UNIT_LEN], seed: u32) -> u32 {
+    let mut hash = 0x811c_9dc5u32 ^ seed;
+    for b in buf.iter_mut() {
+        hash = (hash ^ *b as u32).wrapping_mul(0x0100_0193);
+        *b = hash as u8;
+    }
+    core::hint::black_box(hash)
+}
+
+/// 周期唤醒探针，直到 `done` 置位
+async fn probe(period: Duration, done: &Cell<bool>, hist: &mut LatencyHistogram) {
+    let mut expected = Instant::now() + period;
+    while !done.get() {
+        Timer::at(expected).await;
+        let now = Instant::now();
+        hist.record(now.saturating_duration_since(expected).as_micros() as u32);
+        expected += period;
+        if expected < now {
+            expected = now + period;
+        }
+    }
+}
+
+/// 运行一种方式，返回 (探针延迟, 工作总耗时, 分片统计)
+async fn phase(config: &BudgetBenchConfig, sliced: bool) -> (LatencyReport, u32, BudgetStats) {
+    let done = Cell::new(false);
+    let mut hist = LatencyHistogram::new();
+    let mut buf = [0u8; UNIT_LEN];
+
+    let work = async {
+        // 先让探针登记第一次定时
+        embassy_futures::yield_now().await;
+        let start = Instant::now();
+        let mut budget = Budget::new(config.slice);
+        for unit in 0..config.units {
+            work_unit(&mut buf, unit);
+            if sliced {
+                budget.tick().await;
+            }
+        }
+        let elapsed = start.elapsed().as_micros() as u32;
+        done.set(true);
+        (elapsed, budget.finish())
+    };
+
+    let (_, (elapsed, stats)) = join(probe(config.probe_period, &done, &mut hist), work).await;
+    (hist.report(), elapsed, stats)
+}
+
+/// 运行完整基准 (直接执行、分片执行)
+pub async fn run(config: &BudgetBenchConfig) -> BudgetBenchReport {
+    let (direct, direct_us, _) = phase(config, false).await;
+    let (sliced, sliced_us, budget) = phase(config, true).await;
+    BudgetBenchReport {
+        slice_us: config.slice.as_micros() as u32,
+        direct,
+        sliced,
+        direct_us,
+        sliced_us,
+        budget,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles() {
+        let mut hist = LatencyHistogram::new();
+        for _ in 0..98 {
+            hist.record(15);
+        }
+        hist.record(700);
+        hist.record(120_000);
+        let report = hist.report();
+        assert_eq!(report.samples, 100);
+        assert_eq!(report.p50_us, 20);
+        assert_eq!(report.p99_us, 1_000);
+        assert_eq!(report.max_us, 120_000);
+        assert_eq!(hist.percentile(100), 120_000);
+    }
+}
@@ -0,0 +1,187 @@
+//! 协作式时间片预算
+//!
+//! 把长时间的 CPU 密集操作 (压缩、大文件哈希) 切成时间片: 每片用完预算后主动让出，
+//! 同一执行器上的其他任务最多等待一个时间片，从而约束执行器的延迟尾部。
+//!
+//! # 特性
+//! - 以微秒配置每片预算，时钟每隔若干次 `tick` 才读取一次以降低开销
+//! - `run` / `for_each_chunk` 封装常见的分步循环
+//! - 统计让出次数与最长时间片，用于校准预算 (见 `tasks::bench`)
+//!
+//! 预算只约束让出点之间的间隔: 单个工作单元的耗时应明显小于预算，
+//! 否则实际时间片 = 预算 + 一个工作单元。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::tasks::budget::Budget;
+//!
+//! let mut budget = Budget::from_micros(500);
+//! let mut hasher = Sha256::new();
+//! budget.for_each_chunk(&image, 1024, |chunk| hasher.update(chunk)).await;
+//!
+//! // 自定义步进: 返回 Some 时结束
+//! let compressed = budget.run(|| encoder.step()).await;
+//! log_info!("max slice {} us", budget.stats().max_slice_us);
+//! ```
+
+use embassy_time::{Duration, Instant};
+
+/// 默认时间片预算 (μs)
+pub const DEFAULT_SLICE_US: u64 = 500;
+
+/// 预算统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BudgetStats {
+    /// 让出次数
+    pub yields: u32,
+    /// 已结束时间片的最长耗时 (μs)
+    pub max_slice_us: u32,
+    /// 已结束时间片的总耗时 (μs)
+    pub busy_us: u32,
+}
+
+/// 时间片预算
+#[derive(Debug)]
+pub struct Budget {
+    slice: Duration,
+    /// 每隔多少次 `tick` 读取一次时钟
+    check_every: u16,
+    countdown: u16,
+    slice_start: Instant,
+    stats: BudgetStats,
+}
+
+impl Budget {
+    /// 创建预算，当前时刻开始第一个时间片
+    pub fn new(slice: Duration) -> Self {
+        Self { slice, check_every: 1, countdown: 1, slice_start: Instant::now(), stats: BudgetStats::default() }
+    }
+
+    /// 以微秒创建预算
+    pub fn from_micros(us: u64) -> Self {
+        Self::new(Duration::from_micros(us))
+    }
+
+    /// 每 `ticks` 次 `tick` 才检查一次时钟 (工作单元很小时使用)
+    pub fn with_check_every(mut self, ticks: u16) -> Self {
+        self.check_every = ticks.max(1);
+        self.countdown = self.check_every;
+        self
+    }
+
+    /// 每片预算
+    pub fn slice(&self) -> Duration {
+        self.slice
+    }
+
+    /// 当前时间片已用时间
+    pub fn elapsed(&self) -> Duration {
+        Instant::now().saturating_duration_since(self.slice_start)
+    }
+
+    /// 当前时间片是否已用完
+    pub fn exhausted(&self) -> bool {
+        self.elapsed() >= self.slice
+    }
+
+    /// 开始新的时间片 (例如在等待 I/O 之后，等待期间已经让出过 CPU)
+    pub fn restart(&mut self) {
+        self.slice_start = Instant::now();
+        self.countdown = self.check_every;
+    }
+
+    /// 在工作单元之间调用: 预算用完时让出
+    ///
+    /// # 返回
+    /// 本次是否让出
+    pub async fn tick(&mut self) -> bool {
+        if self.countdown > 1 {
+            self.countdown -= 1;
+            return false;
+        }
+        self.countdown = self.check_every;
+        if !self.exhausted() {
+            return false;
+        }
+        self.yield_now().await;
+        true
+    }
+
+    /// 立即结束当前时间片并让出
+    pub async fn yield_now(&mut self) {
+        self.close_slice();
+        self.stats.yields = self.stats.yields.saturating_add(1);
+        embassy_futures::yield_now().await;
+        self.restart();
+    }
+
+    /// 反复调用 `step` 直到其返回 `Some`，每步之间按预算让出
+    pub async fn run<T>(&mut self, mut step: impl FnMut() -> Option<T>) -> T {
+        loop {
+            if let Some(value) = step() {
+                return value;
+            }
+            self.tick().await;
+        }
+    }
+
+    /// 按 `chunk` 字节分块处理数据，每块之间按预算让出
+    pub async fn for_each_chunk(&mut self, data: &[u8], chunk: usize, mut f: impl FnMut(&[u8])) {
+        for part in data.chunks(chunk.max(1)) {
+            f(part);
+            self.tick().await;
+        }
+    }
+
+    /// 统计 (不含当前未结束的时间片)
+    pub fn stats(&self) -> BudgetStats {
+        self.stats
+    }
+
+    /// 结束当前时间片并返回统计
+    pub fn finish(mut self) -> BudgetStats {
+        self.close_slice();
+        self.stats
+    }
+
+    fn close_slice(&mut self) {
+        let used = self.elapsed().as_micros().min(u32::MAX as u64) as u32;
+        self.stats.max_slice_us = self.stats.max_slice_us.max(used);
+        self.stats.busy_us = self.stats.busy_us.saturating_add(used);
+    }
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Self::from_micros(DEFAULT_SLICE_US)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_interval() {
+        // 零预算: 每次检查都会让出，让出次数只取决于检查间隔
+        let mut budget = Budget::new(Duration::from_ticks(0)).with_check_every(4);
+        let yielded = embassy_futures::block_on(async {
+            let mut yielded = 0;
+            for _ in 0..10 {
+                yielded += budget.tick().await as u32;
+            }
+            yielded
+        });
+        assert_eq!(yielded, 2);
+        assert_eq!(budget.stats().yields, 2);
+
+        let mut steps = 0;
+        let value = embassy_futures::block_on(budget.run(|| {
+            steps += 1;
+            (steps == 8).then_some(steps * 2)
+        }));
+        assert_eq!(value, 16);
+        assert_eq!(budget.finish().yields, 4);
+    }
+}
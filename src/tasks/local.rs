@@ -0,0 +1,263 @@
+//! 任务局部存储
+//!
+//! 按任务 ID 为每个任务保存一个小的固定大小槽位 (错误上下文、随机数状态、
+//! 内存区指针等)，深层调用无需逐层传参:
+//! - 任务 ID 取自执行器的任务控制块地址 (waker 数据指针)，任务存续期间唯一
+//! - 任务主体用 `scoped` 包装后，同步代码也可通过 `TaskId::current` 取得当前任务
+//!   (每核记录正在轮询的任务，中断执行器抢占时嵌套保存/恢复)
+//! - 槽位容量在编译期固定，首次访问时按初始化函数创建
+//!
+//! 任务结束后其控制块可能被同一任务池复用，退出前应调用 `release_current`
+//! 释放槽位，避免新任务读到旧数据。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::tasks::local::{self, TaskLocal};
+//!
+//! static ERROR_CONTEXT: TaskLocal<&'static str, 8> = TaskLocal::new(|| "");
+//!
+//! fn parse_frame(buf: &[u8]) -> Result<Frame, Error> {
+//!     let _ = ERROR_CONTEXT.with(|ctx| *ctx = "parse_frame");
+//!     // ...
+//! }
+//!
+//! #[embassy_executor::task]
+//! async fn modbus_task() {
+//!     local::scoped(async {
+//!         loop { /* ... */ }
+//!     })
+//!     .await
+//! }
+//! ```
+
+use core::cell::RefCell;
+use core::fmt;
+use core::future::{poll_fn, Future};
+use core::pin::Pin;
+use core::sync::atomic::{AtomicU32, Ordering};
+use core::task::{Context, Poll, Waker};
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use heapless::Vec;
+
+use super::multicore::CoreId;
+
+/// 每核正在轮询的 `scoped` 任务 (0 = 无)
+static CURRENT: [AtomicU32; 2] = [AtomicU32::new(0), AtomicU32::new(0)];
+
+// ===== 错误类型 =====
+
+/// 任务局部存储错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalError {
+    /// 不在 `scoped` 任务中
+    NoTask,
+    /// 槽位已满
+    Full,
+    /// 槽位正被访问 (闭包内重入)
+    Busy,
+}
+
+impl fmt::Display for LocalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoTask => write!(f, "Not inside a scoped task"),
+            Self::Full => write!(f, "Task-local slots exhausted"),
+            Self::Busy => write!(f, "Task-local slot already borrowed"),
+        }
+    }
+}
+
+// ===== 任务 ID =====
+
+/// 任务标识
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TaskId(u32);
+
+impl TaskId {
+    fn from_waker(waker: &Waker) -> Self {
+        Self(waker.data() as usize as u32)
+    }
+
+    /// 从原始值创建 (如跟踪日志中的任务 ID)
+    pub const fn from_raw(raw: u32) -> Self {
+        Self(raw)
+    }
+
+    /// 原始值
+    pub const fn as_u32(self) -> u32 {
+        self.0
+    }
+
+    /// 当前 `scoped` 任务的 ID (同步代码可用)
+    pub fn current() -> Option<Self> {
+        match CURRENT[CoreId::current() as usize].load(Ordering::Relaxed) {
+            0 => None,
+            id => Some(Self(id)),
+        }
+    }
+
+    /// 当前任务的 ID (异步上下文，无需 `scoped`)
+    pub async fn of_current() -> Self {
+        poll_fn(|cx| Poll::Ready(Self::from_waker(cx.waker()))).await
+    }
+}
+
+impl fmt::Display for TaskId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "task@{:08x}", self.0)
+    }
+}
+
+// ===== 作用域 =====
+
+/// 标记当前任务的 Future 包装 (见 `scoped`)
+pub struct Scoped<F> {
+    inner: F,
+}
+
+/// 包装任务主体: 每次轮询期间 `TaskId::current` 返回该任务
+pub fn scoped<F: Future>(inner: F) -> Scoped<F> {
+    Scoped { inner }
+}
+
+impl<F: Future> Future for Scoped<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let current = &CURRENT[CoreId::current() as usize];
+        let previous = current.swap(TaskId::from_waker(cx.waker()).0, Ordering::Relaxed);
+        // SAFETY: `inner` 是结构性固定字段，`Scoped` 不会移出它
+        let inner = unsafe { self.map_unchecked_mut(|s| &mut s.inner) };
+        let result = inner.poll(cx);
+        current.store(previous, Ordering::Relaxed);
+        result
+    }
+}
+
+// ===== 局部存储 =====
+
+/// 任务局部存储 (最多 `N` 个任务)
+///
+/// 访问闭包在临界区内执行，应保持简短。
+pub struct TaskLocal<T, const N: usize> {
+    init: fn() -> T,
+    slots: BlockingMutex<CriticalSectionRawMutex, RefCell<Vec<(TaskId, T), N>>>,
+}
+
+impl<T, const N: usize> TaskLocal<T, N> {
+    /// 创建 (`init` 在任务首次访问时生成初值)
+    pub const fn new(init: fn() -> T) -> Self {
+        Self { init, slots: BlockingMutex::new(RefCell::new(Vec::new())) }
+    }
+
+    /// 访问当前 `scoped` 任务的槽位
+    pub fn with<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, LocalError> {
+        self.with_task(TaskId::current().ok_or(LocalError::NoTask)?, f)
+    }
+
+    /// 访问指定任务的槽位 (不存在时创建)
+    pub fn with_task<R>(&self, id: TaskId, f: impl FnOnce(&mut T) -> R) -> Result<R, LocalError> {
+        self.slots.lock(|slots| {
+            let mut slots = slots.try_borrow_mut().map_err(|_| LocalError::Busy)?;
+            let index = match slots.iter().position(|(owner, _)| *owner == id) {
+                Some(index) => index,
+                None => {
+                    slots.push((id, (self.init)())).map_err(|_| LocalError::Full)?;
+                    slots.len() - 1
+                }
+            };
+            Ok(f(&mut slots[index].1))
+        })
+    }
+
+    /// 读取当前任务的值 (尚未创建时返回初值但不占用槽位)
+    pub fn get(&self) -> T
+    where
+        T: Copy,
+    {
+        let Some(id) = TaskId::current() else {
+            return (self.init)();
+        };
+        self.slots.lock(|slots| {
+            slots
+                .try_borrow()
+                .ok()
+                .and_then(|slots| slots.iter().find(|(owner, _)| *owner == id).map(|(_, v)| *v))
+                .unwrap_or_else(self.init)
+        })
+    }
+
+    /// 设置当前任务的值
+    pub fn set(&self, value: T) -> Result<(), LocalError> {
+        self.with(|slot| *slot = value)
+    }
+
+    /// 释放指定任务的槽位，返回其值
+    pub fn release(&self, id: TaskId) -> Option<T> {
+        self.slots.lock(|slots| {
+            let mut slots = slots.try_borrow_mut().ok()?;
+            let index = slots.iter().position(|(owner, _)| *owner == id)?;
+            Some(slots.swap_remove(index).1)
+        })
+    }
+
+    /// 释放当前任务的槽位 (任务退出前调用)
+    pub fn release_current(&self) -> Option<T> {
+        self.release(TaskId::current()?)
+    }
+
+    /// 已占用的槽位数
+    pub fn len(&self) -> usize {
+        self.slots.lock(|slots| slots.try_borrow().map_or(0, |s| s.len()))
+    }
+
+    /// 是否没有任务占用槽位
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 槽位容量
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::task::{RawWaker, RawWakerVTable};
+
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(|p| RawWaker::new(p, &VTABLE), |_| {}, |_| {}, |_| {});
+
+    /// 以 `id` 作为 waker 数据指针轮询一次
+    fn poll_as<F: Future>(id: usize, fut: Pin<&mut F>) -> Poll<F::Output> {
+        let waker = unsafe { Waker::from_raw(RawWaker::new(id as *const (), &VTABLE)) };
+        fut.poll(&mut Context::from_waker(&waker))
+    }
+
+    #[test]
+    fn test_task_local_slots() {
+        static COUNTER: TaskLocal<u32, 2> = TaskLocal::new(|| 10);
+
+        let mut a = core::pin::pin!(scoped(async {
+            assert_eq!(TaskId::current(), Some(TaskId::from_raw(0x100)));
+            COUNTER.with(|n| *n += 1).unwrap();
+            COUNTER.get()
+        }));
+        assert_eq!(poll_as(0x100, a.as_mut()), Poll::Ready(11));
+        assert_eq!(TaskId::current(), None);
+        assert_eq!(COUNTER.with(|_| ()), Err(LocalError::NoTask));
+
+        let (first, second) = (TaskId::from_raw(0x100), TaskId::from_raw(0x200));
+        assert_eq!(COUNTER.with_task(second, |n| *n), Ok(10));
+        assert_eq!(COUNTER.with_task(TaskId::from_raw(0x300), |_| ()), Err(LocalError::Full));
+        let nested = COUNTER.with_task(first, |_| COUNTER.with_task(first, |_| ()));
+        assert_eq!(nested, Ok(Err(LocalError::Busy)));
+
+        assert_eq!(COUNTER.release(first), Some(11));
+        assert_eq!(COUNTER.len(), 1);
+    }
+}
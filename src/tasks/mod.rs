@@ -4,7 +4,22 @@
 //! - `critical`: 高优先级实时任务 (IRAM 执行)
 //! - `normal`: 普通优先级任务
 //! - `multicore`: 双核调度支持
+//! - `workqueue`: 工作队列 (耗时计算卸载到专用执行器)
+//! - `budget`: 协作式时间片预算 (长时间计算按微秒预算主动让出)
+//! - `bench`: 调度延迟基准 (直接执行与分片执行对比)
+//! - `local`: 任务局部存储 (按任务 ID 的固定大小槽位)
+//! - `spawn`: 受监管的任务生成 (返回错误、按种类计数、内存紧张时降级舍弃可选任务)
+
+#![cfg_attr(
+    all(feature = "panic-free", not(test)),
+    deny(clippy::unwrap_used, clippy::expect_used, clippy::panic, clippy::unreachable, clippy::todo, clippy::unimplemented)
+)]
 
 pub mod critical;
 pub mod normal;
 pub mod multicore;
+pub mod workqueue;
+pub mod budget;
+pub mod bench;
+pub mod local;
+pub mod spawn;
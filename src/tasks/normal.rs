@@ -13,6 +13,7 @@ use esp_hal::gpio::Output;
 
 use crate::util::log::*;
 use crate::tasks::critical::{get_sensor_value, get_sample_count, wait_sensor_data};
+use crate::tasks::spawn::{Class, Lease, TaskKind};
 use crate::sync::primitives::CriticalSignal;
 
 // ===== 任务间通信信号 =====
@@ -109,14 +110,19 @@ pub async fn led_blink_task(mut led: Output<'static>) {
 }
 
 // ===== 低优先级任务: 后台处理 =====
+/// 后台任务种类 (可舍弃，单实例)
+pub static BACKGROUND: TaskKind = TaskKind::new("background", Class::Optional, 1);
+
 /// 后台维护任务
 ///
 /// 运行在主执行器，执行非关键的后台操作:
 /// - 系统状态监控
 /// - 内存统计
 /// - 日志聚合
+///
+/// 可舍弃任务: 内存紧张进入降级模式时退出
 #[embassy_executor::task]
-pub async fn background_task() {
+pub async fn background_task(lease: Lease) {
     log_info!("Background task started");
     
     let mut iteration: u64 = 0;
@@ -125,6 +131,11 @@ pub async fn background_task() {
         // 等待传感器批量数据就绪
         let latest_value = wait_sensor_data().await;
         
+        if lease.is_shed() {
+            log_warn!("Background task shed ({} mode)", crate::tasks::spawn::mode());
+            return;
+        }
+        
         iteration += 1;
         
         // 每次收到信号时输出状态
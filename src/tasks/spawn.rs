@@ -0,0 +1,425 @@
+//! 受监管的任务生成
+//!
+//! `Spawner::must_spawn` 在执行器任务区耗尽时直接 panic。本模块提供返回错误的生成接口，
+//! 并按任务种类记账、在内存紧张时降级:
+//! - `TaskKind`: 任务种类 (名称、重要性、并发上限)，统计运行中/已生成/被拒绝次数
+//! - `Lease`: 随任务参数移入任务主体，任务结束时自动归还运行计数
+//! - `Mode`: 运行模式，降级时拒绝生成可舍弃的任务，已运行的任务通过
+//!   `Lease::is_shed` 得知应主动退出 (先舍弃 `Optional`，再舍弃 `Essential`)
+//! - `PressurePolicy`: 按空闲堆内存 (带回滞) 切换模式，`monitor` 周期性评估
+//!
+//! 生成失败 (任务区耗尽) 本身即视为内存压力，至少进入 `Degraded`。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::tasks::spawn::{self, Class, Lease, PressurePolicy, TaskKind};
+//!
+//! static TELEMETRY: TaskKind = TaskKind::new("telemetry", Class::Optional, 1);
+//!
+//! #[embassy_executor::task]
+//! async fn telemetry_task(lease: Lease) {
+//!     while !lease.is_shed() {
+//!         publish_metrics().await;
+//!         Timer::after_secs(10).await;
+//!     }
+//! }
+//!
+//! if let Err(e) = spawn::spawn(spawner, &TELEMETRY, telemetry_task) {
+//!     log_warn!("telemetry not started: {}", e);
+//! }
+//! spawner.must_spawn(pressure_task()); // spawn::monitor(PressurePolicy::new(), Duration::from_secs(1))
+//! ```
+
+use core::cell::RefCell;
+use core::fmt;
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU8, Ordering};
+
+use embassy_executor::{SpawnToken, Spawner};
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_time::{Duration, Timer};
+use heapless::Vec;
+
+use crate::util::log::*;
+
+/// 登记表最多容纳的任务种类
+pub const MAX_KINDS: usize = 16;
+
+// ===== 错误类型 =====
+
+/// 生成错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpawnError {
+    /// 执行器任务区或任务池耗尽
+    Exhausted,
+    /// 该种类运行中的任务已达上限
+    LimitReached,
+    /// 当前运行模式舍弃该种类
+    Shed,
+}
+
+impl fmt::Display for SpawnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exhausted => write!(f, "Task arena exhausted"),
+            Self::LimitReached => write!(f, "Task limit reached"),
+            Self::Shed => write!(f, "Task class shed in current mode"),
+        }
+    }
+}
+
+// ===== 运行模式 =====
+
+/// 任务重要性 (决定降级时的舍弃顺序)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Class {
+    /// 可舍弃 (遥测、诊断等)，最先舍弃
+    Optional,
+    /// 重要，仅在 `Minimal` 模式下舍弃
+    Essential,
+    /// 关键，从不舍弃
+    Critical,
+}
+
+/// 运行模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Mode {
+    /// 正常
+    Normal = 0,
+    /// 降级: 舍弃 `Optional`
+    Degraded = 1,
+    /// 最小: 只保留 `Critical`
+    Minimal = 2,
+}
+
+impl Mode {
+    /// 该模式是否舍弃某类任务
+    pub const fn sheds(&self, class: Class) -> bool {
+        match self {
+            Self::Normal => false,
+            Self::Degraded => matches!(class, Class::Optional),
+            Self::Minimal => !matches!(class, Class::Critical),
+        }
+    }
+
+    /// 模式名称
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Degraded => "degraded",
+            Self::Minimal => "minimal",
+        }
+    }
+
+    const fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Self::Normal,
+            1 => Self::Degraded,
+            _ => Self::Minimal,
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+static MODE: AtomicU8 = AtomicU8::new(Mode::Normal as u8);
+
+/// 当前运行模式
+pub fn mode() -> Mode {
+    Mode::from_u8(MODE.load(Ordering::Acquire))
+}
+
+/// 设置运行模式 (返回之前的模式)
+pub fn set_mode(mode: Mode) -> Mode {
+    let previous = Mode::from_u8(MODE.swap(mode as u8, Ordering::AcqRel));
+    if previous != mode {
+        log_warn!("Task mode: {} -> {}", previous, mode);
+    }
+    previous
+}
+
+/// 至少进入某模式 (不会降低当前模式)
+pub fn escalate(mode: Mode) {
+    let previous = Mode::from_u8(MODE.fetch_max(mode as u8, Ordering::AcqRel));
+    if previous < mode {
+        log_warn!("Task mode: {} -> {}", previous, mode);
+    }
+}
+
+// ===== 内存压力 =====
+
+/// 内存压力策略 (空闲堆内存阈值，字节)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PressurePolicy {
+    /// 低于此值进入 `Degraded`
+    pub degrade_below: usize,
+    /// 低于此值进入 `Minimal`
+    pub minimal_below: usize,
+    /// 高于此值才恢复 `Normal` (回滞，避免在阈值附近反复切换)
+    pub recover_above: usize,
+}
+
+impl PressurePolicy {
+    /// 默认策略: 16KB 降级、4KB 最小、24KB 恢复
+    pub const fn new() -> Self {
+        Self { degrade_below: 16 * 1024, minimal_below: 4 * 1024, recover_above: 24 * 1024 }
+    }
+
+    /// 设置降级阈值
+    pub const fn with_degrade_below(mut self, bytes: usize) -> Self {
+        self.degrade_below = bytes;
+        self
+    }
+
+    /// 设置最小模式阈值
+    pub const fn with_minimal_below(mut self, bytes: usize) -> Self {
+        self.minimal_below = bytes;
+        self
+    }
+
+    /// 设置恢复阈值
+    pub const fn with_recover_above(mut self, bytes: usize) -> Self {
+        self.recover_above = bytes;
+        self
+    }
+
+    /// 根据空闲内存计算新模式
+    ///
+    /// 压力升高时立即升级；压力缓解时逐级恢复，且需越过对应阈值的回滞区。
+    pub fn evaluate(&self, current: Mode, free: usize) -> Mode {
+        if free < self.minimal_below {
+            Mode::Minimal
+        } else if free < self.degrade_below {
+            current.max(Mode::Degraded)
+        } else if free >= self.recover_above {
+            Mode::Normal
+        } else {
+            // 回滞区: 可从 Minimal 恢复到 Degraded，但不直接回到 Normal
+            current.min(Mode::Degraded)
+        }
+    }
+}
+
+impl Default for PressurePolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 按当前空闲堆内存更新模式
+pub fn update_pressure(policy: &PressurePolicy) -> Mode {
+    let next = policy.evaluate(mode(), esp_alloc::HEAP.free());
+    set_mode(next);
+    next
+}
+
+/// 内存压力监测任务主体 (不返回)
+pub async fn monitor(policy: PressurePolicy, interval: Duration) -> ! {
+    loop {
+        update_pressure(&policy);
+        Timer::after(interval).await;
+    }
+}
+
+// ===== 任务种类 =====
+
+/// 种类统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KindStats {
+    /// 种类名称
+    pub name: &'static str,
+    /// 重要性
+    pub class: Class,
+    /// 运行中的任务数
+    pub running: u8,
+    /// 并发上限
+    pub limit: u8,
+    /// 成功生成次数
+    pub spawned: u32,
+    /// 被拒绝次数 (上限、舍弃或任务区耗尽)
+    pub rejected: u32,
+}
+
+impl fmt::Display for KindStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:?} running={}/{} spawned={} rejected={}",
+            self.name, self.class, self.running, self.limit, self.spawned, self.rejected
+        )
+    }
+}
+
+/// 任务种类
+pub struct TaskKind {
+    name: &'static str,
+    class: Class,
+    limit: u8,
+    running: AtomicU8,
+    spawned: AtomicU32,
+    rejected: AtomicU32,
+    registered: AtomicBool,
+}
+
+static KINDS: BlockingMutex<CriticalSectionRawMutex, RefCell<Vec<&'static TaskKind, MAX_KINDS>>> =
+    BlockingMutex::new(RefCell::new(Vec::new()));
+
+impl TaskKind {
+    /// 创建 (`limit` 为同时运行的最大任务数)
+    pub const fn new(name: &'static str, class: Class, limit: u8) -> Self {
+        Self {
+            name,
+            class,
+            limit,
+            running: AtomicU8::new(0),
+            spawned: AtomicU32::new(0),
+            rejected: AtomicU32::new(0),
+            registered: AtomicBool::new(false),
+        }
+    }
+
+    /// 种类名称
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// 重要性
+    pub fn class(&self) -> Class {
+        self.class
+    }
+
+    /// 运行中的任务数
+    pub fn running(&self) -> u8 {
+        self.running.load(Ordering::Acquire)
+    }
+
+    /// 统计快照
+    pub fn stats(&self) -> KindStats {
+        KindStats {
+            name: self.name,
+            class: self.class,
+            running: self.running(),
+            limit: self.limit,
+            spawned: self.spawned.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 占用一个运行名额
+    ///
+    /// 当前模式舍弃该种类或已达上限时失败。
+    pub fn acquire(&'static self) -> Result<Lease, SpawnError> {
+        self.register();
+        if mode().sheds(self.class) {
+            return Err(self.reject(SpawnError::Shed));
+        }
+        self.running
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| (n < self.limit).then_some(n + 1))
+            .map_err(|_| self.reject(SpawnError::LimitReached))?;
+        Ok(Lease { kind: self })
+    }
+
+    fn reject(&self, error: SpawnError) -> SpawnError {
+        self.rejected.fetch_add(1, Ordering::Relaxed);
+        log_warn!("Spawn {} rejected: {}", self.name, error);
+        error
+    }
+
+    fn register(&'static self) {
+        if self.registered.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        KINDS.lock(|kinds| {
+            if kinds.borrow_mut().push(self).is_err() {
+                log_warn!("Task kind registry full, {} not listed", self.name);
+            }
+        });
+    }
+}
+
+/// 遍历已使用过的任务种类的统计
+pub fn for_each_kind(mut f: impl FnMut(KindStats)) {
+    KINDS.lock(|kinds| kinds.borrow().iter().for_each(|kind| f(kind.stats())));
+}
+
+/// 运行名额 (随参数移入任务主体，释放时归还)
+pub struct Lease {
+    kind: &'static TaskKind,
+}
+
+impl Lease {
+    /// 所属种类
+    pub fn kind(&self) -> &'static TaskKind {
+        self.kind
+    }
+
+    /// 当前模式是否舍弃本任务 (任务应尽快退出)
+    pub fn is_shed(&self) -> bool {
+        mode().sheds(self.kind.class)
+    }
+}
+
+impl Drop for Lease {
+    fn drop(&mut self) {
+        self.kind.running.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+// ===== 生成 =====
+
+/// 受监管地生成任务
+///
+/// `task` 接收运行名额并返回任务令牌 (通常直接传入 `#[embassy_executor::task]` 函数)。
+/// 任务区耗尽时名额随失败的令牌一并释放，并至少进入 `Degraded` 模式。
+pub fn spawn<S>(spawner: Spawner, kind: &'static TaskKind, task: impl FnOnce(Lease) -> SpawnToken<S>) -> Result<(), SpawnError> {
+    let lease = kind.acquire()?;
+    match spawner.spawn(task(lease)) {
+        Ok(()) => {
+            kind.spawned.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+        Err(_) => {
+            escalate(Mode::Degraded);
+            Err(kind.reject(SpawnError::Exhausted))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pressure_hysteresis() {
+        let policy = PressurePolicy::new().with_degrade_below(100).with_minimal_below(50).with_recover_above(150);
+        assert_eq!(policy.evaluate(Mode::Normal, 120), Mode::Normal);
+        assert_eq!(policy.evaluate(Mode::Normal, 90), Mode::Degraded);
+        assert_eq!(policy.evaluate(Mode::Degraded, 40), Mode::Minimal);
+        assert_eq!(policy.evaluate(Mode::Minimal, 70), Mode::Minimal);
+        assert_eq!(policy.evaluate(Mode::Minimal, 120), Mode::Degraded);
+        assert_eq!(policy.evaluate(Mode::Degraded, 120), Mode::Degraded);
+        assert_eq!(policy.evaluate(Mode::Degraded, 200), Mode::Normal);
+
+        assert!(Mode::Degraded.sheds(Class::Optional) && !Mode::Degraded.sheds(Class::Essential));
+        assert!(Mode::Minimal.sheds(Class::Essential) && !Mode::Minimal.sheds(Class::Critical));
+    }
+
+    #[test]
+    fn test_lease_limit_and_release() {
+        static KIND: TaskKind = TaskKind::new("worker", Class::Critical, 2);
+        let a = KIND.acquire().unwrap();
+        let _b = KIND.acquire().unwrap();
+        assert_eq!(KIND.acquire().err(), Some(SpawnError::LimitReached));
+        drop(a);
+        assert_eq!(KIND.running(), 1);
+        assert!(KIND.acquire().is_ok());
+
+        let stats = KIND.stats();
+        assert_eq!((stats.running, stats.rejected), (1, 1));
+    }
+}
@@ -0,0 +1,147 @@
+//! 工作队列
+//!
+//! 将耗时的同步计算 (推理、编码、压缩) 交给专用执行器 (通常在 Core1) 顺序执行，
+//! 提交方异步等待完成，不阻塞本核执行器。
+//!
+//! 工作项以 `&'static dyn WorkItem` 形式提交，队列本身不拷贝数据；
+//! 工作项自行保存输入/输出并在 `execute` 结束时发出完成信号。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::tasks::workqueue::{WorkQueue, WorkItem};
+//!
+//! static CORE1_WQ: WorkQueue<8> = WorkQueue::new();
+//!
+//! // Core1 执行器
+//! #[embassy_executor::task]
+//! async fn core1_worker() {
+//!     CORE1_WQ.run().await;
+//! }
+//!
+//! // Core0
+//! CORE1_WQ.submit(&ENCODE_JOB).await;
+//! ENCODE_JOB.done.wait().await;
+//! ```
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use embassy_time::Instant;
+
+use crate::sync::primitives::CriticalChannel;
+
+/// 工作项
+pub trait WorkItem: Sync {
+    /// 执行 (在工作队列执行器上同步运行)
+    fn execute(&self);
+}
+
+/// 排队中的工作项
+struct Pending {
+    item: &'static dyn WorkItem,
+    enqueued: Instant,
+}
+
+/// 工作队列统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WorkQueueStats {
+    /// 已完成工作项
+    pub completed: u32,
+    /// 提交失败 (队列满)
+    pub rejected: u32,
+    /// 最大执行时间 (μs)
+    pub max_exec_us: u32,
+    /// 平均执行时间 (μs)
+    pub avg_exec_us: u32,
+    /// 最大排队延迟 (μs)
+    pub max_wait_us: u32,
+}
+
+/// 工作队列
+pub struct WorkQueue<const N: usize> {
+    queue: CriticalChannel<Pending, N>,
+    completed: AtomicU32,
+    rejected: AtomicU32,
+    max_exec_us: AtomicU32,
+    total_exec_us: AtomicU32,
+    max_wait_us: AtomicU32,
+}
+
+impl<const N: usize> WorkQueue<N> {
+    /// 创建工作队列
+    pub const fn new() -> Self {
+        Self {
+            queue: CriticalChannel::new(),
+            completed: AtomicU32::new(0),
+            rejected: AtomicU32::new(0),
+            max_exec_us: AtomicU32::new(0),
+            total_exec_us: AtomicU32::new(0),
+            max_wait_us: AtomicU32::new(0),
+        }
+    }
+
+    /// 提交工作项 (队列满时等待)
+    pub async fn submit(&self, item: &'static dyn WorkItem) {
+        self.queue.send(Pending { item, enqueued: Instant::now() }).await;
+    }
+
+    /// 尝试提交工作项
+    ///
+    /// # 返回
+    /// 队列满时返回 `false`
+    pub fn try_submit(&self, item: &'static dyn WorkItem) -> bool {
+        let ok = self.queue.try_send(Pending { item, enqueued: Instant::now() }).is_ok();
+        if !ok {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+        }
+        ok
+    }
+
+    /// 排队中的工作项数量
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// 队列是否为空
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// 统计快照
+    pub fn stats(&self) -> WorkQueueStats {
+        let completed = self.completed.load(Ordering::Relaxed);
+        WorkQueueStats {
+            completed,
+            rejected: self.rejected.load(Ordering::Relaxed),
+            max_exec_us: self.max_exec_us.load(Ordering::Relaxed),
+            avg_exec_us: match completed {
+                0 => 0,
+                n => self.total_exec_us.load(Ordering::Relaxed) / n,
+            },
+            max_wait_us: self.max_wait_us.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 运行工作循环 (不返回)，应在专用执行器上调用
+    pub async fn run(&self) -> ! {
+        loop {
+            let pending = self.queue.receive().await;
+            let started = Instant::now();
+            let wait_us = (started - pending.enqueued).as_micros() as u32;
+            self.max_wait_us.fetch_max(wait_us, Ordering::Relaxed);
+
+            pending.item.execute();
+
+            let exec_us = started.elapsed().as_micros() as u32;
+            self.max_exec_us.fetch_max(exec_us, Ordering::Relaxed);
+            self.total_exec_us.fetch_add(exec_us, Ordering::Relaxed);
+            self.completed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl<const N: usize> Default for WorkQueue<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
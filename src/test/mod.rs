@@ -0,0 +1,11 @@
+//! 设备端测试工具
+//!
+//! 在目标板上运行、用于发布前验证的测试支持 (与主机侧 `cargo test` 无关):
+//! - `soak`: 长时间浸泡测试 (反复执行负载，采样内存水位，检测持续增长)
+//! - `selftest`: 设备自检 (Flash、PSRAM、RTC 等检查项，Shell 触发或 OTA 后门控镜像确认)
+
+pub mod selftest;
+pub mod soak;
+
+pub use selftest::{Check, CheckStatus, Checks, SelfTestReport};
+pub use soak::{Probe, SoakConfig, SoakError, SoakReport, SoakRunner, Workload};
@@ -0,0 +1,456 @@
+//! 设备自检
+//!
+//! 在目标板上执行一组登记的检查项，生成通过/失败报告:
+//! - 内置检查: Flash 草稿块读写、PSRAM 图案测试、RTC 漂移
+//! - 应用检查: WiFi 扫描、传感器在位等由闭包实现 (`check`)
+//! - 检查项以元组组合，依次执行，单项失败不影响后续检查
+//!
+//! 两种运行方式:
+//! - Shell: `selftest run` 请求自检任务 (`service`) 执行，`selftest` 查看最近报告
+//! - OTA 后首次启动: `gate_ota` 在运行镜像未验证过时执行自检，全部通过才调用
+//!   `mark_valid`，否则保持待验证状态，由引导程序在下次复位时回滚
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::test::selftest::{self, check, FlashScratch, PsramPattern, RtcDrift};
+//!
+//! let wifi = check("wifi-scan", || async {
+//!     let found = controller.scan_with(&mut radio, &ScanConfig::default()).await.map_err(|_| "scan failed")?;
+//!     if found.is_empty() { Err("no access points") } else { Ok(()) }
+//! });
+//! let imu = check("imu", || async { imu.who_am_i().await.map(|_| ()).map_err(|_| "not present") });
+//! let mut checks = (FlashScratch::new(&mut storage, SCRATCH_BLOCK), (PsramPattern::new(&mut psram_buf), (wifi, imu)));
+//!
+//! // OTA 后首次启动: 通过才确认新镜像
+//! selftest::gate_ota(&mut checks, &kv, || { let _ = ota.mark_valid(); }).await;
+//!
+//! // 之后由 Shell 按需触发
+//! selftest::service(&mut checks).await
+//! ```
+
+use core::cell::RefCell;
+use core::fmt;
+use core::future::Future;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_time::{Duration, Instant, Timer};
+use heapless::Vec;
+
+use crate::fs::kv::KvStore;
+use crate::fs::storage::FlashStorage;
+use crate::sync::primitives::CriticalSignal;
+use crate::util::log::*;
+
+/// 报告最多容纳的检查项
+pub const MAX_CHECKS: usize = 16;
+
+/// 已验证镜像记录的命名空间
+pub const KV_NAMESPACE: &str = "selftest";
+
+/// 已验证镜像记录的键名 (值为镜像 ELF SHA-256)
+pub const KV_VALIDATED: &str = "validated";
+
+// ===== 检查项 =====
+
+/// 单项结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    /// 通过
+    Pass,
+    /// 失败 (原因)
+    Fail(&'static str),
+    /// 跳过 (原因，如硬件未装配)
+    Skip(&'static str),
+}
+
+impl CheckStatus {
+    /// 是否失败
+    pub const fn is_fail(&self) -> bool {
+        matches!(self, Self::Fail(_))
+    }
+}
+
+impl From<Result<(), &'static str>> for CheckStatus {
+    fn from(result: Result<(), &'static str>) -> Self {
+        match result {
+            Ok(()) => Self::Pass,
+            Err(reason) => Self::Fail(reason),
+        }
+    }
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pass => write!(f, "PASS"),
+            Self::Fail(reason) => write!(f, "FAIL ({})", reason),
+            Self::Skip(reason) => write!(f, "SKIP ({})", reason),
+        }
+    }
+}
+
+/// 自检项
+#[allow(async_fn_in_trait)]
+pub trait Check {
+    /// 检查项名称
+    fn name(&self) -> &'static str;
+
+    /// 执行检查
+    async fn run(&mut self) -> CheckStatus;
+}
+
+/// 一组检查项 (单个检查项或元组组合)
+#[allow(async_fn_in_trait)]
+pub trait Checks {
+    /// 依次执行并写入报告
+    async fn run_all(&mut self, report: &mut SelfTestReport);
+}
+
+impl<C: Check> Checks for C {
+    async fn run_all(&mut self, report: &mut SelfTestReport) {
+        let started = Instant::now();
+        let status = self.run().await;
+        if let CheckStatus::Fail(_reason) = status {
+            log_warn!("Selftest {}: {}", self.name(), _reason);
+        }
+        report.record(self.name(), status, started.elapsed());
+    }
+}
+
+/// 两组检查依次执行 (可嵌套组合更多检查)
+impl<A: Checks, B: Checks> Checks for (A, B) {
+    async fn run_all(&mut self, report: &mut SelfTestReport) {
+        self.0.run_all(report).await;
+        self.1.run_all(report).await;
+    }
+}
+
+/// 闭包检查项 (见 `check`)
+pub struct FnCheck<F> {
+    name: &'static str,
+    f: F,
+}
+
+/// 由异步闭包创建检查项 (`Err` 的原因写入报告)
+pub fn check<F, Fut>(name: &'static str, f: F) -> FnCheck<F>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<(), &'static str>>,
+{
+    FnCheck { name, f }
+}
+
+impl<F, Fut> Check for FnCheck<F>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<(), &'static str>>,
+{
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    async fn run(&mut self) -> CheckStatus {
+        (self.f)().await.into()
+    }
+}
+
+// ===== 内置检查 =====
+
+/// 测试图案: 地址相关，能发现地址线短路与粘连位
+fn pattern(index: usize, seed: u32) -> u32 {
+    (index as u32).wrapping_mul(0x9E37_79B9) ^ seed
+}
+
+/// Flash 草稿块读写 (擦除、写入图案、读回校验、再擦除)
+///
+/// 草稿块内容会被破坏，须使用专门预留的块。
+pub struct FlashScratch<'a> {
+    storage: &'a mut FlashStorage,
+    block: u32,
+}
+
+impl<'a> FlashScratch<'a> {
+    /// 创建 (`block` 为分区内预留的草稿块)
+    pub fn new(storage: &'a mut FlashStorage, block: u32) -> Self {
+        Self { storage, block }
+    }
+
+    fn verify(&mut self) -> Result<(), &'static str> {
+        const CHUNK: usize = 256;
+        let mut buf = [0u8; CHUNK];
+        for (i, word) in buf.chunks_exact_mut(4).enumerate() {
+            word.copy_from_slice(&pattern(i, self.block).to_le_bytes());
+        }
+
+        self.storage.erase_block(self.block).map_err(|_| "erase failed")?;
+        self.storage.program(self.block, 0, &buf).map_err(|_| "program failed")?;
+
+        let mut read = [0u8; CHUNK];
+        self.storage.read_block(self.block, &mut read).map_err(|_| "read failed")?;
+        let result = if read == buf { Ok(()) } else { Err("readback mismatch") };
+        self.storage.erase_block(self.block).map_err(|_| "erase failed")?;
+        result
+    }
+}
+
+impl Check for FlashScratch<'_> {
+    fn name(&self) -> &'static str {
+        "flash"
+    }
+
+    async fn run(&mut self) -> CheckStatus {
+        self.verify().into()
+    }
+}
+
+/// PSRAM 图案测试 (写入地址相关图案及其反码，逐字读回)
+///
+/// PSRAM 分配器不支持释放，缓冲区由调用方启动时分配一次并反复使用。
+pub struct PsramPattern<'a> {
+    buffer: &'a mut [u32],
+}
+
+impl<'a> PsramPattern<'a> {
+    /// 创建 (`buffer` 应位于 PSRAM)
+    pub fn new(buffer: &'a mut [u32]) -> Self {
+        Self { buffer }
+    }
+
+    fn verify(&mut self) -> Result<(), &'static str> {
+        if self.buffer.is_empty() {
+            return Err("empty buffer");
+        }
+        for seed in [0, u32::MAX] {
+            for (i, word) in self.buffer.iter_mut().enumerate() {
+                // volatile 访问，避免编译器省略读回
+                unsafe { core::ptr::write_volatile(word, pattern(i, seed)) };
+            }
+            for (i, word) in self.buffer.iter().enumerate() {
+                if unsafe { core::ptr::read_volatile(word) } != pattern(i, seed) {
+                    return Err("pattern mismatch");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Check for PsramPattern<'_> {
+    fn name(&self) -> &'static str {
+        "psram"
+    }
+
+    async fn run(&mut self) -> CheckStatus {
+        self.verify().into()
+    }
+}
+
+/// RTC 漂移: 在 `interval` 内比较 RTC 与单调时钟的走时
+pub struct RtcDrift<F> {
+    read_us: F,
+    interval: Duration,
+    max_ppm: u32,
+}
+
+impl<F: FnMut() -> Option<u64>> RtcDrift<F> {
+    /// 创建 (`read_us` 读取 RTC 微秒计数，RTC 未装配或未设置时返回 `None`)
+    pub fn new(read_us: F) -> Self {
+        Self { read_us, interval: Duration::from_secs(2), max_ppm: 500 }
+    }
+
+    /// 设置测量时长
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// 设置允许的最大漂移 (ppm)
+    pub fn with_max_ppm(mut self, max_ppm: u32) -> Self {
+        self.max_ppm = max_ppm;
+        self
+    }
+}
+
+/// 漂移 (ppm): RTC 走时相对单调时钟的偏差
+pub fn drift_ppm(rtc_elapsed_us: u64, mono_elapsed_us: u64) -> u32 {
+    if mono_elapsed_us == 0 {
+        return u32::MAX;
+    }
+    let diff = rtc_elapsed_us.abs_diff(mono_elapsed_us) as u128;
+    (diff * 1_000_000 / mono_elapsed_us as u128).min(u32::MAX as u128) as u32
+}
+
+impl<F: FnMut() -> Option<u64>> Check for RtcDrift<F> {
+    fn name(&self) -> &'static str {
+        "rtc"
+    }
+
+    async fn run(&mut self) -> CheckStatus {
+        let (Some(rtc_start), mono_start) = ((self.read_us)(), Instant::now()) else {
+            return CheckStatus::Skip("rtc unavailable");
+        };
+        Timer::after(self.interval).await;
+        let (Some(rtc_end), mono_end) = ((self.read_us)(), Instant::now()) else {
+            return CheckStatus::Fail("rtc read failed");
+        };
+        let Some(rtc_elapsed) = rtc_end.checked_sub(rtc_start) else {
+            return CheckStatus::Fail("rtc went backwards");
+        };
+        let ppm = drift_ppm(rtc_elapsed, (mono_end - mono_start).as_micros());
+        if ppm <= self.max_ppm { CheckStatus::Pass } else { CheckStatus::Fail("drift exceeds limit") }
+    }
+}
+
+// ===== 报告 =====
+
+/// 单项结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckResult {
+    /// 检查项名称
+    pub name: &'static str,
+    /// 结果
+    pub status: CheckStatus,
+    /// 耗时 (毫秒)
+    pub elapsed_ms: u32,
+}
+
+/// 自检报告
+#[derive(Debug, Clone, Default)]
+pub struct SelfTestReport {
+    /// 各项结果 (按执行顺序)
+    pub results: Vec<CheckResult, MAX_CHECKS>,
+    /// 超出容量未记录的检查项数
+    pub dropped: u8,
+}
+
+impl SelfTestReport {
+    /// 创建空报告
+    pub const fn new() -> Self {
+        Self { results: Vec::new(), dropped: 0 }
+    }
+
+    /// 记录一项结果
+    pub fn record(&mut self, name: &'static str, status: CheckStatus, elapsed: Duration) {
+        let result = CheckResult { name, status, elapsed_ms: elapsed.as_millis().min(u32::MAX as u64) as u32 };
+        if self.results.push(result).is_err() {
+            self.dropped = self.dropped.saturating_add(1);
+        }
+    }
+
+    /// 失败项数量
+    pub fn failed(&self) -> usize {
+        self.results.iter().filter(|r| r.status.is_fail()).count()
+    }
+
+    /// 是否全部通过 (跳过不算失败；有未记录的项视为不通过)
+    pub fn passed(&self) -> bool {
+        self.failed() == 0 && self.dropped == 0
+    }
+}
+
+impl fmt::Display for SelfTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for result in &self.results {
+            writeln!(f, "{:<12} {:>6} ms  {}", result.name, result.elapsed_ms, result.status)?;
+        }
+        if self.dropped > 0 {
+            writeln!(f, "({} checks not recorded)", self.dropped)?;
+        }
+        writeln!(f, "{}: {} failed", if self.passed() { "PASS" } else { "FAIL" }, self.failed())
+    }
+}
+
+/// 执行一组检查
+pub async fn run<C: Checks>(checks: &mut C) -> SelfTestReport {
+    let mut report = SelfTestReport::new();
+    checks.run_all(&mut report).await;
+    log_info!("Selftest finished: {} checks, {} failed", report.results.len(), report.failed());
+    report
+}
+
+// ===== Shell 触发 =====
+
+static REQUEST: CriticalSignal<()> = CriticalSignal::new();
+
+static LAST: BlockingMutex<CriticalSectionRawMutex, RefCell<Option<SelfTestReport>>> =
+    BlockingMutex::new(RefCell::new(None));
+
+/// 请求自检任务执行一次
+pub fn request() {
+    REQUEST.signal(());
+}
+
+/// 访问最近一次报告
+pub fn with_last_report<R>(f: impl FnOnce(Option<&SelfTestReport>) -> R) -> R {
+    LAST.lock(|last| f(last.borrow().as_ref()))
+}
+
+fn store(report: &SelfTestReport) {
+    LAST.lock(|last| *last.borrow_mut() = Some(report.clone()));
+}
+
+/// 自检任务主体: 等待请求并执行 (不返回)
+pub async fn service<C: Checks>(checks: &mut C) -> ! {
+    loop {
+        REQUEST.wait().await;
+        let report = run(checks).await;
+        store(&report);
+    }
+}
+
+// ===== OTA 确认 =====
+
+/// 运行镜像是否已通过自检
+pub fn is_validated(kv: &KvStore<'_>) -> bool {
+    let mut sha = [0u8; 32];
+    matches!(kv.get(KV_NAMESPACE, KV_VALIDATED, &mut sha), Ok(32))
+        && &sha == crate::sys::app::running().elf_sha256()
+}
+
+/// OTA 后首次启动的自检门控
+///
+/// 运行镜像未验证过时执行自检: 全部通过则调用 `mark_valid` 并记录镜像摘要，
+/// 否则不确认镜像。已验证过时直接返回 `None`。
+pub async fn gate_ota<C: Checks>(checks: &mut C, kv: &KvStore<'_>, mark_valid: impl FnOnce()) -> Option<SelfTestReport> {
+    if is_validated(kv) {
+        return None;
+    }
+    let report = run(checks).await;
+    store(&report);
+    if report.passed() {
+        mark_valid();
+        if let Err(_e) = kv.set(KV_NAMESPACE, KV_VALIDATED, crate::sys::app::running().elf_sha256()) {
+            log_warn!("Selftest: failed to record validated image: {}", _e);
+        }
+        log_info!("Selftest passed, image marked valid");
+    } else {
+        log_error!("Selftest failed, image left pending verification");
+    }
+    Some(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suite_report() {
+        let mut buffer = [0u32; 64];
+        let mut checks = (
+            PsramPattern::new(&mut buffer),
+            (check("sensor", || async { Err("not present") }), check("wifi", || async { Ok(()) })),
+        );
+        let report = embassy_futures::block_on(run(&mut checks));
+
+        let names: Vec<&str, 4> = report.results.iter().map(|r| r.name).collect();
+        assert_eq!(names.as_slice(), &["psram", "sensor", "wifi"]);
+        assert_eq!(report.results[1].status, CheckStatus::Fail("not present"));
+        assert!(!report.passed());
+        assert_eq!(report.failed(), 1);
+
+        assert_eq!(drift_ppm(1_000_100, 1_000_000), 100);
+        assert_eq!(drift_ppm(999_000, 1_000_000), 1000);
+    }
+}
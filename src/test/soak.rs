@@ -0,0 +1,525 @@
+//! 浸泡测试
+//!
+//! 长时间 (数小时) 反复执行负载 —— 连接/断开、文件创建/删除、内存池分配/释放 ——
+//! 同时定期采样堆、PSRAM、内存池等水位，发现持续增长时立即失败。
+//!
+//! 泄漏判定以"窗口最小值"为准: 每 `window` 个采样取一次最小值 (排除负载执行中的
+//! 瞬时占用)，连续 `growth_windows` 个窗口的最小值都比上一次上升超过 `tolerance`
+//! 即判定为泄漏。窗口最小值回落会清零计数，因此缓存预热等一次性增长不会误报。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::test::soak::{self, FileChurn, PoolChurn, Probe, SoakConfig, SoakRunner};
+//!
+//! let rx_pool = || RX_POOL.stats().allocated;
+//! let fs_blocks = || fs.used_blocks().unwrap_or(0) as usize;
+//! let mut runner = SoakRunner::new(SoakConfig::default().with_duration(Duration::from_secs(4 * 3600)));
+//! runner.add_probe(Probe::HEAP)?;
+//! runner.add_probe(Probe::PSRAM)?;
+//! runner.add_probe(Probe::new("rx-pool", &rx_pool))?;
+//! runner.add_probe(Probe::new("fs-blocks", &fs_blocks))?;
+//!
+//! let wifi = soak::workload("wifi", || async {
+//!     radio.disconnect_async().await?;
+//!     radio.connect_async().await
+//! });
+//! let mut load = (wifi, (FileChurn::new(&fs, "/soak.tmp", 4096), PoolChurn::new(&RX_POOL, 4)));
+//! let report = runner.run(&mut load).await?;
+//! log_info!("{}", report);
+//! ```
+
+use core::fmt;
+use core::future::Future;
+
+use embassy_time::{Duration, Instant, Timer};
+use heapless::Vec;
+
+use crate::fs::littlefs::FileSystem;
+use crate::mem::pool::MemoryPool;
+use crate::util::log::*;
+
+/// 最多采样项数量
+pub const MAX_PROBES: usize = 8;
+
+// ===== 错误类型 =====
+
+/// 浸泡测试错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoakError {
+    /// 负载执行失败 (负载名称)
+    Workload(&'static str),
+    /// 负载失败次数超过上限
+    TooManyFailures(u32),
+    /// 检测到持续增长 (采样项名称、相对基线的增长量)
+    Leak {
+        /// 采样项
+        probe: &'static str,
+        /// 增长量
+        growth: usize,
+    },
+    /// 采样项已满
+    TooManyProbes,
+}
+
+impl fmt::Display for SoakError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Workload(name) => write!(f, "Workload '{}' failed", name),
+            Self::TooManyFailures(n) => write!(f, "Too many workload failures ({})", n),
+            Self::Leak { probe, growth } => write!(f, "Leak detected: '{}' grew by {}", probe, growth),
+            Self::TooManyProbes => write!(f, "Too many probes"),
+        }
+    }
+}
+
+// ===== 负载 =====
+
+/// 浸泡负载
+///
+/// `cycle` 执行一轮完整的"申请-释放"动作，结束时应回到与开始时相同的资源状态。
+#[allow(async_fn_in_trait)]
+pub trait Workload {
+    /// 负载名称
+    fn name(&self) -> &'static str;
+
+    /// 执行一轮
+    async fn cycle(&mut self) -> Result<(), SoakError>;
+}
+
+/// 两个负载依次执行 (可嵌套组合更多负载)
+impl<A: Workload, B: Workload> Workload for (A, B) {
+    fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    async fn cycle(&mut self) -> Result<(), SoakError> {
+        self.0.cycle().await?;
+        self.1.cycle().await
+    }
+}
+
+/// 闭包负载 (见 `workload`)
+pub struct FnWorkload<F> {
+    name: &'static str,
+    f: F,
+}
+
+/// 由异步闭包构造负载，闭包返回 `Err` 视为本轮失败
+pub fn workload<F, Fut, E>(name: &'static str, f: F) -> FnWorkload<F>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<(), E>>,
+{
+    FnWorkload { name, f }
+}
+
+impl<F, Fut, E> Workload for FnWorkload<F>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<(), E>>,
+{
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    async fn cycle(&mut self) -> Result<(), SoakError> {
+        (self.f)().await.map_err(|_| SoakError::Workload(self.name))
+    }
+}
+
+/// 内存池负载: 每轮分配 `batch` 个块后全部释放
+pub struct PoolChurn<'a, T: Default, const N: usize, const BACKEND: u8> {
+    pool: &'a MemoryPool<T, N, BACKEND>,
+    batch: usize,
+}
+
+impl<'a, T: Default, const N: usize, const BACKEND: u8> PoolChurn<'a, T, N, BACKEND> {
+    /// 创建
+    pub fn new(pool: &'a MemoryPool<T, N, BACKEND>, batch: usize) -> Self {
+        Self { pool, batch: batch.min(N) }
+    }
+}
+
+impl<T: Default, const N: usize, const BACKEND: u8> Workload for PoolChurn<'_, T, N, BACKEND> {
+    fn name(&self) -> &'static str {
+        "pool"
+    }
+
+    async fn cycle(&mut self) -> Result<(), SoakError> {
+        let mut held: Vec<_, N> = Vec::new();
+        for _ in 0..self.batch {
+            let block = self.pool.alloc_init(T::default()).map_err(|_| SoakError::Workload("pool"))?;
+            let _ = held.push(block);
+        }
+        drop(held);
+        Ok(())
+    }
+}
+
+/// 文件负载: 每轮创建文件、写入 `size` 字节、关闭后删除
+pub struct FileChurn<'a> {
+    fs: &'a FileSystem,
+    path: &'static str,
+    size: usize,
+}
+
+impl<'a> FileChurn<'a> {
+    /// 创建
+    pub fn new(fs: &'a FileSystem, path: &'static str, size: usize) -> Self {
+        Self { fs, path, size }
+    }
+}
+
+impl Workload for FileChurn<'_> {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    async fn cycle(&mut self) -> Result<(), SoakError> {
+        const CHUNK: [u8; 256] = [0xA5; 256];
+        let fail = |_| SoakError::Workload("file");
+
+        let mut file = self.fs.create(self.path).map_err(fail)?;
+        let mut left = self.size;
+        while left > 0 {
+            let n = left.min(CHUNK.len());
+            file.write_all(&CHUNK[..n]).map_err(fail)?;
+            left -= n;
+        }
+        file.close().map_err(fail)?;
+        self.fs.remove(self.path).map_err(fail)
+    }
+}
+
+// ===== 采样项 =====
+
+/// 水位采样项
+#[derive(Clone, Copy)]
+pub struct Probe<'a> {
+    /// 名称
+    pub name: &'static str,
+    sample: &'a dyn Fn() -> usize,
+}
+
+fn heap_used() -> usize {
+    esp_alloc::HEAP.used()
+}
+
+fn psram_used() -> usize {
+    crate::mem::psram::stats().used
+}
+
+impl<'a> Probe<'a> {
+    /// 堆已用字节数
+    pub const HEAP: Probe<'static> = Probe { name: "heap", sample: &heap_used };
+
+    /// PSRAM 已用字节数
+    pub const PSRAM: Probe<'static> = Probe { name: "psram", sample: &psram_used };
+
+    /// 自定义采样项 (如内存池已分配数、文件系统已用块数)
+    pub const fn new(name: &'static str, sample: &'a dyn Fn() -> usize) -> Self {
+        Self { name, sample }
+    }
+
+    /// 读取当前值
+    pub fn sample(&self) -> usize {
+        (self.sample)()
+    }
+}
+
+// ===== 配置 =====
+
+/// 浸泡测试配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SoakConfig {
+    /// 总时长
+    pub duration: Duration,
+    /// 采样间隔
+    pub sample_interval: Duration,
+    /// 每个窗口的采样数
+    pub window: u32,
+    /// 连续增长多少个窗口判定为泄漏
+    pub growth_windows: u32,
+    /// 单窗口允许的增长量 (不计入连续增长)
+    pub tolerance: usize,
+    /// 允许的负载失败次数 (超过后中止)
+    pub max_failures: u32,
+    /// 每轮负载之间的间隔
+    pub pause: Duration,
+}
+
+impl SoakConfig {
+    /// 默认配置: 4 小时，10 秒采样，5 分钟窗口，连续 30 分钟增长判定泄漏
+    pub const fn new() -> Self {
+        Self {
+            duration: Duration::from_secs(4 * 3600),
+            sample_interval: Duration::from_secs(10),
+            window: 30,
+            growth_windows: 6,
+            tolerance: 64,
+            max_failures: 10,
+            pause: Duration::from_millis(100),
+        }
+    }
+
+    /// 设置总时长
+    pub const fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// 设置采样间隔与窗口大小
+    pub const fn with_sampling(mut self, interval: Duration, window: u32) -> Self {
+        self.sample_interval = interval;
+        self.window = window;
+        self
+    }
+
+    /// 设置泄漏判定 (连续增长窗口数、单窗口容差)
+    pub const fn with_growth(mut self, windows: u32, tolerance: usize) -> Self {
+        self.growth_windows = windows;
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// 设置允许的负载失败次数
+    pub const fn with_max_failures(mut self, max: u32) -> Self {
+        self.max_failures = max;
+        self
+    }
+
+    /// 设置每轮间隔
+    pub const fn with_pause(mut self, pause: Duration) -> Self {
+        self.pause = pause;
+        self
+    }
+}
+
+impl Default for SoakConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ===== 趋势检测 =====
+
+/// 单个采样项的水位趋势
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Trend {
+    /// 第一个窗口的最小值
+    pub baseline: Option<usize>,
+    /// 最近一次采样
+    pub last: usize,
+    /// 峰值
+    pub peak: usize,
+    /// 当前连续增长的窗口数
+    pub streak: u32,
+    /// 上次计入增长的窗口最小值
+    reference: usize,
+    /// 当前窗口最小值
+    window_min: usize,
+    /// 当前窗口已采样数
+    samples: u32,
+}
+
+impl Trend {
+    /// 记录一次采样
+    ///
+    /// # 返回
+    /// 判定为泄漏时返回相对基线的增长量
+    pub fn push(&mut self, value: usize, config: &SoakConfig) -> Option<usize> {
+        self.last = value;
+        self.peak = self.peak.max(value);
+        self.window_min = if self.samples == 0 { value } else { self.window_min.min(value) };
+        self.samples += 1;
+        if self.samples < config.window.max(1) {
+            return None;
+        }
+        self.samples = 0;
+
+        let min = self.window_min;
+        let Some(baseline) = self.baseline else {
+            self.baseline = Some(min);
+            self.reference = min;
+            return None;
+        };
+        if min > self.reference + config.tolerance {
+            self.streak += 1;
+            self.reference = min;
+        } else if min <= self.reference {
+            self.streak = 0;
+            self.reference = min;
+        }
+        (self.streak >= config.growth_windows).then(|| min.saturating_sub(baseline))
+    }
+}
+
+// ===== 报告 =====
+
+/// 单个采样项的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProbeSummary {
+    /// 名称
+    pub name: &'static str,
+    /// 趋势
+    pub trend: Trend,
+}
+
+/// 浸泡测试报告
+#[derive(Debug, Clone)]
+pub struct SoakReport {
+    /// 完成的负载轮数
+    pub cycles: u32,
+    /// 失败轮数
+    pub failures: u32,
+    /// 实际运行时长
+    pub elapsed: Duration,
+    /// 各采样项结果
+    pub probes: Vec<ProbeSummary, MAX_PROBES>,
+}
+
+impl fmt::Display for SoakReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "soak: {} cycles, {} failures in {}",
+            self.cycles,
+            self.failures,
+            crate::util::fmt::Elapsed(self.elapsed)
+        )?;
+        for probe in &self.probes {
+            writeln!(
+                f,
+                "  {:<12} baseline {:>8} last {:>8} peak {:>8}",
+                probe.name,
+                probe.trend.baseline.unwrap_or(probe.trend.last),
+                probe.trend.last,
+                probe.trend.peak
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// ===== 运行器 =====
+
+/// 浸泡测试运行器
+pub struct SoakRunner<'a> {
+    config: SoakConfig,
+    probes: Vec<(Probe<'a>, Trend), MAX_PROBES>,
+}
+
+impl<'a> SoakRunner<'a> {
+    /// 创建
+    pub fn new(config: SoakConfig) -> Self {
+        Self { config, probes: Vec::new() }
+    }
+
+    /// 添加采样项
+    pub fn add_probe(&mut self, probe: Probe<'a>) -> Result<(), SoakError> {
+        self.probes.push((probe, Trend::default())).map_err(|_| SoakError::TooManyProbes)
+    }
+
+    /// 当前配置
+    pub fn config(&self) -> &SoakConfig {
+        &self.config
+    }
+
+    /// 运行至配置的时长结束
+    ///
+    /// 检测到泄漏或失败次数超限时立即以错误返回。
+    pub async fn run<W: Workload>(&mut self, workload: &mut W) -> Result<SoakReport, SoakError> {
+        let start = Instant::now();
+        let deadline = start + self.config.duration;
+        let mut next_sample = start;
+        let (mut cycles, mut failures) = (0u32, 0u32);
+
+        log_info!("soak: start, {} probes", self.probes.len());
+        while Instant::now() < deadline {
+            match workload.cycle().await {
+                Ok(()) => cycles += 1,
+                Err(_e) => {
+                    failures += 1;
+                    log_warn!("soak: cycle {} failed: {}", cycles, _e);
+                    if failures > self.config.max_failures {
+                        log_error!("soak: FAILED after {} workload failures", failures);
+                        return Err(SoakError::TooManyFailures(failures));
+                    }
+                }
+            }
+
+            if Instant::now() >= next_sample {
+                next_sample += self.config.sample_interval;
+                self.sample()?;
+            }
+            Timer::after(self.config.pause).await;
+        }
+
+        let report = self.report(cycles, failures, start.elapsed());
+        log_info!("soak: passed, {} cycles", cycles);
+        Ok(report)
+    }
+
+    /// 采样全部采样项并检查趋势
+    fn sample(&mut self) -> Result<(), SoakError> {
+        for (probe, trend) in self.probes.iter_mut() {
+            if let Some(growth) = trend.push(probe.sample(), &self.config) {
+                log_error!(
+                    "soak: FAILED, '{}' grew for {} windows ({} -> {})",
+                    probe.name,
+                    trend.streak,
+                    trend.baseline.unwrap_or(0),
+                    trend.last
+                );
+                return Err(SoakError::Leak { probe: probe.name, growth });
+            }
+        }
+        Ok(())
+    }
+
+    fn report(&self, cycles: u32, failures: u32, elapsed: Duration) -> SoakReport {
+        SoakReport {
+            cycles,
+            failures,
+            elapsed,
+            probes: self.probes.iter().map(|(probe, trend)| ProbeSummary { name: probe.name, trend: *trend }).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trend_detects_growth() {
+        let config = SoakConfig::new().with_sampling(Duration::from_secs(1), 3).with_growth(3, 8);
+
+        // 瞬时占用不影响窗口最小值；小幅抖动不计入
+        let mut steady = Trend::default();
+        for i in 0..60 {
+            let spike = if i % 3 == 1 { 4096 } else { 0 };
+            assert_eq!(steady.push(1000 + spike + i % 5, &config), None);
+        }
+        assert_eq!(steady.baseline, Some(1000));
+
+        // 每个窗口增长 100，第 4 个窗口 (基线之后连续 3 次增长) 判定泄漏
+        let mut leaking = Trend::default();
+        let mut result = None;
+        for i in 0..12 {
+            result = leaking.push(1000 + (i / 3) * 100, &config);
+        }
+        assert_eq!(result, Some(300));
+    }
+
+    #[test]
+    fn test_trend_resets_on_release() {
+        let config = SoakConfig::new().with_sampling(Duration::from_secs(1), 1).with_growth(3, 0);
+        let mut trend = Trend::default();
+        for value in [100, 200, 300, 150, 250, 350] {
+            assert_eq!(trend.push(value, &config), None);
+        }
+        assert_eq!(trend.push(450, &config), Some(350));
+    }
+}
@@ -0,0 +1,330 @@
+//! 控制回路工具
+//!
+//! 提供 PID 控制器与固定周期控制回路，面向电机、温控等闭环应用。
+//!
+//! # 特性
+//! - 输出限幅 + 积分抗饱和 (积分项限幅)
+//! - 微分作用于测量值并带一阶低通，避免设定值突变引起微分冲击
+//! - 无扰切换: 运行中修改增益不会造成输出跳变
+//! - `ControlLoop` 基于 `Ticker` 固定周期执行并统计抖动
+//!
+//! 控制回路本身不创建任务: 在目标执行器 (例如 Priority3 的
+//! InterruptExecutor) 上的任务中调用 `ControlLoop::run` 即可决定其优先级。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::util::control::{ControlCommand, ControlLoop, Pid, PidGains, Plant};
+//!
+//! static COMMANDS: CriticalSignal<ControlCommand> = CriticalSignal::new();
+//!
+//! #[embassy_executor::task]
+//! async fn motor_task(mut motor: Motor) {
+//!     let pid = Pid::new(PidGains::new(0.8, 2.0, 0.01)).with_output_limits(-1.0, 1.0);
+//!     let mut ctl = ControlLoop::new(pid, Duration::from_millis(1));
+//!     ctl.run(&mut motor, &COMMANDS).await;
+//! }
+//!
+//! // 其他任务中调整设定值
+//! COMMANDS.signal(ControlCommand::Setpoint(1500.0));
+//! ```
+
+use embassy_time::{Duration, Instant, Ticker};
+
+use crate::sync::primitives::CriticalSignal;
+use crate::util::log::*;
+
+// ===== PID 控制器 =====
+
+/// PID 增益
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PidGains {
+    /// 比例增益
+    pub kp: f32,
+    /// 积分增益 (1/s)
+    pub ki: f32,
+    /// 微分增益 (s)
+    pub kd: f32,
+}
+
+impl PidGains {
+    /// 创建增益
+    pub const fn new(kp: f32, ki: f32, kd: f32) -> Self {
+        Self { kp, ki, kd }
+    }
+}
+
+/// PID 控制器
+#[derive(Debug, Clone)]
+pub struct Pid {
+    gains: PidGains,
+    /// 输出下限
+    out_min: f32,
+    /// 输出上限
+    out_max: f32,
+    /// 微分低通系数 (0.0 ~ 1.0，1.0 表示不滤波)
+    d_alpha: f32,
+    /// 积分项 (已乘 ki，保证修改 ki 时无扰)
+    integral: f32,
+    /// 滤波后的微分项
+    derivative: f32,
+    /// 上次测量值
+    last_measurement: Option<f32>,
+    /// 上次误差
+    last_error: f32,
+}
+
+impl Pid {
+    /// 创建 PID 控制器 (默认输出不限幅，微分不滤波)
+    pub const fn new(gains: PidGains) -> Self {
+        Self {
+            gains,
+            out_min: f32::NEG_INFINITY,
+            out_max: f32::INFINITY,
+            d_alpha: 1.0,
+            integral: 0.0,
+            derivative: 0.0,
+            last_measurement: None,
+            last_error: 0.0,
+        }
+    }
+
+    /// 设置输出限幅
+    pub fn with_output_limits(mut self, min: f32, max: f32) -> Self {
+        self.out_min = min;
+        self.out_max = max;
+        self
+    }
+
+    /// 设置微分低通系数
+    ///
+    /// 每次更新 `d = d + alpha * (d_raw - d)`，越小越平滑
+    pub fn with_derivative_filter(mut self, alpha: f32) -> Self {
+        self.d_alpha = alpha.clamp(0.0, 1.0);
+        self
+    }
+
+    /// 当前增益
+    pub fn gains(&self) -> PidGains {
+        self.gains
+    }
+
+    /// 无扰修改增益
+    ///
+    /// 积分项以 "已乘 ki" 形式保存，修改 ki 不影响输出；
+    /// 修改 kp 时将比例项的变化量补偿到积分项中。
+    pub fn set_gains(&mut self, gains: PidGains) {
+        if self.last_measurement.is_some() {
+            self.integral += (self.gains.kp - gains.kp) * self.last_error;
+        }
+        self.gains = gains;
+    }
+
+    /// 重置内部状态
+    pub fn reset(&mut self) {
+        self.integral = 0.0;
+        self.derivative = 0.0;
+        self.last_measurement = None;
+        self.last_error = 0.0;
+    }
+
+    /// 计算一次控制输出
+    ///
+    /// # 参数
+    /// - `setpoint`: 设定值
+    /// - `measurement`: 测量值
+    /// - `dt`: 距上次调用的时间 (秒)
+    pub fn update(&mut self, setpoint: f32, measurement: f32, dt: f32) -> f32 {
+        let error = setpoint - measurement;
+        let p = self.gains.kp * error;
+
+        // 微分作用于测量值 (避免设定值突变的微分冲击)
+        if let Some(last) = self.last_measurement {
+            if dt > 0.0 {
+                let raw = -(measurement - last) / dt;
+                self.derivative += self.d_alpha * (raw - self.derivative);
+            }
+        }
+        let d = self.gains.kd * self.derivative;
+
+        // 积分限幅: 积分项不超过输出范围，饱和解除后能立即回调
+        self.integral = (self.integral + self.gains.ki * error * dt).clamp(self.out_min, self.out_max);
+
+        self.last_measurement = Some(measurement);
+        self.last_error = error;
+
+        (p + self.integral + d).clamp(self.out_min, self.out_max)
+    }
+}
+
+// ===== 控制回路 =====
+
+/// 被控对象
+pub trait Plant {
+    /// 读取测量值
+    fn measure(&mut self) -> f32;
+
+    /// 输出控制量
+    fn actuate(&mut self, output: f32);
+}
+
+/// 控制回路命令 (通过 `CriticalSignal` 从其他任务发送)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ControlCommand {
+    /// 修改设定值
+    Setpoint(f32),
+    /// 无扰修改增益
+    Retune(PidGains),
+    /// 停止回路 (输出 0 后返回)
+    Stop,
+}
+
+/// 周期抖动统计
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JitterStats {
+    /// 周期数
+    pub cycles: u64,
+    /// 最大抖动 (μs)
+    pub max_jitter_us: u64,
+    /// 抖动累计 (μs)
+    pub total_jitter_us: u64,
+    /// 超过一个周期的超时次数
+    pub overruns: u32,
+}
+
+impl JitterStats {
+    /// 平均抖动 (μs)
+    pub fn mean_jitter_us(&self) -> u64 {
+        if self.cycles == 0 {
+            0
+        } else {
+            self.total_jitter_us / self.cycles
+        }
+    }
+
+    fn record(&mut self, interval_us: u64, period_us: u64) {
+        let jitter = interval_us.abs_diff(period_us);
+        self.cycles += 1;
+        self.total_jitter_us += jitter;
+        if jitter > self.max_jitter_us {
+            self.max_jitter_us = jitter;
+        }
+        if interval_us >= period_us * 2 {
+            self.overruns += 1;
+        }
+    }
+}
+
+/// 固定周期控制回路
+pub struct ControlLoop {
+    pid: Pid,
+    period: Duration,
+    setpoint: f32,
+    stats: JitterStats,
+}
+
+impl ControlLoop {
+    /// 创建控制回路
+    pub const fn new(pid: Pid, period: Duration) -> Self {
+        Self {
+            pid,
+            period,
+            setpoint: 0.0,
+            stats: JitterStats {
+                cycles: 0,
+                max_jitter_us: 0,
+                total_jitter_us: 0,
+                overruns: 0,
+            },
+        }
+    }
+
+    /// 设置初始设定值
+    pub fn with_setpoint(mut self, setpoint: f32) -> Self {
+        self.setpoint = setpoint;
+        self
+    }
+
+    /// 抖动统计
+    pub fn stats(&self) -> &JitterStats {
+        &self.stats
+    }
+
+    /// PID 控制器
+    pub fn pid(&self) -> &Pid {
+        &self.pid
+    }
+
+    /// 运行控制回路，直到收到 `ControlCommand::Stop`
+    ///
+    /// `dt` 使用实测间隔而非名义周期，调度抖动不会影响积分/微分精度。
+    pub async fn run<P: Plant>(&mut self, plant: &mut P, commands: &CriticalSignal<ControlCommand>) {
+        let period_us = self.period.as_micros();
+        let mut ticker = Ticker::every(self.period);
+        let mut last = Instant::now();
+
+        log_info!("Control loop started, period {}us", period_us);
+
+        loop {
+            ticker.next().await;
+
+            let now = Instant::now();
+            let interval_us = now.duration_since(last).as_micros();
+            last = now;
+            self.stats.record(interval_us, period_us);
+
+            if let Some(cmd) = commands.try_take() {
+                match cmd {
+                    ControlCommand::Setpoint(sp) => self.setpoint = sp,
+                    ControlCommand::Retune(gains) => self.pid.set_gains(gains),
+                    ControlCommand::Stop => {
+                        plant.actuate(0.0);
+                        log_info!(
+                            "Control loop stopped: {} cycles, max jitter {}us, {} overruns",
+                            self.stats.cycles,
+                            self.stats.max_jitter_us,
+                            self.stats.overruns
+                        );
+                        return;
+                    }
+                }
+            }
+
+            let measurement = plant.measure();
+            let dt = interval_us as f32 / 1_000_000.0;
+            let output = self.pid.update(self.setpoint, measurement, dt);
+            plant.actuate(output);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proportional_only() {
+        let mut pid = Pid::new(PidGains::new(2.0, 0.0, 0.0));
+        assert_eq!(pid.update(10.0, 7.0, 0.01), 6.0);
+    }
+
+    #[test]
+    fn test_anti_windup() {
+        let mut pid = Pid::new(PidGains::new(0.0, 10.0, 0.0)).with_output_limits(-1.0, 1.0);
+        for _ in 0..1000 {
+            pid.update(100.0, 0.0, 0.01);
+        }
+        // 积分未无限增长: 误差反向后输出立即离开上限
+        let out = pid.update(-100.0, 0.0, 0.01);
+        assert!(out < 1.0);
+    }
+
+    #[test]
+    fn test_bumpless_retune() {
+        let mut pid = Pid::new(PidGains::new(1.0, 1.0, 0.0));
+        let before = pid.update(5.0, 3.0, 0.0);
+        pid.set_gains(PidGains::new(3.0, 0.5, 0.0));
+        let after = pid.update(5.0, 3.0, 0.0);
+        assert!((before - after).abs() < 1e-5);
+    }
+}
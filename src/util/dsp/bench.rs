@@ -0,0 +1,172 @@
+//! DSP 内核性能对比
+//!
+//! 使用 CPU 周期计数器 (CCOUNT) 对比库内核与同一算法的朴素标量实现，
+//! 在目标板上运行以获取真实数据。两边都是标量代码 (未使用 `ee.*` SIMD 指令)，
+//! 差异来自循环展开、IRAM 放置与查表旋转因子。
+
+use super::fft::{fft, twiddle, Complex32};
+use super::fir::{dot_f32, dot_f32_scalar, dot_q15, dot_q15_scalar, Fir};
+use super::Filter;
+
+/// 单项对比结果
+#[derive(Debug, Clone, Copy)]
+pub struct KernelBench {
+    /// 内核名称
+    pub name: &'static str,
+    /// 问题规模
+    pub len: usize,
+    /// 朴素标量实现周期数
+    pub scalar_cycles: u32,
+    /// 库实现周期数
+    pub optimized_cycles: u32,
+}
+
+impl KernelBench {
+    /// 加速比 (x100，避免浮点格式化)
+    pub fn speedup_x100(&self) -> u32 {
+        if self.optimized_cycles == 0 {
+            return 0;
+        }
+        (self.scalar_cycles as u64 * 100 / self.optimized_cycles as u64) as u32
+    }
+}
+
+/// 读取 CPU 周期计数器
+#[inline(always)]
+pub fn cycle_count() -> u32 {
+    #[cfg(target_arch = "xtensa")]
+    {
+        let ccount: u32;
+        unsafe {
+            core::arch::asm!("rsr.ccount {0}", out(reg) ccount, options(nostack, preserves_flags));
+        }
+        ccount
+    }
+    #[cfg(not(target_arch = "xtensa"))]
+    {
+        0
+    }
+}
+
+/// 测量闭包的周期数
+#[inline(always)]
+pub fn measure<R>(f: impl FnOnce() -> R) -> (R, u32) {
+    let start = cycle_count();
+    let result = core::hint::black_box(f());
+    (result, cycle_count().wrapping_sub(start))
+}
+
+/// 运行全部内核对比
+///
+/// 使用约 14KB 栈空间，建议在普通优先级任务中调用。
+pub fn run() -> [KernelBench; 4] {
+    const LEN: usize = 256;
+    const TAPS: usize = 32;
+
+    let mut fa = [0.0f32; LEN];
+    let mut fb = [0.0f32; LEN];
+    let mut qa = [0i16; LEN];
+    let mut qb = [0i16; LEN];
+    for i in 0..LEN {
+        fa[i] = i as f32 * 0.01;
+        fb[i] = 1.0 - i as f32 * 0.002;
+        qa[i] = (i as i16).wrapping_mul(97);
+        qb[i] = (i as i16).wrapping_mul(-31);
+    }
+
+    let (_, f_scalar) = measure(|| dot_f32_scalar(&fa, &fb));
+    let (_, f_opt) = measure(|| dot_f32(&fa, &fb));
+    let (_, q_scalar) = measure(|| dot_q15_scalar(&qa, &qb));
+    let (_, q_opt) = measure(|| dot_q15(&qa, &qb));
+
+    let mut taps = [0.0f32; TAPS];
+    taps.copy_from_slice(&fb[..TAPS]);
+    let mut out = [0.0f32; LEN];
+    let (_, fir_scalar_cycles) = measure(|| fir_scalar(&taps, &fa, &mut out));
+    let mut fir = Fir::new(&taps);
+    let (_, fir_cycles) = measure(|| fir.process_slice(&fa, &mut out));
+
+    // 两次变换复用同一缓冲区，每次前重新填充输入
+    let mut data = [Complex32::default(); 1024];
+    let fill = |data: &mut [Complex32]| {
+        for (i, c) in data.iter_mut().enumerate() {
+            *c = Complex32::new((i % 16) as f32, 0.0);
+        }
+    };
+    fill(&mut data);
+    let (_, fft_scalar_cycles) = measure(|| fft_scalar(&mut data));
+    fill(&mut data);
+    let (_, fft_cycles) = measure(|| fft(&mut data));
+
+    [
+        KernelBench { name: "dot_f32", len: LEN, scalar_cycles: f_scalar, optimized_cycles: f_opt },
+        KernelBench { name: "dot_q15", len: LEN, scalar_cycles: q_scalar, optimized_cycles: q_opt },
+        KernelBench { name: "fir_f32_32", len: LEN, scalar_cycles: fir_scalar_cycles, optimized_cycles: fir_cycles },
+        KernelBench { name: "fft_1024", len: 1024, scalar_cycles: fft_scalar_cycles, optimized_cycles: fft_cycles },
+    ]
+}
+
+/// 直接型 FIR (标量参考): 每个输出按定义对输入历史求和，起始处不足的历史按 0 处理
+fn fir_scalar(taps: &[f32], input: &[f32], output: &mut [f32]) {
+    for (n, y) in output.iter_mut().enumerate().take(input.len()) {
+        let mut acc = 0.0;
+        for (k, h) in taps.iter().enumerate().take(n + 1) {
+            acc += h * input[n - k];
+        }
+        *y = acc;
+    }
+}
+
+/// 基 2 FFT (标量参考): 与 `fft` 相同的迭代算法，位于 Flash，逐个蝶形查旋转因子
+fn fft_scalar(data: &mut [Complex32]) {
+    let n = data.len();
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if j > i {
+            data.swap(i, j);
+        }
+    }
+
+    let mut span = 2;
+    while span <= n {
+        let half = span / 2;
+        for start in (0..n).step_by(span) {
+            for k in 0..half {
+                let w = twiddle(k, span);
+                let a = data[start + k];
+                let x = data[start + k + half];
+                let b = Complex32::new(x.re * w.re - x.im * w.im, x.re * w.im + x.im * w.re);
+                data[start + k] = Complex32::new(a.re + b.re, a.im + b.im);
+                data[start + k + half] = Complex32::new(a.re - b.re, a.im - b.im);
+            }
+        }
+        span *= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_references_match_kernels() {
+        let taps = [0.5, -0.25, 0.125, 1.0];
+        let input = [1.0, 2.0, -1.0, 0.5, 3.0, 0.0, -2.0, 1.5];
+        let (mut expected, mut actual) = ([0.0f32; 8], [0.0f32; 8]);
+        fir_scalar(&taps, &input, &mut expected);
+        Fir::new(&taps).process_slice(&input, &mut actual);
+        assert!(expected.iter().zip(&actual).all(|(a, b)| (a - b).abs() < 1e-6));
+
+        let mut reference = [Complex32::default(); 64];
+        for (i, c) in reference.iter_mut().enumerate() {
+            *c = Complex32::new((i % 5) as f32, (i % 3) as f32 * 0.5);
+        }
+        let mut data = reference;
+        fft_scalar(&mut reference);
+        fft(&mut data).unwrap();
+        for (a, b) in reference.iter().zip(&data) {
+            assert!((a.re - b.re).abs() < 1e-3 && (a.im - b.im).abs() < 1e-3);
+        }
+    }
+}
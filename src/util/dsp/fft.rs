@@ -0,0 +1,235 @@
+//! 基 2 复数 FFT (最大 1024 点)
+//!
+//! 旋转因子表在编译期生成并放在 Flash 只读段 (4KB)，运行时无三角函数调用。
+//! 较小点数按步长复用同一张表。
+
+use esp_hal::ram;
+
+/// 最大 FFT 点数
+pub const MAX_FFT_SIZE: usize = 1024;
+
+/// 复数 (f32)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Complex32 {
+    pub re: f32,
+    pub im: f32,
+}
+
+impl Complex32 {
+    /// 创建复数
+    pub const fn new(re: f32, im: f32) -> Self {
+        Self { re, im }
+    }
+
+    /// 模的平方
+    #[inline]
+    pub fn norm_sqr(self) -> f32 {
+        self.re * self.re + self.im * self.im
+    }
+
+    #[inline]
+    fn mul(self, other: Self) -> Self {
+        Self {
+            re: self.re * other.re - self.im * other.im,
+            im: self.re * other.im + self.im * other.re,
+        }
+    }
+}
+
+/// FFT 错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FftError {
+    /// 长度不是 2 的幂或超过 MAX_FFT_SIZE
+    InvalidLength,
+}
+
+impl core::fmt::Display for FftError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FftError::InvalidLength => write!(f, "FFT length must be a power of two <= 1024"),
+        }
+    }
+}
+
+// ===== 编译期旋转因子 =====
+
+/// 编译期正弦 (泰勒级数，|x| <= π 时误差 < 1e-12)
+const fn const_sin(x: f64) -> f64 {
+    let mut term = x;
+    let mut sum = x;
+    let mut n = 1;
+    while n < 20 {
+        term = -term * x * x / ((2 * n) as f64 * (2 * n + 1) as f64);
+        sum += term;
+        n += 1;
+    }
+    sum
+}
+
+/// 编译期余弦
+const fn const_cos(x: f64) -> f64 {
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    let mut n = 1;
+    while n < 20 {
+        term = -term * x * x / ((2 * n - 1) as f64 * (2 * n) as f64);
+        sum += term;
+        n += 1;
+    }
+    sum
+}
+
+const fn build_twiddles() -> [Complex32; MAX_FFT_SIZE / 2] {
+    let mut table = [Complex32::new(0.0, 0.0); MAX_FFT_SIZE / 2];
+    let mut k = 0;
+    while k < MAX_FFT_SIZE / 2 {
+        let angle = 2.0 * core::f64::consts::PI * k as f64 / MAX_FFT_SIZE as f64;
+        table[k] = Complex32::new(const_cos(angle) as f32, -const_sin(angle) as f32);
+        k += 1;
+    }
+    table
+}
+
+/// W_1024^k = exp(-2πik/1024)
+static TWIDDLES: [Complex32; MAX_FFT_SIZE / 2] = build_twiddles();
+
+/// W_n^m (0 <= m < n，n 为 2 的幂且 <= MAX_FFT_SIZE)
+pub(super) fn twiddle(m: usize, n: usize) -> Complex32 {
+    let half = n / 2;
+    let stride = MAX_FFT_SIZE / n;
+    if m < half {
+        TWIDDLES[m * stride]
+    } else {
+        // W_n^(m) = -W_n^(m - n/2)
+        let w = TWIDDLES[(m - half) * stride];
+        Complex32::new(-w.re, -w.im)
+    }
+}
+
+// ===== 变换 =====
+
+/// 原地正向 FFT
+///
+/// # 参数
+/// - `data`: 长度为 2 的幂 (2 ~ 1024) 的复数序列
+pub fn fft(data: &mut [Complex32]) -> Result<(), FftError> {
+    transform(data, false)
+}
+
+/// 原地逆 FFT (含 1/N 归一化)
+pub fn ifft(data: &mut [Complex32]) -> Result<(), FftError> {
+    transform(data, true)?;
+    let scale = 1.0 / data.len() as f32;
+    for c in data.iter_mut() {
+        c.re *= scale;
+        c.im *= scale;
+    }
+    Ok(())
+}
+
+#[ram]
+fn transform(data: &mut [Complex32], inverse: bool) -> Result<(), FftError> {
+    let n = data.len();
+    if n < 2 || n > MAX_FFT_SIZE || !n.is_power_of_two() {
+        return Err(FftError::InvalidLength);
+    }
+
+    bit_reverse(data);
+
+    let mut half = 1;
+    while half < n {
+        let span = half * 2;
+        let stride = MAX_FFT_SIZE / span;
+        for start in (0..n).step_by(span) {
+            for k in 0..half {
+                let mut w = TWIDDLES[k * stride];
+                if inverse {
+                    w.im = -w.im;
+                }
+                let a = data[start + k];
+                let b = data[start + k + half].mul(w);
+                data[start + k] = Complex32::new(a.re + b.re, a.im + b.im);
+                data[start + k + half] = Complex32::new(a.re - b.re, a.im - b.im);
+            }
+        }
+        half = span;
+    }
+    Ok(())
+}
+
+fn bit_reverse(data: &mut [Complex32]) {
+    let n = data.len();
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if j > i {
+            data.swap(i, j);
+        }
+    }
+}
+
+/// 实数序列的功率谱
+///
+/// `scratch` 与 `input` 等长 (2 的幂)，`output` 至少 N/2 + 1 个元素。
+/// 返回写入的频点数。
+pub fn power_spectrum(
+    input: &[f32],
+    scratch: &mut [Complex32],
+    output: &mut [f32],
+) -> Result<usize, FftError> {
+    if scratch.len() != input.len() {
+        return Err(FftError::InvalidLength);
+    }
+    for (c, x) in scratch.iter_mut().zip(input) {
+        *c = Complex32::new(*x, 0.0);
+    }
+    fft(scratch)?;
+
+    let bins = (input.len() / 2 + 1).min(output.len());
+    for (y, c) in output[..bins].iter_mut().zip(scratch.iter()) {
+        *y = c.norm_sqr();
+    }
+    Ok(bins)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn test_twiddle_table() {
+        // W^256 = exp(-iπ/2) = -i
+        assert!(close(TWIDDLES[256].re, 0.0));
+        assert!(close(TWIDDLES[256].im, -1.0));
+        assert!(close(TWIDDLES[0].re, 1.0));
+    }
+
+    #[test]
+    fn test_fft_single_tone() {
+        let mut data = [Complex32::default(); 8];
+        for (i, c) in data.iter_mut().enumerate() {
+            // cos(2π·i/8) -> 频点 1 和 7
+            c.re = [1.0, 0.70710677, 0.0, -0.70710677, -1.0, -0.70710677, 0.0, 0.70710677][i];
+        }
+        fft(&mut data).unwrap();
+        assert!(close(data[1].re, 4.0));
+        assert!(close(data[7].re, 4.0));
+        assert!(close(data[0].norm_sqr(), 0.0));
+        assert!(close(data[2].norm_sqr(), 0.0));
+    }
+
+    #[test]
+    fn test_roundtrip_and_length_check() {
+        let mut data = [Complex32::new(1.0, 0.0), Complex32::new(2.0, 0.0), Complex32::new(3.0, 0.0), Complex32::new(4.0, 0.0)];
+        fft(&mut data).unwrap();
+        ifft(&mut data).unwrap();
+        assert!(close(data[2].re, 3.0));
+
+        let mut bad = [Complex32::default(); 6];
+        assert_eq!(fft(&mut bad), Err(FftError::InvalidLength));
+    }
+}
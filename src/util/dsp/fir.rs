@@ -0,0 +1,194 @@
+//! FIR 滤波器与点积内核
+//!
+//! 状态采用"双倍长度"环形缓冲: 每个样本同时写入 `i` 和 `i + TAPS`，
+//! 使当前窗口总是连续的 `TAPS` 个元素，内层循环无取模和分支。
+
+use esp_hal::ram;
+
+use super::Filter;
+
+// ===== 点积内核 =====
+
+/// f32 点积 (标量参考实现)
+pub fn dot_f32_scalar(a: &[f32], b: &[f32]) -> f32 {
+    let mut acc = 0.0;
+    for (x, y) in a.iter().zip(b.iter()) {
+        acc += x * y;
+    }
+    acc
+}
+
+/// f32 点积 (4 路展开)
+///
+/// 四个独立累加器打断 `madd.s` 的数据依赖，让 LX7 FPU 流水线保持满载。
+#[ram]
+pub fn dot_f32(a: &[f32], b: &[f32]) -> f32 {
+    let len = a.len().min(b.len());
+    let (a, b) = (&a[..len], &b[..len]);
+    let mut acc = [0.0f32; 4];
+
+    let mut ca = a.chunks_exact(4);
+    let mut cb = b.chunks_exact(4);
+    for (x, y) in (&mut ca).zip(&mut cb) {
+        acc[0] += x[0] * y[0];
+        acc[1] += x[1] * y[1];
+        acc[2] += x[2] * y[2];
+        acc[3] += x[3] * y[3];
+    }
+    let mut sum = (acc[0] + acc[1]) + (acc[2] + acc[3]);
+    for (x, y) in ca.remainder().iter().zip(cb.remainder()) {
+        sum += x * y;
+    }
+    sum
+}
+
+/// Q15 点积 (标量参考实现)，返回 Q30 累加值
+pub fn dot_q15_scalar(a: &[i16], b: &[i16]) -> i64 {
+    let mut acc = 0i64;
+    for (x, y) in a.iter().zip(b.iter()) {
+        acc += (*x as i32 * *y as i32) as i64;
+    }
+    acc
+}
+
+/// Q15 点积 (4 路展开)，返回 Q30 累加值
+///
+/// 单个乘积在 i32 中计算 (不会溢出)；4 个乘积之和可能超出 i32，
+/// 因此扩展到 i64 后成组并入 i64 累加器。
+#[ram]
+pub fn dot_q15(a: &[i16], b: &[i16]) -> i64 {
+    let len = a.len().min(b.len());
+    let (a, b) = (&a[..len], &b[..len]);
+    let mut acc = 0i64;
+
+    let mut ca = a.chunks_exact(4);
+    let mut cb = b.chunks_exact(4);
+    for (x, y) in (&mut ca).zip(&mut cb) {
+        let p0 = x[0] as i32 * y[0] as i32;
+        let p1 = x[1] as i32 * y[1] as i32;
+        let p2 = x[2] as i32 * y[2] as i32;
+        let p3 = x[3] as i32 * y[3] as i32;
+        acc += (p0 as i64 + p1 as i64) + (p2 as i64 + p3 as i64);
+    }
+    for (x, y) in ca.remainder().iter().zip(cb.remainder()) {
+        acc += (*x as i32 * *y as i32) as i64;
+    }
+    acc
+}
+
+/// Q15 饱和
+#[inline]
+fn saturate_q15(x: i64) -> i16 {
+    x.clamp(i16::MIN as i64, i16::MAX as i64) as i16
+}
+
+// ===== FIR 滤波器 =====
+
+/// f32 FIR 滤波器
+pub struct Fir<const TAPS: usize> {
+    /// 反转后的系数 (与"最旧 → 最新"的窗口顺序对齐，内核即普通点积)
+    coeffs: [f32; TAPS],
+    /// 双倍长度状态
+    state: [[f32; TAPS]; 2],
+    /// 下一个写入位置
+    pos: usize,
+}
+
+impl<const TAPS: usize> Fir<TAPS> {
+    /// 从冲激响应 h[0..TAPS] 创建
+    pub fn new(taps: &[f32; TAPS]) -> Self {
+        const { assert!(TAPS > 0, "filter must have at least one tap") };
+        let mut coeffs = [0.0; TAPS];
+        for (dst, src) in coeffs.iter_mut().zip(taps.iter().rev()) {
+            *dst = *src;
+        }
+        Self { coeffs, state: [[0.0; TAPS]; 2], pos: 0 }
+    }
+}
+
+impl<const TAPS: usize> Filter<f32> for Fir<TAPS> {
+    #[inline]
+    fn update(&mut self, input: f32) -> f32 {
+        let pos = self.pos;
+        self.state[0][pos] = input;
+        self.state[1][pos] = input;
+        self.pos = if pos + 1 == TAPS { 0 } else { pos + 1 };
+
+        let window = &self.state.as_flattened()[pos + 1..pos + 1 + TAPS];
+        dot_f32(window, &self.coeffs)
+    }
+
+    fn reset(&mut self) {
+        self.state = [[0.0; TAPS]; 2];
+        self.pos = 0;
+    }
+}
+
+/// Q15 定点 FIR 滤波器
+///
+/// 系数与样本均为 Q15，输出饱和到 i16。
+pub struct FirQ15<const TAPS: usize> {
+    coeffs: [i16; TAPS],
+    state: [[i16; TAPS]; 2],
+    pos: usize,
+}
+
+impl<const TAPS: usize> FirQ15<TAPS> {
+    /// 从 Q15 冲激响应创建
+    pub fn new(taps: &[i16; TAPS]) -> Self {
+        const { assert!(TAPS > 0, "filter must have at least one tap") };
+        let mut coeffs = [0; TAPS];
+        for (dst, src) in coeffs.iter_mut().zip(taps.iter().rev()) {
+            *dst = *src;
+        }
+        Self { coeffs, state: [[0; TAPS]; 2], pos: 0 }
+    }
+}
+
+impl<const TAPS: usize> Filter<i16> for FirQ15<TAPS> {
+    #[inline]
+    fn update(&mut self, input: i16) -> i16 {
+        let pos = self.pos;
+        self.state[0][pos] = input;
+        self.state[1][pos] = input;
+        self.pos = if pos + 1 == TAPS { 0 } else { pos + 1 };
+
+        let window = &self.state.as_flattened()[pos + 1..pos + 1 + TAPS];
+        saturate_q15(dot_q15(window, &self.coeffs) >> 15)
+    }
+
+    fn reset(&mut self) {
+        self.state = [[0; TAPS]; 2];
+        self.pos = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fir_impulse_response() {
+        let taps = [0.5, 0.25, 0.125];
+        let mut fir = Fir::new(&taps);
+        let out: [f32; 4] = [1.0, 0.0, 0.0, 0.0].map(|x| fir.update(x));
+        assert_eq!(out, [0.5, 0.25, 0.125, 0.0]);
+    }
+
+    #[test]
+    fn test_dot_kernels_match_scalar() {
+        let a: [i16; 7] = [100, -200, 300, 32767, -32768, 5, 6];
+        let b: [i16; 7] = [7, 6, 5, 4, 3, 2, 1];
+        assert_eq!(dot_q15(&a, &b), dot_q15_scalar(&a, &b));
+
+        let fa = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let fb = [1.0, 1.0, 1.0, 1.0, 2.0];
+        assert_eq!(dot_f32(&fa, &fb), dot_f32_scalar(&fa, &fb));
+    }
+
+    #[test]
+    fn test_fir_q15_passthrough() {
+        let mut fir = FirQ15::new(&[i16::MAX]);
+        assert_eq!(fir.update(1000), 999);
+    }
+}
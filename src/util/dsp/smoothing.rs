@@ -0,0 +1,433 @@
+//! 平滑滤波器实现
+
+use esp_hal::ram;
+
+use super::Filter;
+
+// ===== 滑动平均 =====
+
+/// 定点滑动平均
+///
+/// 维护 `N` 个样本的环形窗口和累加和，每次更新 O(1)。
+/// 窗口未填满时按已有样本数求平均。
+pub struct MovingAverage<const N: usize> {
+    window: [i32; N],
+    sum: i64,
+    index: usize,
+    count: usize,
+}
+
+impl<const N: usize> MovingAverage<N> {
+    /// 创建滑动平均滤波器
+    pub const fn new() -> Self {
+        const { assert!(N > 0, "window must be non-empty") };
+        Self { window: [0; N], sum: 0, index: 0, count: 0 }
+    }
+
+    /// 当前平均值
+    #[inline]
+    pub fn value(&self) -> i32 {
+        if self.count == 0 {
+            0
+        } else {
+            (self.sum / self.count as i64) as i32
+        }
+    }
+
+    /// 窗口是否已填满
+    #[inline]
+    pub fn is_warm(&self) -> bool {
+        self.count == N
+    }
+}
+
+impl<const N: usize> Filter<i32> for MovingAverage<N> {
+    #[inline]
+    fn update(&mut self, input: i32) -> i32 {
+        self.sum -= self.window[self.index] as i64;
+        self.sum += input as i64;
+        self.window[self.index] = input;
+        self.index = if self.index + 1 == N { 0 } else { self.index + 1 };
+        if self.count < N {
+            self.count += 1;
+        }
+        self.value()
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl<const N: usize> Default for MovingAverage<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 浮点滑动平均
+pub struct MovingAverageF32<const N: usize> {
+    window: [f32; N],
+    sum: f32,
+    index: usize,
+    count: usize,
+}
+
+impl<const N: usize> MovingAverageF32<N> {
+    /// 创建滑动平均滤波器
+    pub const fn new() -> Self {
+        const { assert!(N > 0, "window must be non-empty") };
+        Self { window: [0.0; N], sum: 0.0, index: 0, count: 0 }
+    }
+}
+
+impl<const N: usize> Filter<f32> for MovingAverageF32<N> {
+    #[inline]
+    fn update(&mut self, input: f32) -> f32 {
+        self.sum += input - self.window[self.index];
+        self.window[self.index] = input;
+        self.index = if self.index + 1 == N { 0 } else { self.index + 1 };
+        if self.count < N {
+            self.count += 1;
+        }
+        self.sum / self.count as f32
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl<const N: usize> Default for MovingAverageF32<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ===== 指数平滑 =====
+
+/// 定点指数平滑 (alpha = 1 / 2^shift)
+///
+/// 内部状态保留 `shift` 位小数，避免逐次截断误差。
+pub struct Ema {
+    /// 累加器 (Q shift 格式)
+    acc: i64,
+    shift: u8,
+    primed: bool,
+}
+
+impl Ema {
+    /// 创建指数平滑滤波器
+    ///
+    /// # 参数
+    /// - `shift`: alpha = 1 / 2^shift (例如 3 表示 alpha = 0.125)
+    pub const fn new(shift: u8) -> Self {
+        Self { acc: 0, shift, primed: false }
+    }
+
+    /// 当前输出值
+    #[inline]
+    pub fn value(&self) -> i32 {
+        (self.acc >> self.shift) as i32
+    }
+}
+
+impl Filter<i32> for Ema {
+    #[ram]
+    fn update(&mut self, input: i32) -> i32 {
+        if !self.primed {
+            // 首个样本直接作为初值，避免从 0 缓慢爬升
+            self.acc = (input as i64) << self.shift;
+            self.primed = true;
+        } else {
+            self.acc += input as i64 - (self.acc >> self.shift);
+        }
+        self.value()
+    }
+
+    fn reset(&mut self) {
+        self.acc = 0;
+        self.primed = false;
+    }
+}
+
+/// 浮点指数平滑
+pub struct EmaF32 {
+    alpha: f32,
+    state: Option<f32>,
+}
+
+impl EmaF32 {
+    /// 创建指数平滑滤波器 (alpha 取值 0.0 ~ 1.0)
+    pub const fn new(alpha: f32) -> Self {
+        Self { alpha, state: None }
+    }
+}
+
+impl Filter<f32> for EmaF32 {
+    #[inline]
+    fn update(&mut self, input: f32) -> f32 {
+        let next = match self.state {
+            Some(prev) => prev + self.alpha * (input - prev),
+            None => input,
+        };
+        self.state = Some(next);
+        next
+    }
+
+    fn reset(&mut self) {
+        self.state = None;
+    }
+}
+
+// ===== 中值滤波 =====
+
+/// 中值滤波器
+///
+/// 去除脉冲噪声 (尖峰)。窗口 `N` 建议为奇数且较小 (3/5/7)，
+/// 每次更新对窗口副本做插入排序，O(N²) 但 N 很小时常数极低。
+pub struct MedianFilter<T, const N: usize> {
+    window: [T; N],
+    index: usize,
+    count: usize,
+}
+
+impl<T: Copy + Ord + Default, const N: usize> MedianFilter<T, N> {
+    /// 创建中值滤波器
+    pub fn new() -> Self {
+        const { assert!(N > 0, "window must be non-empty") };
+        Self { window: [T::default(); N], index: 0, count: 0 }
+    }
+}
+
+impl<T: Copy + Ord + Default, const N: usize> Filter<T> for MedianFilter<T, N> {
+    fn update(&mut self, input: T) -> T {
+        self.window[self.index] = input;
+        self.index = if self.index + 1 == N { 0 } else { self.index + 1 };
+        if self.count < N {
+            self.count += 1;
+        }
+
+        let mut sorted = self.window;
+        let valid = &mut sorted[..self.count];
+        // 插入排序
+        for i in 1..valid.len() {
+            let mut j = i;
+            while j > 0 && valid[j - 1] > valid[j] {
+                valid.swap(j - 1, j);
+                j -= 1;
+            }
+        }
+        valid[valid.len() / 2]
+    }
+
+    fn reset(&mut self) {
+        self.index = 0;
+        self.count = 0;
+    }
+}
+
+impl<T: Copy + Ord + Default, const N: usize> Default for MedianFilter<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ===== 一维卡尔曼滤波 =====
+
+/// 一维卡尔曼滤波器 (恒定值模型)
+///
+/// 适用于缓慢变化的量 (温度、电池电压等)。
+pub struct Kalman1d {
+    /// 过程噪声方差 Q
+    process_noise: f32,
+    /// 测量噪声方差 R
+    measurement_noise: f32,
+    /// 估计值
+    estimate: f32,
+    /// 估计误差方差 P
+    error: f32,
+    primed: bool,
+}
+
+impl Kalman1d {
+    /// 创建卡尔曼滤波器
+    ///
+    /// # 参数
+    /// - `process_noise`: 过程噪声 Q (越大跟踪越快)
+    /// - `measurement_noise`: 测量噪声 R (越大越平滑)
+    pub const fn new(process_noise: f32, measurement_noise: f32) -> Self {
+        Self {
+            process_noise,
+            measurement_noise,
+            estimate: 0.0,
+            error: 1.0,
+            primed: false,
+        }
+    }
+
+    /// 当前估计值
+    pub fn estimate(&self) -> f32 {
+        self.estimate
+    }
+
+    /// 当前估计误差方差
+    pub fn error_variance(&self) -> f32 {
+        self.error
+    }
+}
+
+impl Filter<f32> for Kalman1d {
+    fn update(&mut self, input: f32) -> f32 {
+        if !self.primed {
+            self.estimate = input;
+            self.error = self.measurement_noise;
+            self.primed = true;
+            return self.estimate;
+        }
+
+        // 预测
+        self.error += self.process_noise;
+        // 更新
+        let gain = self.error / (self.error + self.measurement_noise);
+        self.estimate += gain * (input - self.estimate);
+        self.error *= 1.0 - gain;
+        self.estimate
+    }
+
+    fn reset(&mut self) {
+        self.estimate = 0.0;
+        self.error = 1.0;
+        self.primed = false;
+    }
+}
+
+// ===== 抽取 =====
+
+/// 抽取器: 每 `FACTOR` 个样本输出一个平均值
+///
+/// 平均起到简单抗混叠作用
+pub struct Decimator<const FACTOR: usize> {
+    sum: i64,
+    count: usize,
+}
+
+impl<const FACTOR: usize> Decimator<FACTOR> {
+    /// 创建抽取器
+    pub const fn new() -> Self {
+        const { assert!(FACTOR > 0, "factor must be non-zero") };
+        Self { sum: 0, count: 0 }
+    }
+
+    /// 输入一个样本
+    ///
+    /// # 返回
+    /// 每满 `FACTOR` 个样本返回一次平均值
+    #[inline]
+    pub fn push(&mut self, input: i32) -> Option<i32> {
+        self.sum += input as i64;
+        self.count += 1;
+        if self.count == FACTOR {
+            let out = (self.sum / FACTOR as i64) as i32;
+            self.sum = 0;
+            self.count = 0;
+            Some(out)
+        } else {
+            None
+        }
+    }
+
+    /// 批量抽取
+    ///
+    /// `output` 写满后不再消费会产生输出的样本，未消费的输入留给下一次调用。
+    ///
+    /// # 返回
+    /// (消费的输入样本数, 写入 `output` 的样本数)
+    pub fn process_slice(&mut self, input: &[i32], output: &mut [i32]) -> (usize, usize) {
+        let mut written = 0;
+        for (consumed, &x) in input.iter().enumerate() {
+            if self.count + 1 == FACTOR && written == output.len() {
+                return (consumed, written);
+            }
+            if let Some(y) = self.push(x) {
+                output[written] = y;
+                written += 1;
+            }
+        }
+        (input.len(), written)
+    }
+
+    /// 重置
+    pub fn reset(&mut self) {
+        self.sum = 0;
+        self.count = 0;
+    }
+}
+
+impl<const FACTOR: usize> Default for Decimator<FACTOR> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_moving_average() {
+        let mut avg = MovingAverage::<4>::new();
+        assert_eq!(avg.update(4), 4);
+        assert_eq!(avg.update(8), 6);
+        avg.update(0);
+        avg.update(4);
+        assert!(avg.is_warm());
+        // 窗口: [8, 0, 4, 12] -> 6
+        assert_eq!(avg.update(12), 6);
+    }
+
+    #[test]
+    fn test_median_rejects_spike() {
+        let mut med = MedianFilter::<i32, 3>::new();
+        med.update(10);
+        med.update(10);
+        assert_eq!(med.update(1000), 10);
+        assert_eq!(med.update(11), 11);
+    }
+
+    #[test]
+    fn test_ema_fixed_point() {
+        let mut ema = Ema::new(2);
+        assert_eq!(ema.update(100), 100);
+        // 100 + (0 - 100) / 4 = 75
+        assert_eq!(ema.update(0), 75);
+    }
+
+    #[test]
+    fn test_kalman_converges() {
+        let mut k = Kalman1d::new(0.001, 0.5);
+        let mut out = 0.0;
+        for i in 0..200 {
+            let noise = if i % 2 == 0 { 0.5 } else { -0.5 };
+            out = k.update(20.0 + noise);
+        }
+        assert!((out - 20.0).abs() < 0.2);
+    }
+
+    #[test]
+    fn test_decimator() {
+        let mut dec = Decimator::<4>::new();
+        let mut out = [0i32; 2];
+        let n = dec.process_slice(&[1, 2, 3, 4, 10, 10, 10, 10, 5], &mut out);
+        assert_eq!(n, (9, 2));
+        assert_eq!(out, [2, 10]);
+
+        // 输出已满时停在下一个输出块之前，剩余输入下次继续
+        dec.reset();
+        let input = [1, 2, 3, 4, 10, 10, 10, 10, 5];
+        let (consumed, produced) = dec.process_slice(&input, &mut out[..1]);
+        assert_eq!((consumed, produced, out[0]), (7, 1, 2));
+        assert_eq!(dec.process_slice(&input[consumed..], &mut out[..1]), (2, 1));
+        assert_eq!(out[0], 10);
+    }
+}
@@ -0,0 +1,211 @@
+//! 无堆分配的格式化辅助类型
+//!
+//! 日志、Shell 输出和 HTTP 响应共用同一套显示格式:
+//! - `MacAddr`: `7c:df:a1:00:12:ab`
+//! - `Ipv4`: `192.168.1.20`
+//! - `Bytes`: `512 B`、`1.5 KiB`、`12.0 MiB` (1024 进制，保留一位小数)
+//! - `Elapsed`: `850ms`、`12.3s`、`5m03s`、`2h05m03s`、`3d02h05m`
+//!
+//! 均实现 `Display`，启用 `defmt` 时同时实现 `defmt::Format`，
+//! 可直接用于 `log_info!` 等宏。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::util::fmt::{Bytes, Elapsed, Ipv4, MacAddr};
+//!
+//! log_info!("ip {} mac {}", Ipv4(ip), MacAddr(mac));
+//! writeln!(out, "psram: {} used", Bytes(stats.used as u64))?;
+//! writeln!(out, "up {}", Elapsed::from_secs(Instant::now().as_secs()))?;
+//! ```
+
+use core::fmt;
+
+// ===== MAC 地址 =====
+
+/// MAC 地址 (小写十六进制，`:` 分隔)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MacAddr(pub [u8; 6]);
+
+impl fmt::Display for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [a, b, c, d, e, g] = self.0;
+        write!(f, "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}", a, b, c, d, e, g)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for MacAddr {
+    fn format(&self, f: defmt::Formatter) {
+        let [a, b, c, d, e, g] = self.0;
+        defmt::write!(f, "{=u8:02x}:{=u8:02x}:{=u8:02x}:{=u8:02x}:{=u8:02x}:{=u8:02x}", a, b, c, d, e, g)
+    }
+}
+
+// ===== IPv4 地址 =====
+
+/// IPv4 地址 (点分十进制)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ipv4(pub [u8; 4]);
+
+impl fmt::Display for Ipv4 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [a, b, c, d] = self.0;
+        write!(f, "{}.{}.{}.{}", a, b, c, d)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Ipv4 {
+    fn format(&self, f: defmt::Formatter) {
+        let [a, b, c, d] = self.0;
+        defmt::write!(f, "{=u8}.{=u8}.{=u8}.{=u8}", a, b, c, d)
+    }
+}
+
+// ===== 字节数 =====
+
+/// 字节数 (1024 进制)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bytes(pub u64);
+
+impl Bytes {
+    const UNITS: [&'static str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    /// 拆分为 (整数部分, 十分位, 单位)；小于 1 KiB 时十分位为 `None`
+    fn parts(&self) -> (u64, Option<u64>, &'static str) {
+        let mut unit = 0;
+        let mut scale = 1u64;
+        while unit + 1 < Self::UNITS.len() && self.0 >= scale * 1024 {
+            scale *= 1024;
+            unit += 1;
+        }
+        if unit == 0 {
+            return (self.0, None, Self::UNITS[0]);
+        }
+        // 四舍五入到一位小数，进位到 1024 时换用下一单位
+        let round = |scale: u64| (self.0 as u128 * 10 + scale as u128 / 2) / scale as u128;
+        let mut tenths = round(scale);
+        if tenths >= 10240 && unit + 1 < Self::UNITS.len() {
+            unit += 1;
+            tenths = round(scale * 1024);
+        }
+        ((tenths / 10) as u64, Some((tenths % 10) as u64), Self::UNITS[unit])
+    }
+}
+
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.parts() {
+            (whole, Some(tenth), unit) => write!(f, "{}.{} {}", whole, tenth, unit),
+            (whole, None, unit) => write!(f, "{} {}", whole, unit),
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Bytes {
+    fn format(&self, f: defmt::Formatter) {
+        match self.parts() {
+            (whole, Some(tenth), unit) => defmt::write!(f, "{=u64}.{=u64} {=str}", whole, tenth, unit),
+            (whole, None, unit) => defmt::write!(f, "{=u64} {=str}", whole, unit),
+        }
+    }
+}
+
+// ===== 时长 =====
+
+/// 时长 (毫秒精度，按量级选择紧凑格式)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed(pub embassy_time::Duration);
+
+/// 时长的显示形式
+enum ElapsedParts {
+    Millis(u64),
+    Secs(u64, u64),
+    Minutes(u64, u64),
+    Hours(u64, u64, u64),
+    Days(u64, u64, u64),
+}
+
+impl Elapsed {
+    /// 从秒数创建
+    pub const fn from_secs(secs: u64) -> Self {
+        Self(embassy_time::Duration::from_secs(secs))
+    }
+
+    /// 从毫秒数创建
+    pub const fn from_millis(millis: u64) -> Self {
+        Self(embassy_time::Duration::from_millis(millis))
+    }
+
+    fn parts(&self) -> ElapsedParts {
+        let ms = self.0.as_millis();
+        let secs = ms / 1000;
+        match secs {
+            0 => ElapsedParts::Millis(ms),
+            1..=59 => ElapsedParts::Secs(secs, ms % 1000 / 100),
+            60..=3599 => ElapsedParts::Minutes(secs / 60, secs % 60),
+            3600..=86399 => ElapsedParts::Hours(secs / 3600, secs / 60 % 60, secs % 60),
+            _ => ElapsedParts::Days(secs / 86400, secs / 3600 % 24, secs / 60 % 60),
+        }
+    }
+}
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.parts() {
+            ElapsedParts::Millis(ms) => write!(f, "{}ms", ms),
+            ElapsedParts::Secs(s, tenth) => write!(f, "{}.{}s", s, tenth),
+            ElapsedParts::Minutes(m, s) => write!(f, "{}m{:02}s", m, s),
+            ElapsedParts::Hours(h, m, s) => write!(f, "{}h{:02}m{:02}s", h, m, s),
+            ElapsedParts::Days(d, h, m) => write!(f, "{}d{:02}h{:02}m", d, h, m),
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Elapsed {
+    fn format(&self, f: defmt::Formatter) {
+        match self.parts() {
+            ElapsedParts::Millis(ms) => defmt::write!(f, "{=u64}ms", ms),
+            ElapsedParts::Secs(s, tenth) => defmt::write!(f, "{=u64}.{=u64}s", s, tenth),
+            ElapsedParts::Minutes(m, s) => defmt::write!(f, "{=u64}m{=u64:02}s", m, s),
+            ElapsedParts::Hours(h, m, s) => defmt::write!(f, "{=u64}h{=u64:02}m{=u64:02}s", h, m, s),
+            ElapsedParts::Days(d, h, m) => defmt::write!(f, "{=u64}d{=u64:02}h{=u64:02}m", d, h, m),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::fmt::Write;
+    use heapless::String;
+
+    fn show(value: impl fmt::Display) -> String<32> {
+        let mut s = String::new();
+        write!(s, "{}", value).unwrap();
+        s
+    }
+
+    #[test]
+    fn test_addresses() {
+        assert_eq!(show(MacAddr([0x7c, 0xdf, 0xa1, 0x00, 0x12, 0xab])), "7c:df:a1:00:12:ab");
+        assert_eq!(show(Ipv4([192, 168, 1, 20])), "192.168.1.20");
+    }
+
+    #[test]
+    fn test_bytes_and_elapsed() {
+        assert_eq!(show(Bytes(512)), "512 B");
+        assert_eq!(show(Bytes(1536)), "1.5 KiB");
+        assert_eq!(show(Bytes(1024 * 1024 - 1)), "1.0 MiB");
+        assert_eq!(show(Bytes(8 * 1024 * 1024)), "8.0 MiB");
+
+        assert_eq!(show(Elapsed::from_millis(850)), "850ms");
+        assert_eq!(show(Elapsed::from_millis(12_345)), "12.3s");
+        assert_eq!(show(Elapsed::from_secs(303)), "5m03s");
+        assert_eq!(show(Elapsed::from_secs(2 * 3600 + 303)), "2h05m03s");
+        assert_eq!(show(Elapsed::from_secs(3 * 86400 + 2 * 3600 + 300)), "3d02h05m");
+    }
+}
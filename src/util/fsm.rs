@@ -0,0 +1,310 @@
+//! 有限状态机框架
+//!
+//! 状态和事件均为枚举，转换规则由 `Machine::next` 描述 (通常是一个 `match`)，
+//! 支持守卫条件、进入/退出动作以及异步转换处理。每次转换都会记录日志并
+//! 发布 `SystemEvent::StateChanged` 到事件总线。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::util::fsm::{Fsm, Machine, Named};
+//!
+//! #[derive(Clone, Copy, PartialEq)]
+//! enum Link { Down, Connecting, Up }
+//! enum LinkEvent { Start, Connected, Lost }
+//!
+//! impl Named for Link { fn name(&self) -> &'static str { /* ... */ } }
+//! impl Named for LinkEvent { fn name(&self) -> &'static str { /* ... */ } }
+//!
+//! struct LinkManager { retries: u8 }
+//!
+//! impl Machine for LinkManager {
+//!     type State = Link;
+//!     type Event = LinkEvent;
+//!     const NAME: &'static str = "link";
+//!
+//!     fn initial(&self) -> Link { Link::Down }
+//!
+//!     fn next(&self, state: Link, event: &LinkEvent) -> Option<Link> {
+//!         match (state, event) {
+//!             (Link::Down, LinkEvent::Start) => Some(Link::Connecting),
+//!             (Link::Connecting, LinkEvent::Connected) => Some(Link::Up),
+//!             (_, LinkEvent::Lost) => Some(Link::Down),
+//!             _ => None,
+//!         }
+//!     }
+//!
+//!     async fn on_enter(&mut self, state: Link) {
+//!         if state == Link::Connecting { /* 发起连接 */ }
+//!     }
+//! }
+//!
+//! let mut fsm = Fsm::new(LinkManager { retries: 0 });
+//! fsm.start().await;
+//! fsm.dispatch(LinkEvent::Start).await?;
+//! ```
+
+use core::fmt;
+
+use crate::sync::bus::{self, SystemEvent};
+use crate::sync::primitives::CriticalChannel;
+use crate::util::log::*;
+
+/// 提供静态名称 (用于日志和事件总线)
+pub trait Named {
+    /// 名称
+    fn name(&self) -> &'static str;
+}
+
+/// 状态机定义
+#[allow(async_fn_in_trait)]
+pub trait Machine {
+    /// 状态类型
+    type State: Copy + PartialEq + Named;
+    /// 事件类型
+    type Event: Named;
+    /// 状态机名称
+    const NAME: &'static str;
+
+    /// 初始状态
+    fn initial(&self) -> Self::State;
+
+    /// 转换表: 返回目标状态，`None` 表示当前状态不处理该事件
+    fn next(&self, state: Self::State, event: &Self::Event) -> Option<Self::State>;
+
+    /// 守卫条件: 返回 `false` 拒绝本次转换
+    fn guard(&self, _from: Self::State, _event: &Self::Event, _to: Self::State) -> bool {
+        true
+    }
+
+    /// 退出状态动作
+    async fn on_exit(&mut self, _state: Self::State) {}
+
+    /// 转换处理 (在退出旧状态之后、进入新状态之前执行)
+    async fn on_transition(&mut self, _from: Self::State, _event: &Self::Event, _to: Self::State) {}
+
+    /// 进入状态动作
+    async fn on_enter(&mut self, _state: Self::State) {}
+}
+
+/// 状态机错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsmError {
+    /// 当前状态没有对应转换
+    NoTransition,
+    /// 守卫条件拒绝
+    GuardRejected,
+}
+
+impl fmt::Display for FsmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FsmError::NoTransition => write!(f, "No transition for event in current state"),
+            FsmError::GuardRejected => write!(f, "Transition rejected by guard"),
+        }
+    }
+}
+
+/// 状态机运行器
+pub struct Fsm<M: Machine> {
+    machine: M,
+    state: M::State,
+    started: bool,
+    transitions: u32,
+    rejected: u32,
+}
+
+impl<M: Machine> Fsm<M> {
+    /// 创建状态机 (处于初始状态，尚未执行进入动作)
+    pub fn new(machine: M) -> Self {
+        let state = machine.initial();
+        Self {
+            machine,
+            state,
+            started: false,
+            transitions: 0,
+            rejected: 0,
+        }
+    }
+
+    /// 启动: 执行初始状态的进入动作
+    pub async fn start(&mut self) {
+        if !self.started {
+            self.started = true;
+            log_debug!("[fsm:{}] start in {}", M::NAME, self.state.name());
+            self.machine.on_enter(self.state).await;
+        }
+    }
+
+    /// 当前状态
+    #[inline]
+    pub fn state(&self) -> M::State {
+        self.state
+    }
+
+    /// 是否处于指定状态
+    #[inline]
+    pub fn is_in(&self, state: M::State) -> bool {
+        self.state == state
+    }
+
+    /// 状态机上下文
+    pub fn machine(&self) -> &M {
+        &self.machine
+    }
+
+    /// 状态机上下文 (可变)
+    pub fn machine_mut(&mut self) -> &mut M {
+        &mut self.machine
+    }
+
+    /// 已完成的转换次数
+    pub fn transition_count(&self) -> u32 {
+        self.transitions
+    }
+
+    /// 被拒绝/忽略的事件次数
+    pub fn rejected_count(&self) -> u32 {
+        self.rejected
+    }
+
+    /// 分发事件
+    ///
+    /// 自转换 (目标等于当前状态) 同样执行退出/进入动作。
+    ///
+    /// # 返回
+    /// 转换后的状态
+    pub async fn dispatch(&mut self, event: M::Event) -> Result<M::State, FsmError> {
+        self.start().await;
+
+        let from = self.state;
+        let Some(to) = self.machine.next(from, &event) else {
+            self.rejected += 1;
+            log_trace!("[fsm:{}] {} ignored in {}", M::NAME, event.name(), from.name());
+            return Err(FsmError::NoTransition);
+        };
+
+        if !self.machine.guard(from, &event, to) {
+            self.rejected += 1;
+            log_debug!("[fsm:{}] {} -> {} rejected by guard", M::NAME, from.name(), to.name());
+            return Err(FsmError::GuardRejected);
+        }
+
+        self.machine.on_exit(from).await;
+        self.machine.on_transition(from, &event, to).await;
+        self.state = to;
+        self.transitions += 1;
+
+        log_info!("[fsm:{}] {} --{}--> {}", M::NAME, from.name(), event.name(), to.name());
+        bus::publish(SystemEvent::StateChanged {
+            machine: M::NAME,
+            from: from.name(),
+            to: to.name(),
+            event: event.name(),
+        });
+
+        self.machine.on_enter(to).await;
+        Ok(to)
+    }
+
+    /// 从通道持续接收并分发事件 (永不返回)
+    ///
+    /// 被忽略或拒绝的事件不会中断循环
+    pub async fn run<const N: usize>(&mut self, events: &CriticalChannel<M::Event, N>) -> ! {
+        self.start().await;
+        loop {
+            let event = events.receive().await;
+            let _ = self.dispatch(event).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embassy_futures::block_on;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Door {
+        Closed,
+        Open,
+        Locked,
+    }
+
+    enum DoorEvent {
+        Open,
+        Close,
+        Lock,
+    }
+
+    impl Named for Door {
+        fn name(&self) -> &'static str {
+            match self {
+                Door::Closed => "closed",
+                Door::Open => "open",
+                Door::Locked => "locked",
+            }
+        }
+    }
+
+    impl Named for DoorEvent {
+        fn name(&self) -> &'static str {
+            match self {
+                DoorEvent::Open => "open",
+                DoorEvent::Close => "close",
+                DoorEvent::Lock => "lock",
+            }
+        }
+    }
+
+    struct DoorMachine {
+        has_key: bool,
+        entered: u32,
+    }
+
+    impl Machine for DoorMachine {
+        type State = Door;
+        type Event = DoorEvent;
+        const NAME: &'static str = "door";
+
+        fn initial(&self) -> Door {
+            Door::Closed
+        }
+
+        fn next(&self, state: Door, event: &DoorEvent) -> Option<Door> {
+            match (state, event) {
+                (Door::Closed, DoorEvent::Open) => Some(Door::Open),
+                (Door::Open, DoorEvent::Close) => Some(Door::Closed),
+                (Door::Closed, DoorEvent::Lock) => Some(Door::Locked),
+                _ => None,
+            }
+        }
+
+        fn guard(&self, _from: Door, event: &DoorEvent, _to: Door) -> bool {
+            !matches!(event, DoorEvent::Lock) || self.has_key
+        }
+
+        async fn on_enter(&mut self, _state: Door) {
+            self.entered += 1;
+        }
+    }
+
+    #[test]
+    fn test_transitions_and_actions() {
+        let mut fsm = Fsm::new(DoorMachine { has_key: false, entered: 0 });
+        assert_eq!(block_on(fsm.dispatch(DoorEvent::Open)), Ok(Door::Open));
+        assert_eq!(block_on(fsm.dispatch(DoorEvent::Open)), Err(FsmError::NoTransition));
+        assert_eq!(block_on(fsm.dispatch(DoorEvent::Close)), Ok(Door::Closed));
+        // 初始进入 + 两次转换
+        assert_eq!(fsm.machine().entered, 3);
+        assert_eq!(fsm.transition_count(), 2);
+    }
+
+    #[test]
+    fn test_guard() {
+        let mut fsm = Fsm::new(DoorMachine { has_key: false, entered: 0 });
+        assert_eq!(block_on(fsm.dispatch(DoorEvent::Lock)), Err(FsmError::GuardRejected));
+        fsm.machine_mut().has_key = true;
+        assert_eq!(block_on(fsm.dispatch(DoorEvent::Lock)), Ok(Door::Locked));
+    }
+}
@@ -0,0 +1,678 @@
+//! 最小 JSON 序列化 / 反序列化
+//!
+//! 面向嵌入式管理接口的零分配 JSON 支持：
+//! - 序列化: `ToJson` trait + 对象/数组写入器，输出到任意 `fmt::Write`
+//!   (通常为 `heapless::String`)
+//! - 反序列化: 惰性解析，`JsonValue` 直接引用输入文本，
+//!   仅在取字符串时按需反转义；`FromJson` 把值转换为具体类型
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::util::json::{self, ObjectWriter};
+//!
+//! let mut out: heapless::String<128> = heapless::String::new();
+//! let mut obj = ObjectWriter::new(&mut out)?;
+//! obj.field("uptime_ms", &uptime)?.field("name", "sensor-1")?;
+//! obj.finish()?;
+//!
+//! let value = json::parse(r#"{"value": 42, "tags": ["a", "b"]}"#)?;
+//! let v = value.get("value")?.as_i64()?;
+//! ```
+
+use core::fmt::{self, Write};
+
+use heapless::String;
+
+/// JSON 错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonError {
+    /// 语法错误 (附字节偏移)
+    Syntax(usize),
+    /// 输出缓冲区不足
+    Overflow,
+    /// 值类型不符
+    Type,
+    /// 键不存在
+    Missing,
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(pos) => write!(f, "JSON syntax error at byte {}", pos),
+            Self::Overflow => write!(f, "JSON output buffer overflow"),
+            Self::Type => write!(f, "Unexpected JSON value type"),
+            Self::Missing => write!(f, "JSON key not found"),
+        }
+    }
+}
+
+impl From<fmt::Error> for JsonError {
+    fn from(_: fmt::Error) -> Self {
+        JsonError::Overflow
+    }
+}
+
+// ===== 序列化 =====
+
+/// 可序列化为 JSON 的类型
+pub trait ToJson {
+    /// 写出 JSON 表示
+    fn write_json<W: Write + ?Sized>(&self, w: &mut W) -> fmt::Result;
+}
+
+macro_rules! impl_to_json_int {
+    ($($t:ty),*) => {
+        $(impl ToJson for $t {
+            fn write_json<W: Write + ?Sized>(&self, w: &mut W) -> fmt::Result {
+                write!(w, "{}", self)
+            }
+        })*
+    };
+}
+
+impl_to_json_int!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl ToJson for bool {
+    fn write_json<W: Write + ?Sized>(&self, w: &mut W) -> fmt::Result {
+        w.write_str(if *self { "true" } else { "false" })
+    }
+}
+
+impl ToJson for f32 {
+    fn write_json<W: Write + ?Sized>(&self, w: &mut W) -> fmt::Result {
+        // JSON 没有 NaN / Infinity
+        if self.is_finite() { write!(w, "{}", self) } else { w.write_str("null") }
+    }
+}
+
+impl ToJson for str {
+    fn write_json<W: Write + ?Sized>(&self, w: &mut W) -> fmt::Result {
+        write_escaped(w, self)
+    }
+}
+
+impl<const N: usize> ToJson for String<N> {
+    fn write_json<W: Write + ?Sized>(&self, w: &mut W) -> fmt::Result {
+        write_escaped(w, self)
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn write_json<W: Write + ?Sized>(&self, w: &mut W) -> fmt::Result {
+        (**self).write_json(w)
+    }
+}
+
+impl<T: ToJson> ToJson for Option<T> {
+    fn write_json<W: Write + ?Sized>(&self, w: &mut W) -> fmt::Result {
+        match self {
+            Some(v) => v.write_json(w),
+            None => w.write_str("null"),
+        }
+    }
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn write_json<W: Write + ?Sized>(&self, w: &mut W) -> fmt::Result {
+        let mut array = ArrayWriter::new(w)?;
+        for item in self {
+            array.item(item)?;
+        }
+        array.finish()
+    }
+}
+
+impl<T: ToJson, const N: usize> ToJson for heapless::Vec<T, N> {
+    fn write_json<W: Write + ?Sized>(&self, w: &mut W) -> fmt::Result {
+        self.as_slice().write_json(w)
+    }
+}
+
+/// 写出带引号并转义的字符串
+pub fn write_escaped<W: Write + ?Sized>(w: &mut W, s: &str) -> fmt::Result {
+    w.write_char('"')?;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        let escape = match c {
+            '"' => "\\\"",
+            '\\' => "\\\\",
+            '\n' => "\\n",
+            '\r' => "\\r",
+            '\t' => "\\t",
+            c if (c as u32) < 0x20 => "",
+            _ => continue,
+        };
+        w.write_str(&s[start..i])?;
+        if escape.is_empty() {
+            write!(w, "\\u{:04x}", c as u32)?;
+        } else {
+            w.write_str(escape)?;
+        }
+        start = i + c.len_utf8();
+    }
+    w.write_str(&s[start..])?;
+    w.write_char('"')
+}
+
+/// 对象写入器
+pub struct ObjectWriter<'w, W: Write + ?Sized> {
+    w: &'w mut W,
+    first: bool,
+}
+
+impl<'w, W: Write + ?Sized> ObjectWriter<'w, W> {
+    /// 开始一个对象
+    pub fn new(w: &'w mut W) -> Result<Self, fmt::Error> {
+        w.write_char('{')?;
+        Ok(Self { w, first: true })
+    }
+
+    fn key(&mut self, key: &str) -> fmt::Result {
+        if !self.first {
+            self.w.write_char(',')?;
+        }
+        self.first = false;
+        write_escaped(self.w, key)?;
+        self.w.write_char(':')
+    }
+
+    /// 写入字段
+    pub fn field<T: ToJson + ?Sized>(&mut self, key: &str, value: &T) -> Result<&mut Self, fmt::Error> {
+        self.key(key)?;
+        value.write_json(self.w)?;
+        Ok(self)
+    }
+
+    /// 由回调写入字段值 (嵌套对象/数组)
+    pub fn field_with(&mut self, key: &str, f: impl FnOnce(&mut W) -> fmt::Result) -> Result<&mut Self, fmt::Error> {
+        self.key(key)?;
+        f(self.w)?;
+        Ok(self)
+    }
+
+    /// 结束对象
+    pub fn finish(self) -> fmt::Result {
+        self.w.write_char('}')
+    }
+}
+
+/// 数组写入器
+pub struct ArrayWriter<'w, W: Write + ?Sized> {
+    w: &'w mut W,
+    first: bool,
+}
+
+impl<'w, W: Write + ?Sized> ArrayWriter<'w, W> {
+    /// 开始一个数组
+    pub fn new(w: &'w mut W) -> Result<Self, fmt::Error> {
+        w.write_char('[')?;
+        Ok(Self { w, first: true })
+    }
+
+    fn separator(&mut self) -> fmt::Result {
+        if !self.first {
+            self.w.write_char(',')?;
+        }
+        self.first = false;
+        Ok(())
+    }
+
+    /// 写入元素
+    pub fn item<T: ToJson + ?Sized>(&mut self, value: &T) -> Result<&mut Self, fmt::Error> {
+        self.separator()?;
+        value.write_json(self.w)?;
+        Ok(self)
+    }
+
+    /// 由回调写入元素 (嵌套对象/数组)
+    pub fn item_with(&mut self, f: impl FnOnce(&mut W) -> fmt::Result) -> Result<&mut Self, fmt::Error> {
+        self.separator()?;
+        f(self.w)?;
+        Ok(self)
+    }
+
+    /// 结束数组
+    pub fn finish(self) -> fmt::Result {
+        self.w.write_char(']')
+    }
+}
+
+/// 序列化到定长字符串
+pub fn to_string<const N: usize, T: ToJson + ?Sized>(value: &T) -> Result<String<N>, JsonError> {
+    let mut out = String::new();
+    value.write_json(&mut out)?;
+    Ok(out)
+}
+
+// ===== 反序列化 =====
+
+/// JSON 值 (引用输入文本)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonValue<'a> {
+    /// null
+    Null,
+    /// 布尔值
+    Bool(bool),
+    /// 数字 (原始文本)
+    Number(&'a str),
+    /// 字符串 (去掉引号，转义未处理)
+    Str(&'a str),
+    /// 对象 (含花括号的原始文本)
+    Object(&'a str),
+    /// 数组 (含方括号的原始文本)
+    Array(&'a str),
+}
+
+/// 解析完整的 JSON 文档
+pub fn parse(input: &str) -> Result<JsonValue<'_>, JsonError> {
+    let mut cursor = Cursor::new(input);
+    let value = cursor.value()?;
+    cursor.skip_ws();
+    if cursor.pos != input.len() {
+        return Err(JsonError::Syntax(cursor.pos));
+    }
+    Ok(value)
+}
+
+impl<'a> JsonValue<'a> {
+    /// 是否为 null
+    pub fn is_null(&self) -> bool {
+        matches!(self, JsonValue::Null)
+    }
+
+    /// 布尔值
+    pub fn as_bool(&self) -> Result<bool, JsonError> {
+        match self {
+            JsonValue::Bool(b) => Ok(*b),
+            _ => Err(JsonError::Type),
+        }
+    }
+
+    /// 整数值
+    pub fn as_i64(&self) -> Result<i64, JsonError> {
+        match self {
+            JsonValue::Number(n) => n.parse().map_err(|_| JsonError::Type),
+            _ => Err(JsonError::Type),
+        }
+    }
+
+    /// 浮点值
+    pub fn as_f32(&self) -> Result<f32, JsonError> {
+        match self {
+            JsonValue::Number(n) => n.parse().map_err(|_| JsonError::Type),
+            _ => Err(JsonError::Type),
+        }
+    }
+
+    /// 原始字符串 (不含转义时可直接使用)
+    pub fn as_raw_str(&self) -> Result<&'a str, JsonError> {
+        match self {
+            JsonValue::Str(s) => Ok(s),
+            _ => Err(JsonError::Type),
+        }
+    }
+
+    /// 反转义字符串
+    pub fn to_string<const N: usize>(&self) -> Result<String<N>, JsonError> {
+        unescape(self.as_raw_str()?)
+    }
+
+    /// 对象字段
+    pub fn get(&self, key: &str) -> Result<JsonValue<'a>, JsonError> {
+        for entry in self.entries()? {
+            let (k, v) = entry?;
+            if k == key {
+                return Ok(v);
+            }
+        }
+        Err(JsonError::Missing)
+    }
+
+    /// 遍历对象字段 (键为原始文本)
+    pub fn entries(&self) -> Result<Entries<'a>, JsonError> {
+        match self {
+            JsonValue::Object(raw) => Ok(Entries { cursor: Cursor::inner(raw), done: false }),
+            _ => Err(JsonError::Type),
+        }
+    }
+
+    /// 遍历数组元素
+    pub fn items(&self) -> Result<Items<'a>, JsonError> {
+        match self {
+            JsonValue::Array(raw) => Ok(Items { cursor: Cursor::inner(raw), done: false }),
+            _ => Err(JsonError::Type),
+        }
+    }
+}
+
+/// 可从 JSON 值反序列化的类型
+pub trait FromJson<'a>: Sized {
+    /// 从 JSON 值构造
+    fn from_json(value: &JsonValue<'a>) -> Result<Self, JsonError>;
+}
+
+macro_rules! impl_from_json_int {
+    ($($t:ty),*) => {
+        $(impl<'a> FromJson<'a> for $t {
+            fn from_json(value: &JsonValue<'a>) -> Result<Self, JsonError> {
+                <$t>::try_from(value.as_i64()?).map_err(|_| JsonError::Type)
+            }
+        })*
+    };
+}
+
+impl_from_json_int!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+impl<'a> FromJson<'a> for bool {
+    fn from_json(value: &JsonValue<'a>) -> Result<Self, JsonError> {
+        value.as_bool()
+    }
+}
+
+impl<'a> FromJson<'a> for f32 {
+    fn from_json(value: &JsonValue<'a>) -> Result<Self, JsonError> {
+        value.as_f32()
+    }
+}
+
+impl<'a> FromJson<'a> for &'a str {
+    fn from_json(value: &JsonValue<'a>) -> Result<Self, JsonError> {
+        value.as_raw_str()
+    }
+}
+
+impl<'a, const N: usize> FromJson<'a> for String<N> {
+    fn from_json(value: &JsonValue<'a>) -> Result<Self, JsonError> {
+        value.to_string()
+    }
+}
+
+impl<'a> FromJson<'a> for JsonValue<'a> {
+    fn from_json(value: &JsonValue<'a>) -> Result<Self, JsonError> {
+        Ok(*value)
+    }
+}
+
+impl<'a, T: FromJson<'a>> FromJson<'a> for Option<T> {
+    fn from_json(value: &JsonValue<'a>) -> Result<Self, JsonError> {
+        if value.is_null() { Ok(None) } else { T::from_json(value).map(Some) }
+    }
+}
+
+/// 解析并反序列化完整的 JSON 文档
+pub fn from_str<'a, T: FromJson<'a>>(input: &'a str) -> Result<T, JsonError> {
+    T::from_json(&parse(input)?)
+}
+
+/// 对象字段迭代器
+pub struct Entries<'a> {
+    cursor: Cursor<'a>,
+    done: bool,
+}
+
+impl<'a> Iterator for Entries<'a> {
+    type Item = Result<(&'a str, JsonValue<'a>), JsonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.cursor.next_member(b'}', true);
+        match result {
+            Ok(Some((key, value))) => Some(Ok((key.unwrap_or(""), value))),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// 数组元素迭代器
+pub struct Items<'a> {
+    cursor: Cursor<'a>,
+    done: bool,
+}
+
+impl<'a> Iterator for Items<'a> {
+    type Item = Result<JsonValue<'a>, JsonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.cursor.next_member(b']', false) {
+            Ok(Some((_, value))) => Some(Ok(value)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// 解析游标
+struct Cursor<'a> {
+    src: &'a str,
+    pos: usize,
+    first: bool,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(src: &'a str) -> Self {
+        Self { src, pos: 0, first: true }
+    }
+
+    /// 进入对象/数组内部 (跳过开括号)
+    fn inner(raw: &'a str) -> Self {
+        Self { src: raw, pos: 1, first: true }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.src.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\r' | b'\n')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect_byte(&mut self, byte: u8) -> Result<(), JsonError> {
+        self.skip_ws();
+        if self.peek() != Some(byte) {
+            return Err(JsonError::Syntax(self.pos));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    /// 读取下一个成员 (对象为键值对，数组为元素)，遇到 `close` 时返回 `None`
+    #[allow(clippy::type_complexity)]
+    fn next_member(&mut self, close: u8, keyed: bool) -> Result<Option<(Option<&'a str>, JsonValue<'a>)>, JsonError> {
+        self.skip_ws();
+        if self.peek() == Some(close) {
+            return Ok(None);
+        }
+        if !self.first {
+            self.expect_byte(b',')?;
+            self.skip_ws();
+        }
+        self.first = false;
+        let key = if keyed {
+            let JsonValue::Str(key) = self.value()? else {
+                return Err(JsonError::Syntax(self.pos));
+            };
+            self.expect_byte(b':')?;
+            Some(key)
+        } else {
+            None
+        };
+        Ok(Some((key, self.value()?)))
+    }
+
+    fn value(&mut self) -> Result<JsonValue<'a>, JsonError> {
+        self.skip_ws();
+        let start = self.pos;
+        match self.peek().ok_or(JsonError::Syntax(start))? {
+            b'n' => self.literal("null", JsonValue::Null),
+            b't' => self.literal("true", JsonValue::Bool(true)),
+            b'f' => self.literal("false", JsonValue::Bool(false)),
+            b'"' => {
+                self.pos += 1;
+                self.skip_string()?;
+                Ok(JsonValue::Str(&self.src[start + 1..self.pos - 1]))
+            }
+            b'{' | b'[' => {
+                self.skip_container()?;
+                let raw = &self.src[start..self.pos];
+                Ok(if raw.starts_with('{') { JsonValue::Object(raw) } else { JsonValue::Array(raw) })
+            }
+            b'-' | b'0'..=b'9' => {
+                while matches!(self.peek(), Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')) {
+                    self.pos += 1;
+                }
+                Ok(JsonValue::Number(&self.src[start..self.pos]))
+            }
+            _ => Err(JsonError::Syntax(start)),
+        }
+    }
+
+    fn literal(&mut self, text: &str, value: JsonValue<'a>) -> Result<JsonValue<'a>, JsonError> {
+        if !self.src[self.pos..].starts_with(text) {
+            return Err(JsonError::Syntax(self.pos));
+        }
+        self.pos += text.len();
+        Ok(value)
+    }
+
+    /// 跳过字符串剩余部分 (开引号已消费)
+    fn skip_string(&mut self) -> Result<(), JsonError> {
+        loop {
+            match self.peek() {
+                None => return Err(JsonError::Syntax(self.pos)),
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some(b'\\') => self.pos += 2,
+                Some(_) => self.pos += 1,
+            }
+        }
+    }
+
+    /// 跳过平衡的对象/数组 (内部在迭代时再校验)
+    fn skip_container(&mut self) -> Result<(), JsonError> {
+        let mut depth = 0usize;
+        loop {
+            match self.peek() {
+                None => return Err(JsonError::Syntax(self.pos)),
+                Some(b'"') => {
+                    self.pos += 1;
+                    self.skip_string()?;
+                    continue;
+                }
+                Some(b'{' | b'[') => depth += 1,
+                Some(b'}' | b']') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.pos += 1;
+                        return Ok(());
+                    }
+                }
+                Some(_) => {}
+            }
+            self.pos += 1;
+        }
+    }
+}
+
+/// 反转义 JSON 字符串内容
+pub fn unescape<const N: usize>(raw: &str) -> Result<String<N>, JsonError> {
+    let mut out = String::new();
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        let c = if c != '\\' {
+            c
+        } else {
+            match chars.next().ok_or(JsonError::Type)? {
+                '"' => '"',
+                '\\' => '\\',
+                '/' => '/',
+                'b' => '\u{8}',
+                'f' => '\u{c}',
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                'u' => {
+                    let hi = hex4(&mut chars)?;
+                    let code = if (0xD800..0xDC00).contains(&hi) {
+                        // 代理对
+                        if chars.next() != Some('\\') || chars.next() != Some('u') {
+                            return Err(JsonError::Type);
+                        }
+                        let lo = hex4(&mut chars)?;
+                        0x10000 + ((hi - 0xD800) << 10) + (lo.wrapping_sub(0xDC00) & 0x3FF)
+                    } else {
+                        hi
+                    };
+                    char::from_u32(code).ok_or(JsonError::Type)?
+                }
+                _ => return Err(JsonError::Type),
+            }
+        };
+        out.push(c).map_err(|_| JsonError::Overflow)?;
+    }
+    Ok(out)
+}
+
+fn hex4(chars: &mut core::str::Chars<'_>) -> Result<u32, JsonError> {
+    let mut v = 0;
+    for _ in 0..4 {
+        v = (v << 4) | chars.next().and_then(|c| c.to_digit(16)).ok_or(JsonError::Type)?;
+    }
+    Ok(v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_object() {
+        let mut out: String<128> = String::new();
+        let mut obj = ObjectWriter::new(&mut out).unwrap();
+        obj.field("n", &-3i32).unwrap().field("s", "a\"b\n").unwrap().field("v", &[1u8, 2][..]).unwrap();
+        obj.field("o", &None::<u32>).unwrap();
+        obj.finish().unwrap();
+        assert_eq!(out.as_str(), r#"{"n":-3,"s":"a\"b\n","v":[1,2],"o":null}"#);
+    }
+
+    #[test]
+    fn test_parse_nested() {
+        let doc = r#" {"a": {"b": [1, "x\"]", true]}, "c": -2.5e1, "d": "é😀"} "#;
+        let root = parse(doc).unwrap();
+        let items: heapless::Vec<JsonValue, 4> = root.get("a").unwrap().get("b").unwrap().items().unwrap().map(|v| v.unwrap()).collect();
+        assert_eq!(items.as_slice(), &[JsonValue::Number("1"), JsonValue::Str("x\\\"]"), JsonValue::Bool(true)]);
+        assert_eq!(root.get("c").unwrap().as_f32().unwrap(), -25.0);
+        assert_eq!(root.get("d").unwrap().to_string::<8>().unwrap().as_str(), "é😀");
+        assert_eq!(root.get("z").unwrap_err(), JsonError::Missing);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(parse("{\"a\" 1}").unwrap().get("a"), Err(JsonError::Syntax(_))));
+        assert!(matches!(parse("[1, 2"), Err(JsonError::Syntax(_))));
+        assert!(matches!(parse("1 2"), Err(JsonError::Syntax(_))));
+    }
+}
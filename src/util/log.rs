@@ -163,6 +163,8 @@ pub use timed;
 // ===================================================================
 
 /// Debug 断言 (仅在 debug 模式下检查)
+///
+/// 失败时记录错误日志并 panic；启用 `panic-free` feature 时只记录日志。
 #[macro_export]
 macro_rules! debug_assert_msg {
     ($cond:expr, $($arg:tt)*) => {
@@ -170,10 +172,19 @@ macro_rules! debug_assert_msg {
         {
             if !$cond {
                 $crate::log_error!("Assertion failed: {}", format_args!($($arg)*));
-                panic!("Assertion failed");
+                $crate::util::log::assertion_failed();
             }
         }
     };
 }
 
 pub use debug_assert_msg;
+
+/// `debug_assert_msg!` 失败处理
+#[doc(hidden)]
+#[cold]
+#[track_caller]
+pub fn assertion_failed() {
+    #[cfg(not(feature = "panic-free"))]
+    panic!("Assertion failed");
+}
@@ -1,5 +1,22 @@
 //! 工具模块
 //!
 //! 提供通用工具函数和宏
+//! - `timesync`: 时间戳关联 (双核周期计数器、embassy 节拍与外设时间戳映射到统一时间轴)
+
+#![cfg_attr(
+    all(feature = "panic-free", not(test)),
+    deny(clippy::unwrap_used, clippy::expect_used, clippy::panic, clippy::unreachable, clippy::todo, clippy::unimplemented)
+)]
 
 pub mod log;
+pub mod stream;
+pub mod dsp;
+pub mod control;
+pub mod fsm;
+pub mod json;
+pub mod ratelimit;
+pub mod fmt;
+pub mod timesync;
+
+#[cfg(test)]
+mod panic_audit;
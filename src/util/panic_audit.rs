@@ -0,0 +1,142 @@
+//! 无 panic 审计
+//!
+//! 扫描核心模块 (`mem` / `sync` / `tasks` / `util`) 的源码，非测试代码中不允许出现
+//! 可能 panic 的调用 (`unwrap`、`expect`、`panic!`、`assert!` 等)。豁免:
+//! - `#[cfg(test)]` 与 `#[cfg(not(feature = "panic-free"))]` 标注的条目
+//! - `debug_assert*` (release 构建中移除) 与 `const { assert!(..) }` (编译期检查)
+//! - 注释
+//!
+//! 新增核心模块文件时需加入 `SOURCES`。
+//!
+//! # 范围
+//!
+//! 审计与 `panic-free` 下启用的 clippy lint 只拦截**显式**的 panic 调用。
+//! 切片/数组索引越界、整数溢出 (debug 构建)、除以零等隐式 panic 不在检查范围内，
+//! `panic-free` 构建并不保证这些模块完全不会 panic；解析外部输入的代码应自行使用
+//! `get` / `checked_*` 等不会 panic 的接口。
+
+macro_rules! source {
+    ($path:literal) => {
+        ($path, include_str!(concat!("../", $path)))
+    };
+}
+
+/// 被审计的源文件 (相对 `src/`)
+const SOURCES: &[(&str, &str)] = &[
+    source!("mem/bench.rs"),
+    source!("mem/dma.rs"),
+    source!("mem/faults.rs"),
+    source!("mem/flashcache.rs"),
+    source!("mem/mod.rs"),
+    source!("mem/pool.rs"),
+    source!("mem/psram.rs"),
+    source!("sync/bus.rs"),
+    source!("sync/lossy.rs"),
+    source!("sync/mod.rs"),
+    source!("sync/pooled.rs"),
+    source!("sync/primitives.rs"),
+    source!("sync/ringbuffer.rs"),
+    source!("tasks/bench.rs"),
+    source!("tasks/budget.rs"),
+    source!("tasks/critical.rs"),
+    source!("tasks/local.rs"),
+    source!("tasks/mod.rs"),
+    source!("tasks/multicore.rs"),
+    source!("tasks/normal.rs"),
+    source!("tasks/spawn.rs"),
+    source!("tasks/workqueue.rs"),
+    source!("util/control.rs"),
+    source!("util/dsp/bench.rs"),
+    source!("util/dsp/fft.rs"),
+    source!("util/dsp/fir.rs"),
+    source!("util/dsp/iir.rs"),
+    source!("util/dsp/mod.rs"),
+    source!("util/dsp/smoothing.rs"),
+    source!("util/fmt.rs"),
+    source!("util/fsm.rs"),
+    source!("util/json.rs"),
+    source!("util/log.rs"),
+    source!("util/mod.rs"),
+    source!("util/ratelimit.rs"),
+    source!("util/stream.rs"),
+    source!("util/timesync.rs"),
+];
+
+/// 可能 panic 的调用
+const FORBIDDEN: &[&str] = &[
+    ".unwrap()",
+    ".expect(",
+    "panic!(",
+    "unreachable!(",
+    "todo!(",
+    "unimplemented!(",
+    "assert!(",
+    "assert_eq!(",
+    "assert_ne!(",
+];
+
+/// 豁免条目的属性
+const EXEMPT_ATTRS: &[&str] = &["#[cfg(test)]", "#[cfg(not(feature = \"panic-free\"))]"];
+
+/// 扫描源码，对每处违规调用 `report(行号, 代码)`
+fn scan(source: &str, mut report: impl FnMut(usize, &str)) {
+    // 正在跳过的豁免条目: (已进入花括号, 花括号深度)
+    let mut skipping: Option<(bool, i32)> = None;
+    for (index, line) in source.lines().enumerate() {
+        let code = line.trim();
+        if let Some((opened, depth)) = skipping.as_mut() {
+            let opens = code.matches('{').count() as i32;
+            *depth += opens - code.matches('}').count() as i32;
+            *opened |= opens > 0;
+            if (*opened && *depth <= 0) || (!*opened && code.ends_with(';')) {
+                skipping = None;
+            }
+            continue;
+        }
+        if EXEMPT_ATTRS.contains(&code) {
+            skipping = Some((false, 0));
+            continue;
+        }
+        let code = code.split("//").next().unwrap_or("");
+        let violation = FORBIDDEN.iter().flat_map(|pattern| code.match_indices(pattern)).any(|(at, _)| {
+            let before = &code[..at];
+            !before.ends_with("debug_") && !before.contains("const {")
+        });
+        if violation {
+            report(index + 1, code);
+        }
+    }
+}
+
+#[test]
+fn test_scanner() {
+    let sample = "\
+fn a() { x.unwrap_or(0); debug_assert!(ok); const { assert!(N > 0) }; }
+fn f() { debug_assert!(ok); assert!(ok); }
+// y.unwrap()
+#[cfg(not(feature = \"panic-free\"))]
+fn b() {
+    panic!(\"allowed\");
+}
+fn c() { y.unwrap(); }
+#[cfg(test)]
+mod tests {
+    fn d() { z.expect(\"allowed\"); }
+}
+fn e() { assert_eq!(1, 2); }
+";
+    let mut found = [0usize; 4];
+    let mut count = 0;
+    scan(sample, |line, _| {
+        found[count] = line;
+        count += 1;
+    });
+    assert_eq!(&found[..count], &[2, 8, 13]);
+}
+
+#[test]
+fn test_core_modules_panic_free() {
+    for (path, source) in SOURCES {
+        scan(source, |line, code| panic!("src/{}:{}: panic-capable code: {}", path, line, code));
+    }
+}
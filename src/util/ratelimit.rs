@@ -0,0 +1,256 @@
+//! 速率限制
+//!
+//! 用于约束 HTTP 请求、消息发布、日志输出等资源消耗。
+//!
+//! # 特性
+//! - `TokenBucket`: 令牌桶，允许 `capacity` 大小的突发，之后按固定间隔补充
+//! - `SlidingWindow<N>`: 滑动窗口，任意 `window` 时长内最多 `N` 次
+//! - 内部可变 (临界区互斥)，可直接放在 `static` 中被多个任务共享
+//! - `try_acquire` 立即返回，失败时给出需等待的时长；`acquire` 基于 embassy 定时器异步等待
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::util::ratelimit::{RateLimit, SlidingWindow, TokenBucket};
+//!
+//! // 突发 10 条，之后每秒 5 条
+//! static PUBLISH: TokenBucket = TokenBucket::per_second(5, 10);
+//! // 每分钟最多 20 条告警日志
+//! static ALERTS: SlidingWindow<20> = SlidingWindow::new(Duration::from_secs(60));
+//!
+//! PUBLISH.acquire(1).await;
+//! client.publish(topic, payload).await?;
+//!
+//! if ALERTS.try_acquire(1).is_ok() {
+//!     log_warn!("sensor {} out of range", id);
+//! }
+//! ```
+
+use core::cell::{Cell, RefCell};
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_time::{Duration, Instant, Timer};
+use heapless::Deque;
+
+// ===== 公共接口 =====
+
+/// 速率限制器
+pub trait RateLimit {
+    /// 在指定时刻尝试获取 `n` 个许可
+    ///
+    /// 成功时立即扣除；失败时不扣除，返回最早可成功的等待时长。
+    /// `n` 超过限制器容量时按容量计算。
+    fn try_acquire_at(&self, n: u32, now: Instant) -> Result<(), Duration>;
+
+    /// 尝试获取 `n` 个许可
+    fn try_acquire(&self, n: u32) -> Result<(), Duration> {
+        self.try_acquire_at(n, Instant::now())
+    }
+
+    /// 等待直到获取 `n` 个许可
+    #[allow(async_fn_in_trait)]
+    async fn acquire(&self, n: u32) {
+        while let Err(wait) = self.try_acquire(n) {
+            Timer::after(wait).await;
+        }
+    }
+}
+
+// ===== 令牌桶 =====
+
+#[derive(Clone, Copy)]
+struct BucketState {
+    tokens: u32,
+    /// 上次补充的时刻 (保留不足一个间隔的余量)
+    last: Option<Instant>,
+}
+
+/// 令牌桶
+///
+/// 初始为满，每经过 `interval` 补充一个令牌，最多 `capacity` 个。
+pub struct TokenBucket {
+    capacity: u32,
+    interval: Duration,
+    state: BlockingMutex<CriticalSectionRawMutex, Cell<BucketState>>,
+}
+
+impl TokenBucket {
+    /// 创建令牌桶 (`capacity` 至少为 1，`interval` 至少为 1 tick)
+    pub const fn new(capacity: u32, interval: Duration) -> Self {
+        let capacity = if capacity == 0 { 1 } else { capacity };
+        let interval = if interval.as_ticks() == 0 { Duration::from_ticks(1) } else { interval };
+        Self {
+            capacity,
+            interval,
+            state: BlockingMutex::new(Cell::new(BucketState { tokens: capacity, last: None })),
+        }
+    }
+
+    /// 按每秒速率创建 (`rate` 为 0 时按 1 计算)
+    pub const fn per_second(rate: u32, burst: u32) -> Self {
+        let rate = if rate == 0 { 1 } else { rate as u64 };
+        Self::new(burst, Duration::from_ticks(embassy_time::TICK_HZ / rate))
+    }
+
+    /// 容量
+    pub const fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// 补充间隔
+    pub const fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// 当前可用令牌数
+    pub fn available(&self) -> u32 {
+        self.state.lock(|state| self.refill(state.get(), Instant::now()).tokens)
+    }
+
+    /// 重新装满
+    pub fn reset(&self) {
+        self.state.lock(|state| state.set(BucketState { tokens: self.capacity, last: None }));
+    }
+
+    fn refill(&self, mut state: BucketState, now: Instant) -> BucketState {
+        let Some(last) = state.last else {
+            state.last = Some(now);
+            return state;
+        };
+        let elapsed = now.saturating_duration_since(last).as_ticks();
+        let step = self.interval.as_ticks();
+        let added = elapsed / step;
+        if state.tokens as u64 + added >= self.capacity as u64 {
+            state.tokens = self.capacity;
+            state.last = Some(now);
+        } else {
+            state.tokens += added as u32;
+            state.last = Some(last + Duration::from_ticks(added * step));
+        }
+        state
+    }
+}
+
+impl RateLimit for TokenBucket {
+    fn try_acquire_at(&self, n: u32, now: Instant) -> Result<(), Duration> {
+        let n = n.min(self.capacity);
+        self.state.lock(|cell| {
+            let mut state = self.refill(cell.get(), now);
+            let result = if state.tokens >= n {
+                state.tokens -= n;
+                Ok(())
+            } else {
+                // 距离下一个令牌的剩余时间 + 其余缺口
+                let missing = (n - state.tokens) as u64;
+                let since = state.last.map_or(0, |last| now.saturating_duration_since(last).as_ticks());
+                let step = self.interval.as_ticks();
+                Err(Duration::from_ticks(missing * step - since.min(step)))
+            };
+            cell.set(state);
+            result
+        })
+    }
+}
+
+// ===== 滑动窗口 =====
+
+/// 滑动窗口
+///
+/// 记录最近 `N` 次许可的时刻，任意 `window` 时长内最多放行 `N` 次。
+/// 相比令牌桶不允许窗口边界处的双倍突发，代价是 `N` 个时间戳的内存。
+pub struct SlidingWindow<const N: usize> {
+    window: Duration,
+    events: BlockingMutex<CriticalSectionRawMutex, RefCell<Deque<Instant, N>>>,
+}
+
+impl<const N: usize> SlidingWindow<N> {
+    /// 创建滑动窗口
+    pub const fn new(window: Duration) -> Self {
+        Self { window, events: BlockingMutex::new(RefCell::new(Deque::new())) }
+    }
+
+    /// 窗口长度
+    pub const fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// 当前窗口内剩余许可数
+    pub fn available(&self) -> u32 {
+        let now = Instant::now();
+        self.events.lock(|events| {
+            let mut events = events.borrow_mut();
+            self.expire(&mut events, now);
+            (N - events.len()) as u32
+        })
+    }
+
+    /// 清空记录
+    pub fn reset(&self) {
+        self.events.lock(|events| events.borrow_mut().clear());
+    }
+
+    fn expire(&self, events: &mut Deque<Instant, N>, now: Instant) {
+        while let Some(&oldest) = events.front() {
+            if now.saturating_duration_since(oldest) < self.window {
+                break;
+            }
+            events.pop_front();
+        }
+    }
+}
+
+impl<const N: usize> RateLimit for SlidingWindow<N> {
+    fn try_acquire_at(&self, n: u32, now: Instant) -> Result<(), Duration> {
+        let n = (n as usize).min(N);
+        self.events.lock(|events| {
+            let mut events = events.borrow_mut();
+            self.expire(&mut events, now);
+            let free = N - events.len();
+            if free >= n {
+                for _ in 0..n {
+                    let _ = events.push_back(now);
+                }
+                return Ok(());
+            }
+            // 需要再过期 n - free 条记录，等待其中最晚的一条离开窗口
+            let blocking = events.iter().nth(n - free - 1).copied().unwrap_or(now);
+            Err((blocking + self.window).saturating_duration_since(now))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let bucket = TokenBucket::new(3, Duration::from_millis(100));
+        let t0 = Instant::from_millis(1_000);
+        for _ in 0..3 {
+            assert_eq!(bucket.try_acquire_at(1, t0), Ok(()));
+        }
+        assert_eq!(bucket.try_acquire_at(1, t0), Err(Duration::from_millis(100)));
+        assert_eq!(bucket.try_acquire_at(2, t0 + Duration::from_millis(30)), Err(Duration::from_millis(170)));
+        // 部分间隔的余量不会丢失
+        assert_eq!(bucket.try_acquire_at(1, t0 + Duration::from_millis(150)), Ok(()));
+        assert_eq!(bucket.try_acquire_at(1, t0 + Duration::from_millis(200)), Ok(()));
+        assert!(bucket.try_acquire_at(1, t0 + Duration::from_millis(250)).is_err());
+        // 长时间空闲后不超过容量
+        assert_eq!(bucket.try_acquire_at(5, t0 + Duration::from_secs(10)), Ok(()));
+        assert!(bucket.try_acquire_at(1, t0 + Duration::from_secs(10)).is_err());
+    }
+
+    #[test]
+    fn test_sliding_window() {
+        let window: SlidingWindow<2> = SlidingWindow::new(Duration::from_secs(1));
+        let t0 = Instant::from_secs(5);
+        assert_eq!(window.try_acquire_at(1, t0), Ok(()));
+        assert_eq!(window.try_acquire_at(1, t0 + Duration::from_millis(400)), Ok(()));
+        assert_eq!(window.try_acquire_at(1, t0 + Duration::from_millis(500)), Err(Duration::from_millis(500)));
+        assert_eq!(window.try_acquire_at(2, t0 + Duration::from_millis(500)), Err(Duration::from_millis(900)));
+        assert_eq!(window.try_acquire_at(1, t0 + Duration::from_secs(1)), Ok(()));
+        assert!(window.try_acquire_at(1, t0 + Duration::from_millis(1_300)).is_err());
+    }
+}
@@ -0,0 +1,360 @@
+//! 异步流 (Stream) 抽象
+//!
+//! 为传感器管线提供统一的数据流模型，替代各驱动各自的回调风格:
+//! - `Stream` trait: 轻量 `poll_next` 接口 (与 futures::Stream 语义一致)
+//! - 适配器: `map`、`chunked`、`rate_limit`、`tee`
+//! - 数据源: `from_channel` (CriticalChannel)、`iter` (迭代器)
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::util::stream::{self, StreamExt};
+//!
+//! static SAMPLES: CriticalChannel<u16, 32> = CriticalChannel::new();
+//! static LOGGER: CriticalChannel<u16, 8> = CriticalChannel::new();
+//!
+//! let mut pipeline = stream::from_channel(&SAMPLES)
+//!     .map(|raw| raw >> 4)
+//!     .tee(&LOGGER)
+//!     .rate_limit(Duration::from_millis(10))
+//!     .chunked::<16>();
+//!
+//! while let Some(block) = pipeline.next().await {
+//!     process(&block);
+//! }
+//! ```
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::channel::Channel;
+use embassy_time::{Duration, Instant};
+use heapless::Vec;
+
+/// 异步流
+///
+/// 与 `Iterator` 类似，但 `poll_next` 可以返回 `Pending` 并在数据就绪时唤醒。
+pub trait Stream {
+    /// 元素类型
+    type Item;
+
+    /// 尝试获取下一个元素
+    ///
+    /// # 返回
+    /// - `Poll::Ready(Some(item))`: 新元素
+    /// - `Poll::Ready(None)`: 流已结束
+    /// - `Poll::Pending`: 暂无数据，就绪时唤醒 `cx`
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>>;
+}
+
+impl<S: Stream + Unpin + ?Sized> Stream for &mut S {
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut **self).poll_next(cx)
+    }
+}
+
+/// Stream 扩展方法
+pub trait StreamExt: Stream {
+    /// 异步获取下一个元素
+    fn next(&mut self) -> Next<'_, Self>
+    where
+        Self: Unpin,
+    {
+        Next { stream: self }
+    }
+
+    /// 对每个元素应用变换
+    fn map<U, F>(self, f: F) -> Map<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(Self::Item) -> U,
+    {
+        Map { inner: self, f }
+    }
+
+    /// 将元素按 `N` 个一组打包
+    ///
+    /// 上游结束时输出剩余的不完整分组
+    fn chunked<const N: usize>(self) -> Chunked<Self, N>
+    where
+        Self: Sized,
+    {
+        Chunked { inner: self, buffer: Vec::new(), done: false }
+    }
+
+    /// 限速: 每个 `interval` 最多放行一个元素，其余丢弃
+    ///
+    /// 适用于传感器数据 (旧数据无需保留)
+    fn rate_limit(self, interval: Duration) -> RateLimit<Self>
+    where
+        Self: Sized,
+    {
+        RateLimit { inner: self, interval, last: None, dropped: 0 }
+    }
+
+    /// 将每个元素的副本非阻塞地发送到通道
+    ///
+    /// 通道已满时副本被丢弃并计数，主流不受影响
+    fn tee<const N: usize>(
+        self,
+        channel: &Channel<CriticalSectionRawMutex, Self::Item, N>,
+    ) -> Tee<'_, Self, N>
+    where
+        Self: Sized,
+        Self::Item: Clone,
+    {
+        Tee { inner: self, channel, dropped: 0 }
+    }
+}
+
+impl<S: Stream + ?Sized> StreamExt for S {}
+
+// ===== Future: next =====
+
+/// `StreamExt::next` 返回的 Future
+pub struct Next<'a, S: ?Sized> {
+    stream: &'a mut S,
+}
+
+impl<S: Stream + Unpin + ?Sized> Future for Next<'_, S> {
+    type Output = Option<S::Item>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut *self.stream).poll_next(cx)
+    }
+}
+
+// ===== 适配器: map =====
+
+/// `StreamExt::map` 适配器
+pub struct Map<S, F> {
+    inner: S,
+    f: F,
+}
+
+impl<S, F, U> Stream for Map<S, F>
+where
+    S: Stream + Unpin,
+    F: FnMut(S::Item) -> U + Unpin,
+{
+    type Item = U;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<U>> {
+        let this = self.get_mut();
+        match Pin::new(&mut this.inner).poll_next(cx) {
+            Poll::Ready(Some(item)) => Poll::Ready(Some((this.f)(item))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+// ===== 适配器: chunked =====
+
+/// `StreamExt::chunked` 适配器
+pub struct Chunked<S: Stream, const N: usize> {
+    inner: S,
+    buffer: Vec<S::Item, N>,
+    done: bool,
+}
+
+impl<S, const N: usize> Stream for Chunked<S, N>
+where
+    S: Stream + Unpin,
+    S::Item: Unpin,
+{
+    type Item = Vec<S::Item, N>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        loop {
+            match Pin::new(&mut this.inner).poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    // 容量由下方检查保证
+                    let _ = this.buffer.push(item);
+                    if this.buffer.is_full() {
+                        return Poll::Ready(Some(core::mem::take(&mut this.buffer)));
+                    }
+                }
+                Poll::Ready(None) => {
+                    this.done = true;
+                    if this.buffer.is_empty() {
+                        return Poll::Ready(None);
+                    }
+                    return Poll::Ready(Some(core::mem::take(&mut this.buffer)));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+// ===== 适配器: rate_limit =====
+
+/// `StreamExt::rate_limit` 适配器
+pub struct RateLimit<S> {
+    inner: S,
+    interval: Duration,
+    last: Option<Instant>,
+    dropped: u32,
+}
+
+impl<S> RateLimit<S> {
+    /// 被丢弃的元素数量
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+}
+
+impl<S: Stream + Unpin> Stream for RateLimit<S> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<S::Item>> {
+        let this = self.get_mut();
+        loop {
+            match Pin::new(&mut this.inner).poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    let now = Instant::now();
+                    let due = match this.last {
+                        Some(last) => now.duration_since(last) >= this.interval,
+                        None => true,
+                    };
+                    if due {
+                        this.last = Some(now);
+                        return Poll::Ready(Some(item));
+                    }
+                    this.dropped = this.dropped.wrapping_add(1);
+                }
+                other => return other,
+            }
+        }
+    }
+}
+
+// ===== 适配器: tee =====
+
+/// `StreamExt::tee` 适配器
+pub struct Tee<'c, S: Stream, const N: usize> {
+    inner: S,
+    channel: &'c Channel<CriticalSectionRawMutex, S::Item, N>,
+    dropped: u32,
+}
+
+impl<S: Stream, const N: usize> Tee<'_, S, N> {
+    /// 因通道已满而丢弃的副本数量
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+}
+
+impl<S, const N: usize> Stream for Tee<'_, S, N>
+where
+    S: Stream + Unpin,
+    S::Item: Clone,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<S::Item>> {
+        let this = self.get_mut();
+        match Pin::new(&mut this.inner).poll_next(cx) {
+            Poll::Ready(Some(item)) => {
+                if this.channel.try_send(item.clone()).is_err() {
+                    this.dropped = this.dropped.wrapping_add(1);
+                }
+                Poll::Ready(Some(item))
+            }
+            other => other,
+        }
+    }
+}
+
+// ===== 数据源 =====
+
+/// 通道数据源
+pub struct ChannelStream<'c, T, const N: usize> {
+    channel: &'c Channel<CriticalSectionRawMutex, T, N>,
+}
+
+impl<T, const N: usize> Stream for ChannelStream<'_, T, N> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.channel.poll_receive(cx).map(Some)
+    }
+}
+
+/// 将 CriticalChannel 作为永不结束的流
+pub fn from_channel<T, const N: usize>(
+    channel: &Channel<CriticalSectionRawMutex, T, N>,
+) -> ChannelStream<'_, T, N> {
+    ChannelStream { channel }
+}
+
+/// 迭代器数据源
+pub struct Iter<I> {
+    iter: I,
+}
+
+impl<I: Iterator + Unpin> Stream for Iter<I> {
+    type Item = I::Item;
+
+    fn poll_next(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<I::Item>> {
+        Poll::Ready(self.get_mut().iter.next())
+    }
+}
+
+/// 将迭代器转换为立即就绪的流
+pub fn iter<I: IntoIterator>(iter: I) -> Iter<I::IntoIter> {
+    Iter { iter: iter.into_iter() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::task::{RawWaker, RawWakerVTable, Waker};
+
+    fn noop_waker() -> Waker {
+        const VTABLE: RawWakerVTable = RawWakerVTable::new(
+            |_| RawWaker::new(core::ptr::null(), &VTABLE),
+            |_| {},
+            |_| {},
+            |_| {},
+        );
+        unsafe { Waker::from_raw(RawWaker::new(core::ptr::null(), &VTABLE)) }
+    }
+
+    fn poll_once<S: Stream + Unpin>(s: &mut S) -> Poll<Option<S::Item>> {
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        Pin::new(s).poll_next(&mut cx)
+    }
+
+    #[test]
+    fn test_map_and_chunked() {
+        let mut s = iter([1u32, 2, 3, 4, 5]).map(|x| x * 10).chunked::<2>();
+
+        assert_eq!(poll_once(&mut s), Poll::Ready(Some(Vec::from_slice(&[10, 20]).unwrap())));
+        assert_eq!(poll_once(&mut s), Poll::Ready(Some(Vec::from_slice(&[30, 40]).unwrap())));
+        assert_eq!(poll_once(&mut s), Poll::Ready(Some(Vec::from_slice(&[50]).unwrap())));
+        assert_eq!(poll_once(&mut s), Poll::Ready(None));
+    }
+
+    #[test]
+    fn test_tee_drops_when_full() {
+        let channel: Channel<CriticalSectionRawMutex, u8, 1> = Channel::new();
+        let mut s = iter([1u8, 2]).tee(&channel);
+
+        assert_eq!(poll_once(&mut s), Poll::Ready(Some(1)));
+        assert_eq!(poll_once(&mut s), Poll::Ready(Some(2)));
+        assert_eq!(s.dropped(), 1);
+        assert_eq!(channel.try_receive().ok(), Some(1));
+    }
+}
@@ -0,0 +1,378 @@
+//! 时间戳关联
+//!
+//! 多源传感器融合需要统一的时间轴，而各时间源互不同步:
+//! - 两个核各自的 CCOUNT 周期计数器 (240MHz，约 17.9s 回绕)
+//! - embassy 节拍 (`Instant`，作为统一时间轴，单位微秒)
+//! - 外设时间戳 (如 I2S DMA 完成时的采样序号、定时器捕获值)
+//!
+//! `Correlator` 为一个时间源维护到统一时间轴的线性映射:
+//! - 同步点: 本地计数值与统一时间轴上的时刻 (及其不确定度，如中断延迟)
+//! - 频偏: 相隔至少 `min_span` 的两个同步点测得本地时钟相对时间轴的偏差 (ppm)，平滑后使用
+//! - 回绕: 任意位宽的计数器按时间轴上的近似时刻展开，无需高频轮询
+//! - 误差界: 转换结果附带误差 = 同步点不确定度 + 频偏误差 × 外推时长
+//!
+//! 新同步点仅在其不确定度不大于旧同步点外推误差时替换，抖动大的样本不会拉低精度。
+//! CPU 计数器在各核上分别同步 (`sync_cpu` 须在对应核上周期调用)。
+//!
+//! # 示例
+//!
+//! ```rust,ignore
+//! use rustrtos::util::timesync::{self, ClockConfig, SharedClock};
+//!
+//! // 每个核上的任务每秒同步一次 CCOUNT
+//! loop { timesync::sync_cpu(); Timer::after_secs(1).await; }
+//!
+//! // I2S: 以采样序号为时钟，DMA 完成中断中记录同步点 (中断延迟上限 20us)
+//! static I2S_CLOCK: SharedClock = SharedClock::new(ClockConfig::new(48_000).with_tolerance_ppm(50));
+//! I2S_CLOCK.observe(samples_done, Instant::now().as_micros(), 20);
+//!
+//! // 融合: 两个来源的时间戳映射到同一时间轴
+//! let audio = I2S_CLOCK.to_timeline(frame_index, Instant::now().as_micros());
+//! let imu = timesync::cpu_timestamp(0, captured_ccount, Instant::now().as_micros());
+//! ```
+
+use core::cell::RefCell;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_time::{Instant, TICK_HZ};
+use esp_hal::system::Cpu;
+
+use super::dsp::bench::cycle_count;
+
+/// CPU 主频 (Hz)，与 `config::CPU_FREQ_HZ` 一致
+pub const CPU_FREQ_HZ: u32 = 240_000_000;
+
+/// 频偏平滑系数的倒数 (新测量值权重 1/4)
+const RATE_SMOOTHING: i64 = 4;
+
+/// 频偏测量的残余误差下限 (ppm)
+const RESIDUAL_PPM: u32 = 2;
+
+// ===== 配置 =====
+
+/// 时间源配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockConfig {
+    /// 标称频率 (Hz)
+    pub nominal_hz: u32,
+    /// 计数器位宽 (1..=32)
+    pub bits: u8,
+    /// 频偏测得之前假定的频率容差 (ppm)
+    pub tolerance_ppm: u32,
+    /// 测量频偏的最短同步点间隔 (微秒)
+    pub min_span_us: u64,
+}
+
+impl ClockConfig {
+    /// 32 位计数器，容差 100ppm，频偏测量间隔至少 1s
+    pub const fn new(nominal_hz: u32) -> Self {
+        Self { nominal_hz, bits: 32, tolerance_ppm: 100, min_span_us: 1_000_000 }
+    }
+
+    /// CPU 周期计数器 (CCOUNT)
+    pub const fn cpu() -> Self {
+        Self::new(CPU_FREQ_HZ)
+    }
+
+    /// 设置计数器位宽
+    pub const fn with_bits(mut self, bits: u8) -> Self {
+        self.bits = bits;
+        self
+    }
+
+    /// 设置频率容差
+    pub const fn with_tolerance_ppm(mut self, ppm: u32) -> Self {
+        self.tolerance_ppm = ppm;
+        self
+    }
+
+    /// 设置频偏测量的最短间隔
+    pub const fn with_min_span_us(mut self, us: u64) -> Self {
+        self.min_span_us = us;
+        self
+    }
+
+    fn modulus(&self) -> u64 {
+        1u64 << self.bits.clamp(1, 32)
+    }
+}
+
+// ===== 关联 =====
+
+/// 统一时间轴上的时间戳
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stamp {
+    /// 时间轴时刻 (微秒，与 `Instant::as_micros` 同基准)
+    pub us: u64,
+    /// 误差界 (微秒)
+    pub error_us: u32,
+}
+
+impl Stamp {
+    /// 转换为 `Instant`
+    pub fn instant(&self) -> Instant {
+        Instant::from_micros(self.us)
+    }
+}
+
+/// 同步点
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SyncPoint {
+    raw: u32,
+    us: u64,
+    uncertainty_us: u32,
+}
+
+/// 关联统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncStats {
+    /// 收到的同步点数
+    pub observations: u32,
+    /// 被采纳为基准的同步点数
+    pub accepted: u32,
+    /// 平滑后的频偏 (ppm，正数表示本地时钟偏快)
+    pub rate_ppm: i32,
+    /// 频偏测量次数
+    pub rate_samples: u32,
+    /// 当前频偏误差估计 (ppm)
+    pub rate_error_ppm: u32,
+}
+
+/// 单个时间源到统一时间轴的关联
+#[derive(Debug, Clone)]
+pub struct Correlator {
+    config: ClockConfig,
+    anchor: Option<SyncPoint>,
+    rate_anchor: Option<SyncPoint>,
+    stats: SyncStats,
+}
+
+impl Correlator {
+    /// 创建
+    pub const fn new(config: ClockConfig) -> Self {
+        Self {
+            config,
+            anchor: None,
+            rate_anchor: None,
+            stats: SyncStats { observations: 0, accepted: 0, rate_ppm: 0, rate_samples: 0, rate_error_ppm: 0 },
+        }
+    }
+
+    /// 配置
+    pub fn config(&self) -> &ClockConfig {
+        &self.config
+    }
+
+    /// 统计
+    pub fn stats(&self) -> SyncStats {
+        self.stats
+    }
+
+    /// 是否已有同步点
+    pub fn is_synced(&self) -> bool {
+        self.anchor.is_some()
+    }
+
+    fn rate_error_ppm(&self) -> u32 {
+        if self.stats.rate_samples == 0 {
+            self.config.tolerance_ppm
+        } else {
+            self.stats.rate_error_ppm
+        }
+    }
+
+    /// 以时间轴上的近似时刻展开计数差 (`raw - from`)
+    ///
+    /// 近似时刻的误差须小于半个回绕周期。
+    fn unwrap_delta(&self, from: &SyncPoint, raw: u32, near_us: u64) -> i64 {
+        let modulus = self.config.modulus() as i64;
+        let delta = (raw.wrapping_sub(from.raw) as u64 & (modulus as u64 - 1)) as i64;
+        let elapsed_us = near_us as i64 - from.us as i64;
+        let expected = (elapsed_us as i128 * self.config.nominal_hz as i128 / 1_000_000) as i64;
+        // 选取与期望计数最接近的回绕次数
+        let wraps = (expected - delta + modulus / 2).div_euclid(modulus);
+        delta + wraps * modulus
+    }
+
+    /// 计数差换算为时间轴上的微秒数 (已校正频偏)
+    fn ticks_to_us(&self, ticks: i64) -> i64 {
+        let denominator = self.config.nominal_hz as i128 * (1_000_000 + self.stats.rate_ppm as i128);
+        if denominator <= 0 {
+            return 0;
+        }
+        (ticks as i128 * 1_000_000_000_000 / denominator) as i64
+    }
+
+    /// 外推误差
+    fn error_at(&self, anchor: &SyncPoint, elapsed_us: i64) -> u32 {
+        let drift = elapsed_us.unsigned_abs() * self.rate_error_ppm() as u64 / 1_000_000;
+        (anchor.uncertainty_us as u64 + drift).min(u32::MAX as u64) as u32
+    }
+
+    /// 记录同步点: 本地计数 `raw` 对应时间轴时刻 `us`，不确定度 `uncertainty_us`
+    pub fn observe(&mut self, raw: u32, us: u64, uncertainty_us: u32) {
+        self.stats.observations = self.stats.observations.wrapping_add(1);
+        let point = SyncPoint { raw, us, uncertainty_us };
+
+        match self.rate_anchor {
+            Some(from) if us >= from.us + self.config.min_span_us.max(1) => {
+                self.measure_rate(&from, &point);
+                self.rate_anchor = Some(point);
+            }
+            Some(from) if us < from.us => self.rate_anchor = Some(point),
+            Some(_) => {}
+            None => self.rate_anchor = Some(point),
+        }
+
+        let accept = match self.anchor {
+            Some(anchor) => uncertainty_us <= self.error_at(&anchor, us as i64 - anchor.us as i64),
+            None => true,
+        };
+        if accept {
+            self.anchor = Some(point);
+            self.stats.accepted = self.stats.accepted.wrapping_add(1);
+        }
+    }
+
+    fn measure_rate(&mut self, from: &SyncPoint, to: &SyncPoint) {
+        let reference_us = (to.us - from.us) as i64;
+        let ticks = self.unwrap_delta(from, to.raw, to.us);
+        let local_us = (ticks as i128 * 1_000_000 / self.config.nominal_hz.max(1) as i128) as i64;
+        let measured = ((local_us - reference_us) as i128 * 1_000_000 / reference_us as i128) as i64;
+
+        let previous = self.stats.rate_ppm as i64;
+        let rate = if self.stats.rate_samples == 0 { measured } else { previous + (measured - previous) / RATE_SMOOTHING };
+        // 端点不确定度带来的测量误差 + 与估计值的偏离
+        let endpoint_ppm = (from.uncertainty_us as u64 + to.uncertainty_us as u64) * 1_000_000 / reference_us as u64;
+        let deviation = if self.stats.rate_samples == 0 { 0 } else { measured.abs_diff(previous) };
+
+        self.stats.rate_ppm = rate.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
+        self.stats.rate_error_ppm =
+            (endpoint_ppm.max(deviation) + RESIDUAL_PPM as u64).min(self.config.tolerance_ppm.max(RESIDUAL_PPM) as u64) as u32;
+        self.stats.rate_samples = self.stats.rate_samples.wrapping_add(1);
+    }
+
+    /// 将本地计数映射到时间轴
+    ///
+    /// `near_us` 为捕获时刻的近似时间轴时刻 (如中断或任务中的 `Instant::now()`)，
+    /// 用于展开计数器回绕。尚无同步点时返回 `None`。
+    pub fn to_timeline(&self, raw: u32, near_us: u64) -> Option<Stamp> {
+        let anchor = self.anchor?;
+        let ticks = self.unwrap_delta(&anchor, raw, near_us);
+        let elapsed_us = self.ticks_to_us(ticks);
+        let us = (anchor.us as i64).saturating_add(elapsed_us).max(0) as u64;
+        Some(Stamp { us, error_us: self.error_at(&anchor, elapsed_us) })
+    }
+
+    /// 清除同步状态 (时钟源重新配置后)
+    pub fn reset(&mut self) {
+        *self = Self::new(self.config);
+    }
+}
+
+// ===== 共享时间源 =====
+
+/// 可放在 `static` 中共享的关联器 (临界区互斥，可在中断中调用)
+pub struct SharedClock {
+    inner: BlockingMutex<CriticalSectionRawMutex, RefCell<Correlator>>,
+}
+
+impl SharedClock {
+    /// 创建
+    pub const fn new(config: ClockConfig) -> Self {
+        Self { inner: BlockingMutex::new(RefCell::new(Correlator::new(config))) }
+    }
+
+    /// 记录同步点
+    pub fn observe(&self, raw: u32, us: u64, uncertainty_us: u32) {
+        self.inner.lock(|c| c.borrow_mut().observe(raw, us, uncertainty_us));
+    }
+
+    /// 将本地计数映射到时间轴
+    pub fn to_timeline(&self, raw: u32, near_us: u64) -> Option<Stamp> {
+        self.inner.lock(|c| c.borrow().to_timeline(raw, near_us))
+    }
+
+    /// 统计
+    pub fn stats(&self) -> SyncStats {
+        self.inner.lock(|c| c.borrow().stats())
+    }
+
+    /// 清除同步状态
+    pub fn reset(&self) {
+        self.inner.lock(|c| c.borrow_mut().reset());
+    }
+}
+
+/// 两个核的 CCOUNT 关联 (下标为核号)
+pub static CPU: [SharedClock; 2] = [SharedClock::new(ClockConfig::cpu()), SharedClock::new(ClockConfig::cpu())];
+
+fn core_index() -> usize {
+    match Cpu::current() {
+        Cpu::ProCpu => 0,
+        _ => 1,
+    }
+}
+
+/// 在当前核上采样 CCOUNT 并记录同步点
+///
+/// 以读取前后的两次 `Instant` 夹逼计数器读取，不确定度为半个夹逼区间加一个节拍。
+/// 每个核至少每半个回绕周期 (约 8.9s) 调用一次。
+pub fn sync_cpu() {
+    let core = core_index();
+    critical_section::with(|_| {
+        let before = Instant::now().as_micros();
+        let raw = cycle_count();
+        let after = Instant::now().as_micros();
+        let tick_us = (1_000_000 / TICK_HZ).max(1) as u32;
+        let half_window = ((after - before) / 2) as u32;
+        CPU[core].observe(raw, before + (after - before) / 2, half_window + tick_us);
+    });
+}
+
+/// 将某核上捕获的 CCOUNT 映射到时间轴
+pub fn cpu_timestamp(core: usize, ccount: u32, near_us: u64) -> Option<Stamp> {
+    CPU.get(core)?.to_timeline(ccount, near_us)
+}
+
+/// 当前核的 CCOUNT 时间戳 (读取时刻)
+pub fn cpu_now() -> Option<Stamp> {
+    let raw = cycle_count();
+    cpu_timestamp(core_index(), raw, Instant::now().as_micros())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 1MHz、16 位计数器，本地时钟快 50ppm
+    fn local_ticks(us: u64) -> u32 {
+        (us as u128 * 1_000_050 / 1_000_000) as u32 & 0xFFFF
+    }
+
+    #[test]
+    fn test_rate_and_wrap() {
+        let mut clock = Correlator::new(ClockConfig::new(1_000_000).with_bits(16).with_min_span_us(500_000));
+        assert_eq!(clock.to_timeline(0, 0), None);
+
+        for i in 0..=10u64 {
+            let us = i * 1_000_000 + 7;
+            clock.observe(local_ticks(us), us, 2);
+        }
+        let stats = clock.stats();
+        assert_eq!(stats.rate_samples, 10);
+        assert!((stats.rate_ppm - 50).abs() <= 2, "rate {}", stats.rate_ppm);
+
+        // 16 位计数器在 1MHz 下每 65ms 回绕，按近似时刻展开
+        let target = 10_123_456;
+        let stamp = clock.to_timeline(local_ticks(target), target + 20_000).unwrap();
+        assert!(stamp.us.abs_diff(target) <= 2, "{} vs {}", stamp.us, target);
+        assert!(stamp.error_us >= 2 && stamp.error_us < 10);
+
+        // 不确定度大的同步点不替换基准
+        let accepted = clock.stats().accepted;
+        clock.observe(local_ticks(10_200_000), 10_200_000, 5_000);
+        assert_eq!(clock.stats().accepted, accepted);
+    }
+}